@@ -63,6 +63,7 @@ fn build_output_from_diff(diff_summary: &str) -> DocumentationPipelineOutput {
         run_id: format!("doc-sync-{}", Uuid::new_v4()),
         generated_at,
         diff_summary: diff_summary.trim().to_string(),
+        changed_docs: Vec::new(),
         approvals_required: vec![
             AgentApprovalRequirement {
                 role: "doc-lead".to_string(),
@@ -50,6 +50,9 @@ pub struct DocumentationPipelineOutput {
     pub run_id: String,
     pub generated_at: DateTime<Utc>,
     pub diff_summary: String,
+    /// Documentation already edited by the change the pipeline built.
+    #[serde(default)]
+    pub changed_docs: Vec<String>,
     pub approvals_required: Vec<AgentApprovalRequirement>,
     pub approvals_granted: Vec<AgentApprovalRecord>,
     pub services: Vec<ServiceDocumentation>,
@@ -130,6 +133,12 @@ impl DocumentationAgent {
             &mut body,
             format_args!("- Diff Summary: {}", output.diff_summary),
         )?;
+        if !output.changed_docs.is_empty() {
+            pushln(
+                &mut body,
+                format_args!("- Changed Docs: {}", output.changed_docs.join(", ")),
+            )?;
+        }
         let required_summary = if output.approvals_required.is_empty() {
             "none".to_string()
         } else {
@@ -405,6 +414,7 @@ mod tests {
             run_id: "run_123".to_string(),
             generated_at: Utc::now(),
             diff_summary: "Updated services/api and SOP library".to_string(),
+            changed_docs: vec!["docs/api.md".to_string()],
             approvals_required: vec![AgentApprovalRequirement {
                 role: "doc-lead".to_string(),
                 minimum_trust_score: 0.7,
@@ -448,6 +458,9 @@ mod tests {
         let output = build_output();
         agent.process_pipeline_output(&output).unwrap();
 
+        let report = fs::read_to_string(tmp_dir.join("docs/documentation/sync-report.md")).unwrap();
+        assert!(report.contains("- Changed Docs: docs/api.md"));
+
         let wiki_index = fs::read_to_string(tmp_dir.join("docs/wiki/index.md")).unwrap();
         assert!(wiki_index.contains("NOA ARK OS Documentation Wiki"));
 
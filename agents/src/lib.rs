@@ -18,8 +18,12 @@ use noa_core::process::ProcessService;
 pub mod factory;
 pub mod implementations;
 pub mod inference;
+pub mod marketplace;
+pub mod memory;
 pub mod registry;
 pub mod runtime;
+pub mod tools;
+pub mod trace;
 pub mod unified_types;
 
 // Re-export unified types
@@ -27,8 +31,21 @@ pub use unified_types::*;
 
 // Re-export key components
 pub use inference::{InferenceConfig, InferenceEngine, LlamaInferenceEngine};
+pub use marketplace::{
+    AgentArtifact, ExternalAgentManifest, ExternalAgentRecord, ManifestError, ProvenanceRecord,
+    SecurityScanReport, SecurityScanStatus,
+};
+pub use memory::{AgentMemoryError, AgentMemoryStore, RetentionPolicy};
 pub use registry::AgentRegistry;
-pub use runtime::RuntimeManager;
+pub use runtime::{
+    LoadedModel, ModelChurn, ModelChurnEvent, ModelChurnKind, RuntimeManager, VramMonitor,
+    VramMonitorConfig,
+};
+pub use tools::{ToolHandler, ToolRegistry, ToolRegistryError, ToolSpec};
+pub use trace::{
+    ExecutionTrace, SpanKind, SpanStatus, TraceArtifact, TraceError, TraceRecorder, TraceSpan,
+    TraceStore,
+};
 
 /// Version of the agent system
 pub const VERSION: &str = "0.1.0";
@@ -81,6 +98,8 @@ pub enum Error {
     CsvError(#[from] csv::Error),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("Manifest error: {0}")]
+    Manifest(#[from] marketplace::ManifestError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -144,7 +163,8 @@ impl AgentFactory {
 
         if let Some(kernel) = &self.kernel {
             if let Ok(process_service) = kernel.request::<ProcessService>(CAPABILITY_PROCESS) {
-                let _ = process_service.create_process(format!("agent::{id}"));
+                let _ = process_service
+                    .create_owned_process(format!("agent::{id}"), AGENT_FACTORY_CAPABILITY);
             }
         }
 
@@ -0,0 +1,379 @@
+//! Ingestion of third-party agent definitions.
+//!
+//! External agents are described by a JSON manifest following
+//! `schema/agent_manifest.schema.json`. Ingesting a manifest validates it, registers the
+//! agent in the [`AgentRegistry`](crate::AgentRegistry) with a provenance record, and
+//! marks it pending a security scan. Until a passing scan of the exact artifact digest is
+//! recorded the agent is listed but not dispatchable.
+
+use crate::unified_types::{
+    AgentCategory, AgentLanguage, AgentLayer, AgentMetadata, AgentState, HealthStatus,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Value of `schema_version` accepted by this release.
+pub const MANIFEST_SCHEMA_VERSION: &str = "noa.agent/v1";
+
+#[derive(Debug, Error)]
+pub enum ManifestError {
+    #[error("manifest could not be parsed: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("manifest failed validation: {}", .0.join("; "))]
+    Invalid(Vec<String>),
+    #[error("agent id '{0}' belongs to a built-in agent")]
+    Conflict(String),
+    #[error("agent '{0}' was not ingested from a manifest")]
+    NotExternal(String),
+    #[error("scan covered {scanned} but agent '{agent_id}' ships {expected}")]
+    DigestMismatch {
+        agent_id: String,
+        expected: String,
+        scanned: String,
+    },
+}
+
+/// A third-party agent definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExternalAgentManifest {
+    pub schema_version: String,
+    pub agent_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub version: String,
+    pub publisher: String,
+    /// `L1`–`L5` or the legacy layer names; defaults to `L4`.
+    #[serde(default)]
+    pub layer: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    pub capabilities: Vec<String>,
+    /// `rust`, `python`, or `go`.
+    pub language: String,
+    pub artifact: AgentArtifact,
+}
+
+/// The runnable artifact, pinned by content digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum AgentArtifact {
+    Container { image: String, digest: String },
+    Wasm { module: String, digest: String },
+}
+
+impl AgentArtifact {
+    pub fn digest(&self) -> &str {
+        match self {
+            AgentArtifact::Container { digest, .. } | AgentArtifact::Wasm { digest, .. } => digest,
+        }
+    }
+
+    /// `image@digest` or `module@digest`.
+    pub fn reference(&self) -> String {
+        match self {
+            AgentArtifact::Container { image, digest } => format!("{image}@{digest}"),
+            AgentArtifact::Wasm { module, digest } => format!("{module}@{digest}"),
+        }
+    }
+}
+
+impl ExternalAgentManifest {
+    pub fn from_json(raw: &[u8]) -> Result<Self, ManifestError> {
+        Ok(serde_json::from_slice(raw)?)
+    }
+
+    /// Check the manifest against the schema rules, reporting every violation at once.
+    pub fn validate(&self) -> Result<(), ManifestError> {
+        let mut violations = Vec::new();
+        if self.schema_version != MANIFEST_SCHEMA_VERSION {
+            violations.push(format!(
+                "schema_version must be {MANIFEST_SCHEMA_VERSION}, got {}",
+                self.schema_version
+            ));
+        }
+        if !is_identifier(&self.agent_id) {
+            violations.push(format!(
+                "agent_id '{}' must be 3-64 lowercase letters, digits, '-', '_' or '.'",
+                self.agent_id
+            ));
+        }
+        for (field, value) in [
+            ("name", &self.name),
+            ("version", &self.version),
+            ("publisher", &self.publisher),
+        ] {
+            if value.trim().is_empty() {
+                violations.push(format!("{field} must not be empty"));
+            }
+        }
+        if self.capabilities.is_empty() {
+            violations.push("capabilities must list at least one capability".into());
+        }
+        if self
+            .capabilities
+            .iter()
+            .chain(&self.roles)
+            .any(|entry| entry.trim().is_empty())
+        {
+            violations.push("capabilities and roles must not contain empty entries".into());
+        }
+        if self.agent_language().is_none() {
+            violations.push(format!(
+                "language '{}' is not one of rust, python, go",
+                self.language
+            ));
+        }
+        if let Some(layer) = &self.layer {
+            if parse_layer(layer).is_none() {
+                violations.push(format!("layer '{layer}' is not L1-L5"));
+            }
+        }
+        let (reference, digest) = match &self.artifact {
+            AgentArtifact::Container { image, digest } => (image, digest),
+            AgentArtifact::Wasm { module, digest } => (module, digest),
+        };
+        if reference.trim().is_empty() || reference.contains('@') {
+            violations.push(
+                "artifact reference must be set and must not embed a digest; use `digest`".into(),
+            );
+        }
+        if !is_sha256_digest(digest) {
+            violations.push(format!(
+                "artifact digest '{digest}' must be sha256:<64 hex digits>"
+            ));
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ManifestError::Invalid(violations))
+        }
+    }
+
+    fn agent_language(&self) -> Option<AgentLanguage> {
+        match self.language.to_ascii_lowercase().as_str() {
+            "rust" => Some(AgentLanguage::Rust),
+            "python" => Some(AgentLanguage::Python),
+            "go" => Some(AgentLanguage::Go),
+            _ => None,
+        }
+    }
+
+    /// Registry metadata for the agent. It stays `Created` until a scan passes.
+    pub(crate) fn to_metadata(&self, ingested_at: DateTime<Utc>) -> AgentMetadata {
+        let mut agent = AgentMetadata::from_registry(self.name.clone(), self.agent_id.clone());
+        agent.description = self.description.clone();
+        agent.purpose = self.description.clone();
+        agent.role = self.roles.first().cloned().unwrap_or_default();
+        agent.layer = self
+            .layer
+            .as_deref()
+            .and_then(parse_layer)
+            .unwrap_or_default();
+        agent.category = AgentCategory::Plugins;
+        agent.language = self.agent_language().unwrap_or_default();
+        agent.capabilities = self.capabilities.clone();
+        agent.tags = self
+            .roles
+            .iter()
+            .cloned()
+            .chain([
+                "external".to_string(),
+                format!("publisher:{}", self.publisher),
+            ])
+            .collect();
+        agent.state = AgentState::Created;
+        agent.health_status = HealthStatus::Unknown;
+        agent.version = Some(self.version.clone());
+        agent.created_at = Some(ingested_at.to_rfc3339());
+        agent.last_updated = Some(ingested_at.to_rfc3339());
+        agent
+    }
+
+    /// `sha256:` digest of the canonical JSON encoding, recorded as provenance.
+    pub fn digest(&self) -> String {
+        let canonical = serde_json::to_vec(self).unwrap_or_default();
+        format!("sha256:{:x}", Sha256::digest(canonical))
+    }
+}
+
+pub(crate) fn parse_layer(raw: &str) -> Option<AgentLayer> {
+    match raw.to_lowercase().as_str() {
+        "executive" | "l1" | "l1autonomy" => Some(AgentLayer::L1Autonomy),
+        "board" | "l2" | "l2reasoning" => Some(AgentLayer::L2Reasoning),
+        "stack-chief" | "stack_chief" | "l3" | "l3orchestration" => {
+            Some(AgentLayer::L3Orchestration)
+        }
+        "specialist" | "l4" | "l4operations" => Some(AgentLayer::L4Operations),
+        "micro" | "l5" | "l5infrastructure" => Some(AgentLayer::L5Infrastructure),
+        _ => None,
+    }
+}
+
+fn is_identifier(value: &str) -> bool {
+    (3..=64).contains(&value.len())
+        && value.bytes().all(|byte| {
+            byte.is_ascii_lowercase() || byte.is_ascii_digit() || matches!(byte, b'-' | b'_' | b'.')
+        })
+}
+
+fn is_sha256_digest(value: &str) -> bool {
+    value.strip_prefix("sha256:").is_some_and(|hex| {
+        hex.len() == 64
+            && hex
+                .bytes()
+                .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
+    })
+}
+
+/// Where an external agent came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvenanceRecord {
+    /// Path or URL the manifest was read from.
+    pub source: String,
+    pub publisher: String,
+    pub manifest_digest: String,
+    pub artifact: String,
+    pub ingested_at: DateTime<Utc>,
+}
+
+/// Outcome of scanning an artifact, as reported by the scanner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityScanReport {
+    pub scanner: String,
+    /// Digest of the artifact that was scanned; must match the manifest.
+    pub artifact_digest: String,
+    pub passed: bool,
+    #[serde(default)]
+    pub findings: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SecurityScanStatus {
+    Pending,
+    Passed {
+        scanner: String,
+        scanned_at: DateTime<Utc>,
+    },
+    Failed {
+        scanner: String,
+        scanned_at: DateTime<Utc>,
+        findings: Vec<String>,
+    },
+}
+
+/// Registry bookkeeping for an agent ingested from a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalAgentRecord {
+    pub manifest: ExternalAgentManifest,
+    pub provenance: ProvenanceRecord,
+    pub scan: SecurityScanStatus,
+}
+
+impl ExternalAgentRecord {
+    pub(crate) fn new(
+        manifest: ExternalAgentManifest,
+        source: String,
+        ingested_at: DateTime<Utc>,
+    ) -> Self {
+        let provenance = ProvenanceRecord {
+            source,
+            publisher: manifest.publisher.clone(),
+            manifest_digest: manifest.digest(),
+            artifact: manifest.artifact.reference(),
+            ingested_at,
+        };
+        Self {
+            manifest,
+            provenance,
+            scan: SecurityScanStatus::Pending,
+        }
+    }
+
+    pub fn is_dispatchable(&self) -> bool {
+        matches!(self.scan, SecurityScanStatus::Passed { .. })
+    }
+
+    pub(crate) fn apply_scan(
+        &mut self,
+        report: SecurityScanReport,
+        scanned_at: DateTime<Utc>,
+    ) -> Result<(), ManifestError> {
+        let expected = self.manifest.artifact.digest();
+        if report.artifact_digest != expected {
+            return Err(ManifestError::DigestMismatch {
+                agent_id: self.manifest.agent_id.clone(),
+                expected: expected.to_string(),
+                scanned: report.artifact_digest,
+            });
+        }
+        self.scan = if report.passed && report.findings.is_empty() {
+            SecurityScanStatus::Passed {
+                scanner: report.scanner,
+                scanned_at,
+            }
+        } else {
+            SecurityScanStatus::Failed {
+                scanner: report.scanner,
+                scanned_at,
+                findings: report.findings,
+            }
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn manifest_json() -> serde_json::Value {
+        json!({
+            "schema_version": MANIFEST_SCHEMA_VERSION,
+            "agent_id": "acme.summarizer",
+            "name": "Acme Summarizer",
+            "description": "Summarises long documents",
+            "version": "1.2.0",
+            "publisher": "acme",
+            "layer": "L4",
+            "roles": ["summarizer"],
+            "capabilities": ["text.summarize"],
+            "language": "python",
+            "artifact": {
+                "kind": "container",
+                "image": "ghcr.io/acme/summarizer",
+                "digest": format!("sha256:{}", "ab".repeat(32)),
+            }
+        })
+    }
+
+    #[test]
+    fn validation_reports_every_violation() {
+        let manifest =
+            ExternalAgentManifest::from_json(manifest_json().to_string().as_bytes()).unwrap();
+        manifest.validate().expect("valid manifest");
+        assert_eq!(manifest.digest(), manifest.clone().digest());
+
+        let mut broken = manifest_json();
+        broken["agent_id"] = json!("Acme Summarizer");
+        broken["language"] = json!("cobol");
+        broken["artifact"]["digest"] = json!("latest");
+        let broken = ExternalAgentManifest::from_json(broken.to_string().as_bytes()).unwrap();
+        match broken.validate() {
+            Err(ManifestError::Invalid(violations)) => assert_eq!(violations.len(), 3),
+            other => panic!("expected violations, got {other:?}"),
+        }
+
+        let mut unknown = manifest_json();
+        unknown["entrypoint"] = json!("/bin/sh");
+        assert!(matches!(
+            ExternalAgentManifest::from_json(unknown.to_string().as_bytes()),
+            Err(ManifestError::Parse(_))
+        ));
+    }
+}
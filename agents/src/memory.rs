@@ -0,0 +1,351 @@
+//! Per-agent conversation and episodic memory.
+//!
+//! Each agent gets its own [`LongTermMemory`] under `storage/db/agents/<id>/memory`.
+//! A [`RetentionPolicy`] bounds how much is kept, older exchanges can be folded into a
+//! summary produced by an [`InferenceEngine`], and [`AgentMemoryStore::relevant`] picks
+//! the records worth injecting into a new task's context.
+
+use crate::inference::{InferenceConfig, InferenceEngine};
+use chrono::{DateTime, Utc};
+use noa_memory::{LongTermMemory, MemoryError, MemoryRecord, MemoryRole, MemoryStore};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+
+pub use noa_memory::RetentionPolicy;
+
+/// Default root for agent memory, relative to the workspace.
+pub const AGENT_MEMORY_ROOT: &str = "storage/db/agents";
+const MEMORY_DIR: &str = "memory";
+/// Records kept verbatim when older ones are summarized.
+const DEFAULT_KEEP_RECENT: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum AgentMemoryError {
+    #[error("agent id '{0}' cannot be used as a memory directory")]
+    InvalidAgentId(String),
+    #[error(transparent)]
+    Store(#[from] MemoryError),
+    #[error("summarization failed: {0}")]
+    Summarization(String),
+}
+
+pub struct AgentMemoryStore {
+    root: PathBuf,
+    retention: RetentionPolicy,
+    overrides: HashMap<String, RetentionPolicy>,
+    summarize_after: Option<usize>,
+    keep_recent: usize,
+    stores: RwLock<HashMap<String, Arc<LongTermMemory>>>,
+}
+
+impl Default for AgentMemoryStore {
+    fn default() -> Self {
+        Self::new(AGENT_MEMORY_ROOT)
+    }
+}
+
+impl AgentMemoryStore {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            retention: RetentionPolicy::default(),
+            overrides: HashMap::new(),
+            summarize_after: None,
+            keep_recent: DEFAULT_KEEP_RECENT,
+            stores: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Retention applied to every agent without an override.
+    pub fn with_retention(mut self, policy: RetentionPolicy) -> Self {
+        self.retention = policy;
+        self
+    }
+
+    pub fn with_agent_retention(
+        mut self,
+        agent_id: impl Into<String>,
+        policy: RetentionPolicy,
+    ) -> Self {
+        self.overrides.insert(agent_id.into(), policy);
+        self
+    }
+
+    /// Summarize once an agent holds more than `threshold` unsummarized records,
+    /// keeping the newest `keep_recent` verbatim.
+    pub fn with_summarization(mut self, threshold: usize, keep_recent: usize) -> Self {
+        self.summarize_after = Some(threshold);
+        self.keep_recent = keep_recent.min(threshold);
+        self
+    }
+
+    pub fn retention_for(&self, agent_id: &str) -> &RetentionPolicy {
+        self.overrides.get(agent_id).unwrap_or(&self.retention)
+    }
+
+    /// Directory holding `agent_id`'s memory.
+    pub fn memory_dir(&self, agent_id: &str) -> Result<PathBuf, AgentMemoryError> {
+        let valid = !agent_id.is_empty()
+            && agent_id != "."
+            && agent_id != ".."
+            && agent_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(AgentMemoryError::InvalidAgentId(agent_id.to_string()));
+        }
+        Ok(self.root.join(agent_id).join(MEMORY_DIR))
+    }
+
+    fn store(&self, agent_id: &str) -> Result<Arc<LongTermMemory>, AgentMemoryError> {
+        if let Some(store) = self.stores.read().unwrap().get(agent_id) {
+            return Ok(Arc::clone(store));
+        }
+        let mut stores = self.stores.write().unwrap();
+        if let Some(store) = stores.get(agent_id) {
+            return Ok(Arc::clone(store));
+        }
+        let dir = self.memory_dir(agent_id)?;
+        std::fs::create_dir_all(&dir).map_err(MemoryError::from)?;
+        let store = Arc::new(LongTermMemory::open(&dir)?);
+        stores.insert(agent_id.to_string(), Arc::clone(&store));
+        Ok(store)
+    }
+
+    /// Append a record and enforce the agent's retention policy.
+    pub fn record(
+        &self,
+        agent_id: &str,
+        role: MemoryRole,
+        content: &str,
+        tags: Vec<String>,
+    ) -> Result<MemoryRecord, AgentMemoryError> {
+        let store = self.store(agent_id)?;
+        let record = store.append(agent_id, role, content, HashMap::new(), tags)?;
+        store.apply_retention(self.retention_for(agent_id), Utc::now())?;
+        Ok(record)
+    }
+
+    pub fn records(&self, agent_id: &str) -> Result<Vec<MemoryRecord>, AgentMemoryError> {
+        Ok(self.store(agent_id)?.records())
+    }
+
+    /// Apply the agent's retention policy, returning how many records were dropped.
+    pub fn enforce_retention(&self, agent_id: &str) -> Result<usize, AgentMemoryError> {
+        let store = self.store(agent_id)?;
+        Ok(store.apply_retention(self.retention_for(agent_id), Utc::now())?)
+    }
+
+    /// Agents with memory on disk, sorted by id.
+    pub fn agent_ids(&self) -> Result<Vec<String>, AgentMemoryError> {
+        let entries = match std::fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(MemoryError::from(err).into()),
+        };
+        let mut ids: Vec<String> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().join(MEMORY_DIR).is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|id| self.memory_dir(id).is_ok())
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Apply `policy` to every agent with memory on disk, on top of its own
+    /// retention. Returns how many records were dropped.
+    pub fn apply_retention_all(
+        &self,
+        policy: &RetentionPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<usize, AgentMemoryError> {
+        let mut removed = 0;
+        for agent_id in self.agent_ids()? {
+            removed += self.store(&agent_id)?.apply_retention(policy, now)?;
+        }
+        Ok(removed)
+    }
+
+    /// Remove everything linked to `subject_id`: the subject's own memory when it is
+    /// an agent, and records in other agents' memory tagged with it or carrying it as
+    /// a metadata value. Returns how many records were removed.
+    pub fn purge_subject(&self, subject_id: &str) -> Result<usize, AgentMemoryError> {
+        let mut removed = 0;
+        for agent_id in self.agent_ids()? {
+            let store = self.store(&agent_id)?;
+            if agent_id == subject_id {
+                removed += store.remove_where(|_| true)?;
+                continue;
+            }
+            removed += store.remove_where(|record| {
+                record.tags.iter().any(|tag| tag == subject_id)
+                    || record.metadata.values().any(|value| value == subject_id)
+            })?;
+        }
+        Ok(removed)
+    }
+
+    /// Fold older records into a summary written by `engine` once the agent passes the
+    /// summarization threshold. Returns the summary record, if one was produced.
+    pub async fn summarize(
+        &self,
+        agent_id: &str,
+        engine: &dyn InferenceEngine,
+    ) -> Result<Option<MemoryRecord>, AgentMemoryError> {
+        let Some(threshold) = self.summarize_after else {
+            return Ok(None);
+        };
+        let store = self.store(agent_id)?;
+        let pending: Vec<MemoryRecord> = store
+            .records()
+            .into_iter()
+            .filter(|record| record.role != MemoryRole::Summary)
+            .collect();
+        if pending.len() <= threshold {
+            return Ok(None);
+        }
+
+        let folded = &pending[..pending.len() - self.keep_recent];
+        let transcript: String = folded
+            .iter()
+            .map(|record| format!("[{:?}] {}\n", record.role, record.content))
+            .collect();
+        let prompt = format!(
+            "Summarize the following memory of agent {agent_id}. Keep decisions, facts, \
+             and open questions; drop pleasantries.\n\n{transcript}\nSummary:"
+        );
+        let summary = engine
+            .generate(&prompt, InferenceConfig::default())
+            .await
+            .map_err(|err| AgentMemoryError::Summarization(err.to_string()))?;
+        let ids: Vec<u64> = folded.iter().map(|record| record.id).collect();
+        let record = store.compact(&ids, agent_id, summary.trim(), Vec::new())?;
+        Ok(Some(record))
+    }
+
+    /// Up to `limit` records most relevant to `query`: summaries first, then records
+    /// sharing the most terms with the query, newest first on ties. Returned oldest first.
+    pub fn relevant(
+        &self,
+        agent_id: &str,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<MemoryRecord>, AgentMemoryError> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let query_terms = terms(query);
+        let mut scored: Vec<(usize, MemoryRecord)> = self
+            .store(agent_id)?
+            .records()
+            .into_iter()
+            .map(|record| {
+                let score = if record.role == MemoryRole::Summary {
+                    usize::MAX
+                } else {
+                    terms(&record.content).intersection(&query_terms).count()
+                };
+                (score, record)
+            })
+            .filter(|(score, _)| *score > 0)
+            .collect();
+        scored.sort_by(|(a_score, a), (b_score, b)| {
+            b_score.cmp(a_score).then_with(|| b.id.cmp(&a.id))
+        });
+        let mut selected: Vec<MemoryRecord> = scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, record)| record)
+            .collect();
+        selected.sort_by_key(|record| record.timestamp);
+        Ok(selected)
+    }
+}
+
+fn terms(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.len() > 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    struct EchoEngine {
+        prompts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl InferenceEngine for EchoEngine {
+        async fn generate(&self, prompt: &str, _config: InferenceConfig) -> anyhow::Result<String> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok("deploys go through staging first".to_string())
+        }
+
+        fn model_name(&self) -> &str {
+            "echo"
+        }
+
+        async fn is_available(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_is_retained_summarized_and_recalled_per_agent() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AgentMemoryStore::new(dir.path())
+            .with_retention(RetentionPolicy::default().with_max_records(6))
+            .with_summarization(4, 2);
+        for note in [
+            "user asked about staging deploys",
+            "staging deploy approved",
+            "weather small talk",
+            "rollback plan drafted for deploys",
+            "release notes reviewed",
+            "metrics dashboard checked",
+            "deploy window is friday",
+        ] {
+            store
+                .record("deployer", MemoryRole::Observation, note, Vec::new())
+                .unwrap();
+        }
+        assert!(dir.path().join("deployer/memory").is_dir());
+        assert_eq!(store.records("deployer").unwrap().len(), 6);
+        assert!(store.records("planner").unwrap().is_empty());
+        assert!(store.memory_dir("../escape").is_err());
+
+        let engine = EchoEngine {
+            prompts: Mutex::new(Vec::new()),
+        };
+        let summary = store
+            .summarize("deployer", &engine)
+            .await
+            .unwrap()
+            .expect("summary written");
+        assert!(engine.prompts.lock().unwrap()[0].contains("staging deploy approved"));
+        let records = store.records("deployer").unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(store
+            .summarize("deployer", &engine)
+            .await
+            .unwrap()
+            .is_none());
+
+        let recalled = store
+            .relevant("deployer", "schedule the friday deploy", 2)
+            .unwrap();
+        let contents: Vec<_> = recalled.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            ["deploy window is friday", summary.content.as_str()]
+        );
+    }
+}
@@ -2,8 +2,16 @@
 // Integrates the 928-agent directory from the stale/agents drop
 
 use crate::implementations::specialist::PolicyEnforcementAgent;
-use crate::unified_types::{AgentCategory, AgentLayer, AgentMetadata, HealthStatus, RegistryStats};
+use crate::marketplace::{
+    self, ExternalAgentManifest, ExternalAgentRecord, ManifestError, SecurityScanReport,
+    SecurityScanStatus,
+};
+use crate::tools::ToolRegistry;
+use crate::unified_types::{
+    AgentCategory, AgentLayer, AgentMetadata, AgentState, HealthStatus, RegistryStats,
+};
 use crate::{Error, Result};
+use chrono::Utc;
 use std::collections::{HashMap, HashSet};
 use std::io::{Cursor, Read};
 use std::path::Path;
@@ -23,6 +31,12 @@ pub struct AgentRegistry {
 
     /// Registry statistics
     stats: Arc<RwLock<RegistryStats>>,
+
+    /// Provenance and scan state of agents ingested from external manifests
+    external: Arc<RwLock<HashMap<String, ExternalAgentRecord>>>,
+
+    /// Tools agents may declare and workflows may require
+    tools: Arc<ToolRegistry>,
 }
 
 impl AgentRegistry {
@@ -34,9 +48,22 @@ impl AgentRegistry {
             by_layer: Arc::new(RwLock::new(HashMap::new())),
             by_category: Arc::new(RwLock::new(HashMap::new())),
             stats: Arc::new(RwLock::new(RegistryStats::new())),
+            external: Arc::new(RwLock::new(HashMap::new())),
+            tools: Arc::new(ToolRegistry::with_builtin_tools()),
         }
     }
 
+    /// Validate against `tools` instead of the bundled tool catalog
+    pub fn with_tool_registry(mut self, tools: Arc<ToolRegistry>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Shared tool registry
+    pub fn tools(&self) -> Arc<ToolRegistry> {
+        Arc::clone(&self.tools)
+    }
+
     /// Convenience constructor that loads the embedded agent directory
     pub fn with_default_data() -> Result<Self> {
         let registry = Self::new();
@@ -172,25 +199,8 @@ impl AgentRegistry {
     /// - "Specialist" (legacy) / "L4" / "L4Operations" → L4Operations (domain experts)
     /// - "Micro" (legacy) / "L5" / "L5Infrastructure" → L5Infrastructure (task-specific)
     fn parse_layer(s: &str) -> AgentLayer {
-        match s.to_lowercase().as_str() {
-            // L1Autonomy: Root governance (was: Executive)
-            "executive" | "l1" | "l1autonomy" => AgentLayer::L1Autonomy,
-
-            // L2Reasoning: Strategic planning (was: Board)
-            "board" | "l2" | "l2reasoning" => AgentLayer::L2Reasoning,
-
-            // L3Orchestration: Coordination (was: Stack-Chief)
-            "stack-chief" | "stack_chief" | "l3" | "l3orchestration" => AgentLayer::L3Orchestration,
-
-            // L4Operations: Domain experts (was: Specialist)
-            "specialist" | "l4" | "l4operations" => AgentLayer::L4Operations,
-
-            // L5Infrastructure: Task-specific (was: Micro)
-            "micro" | "l5" | "l5infrastructure" => AgentLayer::L5Infrastructure,
-
-            // Default to L4Operations for unrecognized layers
-            _ => AgentLayer::L4Operations,
-        }
+        // Default to L4Operations for unrecognized layers
+        marketplace::parse_layer(s).unwrap_or(AgentLayer::L4Operations)
     }
 
     /// Parse health status from string
@@ -261,6 +271,107 @@ impl AgentRegistry {
         self.rebuild_indexes()
     }
 
+    /// Register a third-party agent from a validated manifest.
+    ///
+    /// The agent is recorded with its provenance and stays undispatchable until
+    /// [`record_security_scan`](Self::record_security_scan) reports a pass. Re-ingesting
+    /// an external agent replaces it and requires a fresh scan.
+    pub fn ingest_manifest(
+        &self,
+        manifest: ExternalAgentManifest,
+        source: impl Into<String>,
+    ) -> Result<ExternalAgentRecord> {
+        manifest.validate()?;
+        let unknown: Vec<String> = manifest
+            .capabilities
+            .iter()
+            .filter(|capability| self.tools.get(capability).is_none())
+            .map(|capability| format!("capability '{capability}' is not a registered tool"))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(ManifestError::Invalid(unknown).into());
+        }
+        let agent_id = manifest.agent_id.clone();
+        let mut external = self.external.write().unwrap();
+        if !external.contains_key(&agent_id) && self.get(&agent_id).is_some() {
+            return Err(ManifestError::Conflict(agent_id).into());
+        }
+
+        let ingested_at = Utc::now();
+        let metadata = manifest.to_metadata(ingested_at);
+        let record = ExternalAgentRecord::new(manifest, source.into(), ingested_at);
+        external.insert(agent_id.clone(), record.clone());
+        drop(external);
+        self.upsert_metadata(metadata)?;
+        info!(
+            "Ingested external agent {} from {} (pending security scan)",
+            agent_id, record.provenance.source
+        );
+        Ok(record)
+    }
+
+    /// Read a JSON manifest from disk and ingest it, recording the path as its source.
+    pub fn ingest_manifest_file<P: AsRef<Path>>(&self, path: P) -> Result<ExternalAgentRecord> {
+        let raw = std::fs::read(path.as_ref())?;
+        let manifest = ExternalAgentManifest::from_json(&raw)?;
+        self.ingest_manifest(manifest, path.as_ref().display().to_string())
+    }
+
+    /// Record a scan of an external agent's artifact. A pass makes the agent dispatchable.
+    pub fn record_security_scan(
+        &self,
+        agent_id: &str,
+        report: SecurityScanReport,
+    ) -> Result<SecurityScanStatus> {
+        let scan = {
+            let mut external = self.external.write().unwrap();
+            let record = external
+                .get_mut(agent_id)
+                .ok_or_else(|| ManifestError::NotExternal(agent_id.to_string()))?;
+            record.apply_scan(report, Utc::now())?;
+            record.scan.clone()
+        };
+
+        if let Some(mut metadata) = self.get(agent_id) {
+            let passed = matches!(scan, SecurityScanStatus::Passed { .. });
+            metadata.state = if passed {
+                AgentState::Ready
+            } else {
+                AgentState::Created
+            };
+            metadata.health_status = if passed {
+                HealthStatus::Healthy
+            } else {
+                HealthStatus::Error
+            };
+            if let SecurityScanStatus::Failed { findings, .. } = &scan {
+                metadata.issues_identified = findings.clone();
+            }
+            self.upsert_metadata(metadata)?;
+        }
+        Ok(scan)
+    }
+
+    /// Provenance and scan state of an agent ingested from a manifest.
+    pub fn external_agent(&self, agent_id: &str) -> Option<ExternalAgentRecord> {
+        self.external.read().unwrap().get(agent_id).cloned()
+    }
+
+    /// All agents ingested from manifests.
+    pub fn external_agents(&self) -> Vec<ExternalAgentRecord> {
+        self.external.read().unwrap().values().cloned().collect()
+    }
+
+    /// Whether work may be dispatched to `agent_id`. Built-in agents always may;
+    /// external agents only after a passing security scan.
+    pub fn is_dispatchable(&self, agent_id: &str) -> bool {
+        self.external
+            .read()
+            .unwrap()
+            .get(agent_id)
+            .is_none_or(ExternalAgentRecord::is_dispatchable)
+    }
+
     /// Get agent by ID
     pub fn get(&self, agent_id: &str) -> Option<AgentMetadata> {
         let agents = self.agents.read().unwrap();
@@ -353,6 +464,82 @@ mod tests {
         assert!(registry.count() >= 300);
     }
 
+    #[test]
+    fn test_external_agents_need_a_passing_scan() {
+        let digest = format!("sha256:{}", "0f".repeat(32));
+        let manifest = ExternalAgentManifest {
+            schema_version: marketplace::MANIFEST_SCHEMA_VERSION.into(),
+            agent_id: "acme.linter".into(),
+            name: "Acme Linter".into(),
+            description: "Lints pull requests".into(),
+            version: "0.3.1".into(),
+            publisher: "acme".into(),
+            layer: Some("micro".into()),
+            roles: vec!["linter".into()],
+            capabilities: vec!["code.lint".into()],
+            language: "go".into(),
+            artifact: marketplace::AgentArtifact::Wasm {
+                module: "oci://registry.acme.dev/linter.wasm".into(),
+                digest: digest.clone(),
+            },
+        };
+        let registry = AgentRegistry::new();
+        let record = registry
+            .ingest_manifest(manifest.clone(), "marketplace/acme-linter.json")
+            .expect("manifest ingested");
+        assert_eq!(record.provenance.publisher, "acme");
+        assert_eq!(record.provenance.manifest_digest, manifest.digest());
+        let agent = registry.get("acme.linter").expect("registered");
+        assert_eq!(agent.layer, AgentLayer::L5Infrastructure);
+        assert_eq!(agent.category, AgentCategory::Plugins);
+        assert!(!registry.is_dispatchable("acme.linter"));
+        assert!(registry.is_dispatchable("pe-ssp"));
+
+        let report = |digest: &str, findings: Vec<String>| SecurityScanReport {
+            scanner: "trivy".into(),
+            artifact_digest: digest.into(),
+            passed: findings.is_empty(),
+            findings,
+        };
+        let other = format!("sha256:{}", "11".repeat(32));
+        assert!(registry
+            .record_security_scan("acme.linter", report(&other, vec![]))
+            .is_err());
+        registry
+            .record_security_scan("acme.linter", report(&digest, vec!["CVE-2024-0001".into()]))
+            .unwrap();
+        assert!(!registry.is_dispatchable("acme.linter"));
+        registry
+            .record_security_scan("acme.linter", report(&digest, vec![]))
+            .unwrap();
+        assert!(registry.is_dispatchable("acme.linter"));
+        assert_eq!(
+            registry.get("acme.linter").unwrap().state,
+            AgentState::Ready
+        );
+
+        registry
+            .upsert_metadata(AgentMetadata::from_registry(
+                "Builtin".into(),
+                "builtin".into(),
+            ))
+            .unwrap();
+        let mut unknown_tool = manifest.clone();
+        unknown_tool.agent_id = "acme.teleporter".into();
+        unknown_tool.capabilities = vec!["code.teleport".into()];
+        assert!(matches!(
+            registry.ingest_manifest(unknown_tool, "marketplace/teleporter.json"),
+            Err(Error::Manifest(ManifestError::Invalid(_)))
+        ));
+
+        let mut clash = manifest;
+        clash.agent_id = "builtin".into();
+        assert!(matches!(
+            registry.ingest_manifest(clash, "marketplace/clash.json"),
+            Err(Error::Manifest(ManifestError::Conflict(_)))
+        ));
+    }
+
     #[test]
     fn test_parse_layer() {
         assert_eq!(AgentRegistry::parse_layer("board"), AgentLayer::L2Reasoning);
@@ -1,20 +1,31 @@
 //! Runtime management for multi-language agents
 
 use crate::{AgentId, AgentLanguage};
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use noa_core::hardware::{sample_gpu_memory, GpuMemorySample};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
 pub struct RuntimeManager {
     active_runtimes: Arc<Mutex<HashMap<AgentId, AgentLanguage>>>,
+    vram: Arc<VramMonitor>,
 }
 
 impl RuntimeManager {
     pub fn new() -> Self {
         Self {
             active_runtimes: Arc::new(Mutex::new(HashMap::new())),
+            vram: Arc::new(VramMonitor::new(VramMonitorConfig::default())),
         }
     }
 
+    /// GPU memory monitor tracking the models this runtime has loaded
+    pub fn vram(&self) -> Arc<VramMonitor> {
+        Arc::clone(&self.vram)
+    }
+
     /// Register an agent with its runtime
     pub fn register(&self, agent_id: AgentId, language: AgentLanguage) {
         let mut runtimes = self.active_runtimes.lock().unwrap();
@@ -66,3 +77,305 @@ impl Default for RuntimeManager {
         Self::new()
     }
 }
+
+/// Pressure thresholds for [`VramMonitor`], as fractions of device memory.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VramMonitorConfig {
+    /// Start unloading once a GPU's used memory exceeds this fraction
+    pub unload_threshold: f64,
+    /// Keep unloading until projected use falls to this fraction
+    pub target: f64,
+}
+
+impl Default for VramMonitorConfig {
+    fn default() -> Self {
+        Self {
+            unload_threshold: 0.9,
+            target: 0.75,
+        }
+    }
+}
+
+/// A model resident in GPU memory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadedModel {
+    pub name: String,
+    pub gpu_index: usize,
+    pub vram_bytes: u64,
+    pub loaded_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    #[serde(skip)]
+    last_used_tick: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelChurnKind {
+    Loaded,
+    /// Loaded again after the monitor had unloaded it
+    Reloaded,
+    Unloaded,
+}
+
+/// One load or unload, kept so runtime planning can see model churn
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelChurnEvent {
+    pub model: String,
+    pub kind: ModelChurnKind,
+    pub gpu_index: usize,
+    pub vram_bytes: u64,
+    /// GPU memory pressure observed when the event was recorded
+    pub pressure: Option<f64>,
+    pub at: DateTime<Utc>,
+}
+
+/// Load, unload, and reload counts for one model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelChurn {
+    pub loads: usize,
+    pub reloads: usize,
+    pub unloads: usize,
+}
+
+/// Called with each model the monitor evicts so the owning runtime can free it
+pub type ModelUnloadHook = Arc<dyn Fn(&LoadedModel) + Send + Sync>;
+
+/// Tracks model VRAM and evicts least-recently-used models under memory pressure
+pub struct VramMonitor {
+    config: VramMonitorConfig,
+    models: Mutex<HashMap<String, LoadedModel>>,
+    evicted: Mutex<HashSet<String>>,
+    samples: Mutex<Vec<GpuMemorySample>>,
+    events: Mutex<Vec<ModelChurnEvent>>,
+    unload_hook: Option<ModelUnloadHook>,
+    clock: AtomicU64,
+}
+
+impl VramMonitor {
+    pub fn new(config: VramMonitorConfig) -> Self {
+        Self {
+            config,
+            models: Mutex::new(HashMap::new()),
+            evicted: Mutex::new(HashSet::new()),
+            samples: Mutex::new(Vec::new()),
+            events: Mutex::new(Vec::new()),
+            unload_hook: None,
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_unload_hook(mut self, hook: ModelUnloadHook) -> Self {
+        self.unload_hook = Some(hook);
+        self
+    }
+
+    /// Record that `name` now occupies `vram_bytes` on `gpu_index`
+    pub fn record_load(&self, name: &str, gpu_index: usize, vram_bytes: u64) {
+        let now = Utc::now();
+        let kind = if self.evicted.lock().unwrap().remove(name) {
+            ModelChurnKind::Reloaded
+        } else {
+            ModelChurnKind::Loaded
+        };
+        self.models.lock().unwrap().insert(
+            name.to_string(),
+            LoadedModel {
+                name: name.to_string(),
+                gpu_index,
+                vram_bytes,
+                loaded_at: now,
+                last_used_at: now,
+                last_used_tick: self.tick(),
+            },
+        );
+        self.push_event(name, kind, gpu_index, vram_bytes, self.pressure(gpu_index));
+    }
+
+    /// Mark `name` as used, moving it to the back of the eviction order
+    pub fn touch(&self, name: &str) {
+        let tick = self.tick();
+        if let Some(model) = self.models.lock().unwrap().get_mut(name) {
+            model.last_used_tick = tick;
+            model.last_used_at = Utc::now();
+        }
+    }
+
+    /// Record that `name` was unloaded outside the monitor
+    pub fn record_unload(&self, name: &str) {
+        if let Some(model) = self.models.lock().unwrap().remove(name) {
+            let pressure = self.pressure(model.gpu_index);
+            self.push_event(
+                name,
+                ModelChurnKind::Unloaded,
+                model.gpu_index,
+                model.vram_bytes,
+                pressure,
+            );
+        }
+    }
+
+    /// Sample GPU memory through `noa_core::hardware` and evict under pressure
+    pub fn refresh(&self) -> Vec<ModelChurnEvent> {
+        self.observe(sample_gpu_memory())
+    }
+
+    /// Evict least-recently-used models on every GPU whose pressure exceeds the
+    /// threshold until its projected use reaches the target. Returns the unloads.
+    pub fn observe(&self, samples: Vec<GpuMemorySample>) -> Vec<ModelChurnEvent> {
+        let mut unloads = Vec::new();
+        for sample in &samples {
+            if sample.pressure() <= self.config.unload_threshold {
+                continue;
+            }
+            let target_bytes = (sample.total_bytes as f64 * self.config.target) as u64;
+            let mut used = sample.used_bytes;
+            let mut candidates: Vec<LoadedModel> = self
+                .models
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|model| model.gpu_index == sample.index)
+                .cloned()
+                .collect();
+            candidates.sort_by_key(|model| model.last_used_tick);
+
+            for model in candidates {
+                if used <= target_bytes {
+                    break;
+                }
+                self.models.lock().unwrap().remove(&model.name);
+                self.evicted.lock().unwrap().insert(model.name.clone());
+                if let Some(hook) = &self.unload_hook {
+                    hook(&model);
+                }
+                unloads.push(self.push_event(
+                    &model.name,
+                    ModelChurnKind::Unloaded,
+                    model.gpu_index,
+                    model.vram_bytes,
+                    Some(sample.pressure()),
+                ));
+                used = used.saturating_sub(model.vram_bytes);
+            }
+        }
+        *self.samples.lock().unwrap() = samples;
+        unloads
+    }
+
+    /// Pressure of `gpu_index` at the last refresh
+    pub fn pressure(&self, gpu_index: usize) -> Option<f64> {
+        self.samples
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|sample| sample.index == gpu_index)
+            .map(GpuMemorySample::pressure)
+    }
+
+    /// Resident models, least recently used first
+    pub fn loaded_models(&self) -> Vec<LoadedModel> {
+        let mut models: Vec<LoadedModel> = self.models.lock().unwrap().values().cloned().collect();
+        models.sort_by_key(|model| model.last_used_tick);
+        models
+    }
+
+    pub fn events(&self) -> Vec<ModelChurnEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Load, reload, and unload counts per model
+    pub fn churn(&self) -> HashMap<String, ModelChurn> {
+        let mut churn: HashMap<String, ModelChurn> = HashMap::new();
+        for event in self.events.lock().unwrap().iter() {
+            let entry = churn.entry(event.model.clone()).or_default();
+            match event.kind {
+                ModelChurnKind::Loaded => entry.loads += 1,
+                ModelChurnKind::Reloaded => {
+                    entry.loads += 1;
+                    entry.reloads += 1;
+                }
+                ModelChurnKind::Unloaded => entry.unloads += 1,
+            }
+        }
+        churn
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn push_event(
+        &self,
+        model: &str,
+        kind: ModelChurnKind,
+        gpu_index: usize,
+        vram_bytes: u64,
+        pressure: Option<f64>,
+    ) -> ModelChurnEvent {
+        let event = ModelChurnEvent {
+            model: model.to_string(),
+            kind,
+            gpu_index,
+            vram_bytes,
+            pressure,
+            at: Utc::now(),
+        };
+        self.events.lock().unwrap().push(event.clone());
+        event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1 << 30;
+
+    fn sample(used_gb: u64) -> Vec<GpuMemorySample> {
+        vec![GpuMemorySample {
+            index: 0,
+            name: "test-gpu".into(),
+            used_bytes: used_gb * GB,
+            total_bytes: 24 * GB,
+        }]
+    }
+
+    #[test]
+    fn pressure_unloads_least_recently_used_models_and_tracks_reloads() {
+        let unloaded = Arc::new(Mutex::new(Vec::new()));
+        let hook_log = Arc::clone(&unloaded);
+        let monitor = VramMonitor::new(VramMonitorConfig::default()).with_unload_hook(Arc::new(
+            move |model: &LoadedModel| hook_log.lock().unwrap().push(model.name.clone()),
+        ));
+        monitor.record_load("planner-7b", 0, 8 * GB);
+        monitor.record_load("coder-13b", 0, 10 * GB);
+        monitor.record_load("embedder", 0, 2 * GB);
+        monitor.touch("planner-7b");
+
+        assert!(monitor.observe(sample(20)).is_empty());
+        assert!((monitor.pressure(0).unwrap() - 20.0 / 24.0).abs() < 1e-9);
+
+        let events = monitor.observe(sample(23));
+        let names: Vec<_> = events.iter().map(|event| event.model.as_str()).collect();
+        assert_eq!(names, ["coder-13b"]);
+        assert_eq!(*unloaded.lock().unwrap(), ["coder-13b"]);
+        let resident: Vec<_> = monitor
+            .loaded_models()
+            .into_iter()
+            .map(|model| model.name)
+            .collect();
+        assert_eq!(resident, ["embedder", "planner-7b"]);
+
+        monitor.record_load("coder-13b", 0, 10 * GB);
+        let churn = monitor.churn();
+        assert_eq!(
+            churn["coder-13b"],
+            ModelChurn {
+                loads: 2,
+                reloads: 1,
+                unloads: 1,
+            }
+        );
+        assert_eq!(churn["planner-7b"].unloads, 0);
+    }
+}
@@ -0,0 +1,277 @@
+//! Central registry of the tools agents can use and workflows can require.
+//!
+//! Every tool has a unique name, a capability id (the string a workflow
+//! `ToolRequirement` or agent manifest refers to), a JSON schema for its input, and an
+//! optional handler. Definitions are checked against this registry when they are loaded
+//! so a misspelt capability fails up front instead of at dispatch.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+
+/// Invokes a tool with input that already satisfied its schema.
+pub type ToolHandler = Arc<dyn Fn(&Value) -> Result<Value, String> + Send + Sync>;
+
+#[derive(Debug, Error)]
+pub enum ToolRegistryError {
+    #[error("unknown tool '{0}'")]
+    UnknownTool(String),
+    #[error("tool '{name}' conflicts with registered tool '{existing}'")]
+    Duplicate { name: String, existing: String },
+    #[error("tool '{0}' has no handler")]
+    NoHandler(String),
+    #[error("input for tool '{tool}' is invalid: {reason}")]
+    InvalidInput { tool: String, reason: String },
+    #[error("tool '{tool}' failed: {reason}")]
+    Failed { tool: String, reason: String },
+    #[error("tool catalog could not be parsed: {0}")]
+    Catalog(#[from] serde_json::Error),
+}
+
+/// Public description of a tool, as listed by [`ToolRegistry::list_tools`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolSpec {
+    pub name: String,
+    pub capability: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub input_schema: Value,
+    /// Whether a handler is attached; ignored when reading a catalog.
+    #[serde(default)]
+    pub invocable: bool,
+}
+
+impl ToolSpec {
+    pub fn new(name: impl Into<String>, capability: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            capability: capability.into(),
+            description: String::new(),
+            input_schema: Value::Null,
+            invocable: false,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn with_input_schema(mut self, schema: Value) -> Self {
+        self.input_schema = schema;
+        self
+    }
+
+    /// Check the top-level `type: object` and `required` members of the input schema.
+    pub fn validate_input(&self, input: &Value) -> Result<(), ToolRegistryError> {
+        let invalid = |reason: String| ToolRegistryError::InvalidInput {
+            tool: self.name.clone(),
+            reason,
+        };
+        if self.input_schema.get("type").and_then(Value::as_str) == Some("object") {
+            let object = input
+                .as_object()
+                .ok_or_else(|| invalid("expected a JSON object".into()))?;
+            let required = self
+                .input_schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str);
+            for field in required {
+                if !object.contains_key(field) {
+                    return Err(invalid(format!("missing required field '{field}'")));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct RegisteredTool {
+    spec: ToolSpec,
+    handler: Option<ToolHandler>,
+}
+
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: RwLock<HashMap<String, RegisteredTool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry seeded with the tools bundled in `data/tool_registry.json`.
+    pub fn with_builtin_tools() -> Self {
+        let registry = Self::new();
+        registry
+            .load_catalog(include_str!("../data/tool_registry.json"))
+            .expect("bundled tool catalog is valid");
+        registry
+    }
+
+    /// Register every tool in a JSON array of [`ToolSpec`]s. Returns how many were added.
+    pub fn load_catalog(&self, raw: &str) -> Result<usize, ToolRegistryError> {
+        let specs: Vec<ToolSpec> = serde_json::from_str(raw)?;
+        let count = specs.len();
+        for spec in specs {
+            self.register(spec)?;
+        }
+        Ok(count)
+    }
+
+    /// Add a tool. Names and capability ids must both be unique.
+    pub fn register(&self, mut spec: ToolSpec) -> Result<(), ToolRegistryError> {
+        let mut tools = self.tools.write().unwrap();
+        if let Some(existing) = tools.values().find(|tool| {
+            tool.spec.name.eq_ignore_ascii_case(&spec.name)
+                || tool.spec.capability.eq_ignore_ascii_case(&spec.capability)
+        }) {
+            return Err(ToolRegistryError::Duplicate {
+                name: spec.name,
+                existing: existing.spec.name.clone(),
+            });
+        }
+        spec.invocable = false;
+        tools.insert(
+            spec.name.to_ascii_lowercase(),
+            RegisteredTool {
+                spec,
+                handler: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Attach the handler for a registered tool, replacing any previous one.
+    pub fn set_handler(
+        &self,
+        reference: &str,
+        handler: ToolHandler,
+    ) -> Result<(), ToolRegistryError> {
+        let mut tools = self.tools.write().unwrap();
+        let tool = Self::lookup_mut(&mut tools, reference)
+            .ok_or_else(|| ToolRegistryError::UnknownTool(reference.to_string()))?;
+        tool.spec.invocable = true;
+        tool.handler = Some(handler);
+        Ok(())
+    }
+
+    /// Find a tool by name or capability id, ignoring case.
+    pub fn get(&self, reference: &str) -> Option<ToolSpec> {
+        let tools = self.tools.read().unwrap();
+        Self::lookup(&tools, reference).map(|tool| tool.spec.clone())
+    }
+
+    /// Resolve `capability` or fail with [`ToolRegistryError::UnknownTool`].
+    pub fn require(&self, capability: &str) -> Result<ToolSpec, ToolRegistryError> {
+        self.get(capability)
+            .ok_or_else(|| ToolRegistryError::UnknownTool(capability.to_string()))
+    }
+
+    /// Every registered tool, sorted by name.
+    pub fn list_tools(&self) -> Vec<ToolSpec> {
+        let mut specs: Vec<ToolSpec> = self
+            .tools
+            .read()
+            .unwrap()
+            .values()
+            .map(|tool| tool.spec.clone())
+            .collect();
+        specs.sort_by(|a, b| a.name.cmp(&b.name));
+        specs
+    }
+
+    /// Validate `input` against the tool's schema and run its handler.
+    pub fn invoke(&self, reference: &str, input: &Value) -> Result<Value, ToolRegistryError> {
+        let (spec, handler) = {
+            let tools = self.tools.read().unwrap();
+            let tool = Self::lookup(&tools, reference)
+                .ok_or_else(|| ToolRegistryError::UnknownTool(reference.to_string()))?;
+            (tool.spec.clone(), tool.handler.clone())
+        };
+        let handler = handler.ok_or_else(|| ToolRegistryError::NoHandler(spec.name.clone()))?;
+        spec.validate_input(input)?;
+        handler(input).map_err(|reason| ToolRegistryError::Failed {
+            tool: spec.name,
+            reason,
+        })
+    }
+
+    fn lookup<'a>(
+        tools: &'a HashMap<String, RegisteredTool>,
+        reference: &str,
+    ) -> Option<&'a RegisteredTool> {
+        tools.get(&reference.to_ascii_lowercase()).or_else(|| {
+            tools
+                .values()
+                .find(|tool| tool.spec.capability.eq_ignore_ascii_case(reference))
+        })
+    }
+
+    fn lookup_mut<'a>(
+        tools: &'a mut HashMap<String, RegisteredTool>,
+        reference: &str,
+    ) -> Option<&'a mut RegisteredTool> {
+        let key = tools
+            .iter()
+            .find(|(key, tool)| {
+                key.eq_ignore_ascii_case(reference)
+                    || tool.spec.capability.eq_ignore_ascii_case(reference)
+            })
+            .map(|(key, _)| key.clone())?;
+        tools.get_mut(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tools_resolve_by_name_or_capability_and_validate_input() {
+        let registry = ToolRegistry::with_builtin_tools();
+        assert!(registry.get("workflow.taskDispatch").is_some());
+        assert_eq!(registry.get("LINT").unwrap().capability, "code.lint");
+        assert!(matches!(
+            registry.require("workflow.teleport"),
+            Err(ToolRegistryError::UnknownTool(_))
+        ));
+        assert!(matches!(
+            registry.register(ToolSpec::new("linter", "code.lint")),
+            Err(ToolRegistryError::Duplicate { .. })
+        ));
+
+        assert!(matches!(
+            registry.invoke("code.lint", &json!({"path": "src"})),
+            Err(ToolRegistryError::NoHandler(_))
+        ));
+        registry
+            .set_handler(
+                "code.lint",
+                Arc::new(|input| Ok(json!({ "linted": input["path"] }))),
+            )
+            .unwrap();
+        assert_eq!(
+            registry.invoke("lint", &json!({"path": "src"})).unwrap(),
+            json!({"linted": "src"})
+        );
+        assert!(matches!(
+            registry.invoke("lint", &json!({})),
+            Err(ToolRegistryError::InvalidInput { .. })
+        ));
+
+        let listed = registry.list_tools();
+        assert!(listed.windows(2).all(|pair| pair[0].name <= pair[1].name));
+        assert!(listed
+            .iter()
+            .any(|tool| tool.name == "lint" && tool.invocable));
+    }
+}
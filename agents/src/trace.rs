@@ -0,0 +1,575 @@
+//! Step-level execution traces for agent tasks.
+//!
+//! A dispatch receipt only records the final output. A [`TraceRecorder`] captures the
+//! steps that led there (resolution, model calls, tool calls and each retry attempt) as
+//! nested spans carrying the intermediate artifacts, and a [`TraceStore`] persists the
+//! finished [`ExecutionTrace`] under `storage/db/agents/traces` so a failed task can be
+//! diagnosed without running it again.
+
+use crate::inference::{InferenceConfig, InferenceEngine};
+use crate::tools::{ToolRegistry, ToolRegistryError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+/// Default root for persisted traces, relative to the workspace.
+pub const AGENT_TRACE_ROOT: &str = "storage/db/agents/traces";
+/// Artifacts whose JSON encoding exceeds this are stored truncated.
+pub const MAX_ARTIFACT_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Error)]
+pub enum TraceError {
+    #[error("trace id '{0}' cannot be used as a file name")]
+    InvalidTraceId(String),
+    #[error("trace '{0}' not found")]
+    NotFound(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanKind {
+    ResolveAgent,
+    Placement,
+    Memory,
+    Instantiate,
+    ModelCall,
+    ToolCall,
+    /// One try of a retried step; always the child of the step's span.
+    Attempt,
+    Custom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Intermediate value captured by a span, such as a prompt, completion or tool output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceArtifact {
+    pub name: String,
+    pub value: Value,
+    #[serde(default)]
+    pub truncated: bool,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl TraceArtifact {
+    fn new(name: impl Into<String>, value: Value) -> Self {
+        let encoded = value.to_string();
+        let (value, truncated) = if encoded.len() > MAX_ARTIFACT_BYTES {
+            let mut end = MAX_ARTIFACT_BYTES;
+            while !encoded.is_char_boundary(end) {
+                end -= 1;
+            }
+            (Value::String(encoded[..end].to_string()), true)
+        } else {
+            (value, false)
+        };
+        Self {
+            name: name.into(),
+            value,
+            truncated,
+            recorded_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceSpan {
+    pub id: String,
+    #[serde(default)]
+    pub parent: Option<String>,
+    pub kind: SpanKind,
+    pub name: String,
+    pub started_at: DateTime<Utc>,
+    #[serde(default)]
+    pub ended_at: Option<DateTime<Utc>>,
+    pub status: SpanStatus,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// 1-based attempt number for [`SpanKind::Attempt`] spans.
+    #[serde(default)]
+    pub attempt: Option<u32>,
+    #[serde(default)]
+    pub attributes: serde_json::Map<String, Value>,
+    #[serde(default)]
+    pub artifacts: Vec<TraceArtifact>,
+}
+
+impl TraceSpan {
+    pub fn artifact(&self, name: &str) -> Option<&TraceArtifact> {
+        self.artifacts.iter().find(|artifact| artifact.name == name)
+    }
+
+    pub fn duration_ms(&self) -> Option<i64> {
+        self.ended_at
+            .map(|ended| (ended - self.started_at).num_milliseconds())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionTrace {
+    pub trace_id: String,
+    pub agent_id: String,
+    pub action: String,
+    pub started_at: DateTime<Utc>,
+    #[serde(default)]
+    pub ended_at: Option<DateTime<Utc>>,
+    pub status: SpanStatus,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub spans: Vec<TraceSpan>,
+}
+
+impl ExecutionTrace {
+    pub fn span(&self, id: &str) -> Option<&TraceSpan> {
+        self.spans.iter().find(|span| span.id == id)
+    }
+
+    /// Direct children of `parent`, or the top-level spans for `None`, in start order.
+    pub fn children(&self, parent: Option<&str>) -> Vec<&TraceSpan> {
+        self.spans
+            .iter()
+            .filter(|span| span.parent.as_deref() == parent)
+            .collect()
+    }
+
+    /// Failed spans none of whose children failed: where each failure originated.
+    pub fn failures(&self) -> Vec<&TraceSpan> {
+        self.spans
+            .iter()
+            .filter(|span| span.status == SpanStatus::Failed)
+            .filter(|span| {
+                !self.spans.iter().any(|child| {
+                    child.parent.as_deref() == Some(span.id.as_str())
+                        && child.status == SpanStatus::Failed
+                })
+            })
+            .collect()
+    }
+}
+
+/// Records spans for one task execution. Clones share the same trace.
+#[derive(Debug, Clone)]
+pub struct TraceRecorder {
+    trace: Arc<Mutex<ExecutionTrace>>,
+}
+
+impl TraceRecorder {
+    pub fn new(agent_id: impl Into<String>, action: impl Into<String>) -> Self {
+        Self {
+            trace: Arc::new(Mutex::new(ExecutionTrace {
+                trace_id: uuid::Uuid::new_v4().to_string(),
+                agent_id: agent_id.into(),
+                action: action.into(),
+                started_at: Utc::now(),
+                ended_at: None,
+                status: SpanStatus::Running,
+                error: None,
+                spans: Vec::new(),
+            })),
+        }
+    }
+
+    pub fn trace_id(&self) -> String {
+        self.trace.lock().unwrap().trace_id.clone()
+    }
+
+    /// Set the agent once it is known, e.g. after role resolution.
+    pub fn set_agent(&self, agent_id: impl Into<String>) {
+        self.trace.lock().unwrap().agent_id = agent_id.into();
+    }
+
+    /// Open a span under `parent` (top level for `None`) and return its id.
+    pub fn start_span(
+        &self,
+        parent: Option<&str>,
+        kind: SpanKind,
+        name: impl Into<String>,
+    ) -> String {
+        let mut trace = self.trace.lock().unwrap();
+        let id = format!("span-{}", trace.spans.len() + 1);
+        trace.spans.push(TraceSpan {
+            id: id.clone(),
+            parent: parent.map(str::to_string),
+            kind,
+            name: name.into(),
+            started_at: Utc::now(),
+            ended_at: None,
+            status: SpanStatus::Running,
+            error: None,
+            attempt: None,
+            attributes: serde_json::Map::new(),
+            artifacts: Vec::new(),
+        });
+        id
+    }
+
+    pub fn set_attribute(&self, span: &str, key: impl Into<String>, value: impl Into<Value>) {
+        self.with_span(span, |span| {
+            span.attributes.insert(key.into(), value.into());
+        });
+    }
+
+    pub fn attach(&self, span: &str, name: impl Into<String>, value: impl Into<Value>) {
+        let artifact = TraceArtifact::new(name, value.into());
+        self.with_span(span, |span| span.artifacts.push(artifact));
+    }
+
+    /// Close a span, failed when `error` is set.
+    pub fn end_span(&self, span: &str, error: Option<String>) {
+        self.with_span(span, |span| {
+            span.ended_at = Some(Utc::now());
+            span.status = if error.is_some() {
+                SpanStatus::Failed
+            } else {
+                SpanStatus::Succeeded
+            };
+            span.error = error;
+        });
+    }
+
+    /// Run `f` inside a new span that closes with its result.
+    pub fn in_span<T, E: Display>(
+        &self,
+        parent: Option<&str>,
+        kind: SpanKind,
+        name: impl Into<String>,
+        f: impl FnOnce(&str) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let span = self.start_span(parent, kind, name);
+        let result = f(&span);
+        self.end_span(&span, result.as_ref().err().map(ToString::to_string));
+        result
+    }
+
+    /// Run `f` up to `attempts` times inside one span, recording every try as a
+    /// [`SpanKind::Attempt`] child. Errors for which `retryable` is false stop early.
+    pub fn retry<T, E: Display>(
+        &self,
+        parent: Option<&str>,
+        kind: SpanKind,
+        name: impl Into<String>,
+        attempts: u32,
+        retryable: impl Fn(&E) -> bool,
+        f: impl FnMut(&str) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let span = self.start_span(parent, kind, name);
+        self.run_attempts(&span, attempts, retryable, f)
+    }
+
+    fn run_attempts<T, E: Display>(
+        &self,
+        span: &str,
+        attempts: u32,
+        retryable: impl Fn(&E) -> bool,
+        mut f: impl FnMut(&str) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let name = self
+            .snapshot_span(span)
+            .map(|span| span.name)
+            .unwrap_or_default();
+        let attempts = attempts.max(1);
+        let mut attempt = 1;
+        let result = loop {
+            let child = self.start_span(Some(span), SpanKind::Attempt, name.clone());
+            self.with_span(&child, |span| span.attempt = Some(attempt));
+            let result = f(&child);
+            self.end_span(&child, result.as_ref().err().map(ToString::to_string));
+            match result {
+                Err(err) if attempt < attempts && retryable(&err) => attempt += 1,
+                result => break result,
+            }
+        };
+        self.set_attribute(span, "attempts", attempt);
+        self.end_span(span, result.as_ref().err().map(ToString::to_string));
+        result
+    }
+
+    /// Generate a completion, recording the prompt and completion as artifacts.
+    pub async fn generate(
+        &self,
+        parent: Option<&str>,
+        engine: &dyn InferenceEngine,
+        prompt: &str,
+        config: InferenceConfig,
+    ) -> anyhow::Result<String> {
+        let span = self.start_span(parent, SpanKind::ModelCall, engine.model_name());
+        self.set_attribute(&span, "max_tokens", config.max_tokens);
+        self.set_attribute(&span, "temperature", config.temperature);
+        self.attach(&span, "prompt", prompt);
+        let result = engine.generate(prompt, config).await;
+        match &result {
+            Ok(completion) => {
+                self.attach(&span, "completion", completion.as_str());
+                self.end_span(&span, None);
+            }
+            Err(err) => self.end_span(&span, Some(err.to_string())),
+        }
+        result
+    }
+
+    /// Invoke a registered tool, retrying handler failures up to `attempts` times.
+    /// The input is attached to the call and each attempt's output to the attempt.
+    pub fn invoke_tool(
+        &self,
+        parent: Option<&str>,
+        tools: &ToolRegistry,
+        reference: &str,
+        input: &Value,
+        attempts: u32,
+    ) -> Result<Value, ToolRegistryError> {
+        let span = self.start_span(parent, SpanKind::ToolCall, reference);
+        self.attach(&span, "input", input.clone());
+        self.run_attempts(
+            &span,
+            attempts,
+            |err| matches!(err, ToolRegistryError::Failed { .. }),
+            |attempt| {
+                let output = tools.invoke(reference, input)?;
+                self.attach(attempt, "output", output.clone());
+                Ok(output)
+            },
+        )
+    }
+
+    /// Close the trace and return it.
+    pub fn finish(&self, error: Option<String>) -> ExecutionTrace {
+        let mut trace = self.trace.lock().unwrap();
+        let now = Utc::now();
+        for span in trace
+            .spans
+            .iter_mut()
+            .filter(|span| span.status == SpanStatus::Running)
+        {
+            span.ended_at = Some(now);
+            span.status = SpanStatus::Failed;
+            span.error = Some("span was not closed".to_string());
+        }
+        trace.ended_at = Some(now);
+        trace.status = if error.is_some() {
+            SpanStatus::Failed
+        } else {
+            SpanStatus::Succeeded
+        };
+        trace.error = error;
+        trace.clone()
+    }
+
+    /// Copy of the trace recorded so far.
+    pub fn snapshot(&self) -> ExecutionTrace {
+        self.trace.lock().unwrap().clone()
+    }
+
+    fn snapshot_span(&self, id: &str) -> Option<TraceSpan> {
+        self.trace.lock().unwrap().span(id).cloned()
+    }
+
+    fn with_span(&self, id: &str, f: impl FnOnce(&mut TraceSpan)) {
+        let mut trace = self.trace.lock().unwrap();
+        if let Some(span) = trace.spans.iter_mut().find(|span| span.id == id) {
+            f(span);
+        }
+    }
+}
+
+/// Finished traces stored as `<root>/<trace_id>.json`.
+#[derive(Debug, Clone)]
+pub struct TraceStore {
+    root: PathBuf,
+}
+
+impl Default for TraceStore {
+    fn default() -> Self {
+        Self::new(AGENT_TRACE_ROOT)
+    }
+}
+
+impl TraceStore {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn save(&self, trace: &ExecutionTrace) -> Result<PathBuf, TraceError> {
+        let path = self.path(&trace.trace_id)?;
+        std::fs::create_dir_all(&self.root)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(trace)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(path)
+    }
+
+    pub fn load(&self, trace_id: &str) -> Result<ExecutionTrace, TraceError> {
+        let path = self.path(trace_id)?;
+        if !path.exists() {
+            return Err(TraceError::NotFound(trace_id.to_string()));
+        }
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Stored traces, optionally only `agent_id`'s, oldest first.
+    pub fn list(&self, agent_id: Option<&str>) -> Result<Vec<ExecutionTrace>, TraceError> {
+        let entries = match std::fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut traces = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let trace: ExecutionTrace = serde_json::from_slice(&std::fs::read(&path)?)?;
+            if agent_id.is_none_or(|agent| trace.agent_id == agent) {
+                traces.push(trace);
+            }
+        }
+        traces.sort_by_key(|trace| trace.started_at);
+        Ok(traces)
+    }
+
+    fn path(&self, trace_id: &str) -> Result<PathBuf, TraceError> {
+        let valid = !trace_id.is_empty()
+            && trace_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+        if !valid {
+            return Err(TraceError::InvalidTraceId(trace_id.to_string()));
+        }
+        Ok(self.root.join(format!("{trace_id}.json")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::ToolSpec;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct FailingEngine;
+
+    #[async_trait]
+    impl InferenceEngine for FailingEngine {
+        async fn generate(
+            &self,
+            _prompt: &str,
+            _config: InferenceConfig,
+        ) -> anyhow::Result<String> {
+            anyhow::bail!("context window exceeded")
+        }
+
+        fn model_name(&self) -> &str {
+            "failing"
+        }
+
+        async fn is_available(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn trace_records_retries_artifacts_and_failures_and_persists() {
+        let tools = ToolRegistry::new();
+        tools
+            .register(ToolSpec::new("linter", "code.lint"))
+            .unwrap();
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&calls);
+        tools
+            .set_handler(
+                "code.lint",
+                Arc::new(move |input: &Value| {
+                    if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                        Err("linter crashed".to_string())
+                    } else {
+                        Ok(json!({ "warnings": 0, "path": input["path"] }))
+                    }
+                }),
+            )
+            .unwrap();
+
+        let recorder = TraceRecorder::new("reviewer", "review");
+        let step = recorder.start_span(None, SpanKind::Custom, "review");
+        let output = recorder
+            .invoke_tool(
+                Some(&step),
+                &tools,
+                "code.lint",
+                &json!({ "path": "src" }),
+                3,
+            )
+            .expect("second attempt succeeds");
+        assert_eq!(output["warnings"], 0);
+        let err = recorder
+            .generate(
+                Some(&step),
+                &FailingEngine,
+                "summarize the lint",
+                InferenceConfig::default(),
+            )
+            .await
+            .expect_err("engine fails");
+        recorder.end_span(&step, Some(err.to_string()));
+        let trace = recorder.finish(Some(err.to_string()));
+
+        let call = trace
+            .spans
+            .iter()
+            .find(|span| span.kind == SpanKind::ToolCall)
+            .expect("tool span");
+        assert_eq!(call.status, SpanStatus::Succeeded);
+        assert_eq!(call.attributes["attempts"], 2);
+        assert_eq!(call.artifact("input").unwrap().value["path"], "src");
+        let attempts = trace.children(Some(&call.id));
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(
+            attempts[0].error.as_deref(),
+            Some("tool 'linter' failed: linter crashed")
+        );
+        assert_eq!(attempts[1].attempt, Some(2));
+        assert!(attempts[1].artifact("output").is_some());
+
+        let failures = trace.failures();
+        assert_eq!(failures.len(), 2, "failed first attempt and model call");
+        let model = failures
+            .iter()
+            .find(|span| span.kind == SpanKind::ModelCall)
+            .expect("model call failed");
+        assert_eq!(
+            model.artifact("prompt").unwrap().value,
+            "summarize the lint"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = TraceStore::new(dir.path());
+        store.save(&trace).unwrap();
+        assert_eq!(store.load(&trace.trace_id).unwrap(), trace);
+        assert_eq!(store.list(Some("reviewer")).unwrap().len(), 1);
+        assert!(store.list(Some("other")).unwrap().is_empty());
+        assert!(matches!(
+            store.load("../escape"),
+            Err(TraceError::InvalidTraceId(_))
+        ));
+    }
+}
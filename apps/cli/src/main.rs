@@ -1,20 +1,24 @@
-use std::io::BufRead;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 
+#[cfg(feature = "cicd")]
+use anyhow::Error;
 use anyhow::{bail, ensure, Context, Result};
 use clap::{Args, Parser, Subcommand};
 use noa_caddy_manager::{CaddyManager, HealthProbe, RateLimitConfig, ReverseProxyRoute};
 #[cfg(feature = "cicd")]
-use noa_cicd::CICDSystem;
+use noa_cicd::{CICDSystem, ConfigContext};
+use noa_core::config::host_profile::{SingleHostProfile, DEFAULT_SINGLE_HOST_PROFILE};
+use noa_core::host_control::RuntimeGraph;
+use noa_core::recovery::RecoveryMode;
 #[cfg(feature = "inference")]
 use noa_inference::{
     CompletionRequest, ProviderRouter, TelemetryEvent, TelemetryHandle, TelemetrySink,
     TelemetryStatus,
 };
 use noa_plugin_sdk::{ToolDescriptor, ToolRegistry};
-use noa_workflow::EvidenceLedgerEntry;
+use noa_workflow::read_evidence_ledger;
 #[cfg(feature = "inference")]
 use noa_workflow::InferenceMetric;
 use noa_workflow::PipelineInstrumentation;
@@ -34,7 +38,7 @@ enum OutputMode {
 #[derive(Parser)]
 #[command(
     name = "noa",
-    about = "NOA Ark OS unified CLI (kernel, world, registry, trust, snapshot, agent, policy, sbom, pipeline, profile)",
+    about = "NOA Ark OS unified CLI (kernel, world, registry, trust, snapshot, agent, policy, sbom, pipeline, profile, doctor, index)",
     long_about = "NOA Ark OS relocation daemon tooling",
     version
 )]
@@ -187,6 +191,24 @@ enum Commands {
         #[command(subcommand)]
         command: PipelineCommands,
     },
+    /// Check workspace state for broken files and optionally repair them
+    Doctor {
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+        /// Apply the remediation of each finding
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Export or import the workspace indexes to seed new hosts
+    Index {
+        #[command(subcommand)]
+        command: IndexCommands,
+    },
+    /// Report disk usage against the per-subsystem storage budgets
+    Storage {
+        #[command(subcommand)]
+        command: StorageCommands,
+    },
 }
 
 #[derive(Subcommand)]
@@ -300,10 +322,27 @@ enum SbomCmd {
 
 #[derive(Subcommand)]
 enum ProfileCmd {
-    Switch { name: String },
+    Switch {
+        name: String,
+    },
     List,
-    Validate { name: String },
-    Diff { a: String, b: String },
+    Validate {
+        name: String,
+    },
+    Diff {
+        a: String,
+        b: String,
+    },
+    /// Generate or update the single-host profile from the current host
+    Generate {
+        #[arg(long, default_value = DEFAULT_SINGLE_HOST_PROFILE)]
+        path: PathBuf,
+        #[arg(long, default_value = "runtime/kernel/graph.yaml")]
+        graph: PathBuf,
+        /// Only validate the existing profile against the runtime graph
+        #[arg(long)]
+        check: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -377,6 +416,90 @@ enum PipelineCommands {
         #[arg(long)]
         workspace: Option<PathBuf>,
     },
+    /// Export a pipeline's signed evidence bundle for auditors
+    ExportEvidence {
+        #[arg(long)]
+        pipeline: String,
+        /// Bundle path; defaults to storage/db/pipelines/evidence/<pipeline>.tar.gz
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Check an evidence bundle's digests and manifest signature
+    VerifyEvidence {
+        #[arg(long)]
+        bundle: PathBuf,
+    },
+    /// Keep a pipeline's artifacts and evidence bundle until unpinned
+    Pin {
+        #[arg(long)]
+        pipeline: String,
+        #[arg(long, default_value = "pinned from the CLI")]
+        reason: String,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Return a pinned pipeline's artifacts to the interim retention
+    Unpin {
+        #[arg(long)]
+        pipeline: String,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Delete artifacts and evidence bundles whose retention expired
+    SweepArtifacts {
+        /// Days an interim build is kept
+        #[arg(long, default_value_t = 14)]
+        interim_days: u64,
+        /// Days a failed build is kept
+        #[arg(long, default_value_t = 2)]
+        failed_days: u64,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum IndexCommands {
+    /// Bundle the indexer, symbol graph, embeddings and search indexes
+    Export {
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Verify a bundle and install it into the workspace
+    Import {
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+        #[arg(long)]
+        bundle: PathBuf,
+    },
+    /// Check a bundle's digests and layout version
+    Verify {
+        #[arg(long)]
+        bundle: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum StorageCommands {
+    /// Show usage, growth and projected exhaustion per subsystem
+    Usage {
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+        /// Time-series store recording usage across runs for growth estimates
+        #[arg(long)]
+        metrics_store: Option<PathBuf>,
+    },
+    /// Apply the action of every exceeded budget
+    Enforce {
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+        #[arg(long)]
+        metrics_store: Option<PathBuf>,
+    },
 }
 
 fn parse_mode(value: &str) -> std::result::Result<ExecutionMode, String> {
@@ -470,6 +593,24 @@ fn main() -> Result<()> {
                     ProfileCmd::List => json!({"component":"profile","action":"list","status":"not_implemented"}),
                     ProfileCmd::Validate { name } => json!({"component":"profile","action":"validate","name": name, "status":"not_implemented"}),
                     ProfileCmd::Diff { a, b } => json!({"component":"profile","action":"diff","a": a, "b": b, "status":"not_implemented"}),
+                    ProfileCmd::Generate { path, graph, check } => {
+                        let graph = RuntimeGraph::load_from_path(&graph)?;
+                        if check {
+                            let issues = SingleHostProfile::load(&path)?.validate(&graph);
+                            ensure!(issues.is_empty(), "{}: {}", path.display(), issues.join("; "));
+                            json!({"component":"profile","action":"check","path": path, "status":"valid"})
+                        } else {
+                            let (profile, inventory) = SingleHostProfile::generate(&path, &graph)?;
+                            json!({
+                                "component": "profile",
+                                "action": "generate",
+                                "path": path,
+                                "classification": inventory.classification,
+                                "runtimes": inventory.runtimes,
+                                "runtime_graph": profile.document().get("runtime_graph"),
+                            })
+                        }
+                    }
                 };
                 print_obj(out_mode, &v)?;
             }
@@ -684,12 +825,7 @@ fn main() -> Result<()> {
                     tags,
                     workspace,
                 } => {
-                    let workspace_root = workspace.unwrap_or_else(|| {
-                        std::env::current_dir().expect("unable to determine workspace")
-                    });
-                    std::env::set_var("NOA_WORKFLOW_ROOT", &workspace_root);
-                    let cicd = CICDSystem::new();
-                    cicd.configure_workspace_root(workspace_root.clone());
+                    let cicd = cicd_in_workspace(workspace);
                     let agent_identifier = agent_id.unwrap_or_else(|| agent.clone());
                     let status = cicd
                         .register_agent_approval(
@@ -711,7 +847,170 @@ fn main() -> Result<()> {
                     });
                     println!("{}", serde_json::to_string_pretty(&payload)?);
                 }
+                PipelineCommands::ExportEvidence {
+                    pipeline,
+                    output,
+                    workspace,
+                } => {
+                    let cicd = cicd_in_workspace(workspace);
+                    let bundle = match output {
+                        Some(path) => {
+                            cicd.export_evidence_bundle_to(&pipeline, &path)
+                                .map_err(Error::msg)?;
+                            path
+                        }
+                        None => cicd.export_evidence_bundle(&pipeline).map_err(Error::msg)?,
+                    };
+                    let verification =
+                        noa_cicd::evidence::verify_evidence_bundle(&bundle).map_err(Error::msg)?;
+                    print_obj(
+                        out_mode,
+                        &json!({
+                            "pipeline_id": pipeline,
+                            "bundle": bundle,
+                            "files": verification.files_verified,
+                            "manifest_sha256": verification.manifest_sha256,
+                        }),
+                    )?;
+                }
+                PipelineCommands::VerifyEvidence { bundle } => {
+                    let verification =
+                        noa_cicd::evidence::verify_evidence_bundle(&bundle).map_err(Error::msg)?;
+                    print_obj(out_mode, &serde_json::to_value(&verification)?)?;
+                }
+                PipelineCommands::Pin {
+                    pipeline,
+                    reason,
+                    workspace,
+                } => {
+                    let cicd = cicd_in_workspace(workspace);
+                    let retention = cicd
+                        .pin_pipeline_artifacts(&pipeline, reason)
+                        .map_err(Error::msg)?;
+                    print_obj(out_mode, &serde_json::to_value(&retention)?)?;
+                }
+                PipelineCommands::Unpin {
+                    pipeline,
+                    workspace,
+                } => {
+                    let cicd = cicd_in_workspace(workspace);
+                    let retention = cicd
+                        .unpin_pipeline_artifacts(&pipeline)
+                        .map_err(Error::msg)?;
+                    print_obj(out_mode, &serde_json::to_value(&retention)?)?;
+                }
+                PipelineCommands::SweepArtifacts {
+                    interim_days,
+                    failed_days,
+                    workspace,
+                } => {
+                    let cicd = cicd_in_workspace(workspace);
+                    let policy = noa_cicd::artifacts::RetentionPolicy {
+                        interim_ttl_secs: interim_days * 24 * 60 * 60,
+                        failed_ttl_secs: failed_days * 24 * 60 * 60,
+                    };
+                    let now = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)?
+                        .as_secs();
+                    let sweep = cicd.sweep_artifacts(&policy, now).map_err(Error::msg)?;
+                    print_obj(out_mode, &serde_json::to_value(&sweep)?)?;
+                }
             },
+            #[cfg(feature = "cicd")]
+            Commands::Doctor { workspace, fix } => {
+                let workspace_root = workspace.unwrap_or_else(|| {
+                    std::env::current_dir().expect("unable to determine workspace")
+                });
+                let doctor = noa_cicd::doctor::Doctor::new(workspace_root);
+                let report = if fix {
+                    doctor.remediate()
+                } else {
+                    doctor.diagnose()
+                };
+                print_obj(out_mode, &serde_json::to_value(&report)?)?;
+                let unresolved = report.unresolved().count();
+                ensure!(unresolved == 0, "{unresolved} workspace problem(s) unresolved");
+            }
+            #[cfg(feature = "cicd")]
+            Commands::Index { command } => {
+                let workspace_root = |workspace: Option<PathBuf>| {
+                    workspace.unwrap_or_else(|| {
+                        std::env::current_dir().expect("unable to determine workspace")
+                    })
+                };
+                let value = match command {
+                    IndexCommands::Export { workspace, output } => {
+                        let manifest = noa_cicd::index_bundle::export_index_bundle(
+                            &workspace_root(workspace),
+                            &output,
+                        )
+                        .map_err(Error::msg)?;
+                        json!({
+                            "bundle": output,
+                            "commit": manifest.commit,
+                            "components": manifest.components,
+                            "files": manifest.files.len(),
+                        })
+                    }
+                    IndexCommands::Import { workspace, bundle } => {
+                        let import = noa_cicd::index_bundle::import_index_bundle(
+                            &bundle,
+                            &workspace_root(workspace),
+                        )
+                        .map_err(Error::msg)?;
+                        json!({
+                            "bundle": bundle,
+                            "commit": import.manifest.commit,
+                            "components": import.manifest.components,
+                            "files": import.files_imported,
+                            "journal_cursor": import.journal_cursor,
+                        })
+                    }
+                    IndexCommands::Verify { bundle } => serde_json::to_value(
+                        noa_cicd::index_bundle::verify_index_bundle(&bundle)
+                            .map_err(Error::msg)?,
+                    )?,
+                };
+                print_obj(out_mode, &value)?;
+            }
+            Commands::Storage { command } => {
+                let (workspace, metrics_store, enforce) = match command {
+                    StorageCommands::Usage {
+                        workspace,
+                        metrics_store,
+                    } => (workspace, metrics_store, false),
+                    StorageCommands::Enforce {
+                        workspace,
+                        metrics_store,
+                    } => (workspace, metrics_store, true),
+                };
+                let workspace_root = workspace.unwrap_or_else(|| {
+                    std::env::current_dir().expect("unable to determine workspace")
+                });
+                let mut manager =
+                    noa_core::storage::StorageManager::with_defaults(&workspace_root);
+                if let Some(dir) = metrics_store {
+                    let store = noa_core::metrics::timeseries::TimeSeriesStore::open(&dir)?;
+                    manager = manager.with_timeseries(Arc::new(store));
+                }
+                let budgets = workspace_root.join(noa_core::storage::STORAGE_BUDGETS_FILE);
+                if budgets.exists() {
+                    manager.load_budgets(&budgets)?;
+                }
+                #[cfg(feature = "cicd")]
+                {
+                    // Pinned releases must survive artifact eviction.
+                    let cicd = cicd_in_workspace(Some(workspace_root.clone()));
+                    manager.add_eviction_guard(cicd.artifact_eviction_guard().map_err(Error::msg)?);
+                }
+                let now = noa_core::storage::now_millis();
+                let value = if enforce {
+                    serde_json::to_value(manager.enforce(now)?)?
+                } else {
+                    serde_json::to_value(manager.usage(now)?)?
+                };
+                print_obj(out_mode, &value)?;
+            }
             #[cfg(not(feature = "inference"))]
             Commands::Query { .. } => {
                 print_obj(out_mode, &json!({"component":"query","status":"inference_disabled"}))?;
@@ -720,12 +1019,31 @@ fn main() -> Result<()> {
             Commands::Pipeline { .. } => {
                 print_obj(out_mode, &json!({"component":"pipeline","status":"cicd_disabled"}))?;
             }
+            #[cfg(not(feature = "cicd"))]
+            Commands::Doctor { .. } => {
+                print_obj(out_mode, &json!({"component":"doctor","status":"cicd_disabled"}))?;
+            }
+            #[cfg(not(feature = "cicd"))]
+            Commands::Index { .. } => {
+                print_obj(out_mode, &json!({"component":"index","status":"cicd_disabled"}))?;
+            }
         }
 
         Ok(())
     })
 }
 
+/// Pipeline system rooted at `workspace`, or the current directory.
+#[cfg(feature = "cicd")]
+fn cicd_in_workspace(workspace: Option<PathBuf>) -> CICDSystem {
+    let workspace_root = workspace
+        .unwrap_or_else(|| std::env::current_dir().expect("unable to determine workspace"));
+    let cicd =
+        CICDSystem::with_context(ConfigContext::from_env().with_workflow_root(&workspace_root));
+    cicd.configure_workspace_root(workspace_root);
+    cicd
+}
+
 fn handle_registry_category(category: &str, query: RegistryArgs) -> Result<()> {
     let RegistryArgs { registry, tool } = query;
     let registry = ToolRegistry::from_path(&registry)
@@ -774,15 +1092,15 @@ fn show_evidence(workflow_filter: Option<String>, limit: Option<usize>) -> Resul
     if !path.exists() {
         bail!("evidence ledger not found at {}", path.display());
     }
-    let file = std::fs::File::open(&path)?;
-    let reader = std::io::BufReader::new(file);
+    let recovered = read_evidence_ledger(&path, RecoveryMode::Lenient)?;
+    for skipped in &recovered.skipped {
+        eprintln!(
+            "warning: skipped corrupt ledger record at {}: {}",
+            skipped.location, skipped.reason
+        );
+    }
     let mut entries = Vec::new();
-    for line in reader.lines() {
-        let line = line?;
-        if line.trim().is_empty() {
-            continue;
-        }
-        let entry: EvidenceLedgerEntry = serde_json::from_str(&line)?;
+    for entry in recovered.records {
         if let Some(workflow) = &workflow_filter {
             let payload_workflow = entry
                 .payload
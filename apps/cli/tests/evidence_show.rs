@@ -3,7 +3,10 @@ use std::sync::{Mutex, Once};
 use std::thread;
 use std::time::Duration;
 
-use noa_workflow::{PipelineInstrumentation, SecurityScanStatus, Stage, StageType, Task};
+use noa_workflow::{
+    PipelineInstrumentation, ResourceRequirements, SandboxSpec, SecurityScanStatus, Stage,
+    StageCacheControl, StageContract, StageType, Task,
+};
 use predicates::prelude::*;
 use serde_json::json;
 use tempfile::TempDir;
@@ -28,7 +31,12 @@ fn sample_stage() -> Stage {
             parameters: HashMap::new(),
             agent_role: None,
             tool_requirements: Vec::new(),
+            resources: ResourceRequirements::default(),
+            sandbox: SandboxSpec::default(),
         }],
+        compensation: vec![],
+        cache: StageCacheControl::default(),
+        contract: StageContract::default(),
     }
 }
 
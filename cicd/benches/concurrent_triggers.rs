@@ -0,0 +1,105 @@
+//! Pipeline triggers from one thread and from eight at once, each trigger appending a
+//! signed entry to the pipeline event log, timed until the log is flushed. The
+//! `triggers_long_log` group starts every run from an event log that already holds
+//! `HISTORY` entries; appends no longer re-read the log for its tail hash, so its
+//! times should stay close to the `triggers` group instead of growing with the log.
+
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use noa_cicd::{CICDSystem, ConfigContext};
+use noa_workflow::{Namespace, PipelineInstrumentation};
+use serde_json::json;
+use tempfile::TempDir;
+
+const TRIGGERS: usize = 64;
+const THREADS: usize = 8;
+const HISTORY: usize = 5_000;
+const EVENT_LOGS: [&str; 2] = [
+    ".workspace/indexes/pipeline_events.log",
+    "storage/db/pipeline_events.log",
+];
+
+fn context(root: &Path) -> ConfigContext {
+    ConfigContext::isolated().with_workflow_root(root)
+}
+
+/// A workspace whose event log holds `HISTORY` entries.
+fn history() -> TempDir {
+    let workspace = tempfile::tempdir().expect("history workspace");
+    let instrumentation =
+        PipelineInstrumentation::with_context(&Namespace::default(), context(workspace.path()))
+            .expect("history instrumentation");
+    for run in 0..HISTORY {
+        instrumentation
+            .log_pipeline_event(
+                "bench",
+                "history",
+                "pipeline.triggered",
+                json!({"run": run}),
+            )
+            .expect("history event");
+    }
+    instrumentation.flush().expect("flush history");
+    workspace
+}
+
+fn system(history: Option<&Path>) -> (TempDir, CICDSystem) {
+    let workspace = tempfile::tempdir().expect("bench workspace");
+    if let Some(history) = history {
+        for log in EVENT_LOGS {
+            let target = workspace.path().join(log);
+            fs::create_dir_all(target.parent().unwrap()).expect("log directory");
+            fs::copy(history.join(log), target).expect("copy event log");
+        }
+    }
+    let system = CICDSystem::with_context(context(workspace.path()));
+    system.configure_workspace_root(workspace.path());
+    (workspace, system)
+}
+
+fn trigger(system: &CICDSystem, count: usize, thread: usize) {
+    for run in 0..count {
+        system
+            .trigger_pipeline(format!("bench-{thread}-{run}"), "abc123".to_string())
+            .expect("trigger pipeline");
+    }
+}
+
+fn run(system: &CICDSystem, threads: usize) {
+    thread::scope(|scope| {
+        for index in 0..threads {
+            scope.spawn(move || trigger(system, TRIGGERS / threads, index));
+        }
+    });
+    system.flush_events().expect("flush pipeline events");
+}
+
+fn bench_triggers(c: &mut Criterion) {
+    let seeded = history();
+    for (group, history) in [
+        ("triggers", None),
+        ("triggers_long_log", Some(seeded.path())),
+    ] {
+        let mut group = c.benchmark_group(group);
+        group.sample_size(10);
+        for (name, threads) in [("serial", 1), ("threads_8", THREADS)] {
+            group.bench_function(name, |b| {
+                b.iter_batched(
+                    || system(history),
+                    |(workspace, system)| {
+                        run(&system, threads);
+                        (workspace, system)
+                    },
+                    BatchSize::PerIteration,
+                );
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_triggers);
+criterion_main!(benches);
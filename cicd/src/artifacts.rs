@@ -0,0 +1,462 @@
+//! Content-addressed registry of pipeline build outputs.
+//!
+//! Attaching an artifact copies the file into `objects/<sha256>` (identical outputs
+//! are stored once) and appends an [`ArtifactRecord`] to the pipeline's index under
+//! `pipelines/<pipeline_id>.json`. Each record carries the evidence ledger signature
+//! written for it, so a deployment can be traced from its pipeline back to the exact
+//! bytes that were built.
+//!
+//! Each pipeline's artifacts also have a [`RetentionClass`], kept in `retention.json`.
+//! Pinned pipelines (promoted releases) are never swept, while interim and failed
+//! builds expire after the TTLs of a [`RetentionPolicy`]. [`ArtifactStore::sweep`]
+//! drops expired indexes and every object no remaining index refers to.
+
+use noa_core::storage::EvictionGuard;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Artifact store relative to the workspace root, scoped per namespace.
+pub const ARTIFACT_STORE_DIR: &str = "storage/db/artifacts";
+
+const OBJECTS_DIR: &str = "objects";
+const PIPELINES_DIR: &str = "pipelines";
+const RETENTION_FILE: &str = "retention.json";
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// How long a pipeline's artifacts are kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionClass {
+    /// Never swept or evicted, e.g. a build promoted to production.
+    Pinned,
+    /// Regular build, kept for the interim TTL.
+    #[default]
+    Interim,
+    /// Build whose pipeline failed, kept for the short failed TTL.
+    Failed,
+}
+
+/// TTLs of the classes that expire, counted from a pipeline's latest artifact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub interim_ttl_secs: u64,
+    pub failed_ttl_secs: u64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            interim_ttl_secs: 14 * DAY_SECS,
+            failed_ttl_secs: 2 * DAY_SECS,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Lifetime of `class`; `None` when it never expires.
+    pub fn ttl(&self, class: RetentionClass) -> Option<u64> {
+        match class {
+            RetentionClass::Pinned => None,
+            RetentionClass::Interim => Some(self.interim_ttl_secs),
+            RetentionClass::Failed => Some(self.failed_ttl_secs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Retention {
+    pub class: RetentionClass,
+    /// Why the class was assigned, e.g. the deployment that pinned it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+/// Outcome of [`ArtifactStore::sweep`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionSweep {
+    /// Pipelines whose artifact index expired, sorted.
+    pub expired_pipelines: Vec<String>,
+    pub removed_objects: usize,
+    pub freed_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactRecord {
+    pub pipeline_id: String,
+    /// Path the artifact was produced at, relative to the workspace when possible.
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+    /// Stage that produced the artifact.
+    pub stage: String,
+    pub recorded_at: u64,
+    /// Signature of the evidence ledger entry recording this artifact.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ledger_reference: Option<String>,
+}
+
+/// Result of re-hashing a recorded artifact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactVerification {
+    pub path: String,
+    pub sha256: String,
+    /// Whether the stored copy still hashes to the recorded digest.
+    pub stored_intact: bool,
+    /// Whether the file at the original path still matches, `None` when it is gone.
+    pub source_matches: Option<bool>,
+    /// Whether a correctly signed evidence ledger entry records the artifact, `None`
+    /// when the ledger was not consulted.
+    #[serde(default)]
+    pub ledger_verified: Option<bool>,
+}
+
+impl ArtifactVerification {
+    pub fn verified(&self) -> bool {
+        self.stored_intact
+            && self.source_matches != Some(false)
+            && self.ledger_verified != Some(false)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where the object with `sha256` is stored.
+    pub fn object_path(&self, sha256: &str) -> PathBuf {
+        let prefix = sha256.get(..2).unwrap_or(sha256);
+        self.root.join(OBJECTS_DIR).join(prefix).join(sha256)
+    }
+
+    /// Copy `source` into the object store and describe it; nothing is indexed yet.
+    pub fn store(
+        &self,
+        pipeline_id: &str,
+        stage: &str,
+        source: &Path,
+        path: &str,
+    ) -> Result<ArtifactRecord, String> {
+        let (sha256, size) = hash_file(source)?;
+        let object = self.object_path(&sha256);
+        if !object.exists() {
+            let parent = object.parent().expect("object path has a parent");
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("failed to create {}: {err}", parent.display()))?;
+            let tmp = object.with_extension("tmp");
+            fs::copy(source, &tmp)
+                .and_then(|_| fs::rename(&tmp, &object))
+                .map_err(|err| format!("failed to store artifact {path}: {err}"))?;
+        }
+        Ok(ArtifactRecord {
+            pipeline_id: pipeline_id.to_string(),
+            path: path.to_string(),
+            sha256,
+            size,
+            stage: stage.to_string(),
+            recorded_at: crate::unix_now(),
+            ledger_reference: None,
+        })
+    }
+
+    /// Add `record` to its pipeline's index, replacing an earlier record for the same path.
+    pub fn index(&self, record: &ArtifactRecord) -> Result<(), String> {
+        let mut records = self.list(&record.pipeline_id)?;
+        records.retain(|existing| existing.path != record.path);
+        records.push(record.clone());
+        records.sort_by(|a, b| a.path.cmp(&b.path));
+        let path = self.index_path(&record.pipeline_id);
+        let parent = path.parent().expect("index path has a parent");
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("failed to create {}: {err}", parent.display()))?;
+        let raw = serde_json::to_vec_pretty(&records)
+            .map_err(|err| format!("failed to encode artifact index: {err}"))?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, raw)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|err| format!("failed to write {}: {err}", path.display()))
+    }
+
+    /// Artifacts recorded for a pipeline, sorted by path.
+    pub fn list(&self, pipeline_id: &str) -> Result<Vec<ArtifactRecord>, String> {
+        let path = self.index_path(pipeline_id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let raw =
+            fs::read(&path).map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        serde_json::from_slice(&raw)
+            .map_err(|err| format!("failed to parse {}: {err}", path.display()))
+    }
+
+    /// Re-hash the stored copy of `record` and, when `source` exists, the original file.
+    pub fn verify(
+        &self,
+        record: &ArtifactRecord,
+        source: Option<&Path>,
+    ) -> Result<ArtifactVerification, String> {
+        let object = self.object_path(&record.sha256);
+        let stored_intact = object.exists() && hash_file(&object)?.0 == record.sha256;
+        let source_matches = match source.filter(|source| source.exists()) {
+            Some(source) => Some(hash_file(source)?.0 == record.sha256),
+            None => None,
+        };
+        Ok(ArtifactVerification {
+            path: record.path.clone(),
+            sha256: record.sha256.clone(),
+            stored_intact,
+            source_matches,
+            ledger_verified: None,
+        })
+    }
+
+    /// Retention of a pipeline's artifacts; [`RetentionClass::Interim`] unless set.
+    pub fn retention(&self, pipeline_id: &str) -> Result<Retention, String> {
+        Ok(self.retentions()?.remove(pipeline_id).unwrap_or_default())
+    }
+
+    /// Assign `class` to a pipeline's artifacts.
+    pub fn set_retention(
+        &self,
+        pipeline_id: &str,
+        class: RetentionClass,
+        reason: Option<String>,
+    ) -> Result<Retention, String> {
+        let retention = Retention {
+            class,
+            reason,
+            updated_at: crate::unix_now(),
+        };
+        let mut retentions = self.retentions()?;
+        retentions.insert(pipeline_id.to_string(), retention.clone());
+        self.write_retentions(&retentions)?;
+        Ok(retention)
+    }
+
+    /// Pipelines whose artifacts are pinned, sorted.
+    pub fn pinned_pipelines(&self) -> Result<Vec<String>, String> {
+        Ok(self
+            .retentions()?
+            .into_iter()
+            .filter(|(_, retention)| retention.class == RetentionClass::Pinned)
+            .map(|(pipeline_id, _)| pipeline_id)
+            .collect())
+    }
+
+    /// Remove the indexes of pipelines whose retention expired at `now`, then every object
+    /// no remaining index refers to.
+    pub fn sweep(&self, policy: &RetentionPolicy, now: u64) -> Result<RetentionSweep, String> {
+        let mut retentions = self.retentions()?;
+        let mut sweep = RetentionSweep::default();
+        let mut referenced = HashSet::new();
+        for (pipeline_id, records) in self.indexes()? {
+            let class = retentions
+                .get(&pipeline_id)
+                .map(|retention| retention.class)
+                .unwrap_or_default();
+            let latest = records.iter().map(|record| record.recorded_at).max();
+            let expired = match (policy.ttl(class), latest) {
+                (Some(ttl), Some(latest)) => latest.saturating_add(ttl) <= now,
+                _ => false,
+            };
+            if expired {
+                let path = self.index_path(&pipeline_id);
+                fs::remove_file(&path)
+                    .map_err(|err| format!("failed to remove {}: {err}", path.display()))?;
+                retentions.remove(&pipeline_id);
+                sweep.expired_pipelines.push(pipeline_id);
+            } else {
+                referenced.extend(records.into_iter().map(|record| record.sha256));
+            }
+        }
+        if !sweep.expired_pipelines.is_empty() {
+            self.write_retentions(&retentions)?;
+        }
+        for (object, size) in self.objects()? {
+            // In-flight copies (`<sha256>.tmp`) belong to a store that has not indexed yet.
+            if object.extension().is_some() {
+                continue;
+            }
+            let sha256 = object.file_name().and_then(|name| name.to_str());
+            if sha256.is_some_and(|sha256| referenced.contains(sha256)) {
+                continue;
+            }
+            fs::remove_file(&object)
+                .map_err(|err| format!("failed to remove {}: {err}", object.display()))?;
+            sweep.removed_objects += 1;
+            sweep.freed_bytes += size;
+        }
+        sweep.expired_pipelines.sort();
+        Ok(sweep)
+    }
+
+    /// Guard for the storage manager's eviction keeping the store's indexes and the
+    /// objects of pinned pipelines. Pinning later needs a fresh guard.
+    pub fn eviction_guard(&self) -> Result<EvictionGuard, String> {
+        let mut pinned = HashSet::new();
+        for pipeline_id in self.pinned_pipelines()? {
+            pinned.extend(
+                self.list(&pipeline_id)?
+                    .into_iter()
+                    .map(|record| self.object_path(&record.sha256)),
+            );
+        }
+        let objects = self.root.join(OBJECTS_DIR);
+        let root = self.root.clone();
+        Ok(Arc::new(move |path: &Path| {
+            path.starts_with(&root) && (!path.starts_with(&objects) || pinned.contains(path))
+        }))
+    }
+
+    fn retentions(&self) -> Result<BTreeMap<String, Retention>, String> {
+        let path = self.root.join(RETENTION_FILE);
+        if !path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let raw =
+            fs::read(&path).map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        serde_json::from_slice(&raw)
+            .map_err(|err| format!("failed to parse {}: {err}", path.display()))
+    }
+
+    fn write_retentions(&self, retentions: &BTreeMap<String, Retention>) -> Result<(), String> {
+        fs::create_dir_all(&self.root)
+            .map_err(|err| format!("failed to create {}: {err}", self.root.display()))?;
+        let path = self.root.join(RETENTION_FILE);
+        let raw = serde_json::to_vec_pretty(retentions)
+            .map_err(|err| format!("failed to encode artifact retention: {err}"))?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, raw)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|err| format!("failed to write {}: {err}", path.display()))
+    }
+
+    /// Every pipeline index, keyed by the pipeline id its records carry.
+    fn indexes(&self) -> Result<Vec<(String, Vec<ArtifactRecord>)>, String> {
+        let dir = self.root.join(PIPELINES_DIR);
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(format!("failed to read {}: {err}", dir.display())),
+        };
+        let mut indexes = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|err| format!("failed to read {}: {err}", dir.display()))?
+                .path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let raw = fs::read(&path)
+                .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+            let records: Vec<ArtifactRecord> = serde_json::from_slice(&raw)
+                .map_err(|err| format!("failed to parse {}: {err}", path.display()))?;
+            if let Some(pipeline_id) = records.first().map(|record| record.pipeline_id.clone()) {
+                indexes.push((pipeline_id, records));
+            }
+        }
+        Ok(indexes)
+    }
+
+    /// Every stored object and its size.
+    fn objects(&self) -> Result<Vec<(PathBuf, u64)>, String> {
+        let dir = self.root.join(OBJECTS_DIR);
+        let prefixes = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(format!("failed to read {}: {err}", dir.display())),
+        };
+        let mut objects = Vec::new();
+        for prefix in prefixes {
+            let prefix = prefix
+                .map_err(|err| format!("failed to read {}: {err}", dir.display()))?
+                .path();
+            let entries = fs::read_dir(&prefix)
+                .map_err(|err| format!("failed to read {}: {err}", prefix.display()))?;
+            for entry in entries {
+                let entry =
+                    entry.map_err(|err| format!("failed to read {}: {err}", prefix.display()))?;
+                let metadata = entry
+                    .metadata()
+                    .map_err(|err| format!("failed to read {}: {err}", entry.path().display()))?;
+                if metadata.is_file() {
+                    objects.push((entry.path(), metadata.len()));
+                }
+            }
+        }
+        Ok(objects)
+    }
+
+    fn index_path(&self, pipeline_id: &str) -> PathBuf {
+        self.root
+            .join(PIPELINES_DIR)
+            .join(format!("{}.json", pipeline_id.replace(['/', '\\'], "_")))
+    }
+}
+
+fn hash_file(path: &Path) -> Result<(String, u64), String> {
+    let mut file =
+        fs::File::open(path).map_err(|err| format!("failed to open {}: {err}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        size += read as u64;
+        hasher.update(&buffer[..read]);
+    }
+    Ok((format!("{:x}", hasher.finalize()), size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn identical_outputs_share_one_object_and_tampering_is_detected() {
+        let workspace = tempdir().unwrap();
+        let store = ArtifactStore::new(workspace.path().join(ARTIFACT_STORE_DIR));
+        let binary = workspace.path().join("noa");
+        fs::write(&binary, b"binary v1").unwrap();
+
+        let first = store.store("p1", "build", &binary, "noa").unwrap();
+        let second = store.store("p2", "build", &binary, "noa").unwrap();
+        assert_eq!(first.sha256, second.sha256);
+        assert_eq!(first.size, 9);
+        store.index(&first).unwrap();
+        store.index(&first).unwrap();
+        assert_eq!(store.list("p1").unwrap(), vec![first.clone()]);
+        assert!(store.list("p3").unwrap().is_empty());
+        assert!(store.verify(&first, Some(&binary)).unwrap().verified());
+
+        fs::write(&binary, b"binary v2").unwrap();
+        let check = store.verify(&first, Some(&binary)).unwrap();
+        assert!(check.stored_intact);
+        assert_eq!(check.source_matches, Some(false));
+
+        fs::write(store.object_path(&first.sha256), b"tampered").unwrap();
+        assert!(!store.verify(&first, None).unwrap().stored_intact);
+    }
+}
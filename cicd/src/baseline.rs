@@ -0,0 +1,275 @@
+//! Learned deployment health baselines.
+//!
+//! Every healthy deployment contributes its metrics to a rolling window kept per
+//! environment. The window's percentiles become the comparison baseline for the
+//! next deployment, and metrics that jump well outside the learned distribution
+//! are flagged as anomalies even when they pass the static thresholds.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Environment, HealthMetrics};
+
+/// Tuning for baseline learning and anomaly detection.
+#[derive(Debug, Clone)]
+pub struct BaselineConfig {
+    /// Number of healthy samples retained per environment.
+    pub window: usize,
+    /// Samples required before anomaly detection is applied.
+    pub min_samples: usize,
+    /// Standard deviations above the mean that count as a regression.
+    pub anomaly_z_score: f64,
+    /// Relative headroom above p95 tolerated before flagging a regression.
+    pub p95_tolerance: f64,
+}
+
+impl Default for BaselineConfig {
+    fn default() -> Self {
+        Self {
+            window: 50,
+            min_samples: 5,
+            anomaly_z_score: 3.0,
+            p95_tolerance: 0.1,
+        }
+    }
+}
+
+/// Percentile view of the learned window for one environment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearnedBaseline {
+    pub environment: Environment,
+    pub sample_count: usize,
+    pub p50: HealthMetrics,
+    pub p95: HealthMetrics,
+}
+
+/// A metric that regressed sharply against the learned baseline.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MetricAnomaly {
+    pub metric: String,
+    pub value: f64,
+    pub mean: f64,
+    pub p95: f64,
+    pub threshold: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PersistedBaselines {
+    environments: Vec<EnvironmentSamples>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EnvironmentSamples {
+    environment: Environment,
+    samples: Vec<HealthMetrics>,
+}
+
+type MetricAccessor = fn(&HealthMetrics) -> f64;
+
+const TRACKED_METRICS: [(&str, MetricAccessor); 4] = [
+    ("error_rate", |m| m.error_rate as f64),
+    ("response_time_ms", |m| m.response_time_ms as f64),
+    ("cpu_usage", |m| m.cpu_usage as f64),
+    ("memory_usage", |m| m.memory_usage as f64),
+];
+
+/// Rolling per-environment window of healthy deployment metrics.
+#[derive(Debug, Clone, Default)]
+pub struct HealthBaselines {
+    config: BaselineConfig,
+    samples: HashMap<Environment, VecDeque<HealthMetrics>>,
+}
+
+impl HealthBaselines {
+    pub fn new(config: BaselineConfig) -> Self {
+        Self {
+            config,
+            samples: HashMap::new(),
+        }
+    }
+
+    /// Load previously persisted samples, starting empty when the file is absent.
+    pub fn load(path: &Path, config: BaselineConfig) -> Result<Self, String> {
+        let mut baselines = Self::new(config);
+        if !path.exists() {
+            return Ok(baselines);
+        }
+        let raw = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read health baselines: {err}"))?;
+        if raw.trim().is_empty() {
+            return Ok(baselines);
+        }
+        let persisted: PersistedBaselines = serde_json::from_str(&raw)
+            .map_err(|err| format!("failed to parse health baselines: {err}"))?;
+        for entry in persisted.environments {
+            for sample in entry.samples {
+                baselines.record(&entry.environment, sample);
+            }
+        }
+        Ok(baselines)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut environments: Vec<EnvironmentSamples> = self
+            .samples
+            .iter()
+            .map(|(environment, samples)| EnvironmentSamples {
+                environment: environment.clone(),
+                samples: samples.iter().cloned().collect(),
+            })
+            .collect();
+        environments.sort_by_key(|entry| format!("{:?}", entry.environment));
+        let payload = serde_json::to_string_pretty(&PersistedBaselines { environments })
+            .map_err(|err| format!("failed to serialise health baselines: {err}"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("failed to create baseline directory: {err}"))?;
+        }
+        fs::write(path, payload).map_err(|err| format!("failed to persist health baselines: {err}"))
+    }
+
+    /// Add metrics from a healthy deployment, evicting the oldest sample when full.
+    pub fn record(&mut self, environment: &Environment, metrics: HealthMetrics) {
+        let window = self.config.window.max(1);
+        let samples = self.samples.entry(environment.clone()).or_default();
+        samples.push_back(metrics);
+        while samples.len() > window {
+            samples.pop_front();
+        }
+    }
+
+    pub fn sample_count(&self, environment: &Environment) -> usize {
+        self.samples.get(environment).map_or(0, VecDeque::len)
+    }
+
+    /// Percentiles of the learned window, if any samples exist.
+    pub fn baseline(&self, environment: &Environment) -> Option<LearnedBaseline> {
+        let samples = self.samples.get(environment).filter(|s| !s.is_empty())?;
+        Some(LearnedBaseline {
+            environment: environment.clone(),
+            sample_count: samples.len(),
+            p50: percentile_metrics(samples, 0.50),
+            p95: percentile_metrics(samples, 0.95),
+        })
+    }
+
+    /// Flag metrics that regress sharply against the learned distribution.
+    ///
+    /// Returns no anomalies until `min_samples` healthy deployments have been recorded.
+    pub fn detect_anomalies(
+        &self,
+        environment: &Environment,
+        metrics: &HealthMetrics,
+    ) -> Vec<MetricAnomaly> {
+        let Some(samples) = self.samples.get(environment) else {
+            return Vec::new();
+        };
+        if samples.len() < self.config.min_samples.max(1) {
+            return Vec::new();
+        }
+
+        TRACKED_METRICS
+            .iter()
+            .filter_map(|(name, accessor)| {
+                let values: Vec<f64> = samples.iter().map(accessor).collect();
+                let mean = values.iter().sum::<f64>() / values.len() as f64;
+                let variance =
+                    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+                let p95 = percentile(values.clone(), 0.95);
+                let threshold = (mean + self.config.anomaly_z_score * variance.sqrt())
+                    .max(p95 * (1.0 + self.config.p95_tolerance));
+                let value = accessor(metrics);
+                (value > threshold).then(|| MetricAnomaly {
+                    metric: name.to_string(),
+                    value,
+                    mean,
+                    p95,
+                    threshold,
+                })
+            })
+            .collect()
+    }
+}
+
+fn percentile(mut values: Vec<f64>, quantile: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let rank = ((values.len() - 1) as f64 * quantile).round() as usize;
+    values[rank.min(values.len() - 1)]
+}
+
+fn percentile_metrics(samples: &VecDeque<HealthMetrics>, quantile: f64) -> HealthMetrics {
+    let pick =
+        |accessor: MetricAccessor| percentile(samples.iter().map(accessor).collect(), quantile);
+    HealthMetrics {
+        error_rate: pick(TRACKED_METRICS[0].1) as f32,
+        response_time_ms: pick(TRACKED_METRICS[1].1).round() as u64,
+        cpu_usage: pick(TRACKED_METRICS[2].1) as f32,
+        memory_usage: pick(TRACKED_METRICS[3].1) as f32,
+        active_connections: percentile(
+            samples
+                .iter()
+                .map(|m| m.active_connections as f64)
+                .collect(),
+            quantile,
+        )
+        .round() as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn metrics(response_time_ms: u64, error_rate: f32) -> HealthMetrics {
+        HealthMetrics {
+            error_rate,
+            response_time_ms,
+            cpu_usage: 40.0,
+            memory_usage: 50.0,
+            active_connections: 10,
+        }
+    }
+
+    #[test]
+    fn percentiles_track_rolling_window() {
+        let mut baselines = HealthBaselines::new(BaselineConfig {
+            window: 4,
+            ..BaselineConfig::default()
+        });
+        for latency in [1000, 100, 110, 120, 130] {
+            baselines.record(&Environment::Staging, metrics(latency, 0.5));
+        }
+
+        let learned = baselines.baseline(&Environment::Staging).unwrap();
+        assert_eq!(learned.sample_count, 4);
+        assert_eq!(learned.p95.response_time_ms, 130);
+        assert!(baselines.baseline(&Environment::Production).is_none());
+    }
+
+    #[test]
+    fn flags_sudden_regressions_and_survives_reload() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("baselines.json");
+        let mut baselines = HealthBaselines::default();
+        for latency in [100, 105, 95, 102, 98, 101] {
+            baselines.record(&Environment::Production, metrics(latency, 0.2));
+        }
+        baselines.save(&path).unwrap();
+
+        let reloaded = HealthBaselines::load(&path, BaselineConfig::default()).unwrap();
+        assert_eq!(reloaded.sample_count(&Environment::Production), 6);
+        assert!(reloaded
+            .detect_anomalies(&Environment::Production, &metrics(104, 0.2))
+            .is_empty());
+        let anomalies = reloaded.detect_anomalies(&Environment::Production, &metrics(180, 0.2));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].metric, "response_time_ms");
+    }
+}
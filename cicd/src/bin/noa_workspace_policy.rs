@@ -0,0 +1,105 @@
+//! Workspace policy check for git hooks and local runs.
+//!
+//! Exits non-zero when violations remain, so `tools/git-hooks/pre-commit.sh` can
+//! block a commit. `--fix` applies the safe fixes and re-checks.
+
+use std::path::PathBuf;
+use std::process;
+
+use clap::Parser;
+use noa_cicd::workspace_policy::{
+    self, PolicyCandidate, PolicyFix, PolicyReport, WorkspacePolicy, WORKSPACE_POLICY_FILE,
+};
+
+#[derive(Debug, Parser)]
+#[command(about = "Check files against the workspace policy")]
+struct Args {
+    /// Workspace root.
+    #[arg(long, default_value = ".")]
+    root: PathBuf,
+    /// Policy file, relative to the root.
+    #[arg(long, default_value = WORKSPACE_POLICY_FILE)]
+    policy: PathBuf,
+    /// Check files staged for commit (the default).
+    #[arg(long, conflicts_with_all = ["base", "all"])]
+    staged: bool,
+    /// Check files changed since this git revision.
+    #[arg(long, conflicts_with = "all")]
+    base: Option<String>,
+    /// Check every tracked file.
+    #[arg(long)]
+    all: bool,
+    /// Apply safe fixes before reporting.
+    #[arg(long)]
+    fix: bool,
+    /// Print the report as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+fn main() {
+    match run(Args::parse()) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("error: {err}");
+            process::exit(2);
+        }
+    }
+}
+
+fn run(args: Args) -> Result<bool, String> {
+    let policy = WorkspacePolicy::load(&args.root.join(&args.policy))?;
+    let mut report = workspace_policy::check(&args.root, &policy, &candidates(&args)?);
+
+    if args.fix && !report.is_clean() {
+        for path in workspace_policy::apply_fixes(&args.root, &report)? {
+            eprintln!("fixed: {path}");
+        }
+        report = workspace_policy::check(&args.root, &policy, &candidates(&args)?);
+    }
+
+    if args.json {
+        let rendered = serde_json::to_string_pretty(&report)
+            .map_err(|err| format!("failed to render report: {err}"))?;
+        println!("{rendered}");
+    } else {
+        print_report(&report);
+    }
+    Ok(report.is_clean())
+}
+
+fn candidates(args: &Args) -> Result<Vec<PolicyCandidate>, String> {
+    if args.all {
+        workspace_policy::tracked_candidates(&args.root)
+    } else if let Some(base) = &args.base {
+        workspace_policy::changed_candidates(&args.root, base)
+    } else {
+        workspace_policy::staged_candidates(&args.root)
+    }
+}
+
+fn print_report(report: &PolicyReport) {
+    for violation in &report.violations {
+        eprintln!("❌ {}: {}", violation.path, violation.message);
+        match &violation.fix {
+            Some(PolicyFix::Untrack { ignore }) => {
+                eprintln!("   fix: git rm --cached and add '{ignore}' to .gitignore (--fix)")
+            }
+            Some(PolicyFix::PrependHeader { header }) => {
+                eprintln!("   fix: prepend '{header}' (--fix)")
+            }
+            Some(PolicyFix::Move { to }) => eprintln!("   suggestion: git mv to {to}"),
+            None => {}
+        }
+    }
+    if report.is_clean() {
+        eprintln!("✅ workspace policy: {} files checked", report.checked);
+    } else {
+        eprintln!(
+            "workspace policy: {} violations in {} files checked",
+            report.violations.len(),
+            report.checked
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Blue/green slots per environment.
+//!
+//! Every environment has a blue and a green slot, and at most one of them is active.
+//! A [`DeploymentStrategy::BlueGreen`](crate::DeploymentStrategy::BlueGreen) deployment
+//! lands in the standby slot. [`CICDSystem::switch_environment`] runs the health gate
+//! of [`CICDSystem::monitor_deployment`] on it and, if it passes, makes its slot the
+//! active one in a single step. The flip is recorded on the deployment and the slots
+//! are persisted with the rest of the pipeline state, so [`CICDSystem::rollback`] can
+//! flip back to the slot that was active before, even after a restart.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{unix_now, CICDSystem, DeploymentStrategy, Environment, PipelineStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Slot {
+    Blue,
+    Green,
+}
+
+impl Slot {
+    pub fn other(self) -> Slot {
+        match self {
+            Slot::Blue => Slot::Green,
+            Slot::Green => Slot::Blue,
+        }
+    }
+}
+
+/// The deployments in an environment's slots and which slot serves traffic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentSlots {
+    pub environment: Environment,
+    /// `None` until a deployment first switches the environment.
+    #[serde(default)]
+    pub active: Option<Slot>,
+    #[serde(default)]
+    pub blue: Option<String>,
+    #[serde(default)]
+    pub green: Option<String>,
+}
+
+impl EnvironmentSlots {
+    fn new(environment: Environment) -> Self {
+        Self {
+            environment,
+            active: None,
+            blue: None,
+            green: None,
+        }
+    }
+
+    /// The slot new deployments land in.
+    pub fn standby(&self) -> Slot {
+        self.active.map_or(Slot::Blue, Slot::other)
+    }
+
+    /// Id of the deployment in `slot`.
+    pub fn occupant(&self, slot: Slot) -> Option<&str> {
+        match slot {
+            Slot::Blue => self.blue.as_deref(),
+            Slot::Green => self.green.as_deref(),
+        }
+    }
+
+    fn occupant_mut(&mut self, slot: Slot) -> &mut Option<String> {
+        match slot {
+            Slot::Blue => &mut self.blue,
+            Slot::Green => &mut self.green,
+        }
+    }
+}
+
+/// A flip of the active slot made by a deployment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlotSwitch {
+    /// Slot active before the switch; `None` for an environment's first switch.
+    pub from: Option<Slot>,
+    pub to: Slot,
+    /// Deployment that was serving from `from`.
+    #[serde(default)]
+    pub previous_deployment: Option<String>,
+    pub switched_at: u64,
+}
+
+impl CICDSystem {
+    /// Slots of `environment`, or `None` before its first blue/green deployment.
+    pub fn environment_slots(&self, environment: &Environment) -> Option<EnvironmentSlots> {
+        self.slots
+            .lock()
+            .expect("slots lock poisoned")
+            .get(environment)
+            .cloned()
+    }
+
+    /// Put a new blue/green deployment into its environment's standby slot.
+    pub(crate) fn assign_standby_slot(&self, deployment_id: &str) -> Result<(), String> {
+        let environment = self.deployment(deployment_id)?.environment;
+        let (slot, active, replaced) = {
+            let mut slots = self.slots.lock().expect("slots lock poisoned");
+            let slots = slots
+                .entry(environment.clone())
+                .or_insert_with(|| EnvironmentSlots::new(environment.clone()));
+            let slot = slots.standby();
+            let replaced = slots.occupant_mut(slot).replace(deployment_id.to_string());
+            (slot, slots.active, replaced)
+        };
+        if let Some(deployment) = self.deployments.lock().unwrap().get_mut(deployment_id) {
+            deployment.slot = Some(slot);
+        }
+        self.persist_state()?;
+        self.emit_deployment_event(
+            deployment_id,
+            "deployment.slot_assigned",
+            json!({
+                "environment": environment,
+                "slot": slot,
+                "active": active,
+                "replaced_deployment": replaced,
+            }),
+        )
+    }
+
+    /// Run the health gate on a blue/green deployment waiting in its standby slot and,
+    /// when it passes, make that slot the active one. Returns whether it switched.
+    pub fn switch_environment(&self, deployment_id: &str) -> Result<bool, String> {
+        let deployment = self.deployment(deployment_id)?;
+        let slot = match (&deployment.strategy, deployment.slot, &deployment.switch) {
+            (DeploymentStrategy::BlueGreen, Some(slot), None)
+                if deployment.status == PipelineStatus::Running =>
+            {
+                slot
+            }
+            _ => {
+                return Err(format!(
+                    "Deployment {} is not a blue/green deployment waiting to switch",
+                    deployment_id
+                ))
+            }
+        };
+
+        if !self.monitor_deployment(deployment_id)? {
+            self.emit_deployment_event(
+                deployment_id,
+                "deployment.switch_blocked",
+                json!({
+                    "environment": deployment.environment,
+                    "slot": slot,
+                }),
+            )?;
+            return Ok(false);
+        }
+
+        let switch = {
+            let mut slots = self.slots.lock().expect("slots lock poisoned");
+            let slots = slots
+                .get_mut(&deployment.environment)
+                .filter(|slots| slots.occupant(slot) == Some(deployment_id))
+                .ok_or_else(|| {
+                    format!(
+                        "Deployment {} no longer holds the {:?} slot",
+                        deployment_id, slot
+                    )
+                })?;
+            let switch = SlotSwitch {
+                from: slots.active,
+                to: slot,
+                previous_deployment: slots
+                    .active
+                    .and_then(|active| slots.occupant(active))
+                    .map(str::to_string),
+                switched_at: unix_now(),
+            };
+            slots.active = Some(slot);
+            switch
+        };
+        if let Some(deployment) = self.deployments.lock().unwrap().get_mut(deployment_id) {
+            deployment.switch = Some(switch.clone());
+            deployment.status = PipelineStatus::Success;
+        }
+        self.persist_state()?;
+        self.emit_deployment_event(
+            deployment_id,
+            "deployment.slot_switched",
+            json!({
+                "environment": deployment.environment,
+                "from": switch.from,
+                "to": switch.to,
+                "previous_deployment": switch.previous_deployment,
+            }),
+        )?;
+        Ok(true)
+    }
+
+    /// Take a rolled back deployment out of its slot. If its switch is still in effect,
+    /// the slot it replaced becomes active again, but only while that slot still holds
+    /// the deployment it replaced; otherwise no slot is left active. The returned switch
+    /// names the slot and deployment made active again, with both `None` in that case.
+    pub(crate) fn release_slot(&self, deployment_id: &str) -> Result<Option<SlotSwitch>, String> {
+        let deployment = self.deployment(deployment_id)?;
+        let Some(slot) = deployment.slot else {
+            return Ok(None);
+        };
+        let mut slots = self.slots.lock().expect("slots lock poisoned");
+        let Some(slots) = slots.get_mut(&deployment.environment) else {
+            return Ok(None);
+        };
+        if slots.occupant(slot) != Some(deployment_id) {
+            return Ok(None);
+        }
+        match deployment.switch {
+            Some(mut switch) if slots.active == Some(slot) => {
+                let restorable = switch.from.is_some_and(|from| {
+                    slots.occupant(from) == switch.previous_deployment.as_deref()
+                });
+                if !restorable {
+                    switch.from = None;
+                    switch.previous_deployment = None;
+                }
+                slots.active = switch.from;
+                Ok(Some(switch))
+            }
+            _ => {
+                *slots.occupant_mut(slot) = None;
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HealthMetrics;
+    use serde_json::Value;
+
+    fn metrics(error_rate: f32) -> HealthMetrics {
+        HealthMetrics {
+            error_rate,
+            response_time_ms: 80,
+            cpu_usage: 30.0,
+            memory_usage: 40.0,
+            active_connections: 50,
+        }
+    }
+
+    fn deploy(system: &CICDSystem, version: &str, error_rate: f32) -> String {
+        let id = system
+            .deploy_to_environment(
+                version.to_string(),
+                Environment::Staging,
+                DeploymentStrategy::BlueGreen,
+            )
+            .unwrap();
+        system
+            .record_deployment_metrics(&id, metrics(error_rate))
+            .unwrap();
+        id
+    }
+
+    #[test]
+    fn switches_pass_the_health_gate_and_rollback_flips_back_after_restart() {
+        let workspace = tempfile::tempdir().unwrap();
+        let system = CICDSystem::with_context(crate::context_in(workspace.path()));
+        system.configure_workspace_root(workspace.path());
+
+        let first = deploy(&system, "v1", 0.5);
+        assert_eq!(system.deployment(&first).unwrap().slot, Some(Slot::Blue));
+        assert!(system.switch_environment(&first).unwrap());
+        assert!(system.switch_environment(&first).is_err());
+
+        let unhealthy = deploy(&system, "v2", 20.0);
+        assert_eq!(
+            system.deployment(&unhealthy).unwrap().slot,
+            Some(Slot::Green)
+        );
+        assert!(!system.switch_environment(&unhealthy).unwrap());
+        let slots = system.environment_slots(&Environment::Staging).unwrap();
+        assert_eq!(slots.active, Some(Slot::Blue));
+
+        let second = deploy(&system, "v3", 0.5);
+        assert!(system.switch_environment(&second).unwrap());
+        let switch = system.deployment(&second).unwrap().switch.unwrap();
+        assert_eq!(switch.from, Some(Slot::Blue));
+        assert_eq!(switch.previous_deployment.as_deref(), Some(first.as_str()));
+
+        let restarted = CICDSystem::with_context(crate::context_in(workspace.path()));
+        restarted.configure_workspace_root(workspace.path());
+        restarted
+            .reload_state(noa_core::recovery::RecoveryMode::Strict)
+            .unwrap();
+        let slots = restarted.environment_slots(&Environment::Staging).unwrap();
+        assert_eq!(slots.active, Some(Slot::Green));
+        assert_eq!(slots.occupant(Slot::Green), Some(second.as_str()));
+
+        restarted.rollback(&second).unwrap();
+        let slots = restarted.environment_slots(&Environment::Staging).unwrap();
+        assert_eq!(slots.active, Some(Slot::Blue));
+        assert_eq!(slots.occupant(Slot::Blue), Some(first.as_str()));
+
+        restarted.flush_events().unwrap();
+        let log = std::fs::read_to_string(
+            workspace
+                .path()
+                .join(".workspace/indexes/pipeline_events.log"),
+        )
+        .unwrap();
+        let scope = format!("deployment::{second}");
+        let restored = log
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .map(|entry| entry["event"].clone())
+            .find(|event| {
+                event["scope"] == scope.as_str()
+                    && event["event_type"] == "deployment.slot_restored"
+            })
+            .expect("slot restore recorded");
+        assert_eq!(restored["metadata"]["active"], "blue");
+    }
+
+    #[test]
+    fn rollback_leaves_no_slot_active_when_the_replaced_deployment_was_evicted() {
+        let workspace = tempfile::tempdir().unwrap();
+        let system = CICDSystem::with_context(crate::context_in(workspace.path()));
+        system.configure_workspace_root(workspace.path());
+
+        let first = deploy(&system, "v1", 0.5);
+        assert!(system.switch_environment(&first).unwrap());
+        let second = deploy(&system, "v2", 0.5);
+        assert!(system.switch_environment(&second).unwrap());
+        let unchecked = deploy(&system, "v3", 0.5);
+        assert_eq!(
+            system.deployment(&unchecked).unwrap().slot,
+            Some(Slot::Blue)
+        );
+
+        system.rollback(&second).unwrap();
+        let slots = system.environment_slots(&Environment::Staging).unwrap();
+        assert_eq!(slots.active, None);
+        assert_eq!(slots.occupant(Slot::Blue), Some(unchecked.as_str()));
+
+        system.flush_events().unwrap();
+        let log = std::fs::read_to_string(
+            workspace
+                .path()
+                .join(".workspace/indexes/pipeline_events.log"),
+        )
+        .unwrap();
+        let scope = format!("deployment::{second}");
+        let restored = log
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .map(|entry| entry["event"].clone())
+            .find(|event| {
+                event["scope"] == scope.as_str()
+                    && event["event_type"] == "deployment.slot_restored"
+            })
+            .expect("slot restore recorded");
+        assert_eq!(restored["metadata"]["active"], Value::Null);
+        assert_eq!(restored["metadata"]["active_deployment"], Value::Null);
+    }
+}
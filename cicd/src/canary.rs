@@ -0,0 +1,342 @@
+//! Canary deployments that shift traffic in steps.
+//!
+//! A deployment started with [`DeploymentStrategy::Canary`] takes the first share of
+//! traffic in its [`CanaryPlan`] (5% by default) instead of all of it. Each call to
+//! [`CICDSystem::advance_canary`] checks the deployment's latest [`HealthMetrics`] the
+//! way [`CICDSystem::monitor_deployment`] does: a healthy canary moves to the next
+//! share, or finishes once it serves all traffic, and an unhealthy one is rolled back.
+//! Moving traffic is left to a [`TrafficShifter`]; without one, the share is only
+//! recorded on the deployment and in its events.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::{CICDSystem, Deployment, HealthMetrics, PipelineStatus};
+
+/// Traffic shares a canary moves through when no plan is configured.
+pub const DEFAULT_CANARY_STEPS: [u8; 4] = [5, 25, 50, 100];
+
+/// Moves a share of a service's traffic onto a canary deployment.
+pub trait TrafficShifter: Send + Sync {
+    /// Route `percent` of the traffic of the deployment's service to `deployment`;
+    /// `0` takes the canary out of rotation.
+    fn shift(&self, deployment: &Deployment, percent: u8) -> Result<(), String>;
+}
+
+/// Traffic shares, in percent, that a canary serves one after another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanaryPlan {
+    steps: Vec<u8>,
+}
+
+impl CanaryPlan {
+    /// Steps must rise strictly, lie between 1 and 100, and end at 100.
+    pub fn new(steps: Vec<u8>) -> Result<Self, String> {
+        if steps.last() != Some(&100) {
+            return Err("canary steps must end at 100 percent".to_string());
+        }
+        if steps.first() == Some(&0) || steps.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(format!(
+                "canary steps must rise strictly from above 0 percent: {steps:?}"
+            ));
+        }
+        Ok(Self { steps })
+    }
+
+    pub fn steps(&self) -> &[u8] {
+        &self.steps
+    }
+}
+
+impl Default for CanaryPlan {
+    fn default() -> Self {
+        Self {
+            steps: DEFAULT_CANARY_STEPS.to_vec(),
+        }
+    }
+}
+
+/// Where a canary deployment stands in its plan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanaryProgress {
+    pub steps: Vec<u8>,
+    /// Index into `steps` of the share being served.
+    pub step: usize,
+    pub traffic_percent: u8,
+}
+
+/// Result of evaluating a canary at its current step.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CanaryOutcome {
+    /// Healthy; the canary now serves `traffic_percent`.
+    Advanced { traffic_percent: u8 },
+    /// Healthy at 100 percent; the deployment succeeded.
+    Completed,
+    /// Unhealthy while serving `traffic_percent`; the deployment was rolled back.
+    RolledBack { traffic_percent: u8 },
+}
+
+impl CICDSystem {
+    /// Traffic shares used by canary deployments started from now on.
+    pub fn configure_canary_plan(&self, plan: CanaryPlan) {
+        *self.canary_plan.lock().expect("canary plan lock poisoned") = plan;
+    }
+
+    /// Shift canary traffic through `shifter` instead of only recording it.
+    pub fn configure_traffic_shifter(&self, shifter: Arc<dyn TrafficShifter>) {
+        *self
+            .traffic_shifter
+            .lock()
+            .expect("traffic shifter lock poisoned") = Some(shifter);
+    }
+
+    fn traffic_shifter(&self) -> Option<Arc<dyn TrafficShifter>> {
+        self.traffic_shifter
+            .lock()
+            .expect("traffic shifter lock poisoned")
+            .clone()
+    }
+
+    /// Put a new canary deployment on the first step of the configured plan.
+    pub(crate) fn start_canary(&self, deployment_id: &str) -> Result<(), String> {
+        let steps = self
+            .canary_plan
+            .lock()
+            .expect("canary plan lock poisoned")
+            .steps()
+            .to_vec();
+        self.shift_canary(deployment_id, steps, 0)
+    }
+
+    /// Evaluate a canary's latest metrics, then move it to its next step, finish it,
+    /// or roll it back.
+    pub fn advance_canary(&self, deployment_id: &str) -> Result<CanaryOutcome, String> {
+        let progress = {
+            let deployments = self.deployments.lock().unwrap();
+            let deployment = deployments
+                .get(deployment_id)
+                .ok_or_else(|| format!("Deployment not found: {}", deployment_id))?;
+            match (&deployment.canary, &deployment.status) {
+                (Some(progress), PipelineStatus::Running) => progress.clone(),
+                (Some(_), status) => {
+                    return Err(format!(
+                        "Canary {} is no longer running: {:?}",
+                        deployment_id, status
+                    ))
+                }
+                (None, _) => return Err(format!("Deployment {} is not a canary", deployment_id)),
+            }
+        };
+
+        if !self.monitor_deployment(deployment_id)? {
+            self.emit_deployment_event(
+                deployment_id,
+                "deployment.canary_step_failed",
+                json!({
+                    "step": progress.step + 1,
+                    "traffic_percent": progress.traffic_percent,
+                }),
+            )?;
+            if let Some(shifter) = self.traffic_shifter() {
+                shifter.shift(&self.deployment(deployment_id)?, 0)?;
+            }
+            self.rollback(deployment_id)?;
+            return Ok(CanaryOutcome::RolledBack {
+                traffic_percent: progress.traffic_percent,
+            });
+        }
+
+        self.emit_deployment_event(
+            deployment_id,
+            "deployment.canary_step_passed",
+            json!({
+                "step": progress.step + 1,
+                "traffic_percent": progress.traffic_percent,
+            }),
+        )?;
+        let next = progress.step + 1;
+        if next < progress.steps.len() {
+            let traffic_percent = progress.steps[next];
+            self.shift_canary(deployment_id, progress.steps, next)?;
+            return Ok(CanaryOutcome::Advanced { traffic_percent });
+        }
+
+        if let Some(deployment) = self.deployments.lock().unwrap().get_mut(deployment_id) {
+            deployment.status = PipelineStatus::Success;
+        }
+        self.persist_state()?;
+        self.emit_deployment_event(
+            deployment_id,
+            "deployment.canary_completed",
+            json!({ "steps": progress.steps }),
+        )?;
+        Ok(CanaryOutcome::Completed)
+    }
+
+    /// Drive a canary to completion or rollback. Before each evaluation, `observe` is
+    /// given the share the canary serves and returns the metrics seen at that share.
+    pub fn run_canary<F>(
+        &self,
+        deployment_id: &str,
+        mut observe: F,
+    ) -> Result<CanaryOutcome, String>
+    where
+        F: FnMut(u8) -> Result<HealthMetrics, String>,
+    {
+        loop {
+            let traffic_percent = self
+                .deployment(deployment_id)?
+                .canary
+                .map(|progress| progress.traffic_percent)
+                .ok_or_else(|| format!("Deployment {} is not a canary", deployment_id))?;
+            self.record_deployment_metrics(deployment_id, observe(traffic_percent)?)?;
+            match self.advance_canary(deployment_id)? {
+                CanaryOutcome::Advanced { .. } => continue,
+                outcome => return Ok(outcome),
+            }
+        }
+    }
+
+    fn shift_canary(&self, deployment_id: &str, steps: Vec<u8>, step: usize) -> Result<(), String> {
+        let traffic_percent = steps[step];
+        let deployment = {
+            let mut deployments = self.deployments.lock().unwrap();
+            let deployment = deployments
+                .get_mut(deployment_id)
+                .ok_or_else(|| format!("Deployment not found: {}", deployment_id))?;
+            deployment.canary = Some(CanaryProgress {
+                steps: steps.clone(),
+                step,
+                traffic_percent,
+            });
+            deployment.clone()
+        };
+        if let Some(shifter) = self.traffic_shifter() {
+            shifter.shift(&deployment, traffic_percent)?;
+        }
+        self.persist_state()?;
+        self.emit_deployment_event(
+            deployment_id,
+            "deployment.canary_step",
+            json!({
+                "step": step + 1,
+                "steps": steps.len(),
+                "traffic_percent": traffic_percent,
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeploymentStrategy, Environment};
+    use serde_json::Value;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingShifter {
+        shifts: Mutex<Vec<u8>>,
+    }
+
+    impl TrafficShifter for RecordingShifter {
+        fn shift(&self, _deployment: &Deployment, percent: u8) -> Result<(), String> {
+            self.shifts.lock().unwrap().push(percent);
+            Ok(())
+        }
+    }
+
+    fn metrics(error_rate: f32) -> HealthMetrics {
+        HealthMetrics {
+            error_rate,
+            response_time_ms: 80,
+            cpu_usage: 30.0,
+            memory_usage: 40.0,
+            active_connections: 50,
+        }
+    }
+
+    fn canary(system: &CICDSystem) -> String {
+        system
+            .deploy_to_environment(
+                "v2".to_string(),
+                Environment::Production,
+                DeploymentStrategy::Canary,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn plans_must_rise_to_full_traffic() {
+        assert_eq!(CanaryPlan::default().steps(), DEFAULT_CANARY_STEPS);
+        assert!(CanaryPlan::new(vec![10, 100]).is_ok());
+        assert!(CanaryPlan::new(vec![10, 50]).is_err());
+        assert!(CanaryPlan::new(vec![50, 25, 100]).is_err());
+        assert!(CanaryPlan::new(vec![0, 100]).is_err());
+        assert!(CanaryPlan::new(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn healthy_canary_walks_every_step_and_unhealthy_one_rolls_back() {
+        let workspace = tempfile::tempdir().unwrap();
+        let system = CICDSystem::with_context(crate::context_in(workspace.path()));
+        system.configure_workspace_root(workspace.path());
+        let shifter = Arc::new(RecordingShifter::default());
+        system.configure_traffic_shifter(shifter.clone());
+
+        let healthy = canary(&system);
+        let outcome = system.run_canary(&healthy, |_| Ok(metrics(0.5))).unwrap();
+        assert_eq!(outcome, CanaryOutcome::Completed);
+        assert_eq!(*shifter.shifts.lock().unwrap(), DEFAULT_CANARY_STEPS);
+        let deployment = system.deployment(&healthy).unwrap();
+        assert_eq!(deployment.status, PipelineStatus::Success);
+        assert!(system.advance_canary(&healthy).is_err());
+
+        shifter.shifts.lock().unwrap().clear();
+        system.configure_canary_plan(CanaryPlan::new(vec![10, 50, 100]).unwrap());
+        let failing = canary(&system);
+        let outcome = system
+            .run_canary(&failing, |percent| {
+                Ok(metrics(if percent >= 50 { 20.0 } else { 0.5 }))
+            })
+            .unwrap();
+        assert_eq!(
+            outcome,
+            CanaryOutcome::RolledBack {
+                traffic_percent: 50
+            }
+        );
+        assert_eq!(*shifter.shifts.lock().unwrap(), [10, 50, 0]);
+        assert_eq!(
+            system.deployment(&failing).unwrap().status,
+            PipelineStatus::RolledBack
+        );
+
+        system.flush_events().unwrap();
+        let log = std::fs::read_to_string(
+            workspace
+                .path()
+                .join(".workspace/indexes/pipeline_events.log"),
+        )
+        .unwrap();
+        let scope = format!("deployment::{failing}");
+        let events: Vec<String> = log
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .map(|entry| entry["event"].clone())
+            .filter(|event| event["scope"] == scope.as_str())
+            .filter_map(|event| event["event_type"].as_str().map(str::to_string))
+            .filter(|event_type| event_type.starts_with("deployment.canary"))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                "deployment.canary_step",
+                "deployment.canary_step_passed",
+                "deployment.canary_step",
+                "deployment.canary_step_failed",
+            ]
+        );
+    }
+}
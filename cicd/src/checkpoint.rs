@@ -0,0 +1,163 @@
+//! Stage checkpoints for long-running Build and Test stages.
+//!
+//! Each completed unit of a stage (a build target or a test suite) is recorded with
+//! a hash of the commit and the unit's artifact. When a pipeline is rerun after a
+//! crash, units whose recorded hash still matches are skipped and the stage resumes
+//! from the first unit that had not completed. Checkpoints are stored next to the
+//! pipeline state and cleared once the pipeline succeeds.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Checkpoint directory relative to the workspace root, scoped per namespace.
+pub const PIPELINE_CHECKPOINT_DIR: &str = "storage/db/pipelines/checkpoints";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CompletedUnit {
+    pub unit: String,
+    pub hash: String,
+    pub completed_at: u64,
+}
+
+/// Units of one stage that finished during an earlier attempt.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StageCheckpoint {
+    pub pipeline_id: String,
+    pub stage: String,
+    pub commit_sha: String,
+    pub units: Vec<CompletedUnit>,
+    pub updated_at: u64,
+}
+
+impl StageCheckpoint {
+    /// Whether `unit` already completed against the same inputs.
+    pub fn is_complete(&self, unit: &str, hash: &str) -> bool {
+        self.units
+            .iter()
+            .any(|completed| completed.unit == unit && completed.hash == hash)
+    }
+}
+
+/// File-backed checkpoints, one JSON file per pipeline stage.
+#[derive(Debug, Clone)]
+pub struct CheckpointStore {
+    root: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn load(&self, pipeline_id: &str, stage: &str) -> Result<Option<StageCheckpoint>, String> {
+        let path = self.path(pipeline_id, stage);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read stage checkpoint: {err}"))?;
+        serde_json::from_str(&raw)
+            .map(Some)
+            .map_err(|err| format!("failed to parse stage checkpoint: {err}"))
+    }
+
+    /// Record a completed unit. A checkpoint left by a different commit is discarded.
+    pub fn record(
+        &self,
+        pipeline_id: &str,
+        stage: &str,
+        commit_sha: &str,
+        unit: &str,
+        hash: &str,
+    ) -> Result<StageCheckpoint, String> {
+        let now = now_secs();
+        let mut checkpoint = self
+            .load(pipeline_id, stage)?
+            .filter(|checkpoint| checkpoint.commit_sha == commit_sha)
+            .unwrap_or_else(|| StageCheckpoint {
+                pipeline_id: pipeline_id.to_string(),
+                stage: stage.to_string(),
+                commit_sha: commit_sha.to_string(),
+                units: Vec::new(),
+                updated_at: now,
+            });
+        checkpoint.units.retain(|completed| completed.unit != unit);
+        checkpoint.units.push(CompletedUnit {
+            unit: unit.to_string(),
+            hash: hash.to_string(),
+            completed_at: now,
+        });
+        checkpoint.updated_at = now;
+
+        let path = self.path(pipeline_id, stage);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("failed to create checkpoint directory: {err}"))?;
+        }
+        let payload = serde_json::to_string_pretty(&checkpoint)
+            .map_err(|err| format!("failed to serialise stage checkpoint: {err}"))?;
+        // Write then rename so a crash mid-write never leaves a truncated checkpoint.
+        let staging = path.with_extension("json.tmp");
+        fs::write(&staging, payload)
+            .and_then(|_| fs::rename(&staging, &path))
+            .map_err(|err| format!("failed to persist stage checkpoint: {err}"))?;
+        Ok(checkpoint)
+    }
+
+    /// Drop every checkpoint recorded for a pipeline.
+    pub fn clear(&self, pipeline_id: &str) -> Result<(), String> {
+        let dir = self.root.join(file_name(pipeline_id));
+        if dir.exists() {
+            fs::remove_dir_all(&dir)
+                .map_err(|err| format!("failed to clear stage checkpoints: {err}"))?;
+        }
+        Ok(())
+    }
+
+    fn path(&self, pipeline_id: &str, stage: &str) -> PathBuf {
+        self.root
+            .join(file_name(pipeline_id))
+            .join(format!("{}.json", file_name(stage)))
+    }
+}
+
+/// Hash identifying a unit's inputs: the commit, the unit name, and the contents of
+/// its artifact when one is declared. A rebuilt artifact invalidates the checkpoint.
+pub fn unit_hash(commit_sha: &str, unit: &str, artifact: Option<&Path>) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+    hasher.update(commit_sha.as_bytes());
+    hasher.update([0]);
+    hasher.update(unit.as_bytes());
+    if let Some(artifact) = artifact {
+        if artifact.exists() {
+            let bytes = fs::read(artifact)
+                .map_err(|err| format!("failed to hash artifact {}: {err}", artifact.display()))?;
+            hasher.update([0]);
+            hasher.update(&bytes);
+        }
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn file_name(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,443 @@
+//! Regression report between two pipelines.
+//!
+//! [`PipelineComparison::between`] lines up what two pipelines recorded, usually
+//! builds of two commits: stage durations, test outcomes and coverage from the
+//! Test stage, the Syft package inventory (SBOM), binary footprints from the
+//! Build stage, and security scan findings. The report serialises to JSON for the
+//! API and renders as markdown for the docs-refresh pipeline.
+//!
+//! Test stages record results when given a `results` parameter pointing at libtest
+//! JSON output (`cargo test -- -Z unstable-options --format json`) and, optionally,
+//! a `coverage` parameter pointing at a `cargo llvm-cov --json --summary-only` export.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::footprint::{FootprintComparison, DEFAULT_REGRESSION_PERCENT};
+use crate::Pipeline;
+
+/// Scan tool whose findings list the packages in the workspace.
+const SBOM_TOOL: &str = "syft";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+/// Test outcomes and line coverage recorded by a Test stage.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TestReport {
+    pub outcomes: BTreeMap<String, TestOutcome>,
+    /// Percentage of lines covered, when a coverage summary was supplied.
+    pub coverage_percent: Option<f64>,
+}
+
+impl TestReport {
+    /// Read the `test` events of libtest JSON output, ignoring other lines.
+    pub fn from_libtest_json(raw: &str) -> Self {
+        let mut report = Self::default();
+        for line in raw.lines() {
+            let Ok(event) = serde_json::from_str::<Value>(line) else {
+                continue;
+            };
+            if event.get("type").and_then(Value::as_str) != Some("test") {
+                continue;
+            }
+            let Some(name) = event.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let outcome = match event.get("event").and_then(Value::as_str) {
+                Some("ok") => TestOutcome::Passed,
+                Some("failed") | Some("timeout") => TestOutcome::Failed,
+                Some("ignored") => TestOutcome::Ignored,
+                _ => continue,
+            };
+            report.outcomes.insert(name.to_string(), outcome);
+        }
+        report
+    }
+
+    /// Line coverage from a `cargo llvm-cov --json --summary-only` export.
+    pub fn coverage_from_llvm_cov(raw: &str) -> Result<f64, String> {
+        let document: Value =
+            serde_json::from_str(raw).map_err(|err| format!("invalid coverage summary: {err}"))?;
+        document
+            .pointer("/data/0/totals/lines/percent")
+            .and_then(Value::as_f64)
+            .ok_or_else(|| "coverage summary has no data[0].totals.lines.percent".to_string())
+    }
+
+    pub fn count(&self, outcome: TestOutcome) -> usize {
+        self.outcomes
+            .values()
+            .filter(|recorded| **recorded == outcome)
+            .count()
+    }
+}
+
+/// Identity of one side of a comparison.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PipelineSummary {
+    pub id: String,
+    pub name: String,
+    pub commit_sha: String,
+}
+
+impl From<&Pipeline> for PipelineSummary {
+    fn from(pipeline: &Pipeline) -> Self {
+        Self {
+            id: pipeline.id.clone(),
+            name: pipeline.name.clone(),
+            commit_sha: pipeline.commit_sha.clone(),
+        }
+    }
+}
+
+/// Duration of a stage in both pipelines; `None` where it did not run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StageDurationDelta {
+    pub stage: String,
+    pub base_ms: Option<u64>,
+    pub head_ms: Option<u64>,
+}
+
+impl StageDurationDelta {
+    pub fn delta_ms(&self) -> Option<i64> {
+        Some(self.head_ms? as i64 - self.base_ms? as i64)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TestDiff {
+    pub base_passed: usize,
+    pub base_failed: usize,
+    pub head_passed: usize,
+    pub head_failed: usize,
+    /// Tests that failed in head but not in base.
+    pub newly_failing: Vec<String>,
+    /// Tests that failed in base and pass in head.
+    pub newly_passing: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CoverageChange {
+    pub base_percent: Option<f64>,
+    pub head_percent: Option<f64>,
+}
+
+impl CoverageChange {
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.head_percent? - self.base_percent?)
+    }
+}
+
+/// Packages reported by the Syft scan of each pipeline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SbomDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SecurityFinding {
+    pub tool: String,
+    pub issue: String,
+}
+
+/// Everything that changed between a base and a head pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PipelineComparison {
+    pub base: PipelineSummary,
+    pub head: PipelineSummary,
+    pub stages: Vec<StageDurationDelta>,
+    /// `None` unless both pipelines recorded test results.
+    pub tests: Option<TestDiff>,
+    pub coverage: CoverageChange,
+    pub sbom: SbomDiff,
+    /// `None` unless both pipelines measured their build footprint.
+    pub footprint: Option<FootprintComparison>,
+    pub new_findings: Vec<SecurityFinding>,
+    pub resolved_findings: Vec<SecurityFinding>,
+}
+
+impl PipelineComparison {
+    pub fn between(base: &Pipeline, head: &Pipeline) -> Self {
+        let mut stages: Vec<StageDurationDelta> = base
+            .stages
+            .iter()
+            .map(|stage| StageDurationDelta {
+                stage: stage.name.clone(),
+                base_ms: stage.duration_ms,
+                head_ms: head
+                    .stages
+                    .iter()
+                    .find(|candidate| candidate.name == stage.name)
+                    .and_then(|candidate| candidate.duration_ms),
+            })
+            .collect();
+        stages.extend(
+            head.stages
+                .iter()
+                .filter(|stage| !base.stages.iter().any(|b| b.name == stage.name))
+                .map(|stage| StageDurationDelta {
+                    stage: stage.name.clone(),
+                    base_ms: None,
+                    head_ms: stage.duration_ms,
+                }),
+        );
+
+        let tests = match (&base.tests, &head.tests) {
+            (Some(base), Some(head)) => Some(diff_tests(base, head)),
+            _ => None,
+        };
+        let coverage = CoverageChange {
+            base_percent: base.tests.as_ref().and_then(|tests| tests.coverage_percent),
+            head_percent: head.tests.as_ref().and_then(|tests| tests.coverage_percent),
+        };
+
+        let base_packages = findings(base, |tool| tool == SBOM_TOOL);
+        let head_packages = findings(head, |tool| tool == SBOM_TOOL);
+        let sbom = SbomDiff {
+            added: issues(head_packages.difference(&base_packages)),
+            removed: issues(base_packages.difference(&head_packages)),
+        };
+
+        let base_findings = findings(base, |tool| tool != SBOM_TOOL);
+        let head_findings = findings(head, |tool| tool != SBOM_TOOL);
+
+        let footprint = match (&base.footprint, &head.footprint) {
+            (Some(before), Some(after)) => Some(FootprintComparison::between(
+                Some((base.id.as_str(), &before.footprint)),
+                &after.footprint,
+                DEFAULT_REGRESSION_PERCENT,
+            )),
+            _ => None,
+        };
+
+        Self {
+            base: base.into(),
+            head: head.into(),
+            stages,
+            tests,
+            coverage,
+            sbom,
+            footprint,
+            new_findings: head_findings.difference(&base_findings).cloned().collect(),
+            resolved_findings: base_findings.difference(&head_findings).cloned().collect(),
+        }
+    }
+
+    /// Whether head fails tests base passed, loses coverage, grows a binary past the
+    /// footprint threshold, or has new security findings.
+    pub fn has_regressions(&self) -> bool {
+        self.tests
+            .as_ref()
+            .is_some_and(|tests| !tests.newly_failing.is_empty())
+            || self.coverage.delta().is_some_and(|delta| delta < 0.0)
+            || self
+                .footprint
+                .as_ref()
+                .is_some_and(|footprint| footprint.regressions().next().is_some())
+            || !self.new_findings.is_empty()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Pipeline comparison\n\nBase `{}` ({}) → head `{}` ({}).\n\n",
+            self.base.id, self.base.commit_sha, self.head.id, self.head.commit_sha
+        );
+
+        out.push_str("## Stage durations\n\n| Stage | Base (ms) | Head (ms) | Change (ms) |\n|---|---:|---:|---:|\n");
+        for stage in &self.stages {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                stage.stage,
+                optional(stage.base_ms),
+                optional(stage.head_ms),
+                stage
+                    .delta_ms()
+                    .map(|delta| format!("{delta:+}"))
+                    .unwrap_or_else(|| "-".to_string())
+            ));
+        }
+
+        out.push_str("\n## Tests\n\n");
+        match &self.tests {
+            Some(tests) => {
+                out.push_str(&format!(
+                    "Passed {} → {}, failed {} → {}.\n",
+                    tests.base_passed, tests.head_passed, tests.base_failed, tests.head_failed
+                ));
+                list(&mut out, "Newly failing", &tests.newly_failing);
+                list(&mut out, "Newly passing", &tests.newly_passing);
+                list(&mut out, "Added", &tests.added);
+                list(&mut out, "Removed", &tests.removed);
+            }
+            None => out.push_str("Test results were not recorded for both pipelines.\n"),
+        }
+        match (
+            self.coverage.base_percent,
+            self.coverage.head_percent,
+            self.coverage.delta(),
+        ) {
+            (Some(base), Some(head), Some(delta)) => out.push_str(&format!(
+                "\nLine coverage {base:.1}% → {head:.1}% ({delta:+.1} points).\n"
+            )),
+            _ => out.push_str("\nCoverage was not recorded for both pipelines.\n"),
+        }
+
+        out.push_str("\n## SBOM\n\n");
+        if self.sbom.added.is_empty() && self.sbom.removed.is_empty() {
+            out.push_str("No package changes.\n");
+        }
+        list(&mut out, "Added", &self.sbom.added);
+        list(&mut out, "Removed", &self.sbom.removed);
+
+        out.push_str("\n## Binary footprint\n\n");
+        match &self.footprint {
+            Some(footprint) if !footprint.deltas.is_empty() => {
+                out.push_str("| Binary | Size (bytes) | Change |\n|---|---:|---:|\n");
+                for delta in &footprint.deltas {
+                    if delta.metric != crate::footprint::FootprintMetric::SizeBytes {
+                        continue;
+                    }
+                    let flag = if delta.regression { " ⚠" } else { "" };
+                    out.push_str(&format!(
+                        "| {} | {} → {} | {:+.1}%{} |\n",
+                        delta.binary, delta.previous, delta.current, delta.change_percent, flag
+                    ));
+                }
+            }
+            Some(_) => out.push_str("No binaries in common.\n"),
+            None => out.push_str("Footprint was not measured for both pipelines.\n"),
+        }
+
+        out.push_str("\n## Security findings\n\n");
+        if self.new_findings.is_empty() {
+            out.push_str("No new findings.\n");
+        }
+        for (label, findings) in [
+            ("New", &self.new_findings),
+            ("Resolved", &self.resolved_findings),
+        ] {
+            let rendered: Vec<String> = findings
+                .iter()
+                .map(|finding| format!("{}: {}", finding.tool, finding.issue))
+                .collect();
+            list(&mut out, label, &rendered);
+        }
+        out
+    }
+
+    pub fn write(&self, dir: &Path) -> Result<(PathBuf, PathBuf), String> {
+        fs::create_dir_all(dir).map_err(|err| format!("failed to create report dir: {err}"))?;
+        let json_path = dir.join("comparison.json");
+        let markdown_path = dir.join("comparison.md");
+        let payload = serde_json::to_string_pretty(self)
+            .map_err(|err| format!("failed to serialise comparison: {err}"))?;
+        fs::write(&json_path, payload)
+            .and_then(|_| fs::write(&markdown_path, self.to_markdown()))
+            .map_err(|err| format!("failed to write comparison: {err}"))?;
+        Ok((json_path, markdown_path))
+    }
+}
+
+fn diff_tests(base: &TestReport, head: &TestReport) -> TestDiff {
+    let failed =
+        |report: &TestReport, name: &str| report.outcomes.get(name) == Some(&TestOutcome::Failed);
+    TestDiff {
+        base_passed: base.count(TestOutcome::Passed),
+        base_failed: base.count(TestOutcome::Failed),
+        head_passed: head.count(TestOutcome::Passed),
+        head_failed: head.count(TestOutcome::Failed),
+        newly_failing: head
+            .outcomes
+            .iter()
+            .filter(|(name, outcome)| **outcome == TestOutcome::Failed && !failed(base, name))
+            .map(|(name, _)| name.clone())
+            .collect(),
+        newly_passing: head
+            .outcomes
+            .iter()
+            .filter(|(name, outcome)| **outcome == TestOutcome::Passed && failed(base, name))
+            .map(|(name, _)| name.clone())
+            .collect(),
+        added: head
+            .outcomes
+            .keys()
+            .filter(|name| !base.outcomes.contains_key(*name))
+            .cloned()
+            .collect(),
+        removed: base
+            .outcomes
+            .keys()
+            .filter(|name| !head.outcomes.contains_key(*name))
+            .cloned()
+            .collect(),
+    }
+}
+
+fn findings(pipeline: &Pipeline, tool: impl Fn(&str) -> bool) -> BTreeSet<SecurityFinding> {
+    pipeline
+        .security_scans
+        .iter()
+        .filter(|scan| tool(&scan.tool))
+        .flat_map(|scan| {
+            scan.issues.iter().map(|issue| SecurityFinding {
+                tool: scan.tool.clone(),
+                issue: issue.clone(),
+            })
+        })
+        .collect()
+}
+
+fn issues<'a>(findings: impl Iterator<Item = &'a SecurityFinding>) -> Vec<String> {
+    findings.map(|finding| finding.issue.clone()).collect()
+}
+
+fn optional(value: Option<u64>) -> String {
+    value
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn list(out: &mut String, label: &str, items: &[String]) {
+    if !items.is_empty() {
+        out.push_str(&format!("\n{label}: {}\n", items.join(", ")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_libtest_events_and_llvm_cov_totals() {
+        let report = TestReport::from_libtest_json(concat!(
+            "{ \"type\": \"suite\", \"event\": \"started\", \"test_count\": 3 }\n",
+            "{ \"type\": \"test\", \"event\": \"started\", \"name\": \"a\" }\n",
+            "{ \"type\": \"test\", \"name\": \"a\", \"event\": \"ok\" }\n",
+            "{ \"type\": \"test\", \"name\": \"b\", \"event\": \"failed\", \"stdout\": \"boom\" }\n",
+            "{ \"type\": \"test\", \"name\": \"c\", \"event\": \"ignored\" }\n",
+            "running 3 tests\n",
+        ));
+        assert_eq!(report.count(TestOutcome::Passed), 1);
+        assert_eq!(report.outcomes["b"], TestOutcome::Failed);
+        assert_eq!(report.outcomes["c"], TestOutcome::Ignored);
+
+        let coverage = TestReport::coverage_from_llvm_cov(
+            r#"{"data":[{"totals":{"lines":{"count":200,"covered":150,"percent":75.0}}}]}"#,
+        );
+        assert_eq!(coverage, Ok(75.0));
+        assert!(TestReport::coverage_from_llvm_cov("{}").is_err());
+    }
+}
@@ -0,0 +1,506 @@
+//! Structured summary of the change a pipeline builds.
+//!
+//! [`DiffSummary::generate`] reads the diff between two commits from git — the files
+//! touched with their line counts and changed hunks — and resolves the hunks against
+//! the workspace symbol graph to find the symbols they edit and the symbols calling
+//! them. Changed documentation is listed separately so the docs-refresh agent knows
+//! which pages were already edited by hand. The summary is attached to the pipeline,
+//! its paths and symbols pick the owners whose approval the pipeline needs, and it
+//! renders as a one-line headline for the pipeline's `diff_summary` and as markdown
+//! for reviewers.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use noa_symbol_graph::SymbolGraph;
+use serde::{Deserialize, Serialize};
+
+use crate::workspace_policy::git;
+
+/// Extensions of files treated as documentation wherever they live.
+const DOC_EXTENSIONS: [&str; 4] = ["md", "mdx", "rst", "adoc"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Copied,
+    TypeChanged,
+}
+
+impl ChangeKind {
+    fn from_status(status: &str) -> Self {
+        match status.chars().next() {
+            Some('A') => Self::Added,
+            Some('D') => Self::Deleted,
+            Some('R') => Self::Renamed,
+            Some('C') => Self::Copied,
+            Some('T') => Self::TypeChanged,
+            _ => Self::Modified,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Modified => "modified",
+            Self::Deleted => "deleted",
+            Self::Renamed => "renamed",
+            Self::Copied => "copied",
+            Self::TypeChanged => "type changed",
+        }
+    }
+}
+
+/// One file in the diff.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    /// Source of a rename or copy.
+    #[serde(default)]
+    pub previous_path: Option<String>,
+    pub kind: ChangeKind,
+    /// Lines added and removed; `None` for binary files.
+    pub insertions: Option<u64>,
+    pub deletions: Option<u64>,
+    /// Inclusive line ranges of the head version touched by the change. A hunk that
+    /// only removes lines is recorded as the line it was removed after.
+    #[serde(default)]
+    pub hunks: Vec<(usize, usize)>,
+}
+
+/// A symbol whose definition the diff touches.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SymbolImpact {
+    pub stable_id: String,
+    pub name: String,
+    pub kind: String,
+    pub file: String,
+    /// Stable ids of the symbols with an edge to this one, such as its callers.
+    #[serde(default)]
+    pub dependents: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DiffSummary {
+    pub base: String,
+    pub head: String,
+    pub files: Vec<FileChange>,
+    pub symbols: Vec<SymbolImpact>,
+    /// Documentation files changed by the diff.
+    pub docs: Vec<String>,
+}
+
+impl DiffSummary {
+    /// Summarise `base..head` in the repository at `root`, resolving symbols against
+    /// `graph` when the workspace has been indexed.
+    pub fn generate(
+        root: &Path,
+        base: &str,
+        head: &str,
+        graph: Option<&SymbolGraph>,
+    ) -> Result<Self, String> {
+        let range = format!("{base}..{head}");
+        let mut files =
+            parse_name_status(&git(root, &["diff", "--name-status", "-M", "-z", &range])?);
+        let counts = parse_numstat(&git(root, &["diff", "--numstat", "-M", "-z", &range])?);
+        let hunks = parse_hunks(&git(
+            root,
+            &["diff", "-U0", "-M", "--no-prefix", "--no-color", &range],
+        )?);
+        for file in &mut files {
+            if let Some((_, insertions, deletions)) =
+                counts.iter().find(|(path, _, _)| path == &file.path)
+            {
+                file.insertions = *insertions;
+                file.deletions = *deletions;
+            }
+            if let Some((_, ranges)) = hunks.iter().find(|(path, _)| path == &file.path) {
+                file.hunks = ranges.clone();
+            }
+        }
+        Ok(Self::from_changes(base, head, files, graph))
+    }
+
+    /// Build a summary from already parsed file changes.
+    pub fn from_changes(
+        base: &str,
+        head: &str,
+        files: Vec<FileChange>,
+        graph: Option<&SymbolGraph>,
+    ) -> Self {
+        let docs = files
+            .iter()
+            .filter(|file| is_doc(&file.path))
+            .map(|file| file.path.clone())
+            .collect();
+        let symbols = graph
+            .map(|graph| impacted_symbols(graph, &files))
+            .unwrap_or_default();
+        Self {
+            base: base.to_string(),
+            head: head.to_string(),
+            files,
+            symbols,
+            docs,
+        }
+    }
+
+    pub fn insertions(&self) -> u64 {
+        self.files.iter().filter_map(|file| file.insertions).sum()
+    }
+
+    pub fn deletions(&self) -> u64 {
+        self.files.iter().filter_map(|file| file.deletions).sum()
+    }
+
+    /// Paths and symbol stable ids the change touches, as accepted by
+    /// [`crate::CICDSystem::require_owner_approvals`].
+    pub fn touched(&self) -> Vec<String> {
+        let mut touched: Vec<String> = self.files.iter().map(|file| file.path.clone()).collect();
+        touched.extend(self.symbols.iter().map(|symbol| symbol.stable_id.clone()));
+        touched
+    }
+
+    /// One line suitable for the pipeline's `diff_summary`.
+    pub fn headline(&self) -> String {
+        let mut line = format!(
+            "{} file{} changed (+{} -{}) between {} and {}",
+            self.files.len(),
+            plural(self.files.len()),
+            self.insertions(),
+            self.deletions(),
+            short(&self.base),
+            short(&self.head)
+        );
+        if !self.symbols.is_empty() {
+            line.push_str(&format!(
+                "; {} symbol{} impacted",
+                self.symbols.len(),
+                plural(self.symbols.len())
+            ));
+        }
+        if !self.docs.is_empty() {
+            line.push_str(&format!("; docs changed: {}", self.docs.join(", ")));
+        }
+        line
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Diff summary\n\n`{}` → `{}`: {}.\n\n",
+            self.base,
+            self.head,
+            self.headline()
+        );
+
+        out.push_str("## Files\n\n");
+        if self.files.is_empty() {
+            out.push_str("No files changed.\n");
+        } else {
+            out.push_str("| File | Change | Lines |\n|---|---|---:|\n");
+            for file in &self.files {
+                let change = match &file.previous_path {
+                    Some(previous) => format!("{} from `{previous}`", file.kind.label()),
+                    None => file.kind.label().to_string(),
+                };
+                let lines = match (file.insertions, file.deletions) {
+                    (Some(insertions), Some(deletions)) => format!("+{insertions} -{deletions}"),
+                    _ => "binary".to_string(),
+                };
+                out.push_str(&format!("| {} | {change} | {lines} |\n", file.path));
+            }
+        }
+
+        out.push_str("\n## Impacted symbols\n\n");
+        if self.symbols.is_empty() {
+            out.push_str("No indexed symbols touched.\n");
+        }
+        for symbol in &self.symbols {
+            out.push_str(&format!(
+                "- `{}` ({} in {})",
+                symbol.name, symbol.kind, symbol.file
+            ));
+            if !symbol.dependents.is_empty() {
+                out.push_str(&format!(", {} dependent(s)", symbol.dependents.len()));
+            }
+            out.push('\n');
+        }
+
+        out.push_str("\n## Documentation\n\n");
+        if self.docs.is_empty() {
+            out.push_str("No documentation changed.\n");
+        }
+        for doc in &self.docs {
+            out.push_str(&format!("- {doc}\n"));
+        }
+        out
+    }
+
+    pub fn write(&self, dir: &Path) -> Result<(PathBuf, PathBuf), String> {
+        fs::create_dir_all(dir).map_err(|err| format!("failed to create report dir: {err}"))?;
+        let json_path = dir.join("diff_summary.json");
+        let markdown_path = dir.join("diff_summary.md");
+        let payload = serde_json::to_string_pretty(self)
+            .map_err(|err| format!("failed to serialise diff summary: {err}"))?;
+        fs::write(&json_path, payload)
+            .and_then(|_| fs::write(&markdown_path, self.to_markdown()))
+            .map_err(|err| format!("failed to write diff summary: {err}"))?;
+        Ok((json_path, markdown_path))
+    }
+}
+
+pub fn is_doc(path: &str) -> bool {
+    let path = Path::new(path);
+    path.starts_with("docs")
+        || path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| DOC_EXTENSIONS.contains(&ext))
+}
+
+/// Symbols defined in the changed files whose span overlaps a hunk. Every symbol of
+/// an added or deleted file counts, as does every symbol of a file whose hunks were
+/// not recorded.
+fn impacted_symbols(graph: &SymbolGraph, files: &[FileChange]) -> Vec<SymbolImpact> {
+    let mut impacted = Vec::new();
+    for file in files {
+        let whole_file =
+            matches!(file.kind, ChangeKind::Added | ChangeKind::Deleted) || file.hunks.is_empty();
+        for node in graph.nodes.values() {
+            if !Path::new(&node.file).ends_with(&file.path) {
+                continue;
+            }
+            let touched = whole_file
+                || file
+                    .hunks
+                    .iter()
+                    .any(|(start, end)| *start <= node.span.1 && node.span.0 <= *end);
+            if !touched {
+                continue;
+            }
+            let dependents: BTreeSet<String> = graph
+                .edges
+                .iter()
+                .filter(|edge| edge.to == node.stable_id && edge.from != node.stable_id)
+                .map(|edge| edge.from.clone())
+                .collect();
+            impacted.push(SymbolImpact {
+                stable_id: node.stable_id.clone(),
+                name: node.name.clone(),
+                kind: node.kind.clone(),
+                file: file.path.clone(),
+                dependents: dependents.into_iter().collect(),
+            });
+        }
+    }
+    impacted
+}
+
+/// Parse `git diff --name-status -z`, where renames and copies list the source first.
+fn parse_name_status(output: &str) -> Vec<FileChange> {
+    let mut fields = output.split('\0').filter(|field| !field.is_empty());
+    let mut files = Vec::new();
+    while let Some(status) = fields.next() {
+        let kind = ChangeKind::from_status(status);
+        let previous_path = if matches!(kind, ChangeKind::Renamed | ChangeKind::Copied) {
+            fields.next().map(str::to_string)
+        } else {
+            None
+        };
+        let Some(path) = fields.next() else {
+            break;
+        };
+        files.push(FileChange {
+            path: path.to_string(),
+            previous_path,
+            kind,
+            insertions: None,
+            deletions: None,
+            hunks: Vec::new(),
+        });
+    }
+    files
+}
+
+/// Parse `git diff --numstat -z` into `(path, insertions, deletions)`. Binary files
+/// report `-` counts; renames leave the path empty and follow it with both names.
+fn parse_numstat(output: &str) -> Vec<(String, Option<u64>, Option<u64>)> {
+    let mut fields = output.split('\0');
+    let mut counts = Vec::new();
+    while let Some(record) = fields.next() {
+        let mut columns = record.splitn(3, '\t');
+        let (Some(insertions), Some(deletions), Some(path)) =
+            (columns.next(), columns.next(), columns.next())
+        else {
+            continue;
+        };
+        let path = if path.is_empty() {
+            fields.next();
+            match fields.next() {
+                Some(destination) => destination.to_string(),
+                None => break,
+            }
+        } else {
+            path.to_string()
+        };
+        counts.push((path, insertions.parse().ok(), deletions.parse().ok()));
+    }
+    counts
+}
+
+/// Parse the head-side ranges of `git diff -U0 --no-prefix` hunks per file.
+fn parse_hunks(output: &str) -> Vec<(String, Vec<(usize, usize)>)> {
+    let mut files: Vec<(String, Vec<(usize, usize)>)> = Vec::new();
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            files.push((path.trim_end().to_string(), Vec::new()));
+            continue;
+        }
+        let Some(header) = line.strip_prefix("@@ ") else {
+            continue;
+        };
+        let Some(added) = header
+            .split_whitespace()
+            .find_map(|part| part.strip_prefix('+'))
+        else {
+            continue;
+        };
+        let (start, count) = match added.split_once(',') {
+            Some((start, count)) => (start.parse().ok(), count.parse().ok()),
+            None => (added.parse().ok(), Some(1)),
+        };
+        let (Some(start), Some(count)) = (start, count) else {
+            continue;
+        };
+        let start: usize = start;
+        let range = if count == 0 {
+            (start.max(1), start.max(1))
+        } else {
+            (start, start + count - 1)
+        };
+        if let Some((_, ranges)) = files.last_mut() {
+            ranges.push(range);
+        }
+    }
+    files
+}
+
+fn short(commit: &str) -> &str {
+    commit.get(..12).unwrap_or(commit)
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noa_symbol_graph::{SymbolEdge, SymbolNode};
+    use std::process::Command;
+
+    fn run_git(root: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-c", "user.name=ci", "-c", "user.email=ci@example.com"])
+            .args(args)
+            .current_dir(root)
+            .output()
+            .expect("git should run");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn node(id: &str, name: &str, file: &str, span: (usize, usize)) -> SymbolNode {
+        SymbolNode {
+            stable_id: id.to_string(),
+            language: "rust".to_string(),
+            name: name.to_string(),
+            kind: "function".to_string(),
+            file: file.to_string(),
+            signature: format!("fn {name}()"),
+            span,
+        }
+    }
+
+    #[test]
+    fn generate_resolves_hunks_to_symbols_and_docs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        run_git(root, &["init", "-q"]);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("src/lib.rs"),
+            "fn first() {\n    1;\n}\n\nfn second() {\n    2;\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("old.txt"), "moved\ncontent\nhere\n").unwrap();
+        run_git(root, &["add", "-A"]);
+        run_git(root, &["commit", "-q", "-m", "base"]);
+        let base = run_git(root, &["rev-parse", "HEAD"]);
+
+        fs::write(
+            root.join("src/lib.rs"),
+            "fn first() {\n    1;\n}\n\nfn second() {\n    22;\n}\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs/guide.txt"), "new page\n").unwrap();
+        fs::write(root.join("logo.bin"), [0u8, 1, 2, 0]).unwrap();
+        run_git(root, &["mv", "old.txt", "new.txt"]);
+        run_git(root, &["add", "-A"]);
+        run_git(root, &["commit", "-q", "-m", "head"]);
+        let head = run_git(root, &["rev-parse", "HEAD"]);
+
+        let mut graph = SymbolGraph::default();
+        for symbol in [
+            node("first", "first", "src/lib.rs", (1, 3)),
+            node("second", "second", "src/lib.rs", (5, 7)),
+            node("caller", "caller", "src/main.rs", (1, 4)),
+        ] {
+            graph.nodes.insert(symbol.stable_id.clone(), symbol);
+        }
+        graph.edges.push(SymbolEdge {
+            from: "caller".to_string(),
+            to: "second".to_string(),
+            kind: "calls".to_string(),
+        });
+
+        let summary = DiffSummary::generate(root, &base, &head, Some(&graph)).unwrap();
+        let files: Vec<(&str, ChangeKind)> = summary
+            .files
+            .iter()
+            .map(|file| (file.path.as_str(), file.kind))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("docs/guide.txt", ChangeKind::Added),
+                ("logo.bin", ChangeKind::Added),
+                ("new.txt", ChangeKind::Renamed),
+                ("src/lib.rs", ChangeKind::Modified),
+            ]
+        );
+        assert_eq!(summary.files[1].insertions, None);
+        assert_eq!(summary.files[2].previous_path.as_deref(), Some("old.txt"));
+        let lib = &summary.files[3];
+        assert_eq!((lib.insertions, lib.deletions), (Some(1), Some(1)));
+        assert_eq!(lib.hunks, [(6, 6)]);
+
+        assert_eq!(summary.symbols.len(), 1);
+        assert_eq!(summary.symbols[0].stable_id, "second");
+        assert_eq!(summary.symbols[0].dependents, ["caller"]);
+        assert_eq!(summary.docs, ["docs/guide.txt"]);
+        assert!(summary.touched().contains(&"second".to_string()));
+        assert!(summary.headline().starts_with("4 files changed (+2 -1)"));
+        assert!(summary.to_markdown().contains("renamed from `old.txt`"));
+    }
+}
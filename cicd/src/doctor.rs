@@ -0,0 +1,619 @@
+//! Workspace health checks with automated remediation.
+//!
+//! [`Doctor::diagnose`] looks for the broken states a crash or a hand edit leaves
+//! behind: a pipeline state document that no longer parses or was written by a newer
+//! release, an evidence ledger without its genesis entry, a symbol graph store with
+//! corrupt lines, and temporary and lock files abandoned by writers that are gone.
+//! [`Doctor::remediate`] runs the same checks and fixes each finding. State documents
+//! are moved aside before they are rewritten; abandoned temporary and lock files are
+//! deleted.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use noa_core::recovery::RecoveryMode;
+use noa_symbol_graph::{SymbolGraph, DEFAULT_STORE_DIR};
+use noa_workflow::{
+    read_evidence_ledger, ConfigContext, EvidenceLedgerKind, Namespace, PipelineInstrumentation,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{PersistedState, PIPELINE_STATE_FILE, PIPELINE_STATE_VERSION};
+
+const EVIDENCE_LEDGER_FILE: &str = "storage/db/evidence/ledger.jsonl";
+
+/// Directories, relative to the workspace root, scanned for temporary and lock files.
+const SCANNED_DIRS: [&str; 2] = [".workspace", "storage"];
+
+/// Temporary and lock files modified more recently than this are assumed to belong
+/// to a writer that is still running.
+pub const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DoctorCheck {
+    /// `state.json` parses and every pipeline and deployment record in it loads.
+    PipelineState,
+    /// `state.json` was written by a release this one can read.
+    StateVersion,
+    /// No staging files or temporary directories were left behind.
+    TempFiles,
+    /// No lock file outlived the process that took it.
+    StaleLocks,
+    /// The evidence ledger starts with its genesis entry.
+    LedgerGenesis,
+    /// Every line of the symbol graph store parses.
+    SymbolGraph,
+}
+
+impl DoctorCheck {
+    pub const ALL: [DoctorCheck; 6] = [
+        DoctorCheck::StateVersion,
+        DoctorCheck::PipelineState,
+        DoctorCheck::LedgerGenesis,
+        DoctorCheck::SymbolGraph,
+        DoctorCheck::TempFiles,
+        DoctorCheck::StaleLocks,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DoctorCheck::PipelineState => "pipeline_state",
+            DoctorCheck::StateVersion => "state_version",
+            DoctorCheck::TempFiles => "temp_files",
+            DoctorCheck::StaleLocks => "stale_locks",
+            DoctorCheck::LedgerGenesis => "ledger_genesis",
+            DoctorCheck::SymbolGraph => "symbol_graph",
+        }
+    }
+}
+
+/// A broken state found by a check, and what remediation does about it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoctorFinding {
+    pub check: DoctorCheck,
+    pub path: PathBuf,
+    pub problem: String,
+    pub remediation: String,
+    /// Whether the remediation was applied.
+    pub remediated: bool,
+    /// Why the remediation failed, when it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoctorReport {
+    pub root: PathBuf,
+    pub checks: Vec<DoctorCheck>,
+    pub findings: Vec<DoctorFinding>,
+}
+
+impl DoctorReport {
+    /// Whether every finding was remediated, which holds trivially when there were none.
+    pub fn is_healthy(&self) -> bool {
+        self.findings.iter().all(|finding| finding.remediated)
+    }
+
+    /// Findings that are still broken.
+    pub fn unresolved(&self) -> impl Iterator<Item = &DoctorFinding> {
+        self.findings.iter().filter(|finding| !finding.remediated)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Workspace doctor\n\nWorkspace `{}`.\n\n",
+            self.root.display()
+        );
+        out.push_str("| Check | Findings | Remediated |\n|---|---:|---:|\n");
+        for check in &self.checks {
+            let findings: Vec<&DoctorFinding> = self
+                .findings
+                .iter()
+                .filter(|finding| finding.check == *check)
+                .collect();
+            let remediated = findings.iter().filter(|finding| finding.remediated).count();
+            out.push_str(&format!(
+                "| {} | {} | {} |\n",
+                check.as_str(),
+                findings.len(),
+                remediated
+            ));
+        }
+        if !self.findings.is_empty() {
+            out.push_str("\n## Findings\n\n");
+        }
+        for finding in &self.findings {
+            let outcome = match (&finding.error, finding.remediated) {
+                (Some(error), _) => format!("remediation failed: {error}"),
+                (None, true) => "remediated".to_string(),
+                (None, false) => "not remediated".to_string(),
+            };
+            out.push_str(&format!(
+                "- `{}` ({}): {}. Remediation: {} ({}).\n",
+                finding.path.display(),
+                finding.check.as_str(),
+                finding.problem,
+                finding.remediation,
+                outcome
+            ));
+        }
+        out
+    }
+}
+
+/// How a finding is fixed.
+#[derive(Debug)]
+enum Remedy {
+    /// Move the state aside and rewrite it with the records that still load.
+    SalvageState,
+    /// Move the state aside under its version for the release that wrote it.
+    ArchiveState {
+        version: u32,
+    },
+    RemoveFile,
+    RemoveDir,
+    RestoreGenesis,
+    RepairSymbolGraph,
+}
+
+#[derive(Debug)]
+struct Diagnosis {
+    finding: DoctorFinding,
+    remedy: Remedy,
+}
+
+impl Diagnosis {
+    fn new(
+        check: DoctorCheck,
+        path: &Path,
+        problem: impl Into<String>,
+        remediation: &str,
+        remedy: Remedy,
+    ) -> Self {
+        Self {
+            finding: DoctorFinding {
+                check,
+                path: path.to_path_buf(),
+                problem: problem.into(),
+                remediation: remediation.to_string(),
+                remediated: false,
+                error: None,
+            },
+            remedy,
+        }
+    }
+}
+
+/// Health checks over the workspace state of one namespace.
+#[derive(Debug, Clone)]
+pub struct Doctor {
+    root: PathBuf,
+    namespace: Namespace,
+    stale_after: Duration,
+}
+
+impl Doctor {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            namespace: Namespace::default(),
+            stale_after: DEFAULT_STALE_AFTER,
+        }
+    }
+
+    /// Check the pipeline state and evidence ledger of `namespace` instead of the
+    /// default one. Temporary and lock files are scanned across all namespaces.
+    pub fn with_namespace(mut self, namespace: Namespace) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    /// Age after which temporary and lock files count as abandoned.
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Run every check without changing the workspace.
+    pub fn diagnose(&self) -> DoctorReport {
+        self.report(self.run_checks())
+    }
+
+    /// Run every check and apply the remediation of each finding.
+    pub fn remediate(&self) -> DoctorReport {
+        let diagnoses = self
+            .run_checks()
+            .into_iter()
+            .map(|mut diagnosis| {
+                match self.apply(&diagnosis) {
+                    Ok(()) => diagnosis.finding.remediated = true,
+                    Err(err) => diagnosis.finding.error = Some(err),
+                }
+                diagnosis
+            })
+            .collect();
+        self.report(diagnoses)
+    }
+
+    fn report(&self, diagnoses: Vec<Diagnosis>) -> DoctorReport {
+        DoctorReport {
+            root: self.root.clone(),
+            checks: DoctorCheck::ALL.to_vec(),
+            findings: diagnoses
+                .into_iter()
+                .map(|diagnosis| diagnosis.finding)
+                .collect(),
+        }
+    }
+
+    fn run_checks(&self) -> Vec<Diagnosis> {
+        let mut diagnoses = Vec::new();
+        diagnoses.extend(self.check_pipeline_state());
+        diagnoses.extend(self.check_ledger_genesis());
+        diagnoses.extend(self.check_symbol_graph());
+        for dir in SCANNED_DIRS {
+            self.scan_leftovers(&self.root.join(dir), &mut diagnoses);
+        }
+        diagnoses
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.root
+            .join(self.namespace.scope_path(PIPELINE_STATE_FILE))
+    }
+
+    fn ledger_path(&self) -> PathBuf {
+        self.root
+            .join(self.namespace.scope_path(EVIDENCE_LEDGER_FILE))
+    }
+
+    fn check_pipeline_state(&self) -> Option<Diagnosis> {
+        let path = self.state_path();
+        let raw = fs::read_to_string(&path).ok()?;
+        if raw.trim().is_empty() {
+            return None;
+        }
+        let salvage = |problem: String| {
+            Some(Diagnosis::new(
+                DoctorCheck::PipelineState,
+                &path,
+                problem,
+                "move the file aside and rewrite it with the records that still load",
+                Remedy::SalvageState,
+            ))
+        };
+        let document: serde_json::Value = match serde_json::from_str(&raw) {
+            Ok(document) => document,
+            Err(err) => return salvage(format!("not valid JSON: {err}")),
+        };
+        if !document.is_object() {
+            return salvage("not a JSON object".to_string());
+        }
+        let version = match PersistedState::version(&document) {
+            Ok(version) => version,
+            Err(err) => return salvage(err),
+        };
+        if version > PIPELINE_STATE_VERSION {
+            return Some(Diagnosis::new(
+                DoctorCheck::StateVersion,
+                &path,
+                format!(
+                    "written with state version {version}; this release reads up to {PIPELINE_STATE_VERSION}"
+                ),
+                "move the file aside under its version so a newer release can restore it",
+                Remedy::ArchiveState { version },
+            ));
+        }
+        match PersistedState::parse(&raw, RecoveryMode::Lenient) {
+            Ok((_, skipped)) if skipped.is_empty() => None,
+            Ok((_, skipped)) => salvage(format!(
+                "{} corrupt record(s): {}",
+                skipped.len(),
+                skipped
+                    .iter()
+                    .map(|skip| skip.location.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            Err(err) => salvage(err),
+        }
+    }
+
+    fn check_ledger_genesis(&self) -> Option<Diagnosis> {
+        let path = self.ledger_path();
+        if !path.exists() {
+            return None;
+        }
+        let problem = match read_evidence_ledger(&path, RecoveryMode::Lenient) {
+            Ok(ledger) => match ledger.records.first() {
+                Some(entry) if entry.kind == EvidenceLedgerKind::Genesis => return None,
+                Some(_) => "first entry is not the genesis entry".to_string(),
+                None => "ledger has no entries".to_string(),
+            },
+            Err(err) => format!("unreadable: {err}"),
+        };
+        Some(Diagnosis::new(
+            DoctorCheck::LedgerGenesis,
+            &path,
+            problem,
+            "prepend a signed genesis entry",
+            Remedy::RestoreGenesis,
+        ))
+    }
+
+    fn check_symbol_graph(&self) -> Option<Diagnosis> {
+        let store = self.root.join(DEFAULT_STORE_DIR);
+        if !store.is_dir() {
+            return None;
+        }
+        let problem = match SymbolGraph::load_with_recovery(&store, RecoveryMode::Lenient) {
+            Ok((_, skipped)) if skipped.is_empty() => return None,
+            Ok((_, skipped)) => format!(
+                "{} corrupt line(s): {}",
+                skipped.len(),
+                skipped
+                    .iter()
+                    .map(|skip| skip.location.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Err(err) => format!("unreadable: {err}"),
+        };
+        Some(Diagnosis::new(
+            DoctorCheck::SymbolGraph,
+            &store,
+            problem,
+            "drop the corrupt lines and re-index on the next journal update",
+            Remedy::RepairSymbolGraph,
+        ))
+    }
+
+    /// Walk `dir` for abandoned temporary files and directories and stale lock files.
+    fn scan_leftovers(&self, dir: &Path, diagnoses: &mut Vec<Diagnosis>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let stale = self.is_stale(&path);
+            if file_type.is_dir() {
+                if is_temp_name(&name) && stale {
+                    diagnoses.push(Diagnosis::new(
+                        DoctorCheck::TempFiles,
+                        &path,
+                        "orphaned temporary directory",
+                        "delete the directory",
+                        Remedy::RemoveDir,
+                    ));
+                } else {
+                    self.scan_leftovers(&path, diagnoses);
+                }
+            } else if file_type.is_file() && stale {
+                if is_temp_name(&name) {
+                    diagnoses.push(Diagnosis::new(
+                        DoctorCheck::TempFiles,
+                        &path,
+                        "orphaned temporary file",
+                        "delete the file",
+                        Remedy::RemoveFile,
+                    ));
+                } else if let Some(problem) = stale_lock(&path, &name) {
+                    diagnoses.push(Diagnosis::new(
+                        DoctorCheck::StaleLocks,
+                        &path,
+                        problem,
+                        "delete the lock file",
+                        Remedy::RemoveFile,
+                    ));
+                }
+            }
+        }
+    }
+
+    fn is_stale(&self, path: &Path) -> bool {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age >= self.stale_after)
+    }
+
+    fn apply(&self, diagnosis: &Diagnosis) -> Result<(), String> {
+        let path = &diagnosis.finding.path;
+        match diagnosis.remedy {
+            Remedy::SalvageState => self.salvage_state(path),
+            Remedy::ArchiveState { version } => {
+                let archived = sibling(path, &format!("v{version}"));
+                fs::rename(path, &archived)
+                    .map_err(|err| format!("failed to move {}: {err}", path.display()))
+            }
+            Remedy::RemoveFile => fs::remove_file(path).map_err(|err| err.to_string()),
+            Remedy::RemoveDir => fs::remove_dir_all(path).map_err(|err| err.to_string()),
+            Remedy::RestoreGenesis => {
+                let context = ConfigContext::isolated().with_workflow_root(&self.root);
+                PipelineInstrumentation::with_context(&self.namespace, context)
+                    .and_then(|instrumentation| instrumentation.restore_evidence_genesis())
+                    .map(|_| ())
+                    .map_err(|err| err.to_string())
+            }
+            Remedy::RepairSymbolGraph => SymbolGraph::repair(path)
+                .map(|_| ())
+                .map_err(|err| err.to_string()),
+        }
+    }
+
+    fn salvage_state(&self, path: &Path) -> Result<(), String> {
+        let raw = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let backup = sibling(path, &format!("corrupt-{}", unix_now()));
+        fs::rename(path, &backup)
+            .map_err(|err| format!("failed to move {}: {err}", path.display()))?;
+        let Ok(serde_json::Value::Object(mut document)) = serde_json::from_str(&raw) else {
+            // Nothing is recoverable; the next run starts from an empty state.
+            return Ok(());
+        };
+        document.remove("version");
+        let (mut state, _) = PersistedState::parse(
+            &serde_json::Value::Object(document).to_string(),
+            RecoveryMode::Lenient,
+        )?;
+        state.version = PIPELINE_STATE_VERSION;
+        let payload = serde_json::to_string_pretty(&state).map_err(|err| err.to_string())?;
+        fs::write(path, payload).map_err(|err| err.to_string())
+    }
+}
+
+/// Names used for staging files and scratch directories by the workspace writers
+/// and the `tempfile` crate.
+fn is_temp_name(name: &str) -> bool {
+    name.starts_with(".tmp")
+        || [".tmp", ".rewrite", ".partial"]
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+}
+
+/// A `.lock` file is stale when it is empty or names a process that is no longer
+/// running. Lock files with other contents are left alone.
+fn stale_lock(path: &Path, name: &str) -> Option<String> {
+    if !name.ends_with(".lock") {
+        return None;
+    }
+    let content = fs::read_to_string(path).ok()?;
+    let content = content.trim();
+    if content.is_empty() {
+        return Some("abandoned lock file".to_string());
+    }
+    let pid: u32 = content.parse().ok()?;
+    let proc_root = Path::new("/proc");
+    if !proc_root.is_dir() || proc_root.join(pid.to_string()).exists() {
+        return None;
+    }
+    Some(format!(
+        "lock held by process {pid}, which is no longer running"
+    ))
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{suffix}"));
+    path.with_file_name(name)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CICDSystem;
+    use noa_symbol_graph::SymbolGraphBuilder;
+    use tempfile::tempdir;
+
+    #[test]
+    fn doctor_finds_and_remediates_broken_workspace_state() {
+        let workspace = tempdir().unwrap();
+        let root = workspace.path();
+        let cicd = CICDSystem::with_context(ConfigContext::isolated().with_workflow_root(root));
+        cicd.configure_workspace_root(root);
+        let kept = cicd
+            .trigger_pipeline("kept".to_string(), "abc123".to_string())
+            .unwrap();
+        let lost = cicd
+            .trigger_pipeline("lost".to_string(), "def456".to_string())
+            .unwrap();
+        fs::write(root.join("lib.rs"), "pub fn indexed() {}").unwrap();
+        SymbolGraphBuilder::new(root).index().unwrap();
+
+        let doctor = Doctor::new(root).with_stale_after(Duration::ZERO);
+        assert!(doctor.diagnose().findings.is_empty());
+
+        let state_path = root.join(PIPELINE_STATE_FILE);
+        let mut state: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap();
+        for pipeline in state["pipelines"].as_array_mut().unwrap() {
+            if pipeline["id"] == lost.as_str() {
+                pipeline["stages"] = serde_json::json!("truncated");
+            }
+        }
+        fs::write(&state_path, state.to_string()).unwrap();
+        let ledger_path = root.join(EVIDENCE_LEDGER_FILE);
+        let ledger = fs::read_to_string(&ledger_path).unwrap();
+        let without_genesis: String = ledger
+            .lines()
+            .skip(1)
+            .map(|line| format!("{line}\n"))
+            .collect();
+        fs::write(&ledger_path, without_genesis).unwrap();
+        let nodes = root.join(DEFAULT_STORE_DIR).join("nodes.jsonl");
+        let mut content = fs::read_to_string(&nodes).unwrap();
+        content.push_str("{\"stable_id\": \"trunc\n");
+        fs::write(&nodes, content).unwrap();
+        let scratch = root.join("storage/db/.tmpA1b2C3");
+        fs::create_dir_all(&scratch).unwrap();
+        fs::write(root.join("storage/db/pipelines/checkpoints.json.tmp"), "{}").unwrap();
+        fs::write(root.join(".workspace/indexes/graph.lock"), "").unwrap();
+        fs::write(
+            root.join(".workspace/indexes/manifest.lock"),
+            "pinned: true",
+        )
+        .unwrap();
+
+        let report = doctor.diagnose();
+        let mut checks: Vec<DoctorCheck> = report.findings.iter().map(|f| f.check).collect();
+        checks.sort_by_key(|check| check.as_str());
+        assert_eq!(
+            checks,
+            vec![
+                DoctorCheck::LedgerGenesis,
+                DoctorCheck::PipelineState,
+                DoctorCheck::StaleLocks,
+                DoctorCheck::SymbolGraph,
+                DoctorCheck::TempFiles,
+                DoctorCheck::TempFiles,
+            ]
+        );
+        assert!(!report.is_healthy());
+        assert!(report.to_markdown().contains("| temp_files | 2 | 0 |"));
+
+        let report = doctor.remediate();
+        assert!(report.is_healthy(), "{}", report.to_markdown());
+        assert!(doctor.diagnose().findings.is_empty());
+        assert!(!scratch.exists());
+        assert!(root.join(".workspace/indexes/manifest.lock").exists());
+        let ledger = read_evidence_ledger(&ledger_path, RecoveryMode::Strict).unwrap();
+        assert_eq!(ledger.records[0].kind, EvidenceLedgerKind::Genesis);
+        assert!(SymbolGraph::load(root.join(DEFAULT_STORE_DIR)).is_ok());
+
+        cicd.reload_state(RecoveryMode::Strict).unwrap();
+        assert!(cicd.get_pipeline_status(&kept).is_some());
+        assert!(cicd.get_pipeline_status(&lost).is_none());
+    }
+
+    #[test]
+    fn doctor_archives_state_written_by_a_newer_release() {
+        let workspace = tempdir().unwrap();
+        let state_path = workspace.path().join(PIPELINE_STATE_FILE);
+        fs::create_dir_all(state_path.parent().unwrap()).unwrap();
+        fs::write(
+            &state_path,
+            r#"{"version": 9, "pipelines": [], "deployments": []}"#,
+        )
+        .unwrap();
+
+        let report = Doctor::new(workspace.path()).remediate();
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].check, DoctorCheck::StateVersion);
+        assert!(report.is_healthy());
+        assert!(!state_path.exists());
+        assert!(state_path.with_file_name("state.json.v9").exists());
+    }
+}
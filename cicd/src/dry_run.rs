@@ -0,0 +1,94 @@
+//! Dry-run plans for pipelines.
+//!
+//! `CICDSystem::dry_run_pipeline` checks approvals and stage executors the way
+//! `execute_pipeline` would and estimates each stage from the durations recorded
+//! by earlier runs, without changing pipeline status or emitting events.
+
+use std::collections::HashMap;
+
+use noa_core::utils::dependency_order;
+use serde::{Deserialize, Serialize};
+
+use crate::{AgentApprovalRequirement, PipelineStage, PipelineStatus, Stage};
+
+/// How a stage would be executed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
+pub enum StageExecution {
+    Builtin,
+    Plugin(String),
+    /// A plugin stage type with no registered executor.
+    Unavailable(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagePlan {
+    pub name: String,
+    pub stage_type: PipelineStage,
+    pub execution: StageExecution,
+    pub estimated_duration_ms: Option<u64>,
+    /// Number of recorded runs of this stage type behind the estimate.
+    pub history_samples: usize,
+    /// Stages this one waits for.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelinePlan {
+    pub pipeline_id: String,
+    pub pipeline_name: String,
+    pub status: PipelineStatus,
+    /// Approval requirements not yet satisfied by a registered approval.
+    pub outstanding_approvals: Vec<AgentApprovalRequirement>,
+    pub stages: Vec<StagePlan>,
+    pub blockers: Vec<String>,
+    /// Longest chain of stage estimates through the dependency graph; stages on
+    /// parallel branches overlap.
+    pub estimated_duration_ms: Option<u64>,
+}
+
+impl PipelinePlan {
+    pub fn runnable(&self) -> bool {
+        self.blockers.is_empty()
+    }
+}
+
+/// Average recorded duration of a stage type across the given stages.
+pub(crate) fn stage_history<'a>(
+    stage_type: &PipelineStage,
+    recorded: impl Iterator<Item = &'a Stage>,
+) -> (Option<u64>, usize) {
+    let durations: Vec<u64> = recorded
+        .filter(|stage| &stage.stage_type == stage_type)
+        .filter_map(|stage| stage.duration_ms)
+        .collect();
+    if durations.is_empty() {
+        return (None, 0);
+    }
+    let total: u64 = durations.iter().sum();
+    (Some(total / durations.len() as u64), durations.len())
+}
+
+/// Time to finish every stage when each starts as soon as its dependencies finish.
+/// `None` when a stage has no estimate.
+pub(crate) fn critical_path_ms(stages: &[StagePlan]) -> Option<u64> {
+    let graph: Vec<(&str, Vec<&String>)> = stages
+        .iter()
+        .map(|stage| (stage.name.as_str(), stage.depends_on.iter().collect()))
+        .collect();
+    let order = dependency_order(&graph).ok()?;
+    let mut finished: HashMap<&str, u64> = HashMap::new();
+    for name in &order {
+        let stage = stages.iter().find(|stage| &stage.name == name)?;
+        let start = stage
+            .depends_on
+            .iter()
+            .filter_map(|dependency| finished.get(dependency.as_str()))
+            .max()
+            .copied()
+            .unwrap_or(0);
+        finished.insert(stage.name.as_str(), start + stage.estimated_duration_ms?);
+    }
+    Some(finished.values().max().copied().unwrap_or(0))
+}
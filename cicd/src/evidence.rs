@@ -0,0 +1,232 @@
+//! Signed evidence bundles for audits.
+//!
+//! An evidence bundle is a gzipped tar holding everything recorded about one
+//! pipeline: the pipeline record, agent approvals, security scan reports, stage
+//! receipts with their Merkle levels, linked deployments, and the signatures of
+//! every ledgered operation. `manifest.json` lists each file with its SHA-256, and
+//! `signature.json` is a policy-signed operation over the manifest digest.
+//! `VERIFY.md` inside the bundle explains how to check it by hand;
+//! [`verify_evidence_bundle`] performs the same checks.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use noa_core::security::{self, OperationKind, OperationRecord, SignedOperation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Bundle directory relative to the workspace root, scoped per namespace.
+pub const EVIDENCE_BUNDLE_DIR: &str = "storage/db/pipelines/evidence";
+/// Bundle layout version written into the manifest.
+pub const EVIDENCE_BUNDLE_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const SIGNATURE_FILE: &str = "signature.json";
+
+/// One file in a bundle and its digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub sha256: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvidenceManifest {
+    pub bundle_version: u32,
+    pub pipeline_id: String,
+    pub namespace: String,
+    pub commit_sha: String,
+    pub created_at: u64,
+    pub files: Vec<ManifestEntry>,
+    /// Ledger lines that could not be parsed and were left out of the bundle.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_ledger_records: Vec<String>,
+}
+
+/// Result of checking a bundle's digests and signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleVerification {
+    pub pipeline_id: String,
+    pub files_verified: usize,
+    pub manifest_sha256: String,
+    /// Whether the manifest signature verifies against this host's policy secret.
+    pub signature_valid: bool,
+}
+
+/// Files gathered for a bundle before it is sealed.
+#[derive(Debug, Default)]
+pub(crate) struct BundleContents {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl BundleContents {
+    pub(crate) fn add_json<T: Serialize>(&mut self, path: &str, value: &T) -> Result<(), String> {
+        let bytes = serde_json::to_vec_pretty(value)
+            .map_err(|err| format!("failed to serialise {path}: {err}"))?;
+        self.files.insert(path.to_string(), bytes);
+        Ok(())
+    }
+
+    pub(crate) fn add_bytes(&mut self, path: String, bytes: Vec<u8>) {
+        self.files.insert(path, bytes);
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn verification_instructions(pipeline_id: &str) -> String {
+    format!(
+        "# Evidence bundle for pipeline {pipeline_id}\n\n\
+         1. Check every file against `{MANIFEST_FILE}`: `sha256sum <path>` must equal the\n   \
+            entry's `sha256`, and no file may be missing or unlisted.\n\
+         2. Compute `sha256sum {MANIFEST_FILE}`; it must equal\n   \
+            `record.metadata.manifest_sha256` in `{SIGNATURE_FILE}`.\n\
+         3. On a host holding the policy secret, run\n   \
+            `noa pipeline verify-evidence --bundle <bundle.tar.gz>` to check the signature.\n\n\
+         `stage_receipts.json` carries each receipt's Merkle leaves and levels; rehashing\n\
+         the leaves must reproduce its `reference` (the Merkle root). `signatures.json`\n\
+         lists every signed operation referenced by the bundle.\n"
+    )
+}
+
+/// Seal `contents` into a signed bundle at `destination`.
+pub(crate) fn write_bundle(
+    destination: &Path,
+    mut contents: BundleContents,
+    mut manifest: EvidenceManifest,
+) -> Result<EvidenceManifest, String> {
+    contents.add_bytes(
+        "VERIFY.md".to_string(),
+        verification_instructions(&manifest.pipeline_id).into_bytes(),
+    );
+    manifest.files = contents
+        .files
+        .iter()
+        .map(|(path, bytes)| ManifestEntry {
+            path: path.clone(),
+            sha256: sha256_hex(bytes),
+            bytes: bytes.len() as u64,
+        })
+        .collect();
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)
+        .map_err(|err| format!("failed to serialise evidence manifest: {err}"))?;
+    let record = OperationRecord::new(
+        OperationKind::Other,
+        "cicd",
+        format!("evidence_bundle::{}", manifest.pipeline_id),
+    )
+    .with_metadata(serde_json::json!({
+        "pipeline_id": manifest.pipeline_id,
+        "manifest_sha256": sha256_hex(&manifest_bytes),
+    }));
+    let signed = security::enforce_operation(record)
+        .map_err(|err| format!("failed to sign evidence bundle: {err}"))?;
+    contents.add_bytes(MANIFEST_FILE.to_string(), manifest_bytes);
+    contents.add_json(SIGNATURE_FILE, &signed)?;
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("failed to create evidence directory: {err}"))?;
+    }
+    let file = fs::File::create(destination)
+        .map_err(|err| format!("failed to create evidence bundle: {err}"))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for (path, bytes) in &contents.files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(manifest.created_at);
+        header.set_cksum();
+        archive
+            .append_data(&mut header, path, bytes.as_slice())
+            .map_err(|err| format!("failed to write {path} to evidence bundle: {err}"))?;
+    }
+    archive
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .and_then(|mut file| file.flush())
+        .map_err(|err| format!("failed to finish evidence bundle: {err}"))?;
+    Ok(manifest)
+}
+
+/// Check every digest in a bundle and the signature over its manifest.
+///
+/// Digest mismatches, missing files, and unlisted files are errors. The signature
+/// can only be checked on a host sharing the signer's policy secret, so its result
+/// is reported rather than enforced.
+pub fn verify_evidence_bundle(path: &Path) -> Result<BundleVerification, String> {
+    let file =
+        fs::File::open(path).map_err(|err| format!("failed to open evidence bundle: {err}"))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut files = BTreeMap::new();
+    let entries = archive
+        .entries()
+        .map_err(|err| format!("failed to read evidence bundle: {err}"))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|err| format!("failed to read evidence bundle: {err}"))?;
+        let name = entry
+            .path()
+            .map_err(|err| format!("invalid path in evidence bundle: {err}"))?
+            .to_string_lossy()
+            .into_owned();
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|err| format!("failed to read {name} from evidence bundle: {err}"))?;
+        files.insert(name, bytes);
+    }
+
+    let manifest_bytes = files
+        .remove(MANIFEST_FILE)
+        .ok_or_else(|| format!("evidence bundle has no {MANIFEST_FILE}"))?;
+    let manifest: EvidenceManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|err| format!("invalid evidence manifest: {err}"))?;
+    let signed: SignedOperation = files
+        .remove(SIGNATURE_FILE)
+        .ok_or_else(|| format!("evidence bundle has no {SIGNATURE_FILE}"))
+        .and_then(|bytes| {
+            serde_json::from_slice(&bytes)
+                .map_err(|err| format!("invalid evidence signature: {err}"))
+        })?;
+
+    for entry in &manifest.files {
+        let bytes = files
+            .remove(&entry.path)
+            .ok_or_else(|| format!("evidence bundle is missing {}", entry.path))?;
+        let digest = sha256_hex(&bytes);
+        if digest != entry.sha256 {
+            return Err(format!(
+                "digest mismatch for {}: manifest {}, found {}",
+                entry.path, entry.sha256, digest
+            ));
+        }
+    }
+    if let Some(unlisted) = files.keys().next() {
+        return Err(format!("evidence bundle contains unlisted file {unlisted}"));
+    }
+
+    let manifest_sha256 = sha256_hex(&manifest_bytes);
+    let signed_digest = signed
+        .record
+        .metadata
+        .get("manifest_sha256")
+        .and_then(|value| value.as_str());
+    if signed_digest != Some(manifest_sha256.as_str()) {
+        return Err("evidence signature does not cover this manifest".to_string());
+    }
+
+    Ok(BundleVerification {
+        pipeline_id: manifest.pipeline_id,
+        files_verified: manifest.files.len(),
+        manifest_sha256,
+        signature_valid: security::verify_signed_operation(&signed),
+    })
+}
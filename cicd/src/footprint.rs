@@ -0,0 +1,414 @@
+//! Binary size and dependency footprint tracking for Build stages.
+//!
+//! After a build, each binary's size and the number of crates it links (its
+//! transitive dependencies in `Cargo.lock`) are recorded on the pipeline and
+//! compared with the last successful pipeline of the same name. Growth beyond the
+//! configured percentage is flagged as a regression; minimal hosts in particular
+//! have little headroom for binaries that quietly double in size.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Growth tolerated before a size or dependency count is flagged.
+pub const DEFAULT_REGRESSION_PERCENT: f64 = 10.0;
+
+/// Directory scanned for binaries when a Build stage does not list its own.
+const RELEASE_DIR: &str = "target/release";
+
+/// A binary to measure, as listed under a Build stage's `binaries` parameter.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BinarySpec {
+    pub name: String,
+    /// Workspace-relative path of the built binary.
+    pub path: String,
+    /// Package whose dependencies the binary links; defaults to `name`.
+    #[serde(default)]
+    pub package: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BinaryFootprint {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    /// Transitive dependency count, when the package is found in `Cargo.lock`.
+    pub dependencies: Option<usize>,
+}
+
+/// Sizes and dependency counts measured after one build.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BuildFootprint {
+    pub binaries: Vec<BinaryFootprint>,
+    /// Packages in the workspace `Cargo.lock`.
+    pub lockfile_packages: Option<usize>,
+}
+
+impl BuildFootprint {
+    /// Measure `specs`, skipping binaries that were not built.
+    pub fn measure(root: &Path, specs: &[BinarySpec]) -> Result<Self, String> {
+        let lock = LockGraph::load(&root.join("Cargo.lock"))?;
+        let mut binaries = Vec::new();
+        for spec in specs {
+            let Ok(metadata) = fs::metadata(root.join(&spec.path)) else {
+                continue;
+            };
+            let package = spec.package.as_deref().unwrap_or(&spec.name);
+            binaries.push(BinaryFootprint {
+                name: spec.name.clone(),
+                path: spec.path.clone(),
+                size_bytes: metadata.len(),
+                dependencies: lock
+                    .as_ref()
+                    .and_then(|lock| lock.dependency_count(package)),
+            });
+        }
+        Ok(Self {
+            binaries,
+            lockfile_packages: lock.map(|lock| lock.len()),
+        })
+    }
+}
+
+/// Executables directly under `target/release`.
+pub fn discover_binaries(root: &Path) -> Vec<BinarySpec> {
+    let Ok(entries) = fs::read_dir(root.join(RELEASE_DIR)) else {
+        return Vec::new();
+    };
+    let mut specs: Vec<BinarySpec> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter(|entry| is_executable(&entry.path()))
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path.file_stem()?.to_str()?.to_string();
+            Some(BinarySpec {
+                name,
+                path: format!("{RELEASE_DIR}/{}", entry.file_name().to_string_lossy()),
+                package: None,
+            })
+        })
+        .collect();
+    specs.sort_by(|a, b| a.name.cmp(&b.name));
+    specs
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "exe")
+}
+
+/// Package dependency graph read from `Cargo.lock`.
+#[derive(Debug, Clone, Default)]
+pub struct LockGraph {
+    /// `name version` -> `name version` of each dependency.
+    packages: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct LockFile {
+    #[serde(default, rename = "package")]
+    packages: Vec<LockPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockPackage {
+    name: String,
+    version: String,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+impl LockGraph {
+    /// Load a lockfile; a missing file yields `None`.
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw =
+            fs::read_to_string(path).map_err(|err| format!("failed to read Cargo.lock: {err}"))?;
+        Self::parse(&raw).map(Some)
+    }
+
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let lock: LockFile =
+            toml::from_str(raw).map_err(|err| format!("failed to parse Cargo.lock: {err}"))?;
+        let versions: BTreeMap<&str, Vec<&str>> =
+            lock.packages
+                .iter()
+                .fold(BTreeMap::new(), |mut versions, package| {
+                    versions
+                        .entry(package.name.as_str())
+                        .or_insert_with(Vec::new)
+                        .push(package.version.as_str());
+                    versions
+                });
+        // Dependencies are written as `name` when only one version is locked and as
+        // `name version (source)` otherwise.
+        let resolve = |dependency: &str| {
+            let mut parts = dependency.split_whitespace();
+            let name = parts.next().unwrap_or_default();
+            let version = parts
+                .next()
+                .or_else(|| versions.get(name).and_then(|v| v.first().copied()))
+                .unwrap_or_default();
+            format!("{name} {version}")
+        };
+        let packages = lock
+            .packages
+            .iter()
+            .map(|package| {
+                (
+                    format!("{} {}", package.name, package.version),
+                    package
+                        .dependencies
+                        .iter()
+                        .map(|dep| resolve(dep))
+                        .collect(),
+                )
+            })
+            .collect();
+        Ok(Self { packages })
+    }
+
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    /// Distinct packages reachable from `package`, excluding itself.
+    pub fn dependency_count(&self, package: &str) -> Option<usize> {
+        let prefix = format!("{package} ");
+        let root = self.packages.keys().find(|key| key.starts_with(&prefix))?;
+        let mut seen = BTreeSet::new();
+        let mut pending = vec![root.clone()];
+        while let Some(current) = pending.pop() {
+            for dependency in self.packages.get(&current).into_iter().flatten() {
+                if seen.insert(dependency.clone()) {
+                    pending.push(dependency.clone());
+                }
+            }
+        }
+        seen.remove(root);
+        Some(seen.len())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FootprintMetric {
+    SizeBytes,
+    Dependencies,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FootprintDelta {
+    pub binary: String,
+    pub metric: FootprintMetric,
+    pub previous: u64,
+    pub current: u64,
+    pub change_percent: f64,
+    pub regression: bool,
+}
+
+/// Comparison of a build's footprint with the previous successful pipeline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FootprintComparison {
+    pub previous_pipeline: Option<String>,
+    pub threshold_percent: f64,
+    pub deltas: Vec<FootprintDelta>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl FootprintComparison {
+    pub fn between(
+        previous: Option<(&str, &BuildFootprint)>,
+        current: &BuildFootprint,
+        threshold_percent: f64,
+    ) -> Self {
+        let mut comparison = Self {
+            previous_pipeline: previous.map(|(id, _)| id.to_string()),
+            threshold_percent,
+            ..Self::default()
+        };
+        let Some((_, previous)) = previous else {
+            return comparison;
+        };
+        for binary in &current.binaries {
+            let Some(before) = previous
+                .binaries
+                .iter()
+                .find(|before| before.name == binary.name)
+            else {
+                comparison.added.push(binary.name.clone());
+                continue;
+            };
+            comparison.push(
+                &binary.name,
+                FootprintMetric::SizeBytes,
+                before.size_bytes,
+                binary.size_bytes,
+            );
+            if let (Some(before), Some(after)) = (before.dependencies, binary.dependencies) {
+                comparison.push(
+                    &binary.name,
+                    FootprintMetric::Dependencies,
+                    before as u64,
+                    after as u64,
+                );
+            }
+        }
+        comparison.removed = previous
+            .binaries
+            .iter()
+            .filter(|before| !current.binaries.iter().any(|b| b.name == before.name))
+            .map(|before| before.name.clone())
+            .collect();
+        comparison
+    }
+
+    fn push(&mut self, binary: &str, metric: FootprintMetric, previous: u64, current: u64) {
+        let change_percent = if previous == 0 {
+            if current == 0 {
+                0.0
+            } else {
+                100.0
+            }
+        } else {
+            (current as f64 - previous as f64) / previous as f64 * 100.0
+        };
+        self.deltas.push(FootprintDelta {
+            binary: binary.to_string(),
+            metric,
+            previous,
+            current,
+            change_percent,
+            regression: change_percent > self.threshold_percent,
+        });
+    }
+
+    pub fn regressions(&self) -> impl Iterator<Item = &FootprintDelta> {
+        self.deltas.iter().filter(|delta| delta.regression)
+    }
+}
+
+/// Footprint and comparison attached to a pipeline and written with its reports.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FootprintReport {
+    pub footprint: BuildFootprint,
+    pub comparison: FootprintComparison,
+}
+
+impl FootprintReport {
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Build footprint\n\n");
+        match &self.comparison.previous_pipeline {
+            Some(previous) => out.push_str(&format!(
+                "Compared with `{}`; regressions above {:.1}% are flagged.\n\n",
+                previous, self.comparison.threshold_percent
+            )),
+            None => out.push_str("No earlier successful pipeline to compare with.\n\n"),
+        }
+        out.push_str("| Binary | Size (bytes) | Dependencies |\n|---|---:|---:|\n");
+        for binary in &self.footprint.binaries {
+            let dependencies = binary
+                .dependencies
+                .map(|count| count.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            out.push_str(&format!(
+                "| {} | {} | {} |\n",
+                binary.name, binary.size_bytes, dependencies
+            ));
+        }
+        if !self.comparison.deltas.is_empty() {
+            out.push_str(
+                "\n| Binary | Metric | Previous | Current | Change |\n|---|---|---:|---:|---:|\n",
+            );
+            for delta in &self.comparison.deltas {
+                let metric = match delta.metric {
+                    FootprintMetric::SizeBytes => "size",
+                    FootprintMetric::Dependencies => "dependencies",
+                };
+                let flag = if delta.regression { " ⚠" } else { "" };
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} | {:+.1}%{} |\n",
+                    delta.binary, metric, delta.previous, delta.current, delta.change_percent, flag
+                ));
+            }
+        }
+        for (label, names) in [
+            ("New binaries", &self.comparison.added),
+            ("Removed binaries", &self.comparison.removed),
+        ] {
+            if !names.is_empty() {
+                out.push_str(&format!("\n{}: {}\n", label, names.join(", ")));
+            }
+        }
+        out
+    }
+
+    pub fn write(&self, dir: &Path) -> Result<(PathBuf, PathBuf), String> {
+        fs::create_dir_all(dir).map_err(|err| format!("failed to create report dir: {err}"))?;
+        let json_path = dir.join("footprint.json");
+        let markdown_path = dir.join("footprint.md");
+        let payload = serde_json::to_string_pretty(self)
+            .map_err(|err| format!("failed to serialise footprint report: {err}"))?;
+        fs::write(&json_path, payload)
+            .and_then(|_| fs::write(&markdown_path, self.to_markdown()))
+            .map_err(|err| format!("failed to write footprint report: {err}"))?;
+        Ok((json_path, markdown_path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_transitive_lock_dependencies() {
+        let lock = LockGraph::parse(
+            r#"
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["serde", "toml"]
+
+[[package]]
+name = "serde"
+version = "1.0.0"
+
+[[package]]
+name = "toml"
+version = "0.8.0"
+dependencies = ["serde", "winnow 0.6.0"]
+
+[[package]]
+name = "winnow"
+version = "0.5.0"
+
+[[package]]
+name = "winnow"
+version = "0.6.0"
+"#,
+        )
+        .unwrap();
+        assert_eq!(lock.len(), 5);
+        assert_eq!(lock.dependency_count("app"), Some(3));
+        assert_eq!(lock.dependency_count("toml"), Some(2));
+        assert_eq!(lock.dependency_count("missing"), None);
+    }
+}
@@ -0,0 +1,211 @@
+//! Deployment health and pipeline KPIs kept in the local time-series store.
+//!
+//! Health metrics reported for deployments are appended as `deployment.*` series
+//! labelled with the service and environment, and the samples that trained a learned
+//! baseline as `baseline.*` series labelled with the environment. When a store is
+//! configured, SLO error budgets and empty baselines are rebuilt from them, so both
+//! survive restarts. Finished pipelines add `pipeline.*` KPIs labelled with the
+//! pipeline name.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use noa_core::metrics::timeseries::{
+    Labels, Resolution, Sample, SeriesQuery, TimeSeriesError, TimeSeriesStore,
+};
+
+use crate::{Environment, HealthMetrics, Pipeline, PipelineStatus};
+
+/// Prefix of the health series reported for deployments.
+pub const DEPLOYMENT_SERIES: &str = "deployment";
+/// Prefix of the health series that trained a learned baseline.
+pub const BASELINE_SERIES: &str = "baseline";
+
+const HEALTH_FIELDS: [&str; 5] = [
+    "error_rate",
+    "response_time_ms",
+    "cpu_usage",
+    "memory_usage",
+    "active_connections",
+];
+
+pub fn environment_label(environment: &Environment) -> &'static str {
+    match environment {
+        Environment::Development => "development",
+        Environment::Staging => "staging",
+        Environment::Production => "production",
+    }
+}
+
+/// Labels of the deployment series of `service` in `environment`.
+pub fn deployment_labels(service: &str, environment: &Environment) -> Labels {
+    Labels::from([
+        ("service".to_string(), service.to_string()),
+        (
+            "environment".to_string(),
+            environment_label(environment).to_string(),
+        ),
+    ])
+}
+
+/// Labels of the baseline series of `environment`.
+pub fn baseline_labels(environment: &Environment) -> Labels {
+    Labels::from([(
+        "environment".to_string(),
+        environment_label(environment).to_string(),
+    )])
+}
+
+/// One sample per health metric, named `<prefix>.<metric>`.
+pub fn health_samples(
+    prefix: &str,
+    metrics: &HealthMetrics,
+    labels: &Labels,
+    timestamp_ms: u64,
+) -> Vec<Sample> {
+    let values = [
+        metrics.error_rate as f64,
+        metrics.response_time_ms as f64,
+        metrics.cpu_usage as f64,
+        metrics.memory_usage as f64,
+        metrics.active_connections as f64,
+    ];
+    HEALTH_FIELDS
+        .iter()
+        .zip(values)
+        .map(|(field, value)| Sample {
+            series: format!("{prefix}.{field}"),
+            labels: labels.clone(),
+            timestamp_ms,
+            value,
+        })
+        .collect()
+}
+
+/// Health metrics recorded under `prefix` and `labels` in `[start_ms, end_ms)`,
+/// oldest first, with their timestamps. Each part of the range is read at the finest
+/// resolution still retained for it, so past raw retention an entry holds the means
+/// of a rollup bucket.
+pub fn health_history(
+    store: &TimeSeriesStore,
+    prefix: &str,
+    labels: &Labels,
+    start_ms: u64,
+    end_ms: u64,
+    now_ms: u64,
+) -> Result<Vec<(u64, HealthMetrics)>, TimeSeriesError> {
+    let mut fields: BTreeMap<(u64, usize), [Option<f64>; 5]> = BTreeMap::new();
+    let mut window_end = end_ms;
+    for resolution in Resolution::ALL {
+        // The coarsest tier covers whatever is left of the range.
+        let window_start = if resolution == Resolution::OneHour {
+            start_ms
+        } else {
+            let retention = store.retention().for_resolution(resolution).as_millis() as u64;
+            start_ms
+                .max(now_ms.saturating_sub(retention))
+                .min(window_end)
+        };
+        read_health(
+            store,
+            prefix,
+            labels,
+            resolution,
+            window_start..window_end,
+            now_ms,
+            &mut fields,
+        )?;
+        window_end = window_start;
+    }
+    Ok(fields
+        .into_iter()
+        .filter_map(|((timestamp_ms, _), values)| {
+            let [Some(error_rate), Some(response_time_ms), Some(cpu_usage), Some(memory_usage), Some(active_connections)] =
+                values
+            else {
+                return None;
+            };
+            Some((
+                timestamp_ms,
+                HealthMetrics {
+                    error_rate: error_rate as f32,
+                    response_time_ms: response_time_ms.round() as u64,
+                    cpu_usage: cpu_usage as f32,
+                    memory_usage: memory_usage as f32,
+                    active_connections: active_connections.round() as u32,
+                },
+            ))
+        })
+        .collect())
+}
+
+fn read_health(
+    store: &TimeSeriesStore,
+    prefix: &str,
+    labels: &Labels,
+    resolution: Resolution,
+    range: Range<u64>,
+    now_ms: u64,
+    fields: &mut BTreeMap<(u64, usize), [Option<f64>; 5]>,
+) -> Result<(), TimeSeriesError> {
+    if range.is_empty() {
+        return Ok(());
+    }
+    for (index, field) in HEALTH_FIELDS.iter().enumerate() {
+        let mut query = SeriesQuery::new(format!("{prefix}.{field}"), range.start, range.end)
+            .with_resolution(resolution);
+        query.labels = labels.clone();
+        for data in store.query(&query, now_ms)? {
+            // Samples sharing a timestamp are told apart by their order.
+            let mut previous = None;
+            let mut repeat = 0;
+            for point in data.points {
+                repeat = if previous == Some(point.timestamp_ms) {
+                    repeat + 1
+                } else {
+                    0
+                };
+                previous = Some(point.timestamp_ms);
+                fields.entry((point.timestamp_ms, repeat)).or_default()[index] = Some(point.mean());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// KPIs of a finished pipeline: whether it succeeded, the summed duration of its
+/// stages, and its test coverage when the Test stage measured it.
+pub fn pipeline_samples(pipeline: &Pipeline, timestamp_ms: u64) -> Vec<Sample> {
+    let labels = Labels::from([("pipeline".to_string(), pipeline.name.clone())]);
+    let sample = |series: &str, value: f64| Sample {
+        series: series.to_string(),
+        labels: labels.clone(),
+        timestamp_ms,
+        value,
+    };
+    let succeeded = if pipeline.status == PipelineStatus::Success {
+        1.0
+    } else {
+        0.0
+    };
+    let mut samples = vec![sample("pipeline.succeeded", succeeded)];
+    let durations: Vec<u64> = pipeline
+        .stages
+        .iter()
+        .filter_map(|stage| stage.duration_ms)
+        .collect();
+    if !durations.is_empty() {
+        samples.push(sample(
+            "pipeline.duration_ms",
+            durations.iter().sum::<u64>() as f64,
+        ));
+    }
+    if let Some(coverage) = pipeline
+        .tests
+        .as_ref()
+        .and_then(|tests| tests.coverage_percent)
+    {
+        samples.push(sample("pipeline.coverage_percent", coverage));
+    }
+    samples
+}
@@ -0,0 +1,532 @@
+//! Portable bundles of the workspace indexes for seeding new hosts.
+//!
+//! Building `.workspace/indexes` from scratch takes a fresh host a long time. An index
+//! bundle is a gzipped tar of the indexer graphs, the symbol graph store, and the
+//! embeddings and search indexes, with `manifest.json` listing each file's SHA-256 and
+//! the metadata needed to judge whether the indexes fit the importing workspace: the
+//! bundle layout version, the version of the tooling that wrote it, and the commit the
+//! workspace was at. [`import_index_bundle`] verifies all of it before touching the
+//! indexes, then replaces each bundled component and points its journal cursor at the
+//! host's own change journal so later edits are indexed incrementally.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use noa_core::fs::ChangeJournal;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::evidence::ManifestEntry;
+use crate::workspace_policy::git;
+
+/// Index directory relative to the workspace root.
+pub const INDEX_DIR: &str = ".workspace/indexes";
+/// Bundle layout version written into the manifest.
+pub const INDEX_BUNDLE_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+/// Journal sequences are local to each host, so cursors are never bundled.
+const JOURNAL_CURSOR: &str = "journal.cursor";
+const INDEXER_FILES: [&str; 3] = [
+    "ast_graph.json",
+    "ownership_graph.json",
+    "config_graph.json",
+];
+
+/// A separately replaceable part of the index directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexComponent {
+    /// AST, ownership and config graphs written by `noa_core::indexer`.
+    Indexer,
+    /// Symbol graph store, see `noa_symbol_graph::DEFAULT_STORE_DIR`.
+    SymbolGraph,
+    Embeddings,
+    Search,
+}
+
+impl IndexComponent {
+    pub const ALL: [IndexComponent; 4] = [
+        IndexComponent::Indexer,
+        IndexComponent::SymbolGraph,
+        IndexComponent::Embeddings,
+        IndexComponent::Search,
+    ];
+
+    /// Directory of the component under the index directory; the indexer writes its
+    /// graphs to the index directory itself.
+    fn directory(self) -> Option<&'static str> {
+        match self {
+            IndexComponent::Indexer => None,
+            IndexComponent::SymbolGraph => Some("symbol_graph"),
+            IndexComponent::Embeddings => Some("embeddings"),
+            IndexComponent::Search => Some("search"),
+        }
+    }
+
+    /// Component owning `relative`, a path under the index directory. Logs, locks and
+    /// other host-local state belong to none.
+    fn of(relative: &Path) -> Option<Self> {
+        let mut parts = relative.components();
+        let first = parts.next()?.as_os_str().to_str()?;
+        if parts.next().is_none() {
+            return INDEXER_FILES
+                .contains(&first)
+                .then_some(IndexComponent::Indexer);
+        }
+        Self::ALL
+            .into_iter()
+            .find(|component| component.directory() == Some(first))
+    }
+}
+
+/// Files and bytes bundled for one component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentSummary {
+    pub component: IndexComponent,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexBundleManifest {
+    pub bundle_version: u32,
+    /// Version of the tooling that wrote the indexes.
+    pub tool_version: String,
+    /// Workspace commit the indexes describe, when the workspace is a git checkout.
+    #[serde(default)]
+    pub commit: Option<String>,
+    pub created_at: u64,
+    pub components: Vec<ComponentSummary>,
+    /// Paths relative to the index directory.
+    pub files: Vec<ManifestEntry>,
+}
+
+/// Outcome of importing a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexImport {
+    pub manifest: IndexBundleManifest,
+    pub files_imported: usize,
+    /// Journal sequence the imported components resume from.
+    pub journal_cursor: u64,
+}
+
+/// Bundle the indexes of the workspace at `workspace_root` into `destination`.
+pub fn export_index_bundle(
+    workspace_root: &Path,
+    destination: &Path,
+) -> Result<IndexBundleManifest, String> {
+    let index_dir = workspace_root.join(INDEX_DIR);
+    let mut files = BTreeMap::new();
+    collect_files(&index_dir, &index_dir, &mut files)?;
+    if files.is_empty() {
+        return Err(format!("no indexes found under {}", index_dir.display()));
+    }
+
+    let manifest = IndexBundleManifest {
+        bundle_version: INDEX_BUNDLE_VERSION,
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        commit: workspace_commit(workspace_root),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| format!("system clock error: {err}"))?
+            .as_secs(),
+        components: summarise(&files),
+        files: files
+            .iter()
+            .map(|(path, bytes)| ManifestEntry {
+                path: path.clone(),
+                sha256: sha256_hex(bytes),
+                bytes: bytes.len() as u64,
+            })
+            .collect(),
+    };
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)
+        .map_err(|err| format!("failed to serialise index manifest: {err}"))?;
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("failed to create bundle directory: {err}"))?;
+    }
+    let file = fs::File::create(destination)
+        .map_err(|err| format!("failed to create index bundle: {err}"))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let entries = std::iter::once((MANIFEST_FILE, manifest_bytes.as_slice())).chain(
+        files
+            .iter()
+            .map(|(path, bytes)| (path.as_str(), bytes.as_slice())),
+    );
+    for (path, bytes) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(manifest.created_at);
+        header.set_cksum();
+        archive
+            .append_data(&mut header, path, bytes)
+            .map_err(|err| format!("failed to write {path} to index bundle: {err}"))?;
+    }
+    archive
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .and_then(|mut file| file.flush())
+        .map_err(|err| format!("failed to finish index bundle: {err}"))?;
+    Ok(manifest)
+}
+
+/// Check every digest in a bundle and that its layout version is supported.
+pub fn verify_index_bundle(path: &Path) -> Result<IndexBundleManifest, String> {
+    read_bundle(path).map(|(manifest, _)| manifest)
+}
+
+/// Verify a bundle and install it into the workspace at `workspace_root`.
+///
+/// The bundle must have been written by a compatible tool version and, when both
+/// sides are git checkouts, at the commit the workspace is at. Each bundled
+/// component replaces the workspace's copy; components the bundle lacks are left
+/// alone.
+pub fn import_index_bundle(bundle: &Path, workspace_root: &Path) -> Result<IndexImport, String> {
+    let (manifest, files) = read_bundle(bundle)?;
+    check_compatible(&manifest, workspace_commit(workspace_root).as_deref())?;
+    let journal = ChangeJournal::open(workspace_root)
+        .map_err(|err| format!("failed to open change journal: {err}"))?;
+    let journal_cursor = journal
+        .changes_since(0)
+        .map_err(|err| format!("failed to read change journal: {err}"))?
+        .last_sequence;
+
+    let index_dir = workspace_root.join(INDEX_DIR);
+    for summary in &manifest.components {
+        clear_component(&index_dir, summary.component)?;
+    }
+    for (path, bytes) in &files {
+        write_atomic(&index_dir.join(path), bytes)?;
+    }
+    for summary in &manifest.components {
+        let cursor_dir = match summary.component {
+            IndexComponent::Indexer => index_dir.clone(),
+            IndexComponent::SymbolGraph => index_dir.join("symbol_graph"),
+            IndexComponent::Embeddings | IndexComponent::Search => continue,
+        };
+        write_atomic(
+            &cursor_dir.join(JOURNAL_CURSOR),
+            journal_cursor.to_string().as_bytes(),
+        )?;
+    }
+
+    Ok(IndexImport {
+        files_imported: files.len(),
+        manifest,
+        journal_cursor,
+    })
+}
+
+fn check_compatible(manifest: &IndexBundleManifest, commit: Option<&str>) -> Result<(), String> {
+    if manifest.bundle_version > INDEX_BUNDLE_VERSION {
+        return Err(format!(
+            "index bundle version {} is newer than supported version {}",
+            manifest.bundle_version, INDEX_BUNDLE_VERSION
+        ));
+    }
+    if release_line(&manifest.tool_version) != release_line(env!("CARGO_PKG_VERSION")) {
+        return Err(format!(
+            "index bundle was written by version {}, this host runs {}",
+            manifest.tool_version,
+            env!("CARGO_PKG_VERSION")
+        ));
+    }
+    if let (Some(bundled), Some(workspace)) = (manifest.commit.as_deref(), commit) {
+        if bundled != workspace {
+            return Err(format!(
+                "index bundle describes commit {bundled}, the workspace is at {workspace}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Major and minor version; index formats only change between release lines.
+fn release_line(version: &str) -> Vec<&str> {
+    version.split('.').take(2).collect()
+}
+
+fn read_bundle(path: &Path) -> Result<(IndexBundleManifest, BTreeMap<String, Vec<u8>>), String> {
+    let file = fs::File::open(path).map_err(|err| format!("failed to open index bundle: {err}"))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut files = BTreeMap::new();
+    let entries = archive
+        .entries()
+        .map_err(|err| format!("failed to read index bundle: {err}"))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|err| format!("failed to read index bundle: {err}"))?;
+        let name = entry
+            .path()
+            .map_err(|err| format!("invalid path in index bundle: {err}"))?
+            .to_string_lossy()
+            .into_owned();
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|err| format!("failed to read {name} from index bundle: {err}"))?;
+        files.insert(name, bytes);
+    }
+
+    let manifest: IndexBundleManifest = files
+        .remove(MANIFEST_FILE)
+        .ok_or_else(|| format!("index bundle has no {MANIFEST_FILE}"))
+        .and_then(|bytes| {
+            serde_json::from_slice(&bytes).map_err(|err| format!("invalid index manifest: {err}"))
+        })?;
+    if manifest.bundle_version > INDEX_BUNDLE_VERSION {
+        return Err(format!(
+            "index bundle version {} is newer than supported version {}",
+            manifest.bundle_version, INDEX_BUNDLE_VERSION
+        ));
+    }
+
+    let mut verified = BTreeMap::new();
+    for entry in &manifest.files {
+        let relative = Path::new(&entry.path);
+        if relative
+            .components()
+            .any(|part| !matches!(part, Component::Normal(_)))
+            || IndexComponent::of(relative).is_none()
+        {
+            return Err(format!("index bundle lists invalid path {}", entry.path));
+        }
+        let bytes = files
+            .remove(&entry.path)
+            .ok_or_else(|| format!("index bundle is missing {}", entry.path))?;
+        let digest = sha256_hex(&bytes);
+        if digest != entry.sha256 {
+            return Err(format!(
+                "digest mismatch for {}: manifest {}, found {}",
+                entry.path, entry.sha256, digest
+            ));
+        }
+        verified.insert(entry.path.clone(), bytes);
+    }
+    if let Some(unlisted) = files.keys().next() {
+        return Err(format!("index bundle contains unlisted file {unlisted}"));
+    }
+    Ok((manifest, verified))
+}
+
+/// Read the component files under `dir`, keyed by their bundle path.
+fn collect_files(
+    index_dir: &Path,
+    dir: &Path,
+    files: &mut BTreeMap<String, Vec<u8>>,
+) -> Result<(), String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(format!("failed to read {}: {err}", dir.display())),
+    };
+    for entry in entries {
+        let entry = entry.map_err(|err| format!("failed to read {}: {err}", dir.display()))?;
+        let path = entry.path();
+        let file_type = entry
+            .file_type()
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        if file_type.is_dir() {
+            collect_files(index_dir, &path, files)?;
+            continue;
+        }
+        let relative = path
+            .strip_prefix(index_dir)
+            .expect("listed path is under the index directory");
+        if !file_type.is_file()
+            || entry.file_name() == JOURNAL_CURSOR
+            || IndexComponent::of(relative).is_none()
+        {
+            continue;
+        }
+        let bytes =
+            fs::read(&path).map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        files.insert(bundle_path(relative), bytes);
+    }
+    Ok(())
+}
+
+/// Remove the workspace's copy of a component before the bundled one is written.
+fn clear_component(index_dir: &Path, component: IndexComponent) -> Result<(), String> {
+    let result = match component.directory() {
+        Some(directory) => fs::remove_dir_all(index_dir.join(directory)),
+        None => INDEXER_FILES
+            .iter()
+            .map(|file| fs::remove_file(index_dir.join(file)))
+            .find(|result| {
+                result
+                    .as_ref()
+                    .is_err_and(|err| err.kind() != std::io::ErrorKind::NotFound)
+            })
+            .unwrap_or(Ok(())),
+    };
+    match result {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("failed to clear {component:?} index: {err}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let parent = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(parent)
+        .map_err(|err| format!("failed to create {}: {err}", parent.display()))?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, bytes)
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+fn summarise(files: &BTreeMap<String, Vec<u8>>) -> Vec<ComponentSummary> {
+    let mut components: BTreeMap<IndexComponent, ComponentSummary> = BTreeMap::new();
+    for (path, bytes) in files {
+        let Some(component) = IndexComponent::of(Path::new(path)) else {
+            continue;
+        };
+        let summary = components
+            .entry(component)
+            .or_insert_with(|| ComponentSummary {
+                component,
+                files: 0,
+                bytes: 0,
+            });
+        summary.files += 1;
+        summary.bytes += bytes.len() as u64;
+    }
+    components.into_values().collect()
+}
+
+fn workspace_commit(workspace_root: &Path) -> Option<String> {
+    git(workspace_root, &["rev-parse", "HEAD"])
+        .ok()
+        .map(|commit| commit.trim().to_string())
+}
+
+/// Forward-slash path so bundles are portable between platforms.
+fn bundle_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(INDEX_DIR).join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn export_and_import_seed_a_fresh_workspace() {
+        let source = tempfile::tempdir().unwrap();
+        write(source.path(), "ast_graph.json", "{\"nodes\":[]}");
+        write(source.path(), "config_graph.json", "{}");
+        write(source.path(), "journal.cursor", "42");
+        write(source.path(), "symbol_graph/nodes.jsonl", "{}\n");
+        write(source.path(), "symbol_graph/journal.cursor", "42");
+        write(source.path(), "embeddings/chunks.bin", "vectors");
+        write(source.path(), "pipeline_events.log", "host-local\n");
+        let bundle = source.path().join("indexes.tar.gz");
+
+        let manifest = export_index_bundle(source.path(), &bundle).unwrap();
+        let paths: Vec<&str> = manifest
+            .files
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "ast_graph.json",
+                "config_graph.json",
+                "embeddings/chunks.bin",
+                "symbol_graph/nodes.jsonl"
+            ]
+        );
+        assert_eq!(manifest.components.len(), 3);
+        assert_eq!(verify_index_bundle(&bundle).unwrap(), manifest);
+
+        let target = tempfile::tempdir().unwrap();
+        write(target.path(), "symbol_graph/stale.jsonl", "old");
+        write(target.path(), "search/terms.idx", "kept");
+        let journal = ChangeJournal::open(target.path()).unwrap();
+        journal
+            .write("agent", "src/lib.rs", "fn main() {}")
+            .unwrap();
+
+        let imported = import_index_bundle(&bundle, target.path()).unwrap();
+        assert_eq!(imported.files_imported, 4);
+        assert_eq!(imported.journal_cursor, 1);
+        let index_dir = target.path().join(INDEX_DIR);
+        assert_eq!(
+            fs::read_to_string(index_dir.join("embeddings/chunks.bin")).unwrap(),
+            "vectors"
+        );
+        assert!(!index_dir.join("symbol_graph/stale.jsonl").exists());
+        assert!(index_dir.join("search/terms.idx").exists());
+        assert!(!index_dir.join("pipeline_events.log").exists());
+        assert_eq!(
+            fs::read_to_string(index_dir.join("symbol_graph/journal.cursor")).unwrap(),
+            "1"
+        );
+
+        let mut stale = manifest.clone();
+        stale.commit = Some("abc".to_string());
+        let err = check_compatible(&stale, Some("def")).unwrap_err();
+        assert!(err.contains("commit abc"), "{err}");
+        stale.tool_version = "99.0.0".to_string();
+        assert!(check_compatible(&stale, None).is_err());
+    }
+
+    #[test]
+    fn verify_rejects_tampered_bundle() {
+        let source = tempfile::tempdir().unwrap();
+        write(source.path(), "symbol_graph/nodes.jsonl", "{}\n");
+        let bundle = source.path().join("indexes.tar.gz");
+        let mut manifest = export_index_bundle(source.path(), &bundle).unwrap();
+
+        manifest.files[0].sha256 = sha256_hex(b"other");
+        let file = fs::File::create(&bundle).unwrap();
+        let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        for (path, bytes) in [
+            (MANIFEST_FILE, serde_json::to_vec(&manifest).unwrap()),
+            ("symbol_graph/nodes.jsonl", b"{}\n".to_vec()),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            archive
+                .append_data(&mut header, path, bytes.as_slice())
+                .unwrap();
+        }
+        archive.into_inner().unwrap().finish().unwrap();
+
+        let err = verify_index_bundle(&bundle).unwrap_err();
+        assert!(err.contains("digest mismatch"), "{err}");
+        let target = tempfile::tempdir().unwrap();
+        assert!(import_index_bundle(&bundle, target.path()).is_err());
+        assert!(!target.path().join(INDEX_DIR).exists());
+    }
+}
@@ -1,34 +1,134 @@
 //! CI/CD System - Continuous Delivery focused with CRC integration
 
+pub mod artifacts;
+pub mod baseline;
+pub mod blue_green;
+pub mod canary;
+pub mod checkpoint;
+pub mod compare;
+pub mod diff_summary;
+pub mod doctor;
+pub mod dry_run;
+pub mod evidence;
+pub mod footprint;
+pub mod history;
+pub mod index_bundle;
 pub mod ledger;
+pub mod lint;
+pub mod pipeline_spec;
+pub mod pipeline_table;
+pub mod retry;
+pub mod routing;
+pub mod slo;
+pub mod stage_graph;
+pub mod stage_plugins;
 pub mod trigger;
 pub mod validation;
+pub mod webhook;
+pub mod worker_pool;
+pub mod workspace_policy;
 
+use artifacts::{
+    ArtifactRecord, ArtifactStore, ArtifactVerification, Retention, RetentionClass,
+    RetentionPolicy, RetentionSweep, ARTIFACT_STORE_DIR,
+};
+use baseline::{BaselineConfig, HealthBaselines};
+use blue_green::{EnvironmentSlots, Slot, SlotSwitch};
+use canary::{CanaryPlan, CanaryProgress, TrafficShifter};
+use checkpoint::{CheckpointStore, StageCheckpoint, PIPELINE_CHECKPOINT_DIR};
+use compare::{PipelineComparison, TestOutcome, TestReport};
+use diff_summary::DiffSummary;
+use doctor::{Doctor, DoctorReport};
+use dry_run::{PipelinePlan, StageExecution, StagePlan};
+use evidence::{BundleContents, EvidenceManifest, EVIDENCE_BUNDLE_DIR};
+use footprint::{
+    BinarySpec, BuildFootprint, FootprintComparison, FootprintReport, DEFAULT_REGRESSION_PERCENT,
+};
+use lint::{CargoLintRunner, LintBaseline, LintReport, LintRunner, LINT_BASELINE_FILE};
+use noa_caddy_manager::ReverseProxyRoute;
+use noa_core::config::host_profile::SingleHostProfile;
+use noa_core::cost::{CostAccount, CostKind, CostLedger, CostUsage};
+use noa_core::host_control::RuntimeGraph;
+use noa_core::metrics::timeseries::{Sample, TimeSeriesStore};
+use noa_core::recovery::{self, RecoveryMode, SkippedRecord};
+use noa_core::scheduler::{HostResources, JobPriority, ReservationGuard};
 use noa_security_shim::{
     run_gitleaks, run_grype, run_syft, run_trivy, ScanConfig, ScanResult, ScanStatus,
+    DEFAULT_REPORT_DIR,
 };
-use noa_workflow::{PipelineInstrumentation, SecurityScanReport, SecurityScanStatus};
+use noa_symbol_graph::{CodeOwners, DeadCodeReport, Ownership, SymbolGraph, DEFAULT_STORE_DIR};
+pub use noa_workflow::{AgentApproval, AgentApprovalRequirement, ConfigContext};
+use noa_workflow::{
+    ConcurrencyGovernor, ConcurrencyPermit, ConcurrencySnapshot, DeploymentOutcomeRecord,
+    EvidenceLedgerKind, Namespace, NamespaceError, NamespaceQuota, NamespaceRegistry,
+    PipelineInstrumentation, SecurityScanReport, SecurityScanStatus,
+};
+use pipeline_spec::PipelineSpec;
+use pipeline_table::{PipelineSnapshot, PipelineTable};
+use retry::RetryPolicy;
+use routing::{CaddyRouteController, DeploymentRoute, RouteController, CADDY_ADMIN_ENV};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use slo::{ErrorBudgetStatus, SloDefinition, SloObservation, SloTracker};
+use stage_graph::GraphOutcome;
+use stage_plugins::{StageContext, StageExecutor, StageExecutorRegistry};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
+use trigger::PipelineSchedule;
+use webhook::{PipelineSource, WebhookDelivery, WebhookError, WebhookEvent, WebhookOutcome};
+use worker_pool::{PipelineProgress, RunQueue};
+use workspace_policy::{PolicyReport, WorkspacePolicy, WORKSPACE_POLICY_FILE};
 
 const PIPELINE_STATE_FILE: &str = "storage/db/pipelines/state.json";
+const HEALTH_BASELINE_FILE: &str = "storage/db/pipelines/health_baselines.json";
+const PIPELINE_REPORTS_DIR: &str = "storage/db/pipelines/reports";
+const KERNEL_RUNTIME_GRAPH: &str = "runtime/kernel/graph.yaml";
+/// Trust score required from owners whose areas a pipeline touches.
+const OWNER_APPROVAL_TRUST_SCORE: f32 = 0.7;
+
+/// Build targets and test suites run when a stage does not list its own.
+const DEFAULT_BUILD_TARGETS: &[&str] = &["rust", "go", "python", ".net"];
+const DEFAULT_TEST_SUITES: &[&str] = &["unit", "integration", "api"];
+/// Host resources build and test stages reserve when they declare none.
+const DEFAULT_STAGE_RESOURCES: HostResources = HostResources {
+    cpu_cores: 1,
+    vram_mb: 0,
+};
+/// How long a stage queues for host resources before failing.
+const DEFAULT_RESOURCE_WAIT_SECS: u64 = 600;
+
+/// Service name used for deployments that do not name one explicitly.
+pub const DEFAULT_SERVICE: &str = "noa-ark-os";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum PipelineStage {
+    #[serde(alias = "crc")]
     CRC, // Continuous ReCode (new)
+    #[serde(alias = "validate")]
     Validate,
+    #[serde(alias = "policy")]
+    Policy,
+    #[serde(alias = "lint")]
+    Lint,
+    #[serde(alias = "build")]
     Build,
+    #[serde(alias = "test")]
     Test,
+    #[serde(alias = "single_host_acceptance")]
     SingleHostAcceptance,
+    #[serde(alias = "deploy")]
     Deploy,
+    #[serde(alias = "verify")]
     Verify,
+    #[serde(alias = "promote")]
     Promote,
+    #[serde(alias = "docs_refresh")]
     DocsRefresh,
+    /// Third-party stage type dispatched to a registered [`stage_plugins::StageExecutor`].
+    Plugin(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -41,8 +141,11 @@ pub enum DeploymentStrategy {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Environment {
+    #[serde(alias = "development")]
     Development,
+    #[serde(alias = "staging")]
     Staging,
+    #[serde(alias = "production")]
     Production,
 }
 
@@ -57,6 +160,10 @@ pub enum PipelineStatus {
     AgentReview,
     AgentApproved,
     AgentEscalated,
+    /// Stopped between stages by `pause_pipeline`; `resume_pipeline` continues it.
+    Paused,
+    /// Stopped between stages by `cancel_pipeline`; it cannot be executed again.
+    Cancelled,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,43 +183,28 @@ pub struct Pipeline {
     pub approvals_granted: Vec<AgentApproval>,
     #[serde(default)]
     pub security_scans: Vec<SecurityScanReport>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct AgentApprovalRequirement {
-    pub role: String,
-    pub minimum_trust_score: f32,
     #[serde(default)]
-    pub required_evidence_tags: Vec<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct AgentApproval {
-    pub role: String,
-    pub agent_id: String,
-    pub trust_score: f32,
+    pub spec_path: Option<String>,
     #[serde(default)]
-    pub evidence_tags: Vec<String>,
+    pub spec: Option<PipelineSpec>,
+    /// Violations found by the most recent Policy stage run.
     #[serde(default)]
-    pub evidence_references: Vec<String>,
-    pub recorded_at: u64,
-}
-
-impl AgentApprovalRequirement {
-    fn is_satisfied_by(&self, approval: &AgentApproval) -> bool {
-        if approval.role != self.role {
-            return false;
-        }
-        if approval.trust_score + f32::EPSILON < self.minimum_trust_score {
-            return false;
-        }
-        self.required_evidence_tags.iter().all(|required| {
-            approval
-                .evidence_tags
-                .iter()
-                .any(|provided| provided == required)
-        })
-    }
+    pub policy: Option<PolicyReport>,
+    /// Findings of the most recent Lint stage run.
+    #[serde(default)]
+    pub lint: Option<LintReport>,
+    /// Binary sizes and dependency counts measured by the Build stage.
+    #[serde(default)]
+    pub footprint: Option<FootprintReport>,
+    /// Test outcomes and coverage recorded by the Test stage.
+    #[serde(default)]
+    pub tests: Option<TestReport>,
+    /// Files, symbols and docs changed by the commit, generated from git.
+    #[serde(default)]
+    pub diff: Option<DiffSummary>,
+    /// Branch and pull request the commit was pushed to, for webhook-triggered runs.
+    #[serde(default)]
+    pub source: Option<PipelineSource>,
 }
 
 impl Pipeline {
@@ -136,6 +228,15 @@ impl Pipeline {
             .map(|requirement| requirement.role.clone())
             .collect()
     }
+
+    /// Names of the stages that have not completed in the current run.
+    fn remaining_stages(&self) -> Vec<String> {
+        self.stages
+            .iter()
+            .filter(|stage| stage.status != PipelineStatus::Success)
+            .map(|stage| stage.name.clone())
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -155,29 +256,8 @@ fn map_scan_status(status: &ScanStatus) -> SecurityScanStatus {
 }
 
 #[cfg(test)]
-pub struct EnvGuard {
-    key: &'static str,
-    prev: Option<String>,
-}
-
-#[cfg(test)]
-impl EnvGuard {
-    fn set(key: &'static str, value: &Path) -> Self {
-        let prev = std::env::var(key).ok();
-        std::env::set_var(key, value);
-        Self { key, prev }
-    }
-}
-
-#[cfg(test)]
-impl Drop for EnvGuard {
-    fn drop(&mut self) {
-        if let Some(ref val) = self.prev {
-            std::env::set_var(self.key, val);
-        } else {
-            std::env::remove_var(self.key);
-        }
-    }
+fn context_in(root: &Path) -> ConfigContext {
+    ConfigContext::isolated().with_workflow_root(root)
 }
 
 #[cfg(test)]
@@ -188,8 +268,7 @@ mod tests {
     #[test]
     fn validation_skips_when_scanners_disabled() {
         let workspace = tempdir().unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", workspace.path());
-        let system = CICDSystem::new();
+        let system = CICDSystem::with_context(context_in(workspace.path()));
         system.configure_workspace_root(workspace.path());
 
         let pipeline_id = system
@@ -199,7 +278,7 @@ mod tests {
             .validate(&pipeline_id)
             .expect("validation should succeed when scanners disabled");
 
-        let pipelines = system.pipelines.lock().unwrap();
+        let pipelines = system.pipelines.lock();
         let pipeline = pipelines.get(&pipeline_id).unwrap();
         assert!(!pipeline.security_scans.is_empty());
         assert!(pipeline
@@ -208,12 +287,44 @@ mod tests {
             .all(|scan| scan.status == SecurityScanStatus::Skipped));
     }
 
+    #[test]
+    fn single_host_acceptance_checks_the_runtime_graph() {
+        let repo = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let workspace = tempdir().unwrap();
+        let graph = workspace.path().join(KERNEL_RUNTIME_GRAPH);
+        std::fs::create_dir_all(graph.parent().unwrap()).unwrap();
+        std::fs::copy(repo.join(KERNEL_RUNTIME_GRAPH), &graph).unwrap();
+        let system = CICDSystem::with_context(context_in(workspace.path()));
+        system.configure_workspace_root(workspace.path());
+        let pipeline_id = system
+            .trigger_pipeline("demo".into(), "abc123".into())
+            .expect("pipeline should trigger");
+
+        let committed = repo.join("server/profiles/single_host/profile.toml");
+        system.configure_single_host_profile(committed.to_string_lossy());
+        system
+            .single_host_acceptance(&pipeline_id)
+            .expect("committed profile matches the graph");
+
+        let profile = workspace.path().join("profile.toml");
+        std::fs::write(
+            &profile,
+            "[profile]\nname = \"single_host\"\n\n[runtime_graph]\nservices = [\"gateway\"]\n",
+        )
+        .unwrap();
+        system.configure_single_host_profile(profile.to_string_lossy());
+        let err = system.single_host_acceptance(&pipeline_id).unwrap_err();
+        assert!(
+            err.contains("service gateway requires adaptive-runtime"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn validation_fails_when_secrets_detected() {
         let workspace = tempdir().unwrap();
         std::fs::write(workspace.path().join("secrets.env"), "API_TOKEN=SECRET=123").unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", workspace.path());
-        let system = CICDSystem::new();
+        let system = CICDSystem::with_context(context_in(workspace.path()));
         system.configure_workspace_root(workspace.path());
         system.configure_scanner_flags(ScannerFlags {
             syft: false,
@@ -228,7 +339,7 @@ mod tests {
         let result = system.validate(&pipeline_id);
         assert!(result.is_err());
 
-        let pipelines = system.pipelines.lock().unwrap();
+        let pipelines = system.pipelines.lock();
         let pipeline = pipelines.get(&pipeline_id).unwrap();
         assert!(pipeline
             .security_scans
@@ -238,17 +349,12 @@ mod tests {
 }
 
 impl ScannerFlags {
-    fn from_env() -> Self {
-        fn enabled(key: &str) -> bool {
-            std::env::var(key)
-                .map(|value| matches!(value.as_str(), "1" | "true" | "TRUE"))
-                .unwrap_or(false)
-        }
+    fn from_context(context: &ConfigContext) -> Self {
         Self {
-            syft: enabled("NOA_CICD_ENABLE_SYFT"),
-            grype: enabled("NOA_CICD_ENABLE_GRYPE"),
-            trivy: enabled("NOA_CICD_ENABLE_TRIVY"),
-            gitleaks: enabled("NOA_CICD_ENABLE_GITLEAKS"),
+            syft: context.flag("NOA_CICD_ENABLE_SYFT"),
+            grype: context.flag("NOA_CICD_ENABLE_GRYPE"),
+            trivy: context.flag("NOA_CICD_ENABLE_TRIVY"),
+            gitleaks: context.flag("NOA_CICD_ENABLE_GITLEAKS"),
         }
     }
 }
@@ -259,17 +365,48 @@ pub struct Stage {
     pub stage_type: PipelineStage,
     pub status: PipelineStatus,
     pub duration_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub parameters: serde_json::Value,
+    /// Retries for transient failures; a single attempt when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    /// Attempts the stage took in its latest run.
+    #[serde(default)]
+    pub attempts: u32,
+    /// Stages that must succeed before this one starts; see [`stage_graph`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Deployment {
     pub id: String,
+    #[serde(default = "default_service")]
+    pub service: String,
     pub environment: Environment,
     pub strategy: DeploymentStrategy,
     pub version: String,
     pub status: PipelineStatus,
     pub health_metrics: HealthMetrics,
     pub auto_approved: bool, // new
+    /// Whether this deployment's metrics already contributed to the learned baseline.
+    #[serde(default)]
+    pub baseline_recorded: bool,
+    /// Pipeline whose build this deployment ships, included in its evidence bundle.
+    #[serde(default)]
+    pub pipeline_id: Option<String>,
+    /// Reverse proxy route pushed for this deployment and its admin API results.
+    #[serde(default)]
+    pub route: Option<DeploymentRoute>,
+    /// Traffic step of a canary deployment; see [`canary`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canary: Option<CanaryProgress>,
+    /// Slot of a blue/green deployment; see [`blue_green`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<Slot>,
+    /// Active-slot flip made by this deployment, undone by [`CICDSystem::rollback`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub switch: Option<SlotSwitch>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -295,59 +432,185 @@ impl Default for HealthMetrics {
 
 impl HealthMetrics {
     /// Check if metrics are healthy
+    ///
+    /// The response-time comparison is skipped while no baseline has been learned yet.
     pub fn is_healthy(&self, baseline: &HealthMetrics) -> bool {
         self.error_rate < 5.0
-            && self.response_time_ms < baseline.response_time_ms * 2
+            && (baseline.response_time_ms == 0
+                || self.response_time_ms < baseline.response_time_ms * 2)
             && self.cpu_usage < 90.0
             && self.memory_usage < 90.0
     }
 }
 
 pub struct CICDSystem {
-    pipelines: Arc<Mutex<HashMap<String, Pipeline>>>,
+    pipelines: Arc<PipelineTable>,
     deployments: Arc<Mutex<HashMap<String, Deployment>>>,
-    baseline_metrics: Arc<Mutex<HashMap<Environment, HealthMetrics>>>,
+    schedules: Arc<Mutex<HashMap<String, PipelineSchedule>>>,
+    baselines: Arc<Mutex<HealthBaselines>>,
+    slos: Arc<Mutex<SloTracker>>,
     auto_approve_threshold: f32, // new
     single_host_profile: Arc<Mutex<Option<String>>>,
     instrumentation: Arc<PipelineInstrumentation>,
     scanner_flags: Arc<Mutex<ScannerFlags>>,
     workspace_root: Arc<Mutex<PathBuf>>,
+    stage_executors: Arc<StageExecutorRegistry>,
+    ownership: Arc<Mutex<Option<Arc<Ownership>>>>,
+    concurrency: Arc<Mutex<Option<Arc<ConcurrencyGovernor>>>>,
+    costs: Arc<Mutex<Option<Arc<CostLedger>>>>,
+    timeseries: Arc<Mutex<Option<Arc<TimeSeriesStore>>>>,
+    lint_runner: Arc<Mutex<Arc<dyn LintRunner>>>,
+    route_controller: Arc<Mutex<Option<Arc<dyn RouteController>>>>,
+    environment_routes: Arc<Mutex<HashMap<Environment, ReverseProxyRoute>>>,
+    canary_plan: Arc<Mutex<CanaryPlan>>,
+    traffic_shifter: Arc<Mutex<Option<Arc<dyn TrafficShifter>>>>,
+    slots: Arc<Mutex<HashMap<Environment, EnvironmentSlots>>>,
+    run_queue: Arc<Mutex<RunQueue>>,
+    namespace: Namespace,
+    quota: NamespaceQuota,
+    context: ConfigContext,
 }
 
 impl CICDSystem {
-    fn initialise(threshold: f32) -> Self {
-        let instrumentation = PipelineInstrumentation::new()
+    fn initialise(
+        threshold: f32,
+        namespace: Namespace,
+        quota: NamespaceQuota,
+        context: ConfigContext,
+    ) -> Self {
+        let instrumentation = PipelineInstrumentation::with_context(&namespace, context.clone())
             .expect("failed to initialise pipeline instrumentation for CI/CD");
         let system = Self {
-            pipelines: Arc::new(Mutex::new(HashMap::new())),
+            pipelines: Arc::new(PipelineTable::default()),
             deployments: Arc::new(Mutex::new(HashMap::new())),
-            baseline_metrics: Arc::new(Mutex::new(HashMap::new())),
+            schedules: Arc::new(Mutex::new(HashMap::new())),
+            baselines: Arc::new(Mutex::new(HealthBaselines::default())),
+            slos: Arc::new(Mutex::new(SloTracker::new())),
             auto_approve_threshold: threshold,
             single_host_profile: Arc::new(Mutex::new(Some(
                 "server/profiles/single_host/profile.toml".to_string(),
             ))),
             instrumentation: Arc::new(instrumentation),
-            scanner_flags: Arc::new(Mutex::new(ScannerFlags::from_env())),
+            scanner_flags: Arc::new(Mutex::new(ScannerFlags::from_context(&context))),
             workspace_root: Arc::new(Mutex::new(PathBuf::from("."))),
+            stage_executors: Arc::new(StageExecutorRegistry::new()),
+            ownership: Arc::new(Mutex::new(None)),
+            concurrency: Arc::new(Mutex::new(None)),
+            costs: Arc::new(Mutex::new(None)),
+            timeseries: Arc::new(Mutex::new(None)),
+            lint_runner: Arc::new(Mutex::new(Arc::new(CargoLintRunner))),
+            route_controller: Arc::new(Mutex::new(None)),
+            environment_routes: Arc::new(Mutex::new(HashMap::new())),
+            canary_plan: Arc::new(Mutex::new(CanaryPlan::default())),
+            traffic_shifter: Arc::new(Mutex::new(None)),
+            slots: Arc::new(Mutex::new(HashMap::new())),
+            run_queue: Arc::new(Mutex::new(RunQueue::default())),
+            namespace,
+            quota,
+            context,
         };
-        if let Err(err) = system.load_state_from_disk() {
+        if let Some(endpoint) = system.context.var(CADDY_ADMIN_ENV) {
+            match CaddyRouteController::new(&endpoint) {
+                Ok(controller) => system.configure_route_controller(Arc::new(controller)),
+                Err(err) => {
+                    let _ = system.emit_pipeline_event(
+                        "cicd::routing",
+                        "cicd",
+                        "pipeline.route_controller_failed",
+                        json!({ "endpoint": endpoint, "error": err }),
+                    );
+                }
+            }
+        }
+        if let Err(err) = system.reload_baselines() {
             let _ = system.emit_pipeline_event(
-                "cicd::state",
+                "cicd::baselines",
                 "cicd",
-                "pipeline.state_load_failed",
+                "pipeline.baseline_load_failed",
                 json!({ "error": err }),
             );
         }
+        match system.reload_state(RecoveryMode::Lenient) {
+            Ok(skipped) if !skipped.is_empty() => {
+                let _ = system.emit_pipeline_event(
+                    "cicd::state",
+                    "cicd",
+                    "pipeline.state_records_skipped",
+                    json!({ "skipped": skipped }),
+                );
+            }
+            Ok(_) => {}
+            Err(err) => {
+                let _ = system.emit_pipeline_event(
+                    "cicd::state",
+                    "cicd",
+                    "pipeline.state_load_failed",
+                    json!({ "error": err }),
+                );
+            }
+        }
         system
     }
 
     pub fn new() -> Self {
-        Self::initialise(0.95)
+        Self::with_context(ConfigContext::from_env())
     }
 
     /// Create CI/CD system with custom auto-approve threshold
     pub fn with_threshold(threshold: f32) -> Self {
-        Self::initialise(threshold)
+        Self::initialise(
+            threshold,
+            Namespace::default(),
+            NamespaceQuota::default(),
+            ConfigContext::from_env(),
+        )
+    }
+
+    /// Create CI/CD system whose instrumentation, scanner flags, and scan reports
+    /// come from `context` instead of the process environment
+    pub fn with_context(context: ConfigContext) -> Self {
+        Self::initialise(
+            0.95,
+            Namespace::default(),
+            NamespaceQuota::default(),
+            context,
+        )
+    }
+
+    /// Create CI/CD system confined to a registered namespace
+    ///
+    /// Pipeline state, baselines, and instrumentation are stored under the namespace and
+    /// its quota limits how many pipelines may be tracked.
+    pub fn for_namespace(
+        registry: &NamespaceRegistry,
+        namespace: Namespace,
+    ) -> Result<Self, NamespaceError> {
+        Self::for_namespace_with_context(registry, namespace, ConfigContext::from_env())
+    }
+
+    /// Create CI/CD system confined to a registered namespace under `context`
+    pub fn for_namespace_with_context(
+        registry: &NamespaceRegistry,
+        namespace: Namespace,
+        context: ConfigContext,
+    ) -> Result<Self, NamespaceError> {
+        let quota = registry.quota(&namespace)?;
+        Ok(Self::initialise(0.95, namespace, quota, context))
+    }
+
+    pub fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
+
+    pub fn context(&self) -> &ConfigContext {
+        &self.context
+    }
+
+    /// Wait until every pipeline event emitted so far is written to the event logs.
+    pub fn flush_events(&self) -> Result<(), String> {
+        self.instrumentation
+            .flush()
+            .map_err(|err| format!("telemetry error: {}", err))
     }
 
     fn emit_pipeline_event(
@@ -383,23 +646,61 @@ impl CICDSystem {
             .lock()
             .expect("workspace root lock poisoned")
             .clone();
-        root.join(PIPELINE_STATE_FILE)
+        root.join(self.namespace.scope_path(PIPELINE_STATE_FILE))
+    }
+
+    fn checkpoints(&self) -> CheckpointStore {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        CheckpointStore::new(root.join(self.namespace.scope_path(PIPELINE_CHECKPOINT_DIR)))
+    }
+
+    fn artifact_store(&self) -> ArtifactStore {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        ArtifactStore::new(root.join(self.namespace.scope_path(ARTIFACT_STORE_DIR)))
+    }
+
+    fn baseline_path(&self) -> PathBuf {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        root.join(self.namespace.scope_path(HEALTH_BASELINE_FILE))
     }
 
-    fn load_state_from_disk(&self) -> Result<(), String> {
+    fn reload_baselines(&self) -> Result<(), String> {
+        let loaded = HealthBaselines::load(&self.baseline_path(), BaselineConfig::default())?;
+        *self.baselines.lock().unwrap() = loaded;
+        Ok(())
+    }
+
+    /// Replace in-memory pipelines, deployments, schedules, blue/green slots, and SLOs with
+    /// the persisted state.
+    ///
+    /// In lenient mode records that fail to parse are dropped and returned; the
+    /// constructor loads this way so a corrupt entry cannot lose the whole history.
+    pub fn reload_state(&self, mode: RecoveryMode) -> Result<Vec<SkippedRecord>, String> {
         let path = self.state_path();
         if !path.exists() {
-            return Ok(());
+            return Ok(Vec::new());
         }
         let raw = fs::read_to_string(&path)
             .map_err(|err| format!("failed to read pipeline state: {err}"))?;
         if raw.trim().is_empty() {
-            return Ok(());
+            return Ok(Vec::new());
         }
-        let state: PersistedState = serde_json::from_str(&raw)
+        let (state, skipped) = PersistedState::parse(&raw, mode)
             .map_err(|err| format!("failed to parse pipeline state: {err}"))?;
         {
-            let mut pipelines = self.pipelines.lock().unwrap();
+            let mut pipelines = self.pipelines.lock();
             pipelines.clear();
             for pipeline in state.pipelines {
                 pipelines.insert(pipeline.id.clone(), pipeline);
@@ -412,21 +713,83 @@ impl CICDSystem {
                 deployments.insert(deployment.id.clone(), deployment);
             }
         }
-        Ok(())
+        {
+            let mut schedules = self.schedules.lock().unwrap();
+            schedules.clear();
+            for schedule in state.schedules {
+                schedules.insert(schedule.id.clone(), schedule);
+            }
+        }
+        {
+            let mut slots = self.slots.lock().expect("slots lock poisoned");
+            slots.clear();
+            for environment in state.slots {
+                slots.insert(environment.environment.clone(), environment);
+            }
+        }
+        self.slos
+            .lock()
+            .unwrap()
+            .restore(state.slo_definitions, state.slo_observations);
+        Ok(skipped)
+    }
+
+    /// Check the workspace state of this system's namespace for broken files, and
+    /// fix what was found when `remediate` is set.
+    pub fn doctor(&self, remediate: bool) -> DoctorReport {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let doctor = Doctor::new(root).with_namespace(self.namespace.clone());
+        if remediate {
+            doctor.remediate()
+        } else {
+            doctor.diagnose()
+        }
+    }
+
+    fn deployment(&self, deployment_id: &str) -> Result<Deployment, String> {
+        self.deployments
+            .lock()
+            .unwrap()
+            .get(deployment_id)
+            .cloned()
+            .ok_or_else(|| format!("Deployment not found: {}", deployment_id))
     }
 
     fn persist_state(&self) -> Result<(), String> {
-        let pipelines: Vec<Pipeline> = {
-            let pipelines = self.pipelines.lock().unwrap();
-            pipelines.values().cloned().collect()
-        };
+        let pipelines: Vec<Pipeline> = self.pipelines.snapshot().pipelines().cloned().collect();
         let deployments: Vec<Deployment> = {
             let deployments = self.deployments.lock().unwrap();
             deployments.values().cloned().collect()
         };
+        let schedules: Vec<PipelineSchedule> = {
+            let schedules = self.schedules.lock().unwrap();
+            let mut schedules: Vec<_> = schedules.values().cloned().collect();
+            schedules.sort_by(|a, b| a.id.cmp(&b.id));
+            schedules
+        };
+        let slots: Vec<EnvironmentSlots> = self
+            .slots
+            .lock()
+            .expect("slots lock poisoned")
+            .values()
+            .cloned()
+            .collect();
+        let (slo_definitions, slo_observations) = {
+            let slos = self.slos.lock().unwrap();
+            (slos.definitions().to_vec(), slos.observations().to_vec())
+        };
         let state = PersistedState {
+            version: PIPELINE_STATE_VERSION,
             pipelines,
             deployments,
+            schedules,
+            slots,
+            slo_definitions,
+            slo_observations,
         };
         let payload = serde_json::to_string_pretty(&state)
             .map_err(|err| format!("failed to serialise pipeline state: {err}"))?;
@@ -440,6 +803,31 @@ impl CICDSystem {
         Ok(())
     }
 
+    /// Scheduled pipeline triggers, sorted by id. See [`trigger::PipelineScheduler`].
+    pub fn schedules(&self) -> Vec<PipelineSchedule> {
+        let mut schedules: Vec<_> = self.schedules.lock().unwrap().values().cloned().collect();
+        schedules.sort_by(|a, b| a.id.cmp(&b.id));
+        schedules
+    }
+
+    /// Store a schedule, replacing one with the same id, and persist the state.
+    pub fn save_schedule(&self, schedule: PipelineSchedule) -> Result<(), String> {
+        self.schedules
+            .lock()
+            .unwrap()
+            .insert(schedule.id.clone(), schedule);
+        self.persist_state()
+    }
+
+    /// Remove a schedule, returning whether it existed.
+    pub fn remove_schedule(&self, id: &str) -> Result<bool, String> {
+        let removed = self.schedules.lock().unwrap().remove(id).is_some();
+        if removed {
+            self.persist_state()?;
+        }
+        Ok(removed)
+    }
+
     /// Register the single-host profile manifest used for acceptance tests.
     pub fn configure_single_host_profile<P: Into<String>>(&self, profile_path: P) {
         let mut guard = self
@@ -449,13 +837,23 @@ impl CICDSystem {
         *guard = Some(profile_path.into());
     }
 
-    /// Override the workspace root used by offline scanners.
+    /// Override the workspace root used by offline scanners and reload learned baselines.
     pub fn configure_workspace_root<P: Into<PathBuf>>(&self, root: P) {
-        let mut guard = self
-            .workspace_root
-            .lock()
-            .expect("workspace root lock poisoned");
-        *guard = root.into();
+        {
+            let mut guard = self
+                .workspace_root
+                .lock()
+                .expect("workspace root lock poisoned");
+            *guard = root.into();
+        }
+        if let Err(err) = self.reload_baselines() {
+            let _ = self.emit_pipeline_event(
+                "cicd::baselines",
+                "cicd",
+                "pipeline.baseline_load_failed",
+                json!({ "error": err }),
+            );
+        }
     }
 
     /// Enable or disable specific offline scanners.
@@ -467,103 +865,423 @@ impl CICDSystem {
         *guard = flags;
     }
 
-    /// Trigger a new pipeline (can be triggered by CRC)
-    pub fn trigger_pipeline(&self, name: String, commit_sha: String) -> Result<String, String> {
-        let id = format!("pipeline_{}", uuid::Uuid::new_v4());
-
-        let pipeline = Pipeline {
-            id: id.clone(),
-            name,
-            status: PipelineStatus::Pending,
-            stages: vec![
-                Stage {
-                    name: "validate".to_string(),
-                    stage_type: PipelineStage::Validate,
-                    status: PipelineStatus::Pending,
-                    duration_ms: None,
-                },
-                Stage {
-                    name: "build".to_string(),
-                    stage_type: PipelineStage::Build,
-                    status: PipelineStatus::Pending,
-                    duration_ms: None,
-                },
-                Stage {
-                    name: "test".to_string(),
-                    stage_type: PipelineStage::Test,
-                    status: PipelineStatus::Pending,
-                    duration_ms: None,
-                },
-                Stage {
-                    name: "single_host_acceptance".to_string(),
-                    stage_type: PipelineStage::SingleHostAcceptance,
-                    status: PipelineStatus::Pending,
-                    duration_ms: None,
-                },
-                Stage {
-                    name: "deploy".to_string(),
-                    stage_type: PipelineStage::Deploy,
-                    status: PipelineStatus::Pending,
-                    duration_ms: None,
-                },
-            ],
-            commit_sha,
-            triggered_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-            crc_job_id: None,
-            auto_approved: false,
-            ai_confidence: 0.0,
-            diff_summary: None,
-            approvals_required: Vec::new(),
-            approvals_granted: Vec::new(),
-            security_scans: Vec::new(),
-        };
-        let metadata = json!({
-            "name": pipeline.name.clone(),
-            "commit_sha": pipeline.commit_sha.clone(),
-            "triggered_at": pipeline.triggered_at,
-        });
+    /// Limit concurrent pipeline executions with a governor shared across the host.
+    ///
+    /// The namespace's `max_concurrent_runs` quota, when set, becomes its limit, and its
+    /// `scheduling_weight` its share of the queue. Pipelines whose spec sets
+    /// `priority: critical` queue ahead of normal runs.
+    pub fn configure_concurrency(&self, governor: Arc<ConcurrencyGovernor>) {
+        if self.quota.max_concurrent_runs.is_some() {
+            governor.set_namespace_limit(&self.namespace, self.quota.max_concurrent_runs);
+        }
+        if self.quota.scheduling_weight.is_some() {
+            governor.set_namespace_weight(&self.namespace, self.quota.scheduling_weight);
+        }
+        let mut guard = self.concurrency.lock().expect("concurrency lock poisoned");
+        *guard = Some(governor);
+    }
 
-        let mut pipelines = self.pipelines.lock().unwrap();
-        pipelines.insert(id.clone(), pipeline);
-        drop(pipelines);
+    /// Charge executed stages to `ledger` under this system's namespace.
+    ///
+    /// The account identity is `pipeline:<name>`. A stage is charged its duration times
+    /// the CPU cores it reserved (one when it reserved none), and GPU-seconds when it
+    /// reserved VRAM.
+    pub fn configure_cost_accounting(&self, ledger: Arc<CostLedger>) {
+        let mut guard = self.costs.lock().expect("cost ledger lock poisoned");
+        *guard = Some(ledger);
+    }
 
-        self.persist_state()?;
-        self.emit_pipeline_event(&id, "cicd", "pipeline.triggered", metadata)?;
+    /// Keep deployment health and pipeline KPIs in `store` (see [`history`]).
+    ///
+    /// Environments without learned baseline samples are seeded from the store, and
+    /// SLOs start from the deployment health recorded within their window.
+    pub fn configure_timeseries(&self, store: Arc<TimeSeriesStore>) -> Result<(), String> {
+        let now_ms = unix_now_ms();
+        {
+            let mut baselines = self.baselines.lock().unwrap();
+            let mut seeded = false;
+            for environment in [
+                Environment::Development,
+                Environment::Staging,
+                Environment::Production,
+            ] {
+                if baselines.sample_count(&environment) > 0 {
+                    continue;
+                }
+                let samples = history::health_history(
+                    &store,
+                    history::BASELINE_SERIES,
+                    &history::baseline_labels(&environment),
+                    0,
+                    now_ms + 1,
+                    now_ms,
+                )
+                .map_err(|err| format!("failed to read baseline history: {err}"))?;
+                for (_, metrics) in samples {
+                    baselines.record(&environment, metrics);
+                    seeded = true;
+                }
+            }
+            if seeded {
+                baselines.save(&self.baseline_path())?;
+            }
+        }
+        let definitions = self.slos.lock().unwrap().definitions().to_vec();
+        for definition in &definitions {
+            self.backfill_slo(&store, definition, now_ms)?;
+        }
+        *self.timeseries.lock().expect("time-series lock poisoned") = Some(store);
+        Ok(())
+    }
 
-        Ok(id)
+    fn timeseries(&self) -> Option<Arc<TimeSeriesStore>> {
+        self.timeseries
+            .lock()
+            .expect("time-series lock poisoned")
+            .clone()
     }
 
-    /// Trigger pipeline from CRC (with AI confidence)
-    pub fn trigger_from_crc(
+    /// Seed an SLO's observations with the deployment health recorded in its window.
+    fn backfill_slo(
         &self,
-        name: String,
-        commit_sha: String,
-        crc_job_id: String,
-        ai_confidence: f32,
-    ) -> Result<String, String> {
-        let id = self.trigger_pipeline(name, commit_sha)?;
+        store: &TimeSeriesStore,
+        definition: &SloDefinition,
+        now_ms: u64,
+    ) -> Result<(), String> {
+        let samples = history::health_history(
+            store,
+            history::DEPLOYMENT_SERIES,
+            &history::deployment_labels(&definition.service, &definition.environment),
+            now_ms.saturating_sub(definition.window_secs.saturating_mul(1000)),
+            now_ms + 1,
+            now_ms,
+        )
+        .map_err(|err| format!("failed to read deployment history: {err}"))?;
+        self.slos.lock().unwrap().backfill(
+            &definition.service,
+            &definition.environment,
+            samples
+                .into_iter()
+                .map(|(timestamp_ms, metrics)| (timestamp_ms / 1000, metrics)),
+        );
+        Ok(())
+    }
 
-        // Update with CRC info
-        let event = {
-            let mut pipelines = self.pipelines.lock().unwrap();
-            if let Some(pipeline) = pipelines.get_mut(&id) {
-                pipeline.crc_job_id = Some(crc_job_id);
-                pipeline.ai_confidence = ai_confidence;
-                pipeline.auto_approved = ai_confidence >= self.auto_approve_threshold;
+    /// Append samples to the configured store. Failures are reported as events and
+    /// do not fail the caller.
+    fn append_history(&self, subject: &str, samples: Vec<Sample>) {
+        let Some(store) = self.timeseries() else {
+            return;
+        };
+        if let Err(err) = store.append_all(&samples) {
+            let _ = self.emit_pipeline_event(
+                subject,
+                "cicd",
+                "pipeline.history_append_failed",
+                json!({ "error": err.to_string() }),
+            );
+        }
+    }
 
-                if pipeline.auto_approved {
-                    pipeline.status = PipelineStatus::AutoApproved;
-                    Some((
-                        "pipeline.auto_approved",
-                        json!({
-                            "ai_confidence": ai_confidence,
-                            "threshold": self.auto_approve_threshold,
-                            "source": "crc",
-                        }),
-                    ))
+    /// Queue position of a pipeline waiting for a concurrency slot.
+    pub fn pipeline_queue_position(&self, pipeline_id: &str) -> Option<usize> {
+        let governor = self
+            .concurrency
+            .lock()
+            .expect("concurrency lock poisoned")
+            .clone()?;
+        governor.queue_position(&self.pipeline_run_key(pipeline_id))
+    }
+
+    fn pipeline_run_key(&self, pipeline_id: &str) -> String {
+        format!("pipeline:{}/{}", self.namespace, pipeline_id)
+    }
+
+    /// Running and queued runs, with queue metrics per namespace, of the governor
+    /// this system shares.
+    pub fn concurrency_snapshot(&self) -> Option<ConcurrencySnapshot> {
+        let governor = self
+            .concurrency
+            .lock()
+            .expect("concurrency lock poisoned")
+            .clone()?;
+        Some(governor.snapshot())
+    }
+
+    fn acquire_pipeline_slot(&self, pipeline_id: &str) -> Option<ConcurrencyPermit> {
+        let governor = self
+            .concurrency
+            .lock()
+            .expect("concurrency lock poisoned")
+            .clone()?;
+        let priority = {
+            let pipelines = self.pipelines.lock();
+            pipelines
+                .get(pipeline_id)
+                .and_then(|pipeline| pipeline.spec.as_ref())
+                .map(|spec| spec.priority)
+                .unwrap_or_default()
+        };
+        Some(governor.acquire_with_priority(
+            &self.namespace,
+            &self.pipeline_run_key(pipeline_id),
+            priority,
+            |position| {
+                let _ = self.emit_pipeline_event(
+                    pipeline_id,
+                    "cicd",
+                    "pipeline.queued",
+                    json!({ "position": position }),
+                );
+            },
+        ))
+    }
+
+    /// Replace the runner producing rustfmt and clippy output for the Lint stage.
+    pub fn configure_lint_runner(&self, runner: Arc<dyn LintRunner>) {
+        let mut guard = self.lint_runner.lock().expect("lint runner lock poisoned");
+        *guard = runner;
+    }
+
+    /// Push deploy routes through `controller`, e.g. a [`CaddyRouteController`].
+    pub fn configure_route_controller(&self, controller: Arc<dyn RouteController>) {
+        let mut guard = self
+            .route_controller
+            .lock()
+            .expect("route controller lock poisoned");
+        *guard = Some(controller);
+    }
+
+    /// Route template applied whenever a service is deployed to `environment`.
+    pub fn configure_environment_route(&self, environment: Environment, route: ReverseProxyRoute) {
+        self.environment_routes
+            .lock()
+            .expect("environment routes lock poisoned")
+            .insert(environment, route);
+    }
+
+    fn route_controller(&self) -> Option<Arc<dyn RouteController>> {
+        self.route_controller
+            .lock()
+            .expect("route controller lock poisoned")
+            .clone()
+    }
+
+    fn environment_route(&self, environment: &Environment) -> Option<ReverseProxyRoute> {
+        self.environment_routes
+            .lock()
+            .expect("environment routes lock poisoned")
+            .get(environment)
+            .cloned()
+    }
+
+    /// Resolve owner approvals from the given rules instead of the workspace CODEOWNERS file.
+    pub fn configure_ownership(&self, ownership: Ownership) {
+        let mut guard = self.ownership.lock().expect("ownership lock poisoned");
+        *guard = Some(Arc::new(ownership));
+    }
+
+    fn ownership(&self) -> Result<Option<Arc<Ownership>>, String> {
+        if let Some(ownership) = self
+            .ownership
+            .lock()
+            .expect("ownership lock poisoned")
+            .clone()
+        {
+            return Ok(Some(ownership));
+        }
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let codeowners = CodeOwners::discover(&root)
+            .map_err(|err| format!("failed to load CODEOWNERS: {err}"))?;
+        Ok(codeowners.map(|codeowners| Arc::new(Ownership::new(codeowners, Default::default()))))
+    }
+
+    /// Trigger a new pipeline (can be triggered by CRC)
+    ///
+    /// Uses the `pipeline.toml`/`pipeline.yaml` definition at the workspace root when present.
+    pub fn trigger_pipeline(&self, name: String, commit_sha: String) -> Result<String, String> {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        self.trigger_pipeline_in(name, commit_sha, &root)
+    }
+
+    /// Trigger a new pipeline whose named stages use the given retry policies instead of
+    /// the ones declared in the pipeline definition.
+    pub fn trigger_pipeline_with_retries(
+        &self,
+        name: String,
+        commit_sha: String,
+        retries: &HashMap<String, RetryPolicy>,
+    ) -> Result<String, String> {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        self.trigger_in(name, commit_sha, &root, retries)
+    }
+
+    /// Trigger a new pipeline for the repository or drop rooted at `source_root`.
+    pub fn trigger_pipeline_in(
+        &self,
+        name: String,
+        commit_sha: String,
+        source_root: &Path,
+    ) -> Result<String, String> {
+        self.trigger_in(name, commit_sha, source_root, &HashMap::new())
+    }
+
+    fn trigger_in(
+        &self,
+        name: String,
+        commit_sha: String,
+        source_root: &Path,
+        retries: &HashMap<String, RetryPolicy>,
+    ) -> Result<String, String> {
+        {
+            let pipelines = self.pipelines.lock();
+            NamespaceQuota::check(
+                &self.namespace,
+                "pipelines",
+                self.quota.max_pipelines,
+                pipelines.len(),
+            )
+            .map_err(|err| err.to_string())?;
+        }
+        let id = format!("pipeline_{}", uuid::Uuid::new_v4());
+        let spec = PipelineSpec::discover(source_root).map_err(|err| err.to_string())?;
+        if let Some((path, spec)) = &spec {
+            let missing: Vec<&str> = spec
+                .plugin_stage_types()
+                .into_iter()
+                .filter(|stage_type| !self.stage_executors.contains(stage_type))
+                .collect();
+            if !missing.is_empty() {
+                return Err(format!(
+                    "{} references unregistered stage types: {}",
+                    path.display(),
+                    missing.join(", ")
+                ));
+            }
+        }
+
+        let (mut stages, approvals_required, spec_path, spec) = match spec {
+            Some((path, spec)) => (
+                spec.stages
+                    .iter()
+                    .map(|stage| Stage {
+                        name: stage.name.clone(),
+                        stage_type: stage.stage_type.clone(),
+                        status: PipelineStatus::Pending,
+                        duration_ms: None,
+                        parameters: stage.parameters.clone(),
+                        retry: stage.retry.clone(),
+                        attempts: 0,
+                        depends_on: stage.depends_on.clone(),
+                    })
+                    .collect(),
+                spec.approvals.clone(),
+                Some(path.display().to_string()),
+                Some(spec),
+            ),
+            None => (default_stages(), Vec::new(), None, None),
+        };
+        for (stage_name, policy) in retries {
+            let issues = policy.issues(stage_name);
+            if !issues.is_empty() {
+                return Err(issues.join("; "));
+            }
+            let stage = stages
+                .iter_mut()
+                .find(|stage| &stage.name == stage_name)
+                .ok_or_else(|| format!("Retry override for unknown stage: {}", stage_name))?;
+            stage.retry = Some(policy.clone());
+        }
+
+        let pipeline = Pipeline {
+            id: id.clone(),
+            name,
+            status: if approvals_required.is_empty() {
+                PipelineStatus::Pending
+            } else {
+                PipelineStatus::AgentReview
+            },
+            stages,
+            commit_sha,
+            triggered_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            crc_job_id: None,
+            auto_approved: false,
+            ai_confidence: 0.0,
+            diff_summary: None,
+            approvals_required,
+            approvals_granted: Vec::new(),
+            security_scans: Vec::new(),
+            spec_path,
+            spec,
+            policy: None,
+            lint: None,
+            footprint: None,
+            tests: None,
+            diff: None,
+            source: None,
+        };
+        let metadata = json!({
+            "name": pipeline.name.clone(),
+            "commit_sha": pipeline.commit_sha.clone(),
+            "triggered_at": pipeline.triggered_at,
+            "spec_path": pipeline.spec_path.clone(),
+            "stages": pipeline.stages.iter().map(|stage| stage.name.clone()).collect::<Vec<_>>(),
+            "retry_overrides": retries,
+        });
+
+        let mut pipelines = self.pipelines.lock();
+        pipelines.insert(id.clone(), pipeline);
+        drop(pipelines);
+
+        self.persist_state()?;
+        self.emit_pipeline_event(&id, "cicd", "pipeline.triggered", metadata)?;
+
+        Ok(id)
+    }
+
+    /// Trigger pipeline from CRC (with AI confidence)
+    pub fn trigger_from_crc(
+        &self,
+        name: String,
+        commit_sha: String,
+        crc_job_id: String,
+        ai_confidence: f32,
+    ) -> Result<String, String> {
+        let id = self.trigger_pipeline(name, commit_sha)?;
+
+        // Update with CRC info
+        let event = {
+            let mut pipelines = self.pipelines.lock();
+            if let Some(pipeline) = pipelines.get_mut(&id) {
+                pipeline.crc_job_id = Some(crc_job_id);
+                pipeline.ai_confidence = ai_confidence;
+                pipeline.auto_approved = ai_confidence >= self.auto_approve_threshold;
+
+                if pipeline.auto_approved {
+                    pipeline.status = PipelineStatus::AutoApproved;
+                    Some((
+                        "pipeline.auto_approved",
+                        json!({
+                            "ai_confidence": ai_confidence,
+                            "threshold": self.auto_approve_threshold,
+                            "source": "crc",
+                        }),
+                    ))
                 } else {
                     pipeline.status = PipelineStatus::AgentReview;
                     let outstanding_roles = pipeline.outstanding_agent_roles();
@@ -589,6 +1307,56 @@ impl CICDSystem {
         Ok(id)
     }
 
+    /// Trigger a pipeline for a commit pushed to a forge and record its branch and pull
+    /// request.
+    pub fn trigger_pipeline_from_source(
+        &self,
+        name: String,
+        commit_sha: String,
+        source: PipelineSource,
+    ) -> Result<String, String> {
+        let id = self.trigger_pipeline(name, commit_sha)?;
+        if let Some(pipeline) = self.pipelines.lock().get_mut(&id) {
+            pipeline.source = Some(source.clone());
+        }
+        self.persist_state()?;
+        self.emit_pipeline_event(
+            &id,
+            "cicd",
+            "pipeline.source_attached",
+            serde_json::to_value(&source).map_err(|err| err.to_string())?,
+        )?;
+        Ok(id)
+    }
+
+    /// Verify a forge webhook against the configured secret and trigger a pipeline for
+    /// pushes and pull requests. The pipeline is named `pipeline`, or after the repository.
+    pub fn handle_webhook(
+        &self,
+        delivery: &WebhookDelivery,
+        body: &[u8],
+        pipeline: Option<&str>,
+    ) -> Result<WebhookOutcome, WebhookError> {
+        let secret = self
+            .context
+            .var(webhook::WEBHOOK_SECRET_ENV)
+            .filter(|secret| !secret.is_empty())
+            .ok_or(WebhookError::NotConfigured)?;
+        delivery.verify(&secret, body)?;
+        match delivery.parse(body)? {
+            WebhookEvent::Trigger { commit_sha, source } => {
+                let name = pipeline
+                    .unwrap_or_else(|| webhook::pipeline_name(&source.repository))
+                    .to_string();
+                let pipeline_id = self
+                    .trigger_pipeline_from_source(name, commit_sha, source)
+                    .map_err(WebhookError::Trigger)?;
+                Ok(WebhookOutcome::Triggered { pipeline_id })
+            }
+            WebhookEvent::Ignored { reason } => Ok(WebhookOutcome::Ignored { reason }),
+        }
+    }
+
     pub fn trigger_doc_refresh_pipeline(
         &self,
         commit_sha: String,
@@ -611,18 +1379,30 @@ impl CICDSystem {
                     stage_type: PipelineStage::Validate,
                     status: PipelineStatus::Pending,
                     duration_ms: None,
+                    parameters: serde_json::Value::Null,
+                    retry: None,
+                    attempts: 0,
+                    depends_on: Vec::new(),
                 },
                 Stage {
                     name: "docs-refresh".to_string(),
                     stage_type: PipelineStage::DocsRefresh,
                     status: PipelineStatus::Pending,
                     duration_ms: None,
+                    parameters: serde_json::Value::Null,
+                    retry: None,
+                    attempts: 0,
+                    depends_on: Vec::new(),
                 },
                 Stage {
                     name: "verify".to_string(),
                     stage_type: PipelineStage::Verify,
                     status: PipelineStatus::Pending,
                     duration_ms: None,
+                    parameters: serde_json::Value::Null,
+                    retry: None,
+                    attempts: 0,
+                    depends_on: Vec::new(),
                 },
             ],
             commit_sha,
@@ -637,6 +1417,14 @@ impl CICDSystem {
             approvals_required,
             approvals_granted: Vec::new(),
             security_scans: Vec::new(),
+            spec_path: None,
+            spec: None,
+            policy: None,
+            lint: None,
+            footprint: None,
+            tests: None,
+            diff: None,
+            source: None,
         };
         let metadata = json!({
             "commit_sha": pipeline.commit_sha.clone(),
@@ -653,7 +1441,7 @@ impl CICDSystem {
         });
 
         {
-            let mut pipelines = self.pipelines.lock().unwrap();
+            let mut pipelines = self.pipelines.lock();
             pipelines.insert(id.clone(), pipeline);
         }
 
@@ -663,6 +1451,173 @@ impl CICDSystem {
         Ok(id)
     }
 
+    /// Trigger a docs-refresh pipeline for `base..head` with its diff summary generated
+    /// from git instead of supplied by the caller.
+    pub fn trigger_doc_refresh_for_range(
+        &self,
+        base: &str,
+        head: &str,
+        approvals_required: Vec<AgentApprovalRequirement>,
+    ) -> Result<String, String> {
+        let summary = self.generate_diff_summary(base, head)?;
+        let id = self.trigger_doc_refresh_pipeline(
+            head.to_string(),
+            summary.headline(),
+            approvals_required,
+        )?;
+        self.attach_diff_summary(&id, summary)?;
+        Ok(id)
+    }
+
+    /// Summarise `base..<pipeline commit>` and attach the summary to the pipeline.
+    pub fn summarize_diff(&self, pipeline_id: &str, base: &str) -> Result<DiffSummary, String> {
+        let head = {
+            let pipelines = self.pipelines.lock();
+            pipelines
+                .get(pipeline_id)
+                .map(|pipeline| pipeline.commit_sha.clone())
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?
+        };
+        let summary = self.generate_diff_summary(base, &head)?;
+        self.attach_diff_summary(pipeline_id, summary.clone())?;
+        Ok(summary)
+    }
+
+    /// Summarise `base..head` in the workspace repository, resolving symbols against the
+    /// workspace symbol graph when one has been indexed.
+    pub fn generate_diff_summary(&self, base: &str, head: &str) -> Result<DiffSummary, String> {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let store = root.join(DEFAULT_STORE_DIR);
+        let graph = if store.join("nodes.jsonl").exists() {
+            Some(
+                SymbolGraph::load(&store)
+                    .map_err(|err| format!("failed to load symbol graph: {err}"))?,
+            )
+        } else {
+            None
+        };
+        DiffSummary::generate(&root, base, head, graph.as_ref())
+    }
+
+    /// Attach a diff summary to a pipeline. Its headline becomes the pipeline's
+    /// `diff_summary`, the full report is stored with the evidence, and the owners of
+    /// the paths and symbols it touches are required to approve.
+    pub fn attach_diff_summary(
+        &self,
+        pipeline_id: &str,
+        summary: DiffSummary,
+    ) -> Result<(), String> {
+        {
+            let mut pipelines = self.pipelines.lock();
+            let pipeline = pipelines
+                .get_mut(pipeline_id)
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            pipeline.diff_summary = Some(summary.headline());
+            pipeline.diff = Some(summary.clone());
+        }
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let dir = root
+            .join(self.namespace.scope_path(PIPELINE_REPORTS_DIR))
+            .join(pipeline_id);
+        let (json_path, markdown_path) = summary.write(&dir)?;
+
+        self.persist_state()?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.diff_summarized",
+            json!({
+                "base": summary.base,
+                "head": summary.head,
+                "files": summary.files.len(),
+                "insertions": summary.insertions(),
+                "deletions": summary.deletions(),
+                "symbols": summary.symbols.len(),
+                "docs": summary.docs,
+                "json": json_path,
+                "markdown": markdown_path,
+            }),
+        )?;
+        self.require_owner_approvals(pipeline_id, &summary.touched())?;
+        Ok(())
+    }
+
+    /// Trigger a pipeline and require approval from the owners of everything it touches.
+    ///
+    /// Each touched entry is a repository-relative path or a symbol stable id.
+    pub fn trigger_pipeline_for_changes(
+        &self,
+        name: String,
+        commit_sha: String,
+        touched: &[String],
+    ) -> Result<String, String> {
+        let id = self.trigger_pipeline(name, commit_sha)?;
+        self.require_owner_approvals(&id, touched)?;
+        Ok(id)
+    }
+
+    /// Add an approval requirement for every owner of the touched paths or symbols that the
+    /// pipeline does not already require, returning the requirements that were added.
+    pub fn require_owner_approvals(
+        &self,
+        pipeline_id: &str,
+        touched: &[String],
+    ) -> Result<Vec<AgentApprovalRequirement>, String> {
+        let Some(ownership) = self.ownership()? else {
+            return Ok(Vec::new());
+        };
+        let owners = ownership.owners_for(touched);
+
+        let added = {
+            let mut pipelines = self.pipelines.lock();
+            let pipeline = pipelines
+                .get_mut(pipeline_id)
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            let added: Vec<AgentApprovalRequirement> = owners
+                .into_iter()
+                .filter(|owner| {
+                    !pipeline
+                        .approvals_required
+                        .iter()
+                        .any(|req| &req.role == owner)
+                })
+                .map(|owner| AgentApprovalRequirement {
+                    role: owner,
+                    minimum_trust_score: OWNER_APPROVAL_TRUST_SCORE,
+                    required_evidence_tags: Vec::new(),
+                })
+                .collect();
+            pipeline.approvals_required.extend(added.iter().cloned());
+            if !added.is_empty() && pipeline.status == PipelineStatus::Pending {
+                pipeline.status = PipelineStatus::AgentReview;
+            }
+            added
+        };
+        if added.is_empty() {
+            return Ok(added);
+        }
+
+        self.persist_state()?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.owner_approvals_required",
+            json!({
+                "touched": touched,
+                "roles": added.iter().map(|req| req.role.clone()).collect::<Vec<_>>(),
+            }),
+        )?;
+        Ok(added)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn register_agent_approval(
         &self,
@@ -687,7 +1642,7 @@ impl CICDSystem {
         };
 
         let (status, event_type, metadata) = {
-            let mut pipelines = self.pipelines.lock().unwrap();
+            let mut pipelines = self.pipelines.lock();
             let pipeline = pipelines
                 .get_mut(pipeline_id)
                 .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
@@ -807,37 +1762,248 @@ impl CICDSystem {
 
     /// Execute pipeline with full automation
     pub fn execute_pipeline(&self, pipeline_id: &str) -> Result<(), String> {
-        let stages = {
-            let pipelines = self.pipelines.lock().unwrap();
-            let pipeline = pipelines
-                .get(pipeline_id)
-                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
-            if matches!(
-                pipeline.status,
-                PipelineStatus::AgentReview | PipelineStatus::AgentEscalated
-            ) {
-                return Err("Pipeline requires agent approval before execution".to_string());
-            }
-            if !pipeline.agent_requirements_satisfied() {
-                return Err("Pipeline is waiting for agent approvals".to_string());
-            }
+        self.run_pipeline(pipeline_id, false)
+    }
+
+    /// Stage progress of a pipeline, and its queue position while it waits for a
+    /// [`worker_pool::PipelineWorkerPool`] worker.
+    pub fn get_pipeline_progress(&self, pipeline_id: &str) -> Option<PipelineProgress> {
+        let queue_position = self
+            .run_queue
+            .lock()
+            .expect("run queue lock poisoned")
+            .position(pipeline_id);
+        let pipelines = self.pipelines.snapshot();
+        let pipeline = pipelines.get(pipeline_id)?;
+        let stages_total = pipeline.stages.len();
+        let stages_completed = pipeline
+            .stages
+            .iter()
+            .filter(|stage| stage.status == PipelineStatus::Success)
+            .count();
+        let estimated_remaining_ms = pipeline
+            .stages
+            .iter()
+            .filter(|stage| stage.status != PipelineStatus::Success)
+            .map(|stage| {
+                dry_run::stage_history(
+                    &stage.stage_type,
+                    pipelines
+                        .pipelines()
+                        .filter(|recorded| recorded.id != pipeline.id)
+                        .flat_map(|recorded| recorded.stages.iter()),
+                )
+                .0
+            })
+            .sum::<Option<u64>>();
+        Some(PipelineProgress {
+            pipeline_id: pipeline.id.clone(),
+            status: pipeline.status.clone(),
+            queue_position,
+            stages_total,
+            stages_completed,
+            current_stage: pipeline
+                .stages
+                .iter()
+                .find(|stage| stage.status == PipelineStatus::Running)
+                .map(|stage| stage.name.clone()),
+            fraction_complete: if stages_total == 0 {
+                1.0
+            } else {
+                stages_completed as f64 / stages_total as f64
+            },
+            estimated_remaining_ms,
+        })
+    }
+
+    /// Queue a pipeline for a worker pool; it may only be submitted once at a time.
+    fn enqueue_run(&self, pipeline_id: &str) -> Result<(), String> {
+        if !self.pipelines.lock().contains_key(pipeline_id) {
+            return Err(format!("Pipeline not found: {}", pipeline_id));
+        }
+        let position = {
+            let mut queue = self.run_queue.lock().expect("run queue lock poisoned");
+            queue.enqueue(pipeline_id)?;
+            queue.position(pipeline_id)
+        };
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.queued",
+            json!({ "queue_position": position }),
+        )
+    }
+
+    fn start_run(&self, pipeline_id: &str) {
+        self.run_queue
+            .lock()
+            .expect("run queue lock poisoned")
+            .start(pipeline_id);
+    }
+
+    fn finish_run(&self, pipeline_id: &str) {
+        self.run_queue
+            .lock()
+            .expect("run queue lock poisoned")
+            .finish(pipeline_id);
+    }
+
+    /// Stop a pipeline before its next stage. A running stage finishes first; a
+    /// pipeline that has not started yet is held until resumed.
+    pub fn pause_pipeline(&self, pipeline_id: &str) -> Result<(), String> {
+        self.halt_pipeline(pipeline_id, PipelineStatus::Paused, "pipeline.paused")
+    }
+
+    /// Abort a pipeline before its next stage. A running stage finishes first; the
+    /// remaining stages never run.
+    pub fn cancel_pipeline(&self, pipeline_id: &str) -> Result<(), String> {
+        self.halt_pipeline(pipeline_id, PipelineStatus::Cancelled, "pipeline.cancelled")
+    }
+
+    /// Continue a paused pipeline from the first stage that has not completed.
+    pub fn resume_pipeline(&self, pipeline_id: &str) -> Result<(), String> {
+        let remaining = {
+            let pipelines = self.pipelines.lock();
+            let pipeline = pipelines
+                .get(pipeline_id)
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            if pipeline.status != PipelineStatus::Paused {
+                return Err(format!(
+                    "Pipeline {} is not paused (status {:?})",
+                    pipeline_id, pipeline.status
+                ));
+            }
+            pipeline.remaining_stages()
+        };
+        self.update_pipeline_status(pipeline_id, PipelineStatus::Pending)?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.resumed",
+            json!({ "remaining_stages": remaining }),
+        )?;
+        self.run_pipeline(pipeline_id, true)
+    }
+
+    fn halt_pipeline(
+        &self,
+        pipeline_id: &str,
+        status: PipelineStatus,
+        event_type: &str,
+    ) -> Result<(), String> {
+        let (previous, remaining) = {
+            let pipelines = self.pipelines.lock();
+            let pipeline = pipelines
+                .get(pipeline_id)
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            if pipeline.status == status {
+                return Ok(());
+            }
+            if matches!(
+                pipeline.status,
+                PipelineStatus::Success
+                    | PipelineStatus::Failed
+                    | PipelineStatus::RolledBack
+                    | PipelineStatus::Cancelled
+            ) {
+                return Err(format!(
+                    "Pipeline {} has already finished (status {:?})",
+                    pipeline_id, pipeline.status
+                ));
+            }
+            (pipeline.status.clone(), pipeline.remaining_stages())
+        };
+        self.update_pipeline_status(pipeline_id, status)?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            event_type,
+            json!({
+                "previous": previous,
+                "remaining_stages": remaining,
+            }),
+        )
+    }
+
+    /// Status of a pipeline that was paused or cancelled while it ran.
+    fn halted_status(&self, pipeline_id: &str) -> Option<PipelineStatus> {
+        let pipelines = self.pipelines.lock();
+        pipelines
+            .get(pipeline_id)
+            .map(|pipeline| pipeline.status.clone())
+            .filter(|status| matches!(status, PipelineStatus::Paused | PipelineStatus::Cancelled))
+    }
+
+    /// Run the pipeline's stages, skipping those already completed when resuming.
+    /// Pause and cancel requests are honoured between stages.
+    fn run_pipeline(&self, pipeline_id: &str, resume: bool) -> Result<(), String> {
+        let stages = {
+            let mut pipelines = self.pipelines.lock();
+            let pipeline = pipelines
+                .get_mut(pipeline_id)
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            match pipeline.status {
+                PipelineStatus::AgentReview | PipelineStatus::AgentEscalated => {
+                    return Err("Pipeline requires agent approval before execution".to_string());
+                }
+                PipelineStatus::Paused => {
+                    return Err(format!(
+                        "Pipeline {} is paused; resume it to continue",
+                        pipeline_id
+                    ));
+                }
+                PipelineStatus::Cancelled => {
+                    return Err(format!("Pipeline {} was cancelled", pipeline_id));
+                }
+                _ => {}
+            }
+            if !pipeline.agent_requirements_satisfied() {
+                return Err("Pipeline is waiting for agent approvals".to_string());
+            }
+            if !resume {
+                // Stage statuses track this run's progress; durations stay for estimates.
+                for stage in &mut pipeline.stages {
+                    stage.status = PipelineStatus::Pending;
+                }
+            }
             pipeline.stages.clone()
         };
 
+        let _permit = self.acquire_pipeline_slot(pipeline_id);
+        // Hold bulk background work (archiving, compaction, embeddings) while the run is active.
+        let _pause = noa_core::scheduler::global()
+            .pause_guard(format!("pipeline:{}", pipeline_id), JobPriority::Normal);
+        if let Some(status) = self.halted_status(pipeline_id) {
+            return self.stop_run(pipeline_id, status, &stages);
+        }
         self.update_pipeline_status(pipeline_id, PipelineStatus::Running)?;
         self.emit_pipeline_event(
             pipeline_id,
             "cicd",
             "pipeline.execution_started",
-            json!({ "stage_count": stages.len() }),
+            json!({ "stage_count": stages.len(), "resumed": resume }),
         )?;
 
-        // Execute each stage
-        for stage in stages {
-            self.execute_stage(pipeline_id, &stage)?;
+        match self.run_stage_graph(pipeline_id, &stages) {
+            GraphOutcome::Completed => {}
+            GraphOutcome::Halted(status) => return self.stop_run(pipeline_id, status, &stages),
+            GraphOutcome::Failed { stage, error } => {
+                // The stage error is what the caller needs; retention is best effort here.
+                let _ = self.classify_artifacts(
+                    pipeline_id,
+                    RetentionClass::Failed,
+                    Some(format!("stage {} failed", stage)),
+                );
+                return Err(error);
+            }
+        }
+        if let Some(status) = self.halted_status(pipeline_id) {
+            return self.stop_run(pipeline_id, status, &stages);
         }
 
-        // Mark pipeline as success
+        // Mark pipeline as success; a later rerun starts from scratch
+        self.checkpoints().clear(pipeline_id)?;
+        self.classify_artifacts(pipeline_id, RetentionClass::Interim, None)?;
         self.update_pipeline_status(pipeline_id, PipelineStatus::Success)?;
         self.emit_pipeline_event(
             pipeline_id,
@@ -848,8 +2014,167 @@ impl CICDSystem {
         Ok(())
     }
 
+    /// Record that a run stopped on a pause or cancel request and report it to the caller.
+    fn stop_run(
+        &self,
+        pipeline_id: &str,
+        status: PipelineStatus,
+        stages: &[Stage],
+    ) -> Result<(), String> {
+        let remaining = {
+            let pipelines = self.pipelines.lock();
+            pipelines
+                .get(pipeline_id)
+                .map(Pipeline::remaining_stages)
+                .unwrap_or_else(|| stages.iter().map(|stage| stage.name.clone()).collect())
+        };
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.execution_halted",
+            json!({
+                "status": status,
+                "remaining_stages": remaining,
+            }),
+        )?;
+        Err(match status {
+            PipelineStatus::Cancelled => format!("Pipeline {} was cancelled", pipeline_id),
+            _ => format!("Pipeline {} was paused", pipeline_id),
+        })
+    }
+
+    /// Plan a pipeline run without executing it.
+    ///
+    /// Reports the approvals and stage executors `execute_pipeline` would require, with
+    /// per-stage estimates averaged from the durations recorded by earlier runs.
+    pub fn dry_run_pipeline(&self, pipeline_id: &str) -> Result<PipelinePlan, String> {
+        let pipelines = self.pipelines.lock();
+        let pipeline = pipelines
+            .get(pipeline_id)
+            .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+
+        let mut blockers = Vec::new();
+        if matches!(
+            pipeline.status,
+            PipelineStatus::AgentReview | PipelineStatus::AgentEscalated
+        ) {
+            blockers.push("Pipeline requires agent approval before execution".to_string());
+        }
+        let outstanding_approvals: Vec<AgentApprovalRequirement> = pipeline
+            .approvals_required
+            .iter()
+            .filter(|requirement| {
+                !pipeline
+                    .approvals_granted
+                    .iter()
+                    .any(|approval| requirement.is_satisfied_by(approval))
+            })
+            .cloned()
+            .collect();
+        if !outstanding_approvals.is_empty() {
+            blockers.push(format!(
+                "Pipeline is waiting for agent approvals: {}",
+                pipeline.outstanding_agent_roles().join(", ")
+            ));
+        }
+
+        let dependencies = stage_graph::dependencies(&pipeline.stages);
+        let stages: Vec<StagePlan> = pipeline
+            .stages
+            .iter()
+            .zip(dependencies)
+            .map(|(stage, (_, depends_on))| {
+                let execution = match &stage.stage_type {
+                    PipelineStage::Plugin(stage_type)
+                        if self.stage_executors.contains(stage_type) =>
+                    {
+                        StageExecution::Plugin(stage_type.clone())
+                    }
+                    PipelineStage::Plugin(stage_type) => {
+                        blockers.push(format!(
+                            "No executor registered for stage type: {}",
+                            stage_type
+                        ));
+                        StageExecution::Unavailable(stage_type.clone())
+                    }
+                    _ => StageExecution::Builtin,
+                };
+                let (estimated_duration_ms, history_samples) = dry_run::stage_history(
+                    &stage.stage_type,
+                    pipelines
+                        .values()
+                        .filter(|recorded| recorded.id != pipeline.id)
+                        .flat_map(|recorded| recorded.stages.iter()),
+                );
+                StagePlan {
+                    name: stage.name.clone(),
+                    stage_type: stage.stage_type.clone(),
+                    execution,
+                    estimated_duration_ms,
+                    history_samples,
+                    depends_on,
+                }
+            })
+            .collect();
+        let estimated_duration_ms = dry_run::critical_path_ms(&stages);
+
+        Ok(PipelinePlan {
+            pipeline_id: pipeline.id.clone(),
+            pipeline_name: pipeline.name.clone(),
+            status: pipeline.status.clone(),
+            outstanding_approvals,
+            stages,
+            blockers,
+            estimated_duration_ms,
+        })
+    }
+
+    /// Execute a stage, retrying failures its retry policy treats as transient.
+    /// Retries stop early once the pipeline is paused or cancelled.
+    fn execute_stage_with_retry(&self, pipeline_id: &str, stage: &Stage) -> Result<(), String> {
+        let policy = stage.retry.clone().unwrap_or_default();
+        let mut attempt = 1;
+        loop {
+            self.record_stage_attempt(pipeline_id, &stage.name, attempt);
+            let err = match self.execute_stage(pipeline_id, stage, attempt) {
+                Ok(()) => return Ok(()),
+                Err(err) => err,
+            };
+            if !policy.should_retry(attempt, &err) || self.halted_status(pipeline_id).is_some() {
+                self.emit_pipeline_event(
+                    pipeline_id,
+                    "cicd",
+                    "pipeline.stage_failed",
+                    json!({
+                        "stage": stage.name,
+                        "stage_type": stage.stage_type,
+                        "attempts": attempt,
+                        "error": err,
+                    }),
+                )?;
+                return Err(err);
+            }
+            let backoff = policy.backoff(attempt);
+            self.emit_pipeline_event(
+                pipeline_id,
+                "cicd",
+                "pipeline.stage_retrying",
+                json!({
+                    "stage": stage.name,
+                    "stage_type": stage.stage_type,
+                    "attempt": attempt,
+                    "max_attempts": policy.max_attempts,
+                    "backoff_ms": backoff.as_millis() as u64,
+                    "error": err,
+                }),
+            )?;
+            std::thread::sleep(backoff);
+            attempt += 1;
+        }
+    }
+
     /// Execute a single stage
-    fn execute_stage(&self, pipeline_id: &str, stage: &Stage) -> Result<(), String> {
+    fn execute_stage(&self, pipeline_id: &str, stage: &Stage, attempt: u32) -> Result<(), String> {
         self.emit_pipeline_event(
             pipeline_id,
             "cicd",
@@ -857,24 +2182,38 @@ impl CICDSystem {
             json!({
                 "stage": stage.name,
                 "stage_type": stage.stage_type,
+                "attempt": attempt,
             }),
         )?;
 
+        let reservation = self.reserve_stage_resources(pipeline_id, stage)?;
         let start = std::time::Instant::now();
 
         // Simulate stage execution
-        match stage.stage_type {
+        match &stage.stage_type {
             PipelineStage::CRC => self.crc_stage(pipeline_id)?,
             PipelineStage::Validate => self.validate(pipeline_id)?,
-            PipelineStage::Build => self.build(pipeline_id)?,
-            PipelineStage::Test => self.test(pipeline_id)?,
+            PipelineStage::Policy => self.workspace_policy(pipeline_id, stage)?,
+            PipelineStage::Lint => self.lint(pipeline_id, stage)?,
+            PipelineStage::Build => self.build(pipeline_id, stage)?,
+            PipelineStage::Test => self.test(pipeline_id, stage)?,
             PipelineStage::SingleHostAcceptance => self.single_host_acceptance(pipeline_id)?,
-            PipelineStage::Deploy => self.deploy(pipeline_id)?,
+            PipelineStage::Deploy => self.deploy(pipeline_id, stage)?,
             PipelineStage::DocsRefresh => self.docs_refresh(pipeline_id)?,
+            PipelineStage::Plugin(stage_type) => {
+                self.plugin_stage(pipeline_id, stage, stage_type)?
+            }
             _ => {}
         }
 
-        let duration = start.elapsed().as_millis() as u64;
+        let elapsed = start.elapsed();
+        let duration = elapsed.as_millis() as u64;
+        self.record_stage_duration(pipeline_id, &stage.name, duration);
+        self.charge_stage(
+            pipeline_id,
+            reservation.as_ref().map(|(_, resources)| *resources),
+            elapsed.as_secs_f64(),
+        )?;
         self.emit_pipeline_event(
             pipeline_id,
             "cicd",
@@ -883,12 +2222,152 @@ impl CICDSystem {
                 "stage": stage.name,
                 "stage_type": stage.stage_type,
                 "duration_ms": duration,
+                "attempts": attempt,
             }),
         )?;
 
         Ok(())
     }
 
+    /// Attribute a finished stage's resource usage when cost accounting is configured
+    fn charge_stage(
+        &self,
+        pipeline_id: &str,
+        reserved: Option<HostResources>,
+        seconds: f64,
+    ) -> Result<(), String> {
+        let Some(ledger) = self
+            .costs
+            .lock()
+            .expect("cost ledger lock poisoned")
+            .clone()
+        else {
+            return Ok(());
+        };
+        let name = {
+            let pipelines = self.pipelines.lock();
+            pipelines
+                .get(pipeline_id)
+                .map(|pipeline| pipeline.name.clone())
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?
+        };
+        let cores = reserved.map_or(1, |resources| resources.cpu_cores.max(1));
+        let mut usage = CostUsage::cpu(seconds * f64::from(cores));
+        if reserved.is_some_and(|resources| resources.vram_mb > 0) {
+            usage.gpu_seconds = seconds;
+        }
+        let account = CostAccount::new(format!("pipeline:{name}"), self.namespace.to_string());
+        let alerts = ledger
+            .charge(CostKind::Pipeline, pipeline_id, account, usage)
+            .map_err(|err| format!("failed to record stage cost: {err}"))?;
+        for alert in alerts {
+            self.emit_pipeline_event(pipeline_id, "cicd", "cost.budget_alert", json!(alert))?;
+        }
+        Ok(())
+    }
+
+    /// Record which attempt of the stage is running on its receipt
+    fn record_stage_attempt(&self, pipeline_id: &str, stage_name: &str, attempt: u32) {
+        let mut pipelines = self.pipelines.lock();
+        if let Some(stage) = pipelines
+            .get_mut(pipeline_id)
+            .and_then(|pipeline| pipeline.stages.iter_mut().find(|s| s.name == stage_name))
+        {
+            stage.attempts = attempt;
+        }
+    }
+
+    fn set_stage_status(&self, pipeline_id: &str, stage_name: &str, status: PipelineStatus) {
+        let mut pipelines = self.pipelines.lock();
+        if let Some(stage) = pipelines
+            .get_mut(pipeline_id)
+            .and_then(|pipeline| pipeline.stages.iter_mut().find(|s| s.name == stage_name))
+        {
+            stage.status = status;
+        }
+    }
+
+    /// Keep the stage's duration on the pipeline so later dry runs can estimate from it
+    fn record_stage_duration(&self, pipeline_id: &str, stage_name: &str, duration_ms: u64) {
+        let mut pipelines = self.pipelines.lock();
+        if let Some(stage) = pipelines
+            .get_mut(pipeline_id)
+            .and_then(|pipeline| pipeline.stages.iter_mut().find(|s| s.name == stage_name))
+        {
+            stage.status = PipelineStatus::Success;
+            stage.duration_ms = Some(duration_ms);
+        }
+    }
+
+    /// Register an executor for a third-party stage type.
+    pub fn register_stage_executor(&self, executor: Arc<dyn StageExecutor>) -> Result<(), String> {
+        let stage_type = executor.stage_type().to_string();
+        self.stage_executors.register(executor)?;
+        self.emit_pipeline_event(
+            "cicd::stage_plugins",
+            "cicd",
+            "pipeline.stage_executor_registered",
+            json!({ "stage_type": stage_type }),
+        )
+    }
+
+    /// Registry of plugin stage executors available to pipelines.
+    pub fn stage_executors(&self) -> Arc<StageExecutorRegistry> {
+        Arc::clone(&self.stage_executors)
+    }
+
+    /// Dispatch a plugin stage to its registered executor
+    fn plugin_stage(
+        &self,
+        pipeline_id: &str,
+        stage: &Stage,
+        stage_type: &str,
+    ) -> Result<(), String> {
+        let executor = self
+            .stage_executors
+            .get(stage_type)
+            .ok_or_else(|| format!("No executor registered for stage type: {}", stage_type))?;
+        let context = {
+            let pipelines = self.pipelines.lock();
+            let pipeline = pipelines
+                .get(pipeline_id)
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            StageContext {
+                pipeline_id: pipeline_id.to_string(),
+                pipeline_name: pipeline.name.clone(),
+                stage_name: stage.name.clone(),
+                stage_type: stage_type.to_string(),
+                commit_sha: pipeline.commit_sha.clone(),
+                workspace_root: self
+                    .workspace_root
+                    .lock()
+                    .expect("workspace root lock poisoned")
+                    .clone(),
+                parameters: stage.parameters.clone(),
+            }
+        };
+
+        let report = executor.execute(&context)?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.plugin_stage_reported",
+            json!({
+                "stage": stage.name,
+                "stage_type": stage_type,
+                "report": report,
+            }),
+        )?;
+        if report.success {
+            Ok(())
+        } else {
+            Err(format!(
+                "Stage {} ({}) failed: {}",
+                stage.name, stage_type, report.summary
+            ))
+        }
+    }
+
     /// CRC stage (if needed)
     fn crc_stage(&self, pipeline_id: &str) -> Result<(), String> {
         self.emit_pipeline_event(
@@ -907,13 +2386,20 @@ impl CICDSystem {
             "pipeline.validation_started",
             json!({}),
         )?;
-        let flags = {
-            let guard = self
-                .scanner_flags
-                .lock()
-                .expect("scanner flag lock poisoned");
-            guard.clone()
+        let spec_flags = {
+            let pipelines = self.pipelines.lock();
+            pipelines
+                .get(pipeline_id)
+                .and_then(|pipeline| pipeline.spec.as_ref())
+                .and_then(|spec| spec.scanners.as_ref())
+                .map(ScannerFlags::from)
         };
+        let flags = spec_flags.unwrap_or_else(|| {
+            self.scanner_flags
+                .lock()
+                .expect("scanner flag lock poisoned")
+                .clone()
+        });
         let workspace = {
             self.workspace_root
                 .lock()
@@ -973,10 +2459,8 @@ impl CICDSystem {
     where
         Runner: Fn(&ScanConfig) -> Result<ScanResult, noa_security_shim::ShimError>,
     {
-        let config = ScanConfig {
-            target: workspace.to_path_buf(),
-            ..ScanConfig::default()
-        };
+        let config = ScanConfig::for_target(workspace)
+            .with_cache_dir(self.context.resolve_path(DEFAULT_REPORT_DIR));
         let result = runner(&config).map_err(|err| format!("{} scan failed: {}", tool, err))?;
         let issues: Vec<String> = result
             .findings
@@ -1027,7 +2511,7 @@ impl CICDSystem {
         pipeline_id: &str,
         report: SecurityScanReport,
     ) -> Result<(), String> {
-        let mut pipelines = self.pipelines.lock().unwrap();
+        let mut pipelines = self.pipelines.lock();
         if let Some(pipeline) = pipelines.get_mut(pipeline_id) {
             pipeline.security_scans.push(report);
             Ok(())
@@ -1036,31 +2520,504 @@ impl CICDSystem {
         }
     }
 
-    /// Build stage
-    fn build(&self, pipeline_id: &str) -> Result<(), String> {
+    /// Policy stage
+    ///
+    /// Checks files changed since the `base` stage parameter (every tracked file when
+    /// unset) against the workspace policy named by the `policy` parameter. Cheap
+    /// enough to run before Validate; fails on any violation.
+    fn workspace_policy(&self, pipeline_id: &str, stage: &Stage) -> Result<(), String> {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let policy = WorkspacePolicy::load(&workspace_policy_path(&root, stage))?;
+        let candidates = match stage
+            .parameters
+            .get("base")
+            .and_then(|value| value.as_str())
+        {
+            Some(base) => workspace_policy::changed_candidates(&root, base)?,
+            None => workspace_policy::tracked_candidates(&root)?,
+        };
+        let report = workspace_policy::check(&root, &policy, &candidates);
+
+        {
+            let mut pipelines = self.pipelines.lock();
+            let pipeline = pipelines
+                .get_mut(pipeline_id)
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            pipeline.policy = Some(report.clone());
+        }
+        self.persist_state()?;
         self.emit_pipeline_event(
             pipeline_id,
             "cicd",
-            "pipeline.build_components",
+            "pipeline.policy_completed",
             json!({
-                "targets": ["rust", "go", "python", ".net"],
+                "checked": report.checked,
+                "violations": report.violations,
             }),
-        )
+        )?;
+
+        if report.is_clean() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Workspace policy found {} violations",
+                report.violations.len()
+            ))
+        }
     }
 
-    /// Test stage
-    fn test(&self, pipeline_id: &str) -> Result<(), String> {
+    /// Lint stage
+    ///
+    /// Runs rustfmt and clippy (each can be disabled with a `rustfmt`/`clippy: false`
+    /// stage parameter) and fails only on findings not covered by the baseline file
+    /// named by the `baseline` parameter.
+    fn lint(&self, pipeline_id: &str, stage: &Stage) -> Result<(), String> {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        if !root.join("Cargo.toml").exists() {
+            return self.emit_pipeline_event(
+                pipeline_id,
+                "cicd",
+                "pipeline.lint_skipped",
+                json!({ "reason": "no Cargo.toml in workspace root" }),
+            );
+        }
+        let runner = self
+            .lint_runner
+            .lock()
+            .expect("lint runner lock poisoned")
+            .clone();
+        let enabled = |tool: &str| {
+            stage
+                .parameters
+                .get(tool)
+                .and_then(|value| value.as_bool())
+                .unwrap_or(true)
+        };
+
+        let mut findings = Vec::new();
+        if enabled("rustfmt") {
+            findings.extend(lint::parse_rustfmt(&runner.rustfmt(&root)?, &root));
+        }
+        if enabled("clippy") {
+            findings.extend(lint::parse_clippy(&runner.clippy(&root)?, &root));
+        }
+        let baseline = LintBaseline::load(&lint_baseline_path(&root, stage))?;
+        let report = LintReport::compare(findings, &baseline);
+        let new_findings: Vec<_> = report.new_findings().cloned().collect();
+
+        {
+            let mut pipelines = self.pipelines.lock();
+            let pipeline = pipelines
+                .get_mut(pipeline_id)
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            pipeline.lint = Some(report.clone());
+        }
+        self.persist_state()?;
         self.emit_pipeline_event(
             pipeline_id,
             "cicd",
-            "pipeline.tests_executed",
+            "pipeline.lint_completed",
             json!({
-                "suites": ["unit", "integration", "api"],
+                "findings": report.findings.len(),
+                "baselined": report.baselined,
+                "fixed": report.fixed,
+                "new": new_findings,
             }),
-        )
+        )?;
+
+        if new_findings.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Lint found {} new violations not in the baseline",
+                new_findings.len()
+            ))
+        }
     }
 
-    /// Acceptance checks for the single-host profile
+    /// Findings recorded by the pipeline's last Lint stage run.
+    pub fn lint_report(&self, pipeline_id: &str) -> Option<LintReport> {
+        self.pipelines.snapshot().get(pipeline_id)?.lint.clone()
+    }
+
+    /// Accept the pipeline's current lint findings as the new baseline.
+    pub fn accept_lint_baseline(&self, pipeline_id: &str) -> Result<PathBuf, String> {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let (report, path) = {
+            let pipelines = self.pipelines.lock();
+            let pipeline = pipelines
+                .get(pipeline_id)
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            let report = pipeline
+                .lint
+                .clone()
+                .ok_or_else(|| format!("Pipeline {} has no lint findings", pipeline_id))?;
+            let path = pipeline
+                .stages
+                .iter()
+                .find(|stage| stage.stage_type == PipelineStage::Lint)
+                .map(|stage| lint_baseline_path(&root, stage))
+                .unwrap_or_else(|| root.join(LINT_BASELINE_FILE));
+            (report, path)
+        };
+        LintBaseline::from_findings(&report.findings).save(&path)?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.lint_baseline_updated",
+            json!({
+                "path": path,
+                "findings": report.findings.len(),
+            }),
+        )?;
+        Ok(path)
+    }
+
+    /// Build stage
+    /// Reserve the host resources a stage needs on the kernel scheduler, queueing
+    /// behind existing reservations such as the inference backend's.
+    ///
+    /// Stages declare `resources` (`cpu_cores`, `vram_mb`) and `resource_wait_secs`
+    /// in their parameters; build and test stages default to one core.
+    fn reserve_stage_resources(
+        &self,
+        pipeline_id: &str,
+        stage: &Stage,
+    ) -> Result<Option<(ReservationGuard, HostResources)>, String> {
+        let resources = match stage.parameters.get("resources") {
+            Some(declared) => serde_json::from_value(declared.clone())
+                .map_err(|err| format!("invalid resources parameter: {err}"))?,
+            None if matches!(stage.stage_type, PipelineStage::Build | PipelineStage::Test) => {
+                DEFAULT_STAGE_RESOURCES
+            }
+            None => return Ok(None),
+        };
+        let wait = stage
+            .parameters
+            .get("resource_wait_secs")
+            .and_then(|value| value.as_u64())
+            .unwrap_or(DEFAULT_RESOURCE_WAIT_SECS);
+
+        let started = std::time::Instant::now();
+        let reservation = noa_core::scheduler::global()
+            .acquire(
+                format!("pipeline:{}:{}", pipeline_id, stage.name),
+                resources,
+                std::time::Duration::from_secs(wait),
+            )
+            .map_err(|err| err.to_string())?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.stage_resources_reserved",
+            json!({
+                "stage": stage.name,
+                "resources": resources,
+                "waited_ms": started.elapsed().as_millis() as u64,
+            }),
+        )?;
+        Ok(Some((reservation, resources)))
+    }
+
+    fn build(&self, pipeline_id: &str, stage: &Stage) -> Result<(), String> {
+        let (targets, resumed) =
+            self.run_checkpointed_units(pipeline_id, stage, "targets", DEFAULT_BUILD_TARGETS)?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.build_components",
+            json!({
+                "targets": targets,
+                "resumed": resumed,
+            }),
+        )?;
+        self.record_footprint(pipeline_id, stage)?;
+        self.attach_stage_artifacts(pipeline_id, stage)
+    }
+
+    /// Attach the build outputs named by the stage's `artifacts` parameter (unit to
+    /// path) to the artifact registry. Outputs that were not produced are skipped.
+    fn attach_stage_artifacts(&self, pipeline_id: &str, stage: &Stage) -> Result<(), String> {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let paths = stage
+            .parameters
+            .get("artifacts")
+            .and_then(|artifacts| artifacts.as_object())
+            .into_iter()
+            .flat_map(|artifacts| artifacts.values())
+            .filter_map(|path| path.as_str());
+        for path in paths {
+            if root.join(path).is_file() {
+                self.attach_artifact(pipeline_id, &stage.name, path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Measure the built binaries and compare them with the last successful pipeline
+    /// of the same name.
+    ///
+    /// Binaries are listed under the stage's `binaries` parameter or discovered in
+    /// `target/release`. Growth above `regression_percent` is flagged in the report and
+    /// fails the stage only when `fail_on_footprint_regression` is set.
+    fn record_footprint(&self, pipeline_id: &str, stage: &Stage) -> Result<(), String> {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let specs: Vec<BinarySpec> = match stage.parameters.get("binaries") {
+            Some(listed) => serde_json::from_value(listed.clone())
+                .map_err(|err| format!("invalid binaries parameter: {err}"))?,
+            None => footprint::discover_binaries(&root),
+        };
+        let footprint = BuildFootprint::measure(&root, &specs)?;
+        if footprint.binaries.is_empty() {
+            return Ok(());
+        }
+        let threshold = stage
+            .parameters
+            .get("regression_percent")
+            .and_then(|value| value.as_f64())
+            .unwrap_or(DEFAULT_REGRESSION_PERCENT);
+
+        let report = {
+            let mut pipelines = self.pipelines.lock();
+            let name = pipelines
+                .get(pipeline_id)
+                .map(|pipeline| pipeline.name.clone())
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            let previous = pipelines
+                .values()
+                .filter(|pipeline| {
+                    pipeline.id != pipeline_id
+                        && pipeline.name == name
+                        && pipeline.status == PipelineStatus::Success
+                })
+                .filter_map(|pipeline| {
+                    pipeline
+                        .footprint
+                        .as_ref()
+                        .map(|report| (pipeline, &report.footprint))
+                })
+                .max_by_key(|(pipeline, _)| pipeline.triggered_at)
+                .map(|(pipeline, footprint)| (pipeline.id.as_str(), footprint));
+            let report = FootprintReport {
+                comparison: FootprintComparison::between(previous, &footprint, threshold),
+                footprint,
+            };
+            if let Some(pipeline) = pipelines.get_mut(pipeline_id) {
+                pipeline.footprint = Some(report.clone());
+            }
+            report
+        };
+        self.persist_state()?;
+
+        let dir = root
+            .join(self.namespace.scope_path(PIPELINE_REPORTS_DIR))
+            .join(pipeline_id);
+        let (json_path, markdown_path) = report.write(&dir)?;
+        let regressions: Vec<_> = report.comparison.regressions().cloned().collect();
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.footprint_recorded",
+            json!({
+                "binaries": report.footprint.binaries.len(),
+                "previous_pipeline": report.comparison.previous_pipeline,
+                "regressions": regressions,
+                "json": json_path,
+                "markdown": markdown_path,
+            }),
+        )?;
+
+        let fail = stage
+            .parameters
+            .get("fail_on_footprint_regression")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        if fail && !regressions.is_empty() {
+            return Err(format!(
+                "Build footprint regressed more than {}% for: {}",
+                threshold,
+                regressions
+                    .iter()
+                    .map(|delta| delta.binary.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Binary footprint and comparison recorded by the pipeline's Build stage.
+    pub fn footprint_report(&self, pipeline_id: &str) -> Option<FootprintReport> {
+        self.pipelines
+            .snapshot()
+            .get(pipeline_id)?
+            .footprint
+            .clone()
+    }
+
+    /// Test stage
+    fn test(&self, pipeline_id: &str, stage: &Stage) -> Result<(), String> {
+        let (suites, resumed) =
+            self.run_checkpointed_units(pipeline_id, stage, "suites", DEFAULT_TEST_SUITES)?;
+        let report = self.record_test_results(pipeline_id, stage)?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.tests_executed",
+            json!({
+                "suites": suites,
+                "resumed": resumed,
+                "passed": report.as_ref().map(|report| report.count(TestOutcome::Passed)),
+                "failed": report.as_ref().map(|report| report.count(TestOutcome::Failed)),
+                "coverage_percent": report.as_ref().and_then(|report| report.coverage_percent),
+            }),
+        )
+    }
+
+    /// Store the libtest JSON named by the stage's `results` parameter, and the
+    /// llvm-cov summary named by `coverage`, so later pipelines can compare against them.
+    fn record_test_results(
+        &self,
+        pipeline_id: &str,
+        stage: &Stage,
+    ) -> Result<Option<TestReport>, String> {
+        let Some(results) = stage.parameters.get("results").and_then(|v| v.as_str()) else {
+            return Ok(None);
+        };
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let read = |relative: &str| {
+            fs::read_to_string(root.join(relative))
+                .map_err(|err| format!("failed to read {relative}: {err}"))
+        };
+        let mut report = TestReport::from_libtest_json(&read(results)?);
+        if let Some(coverage) = stage.parameters.get("coverage").and_then(|v| v.as_str()) {
+            report.coverage_percent = Some(TestReport::coverage_from_llvm_cov(&read(coverage)?)?);
+        }
+        {
+            let mut pipelines = self.pipelines.lock();
+            let pipeline = pipelines
+                .get_mut(pipeline_id)
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            pipeline.tests = Some(report.clone());
+        }
+        self.persist_state()?;
+        Ok(Some(report))
+    }
+
+    /// Regression report of `head` against `base`: stage durations, tests, coverage,
+    /// SBOM, binary sizes, and security findings.
+    pub fn compare(&self, base: &str, head: &str) -> Result<PipelineComparison, String> {
+        let pipelines = self.pipelines.snapshot();
+        let lookup = |id: &str| {
+            pipelines
+                .get(id)
+                .ok_or_else(|| format!("Pipeline not found: {}", id))
+        };
+        Ok(PipelineComparison::between(lookup(base)?, lookup(head)?))
+    }
+
+    /// Run a stage's units in order, checkpointing each one as it completes.
+    ///
+    /// Units are listed under `key` in the stage parameters (falling back to
+    /// `defaults`); `artifacts` may map a unit to a workspace-relative file whose
+    /// contents are part of its checkpoint hash. Returns every unit alongside the
+    /// ones skipped because an earlier attempt already completed them.
+    fn run_checkpointed_units(
+        &self,
+        pipeline_id: &str,
+        stage: &Stage,
+        key: &str,
+        defaults: &[&str],
+    ) -> Result<(Vec<String>, Vec<String>), String> {
+        let units: Vec<String> = match stage.parameters.get(key).and_then(|v| v.as_array()) {
+            Some(listed) => listed
+                .iter()
+                .filter_map(|unit| unit.as_str().map(str::to_string))
+                .collect(),
+            None => defaults.iter().map(|unit| unit.to_string()).collect(),
+        };
+        let commit_sha = {
+            let pipelines = self.pipelines.lock();
+            pipelines
+                .get(pipeline_id)
+                .map(|pipeline| pipeline.commit_sha.clone())
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?
+        };
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let store = self.checkpoints();
+        let previous = store
+            .load(pipeline_id, &stage.name)?
+            .filter(|checkpoint| checkpoint.commit_sha == commit_sha);
+
+        let mut resumed = Vec::new();
+        for unit in &units {
+            let artifact = stage
+                .parameters
+                .get("artifacts")
+                .and_then(|artifacts| artifacts.get(unit.as_str()))
+                .and_then(|path| path.as_str())
+                .map(|path| root.join(path));
+            let hash = checkpoint::unit_hash(&commit_sha, unit, artifact.as_deref())?;
+            if previous
+                .as_ref()
+                .is_some_and(|checkpoint| checkpoint.is_complete(unit, &hash))
+            {
+                resumed.push(unit.clone());
+                continue;
+            }
+            self.emit_pipeline_event(
+                pipeline_id,
+                "cicd",
+                "pipeline.stage_unit_completed",
+                json!({
+                    "stage": stage.name,
+                    "unit": unit,
+                    "hash": hash,
+                }),
+            )?;
+            store.record(pipeline_id, &stage.name, &commit_sha, unit, &hash)?;
+        }
+        Ok((units, resumed))
+    }
+
+    /// Units an interrupted run of `stage_name` already completed, if any.
+    pub fn stage_checkpoint(
+        &self,
+        pipeline_id: &str,
+        stage_name: &str,
+    ) -> Result<Option<StageCheckpoint>, String> {
+        self.checkpoints().load(pipeline_id, stage_name)
+    }
+
+    /// Acceptance checks for the single-host profile
     fn single_host_acceptance(&self, pipeline_id: &str) -> Result<(), String> {
         let profile_path = {
             let guard = self
@@ -1084,6 +3041,26 @@ impl CICDSystem {
             return Err("Profile manifest does not describe the single_host configuration".into());
         }
 
+        // Check the profile against the runtime graph when the workspace has one.
+        let graph_path = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .join(KERNEL_RUNTIME_GRAPH);
+        let graph_validated = graph_path.exists();
+        if graph_validated {
+            let graph = RuntimeGraph::load_from_path(&graph_path).map_err(|err| err.to_string())?;
+            let issues = SingleHostProfile::parse(&manifest)
+                .map_err(|err| format!("Failed to parse profile: {err}"))?
+                .validate(&graph);
+            if !issues.is_empty() {
+                return Err(format!(
+                    "Single-host profile does not match the runtime graph: {}",
+                    issues.join("; ")
+                ));
+            }
+        }
+
         self.emit_pipeline_event(
             pipeline_id,
             "cicd",
@@ -1091,28 +3068,80 @@ impl CICDSystem {
             json!({
                 "profile_path": profile_path,
                 "manifest_size": manifest.len(),
+                "graph_validated": graph_validated,
             }),
         )
     }
 
     /// Deploy stage
-    fn deploy(&self, pipeline_id: &str) -> Result<(), String> {
+    ///
+    /// Targets the `environment` parameter (staging by default). When a route is
+    /// configured for that environment, the pipeline's build is deployed and the route
+    /// pushed; otherwise only the intent is recorded.
+    fn deploy(&self, pipeline_id: &str, stage: &Stage) -> Result<(), String> {
+        let environment: Environment = match stage.parameters.get("environment") {
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|err| format!("invalid deploy environment: {err}"))?,
+            None => Environment::Staging,
+        };
         self.emit_pipeline_event(
             pipeline_id,
             "cicd",
             "pipeline.deploy_initiated",
-            json!({ "target": "staging" }),
+            json!({ "target": history::environment_label(&environment) }),
+        )?;
+        if self.route_controller().is_none() || self.environment_route(&environment).is_none() {
+            return Ok(());
+        }
+
+        let strategy: DeploymentStrategy = match stage.parameters.get("strategy") {
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|err| format!("invalid deploy strategy: {err}"))?,
+            None => DeploymentStrategy::BlueGreen,
+        };
+        let version = match stage.parameters.get("version").and_then(|v| v.as_str()) {
+            Some(version) => version.to_string(),
+            None => self
+                .pipelines
+                .lock()
+                .get(pipeline_id)
+                .map(|pipeline| pipeline.commit_sha.clone())
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?,
+        };
+        let service = stage
+            .parameters
+            .get("service")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_SERVICE)
+            .to_string();
+        let deployment_id = self.start_deployment(
+            service,
+            version,
+            environment,
+            strategy,
+            Some(pipeline_id.to_string()),
+        )?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.deploy_started",
+            json!({ "deployment_id": deployment_id }),
         )
     }
 
     fn docs_refresh(&self, pipeline_id: &str) -> Result<(), String> {
-        let diff_summary = {
-            let pipelines = self.pipelines.lock().unwrap();
-            pipelines
-                .get(pipeline_id)
-                .and_then(|p| p.diff_summary.clone())
-                .unwrap_or_else(|| "No diff summary provided".to_string())
+        let (diff_summary, diff) = {
+            let pipelines = self.pipelines.lock();
+            let pipeline = pipelines.get(pipeline_id);
+            (
+                pipeline
+                    .and_then(|p| p.diff_summary.clone())
+                    .unwrap_or_else(|| "No diff summary provided".to_string()),
+                pipeline.and_then(|p| p.diff.clone()),
+            )
         };
+        let dead_code = self.dead_code_evidence(pipeline_id)?;
+        let comparison = self.comparison_evidence(pipeline_id)?;
 
         self.emit_pipeline_event(
             pipeline_id,
@@ -1120,17 +3149,150 @@ impl CICDSystem {
             "pipeline.docs_refresh",
             json!({
                 "diff_summary": diff_summary,
+                "changed_docs": diff.as_ref().map(|diff| diff.docs.clone()).unwrap_or_default(),
+                "impacted_symbols": diff
+                    .as_ref()
+                    .map(|diff| diff.symbols.iter().map(|symbol| symbol.name.clone()).collect())
+                    .unwrap_or_else(Vec::new),
                 "agent": "documentation",
+                "dead_code": dead_code,
+                "comparison": comparison,
             }),
         )
     }
 
+    /// Compare the latest successful pipeline at this commit with the previous
+    /// successful run of the same pipeline, and store the report with the evidence.
+    fn comparison_evidence(&self, pipeline_id: &str) -> Result<Option<serde_json::Value>, String> {
+        let comparison = {
+            let pipelines = self.pipelines.lock();
+            let commit_sha = pipelines
+                .get(pipeline_id)
+                .map(|pipeline| pipeline.commit_sha.clone())
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            let succeeded = |pipeline: &&Pipeline| {
+                pipeline.id != pipeline_id && pipeline.status == PipelineStatus::Success
+            };
+            let Some(head) = pipelines
+                .values()
+                .filter(succeeded)
+                .filter(|pipeline| pipeline.commit_sha == commit_sha)
+                .max_by_key(|pipeline| pipeline.triggered_at)
+            else {
+                return Ok(None);
+            };
+            let Some(base) = pipelines
+                .values()
+                .filter(succeeded)
+                .filter(|pipeline| {
+                    pipeline.name == head.name
+                        && pipeline.commit_sha != commit_sha
+                        && pipeline.triggered_at <= head.triggered_at
+                })
+                .max_by_key(|pipeline| pipeline.triggered_at)
+            else {
+                return Ok(None);
+            };
+            PipelineComparison::between(base, head)
+        };
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let dir = root
+            .join(self.namespace.scope_path(PIPELINE_REPORTS_DIR))
+            .join(pipeline_id);
+        let (json_path, markdown_path) = comparison.write(&dir)?;
+        Ok(Some(json!({
+            "base": comparison.base.id,
+            "head": comparison.head.id,
+            "regressions": comparison.has_regressions(),
+            "json": json_path,
+            "markdown": markdown_path,
+        })))
+    }
+
+    /// Rank orphan symbols and files from the workspace symbol graph, when one has been
+    /// indexed, and store the report with the pipeline's evidence.
+    fn dead_code_evidence(&self, pipeline_id: &str) -> Result<Option<serde_json::Value>, String> {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let store = root.join(DEFAULT_STORE_DIR);
+        if !store.join("nodes.jsonl").exists() {
+            return Ok(None);
+        }
+        let graph = SymbolGraph::load(&store)
+            .map_err(|err| format!("failed to load symbol graph: {err}"))?;
+        let report = DeadCodeReport::analyze(&graph);
+        let dir = root
+            .join(self.namespace.scope_path(PIPELINE_REPORTS_DIR))
+            .join(pipeline_id);
+        let (json_path, markdown_path) = report
+            .write(&dir)
+            .map_err(|err| format!("failed to write dead code report: {err}"))?;
+        Ok(Some(json!({
+            "orphan_symbols": report.orphan_symbols.len(),
+            "orphan_files": report.orphan_files.len(),
+            "json": json_path,
+            "markdown": markdown_path,
+        })))
+    }
+
     /// Deploy to environment with strategy and auto-approval
     pub fn deploy_to_environment(
         &self,
         version: String,
         environment: Environment,
         strategy: DeploymentStrategy,
+    ) -> Result<String, String> {
+        self.deploy_service_to_environment(
+            DEFAULT_SERVICE.to_string(),
+            version,
+            environment,
+            strategy,
+        )
+    }
+
+    /// Deploy a named service so its SLOs and error budgets apply
+    pub fn deploy_service_to_environment(
+        &self,
+        service: String,
+        version: String,
+        environment: Environment,
+        strategy: DeploymentStrategy,
+    ) -> Result<String, String> {
+        self.start_deployment(service, version, environment, strategy, None)
+    }
+
+    /// Deploy the build of a pipeline, linking the deployment to its evidence bundle.
+    pub fn deploy_pipeline_to_environment(
+        &self,
+        pipeline_id: &str,
+        version: String,
+        environment: Environment,
+        strategy: DeploymentStrategy,
+    ) -> Result<String, String> {
+        self.check_pipeline_environment(pipeline_id, &environment)?;
+        self.start_deployment(
+            DEFAULT_SERVICE.to_string(),
+            version,
+            environment,
+            strategy,
+            Some(pipeline_id.to_string()),
+        )
+    }
+
+    fn start_deployment(
+        &self,
+        service: String,
+        version: String,
+        environment: Environment,
+        strategy: DeploymentStrategy,
+        pipeline_id: Option<String>,
     ) -> Result<String, String> {
         let id = format!("deploy_{}", uuid::Uuid::new_v4());
 
@@ -1138,18 +3300,32 @@ impl CICDSystem {
         let auto_approved = true; // Based on pipeline status
 
         let environment_for_metadata = environment.clone();
+        let canary = strategy == DeploymentStrategy::Canary;
+        let blue_green = strategy == DeploymentStrategy::BlueGreen;
         let strategy_for_metadata = strategy.clone();
         let version_for_metadata = version.clone();
 
-        let deployment = Deployment {
+        let mut deployment = Deployment {
             id: id.clone(),
+            service: service.clone(),
             environment: environment.clone(),
             strategy,
             version,
             status: PipelineStatus::Running,
             health_metrics: HealthMetrics::default(),
             auto_approved,
+            baseline_recorded: false,
+            pipeline_id,
+            route: None,
+            canary: None,
+            slot: None,
+            switch: None,
         };
+        let route_result = self.apply_deployment_route(&service, &environment);
+        match &route_result {
+            Ok(route) => deployment.route = route.clone(),
+            Err(_) => deployment.status = PipelineStatus::Failed,
+        }
 
         let mut deployments = self.deployments.lock().unwrap();
         deployments.insert(id.clone(), deployment);
@@ -1157,6 +3333,32 @@ impl CICDSystem {
 
         self.persist_state()?;
 
+        match route_result {
+            Ok(Some(route)) => self.emit_deployment_event(
+                &id,
+                "deployment.route_applied",
+                json!({
+                    "route_id": route.route_id,
+                    "domain": route.domain,
+                    "upstreams": route.upstreams,
+                    "replaced": route.previous.is_some(),
+                    "status": route.applied.status,
+                }),
+            )?,
+            Ok(None) => {}
+            Err(err) => {
+                self.emit_deployment_event(
+                    &id,
+                    "deployment.route_failed",
+                    json!({ "error": err }),
+                )?;
+                return Err(format!(
+                    "Deployment {} failed to apply its route: {}",
+                    id, err
+                ));
+            }
+        }
+
         let event_type = if auto_approved {
             "deployment.auto_start"
         } else {
@@ -1166,19 +3368,124 @@ impl CICDSystem {
             &id,
             event_type,
             json!({
+                "service": service,
                 "environment": environment_for_metadata,
                 "strategy": strategy_for_metadata,
                 "version": version_for_metadata,
                 "auto_approved": auto_approved,
             }),
         )?;
+        if canary {
+            self.start_canary(&id)?;
+        }
+        if blue_green {
+            self.assign_standby_slot(&id)?;
+        }
 
         Ok(id)
     }
 
+    /// Push the route configured for `environment`, remembering what it replaces.
+    fn apply_deployment_route(
+        &self,
+        service: &str,
+        environment: &Environment,
+    ) -> Result<Option<DeploymentRoute>, String> {
+        let (Some(controller), Some(mut route)) =
+            (self.route_controller(), self.environment_route(environment))
+        else {
+            return Ok(None);
+        };
+        let route_id = route
+            .id
+            .clone()
+            .unwrap_or_else(|| routing::route_id(service, environment));
+        route.id = Some(route_id.clone());
+        let previous = controller.current(&route_id)?;
+        let applied = controller.apply(&route)?;
+        Ok(Some(DeploymentRoute {
+            route_id,
+            domain: route.domain,
+            upstreams: route.upstreams,
+            previous,
+            applied,
+            restored: None,
+        }))
+    }
+
+    /// Record observed health metrics for a running deployment.
+    pub fn record_deployment_metrics(
+        &self,
+        deployment_id: &str,
+        metrics: HealthMetrics,
+    ) -> Result<(), String> {
+        let (service, environment) = {
+            let mut deployments = self.deployments.lock().unwrap();
+            let deployment = deployments
+                .get_mut(deployment_id)
+                .ok_or_else(|| format!("Deployment not found: {}", deployment_id))?;
+            deployment.health_metrics = metrics.clone();
+            (deployment.service.clone(), deployment.environment.clone())
+        };
+        self.slos
+            .lock()
+            .unwrap()
+            .observe(&service, &environment, &metrics, unix_now());
+        self.append_history(
+            &format!("deployment::{}", deployment_id),
+            history::health_samples(
+                history::DEPLOYMENT_SERIES,
+                &metrics,
+                &history::deployment_labels(&service, &environment),
+                unix_now_ms(),
+            ),
+        );
+        self.persist_state()
+    }
+
+    /// Register an SLO for a service in an environment.
+    pub fn define_slo(&self, definition: SloDefinition) -> Result<(), String> {
+        let name = definition.name.clone();
+        self.slos
+            .lock()
+            .unwrap()
+            .define(definition.clone())
+            .map_err(|err| err.to_string())?;
+        if let Some(store) = self.timeseries() {
+            self.backfill_slo(&store, &definition, unix_now_ms())?;
+        }
+        self.persist_state()?;
+        self.emit_pipeline_event(
+            "cicd::slo",
+            "cicd",
+            "pipeline.slo_defined",
+            json!({ "slo": name }),
+        )
+    }
+
+    /// Current error-budget status of every SLO covering `service` in `environment`.
+    pub fn error_budget_status(
+        &self,
+        service: &str,
+        environment: &Environment,
+    ) -> Vec<ErrorBudgetStatus> {
+        self.slos
+            .lock()
+            .unwrap()
+            .budget_status(service, environment, unix_now())
+    }
+
+    /// Learned p50/p95 baseline for an environment, if any healthy deployments were recorded.
+    pub fn learned_baseline(&self, environment: &Environment) -> Option<baseline::LearnedBaseline> {
+        self.baselines.lock().unwrap().baseline(environment)
+    }
+
     /// Monitor deployment health with auto-rollback
+    ///
+    /// Metrics are compared against the environment's learned p95 baseline and checked for
+    /// sudden regressions. Healthy deployments feed their metrics back into the baseline.
     pub fn monitor_deployment(&self, deployment_id: &str) -> Result<bool, String> {
-        let (environment, metrics) = {
+        let (environment, metrics, already_recorded) = {
             let deployments = self.deployments.lock().unwrap();
             let deployment = deployments
                 .get(deployment_id)
@@ -1186,18 +3493,44 @@ impl CICDSystem {
             (
                 deployment.environment.clone(),
                 deployment.health_metrics.clone(),
+                deployment.baseline_recorded,
             )
         };
 
-        let baseline = {
-            let baseline_metrics = self.baseline_metrics.lock().unwrap();
-            baseline_metrics
-                .get(&environment)
-                .cloned()
-                .unwrap_or_default()
+        let (learned, anomalies) = {
+            let baselines = self.baselines.lock().unwrap();
+            (
+                baselines.baseline(&environment),
+                baselines.detect_anomalies(&environment, &metrics),
+            )
         };
+        let baseline = learned
+            .as_ref()
+            .map(|learned| learned.p95.clone())
+            .unwrap_or_default();
+
+        let is_healthy = metrics.is_healthy(&baseline) && anomalies.is_empty();
 
-        let is_healthy = metrics.is_healthy(&baseline);
+        if is_healthy && !already_recorded {
+            {
+                let mut baselines = self.baselines.lock().unwrap();
+                baselines.record(&environment, metrics.clone());
+                baselines.save(&self.baseline_path())?;
+            }
+            self.append_history(
+                &format!("deployment::{}", deployment_id),
+                history::health_samples(
+                    history::BASELINE_SERIES,
+                    &metrics,
+                    &history::baseline_labels(&environment),
+                    unix_now_ms(),
+                ),
+            );
+            if let Some(deployment) = self.deployments.lock().unwrap().get_mut(deployment_id) {
+                deployment.baseline_recorded = true;
+            }
+            self.persist_state()?;
+        }
 
         let event_type = if is_healthy {
             "deployment.health_passed"
@@ -1211,6 +3544,8 @@ impl CICDSystem {
                 "environment": environment,
                 "metrics": metrics,
                 "baseline": baseline,
+                "baseline_samples": learned.as_ref().map_or(0, |learned| learned.sample_count),
+                "anomalies": anomalies,
             }),
         )?;
 
@@ -1218,54 +3553,191 @@ impl CICDSystem {
     }
 
     /// Rollback deployment (automatic)
+    ///
+    /// A route pushed by the deployment is restored to its previous configuration, or
+    /// removed if the deployment created it.
     pub fn rollback(&self, deployment_id: &str) -> Result<(), String> {
+        let pending_route = {
+            let deployments = self.deployments.lock().unwrap();
+            let deployment = deployments
+                .get(deployment_id)
+                .ok_or_else(|| format!("Deployment not found: {}", deployment_id))?;
+            deployment
+                .route
+                .clone()
+                .filter(|route| route.restored.is_none())
+        };
+        let restored = match &pending_route {
+            Some(route) => {
+                let controller = self.route_controller().ok_or_else(|| {
+                    format!("No route controller to restore route {}", route.route_id)
+                })?;
+                Some(controller.restore(&route.route_id, route.previous.as_ref())?)
+            }
+            None => None,
+        };
+        // Only flip slots back once the route is restored, so a failed restore leaves both.
+        let slot_switch = self.release_slot(deployment_id)?;
+
         let mut deployments = self.deployments.lock().unwrap();
-        if let Some(deployment) = deployments.get_mut(deployment_id) {
-            deployment.status = PipelineStatus::RolledBack;
-            let environment = deployment.environment.clone();
-            let strategy = deployment.strategy.clone();
-            let version = deployment.version.clone();
-            drop(deployments);
+        let deployment = deployments
+            .get_mut(deployment_id)
+            .ok_or_else(|| format!("Deployment not found: {}", deployment_id))?;
+        deployment.status = PipelineStatus::RolledBack;
+        if let (Some(route), Some(restored)) = (deployment.route.as_mut(), restored.clone()) {
+            route.restored = Some(restored);
+        }
+        let environment = deployment.environment.clone();
+        let strategy = deployment.strategy.clone();
+        let version = deployment.version.clone();
+        drop(deployments);
 
-            self.persist_state()?;
+        self.persist_state()?;
+        if let (Some(route), Some(restored)) = (pending_route, restored) {
+            self.emit_deployment_event(
+                deployment_id,
+                "deployment.route_restored",
+                json!({
+                    "route_id": route.route_id,
+                    "removed": route.previous.is_none(),
+                    "status": restored.status,
+                }),
+            )?;
+        }
+        if let Some(switch) = slot_switch {
             self.emit_deployment_event(
                 deployment_id,
-                "deployment.rolled_back",
+                "deployment.slot_restored",
                 json!({
                     "environment": environment,
-                    "strategy": strategy,
-                    "version": version,
+                    "slot": switch.to,
+                    "active": switch.from,
+                    "active_deployment": switch.previous_deployment,
                 }),
             )?;
-            Ok(())
-        } else {
-            Err(format!("Deployment not found: {}", deployment_id))
         }
+        self.emit_deployment_event(
+            deployment_id,
+            "deployment.rolled_back",
+            json!({
+                "environment": environment,
+                "strategy": strategy,
+                "version": version,
+            }),
+        )?;
+        Ok(())
     }
 
     /// Auto-promote if healthy (full automation)
+    ///
+    /// Promotion is also blocked by the promotion gate of the deployment's pipeline, and
+    /// while any SLO of the service in the target environment has exhausted its error budget. Budget status is recorded in the deployment outcome report.
     pub fn auto_promote(
         &self,
         deployment_id: &str,
         to_environment: Environment,
     ) -> Result<(), String> {
-        if self.monitor_deployment(deployment_id)? {
-            self.emit_deployment_event(
-                deployment_id,
-                "deployment.auto_promote",
-                json!({ "target_environment": to_environment }),
-            )?;
-            Ok(())
+        let healthy = self.monitor_deployment(deployment_id)?;
+        let deployment = self.deployment(deployment_id)?;
+        let (service, from_environment, pipeline_id) = (
+            deployment.service,
+            deployment.environment,
+            deployment.pipeline_id,
+        );
+        let gate = match &pipeline_id {
+            Some(pipeline_id) => self
+                .check_promotion_gate(pipeline_id, &from_environment, &to_environment)
+                .err(),
+            None => None,
+        };
+        let budgets = self.error_budget_status(&service, &to_environment);
+        let exhausted: Vec<&str> = budgets
+            .iter()
+            .filter(|budget| budget.exhausted)
+            .map(|budget| budget.slo.as_str())
+            .collect();
+
+        let blocked_reason = if !healthy {
+            Some("Deployment not healthy for auto-promotion".to_string())
+        } else if gate.is_some() {
+            gate
+        } else if !exhausted.is_empty() {
+            Some(format!(
+                "Error budget exhausted for SLOs: {}",
+                exhausted.join(", ")
+            ))
         } else {
-            self.emit_deployment_event(
-                deployment_id,
-                "deployment.auto_promote_blocked",
-                json!({ "target_environment": to_environment }),
-            )?;
-            Err("Deployment not healthy for auto-promotion".to_string())
+            None
+        };
+
+        self.record_promotion_outcome(
+            deployment_id,
+            &to_environment,
+            blocked_reason.as_deref(),
+            &budgets,
+        )?;
+
+        match blocked_reason {
+            None => {
+                self.emit_deployment_event(
+                    deployment_id,
+                    "deployment.auto_promote",
+                    json!({
+                        "target_environment": to_environment,
+                        "error_budgets": budgets,
+                    }),
+                )?;
+                if to_environment == Environment::Production {
+                    self.pin_promoted_artifacts(deployment_id)?;
+                }
+                Ok(())
+            }
+            Some(reason) => {
+                self.emit_deployment_event(
+                    deployment_id,
+                    "deployment.auto_promote_blocked",
+                    json!({
+                        "target_environment": to_environment,
+                        "reason": reason,
+                        "error_budgets": budgets,
+                    }),
+                )?;
+                Err(reason)
+            }
         }
     }
 
+    fn record_promotion_outcome(
+        &self,
+        deployment_id: &str,
+        to_environment: &Environment,
+        blocked_reason: Option<&str>,
+        budgets: &[ErrorBudgetStatus],
+    ) -> Result<(), String> {
+        let recorded_at = time::OffsetDateTime::now_utc()
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|err| format!("failed to format outcome timestamp: {err}"))?;
+        self.instrumentation
+            .record_deployment_outcome(DeploymentOutcomeRecord {
+                workflow_id: deployment_id.to_string(),
+                stage_id: "auto_promote".to_string(),
+                agent_role: "cicd".to_string(),
+                agent_id: "cicd".to_string(),
+                action: format!("promote:{:?}", to_environment),
+                status: if blocked_reason.is_some() {
+                    "blocked".to_string()
+                } else {
+                    "promoted".to_string()
+                },
+                notes: json!({
+                    "reason": blocked_reason,
+                    "error_budgets": budgets,
+                }),
+                recorded_at,
+            })
+            .map_err(|err| format!("failed to record deployment outcome: {err}"))
+    }
+
     /// Complete end-to-end automation
     pub fn full_auto_pipeline(&self, crc_job_id: String, ai_confidence: f32) -> Result<(), String> {
         self.emit_pipeline_event(
@@ -1290,7 +3762,7 @@ impl CICDSystem {
         let mut proceed = true;
         let mut status = None;
         {
-            let pipelines = self.pipelines.lock().unwrap();
+            let pipelines = self.pipelines.lock();
             if let Some(pipeline) = pipelines.get(&pipeline_id) {
                 if !(pipeline.auto_approved
                     || matches!(pipeline.status, PipelineStatus::AgentApproved))
@@ -1314,7 +3786,8 @@ impl CICDSystem {
         self.execute_pipeline(&pipeline_id)?;
 
         // Deploy to Staging (auto)
-        let staging_deploy = self.deploy_to_environment(
+        let staging_deploy = self.deploy_pipeline_to_environment(
+            &pipeline_id,
             "v1.0.0".to_string(),
             Environment::Staging,
             DeploymentStrategy::BlueGreen,
@@ -1322,8 +3795,22 @@ impl CICDSystem {
 
         // Monitor and auto-promote
         if self.monitor_deployment(&staging_deploy)? {
+            if let Err(reason) = self.check_promotion_gate(
+                &pipeline_id,
+                &Environment::Staging,
+                &Environment::Production,
+            ) {
+                self.emit_pipeline_event(
+                    &pipeline_id,
+                    "cicd",
+                    "pipeline.full_auto.halted",
+                    json!({ "reason": reason }),
+                )?;
+                return Ok(());
+            }
             // Deploy to Production (auto)
-            let prod_deploy = self.deploy_to_environment(
+            let prod_deploy = self.deploy_pipeline_to_environment(
+                &pipeline_id,
                 "v1.0.0".to_string(),
                 Environment::Production,
                 DeploymentStrategy::Canary,
@@ -1364,19 +3851,28 @@ impl CICDSystem {
         pipeline_id: &str,
         status: PipelineStatus,
     ) -> Result<(), String> {
-        let (previous, changed) = {
-            let mut pipelines = self.pipelines.lock().unwrap();
+        let (previous, changed, finished) = {
+            let mut pipelines = self.pipelines.lock();
             if let Some(pipeline) = pipelines.get_mut(pipeline_id) {
                 let previous = pipeline.status.clone();
                 let changed = previous != status;
                 pipeline.status = status.clone();
-                (Some(previous), changed)
+                let finished = (changed
+                    && matches!(status, PipelineStatus::Success | PipelineStatus::Failed))
+                .then(|| pipeline.clone());
+                (Some(previous), changed, finished)
             } else {
                 return Err(format!("Pipeline not found: {}", pipeline_id));
             }
         };
 
         self.persist_state()?;
+        if let Some(pipeline) = finished {
+            self.append_history(
+                pipeline_id,
+                history::pipeline_samples(&pipeline, unix_now_ms()),
+            );
+        }
         if changed {
             self.emit_pipeline_event(
                 pipeline_id,
@@ -1391,12 +3887,230 @@ impl CICDSystem {
         Ok(())
     }
 
+    /// Check the pipeline definition's promotion gate between two environments.
+    ///
+    /// Pipelines without a definition, or without a gate for the pair, are not restricted.
+    pub fn check_promotion_gate(
+        &self,
+        pipeline_id: &str,
+        from: &Environment,
+        to: &Environment,
+    ) -> Result<(), String> {
+        self.check_pipeline_environment(pipeline_id, to)?;
+        let pipelines = self.pipelines.lock();
+        let pipeline = pipelines
+            .get(pipeline_id)
+            .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+        let Some(spec) = pipeline.spec.as_ref() else {
+            return Ok(());
+        };
+        let Some(gate) = spec.promotion_gate(from, to) else {
+            return Ok(());
+        };
+        if let Some(minimum) = gate.min_ai_confidence {
+            if pipeline.ai_confidence + f32::EPSILON < minimum {
+                return Err(format!(
+                    "promotion {:?} -> {:?} requires AI confidence {:.2}, pipeline has {:.2}",
+                    from, to, minimum, pipeline.ai_confidence
+                ));
+            }
+        }
+        let missing: Vec<&String> = gate
+            .required_roles
+            .iter()
+            .filter(|role| {
+                !pipeline
+                    .approvals_granted
+                    .iter()
+                    .any(|approval| &approval.role == *role)
+            })
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "promotion {:?} -> {:?} is waiting for approvals from {:?}",
+                from, to, missing
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reject environments the pipeline definition does not list as targets.
+    fn check_pipeline_environment(
+        &self,
+        pipeline_id: &str,
+        environment: &Environment,
+    ) -> Result<(), String> {
+        let pipelines = self.pipelines.lock();
+        let pipeline = pipelines
+            .get(pipeline_id)
+            .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+        match pipeline.spec.as_ref() {
+            Some(spec)
+                if !spec.environments.is_empty() && !spec.environments.contains(environment) =>
+            {
+                Err(format!(
+                    "environment {:?} is not a target of pipeline {}",
+                    environment, pipeline_id
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Write the signed evidence bundle of a pipeline to the namespace's evidence
+    /// directory and return its path.
+    pub fn export_evidence_bundle(&self, pipeline_id: &str) -> Result<PathBuf, String> {
+        let path = self
+            .evidence_bundle_dir()
+            .join(format!("{pipeline_id}.tar.gz"));
+        self.export_evidence_bundle_to(pipeline_id, &path)?;
+        Ok(path)
+    }
+
+    /// Gather the pipeline record, approvals, security scans, stage receipts, artifacts,
+    /// linked deployments, and operation signatures into a signed bundle at `destination`.
+    pub fn export_evidence_bundle_to(
+        &self,
+        pipeline_id: &str,
+        destination: &Path,
+    ) -> Result<EvidenceManifest, String> {
+        let pipeline = self
+            .pipelines
+            .lock()
+            .get(pipeline_id)
+            .cloned()
+            .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+        let mut deployments: Vec<Deployment> = self
+            .deployments
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|deployment| deployment.pipeline_id.as_deref() == Some(pipeline_id))
+            .cloned()
+            .collect();
+        deployments.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let ledger = self
+            .instrumentation
+            .evidence_ledger(RecoveryMode::Lenient)
+            .map_err(|err| format!("failed to read evidence ledger: {err}"))?;
+        let (receipts, related): (Vec<_>, Vec<_>) = ledger
+            .records
+            .into_iter()
+            .filter(|entry| {
+                ["workflow_id", "subject"]
+                    .iter()
+                    .any(|key| entry.payload.get(key).and_then(|v| v.as_str()) == Some(pipeline_id))
+            })
+            .partition(|entry| entry.kind == EvidenceLedgerKind::StageReceipt);
+
+        let signatures: Vec<serde_json::Value> = pipeline
+            .security_scans
+            .iter()
+            .map(|scan| {
+                (
+                    format!("security_scan:{}", scan.tool),
+                    &scan.signed_operation,
+                )
+            })
+            .chain(receipts.iter().chain(&related).map(|entry| {
+                (
+                    format!("ledger:{}", entry.reference),
+                    &entry.signed_operation,
+                )
+            }))
+            .map(|(source, signed)| {
+                json!({
+                    "source": source,
+                    "operation_id": signed.record.operation_id,
+                    "hash": signed.hash,
+                    "signature": signed.signature,
+                    "previous_signature": signed.previous_signature,
+                    "verified": noa_core::security::verify_signed_operation(signed),
+                })
+            })
+            .collect();
+
+        let mut contents = BundleContents::default();
+        contents.add_json("pipeline.json", &pipeline)?;
+        contents.add_json(
+            "approvals.json",
+            &json!({
+                "required": pipeline.approvals_required,
+                "granted": pipeline.approvals_granted,
+            }),
+        )?;
+        contents.add_json("security_scans.json", &pipeline.security_scans)?;
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        for artifact in pipeline
+            .security_scans
+            .iter()
+            .filter_map(|scan| scan.report_artifact.as_deref())
+        {
+            let path = root.join(artifact);
+            if let (Some(name), Ok(bytes)) = (path.file_name(), fs::read(&path)) {
+                contents.add_bytes(
+                    format!("security_reports/{}", name.to_string_lossy()),
+                    bytes,
+                );
+            }
+        }
+        contents.add_json("stage_receipts.json", &receipts)?;
+        contents.add_json("ledger_entries.json", &related)?;
+        contents.add_json("deployments.json", &deployments)?;
+        contents.add_json("artifacts.json", &self.list_artifacts(pipeline_id)?)?;
+        contents.add_json("signatures.json", &signatures)?;
+
+        let manifest = evidence::write_bundle(
+            destination,
+            contents,
+            EvidenceManifest {
+                bundle_version: evidence::EVIDENCE_BUNDLE_VERSION,
+                pipeline_id: pipeline_id.to_string(),
+                namespace: self.namespace.to_string(),
+                commit_sha: pipeline.commit_sha.clone(),
+                created_at: unix_now(),
+                files: Vec::new(),
+                skipped_ledger_records: ledger
+                    .skipped
+                    .iter()
+                    .map(|skipped| format!("{}: {}", skipped.location, skipped.reason))
+                    .collect(),
+            },
+        )?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.evidence_exported",
+            json!({
+                "bundle": destination,
+                "files": manifest.files.len(),
+            }),
+        )?;
+        Ok(manifest)
+    }
+
     /// Get pipeline status
     pub fn get_pipeline_status(&self, pipeline_id: &str) -> Option<PipelineStatus> {
-        let pipelines = self.pipelines.lock().unwrap();
+        let pipelines = self.pipelines.snapshot();
         pipelines.get(pipeline_id).map(|p| p.status.clone())
     }
 
+    /// Get pipeline status on behalf of another namespace, denied unless granted
+    pub fn get_pipeline_status_for(
+        &self,
+        requester: &Namespace,
+        registry: &NamespaceRegistry,
+        pipeline_id: &str,
+    ) -> Result<Option<PipelineStatus>, NamespaceError> {
+        registry.authorize(requester, &self.namespace)?;
+        Ok(self.get_pipeline_status(pipeline_id))
+    }
+
     /// Get deployment metrics
     pub fn get_metrics(&self, deployment_id: &str) -> Option<HealthMetrics> {
         let deployments = self.deployments.lock().unwrap();
@@ -1407,100 +4121,518 @@ impl CICDSystem {
 
     /// Get pipeline by CRC job
     pub fn get_pipeline_by_crc(&self, crc_job_id: &str) -> Option<Pipeline> {
-        let pipelines = self.pipelines.lock().unwrap();
-        pipelines
-            .values()
+        self.pipelines
+            .snapshot()
+            .pipelines()
             .find(|p| p.crc_job_id.as_deref() == Some(crc_job_id))
             .cloned()
     }
-}
 
-impl Default for CICDSystem {
-    fn default() -> Self {
-        Self::new()
+    /// Every pipeline as of the last update. Taking a snapshot never waits for a
+    /// running pipeline, so pollers should prefer it to repeated lookups.
+    pub fn pipeline_snapshot(&self) -> PipelineSnapshot {
+        self.pipelines.snapshot()
     }
-}
 
-#[derive(Debug, Serialize, Deserialize)]
-struct PersistedState {
-    pipelines: Vec<Pipeline>,
-    deployments: Vec<Deployment>,
-}
-
-#[cfg(test)]
-mod pipeline_tests {
-    use super::*;
-    use serde_json::Value;
-    use tempfile::tempdir;
+    /// Get a pipeline by id
+    pub fn get_pipeline(&self, pipeline_id: &str) -> Option<Pipeline> {
+        let pipelines = self.pipelines.snapshot();
+        pipelines.get(pipeline_id).cloned()
+    }
 
-    #[test]
-    fn test_pipeline_trigger() {
-        let workspace = tempdir().unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", workspace.path());
-        let cicd = CICDSystem::new();
-        cicd.configure_workspace_root(workspace.path());
-        let id = cicd
-            .trigger_pipeline("test".to_string(), "abc123".to_string())
-            .unwrap();
+    /// Every pipeline, oldest trigger first
+    pub fn list_pipelines(&self) -> Vec<Pipeline> {
+        let pipelines = self.pipelines.snapshot();
+        let mut listed: Vec<Pipeline> = pipelines.pipelines().cloned().collect();
+        listed.sort_by(|a, b| {
+            a.triggered_at
+                .cmp(&b.triggered_at)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        listed
+    }
 
-        assert!(cicd.get_pipeline_status(&id).is_some());
+    /// Deployments shipping the given pipeline's build
+    pub fn deployments_for_pipeline(&self, pipeline_id: &str) -> Vec<Deployment> {
+        let deployments = self.deployments.lock().unwrap();
+        let mut listed: Vec<Deployment> = deployments
+            .values()
+            .filter(|d| d.pipeline_id.as_deref() == Some(pipeline_id))
+            .cloned()
+            .collect();
+        listed.sort_by(|a, b| a.id.cmp(&b.id));
+        listed
     }
 
-    #[test]
-    fn test_auto_approve() {
-        let workspace = tempdir().unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", workspace.path());
-        let cicd = CICDSystem::new();
-        cicd.configure_workspace_root(workspace.path());
-        let id = cicd
-            .trigger_from_crc(
-                "test".to_string(),
-                "abc123".to_string(),
-                "crc_123".to_string(),
-                0.96, // High confidence
+    /// Record a build output of `stage` in the content-addressed artifact store and the
+    /// evidence ledger. Relative paths resolve against the workspace root.
+    pub fn attach_artifact(
+        &self,
+        pipeline_id: &str,
+        stage: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<ArtifactRecord, String> {
+        {
+            let pipelines = self.pipelines.lock();
+            let pipeline = pipelines
+                .get(pipeline_id)
+                .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+            if !pipeline.stages.iter().any(|s| s.name == stage) {
+                return Err(format!("Pipeline {} has no stage {}", pipeline_id, stage));
+            }
+        }
+        let (source, display) = self.artifact_source(path.as_ref());
+        let store = self.artifact_store();
+        let mut record = store.store(pipeline_id, stage, &source, &display)?;
+        let signed = self
+            .instrumentation
+            .log_artifact(
+                pipeline_id,
+                stage,
+                &record.path,
+                &record.sha256,
+                record.size,
             )
-            .unwrap();
+            .map_err(|err| format!("failed to ledger artifact {}: {err}", record.path))?;
+        record.ledger_reference = Some(signed.signature);
+        store.index(&record)?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.artifact_attached",
+            json!({
+                "stage": stage,
+                "path": record.path,
+                "sha256": record.sha256,
+                "size": record.size,
+            }),
+        )?;
+        Ok(record)
+    }
 
-        let status = cicd.get_pipeline_status(&id).unwrap();
-        assert_eq!(status, PipelineStatus::AutoApproved);
+    /// Artifacts attached to a pipeline, sorted by path
+    pub fn list_artifacts(&self, pipeline_id: &str) -> Result<Vec<ArtifactRecord>, String> {
+        self.artifact_store().list(pipeline_id)
     }
 
-    #[test]
-    fn test_agent_review() {
-        let workspace = tempdir().unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", workspace.path());
-        let cicd = CICDSystem::new();
-        cicd.configure_workspace_root(workspace.path());
-        let id = cicd
-            .trigger_from_crc(
-                "test".to_string(),
-                "abc123".to_string(),
-                "crc_123".to_string(),
-                0.85, // Lower confidence
-            )
-            .unwrap();
+    /// Retention class of a pipeline's artifacts and evidence bundle
+    pub fn artifact_retention(&self, pipeline_id: &str) -> Result<Retention, String> {
+        self.artifact_store().retention(pipeline_id)
+    }
 
-        let status = cicd.get_pipeline_status(&id).unwrap();
-        assert_eq!(status, PipelineStatus::AgentReview);
+    /// Keep a pipeline's artifacts and evidence bundle until it is unpinned.
+    pub fn pin_pipeline_artifacts(
+        &self,
+        pipeline_id: &str,
+        reason: impl Into<String>,
+    ) -> Result<Retention, String> {
+        self.set_artifact_retention(pipeline_id, RetentionClass::Pinned, Some(reason.into()))
     }
 
-    #[test]
-    fn test_agent_approval_policy() {
-        let workspace = tempdir().unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", workspace.path());
-        let cicd = CICDSystem::new();
-        cicd.configure_workspace_root(workspace.path());
-        let pipeline_id = cicd
-            .trigger_doc_refresh_pipeline(
-                "abc123".to_string(),
-                "docs update".to_string(),
-                vec![AgentApprovalRequirement {
-                    role: "release-agent".to_string(),
-                    minimum_trust_score: 0.7,
-                    required_evidence_tags: vec!["ledger:release".to_string()],
-                }],
-            )
-            .unwrap();
+    /// Return a pinned pipeline's artifacts to the interim TTL.
+    pub fn unpin_pipeline_artifacts(&self, pipeline_id: &str) -> Result<Retention, String> {
+        self.set_artifact_retention(pipeline_id, RetentionClass::Interim, None)
+    }
+
+    /// Delete the artifacts and evidence bundles of pipelines whose retention expired at
+    /// `now` (seconds since the epoch).
+    pub fn sweep_artifacts(
+        &self,
+        policy: &RetentionPolicy,
+        now: u64,
+    ) -> Result<RetentionSweep, String> {
+        let sweep = self.artifact_store().sweep(policy, now)?;
+        let evidence_dir = self.evidence_bundle_dir();
+        for pipeline_id in &sweep.expired_pipelines {
+            let bundle = evidence_dir.join(format!("{pipeline_id}.tar.gz"));
+            match fs::remove_file(&bundle) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(format!("failed to remove {}: {err}", bundle.display())),
+            }
+        }
+        Ok(sweep)
+    }
+
+    /// Storage manager guard keeping the artifact indexes and the artifacts and evidence
+    /// bundles of pinned pipelines out of budget eviction.
+    pub fn artifact_eviction_guard(&self) -> Result<noa_core::storage::EvictionGuard, String> {
+        let store = self.artifact_store();
+        let artifacts = store.eviction_guard()?;
+        let evidence_dir = self.evidence_bundle_dir();
+        let bundles: HashSet<PathBuf> = store
+            .pinned_pipelines()?
+            .into_iter()
+            .map(|pipeline_id| evidence_dir.join(format!("{pipeline_id}.tar.gz")))
+            .collect();
+        Ok(Arc::new(move |path: &Path| {
+            artifacts(path) || bundles.contains(path)
+        }))
+    }
+
+    fn set_artifact_retention(
+        &self,
+        pipeline_id: &str,
+        class: RetentionClass,
+        reason: Option<String>,
+    ) -> Result<Retention, String> {
+        let store = self.artifact_store();
+        let known = self.pipelines.lock().contains_key(pipeline_id);
+        if !known && store.list(pipeline_id)?.is_empty() {
+            return Err(format!("Pipeline not found: {}", pipeline_id));
+        }
+        let retention = store.set_retention(pipeline_id, class, reason)?;
+        self.emit_pipeline_event(
+            pipeline_id,
+            "cicd",
+            "pipeline.artifact_retention",
+            json!({ "class": retention.class, "reason": retention.reason }),
+        )?;
+        Ok(retention)
+    }
+
+    /// Record the outcome class of a run's artifacts; pinned artifacts stay pinned.
+    fn classify_artifacts(
+        &self,
+        pipeline_id: &str,
+        class: RetentionClass,
+        reason: Option<String>,
+    ) -> Result<(), String> {
+        let store = self.artifact_store();
+        if store.list(pipeline_id)?.is_empty() {
+            return Ok(());
+        }
+        let current = store.retention(pipeline_id)?.class;
+        if current != class && current != RetentionClass::Pinned {
+            store.set_retention(pipeline_id, class, reason)?;
+        }
+        Ok(())
+    }
+
+    /// Pin the build a deployment promoted to production.
+    fn pin_promoted_artifacts(&self, deployment_id: &str) -> Result<(), String> {
+        let pipeline_id = self
+            .deployments
+            .lock()
+            .unwrap()
+            .get(deployment_id)
+            .and_then(|deployment| deployment.pipeline_id.clone());
+        let Some(pipeline_id) = pipeline_id else {
+            return Ok(());
+        };
+        self.pin_pipeline_artifacts(
+            &pipeline_id,
+            format!("promoted to production by {deployment_id}"),
+        )?;
+        Ok(())
+    }
+
+    fn evidence_bundle_dir(&self) -> PathBuf {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        root.join(self.namespace.scope_path(EVIDENCE_BUNDLE_DIR))
+    }
+
+    /// Re-hash an attached artifact's stored copy and workspace file, and check that its
+    /// evidence ledger entry is present and correctly signed.
+    pub fn verify_artifact(
+        &self,
+        pipeline_id: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<ArtifactVerification, String> {
+        let (source, display) = self.artifact_source(path.as_ref());
+        let record = self
+            .list_artifacts(pipeline_id)?
+            .into_iter()
+            .find(|record| record.path == display)
+            .ok_or_else(|| format!("Pipeline {} has no artifact {}", pipeline_id, display))?;
+        let mut verification = self.artifact_store().verify(&record, Some(&source))?;
+        let ledger = self
+            .instrumentation
+            .evidence_ledger(RecoveryMode::Lenient)
+            .map_err(|err| format!("failed to read evidence ledger: {err}"))?;
+        let ledgered = ledger.records.iter().any(|entry| {
+            entry.kind == EvidenceLedgerKind::Artifact
+                && entry.reference == record.sha256
+                && entry.payload["subject"] == pipeline_id
+                && entry.payload["path"] == record.path.as_str()
+                && record.ledger_reference.as_deref() == Some(&entry.signed_operation.signature)
+                && noa_core::security::verify_signed_operation(&entry.signed_operation)
+        });
+        verification.ledger_verified = Some(ledgered);
+        Ok(verification)
+    }
+
+    /// Artifacts of the pipeline a deployment ships
+    pub fn deployment_artifacts(&self, deployment_id: &str) -> Result<Vec<ArtifactRecord>, String> {
+        let pipeline_id = self
+            .deployments
+            .lock()
+            .unwrap()
+            .get(deployment_id)
+            .ok_or_else(|| format!("Deployment not found: {}", deployment_id))?
+            .pipeline_id
+            .clone();
+        match pipeline_id {
+            Some(pipeline_id) => self.list_artifacts(&pipeline_id),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Absolute location of an artifact path and the form it is recorded under
+    fn artifact_source(&self, path: &Path) -> (PathBuf, String) {
+        let root = self
+            .workspace_root
+            .lock()
+            .expect("workspace root lock poisoned")
+            .clone();
+        let source = root.join(path);
+        let display = source
+            .strip_prefix(&root)
+            .unwrap_or(&source)
+            .to_string_lossy()
+            .into_owned();
+        (source, display)
+    }
+
+    /// Promotion outcomes recorded for a deployment, oldest first
+    pub fn deployment_outcomes(
+        &self,
+        deployment_id: &str,
+    ) -> Result<Vec<DeploymentOutcomeRecord>, String> {
+        let outcomes = self
+            .instrumentation
+            .deployment_outcomes()
+            .map_err(|err| format!("failed to read deployment outcomes: {err}"))?;
+        Ok(outcomes
+            .into_iter()
+            .filter(|outcome| outcome.workflow_id == deployment_id)
+            .collect())
+    }
+}
+
+impl Default for CICDSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn unix_now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn lint_baseline_path(root: &Path, stage: &Stage) -> PathBuf {
+    root.join(
+        stage
+            .parameters
+            .get("baseline")
+            .and_then(|value| value.as_str())
+            .unwrap_or(LINT_BASELINE_FILE),
+    )
+}
+
+fn workspace_policy_path(root: &Path, stage: &Stage) -> PathBuf {
+    root.join(
+        stage
+            .parameters
+            .get("policy")
+            .and_then(|value| value.as_str())
+            .unwrap_or(WORKSPACE_POLICY_FILE),
+    )
+}
+
+fn default_service() -> String {
+    DEFAULT_SERVICE.to_string()
+}
+
+fn default_stages() -> Vec<Stage> {
+    [
+        ("validate", PipelineStage::Validate),
+        ("build", PipelineStage::Build),
+        ("test", PipelineStage::Test),
+        (
+            "single_host_acceptance",
+            PipelineStage::SingleHostAcceptance,
+        ),
+        ("deploy", PipelineStage::Deploy),
+    ]
+    .into_iter()
+    .map(|(name, stage_type)| Stage {
+        name: name.to_string(),
+        stage_type,
+        status: PipelineStatus::Pending,
+        duration_ms: None,
+        parameters: serde_json::Value::Null,
+        retry: None,
+        attempts: 0,
+        depends_on: Vec::new(),
+    })
+    .collect()
+}
+
+/// Version of the pipeline state document written by this release. Documents
+/// without a version predate it and load as version 1.
+pub const PIPELINE_STATE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedState {
+    version: u32,
+    pipelines: Vec<Pipeline>,
+    deployments: Vec<Deployment>,
+    #[serde(default)]
+    schedules: Vec<PipelineSchedule>,
+    #[serde(default)]
+    slots: Vec<EnvironmentSlots>,
+    /// SLOs and the observations their error budgets are computed from, so a restart
+    /// does not reset the budgets checked by [`CICDSystem::auto_promote`].
+    #[serde(default)]
+    slo_definitions: Vec<SloDefinition>,
+    #[serde(default)]
+    slo_observations: Vec<SloObservation>,
+}
+
+impl PersistedState {
+    /// Parse each pipeline, deployment, and schedule on its own so that, in lenient mode, one
+    /// corrupt record is skipped instead of rejecting the document.
+    fn parse(raw: &str, mode: RecoveryMode) -> Result<(Self, Vec<SkippedRecord>), String> {
+        let document: serde_json::Value =
+            serde_json::from_str(raw).map_err(|err| err.to_string())?;
+        if !document.is_object() {
+            return Err("expected a JSON object".to_string());
+        }
+        let version = Self::version(&document)?;
+        if version > PIPELINE_STATE_VERSION {
+            return Err(format!(
+                "state version {version} is newer than supported version {PIPELINE_STATE_VERSION}"
+            ));
+        }
+        let pipelines =
+            recovery::parse_records(&document, "pipelines", mode).map_err(|err| err.to_string())?;
+        let deployments = recovery::parse_records(&document, "deployments", mode)
+            .map_err(|err| err.to_string())?;
+        let schedules =
+            recovery::parse_records(&document, "schedules", mode).map_err(|err| err.to_string())?;
+        let slots =
+            recovery::parse_records(&document, "slots", mode).map_err(|err| err.to_string())?;
+        let slo_definitions = recovery::parse_records(&document, "slo_definitions", mode)
+            .map_err(|err| err.to_string())?;
+        let slo_observations = recovery::parse_records(&document, "slo_observations", mode)
+            .map_err(|err| err.to_string())?;
+        let mut skipped = pipelines.skipped;
+        skipped.extend(deployments.skipped);
+        skipped.extend(schedules.skipped);
+        skipped.extend(slots.skipped);
+        skipped.extend(slo_definitions.skipped);
+        skipped.extend(slo_observations.skipped);
+        Ok((
+            Self {
+                version,
+                pipelines: pipelines.records,
+                deployments: deployments.records,
+                schedules: schedules.records,
+                slots: slots.records,
+                slo_definitions: slo_definitions.records,
+                slo_observations: slo_observations.records,
+            },
+            skipped,
+        ))
+    }
+
+    fn version(document: &serde_json::Value) -> Result<u32, String> {
+        match document.get("version") {
+            None | Some(serde_json::Value::Null) => Ok(1),
+            Some(value) => value
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| format!("state version must be an unsigned integer, found {value}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod pipeline_tests {
+    use super::*;
+    use noa_core::cost::{CostBudget, CostLimits};
+    use serde_json::Value;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_pipeline_trigger() {
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let id = cicd
+            .trigger_pipeline("test".to_string(), "abc123".to_string())
+            .unwrap();
+
+        assert!(cicd.get_pipeline_status(&id).is_some());
+    }
+
+    #[test]
+    fn test_auto_approve() {
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let id = cicd
+            .trigger_from_crc(
+                "test".to_string(),
+                "abc123".to_string(),
+                "crc_123".to_string(),
+                0.96, // High confidence
+            )
+            .unwrap();
+
+        let status = cicd.get_pipeline_status(&id).unwrap();
+        assert_eq!(status, PipelineStatus::AutoApproved);
+    }
+
+    #[test]
+    fn test_agent_review() {
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let id = cicd
+            .trigger_from_crc(
+                "test".to_string(),
+                "abc123".to_string(),
+                "crc_123".to_string(),
+                0.85, // Lower confidence
+            )
+            .unwrap();
+
+        let status = cicd.get_pipeline_status(&id).unwrap();
+        assert_eq!(status, PipelineStatus::AgentReview);
+    }
+
+    #[test]
+    fn test_agent_approval_policy() {
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let pipeline_id = cicd
+            .trigger_doc_refresh_pipeline(
+                "abc123".to_string(),
+                "docs update".to_string(),
+                vec![AgentApprovalRequirement {
+                    role: "release-agent".to_string(),
+                    minimum_trust_score: 0.7,
+                    required_evidence_tags: vec!["ledger:release".to_string()],
+                }],
+            )
+            .unwrap();
 
         // Low trust should escalate
         let result = cicd.register_agent_approval(
@@ -1535,11 +4667,35 @@ mod pipeline_tests {
         );
     }
 
+    #[test]
+    fn test_docs_refresh_attaches_dead_code_report() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(workspace.path().join("stale.rs"), "pub fn forgotten() {}\n").unwrap();
+        noa_symbol_graph::SymbolGraphBuilder::new(workspace.path())
+            .index()
+            .unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let id = cicd
+            .trigger_doc_refresh_pipeline("abc123".to_string(), "docs".to_string(), Vec::new())
+            .unwrap();
+
+        cicd.execute_pipeline(&id).unwrap();
+        let report_path = workspace
+            .path()
+            .join(PIPELINE_REPORTS_DIR)
+            .join(&id)
+            .join("dead_code.json");
+        let report: DeadCodeReport =
+            serde_json::from_str(&std::fs::read_to_string(report_path).unwrap()).unwrap();
+        assert_eq!(report.orphan_symbols[0].name, "forgotten");
+        assert_eq!(report.orphan_files.len(), 1);
+    }
+
     #[test]
     fn test_pipeline_telemetry_log() {
         let workspace = tempdir().unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", workspace.path());
-        let cicd = CICDSystem::new();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
         cicd.configure_workspace_root(workspace.path());
         let id = cicd
             .trigger_from_crc(
@@ -1550,6 +4706,7 @@ mod pipeline_tests {
             )
             .unwrap();
 
+        cicd.flush_events().unwrap();
         let log_path = workspace
             .path()
             .join(".workspace")
@@ -1569,4 +4726,1500 @@ mod pipeline_tests {
             Some("pipeline.auto_approved")
         );
     }
+
+    #[test]
+    fn test_pipeline_uses_definition_when_present() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            r#"
+stages:
+  - name: lint
+    type: validate
+  - name: compile
+    type: build
+approvals:
+  - role: release-agent
+    minimum_trust_score: 0.7
+environments: [staging, production]
+promotion_gates:
+  - from: staging
+    to: production
+    required_roles: [release-agent]
+"#,
+        )
+        .unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+
+        let id = cicd
+            .trigger_pipeline("spec".to_string(), "abc123".to_string())
+            .unwrap();
+        {
+            let pipelines = cicd.pipelines.lock();
+            let pipeline = pipelines.get(&id).unwrap();
+            let names: Vec<&str> = pipeline.stages.iter().map(|s| s.name.as_str()).collect();
+            assert_eq!(names, vec!["lint", "compile"]);
+            assert_eq!(pipeline.status, PipelineStatus::AgentReview);
+        }
+
+        assert!(cicd
+            .check_promotion_gate(&id, &Environment::Staging, &Environment::Production)
+            .is_err());
+        cicd.register_agent_approval(&id, "release-agent", "agent-1", 0.9, vec![], vec![])
+            .unwrap();
+        cicd.check_promotion_gate(&id, &Environment::Staging, &Environment::Production)
+            .expect("gate satisfied after approval");
+    }
+
+    #[test]
+    fn test_promotion_gates_block_deployments_and_auto_promotion() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            r#"
+stages:
+  - name: compile
+    type: build
+approvals:
+  - role: release-agent
+    minimum_trust_score: 0.7
+environments: [staging, production]
+promotion_gates:
+  - from: staging
+    to: production
+    required_roles: [release-agent]
+"#,
+        )
+        .unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let id = cicd
+            .trigger_pipeline("gated".to_string(), "abc123".to_string())
+            .unwrap();
+
+        let err = cicd
+            .deploy_pipeline_to_environment(
+                &id,
+                "v1".to_string(),
+                Environment::Development,
+                DeploymentStrategy::RollingUpdate,
+            )
+            .unwrap_err();
+        assert!(err.contains("not a target"), "{err}");
+
+        let staging = cicd
+            .deploy_pipeline_to_environment(
+                &id,
+                "v1".to_string(),
+                Environment::Staging,
+                DeploymentStrategy::RollingUpdate,
+            )
+            .unwrap();
+        cicd.record_deployment_metrics(
+            &staging,
+            HealthMetrics {
+                error_rate: 0.5,
+                response_time_ms: 80,
+                cpu_usage: 30.0,
+                memory_usage: 40.0,
+                active_connections: 50,
+            },
+        )
+        .unwrap();
+        let err = cicd
+            .auto_promote(&staging, Environment::Production)
+            .unwrap_err();
+        assert!(err.contains("waiting for approvals"), "{err}");
+
+        cicd.register_agent_approval(&id, "release-agent", "agent-1", 0.9, vec![], vec![])
+            .unwrap();
+        cicd.auto_promote(&staging, Environment::Production)
+            .expect("promotion allowed after approval");
+    }
+
+    #[test]
+    fn test_build_stage_resumes_from_checkpoint() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            r#"
+stages:
+  - name: compile
+    type: build
+    parameters:
+      targets: [core, agents, server]
+      artifacts:
+        agents: bin/agents
+"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(workspace.path().join("bin")).unwrap();
+        std::fs::write(workspace.path().join("bin/agents"), "rebuilt").unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let id = cicd
+            .trigger_pipeline("resume".to_string(), "abc123".to_string())
+            .unwrap();
+
+        // An interrupted attempt finished `core` and built `agents` from an older binary
+        let store = cicd.checkpoints();
+        let core = checkpoint::unit_hash("abc123", "core", None).unwrap();
+        store
+            .record(&id, "compile", "abc123", "core", &core)
+            .unwrap();
+        store
+            .record(&id, "compile", "abc123", "agents", "stale")
+            .unwrap();
+        let saved = cicd.stage_checkpoint(&id, "compile").unwrap().unwrap();
+        assert!(saved.is_complete("core", &core));
+
+        cicd.execute_pipeline(&id).unwrap();
+        cicd.flush_events().unwrap();
+        let log = std::fs::read_to_string(
+            workspace
+                .path()
+                .join(".workspace/indexes/pipeline_events.log"),
+        )
+        .unwrap();
+        let events: Vec<Value> = log
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .map(|entry| entry["event"].clone())
+            .filter(|event| event["scope"] == id.as_str())
+            .collect();
+        let built: Vec<&str> = events
+            .iter()
+            .filter(|event| event["event_type"] == "pipeline.stage_unit_completed")
+            .filter_map(|event| event["metadata"]["unit"].as_str())
+            .collect();
+        assert_eq!(built, vec!["agents", "server"]);
+        let summary = events
+            .iter()
+            .find(|event| event["event_type"] == "pipeline.build_components")
+            .unwrap();
+        assert_eq!(summary["metadata"]["resumed"], json!(["core"]));
+        assert!(cicd.stage_checkpoint(&id, "compile").unwrap().is_none());
+        let attached = cicd.list_artifacts(&id).unwrap();
+        assert_eq!(attached.len(), 1);
+        assert_eq!(
+            (attached[0].stage.as_str(), attached[0].path.as_str()),
+            ("compile", "bin/agents")
+        );
+    }
+
+    #[test]
+    fn test_stages_reserve_host_resources_while_running() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            r#"
+stages:
+  - name: compile
+    type: build
+    parameters:
+      targets: [core]
+      resources: { cpu_cores: 2, vram_mb: 512 }
+  - name: unit
+    type: test
+    parameters:
+      suites: [unit]
+"#,
+        )
+        .unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let ledger = Arc::new(CostLedger::new().with_budget(CostBudget::new(
+            "no-gpu",
+            CostLimits {
+                gpu_seconds: Some(0.0),
+                ..CostLimits::default()
+            },
+        )));
+        cicd.configure_cost_accounting(ledger.clone());
+        let id = cicd
+            .trigger_pipeline("reserve".to_string(), "abc123".to_string())
+            .unwrap();
+        cicd.execute_pipeline(&id).unwrap();
+
+        let usage = ledger.subject_usage(CostKind::Pipeline, &id);
+        assert!(usage.cpu_seconds > 0.0);
+        assert!(usage.gpu_seconds > 0.0);
+        assert_eq!(
+            ledger.owner(CostKind::Pipeline, &id).unwrap().identity,
+            "pipeline:reserve"
+        );
+        assert_eq!(ledger.alerts().len(), 1);
+
+        cicd.flush_events().unwrap();
+        let log = std::fs::read_to_string(
+            workspace
+                .path()
+                .join(".workspace/indexes/pipeline_events.log"),
+        )
+        .unwrap();
+        let reserved: Vec<(String, Value)> = log
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .map(|entry| entry["event"].clone())
+            .filter(|event| event["scope"] == id.as_str())
+            .filter(|event| event["event_type"] == "pipeline.stage_resources_reserved")
+            .map(|event| {
+                (
+                    event["metadata"]["stage"].as_str().unwrap().to_string(),
+                    event["metadata"]["resources"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            reserved,
+            vec![
+                (
+                    "compile".to_string(),
+                    json!({"cpu_cores": 2, "vram_mb": 512})
+                ),
+                ("unit".to_string(), json!({"cpu_cores": 1, "vram_mb": 0})),
+            ]
+        );
+        assert!(log.lines().any(
+            |line| line.contains("cost.budget_alert") && line.contains("\"budget\":\"no-gpu\"")
+        ));
+        let holder = format!("pipeline:{}:", id);
+        assert!(!noa_core::scheduler::global()
+            .summary()
+            .resources
+            .reservations
+            .iter()
+            .any(|reservation| reservation.holder.starts_with(&holder)));
+    }
+
+    #[test]
+    fn test_build_footprint_flags_regressions_against_last_success() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            r#"
+stages:
+  - name: compile
+    type: build
+    parameters:
+      targets: [rust]
+      regression_percent: 20
+      binaries:
+        - name: gateway
+          path: bin/gateway
+          package: noa_gateway
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            workspace.path().join("Cargo.lock"),
+            "version = 3\n\n[[package]]\nname = \"noa_gateway\"\nversion = \"0.1.0\"\ndependencies = [\"serde\"]\n\n[[package]]\nname = \"serde\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(workspace.path().join("bin")).unwrap();
+        std::fs::write(workspace.path().join("bin/gateway"), vec![0u8; 1000]).unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+
+        let first = cicd
+            .trigger_pipeline("gateway".to_string(), "abc123".to_string())
+            .unwrap();
+        cicd.execute_pipeline(&first).unwrap();
+        let report = cicd.footprint_report(&first).unwrap();
+        assert_eq!(report.footprint.binaries[0].size_bytes, 1000);
+        assert_eq!(report.footprint.binaries[0].dependencies, Some(1));
+        assert!(report.comparison.previous_pipeline.is_none());
+
+        std::fs::write(workspace.path().join("bin/gateway"), vec![0u8; 1500]).unwrap();
+        let second = cicd
+            .trigger_pipeline("gateway".to_string(), "def456".to_string())
+            .unwrap();
+        cicd.execute_pipeline(&second).unwrap();
+        let report = cicd.footprint_report(&second).unwrap();
+        assert_eq!(
+            report.comparison.previous_pipeline.as_deref(),
+            Some(first.as_str())
+        );
+        let regressions: Vec<_> = report.comparison.regressions().collect();
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].metric, footprint::FootprintMetric::SizeBytes);
+        assert_eq!(regressions[0].change_percent, 50.0);
+        let markdown = std::fs::read_to_string(
+            workspace
+                .path()
+                .join(PIPELINE_REPORTS_DIR)
+                .join(&second)
+                .join("footprint.md"),
+        )
+        .unwrap();
+        assert!(markdown.contains("| gateway | size | 1000 | 1500 | +50.0% ⚠ |"));
+    }
+
+    #[test]
+    fn test_compare_reports_test_coverage_and_size_regressions() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            r#"
+stages:
+  - name: compile
+    type: build
+    parameters:
+      targets: [rust]
+      binaries:
+        - name: gateway
+          path: bin/gateway
+  - name: unit
+    type: test
+    parameters:
+      suites: [unit]
+      results: target/tests.json
+      coverage: target/coverage.json
+"#,
+        )
+        .unwrap();
+        let write_run = |size: usize, second_test: &str, coverage: f64| {
+            std::fs::create_dir_all(workspace.path().join("bin")).unwrap();
+            std::fs::create_dir_all(workspace.path().join("target")).unwrap();
+            std::fs::write(workspace.path().join("bin/gateway"), vec![0u8; size]).unwrap();
+            std::fs::write(
+                workspace.path().join("target/tests.json"),
+                format!(
+                    "{{\"type\":\"test\",\"event\":\"ok\",\"name\":\"parses\"}}\n{{\"type\":\"test\",\"event\":\"{second_test}\",\"name\":\"routes\"}}\n"
+                ),
+            )
+            .unwrap();
+            std::fs::write(
+                workspace.path().join("target/coverage.json"),
+                format!(r#"{{"data":[{{"totals":{{"lines":{{"percent":{coverage}}}}}}}]}}"#),
+            )
+            .unwrap();
+        };
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+
+        write_run(1000, "ok", 80.0);
+        let base = cicd
+            .trigger_pipeline("gateway".to_string(), "abc123".to_string())
+            .unwrap();
+        cicd.execute_pipeline(&base).unwrap();
+        write_run(1500, "failed", 72.5);
+        let head = cicd
+            .trigger_pipeline("gateway".to_string(), "def456".to_string())
+            .unwrap();
+        // The failing test is recorded, not enforced, by the Test stage.
+        cicd.execute_pipeline(&head).unwrap();
+
+        let comparison = cicd.compare(&base, &head).unwrap();
+        assert_eq!(comparison.head.commit_sha, "def456");
+        assert_eq!(comparison.stages.len(), 2);
+        let tests = comparison.tests.as_ref().unwrap();
+        assert_eq!(tests.newly_failing, vec!["routes".to_string()]);
+        assert_eq!(comparison.coverage.delta(), Some(-7.5));
+        let footprint = comparison.footprint.as_ref().unwrap();
+        assert_eq!(footprint.regressions().count(), 1);
+        assert!(comparison.has_regressions());
+        assert!(comparison.to_markdown().contains("Newly failing: routes"));
+        assert!(cicd.compare(&base, "missing").is_err());
+    }
+
+    struct CannedLint;
+
+    impl LintRunner for CannedLint {
+        fn rustfmt(&self, workspace: &Path) -> Result<String, String> {
+            Ok(format!(
+                "Diff in {}/core/src/lib.rs at line 3:\n",
+                workspace.display()
+            ))
+        }
+
+        fn clippy(&self, _workspace: &Path) -> Result<String, String> {
+            Ok(r#"{"reason":"compiler-message","message":{"message":"redundant clone","level":"warning","code":{"code":"clippy::redundant_clone"},"spans":[{"file_name":"cicd/src/lib.rs","line_start":42,"is_primary":true}]}}"#.to_string())
+        }
+    }
+
+    #[test]
+    fn test_lint_stage_fails_only_on_new_violations() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(workspace.path().join("Cargo.toml"), "[workspace]\n").unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            r#"
+stages:
+  - name: lint
+    type: lint
+    parameters:
+      baseline: ci/lint-baseline.json
+"#,
+        )
+        .unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        cicd.configure_lint_runner(Arc::new(CannedLint));
+
+        let first = cicd
+            .trigger_pipeline("lint".to_string(), "abc123".to_string())
+            .unwrap();
+        let err = cicd
+            .execute_pipeline(&first)
+            .expect_err("new findings fail");
+        assert!(err.contains("2 new violations"), "{err}");
+        let report = cicd.lint_report(&first).unwrap();
+        assert_eq!(report.findings[0].file, "cicd/src/lib.rs");
+        assert_eq!(report.findings[0].lint, "clippy::redundant_clone");
+        assert_eq!(report.findings[1].file, "core/src/lib.rs");
+        assert_eq!(report.findings[1].line, 3);
+
+        let baseline = cicd.accept_lint_baseline(&first).unwrap();
+        assert_eq!(baseline, workspace.path().join("ci/lint-baseline.json"));
+
+        let second = cicd
+            .trigger_pipeline("lint".to_string(), "def456".to_string())
+            .unwrap();
+        cicd.execute_pipeline(&second).unwrap();
+        let report = cicd.lint_report(&second).unwrap();
+        assert_eq!(report.baselined, 2);
+        assert_eq!(report.new_findings().count(), 0);
+    }
+
+    #[derive(Default)]
+    struct FakeRoutes {
+        routes: Mutex<HashMap<String, Value>>,
+    }
+
+    impl RouteController for FakeRoutes {
+        fn current(&self, id: &str) -> Result<Option<Value>, String> {
+            Ok(self.routes.lock().unwrap().get(id).cloned())
+        }
+
+        fn apply(
+            &self,
+            route: &ReverseProxyRoute,
+        ) -> Result<noa_caddy_manager::AdminResponse, String> {
+            let id = route.id.clone().ok_or("route without id")?;
+            let config = json!({ "@id": id, "upstreams": route.upstreams });
+            let status = match self.routes.lock().unwrap().insert(id, config) {
+                Some(_) => 200,
+                None => 201,
+            };
+            Ok(noa_caddy_manager::AdminResponse {
+                status,
+                body: String::new(),
+            })
+        }
+
+        fn restore(
+            &self,
+            id: &str,
+            previous: Option<&Value>,
+        ) -> Result<noa_caddy_manager::AdminResponse, String> {
+            let mut routes = self.routes.lock().unwrap();
+            match previous {
+                Some(config) => routes.insert(id.to_string(), config.clone()),
+                None => routes.remove(id),
+            };
+            Ok(noa_caddy_manager::AdminResponse {
+                status: 200,
+                body: String::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_deploy_stage_pushes_route_and_rollback_restores_it() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            r#"
+stages:
+  - name: deploy
+    type: deploy
+    parameters:
+      environment: staging
+"#,
+        )
+        .unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let routes = Arc::new(FakeRoutes::default());
+        cicd.configure_route_controller(routes.clone());
+        let route_id = routing::route_id(DEFAULT_SERVICE, &Environment::Staging);
+        let upstream = |port: u16| ReverseProxyRoute {
+            domain: "staging.noa.local".to_string(),
+            upstreams: vec![format!("127.0.0.1:{port}")],
+            ..ReverseProxyRoute::default()
+        };
+
+        cicd.configure_environment_route(Environment::Staging, upstream(8080));
+        let first = cicd
+            .trigger_pipeline("deploy".to_string(), "abc123".to_string())
+            .unwrap();
+        cicd.execute_pipeline(&first).unwrap();
+        let created = cicd.deployments_for_pipeline(&first).remove(0);
+        let route = created.route.clone().unwrap();
+        assert_eq!(created.version, "abc123");
+        assert_eq!(route.route_id, route_id);
+        assert_eq!(route.applied.status, 201);
+        assert!(route.previous.is_none());
+
+        cicd.configure_environment_route(Environment::Staging, upstream(8081));
+        let second = cicd
+            .trigger_pipeline("deploy".to_string(), "def456".to_string())
+            .unwrap();
+        cicd.execute_pipeline(&second).unwrap();
+        let replaced = cicd.deployments_for_pipeline(&second).remove(0);
+        let route = replaced.route.clone().unwrap();
+        assert_eq!(route.applied.status, 200);
+        let previous = route.previous.clone().unwrap();
+        assert_eq!(previous["upstreams"][0], "127.0.0.1:8080");
+
+        cicd.rollback(&replaced.id).unwrap();
+        assert_eq!(routes.current(&route_id).unwrap(), Some(previous));
+        let rolled_back = cicd.deployments_for_pipeline(&second).remove(0);
+        assert_eq!(rolled_back.status, PipelineStatus::RolledBack);
+        assert!(rolled_back.route.unwrap().restored.is_some());
+
+        cicd.rollback(&created.id).unwrap();
+        assert_eq!(routes.current(&route_id).unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_definition_blocks_trigger() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(workspace.path().join("pipeline.toml"), "stages = []\n").unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+
+        let err = cicd
+            .trigger_pipeline("spec".to_string(), "abc123".to_string())
+            .expect_err("empty definition rejected");
+        assert!(err.contains("at least one stage"), "{err}");
+    }
+
+    struct PlanExecutor;
+
+    impl StageExecutor for PlanExecutor {
+        fn stage_type(&self) -> &str {
+            "terraform-plan"
+        }
+
+        fn execute(&self, context: &StageContext) -> Result<stage_plugins::StageReport, String> {
+            if context.parameters["workspace"] == "prod" {
+                Ok(stage_plugins::StageReport::succeeded(
+                    "plan: 0 to add",
+                    json!({ "changes": 0 }),
+                ))
+            } else {
+                Ok(stage_plugins::StageReport::failed("unknown workspace"))
+            }
+        }
+    }
+
+    #[test]
+    fn test_plugin_stage_runs_registered_executor() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            "stages:\n  - name: plan\n    type: terraform-plan\n    parameters:\n      workspace: prod\n",
+        )
+        .unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+
+        let err = cicd
+            .trigger_pipeline("infra".to_string(), "abc123".to_string())
+            .expect_err("unregistered stage type rejected");
+        assert!(err.contains("terraform-plan"), "{err}");
+
+        cicd.register_stage_executor(Arc::new(PlanExecutor))
+            .unwrap();
+        let id = cicd
+            .trigger_pipeline("infra".to_string(), "abc123".to_string())
+            .unwrap();
+        cicd.execute_pipeline(&id).expect("plugin stage succeeds");
+        assert_eq!(
+            cicd.get_pipeline_status(&id).unwrap(),
+            PipelineStatus::Success
+        );
+    }
+
+    /// Blocks its first run until released, so a test can act while a stage runs.
+    struct GateExecutor {
+        started: Mutex<Option<std::sync::mpsc::Sender<()>>>,
+        release: Mutex<std::sync::mpsc::Receiver<()>>,
+    }
+
+    impl StageExecutor for GateExecutor {
+        fn stage_type(&self) -> &str {
+            "gate"
+        }
+
+        fn execute(&self, _context: &StageContext) -> Result<stage_plugins::StageReport, String> {
+            if let Some(started) = self.started.lock().unwrap().take() {
+                started.send(()).unwrap();
+                self.release.lock().unwrap().recv().unwrap();
+            }
+            Ok(stage_plugins::StageReport::succeeded("open", Value::Null))
+        }
+    }
+
+    #[test]
+    fn test_pause_resume_and_cancel_stop_between_stages() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            "stages:\n  - name: first\n    type: gate\n  - name: second\n    type: gate\n",
+        )
+        .unwrap();
+        let cicd = Arc::new(CICDSystem::with_context(context_in(workspace.path())));
+        cicd.configure_workspace_root(workspace.path());
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel();
+        cicd.register_stage_executor(Arc::new(GateExecutor {
+            started: Mutex::new(Some(started_tx)),
+            release: Mutex::new(release_rx),
+        }))
+        .unwrap();
+
+        let id = cicd
+            .trigger_pipeline("gated".to_string(), "abc123".to_string())
+            .unwrap();
+        let run = {
+            let cicd = Arc::clone(&cicd);
+            let id = id.clone();
+            std::thread::spawn(move || cicd.execute_pipeline(&id))
+        };
+        started_rx.recv().unwrap();
+        cicd.pause_pipeline(&id).unwrap();
+        release_tx.send(()).unwrap();
+        let err = run.join().unwrap().expect_err("run stops at the pause");
+        assert!(err.contains("paused"), "{err}");
+
+        let pipeline = cicd.get_pipeline(&id).unwrap();
+        assert_eq!(pipeline.status, PipelineStatus::Paused);
+        assert_eq!(pipeline.remaining_stages(), ["second"]);
+        let persisted =
+            std::fs::read_to_string(workspace.path().join(PIPELINE_STATE_FILE)).unwrap();
+        assert!(persisted.contains("\"Paused\""));
+        assert!(cicd.execute_pipeline(&id).is_err());
+
+        cicd.resume_pipeline(&id).unwrap();
+        assert_eq!(cicd.get_pipeline_status(&id), Some(PipelineStatus::Success));
+        assert!(cicd.resume_pipeline(&id).is_err());
+        assert!(cicd.cancel_pipeline(&id).is_err());
+
+        let queued = cicd
+            .trigger_pipeline("gated".to_string(), "def456".to_string())
+            .unwrap();
+        cicd.cancel_pipeline(&queued).unwrap();
+        assert_eq!(
+            cicd.get_pipeline_status(&queued),
+            Some(PipelineStatus::Cancelled)
+        );
+        let err = cicd.execute_pipeline(&queued).unwrap_err();
+        assert!(err.contains("cancelled"), "{err}");
+        assert!(cicd.resume_pipeline(&queued).is_err());
+    }
+
+    /// Fails with a transient scanner error while `failures` remain.
+    struct FlakyExecutor {
+        failures: Mutex<u32>,
+    }
+
+    impl StageExecutor for FlakyExecutor {
+        fn stage_type(&self) -> &str {
+            "flaky-scan"
+        }
+
+        fn execute(&self, _context: &StageContext) -> Result<stage_plugins::StageReport, String> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                Err("scanner IO error: lockfile unreadable".to_string())
+            } else {
+                Ok(stage_plugins::StageReport::succeeded("clean", Value::Null))
+            }
+        }
+    }
+
+    #[test]
+    fn test_stage_retry_policy_retries_transient_failures() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            "stages:\n  - name: scan\n    type: flaky-scan\n    retry:\n      max_attempts: 3\n      backoff_ms: 1\n      retry_on: [\"io error\"]\n",
+        )
+        .unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let flaky = Arc::new(FlakyExecutor {
+            failures: Mutex::new(2),
+        });
+        cicd.register_stage_executor(flaky.clone()).unwrap();
+
+        let id = cicd
+            .trigger_pipeline("scan".to_string(), "abc123".to_string())
+            .unwrap();
+        cicd.execute_pipeline(&id).unwrap();
+        let pipeline = cicd.get_pipeline(&id).unwrap();
+        assert_eq!(pipeline.stages[0].attempts, 3);
+        assert_eq!(pipeline.stages[0].status, PipelineStatus::Success);
+
+        cicd.flush_events().unwrap();
+        let log = std::fs::read_to_string(
+            workspace
+                .path()
+                .join(".workspace/indexes/pipeline_events.log"),
+        )
+        .unwrap();
+        let events: Vec<Value> = log
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .map(|entry| entry["event"].clone())
+            .filter(|event| event["scope"] == id.as_str())
+            .collect();
+        let retries: Vec<u64> = events
+            .iter()
+            .filter(|event| event["event_type"] == "pipeline.stage_retrying")
+            .filter_map(|event| event["metadata"]["backoff_ms"].as_u64())
+            .collect();
+        assert_eq!(retries, vec![1, 2]);
+        let completed = events
+            .iter()
+            .find(|event| event["event_type"] == "pipeline.stage_completed")
+            .unwrap();
+        assert_eq!(completed["metadata"]["attempts"], 3);
+
+        let overrides = HashMap::from([("scan".to_string(), RetryPolicy::new(1, 0))]);
+        let single = cicd
+            .trigger_pipeline_with_retries("scan".to_string(), "def456".to_string(), &overrides)
+            .unwrap();
+        *flaky.failures.lock().unwrap() = 1;
+        let err = cicd.execute_pipeline(&single).unwrap_err();
+        assert!(err.contains("IO error"), "{err}");
+        assert_eq!(cicd.get_pipeline(&single).unwrap().stages[0].attempts, 1);
+
+        let unknown = HashMap::from([("deploy".to_string(), RetryPolicy::new(2, 0))]);
+        let err = cicd
+            .trigger_pipeline_with_retries("scan".to_string(), "def456".to_string(), &unknown)
+            .unwrap_err();
+        assert!(err.contains("unknown stage: deploy"), "{err}");
+    }
+
+    #[test]
+    fn test_dry_run_plans_pipeline_without_executing() {
+        let workspace = tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            "stages:\n  - name: plan\n    type: terraform-plan\n    parameters:\n      workspace: prod\n",
+        )
+        .unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        cicd.register_stage_executor(Arc::new(PlanExecutor))
+            .unwrap();
+
+        let first = cicd
+            .trigger_pipeline("infra".to_string(), "abc123".to_string())
+            .unwrap();
+        let plan = cicd.dry_run_pipeline(&first).unwrap();
+        assert!(plan.runnable(), "{:?}", plan.blockers);
+        assert_eq!(
+            plan.stages[0].execution,
+            dry_run::StageExecution::Plugin("terraform-plan".to_string())
+        );
+        assert_eq!(plan.estimated_duration_ms, None);
+        assert_eq!(cicd.get_pipeline_status(&first), Some(plan.status));
+
+        cicd.execute_pipeline(&first).unwrap();
+        let second = cicd
+            .trigger_pipeline("infra".to_string(), "def456".to_string())
+            .unwrap();
+        let plan = cicd.dry_run_pipeline(&second).unwrap();
+        assert_eq!(plan.stages[0].history_samples, 1);
+        assert!(plan.estimated_duration_ms.is_some());
+
+        let gated = cicd
+            .trigger_doc_refresh_pipeline(
+                "abc123".to_string(),
+                "docs update".to_string(),
+                vec![AgentApprovalRequirement {
+                    role: "release-agent".to_string(),
+                    minimum_trust_score: 0.7,
+                    required_evidence_tags: vec![],
+                }],
+            )
+            .unwrap();
+        let plan = cicd.dry_run_pipeline(&gated).unwrap();
+        assert!(!plan.runnable());
+        assert_eq!(plan.outstanding_approvals.len(), 1);
+        assert!(plan.blockers.iter().any(|b| b.contains("release-agent")));
+    }
+
+    #[test]
+    fn test_owner_approvals_follow_codeowners() {
+        let workspace = tempdir().unwrap();
+        std::fs::create_dir_all(workspace.path().join(".github")).unwrap();
+        std::fs::write(
+            workspace.path().join(".github/CODEOWNERS"),
+            "* @noa-ark/maintainers\nserver/ai/* @noa-ark/ai-systems\n",
+        )
+        .unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+
+        let id = cicd
+            .trigger_pipeline_for_changes(
+                "ai".to_string(),
+                "abc123".to_string(),
+                &["server/ai/router.rs".to_string()],
+            )
+            .unwrap();
+        assert_eq!(
+            cicd.get_pipeline_status(&id),
+            Some(PipelineStatus::AgentReview)
+        );
+        let added = cicd
+            .require_owner_approvals(
+                &id,
+                &["server/ai/router.rs".to_string(), "README.md".to_string()],
+            )
+            .unwrap();
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].role, "@noa-ark/maintainers");
+
+        let plan = cicd.dry_run_pipeline(&id).unwrap();
+        let roles: Vec<&str> = plan
+            .outstanding_approvals
+            .iter()
+            .map(|req| req.role.as_str())
+            .collect();
+        assert_eq!(roles, vec!["@noa-ark/ai-systems", "@noa-ark/maintainers"]);
+    }
+
+    #[test]
+    fn test_doc_refresh_for_range_attaches_generated_diff_summary() {
+        let workspace = tempdir().unwrap();
+        let root = workspace.path();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(["-c", "user.name=ci", "-c", "user.email=ci@example.com"])
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init", "-q"]);
+        std::fs::create_dir_all(root.join(".github")).unwrap();
+        std::fs::write(root.join(".github/CODEOWNERS"), "docs/* @noa-ark/docs\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "base"]);
+        let base = git(&["rev-parse", "HEAD"]);
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/guide.md"), "# Guide\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "docs"]);
+        let head = git(&["rev-parse", "HEAD"]);
+
+        let cicd = CICDSystem::with_context(context_in(root));
+        cicd.configure_workspace_root(root);
+        let id = cicd
+            .trigger_doc_refresh_for_range(&base, &head, Vec::new())
+            .unwrap();
+
+        let pipeline = cicd.get_pipeline(&id).unwrap();
+        assert_eq!(pipeline.commit_sha, head);
+        let diff = pipeline.diff.as_ref().unwrap();
+        assert_eq!(diff.docs, ["docs/guide.md"]);
+        assert_eq!(pipeline.diff_summary, Some(diff.headline()));
+        assert_eq!(pipeline.status, PipelineStatus::AgentReview);
+        assert_eq!(pipeline.approvals_required[0].role, "@noa-ark/docs");
+        assert!(root
+            .join(PIPELINE_REPORTS_DIR)
+            .join(&id)
+            .join("diff_summary.md")
+            .exists());
+    }
+
+    #[test]
+    fn test_monitor_learns_baseline_and_flags_regressions() {
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let observed = |response_time_ms| HealthMetrics {
+            error_rate: 0.5,
+            response_time_ms,
+            cpu_usage: 35.0,
+            memory_usage: 45.0,
+            active_connections: 20,
+        };
+
+        for latency in [120, 118, 125, 122, 119] {
+            let id = cicd
+                .deploy_to_environment(
+                    "v1".to_string(),
+                    Environment::Staging,
+                    DeploymentStrategy::BlueGreen,
+                )
+                .unwrap();
+            cicd.record_deployment_metrics(&id, observed(latency))
+                .unwrap();
+            assert!(cicd.monitor_deployment(&id).unwrap());
+            assert!(cicd.monitor_deployment(&id).unwrap());
+        }
+        let learned = cicd.learned_baseline(&Environment::Staging).unwrap();
+        assert_eq!(learned.sample_count, 5);
+        assert_eq!(learned.p95.response_time_ms, 125);
+
+        let restarted = CICDSystem::with_context(context_in(workspace.path()));
+        restarted.configure_workspace_root(workspace.path());
+        let regressed = restarted
+            .deploy_to_environment(
+                "v2".to_string(),
+                Environment::Staging,
+                DeploymentStrategy::BlueGreen,
+            )
+            .unwrap();
+        restarted
+            .record_deployment_metrics(&regressed, observed(200))
+            .unwrap();
+        assert!(!restarted.monitor_deployment(&regressed).unwrap());
+        assert_eq!(
+            restarted
+                .learned_baseline(&Environment::Staging)
+                .unwrap()
+                .sample_count,
+            5
+        );
+    }
+
+    #[test]
+    fn test_auto_promote_blocked_when_error_budget_exhausted() {
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        cicd.define_slo(SloDefinition::new(
+            "checkout-availability",
+            "checkout",
+            Environment::Production,
+            slo::SloObjective::Availability { target: 0.999 },
+        ))
+        .unwrap();
+
+        let degraded = cicd
+            .deploy_service_to_environment(
+                "checkout".to_string(),
+                "v1".to_string(),
+                Environment::Production,
+                DeploymentStrategy::Canary,
+            )
+            .unwrap();
+        cicd.record_deployment_metrics(
+            &degraded,
+            HealthMetrics {
+                error_rate: 2.0,
+                response_time_ms: 90,
+                cpu_usage: 30.0,
+                memory_usage: 40.0,
+                active_connections: 50,
+            },
+        )
+        .unwrap();
+        let budgets = cicd.error_budget_status("checkout", &Environment::Production);
+        assert_eq!(budgets.len(), 1);
+        assert!(budgets[0].exhausted);
+
+        let err = cicd
+            .auto_promote(&degraded, Environment::Production)
+            .expect_err("exhausted budget blocks promotion");
+        assert!(err.contains("checkout-availability"), "{err}");
+
+        let report = std::fs::read_to_string(
+            workspace
+                .path()
+                .join("docs/reports/AGENT_DEPLOYMENT_OUTCOMES.md"),
+        )
+        .unwrap();
+        assert!(report.contains("| blocked |"), "{report}");
+        assert!(report.contains("budget_consumed"));
+
+        let unrelated = cicd
+            .deploy_to_environment(
+                "v1".to_string(),
+                Environment::Production,
+                DeploymentStrategy::Canary,
+            )
+            .unwrap();
+        cicd.auto_promote(&unrelated, Environment::Production)
+            .expect("services without SLOs promote");
+    }
+
+    #[test]
+    fn test_error_budgets_survive_a_restart() {
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        cicd.define_slo(SloDefinition::new(
+            "checkout-availability",
+            "checkout",
+            Environment::Production,
+            slo::SloObjective::Availability { target: 0.999 },
+        ))
+        .unwrap();
+        let degraded = cicd
+            .deploy_service_to_environment(
+                "checkout".to_string(),
+                "v1".to_string(),
+                Environment::Production,
+                DeploymentStrategy::Canary,
+            )
+            .unwrap();
+        cicd.record_deployment_metrics(
+            &degraded,
+            HealthMetrics {
+                error_rate: 2.0,
+                response_time_ms: 90,
+                cpu_usage: 30.0,
+                memory_usage: 40.0,
+                active_connections: 50,
+            },
+        )
+        .unwrap();
+
+        let restarted = CICDSystem::with_context(context_in(workspace.path()));
+        restarted.configure_workspace_root(workspace.path());
+        restarted.reload_state(RecoveryMode::Strict).unwrap();
+        let budgets = restarted.error_budget_status("checkout", &Environment::Production);
+        assert_eq!(budgets.len(), 1);
+        assert!(budgets[0].exhausted);
+        let err = restarted
+            .auto_promote(&degraded, Environment::Production)
+            .expect_err("the exhausted budget still blocks promotion");
+        assert!(err.contains("checkout-availability"), "{err}");
+    }
+
+    #[test]
+    fn test_artifacts_are_content_addressed_ledgered_and_traced_from_deployments() {
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let id = cicd
+            .trigger_pipeline("release".to_string(), "abc123".to_string())
+            .unwrap();
+        std::fs::create_dir_all(workspace.path().join("target/release")).unwrap();
+        let binary = workspace.path().join("target/release/noa");
+        std::fs::write(&binary, b"release build").unwrap();
+
+        assert!(cicd
+            .attach_artifact(&id, "lint", "target/release/noa")
+            .is_err());
+        let record = cicd
+            .attach_artifact(&id, "build", "target/release/noa")
+            .unwrap();
+        assert_eq!(record.path, "target/release/noa");
+        assert_eq!(record.size, 13);
+        assert_eq!(cicd.list_artifacts(&id).unwrap(), vec![record.clone()]);
+        assert!(workspace
+            .path()
+            .join(ARTIFACT_STORE_DIR)
+            .join("objects")
+            .join(&record.sha256[..2])
+            .join(&record.sha256)
+            .exists());
+        let check = cicd.verify_artifact(&id, "target/release/noa").unwrap();
+        assert!(check.verified(), "{check:?}");
+        assert_eq!(check.ledger_verified, Some(true));
+
+        let deployment = cicd
+            .deploy_pipeline_to_environment(
+                &id,
+                "1.0.0".to_string(),
+                Environment::Staging,
+                DeploymentStrategy::BlueGreen,
+            )
+            .unwrap();
+        let shipped = cicd.deployment_artifacts(&deployment).unwrap();
+        assert_eq!(shipped[0].sha256, record.sha256);
+
+        let bundle = workspace.path().join("evidence.tar.gz");
+        let manifest = cicd.export_evidence_bundle_to(&id, &bundle).unwrap();
+        assert!(manifest
+            .files
+            .iter()
+            .any(|file| file.path == "artifacts.json"));
+
+        std::fs::write(&binary, b"rebuilt").unwrap();
+        let check = cicd.verify_artifact(&id, "target/release/noa").unwrap();
+        assert_eq!(check.source_matches, Some(false));
+        assert!(!check.verified());
+    }
+
+    #[test]
+    fn test_promoted_artifacts_are_pinned_against_sweeps_and_eviction() {
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        std::fs::create_dir_all(workspace.path().join("target")).unwrap();
+        let attach = |build: &str| {
+            let id = cicd
+                .trigger_pipeline(build.to_string(), "abc123".to_string())
+                .unwrap();
+            std::fs::write(workspace.path().join("target").join(build), build).unwrap();
+            let record = cicd
+                .attach_artifact(&id, "build", format!("target/{build}"))
+                .unwrap();
+            cicd.export_evidence_bundle(&id).unwrap();
+            (id, record)
+        };
+        let (release, release_artifact) = attach("release");
+        let (nightly, nightly_artifact) = attach("nightly");
+        let (broken, _) = attach("broken");
+
+        let deployment = cicd
+            .deploy_pipeline_to_environment(
+                &release,
+                "1.0.0".to_string(),
+                Environment::Production,
+                DeploymentStrategy::Canary,
+            )
+            .unwrap();
+        cicd.auto_promote(&deployment, Environment::Production)
+            .unwrap();
+        let pinned = cicd.artifact_retention(&release).unwrap();
+        assert_eq!(pinned.class, RetentionClass::Pinned);
+        assert!(pinned.reason.unwrap().contains(&deployment));
+        cicd.artifact_store()
+            .set_retention(&broken, RetentionClass::Failed, None)
+            .unwrap();
+
+        let policy = RetentionPolicy {
+            interim_ttl_secs: 3_600,
+            failed_ttl_secs: 60,
+        };
+        let now = unix_now();
+        let sweep = cicd.sweep_artifacts(&policy, now + 120).unwrap();
+        assert_eq!(sweep.expired_pipelines, vec![broken.clone()]);
+        assert_eq!(sweep.removed_objects, 1);
+        let bundle = |id: &str| cicd.evidence_bundle_dir().join(format!("{id}.tar.gz"));
+        assert!(!bundle(&broken).exists());
+
+        // Pinned objects and the store's own files survive budget eviction.
+        let manager = noa_core::storage::StorageManager::new(workspace.path()).with_budget(
+            noa_core::storage::StorageBudget::new("artifacts", ARTIFACT_STORE_DIR, 1)
+                .with_action(noa_core::storage::BudgetAction::EvictOldest),
+        );
+        manager.add_eviction_guard(cicd.artifact_eviction_guard().unwrap());
+        let report = manager.enforce(noa_core::storage::now_millis()).unwrap();
+        assert_eq!(report.actions[0].evicted.len(), 1);
+        assert!(report.actions[0].protected > 0);
+        let object = |record: &ArtifactRecord| cicd.artifact_store().object_path(&record.sha256);
+        assert!(object(&release_artifact).exists());
+        assert!(!object(&nightly_artifact).exists());
+        assert_eq!(cicd.list_artifacts(&nightly).unwrap().len(), 1);
+
+        let sweep = cicd.sweep_artifacts(&policy, now + 7_200).unwrap();
+        assert_eq!(sweep.expired_pipelines, vec![nightly.clone()]);
+        assert!(cicd.list_artifacts(&nightly).unwrap().is_empty());
+        assert!(!bundle(&nightly).exists());
+        assert!(bundle(&release).exists());
+        assert_eq!(cicd.list_artifacts(&release).unwrap().len(), 1);
+        assert!(object(&release_artifact).exists());
+
+        cicd.unpin_pipeline_artifacts(&release).unwrap();
+        let sweep = cicd.sweep_artifacts(&policy, now + 7_200).unwrap();
+        assert_eq!(sweep.expired_pipelines, vec![release.clone()]);
+        assert!(!object(&release_artifact).exists());
+        assert!(cicd.pin_pipeline_artifacts("missing", "typo").is_err());
+    }
+
+    #[test]
+    fn test_gitlab_push_webhook_triggers_pipeline_with_branch_metadata() {
+        let workspace = tempdir().unwrap();
+        let body = json!({
+            "ref": "refs/heads/release/2.4",
+            "checkout_sha": "c0ffee",
+            "user_username": "maintainer",
+            "project": { "path_with_namespace": "noa/ark" },
+            "commits": [{ "id": "c0ffee", "message": "Cut 2.4\n", "url": "https://gitlab.example/c0ffee" }],
+        })
+        .to_string();
+        let delivery = WebhookDelivery::from_headers(|name| match name {
+            "x-gitlab-event" => Some("Push Hook"),
+            "x-gitlab-token" => Some("hook-token"),
+            _ => None,
+        })
+        .unwrap();
+
+        let unconfigured = CICDSystem::with_context(context_in(workspace.path()));
+        unconfigured.configure_workspace_root(workspace.path());
+        assert_eq!(
+            unconfigured.handle_webhook(&delivery, body.as_bytes(), None),
+            Err(WebhookError::NotConfigured)
+        );
+
+        let cicd = CICDSystem::with_context(
+            context_in(workspace.path()).with_var(webhook::WEBHOOK_SECRET_ENV, "hook-token"),
+        );
+        cicd.configure_workspace_root(workspace.path());
+        let WebhookOutcome::Triggered { pipeline_id } = cicd
+            .handle_webhook(&delivery, body.as_bytes(), None)
+            .unwrap()
+        else {
+            panic!("push triggers a pipeline");
+        };
+        let pipeline = cicd.get_pipeline(&pipeline_id).unwrap();
+        assert_eq!(pipeline.name, "ark");
+        assert_eq!(pipeline.commit_sha, "c0ffee");
+        let source = pipeline.source.unwrap();
+        assert_eq!(source.branch, "release/2.4");
+        assert_eq!(source.title.as_deref(), Some("Cut 2.4"));
+
+        let tag = body.replace("refs/heads/release/2.4", "refs/tags/v2.4");
+        assert!(matches!(
+            cicd.handle_webhook(&delivery, tag.as_bytes(), Some("ark")),
+            Ok(WebhookOutcome::Ignored { .. })
+        ));
+    }
+
+    #[test]
+    fn test_timeseries_history_seeds_baselines_and_slos_in_new_workspace() {
+        let history_dir = tempdir().unwrap();
+        let store = Arc::new(TimeSeriesStore::open(history_dir.path()).unwrap());
+        let healthy = HealthMetrics {
+            error_rate: 0.5,
+            response_time_ms: 120,
+            cpu_usage: 35.0,
+            memory_usage: 45.0,
+            active_connections: 20,
+        };
+
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        cicd.configure_timeseries(store.clone()).unwrap();
+        for _ in 0..3 {
+            let id = cicd
+                .deploy_to_environment(
+                    "v1".to_string(),
+                    Environment::Staging,
+                    DeploymentStrategy::BlueGreen,
+                )
+                .unwrap();
+            cicd.record_deployment_metrics(&id, healthy.clone())
+                .unwrap();
+            assert!(cicd.monitor_deployment(&id).unwrap());
+        }
+        let degraded = cicd
+            .deploy_service_to_environment(
+                "checkout".to_string(),
+                "v1".to_string(),
+                Environment::Production,
+                DeploymentStrategy::Canary,
+            )
+            .unwrap();
+        cicd.record_deployment_metrics(
+            &degraded,
+            HealthMetrics {
+                error_rate: 2.0,
+                ..healthy.clone()
+            },
+        )
+        .unwrap();
+
+        let fresh = tempdir().unwrap();
+        let restored = CICDSystem::with_context(context_in(fresh.path()));
+        restored.configure_workspace_root(fresh.path());
+        assert!(restored.learned_baseline(&Environment::Staging).is_none());
+        restored.configure_timeseries(store).unwrap();
+        let learned = restored.learned_baseline(&Environment::Staging).unwrap();
+        assert_eq!(learned.sample_count, 3);
+        assert_eq!(learned.p95.response_time_ms, 120);
+
+        restored
+            .define_slo(SloDefinition::new(
+                "checkout-availability",
+                "checkout",
+                Environment::Production,
+                slo::SloObjective::Availability { target: 0.999 },
+            ))
+            .unwrap();
+        let budgets = restored.error_budget_status("checkout", &Environment::Production);
+        assert_eq!(budgets.len(), 1);
+        assert!(budgets[0].exhausted);
+    }
+
+    #[test]
+    fn test_namespaced_system_isolates_state_and_enforces_quota() {
+        let workspace = tempdir().unwrap();
+        let team = Namespace::new("team-a").unwrap();
+        let outsider = Namespace::new("team-b").unwrap();
+        let mut registry = NamespaceRegistry::open_in(&context_in(workspace.path())).unwrap();
+        registry
+            .register(
+                team.clone(),
+                NamespaceQuota {
+                    max_pipelines: Some(1),
+                    ..NamespaceQuota::default()
+                },
+            )
+            .unwrap();
+        registry
+            .register(outsider.clone(), NamespaceQuota::default())
+            .unwrap();
+
+        let cicd = CICDSystem::for_namespace_with_context(
+            &registry,
+            team.clone(),
+            context_in(workspace.path()),
+        )
+        .unwrap();
+        cicd.configure_workspace_root(workspace.path());
+        let id = cicd
+            .trigger_pipeline("tenant".to_string(), "abc123".to_string())
+            .unwrap();
+        assert!(workspace
+            .path()
+            .join("storage/db/team-a/pipelines/state.json")
+            .exists());
+        assert!(!workspace.path().join(PIPELINE_STATE_FILE).exists());
+
+        let err = cicd
+            .trigger_pipeline("tenant".to_string(), "def456".to_string())
+            .expect_err("pipeline quota enforced");
+        assert!(err.contains("quota exceeded"), "{err}");
+
+        assert!(cicd
+            .get_pipeline_status_for(&outsider, &registry, &id)
+            .is_err());
+        assert!(cicd
+            .get_pipeline_status_for(&team, &registry, &id)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_evidence_bundle_export_and_verification() {
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let id = cicd
+            .trigger_pipeline("release".to_string(), "abc123".to_string())
+            .unwrap();
+        cicd.log_skipped_scan(&id, "gitleaks", "disabled").unwrap();
+        let deployment = cicd
+            .deploy_pipeline_to_environment(
+                &id,
+                "v2.0.0".to_string(),
+                Environment::Staging,
+                DeploymentStrategy::BlueGreen,
+            )
+            .unwrap();
+        cicd.deploy_to_environment(
+            "v0.0.1".to_string(),
+            Environment::Development,
+            DeploymentStrategy::RollingUpdate,
+        )
+        .unwrap();
+
+        let bundle = cicd.export_evidence_bundle(&id).unwrap();
+        assert!(bundle.ends_with(format!("{id}.tar.gz")));
+        let verification = evidence::verify_evidence_bundle(&bundle).unwrap();
+        assert_eq!(verification.pipeline_id, id);
+        assert!(verification.signature_valid);
+
+        let mut files = HashMap::new();
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(
+            fs::File::open(&bundle).unwrap(),
+        ));
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut bytes = Vec::new();
+            std::io::Read::read_to_end(&mut entry, &mut bytes).unwrap();
+            files.insert(name, bytes);
+        }
+        for expected in [
+            "pipeline.json",
+            "approvals.json",
+            "security_scans.json",
+            "stage_receipts.json",
+            "signatures.json",
+            "VERIFY.md",
+            "manifest.json",
+            "signature.json",
+        ] {
+            assert!(files.contains_key(expected), "missing {expected}");
+        }
+        let deployments: Vec<Deployment> =
+            serde_json::from_slice(&files["deployments.json"]).unwrap();
+        assert_eq!(deployments.len(), 1);
+        assert_eq!(deployments[0].id, deployment);
+        let scans: Vec<SecurityScanReport> =
+            serde_json::from_slice(&files["security_scans.json"]).unwrap();
+        assert_eq!(scans[0].tool, "gitleaks");
+
+        files.insert("pipeline.json".to_string(), b"{}".to_vec());
+        let tampered = workspace.path().join("tampered.tar.gz");
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            fs::File::create(&tampered).unwrap(),
+            flate2::Compression::default(),
+        ));
+        for (name, bytes) in &files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, bytes.as_slice())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+        let err = evidence::verify_evidence_bundle(&tampered).unwrap_err();
+        assert!(err.contains("digest mismatch for pipeline.json"), "{err}");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn persisted_state_parser_never_panics(raw in proptest::prelude::any::<String>()) {
+            for mode in [RecoveryMode::Strict, RecoveryMode::Lenient] {
+                let _ = PersistedState::parse(&raw, mode);
+                let _ = PersistedState::parse(&format!("{{\"pipelines\":[{raw}]}}"), mode);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lenient_state_load_skips_corrupt_records() {
+        let workspace = tempdir().unwrap();
+        let cicd = CICDSystem::with_context(context_in(workspace.path()));
+        cicd.configure_workspace_root(workspace.path());
+        let first = cicd
+            .trigger_pipeline("first".to_string(), "abc123".to_string())
+            .unwrap();
+        let second = cicd
+            .trigger_pipeline("second".to_string(), "def456".to_string())
+            .unwrap();
+
+        let path = workspace.path().join(PIPELINE_STATE_FILE);
+        let mut state: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let pipelines = state["pipelines"].as_array_mut().unwrap();
+        let corrupt = pipelines
+            .iter()
+            .position(|pipeline| pipeline["id"] == second.as_str())
+            .unwrap();
+        pipelines[corrupt]["stages"] = json!("truncated");
+        fs::write(&path, state.to_string()).unwrap();
+
+        assert!(cicd.reload_state(RecoveryMode::Strict).is_err());
+        let skipped = cicd.reload_state(RecoveryMode::Lenient).unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].location, format!("pipelines[{corrupt}]"));
+        assert!(cicd.get_pipeline_status(&first).is_some());
+        assert!(cicd.get_pipeline_status(&second).is_none());
+
+        fs::write(&path, "{\"pipelines\": 7").unwrap();
+        assert!(cicd.reload_state(RecoveryMode::Lenient).is_err());
+    }
 }
@@ -0,0 +1,365 @@
+//! Workspace lint stage: rustfmt and clippy findings gated by a baseline.
+//!
+//! `cargo fmt --check` and `cargo clippy --message-format=json` output is parsed into
+//! structured findings attached to the pipeline. Findings already recorded in the
+//! baseline file are tolerated as existing debt; the stage fails only when a lint
+//! occurs more often in a file than the baseline allows. Baseline entries ignore line
+//! numbers so unrelated edits that shift code do not surface old findings as new.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Baseline file relative to the workspace root, unless the stage names another.
+pub const LINT_BASELINE_FILE: &str = "lint-baseline.json";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum LintTool {
+    Rustfmt,
+    Clippy,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Error,
+    Warning,
+}
+
+/// One diagnostic reported by rustfmt or clippy.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LintFinding {
+    pub tool: LintTool,
+    /// Workspace-relative path.
+    pub file: String,
+    pub line: u32,
+    /// Lint name, e.g. `clippy::needless_return`; `rustfmt` for formatting diffs.
+    pub lint: String,
+    pub level: LintLevel,
+    pub message: String,
+    /// Set when the finding is not covered by the baseline.
+    #[serde(default)]
+    pub new: bool,
+}
+
+impl LintFinding {
+    fn key(&self) -> BaselineKey {
+        BaselineKey {
+            tool: self.tool,
+            file: self.file.clone(),
+            lint: self.lint.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+struct BaselineKey {
+    tool: LintTool,
+    file: String,
+    lint: String,
+}
+
+/// Tolerated occurrences of a lint in one file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BaselineEntry {
+    pub tool: LintTool,
+    pub file: String,
+    pub lint: String,
+    pub count: usize,
+}
+
+/// Existing lint debt that does not block pipelines.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LintBaseline {
+    pub entries: Vec<BaselineEntry>,
+}
+
+impl LintBaseline {
+    /// Baseline accepting every finding in `findings`.
+    pub fn from_findings(findings: &[LintFinding]) -> Self {
+        let mut counts: BTreeMap<BaselineKey, usize> = BTreeMap::new();
+        for finding in findings {
+            *counts.entry(finding.key()).or_default() += 1;
+        }
+        Self {
+            entries: counts
+                .into_iter()
+                .map(|(key, count)| BaselineEntry {
+                    tool: key.tool,
+                    file: key.file,
+                    lint: key.lint,
+                    count,
+                })
+                .collect(),
+        }
+    }
+
+    /// Load a baseline; a missing file is an empty baseline.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read lint baseline: {err}"))?;
+        serde_json::from_str(&raw).map_err(|err| format!("failed to parse lint baseline: {err}"))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("failed to create lint baseline directory: {err}"))?;
+        }
+        let payload = serde_json::to_string_pretty(self)
+            .map_err(|err| format!("failed to serialise lint baseline: {err}"))?;
+        fs::write(path, payload).map_err(|err| format!("failed to write lint baseline: {err}"))
+    }
+}
+
+/// Findings of one Lint stage run, compared against the baseline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LintReport {
+    pub findings: Vec<LintFinding>,
+    /// Findings covered by the baseline.
+    pub baselined: usize,
+    /// Baselined occurrences that no longer appear; the baseline can be tightened.
+    pub fixed: usize,
+}
+
+impl LintReport {
+    /// Mark findings exceeding the baseline's per-file allowance as new.
+    pub fn compare(mut findings: Vec<LintFinding>, baseline: &LintBaseline) -> Self {
+        let mut allowance: BTreeMap<BaselineKey, usize> = baseline
+            .entries
+            .iter()
+            .map(|entry| {
+                (
+                    BaselineKey {
+                        tool: entry.tool,
+                        file: entry.file.clone(),
+                        lint: entry.lint.clone(),
+                    },
+                    entry.count,
+                )
+            })
+            .collect();
+        findings.sort_by(|a, b| (&a.file, a.line, &a.lint).cmp(&(&b.file, b.line, &b.lint)));
+
+        let mut baselined = 0;
+        for finding in &mut findings {
+            match allowance.get_mut(&finding.key()) {
+                Some(remaining) if *remaining > 0 => {
+                    *remaining -= 1;
+                    baselined += 1;
+                    finding.new = false;
+                }
+                _ => finding.new = true,
+            }
+        }
+        Self {
+            findings,
+            baselined,
+            fixed: allowance.values().sum(),
+        }
+    }
+
+    pub fn new_findings(&self) -> impl Iterator<Item = &LintFinding> {
+        self.findings.iter().filter(|finding| finding.new)
+    }
+}
+
+/// Produces raw rustfmt and clippy output for a workspace.
+pub trait LintRunner: Send + Sync {
+    /// Output of `cargo fmt --all -- --check`.
+    fn rustfmt(&self, workspace: &Path) -> Result<String, String>;
+    /// JSON lines from `cargo clippy --message-format=json`.
+    fn clippy(&self, workspace: &Path) -> Result<String, String>;
+}
+
+/// Runs the cargo toolchain found on `PATH`.
+#[derive(Debug, Default)]
+pub struct CargoLintRunner;
+
+impl CargoLintRunner {
+    fn cargo(workspace: &Path, args: &[&str]) -> Result<String, String> {
+        // Both commands exit non-zero when they report findings; only a failure to
+        // launch cargo is an error here.
+        let output = Command::new("cargo")
+            .args(args)
+            .current_dir(workspace)
+            .output()
+            .map_err(|err| format!("failed to run cargo {}: {err}", args[0]))?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl LintRunner for CargoLintRunner {
+    fn rustfmt(&self, workspace: &Path) -> Result<String, String> {
+        Self::cargo(workspace, &["fmt", "--all", "--", "--check"])
+    }
+
+    fn clippy(&self, workspace: &Path) -> Result<String, String> {
+        Self::cargo(
+            workspace,
+            &[
+                "clippy",
+                "--workspace",
+                "--all-targets",
+                "--message-format=json",
+            ],
+        )
+    }
+}
+
+/// Parse `rustfmt --check` output; each `Diff in <file> at line <n>:` hunk is a finding.
+pub fn parse_rustfmt(output: &str, workspace: &Path) -> Vec<LintFinding> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let location = line.strip_prefix("Diff in ")?.trim_end_matches(':');
+            // rustfmt prints either `<file> at line <n>` or `<file>:<n>`.
+            let (file, line) = location
+                .rsplit_once(" at line ")
+                .or_else(|| location.rsplit_once(':'))?;
+            Some(LintFinding {
+                tool: LintTool::Rustfmt,
+                file: relative(file, workspace),
+                line: line.trim().parse().ok()?,
+                lint: "rustfmt".to_string(),
+                level: LintLevel::Warning,
+                message: "file is not rustfmt-formatted".to_string(),
+                new: false,
+            })
+        })
+        .collect()
+}
+
+/// Parse cargo's JSON message stream, keeping warnings and errors that carry a
+/// primary span. Diagnostics repeated across targets are reported once.
+pub fn parse_clippy(output: &str, workspace: &Path) -> Vec<LintFinding> {
+    let mut findings: Vec<LintFinding> = Vec::new();
+    for line in output.lines() {
+        let Ok(record) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if record.get("reason").and_then(Value::as_str) != Some("compiler-message") {
+            continue;
+        }
+        let Some(message) = record.get("message") else {
+            continue;
+        };
+        let level = match message.get("level").and_then(Value::as_str) {
+            Some("error") => LintLevel::Error,
+            Some("warning") => LintLevel::Warning,
+            _ => continue,
+        };
+        let Some(span) = message
+            .get("spans")
+            .and_then(Value::as_array)
+            .and_then(|spans| {
+                spans
+                    .iter()
+                    .find(|span| span.get("is_primary").and_then(Value::as_bool) == Some(true))
+            })
+        else {
+            continue;
+        };
+        let finding = LintFinding {
+            tool: LintTool::Clippy,
+            file: relative(
+                span.get("file_name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default(),
+                workspace,
+            ),
+            line: span.get("line_start").and_then(Value::as_u64).unwrap_or(0) as u32,
+            lint: message
+                .get("code")
+                .and_then(|code| code.get("code"))
+                .and_then(Value::as_str)
+                .unwrap_or("rustc")
+                .to_string(),
+            level,
+            message: message
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            new: false,
+        };
+        if !findings.contains(&finding) {
+            findings.push(finding);
+        }
+    }
+    findings
+}
+
+fn relative(file: &str, workspace: &Path) -> String {
+    Path::new(file)
+        .strip_prefix(workspace)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| file.to_string())
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_findings_beyond_the_baseline_are_new() {
+        let workspace = Path::new("/work");
+        let clippy = [
+            r#"{"reason":"compiler-artifact","target":{"name":"demo"}}"#,
+            r#"{"reason":"compiler-message","message":{"message":"unneeded `return` statement","level":"warning","code":{"code":"clippy::needless_return"},"spans":[{"file_name":"src/lib.rs","line_start":4,"is_primary":true}]}}"#,
+            r#"{"reason":"compiler-message","message":{"message":"unneeded `return` statement","level":"warning","code":{"code":"clippy::needless_return"},"spans":[{"file_name":"src/lib.rs","line_start":4,"is_primary":true}]}}"#,
+            r#"{"reason":"compiler-message","message":{"message":"unneeded `return` statement","level":"warning","code":{"code":"clippy::needless_return"},"spans":[{"file_name":"src/lib.rs","line_start":20,"is_primary":true}]}}"#,
+            r#"{"reason":"compiler-message","message":{"message":"1 warning emitted","level":"warning","code":null,"spans":[]}}"#,
+        ]
+        .join("\n");
+        let rustfmt = "Diff in /work/src/main.rs at line 7:\n-fn main(){}\n+fn main() {}\n";
+
+        let mut findings = parse_clippy(&clippy, workspace);
+        assert_eq!(findings.len(), 2);
+        findings.extend(parse_rustfmt(rustfmt, workspace));
+        assert_eq!(findings[2].file, "src/main.rs");
+        assert_eq!(findings[2].line, 7);
+
+        let baseline = LintBaseline {
+            entries: vec![
+                BaselineEntry {
+                    tool: LintTool::Clippy,
+                    file: "src/lib.rs".to_string(),
+                    lint: "clippy::needless_return".to_string(),
+                    count: 1,
+                },
+                BaselineEntry {
+                    tool: LintTool::Clippy,
+                    file: "src/old.rs".to_string(),
+                    lint: "clippy::redundant_clone".to_string(),
+                    count: 2,
+                },
+            ],
+        };
+        let report = LintReport::compare(findings, &baseline);
+        let new: Vec<_> = report
+            .new_findings()
+            .map(|finding| (finding.file.as_str(), finding.line))
+            .collect();
+        assert_eq!(new, [("src/lib.rs", 20), ("src/main.rs", 7)]);
+        assert_eq!(report.baselined, 1);
+        assert_eq!(report.fixed, 2);
+
+        let accepted = LintBaseline::from_findings(&report.findings);
+        assert_eq!(
+            LintReport::compare(report.findings, &accepted)
+                .new_findings()
+                .count(),
+            0
+        );
+    }
+}
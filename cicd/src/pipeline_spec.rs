@@ -0,0 +1,456 @@
+//! Declarative pipeline-as-code definitions.
+//!
+//! A repository or CRC drop can ship a `pipeline.toml` or `pipeline.yaml` at its
+//! root describing the stages to run, which offline scanners to enable, the agent
+//! approvals required before execution, the environments it targets and the
+//! gates guarding promotion between them. `CICDSystem::trigger_pipeline` uses
+//! the definition when present and falls back to the built-in stage list otherwise.
+//!
+//! Stage types that are not built in are treated as plugin stages and must be
+//! registered with the system's stage executor registry before triggering.
+//! Stages may list the stages they wait for in `depends_on`; the definition is
+//! rejected if those dependencies name unknown stages or form a cycle.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use noa_workflow::RunPriority;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::retry::RetryPolicy;
+use crate::stage_graph;
+use crate::{AgentApprovalRequirement, Environment, PipelineStage, ScannerFlags};
+
+/// File names probed, in order, when discovering a pipeline definition.
+pub const PIPELINE_SPEC_FILES: [&str; 3] = ["pipeline.toml", "pipeline.yaml", "pipeline.yml"];
+
+/// Current schema version understood by the loader.
+pub const PIPELINE_SPEC_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum PipelineSpecError {
+    #[error("failed to read pipeline definition {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to parse pipeline definition {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+    #[error("unsupported pipeline definition format: {0}")]
+    UnsupportedFormat(PathBuf),
+    #[error("invalid pipeline definition {path}:\n{issues}")]
+    Invalid { path: PathBuf, issues: SpecIssues },
+}
+
+/// Every validation problem found in a definition, reported together.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpecIssues(pub Vec<String>);
+
+impl fmt::Display for SpecIssues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self.0.iter().map(|issue| format!("  - {issue}")).collect();
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+/// Declarative pipeline definition.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineSpec {
+    #[serde(default = "PipelineSpec::default_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub stages: Vec<StageSpec>,
+    #[serde(default)]
+    pub scanners: Option<ScannerSpec>,
+    #[serde(default)]
+    pub approvals: Vec<AgentApprovalRequirement>,
+    #[serde(default)]
+    pub environments: Vec<Environment>,
+    #[serde(default)]
+    pub promotion_gates: Vec<PromotionGate>,
+    /// Queue priority of runs on a shared concurrency governor.
+    #[serde(default)]
+    pub priority: RunPriority,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct StageSpec {
+    pub name: String,
+    #[serde(
+        rename = "type",
+        serialize_with = "serialize_stage_type",
+        deserialize_with = "deserialize_stage_type"
+    )]
+    pub stage_type: PipelineStage,
+    /// Free-form settings passed to plugin stage executors.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub parameters: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    /// Stages that must succeed before this one starts. Without any `depends_on` in
+    /// the definition, stages run one after another in the order declared.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+}
+
+/// Resolve a stage type name to a built-in stage, accepting either spelling.
+pub fn builtin_stage(name: &str) -> Option<PipelineStage> {
+    serde_json::from_value::<PipelineStage>(Value::String(name.to_string()))
+        .ok()
+        .filter(|stage| !matches!(stage, PipelineStage::Plugin(_)))
+}
+
+fn serialize_stage_type<S: Serializer>(
+    stage: &PipelineStage,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match stage {
+        PipelineStage::Plugin(name) => serializer.serialize_str(name),
+        builtin => builtin.serialize(serializer),
+    }
+}
+
+fn deserialize_stage_type<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<PipelineStage, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Ok(builtin_stage(&name).unwrap_or(PipelineStage::Plugin(name)))
+}
+
+/// Offline scanners enabled for pipelines using this definition.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ScannerSpec {
+    #[serde(default)]
+    pub syft: bool,
+    #[serde(default)]
+    pub grype: bool,
+    #[serde(default)]
+    pub trivy: bool,
+    #[serde(default)]
+    pub gitleaks: bool,
+}
+
+impl From<&ScannerSpec> for ScannerFlags {
+    fn from(spec: &ScannerSpec) -> Self {
+        ScannerFlags {
+            syft: spec.syft,
+            grype: spec.grype,
+            trivy: spec.trivy,
+            gitleaks: spec.gitleaks,
+        }
+    }
+}
+
+/// Conditions that must hold before a build is promoted between environments.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PromotionGate {
+    pub from: Environment,
+    pub to: Environment,
+    #[serde(default)]
+    pub min_ai_confidence: Option<f32>,
+    #[serde(default)]
+    pub required_roles: Vec<String>,
+}
+
+impl PipelineSpec {
+    fn default_version() -> u32 {
+        PIPELINE_SPEC_VERSION
+    }
+
+    /// Look for a pipeline definition at the root of `dir`.
+    pub fn discover(dir: &Path) -> Result<Option<(PathBuf, Self)>, PipelineSpecError> {
+        for file in PIPELINE_SPEC_FILES {
+            let path = dir.join(file);
+            if path.is_file() {
+                let spec = Self::load(&path)?;
+                return Ok(Some((path, spec)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Load and validate a definition, choosing the parser from the file extension.
+    pub fn load(path: &Path) -> Result<Self, PipelineSpecError> {
+        let raw = fs::read_to_string(path).map_err(|source| PipelineSpecError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml_str(&raw, path),
+            Some("yaml") | Some("yml") => Self::from_yaml_str(&raw, path),
+            _ => Err(PipelineSpecError::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+
+    pub fn from_toml_str(raw: &str, origin: &Path) -> Result<Self, PipelineSpecError> {
+        let spec: Self = toml::from_str(raw).map_err(|err| PipelineSpecError::Parse {
+            path: origin.to_path_buf(),
+            message: err.to_string(),
+        })?;
+        spec.validated(origin)
+    }
+
+    pub fn from_yaml_str(raw: &str, origin: &Path) -> Result<Self, PipelineSpecError> {
+        let spec: Self = serde_yaml::from_str(raw).map_err(|err| PipelineSpecError::Parse {
+            path: origin.to_path_buf(),
+            message: err.to_string(),
+        })?;
+        spec.validated(origin)
+    }
+
+    fn validated(self, origin: &Path) -> Result<Self, PipelineSpecError> {
+        let issues = self.validate();
+        if issues.0.is_empty() {
+            Ok(self)
+        } else {
+            Err(PipelineSpecError::Invalid {
+                path: origin.to_path_buf(),
+                issues,
+            })
+        }
+    }
+
+    /// Collect every structural problem in the definition.
+    pub fn validate(&self) -> SpecIssues {
+        let mut issues = Vec::new();
+
+        if self.version != PIPELINE_SPEC_VERSION {
+            issues.push(format!(
+                "version {} is not supported (expected {})",
+                self.version, PIPELINE_SPEC_VERSION
+            ));
+        }
+
+        if self.stages.is_empty() {
+            issues.push("at least one stage must be declared".to_string());
+        }
+        let mut stage_names = HashSet::new();
+        for (index, stage) in self.stages.iter().enumerate() {
+            if stage.name.trim().is_empty() {
+                issues.push(format!("stages[{index}] has an empty name"));
+            } else if !stage_names.insert(stage.name.as_str()) {
+                issues.push(format!("stage '{}' is declared more than once", stage.name));
+            }
+            if let PipelineStage::Plugin(plugin) = &stage.stage_type {
+                if plugin.trim().is_empty() {
+                    issues.push(format!("stage '{}' has an empty type", stage.name));
+                }
+            }
+            if let Some(retry) = &stage.retry {
+                issues.extend(retry.issues(&stage.name));
+            }
+        }
+        let graph: Vec<(&str, &[String])> = self
+            .stages
+            .iter()
+            .map(|stage| (stage.name.as_str(), stage.depends_on.as_slice()))
+            .collect();
+        issues.extend(stage_graph::issues(&graph));
+
+        let mut roles = HashSet::new();
+        for (index, approval) in self.approvals.iter().enumerate() {
+            if approval.role.trim().is_empty() {
+                issues.push(format!("approvals[{index}] has an empty role"));
+            } else if !roles.insert(approval.role.as_str()) {
+                issues.push(format!(
+                    "approval role '{}' is declared more than once",
+                    approval.role
+                ));
+            }
+            if !(0.0..=1.0).contains(&approval.minimum_trust_score) {
+                issues.push(format!(
+                    "approval role '{}' has minimum_trust_score {} outside 0.0..=1.0",
+                    approval.role, approval.minimum_trust_score
+                ));
+            }
+        }
+
+        let mut environments = HashSet::new();
+        for environment in &self.environments {
+            if !environments.insert(environment) {
+                issues.push(format!(
+                    "environment {:?} is declared more than once",
+                    environment
+                ));
+            }
+        }
+
+        for gate in &self.promotion_gates {
+            let label = format!("promotion gate {:?} -> {:?}", gate.from, gate.to);
+            if gate.from == gate.to {
+                issues.push(format!("{label} promotes an environment to itself"));
+            }
+            for environment in [&gate.from, &gate.to] {
+                if !self.environments.is_empty() && !environments.contains(environment) {
+                    issues.push(format!(
+                        "{label} references environment {:?} missing from environments",
+                        environment
+                    ));
+                }
+            }
+            if let Some(confidence) = gate.min_ai_confidence {
+                if !(0.0..=1.0).contains(&confidence) {
+                    issues.push(format!(
+                        "{label} has min_ai_confidence {confidence} outside 0.0..=1.0"
+                    ));
+                }
+            }
+            for role in &gate.required_roles {
+                if !roles.contains(role.as_str()) {
+                    issues.push(format!(
+                        "{label} requires role '{role}' which has no approval entry"
+                    ));
+                }
+            }
+        }
+
+        SpecIssues(issues)
+    }
+
+    /// Plugin stage types referenced by this definition.
+    pub fn plugin_stage_types(&self) -> Vec<&str> {
+        self.stages
+            .iter()
+            .filter_map(|stage| match &stage.stage_type {
+                PipelineStage::Plugin(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Find the gate guarding promotion from `from` to `to`.
+    pub fn promotion_gate(&self, from: &Environment, to: &Environment) -> Option<&PromotionGate> {
+        self.promotion_gates
+            .iter()
+            .find(|gate| &gate.from == from && &gate.to == to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const TOML_SPEC: &str = r#"
+name = "service-release"
+environments = ["staging", "production"]
+
+[[stages]]
+name = "validate"
+type = "validate"
+
+[[stages]]
+name = "build"
+type = "Build"
+
+[scanners]
+gitleaks = true
+
+[[approvals]]
+role = "release-agent"
+minimum_trust_score = 0.8
+
+[[promotion_gates]]
+from = "staging"
+to = "production"
+required_roles = ["release-agent"]
+"#;
+
+    #[test]
+    fn discovers_toml_definition() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("pipeline.toml"), TOML_SPEC).unwrap();
+
+        let (path, spec) = PipelineSpec::discover(dir.path())
+            .expect("definition loads")
+            .expect("definition present");
+        assert!(path.ends_with("pipeline.toml"));
+        assert_eq!(spec.stages.len(), 2);
+        assert_eq!(spec.stages[0].stage_type, PipelineStage::Validate);
+        assert!(spec.scanners.as_ref().unwrap().gitleaks);
+        assert!(spec
+            .promotion_gate(&Environment::Staging, &Environment::Production)
+            .is_some());
+    }
+
+    #[test]
+    fn reports_every_validation_issue() {
+        let yaml = r#"
+stages:
+  - name: build
+    type: build
+  - name: build
+    type: test
+approvals:
+  - role: release-agent
+    minimum_trust_score: 1.5
+environments: [staging]
+promotion_gates:
+  - from: staging
+    to: production
+    required_roles: [security-agent]
+"#;
+        let err = PipelineSpec::from_yaml_str(yaml, Path::new("pipeline.yaml"))
+            .expect_err("definition should be rejected");
+        let PipelineSpecError::Invalid { issues, .. } = err else {
+            panic!("expected validation failure, got {err}");
+        };
+        assert_eq!(issues.0.len(), 4, "{issues}");
+        assert!(issues.to_string().contains("declared more than once"));
+        assert!(issues.to_string().contains("security-agent"));
+    }
+
+    #[test]
+    fn rejects_unknown_and_cyclic_stage_dependencies() {
+        let unknown = "stages:\n  - name: build\n    type: build\n    depends_on: [fetch, build]\n";
+        let err = PipelineSpec::from_yaml_str(unknown, Path::new("pipeline.yaml")).unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains("'fetch', which is not a stage"),
+            "{message}"
+        );
+        assert!(message.contains("'build' depends on itself"), "{message}");
+
+        let cyclic = "stages:
+  - name: build
+    type: build
+    depends_on: [test]
+  - name: test
+    type: test
+    depends_on: [build]
+  - name: lint
+    type: lint
+";
+        let err = PipelineSpec::from_yaml_str(cyclic, Path::new("pipeline.yaml")).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("stages form a dependency cycle: build, test"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn unknown_stage_type_becomes_plugin_stage() {
+        let yaml = "stages:\n  - name: plan\n    type: terraform-plan\n    parameters:\n      workspace: prod\n";
+        let spec = PipelineSpec::from_yaml_str(yaml, Path::new("pipeline.yaml")).unwrap();
+        assert_eq!(
+            spec.stages[0].stage_type,
+            PipelineStage::Plugin("terraform-plan".to_string())
+        );
+        assert_eq!(spec.stages[0].parameters["workspace"], "prod");
+        assert_eq!(spec.plugin_stage_types(), vec!["terraform-plan"]);
+
+        let round_trip = serde_yaml::to_string(&spec).unwrap();
+        assert!(round_trip.contains("type: terraform-plan"), "{round_trip}");
+    }
+}
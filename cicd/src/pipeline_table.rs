@@ -0,0 +1,205 @@
+//! Pipeline state shared by the executor and its readers.
+//!
+//! Stage runs, retries and status changes update pipelines under one mutex. Status
+//! queries and listings used to take that same mutex, so a dashboard polling every
+//! pipeline held up the executor between stage updates. [`PipelineTable`] keeps a
+//! copy-on-write [`PipelineSnapshot`] beside the mutex: each write republishes it
+//! before the mutex is released, cloning only the pipelines that changed, and readers
+//! clone the snapshot's `Arc` without touching the mutex.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
+
+use crate::Pipeline;
+
+/// Pipelines as of the last write, keyed by id. Cheap to clone and never blocks
+/// writers while held.
+#[derive(Clone, Default)]
+pub struct PipelineSnapshot {
+    pipelines: Arc<HashMap<String, Arc<Pipeline>>>,
+}
+
+impl PipelineSnapshot {
+    pub fn get(&self, pipeline_id: &str) -> Option<&Pipeline> {
+        self.pipelines.get(pipeline_id).map(Arc::as_ref)
+    }
+
+    pub fn contains(&self, pipeline_id: &str) -> bool {
+        self.pipelines.contains_key(pipeline_id)
+    }
+
+    /// Every pipeline, in no particular order.
+    pub fn pipelines(&self) -> impl Iterator<Item = &Pipeline> {
+        self.pipelines.values().map(Arc::as_ref)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct PipelineTable {
+    pipelines: Mutex<HashMap<String, Pipeline>>,
+    published: RwLock<PipelineSnapshot>,
+}
+
+impl PipelineTable {
+    /// Lock the pipelines for an update. Readers that only need a consistent view
+    /// should use [`PipelineTable::snapshot`] instead.
+    pub(crate) fn lock(&self) -> PipelineGuard<'_> {
+        PipelineGuard {
+            table: self,
+            pipelines: self.pipelines.lock().expect("pipeline lock poisoned"),
+            changed: Changed::None,
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> PipelineSnapshot {
+        self.published
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+enum Changed {
+    None,
+    Some(HashSet<String>),
+    All,
+}
+
+impl Changed {
+    fn insert(&mut self, pipeline_id: &str) {
+        match self {
+            Changed::None => *self = Changed::Some(HashSet::from([pipeline_id.to_string()])),
+            Changed::Some(ids) => {
+                ids.insert(pipeline_id.to_string());
+            }
+            Changed::All => {}
+        }
+    }
+}
+
+/// Locked pipelines. Reads go through `Deref`; writes go through the methods below,
+/// which note what changed so dropping the guard republishes just those pipelines.
+pub(crate) struct PipelineGuard<'a> {
+    table: &'a PipelineTable,
+    pipelines: MutexGuard<'a, HashMap<String, Pipeline>>,
+    changed: Changed,
+}
+
+impl PipelineGuard<'_> {
+    pub(crate) fn get_mut(&mut self, pipeline_id: &str) -> Option<&mut Pipeline> {
+        let pipeline = self.pipelines.get_mut(pipeline_id)?;
+        self.changed.insert(pipeline_id);
+        Some(pipeline)
+    }
+
+    pub(crate) fn insert(&mut self, pipeline_id: String, pipeline: Pipeline) -> Option<Pipeline> {
+        self.changed.insert(&pipeline_id);
+        self.pipelines.insert(pipeline_id, pipeline)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.pipelines.clear();
+        self.changed = Changed::All;
+    }
+
+    /// Build the next snapshot before taking the write lock, so readers only ever
+    /// wait for the pointer swap. The pipeline mutex keeps publishes in write order.
+    fn publish(&mut self) {
+        let next = match std::mem::replace(&mut self.changed, Changed::None) {
+            Changed::None => return,
+            Changed::All => self
+                .pipelines
+                .iter()
+                .map(|(id, pipeline)| (id.clone(), Arc::new(pipeline.clone())))
+                .collect(),
+            Changed::Some(ids) => {
+                let mut next = HashMap::clone(&self.table.snapshot().pipelines);
+                for id in ids {
+                    match self.pipelines.get(&id) {
+                        Some(pipeline) => next.insert(id, Arc::new(pipeline.clone())),
+                        None => next.remove(&id),
+                    };
+                }
+                next
+            }
+        };
+        *self
+            .table
+            .published
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = PipelineSnapshot {
+            pipelines: Arc::new(next),
+        };
+    }
+}
+
+impl Deref for PipelineGuard<'_> {
+    type Target = HashMap<String, Pipeline>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.pipelines
+    }
+}
+
+impl Drop for PipelineGuard<'_> {
+    fn drop(&mut self) {
+        self.publish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CICDSystem, PipelineStatus};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn readers_see_published_state_while_a_writer_holds_the_lock() {
+        let workspace = tempfile::tempdir().unwrap();
+        let system = CICDSystem::with_context(crate::context_in(workspace.path()));
+        system.configure_workspace_root(workspace.path());
+        let first = system
+            .trigger_pipeline("first".to_string(), "abc123".to_string())
+            .unwrap();
+        let second = system
+            .trigger_pipeline("second".to_string(), "def456".to_string())
+            .unwrap();
+        let before = system.pipeline_snapshot();
+
+        let mut pipelines = system.pipelines.lock();
+        pipelines.get_mut(&first).unwrap().status = PipelineStatus::Running;
+        thread::scope(|scope| {
+            let polled = scope
+                .spawn(|| {
+                    (
+                        system.get_pipeline_status(&first),
+                        system.list_pipelines().len(),
+                    )
+                })
+                .join()
+                .unwrap();
+            assert_eq!(polled, (Some(PipelineStatus::Pending), 2));
+        });
+        drop(pipelines);
+
+        let after = system.pipeline_snapshot();
+        assert_eq!(
+            system.get_pipeline_status(&first),
+            Some(PipelineStatus::Running)
+        );
+        assert_eq!(before.get(&first).unwrap().status, PipelineStatus::Pending);
+        assert!(Arc::ptr_eq(
+            &before.pipelines[&second],
+            &after.pipelines[&second]
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+//! Automatic retries for transient stage failures.
+//!
+//! A stage may carry a [`RetryPolicy`], declared in the pipeline spec or overridden when
+//! the pipeline is triggered. A failed attempt whose error matches `retry_on` is run
+//! again after an exponentially growing delay until `max_attempts` is reached. Build and
+//! Test stages resume from their checkpoints, so a retry only repeats unfinished units.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Upper bound on the delay before a single retry.
+pub const MAX_BACKOFF_MS: u64 = 60_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RetryPolicy {
+    /// Total attempts including the first; 1 disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further one.
+    #[serde(default)]
+    pub backoff_ms: u64,
+    /// Case-insensitive fragments of the error messages worth retrying. Empty retries
+    /// every failure.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retry_on: Vec<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff_ms: 0,
+            retry_on: Vec::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, backoff_ms: u64) -> Self {
+        Self {
+            max_attempts,
+            backoff_ms,
+            retry_on: Vec::new(),
+        }
+    }
+
+    pub fn with_retry_on(mut self, fragment: impl Into<String>) -> Self {
+        self.retry_on.push(fragment.into());
+        self
+    }
+
+    /// Whether a failure of `attempt` (1-based) with `error` should be tried again.
+    pub fn should_retry(&self, attempt: u32, error: &str) -> bool {
+        if attempt >= self.max_attempts {
+            return false;
+        }
+        if self.retry_on.is_empty() {
+            return true;
+        }
+        let error = error.to_lowercase();
+        self.retry_on
+            .iter()
+            .any(|fragment| error.contains(&fragment.to_lowercase()))
+    }
+
+    /// Delay before running attempt `attempt + 1`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u64
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u64::MAX);
+        Duration::from_millis(self.backoff_ms.saturating_mul(factor).min(MAX_BACKOFF_MS))
+    }
+
+    /// Problems with the policy, prefixed with the stage it belongs to.
+    pub fn issues(&self, stage: &str) -> Vec<String> {
+        let mut issues = Vec::new();
+        if self.max_attempts == 0 {
+            issues.push(format!(
+                "stage '{stage}' retry.max_attempts must be at least 1"
+            ));
+        }
+        if self
+            .retry_on
+            .iter()
+            .any(|fragment| fragment.trim().is_empty())
+        {
+            issues.push(format!("stage '{stage}' retry.retry_on has an empty entry"));
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_matching_errors_with_exponential_backoff() {
+        let policy = RetryPolicy::new(3, 100).with_retry_on("I/O error");
+        assert!(policy.should_retry(1, "scanner failed: i/o error reading lockfile"));
+        assert!(!policy.should_retry(1, "2 high severity findings"));
+        assert!(!policy.should_retry(3, "scanner failed: I/O error"));
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(
+            RetryPolicy::new(80, 1_000).backoff(70),
+            Duration::from_millis(MAX_BACKOFF_MS)
+        );
+        assert!(!RetryPolicy::default().should_retry(1, "anything"));
+        assert_eq!(RetryPolicy::new(0, 0).issues("scan").len(), 1);
+    }
+}
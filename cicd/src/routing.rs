@@ -0,0 +1,117 @@
+//! Deploy-time reverse proxy routing through the Caddy admin API.
+//!
+//! Each environment may be given a [`ReverseProxyRoute`] template. Deploying a service
+//! there tags the route with a stable id (`noa-<service>-<environment>`), remembers the
+//! configuration it replaces, and pushes it through a [`RouteController`]; the admin
+//! API response is kept on the deployment as a [`DeploymentRoute`]. Rolling the
+//! deployment back puts the remembered configuration back, or removes the route if the
+//! deployment created it.
+
+use noa_caddy_manager::{AdminResponse, CaddyManager, ReverseProxyRoute};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+
+use crate::history::environment_label;
+use crate::Environment;
+
+/// Caddy admin endpoint used for deploy routes when set, e.g. `http://127.0.0.1:2019`.
+pub const CADDY_ADMIN_ENV: &str = "NOA_CADDY_ADMIN_ENDPOINT";
+
+/// Applies and reverts reverse proxy routes.
+pub trait RouteController: Send + Sync {
+    /// Current configuration of the route tagged `id`, `None` when there is none.
+    fn current(&self, id: &str) -> Result<Option<Value>, String>;
+    /// Create `route`, or replace the route with the same id.
+    fn apply(&self, route: &ReverseProxyRoute) -> Result<AdminResponse, String>;
+    /// Put `previous` back under `id`, or remove the route when there was none.
+    fn restore(&self, id: &str, previous: Option<&Value>) -> Result<AdminResponse, String>;
+}
+
+/// Route change made by a deployment and, after a rollback, its reversal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeploymentRoute {
+    pub route_id: String,
+    pub domain: String,
+    pub upstreams: Vec<String>,
+    /// Configuration the route had before the deployment; `None` if it was created.
+    #[serde(default)]
+    pub previous: Option<Value>,
+    pub applied: AdminResponse,
+    #[serde(default)]
+    pub restored: Option<AdminResponse>,
+}
+
+/// Id of the route serving `service` in `environment`.
+pub fn route_id(service: &str, environment: &Environment) -> String {
+    let environment = environment_label(environment);
+    let service: String = service
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("noa-{service}-{environment}")
+}
+
+/// Drives a live Caddy instance through its admin API.
+pub struct CaddyRouteController {
+    manager: CaddyManager,
+}
+
+impl CaddyRouteController {
+    pub fn new(admin_endpoint: impl AsRef<str>) -> Result<Self, String> {
+        let manager = CaddyManager::new(admin_endpoint).map_err(|err| err.to_string())?;
+        Ok(Self { manager })
+    }
+}
+
+impl RouteController for CaddyRouteController {
+    fn current(&self, id: &str) -> Result<Option<Value>, String> {
+        block_on(self.manager.route(id))
+    }
+
+    fn apply(&self, route: &ReverseProxyRoute) -> Result<AdminResponse, String> {
+        block_on(self.manager.upsert_route(route))
+    }
+
+    fn restore(&self, id: &str, previous: Option<&Value>) -> Result<AdminResponse, String> {
+        match previous {
+            Some(config) => block_on(self.manager.replace_route_json(id, config)),
+            None => block_on(self.manager.delete_route(id)),
+        }
+    }
+}
+
+/// Run an admin API call on a dedicated runtime so deployments work both inside and
+/// outside an existing Tokio context.
+fn block_on<T: Send>(future: impl Future<Output = anyhow::Result<T>> + Send) -> Result<T, String> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(move || {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|err| format!("failed to start admin API runtime: {err}"))?
+                    .block_on(future)
+                    .map_err(|err| format!("{err:#}"))
+            })
+            .join()
+            .map_err(|_| "admin API call panicked".to_string())?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_ids_are_stable_per_service_and_environment() {
+        assert_eq!(
+            route_id("noa-gateway", &Environment::Production),
+            "noa-noa-gateway-production"
+        );
+        assert_eq!(
+            route_id("api/v2", &Environment::Staging),
+            "noa-api-v2-staging"
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Service level objectives and error-budget tracking.
+//!
+//! SLOs are declared per service and environment. Health metrics reported for
+//! deployments are kept as observations, and each objective's error budget is
+//! computed over its rolling window. `CICDSystem::auto_promote` refuses to promote
+//! into an environment whose budget is exhausted.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Environment, HealthMetrics};
+
+/// Default rolling window for error-budget accounting (30 days).
+pub const DEFAULT_SLO_WINDOW_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Default short window used to report the current burn rate (1 hour).
+pub const DEFAULT_BURN_WINDOW_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SloError {
+    #[error("SLO '{0}' is already defined")]
+    Duplicate(String),
+    #[error("SLO '{name}' target {target} must be within 0.0..1.0")]
+    InvalidTarget { name: String, target: f64 },
+    #[error("SLO name must not be empty")]
+    EmptyName,
+    #[error("SLO '{0}' window must be non-zero")]
+    EmptyWindow(String),
+}
+
+/// What an SLO measures.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SloObjective {
+    /// Fraction of requests that must succeed.
+    Availability { target: f64 },
+    /// Fraction of observations that must respond within `threshold_ms`.
+    Latency { threshold_ms: u64, target: f64 },
+}
+
+impl SloObjective {
+    pub fn target(&self) -> f64 {
+        match self {
+            SloObjective::Availability { target } | SloObjective::Latency { target, .. } => *target,
+        }
+    }
+
+    /// Split an observation into (total, bad) events for this objective.
+    fn events(&self, metrics: &HealthMetrics) -> (f64, f64) {
+        let total = metrics.active_connections.max(1) as f64;
+        let bad = match self {
+            SloObjective::Availability { .. } => {
+                total * (metrics.error_rate as f64 / 100.0).clamp(0.0, 1.0)
+            }
+            SloObjective::Latency { threshold_ms, .. } => {
+                if metrics.response_time_ms > *threshold_ms {
+                    total
+                } else {
+                    0.0
+                }
+            }
+        };
+        (total, bad)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SloDefinition {
+    pub name: String,
+    pub service: String,
+    pub environment: Environment,
+    pub objective: SloObjective,
+    #[serde(default = "SloDefinition::default_window")]
+    pub window_secs: u64,
+}
+
+impl SloDefinition {
+    fn default_window() -> u64 {
+        DEFAULT_SLO_WINDOW_SECS
+    }
+
+    pub fn new(
+        name: impl Into<String>,
+        service: impl Into<String>,
+        environment: Environment,
+        objective: SloObjective,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            service: service.into(),
+            environment,
+            objective,
+            window_secs: DEFAULT_SLO_WINDOW_SECS,
+        }
+    }
+
+    pub fn with_window_secs(mut self, window_secs: u64) -> Self {
+        self.window_secs = window_secs;
+        self
+    }
+}
+
+/// Health metrics observed for a service in an environment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloObservation {
+    pub service: String,
+    pub environment: Environment,
+    pub recorded_at: u64,
+    pub metrics: HealthMetrics,
+}
+
+/// Error-budget position of one SLO, as reported in deployment outcomes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ErrorBudgetStatus {
+    pub slo: String,
+    pub service: String,
+    pub environment: Environment,
+    pub target: f64,
+    /// Observed good-event ratio over the window (1.0 when nothing was observed).
+    pub observed: f64,
+    /// Fraction of the error budget consumed; values above 1.0 mean it is overspent.
+    pub budget_consumed: f64,
+    pub budget_remaining: f64,
+    /// Budget consumption rate over the burn window; 1.0 spends the budget exactly on schedule.
+    pub burn_rate: f64,
+    pub exhausted: bool,
+}
+
+/// SLO registry with the observations needed to compute error budgets.
+#[derive(Debug, Clone)]
+pub struct SloTracker {
+    definitions: Vec<SloDefinition>,
+    observations: Vec<SloObservation>,
+    burn_window_secs: u64,
+}
+
+impl Default for SloTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SloTracker {
+    pub fn new() -> Self {
+        Self {
+            definitions: Vec::new(),
+            observations: Vec::new(),
+            burn_window_secs: DEFAULT_BURN_WINDOW_SECS,
+        }
+    }
+
+    pub fn define(&mut self, definition: SloDefinition) -> Result<(), SloError> {
+        if definition.name.trim().is_empty() {
+            return Err(SloError::EmptyName);
+        }
+        let target = definition.objective.target();
+        if !(0.0..1.0).contains(&target) {
+            return Err(SloError::InvalidTarget {
+                name: definition.name,
+                target,
+            });
+        }
+        if definition.window_secs == 0 {
+            return Err(SloError::EmptyWindow(definition.name));
+        }
+        if self.definitions.iter().any(|d| d.name == definition.name) {
+            return Err(SloError::Duplicate(definition.name));
+        }
+        self.definitions.push(definition);
+        Ok(())
+    }
+
+    pub fn definitions(&self) -> &[SloDefinition] {
+        &self.definitions
+    }
+
+    pub fn observations(&self) -> &[SloObservation] {
+        &self.observations
+    }
+
+    /// Replace the definitions and observations, e.g. with those persisted before a
+    /// restart.
+    pub fn restore(&mut self, definitions: Vec<SloDefinition>, observations: Vec<SloObservation>) {
+        self.definitions = definitions;
+        self.observations = observations;
+    }
+
+    /// Record metrics for a service, pruning observations older than every window.
+    pub fn observe(
+        &mut self,
+        service: &str,
+        environment: &Environment,
+        metrics: &HealthMetrics,
+        now: u64,
+    ) {
+        self.observations.push(SloObservation {
+            service: service.to_string(),
+            environment: environment.clone(),
+            recorded_at: now,
+            metrics: metrics.clone(),
+        });
+        let horizon = self
+            .definitions
+            .iter()
+            .map(|d| d.window_secs)
+            .max()
+            .unwrap_or(DEFAULT_SLO_WINDOW_SECS);
+        let cutoff = now.saturating_sub(horizon);
+        self.observations.retain(|o| o.recorded_at >= cutoff);
+    }
+
+    /// Add observations made before this tracker started, oldest first, e.g. from
+    /// the time-series store. Those not older than the earliest observation already
+    /// held for the service and environment are skipped as duplicates.
+    pub fn backfill(
+        &mut self,
+        service: &str,
+        environment: &Environment,
+        history: impl IntoIterator<Item = (u64, HealthMetrics)>,
+    ) {
+        let earliest = self
+            .observations
+            .iter()
+            .filter(|o| o.service == service && &o.environment == environment)
+            .map(|o| o.recorded_at)
+            .min()
+            .unwrap_or(u64::MAX);
+        for (recorded_at, metrics) in history {
+            if recorded_at >= earliest {
+                break;
+            }
+            self.observations.push(SloObservation {
+                service: service.to_string(),
+                environment: environment.clone(),
+                recorded_at,
+                metrics,
+            });
+        }
+    }
+
+    /// Budget status for every SLO covering `service` in `environment`.
+    pub fn budget_status(
+        &self,
+        service: &str,
+        environment: &Environment,
+        now: u64,
+    ) -> Vec<ErrorBudgetStatus> {
+        self.definitions
+            .iter()
+            .filter(|d| d.service == service && &d.environment == environment)
+            .map(|d| self.status_for(d, now))
+            .collect()
+    }
+
+    fn status_for(&self, definition: &SloDefinition, now: u64) -> ErrorBudgetStatus {
+        let target = definition.objective.target();
+        let allowed = 1.0 - target;
+        let (total, bad) = self.tally(definition, now.saturating_sub(definition.window_secs));
+        let (burn_total, burn_bad) =
+            self.tally(definition, now.saturating_sub(self.burn_window_secs));
+
+        let bad_ratio = if total > 0.0 { bad / total } else { 0.0 };
+        let burn_ratio = if burn_total > 0.0 {
+            burn_bad / burn_total
+        } else {
+            0.0
+        };
+        let budget_consumed = bad_ratio / allowed;
+
+        ErrorBudgetStatus {
+            slo: definition.name.clone(),
+            service: definition.service.clone(),
+            environment: definition.environment.clone(),
+            target,
+            observed: 1.0 - bad_ratio,
+            budget_consumed,
+            budget_remaining: (1.0 - budget_consumed).max(0.0),
+            burn_rate: burn_ratio / allowed,
+            exhausted: budget_consumed >= 1.0,
+        }
+    }
+
+    fn tally(&self, definition: &SloDefinition, since: u64) -> (f64, f64) {
+        self.observations
+            .iter()
+            .filter(|o| {
+                o.service == definition.service
+                    && o.environment == definition.environment
+                    && o.recorded_at >= since
+            })
+            .map(|o| definition.objective.events(&o.metrics))
+            .fold((0.0, 0.0), |(total, bad), (t, b)| (total + t, bad + b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(error_rate: f32, response_time_ms: u64) -> HealthMetrics {
+        HealthMetrics {
+            error_rate,
+            response_time_ms,
+            cpu_usage: 20.0,
+            memory_usage: 30.0,
+            active_connections: 100,
+        }
+    }
+
+    #[test]
+    fn budget_burns_from_observed_errors() {
+        let mut tracker = SloTracker::new();
+        tracker
+            .define(SloDefinition::new(
+                "api-availability",
+                "api",
+                Environment::Production,
+                SloObjective::Availability { target: 0.99 },
+            ))
+            .unwrap();
+
+        tracker.observe("api", &Environment::Production, &metrics(0.5, 80), 1_000);
+        let status = &tracker.budget_status("api", &Environment::Production, 1_000)[0];
+        assert!((status.budget_consumed - 0.5).abs() < 1e-9);
+        assert!(!status.exhausted);
+
+        tracker.observe("api", &Environment::Production, &metrics(3.0, 80), 2_000);
+        let status = &tracker.budget_status("api", &Environment::Production, 2_000)[0];
+        assert!(status.exhausted, "{status:?}");
+        assert_eq!(status.budget_remaining, 0.0);
+        assert!(tracker
+            .budget_status("api", &Environment::Staging, 2_000)
+            .is_empty());
+    }
+
+    #[test]
+    fn latency_observations_age_out_of_window() {
+        let mut tracker = SloTracker::new();
+        tracker
+            .define(
+                SloDefinition::new(
+                    "api-latency",
+                    "api",
+                    Environment::Staging,
+                    SloObjective::Latency {
+                        threshold_ms: 200,
+                        target: 0.9,
+                    },
+                )
+                .with_window_secs(600),
+            )
+            .unwrap();
+        assert_eq!(
+            tracker.define(SloDefinition::new(
+                "api-latency",
+                "api",
+                Environment::Staging,
+                SloObjective::Availability { target: 0.5 },
+            )),
+            Err(SloError::Duplicate("api-latency".to_string()))
+        );
+
+        tracker.observe("api", &Environment::Staging, &metrics(0.0, 450), 0);
+        assert!(tracker.budget_status("api", &Environment::Staging, 0)[0].exhausted);
+
+        tracker.observe("api", &Environment::Staging, &metrics(0.0, 120), 1_000);
+        let status = &tracker.budget_status("api", &Environment::Staging, 1_000)[0];
+        assert!(!status.exhausted);
+        assert_eq!(status.observed, 1.0);
+    }
+}
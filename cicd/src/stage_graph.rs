@@ -0,0 +1,255 @@
+//! Stage dependency graphs.
+//!
+//! A pipeline definition can give each stage a `depends_on` list naming the stages
+//! that must succeed before it starts. When no stage declares one, the stages form
+//! a chain in declaration order, which is how the built-in stage list runs. Once any
+//! stage declares `depends_on`, every stage waits only for the stages it lists, and
+//! stages whose dependencies have all succeeded run at the same time.
+
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::thread;
+
+use noa_core::utils::{dependency_order, DependencyError};
+
+use crate::{CICDSystem, PipelineStatus, Stage};
+
+/// The stages each stage waits for, in stage order.
+pub(crate) fn dependencies(stages: &[Stage]) -> Vec<(String, Vec<String>)> {
+    let declared = stages.iter().any(|stage| !stage.depends_on.is_empty());
+    stages
+        .iter()
+        .enumerate()
+        .map(|(index, stage)| {
+            let depends_on = if declared {
+                stage.depends_on.clone()
+            } else {
+                index
+                    .checked_sub(1)
+                    .map(|previous| vec![stages[previous].name.clone()])
+                    .unwrap_or_default()
+            };
+            (stage.name.clone(), depends_on)
+        })
+        .collect()
+}
+
+/// Problems with the `depends_on` lists of `stages`, given as `(name, depends_on)`:
+/// unknown stages, stages that depend on themselves, and cycles.
+pub(crate) fn issues(stages: &[(&str, &[String])]) -> Vec<String> {
+    let names: HashSet<&str> = stages.iter().map(|(name, _)| *name).collect();
+    let mut issues = Vec::new();
+    for (name, depends_on) in stages {
+        for dependency in depends_on.iter() {
+            if dependency == name {
+                issues.push(format!("stage '{name}' depends on itself"));
+            } else if !names.contains(dependency.as_str()) {
+                issues.push(format!(
+                    "stage '{name}' depends on '{dependency}', which is not a stage"
+                ));
+            }
+        }
+    }
+    if issues.is_empty() {
+        let graph: Vec<(&str, Vec<&String>)> = stages
+            .iter()
+            .map(|(name, depends_on)| (*name, depends_on.iter().collect()))
+            .collect();
+        if let Err(DependencyError::Cycle(members)) = dependency_order(&graph) {
+            issues.push(format!(
+                "stages form a dependency cycle: {}",
+                members.join(", ")
+            ));
+        }
+    }
+    issues
+}
+
+/// How a run of a pipeline's stage graph ended.
+pub(crate) enum GraphOutcome {
+    Completed,
+    /// A pause or cancel request stopped the run before every stage started.
+    Halted(PipelineStatus),
+    /// The first stage to fail, after the stages running alongside it finished.
+    Failed {
+        stage: String,
+        error: String,
+    },
+}
+
+impl CICDSystem {
+    /// Run every stage that has not succeeded yet, each once its dependencies have
+    /// succeeded. Stages that become ready together run on their own threads. After a
+    /// failure or a halt request no further stage starts.
+    pub(crate) fn run_stage_graph(&self, pipeline_id: &str, stages: &[Stage]) -> GraphOutcome {
+        let dependencies = dependencies(stages);
+        let mut succeeded: HashSet<&str> = stages
+            .iter()
+            .filter(|stage| stage.status == PipelineStatus::Success)
+            .map(|stage| stage.name.as_str())
+            .collect();
+        let mut waiting: Vec<usize> = (0..stages.len())
+            .filter(|&index| !succeeded.contains(stages[index].name.as_str()))
+            .collect();
+        let mut halted = None;
+        let mut failed = None;
+
+        thread::scope(|scope| {
+            let (finished, results) = mpsc::channel();
+            let mut running = 0;
+            loop {
+                while failed.is_none() && halted.is_none() {
+                    let Some(position) = waiting.iter().position(|&index| {
+                        dependencies[index]
+                            .1
+                            .iter()
+                            .all(|dependency| succeeded.contains(dependency.as_str()))
+                    }) else {
+                        break;
+                    };
+                    if let Some(status) = self.halted_status(pipeline_id) {
+                        halted = Some(status);
+                        break;
+                    }
+                    let index = waiting.remove(position);
+                    let stage = &stages[index];
+                    self.set_stage_status(pipeline_id, &stage.name, PipelineStatus::Running);
+                    let finished = finished.clone();
+                    scope.spawn(move || {
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                            self.execute_stage_with_retry(pipeline_id, stage)
+                        }))
+                        .unwrap_or_else(|_| Err(format!("stage {} panicked", stage.name)));
+                        let _ = finished.send((index, result));
+                    });
+                    running += 1;
+                }
+                if running == 0 {
+                    break;
+                }
+                let (index, result) = results.recv().expect("stage threads report before exiting");
+                running -= 1;
+                let stage = &stages[index];
+                match result {
+                    Ok(()) => {
+                        succeeded.insert(stage.name.as_str());
+                    }
+                    Err(error) => {
+                        self.set_stage_status(pipeline_id, &stage.name, PipelineStatus::Failed);
+                        failed.get_or_insert_with(|| (stage.name.clone(), error));
+                    }
+                }
+            }
+        });
+
+        match (failed, halted, waiting.first()) {
+            (Some((stage, error)), _, _) => GraphOutcome::Failed { stage, error },
+            (None, Some(status), _) => GraphOutcome::Halted(status),
+            // Only a definition that skipped validation can leave a stage unreachable.
+            (None, None, Some(&index)) => GraphOutcome::Failed {
+                stage: stages[index].name.clone(),
+                error: format!(
+                    "stage {} depends on stages that cannot run",
+                    stages[index].name
+                ),
+            },
+            (None, None, None) => GraphOutcome::Completed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stage_plugins::{StageContext, StageExecutor, StageReport};
+    use serde_json::Value;
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::time::Duration;
+
+    /// Records stage order. Stages with `wait` set are held until two stages have
+    /// run at once, or five seconds have passed.
+    #[derive(Default)]
+    struct Overlap {
+        state: Mutex<(usize, usize, Vec<String>)>,
+        changed: Condvar,
+    }
+
+    impl StageExecutor for Overlap {
+        fn stage_type(&self) -> &str {
+            "overlap"
+        }
+
+        fn execute(&self, context: &StageContext) -> Result<StageReport, String> {
+            let mut state = self.state.lock().unwrap();
+            state.0 += 1;
+            state.1 = state.1.max(state.0);
+            self.changed.notify_all();
+            if context.parameters["wait"] == true {
+                state = self
+                    .changed
+                    .wait_timeout_while(state, Duration::from_secs(5), |state| state.1 < 2)
+                    .unwrap()
+                    .0;
+            }
+            state.2.push(context.stage_name.clone());
+            state.0 -= 1;
+            Ok(StageReport::succeeded("done", Value::Null))
+        }
+    }
+
+    #[test]
+    fn independent_stages_run_together_and_joins_wait_for_both() {
+        let workspace = tempfile::tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            "stages:
+  - name: publish
+    type: overlap
+    depends_on: [unit, lint]
+  - name: unit
+    type: overlap
+    depends_on: [checkout]
+    parameters: {wait: true}
+  - name: lint
+    type: overlap
+    depends_on: [checkout]
+    parameters: {wait: true}
+  - name: checkout
+    type: overlap
+",
+        )
+        .unwrap();
+        let system = CICDSystem::with_context(crate::context_in(workspace.path()));
+        system.configure_workspace_root(workspace.path());
+        let overlap = Arc::new(Overlap::default());
+        system.register_stage_executor(overlap.clone()).unwrap();
+
+        let id = system
+            .trigger_pipeline("fan-out".to_string(), "abc123".to_string())
+            .unwrap();
+        let plan = system.dry_run_pipeline(&id).unwrap();
+        assert_eq!(plan.stages[0].depends_on, ["unit", "lint"]);
+        system.execute_pipeline(&id).unwrap();
+
+        let state = overlap.state.lock().unwrap();
+        assert_eq!(state.1, 2, "unit and lint should overlap");
+        assert_eq!(state.2.first().map(String::as_str), Some("checkout"));
+        assert_eq!(state.2.last().map(String::as_str), Some("publish"));
+        let pipeline = system.get_pipeline(&id).unwrap();
+        assert_eq!(pipeline.status, PipelineStatus::Success);
+        assert!(pipeline
+            .stages
+            .iter()
+            .all(|stage| stage.status == PipelineStatus::Success));
+    }
+
+    #[test]
+    fn stages_without_depends_on_run_in_declared_order() {
+        let stages: Vec<Stage> = crate::default_stages();
+        let dependencies = dependencies(&stages);
+        assert!(dependencies[0].1.is_empty());
+        assert_eq!(dependencies[1].1, ["validate"]);
+        assert_eq!(dependencies[4].1, ["single_host_acceptance"]);
+    }
+}
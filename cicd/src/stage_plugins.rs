@@ -0,0 +1,242 @@
+//! Pluggable pipeline stage executors.
+//!
+//! Built-in stages are handled by `CICDSystem` directly. Any other stage type
+//! named in a pipeline definition is dispatched to an executor registered here,
+//! either an in-process [`StageExecutor`] or a `crc_adapter_sdk` capability
+//! adapter (including WASM-backed adapters) wrapped in [`AdapterStageExecutor`].
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use crc_adapter_sdk::CapabilityAdapter;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Input handed to a stage executor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageContext {
+    pub pipeline_id: String,
+    pub pipeline_name: String,
+    pub stage_name: String,
+    pub stage_type: String,
+    pub commit_sha: String,
+    pub workspace_root: PathBuf,
+    #[serde(default)]
+    pub parameters: Value,
+}
+
+/// Outcome reported by a stage executor.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StageReport {
+    pub success: bool,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub outputs: Value,
+}
+
+impl StageReport {
+    pub fn succeeded(summary: impl Into<String>, outputs: Value) -> Self {
+        Self {
+            success: true,
+            summary: summary.into(),
+            outputs,
+        }
+    }
+
+    pub fn failed(summary: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            summary: summary.into(),
+            outputs: Value::Null,
+        }
+    }
+}
+
+/// Execute contract implemented by third-party stage types.
+pub trait StageExecutor: Send + Sync {
+    /// Stage type name referenced from pipeline definitions, e.g. `terraform-plan`.
+    fn stage_type(&self) -> &str;
+
+    fn execute(&self, context: &StageContext) -> Result<StageReport, String>;
+}
+
+/// Registry of stage executors keyed by stage type name.
+#[derive(Default)]
+pub struct StageExecutorRegistry {
+    executors: RwLock<HashMap<String, Arc<dyn StageExecutor>>>,
+}
+
+impl StageExecutorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an executor. Names must be unique and must not shadow a built-in stage.
+    pub fn register(&self, executor: Arc<dyn StageExecutor>) -> Result<(), String> {
+        let name = executor.stage_type().to_string();
+        if name.trim().is_empty() {
+            return Err("stage executor name must not be empty".to_string());
+        }
+        if crate::pipeline_spec::builtin_stage(&name).is_some() {
+            return Err(format!("stage type '{name}' is a built-in stage"));
+        }
+        let mut executors = self
+            .executors
+            .write()
+            .expect("stage executor registry poisoned");
+        if executors.contains_key(&name) {
+            return Err(format!("stage type '{name}' is already registered"));
+        }
+        executors.insert(name, executor);
+        Ok(())
+    }
+
+    pub fn get(&self, stage_type: &str) -> Option<Arc<dyn StageExecutor>> {
+        self.executors
+            .read()
+            .expect("stage executor registry poisoned")
+            .get(stage_type)
+            .cloned()
+    }
+
+    pub fn contains(&self, stage_type: &str) -> bool {
+        self.get(stage_type).is_some()
+    }
+
+    pub fn stage_types(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .executors
+            .read()
+            .expect("stage executor registry poisoned")
+            .keys()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+/// Runs a CRC capability adapter as a pipeline stage.
+///
+/// The adapter receives the serialised [`StageContext`] and may return either a
+/// [`StageReport`] or any other JSON value, which is treated as successful output.
+pub struct AdapterStageExecutor {
+    stage_type: String,
+    adapter: Arc<dyn CapabilityAdapter>,
+}
+
+impl AdapterStageExecutor {
+    pub fn new(stage_type: impl Into<String>, adapter: Arc<dyn CapabilityAdapter>) -> Self {
+        Self {
+            stage_type: stage_type.into(),
+            adapter,
+        }
+    }
+}
+
+impl StageExecutor for AdapterStageExecutor {
+    fn stage_type(&self) -> &str {
+        &self.stage_type
+    }
+
+    fn execute(&self, context: &StageContext) -> Result<StageReport, String> {
+        let input = serde_json::to_value(context)
+            .map_err(|err| format!("failed to encode stage context: {err}"))?;
+        let adapter = Arc::clone(&self.adapter);
+        // Adapters are async; drive them on a dedicated runtime so executors work
+        // both inside and outside an existing Tokio context.
+        let output = std::thread::scope(|scope| {
+            scope
+                .spawn(move || {
+                    let runtime = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .map_err(|err| format!("failed to start adapter runtime: {err}"))?;
+                    runtime
+                        .block_on(adapter.execute(input))
+                        .map_err(|err| err.to_string())
+                })
+                .join()
+                .map_err(|_| "stage adapter panicked".to_string())?
+        })?;
+
+        Ok(
+            serde_json::from_value::<StageReport>(output.clone()).unwrap_or_else(|_| {
+                StageReport::succeeded(
+                    format!("adapter {} completed", self.adapter.metadata().id),
+                    json!(output),
+                )
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use crc_adapter_sdk::AdapterMetadata;
+
+    struct EchoAdapter;
+
+    #[async_trait]
+    impl CapabilityAdapter for EchoAdapter {
+        async fn execute(&self, input: Value) -> anyhow::Result<Value> {
+            Ok(
+                json!({ "stage": input["stage_name"], "workspace": input["parameters"]["workspace"] }),
+            )
+        }
+
+        fn metadata(&self) -> AdapterMetadata {
+            AdapterMetadata {
+                id: "echo".to_string(),
+                kind: "stage".to_string(),
+                version: "0.1.0".to_string(),
+                requires: Vec::new(),
+                provides: vec!["terraform-plan".to_string()],
+            }
+        }
+    }
+
+    fn context() -> StageContext {
+        StageContext {
+            pipeline_id: "pipeline_1".to_string(),
+            pipeline_name: "infra".to_string(),
+            stage_name: "plan".to_string(),
+            stage_type: "terraform-plan".to_string(),
+            commit_sha: "abc123".to_string(),
+            workspace_root: PathBuf::from("."),
+            parameters: json!({ "workspace": "prod" }),
+        }
+    }
+
+    #[test]
+    fn adapter_output_becomes_stage_report() {
+        let executor = AdapterStageExecutor::new("terraform-plan", Arc::new(EchoAdapter));
+        let report = executor.execute(&context()).unwrap();
+        assert!(report.success);
+        assert_eq!(
+            report.outputs,
+            json!({ "stage": "plan", "workspace": "prod" })
+        );
+    }
+
+    #[test]
+    fn registry_rejects_builtin_and_duplicate_names() {
+        let registry = StageExecutorRegistry::new();
+        let builtin = AdapterStageExecutor::new("build", Arc::new(EchoAdapter));
+        assert!(registry.register(Arc::new(builtin)).is_err());
+
+        let plan = || {
+            Arc::new(AdapterStageExecutor::new(
+                "terraform-plan",
+                Arc::new(EchoAdapter),
+            ))
+        };
+        registry.register(plan()).unwrap();
+        assert!(registry.register(plan()).is_err());
+        assert_eq!(registry.stage_types(), vec!["terraform-plan"]);
+    }
+}
@@ -2,8 +2,11 @@
 // Listens for CRC completion events and triggers CI/CD pipelines
 
 use crate::ledger::{AuditLedger, LedgerAction, LedgerEntry};
+use crate::CICDSystem;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 use tokio::fs;
 use tokio::process::Command;
@@ -28,6 +31,9 @@ pub enum Error {
 
     #[error("Send error: {0}")]
     SendError(String),
+
+    #[error("Invalid schedule: {0}")]
+    InvalidSchedule(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -734,6 +740,349 @@ impl TriggerManager {
     }
 }
 
+// === Scheduled Triggers ===
+
+/// How often a scheduled pipeline fires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    /// Five-field cron expression evaluated in UTC.
+    Cron(CronExpression),
+    /// Fixed interval in seconds.
+    Every { secs: u64 },
+}
+
+impl Schedule {
+    pub fn cron(expression: &str) -> Result<Self> {
+        expression.parse().map(Schedule::Cron)
+    }
+
+    pub fn every(interval: Duration) -> Result<Self> {
+        match interval.as_secs() {
+            0 => Err(Error::InvalidSchedule(
+                "interval must be at least one second".to_string(),
+            )),
+            secs => Ok(Schedule::Every { secs }),
+        }
+    }
+
+    /// First fire time strictly after `after`, in unix seconds.
+    pub fn next_after(&self, after: u64) -> Option<u64> {
+        match self {
+            Schedule::Cron(expression) => expression.next_after(after),
+            Schedule::Every { secs } => after.checked_add((*secs).max(1)),
+        }
+    }
+}
+
+/// Parsed `minute hour day-of-month month day-of-week` expression.
+///
+/// Fields accept `*`, values, ranges (`1-5`), lists (`1,15`) and steps (`*/15`, `0-30/10`).
+/// Day-of-week runs from 0 (Sunday) to 6, with 7 also meaning Sunday. As in cron, when
+/// both day fields are restricted a day matching either one fires. `@hourly`, `@daily`,
+/// `@weekly`, `@monthly` and `@yearly` are accepted as shorthands.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CronExpression {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+/// How far ahead a cron expression is searched before it is considered unsatisfiable.
+const CRON_SEARCH_HORIZON_SECS: u64 = 5 * 366 * 86_400;
+
+impl CronExpression {
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// First matching minute strictly after `after`, in unix seconds.
+    pub fn next_after(&self, after: u64) -> Option<u64> {
+        let mut candidate = (after / 60 + 1) * 60;
+        let horizon = candidate.saturating_add(CRON_SEARCH_HORIZON_SECS);
+        while candidate < horizon {
+            let at = time::OffsetDateTime::from_unix_timestamp(candidate as i64).ok()?;
+            if !has_bit(self.months, u8::from(at.month()) as u32) {
+                candidate = start_of_next_month(at)?;
+            } else if !self.day_matches(
+                at.day() as u32,
+                at.weekday().number_days_from_sunday() as u32,
+            ) {
+                candidate = candidate - candidate % 86_400 + 86_400;
+            } else if !has_bit(self.hours, at.hour() as u32) {
+                candidate = candidate - candidate % 3_600 + 3_600;
+            } else if !has_bit(self.minutes, at.minute() as u32) {
+                candidate += 60;
+            } else {
+                return Some(candidate);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, day_of_month: u32, day_of_week: u32) -> bool {
+        let by_month = has_bit(self.days_of_month, day_of_month);
+        let by_week = has_bit(self.days_of_week, day_of_week);
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => by_month || by_week,
+            _ => by_month && by_week,
+        }
+    }
+}
+
+impl std::str::FromStr for CronExpression {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        let expanded = match source.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(Error::InvalidSchedule(format!(
+                "cron expression '{source}' must have 5 fields, found {}",
+                fields.len()
+            )));
+        };
+        let mut days_of_week = parse_cron_field(day_of_week, 0, 7, "day-of-week")?;
+        if has_bit(days_of_week, 7) {
+            days_of_week |= 1;
+        }
+        Ok(Self {
+            source: source.trim().to_string(),
+            minutes: parse_cron_field(minute, 0, 59, "minute")?,
+            hours: parse_cron_field(hour, 0, 23, "hour")?,
+            days_of_month: parse_cron_field(day_of_month, 1, 31, "day-of-month")?,
+            months: parse_cron_field(month, 1, 12, "month")?,
+            days_of_week,
+            day_of_month_restricted: day_of_month != "*",
+            day_of_week_restricted: day_of_week != "*",
+        })
+    }
+}
+
+impl TryFrom<String> for CronExpression {
+    type Error = Error;
+
+    fn try_from(source: String) -> Result<Self> {
+        source.parse()
+    }
+}
+
+impl From<CronExpression> for String {
+    fn from(expression: CronExpression) -> Self {
+        expression.source
+    }
+}
+
+fn parse_cron_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64> {
+    let invalid = || Error::InvalidSchedule(format!("invalid cron {name} field '{field}'"));
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (
+                    start.parse().map_err(|_| invalid())?,
+                    end.parse().map_err(|_| invalid())?,
+                ),
+                None => {
+                    let value = range.parse().map_err(|_| invalid())?;
+                    (value, if part.contains('/') { max } else { value })
+                }
+            },
+        };
+        if step == 0 || start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn has_bit(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+fn start_of_next_month(at: time::OffsetDateTime) -> Option<u64> {
+    let (year, month) = match at.month() {
+        time::Month::December => (at.year() + 1, time::Month::January),
+        month => (at.year(), month.next()),
+    };
+    let start = time::Date::from_calendar_date(year, month, 1).ok()?;
+    u64::try_from(start.midnight().assume_utc().unix_timestamp()).ok()
+}
+
+/// Pipeline triggered on a [`Schedule`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineSchedule {
+    pub id: String,
+    pub pipeline: String,
+    /// Commit or ref handed to every run.
+    pub commit_sha: String,
+    pub schedule: Schedule,
+    /// Next fire time in unix seconds; `None` when the schedule can never fire again.
+    pub next_run_at: Option<u64>,
+    #[serde(default)]
+    pub last_run_at: Option<u64>,
+    /// Pipeline started by the latest run.
+    #[serde(default)]
+    pub last_pipeline_id: Option<String>,
+    /// Error of the latest run, cleared when a run succeeds.
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+/// Outcome of one scheduled trigger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledRun {
+    pub schedule_id: String,
+    pub fired_at: u64,
+    pub result: std::result::Result<String, String>,
+}
+
+/// Longest the background task sleeps before re-reading schedules, so ones added later
+/// are picked up.
+pub const SCHEDULER_MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// Fires `trigger_pipeline` on cron or interval schedules.
+///
+/// Schedules are kept in the CI/CD system's pipeline state file, so they survive restarts.
+/// A schedule whose fire time passed while the process was down fires once on the next
+/// tick and is then rescheduled from the current time.
+pub struct PipelineScheduler {
+    cicd: Arc<CICDSystem>,
+}
+
+impl PipelineScheduler {
+    pub fn new(cicd: Arc<CICDSystem>) -> Self {
+        Self { cicd }
+    }
+
+    /// Add, or replace, the schedule `id`, first firing after `now`.
+    pub fn schedule(
+        &self,
+        id: impl Into<String>,
+        pipeline: impl Into<String>,
+        commit_sha: impl Into<String>,
+        schedule: Schedule,
+        now: u64,
+    ) -> Result<PipelineSchedule> {
+        let next_run_at = schedule.next_after(now);
+        if next_run_at.is_none() {
+            return Err(Error::InvalidSchedule("schedule never fires".to_string()));
+        }
+        let entry = PipelineSchedule {
+            id: id.into(),
+            pipeline: pipeline.into(),
+            commit_sha: commit_sha.into(),
+            schedule,
+            next_run_at,
+            last_run_at: None,
+            last_pipeline_id: None,
+            last_error: None,
+        };
+        self.cicd
+            .save_schedule(entry.clone())
+            .map_err(Error::SystemError)?;
+        Ok(entry)
+    }
+
+    /// Remove a schedule, returning whether it existed.
+    pub fn unschedule(&self, id: &str) -> Result<bool> {
+        self.cicd.remove_schedule(id).map_err(Error::SystemError)
+    }
+
+    pub fn schedules(&self) -> Vec<PipelineSchedule> {
+        self.cicd.schedules()
+    }
+
+    /// Trigger every schedule due at `now` and move each to its next fire time.
+    pub fn run_due(&self, now: u64) -> Result<Vec<ScheduledRun>> {
+        let mut runs = Vec::new();
+        for mut entry in self.cicd.schedules() {
+            if entry.next_run_at.is_none_or(|at| at > now) {
+                continue;
+            }
+            let result = self
+                .cicd
+                .trigger_pipeline(entry.pipeline.clone(), entry.commit_sha.clone());
+            match &result {
+                Ok(pipeline_id) => {
+                    info!(schedule = %entry.id, pipeline_id = %pipeline_id, "scheduled pipeline triggered");
+                    entry.last_pipeline_id = Some(pipeline_id.clone());
+                    entry.last_error = None;
+                }
+                Err(err) => {
+                    warn!(schedule = %entry.id, error = %err, "scheduled pipeline failed to trigger");
+                    entry.last_error = Some(err.clone());
+                }
+            }
+            entry.last_run_at = Some(now);
+            entry.next_run_at = entry.schedule.next_after(now);
+            runs.push(ScheduledRun {
+                schedule_id: entry.id.clone(),
+                fired_at: now,
+                result,
+            });
+            self.cicd.save_schedule(entry).map_err(Error::SystemError)?;
+        }
+        Ok(runs)
+    }
+
+    /// Seconds until the earliest schedule is due, capped at [`SCHEDULER_MAX_SLEEP`].
+    pub fn sleep_duration(&self, now: u64) -> Duration {
+        self.cicd
+            .schedules()
+            .iter()
+            .filter_map(|entry| entry.next_run_at)
+            .min()
+            .map(|at| Duration::from_secs(at.saturating_sub(now)))
+            .unwrap_or(SCHEDULER_MAX_SLEEP)
+            .min(SCHEDULER_MAX_SLEEP)
+    }
+
+    /// Run the scheduler on a background tokio task until it is aborted.
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let scheduler = self.clone();
+                let ran = tokio::task::spawn_blocking(move || scheduler.run_due(unix_now())).await;
+                match ran {
+                    Ok(Err(err)) => error!(error = %err, "pipeline scheduler tick failed"),
+                    Err(err) => error!(error = %err, "pipeline scheduler tick panicked"),
+                    Ok(Ok(_)) => {}
+                }
+                tokio::time::sleep(self.sleep_duration(unix_now())).await;
+            }
+        })
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -899,4 +1248,106 @@ mod tests {
         let entries = manager.ledger.load_entries().await.unwrap();
         assert!(entries.iter().any(|entry| entry.drop_id == "feature"));
     }
+
+    #[test]
+    fn cron_expressions_find_the_next_matching_minute() {
+        // 2024-01-01 00:00:00 UTC, a Monday.
+        let monday = 1_704_067_200;
+        let weekdays = Schedule::cron("30 9 * * 1-5").unwrap();
+        assert_eq!(
+            weekdays.next_after(monday),
+            Some(monday + 9 * 3_600 + 1_800)
+        );
+        let quarter_hours = Schedule::cron("*/15 * * * *").unwrap();
+        assert_eq!(quarter_hours.next_after(monday), Some(monday + 900));
+        assert_eq!(quarter_hours.next_after(monday + 1), Some(monday + 900));
+        assert_eq!(
+            Schedule::cron("@monthly").unwrap().next_after(monday),
+            Some(1_706_745_600)
+        );
+        // Leap days only come round again in 2028.
+        assert_eq!(
+            Schedule::cron("0 0 29 2 *")
+                .unwrap()
+                .next_after(1_709_251_200),
+            Some(1_835_395_200)
+        );
+        // Restricting both day fields fires on either: the 3rd or any Sunday.
+        let either = Schedule::cron("0 0 3 * 0").unwrap();
+        assert_eq!(either.next_after(monday), Some(monday + 2 * 86_400));
+        assert_eq!(
+            either.next_after(monday + 2 * 86_400),
+            Some(monday + 6 * 86_400)
+        );
+        assert_eq!(
+            Schedule::cron("0 0 * * 7").unwrap().next_after(monday),
+            Some(monday + 6 * 86_400)
+        );
+
+        for invalid in [
+            "61 * * * *",
+            "* * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "0 0 31 2 *x",
+        ] {
+            assert!(
+                matches!(Schedule::cron(invalid), Err(Error::InvalidSchedule(_))),
+                "{invalid}"
+            );
+        }
+        assert_eq!(
+            Schedule::cron("0 0 30 2 *").unwrap().next_after(monday),
+            None
+        );
+        assert!(Schedule::every(Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn scheduled_pipelines_fire_when_due_and_survive_restart() {
+        let workspace = tempdir().unwrap();
+        let open = || {
+            let cicd = CICDSystem::with_context(crate::context_in(workspace.path()));
+            cicd.configure_workspace_root(workspace.path());
+            cicd.reload_state(noa_core::recovery::RecoveryMode::Strict)
+                .unwrap();
+            Arc::new(cicd)
+        };
+        let scheduler = PipelineScheduler::new(open());
+        let hourly = Schedule::every(Duration::from_secs(3_600)).unwrap();
+        scheduler
+            .schedule("nightly", "nightly-build", "main", hourly.clone(), 1_000)
+            .unwrap();
+        let err = scheduler
+            .schedule(
+                "never",
+                "x",
+                "main",
+                Schedule::cron("0 0 30 2 *").unwrap(),
+                1_000,
+            )
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidSchedule(_)));
+        assert!(scheduler.run_due(4_599).unwrap().is_empty());
+        assert_eq!(scheduler.sleep_duration(4_599), Duration::from_secs(1));
+
+        let runs = scheduler.run_due(4_600).unwrap();
+        assert_eq!(runs.len(), 1);
+        let pipeline_id = runs[0].result.clone().expect("pipeline triggered");
+
+        let restarted = PipelineScheduler::new(open());
+        let schedules = restarted.schedules();
+        assert_eq!(schedules.len(), 1);
+        assert_eq!(schedules[0].schedule, hourly);
+        assert_eq!(schedules[0].last_run_at, Some(4_600));
+        assert_eq!(schedules[0].next_run_at, Some(8_200));
+        assert_eq!(schedules[0].last_pipeline_id, Some(pipeline_id));
+        assert!(restarted.run_due(8_199).unwrap().is_empty());
+        assert_eq!(restarted.run_due(20_000).unwrap().len(), 1);
+        assert_eq!(restarted.schedules()[0].next_run_at, Some(23_600));
+
+        assert!(restarted.unschedule("nightly").unwrap());
+        assert!(!restarted.unschedule("nightly").unwrap());
+        assert!(open().schedules().is_empty());
+    }
 }
@@ -0,0 +1,449 @@
+//! Push and pull request webhooks from GitHub and GitLab.
+//!
+//! A [`WebhookDelivery`] is read from the request headers, checked against the shared
+//! secret (`X-Hub-Signature-256` HMAC for GitHub, `X-Gitlab-Token` for GitLab), and
+//! turned into a [`WebhookEvent`]. Pushes to a branch and opened or updated pull (merge)
+//! requests trigger a pipeline for the head commit, which keeps the [`PipelineSource`]
+//! describing where the commit came from. Everything else, such as pings, tag pushes,
+//! branch deletions, or closed pull requests, is acknowledged and ignored.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+/// Shared secret webhook deliveries must be signed with.
+pub const WEBHOOK_SECRET_ENV: &str = "NOA_WEBHOOK_SECRET";
+
+const NULL_SHA: &str = "0000000000000000000000000000000000000000";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WebhookError {
+    #[error("webhook secret is not configured; set {WEBHOOK_SECRET_ENV}")]
+    NotConfigured,
+    #[error("webhook signature rejected: {0}")]
+    Unauthorized(String),
+    #[error("invalid webhook payload: {0}")]
+    InvalidPayload(String),
+    #[error("failed to trigger pipeline: {0}")]
+    Trigger(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookProvider {
+    Github,
+    Gitlab,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceEventKind {
+    Push,
+    PullRequest,
+}
+
+/// Where a pipeline's commit came from, as reported by the forge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineSource {
+    pub provider: WebhookProvider,
+    pub kind: SourceEventKind,
+    /// Repository path, e.g. `FlexNetOS/noa_ark_os`.
+    pub repository: String,
+    /// Pushed branch, or the pull request's head branch.
+    pub branch: String,
+    /// Branch a pull request targets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_branch: Option<String>,
+    /// Pull request number (GitLab merge request IID).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_request: Option<u64>,
+    /// Pull request title, or the head commit's subject line for pushes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Delivery id assigned by the forge, for correlating retries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery: Option<String>,
+}
+
+/// What a delivery asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookEvent {
+    Trigger {
+        commit_sha: String,
+        source: PipelineSource,
+    },
+    Ignored {
+        reason: String,
+    },
+}
+
+/// Result of handling a delivery, as returned to the forge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum WebhookOutcome {
+    Triggered { pipeline_id: String },
+    Ignored { reason: String },
+}
+
+/// Headers identifying and authenticating a webhook delivery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookDelivery {
+    pub provider: WebhookProvider,
+    /// `X-GitHub-Event` or `X-Gitlab-Event` value.
+    pub event: String,
+    /// `X-Hub-Signature-256` for GitHub, `X-Gitlab-Token` for GitLab.
+    pub credential: Option<String>,
+    pub delivery: Option<String>,
+}
+
+impl WebhookDelivery {
+    /// Read a delivery from request headers; `None` when no forge event header is set.
+    /// `header` looks names up case-insensitively.
+    pub fn from_headers<'a>(header: impl Fn(&str) -> Option<&'a str>) -> Option<Self> {
+        let owned = |name: &str| header(name).map(str::to_string);
+        if let Some(event) = owned("x-github-event") {
+            return Some(Self {
+                provider: WebhookProvider::Github,
+                event,
+                credential: owned("x-hub-signature-256"),
+                delivery: owned("x-github-delivery"),
+            });
+        }
+        owned("x-gitlab-event").map(|event| Self {
+            provider: WebhookProvider::Gitlab,
+            event,
+            credential: owned("x-gitlab-token"),
+            delivery: owned("x-gitlab-event-uuid"),
+        })
+    }
+
+    /// Check that `body` was sent by a forge holding `secret`.
+    pub fn verify(&self, secret: &str, body: &[u8]) -> Result<(), WebhookError> {
+        let credential = self
+            .credential
+            .as_deref()
+            .ok_or_else(|| WebhookError::Unauthorized("missing signature header".into()))?;
+        let valid = match self.provider {
+            WebhookProvider::Github => credential
+                .strip_prefix("sha256=")
+                .and_then(|signature| hex::decode(signature).ok())
+                .is_some_and(|signature| {
+                    signer(secret)
+                        .chain_update(body)
+                        .verify_slice(&signature)
+                        .is_ok()
+                }),
+            WebhookProvider::Gitlab => credential.as_bytes().ct_eq(secret.as_bytes()).into(),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(WebhookError::Unauthorized("signature mismatch".into()))
+        }
+    }
+
+    /// Map the delivery's payload to a pipeline trigger.
+    pub fn parse(&self, body: &[u8]) -> Result<WebhookEvent, WebhookError> {
+        let payload: Value = serde_json::from_slice(body)
+            .map_err(|err| WebhookError::InvalidPayload(err.to_string()))?;
+        match (self.provider, self.event.as_str()) {
+            (WebhookProvider::Github, "push") => self.github_push(&payload),
+            (WebhookProvider::Github, "pull_request") => self.github_pull_request(&payload),
+            (WebhookProvider::Gitlab, "Push Hook") => self.gitlab_push(&payload),
+            (WebhookProvider::Gitlab, "Merge Request Hook") => self.gitlab_merge_request(&payload),
+            (_, event) => Ok(ignored(format!("{event} events do not trigger pipelines"))),
+        }
+    }
+
+    fn github_push(&self, payload: &Value) -> Result<WebhookEvent, WebhookError> {
+        let reference = required(payload, "/ref")?;
+        let Some(branch) = reference.strip_prefix("refs/heads/") else {
+            return Ok(ignored(format!("{reference} is not a branch")));
+        };
+        let commit_sha = required(payload, "/after")?;
+        if payload["deleted"].as_bool() == Some(true) || commit_sha == NULL_SHA {
+            return Ok(ignored(format!("branch {branch} was deleted")));
+        }
+        Ok(WebhookEvent::Trigger {
+            commit_sha: commit_sha.to_string(),
+            source: PipelineSource {
+                provider: self.provider,
+                kind: SourceEventKind::Push,
+                repository: required(payload, "/repository/full_name")?.to_string(),
+                branch: branch.to_string(),
+                base_branch: None,
+                pull_request: None,
+                title: optional(payload, "/head_commit/message").map(subject),
+                author: optional(payload, "/pusher/name")
+                    .or_else(|| optional(payload, "/sender/login"))
+                    .map(str::to_string),
+                url: optional(payload, "/compare").map(str::to_string),
+                delivery: self.delivery.clone(),
+            },
+        })
+    }
+
+    fn github_pull_request(&self, payload: &Value) -> Result<WebhookEvent, WebhookError> {
+        let action = required(payload, "/action")?;
+        if !matches!(
+            action,
+            "opened" | "synchronize" | "reopened" | "ready_for_review"
+        ) {
+            return Ok(ignored(format!("pull request action {action}")));
+        }
+        Ok(WebhookEvent::Trigger {
+            commit_sha: required(payload, "/pull_request/head/sha")?.to_string(),
+            source: PipelineSource {
+                provider: self.provider,
+                kind: SourceEventKind::PullRequest,
+                repository: required(payload, "/repository/full_name")?.to_string(),
+                branch: required(payload, "/pull_request/head/ref")?.to_string(),
+                base_branch: optional(payload, "/pull_request/base/ref").map(str::to_string),
+                pull_request: payload
+                    .pointer("/pull_request/number")
+                    .and_then(Value::as_u64),
+                title: optional(payload, "/pull_request/title").map(str::to_string),
+                author: optional(payload, "/pull_request/user/login").map(str::to_string),
+                url: optional(payload, "/pull_request/html_url").map(str::to_string),
+                delivery: self.delivery.clone(),
+            },
+        })
+    }
+
+    fn gitlab_push(&self, payload: &Value) -> Result<WebhookEvent, WebhookError> {
+        let reference = required(payload, "/ref")?;
+        let Some(branch) = reference.strip_prefix("refs/heads/") else {
+            return Ok(ignored(format!("{reference} is not a branch")));
+        };
+        let Some(commit_sha) = optional(payload, "/checkout_sha") else {
+            return Ok(ignored(format!("branch {branch} was deleted")));
+        };
+        let head = payload["commits"].as_array().and_then(|commits| {
+            commits
+                .iter()
+                .find(|commit| commit["id"].as_str() == Some(commit_sha))
+        });
+        Ok(WebhookEvent::Trigger {
+            commit_sha: commit_sha.to_string(),
+            source: PipelineSource {
+                provider: self.provider,
+                kind: SourceEventKind::Push,
+                repository: required(payload, "/project/path_with_namespace")?.to_string(),
+                branch: branch.to_string(),
+                base_branch: None,
+                pull_request: None,
+                title: head
+                    .and_then(|commit| commit["message"].as_str())
+                    .map(subject),
+                author: optional(payload, "/user_username").map(str::to_string),
+                url: head
+                    .and_then(|commit| commit["url"].as_str())
+                    .map(str::to_string),
+                delivery: self.delivery.clone(),
+            },
+        })
+    }
+
+    fn gitlab_merge_request(&self, payload: &Value) -> Result<WebhookEvent, WebhookError> {
+        let action = required(payload, "/object_attributes/action")?;
+        if !matches!(action, "open" | "reopen" | "update") {
+            return Ok(ignored(format!("merge request action {action}")));
+        }
+        // Updates that only change the description or labels carry no new commits.
+        if action == "update" && payload.pointer("/object_attributes/oldrev").is_none() {
+            return Ok(ignored("merge request update without new commits".into()));
+        }
+        Ok(WebhookEvent::Trigger {
+            commit_sha: required(payload, "/object_attributes/last_commit/id")?.to_string(),
+            source: PipelineSource {
+                provider: self.provider,
+                kind: SourceEventKind::PullRequest,
+                repository: required(payload, "/project/path_with_namespace")?.to_string(),
+                branch: required(payload, "/object_attributes/source_branch")?.to_string(),
+                base_branch: optional(payload, "/object_attributes/target_branch")
+                    .map(str::to_string),
+                pull_request: payload
+                    .pointer("/object_attributes/iid")
+                    .and_then(Value::as_u64),
+                title: optional(payload, "/object_attributes/title").map(str::to_string),
+                author: optional(payload, "/user/username").map(str::to_string),
+                url: optional(payload, "/object_attributes/url").map(str::to_string),
+                delivery: self.delivery.clone(),
+            },
+        })
+    }
+}
+
+/// Pipeline name for a repository path: its last segment.
+pub fn pipeline_name(repository: &str) -> &str {
+    repository.rsplit('/').next().unwrap_or(repository)
+}
+
+fn ignored(reason: String) -> WebhookEvent {
+    WebhookEvent::Ignored { reason }
+}
+
+fn required<'a>(payload: &'a Value, pointer: &str) -> Result<&'a str, WebhookError> {
+    optional(payload, pointer)
+        .ok_or_else(|| WebhookError::InvalidPayload(format!("missing {pointer}")))
+}
+
+fn optional<'a>(payload: &'a Value, pointer: &str) -> Option<&'a str> {
+    payload.pointer(pointer).and_then(Value::as_str)
+}
+
+fn subject(message: &str) -> String {
+    message.lines().next().unwrap_or_default().to_string()
+}
+
+fn signer(secret: &str) -> Hmac<Sha256> {
+    Hmac::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn github(event: &str, secret: &str, body: &[u8]) -> WebhookDelivery {
+        let signature = format!(
+            "sha256={}",
+            hex::encode(signer(secret).chain_update(body).finalize().into_bytes())
+        );
+        let headers = [
+            ("x-github-event", event.to_string()),
+            ("x-hub-signature-256", signature),
+            ("x-github-delivery", "d-1".to_string()),
+        ];
+        WebhookDelivery::from_headers(|name| {
+            headers
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.as_str())
+        })
+        .expect("github delivery")
+    }
+
+    #[test]
+    fn github_signatures_match_rfc_4231_vector() {
+        let delivery = |credential: &str| WebhookDelivery {
+            provider: WebhookProvider::Github,
+            event: "push".into(),
+            credential: Some(credential.into()),
+            delivery: None,
+        };
+        let body = b"what do ya want for nothing?";
+        let signed =
+            delivery("sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        signed.verify("Jefe", body).unwrap();
+        assert!(signed
+            .verify("Jefe", b"what do ya want for something?")
+            .is_err());
+        assert!(delivery("sha256=not-hex").verify("Jefe", body).is_err());
+        assert!(
+            delivery("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+                .verify("Jefe", body)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn github_pushes_and_pull_requests_map_to_triggers() {
+        let push = json!({
+            "ref": "refs/heads/main",
+            "after": "abc123",
+            "deleted": false,
+            "compare": "https://github.com/FlexNetOS/noa_ark_os/compare/a...b",
+            "repository": { "full_name": "FlexNetOS/noa_ark_os" },
+            "head_commit": { "message": "Fix scheduler\n\nDetails" },
+            "pusher": { "name": "octocat" },
+        })
+        .to_string();
+        let delivery = github("push", "s3cret", push.as_bytes());
+        delivery.verify("s3cret", push.as_bytes()).unwrap();
+        assert_eq!(
+            delivery.verify("other", push.as_bytes()),
+            Err(WebhookError::Unauthorized("signature mismatch".into()))
+        );
+        let WebhookEvent::Trigger { commit_sha, source } = delivery.parse(push.as_bytes()).unwrap()
+        else {
+            panic!("push triggers");
+        };
+        assert_eq!(commit_sha, "abc123");
+        assert_eq!(source.branch, "main");
+        assert_eq!(source.title.as_deref(), Some("Fix scheduler"));
+        assert_eq!(source.delivery.as_deref(), Some("d-1"));
+        assert_eq!(pipeline_name(&source.repository), "noa_ark_os");
+
+        let pull = json!({
+            "action": "synchronize",
+            "repository": { "full_name": "FlexNetOS/noa_ark_os" },
+            "pull_request": {
+                "number": 42,
+                "title": "Add lanes",
+                "html_url": "https://github.com/FlexNetOS/noa_ark_os/pull/42",
+                "user": { "login": "octocat" },
+                "head": { "sha": "def456", "ref": "feature/lanes" },
+                "base": { "ref": "main" },
+            },
+        })
+        .to_string();
+        let event = github("pull_request", "s3cret", pull.as_bytes())
+            .parse(pull.as_bytes())
+            .unwrap();
+        let WebhookEvent::Trigger { commit_sha, source } = event else {
+            panic!("pull request triggers");
+        };
+        assert_eq!(commit_sha, "def456");
+        assert_eq!(source.kind, SourceEventKind::PullRequest);
+        assert_eq!(source.pull_request, Some(42));
+        assert_eq!(source.base_branch.as_deref(), Some("main"));
+
+        let closed = pull.replace("synchronize", "closed");
+        assert!(matches!(
+            github("pull_request", "s3cret", closed.as_bytes()).parse(closed.as_bytes()),
+            Ok(WebhookEvent::Ignored { .. })
+        ));
+    }
+
+    #[test]
+    fn gitlab_merge_requests_use_the_shared_token() {
+        let body = json!({
+            "user": { "username": "maintainer" },
+            "project": { "path_with_namespace": "noa/ark" },
+            "object_attributes": {
+                "action": "open",
+                "iid": 7,
+                "title": "Retention",
+                "url": "https://gitlab.example/noa/ark/-/merge_requests/7",
+                "source_branch": "retention",
+                "target_branch": "main",
+                "last_commit": { "id": "fed789" },
+            },
+        })
+        .to_string();
+        let delivery = WebhookDelivery::from_headers(|name| match name {
+            "x-gitlab-event" => Some("Merge Request Hook"),
+            "x-gitlab-token" => Some("token"),
+            _ => None,
+        })
+        .unwrap();
+        delivery.verify("token", body.as_bytes()).unwrap();
+        assert!(delivery.verify("other", body.as_bytes()).is_err());
+        let WebhookEvent::Trigger { commit_sha, source } = delivery.parse(body.as_bytes()).unwrap()
+        else {
+            panic!("merge request triggers");
+        };
+        assert_eq!(commit_sha, "fed789");
+        assert_eq!(source.provider, WebhookProvider::Gitlab);
+        assert_eq!(source.pull_request, Some(7));
+        assert_eq!(source.author.as_deref(), Some("maintainer"));
+    }
+}
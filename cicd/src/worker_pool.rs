@@ -0,0 +1,202 @@
+//! Asynchronous pipeline execution on a bounded pool of workers.
+//!
+//! [`PipelineWorkerPool::submit`] queues a pipeline and returns at once. A tokio task
+//! waits for one of the pool's `max_parallel` slots, then runs
+//! [`CICDSystem::execute_pipeline`] on the blocking thread pool, so stages keep their
+//! synchronous executors while several pipelines make progress at the same time.
+//! Slots are handed out in submission order. [`CICDSystem::get_pipeline_progress`]
+//! reports where each pipeline is, including its place in the queue.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+use crate::{CICDSystem, PipelineStatus};
+
+/// Point-in-time progress of a pipeline run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineProgress {
+    pub pipeline_id: String,
+    pub status: PipelineStatus,
+    /// Pipelines ahead of this one waiting for a worker; `None` unless it is queued.
+    pub queue_position: Option<usize>,
+    pub stages_total: usize,
+    pub stages_completed: usize,
+    /// Stage executing right now.
+    pub current_stage: Option<String>,
+    /// Share of stages completed, from 0.0 to 1.0.
+    pub fraction_complete: f64,
+    /// Average recorded duration of the stages still to run; `None` when one of them
+    /// has no history.
+    pub estimated_remaining_ms: Option<u64>,
+}
+
+/// Pipelines submitted to a worker pool and not finished yet.
+#[derive(Debug, Default)]
+pub(crate) struct RunQueue {
+    queued: VecDeque<String>,
+    running: HashSet<String>,
+}
+
+impl RunQueue {
+    pub(crate) fn enqueue(&mut self, pipeline_id: &str) -> Result<(), String> {
+        if self.running.contains(pipeline_id) || self.queued.iter().any(|id| id == pipeline_id) {
+            return Err(format!("Pipeline {} is already submitted", pipeline_id));
+        }
+        self.queued.push_back(pipeline_id.to_string());
+        Ok(())
+    }
+
+    pub(crate) fn start(&mut self, pipeline_id: &str) {
+        self.queued.retain(|id| id != pipeline_id);
+        self.running.insert(pipeline_id.to_string());
+    }
+
+    pub(crate) fn finish(&mut self, pipeline_id: &str) {
+        self.queued.retain(|id| id != pipeline_id);
+        self.running.remove(pipeline_id);
+    }
+
+    pub(crate) fn position(&self, pipeline_id: &str) -> Option<usize> {
+        self.queued.iter().position(|id| id == pipeline_id)
+    }
+}
+
+/// Runs submitted pipelines concurrently, at most `max_parallel` at a time.
+pub struct PipelineWorkerPool {
+    system: Arc<CICDSystem>,
+    slots: Arc<Semaphore>,
+    max_parallel: usize,
+}
+
+impl PipelineWorkerPool {
+    pub fn new(system: Arc<CICDSystem>, max_parallel: usize) -> Self {
+        let max_parallel = max_parallel.max(1);
+        Self {
+            system,
+            slots: Arc::new(Semaphore::new(max_parallel)),
+            max_parallel,
+        }
+    }
+
+    pub fn max_parallel(&self) -> usize {
+        self.max_parallel
+    }
+
+    /// Pipelines currently holding a worker.
+    pub fn running(&self) -> usize {
+        self.max_parallel - self.slots.available_permits()
+    }
+
+    /// Queue a pipeline for execution; the handle resolves to the run's result. Must be
+    /// called from within a tokio runtime.
+    pub fn submit(&self, pipeline_id: &str) -> Result<JoinHandle<Result<(), String>>, String> {
+        self.system.enqueue_run(pipeline_id)?;
+        let system = Arc::clone(&self.system);
+        let slots = Arc::clone(&self.slots);
+        let pipeline_id = pipeline_id.to_string();
+        Ok(tokio::spawn(async move {
+            let _slot = slots
+                .acquire_owned()
+                .await
+                .expect("worker pool semaphore is never closed");
+            system.start_run(&pipeline_id);
+            let worker = Arc::clone(&system);
+            let id = pipeline_id.clone();
+            let result = tokio::task::spawn_blocking(move || worker.execute_pipeline(&id))
+                .await
+                .unwrap_or_else(|err| Err(format!("pipeline worker failed: {err}")));
+            system.finish_run(&pipeline_id);
+            result
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stage_plugins::{StageContext, StageExecutor, StageReport};
+    use serde_json::Value;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Holds every stage until the test opens the gate.
+    struct Gate {
+        open: Mutex<bool>,
+        opened: std::sync::Condvar,
+    }
+
+    impl StageExecutor for Gate {
+        fn stage_type(&self) -> &str {
+            "gate"
+        }
+
+        fn execute(&self, _context: &StageContext) -> Result<StageReport, String> {
+            let open = self.open.lock().unwrap();
+            drop(self.opened.wait_while(open, |open| !*open).unwrap());
+            Ok(StageReport::succeeded("open", Value::Null))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn pool_limits_parallel_runs_and_reports_progress() {
+        let workspace = tempfile::tempdir().unwrap();
+        std::fs::write(
+            workspace.path().join("pipeline.yaml"),
+            "stages:\n  - name: wait\n    type: gate\n  - name: after\n    type: gate\n",
+        )
+        .unwrap();
+        let system = Arc::new(CICDSystem::with_context(crate::context_in(
+            workspace.path(),
+        )));
+        system.configure_workspace_root(workspace.path());
+        let gate = Arc::new(Gate {
+            open: Mutex::new(false),
+            opened: std::sync::Condvar::new(),
+        });
+        system.register_stage_executor(gate.clone()).unwrap();
+        let ids: Vec<String> = (0..3)
+            .map(|n| {
+                system
+                    .trigger_pipeline(format!("build-{n}"), "abc123".to_string())
+                    .unwrap()
+            })
+            .collect();
+
+        let pool = PipelineWorkerPool::new(Arc::clone(&system), 2);
+        let handles: Vec<_> = ids.iter().map(|id| pool.submit(id).unwrap()).collect();
+        assert!(pool.submit(&ids[0]).is_err());
+        let started = |id: &str| {
+            system
+                .get_pipeline_progress(id)
+                .is_some_and(|progress| progress.current_stage.is_some())
+        };
+        for _ in 0..200 {
+            if started(&ids[0]) && started(&ids[1]) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(pool.running(), 2);
+        let progress = system.get_pipeline_progress(&ids[0]).unwrap();
+        assert_eq!(progress.status, PipelineStatus::Running);
+        assert_eq!(progress.current_stage.as_deref(), Some("wait"));
+        assert_eq!(progress.stages_total, 2);
+        let waiting = system.get_pipeline_progress(&ids[2]).unwrap();
+        assert_eq!(waiting.queue_position, Some(0));
+        assert_eq!(waiting.stages_completed, 0);
+
+        *gate.open.lock().unwrap() = true;
+        gate.opened.notify_all();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+        let done = system.get_pipeline_progress(&ids[2]).unwrap();
+        assert_eq!(done.status, PipelineStatus::Success);
+        assert_eq!(done.queue_position, None);
+        assert_eq!(done.fraction_complete, 1.0);
+        assert_eq!(done.estimated_remaining_ms, Some(0));
+    }
+}
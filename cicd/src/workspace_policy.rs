@@ -0,0 +1,477 @@
+//! Workspace policy stage: fast static checks that run before anything builds.
+//!
+//! The policy (`workspace-policy.toml` at the workspace root unless the stage names
+//! another file) caps file sizes, forbids committed build artifacts, requires headers
+//! in newly added source files, and pins file kinds to directories. Only the files
+//! handed to [`check`] are inspected, so the same check serves as a pre-commit hook
+//! over staged files and as the first pipeline step over a diff or the whole tree.
+//! Patterns use CODEOWNERS semantics. Violations carry a [`PolicyFix`] where one is
+//! known; [`apply_fixes`] applies those that cannot lose work.
+
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::Command;
+
+use noa_symbol_graph::ownership::path_matches;
+use serde::{Deserialize, Serialize};
+
+/// Policy file relative to the workspace root, unless the stage names another.
+pub const WORKSPACE_POLICY_FILE: &str = "workspace-policy.toml";
+const DEFAULT_MAX_FILE_MB: u64 = 5;
+/// Leading lines searched for a required header.
+const HEADER_SCAN_LINES: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WorkspacePolicy {
+    /// Largest file that may be committed, in megabytes.
+    pub max_file_mb: u64,
+    /// Build outputs and caches that must never be committed.
+    pub generated: Vec<String>,
+    /// Paths exempt from every rule, e.g. vendored archives.
+    pub allow: Vec<String>,
+    pub headers: Vec<HeaderRule>,
+    pub layout: Vec<LayoutRule>,
+}
+
+impl Default for WorkspacePolicy {
+    fn default() -> Self {
+        Self {
+            max_file_mb: DEFAULT_MAX_FILE_MB,
+            generated: [
+                "target/",
+                "node_modules/",
+                "__pycache__/",
+                "*.pyc",
+                "*.o",
+                ".DS_Store",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+            allow: Vec::new(),
+            headers: Vec::new(),
+            layout: Vec::new(),
+        }
+    }
+}
+
+impl WorkspacePolicy {
+    /// Load a policy; a missing file is the default policy.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read workspace policy: {err}"))?;
+        toml::from_str(&raw).map_err(|err| format!("failed to parse workspace policy: {err}"))
+    }
+}
+
+/// New files matching `pattern` must contain `header` within their first lines.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeaderRule {
+    pub pattern: String,
+    pub header: String,
+}
+
+/// Files matching `pattern` must live under one of the `allowed` patterns.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LayoutRule {
+    pub pattern: String,
+    pub allowed: Vec<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyRule {
+    Oversized,
+    GeneratedArtifact,
+    MissingHeader,
+    Layout,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PolicyFix {
+    /// Stop tracking the file and add `ignore` to `.gitignore`.
+    Untrack { ignore: String },
+    /// Insert the required header at the top of the file.
+    PrependHeader { header: String },
+    /// Move the file to `to`. Only suggested; references may need updating.
+    Move { to: String },
+}
+
+impl PolicyFix {
+    /// Whether [`apply_fixes`] may apply this fix without review.
+    pub fn is_safe(&self) -> bool {
+        !matches!(self, PolicyFix::Move { .. })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PolicyViolation {
+    pub rule: PolicyRule,
+    /// Workspace-relative path.
+    pub path: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<PolicyFix>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PolicyReport {
+    pub checked: usize,
+    pub violations: Vec<PolicyViolation>,
+}
+
+impl PolicyReport {
+    pub fn is_clean(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// A file to check; header rules apply only to `added` files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyCandidate {
+    pub path: String,
+    pub added: bool,
+}
+
+impl PolicyCandidate {
+    pub fn existing(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            added: false,
+        }
+    }
+
+    pub fn added(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            added: true,
+        }
+    }
+}
+
+/// Check `candidates` under `root` against `policy`. Files that no longer exist
+/// are skipped.
+pub fn check(
+    root: &Path,
+    policy: &WorkspacePolicy,
+    candidates: &[PolicyCandidate],
+) -> PolicyReport {
+    let mut report = PolicyReport::default();
+    let max_bytes = policy.max_file_mb.saturating_mul(1024 * 1024);
+    for candidate in candidates {
+        let path = candidate.path.trim_start_matches("./");
+        if policy
+            .allow
+            .iter()
+            .any(|pattern| path_matches(pattern, path))
+        {
+            continue;
+        }
+        let Ok(metadata) = fs::metadata(root.join(path)) else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        report.checked += 1;
+
+        if let Some(pattern) = policy
+            .generated
+            .iter()
+            .find(|pattern| path_matches(pattern, path))
+        {
+            report.violations.push(PolicyViolation {
+                rule: PolicyRule::GeneratedArtifact,
+                path: path.to_string(),
+                message: format!("generated artifact matching '{pattern}' is committed"),
+                fix: Some(PolicyFix::Untrack {
+                    ignore: pattern.clone(),
+                }),
+            });
+            continue;
+        }
+
+        if metadata.len() > max_bytes {
+            report.violations.push(PolicyViolation {
+                rule: PolicyRule::Oversized,
+                path: path.to_string(),
+                message: format!(
+                    "{:.1} MB exceeds the {} MB limit",
+                    metadata.len() as f64 / (1024.0 * 1024.0),
+                    policy.max_file_mb
+                ),
+                fix: None,
+            });
+        }
+
+        if candidate.added {
+            for rule in policy
+                .headers
+                .iter()
+                .filter(|rule| path_matches(&rule.pattern, path))
+            {
+                if !has_header(&root.join(path), &rule.header) {
+                    report.violations.push(PolicyViolation {
+                        rule: PolicyRule::MissingHeader,
+                        path: path.to_string(),
+                        message: format!("new file is missing the header '{}'", rule.header),
+                        fix: Some(PolicyFix::PrependHeader {
+                            header: rule.header.clone(),
+                        }),
+                    });
+                }
+            }
+        }
+
+        for rule in policy.layout.iter().filter(|rule| {
+            path_matches(&rule.pattern, path)
+                && !rule
+                    .allowed
+                    .iter()
+                    .any(|allowed| path_matches(allowed, path))
+        }) {
+            let reason = rule
+                .reason
+                .clone()
+                .unwrap_or_else(|| format!("must live under {}", rule.allowed.join(", ")));
+            report.violations.push(PolicyViolation {
+                rule: PolicyRule::Layout,
+                path: path.to_string(),
+                message: format!("'{}' files {reason}", rule.pattern),
+                fix: suggested_move(path, &rule.allowed),
+            });
+        }
+    }
+    report
+}
+
+fn has_header(path: &Path, header: &str) -> bool {
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    BufReader::new(file)
+        .lines()
+        .take(HEADER_SCAN_LINES)
+        .map_while(Result::ok)
+        .any(|line| line.contains(header))
+}
+
+/// A move into the single plain directory a layout rule allows, if there is one.
+fn suggested_move(path: &str, allowed: &[String]) -> Option<PolicyFix> {
+    let [directory] = allowed else {
+        return None;
+    };
+    if directory.contains(['*', '?']) {
+        return None;
+    }
+    let file_name = Path::new(path).file_name()?.to_str()?;
+    Some(PolicyFix::Move {
+        to: format!("{}/{}", directory.trim_matches('/'), file_name),
+    })
+}
+
+/// Apply every safe fix in `report` under `root`, returning the paths changed.
+/// Untracked files are removed from the git index only; the working copy stays.
+pub fn apply_fixes(root: &Path, report: &PolicyReport) -> Result<Vec<String>, String> {
+    let mut changed = Vec::new();
+    for violation in &report.violations {
+        let Some(fix) = violation.fix.as_ref().filter(|fix| fix.is_safe()) else {
+            continue;
+        };
+        match fix {
+            PolicyFix::PrependHeader { header } => {
+                let path = root.join(&violation.path);
+                let content = fs::read_to_string(&path)
+                    .map_err(|err| format!("failed to read {}: {err}", violation.path))?;
+                fs::write(&path, format!("{header}\n{content}"))
+                    .map_err(|err| format!("failed to write {}: {err}", violation.path))?;
+            }
+            PolicyFix::Untrack { ignore } => {
+                ensure_ignored(root, ignore)?;
+                git(root, &["rm", "--cached", "--quiet", "--", &violation.path])?;
+            }
+            PolicyFix::Move { .. } => unreachable!("moves are never applied"),
+        }
+        changed.push(violation.path.clone());
+    }
+    Ok(changed)
+}
+
+fn ensure_ignored(root: &Path, pattern: &str) -> Result<(), String> {
+    let path = root.join(".gitignore");
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    if existing.lines().any(|line| line.trim() == pattern) {
+        return Ok(());
+    }
+    let separator = if existing.is_empty() || existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    fs::write(&path, format!("{existing}{separator}{pattern}\n"))
+        .map_err(|err| format!("failed to update .gitignore: {err}"))
+}
+
+/// Files staged for commit, with added files marked.
+pub fn staged_candidates(root: &Path) -> Result<Vec<PolicyCandidate>, String> {
+    Ok(parse_name_status(&git(
+        root,
+        &["diff", "--cached", "--name-status", "--diff-filter=ACMR"],
+    )?))
+}
+
+/// Files changed since `base`, with added files marked.
+pub fn changed_candidates(root: &Path, base: &str) -> Result<Vec<PolicyCandidate>, String> {
+    Ok(parse_name_status(&git(
+        root,
+        &["diff", "--name-status", "--diff-filter=ACMR", base],
+    )?))
+}
+
+/// Every tracked file; none count as added.
+pub fn tracked_candidates(root: &Path) -> Result<Vec<PolicyCandidate>, String> {
+    Ok(git(root, &["ls-files"])?
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(PolicyCandidate::existing)
+        .collect())
+}
+
+fn parse_name_status(output: &str) -> Vec<PolicyCandidate> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let status = fields.next()?;
+            // Renames and copies list the source first; the destination is what lands.
+            let path = fields.next_back()?;
+            Some(PolicyCandidate {
+                path: path.to_string(),
+                added: status.starts_with(['A', 'C']),
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn git(root: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|err| format!("failed to run git {}: {err}", args.join(" ")))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &[u8]) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn check_flags_each_rule_with_fixes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let policy: WorkspacePolicy = toml::from_str(
+            r#"
+            max_file_mb = 1
+            allow = ["vendor/**"]
+
+            [[headers]]
+            pattern = "*.rs"
+            header = "SPDX-License-Identifier: MIT"
+
+            [[layout]]
+            pattern = "*.sh"
+            allowed = ["scripts/"]
+            "#,
+        )
+        .unwrap();
+        assert!(policy.generated.contains(&"target/".to_string()));
+
+        write(root, "big.bin", &vec![0; 2 * 1024 * 1024]);
+        write(root, "vendor/big.tar", &vec![0; 2 * 1024 * 1024]);
+        write(root, "cicd/target/debug/out", b"");
+        write(root, "src/new.rs", b"fn main() {}\n");
+        write(root, "src/old.rs", b"fn old() {}\n");
+        write(root, "src/ok.rs", b"// SPDX-License-Identifier: MIT\n");
+        write(root, "tools/run.sh", b"#!/bin/sh\n");
+        write(root, "scripts/run.sh", b"#!/bin/sh\n");
+
+        let candidates = [
+            PolicyCandidate::existing("big.bin"),
+            PolicyCandidate::existing("vendor/big.tar"),
+            PolicyCandidate::added("cicd/target/debug/out"),
+            PolicyCandidate::added("src/new.rs"),
+            PolicyCandidate::existing("src/old.rs"),
+            PolicyCandidate::added("src/ok.rs"),
+            PolicyCandidate::added("tools/run.sh"),
+            PolicyCandidate::added("scripts/run.sh"),
+            PolicyCandidate::added("deleted.rs"),
+        ];
+        let report = check(root, &policy, &candidates);
+        assert_eq!(report.checked, 7);
+        let found: Vec<_> = report
+            .violations
+            .iter()
+            .map(|violation| (violation.rule, violation.path.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (PolicyRule::Oversized, "big.bin"),
+                (PolicyRule::GeneratedArtifact, "cicd/target/debug/out"),
+                (PolicyRule::MissingHeader, "src/new.rs"),
+                (PolicyRule::Layout, "tools/run.sh"),
+            ]
+        );
+        assert_eq!(
+            report.violations[3].fix,
+            Some(PolicyFix::Move {
+                to: "scripts/run.sh".to_string()
+            })
+        );
+
+        let header_only = PolicyReport {
+            checked: 1,
+            violations: vec![report.violations[2].clone(), report.violations[3].clone()],
+        };
+        assert_eq!(apply_fixes(root, &header_only).unwrap(), ["src/new.rs"]);
+        let fixed = fs::read_to_string(root.join("src/new.rs")).unwrap();
+        assert!(fixed.starts_with("SPDX-License-Identifier: MIT\nfn main()"));
+        assert!(root.join("tools/run.sh").exists());
+    }
+
+    #[test]
+    fn name_status_marks_added_and_renamed_destinations() {
+        let candidates =
+            parse_name_status("A\tsrc/new.rs\nM\tsrc/lib.rs\nR100\told.rs\tsrc/moved.rs\n");
+        assert_eq!(
+            candidates,
+            [
+                PolicyCandidate::added("src/new.rs"),
+                PolicyCandidate::existing("src/lib.rs"),
+                PolicyCandidate::existing("src/moved.rs"),
+            ]
+        );
+    }
+}
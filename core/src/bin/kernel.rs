@@ -1,9 +1,16 @@
 use std::sync::Arc;
 
+use noa_core::config::manifest::KernelManifest;
+use noa_core::events;
+use noa_core::flags;
+use noa_core::host_control::{supervise, ServiceHooks};
+use noa_core::scheduler::{self, JobPriority};
 use noa_core::scorekeeper::{api, Scorekeeper};
+use noa_core::token;
 
 #[tokio::main]
 async fn main() {
+    events::install_panic_hook();
     if let Err(err) = run().await {
         eprintln!("Kernel initialization failed: {err}");
     }
@@ -28,22 +35,36 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-async fn shutdown_signal() {
-    #[cfg(unix)]
-    {
-        use tokio::signal::unix::{signal, SignalKind};
-        let mut sigterm =
-            signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
-        let mut sigint = tokio::signal::unix::signal(SignalKind::interrupt())
-            .expect("failed to install SIGINT handler");
-        tokio::select! {
-            _ = sigterm.recv() => {}
-            _ = sigint.recv() => {}
+struct KernelHooks;
+
+impl ServiceHooks for KernelHooks {
+    /// Re-read the feature flags, then re-apply token policies from the manifest
+    /// named by `NOA_KERNEL_MANIFEST`.
+    fn reload(&self) -> Result<(), String> {
+        let changes = flags::global()
+            .reload("kernel")
+            .map_err(|err| err.to_string())?;
+        if !changes.is_empty() {
+            println!("Applied {} feature flag change(s)", changes.len());
         }
+        let Ok(path) = std::env::var("NOA_KERNEL_MANIFEST") else {
+            return Ok(());
+        };
+        let manifest = KernelManifest::load_from_yaml(&path).map_err(|err| err.to_string())?;
+        manifest.validate().map_err(|err| err.to_string())?;
+        token::configure_from_manifest(&manifest);
+        println!("Reloaded token policies from {path}");
+        Ok(())
+    }
+
+    fn drain(&self) {
+        scheduler::global().pause("shutdown", JobPriority::High);
     }
-    #[cfg(not(unix))]
-    {
-        let _ = tokio::signal::ctrl_c().await;
+}
+
+async fn shutdown_signal() {
+    if let Err(err) = supervise(&KernelHooks).await {
+        eprintln!("Service lifecycle handling failed: {err}");
     }
     println!("Shutting down kernel API");
 }
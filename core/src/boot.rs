@@ -0,0 +1,370 @@
+//! Boot-time instrumentation and startup budgets for core initialization.
+//!
+//! [`crate::init`] times every subsystem it brings up and compares the result
+//! against a [`BootBudget`] sized for the host's [`HostClassification`]; minimal
+//! hosts get the strictest budget. Non-critical work (workspace indexing, plus any
+//! task a caller registers such as a symbol graph refresh) can be moved to
+//! background threads once the critical path has already overrun its budget.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+
+use crate::hardware::HostClassification;
+
+/// Hosts with less memory than this are treated as [`HostClassification::Minimal`].
+const MINIMAL_HOST_MEMORY_BYTES: u64 = 6 * 1024 * 1024 * 1024;
+/// Environment switch enabling deferral of non-critical boot work.
+pub const DEFER_NON_CRITICAL_ENV: &str = "NOA_BOOT_DEFER_NON_CRITICAL";
+
+fn last_report_slot() -> &'static Mutex<Option<BootReport>> {
+    static LAST_REPORT: OnceLock<Mutex<Option<BootReport>>> = OnceLock::new();
+    LAST_REPORT.get_or_init(|| Mutex::new(None))
+}
+
+/// The report produced by the most recent call to [`crate::init`].
+pub fn last_boot_report() -> Option<BootReport> {
+    last_report_slot().lock().unwrap().clone()
+}
+
+pub(crate) fn store_report(report: BootReport) {
+    *last_report_slot().lock().unwrap() = Some(report);
+}
+
+/// Startup time allowed for a host tier, in milliseconds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BootBudget {
+    /// Limit for the whole boot, including non-critical work run inline.
+    pub total_ms: u64,
+    /// Limit applied to each subsystem without an override.
+    pub subsystem_ms: u64,
+    #[serde(default)]
+    pub overrides: BTreeMap<String, u64>,
+}
+
+impl BootBudget {
+    /// Defaults sized for the host tier.
+    pub fn for_host(classification: &HostClassification) -> Self {
+        let (total_ms, subsystem_ms) = match classification {
+            HostClassification::Minimal => (1_500, 250),
+            HostClassification::Standard => (4_000, 750),
+            HostClassification::Accelerated => (5_000, 1_000),
+        };
+        Self {
+            total_ms,
+            subsystem_ms,
+            overrides: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_override(mut self, subsystem: impl Into<String>, limit_ms: u64) -> Self {
+        self.overrides.insert(subsystem.into(), limit_ms);
+        self
+    }
+
+    pub fn limit_for(&self, subsystem: &str) -> u64 {
+        self.overrides
+            .get(subsystem)
+            .copied()
+            .unwrap_or(self.subsystem_ms)
+    }
+}
+
+/// Classify the current host from its physical memory. GPU probing is too slow to
+/// run on the boot path, so accelerated hosts are reported as standard.
+pub fn detect_host_classification() -> HostClassification {
+    let mut system = System::new();
+    system.refresh_memory();
+    classify_memory(system.total_memory())
+}
+
+pub(crate) fn classify_memory(total_bytes: u64) -> HostClassification {
+    if total_bytes < MINIMAL_HOST_MEMORY_BYTES {
+        HostClassification::Minimal
+    } else {
+        HostClassification::Standard
+    }
+}
+
+/// Measured start-up time of one subsystem.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubsystemTiming {
+    pub name: String,
+    pub elapsed_ms: f64,
+    pub limit_ms: u64,
+    pub critical: bool,
+    /// Set when the subsystem was handed to a background thread instead of timed.
+    pub deferred: bool,
+}
+
+impl SubsystemTiming {
+    pub fn over_budget(&self) -> bool {
+        !self.deferred && self.elapsed_ms > self.limit_ms as f64
+    }
+}
+
+/// Outcome of a boot: per-subsystem timings against the host budget.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootReport {
+    pub classification: HostClassification,
+    pub budget: BootBudget,
+    pub subsystems: Vec<SubsystemTiming>,
+    pub total_ms: f64,
+}
+
+impl BootReport {
+    pub fn over_budget(&self) -> bool {
+        self.total_ms > self.budget.total_ms as f64
+            || self.subsystems.iter().any(SubsystemTiming::over_budget)
+    }
+
+    pub fn deferred(&self) -> Vec<&str> {
+        self.subsystems
+            .iter()
+            .filter(|timing| timing.deferred)
+            .map(|timing| timing.name.as_str())
+            .collect()
+    }
+
+    /// Print the report alongside the rest of the boot output.
+    pub fn log(&self) {
+        println!(
+            "Boot report ({:?} host): {:.1}ms of {}ms budget",
+            self.classification, self.total_ms, self.budget.total_ms
+        );
+        for timing in &self.subsystems {
+            if timing.deferred {
+                println!("  {:<10} deferred to background", timing.name);
+            } else {
+                let marker = if timing.over_budget() {
+                    " OVER BUDGET"
+                } else {
+                    ""
+                };
+                println!(
+                    "  {:<10} {:>8.1}ms / {}ms{}",
+                    timing.name, timing.elapsed_ms, timing.limit_ms, marker
+                );
+            }
+        }
+    }
+}
+
+type BootTaskFn = Box<dyn FnOnce() -> Result<(), String> + Send>;
+
+/// Non-critical boot work that may run on a background thread.
+pub struct DeferrableTask {
+    name: String,
+    run: BootTaskFn,
+}
+
+impl DeferrableTask {
+    pub fn new(
+        name: impl Into<String>,
+        run: impl FnOnce() -> Result<(), String> + Send + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            run: Box::new(run),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// How [`crate::init_with_options`] sizes its budget and treats non-critical work.
+pub struct BootOptions {
+    pub classification: Option<HostClassification>,
+    pub budget: Option<BootBudget>,
+    /// Move non-critical tasks to background threads when the critical path overran.
+    pub defer_when_over_budget: bool,
+    pub(crate) extra_tasks: Vec<DeferrableTask>,
+}
+
+impl Default for BootOptions {
+    fn default() -> Self {
+        Self {
+            classification: None,
+            budget: None,
+            defer_when_over_budget: std::env::var(DEFER_NON_CRITICAL_ENV)
+                .map(|value| matches!(value.as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+            extra_tasks: Vec::new(),
+        }
+    }
+}
+
+impl BootOptions {
+    pub fn with_classification(mut self, classification: HostClassification) -> Self {
+        self.classification = Some(classification);
+        self
+    }
+
+    pub fn with_budget(mut self, budget: BootBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn with_deferral(mut self, defer: bool) -> Self {
+        self.defer_when_over_budget = defer;
+        self
+    }
+
+    /// Register extra non-critical work, e.g. a symbol graph refresh.
+    pub fn with_task(mut self, task: DeferrableTask) -> Self {
+        self.extra_tasks.push(task);
+        self
+    }
+}
+
+/// Times boot phases against a budget and builds the [`BootReport`].
+pub struct BootRecorder {
+    classification: HostClassification,
+    budget: BootBudget,
+    started: Instant,
+    subsystems: Vec<SubsystemTiming>,
+}
+
+impl BootRecorder {
+    pub fn new(classification: HostClassification, budget: BootBudget) -> Self {
+        Self {
+            classification,
+            budget,
+            started: Instant::now(),
+            subsystems: Vec::new(),
+        }
+    }
+
+    /// Run a critical subsystem and record how long it took.
+    pub fn time<T, E>(&mut self, name: &str, init: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+        self.measure(name, true, init)
+    }
+
+    fn measure<T, E>(
+        &mut self,
+        name: &str,
+        critical: bool,
+        init: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let started = Instant::now();
+        let result = init();
+        self.subsystems.push(SubsystemTiming {
+            name: name.to_string(),
+            elapsed_ms: as_millis(started.elapsed()),
+            limit_ms: self.budget.limit_for(name),
+            critical,
+            deferred: false,
+        });
+        result
+    }
+
+    /// Whether the boot so far has overrun the total or any per-subsystem limit.
+    pub fn over_budget(&self) -> bool {
+        as_millis(self.started.elapsed()) > self.budget.total_ms as f64
+            || self.subsystems.iter().any(SubsystemTiming::over_budget)
+    }
+
+    /// Run non-critical tasks inline, or on background threads when `defer` is set
+    /// and the critical path has already overrun. Inline failures abort the boot;
+    /// background failures are logged.
+    pub fn run_non_critical(
+        &mut self,
+        tasks: Vec<DeferrableTask>,
+        defer: bool,
+    ) -> Result<Vec<JoinHandle<()>>, String> {
+        let defer = defer && self.over_budget();
+        let mut handles = Vec::new();
+        for task in tasks {
+            if !defer {
+                self.measure(&task.name, false, task.run)?;
+                continue;
+            }
+            self.subsystems.push(SubsystemTiming {
+                limit_ms: self.budget.limit_for(&task.name),
+                name: task.name.clone(),
+                elapsed_ms: 0.0,
+                critical: false,
+                deferred: true,
+            });
+            let name = task.name;
+            let run = task.run;
+            handles.push(std::thread::spawn(move || {
+                if let Err(err) = run() {
+                    eprintln!("Deferred boot task {name} failed: {err}");
+                }
+            }));
+        }
+        Ok(handles)
+    }
+
+    pub fn finish(self) -> BootReport {
+        BootReport {
+            classification: self.classification,
+            budget: self.budget,
+            subsystems: self.subsystems,
+            total_ms: as_millis(self.started.elapsed()),
+        }
+    }
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn non_critical_tasks_defer_only_when_over_budget() {
+        let minimal = BootBudget::for_host(&HostClassification::Minimal);
+        let standard = BootBudget::for_host(&HostClassification::Standard);
+        assert!(minimal.total_ms < standard.total_ms);
+        assert!(minimal.subsystem_ms < standard.subsystem_ms);
+        assert_eq!(classify_memory(2 << 30), HostClassification::Minimal);
+
+        let mut within = BootRecorder::new(HostClassification::Standard, standard);
+        within.time("memory", || Ok::<_, String>(())).unwrap();
+        let handles = within
+            .run_non_critical(vec![DeferrableTask::new("indexer", || Ok(()))], true)
+            .unwrap();
+        assert!(handles.is_empty());
+        let report = within.finish();
+        assert!(!report.over_budget());
+        assert!(report.deferred().is_empty());
+
+        let tight = BootBudget::for_host(&HostClassification::Minimal).with_override("kernel", 0);
+        let mut over = BootRecorder::new(HostClassification::Minimal, tight);
+        over.time("kernel", || {
+            std::thread::sleep(Duration::from_millis(2));
+            Ok::<_, String>(())
+        })
+        .unwrap();
+        assert!(over.over_budget());
+
+        let (tx, rx) = mpsc::channel();
+        let handles = over
+            .run_non_critical(
+                vec![DeferrableTask::new("symbols", move || {
+                    tx.send(()).map_err(|err| err.to_string())
+                })],
+                true,
+            )
+            .unwrap();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        rx.recv().unwrap();
+
+        let report = over.finish();
+        assert!(report.over_budget());
+        assert_eq!(report.deferred(), vec!["symbols"]);
+    }
+}
@@ -6,15 +6,18 @@ use super::{
     CapabilityDefinition, CapabilityError, CapabilityRegistry, CapabilityResult, DynCapability,
 };
 use crate::config::manifest::{
-    CAPABILITY_FILESYSTEM, CAPABILITY_GATEWAY, CAPABILITY_IPC, CAPABILITY_MEMORY,
-    CAPABILITY_PROCESS, CAPABILITY_RUNTIME_MANAGER, CAPABILITY_SECURITY,
+    CAPABILITY_FEATURE_FLAGS, CAPABILITY_FILESYSTEM, CAPABILITY_GATEWAY, CAPABILITY_IPC,
+    CAPABILITY_MEMORY, CAPABILITY_PROCESS, CAPABILITY_RUNTIME_MANAGER, CAPABILITY_SCHEDULER,
+    CAPABILITY_SECURITY,
 };
+use crate::flags::FlagsCapability;
 use crate::fs::FileSystemService;
 use crate::gateway::Gateway;
 use crate::ipc::IpcService;
 use crate::memory::MemoryManager;
 use crate::process::ProcessService;
 use crate::runtime::RuntimeManager;
+use crate::scheduler::BackgroundScheduler;
 use crate::security::SecurityService;
 
 fn wrap_init_error(id: &str, err: impl ToString) -> CapabilityError {
@@ -100,5 +103,25 @@ pub fn register_default_capabilities(registry: &CapabilityRegistry) -> Capabilit
             .build(),
     )?;
 
+    registry.register_definition(
+        CapabilityDefinition::builder(CAPABILITY_SCHEDULER)
+            .description("Background job scheduler")
+            .init_with(|_| {
+                crate::scheduler::init()
+                    .map_err(|err| wrap_init_error(CAPABILITY_SCHEDULER, err))?;
+                let scheduler: BackgroundScheduler = crate::scheduler::global().clone();
+                Ok(Arc::new(scheduler) as DynCapability)
+            })
+            .build(),
+    )?;
+
+    registry.register_definition(
+        CapabilityDefinition::builder(CAPABILITY_FEATURE_FLAGS)
+            .description("Feature flags with targeting rules")
+            .depends_on([CAPABILITY_SECURITY])
+            .init_with(|_| Ok(Arc::new(FlagsCapability) as DynCapability))
+            .build(),
+    )?;
+
     Ok(())
 }
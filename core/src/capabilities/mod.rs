@@ -2,13 +2,16 @@
 //!
 //! The capability system exposes every core subsystem as a kernel-managed
 //! resource. Higher-level modules can request these resources dynamically
-//! instead of importing concrete implementations directly.
+//! instead of importing concrete implementations directly. Lifecycle changes
+//! are published on the registry's [`KernelEventBus`].
 
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
 
 use crate::config::manifest::{CapabilityManifestEntry, KernelManifest};
+use crate::events::{KernelEventBus, KernelEventKind};
 
 /// Result alias for capability operations.
 pub type CapabilityResult<T> = Result<T, CapabilityError>;
@@ -41,7 +44,7 @@ pub enum CapabilityError {
 
 /// Lifecycle states tracked for each capability.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum CapabilityState {
+pub enum CapabilityState {
     Registered,
     Initializing,
     Ready,
@@ -175,17 +178,29 @@ impl CapabilityContext {
 pub struct CapabilityRegistry {
     entries: RwLock<HashMap<String, RegisteredCapability>>,
     init_order: Mutex<Vec<String>>,
+    events: KernelEventBus,
 }
 
 impl CapabilityRegistry {
-    /// Create an empty registry.
+    /// Create an empty registry publishing on a bus of its own.
     pub fn new() -> Self {
+        Self::with_events(KernelEventBus::new())
+    }
+
+    /// Create an empty registry publishing lifecycle events on `events`.
+    pub fn with_events(events: KernelEventBus) -> Self {
         Self {
             entries: RwLock::new(HashMap::new()),
             init_order: Mutex::new(Vec::new()),
+            events,
         }
     }
 
+    /// Bus receiving this registry's lifecycle events.
+    pub fn events(&self) -> &KernelEventBus {
+        &self.events
+    }
+
     /// Register a new capability definition.
     pub fn register_definition(&self, definition: CapabilityDefinition) -> CapabilityResult<()> {
         let mut entries = self.entries.write().unwrap();
@@ -194,13 +209,35 @@ impl CapabilityRegistry {
             return Err(CapabilityError::AlreadyRegistered(id));
         }
         entries.insert(
-            id,
+            id.clone(),
             RegisteredCapability {
                 definition: Arc::new(definition),
                 state: CapabilityState::Registered,
                 instance: None,
             },
         );
+        drop(entries);
+        self.events
+            .publish(KernelEventKind::CapabilityRegistered { capability: id });
+        Ok(())
+    }
+
+    /// Swap the definition of a registered capability.
+    ///
+    /// The previous instance is dropped without running its shutdown hook and the
+    /// replacement initializes on next use, even if the previous one had failed.
+    pub fn replace_definition(&self, definition: CapabilityDefinition) -> CapabilityResult<()> {
+        let mut entries = self.entries.write().unwrap();
+        let id = definition.id().to_string();
+        let entry = entries
+            .get_mut(&id)
+            .ok_or_else(|| CapabilityError::UnknownCapability(id.clone()))?;
+        entry.definition = Arc::new(definition);
+        entry.state = CapabilityState::Registered;
+        entry.instance = None;
+        drop(entries);
+        self.events
+            .publish(KernelEventKind::CapabilityReplaced { capability: id });
         Ok(())
     }
 
@@ -239,24 +276,26 @@ impl CapabilityRegistry {
         }
 
         for dependency in all_dependencies {
-            self.ensure_initialized(&dependency, kernel)?;
+            if let Err(err) = self.ensure_initialized(&dependency, kernel) {
+                self.mark_failed(id, format!("dependency {dependency} unavailable: {err}"));
+                return Err(err);
+            }
         }
 
         let context = CapabilityContext::new(kernel.clone(), definition.id.clone());
+        let started = Instant::now();
         let instance = match (definition.initializer)(&context) {
             Ok(instance) => instance,
             Err(err) => {
-                let mut entries = self.entries.write().unwrap();
-                if let Some(entry) = entries.get_mut(id) {
-                    entry.state = CapabilityState::Failed;
-                }
                 let message = err.to_string();
+                self.mark_failed(id, message.clone());
                 return Err(CapabilityError::InitializationFailed(
                     id.to_string(),
                     message,
                 ));
             }
         };
+        let duration_ms = started.elapsed().as_millis() as u64;
 
         {
             let mut entries = self.entries.write().unwrap();
@@ -273,9 +312,23 @@ impl CapabilityRegistry {
             }
         }
 
+        self.events.publish(KernelEventKind::CapabilityInitialized {
+            capability: id.to_string(),
+            duration_ms,
+        });
         Ok(())
     }
 
+    fn mark_failed(&self, id: &str, error: String) {
+        if let Some(entry) = self.entries.write().unwrap().get_mut(id) {
+            entry.state = CapabilityState::Failed;
+        }
+        self.events.publish(KernelEventKind::CapabilityFailed {
+            capability: id.to_string(),
+            error,
+        });
+    }
+
     /// Retrieve an initialized capability instance.
     pub fn instance(&self, id: &str) -> CapabilityResult<DynCapability> {
         let entries = self.entries.read().unwrap();
@@ -289,6 +342,15 @@ impl CapabilityRegistry {
             .ok_or_else(|| CapabilityError::NotInitialized(id.to_string()))
     }
 
+    /// Current lifecycle state of every registered capability, keyed by id.
+    pub fn states(&self) -> BTreeMap<String, CapabilityState> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .iter()
+            .map(|(id, entry)| (id.clone(), entry.state))
+            .collect()
+    }
+
     /// Shut down all capabilities in reverse initialization order.
     pub fn shutdown_all(&self, kernel: &KernelHandle) -> CapabilityResult<()> {
         let order = self.init_order.lock().unwrap().clone();
@@ -356,10 +418,16 @@ impl CapabilityRegistry {
                 instance: None,
             },
         );
-        Err(CapabilityError::ManifestError(format!(
+        drop(entries);
+        let error = format!(
             "no provider registered for capability {}",
             manifest_entry.id
-        )))
+        );
+        self.events.publish(KernelEventKind::CapabilityFailed {
+            capability: manifest_entry.id.clone(),
+            error: error.clone(),
+        });
+        Err(CapabilityError::ManifestError(error))
     }
 }
 
@@ -0,0 +1,617 @@
+//! Baseline generation for the single-host deployment profile.
+//!
+//! [`HostInventory::detect`] inspects the current host: its hardware profile,
+//! the runtimes answering `--version`, and which of the profile's ports are
+//! free. [`SingleHostProfile::refresh`] folds an inventory into the profile
+//! manifest (`server/profiles/single_host/profile.toml`): resource and budget
+//! limits follow the hardware, `[tools].allowed` follows the installed
+//! runtimes, busy ports move to the next free one, and `[runtime_graph]` lists
+//! the kernel runtime graph services the host class can run. Sections the
+//! profiler does not own are kept as they are.
+//!
+//! [`SingleHostProfile::validate`] checks a manifest against the runtime graph;
+//! the CI/CD single-host acceptance stage runs it on every pipeline.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::Command;
+
+use thiserror::Error;
+use toml::{Table, Value};
+
+use crate::boot::classify_memory;
+use crate::hardware::{detect_hardware_profile, HardwareProfile, HostClassification};
+use crate::host_control::{HostControlError, RuntimeGraph};
+
+/// Manifest location relative to the workspace root.
+pub const DEFAULT_SINGLE_HOST_PROFILE: &str = "server/profiles/single_host/profile.toml";
+
+/// Runtimes probed with `<name> --version` when building an inventory.
+pub const PROBED_RUNTIMES: &[&str] = &[
+    "bash", "cargo", "docker", "git", "go", "node", "python3", "rustc",
+];
+
+/// Ports tried after a busy one before the profiler gives up on it.
+pub const PORT_SEARCH_SPAN: u16 = 16;
+
+/// Ports the profiler manages, as (section path, key) of `host:port` or URL values.
+const PORT_FIELDS: &[(&[&str], &str)] = &[
+    (&["services", "api_gateway"], "socket"),
+    (&["profile", "runtime"], "metrics_endpoint"),
+];
+
+#[derive(Debug, Error)]
+pub enum HostProfileError {
+    #[error("failed to read or write profile: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse profile: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("failed to serialize profile: {0}")]
+    Serialize(#[from] toml::ser::Error),
+    #[error(transparent)]
+    Graph(#[from] HostControlError),
+    #[error("profile does not match the runtime graph: {}", .0.join("; "))]
+    Invalid(Vec<String>),
+}
+
+/// What the profiler found on the host.
+#[derive(Debug, Clone)]
+pub struct HostInventory {
+    pub hardware: HardwareProfile,
+    pub classification: HostClassification,
+    /// Installed runtimes and the first line of their `--version` output.
+    pub runtimes: BTreeMap<String, String>,
+    /// Probed ports that could be bound on the loopback interface.
+    pub available_ports: BTreeSet<u16>,
+}
+
+impl HostInventory {
+    /// Inspect the current host, probing each of `ports` and the
+    /// [`PORT_SEARCH_SPAN`] ports after it.
+    pub fn detect(ports: &[u16]) -> Self {
+        let hardware = detect_hardware_profile();
+        let classification = classify_hardware(&hardware);
+        let runtimes = PROBED_RUNTIMES
+            .iter()
+            .filter_map(|name| runtime_version(name).map(|version| (name.to_string(), version)))
+            .collect();
+        let available_ports = ports
+            .iter()
+            .flat_map(|port| *port..=port.saturating_add(PORT_SEARCH_SPAN))
+            .filter(|port| TcpListener::bind(("127.0.0.1", *port)).is_ok())
+            .collect();
+        Self {
+            hardware,
+            classification,
+            runtimes,
+            available_ports,
+        }
+    }
+
+    /// `preferred` if it is free, otherwise the first free port after it.
+    fn free_port(&self, preferred: u16) -> Option<u16> {
+        (preferred..=preferred.saturating_add(PORT_SEARCH_SPAN))
+            .find(|port| self.available_ports.contains(port))
+    }
+}
+
+/// Host class from detected hardware; any GPU makes the host accelerated.
+pub fn classify_hardware(hardware: &HardwareProfile) -> HostClassification {
+    if hardware.has_gpu() {
+        HostClassification::Accelerated
+    } else {
+        classify_memory(hardware.memory.total_bytes)
+    }
+}
+
+fn runtime_version(name: &str) -> Option<String> {
+    let output = Command::new(name).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = if output.stdout.is_empty() {
+        output.stderr
+    } else {
+        output.stdout
+    };
+    Some(
+        String::from_utf8_lossy(&text)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    )
+}
+
+/// The single-host profile manifest as an editable TOML document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SingleHostProfile {
+    document: Table,
+}
+
+impl SingleHostProfile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, HostProfileError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(raw: &str) -> Result<Self, HostProfileError> {
+        Ok(Self {
+            document: raw.parse::<Table>()?,
+        })
+    }
+
+    /// Minimal manifest used when no profile exists yet.
+    pub fn baseline() -> Self {
+        let mut profile = Self {
+            document: Table::new(),
+        };
+        let metadata = profile.section(&["profile"]);
+        metadata.insert("name".into(), "single_host".into());
+        metadata.insert(
+            "description".into(),
+            "Co-locate gateway, MCP server, workflow orchestrator, and sandbox controllers on a single kernel-managed host".into(),
+        );
+        metadata.insert("version".into(), "0.1.0".into());
+        profile
+            .section(&["services", "api_gateway"])
+            .insert("socket".into(), "0.0.0.0:8443".into());
+        profile.section(&["profile", "runtime"]).insert(
+            "metrics_endpoint".into(),
+            "http://127.0.0.1:9310/metrics".into(),
+        );
+        profile
+    }
+
+    pub fn document(&self) -> &Table {
+        &self.document
+    }
+
+    /// Ports currently assigned to the fields the profiler manages.
+    pub fn ports(&self) -> Vec<u16> {
+        PORT_FIELDS
+            .iter()
+            .filter_map(|(section, key)| self.lookup(section, key)?.as_str().and_then(port_of))
+            .collect()
+    }
+
+    /// Update the host-derived sections from `inventory`. Only services whose
+    /// class requirements and hard dependencies the host meets are enabled.
+    pub fn refresh(&mut self, inventory: &HostInventory, graph: &RuntimeGraph) {
+        let cores = inventory.hardware.cpu.logical_cores.max(1) as i64;
+        let memory_mb = (inventory.hardware.memory.total_bytes / (1024 * 1024)) as i64;
+        let soft_mb = memory_mb * 7 / 8;
+        let reserved = (cores / 8).max(1);
+
+        let host = self.section(&["host"]);
+        host.insert(
+            "classification".into(),
+            classification_name(&inventory.classification).into(),
+        );
+        host.insert("logical_cores".into(), cores.into());
+        host.insert("memory_mb".into(), memory_mb.into());
+        host.insert("gpus".into(), (inventory.hardware.gpus.len() as i64).into());
+        host.insert(
+            "runtimes".into(),
+            Value::Table(
+                inventory
+                    .runtimes
+                    .iter()
+                    .map(|(name, version)| (name.clone(), version.clone().into()))
+                    .collect(),
+            ),
+        );
+
+        let cpu = self.section(&["resources", "cpu"]);
+        cpu.insert("reserved_cores".into(), reserved.into());
+        cpu.insert("max_cores".into(), cores.into());
+        cpu.insert("burst_cores".into(), (cores / 4).max(1).into());
+        let memory = self.section(&["resources", "memory"]);
+        memory.insert("limit_mb".into(), memory_mb.into());
+        memory.insert("soft_limit_mb".into(), soft_mb.into());
+        let budget_cpu = self.section(&["budgets", "cpu"]);
+        budget_cpu.insert("reserved_cores".into(), reserved.into());
+        budget_cpu.insert("max_cores".into(), cores.into());
+        let budget_memory = self.section(&["budgets", "memory"]);
+        budget_memory.insert("soft_mb".into(), soft_mb.into());
+        budget_memory.insert("hard_mb".into(), memory_mb.into());
+        self.fit_service_quotas(soft_mb);
+
+        let denied = self.strings(&["tools"], "denied");
+        let allowed: Vec<Value> = inventory
+            .runtimes
+            .keys()
+            .filter(|name| !denied.contains(*name))
+            .map(|name| name.clone().into())
+            .collect();
+        self.section(&["tools"])
+            .insert("allowed".into(), Value::Array(allowed));
+
+        for (section, key) in PORT_FIELDS {
+            let Some(current) = self.lookup(section, key).and_then(Value::as_str) else {
+                continue;
+            };
+            let Some(port) = port_of(current) else {
+                continue;
+            };
+            if let Some(free) = inventory.free_port(port).filter(|free| *free != port) {
+                let moved = replace_port(current, port, free);
+                self.section(section).insert(key.to_string(), moved.into());
+            }
+        }
+
+        let (enabled, excluded) = plan_services(graph, &inventory.classification);
+        let runtime_graph = self.section(&["runtime_graph"]);
+        runtime_graph.insert("services".into(), strings_value(enabled));
+        runtime_graph.insert("excluded".into(), strings_value(excluded));
+    }
+
+    /// Problems that keep the profile from running the runtime graph on its host.
+    pub fn validate(&self, graph: &RuntimeGraph) -> Vec<String> {
+        let mut issues = Vec::new();
+        if self.lookup(&["profile"], "name").and_then(Value::as_str) != Some("single_host") {
+            issues.push("profile.name must be \"single_host\"".to_string());
+        }
+
+        let classification = match self.lookup(&["host"], "classification") {
+            Some(value) => match value.as_str().and_then(parse_classification) {
+                Some(classification) => classification,
+                None => {
+                    issues.push(format!("unknown host.classification {value}"));
+                    HostClassification::default()
+                }
+            },
+            None => HostClassification::default(),
+        };
+
+        let services = self.strings(&["runtime_graph"], "services");
+        if services.is_empty() {
+            issues.push(
+                "runtime_graph.services is empty; regenerate the profile on the target host"
+                    .to_string(),
+            );
+        }
+        for id in &services {
+            let Some(service) = graph.service(id) else {
+                issues.push(format!("service {id} is not in the runtime graph"));
+                continue;
+            };
+            if !service.supports(&classification) {
+                issues.push(format!(
+                    "service {id} does not support {} hosts",
+                    classification_name(&classification)
+                ));
+            }
+            for dependency in &service.requires {
+                if !services.contains(dependency) {
+                    issues.push(format!(
+                        "service {id} requires {dependency}, which is not enabled"
+                    ));
+                }
+            }
+        }
+
+        let limit_mb = self.integer(&["resources", "memory"], "limit_mb");
+        let quotas: i64 = self
+            .lookup(&[], "services")
+            .and_then(Value::as_table)
+            .map(|services| services.values().filter_map(memory_quota).sum())
+            .unwrap_or_default();
+        if let Some(limit_mb) = limit_mb {
+            if quotas > limit_mb {
+                issues.push(format!(
+                    "service memory quotas ({quotas} MiB) exceed resources.memory.limit_mb ({limit_mb} MiB)"
+                ));
+            }
+        }
+        if let (Some(max), Some(cores)) = (
+            self.integer(&["resources", "cpu"], "max_cores"),
+            self.integer(&["host"], "logical_cores"),
+        ) {
+            if max > cores {
+                issues.push(format!(
+                    "resources.cpu.max_cores ({max}) exceeds the host's {cores} cores"
+                ));
+            }
+        }
+        if let (Some(reserved), Some(max)) = (
+            self.integer(&["budgets", "cpu"], "reserved_cores"),
+            self.integer(&["budgets", "cpu"], "max_cores"),
+        ) {
+            if reserved > max {
+                issues.push(format!(
+                    "budgets.cpu.reserved_cores ({reserved}) exceeds budgets.cpu.max_cores ({max})"
+                ));
+            }
+        }
+
+        let ports = self.ports();
+        let unique: BTreeSet<_> = ports.iter().collect();
+        if unique.len() != ports.len() {
+            issues.push(format!("profile assigns the same port twice: {ports:?}"));
+        }
+        issues
+    }
+
+    /// Regenerate the manifest at `path` for the current host and write it back
+    /// once it validates against `graph`.
+    pub fn generate(
+        path: impl AsRef<Path>,
+        graph: &RuntimeGraph,
+    ) -> Result<(Self, HostInventory), HostProfileError> {
+        let path = path.as_ref();
+        let mut profile = if path.exists() {
+            Self::load(path)?
+        } else {
+            Self::baseline()
+        };
+        let inventory = HostInventory::detect(&profile.ports());
+        profile.refresh(&inventory, graph);
+        let issues = profile.validate(graph);
+        if !issues.is_empty() {
+            return Err(HostProfileError::Invalid(issues));
+        }
+        profile.save(path)?;
+        Ok((profile, inventory))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), HostProfileError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_toml_string()?)?;
+        Ok(())
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, HostProfileError> {
+        Ok(toml::to_string_pretty(&self.document)?)
+    }
+
+    /// Shrink service memory quotas proportionally when together they exceed `limit_mb`.
+    fn fit_service_quotas(&mut self, limit_mb: i64) {
+        let Some(services) = self
+            .document
+            .get_mut("services")
+            .and_then(Value::as_table_mut)
+        else {
+            return;
+        };
+        let total: i64 = services.values().filter_map(memory_quota).sum();
+        if total <= limit_mb || total == 0 {
+            return;
+        }
+        for (_, service) in services.iter_mut() {
+            if let Some(quota) = service
+                .get_mut("quota")
+                .and_then(Value::as_table_mut)
+                .and_then(|quota| quota.get_mut("memory_mb"))
+            {
+                if let Some(current) = quota.as_integer() {
+                    *quota = Value::Integer(current * limit_mb / total);
+                }
+            }
+        }
+    }
+
+    fn lookup(&self, section: &[&str], key: &str) -> Option<&Value> {
+        let mut table = &self.document;
+        for name in section {
+            table = table.get(*name)?.as_table()?;
+        }
+        table.get(key)
+    }
+
+    fn integer(&self, section: &[&str], key: &str) -> Option<i64> {
+        self.lookup(section, key).and_then(Value::as_integer)
+    }
+
+    fn strings(&self, section: &[&str], key: &str) -> Vec<String> {
+        self.lookup(section, key)
+            .and_then(Value::as_array)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Table at `path`, created (replacing non-table values) where missing.
+    fn section(&mut self, path: &[&str]) -> &mut Table {
+        let mut table = &mut self.document;
+        for name in path {
+            let entry = table
+                .entry(name.to_string())
+                .or_insert_with(|| Value::Table(Table::new()));
+            if !entry.is_table() {
+                *entry = Value::Table(Table::new());
+            }
+            table = entry.as_table_mut().expect("entry is a table");
+        }
+        table
+    }
+}
+
+/// Graph services in boot order, split into those the host can run and the rest.
+fn plan_services(
+    graph: &RuntimeGraph,
+    classification: &HostClassification,
+) -> (Vec<String>, Vec<String>) {
+    let mut enabled: Vec<String> = Vec::new();
+    let mut excluded = Vec::new();
+    for id in &graph.boot_order {
+        let runnable = graph.service(id).is_some_and(|service| {
+            service.supports(classification)
+                && service
+                    .requires
+                    .iter()
+                    .all(|dependency| enabled.contains(dependency))
+        });
+        if runnable {
+            enabled.push(id.clone());
+        } else {
+            excluded.push(id.clone());
+        }
+    }
+    (enabled, excluded)
+}
+
+fn memory_quota(service: &Value) -> Option<i64> {
+    service.get("quota")?.get("memory_mb")?.as_integer()
+}
+
+fn strings_value(values: Vec<String>) -> Value {
+    Value::Array(values.into_iter().map(Value::from).collect())
+}
+
+/// Port of a `host:port` socket or an `http://host:port/...` URL.
+fn port_of(address: &str) -> Option<u16> {
+    let rest = address
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(address);
+    let authority = rest.split('/').next()?;
+    authority.rsplit_once(':')?.1.parse().ok()
+}
+
+fn replace_port(address: &str, from: u16, to: u16) -> String {
+    address.replacen(&format!(":{from}"), &format!(":{to}"), 1)
+}
+
+fn classification_name(classification: &HostClassification) -> &'static str {
+    match classification {
+        HostClassification::Minimal => "minimal",
+        HostClassification::Standard => "standard",
+        HostClassification::Accelerated => "accelerated",
+    }
+}
+
+fn parse_classification(name: &str) -> Option<HostClassification> {
+    match name {
+        "minimal" => Some(HostClassification::Minimal),
+        "standard" => Some(HostClassification::Standard),
+        "accelerated" => Some(HostClassification::Accelerated),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::{CpuProfile, MemoryProfile};
+    use std::path::PathBuf;
+
+    fn graph() -> RuntimeGraph {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../runtime/kernel/graph.yaml");
+        RuntimeGraph::load_from_path(path).expect("runtime graph fixture loads")
+    }
+
+    fn inventory(classification: HostClassification, free: &[u16]) -> HostInventory {
+        HostInventory {
+            hardware: HardwareProfile {
+                cpu: CpuProfile {
+                    brand: "test".into(),
+                    vendor: "test".into(),
+                    physical_cores: 2,
+                    logical_cores: 4,
+                    frequency_mhz: None,
+                },
+                memory: MemoryProfile {
+                    total_bytes: 8 * 1024 * 1024 * 1024,
+                    available_bytes: 4 * 1024 * 1024 * 1024,
+                },
+                gpus: Vec::new(),
+                accelerators: Vec::new(),
+            },
+            classification,
+            runtimes: [("cargo", "cargo 1.80.0"), ("docker", "Docker 26.1")]
+                .into_iter()
+                .map(|(name, version)| (name.to_string(), version.to_string()))
+                .collect(),
+            available_ports: free.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn committed_profile_matches_the_runtime_graph() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(DEFAULT_SINGLE_HOST_PROFILE);
+        let profile = SingleHostProfile::load(path).unwrap();
+        assert_eq!(profile.validate(&graph()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn refresh_sizes_the_profile_for_the_host() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(DEFAULT_SINGLE_HOST_PROFILE);
+        let mut profile = SingleHostProfile::load(path).unwrap();
+        // 8443 is taken; the gateway moves to the next free port.
+        profile.refresh(
+            &inventory(HostClassification::Minimal, &[8445, 9310]),
+            &graph(),
+        );
+
+        assert!(profile.validate(&graph()).is_empty());
+        assert_eq!(profile.integer(&["resources", "cpu"], "max_cores"), Some(4));
+        assert_eq!(
+            profile.integer(&["budgets", "memory"], "hard_mb"),
+            Some(8192)
+        );
+        let quotas: i64 = profile.document()["services"]
+            .as_table()
+            .unwrap()
+            .values()
+            .filter_map(memory_quota)
+            .sum();
+        assert!(quotas <= 7168);
+        // docker stays denied even though it is installed.
+        assert_eq!(profile.strings(&["tools"], "allowed"), vec!["cargo"]);
+        assert_eq!(profile.ports(), vec![8445, 9310]);
+
+        let services = profile.strings(&["runtime_graph"], "services");
+        assert_eq!(
+            services,
+            vec!["kernel", "storage", "observability", "runtime-manager"]
+        );
+        assert!(profile
+            .strings(&["runtime_graph"], "excluded")
+            .contains(&"gateway".to_string()));
+
+        let reparsed = SingleHostProfile::parse(&profile.to_toml_string().unwrap()).unwrap();
+        assert_eq!(reparsed, profile);
+    }
+
+    #[test]
+    fn validation_flags_services_the_host_cannot_run() {
+        let mut profile = SingleHostProfile::baseline();
+        profile.refresh(
+            &inventory(HostClassification::Standard, &[8443, 9310]),
+            &graph(),
+        );
+        assert!(profile.validate(&graph()).is_empty());
+
+        profile
+            .section(&["host"])
+            .insert("classification".into(), "minimal".into());
+        profile
+            .section(&["runtime_graph"])
+            .insert("services".into(), strings_value(vec!["ui-api".into()]));
+        let issues = profile.validate(&graph());
+        assert_eq!(
+            issues,
+            vec!["service ui-api requires gateway, which is not enabled"]
+        );
+
+        profile
+            .section(&["runtime_graph"])
+            .insert("services".into(), strings_value(vec!["gateway".into()]));
+        let issues = profile.validate(&graph());
+        assert!(issues.contains(&"service gateway does not support minimal hosts".to_string()));
+    }
+}
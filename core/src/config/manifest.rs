@@ -21,12 +21,20 @@ pub const CAPABILITY_SECURITY: &str = "core.security";
 pub const CAPABILITY_GATEWAY: &str = "core.gateway";
 /// Capability identifier for the runtime manager.
 pub const CAPABILITY_RUNTIME_MANAGER: &str = "core.runtime.manager";
+/// Capability identifier for the background job scheduler.
+pub const CAPABILITY_SCHEDULER: &str = "core.scheduler";
+/// Capability identifier for the feature flag service.
+pub const CAPABILITY_FEATURE_FLAGS: &str = "core.flags";
 /// Capability identifier for the agent factory subsystem.
 pub const CAPABILITY_AGENT_FACTORY: &str = "agents.factory";
 /// Scope granting host environment takeover privileges.
 pub const SCOPE_HOST_ENVIRONMENT_TAKEOVER: &str = "host.environment.takeover";
 /// Scope granting host resource arbitration privileges.
 pub const SCOPE_HOST_RESOURCE_ARBITRATE: &str = "host.resource.arbitrate";
+/// Scope granting installation of the NOA stack as system services.
+pub const SCOPE_HOST_SERVICE_INSTALL: &str = "host.service.install";
+/// Scope granting self-update and rollback of the NOA binaries.
+pub const SCOPE_HOST_UPDATE: &str = "host.update";
 
 fn default_autostart() -> bool {
     true
@@ -88,6 +96,12 @@ impl KernelManifest {
                 ],
                 ..CapabilityManifestEntry::new(CAPABILITY_RUNTIME_MANAGER)
             },
+            CapabilityManifestEntry::new(CAPABILITY_SCHEDULER),
+            CapabilityManifestEntry {
+                id: CAPABILITY_FEATURE_FLAGS.to_string(),
+                depends_on: vec![CAPABILITY_SECURITY.to_string()],
+                ..CapabilityManifestEntry::new(CAPABILITY_FEATURE_FLAGS)
+            },
         ];
 
         let mut agent_factory_capability = CapabilityManifestEntry::new(CAPABILITY_AGENT_FACTORY);
@@ -143,6 +157,32 @@ impl KernelManifest {
                     CAPABILITY_SECURITY.to_string(),
                 ],
             },
+            TokenPolicyManifestEntry {
+                scope: SCOPE_HOST_SERVICE_INSTALL.to_string(),
+                description: Some(
+                    "Allows an actor to install or remove the NOA stack as system services"
+                        .to_string(),
+                ),
+                ttl_seconds: 300,
+                capabilities: vec![
+                    CAPABILITY_PROCESS.to_string(),
+                    CAPABILITY_FILESYSTEM.to_string(),
+                    CAPABILITY_SECURITY.to_string(),
+                ],
+            },
+            TokenPolicyManifestEntry {
+                scope: SCOPE_HOST_UPDATE.to_string(),
+                description: Some(
+                    "Allows an actor to install or roll back releases of the NOA binaries"
+                        .to_string(),
+                ),
+                ttl_seconds: 300,
+                capabilities: vec![
+                    CAPABILITY_PROCESS.to_string(),
+                    CAPABILITY_FILESYSTEM.to_string(),
+                    CAPABILITY_SECURITY.to_string(),
+                ],
+            },
         ];
 
         Self {
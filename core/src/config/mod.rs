@@ -1,4 +1,5 @@
 //! Kernel configuration primitives.
 
+pub mod host_profile;
 pub mod manifest;
 pub mod profile;
@@ -0,0 +1,733 @@
+//! Cost attribution and showback
+//!
+//! Gateway requests, workflow runs, and CI/CD pipelines charge the resources they
+//! consume ([`CostUsage`]: CPU-seconds, tokens, GPU-seconds, stored bytes) to a
+//! [`CostAccount`], the identity that originated the work and its namespace. The
+//! [`CostLedger`] keeps the charges, answers [`CostQuery`] reports per account and
+//! subject, and raises a [`BudgetAlert`] when an account crosses a [`CostBudget`].
+//! Alerts are also published on the `cost_alerts` telemetry stream.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::ops::{Add, AddAssign};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::telemetry::{FanoutEmitter, TelemetryEmitter, TelemetryRecord};
+
+/// Telemetry stream budget alerts are emitted on.
+pub const COST_ALERT_STREAM: &str = "cost_alerts";
+/// Fraction of a limit at which a warning is raised when a budget sets none.
+pub const DEFAULT_WARN_RATIO: f64 = 0.8;
+
+#[derive(Debug, Error)]
+pub enum CostError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("no charges recorded for {kind} '{subject}'")]
+    UnknownSubject { kind: CostKind, subject: String },
+}
+
+/// Resources consumed by a unit of work.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostUsage {
+    #[serde(default)]
+    pub cpu_seconds: f64,
+    #[serde(default)]
+    pub tokens: u64,
+    #[serde(default)]
+    pub gpu_seconds: f64,
+    #[serde(default)]
+    pub storage_bytes: u64,
+}
+
+impl CostUsage {
+    pub fn cpu(seconds: f64) -> Self {
+        Self {
+            cpu_seconds: seconds,
+            ..Self::default()
+        }
+    }
+
+    pub fn tokens(tokens: u64) -> Self {
+        Self {
+            tokens,
+            ..Self::default()
+        }
+    }
+
+    pub fn gpu(seconds: f64) -> Self {
+        Self {
+            gpu_seconds: seconds,
+            ..Self::default()
+        }
+    }
+
+    pub fn storage(bytes: u64) -> Self {
+        Self {
+            storage_bytes: bytes,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn get(&self, dimension: CostDimension) -> f64 {
+        match dimension {
+            CostDimension::CpuSeconds => self.cpu_seconds,
+            CostDimension::Tokens => self.tokens as f64,
+            CostDimension::GpuSeconds => self.gpu_seconds,
+            CostDimension::StorageBytes => self.storage_bytes as f64,
+        }
+    }
+}
+
+impl Add for CostUsage {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl AddAssign for CostUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.cpu_seconds += other.cpu_seconds;
+        self.tokens += other.tokens;
+        self.gpu_seconds += other.gpu_seconds;
+        self.storage_bytes += other.storage_bytes;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostDimension {
+    CpuSeconds,
+    Tokens,
+    GpuSeconds,
+    StorageBytes,
+}
+
+impl CostDimension {
+    pub const ALL: [CostDimension; 4] = [
+        CostDimension::CpuSeconds,
+        CostDimension::Tokens,
+        CostDimension::GpuSeconds,
+        CostDimension::StorageBytes,
+    ];
+}
+
+impl fmt::Display for CostDimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CostDimension::CpuSeconds => "cpu_seconds",
+            CostDimension::Tokens => "tokens",
+            CostDimension::GpuSeconds => "gpu_seconds",
+            CostDimension::StorageBytes => "storage_bytes",
+        })
+    }
+}
+
+/// What incurred a charge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostKind {
+    Request,
+    Workflow,
+    Pipeline,
+}
+
+impl fmt::Display for CostKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CostKind::Request => "request",
+            CostKind::Workflow => "workflow",
+            CostKind::Pipeline => "pipeline",
+        })
+    }
+}
+
+/// Who pays: the originating identity (user or agent) within its namespace.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CostAccount {
+    pub identity: String,
+    pub namespace: String,
+}
+
+impl CostAccount {
+    pub fn new(identity: impl Into<String>, namespace: impl Into<String>) -> Self {
+        Self {
+            identity: identity.into(),
+            namespace: namespace.into(),
+        }
+    }
+}
+
+/// One charge against an account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostCharge {
+    pub kind: CostKind,
+    /// Request, workflow, or pipeline id.
+    pub subject: String,
+    pub account: CostAccount,
+    pub usage: CostUsage,
+    pub recorded_at: u64,
+}
+
+/// Per-dimension limits; unset dimensions are not limited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostLimits {
+    #[serde(default)]
+    pub cpu_seconds: Option<f64>,
+    #[serde(default)]
+    pub tokens: Option<u64>,
+    #[serde(default)]
+    pub gpu_seconds: Option<f64>,
+    #[serde(default)]
+    pub storage_bytes: Option<u64>,
+}
+
+impl CostLimits {
+    fn get(&self, dimension: CostDimension) -> Option<f64> {
+        match dimension {
+            CostDimension::CpuSeconds => self.cpu_seconds,
+            CostDimension::Tokens => self.tokens.map(|limit| limit as f64),
+            CostDimension::GpuSeconds => self.gpu_seconds,
+            CostDimension::StorageBytes => self.storage_bytes.map(|limit| limit as f64),
+        }
+    }
+}
+
+/// Spending limit over every account matching `namespace` and `identity`; an unset
+/// selector matches all.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostBudget {
+    pub name: String,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub identity: Option<String>,
+    pub limits: CostLimits,
+    /// Fraction of a limit at which a warning is raised.
+    #[serde(default = "default_warn_ratio")]
+    pub warn_ratio: f64,
+}
+
+fn default_warn_ratio() -> f64 {
+    DEFAULT_WARN_RATIO
+}
+
+impl CostBudget {
+    pub fn new(name: impl Into<String>, limits: CostLimits) -> Self {
+        Self {
+            name: name.into(),
+            namespace: None,
+            identity: None,
+            limits,
+            warn_ratio: DEFAULT_WARN_RATIO,
+        }
+    }
+
+    pub fn for_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    pub fn for_identity(mut self, identity: impl Into<String>) -> Self {
+        self.identity = Some(identity.into());
+        self
+    }
+
+    pub fn covers(&self, account: &CostAccount) -> bool {
+        self.namespace
+            .as_ref()
+            .is_none_or(|namespace| *namespace == account.namespace)
+            && self
+                .identity
+                .as_ref()
+                .is_none_or(|identity| *identity == account.identity)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertLevel {
+    Warning,
+    Exceeded,
+}
+
+/// Raised once per budget, dimension, and level when a charge crosses the threshold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetAlert {
+    pub budget: String,
+    pub level: AlertLevel,
+    pub dimension: CostDimension,
+    pub spent: f64,
+    pub limit: f64,
+    /// Account whose charge crossed the threshold.
+    pub account: CostAccount,
+    pub kind: CostKind,
+    pub subject: String,
+    pub raised_at: u64,
+}
+
+/// Filter for [`CostLedger::report`]; unset fields match everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostQuery {
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub identity: Option<String>,
+    #[serde(default)]
+    pub kind: Option<CostKind>,
+    /// Only charges recorded at or after this Unix time in milliseconds.
+    #[serde(default)]
+    pub since: Option<u64>,
+}
+
+impl CostQuery {
+    fn matches(&self, charge: &CostCharge) -> bool {
+        self.namespace
+            .as_ref()
+            .is_none_or(|namespace| *namespace == charge.account.namespace)
+            && self
+                .identity
+                .as_ref()
+                .is_none_or(|identity| *identity == charge.account.identity)
+            && self.kind.is_none_or(|kind| kind == charge.kind)
+            && self.since.is_none_or(|since| charge.recorded_at >= since)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountCost {
+    pub account: CostAccount,
+    pub usage: CostUsage,
+    /// Usage per kind of work, e.g. how much came from pipelines.
+    pub by_kind: BTreeMap<CostKind, CostUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubjectCost {
+    pub kind: CostKind,
+    pub subject: String,
+    pub account: CostAccount,
+    pub usage: CostUsage,
+    pub charges: usize,
+}
+
+/// Showback for the charges matching a query, largest consumers first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostReport {
+    pub query: CostQuery,
+    pub total: CostUsage,
+    pub accounts: Vec<AccountCost>,
+    pub subjects: Vec<SubjectCost>,
+    pub alerts: Vec<BudgetAlert>,
+}
+
+#[derive(Default)]
+struct LedgerState {
+    charges: Vec<CostCharge>,
+    /// Account each subject was first charged to.
+    owners: HashMap<(CostKind, String), CostAccount>,
+    budgets: Vec<CostBudget>,
+    alerts: Vec<BudgetAlert>,
+}
+
+impl LedgerState {
+    fn push(&mut self, charge: CostCharge) {
+        self.owners
+            .entry((charge.kind, charge.subject.clone()))
+            .or_insert_with(|| charge.account.clone());
+        self.charges.push(charge);
+    }
+
+    fn spent(&self, budget: &CostBudget) -> CostUsage {
+        self.charges
+            .iter()
+            .filter(|charge| budget.covers(&charge.account))
+            .fold(CostUsage::default(), |total, charge| total + charge.usage)
+    }
+}
+
+/// Accumulates cost charges, optionally appending them as JSON lines to a file.
+pub struct CostLedger {
+    state: Mutex<LedgerState>,
+    path: Option<PathBuf>,
+    emitter: FanoutEmitter,
+}
+
+impl fmt::Debug for CostLedger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("CostLedger")
+            .field("path", &self.path)
+            .field("charges", &state.charges.len())
+            .field("budgets", &state.budgets.len())
+            .finish()
+    }
+}
+
+impl Default for CostLedger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CostLedger {
+    /// In-memory ledger.
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(LedgerState::default()),
+            path: None,
+            emitter: FanoutEmitter::default(),
+        }
+    }
+
+    /// Ledger persisted at `path`; charges already in the file are loaded.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, CostError> {
+        let path = path.into();
+        let mut state = LedgerState::default();
+        if path.exists() {
+            for line in fs::read_to_string(&path)?.lines() {
+                if !line.trim().is_empty() {
+                    state.push(serde_json::from_str(line)?);
+                }
+            }
+        }
+        Ok(Self {
+            state: Mutex::new(state),
+            path: Some(path),
+            emitter: FanoutEmitter::default(),
+        })
+    }
+
+    pub fn with_budget(self, budget: CostBudget) -> Self {
+        self.set_budget(budget);
+        self
+    }
+
+    /// Also publish budget alerts to `emitter` on the `cost_alerts` stream.
+    pub fn with_emitter(mut self, emitter: Arc<dyn TelemetryEmitter>) -> Self {
+        self.emitter = self.emitter.with(emitter);
+        self
+    }
+
+    /// Add a budget, replacing any with the same name.
+    pub fn set_budget(&self, budget: CostBudget) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .budgets
+            .retain(|existing| existing.name != budget.name);
+        state.budgets.push(budget);
+    }
+
+    /// Load budgets from a JSON array, e.g. `storage/telemetry/cost_budgets.json`.
+    pub fn load_budgets(&self, path: &Path) -> Result<usize, CostError> {
+        let budgets: Vec<CostBudget> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let count = budgets.len();
+        for budget in budgets {
+            self.set_budget(budget);
+        }
+        Ok(count)
+    }
+
+    pub fn budgets(&self) -> Vec<CostBudget> {
+        self.state.lock().unwrap().budgets.clone()
+    }
+
+    /// Charge `usage` for `subject` to `account`, returning the budget alerts it raised.
+    pub fn charge(
+        &self,
+        kind: CostKind,
+        subject: &str,
+        account: CostAccount,
+        usage: CostUsage,
+    ) -> Result<Vec<BudgetAlert>, CostError> {
+        let charge = CostCharge {
+            kind,
+            subject: subject.to_string(),
+            account,
+            usage,
+            recorded_at: now_millis(),
+        };
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", serde_json::to_string(&charge)?)?;
+        }
+
+        let alerts = {
+            let mut state = self.state.lock().unwrap();
+            let before: Vec<CostUsage> = state
+                .budgets
+                .iter()
+                .map(|budget| state.spent(budget))
+                .collect();
+            state.push(charge.clone());
+            let alerts: Vec<BudgetAlert> = state
+                .budgets
+                .iter()
+                .zip(before)
+                .filter(|(budget, _)| budget.covers(&charge.account))
+                .flat_map(|(budget, before)| crossed(budget, before, &charge))
+                .collect();
+            state.alerts.extend(alerts.iter().cloned());
+            alerts
+        };
+        for alert in &alerts {
+            // Alerts stay in the ledger even when an emitter is unreachable.
+            let _ = TelemetryRecord::of(COST_ALERT_STREAM, alert)
+                .and_then(|record| self.emitter.emit(&record));
+        }
+        Ok(alerts)
+    }
+
+    /// Charge more usage to a subject already charged, e.g. inference tokens for a
+    /// gateway request, on the account it was first charged to.
+    pub fn charge_subject(
+        &self,
+        kind: CostKind,
+        subject: &str,
+        usage: CostUsage,
+    ) -> Result<Vec<BudgetAlert>, CostError> {
+        let account = self
+            .owner(kind, subject)
+            .ok_or_else(|| CostError::UnknownSubject {
+                kind,
+                subject: subject.to_string(),
+            })?;
+        self.charge(kind, subject, account, usage)
+    }
+
+    /// Account a subject was first charged to.
+    pub fn owner(&self, kind: CostKind, subject: &str) -> Option<CostAccount> {
+        self.state
+            .lock()
+            .unwrap()
+            .owners
+            .get(&(kind, subject.to_string()))
+            .cloned()
+    }
+
+    /// Total usage charged for a subject.
+    pub fn subject_usage(&self, kind: CostKind, subject: &str) -> CostUsage {
+        self.state
+            .lock()
+            .unwrap()
+            .charges
+            .iter()
+            .filter(|charge| charge.kind == kind && charge.subject == subject)
+            .fold(CostUsage::default(), |total, charge| total + charge.usage)
+    }
+
+    /// Alerts raised so far, oldest first.
+    pub fn alerts(&self) -> Vec<BudgetAlert> {
+        self.state.lock().unwrap().alerts.clone()
+    }
+
+    pub fn report(&self, query: &CostQuery) -> CostReport {
+        let state = self.state.lock().unwrap();
+        let mut total = CostUsage::default();
+        let mut accounts: BTreeMap<CostAccount, AccountCost> = BTreeMap::new();
+        let mut subjects: BTreeMap<(CostKind, String), SubjectCost> = BTreeMap::new();
+        for charge in state.charges.iter().filter(|charge| query.matches(charge)) {
+            total += charge.usage;
+            let account = accounts
+                .entry(charge.account.clone())
+                .or_insert_with(|| AccountCost {
+                    account: charge.account.clone(),
+                    usage: CostUsage::default(),
+                    by_kind: BTreeMap::new(),
+                });
+            account.usage += charge.usage;
+            *account.by_kind.entry(charge.kind).or_default() += charge.usage;
+            let subject = subjects
+                .entry((charge.kind, charge.subject.clone()))
+                .or_insert_with(|| SubjectCost {
+                    kind: charge.kind,
+                    subject: charge.subject.clone(),
+                    account: charge.account.clone(),
+                    usage: CostUsage::default(),
+                    charges: 0,
+                });
+            subject.usage += charge.usage;
+            subject.charges += 1;
+        }
+
+        let mut accounts: Vec<AccountCost> = accounts.into_values().collect();
+        accounts.sort_by(|a, b| heaviest_first(&a.usage, &b.usage));
+        let mut subjects: Vec<SubjectCost> = subjects.into_values().collect();
+        subjects.sort_by(|a, b| heaviest_first(&a.usage, &b.usage));
+        let alerts = state
+            .alerts
+            .iter()
+            .filter(|alert| {
+                query
+                    .namespace
+                    .as_ref()
+                    .is_none_or(|namespace| *namespace == alert.account.namespace)
+                    && query
+                        .identity
+                        .as_ref()
+                        .is_none_or(|identity| *identity == alert.account.identity)
+            })
+            .cloned()
+            .collect();
+        CostReport {
+            query: query.clone(),
+            total,
+            accounts,
+            subjects,
+            alerts,
+        }
+    }
+}
+
+/// Alerts for every threshold of `budget` that `charge` pushed spending across.
+fn crossed(budget: &CostBudget, before: CostUsage, charge: &CostCharge) -> Vec<BudgetAlert> {
+    let after = before + charge.usage;
+    let mut alerts = Vec::new();
+    for dimension in CostDimension::ALL {
+        let Some(limit) = budget.limits.get(dimension) else {
+            continue;
+        };
+        let (was, now) = (before.get(dimension), after.get(dimension));
+        let level = if was <= limit && now > limit {
+            AlertLevel::Exceeded
+        } else if was < limit * budget.warn_ratio && now >= limit * budget.warn_ratio {
+            AlertLevel::Warning
+        } else {
+            continue;
+        };
+        alerts.push(BudgetAlert {
+            budget: budget.name.clone(),
+            level,
+            dimension,
+            spent: now,
+            limit,
+            account: charge.account.clone(),
+            kind: charge.kind,
+            subject: charge.subject.clone(),
+            raised_at: charge.recorded_at,
+        });
+    }
+    alerts
+}
+
+/// Orders by CPU-seconds, then GPU-seconds, then tokens, descending.
+fn heaviest_first(a: &CostUsage, b: &CostUsage) -> std::cmp::Ordering {
+    b.cpu_seconds
+        .total_cmp(&a.cpu_seconds)
+        .then(b.gpu_seconds.total_cmp(&a.gpu_seconds))
+        .then(b.tokens.cmp(&a.tokens))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::MemoryEmitter;
+
+    #[test]
+    fn charges_roll_up_per_account_and_raise_budget_alerts_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cost_ledger.jsonl");
+        let memory = Arc::new(MemoryEmitter::default());
+        let ledger = CostLedger::open(&path)
+            .unwrap()
+            .with_emitter(memory.clone())
+            .with_budget(
+                CostBudget::new(
+                    "research-tokens",
+                    CostLimits {
+                        tokens: Some(1_000),
+                        ..CostLimits::default()
+                    },
+                )
+                .for_namespace("research"),
+            );
+        let alice = CostAccount::new("user:alice", "research");
+        let builder = CostAccount::new("agent:builder", "ops");
+
+        assert!(ledger
+            .charge(
+                CostKind::Request,
+                "req-1",
+                alice.clone(),
+                CostUsage::cpu(0.5)
+            )
+            .unwrap()
+            .is_empty());
+        let warned = ledger
+            .charge_subject(CostKind::Request, "req-1", CostUsage::tokens(850))
+            .unwrap();
+        assert_eq!(warned.len(), 1);
+        assert_eq!(warned[0].level, AlertLevel::Warning);
+        assert_eq!(warned[0].account, alice);
+        let exceeded = ledger
+            .charge(
+                CostKind::Workflow,
+                "wf-1",
+                alice.clone(),
+                CostUsage::tokens(200),
+            )
+            .unwrap();
+        assert_eq!(exceeded[0].level, AlertLevel::Exceeded);
+        assert!(ledger
+            .charge(
+                CostKind::Workflow,
+                "wf-1",
+                alice.clone(),
+                CostUsage::tokens(50)
+            )
+            .unwrap()
+            .is_empty());
+        ledger
+            .charge(CostKind::Pipeline, "pl-1", builder, CostUsage::gpu(3.0))
+            .unwrap();
+        assert!(matches!(
+            ledger.charge_subject(CostKind::Pipeline, "pl-missing", CostUsage::cpu(1.0)),
+            Err(CostError::UnknownSubject { .. })
+        ));
+
+        let report = ledger.report(&CostQuery {
+            namespace: Some("research".into()),
+            ..CostQuery::default()
+        });
+        assert_eq!(report.total.tokens, 1_100);
+        assert_eq!(report.accounts.len(), 1);
+        assert_eq!(report.accounts[0].by_kind[&CostKind::Workflow].tokens, 250);
+        assert_eq!(report.subjects.len(), 2);
+        assert_eq!(report.alerts.len(), 2);
+        assert_eq!(memory.records().len(), 2);
+        assert_eq!(memory.records()[0].stream, COST_ALERT_STREAM);
+
+        let reopened = CostLedger::open(&path).unwrap();
+        assert_eq!(
+            reopened.subject_usage(CostKind::Request, "req-1"),
+            CostUsage::cpu(0.5) + CostUsage::tokens(850)
+        );
+        assert_eq!(reopened.report(&CostQuery::default()).subjects.len(), 3);
+    }
+}
@@ -0,0 +1,307 @@
+//! Kernel event bus
+//!
+//! The capability registry publishes a [`KernelEvent`] whenever a capability is
+//! registered, initialized, fails, or is replaced, and components hand errors
+//! they cannot handle to [`report_error`] instead of printing them. Subscribers
+//! implement [`KernelEventSubscriber`] and are called synchronously in publish
+//! order; consumers that poll, such as the UI shell, take a receiver from
+//! [`KernelEventBus::channel`]. The bus keeps the latest [`HISTORY_LIMIT`] events
+//! so late subscribers can catch up through [`KernelEventBus::recent`].
+
+use std::collections::VecDeque;
+use std::panic;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::current_timestamp_millis;
+
+/// Events kept for [`KernelEventBus::recent`].
+pub const HISTORY_LIMIT: usize = 256;
+
+/// What happened in the kernel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum KernelEventKind {
+    CapabilityRegistered {
+        capability: String,
+    },
+    CapabilityInitialized {
+        capability: String,
+        duration_ms: u64,
+    },
+    CapabilityFailed {
+        capability: String,
+        error: String,
+    },
+    /// A new definition took over a registered capability; it initializes on next use.
+    CapabilityReplaced {
+        capability: String,
+    },
+    /// An error no caller could handle, e.g. a failed shutdown or a panic.
+    UnhandledError {
+        source: String,
+        message: String,
+    },
+}
+
+impl KernelEventKind {
+    /// Snake-case name, used as the metric label and log field.
+    pub fn name(&self) -> &'static str {
+        match self {
+            KernelEventKind::CapabilityRegistered { .. } => "capability_registered",
+            KernelEventKind::CapabilityInitialized { .. } => "capability_initialized",
+            KernelEventKind::CapabilityFailed { .. } => "capability_failed",
+            KernelEventKind::CapabilityReplaced { .. } => "capability_replaced",
+            KernelEventKind::UnhandledError { .. } => "unhandled_error",
+        }
+    }
+
+    /// Capability the event concerns, if any.
+    pub fn capability(&self) -> Option<&str> {
+        match self {
+            KernelEventKind::CapabilityRegistered { capability }
+            | KernelEventKind::CapabilityInitialized { capability, .. }
+            | KernelEventKind::CapabilityFailed { capability, .. }
+            | KernelEventKind::CapabilityReplaced { capability } => Some(capability),
+            KernelEventKind::UnhandledError { .. } => None,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            KernelEventKind::CapabilityFailed { .. } | KernelEventKind::UnhandledError { .. }
+        )
+    }
+}
+
+/// An event as delivered to subscribers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KernelEvent {
+    /// Position on the bus that published it, starting at 1.
+    pub sequence: u64,
+    pub timestamp: u128,
+    #[serde(flatten)]
+    pub kind: KernelEventKind,
+}
+
+/// Receives every event published on a bus it subscribed to.
+pub trait KernelEventSubscriber: Send + Sync {
+    fn on_event(&self, event: &KernelEvent);
+}
+
+impl<F> KernelEventSubscriber for F
+where
+    F: Fn(&KernelEvent) + Send + Sync,
+{
+    fn on_event(&self, event: &KernelEvent) {
+        self(event)
+    }
+}
+
+#[derive(Default)]
+struct BusInner {
+    sequence: AtomicU64,
+    subscribers: RwLock<Vec<Arc<dyn KernelEventSubscriber>>>,
+    channels: Mutex<Vec<Sender<KernelEvent>>>,
+    history: Mutex<VecDeque<KernelEvent>>,
+}
+
+/// Fan-out channel for kernel events. Clones share subscribers and history.
+#[derive(Clone, Default)]
+pub struct KernelEventBus {
+    inner: Arc<BusInner>,
+}
+
+impl KernelEventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self, subscriber: Arc<dyn KernelEventSubscriber>) {
+        self.inner
+            .subscribers
+            .write()
+            .expect("kernel event subscribers poisoned")
+            .push(subscriber);
+    }
+
+    /// Receiver for events published from now on; dropped receivers are pruned.
+    pub fn channel(&self) -> Receiver<KernelEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.inner
+            .channels
+            .lock()
+            .expect("kernel event channels poisoned")
+            .push(sender);
+        receiver
+    }
+
+    pub fn publish(&self, kind: KernelEventKind) -> KernelEvent {
+        let event = KernelEvent {
+            sequence: self.inner.sequence.fetch_add(1, Ordering::SeqCst) + 1,
+            timestamp: current_timestamp_millis(),
+            kind,
+        };
+        {
+            let mut history = self
+                .inner
+                .history
+                .lock()
+                .expect("kernel event history poisoned");
+            if history.len() == HISTORY_LIMIT {
+                history.pop_front();
+            }
+            history.push_back(event.clone());
+        }
+        self.inner
+            .channels
+            .lock()
+            .expect("kernel event channels poisoned")
+            .retain(|sender| sender.send(event.clone()).is_ok());
+        // Subscribers may publish in turn, so call them without holding the lock.
+        let subscribers = self
+            .inner
+            .subscribers
+            .read()
+            .expect("kernel event subscribers poisoned")
+            .clone();
+        for subscriber in subscribers {
+            subscriber.on_event(&event);
+        }
+        event
+    }
+
+    /// Publish an [`KernelEventKind::UnhandledError`].
+    pub fn report_error(&self, source: impl Into<String>, message: impl ToString) -> KernelEvent {
+        self.publish(KernelEventKind::UnhandledError {
+            source: source.into(),
+            message: message.to_string(),
+        })
+    }
+
+    /// Events still in the history, oldest first.
+    pub fn recent(&self) -> Vec<KernelEvent> {
+        self.inner
+            .history
+            .lock()
+            .expect("kernel event history poisoned")
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Bus the kernel's capability registry publishes on.
+pub fn global() -> &'static KernelEventBus {
+    static BUS: OnceLock<KernelEventBus> = OnceLock::new();
+    BUS.get_or_init(KernelEventBus::new)
+}
+
+/// Report an error no caller can handle on the [`global`] bus.
+pub fn report_error(source: impl Into<String>, message: impl ToString) -> KernelEvent {
+    global().report_error(source, message)
+}
+
+/// Report panics on the [`global`] bus before running the previously installed hook.
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let source = info
+            .location()
+            .map(|location| format!("panic@{}:{}", location.file(), location.line()))
+            .unwrap_or_else(|| "panic".to_string());
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        report_error(source, message);
+        previous(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capabilities::{
+        CapabilityDefinition, CapabilityError, CapabilityRegistry, CapabilityState, DynCapability,
+        KernelHandle,
+    };
+    use crate::config::manifest::KernelManifest;
+
+    fn capability(id: &str, fails: bool) -> CapabilityDefinition {
+        let owned = id.to_string();
+        CapabilityDefinition::builder(id)
+            .init_with(move |_| {
+                if fails {
+                    Err(CapabilityError::InitializationFailed(
+                        owned.clone(),
+                        "device missing".to_string(),
+                    ))
+                } else {
+                    Ok(Arc::new(()) as DynCapability)
+                }
+            })
+            .build()
+    }
+
+    #[test]
+    fn registry_lifecycle_reaches_subscribers_and_channels() {
+        let bus = KernelEventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        {
+            let seen = Arc::clone(&seen);
+            bus.subscribe(Arc::new(move |event: &KernelEvent| {
+                seen.lock().unwrap().push(event.kind.name());
+            }));
+        }
+        let receiver = bus.channel();
+
+        let registry = Arc::new(CapabilityRegistry::with_events(bus.clone()));
+        let kernel = KernelHandle::new(Arc::clone(&registry), Arc::new(KernelManifest::default()));
+        registry
+            .register_definition(capability("probe", true))
+            .unwrap();
+        registry
+            .register_definition(
+                CapabilityDefinition::builder("consumer")
+                    .depends_on(["probe"])
+                    .init_with(|_| Ok(Arc::new(()) as DynCapability))
+                    .build(),
+            )
+            .unwrap();
+
+        assert!(kernel.ensure("consumer").is_err());
+        assert_eq!(registry.states()["consumer"], CapabilityState::Failed);
+
+        registry
+            .replace_definition(capability("probe", false))
+            .unwrap();
+        kernel.ensure("probe").unwrap();
+        bus.report_error("kernel.shutdown", "hook timed out");
+
+        let expected = vec![
+            "capability_registered",
+            "capability_registered",
+            "capability_failed",
+            "capability_failed",
+            "capability_replaced",
+            "capability_initialized",
+            "unhandled_error",
+        ];
+        assert_eq!(*seen.lock().unwrap(), expected);
+        let delivered: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(delivered, bus.recent());
+        assert!(matches!(
+            &delivered[3].kind,
+            KernelEventKind::CapabilityFailed { capability, error }
+                if capability == "consumer" && error.starts_with("dependency probe unavailable")
+        ));
+        assert_eq!(delivered.last().unwrap().sequence, 7);
+    }
+}
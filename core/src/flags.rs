@@ -0,0 +1,582 @@
+//! Kernel feature flags with targeting rules.
+//!
+//! Flags are defined in YAML (by default [`DEFAULT_FLAGS_PATH`], or the file named
+//! by `NOA_FEATURE_FLAGS`) so risky subsystems can be rolled out gradually:
+//!
+//! ```yaml
+//! flags:
+//!   workflow.speculative_execution:
+//!     description: Start likely stages before their inputs are final
+//!     enabled: false
+//!     rules:
+//!       - host_classes: [minimal]
+//!         enabled: false
+//!       - namespaces: [canary]
+//!         percentage: 25
+//! ```
+//!
+//! The first rule whose conditions all hold decides the flag; a flag without a
+//! matching rule takes its `enabled` default. A `percentage` admits a stable share
+//! of subjects, bucketed by a hash of the flag name and the context's subject (or
+//! namespace), so a subject keeps its answer as the rollout widens. Evaluation only
+//! takes a read lock on the loaded set. Every change, whether edited in the file
+//! and picked up by the watcher or applied with [`FeatureFlagService::set`], is
+//! signed into the security audit trail.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::hardware::HostClassification;
+use crate::security::{self, OperationKind, OperationRecord};
+
+/// Flag definitions shipped with the kernel.
+pub const DEFAULT_FLAGS_PATH: &str = "core/config/feature_flags.yaml";
+/// Audit scope of flag changes.
+pub const FLAGS_SCOPE: &str = "kernel.feature_flags";
+/// How often the watcher checks the flag file for edits.
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, thiserror::Error)]
+pub enum FlagError {
+    #[error("failed to access flag file {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to parse flag file: {0}")]
+    Parse(#[from] serde_yaml::Error),
+    #[error("invalid flag {flag}: {reason}")]
+    Invalid { flag: String, reason: String },
+    #[error("audit trail rejected the flag change: {0}")]
+    Audit(String),
+}
+
+/// One rule of a flag. Unset conditions match everything.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TargetingRule {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub host_classes: Vec<HostClassification>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub namespaces: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agent_roles: Vec<String>,
+    /// Share of subjects, 0-100, the rule applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentage: Option<u8>,
+    /// Value of the flag when the rule matches.
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
+impl Default for TargetingRule {
+    fn default() -> Self {
+        Self {
+            host_classes: Vec::new(),
+            namespaces: Vec::new(),
+            agent_roles: Vec::new(),
+            percentage: None,
+            enabled: true,
+        }
+    }
+}
+
+impl TargetingRule {
+    fn matches(
+        &self,
+        flag: &str,
+        context: &FlagContext,
+        host_class: Option<&HostClassification>,
+    ) -> bool {
+        let within = |allowed: &[String], value: &Option<String>| {
+            allowed.is_empty()
+                || value
+                    .as_ref()
+                    .is_some_and(|value| allowed.iter().any(|entry| entry == value))
+        };
+        (self.host_classes.is_empty()
+            || host_class.is_some_and(|class| self.host_classes.contains(class)))
+            && within(&self.namespaces, &context.namespace)
+            && within(&self.agent_roles, &context.agent_role)
+            && self
+                .percentage
+                .is_none_or(|percentage| cohort(flag, context) < percentage)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FlagDefinition {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Value when no rule matches.
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<TargetingRule>,
+}
+
+/// The flags of a flag file, keyed by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FlagSet {
+    #[serde(default)]
+    pub flags: BTreeMap<String, FlagDefinition>,
+}
+
+impl FlagSet {
+    pub fn from_yaml(content: &str) -> Result<Self, FlagError> {
+        let set: Self = serde_yaml::from_str(content)?;
+        set.validate()?;
+        Ok(set)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, FlagError> {
+        let content = fs::read_to_string(path).map_err(|source| FlagError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_yaml(&content)
+    }
+
+    pub fn validate(&self) -> Result<(), FlagError> {
+        for (flag, definition) in &self.flags {
+            let invalid = |reason: &str| FlagError::Invalid {
+                flag: flag.clone(),
+                reason: reason.to_string(),
+            };
+            if flag.trim().is_empty() {
+                return Err(invalid("flag names must not be empty"));
+            }
+            if definition
+                .rules
+                .iter()
+                .any(|rule| rule.percentage.is_some_and(|percentage| percentage > 100))
+            {
+                return Err(invalid("percentage must be between 0 and 100"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Value of `flag` for `context`; unknown flags are off.
+    pub fn evaluate(&self, flag: &str, context: &FlagContext) -> bool {
+        self.evaluate_on(flag, context, context.host_class.as_ref())
+    }
+
+    fn evaluate_on(
+        &self,
+        flag: &str,
+        context: &FlagContext,
+        host_class: Option<&HostClassification>,
+    ) -> bool {
+        let Some(definition) = self.flags.get(flag) else {
+            return false;
+        };
+        definition
+            .rules
+            .iter()
+            .find(|rule| rule.matches(flag, context, host_class))
+            .map_or(definition.enabled, |rule| rule.enabled)
+    }
+}
+
+/// What a flag is evaluated for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlagContext {
+    pub host_class: Option<HostClassification>,
+    pub namespace: Option<String>,
+    pub agent_role: Option<String>,
+    /// Stable key for percentage rollouts, e.g. an agent or pipeline id.
+    pub subject: Option<String>,
+}
+
+impl FlagContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_host_class(mut self, host_class: HostClassification) -> Self {
+        self.host_class = Some(host_class);
+        self
+    }
+
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    pub fn with_agent_role(mut self, agent_role: impl Into<String>) -> Self {
+        self.agent_role = Some(agent_role.into());
+        self
+    }
+
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+}
+
+/// Rollout bucket, 0-99, of the context's subject for `flag`.
+pub fn cohort(flag: &str, context: &FlagContext) -> u8 {
+    let key = context
+        .subject
+        .as_deref()
+        .or(context.namespace.as_deref())
+        .unwrap_or_default();
+    let digest = Sha256::digest(format!("{flag}:{key}"));
+    (u64::from_be_bytes(digest[..8].try_into().expect("sha256 has 8 bytes")) % 100) as u8
+}
+
+/// An audited change of one flag.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagChange {
+    pub flag: String,
+    pub before: Option<FlagDefinition>,
+    pub after: Option<FlagDefinition>,
+    pub actor: String,
+    pub operation_id: String,
+}
+
+/// The loaded flags, their source file, and the audited changes applied so far.
+#[derive(Debug, Default)]
+pub struct FeatureFlagService {
+    flags: RwLock<Arc<FlagSet>>,
+    path: Option<PathBuf>,
+    host_class: Option<HostClassification>,
+    loaded_modified: Mutex<Option<SystemTime>>,
+    changes: Mutex<Vec<FlagChange>>,
+}
+
+impl FeatureFlagService {
+    pub fn new(flags: FlagSet) -> Self {
+        Self {
+            flags: RwLock::new(Arc::new(flags)),
+            ..Self::default()
+        }
+    }
+
+    /// Load the flags from `path`; [`reload`](Self::reload) re-reads it.
+    pub fn from_file(path: impl Into<PathBuf>) -> Result<Self, FlagError> {
+        let path = path.into();
+        let flags = FlagSet::load(&path)?;
+        Ok(Self {
+            flags: RwLock::new(Arc::new(flags)),
+            loaded_modified: Mutex::new(modified(&path)),
+            path: Some(path),
+            ..Self::default()
+        })
+    }
+
+    /// Host class assumed by contexts that do not name one.
+    pub fn with_host_class(mut self, host_class: HostClassification) -> Self {
+        self.host_class = Some(host_class);
+        self
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn snapshot(&self) -> Arc<FlagSet> {
+        self.flags.read().expect("flag set lock poisoned").clone()
+    }
+
+    pub fn is_enabled(&self, flag: &str, context: &FlagContext) -> bool {
+        let host_class = context.host_class.as_ref().or(self.host_class.as_ref());
+        self.flags
+            .read()
+            .expect("flag set lock poisoned")
+            .evaluate_on(flag, context, host_class)
+    }
+
+    /// Audited changes applied since the service was created, oldest first.
+    pub fn changes(&self) -> Vec<FlagChange> {
+        self.changes
+            .lock()
+            .expect("flag changes lock poisoned")
+            .clone()
+    }
+
+    /// Define, replace, or (with `None`) remove a flag. The change is written back
+    /// to the flag file when the service has one.
+    pub fn set(
+        &self,
+        flag: &str,
+        definition: Option<FlagDefinition>,
+        actor: &str,
+    ) -> Result<Option<FlagChange>, FlagError> {
+        let mut next = (*self.snapshot()).clone();
+        match definition {
+            Some(definition) => next.flags.insert(flag.to_string(), definition),
+            None => next.flags.remove(flag),
+        };
+        next.validate()?;
+        if let Some(path) = &self.path {
+            let content = serde_yaml::to_string(&next)?;
+            fs::write(path, content).map_err(|source| FlagError::Io {
+                path: path.clone(),
+                source,
+            })?;
+            *self
+                .loaded_modified
+                .lock()
+                .expect("flag file lock poisoned") = modified(path);
+        }
+        Ok(self.apply(next, actor)?.pop())
+    }
+
+    /// Re-read the flag file and apply what changed.
+    pub fn reload(&self, actor: &str) -> Result<Vec<FlagChange>, FlagError> {
+        let Some(path) = &self.path else {
+            return Ok(Vec::new());
+        };
+        let next = FlagSet::load(path)?;
+        *self
+            .loaded_modified
+            .lock()
+            .expect("flag file lock poisoned") = modified(path);
+        self.apply(next, actor)
+    }
+
+    /// [`reload`](Self::reload) if the flag file was modified since it was last read.
+    pub fn reload_if_changed(&self, actor: &str) -> Result<Vec<FlagChange>, FlagError> {
+        let Some(path) = &self.path else {
+            return Ok(Vec::new());
+        };
+        if modified(path)
+            == *self
+                .loaded_modified
+                .lock()
+                .expect("flag file lock poisoned")
+        {
+            return Ok(Vec::new());
+        }
+        self.reload(actor)
+    }
+
+    /// Poll the flag file every `interval` until the service is dropped.
+    pub fn watch(self: &Arc<Self>, interval: Duration) -> thread::JoinHandle<()> {
+        let service: Weak<Self> = Arc::downgrade(self);
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            let Some(service) = service.upgrade() else {
+                break;
+            };
+            if let Err(err) = service.reload_if_changed("kernel/flag-watcher") {
+                eprintln!("[FLAGS] Failed to reload feature flags: {err}");
+            }
+        })
+    }
+
+    /// Sign every difference between the loaded set and `next`, then swap it in.
+    fn apply(&self, next: FlagSet, actor: &str) -> Result<Vec<FlagChange>, FlagError> {
+        let mut flags = self.flags.write().expect("flag set lock poisoned");
+        let mut names: Vec<&String> = flags.flags.keys().chain(next.flags.keys()).collect();
+        names.sort();
+        names.dedup();
+        let mut changes = Vec::new();
+        for flag in names {
+            let before = flags.flags.get(flag).cloned();
+            let after = next.flags.get(flag).cloned();
+            if before == after {
+                continue;
+            }
+            let operation = security::enforce_operation(
+                OperationRecord::new(OperationKind::Other, actor, FLAGS_SCOPE)
+                    .with_context(None, Some(flag.clone()))
+                    .with_metadata(json!({ "before": before, "after": after })),
+            )
+            .map_err(|err| FlagError::Audit(err.to_string()))?;
+            changes.push(FlagChange {
+                flag: flag.clone(),
+                before,
+                after,
+                actor: actor.to_string(),
+                operation_id: operation.record.operation_id,
+            });
+        }
+        if !changes.is_empty() {
+            *flags = Arc::new(next);
+            self.changes
+                .lock()
+                .expect("flag changes lock poisoned")
+                .extend(changes.iter().cloned());
+        }
+        Ok(changes)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn service_slot() -> &'static RwLock<Arc<FeatureFlagService>> {
+    static SERVICE: OnceLock<RwLock<Arc<FeatureFlagService>>> = OnceLock::new();
+    SERVICE.get_or_init(|| RwLock::new(Arc::new(FeatureFlagService::default())))
+}
+
+/// The kernel-wide flag service; empty until [`init`] or [`install`].
+pub fn global() -> Arc<FeatureFlagService> {
+    service_slot()
+        .read()
+        .expect("flag service lock poisoned")
+        .clone()
+}
+
+/// Replace the kernel-wide flag service.
+pub fn install(service: Arc<FeatureFlagService>) {
+    *service_slot().write().expect("flag service lock poisoned") = service;
+}
+
+/// Capability handle over the kernel-wide flag service.
+#[derive(Clone, Default)]
+pub struct FlagsCapability;
+
+impl FlagsCapability {
+    pub fn is_enabled(&self, flag: &str, context: &FlagContext) -> bool {
+        is_enabled(flag, context)
+    }
+
+    pub fn set(
+        &self,
+        flag: &str,
+        definition: Option<FlagDefinition>,
+        actor: &str,
+    ) -> Result<Option<FlagChange>, FlagError> {
+        global().set(flag, definition, actor)
+    }
+
+    pub fn reload(&self, actor: &str) -> Result<Vec<FlagChange>, FlagError> {
+        global().reload(actor)
+    }
+}
+
+/// Whether `flag` is on for `context` in the kernel-wide service.
+pub fn is_enabled(flag: &str, context: &FlagContext) -> bool {
+    global().is_enabled(flag, context)
+}
+
+/// Load the kernel's flag file, when there is one, and watch it for edits.
+pub fn init(host_class: &HostClassification) -> Result<(), &'static str> {
+    println!("[FLAGS] Loading feature flags...");
+    let path = std::env::var("NOA_FEATURE_FLAGS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_FLAGS_PATH));
+    if !path.exists() {
+        install(Arc::new(
+            FeatureFlagService::default().with_host_class(host_class.clone()),
+        ));
+        return Ok(());
+    }
+    let service = FeatureFlagService::from_file(&path)
+        .map_err(|err| {
+            eprintln!("[FLAGS] Failed to load {}: {err}", path.display());
+            "failed to load feature flags"
+        })?
+        .with_host_class(host_class.clone());
+    let service = Arc::new(service);
+    service.watch(DEFAULT_WATCH_INTERVAL);
+    install(service);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLAGS: &str = r#"
+flags:
+  workflow.speculative_execution:
+    enabled: false
+    rules:
+      - host_classes: [minimal]
+        enabled: false
+      - namespaces: [canary]
+        agent_roles: [planner]
+      - percentage: 50
+  workflow.remote_dispatch:
+    enabled: true
+"#;
+
+    #[test]
+    fn rules_target_hosts_namespaces_roles_and_percentages() {
+        let flags = FlagSet::from_yaml(FLAGS).unwrap();
+        let flag = "workflow.speculative_execution";
+        let canary = FlagContext::new()
+            .with_namespace("canary")
+            .with_agent_role("planner");
+        assert!(flags.evaluate(flag, &canary));
+        assert!(!flags.evaluate(
+            flag,
+            &canary.clone().with_host_class(HostClassification::Minimal)
+        ));
+        assert!(flags.evaluate("workflow.remote_dispatch", &FlagContext::new()));
+        assert!(!flags.evaluate("unknown", &canary));
+
+        let enabled = (0..200)
+            .map(|n| FlagContext::new().with_subject(format!("agent-{n}")))
+            .filter(|context| flags.evaluate(flag, context))
+            .count();
+        assert!((70..130).contains(&enabled), "{enabled} of 200 enabled");
+        let subject = FlagContext::new().with_subject("agent-7");
+        assert_eq!(flags.evaluate(flag, &subject), cohort(flag, &subject) < 50);
+
+        let invalid = FLAGS.replace("percentage: 50", "percentage: 150");
+        assert!(matches!(
+            FlagSet::from_yaml(&invalid),
+            Err(FlagError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn file_edits_reload_and_changes_are_audited() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("flags.yaml");
+        fs::write(&path, FLAGS).unwrap();
+        let service = FeatureFlagService::from_file(&path)
+            .unwrap()
+            .with_host_class(HostClassification::Minimal);
+        let flag = "workflow.speculative_execution";
+        let everyone = FlagContext::new().with_subject("agent-1");
+        assert!(!service.is_enabled(flag, &everyone));
+        assert!(service.reload_if_changed("tester").unwrap().is_empty());
+
+        fs::write(&path, FLAGS.replace("[minimal]", "[accelerated]")).unwrap();
+        let changes = service.reload("tester").unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].flag, flag);
+        assert!(security::audit_trail()
+            .iter()
+            .any(|operation| operation.record.operation_id == changes[0].operation_id));
+
+        let change = service
+            .set(
+                flag,
+                Some(FlagDefinition {
+                    enabled: true,
+                    ..FlagDefinition::default()
+                }),
+                "operator",
+            )
+            .unwrap()
+            .expect("flag changed");
+        assert_eq!(change.actor, "operator");
+        assert!(service.is_enabled(flag, &everyone));
+        assert_eq!(FlagSet::load(&path).unwrap(), *service.snapshot());
+        assert!(service.reload_if_changed("tester").unwrap().is_empty());
+        assert_eq!(service.changes().len(), 2);
+    }
+}
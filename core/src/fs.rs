@@ -1,4 +1,5 @@
-//! File system interface with registry metadata syncing
+//! File system interface with registry metadata syncing, and the workspace
+//! change journal (see [`ChangeJournal`]).
 
 use crate::memory;
 use crate::memory::{RegistryGraph, RegistryNode};
@@ -6,6 +7,13 @@ use std::collections::HashMap;
 use std::fmt;
 use std::sync::{Mutex, OnceLock};
 
+mod journal;
+
+pub use journal::{
+    ChangeJournal, ChangeKind, ChangeRecord, ChangeSet, JournalError, DEFAULT_JOURNAL_PATH,
+    JOURNAL_SCOPE,
+};
+
 const DEFAULT_FILE_MODE: u32 = 0o644;
 
 #[derive(Debug, Clone, Default)]
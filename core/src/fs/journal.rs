@@ -0,0 +1,481 @@
+//! Journal of file changes made under the workspace.
+//!
+//! Components that modify workspace files write, rename, and delete through a
+//! [`ChangeJournal`] instead of `std::fs`. Each change appends a [`ChangeRecord`]
+//! (actor, workspace-relative path, content hash before and after) to a JSON-lines
+//! journal and is signed into the security audit trail, so the journal answers
+//! "which agent modified this file". A journal built [`with_token`] only accepts
+//! changes under the token's `read_write` storage roots.
+//!
+//! The indexer and the symbol graph read [`ChangeJournal::changes_since`] to update
+//! only the files that changed since their last refresh.
+//!
+//! [`with_token`]: ChangeJournal::with_token
+
+use std::collections::BTreeSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::config::profile::{CapabilityToken, StorageMode};
+use crate::recovery::{self, RecordError, RecoveryMode};
+use crate::security::{self, OperationKind, OperationRecord};
+use crate::utils::{current_timestamp_millis, simple_hash};
+
+/// Journal location relative to the workspace root.
+pub const DEFAULT_JOURNAL_PATH: &str = ".workspace/journal/changes.jsonl";
+
+/// Scope under which journaled changes are signed into the audit trail.
+pub const JOURNAL_SCOPE: &str = "fs.journal";
+
+#[derive(Debug, Error)]
+pub enum JournalError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("corrupt journal: {0}")]
+    Record(#[from] RecordError),
+    #[error("{0} is outside the workspace")]
+    OutsideWorkspace(String),
+    #[error("{actor} may not modify {path}: no read_write storage root covers it")]
+    Denied { actor: String, path: String },
+    #[error("audit trail rejected the change: {0}")]
+    Audit(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Write,
+    Rename,
+    Delete,
+}
+
+/// One journaled change. Hashes are `None` where the file did not exist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    pub sequence: u64,
+    pub timestamp: u128,
+    pub actor: String,
+    pub kind: ChangeKind,
+    /// Workspace-relative path the change left behind: the destination of a rename.
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    pub hash_before: Option<String>,
+    pub hash_after: Option<String>,
+    /// Id of the signed operation recorded in the security audit trail.
+    pub operation_id: String,
+}
+
+/// Paths touched by a run of journal records, for incremental consumers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeSet {
+    /// Paths written or renamed into, and still present after the run.
+    pub updated: BTreeSet<String>,
+    /// Paths deleted or renamed away, and not recreated afterwards.
+    pub removed: BTreeSet<String>,
+    /// Sequence of the last record included; pass it to the next `changes_since`.
+    pub last_sequence: u64,
+}
+
+impl ChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.updated.is_empty() && self.removed.is_empty()
+    }
+
+    fn update(&mut self, path: &str) {
+        self.removed.remove(path);
+        self.updated.insert(path.to_string());
+    }
+
+    fn remove(&mut self, path: &str) {
+        self.updated.remove(path);
+        self.removed.insert(path.to_string());
+    }
+}
+
+/// Append-only journal of file changes under a workspace root.
+#[derive(Debug)]
+pub struct ChangeJournal {
+    root: PathBuf,
+    path: PathBuf,
+    /// Absolute roots changes are confined to; unrestricted when `None`.
+    writable: Option<Vec<PathBuf>>,
+    next_sequence: Mutex<u64>,
+}
+
+impl ChangeJournal {
+    /// Journal for the workspace at `root`, stored at [`DEFAULT_JOURNAL_PATH`].
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, JournalError> {
+        let root = root.as_ref().to_path_buf();
+        let path = root.join(DEFAULT_JOURNAL_PATH);
+        Self::at(root, path)
+    }
+
+    /// Journal for the workspace at `root`, stored at `path`.
+    pub fn at(root: impl Into<PathBuf>, path: impl Into<PathBuf>) -> Result<Self, JournalError> {
+        let journal = Self {
+            root: root.into(),
+            path: path.into(),
+            writable: None,
+            next_sequence: Mutex::new(1),
+        };
+        let next = journal
+            .records()?
+            .last()
+            .map(|record| record.sequence + 1)
+            .unwrap_or(1);
+        *journal.next_sequence.lock().unwrap() = next;
+        Ok(journal)
+    }
+
+    /// Only accept changes under the token's `read_write` storage roots.
+    pub fn with_token(mut self, token: &CapabilityToken) -> Self {
+        self.writable = Some(
+            token
+                .storage_roots
+                .iter()
+                .filter(|root| root.mode == StorageMode::ReadWrite)
+                .filter_map(|root| normalize(&self.root.join(&root.path)))
+                .collect(),
+        );
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write `contents` to `path`, creating parent directories.
+    pub fn write(
+        &self,
+        actor: &str,
+        path: impl AsRef<Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> Result<ChangeRecord, JournalError> {
+        let (absolute, relative) = self.authorize(actor, path.as_ref())?;
+        let hash_before = hash_file(&absolute)?;
+        if let Some(parent) = absolute.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&absolute, contents.as_ref())?;
+        let hash_after = Some(hash_bytes(contents.as_ref()));
+        self.append(
+            actor,
+            ChangeKind::Write,
+            relative,
+            None,
+            hash_before,
+            hash_after,
+        )
+    }
+
+    /// Rename `from` to `to`; both must be writable by `actor`.
+    pub fn rename(
+        &self,
+        actor: &str,
+        from: impl AsRef<Path>,
+        to: impl AsRef<Path>,
+    ) -> Result<ChangeRecord, JournalError> {
+        let (source, source_relative) = self.authorize(actor, from.as_ref())?;
+        let (target, target_relative) = self.authorize(actor, to.as_ref())?;
+        let hash = hash_file(&source)?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&source, &target)?;
+        self.append(
+            actor,
+            ChangeKind::Rename,
+            target_relative,
+            Some(source_relative),
+            hash.clone(),
+            hash,
+        )
+    }
+
+    /// Delete the file at `path`.
+    pub fn remove(
+        &self,
+        actor: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<ChangeRecord, JournalError> {
+        let (absolute, relative) = self.authorize(actor, path.as_ref())?;
+        let hash_before = hash_file(&absolute)?;
+        fs::remove_file(&absolute)?;
+        self.append(actor, ChangeKind::Delete, relative, None, hash_before, None)
+    }
+
+    /// Every journaled change, oldest first.
+    pub fn records(&self) -> Result<Vec<ChangeRecord>, JournalError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let recovered = recovery::parse_jsonl(&fs::read(&self.path)?, RecoveryMode::Strict)?;
+        Ok(recovered.records)
+    }
+
+    /// Paths touched by changes after `sequence` (0 for the whole journal).
+    pub fn changes_since(&self, sequence: u64) -> Result<ChangeSet, JournalError> {
+        let mut changes = ChangeSet {
+            last_sequence: sequence,
+            ..ChangeSet::default()
+        };
+        for record in self.records()? {
+            if record.sequence <= sequence {
+                continue;
+            }
+            if let Some(source) = &record.renamed_from {
+                changes.remove(source);
+            }
+            match record.kind {
+                ChangeKind::Write | ChangeKind::Rename => changes.update(&record.path),
+                ChangeKind::Delete => changes.remove(&record.path),
+            }
+            changes.last_sequence = record.sequence;
+        }
+        Ok(changes)
+    }
+
+    /// Changes that wrote, renamed, or deleted `path`, oldest first.
+    pub fn history(&self, path: impl AsRef<Path>) -> Result<Vec<ChangeRecord>, JournalError> {
+        let relative = self.relative(path.as_ref())?;
+        Ok(self
+            .records()?
+            .into_iter()
+            .filter(|record| {
+                record.path == relative || record.renamed_from.as_deref() == Some(&relative)
+            })
+            .collect())
+    }
+
+    /// Actor behind the latest change to `path`.
+    pub fn last_modified_by(&self, path: impl AsRef<Path>) -> Result<Option<String>, JournalError> {
+        Ok(self.history(path)?.pop().map(|record| record.actor))
+    }
+
+    fn relative(&self, path: &Path) -> Result<String, JournalError> {
+        let root = normalize(&self.root).unwrap_or_default();
+        normalize(&self.root.join(path))
+            .and_then(|absolute| {
+                absolute
+                    .strip_prefix(&root)
+                    .ok()
+                    .filter(|relative| !relative.as_os_str().is_empty())
+                    .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+            })
+            .ok_or_else(|| JournalError::OutsideWorkspace(path.display().to_string()))
+    }
+
+    fn authorize(&self, actor: &str, path: &Path) -> Result<(PathBuf, String), JournalError> {
+        let relative = self.relative(path)?;
+        let absolute = normalize(&self.root.join(&relative)).unwrap_or_default();
+        if let Some(writable) = &self.writable {
+            if !writable.iter().any(|root| absolute.starts_with(root)) {
+                return Err(JournalError::Denied {
+                    actor: actor.to_string(),
+                    path: relative,
+                });
+            }
+        }
+        Ok((absolute, relative))
+    }
+
+    fn append(
+        &self,
+        actor: &str,
+        kind: ChangeKind,
+        path: String,
+        renamed_from: Option<String>,
+        hash_before: Option<String>,
+        hash_after: Option<String>,
+    ) -> Result<ChangeRecord, JournalError> {
+        let operation_kind = match kind {
+            ChangeKind::Write => OperationKind::FileWrite,
+            ChangeKind::Rename => OperationKind::FileMove,
+            ChangeKind::Delete => OperationKind::FileDelete,
+        };
+        let operation = security::enforce_operation(
+            OperationRecord::new(operation_kind, actor, JOURNAL_SCOPE)
+                .with_context(renamed_from.clone(), Some(path.clone()))
+                .with_metadata(json!({
+                    "hash_before": hash_before,
+                    "hash_after": hash_after,
+                })),
+        )
+        .map_err(|err| JournalError::Audit(err.to_string()))?;
+
+        let mut next_sequence = self.next_sequence.lock().unwrap();
+        let record = ChangeRecord {
+            sequence: *next_sequence,
+            timestamp: current_timestamp_millis(),
+            actor: actor.to_string(),
+            kind,
+            path,
+            renamed_from,
+            hash_before,
+            hash_after,
+            operation_id: operation.record.operation_id,
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        *next_sequence += 1;
+        Ok(record)
+    }
+}
+
+fn hash_bytes(bytes: &[u8]) -> String {
+    simple_hash(&String::from_utf8_lossy(bytes))
+}
+
+fn hash_file(path: &Path) -> Result<Option<String>, JournalError> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(hash_bytes(&bytes))),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Resolve `.` and `..` lexically, as the path may not exist yet; `None` when `..`
+/// climbs above the start of the path.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::profile::{EgressMode, StorageRoot};
+    use tempfile::tempdir;
+
+    fn token(roots: &[(&str, StorageMode)]) -> CapabilityToken {
+        CapabilityToken {
+            profile_name: "test".to_string(),
+            description: String::new(),
+            version: None,
+            allowed_tools: Vec::new(),
+            denied_tools: Vec::new(),
+            egress_mode: EgressMode::Denied,
+            allowed_egress_destinations: Vec::new(),
+            egress_notes: None,
+            cpu_budget: None,
+            memory_budget: None,
+            network_budget: None,
+            storage_roots: roots
+                .iter()
+                .map(|(path, mode)| StorageRoot {
+                    name: path.to_string(),
+                    path: path.to_string(),
+                    mode: *mode,
+                    quota_mb: None,
+                })
+                .collect(),
+            issued_at_ms: 0,
+            expires_at_ms: u128::MAX,
+        }
+    }
+
+    #[test]
+    fn changes_are_attributed_and_summarised_for_incremental_consumers() {
+        let dir = tempdir().unwrap();
+        let journal = ChangeJournal::open(dir.path()).unwrap();
+
+        let created = journal
+            .write("agent-a", "src/lib.rs", "pub fn a() {}")
+            .unwrap();
+        assert_eq!(created.hash_before, None);
+        let edited = journal
+            .write("agent-b", "src/lib.rs", "pub fn b() {}")
+            .unwrap();
+        assert_eq!(edited.hash_before, created.hash_after);
+        journal.write("agent-a", "src/old.rs", "").unwrap();
+        let cursor = journal.changes_since(0).unwrap().last_sequence;
+        assert_eq!(cursor, 3);
+
+        journal
+            .rename("agent-c", "src/old.rs", "src/new.rs")
+            .unwrap();
+        journal.remove("agent-a", "src/lib.rs").unwrap();
+        assert!(matches!(
+            journal.write("agent-a", "../escape.rs", ""),
+            Err(JournalError::OutsideWorkspace(_))
+        ));
+
+        let changes = journal.changes_since(cursor).unwrap();
+        assert_eq!(changes.updated, BTreeSet::from(["src/new.rs".to_string()]));
+        assert_eq!(
+            changes.removed,
+            BTreeSet::from(["src/lib.rs".to_string(), "src/old.rs".to_string()])
+        );
+        assert_eq!(changes.last_sequence, 5);
+
+        let reopened = ChangeJournal::open(dir.path()).unwrap();
+        assert_eq!(
+            reopened.last_modified_by("src/new.rs").unwrap().as_deref(),
+            Some("agent-c")
+        );
+        let history = reopened.history("src/lib.rs").unwrap();
+        assert_eq!(
+            history.iter().map(|r| r.actor.as_str()).collect::<Vec<_>>(),
+            ["agent-a", "agent-b", "agent-a"]
+        );
+        let audited = security::audit_trail()
+            .into_iter()
+            .find(|op| op.record.operation_id == history[1].operation_id)
+            .expect("change signed into the audit trail");
+        assert_eq!(audited.record.actor, "agent-b");
+        assert_eq!(reopened.write("agent-a", "x.rs", "").unwrap().sequence, 6);
+    }
+
+    #[test]
+    fn tokens_confine_changes_to_read_write_roots() {
+        let dir = tempdir().unwrap();
+        let journal = ChangeJournal::open(dir.path())
+            .unwrap()
+            .with_token(&token(&[
+                ("sandbox", StorageMode::ReadWrite),
+                ("docs", StorageMode::ReadOnly),
+            ]));
+
+        journal.write("agent", "sandbox/out.txt", "ok").unwrap();
+        assert!(matches!(
+            journal.write("agent", "docs/readme.md", "no"),
+            Err(JournalError::Denied { .. })
+        ));
+        assert!(matches!(
+            journal.rename("agent", "sandbox/out.txt", "docs/out.txt"),
+            Err(JournalError::Denied { .. })
+        ));
+        assert!(dir.path().join("sandbox/out.txt").exists());
+        assert_eq!(journal.records().unwrap().len(), 1);
+    }
+}
@@ -19,6 +19,17 @@ pub struct MemoryProfile {
     pub available_bytes: u64,
 }
 
+impl MemoryProfile {
+    /// Fraction of physical memory in use, between 0.0 and 1.0.
+    pub fn pressure(&self) -> f64 {
+        if self.total_bytes == 0 {
+            0.0
+        } else {
+            1.0 - self.available_bytes.min(self.total_bytes) as f64 / self.total_bytes as f64
+        }
+    }
+}
+
 /// GPU family identifiers used to drive backend selection.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum GpuBackend {
@@ -112,6 +123,16 @@ impl HardwareProfile {
     }
 }
 
+/// Coarse host tier used to size runtime plans and concurrency limits.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HostClassification {
+    Minimal,
+    #[default]
+    Standard,
+    Accelerated,
+}
+
 /// Detect hardware capabilities on the current host.
 pub fn detect_hardware_profile() -> HardwareProfile {
     let mut system = System::new_all();
@@ -203,6 +224,62 @@ fn query_nvidia_smi() -> Vec<GpuProfile> {
     gpus
 }
 
+/// Point-in-time memory use of one GPU, refreshed separately from the static profile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GpuMemorySample {
+    pub index: usize,
+    pub name: String,
+    pub used_bytes: u64,
+    pub total_bytes: u64,
+}
+
+impl GpuMemorySample {
+    /// Fraction of device memory in use, between 0.0 and 1.0.
+    pub fn pressure(&self) -> f64 {
+        if self.total_bytes == 0 {
+            0.0
+        } else {
+            self.used_bytes as f64 / self.total_bytes as f64
+        }
+    }
+}
+
+/// Sample current GPU memory use. Hosts without a supported GPU report nothing.
+pub fn sample_gpu_memory() -> Vec<GpuMemorySample> {
+    let output = Command::new("nvidia-smi")
+        .args([
+            "--query-gpu=index,name,memory.used,memory.total",
+            "--format=csv,noheader,nounits",
+        ])
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_gpu_memory_csv(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn parse_gpu_memory_csv(stdout: &str) -> Vec<GpuMemorySample> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<_> = line.split(',').map(|s| s.trim()).collect();
+            if parts.len() < 4 {
+                return None;
+            }
+            let mib = |value: &str| value.parse::<u64>().ok().map(|mb| mb * 1024 * 1024);
+            Some(GpuMemorySample {
+                index: parts[0].parse().ok()?,
+                name: parts[1].to_string(),
+                used_bytes: mib(parts[2])?,
+                total_bytes: mib(parts[3])?,
+            })
+        })
+        .collect()
+}
+
 fn detect_accelerators(gpus: &[GpuProfile]) -> Vec<AcceleratorProfile> {
     let mut accelerators = Vec::new();
 
@@ -260,6 +337,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn gpu_memory_samples_parse_nvidia_smi_csv() {
+        let samples =
+            parse_gpu_memory_csv("0, NVIDIA A10, 18432, 24576\n\n1, NVIDIA A10, n/a, 24576\n");
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].index, 0);
+        assert_eq!(samples[0].used_bytes, 18432 * 1024 * 1024);
+        assert!((samples[0].pressure() - 0.75).abs() < f64::EPSILON);
+    }
+
     #[test]
     fn hardware_profile_memory_helpers() {
         let profile = HardwareProfile {
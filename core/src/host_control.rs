@@ -1,12 +1,36 @@
-//! Host control surface enabling environment takeover and resource arbitration.
+//! Host control surface enabling environment takeover, resource arbitration,
+//! installing the NOA stack as system services (see [`ServicePlan`]), and updating
+//! its binaries (see [`SelfUpdater`]).
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Mutex, OnceLock};
 
-use crate::config::manifest::{SCOPE_HOST_ENVIRONMENT_TAKEOVER, SCOPE_HOST_RESOURCE_ARBITRATE};
+use crate::config::manifest::{
+    SCOPE_HOST_ENVIRONMENT_TAKEOVER, SCOPE_HOST_RESOURCE_ARBITRATE, SCOPE_HOST_SERVICE_INSTALL,
+    SCOPE_HOST_UPDATE,
+};
 use crate::time::current_timestamp_millis;
 use crate::token::{self, TokenError};
 
+mod lifecycle;
+mod update;
+
+#[cfg(windows)]
+pub use lifecycle::run_windows_service;
+pub use lifecycle::{
+    install, next_signal, notify, service_name, supervise, uninstall, LifecycleSignal,
+    RuntimeGraph, RuntimeGraphService, ServiceCommandRunner, ServiceDefinition, ServiceHooks,
+    ServiceInstallConfig, ServiceInstallReport, ServiceManagerKind, ServicePlan,
+    SystemCommandRunner, DEFAULT_STOP_TIMEOUT_SECS, DEFAULT_SYSTEMD_UNIT_DIR, NOTIFY_READY,
+    NOTIFY_RELOADING, NOTIFY_STOPPING, STACK_TARGET,
+};
+pub use update::{
+    DirectorySource, InstalledRelease, ReleaseBinary, ReleaseManifest, ReleaseSource, SelfUpdater,
+    Slot, UpdateError, UpdateHealthCheck, UpdateOutcome, RELEASE_MANIFEST_FILE,
+    RELEASE_SIGNATURE_FILE,
+};
+
 /// Lease describing a token-bound environment takeover.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EnvironmentLease {
@@ -45,6 +69,18 @@ pub enum HostControlError {
     EnvironmentNotLeased(String),
     #[error("environment {0} isolated from token")]
     EnvironmentIsolationViolation(String),
+    #[error("invalid runtime graph: {0}")]
+    ServiceGraph(String),
+    #[error("service command `{command}` failed: {reason}")]
+    ServiceCommand { command: String, reason: String },
+    #[error("service file {path}: {source}")]
+    ServiceFile {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Update(#[from] UpdateError),
 }
 
 #[derive(Debug, Default)]
@@ -141,6 +177,51 @@ impl HostControlService {
         })
     }
 
+    /// Install the services of `plan` with the host service manager.
+    pub fn install_services(
+        &self,
+        token: &str,
+        plan: &ServicePlan,
+        runner: &dyn ServiceCommandRunner,
+    ) -> Result<ServiceInstallReport, HostControlError> {
+        token::service().validate(token, SCOPE_HOST_SERVICE_INSTALL)?;
+        install(plan, runner)
+    }
+
+    /// Stop and remove the services of `plan`.
+    pub fn uninstall_services(
+        &self,
+        token: &str,
+        plan: &ServicePlan,
+        runner: &dyn ServiceCommandRunner,
+    ) -> Result<ServiceInstallReport, HostControlError> {
+        token::service().validate(token, SCOPE_HOST_SERVICE_INSTALL)?;
+        uninstall(plan, runner)
+    }
+
+    /// Install the release published at `source` into the updater's inactive slot,
+    /// rolling back if `health` rejects it.
+    pub fn apply_update(
+        &self,
+        token: &str,
+        updater: &SelfUpdater,
+        source: &dyn ReleaseSource,
+        health: &dyn UpdateHealthCheck,
+    ) -> Result<UpdateOutcome, HostControlError> {
+        token::service().validate(token, SCOPE_HOST_UPDATE)?;
+        Ok(updater.apply(source, health)?)
+    }
+
+    /// Make the updater's previous slot active again.
+    pub fn rollback_update(
+        &self,
+        token: &str,
+        updater: &SelfUpdater,
+    ) -> Result<InstalledRelease, HostControlError> {
+        token::service().validate(token, SCOPE_HOST_UPDATE)?;
+        Ok(updater.rollback()?)
+    }
+
     /// Enumerate active leases.
     pub fn active_leases(&self) -> Vec<EnvironmentLease> {
         let store = self.store.lock().expect("lease store mutex poisoned");
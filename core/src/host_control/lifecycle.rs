@@ -0,0 +1,793 @@
+//! Service manager integration for the NOA stack.
+//!
+//! [`ServicePlan::from_graph`] turns the kernel runtime graph
+//! (`runtime/kernel/graph.yaml`) into one systemd unit or Windows service per graph
+//! service, in boot order, depending on the services it `requires` (hard) and
+//! `optional` (soft, systemd only). [`install`] and [`uninstall`] apply a plan
+//! through a [`ServiceCommandRunner`]; `HostControlService::install_services`
+//! gates them behind the `host.service.install` scope.
+//!
+//! A running service reports readiness with [`notify`] (sd_notify) and hands
+//! [`supervise`] its [`ServiceHooks`], which maps reload requests (SIGHUP) onto
+//! `reload` and stop requests (SIGTERM, SIGINT, console close) onto `drain`. On
+//! Windows, `run_windows_service` does the same for service control requests.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use super::HostControlError;
+use crate::hardware::HostClassification;
+
+/// Name of the systemd target grouping every NOA unit.
+pub const STACK_TARGET: &str = "noa-ark-os";
+/// Default directory for generated systemd units.
+pub const DEFAULT_SYSTEMD_UNIT_DIR: &str = "/etc/systemd/system";
+/// Seconds a service may spend draining before it is killed.
+pub const DEFAULT_STOP_TIMEOUT_SECS: u64 = 90;
+
+pub const NOTIFY_READY: &str = "READY=1";
+pub const NOTIFY_RELOADING: &str = "RELOADING=1";
+pub const NOTIFY_STOPPING: &str = "STOPPING=1";
+
+/// Service entries of the kernel runtime graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeGraph {
+    pub boot_order: Vec<String>,
+    pub services: Vec<RuntimeGraphService>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeGraphService {
+    pub id: String,
+    #[serde(default)]
+    pub requires: Vec<String>,
+    #[serde(default)]
+    pub optional: Vec<String>,
+    /// Host classes able to run the service; empty means every class.
+    #[serde(default)]
+    pub supported_classes: Vec<HostClassification>,
+}
+
+impl RuntimeGraphService {
+    pub fn supports(&self, classification: &HostClassification) -> bool {
+        self.supported_classes.is_empty() || self.supported_classes.contains(classification)
+    }
+}
+
+impl RuntimeGraph {
+    pub fn service(&self, id: &str) -> Option<&RuntimeGraphService> {
+        self.services.iter().find(|service| service.id == id)
+    }
+
+    pub fn load_from_path(path: impl AsRef<Path>) -> Result<Self, HostControlError> {
+        let path = path.as_ref();
+        let raw = fs::read_to_string(path).map_err(|source| HostControlError::ServiceFile {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        serde_yaml::from_str(&raw)
+            .map_err(|err| HostControlError::ServiceGraph(format!("{}: {}", path.display(), err)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceManagerKind {
+    Systemd,
+    Windows,
+}
+
+impl ServiceManagerKind {
+    /// Service manager of the running platform, if one is supported.
+    pub fn current() -> Option<Self> {
+        if cfg!(target_os = "linux") {
+            Some(Self::Systemd)
+        } else if cfg!(windows) {
+            Some(Self::Windows)
+        } else {
+            None
+        }
+    }
+}
+
+/// How generated services are launched and where their definitions go.
+#[derive(Debug, Clone)]
+pub struct ServiceInstallConfig {
+    /// Binary started as `<binary> --service <id>` unless a command is set.
+    pub binary: PathBuf,
+    pub unit_dir: PathBuf,
+    pub user: Option<String>,
+    /// Environment for every unit (systemd only).
+    pub environment: BTreeMap<String, String>,
+    pub stop_timeout_secs: u64,
+    /// Command lines overriding the default for individual graph services.
+    pub commands: BTreeMap<String, String>,
+}
+
+impl ServiceInstallConfig {
+    pub fn new(binary: impl Into<PathBuf>) -> Self {
+        Self {
+            binary: binary.into(),
+            unit_dir: PathBuf::from(DEFAULT_SYSTEMD_UNIT_DIR),
+            user: None,
+            environment: BTreeMap::new(),
+            stop_timeout_secs: DEFAULT_STOP_TIMEOUT_SECS,
+            commands: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_unit_dir(mut self, unit_dir: impl Into<PathBuf>) -> Self {
+        self.unit_dir = unit_dir.into();
+        self
+    }
+
+    pub fn with_user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    pub fn with_environment(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.environment.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_stop_timeout_secs(mut self, secs: u64) -> Self {
+        self.stop_timeout_secs = secs;
+        self
+    }
+
+    pub fn with_command(mut self, service: impl Into<String>, command: impl Into<String>) -> Self {
+        self.commands.insert(service.into(), command.into());
+        self
+    }
+
+    fn command_for(&self, service: &str) -> String {
+        self.commands
+            .get(service)
+            .cloned()
+            .unwrap_or_else(|| format!("\"{}\" --service {}", self.binary.display(), service))
+    }
+}
+
+/// Service manager name of a graph service.
+pub fn service_name(service: &str) -> String {
+    format!("noa-{service}")
+}
+
+/// One graph service as a systemd unit or Windows service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceDefinition {
+    pub service_id: String,
+    pub name: String,
+    pub command: String,
+    /// Service names that must be running first.
+    pub requires: Vec<String>,
+    /// Service names started first when present.
+    pub wants: Vec<String>,
+}
+
+impl ServiceDefinition {
+    pub fn unit_file_name(&self) -> String {
+        format!("{}.service", self.name)
+    }
+
+    pub fn render_systemd_unit(&self, config: &ServiceInstallConfig) -> String {
+        let units = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| format!("{name}.service"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let mut after = self.requires.clone();
+        after.extend(self.wants.iter().cloned());
+
+        let mut unit = format!(
+            "[Unit]\nDescription=NOA ARK OS {}\nPartOf={STACK_TARGET}.target\n",
+            self.service_id
+        );
+        if !after.is_empty() {
+            unit.push_str(&format!("After={}\n", units(&after)));
+        }
+        if !self.requires.is_empty() {
+            unit.push_str(&format!("Requires={}\n", units(&self.requires)));
+        }
+        if !self.wants.is_empty() {
+            unit.push_str(&format!("Wants={}\n", units(&self.wants)));
+        }
+        unit.push_str(&format!(
+            "\n[Service]\nType=notify\nNotifyAccess=main\nExecStart={}\n\
+             ExecReload=/bin/kill -HUP $MAINPID\nKillSignal=SIGTERM\nTimeoutStopSec={}\n\
+             Restart=on-failure\n",
+            self.command, config.stop_timeout_secs
+        ));
+        if let Some(user) = &config.user {
+            unit.push_str(&format!("User={user}\n"));
+        }
+        unit.push_str(&format!(
+            "Environment=\"NOA_SERVICE_ID={}\"\n",
+            self.service_id
+        ));
+        for (key, value) in &config.environment {
+            unit.push_str(&format!("Environment=\"{key}={value}\"\n"));
+        }
+        unit.push_str(&format!("\n[Install]\nWantedBy={STACK_TARGET}.target\n"));
+        unit
+    }
+
+    /// Arguments to `sc.exe` creating the service. The service control manager
+    /// has no soft dependencies, so `wants` is not represented.
+    pub fn windows_create_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "create".to_string(),
+            self.name.clone(),
+            "binPath=".to_string(),
+            self.command.clone(),
+            "start=".to_string(),
+            "auto".to_string(),
+            "DisplayName=".to_string(),
+            format!("NOA ARK OS {}", self.service_id),
+        ];
+        if !self.requires.is_empty() {
+            args.push("depend=".to_string());
+            args.push(self.requires.join("/"));
+        }
+        args
+    }
+}
+
+/// Service definitions for every graph service, in boot order.
+#[derive(Debug, Clone)]
+pub struct ServicePlan {
+    pub manager: ServiceManagerKind,
+    pub config: ServiceInstallConfig,
+    pub services: Vec<ServiceDefinition>,
+}
+
+impl ServicePlan {
+    pub fn from_graph(
+        graph: &RuntimeGraph,
+        manager: ServiceManagerKind,
+        config: ServiceInstallConfig,
+    ) -> Result<Self, HostControlError> {
+        let by_id: BTreeMap<&str, &RuntimeGraphService> = graph
+            .services
+            .iter()
+            .map(|service| (service.id.as_str(), service))
+            .collect();
+        let mut ordered: Vec<&RuntimeGraphService> = Vec::new();
+        for id in &graph.boot_order {
+            let service = by_id.get(id.as_str()).ok_or_else(|| {
+                HostControlError::ServiceGraph(format!("boot order lists unknown service {id}"))
+            })?;
+            ordered.push(service);
+        }
+        for service in &graph.services {
+            if !graph.boot_order.contains(&service.id) {
+                ordered.push(service);
+            }
+        }
+
+        let mut services = Vec::new();
+        for service in ordered {
+            if let Some(missing) = service
+                .requires
+                .iter()
+                .find(|dependency| !by_id.contains_key(dependency.as_str()))
+            {
+                return Err(HostControlError::ServiceGraph(format!(
+                    "{} requires unknown service {}",
+                    service.id, missing
+                )));
+            }
+            services.push(ServiceDefinition {
+                service_id: service.id.clone(),
+                name: service_name(&service.id),
+                command: config.command_for(&service.id),
+                requires: service.requires.iter().map(|id| service_name(id)).collect(),
+                wants: service
+                    .optional
+                    .iter()
+                    .filter(|id| by_id.contains_key(id.as_str()))
+                    .map(|id| service_name(id))
+                    .collect(),
+            });
+        }
+        Ok(Self {
+            manager,
+            config,
+            services,
+        })
+    }
+
+    pub fn render_systemd_target(&self) -> String {
+        let units = self
+            .services
+            .iter()
+            .map(ServiceDefinition::unit_file_name)
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(
+            "[Unit]\nDescription=NOA ARK OS stack\nWants={units}\nAfter=network-online.target\n\
+             \n[Install]\nWantedBy=multi-user.target\n"
+        )
+    }
+
+    fn target_path(&self) -> PathBuf {
+        self.config.unit_dir.join(format!("{STACK_TARGET}.target"))
+    }
+
+    fn unit_path(&self, service: &ServiceDefinition) -> PathBuf {
+        self.config.unit_dir.join(service.unit_file_name())
+    }
+}
+
+/// Runs service manager commands (`systemctl`, `sc.exe`).
+pub trait ServiceCommandRunner {
+    fn run(&self, program: &str, args: &[String]) -> Result<(), String>;
+}
+
+/// Runs commands on the host.
+pub struct SystemCommandRunner;
+
+impl ServiceCommandRunner for SystemCommandRunner {
+    fn run(&self, program: &str, args: &[String]) -> Result<(), String> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|err| err.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
+
+/// Files written and commands run by [`install`] or [`uninstall`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceInstallReport {
+    pub files: Vec<PathBuf>,
+    pub commands: Vec<String>,
+}
+
+impl ServiceInstallReport {
+    fn run(
+        &mut self,
+        runner: &dyn ServiceCommandRunner,
+        program: &str,
+        args: Vec<String>,
+    ) -> Result<(), HostControlError> {
+        let command = format!("{program} {}", args.join(" "));
+        runner
+            .run(program, &args)
+            .map_err(|reason| HostControlError::ServiceCommand {
+                command: command.clone(),
+                reason,
+            })?;
+        self.commands.push(command);
+        Ok(())
+    }
+}
+
+/// Write and register every service in the plan and enable the stack.
+pub fn install(
+    plan: &ServicePlan,
+    runner: &dyn ServiceCommandRunner,
+) -> Result<ServiceInstallReport, HostControlError> {
+    let mut report = ServiceInstallReport::default();
+    match plan.manager {
+        ServiceManagerKind::Systemd => {
+            let unit_dir = &plan.config.unit_dir;
+            fs::create_dir_all(unit_dir).map_err(|source| HostControlError::ServiceFile {
+                path: unit_dir.clone(),
+                source,
+            })?;
+            let mut files: Vec<(PathBuf, String)> = plan
+                .services
+                .iter()
+                .map(|service| {
+                    (
+                        plan.unit_path(service),
+                        service.render_systemd_unit(&plan.config),
+                    )
+                })
+                .collect();
+            files.push((plan.target_path(), plan.render_systemd_target()));
+            for (path, contents) in files {
+                fs::write(&path, contents).map_err(|source| HostControlError::ServiceFile {
+                    path: path.clone(),
+                    source,
+                })?;
+                report.files.push(path);
+            }
+            report.run(runner, "systemctl", vec!["daemon-reload".into()])?;
+            report.run(
+                runner,
+                "systemctl",
+                vec!["enable".into(), format!("{STACK_TARGET}.target")],
+            )?;
+        }
+        ServiceManagerKind::Windows => {
+            for service in &plan.services {
+                report.run(runner, "sc.exe", service.windows_create_args())?;
+                report.run(
+                    runner,
+                    "sc.exe",
+                    vec![
+                        "failure".into(),
+                        service.name.clone(),
+                        "reset=".into(),
+                        "86400".into(),
+                        "actions=".into(),
+                        "restart/5000".into(),
+                    ],
+                )?;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Stop and remove every service in the plan.
+pub fn uninstall(
+    plan: &ServicePlan,
+    runner: &dyn ServiceCommandRunner,
+) -> Result<ServiceInstallReport, HostControlError> {
+    let mut report = ServiceInstallReport::default();
+    match plan.manager {
+        ServiceManagerKind::Systemd => {
+            report.run(
+                runner,
+                "systemctl",
+                vec![
+                    "disable".into(),
+                    "--now".into(),
+                    format!("{STACK_TARGET}.target"),
+                ],
+            )?;
+            let mut paths: Vec<PathBuf> = plan
+                .services
+                .iter()
+                .map(|service| plan.unit_path(service))
+                .collect();
+            paths.push(plan.target_path());
+            for path in paths {
+                match fs::remove_file(&path) {
+                    Ok(()) => report.files.push(path),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(source) => return Err(HostControlError::ServiceFile { path, source }),
+                }
+            }
+            report.run(runner, "systemctl", vec!["daemon-reload".into()])?;
+        }
+        ServiceManagerKind::Windows => {
+            for service in plan.services.iter().rev() {
+                // A service that is not running refuses to stop; deletion still applies.
+                let _ = report.run(runner, "sc.exe", vec!["stop".into(), service.name.clone()]);
+                report.run(
+                    runner,
+                    "sc.exe",
+                    vec!["delete".into(), service.name.clone()],
+                )?;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Send a state such as [`NOTIFY_READY`] to the service manager. Returns
+/// `Ok(false)` when the process was not started by systemd.
+pub fn notify(state: &str) -> std::io::Result<bool> {
+    match std::env::var_os("NOTIFY_SOCKET") {
+        Some(socket) => notify_socket(Path::new(&socket), state),
+        None => Ok(false),
+    }
+}
+
+#[cfg(unix)]
+fn notify_socket(socket: &Path, state: &str) -> std::io::Result<bool> {
+    use std::os::unix::net::UnixDatagram;
+
+    let datagram = UnixDatagram::unbound()?;
+    let raw = socket.to_string_lossy();
+    if let Some(name) = raw.strip_prefix('@') {
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            let address = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+            datagram.send_to_addr(state.as_bytes(), &address)?;
+            return Ok(true);
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = name;
+            return Ok(false);
+        }
+    }
+    datagram.send_to(state.as_bytes(), socket)?;
+    Ok(true)
+}
+
+#[cfg(not(unix))]
+fn notify_socket(_socket: &Path, _state: &str) -> std::io::Result<bool> {
+    Ok(false)
+}
+
+/// Callbacks a running service exposes to its service manager.
+pub trait ServiceHooks: Send + Sync {
+    /// Re-read configuration without restarting.
+    fn reload(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Stop accepting work and let in-flight work finish.
+    fn drain(&self) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleSignal {
+    Stop,
+    Reload,
+}
+
+/// Wait for the next stop or reload request.
+pub async fn next_signal() -> std::io::Result<LifecycleSignal> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        let mut hangup = signal(SignalKind::hangup())?;
+        tokio::select! {
+            _ = terminate.recv() => Ok(LifecycleSignal::Stop),
+            _ = interrupt.recv() => Ok(LifecycleSignal::Stop),
+            _ = hangup.recv() => Ok(LifecycleSignal::Reload),
+        }
+    }
+    #[cfg(windows)]
+    {
+        use tokio::signal::windows::{ctrl_c, ctrl_close, ctrl_shutdown};
+        let mut interrupt = ctrl_c()?;
+        let mut close = ctrl_close()?;
+        let mut shutdown = ctrl_shutdown()?;
+        tokio::select! {
+            _ = interrupt.recv() => Ok(LifecycleSignal::Stop),
+            _ = close.recv() => Ok(LifecycleSignal::Stop),
+            _ = shutdown.recv() => Ok(LifecycleSignal::Stop),
+        }
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        tokio::signal::ctrl_c().await?;
+        Ok(LifecycleSignal::Stop)
+    }
+}
+
+/// Report readiness, then serve reload requests until a stop request has been
+/// drained. The caller runs its shutdown path afterwards. Notifications that
+/// cannot be delivered are logged rather than stopping the service.
+pub async fn supervise(hooks: &dyn ServiceHooks) -> std::io::Result<()> {
+    let send = |state: &str| {
+        if let Err(err) = notify(state) {
+            eprintln!("[HOST_CONTROL] sd_notify {state} failed: {err}");
+        }
+    };
+    send(NOTIFY_READY);
+    loop {
+        match next_signal().await? {
+            LifecycleSignal::Reload => {
+                send(NOTIFY_RELOADING);
+                if let Err(err) = hooks.reload() {
+                    eprintln!("[HOST_CONTROL] reload failed: {err}");
+                    send(&format!("STATUS=reload failed: {err}"));
+                }
+                send(NOTIFY_READY);
+            }
+            LifecycleSignal::Stop => {
+                send(NOTIFY_STOPPING);
+                hooks.drain();
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use windows::run_windows_service;
+
+#[cfg(windows)]
+mod windows {
+    use std::ffi::OsString;
+    use std::sync::{mpsc, Arc, OnceLock};
+    use std::time::Duration;
+
+    use windows_service::service::{
+        ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus,
+        ServiceType,
+    };
+    use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+    use windows_service::{define_windows_service, service_dispatcher};
+
+    use super::ServiceHooks;
+
+    static SERVICE: OnceLock<(String, Arc<dyn ServiceHooks>)> = OnceLock::new();
+
+    define_windows_service!(ffi_service_main, service_main);
+
+    /// Hand the calling thread to the service control manager until the service
+    /// stops. Start the service's work on other threads first; stop requests
+    /// call `drain` and parameter changes call `reload`.
+    pub fn run_windows_service(
+        name: &str,
+        hooks: Arc<dyn ServiceHooks>,
+    ) -> windows_service::Result<()> {
+        let _ = SERVICE.set((name.to_string(), hooks));
+        service_dispatcher::start(name, ffi_service_main)
+    }
+
+    fn service_main(_arguments: Vec<OsString>) {
+        let Some((name, hooks)) = SERVICE.get() else {
+            return;
+        };
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let reload_hooks = Arc::clone(hooks);
+        let handler = move |control| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                let _ = stop_tx.send(());
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::ParamChange => {
+                if let Err(err) = reload_hooks.reload() {
+                    eprintln!("[HOST_CONTROL] reload failed: {err}");
+                }
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        };
+        let Ok(status) = service_control_handler::register(name, handler) else {
+            return;
+        };
+        let report = |state, controls_accepted| {
+            let _ = status.set_service_status(ServiceStatus {
+                service_type: ServiceType::OWN_PROCESS,
+                current_state: state,
+                controls_accepted,
+                exit_code: ServiceExitCode::Win32(0),
+                checkpoint: 0,
+                wait_hint: Duration::from_secs(30),
+                process_id: None,
+            });
+        };
+
+        report(
+            ServiceState::Running,
+            ServiceControlAccept::STOP
+                | ServiceControlAccept::SHUTDOWN
+                | ServiceControlAccept::PARAM_CHANGE,
+        );
+        let _ = stop_rx.recv();
+        report(ServiceState::StopPending, ServiceControlAccept::empty());
+        hooks.drain();
+        report(ServiceState::Stopped, ServiceControlAccept::empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingRunner {
+        commands: Mutex<Vec<String>>,
+    }
+
+    impl ServiceCommandRunner for RecordingRunner {
+        fn run(&self, program: &str, args: &[String]) -> Result<(), String> {
+            self.commands
+                .lock()
+                .unwrap()
+                .push(format!("{program} {}", args.join(" ")));
+            Ok(())
+        }
+    }
+
+    fn graph() -> RuntimeGraph {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../runtime/kernel/graph.yaml");
+        RuntimeGraph::load_from_path(path).expect("runtime graph fixture loads")
+    }
+
+    #[test]
+    fn systemd_plan_installs_units_from_the_runtime_graph() {
+        let units = tempfile::tempdir().unwrap();
+        let config = ServiceInstallConfig::new("/opt/noa/bin/noa_kernel")
+            .with_unit_dir(units.path())
+            .with_user("noa")
+            .with_environment("NOA_WORKFLOW_ROOT", "/var/lib/noa");
+        let plan = ServicePlan::from_graph(&graph(), ServiceManagerKind::Systemd, config).unwrap();
+        assert_eq!(plan.services[0].name, "noa-kernel");
+
+        let gateway = plan
+            .services
+            .iter()
+            .find(|service| service.service_id == "gateway")
+            .unwrap();
+        let unit = gateway.render_systemd_unit(&plan.config);
+        assert!(unit.contains("Requires=noa-adaptive-runtime.service\n"));
+        assert!(unit.contains("Wants=noa-observability.service\n"));
+        assert!(unit.contains("Type=notify\n"));
+        assert!(unit.contains("ExecStart=\"/opt/noa/bin/noa_kernel\" --service gateway\n"));
+        assert!(unit.contains("ExecReload=/bin/kill -HUP $MAINPID\n"));
+        assert!(unit.contains("Environment=\"NOA_WORKFLOW_ROOT=/var/lib/noa\"\n"));
+
+        let runner = RecordingRunner::default();
+        let report = install(&plan, &runner).unwrap();
+        assert_eq!(report.files.len(), plan.services.len() + 1);
+        let target = fs::read_to_string(units.path().join("noa-ark-os.target")).unwrap();
+        assert!(target.contains("noa-gateway.service"));
+        assert_eq!(
+            report.commands,
+            [
+                "systemctl daemon-reload",
+                "systemctl enable noa-ark-os.target"
+            ]
+        );
+
+        let removed = uninstall(&plan, &runner).unwrap();
+        assert_eq!(removed.files.len(), report.files.len());
+        assert!(fs::read_dir(units.path()).unwrap().next().is_none());
+
+        let windows = ServicePlan::from_graph(
+            &graph(),
+            ServiceManagerKind::Windows,
+            ServiceInstallConfig::new(r"C:\NOA\noa_kernel.exe"),
+        )
+        .unwrap();
+        let gateway = windows
+            .services
+            .iter()
+            .find(|service| service.service_id == "gateway")
+            .unwrap();
+        let args = gateway.windows_create_args();
+        assert_eq!(args[..2], ["create", "noa-gateway"]);
+        assert_eq!(args[args.len() - 2..], ["depend=", "noa-adaptive-runtime"]);
+    }
+
+    #[test]
+    fn plans_reject_unknown_dependencies() {
+        let graph = RuntimeGraph {
+            boot_order: vec!["gateway".into()],
+            services: vec![RuntimeGraphService {
+                id: "gateway".into(),
+                requires: vec!["kernel".into()],
+                optional: vec![],
+                supported_classes: vec![],
+            }],
+        };
+        let err = ServicePlan::from_graph(
+            &graph,
+            ServiceManagerKind::Systemd,
+            ServiceInstallConfig::new("noa"),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("requires unknown service kernel"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn notify_sends_state_to_the_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notify.sock");
+        let socket = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+        assert!(notify_socket(&path, NOTIFY_READY).unwrap());
+        let mut buffer = [0u8; 32];
+        let read = socket.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..read], NOTIFY_READY.as_bytes());
+    }
+}
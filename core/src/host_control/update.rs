@@ -0,0 +1,606 @@
+//! Self-update of the NOA binaries through blue/green slots.
+//!
+//! Binaries live in `<root>/slots/blue` and `<root>/slots/green`; `<root>/active`
+//! names the slot services run from, and on Unix `<root>/current` links to it so
+//! service units can start `<root>/current/<binary>`. [`SelfUpdater::apply`]
+//! fetches a [`ReleaseManifest`] and its signature from a [`ReleaseSource`],
+//! checks the signature against the policy secret and the host's rollout cohort,
+//! downloads every binary into the inactive slot, verifies its sha256, and swaps
+//! the slots. If the [`UpdateHealthCheck`] then fails, the previous slot is made
+//! active again and the outcome is [`UpdateOutcome::RolledBack`].
+//!
+//! Releases are signed with [`ReleaseManifest::sign`], which registers a policy
+//! operation covering the manifest's sha256, the same way evidence bundles are.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::security::{self, OperationKind, OperationRecord, SignedOperation};
+
+/// Manifest file name, relative to the release source.
+pub const RELEASE_MANIFEST_FILE: &str = "release.json";
+/// Signature over [`RELEASE_MANIFEST_FILE`], relative to the release source.
+pub const RELEASE_SIGNATURE_FILE: &str = "release.sig.json";
+const ACTIVE_FILE: &str = "active";
+const CURRENT_LINK: &str = "current";
+const SLOTS_DIR: &str = "slots";
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateError {
+    #[error("failed to fetch {path}: {reason}")]
+    Fetch { path: String, reason: String },
+    #[error("invalid release manifest: {0}")]
+    Manifest(String),
+    #[error("release signature rejected: {0}")]
+    Signature(String),
+    #[error("{binary} sha256 {actual} does not match the release manifest ({expected})")]
+    Digest {
+        binary: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("update file {path}: {source}")]
+    File {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("no previous slot to roll back to")]
+    NothingToRollBack,
+}
+
+/// One binary of a release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseBinary {
+    pub name: String,
+    /// Location relative to the release source.
+    pub path: String,
+    pub sha256: String,
+}
+
+/// Published description of a release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    pub version: String,
+    /// Commit and pipeline the binaries were built from.
+    pub commit_sha: String,
+    #[serde(default)]
+    pub pipeline_id: Option<String>,
+    /// Share of hosts, by cohort, offered the release; raised as the rollout proceeds.
+    #[serde(default = "full_rollout")]
+    pub rollout_percent: u8,
+    pub binaries: Vec<ReleaseBinary>,
+}
+
+fn full_rollout() -> u8 {
+    100
+}
+
+impl ReleaseManifest {
+    /// Sign the manifest's canonical JSON, returning the manifest bytes to publish
+    /// as [`RELEASE_MANIFEST_FILE`] and the signature for [`RELEASE_SIGNATURE_FILE`].
+    pub fn sign(&self) -> Result<(Vec<u8>, SignedOperation), UpdateError> {
+        let bytes = serde_json::to_vec_pretty(self)
+            .map_err(|err| UpdateError::Manifest(err.to_string()))?;
+        let record = OperationRecord::new(
+            OperationKind::Other,
+            "host_control",
+            format!("release::{}", self.version),
+        )
+        .with_metadata(json!({
+            "version": self.version,
+            "commit_sha": self.commit_sha,
+            "manifest_sha256": sha256_hex(&bytes),
+        }));
+        let signed = security::enforce_operation(record)
+            .map_err(|err| UpdateError::Signature(err.to_string()))?;
+        Ok((bytes, signed))
+    }
+
+    /// Parse manifest bytes after checking that `signature` covers them and verifies.
+    pub fn verify(bytes: &[u8], signature: &SignedOperation) -> Result<Self, UpdateError> {
+        let signed_digest = signature
+            .record
+            .metadata
+            .get("manifest_sha256")
+            .and_then(|value| value.as_str());
+        if signed_digest != Some(sha256_hex(bytes).as_str()) {
+            return Err(UpdateError::Signature(
+                "signature does not cover this manifest".to_string(),
+            ));
+        }
+        if !security::verify_signed_operation(signature) {
+            return Err(UpdateError::Signature(
+                "signature does not verify against the policy secret".to_string(),
+            ));
+        }
+        let manifest: Self =
+            serde_json::from_slice(bytes).map_err(|err| UpdateError::Manifest(err.to_string()))?;
+        if manifest.binaries.is_empty() {
+            return Err(UpdateError::Manifest(
+                "release lists no binaries".to_string(),
+            ));
+        }
+        if let Some(binary) = manifest
+            .binaries
+            .iter()
+            .find(|binary| Path::new(&binary.name).components().count() != 1)
+        {
+            return Err(UpdateError::Manifest(format!(
+                "binary name {} is not a file name",
+                binary.name
+            )));
+        }
+        Ok(manifest)
+    }
+}
+
+/// Where releases are published, e.g. a mirror directory or an HTTP server.
+pub trait ReleaseSource {
+    fn fetch(&self, path: &str) -> Result<Vec<u8>, String>;
+}
+
+/// Release published to a local or mounted directory.
+pub struct DirectorySource {
+    root: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl ReleaseSource for DirectorySource {
+    fn fetch(&self, path: &str) -> Result<Vec<u8>, String> {
+        fs::read(self.root.join(path)).map_err(|err| err.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Slot {
+    Blue,
+    Green,
+}
+
+impl Slot {
+    pub fn name(self) -> &'static str {
+        match self {
+            Slot::Blue => "blue",
+            Slot::Green => "green",
+        }
+    }
+
+    pub fn other(self) -> Self {
+        match self {
+            Slot::Blue => Slot::Green,
+            Slot::Green => Slot::Blue,
+        }
+    }
+}
+
+/// The release installed in a slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledRelease {
+    pub slot: Slot,
+    pub dir: PathBuf,
+    pub manifest: ReleaseManifest,
+}
+
+impl InstalledRelease {
+    pub fn binary_path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
+
+/// Decides whether a freshly activated release is healthy, typically by restarting
+/// the stack's services and probing them.
+pub trait UpdateHealthCheck {
+    fn check(&self, release: &InstalledRelease) -> Result<(), String>;
+}
+
+impl<F> UpdateHealthCheck for F
+where
+    F: Fn(&InstalledRelease) -> Result<(), String>,
+{
+    fn check(&self, release: &InstalledRelease) -> Result<(), String> {
+        self(release)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    /// The active slot already runs this version.
+    UpToDate {
+        version: String,
+    },
+    /// The host's cohort is outside the release's rollout percentage.
+    NotInRollout {
+        version: String,
+        cohort: u8,
+        rollout_percent: u8,
+    },
+    Applied {
+        version: String,
+        slot: Slot,
+    },
+    /// The release failed its health check and the previous slot is active again.
+    RolledBack {
+        version: String,
+        restored: Option<Slot>,
+        reason: String,
+    },
+}
+
+/// Installs releases into the blue/green slots under `root`.
+pub struct SelfUpdater {
+    root: PathBuf,
+    host_id: String,
+}
+
+impl SelfUpdater {
+    /// Updater for `root`, placing this host in a rollout cohort by host name.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            host_id: sysinfo::System::host_name().unwrap_or_else(|| "localhost".to_string()),
+        }
+    }
+
+    pub fn with_host_id(mut self, host_id: impl Into<String>) -> Self {
+        self.host_id = host_id.into();
+        self
+    }
+
+    pub fn slot_dir(&self, slot: Slot) -> PathBuf {
+        self.root.join(SLOTS_DIR).join(slot.name())
+    }
+
+    /// The slot services run from, if a release has been installed.
+    pub fn active(&self) -> Result<Option<InstalledRelease>, UpdateError> {
+        let path = self.root.join(ACTIVE_FILE);
+        let slot = match fs::read_to_string(&path) {
+            Ok(raw) => match raw.trim() {
+                "blue" => Slot::Blue,
+                "green" => Slot::Green,
+                other => {
+                    return Err(UpdateError::Manifest(format!(
+                        "{} names unknown slot {other}",
+                        path.display()
+                    )))
+                }
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(UpdateError::File { path, source }),
+        };
+        self.installed(slot)
+    }
+
+    /// Position of this host in `version`'s rollout, from 0 to 99.
+    pub fn cohort(&self, version: &str) -> u8 {
+        let digest = Sha256::digest(format!("{}:{version}", self.host_id));
+        (u64::from_be_bytes(digest[..8].try_into().expect("sha256 has 8 bytes")) % 100) as u8
+    }
+
+    /// Fetch and verify the published manifest.
+    pub fn fetch_manifest(
+        &self,
+        source: &dyn ReleaseSource,
+    ) -> Result<ReleaseManifest, UpdateError> {
+        let bytes = fetch(source, RELEASE_MANIFEST_FILE)?;
+        let signature: SignedOperation =
+            serde_json::from_slice(&fetch(source, RELEASE_SIGNATURE_FILE)?)
+                .map_err(|err| UpdateError::Signature(err.to_string()))?;
+        ReleaseManifest::verify(&bytes, &signature)
+    }
+
+    /// Stage the published release in the inactive slot, activate it, and roll back
+    /// if `health` rejects it.
+    pub fn apply(
+        &self,
+        source: &dyn ReleaseSource,
+        health: &dyn UpdateHealthCheck,
+    ) -> Result<UpdateOutcome, UpdateError> {
+        let manifest = self.fetch_manifest(source)?;
+        let active = self.active()?;
+        if active
+            .as_ref()
+            .is_some_and(|release| release.manifest.version == manifest.version)
+        {
+            return Ok(UpdateOutcome::UpToDate {
+                version: manifest.version,
+            });
+        }
+        let cohort = self.cohort(&manifest.version);
+        if cohort >= manifest.rollout_percent {
+            return Ok(UpdateOutcome::NotInRollout {
+                version: manifest.version,
+                cohort,
+                rollout_percent: manifest.rollout_percent,
+            });
+        }
+
+        let previous = active.map(|release| release.slot);
+        let staging = previous.map(Slot::other).unwrap_or(Slot::Blue);
+        let staged = self.stage(source, &manifest, staging)?;
+        self.activate(staging)?;
+        match health.check(&staged) {
+            Ok(()) => Ok(UpdateOutcome::Applied {
+                version: manifest.version,
+                slot: staging,
+            }),
+            Err(reason) => {
+                match previous {
+                    Some(slot) => self.activate(slot)?,
+                    None => self.deactivate()?,
+                }
+                Ok(UpdateOutcome::RolledBack {
+                    version: manifest.version,
+                    restored: previous,
+                    reason,
+                })
+            }
+        }
+    }
+
+    /// Make the inactive slot active again, if it holds a release.
+    pub fn rollback(&self) -> Result<InstalledRelease, UpdateError> {
+        let active = self.active()?.ok_or(UpdateError::NothingToRollBack)?;
+        let previous = self
+            .installed(active.slot.other())?
+            .ok_or(UpdateError::NothingToRollBack)?;
+        self.activate(previous.slot)?;
+        Ok(previous)
+    }
+
+    fn installed(&self, slot: Slot) -> Result<Option<InstalledRelease>, UpdateError> {
+        let dir = self.slot_dir(slot);
+        let path = dir.join(RELEASE_MANIFEST_FILE);
+        let raw = match fs::read(&path) {
+            Ok(raw) => raw,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(UpdateError::File { path, source }),
+        };
+        let manifest =
+            serde_json::from_slice(&raw).map_err(|err| UpdateError::Manifest(err.to_string()))?;
+        Ok(Some(InstalledRelease {
+            slot,
+            dir,
+            manifest,
+        }))
+    }
+
+    fn stage(
+        &self,
+        source: &dyn ReleaseSource,
+        manifest: &ReleaseManifest,
+        slot: Slot,
+    ) -> Result<InstalledRelease, UpdateError> {
+        let dir = self.slot_dir(slot);
+        if dir.exists() {
+            fs::remove_dir_all(&dir).map_err(|source| UpdateError::File {
+                path: dir.clone(),
+                source,
+            })?;
+        }
+        fs::create_dir_all(&dir).map_err(|source| UpdateError::File {
+            path: dir.clone(),
+            source,
+        })?;
+        for binary in &manifest.binaries {
+            let bytes = fetch(source, &binary.path)?;
+            let actual = sha256_hex(&bytes);
+            if !actual.eq_ignore_ascii_case(&binary.sha256) {
+                return Err(UpdateError::Digest {
+                    binary: binary.name.clone(),
+                    expected: binary.sha256.clone(),
+                    actual,
+                });
+            }
+            let path = dir.join(&binary.name);
+            write(&path, &bytes)?;
+            make_executable(&path)?;
+        }
+        let payload = serde_json::to_vec_pretty(manifest)
+            .map_err(|err| UpdateError::Manifest(err.to_string()))?;
+        // Written last: a slot without a manifest is an incomplete stage, never a release.
+        write(&dir.join(RELEASE_MANIFEST_FILE), &payload)?;
+        Ok(InstalledRelease {
+            slot,
+            dir,
+            manifest: manifest.clone(),
+        })
+    }
+
+    fn activate(&self, slot: Slot) -> Result<(), UpdateError> {
+        let pointer = self.root.join(ACTIVE_FILE);
+        let staged = self.root.join(format!("{ACTIVE_FILE}.tmp"));
+        write(&staged, slot.name().as_bytes())?;
+        rename(&staged, &pointer)?;
+        #[cfg(unix)]
+        {
+            let link = self.root.join(format!("{CURRENT_LINK}.tmp"));
+            let _ = fs::remove_file(&link);
+            std::os::unix::fs::symlink(Path::new(SLOTS_DIR).join(slot.name()), &link).map_err(
+                |source| UpdateError::File {
+                    path: link.clone(),
+                    source,
+                },
+            )?;
+            rename(&link, &self.root.join(CURRENT_LINK))?;
+        }
+        Ok(())
+    }
+
+    fn deactivate(&self) -> Result<(), UpdateError> {
+        for name in [ACTIVE_FILE, CURRENT_LINK] {
+            let path = self.root.join(name);
+            match fs::remove_file(&path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(UpdateError::File { path, source: err })
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn fetch(source: &dyn ReleaseSource, path: &str) -> Result<Vec<u8>, UpdateError> {
+    source.fetch(path).map_err(|reason| UpdateError::Fetch {
+        path: path.to_string(),
+        reason,
+    })
+}
+
+fn write(path: &Path, bytes: &[u8]) -> Result<(), UpdateError> {
+    fs::write(path, bytes).map_err(|source| UpdateError::File {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn rename(from: &Path, to: &Path) -> Result<(), UpdateError> {
+    fs::rename(from, to).map_err(|source| UpdateError::File {
+        path: to.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), UpdateError> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).map_err(|source| {
+        UpdateError::File {
+            path: path.to_path_buf(),
+            source,
+        }
+    })
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<(), UpdateError> {
+    Ok(())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn publish(dir: &Path, version: &str, kernel: &[u8], rollout_percent: u8) {
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        fs::write(dir.join("bin/noa_kernel"), kernel).unwrap();
+        let manifest = ReleaseManifest {
+            version: version.to_string(),
+            commit_sha: "abc123".to_string(),
+            pipeline_id: None,
+            rollout_percent,
+            binaries: vec![ReleaseBinary {
+                name: "noa_kernel".to_string(),
+                path: "bin/noa_kernel".to_string(),
+                sha256: sha256_hex(kernel),
+            }],
+        };
+        let (bytes, signature) = manifest.sign().unwrap();
+        fs::write(dir.join(RELEASE_MANIFEST_FILE), bytes).unwrap();
+        fs::write(
+            dir.join(RELEASE_SIGNATURE_FILE),
+            serde_json::to_vec(&signature).unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn healthy(_: &InstalledRelease) -> Result<(), String> {
+        Ok(())
+    }
+
+    #[test]
+    fn swaps_slots_and_rolls_back_unhealthy_releases() {
+        let release = tempfile::tempdir().unwrap();
+        let host = tempfile::tempdir().unwrap();
+        let source = DirectorySource::new(release.path());
+        let updater = SelfUpdater::new(host.path()).with_host_id("edge-01");
+
+        publish(release.path(), "1.0.0", b"v1", 100);
+        assert_eq!(
+            updater.apply(&source, &healthy).unwrap(),
+            UpdateOutcome::Applied {
+                version: "1.0.0".to_string(),
+                slot: Slot::Blue
+            }
+        );
+        assert_eq!(
+            updater.apply(&source, &healthy).unwrap(),
+            UpdateOutcome::UpToDate {
+                version: "1.0.0".to_string()
+            }
+        );
+
+        publish(release.path(), "1.1.0", b"v2", 100);
+        let failing = |release: &InstalledRelease| {
+            assert_eq!(fs::read(release.binary_path("noa_kernel")).unwrap(), b"v2");
+            Err("kernel did not report ready".to_string())
+        };
+        let outcome = updater.apply(&source, &failing).unwrap();
+        assert!(matches!(
+            outcome,
+            UpdateOutcome::RolledBack {
+                restored: Some(Slot::Blue),
+                ..
+            }
+        ));
+        let active = updater.active().unwrap().unwrap();
+        assert_eq!(active.manifest.version, "1.0.0");
+        #[cfg(unix)]
+        assert_eq!(
+            fs::read(host.path().join("current/noa_kernel")).unwrap(),
+            b"v1"
+        );
+
+        updater.apply(&source, &healthy).unwrap();
+        assert_eq!(updater.active().unwrap().unwrap().slot, Slot::Green);
+        assert_eq!(updater.rollback().unwrap().manifest.version, "1.0.0");
+    }
+
+    #[test]
+    fn rejects_tampered_binaries_and_honours_rollout() {
+        let release = tempfile::tempdir().unwrap();
+        let host = tempfile::tempdir().unwrap();
+        let source = DirectorySource::new(release.path());
+        let updater = SelfUpdater::new(host.path()).with_host_id("edge-02");
+
+        publish(release.path(), "2.0.0", b"v2", 0);
+        assert!(matches!(
+            updater.apply(&source, &healthy).unwrap(),
+            UpdateOutcome::NotInRollout {
+                rollout_percent: 0,
+                ..
+            }
+        ));
+
+        publish(release.path(), "2.0.0", b"v2", 100);
+        fs::write(release.path().join("bin/noa_kernel"), b"tampered").unwrap();
+        assert!(matches!(
+            updater.apply(&source, &healthy),
+            Err(UpdateError::Digest { .. })
+        ));
+        assert!(updater.active().unwrap().is_none());
+
+        let mut manifest = fs::read(release.path().join(RELEASE_MANIFEST_FILE)).unwrap();
+        manifest.extend_from_slice(b" ");
+        fs::write(release.path().join(RELEASE_MANIFEST_FILE), manifest).unwrap();
+        assert!(matches!(
+            updater.fetch_manifest(&source),
+            Err(UpdateError::Signature(_))
+        ));
+    }
+}
@@ -59,6 +59,37 @@ impl AstGraph {
             edges,
         })
     }
+
+    /// Drop the nodes for `changed` files (relative to `root`) and re-parse the ones
+    /// that still exist, leaving every other node untouched.
+    pub fn apply_changes(
+        &mut self,
+        root: impl AsRef<Path>,
+        changed: &[PathBuf],
+    ) -> Result<(), IndexerError> {
+        let root = root.as_ref();
+        let stale: Vec<String> = changed.iter().map(|path| module_id(path)).collect();
+        self.nodes.retain(|node| !stale.contains(&node.id));
+        self.edges.retain(|edge| !stale.contains(&edge.from));
+
+        for relative in changed {
+            let path = root.join(relative);
+            if relative.extension().and_then(|ext| ext.to_str()) != Some("rs") || !path.is_file() {
+                continue;
+            }
+            let node = build_node(&path, relative.clone(), &mut self.edges)?;
+            self.nodes.push(node);
+        }
+        self.generated_at = crate::utils::current_timestamp_millis();
+        Ok(())
+    }
+}
+
+fn module_id(relative: &Path) -> String {
+    relative
+        .to_string_lossy()
+        .replace('\\', "/")
+        .replace(".rs", "")
 }
 
 fn build_node(
@@ -68,10 +99,7 @@ fn build_node(
 ) -> Result<AstNode, IndexerError> {
     let source = fs::read_to_string(path)?;
     let syntax = syn::parse_file(&source)?;
-    let module_id = relative
-        .to_string_lossy()
-        .replace('\\', "/")
-        .replace(".rs", "");
+    let module_id = module_id(&relative);
     let mut functions = 0;
     let mut structs = 0;
     let mut enums = 0;
@@ -14,12 +14,15 @@ use std::io::BufWriter;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+use crate::fs::{ChangeJournal, JournalError};
 use crate::memory::RegistryError;
 
 const DEFAULT_OUTPUT_DIR: &str = ".workspace/indexes";
 const AST_INDEX: &str = "ast_graph.json";
 const OWNERSHIP_INDEX: &str = "ownership_graph.json";
 const CONFIG_INDEX: &str = "config_graph.json";
+/// Last journal sequence folded into the persisted indexes.
+const JOURNAL_CURSOR: &str = "journal.cursor";
 
 #[derive(Debug, Error)]
 pub enum IndexerError {
@@ -33,6 +36,8 @@ pub enum IndexerError {
     Registry(#[from] RegistryError),
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    #[error("journal error: {0}")]
+    Journal(#[from] JournalError),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,6 +85,61 @@ impl IndexerService {
         Ok(artifacts)
     }
 
+    /// Update the persisted indexes with the files changed in `journal` since the
+    /// last refresh. Falls back to a full [`refresh`](Self::refresh) when no index
+    /// has been written yet.
+    pub fn refresh_from_journal(
+        &self,
+        journal: &ChangeJournal,
+    ) -> Result<IndexArtifacts, IndexerError> {
+        let cursor_path = self.output.join(JOURNAL_CURSOR);
+        let ast_path = self.output.join(AST_INDEX);
+        let config_path = self.output.join(CONFIG_INDEX);
+        if !cursor_path.exists() || !ast_path.exists() || !config_path.exists() {
+            let changes = journal.changes_since(0)?;
+            let artifacts = self.refresh()?;
+            fs::write(&cursor_path, changes.last_sequence.to_string())?;
+            return Ok(artifacts);
+        }
+
+        let cursor = fs::read_to_string(&cursor_path)?
+            .trim()
+            .parse::<u64>()
+            .unwrap_or(0);
+        let changes = journal.changes_since(cursor)?;
+        let changed: Vec<PathBuf> = changes
+            .updated
+            .iter()
+            .chain(changes.removed.iter())
+            .filter_map(|path| {
+                journal
+                    .root()
+                    .join(path)
+                    .strip_prefix(&self.source)
+                    .ok()
+                    .map(Path::to_path_buf)
+            })
+            .collect();
+
+        let mut ast: AstGraph = serde_json::from_slice(&fs::read(&ast_path)?)?;
+        ast.apply_changes(&self.source, &changed)?;
+        let config = if changed.iter().any(|path| path.ends_with("Cargo.toml")) {
+            ConfigGraph::build(&self.source)?
+        } else {
+            serde_json::from_slice(&fs::read(&config_path)?)?
+        };
+
+        let artifacts = IndexArtifacts {
+            generated_at: crate::utils::current_timestamp_millis(),
+            ast,
+            ownership: OwnershipGraph::build()?,
+            config,
+        };
+        self.persist(&artifacts)?;
+        fs::write(&cursor_path, changes.last_sequence.to_string())?;
+        Ok(artifacts)
+    }
+
     pub fn persist(&self, artifacts: &IndexArtifacts) -> Result<(), IndexerError> {
         fs::create_dir_all(&self.output)?;
         write_json(self.output.join(AST_INDEX), &artifacts.ast)?;
@@ -111,4 +171,47 @@ mod tests {
         assert!(dir.path().join(OWNERSHIP_INDEX).exists());
         assert!(dir.path().join(CONFIG_INDEX).exists());
     }
+
+    #[test]
+    fn journal_refresh_reindexes_only_changed_files() {
+        let workspace = tempdir().unwrap();
+        let output = tempdir().unwrap();
+        fs::create_dir_all(workspace.path().join("src")).unwrap();
+        fs::write(workspace.path().join("src/kept.rs"), "struct Kept;").unwrap();
+        fs::write(workspace.path().join("src/gone.rs"), "fn gone() {}").unwrap();
+        let journal = ChangeJournal::open(workspace.path()).unwrap();
+        let service = IndexerService::new(workspace.path()).with_output_dir(output.path());
+        service.refresh_from_journal(&journal).unwrap();
+
+        journal
+            .write("agent-a", "src/kept.rs", "struct Kept; fn added() {}")
+            .unwrap();
+        journal.remove("agent-a", "src/gone.rs").unwrap();
+        journal
+            .write("agent-b", "src/new.rs", "use std::fs; enum New {}")
+            .unwrap();
+        // Changes made behind the journal's back are not picked up incrementally.
+        fs::write(workspace.path().join("src/untracked.rs"), "fn x() {}").unwrap();
+
+        let artifacts = service.refresh_from_journal(&journal).unwrap();
+        let mut ids: Vec<_> = artifacts.ast.nodes.iter().map(|n| n.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["src/kept", "src/new"]);
+        let kept = artifacts
+            .ast
+            .nodes
+            .iter()
+            .find(|n| n.id == "src/kept")
+            .unwrap();
+        assert_eq!((kept.structs, kept.functions), (1, 1));
+        assert!(artifacts
+            .ast
+            .edges
+            .iter()
+            .any(|edge| edge.from == "src/new" && edge.to == "std::fs"));
+        assert_eq!(
+            fs::read_to_string(output.path().join(JOURNAL_CURSOR)).unwrap(),
+            "3"
+        );
+    }
 }
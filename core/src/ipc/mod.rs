@@ -0,0 +1,204 @@
+//! Inter-process communication (IPC) subsystem
+
+pub mod schema;
+pub mod threads;
+pub mod topics;
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use schema::{SchemaError, TopicMessage};
+use threads::{ThreadError, ThreadId, ThreadInfo, ThreadMessage, ThreadPost, ThreadScope};
+
+pub type ChannelId = u64;
+
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub from: u64,
+    pub to: u64,
+    pub data: Vec<u8>,
+}
+
+fn message_queues() -> &'static Mutex<HashMap<ChannelId, Vec<Message>>> {
+    static MESSAGE_QUEUES: OnceLock<Mutex<HashMap<ChannelId, Vec<Message>>>> = OnceLock::new();
+    MESSAGE_QUEUES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Initialize IPC subsystem
+pub fn init() -> Result<(), &'static str> {
+    println!("[IPC] Initializing inter-process communication...");
+    if let Ok(root) = std::env::var("NOA_IPC_THREADS_DIR") {
+        threads::configure(root).map_err(|err| {
+            eprintln!("[IPC] Failed to load conversation threads: {err}");
+            "failed to load IPC threads"
+        })?;
+    }
+    Ok(())
+}
+
+fn create_channel_inner(channel_id: ChannelId) -> Result<(), &'static str> {
+    let mut queues = message_queues().lock().unwrap();
+    queues.insert(channel_id, Vec::new());
+    Ok(())
+}
+
+fn send_message_inner(channel_id: ChannelId, message: Message) -> Result<(), &'static str> {
+    let mut queues = message_queues().lock().unwrap();
+    if let Some(queue) = queues.get_mut(&channel_id) {
+        queue.push(message);
+        Ok(())
+    } else {
+        Err("Channel not found")
+    }
+}
+
+fn receive_message_inner(channel_id: ChannelId) -> Option<Message> {
+    let mut queues = message_queues().lock().unwrap();
+    if let Some(queue) = queues.get_mut(&channel_id) {
+        if !queue.is_empty() {
+            Some(queue.remove(0))
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+/// Capability wrapper over IPC primitives.
+#[derive(Clone, Default)]
+pub struct IpcService;
+
+impl IpcService {
+    /// Register a new communication channel.
+    pub fn create_channel(&self, channel_id: ChannelId) -> Result<(), &'static str> {
+        create_channel_inner(channel_id)
+    }
+
+    /// Deliver a message to a channel.
+    pub fn send_message(
+        &self,
+        channel_id: ChannelId,
+        message: Message,
+    ) -> Result<(), &'static str> {
+        send_message_inner(channel_id, message)
+    }
+
+    /// Pull the next message from a channel queue.
+    pub fn receive_message(&self, channel_id: ChannelId) -> Option<Message> {
+        receive_message_inner(channel_id)
+    }
+
+    /// Deliver a typed message; its schema must be registered by the publisher.
+    pub fn publish<T: TopicMessage>(
+        &self,
+        channel_id: ChannelId,
+        from: u64,
+        to: u64,
+        message: &T,
+    ) -> Result<(), SchemaError> {
+        let message = schema::encode(from, to, message)?;
+        send_message_inner(channel_id, message).map_err(|err| SchemaError::Channel(err.to_string()))
+    }
+
+    /// Pull the next message from a channel and decode it as `T`.
+    pub fn receive_typed<T: TopicMessage>(
+        &self,
+        channel_id: ChannelId,
+    ) -> Option<Result<T, SchemaError>> {
+        receive_message_inner(channel_id).map(|message| schema::decode(&message))
+    }
+
+    /// Open a conversation thread about a workflow or pipeline.
+    pub fn open_thread(
+        &self,
+        scope: ThreadScope,
+        subject: &str,
+        agent: &str,
+    ) -> Result<ThreadInfo, ThreadError> {
+        threads::bus().open_thread(scope, subject, agent)
+    }
+
+    /// Join a thread; the agent receives its messages from the first one.
+    pub fn join_thread(&self, thread: ThreadId, agent: &str) -> Result<ThreadInfo, ThreadError> {
+        threads::bus().join(thread, agent)
+    }
+
+    /// Post to a thread the sender has joined.
+    pub fn post_to_thread(
+        &self,
+        thread: ThreadId,
+        post: ThreadPost,
+    ) -> Result<ThreadMessage, ThreadError> {
+        threads::bus().post(thread, post)
+    }
+
+    /// Pull the agent's unread thread messages in sequence order.
+    pub fn receive_from_thread(
+        &self,
+        thread: ThreadId,
+        agent: &str,
+        limit: usize,
+    ) -> Result<Vec<ThreadMessage>, ThreadError> {
+        threads::bus().receive(thread, agent, limit)
+    }
+
+    /// Re-read a thread from a sequence number.
+    pub fn replay_thread(
+        &self,
+        thread: ThreadId,
+        from: u64,
+        limit: usize,
+    ) -> Result<Vec<ThreadMessage>, ThreadError> {
+        threads::bus().replay(thread, from, limit)
+    }
+
+    /// Threads opened about a workflow or pipeline.
+    pub fn threads_for(&self, scope: &ThreadScope) -> Vec<ThreadInfo> {
+        threads::bus().threads_for(scope)
+    }
+}
+
+/// Create a new channel.
+pub fn create_channel(channel_id: ChannelId) -> Result<(), &'static str> {
+    IpcService.create_channel(channel_id)
+}
+
+/// Send a message.
+pub fn send_message(channel_id: ChannelId, message: Message) -> Result<(), &'static str> {
+    IpcService.send_message(channel_id, message)
+}
+
+/// Receive a message.
+pub fn receive_message(channel_id: ChannelId) -> Option<Message> {
+    IpcService.receive_message(channel_id)
+}
+
+/// Publish a typed message.
+pub fn publish<T: TopicMessage>(
+    channel_id: ChannelId,
+    from: u64,
+    to: u64,
+    message: &T,
+) -> Result<(), SchemaError> {
+    IpcService.publish(channel_id, from, to, message)
+}
+
+/// Open a conversation thread.
+pub fn open_thread(
+    scope: ThreadScope,
+    subject: &str,
+    agent: &str,
+) -> Result<ThreadInfo, ThreadError> {
+    IpcService.open_thread(scope, subject, agent)
+}
+
+/// Join a conversation thread.
+pub fn join_thread(thread: ThreadId, agent: &str) -> Result<ThreadInfo, ThreadError> {
+    IpcService.join_thread(thread, agent)
+}
+
+/// Post to a conversation thread.
+pub fn post_to_thread(thread: ThreadId, post: ThreadPost) -> Result<ThreadMessage, ThreadError> {
+    IpcService.post_to_thread(thread, post)
+}
@@ -0,0 +1,378 @@
+//! Versioned, typed message schemas for IPC topics.
+//!
+//! Every topic published on the bus is described by a [`MessageSchema`]: the topic
+//! name, a version number, and the message fields with their Rust types. Types
+//! declared with [`ipc_topic!`](crate::ipc_topic) implement [`TopicMessage`] and
+//! carry their schema with them. Publishers register their schema before sending;
+//! a new version is accepted only when it stays readable by consumers of the
+//! versions around it (no removed or retyped required fields, and added fields
+//! must be optional).
+
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::Message;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SchemaError {
+    #[error("topic {0} is not registered")]
+    UnknownTopic(String),
+    #[error("topic {topic} has no registered version {version}")]
+    UnknownVersion { topic: String, version: u32 },
+    #[error("topic {topic} v{version} is already registered with a different definition")]
+    Conflict { topic: String, version: u32 },
+    #[error("topic {topic} v{version} is incompatible with v{other}: {reason}")]
+    Incompatible {
+        topic: String,
+        version: u32,
+        other: u32,
+        reason: String,
+    },
+    #[error("expected a {expected} message, found {found}")]
+    TopicMismatch { expected: String, found: String },
+    #[error("invalid message payload: {0}")]
+    Payload(String),
+    #[error("channel error: {0}")]
+    Channel(String),
+}
+
+/// One field of a message, with its type as written in the Rust definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSchema {
+    pub name: String,
+    pub ty: String,
+}
+
+impl FieldSchema {
+    pub fn new(name: &str, ty: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ty: ty.split_whitespace().collect(),
+        }
+    }
+
+    /// Optional fields may be absent from a payload.
+    pub fn optional(&self) -> bool {
+        self.ty.starts_with("Option<")
+    }
+}
+
+/// Definition of one version of a topic's message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageSchema {
+    pub topic: String,
+    pub version: u32,
+    pub fields: Vec<FieldSchema>,
+}
+
+impl MessageSchema {
+    fn field(&self, name: &str) -> Option<&FieldSchema> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+/// A serde type bound to a topic and schema version.
+pub trait TopicMessage: Serialize + DeserializeOwned {
+    const TOPIC: &'static str;
+    const VERSION: u32;
+
+    fn fields() -> Vec<FieldSchema>;
+
+    fn schema() -> MessageSchema {
+        MessageSchema {
+            topic: Self::TOPIC.to_string(),
+            version: Self::VERSION,
+            fields: Self::fields(),
+        }
+    }
+}
+
+/// Declare a typed IPC message and implement [`TopicMessage`] for it.
+///
+/// ```
+/// noa_core::ipc_topic! {
+///     /// Emitted when an index refresh completes.
+///     pub struct IndexRefreshed("indexer.refreshed", 1) {
+///         pub files: u64,
+///         pub duration_ms: Option<u64>,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! ipc_topic {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident ($topic:literal, $version:literal) {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident : $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, ::serde::Serialize, ::serde::Deserialize)]
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $ty,)*
+        }
+
+        impl $crate::ipc::schema::TopicMessage for $name {
+            const TOPIC: &'static str = $topic;
+            const VERSION: u32 = $version;
+
+            fn fields() -> Vec<$crate::ipc::schema::FieldSchema> {
+                vec![$($crate::ipc::schema::FieldSchema::new(stringify!($field), stringify!($ty)),)*]
+            }
+        }
+    };
+}
+
+/// Check that consumers of `older` can read `newer` messages and vice versa.
+pub fn check_compatibility(older: &MessageSchema, newer: &MessageSchema) -> Result<(), String> {
+    for field in &older.fields {
+        match newer.field(&field.name) {
+            Some(next) if next.ty != field.ty => {
+                return Err(format!(
+                    "field {} changed type from {} to {}",
+                    field.name, field.ty, next.ty
+                ))
+            }
+            None if !field.optional() => {
+                return Err(format!("required field {} was removed", field.name))
+            }
+            _ => {}
+        }
+    }
+    for field in &newer.fields {
+        if older.field(&field.name).is_none() && !field.optional() {
+            return Err(format!("added field {} must be optional", field.name));
+        }
+    }
+    Ok(())
+}
+
+/// All known versions of every registered topic.
+#[derive(Debug, Default)]
+pub struct SchemaRegistry {
+    topics: BTreeMap<String, BTreeMap<u32, MessageSchema>>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry preloaded with the topics defined in [`super::topics`].
+    pub fn with_builtin_topics() -> Self {
+        let mut registry = Self::new();
+        super::topics::register_builtin(&mut registry);
+        registry
+    }
+
+    pub fn register<T: TopicMessage>(&mut self) -> Result<(), SchemaError> {
+        self.register_schema(T::schema())
+    }
+
+    /// Add a schema version, checking it against its neighbouring versions.
+    /// Re-registering an identical definition is a no-op.
+    pub fn register_schema(&mut self, schema: MessageSchema) -> Result<(), SchemaError> {
+        let versions = self.topics.entry(schema.topic.clone()).or_default();
+        if let Some(existing) = versions.get(&schema.version) {
+            return if existing == &schema {
+                Ok(())
+            } else {
+                Err(SchemaError::Conflict {
+                    topic: schema.topic,
+                    version: schema.version,
+                })
+            };
+        }
+
+        let previous = versions.range(..schema.version).next_back();
+        let next = versions.range(schema.version + 1..).next();
+        for (older, newer, other) in [
+            previous.map(|(version, older)| (older, &schema, *version)),
+            next.map(|(version, newer)| (&schema, newer, *version)),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            check_compatibility(older, newer).map_err(|reason| SchemaError::Incompatible {
+                topic: schema.topic.clone(),
+                version: schema.version,
+                other,
+                reason,
+            })?;
+        }
+
+        versions.insert(schema.version, schema);
+        Ok(())
+    }
+
+    pub fn schema(&self, topic: &str, version: u32) -> Result<&MessageSchema, SchemaError> {
+        let versions = self
+            .topics
+            .get(topic)
+            .ok_or_else(|| SchemaError::UnknownTopic(topic.to_string()))?;
+        versions
+            .get(&version)
+            .ok_or_else(|| SchemaError::UnknownVersion {
+                topic: topic.to_string(),
+                version,
+            })
+    }
+
+    pub fn latest(&self, topic: &str) -> Option<&MessageSchema> {
+        self.topics
+            .get(topic)
+            .and_then(|versions| versions.values().next_back())
+    }
+
+    pub fn topics(&self) -> impl Iterator<Item = &str> {
+        self.topics.keys().map(String::as_str)
+    }
+}
+
+fn global_registry() -> &'static RwLock<SchemaRegistry> {
+    static REGISTRY: OnceLock<RwLock<SchemaRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(SchemaRegistry::with_builtin_topics()))
+}
+
+/// Register a publisher's message type with the kernel-wide registry.
+pub fn register_publisher<T: TopicMessage>() -> Result<(), SchemaError> {
+    global_registry().write().unwrap().register::<T>()
+}
+
+/// Latest registered definition of a topic in the kernel-wide registry.
+pub fn latest_schema(topic: &str) -> Option<MessageSchema> {
+    global_registry().read().unwrap().latest(topic).cloned()
+}
+
+/// Wire format of typed messages: `{"topic", "version", "payload"}` JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicEnvelope {
+    pub topic: String,
+    pub version: u32,
+    pub payload: Value,
+}
+
+/// Encode a registered message type as an IPC message.
+pub fn encode<T: TopicMessage>(from: u64, to: u64, message: &T) -> Result<Message, SchemaError> {
+    let envelope = envelope(message)?;
+    let data =
+        serde_json::to_vec(&envelope).map_err(|err| SchemaError::Payload(err.to_string()))?;
+    Ok(Message { from, to, data })
+}
+
+/// Decode an IPC message as `T`. Any registered version of the topic is accepted,
+/// since registration guarantees versions are mutually readable.
+pub fn decode<T: TopicMessage>(message: &Message) -> Result<T, SchemaError> {
+    let envelope: TopicEnvelope = serde_json::from_slice(&message.data)
+        .map_err(|err| SchemaError::Payload(err.to_string()))?;
+    decode_envelope(envelope)
+}
+
+/// Wrap a registered message type in its envelope without addressing it.
+pub fn envelope<T: TopicMessage>(message: &T) -> Result<TopicEnvelope, SchemaError> {
+    global_registry()
+        .read()
+        .unwrap()
+        .schema(T::TOPIC, T::VERSION)?;
+    Ok(TopicEnvelope {
+        topic: T::TOPIC.to_string(),
+        version: T::VERSION,
+        payload: serde_json::to_value(message)
+            .map_err(|err| SchemaError::Payload(err.to_string()))?,
+    })
+}
+
+/// Decode an envelope as `T`, accepting any registered version of its topic.
+pub fn decode_envelope<T: TopicMessage>(envelope: TopicEnvelope) -> Result<T, SchemaError> {
+    if envelope.topic != T::TOPIC {
+        return Err(SchemaError::TopicMismatch {
+            expected: T::TOPIC.to_string(),
+            found: envelope.topic,
+        });
+    }
+    global_registry()
+        .read()
+        .unwrap()
+        .schema(&envelope.topic, envelope.version)?;
+    serde_json::from_value(envelope.payload).map_err(|err| SchemaError::Payload(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::topics::CrcDropReady;
+
+    crate::ipc_topic! {
+        struct DropReadyV2("crc.drop.ready", 2) {
+            drop_id: String,
+            priority: Option<u32>,
+            archive: Option<String>,
+        }
+    }
+
+    crate::ipc_topic! {
+        struct DropReadyBroken("crc.drop.ready", 3) {
+            drop_id: u64,
+            priority: Option<u32>,
+        }
+    }
+
+    #[test]
+    fn registry_checks_compatibility_and_round_trips_messages() {
+        let mut registry = SchemaRegistry::with_builtin_topics();
+        assert!(registry.topics().any(|topic| topic == "crc.drop.ready"));
+        assert_eq!(
+            registry.latest("crc.drop.ready").unwrap().fields[1].ty,
+            "Option<u32>"
+        );
+
+        registry.register::<CrcDropReady>().unwrap();
+        registry.register::<DropReadyV2>().unwrap();
+        assert!(matches!(
+            registry.register::<DropReadyBroken>(),
+            Err(SchemaError::Incompatible { other: 2, .. })
+        ));
+        let mut conflicting = DropReadyV2::schema();
+        conflicting.fields.pop();
+        assert!(matches!(
+            registry.register_schema(conflicting),
+            Err(SchemaError::Conflict { version: 2, .. })
+        ));
+
+        register_publisher::<DropReadyV2>().unwrap();
+        let message = encode(
+            1,
+            2,
+            &DropReadyV2 {
+                drop_id: "drop-9".into(),
+                priority: Some(2),
+                archive: Some("archive/drop-9.tar.zst".into()),
+            },
+        )
+        .unwrap();
+        let decoded: CrcDropReady = decode(&message).unwrap();
+        assert_eq!(decoded.drop_id, "drop-9");
+        assert_eq!(decoded.priority, Some(2));
+
+        let unregistered = encode(
+            1,
+            2,
+            &DropReadyBroken {
+                drop_id: 9,
+                priority: None,
+            },
+        );
+        assert!(matches!(
+            unregistered,
+            Err(SchemaError::UnknownVersion { version: 3, .. })
+        ));
+        assert!(matches!(
+            decode::<crate::ipc::topics::AgentStateChanged>(&message),
+            Err(SchemaError::TopicMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,486 @@
+//! Conversation threads on the IPC bus.
+//!
+//! A thread groups the messages agents exchange about one workflow or pipeline.
+//! Every message takes the next sequence number of its thread, so all participants
+//! observe the same order, and a reply may only point at a message that precedes
+//! it. Posts carry a sender-chosen message id; posting an id again returns the
+//! original message instead of appending a duplicate. Participants read from a
+//! cursor kept per agent, and anyone may replay a thread from an offset.
+//!
+//! A persistent bus keeps each thread under `<root>/<thread id>/`: `thread.json`
+//! holds the scope, subject, and participant cursors, and `messages.jsonl` is the
+//! append-only message log.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::schema::{self, SchemaError, TopicEnvelope, TopicMessage};
+use crate::recovery::{self, RecoveryMode};
+
+pub type ThreadId = u64;
+
+const THREAD_FILE: &str = "thread.json";
+const MESSAGES_FILE: &str = "messages.jsonl";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ThreadError {
+    #[error("thread {0} does not exist")]
+    UnknownThread(ThreadId),
+    #[error("agent {agent} has not joined thread {thread}")]
+    NotParticipant { thread: ThreadId, agent: String },
+    #[error("message {reply_to} does not precede the reply in thread {thread}")]
+    UnknownParent { thread: ThreadId, reply_to: u64 },
+    #[error(transparent)]
+    Schema(#[from] SchemaError),
+    #[error("thread storage error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("thread record error: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+/// The workflow or pipeline a thread is about.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum ThreadScope {
+    Workflow(String),
+    Pipeline(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadInfo {
+    pub id: ThreadId,
+    pub scope: ThreadScope,
+    pub subject: String,
+    pub opened_by: String,
+    pub opened_at_ms: u64,
+    /// Participants with the offset of the next message each will receive.
+    pub participants: BTreeMap<String, u64>,
+}
+
+/// A message as ordered in its thread.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadMessage {
+    pub thread: ThreadId,
+    /// Position in the thread; sequences start at 0 and have no gaps.
+    pub sequence: u64,
+    pub message_id: String,
+    pub sender: String,
+    pub reply_to: Option<u64>,
+    pub sent_at_ms: u64,
+    pub envelope: TopicEnvelope,
+}
+
+impl ThreadMessage {
+    pub fn decode<T: TopicMessage>(&self) -> Result<T, SchemaError> {
+        schema::decode_envelope(self.envelope.clone())
+    }
+}
+
+/// A typed message to be posted to a thread.
+#[derive(Debug, Clone)]
+pub struct ThreadPost {
+    pub sender: String,
+    /// Deduplication key; reposting it returns the message already in the thread.
+    pub message_id: String,
+    pub reply_to: Option<u64>,
+    pub envelope: TopicEnvelope,
+}
+
+impl ThreadPost {
+    /// Wrap `message`; its schema must be registered like any published topic.
+    pub fn new<T: TopicMessage>(
+        sender: impl Into<String>,
+        message_id: impl Into<String>,
+        message: &T,
+    ) -> Result<Self, SchemaError> {
+        Ok(Self {
+            sender: sender.into(),
+            message_id: message_id.into(),
+            reply_to: None,
+            envelope: schema::envelope(message)?,
+        })
+    }
+
+    pub fn with_reply_to(mut self, sequence: u64) -> Self {
+        self.reply_to = Some(sequence);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Thread {
+    info: ThreadInfo,
+    messages: Vec<ThreadMessage>,
+    ids: HashMap<String, u64>,
+}
+
+impl Thread {
+    fn new(info: ThreadInfo, messages: Vec<ThreadMessage>) -> Self {
+        let ids = messages
+            .iter()
+            .map(|message| (message.message_id.clone(), message.sequence))
+            .collect();
+        Self {
+            info,
+            messages,
+            ids,
+        }
+    }
+
+    fn require_participant(&self, agent: &str) -> Result<(), ThreadError> {
+        if self.info.participants.contains_key(agent) {
+            Ok(())
+        } else {
+            Err(ThreadError::NotParticipant {
+                thread: self.info.id,
+                agent: agent.to_string(),
+            })
+        }
+    }
+
+    fn slice(&self, from: u64, limit: usize) -> Vec<ThreadMessage> {
+        self.messages
+            .iter()
+            .skip(from.min(self.messages.len() as u64) as usize)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Threads with their messages, optionally persisted under a root directory.
+#[derive(Debug)]
+pub struct ThreadBus {
+    root: Option<PathBuf>,
+    threads: Mutex<BTreeMap<ThreadId, Thread>>,
+}
+
+impl Default for ThreadBus {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+impl ThreadBus {
+    pub fn in_memory() -> Self {
+        Self {
+            root: None,
+            threads: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Load the threads persisted under `root`, creating it if needed.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, ThreadError> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        let mut threads = BTreeMap::new();
+        for entry in fs::read_dir(&root)? {
+            let path = entry?.path();
+            let Some(id) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.parse::<ThreadId>().ok())
+            else {
+                continue;
+            };
+            let info: ThreadInfo = match fs::read(path.join(THREAD_FILE)) {
+                Ok(bytes) => serde_json::from_slice(&bytes)?,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+            threads.insert(id, Thread::new(info, load_messages(&path)?));
+        }
+        Ok(Self {
+            root: Some(root),
+            threads: Mutex::new(threads),
+        })
+    }
+
+    /// Open a thread about `scope`; the opener is its first participant.
+    pub fn open_thread(
+        &self,
+        scope: ThreadScope,
+        subject: impl Into<String>,
+        opened_by: impl Into<String>,
+    ) -> Result<ThreadInfo, ThreadError> {
+        let mut threads = self.threads.lock().expect("thread bus lock poisoned");
+        let id = threads.keys().next_back().map_or(1, |last| last + 1);
+        let opened_by = opened_by.into();
+        let info = ThreadInfo {
+            id,
+            scope,
+            subject: subject.into(),
+            participants: BTreeMap::from([(opened_by.clone(), 0)]),
+            opened_by,
+            opened_at_ms: now_ms(),
+        };
+        if let Some(dir) = self.thread_dir(id) {
+            fs::create_dir_all(&dir)?;
+            save_info(&dir, &info)?;
+        }
+        threads.insert(id, Thread::new(info.clone(), Vec::new()));
+        Ok(info)
+    }
+
+    /// Add `agent` to a thread. New participants start reading from the first
+    /// message; joining again keeps the agent's cursor.
+    pub fn join(&self, thread: ThreadId, agent: &str) -> Result<ThreadInfo, ThreadError> {
+        let mut threads = self.threads.lock().expect("thread bus lock poisoned");
+        let entry = threads
+            .get_mut(&thread)
+            .ok_or(ThreadError::UnknownThread(thread))?;
+        if !entry.info.participants.contains_key(agent) {
+            let mut info = entry.info.clone();
+            info.participants.insert(agent.to_string(), 0);
+            if let Some(dir) = self.thread_dir(thread) {
+                save_info(&dir, &info)?;
+            }
+            entry.info = info;
+        }
+        Ok(entry.info.clone())
+    }
+
+    /// Append a message from a participant, or return the message already posted
+    /// under the same message id.
+    pub fn post(&self, thread: ThreadId, post: ThreadPost) -> Result<ThreadMessage, ThreadError> {
+        let mut threads = self.threads.lock().expect("thread bus lock poisoned");
+        let entry = threads
+            .get_mut(&thread)
+            .ok_or(ThreadError::UnknownThread(thread))?;
+        entry.require_participant(&post.sender)?;
+        if let Some(sequence) = entry.ids.get(&post.message_id) {
+            return Ok(entry.messages[*sequence as usize].clone());
+        }
+        let sequence = entry.messages.len() as u64;
+        if let Some(reply_to) = post.reply_to.filter(|reply_to| *reply_to >= sequence) {
+            return Err(ThreadError::UnknownParent { thread, reply_to });
+        }
+        let message = ThreadMessage {
+            thread,
+            sequence,
+            message_id: post.message_id,
+            sender: post.sender,
+            reply_to: post.reply_to,
+            sent_at_ms: now_ms(),
+            envelope: post.envelope,
+        };
+        if let Some(dir) = self.thread_dir(thread) {
+            let mut line = serde_json::to_vec(&message)?;
+            line.push(b'\n');
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(dir.join(MESSAGES_FILE))?
+                .write_all(&line)?;
+        }
+        entry.ids.insert(message.message_id.clone(), sequence);
+        entry.messages.push(message.clone());
+        Ok(message)
+    }
+
+    /// Up to `limit` messages starting at sequence `from`.
+    pub fn replay(
+        &self,
+        thread: ThreadId,
+        from: u64,
+        limit: usize,
+    ) -> Result<Vec<ThreadMessage>, ThreadError> {
+        let threads = self.threads.lock().expect("thread bus lock poisoned");
+        let entry = threads
+            .get(&thread)
+            .ok_or(ThreadError::UnknownThread(thread))?;
+        Ok(entry.slice(from, limit))
+    }
+
+    /// Up to `limit` messages past `agent`'s cursor, advancing the cursor over them.
+    pub fn receive(
+        &self,
+        thread: ThreadId,
+        agent: &str,
+        limit: usize,
+    ) -> Result<Vec<ThreadMessage>, ThreadError> {
+        let mut threads = self.threads.lock().expect("thread bus lock poisoned");
+        let entry = threads
+            .get_mut(&thread)
+            .ok_or(ThreadError::UnknownThread(thread))?;
+        entry.require_participant(agent)?;
+        let cursor = entry.info.participants[agent];
+        let messages = entry.slice(cursor, limit);
+        if !messages.is_empty() {
+            let mut info = entry.info.clone();
+            info.participants
+                .insert(agent.to_string(), cursor + messages.len() as u64);
+            if let Some(dir) = self.thread_dir(thread) {
+                save_info(&dir, &info)?;
+            }
+            entry.info = info;
+        }
+        Ok(messages)
+    }
+
+    pub fn thread(&self, thread: ThreadId) -> Option<ThreadInfo> {
+        let threads = self.threads.lock().expect("thread bus lock poisoned");
+        threads.get(&thread).map(|entry| entry.info.clone())
+    }
+
+    /// Threads opened about `scope`, oldest first.
+    pub fn threads_for(&self, scope: &ThreadScope) -> Vec<ThreadInfo> {
+        let threads = self.threads.lock().expect("thread bus lock poisoned");
+        threads
+            .values()
+            .filter(|entry| &entry.info.scope == scope)
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+
+    fn thread_dir(&self, thread: ThreadId) -> Option<PathBuf> {
+        self.root.as_ref().map(|root| root.join(thread.to_string()))
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn save_info(dir: &Path, info: &ThreadInfo) -> Result<(), ThreadError> {
+    let path = dir.join(THREAD_FILE);
+    let staging = path.with_extension("json.tmp");
+    fs::write(&staging, serde_json::to_vec_pretty(info)?)?;
+    fs::rename(&staging, &path)?;
+    Ok(())
+}
+
+/// Messages of a thread directory, up to the first gap in the sequence. A log
+/// with a torn or out-of-order tail is rewritten with the messages kept, so later
+/// appends start on a clean line.
+fn load_messages(dir: &Path) -> Result<Vec<ThreadMessage>, ThreadError> {
+    let path = dir.join(MESSAGES_FILE);
+    let content = match fs::read(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let recovered = recovery::parse_jsonl::<ThreadMessage>(&content, RecoveryMode::Lenient)
+        .map(|recovered| recovered.records)
+        .unwrap_or_default();
+    let total = recovered.len();
+    let messages: Vec<ThreadMessage> = recovered
+        .into_iter()
+        .enumerate()
+        .take_while(|(index, message)| message.sequence == *index as u64)
+        .map(|(_, message)| message)
+        .collect();
+    if messages.len() != total || !(content.is_empty() || content.ends_with(b"\n")) {
+        let mut rewritten = Vec::new();
+        for message in &messages {
+            rewritten.extend(serde_json::to_vec(message)?);
+            rewritten.push(b'\n');
+        }
+        let staging = path.with_extension("jsonl.tmp");
+        fs::write(&staging, rewritten)?;
+        fs::rename(&staging, &path)?;
+    }
+    Ok(messages)
+}
+
+fn global_bus() -> &'static RwLock<Arc<ThreadBus>> {
+    static BUS: OnceLock<RwLock<Arc<ThreadBus>>> = OnceLock::new();
+    BUS.get_or_init(|| RwLock::new(Arc::new(ThreadBus::in_memory())))
+}
+
+/// Persist the kernel-wide threads under `root`, loading the ones already there.
+pub fn configure(root: impl Into<PathBuf>) -> Result<(), ThreadError> {
+    let bus = ThreadBus::open(root)?;
+    *global_bus().write().expect("thread bus lock poisoned") = Arc::new(bus);
+    Ok(())
+}
+
+/// The kernel-wide thread bus; in memory until [`configure`] is called.
+pub fn bus() -> Arc<ThreadBus> {
+    global_bus()
+        .read()
+        .expect("thread bus lock poisoned")
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::topics::ThreadNote;
+
+    fn note(sender: &str, id: &str, text: &str) -> ThreadPost {
+        ThreadPost::new(sender, id, &ThreadNote { text: text.into() }).unwrap()
+    }
+
+    #[test]
+    fn threads_order_dedup_and_replay_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let bus = ThreadBus::open(dir.path()).unwrap();
+        let scope = ThreadScope::Pipeline("pipeline-7".into());
+        let thread = bus
+            .open_thread(scope.clone(), "flaky tests", "planner")
+            .unwrap();
+
+        let first = bus
+            .post(thread.id, note("planner", "m-1", "rerun?"))
+            .unwrap();
+        assert!(matches!(
+            bus.post(thread.id, note("reviewer", "m-2", "yes")),
+            Err(ThreadError::NotParticipant { .. })
+        ));
+        bus.join(thread.id, "reviewer").unwrap();
+        let reply = bus
+            .post(
+                thread.id,
+                note("reviewer", "m-2", "yes").with_reply_to(first.sequence),
+            )
+            .unwrap();
+        assert_eq!(reply.sequence, 1);
+        let duplicate = bus
+            .post(thread.id, note("reviewer", "m-2", "yes, again"))
+            .unwrap();
+        assert_eq!(duplicate.sequence, 1);
+        assert!(matches!(
+            bus.post(thread.id, note("planner", "m-3", "?").with_reply_to(5)),
+            Err(ThreadError::UnknownParent { reply_to: 5, .. })
+        ));
+
+        let received = bus.receive(thread.id, "reviewer", 1).unwrap();
+        assert_eq!(received[0].message_id, "m-1");
+
+        let log = dir.path().join(thread.id.to_string()).join(MESSAGES_FILE);
+        let mut torn = OpenOptions::new().append(true).open(&log).unwrap();
+        torn.write_all(br#"{"thread":1,"sequ"#).unwrap();
+
+        let restarted = ThreadBus::open(dir.path()).unwrap();
+        assert_eq!(
+            restarted.threads_for(&scope),
+            vec![bus.thread(thread.id).unwrap()]
+        );
+        let rest = restarted.receive(thread.id, "reviewer", 10).unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].decode::<ThreadNote>().unwrap().text, "yes");
+        assert_eq!(rest[0].reply_to, Some(0));
+        let replayed = restarted.replay(thread.id, 0, 10).unwrap();
+        let ids: Vec<_> = replayed.iter().map(|m| m.message_id.as_str()).collect();
+        assert_eq!(ids, ["m-1", "m-2"]);
+
+        let next = restarted
+            .post(thread.id, note("planner", "m-3", "done"))
+            .unwrap();
+        assert_eq!(next.sequence, 2);
+        let other = restarted
+            .open_thread(ThreadScope::Workflow("wf".into()), "deploy", "planner")
+            .unwrap();
+        assert_eq!(other.id, thread.id + 1);
+    }
+}
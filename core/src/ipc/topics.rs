@@ -0,0 +1,50 @@
+//! Message definitions for topics shared between workflow, cicd, and agents.
+
+use super::schema::{SchemaRegistry, TopicMessage};
+use crate::ipc_topic;
+
+ipc_topic! {
+    /// A CRC drop finished processing and is ready for follow-up workflows.
+    pub struct CrcDropReady("crc.drop.ready", 1) {
+        pub drop_id: String,
+        pub priority: Option<u32>,
+    }
+}
+
+ipc_topic! {
+    /// A pipeline stage finished, successfully or not.
+    pub struct PipelineStageCompleted("pipeline.stage.completed", 1) {
+        pub pipeline_id: String,
+        pub stage: String,
+        pub success: bool,
+        pub error: Option<String>,
+    }
+}
+
+ipc_topic! {
+    /// An agent moved to a new lifecycle state.
+    pub struct AgentStateChanged("agent.state.changed", 1) {
+        pub agent_id: String,
+        pub state: String,
+    }
+}
+
+ipc_topic! {
+    /// Free-form text posted to a conversation thread.
+    pub struct ThreadNote("thread.note", 1) {
+        pub text: String,
+    }
+}
+
+pub(crate) fn register_builtin(registry: &mut SchemaRegistry) {
+    for schema in [
+        CrcDropReady::schema(),
+        PipelineStageCompleted::schema(),
+        AgentStateChanged::schema(),
+        ThreadNote::schema(),
+    ] {
+        registry
+            .register_schema(schema)
+            .expect("built-in IPC topics are compatible");
+    }
+}
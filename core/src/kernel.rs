@@ -9,9 +9,11 @@ use crate::capabilities::builtin::register_default_capabilities;
 use crate::capabilities::{CapabilityError, CapabilityRegistry, KernelHandle};
 use crate::config::manifest::{KernelManifest, ManifestError};
 use crate::config::profile::{CapabilityToken, ProfileDocument, ProfileError};
+use crate::events;
 use crate::metrics::{self, AggregatedTelemetry, LoadLevel};
 use crate::security::{self, OperationKind, SignedOperation};
 use crate::token;
+use crate::watchdog::{KernelWatchdog, WatchdogReport};
 
 static KERNEL_RUNNING: AtomicBool = AtomicBool::new(false);
 
@@ -20,6 +22,18 @@ fn global_kernel() -> &'static Mutex<Option<KernelHandle>> {
     GLOBAL_KERNEL.get_or_init(|| Mutex::new(None))
 }
 
+/// Watchdog subscribed to the global kernel event bus.
+pub fn watchdog() -> Arc<KernelWatchdog> {
+    static WATCHDOG: OnceLock<Arc<KernelWatchdog>> = OnceLock::new();
+    WATCHDOG
+        .get_or_init(|| {
+            let watchdog = Arc::new(KernelWatchdog::new());
+            events::global().subscribe(watchdog.clone());
+            watchdog
+        })
+        .clone()
+}
+
 fn active_profile_slot() -> &'static Mutex<Option<ActiveProfile>> {
     static ACTIVE_PROFILE: OnceLock<Mutex<Option<ActiveProfile>>> = OnceLock::new();
     ACTIVE_PROFILE.get_or_init(|| Mutex::new(None))
@@ -79,6 +93,7 @@ pub struct AgentHealthSnapshot {
     pub telemetry: Option<AggregatedTelemetry>,
     pub load_level: LoadLevel,
     pub security_incidents: Vec<SecurityIncident>,
+    pub watchdog: WatchdogReport,
 }
 
 impl AgentHealthSnapshot {
@@ -120,6 +135,19 @@ impl AgentHealthSnapshot {
             confidence = (confidence + 0.15).min(0.99_f32);
         }
 
+        if !self.watchdog.failed_capabilities.is_empty() {
+            rationale.push(format!(
+                "failing capabilities: {}",
+                self.watchdog
+                    .failed_capabilities
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+            confidence = (confidence + 0.1).min(0.99_f32);
+        }
+
         MachineRemediationDirective {
             prefer_machine: true,
             confidence: confidence.min(0.99_f32),
@@ -196,6 +224,7 @@ impl AiControlLoop for KernelHandle {
             telemetry,
             load_level,
             security_incidents,
+            watchdog: watchdog().report(),
         }
     }
 }
@@ -215,7 +244,9 @@ pub fn init_with_manifest(manifest: KernelManifest) -> Result<KernelHandle, Kern
 
     let manifest = Arc::new(manifest);
     token::configure_from_manifest(&manifest);
-    let registry = Arc::new(CapabilityRegistry::new());
+    // Subscribe the watchdog before registration so it sees every lifecycle event
+    watchdog();
+    let registry = Arc::new(CapabilityRegistry::with_events(events::global().clone()));
 
     register_default_capabilities(&registry)?;
 
@@ -251,6 +282,7 @@ pub fn shutdown() {
     if let Some(handle) = handle {
         if let Err(err) = handle.shutdown() {
             eprintln!("[KERNEL] shutdown error: {err}");
+            events::report_error("kernel.shutdown", err);
         }
     }
 
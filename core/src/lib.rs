@@ -7,8 +7,12 @@
 //! - System calls
 //! - Resource scheduling
 
+pub mod boot;
 pub mod capabilities;
 pub mod config;
+pub mod cost;
+pub mod events;
+pub mod flags;
 pub mod fs;
 pub mod gateway;
 pub mod hardware;
@@ -19,13 +23,18 @@ pub mod kernel;
 pub mod memory;
 pub mod metrics;
 pub mod process;
+pub mod recovery;
 pub mod runtime;
+pub mod scheduler;
 pub mod scorekeeper;
 pub mod security;
+pub mod storage;
 pub mod symbols;
+pub mod telemetry;
 pub mod time;
 pub mod token;
 pub mod utils;
+pub mod watchdog;
 pub mod world;
 
 /// Core OS version
@@ -33,24 +42,63 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Initialize the core OS using the kernel capability system.
 pub fn init() -> Result<capabilities::KernelHandle, kernel::KernelError> {
+    init_with_options(boot::BootOptions::default())
+}
+
+/// Initialize the core OS, timing each subsystem against the host's boot budget.
+///
+/// The resulting report is logged and kept available through
+/// [`boot::last_boot_report`].
+pub fn init_with_options(
+    options: boot::BootOptions,
+) -> Result<capabilities::KernelHandle, kernel::KernelError> {
     println!("NOA ARK OS Core v{}", VERSION);
+    let classification = options
+        .classification
+        .clone()
+        .unwrap_or_else(boot::detect_host_classification);
+    let budget = options
+        .budget
+        .clone()
+        .unwrap_or_else(|| boot::BootBudget::for_host(&classification));
+    let mut recorder = boot::BootRecorder::new(classification.clone(), budget);
+
     println!("Initializing kernel-managed capabilities...");
-    let handle = kernel::init()?;
+    let handle = recorder.time("kernel", kernel::init)?;
     println!("Initializing core services...");
 
     // Initialize subsystems
-    kernel::init()?;
-    memory::init().map_err(|e| kernel::KernelError::Init(e.to_string()))?;
-    process::init().map_err(|e| kernel::KernelError::Init(e.to_string()))?;
-    ipc::init().map_err(|e| kernel::KernelError::Init(e.to_string()))?;
-    fs::init().map_err(|e| kernel::KernelError::Init(e.to_string()))?;
-    security::init().map_err(|e| kernel::KernelError::Init(e.to_string()))?;
-    gateway::init()
+    let init_error = |e: &'static str| kernel::KernelError::Init(e.to_string());
+    recorder.time("memory", memory::init).map_err(init_error)?;
+    recorder
+        .time("process", process::init)
+        .map_err(init_error)?;
+    recorder.time("ipc", ipc::init).map_err(init_error)?;
+    recorder.time("fs", fs::init).map_err(init_error)?;
+    recorder
+        .time("security", security::init)
+        .map_err(init_error)?;
+    recorder
+        .time("flags", || flags::init(&classification))
+        .map_err(init_error)?;
+    recorder
+        .time("gateway", gateway::init)
         .map_err(|_| kernel::KernelError::Init("gateway initialization failed".to_string()))?;
 
-    indexer::IndexerService::for_workspace()
-        .refresh()
-        .map_err(|e| kernel::KernelError::Init(format!("workspace indexing failed: {}", e)))?;
+    let mut tasks = vec![boot::DeferrableTask::new("indexer", || {
+        indexer::IndexerService::for_workspace()
+            .refresh()
+            .map(|_| ())
+            .map_err(|e| format!("workspace indexing failed: {}", e))
+    })];
+    tasks.extend(options.extra_tasks);
+    recorder
+        .run_non_critical(tasks, options.defer_when_over_budget)
+        .map_err(kernel::KernelError::Init)?;
+
+    let report = recorder.finish();
+    report.log();
+    boot::store_report(report);
 
     println!("Core OS initialized successfully");
     Ok(handle)
@@ -1,8 +1,12 @@
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
-use std::sync::{OnceLock, RwLock};
+use std::sync::{Arc, OnceLock, RwLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+pub mod timeseries;
+
+use timeseries::{Sample, TimeSeriesStore};
+
 const MAX_HISTORY: usize = 32;
 
 fn registry() -> &'static RwLock<TelemetryRegistry> {
@@ -10,6 +14,11 @@ fn registry() -> &'static RwLock<TelemetryRegistry> {
     REGISTRY.get_or_init(|| RwLock::new(TelemetryRegistry::default()))
 }
 
+fn store_slot() -> &'static RwLock<Option<Arc<TimeSeriesStore>>> {
+    static STORE: OnceLock<RwLock<Option<Arc<TimeSeriesStore>>>> = OnceLock::new();
+    STORE.get_or_init(|| RwLock::new(None))
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum LoadLevel {
     Idle,
@@ -152,11 +161,51 @@ fn derive_load_level(cpu: f32, memory: f32, inference_queue: f32, sandbox_queue:
     }
 }
 
+impl TelemetrySnapshot {
+    /// The snapshot as `os.*` samples for the time-series store.
+    pub fn samples(&self) -> Vec<Sample> {
+        let timestamp_ms = self.timestamp as u64;
+        [
+            ("os.cpu_utilisation", self.cpu_utilisation as f64),
+            ("os.memory_utilisation", self.memory_utilisation as f64),
+            ("os.agent_concurrency", self.agent_concurrency as f64),
+            (
+                "os.inference_queue_depth",
+                self.inference_queue_depth as f64,
+            ),
+            ("os.sandbox_queue_depth", self.sandbox_queue_depth as f64),
+        ]
+        .into_iter()
+        .map(|(series, value)| Sample::new(series, timestamp_ms, value))
+        .collect()
+    }
+}
+
 pub fn record(snapshot: TelemetrySnapshot) {
+    if let Some(store) = history_store() {
+        if let Err(err) = store.append_all(&snapshot.samples()) {
+            eprintln!("[METRICS] Failed to append telemetry history: {err}");
+        }
+    }
     let mut registry = registry().write().expect("metrics registry lock poisoned");
     registry.record(snapshot);
 }
 
+/// Keep every recorded snapshot in `store` as well as the in-memory window, so
+/// history outlives the process and the Prometheus scrape.
+pub fn attach_store(store: Arc<TimeSeriesStore>) {
+    let mut slot = store_slot().write().expect("metrics store lock poisoned");
+    slot.replace(store);
+}
+
+/// The store attached with [`attach_store`], if any.
+pub fn history_store() -> Option<Arc<TimeSeriesStore>> {
+    store_slot()
+        .read()
+        .expect("metrics store lock poisoned")
+        .clone()
+}
+
 pub fn current_snapshot() -> Option<TelemetrySnapshot> {
     let registry = registry().read().expect("metrics registry lock poisoned");
     registry.latest()
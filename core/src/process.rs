@@ -3,17 +3,34 @@
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, Signal, System};
+
+use crate::security::{self, Permission, UserId};
+use crate::utils::current_timestamp_millis;
 
 pub type ProcessId = u64;
 
-#[derive(Debug, Clone)]
+/// How often `kill` polls an OS process while waiting out the grace period.
+const KILL_POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Process {
     pub id: ProcessId,
     pub name: String,
     pub state: ProcessState,
+    /// Capability that created the process, when known.
+    pub owner: Option<String>,
+    /// Backing OS process, if the entry wraps one.
+    pub os_pid: Option<u32>,
+    /// Start of the current run, in milliseconds since the Unix epoch.
+    pub started_at: u128,
+    pub restarts: u32,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ProcessState {
     Ready,
     Running,
@@ -39,12 +56,54 @@ pub fn init() -> Result<(), &'static str> {
     Ok(())
 }
 
-fn create_process_inner(name: String) -> Result<ProcessId, &'static str> {
+/// Process-table errors surfaced to callers managing processes.
+#[derive(Debug, thiserror::Error)]
+pub enum ProcessError {
+    #[error("process {0} not found")]
+    NotFound(ProcessId),
+    #[error("user {user_id} may not terminate process {pid}")]
+    PermissionDenied { user_id: UserId, pid: ProcessId },
+    #[error("failed to signal OS process {0}")]
+    Signal(u32),
+}
+
+/// A live process-table row with resource usage sampled at listing time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessEntry {
+    pub pid: ProcessId,
+    pub name: String,
+    pub state: ProcessState,
+    pub owner: Option<String>,
+    pub os_pid: Option<u32>,
+    /// CPU use of the backing OS process; `None` for in-kernel processes.
+    pub cpu_percent: Option<f32>,
+    pub memory_bytes: Option<u64>,
+    pub uptime_secs: u64,
+    pub restarts: u32,
+}
+
+/// How a `kill` request ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillOutcome {
+    /// The process stopped within the grace period.
+    Graceful,
+    /// The process outlived the grace period and was killed.
+    Forced,
+    /// The process had already terminated.
+    AlreadyTerminated,
+}
+
+fn create_process_inner(name: String, owner: Option<String>) -> Result<ProcessId, &'static str> {
     let pid = next_pid();
     let process = Process {
         id: pid,
         name,
         state: ProcessState::Ready,
+        owner,
+        os_pid: None,
+        started_at: current_timestamp_millis(),
+        restarts: 0,
     };
 
     let mut table = process_table().lock().unwrap();
@@ -65,7 +124,35 @@ pub struct ProcessService;
 impl ProcessService {
     /// Create a new process through the kernel-managed capability.
     pub fn create_process(&self, name: String) -> Result<ProcessId, &'static str> {
-        create_process_inner(name)
+        create_process_inner(name, None)
+    }
+
+    /// Create a process on behalf of the capability that owns it.
+    pub fn create_owned_process(
+        &self,
+        name: String,
+        owner: impl Into<String>,
+    ) -> Result<ProcessId, &'static str> {
+        create_process_inner(name, Some(owner.into()))
+    }
+
+    /// Associate a process entry with the OS process doing its work.
+    pub fn attach_os_process(&self, pid: ProcessId, os_pid: u32) -> Result<(), ProcessError> {
+        let mut table = process_table().lock().unwrap();
+        let process = table.get_mut(&pid).ok_or(ProcessError::NotFound(pid))?;
+        process.os_pid = Some(os_pid);
+        process.state = ProcessState::Running;
+        Ok(())
+    }
+
+    /// Record that a process was restarted, resetting its uptime.
+    pub fn record_restart(&self, pid: ProcessId) -> Result<u32, ProcessError> {
+        let mut table = process_table().lock().unwrap();
+        let process = table.get_mut(&pid).ok_or(ProcessError::NotFound(pid))?;
+        process.restarts += 1;
+        process.started_at = current_timestamp_millis();
+        process.state = ProcessState::Running;
+        Ok(process.restarts)
     }
 
     /// Fetch a process record by identifier.
@@ -78,6 +165,112 @@ impl ProcessService {
         let table = process_table().lock().unwrap();
         table.values().cloned().collect()
     }
+
+    /// Live (non-terminated) processes with CPU and memory use of their OS
+    /// processes, ordered by PID.
+    pub fn list(&self) -> Vec<ProcessEntry> {
+        let mut processes = self
+            .list_processes()
+            .into_iter()
+            .filter(|process| process.state != ProcessState::Terminated)
+            .collect::<Vec<_>>();
+        processes.sort_by_key(|process| process.id);
+
+        let mut system = System::new();
+        let now = current_timestamp_millis();
+        processes
+            .into_iter()
+            .map(|process| {
+                let usage = process.os_pid.and_then(|os_pid| {
+                    let pid = Pid::from_u32(os_pid);
+                    system.refresh_process_specifics(
+                        pid,
+                        ProcessRefreshKind::new().with_cpu().with_memory(),
+                    );
+                    system.process(pid).map(|os| (os.cpu_usage(), os.memory()))
+                });
+                ProcessEntry {
+                    pid: process.id,
+                    name: process.name,
+                    state: process.state,
+                    owner: process.owner,
+                    os_pid: process.os_pid,
+                    cpu_percent: usage.map(|(cpu, _)| cpu),
+                    memory_bytes: usage.map(|(_, memory)| memory),
+                    uptime_secs: (now.saturating_sub(process.started_at) / 1000) as u64,
+                    restarts: process.restarts,
+                }
+            })
+            .collect()
+    }
+
+    /// Terminate a process. OS-backed processes receive SIGTERM and are killed
+    /// outright if still alive after `grace`. Requires execute permission.
+    pub fn kill(
+        &self,
+        user_id: UserId,
+        pid: ProcessId,
+        grace: Duration,
+    ) -> Result<KillOutcome, ProcessError> {
+        if !security::check_permission(user_id, Permission::Execute) {
+            return Err(ProcessError::PermissionDenied { user_id, pid });
+        }
+        let process = get_process_inner(pid).ok_or(ProcessError::NotFound(pid))?;
+        if process.state == ProcessState::Terminated {
+            return Ok(KillOutcome::AlreadyTerminated);
+        }
+
+        let outcome = match process.os_pid {
+            Some(os_pid) => terminate_os_process(os_pid, grace)?,
+            None => KillOutcome::Graceful,
+        };
+
+        let mut table = process_table().lock().unwrap();
+        if let Some(process) = table.get_mut(&pid) {
+            process.state = ProcessState::Terminated;
+        }
+        Ok(outcome)
+    }
+}
+
+fn terminate_os_process(os_pid: u32, grace: Duration) -> Result<KillOutcome, ProcessError> {
+    let pid = Pid::from_u32(os_pid);
+    let mut system = System::new();
+    if !os_process_alive(&mut system, pid) {
+        return Ok(KillOutcome::AlreadyTerminated);
+    }
+
+    let signalled = system
+        .process(pid)
+        .and_then(|process| process.kill_with(Signal::Term))
+        .unwrap_or(false);
+    if signalled {
+        let deadline = Instant::now() + grace;
+        while Instant::now() < deadline {
+            if !os_process_alive(&mut system, pid) {
+                return Ok(KillOutcome::Graceful);
+            }
+            std::thread::sleep(KILL_POLL_INTERVAL);
+        }
+        if !os_process_alive(&mut system, pid) {
+            return Ok(KillOutcome::Graceful);
+        }
+    }
+
+    match system.process(pid).map(|process| process.kill()) {
+        Some(true) => Ok(KillOutcome::Forced),
+        _ => Err(ProcessError::Signal(os_pid)),
+    }
+}
+
+fn os_process_alive(system: &mut System, pid: Pid) -> bool {
+    system.refresh_process_specifics(pid, ProcessRefreshKind::new())
+        && system.process(pid).is_some_and(|process| {
+            !matches!(
+                process.status(),
+                ProcessStatus::Zombie | ProcessStatus::Dead
+            )
+        })
 }
 
 /// Create a new process.
@@ -89,3 +282,50 @@ pub fn create_process(name: String) -> Result<ProcessId, &'static str> {
 pub fn get_process(pid: ProcessId) -> Option<Process> {
     ProcessService.get_process(pid)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_samples_os_usage_and_kill_checks_permissions() {
+        security::init().unwrap();
+        let service = ProcessService;
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("spawn sleep");
+        let pid = service
+            .create_owned_process("sleeper".into(), "test.capability")
+            .unwrap();
+        service.attach_os_process(pid, child.id()).unwrap();
+        assert_eq!(service.record_restart(pid).unwrap(), 1);
+
+        let entry = service
+            .list()
+            .into_iter()
+            .find(|entry| entry.pid == pid)
+            .expect("process listed");
+        assert_eq!(entry.owner.as_deref(), Some("test.capability"));
+        assert_eq!(entry.os_pid, Some(child.id()));
+        assert_eq!(entry.restarts, 1);
+        assert!(entry.memory_bytes.is_some());
+
+        assert!(matches!(
+            service.kill(4242, pid, Duration::from_millis(10)),
+            Err(ProcessError::PermissionDenied { .. })
+        ));
+
+        let reaper = std::thread::spawn(move || child.wait());
+        let outcome = service.kill(0, pid, Duration::from_secs(5)).unwrap();
+        assert_eq!(outcome, KillOutcome::Graceful);
+        reaper.join().unwrap().unwrap();
+
+        assert!(service.list().iter().all(|entry| entry.pid != pid));
+        assert_eq!(
+            service.kill(0, pid, Duration::ZERO).unwrap(),
+            KillOutcome::AlreadyTerminated
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Lenient loading of persisted records.
+//!
+//! Stores written as JSON lines (evidence ledgers, symbol graphs) or as JSON
+//! documents holding record arrays (pipeline state) can be truncated or corrupted
+//! by a crash or a hand edit. [`RecoveryMode::Strict`] rejects the whole store on
+//! the first bad record, as the loaders always have; [`RecoveryMode::Lenient`]
+//! keeps every record that parses and reports the ones it skipped. Neither mode
+//! panics, whatever bytes are on disk.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How a loader treats records that fail to parse.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryMode {
+    /// Fail on the first bad record.
+    #[default]
+    Strict,
+    /// Skip bad records and report them.
+    Lenient,
+}
+
+/// A record a lenient load left out, with where it was and why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedRecord {
+    /// Position in the store, e.g. `line 3` or `pipelines[2]`.
+    pub location: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid record at {location}: {reason}")]
+pub struct RecordError {
+    pub location: String,
+    pub reason: String,
+}
+
+/// Records recovered from a store, and the ones skipped on the way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovered<T> {
+    pub records: Vec<T>,
+    pub skipped: Vec<SkippedRecord>,
+}
+
+impl<T> Default for Recovered<T> {
+    fn default() -> Self {
+        Self {
+            records: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+}
+
+impl<T> Recovered<T> {
+    /// Whether every record parsed.
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_empty()
+    }
+
+    fn accept<E: ToString>(
+        &mut self,
+        mode: RecoveryMode,
+        location: impl FnOnce() -> String,
+        parsed: Result<T, E>,
+    ) -> Result<(), RecordError> {
+        match parsed {
+            Ok(record) => self.records.push(record),
+            Err(err) => {
+                let skipped = SkippedRecord {
+                    location: location(),
+                    reason: err.to_string(),
+                };
+                if mode == RecoveryMode::Strict {
+                    return Err(RecordError {
+                        location: skipped.location,
+                        reason: skipped.reason,
+                    });
+                }
+                self.skipped.push(skipped);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse newline-delimited JSON records. Blank lines are ignored; lines are
+/// numbered from 1 in reports.
+pub fn parse_jsonl<T: DeserializeOwned>(
+    content: &[u8],
+    mode: RecoveryMode,
+) -> Result<Recovered<T>, RecordError> {
+    let mut recovered = Recovered::default();
+    for (index, line) in content.split(|byte| *byte == b'\n').enumerate() {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        recovered.accept(
+            mode,
+            || format!("line {}", index + 1),
+            serde_json::from_slice(line),
+        )?;
+    }
+    Ok(recovered)
+}
+
+/// Deserialize each element of a JSON array field, e.g. `pipelines` of a state
+/// document. A missing field yields no records; a field that is not an array is
+/// reported as a single bad record.
+pub fn parse_records<T: DeserializeOwned>(
+    document: &Value,
+    field: &str,
+    mode: RecoveryMode,
+) -> Result<Recovered<T>, RecordError> {
+    let mut recovered = Recovered::default();
+    match document.get(field) {
+        None | Some(Value::Null) => {}
+        Some(Value::Array(values)) => {
+            for (index, value) in values.iter().enumerate() {
+                recovered.accept(mode, || format!("{field}[{index}]"), T::deserialize(value))?;
+            }
+        }
+        Some(other) => {
+            recovered.accept(
+                mode,
+                || field.to_string(),
+                Err(format!("expected an array, found {}", json_kind(other))),
+            )?;
+        }
+    }
+    Ok(recovered)
+}
+
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        id: u64,
+        name: String,
+    }
+
+    fn record() -> impl Strategy<Value = Record> {
+        (any::<u64>(), ".*").prop_map(|(id, name)| Record { id, name })
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic_on_arbitrary_input(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+            for mode in [RecoveryMode::Strict, RecoveryMode::Lenient] {
+                let _ = parse_jsonl::<Record>(&bytes, mode);
+                if let Ok(document) = serde_json::from_slice::<Value>(&bytes) {
+                    let _ = parse_records::<Record>(&document, "records", mode);
+                }
+            }
+        }
+
+        #[test]
+        fn lenient_mode_keeps_good_records_and_reports_bad_ones(
+            lines in proptest::collection::vec(
+                prop_oneof![
+                    record().prop_map(Ok),
+                    "[^\n]*".prop_map(|garbage| Err(format!("#{garbage}"))),
+                ],
+                0..16,
+            )
+        ) {
+            let content = lines
+                .iter()
+                .map(|line| match line {
+                    Ok(record) => serde_json::to_string(record).unwrap(),
+                    Err(garbage) => garbage.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let expected: Vec<Record> = lines.iter().filter_map(|line| line.clone().ok()).collect();
+            let bad: Vec<String> = lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.is_err())
+                .map(|(index, _)| format!("line {}", index + 1))
+                .collect();
+
+            let recovered = parse_jsonl::<Record>(content.as_bytes(), RecoveryMode::Lenient).unwrap();
+            prop_assert_eq!(&recovered.records, &expected);
+            let skipped: Vec<String> = recovered.skipped.iter().map(|skip| skip.location.clone()).collect();
+            prop_assert_eq!(&skipped, &bad);
+
+            let strict = parse_jsonl::<Record>(content.as_bytes(), RecoveryMode::Strict);
+            match bad.first() {
+                Some(first) => prop_assert_eq!(&strict.unwrap_err().location, first),
+                None => prop_assert_eq!(&strict.unwrap().records, &expected),
+            }
+
+            let document = json!({ "records": content.lines().map(|line| {
+                serde_json::from_str::<Value>(line).unwrap_or_else(|_| json!(line))
+            }).collect::<Vec<_>>() });
+            let recovered = parse_records::<Record>(&document, "records", RecoveryMode::Lenient).unwrap();
+            prop_assert_eq!(recovered.records, expected);
+            prop_assert_eq!(recovered.skipped.len(), bad.len());
+        }
+    }
+}
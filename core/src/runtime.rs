@@ -4,13 +4,14 @@
 //! in the kernel manifest. Each runtime is modeled as a plugin with explicit
 //! dependencies so that startup ordering is deterministic and reproducible.
 
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
 use std::sync::RwLock;
 
 use crate::capabilities::{CapabilityError, CapabilityResult};
 use crate::config::manifest::{RuntimeKind, RuntimeManifestEntry};
 use crate::kernel::{self, AiControlLoop, MachineRemediationDirective};
 use crate::metrics::AggregatedTelemetry;
+use crate::utils::{dependency_order, DependencyError};
 
 /// Runtime plugin state tracked by the kernel.
 #[derive(Debug, Clone)]
@@ -166,58 +167,16 @@ impl RuntimeManager {
 
     fn compute_boot_order(&self) -> CapabilityResult<Vec<String>> {
         let plugins = self.plugins.read().unwrap();
-        let mut in_degree: HashMap<String, usize> = HashMap::new();
-        let mut adjacency: HashMap<String, Vec<String>> = HashMap::new();
-
-        for plugin in plugins.values() {
-            in_degree.entry(plugin.name.clone()).or_insert(0);
-            for dependency in &plugin.depends_on {
-                if !plugins.contains_key(dependency) {
-                    return Err(CapabilityError::ManifestError(format!(
-                        "runtime {} depends on missing runtime {}",
-                        plugin.name, dependency
-                    )));
-                }
-                *in_degree.entry(plugin.name.clone()).or_insert(0) += 1;
-                adjacency
-                    .entry(dependency.clone())
-                    .or_default()
-                    .push(plugin.name.clone());
-            }
-        }
-
-        let mut queue: VecDeque<String> = in_degree
-            .iter()
-            .filter_map(|(runtime, degree)| {
-                if *degree == 0 {
-                    Some(runtime.clone())
-                } else {
-                    None
-                }
-            })
+        let mut graph: Vec<(&String, Vec<&String>)> = plugins
+            .values()
+            .map(|plugin| (&plugin.name, plugin.depends_on.iter().collect()))
             .collect();
-
-        let mut ordered = Vec::new();
-        while let Some(runtime) = queue.pop_front() {
-            ordered.push(runtime.clone());
-            if let Some(children) = adjacency.get(&runtime) {
-                for child in children {
-                    if let Some(degree) = in_degree.get_mut(child) {
-                        *degree -= 1;
-                        if *degree == 0 {
-                            queue.push_back(child.clone());
-                        }
-                    }
-                }
-            }
-        }
-
-        if ordered.len() != plugins.len() {
-            return Err(CapabilityError::DependencyCycle(
-                plugins.keys().cloned().collect(),
-            ));
-        }
-
-        Ok(ordered)
+        graph.sort_by(|a, b| a.0.cmp(b.0));
+        dependency_order(&graph).map_err(|err| match err {
+            DependencyError::Missing { node, dependency } => CapabilityError::ManifestError(
+                format!("runtime {node} depends on missing runtime {dependency}"),
+            ),
+            DependencyError::Cycle(runtimes) => CapabilityError::DependencyCycle(runtimes),
+        })
     }
 }
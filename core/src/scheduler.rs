@@ -0,0 +1,775 @@
+//! Kernel scheduler for capability background work.
+//!
+//! Indexing, archiving, compaction, and embedding generation are submitted as
+//! background jobs with a [`JobPriority`]. Each priority class has its own
+//! concurrency cap, and higher classes are always dispatched first. Work that must
+//! not be disturbed (such as a pipeline run) can pause dispatch of the classes
+//! below a threshold; jobs already running are never interrupted.
+//!
+//! The scheduler also arbitrates host resources. Long-lived services such as the
+//! inference backend [`reserve`](BackgroundScheduler::reserve) CPU cores and VRAM
+//! up front; work such as CI build stages [`acquire`](BackgroundScheduler::acquire)
+//! what they need before running and queue, in request order, while granting it
+//! would exceed the host capacity. Reservations and queued requests are part of the
+//! [`SchedulerSummary`].
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::hardware::{HardwareProfile, HostClassification};
+use crate::utils::current_timestamp_millis;
+
+pub type JobId = u64;
+pub type ReservationId = u64;
+
+/// Finished jobs kept for introspection before the oldest are dropped.
+const MAX_FINISHED_JOBS: usize = 256;
+
+/// Priority class of a background job, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobPriority {
+    /// Bulk maintenance such as archiving, compaction, and embedding generation.
+    Low,
+    Normal,
+    /// Work a user or pipeline is waiting on, such as index refreshes.
+    High,
+}
+
+impl JobPriority {
+    pub const ALL: [JobPriority; 3] = [JobPriority::High, JobPriority::Normal, JobPriority::Low];
+}
+
+/// Maximum number of jobs of each class running at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchedulerLimits {
+    pub high: usize,
+    pub normal: usize,
+    pub low: usize,
+}
+
+impl SchedulerLimits {
+    /// Defaults sized for the host tier.
+    pub fn for_host(classification: &HostClassification) -> Self {
+        match classification {
+            HostClassification::Minimal => Self {
+                high: 1,
+                normal: 1,
+                low: 1,
+            },
+            HostClassification::Standard => Self {
+                high: 2,
+                normal: 2,
+                low: 1,
+            },
+            HostClassification::Accelerated => Self {
+                high: 4,
+                normal: 2,
+                low: 2,
+            },
+        }
+    }
+
+    pub fn limit(&self, priority: JobPriority) -> usize {
+        match priority {
+            JobPriority::High => self.high,
+            JobPriority::Normal => self.normal,
+            JobPriority::Low => self.low,
+        }
+    }
+}
+
+impl Default for SchedulerLimits {
+    fn default() -> Self {
+        Self::for_host(&HostClassification::default())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl JobState {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobState::Completed | JobState::Failed)
+    }
+}
+
+/// Introspection record for one job.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub id: JobId,
+    pub name: String,
+    pub priority: JobPriority,
+    pub state: JobState,
+    pub error: Option<String>,
+    pub submitted_at: u128,
+    pub started_at: Option<u128>,
+    pub finished_at: Option<u128>,
+}
+
+/// Queue depth and activity of one priority class.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassSummary {
+    pub priority: JobPriority,
+    pub queued: usize,
+    pub running: usize,
+    pub limit: usize,
+    pub paused: bool,
+}
+
+/// CPU cores and GPU memory claimed from the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HostResources {
+    pub cpu_cores: u32,
+    pub vram_mb: u64,
+}
+
+impl HostResources {
+    pub fn new(cpu_cores: u32, vram_mb: u64) -> Self {
+        Self { cpu_cores, vram_mb }
+    }
+
+    /// Logical cores and total GPU memory of a detected host.
+    pub fn from_profile(profile: &HardwareProfile) -> Self {
+        Self {
+            cpu_cores: profile.cpu.logical_cores as u32,
+            vram_mb: profile
+                .gpus
+                .iter()
+                .filter_map(|gpu| gpu.memory_total_bytes)
+                .sum::<u64>()
+                / (1024 * 1024),
+        }
+    }
+
+    pub fn fits_within(&self, available: &HostResources) -> bool {
+        self.cpu_cores <= available.cpu_cores && self.vram_mb <= available.vram_mb
+    }
+
+    fn plus(self, other: HostResources) -> Self {
+        Self {
+            cpu_cores: self.cpu_cores.saturating_add(other.cpu_cores),
+            vram_mb: self.vram_mb.saturating_add(other.vram_mb),
+        }
+    }
+
+    fn minus(self, other: HostResources) -> Self {
+        Self {
+            cpu_cores: self.cpu_cores.saturating_sub(other.cpu_cores),
+            vram_mb: self.vram_mb.saturating_sub(other.vram_mb),
+        }
+    }
+}
+
+impl fmt::Display for HostResources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} cores / {} MiB VRAM", self.cpu_cores, self.vram_mb)
+    }
+}
+
+/// Resources granted to one holder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reservation {
+    pub id: ReservationId,
+    pub holder: String,
+    pub resources: HostResources,
+    pub granted_at: u128,
+}
+
+/// A request waiting for reserved resources to be released.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingReservation {
+    pub holder: String,
+    pub resources: HostResources,
+    pub requested_at: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ResourceSummary {
+    /// Host capacity; `None` until the host has been detected, in which case every
+    /// request is granted.
+    pub capacity: Option<HostResources>,
+    pub reserved: HostResources,
+    pub reservations: Vec<Reservation>,
+    /// Queued requests, oldest first.
+    pub pending: Vec<PendingReservation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ReservationError {
+    #[error("{holder} requested {requested}, more than the host capacity of {capacity}")]
+    ExceedsCapacity {
+        holder: String,
+        requested: HostResources,
+        capacity: HostResources,
+    },
+    #[error("{holder} requested {requested} but only {available} is unreserved")]
+    Unavailable {
+        holder: String,
+        requested: HostResources,
+        available: HostResources,
+    },
+    #[error("{holder} timed out waiting for {requested}")]
+    TimedOut {
+        holder: String,
+        requested: HostResources,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchedulerSummary {
+    pub classes: Vec<ClassSummary>,
+    /// Active pause reasons.
+    pub pauses: Vec<String>,
+    pub resources: ResourceSummary,
+}
+
+type JobFn = Box<dyn FnOnce() -> Result<(), String> + Send>;
+
+#[derive(Default)]
+struct SchedulerState {
+    limits: SchedulerLimits,
+    next_id: JobId,
+    queues: BTreeMap<JobPriority, VecDeque<(JobId, JobFn)>>,
+    running: BTreeMap<JobPriority, usize>,
+    jobs: BTreeMap<JobId, JobStatus>,
+    /// Pause reason -> classes strictly below this priority are held.
+    pauses: BTreeMap<String, JobPriority>,
+    capacity: Option<HostResources>,
+    next_reservation: ReservationId,
+    reservations: BTreeMap<ReservationId, Reservation>,
+    /// Requests waiting in [`BackgroundScheduler::acquire`], keyed by their future id.
+    pending: VecDeque<(ReservationId, PendingReservation)>,
+}
+
+impl SchedulerState {
+    fn paused(&self, priority: JobPriority) -> bool {
+        self.pauses.values().any(|threshold| priority < *threshold)
+    }
+
+    fn reserved(&self) -> HostResources {
+        self.reservations
+            .values()
+            .fold(HostResources::default(), |total, reservation| {
+                total.plus(reservation.resources)
+            })
+    }
+
+    fn available(&self) -> Option<HostResources> {
+        self.capacity
+            .map(|capacity| capacity.minus(self.reserved()))
+    }
+
+    fn fits(&self, resources: &HostResources) -> bool {
+        self.available()
+            .is_none_or(|available| resources.fits_within(&available))
+    }
+
+    fn check_capacity(
+        &self,
+        holder: &str,
+        resources: &HostResources,
+    ) -> Result<(), ReservationError> {
+        match self.capacity {
+            Some(capacity) if !resources.fits_within(&capacity) => {
+                Err(ReservationError::ExceedsCapacity {
+                    holder: holder.to_string(),
+                    requested: *resources,
+                    capacity,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn grant(&mut self, id: ReservationId, holder: String, resources: HostResources) {
+        self.reservations.insert(
+            id,
+            Reservation {
+                id,
+                holder,
+                resources,
+                granted_at: current_timestamp_millis(),
+            },
+        );
+    }
+
+    fn prune_finished(&mut self) {
+        let finished = self
+            .jobs
+            .values()
+            .filter(|job| job.state.is_finished())
+            .count();
+        if finished <= MAX_FINISHED_JOBS {
+            return;
+        }
+        let stale = self
+            .jobs
+            .values()
+            .filter(|job| job.state.is_finished())
+            .take(finished - MAX_FINISHED_JOBS)
+            .map(|job| job.id)
+            .collect::<Vec<_>>();
+        for id in stale {
+            self.jobs.remove(&id);
+        }
+    }
+}
+
+/// Priority-aware background job scheduler exposed as the `core.scheduler` capability.
+#[derive(Clone, Default)]
+pub struct BackgroundScheduler {
+    shared: Arc<(Mutex<SchedulerState>, Condvar)>,
+}
+
+impl BackgroundScheduler {
+    pub fn new(limits: SchedulerLimits) -> Self {
+        let scheduler = Self::default();
+        scheduler.lock().limits = limits;
+        scheduler
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SchedulerState> {
+        self.shared.0.lock().unwrap()
+    }
+
+    /// Queue a job; it starts as soon as its class has a free slot and is not paused.
+    pub fn submit(
+        &self,
+        name: impl Into<String>,
+        priority: JobPriority,
+        job: impl FnOnce() -> Result<(), String> + Send + 'static,
+    ) -> JobId {
+        let id = {
+            let mut state = self.lock();
+            state.next_id += 1;
+            let id = state.next_id;
+            state.jobs.insert(
+                id,
+                JobStatus {
+                    id,
+                    name: name.into(),
+                    priority,
+                    state: JobState::Queued,
+                    error: None,
+                    submitted_at: current_timestamp_millis(),
+                    started_at: None,
+                    finished_at: None,
+                },
+            );
+            state
+                .queues
+                .entry(priority)
+                .or_default()
+                .push_back((id, Box::new(job)));
+            id
+        };
+        self.dispatch();
+        id
+    }
+
+    fn dispatch(&self) {
+        let mut state = self.lock();
+        for priority in JobPriority::ALL {
+            if state.paused(priority) {
+                continue;
+            }
+            let limit = state.limits.limit(priority);
+            while state.running.get(&priority).copied().unwrap_or(0) < limit {
+                let Some((id, job)) = state
+                    .queues
+                    .get_mut(&priority)
+                    .and_then(VecDeque::pop_front)
+                else {
+                    break;
+                };
+                *state.running.entry(priority).or_default() += 1;
+                if let Some(status) = state.jobs.get_mut(&id) {
+                    status.state = JobState::Running;
+                    status.started_at = Some(current_timestamp_millis());
+                }
+                let scheduler = self.clone();
+                std::thread::spawn(move || scheduler.run(id, priority, job));
+            }
+        }
+    }
+
+    fn run(&self, id: JobId, priority: JobPriority, job: JobFn) {
+        let outcome = panic::catch_unwind(AssertUnwindSafe(job))
+            .unwrap_or_else(|_| Err("job panicked".to_string()));
+        {
+            let mut state = self.lock();
+            if let Some(running) = state.running.get_mut(&priority) {
+                *running -= 1;
+            }
+            if let Some(status) = state.jobs.get_mut(&id) {
+                status.finished_at = Some(current_timestamp_millis());
+                match outcome {
+                    Ok(()) => status.state = JobState::Completed,
+                    Err(err) => {
+                        status.state = JobState::Failed;
+                        status.error = Some(err);
+                    }
+                }
+            }
+            state.prune_finished();
+        }
+        self.shared.1.notify_all();
+        self.dispatch();
+    }
+
+    /// Hold queued jobs below `below` until [`resume`](Self::resume) is called
+    /// with the same reason.
+    pub fn pause(&self, reason: impl Into<String>, below: JobPriority) {
+        self.lock().pauses.insert(reason.into(), below);
+    }
+
+    pub fn resume(&self, reason: &str) {
+        let removed = self.lock().pauses.remove(reason).is_some();
+        if removed {
+            self.dispatch();
+        }
+    }
+
+    /// Pause lower classes for as long as the returned guard is alive.
+    pub fn pause_guard(&self, reason: impl Into<String>, below: JobPriority) -> PauseGuard {
+        let reason = reason.into();
+        self.pause(reason.clone(), below);
+        PauseGuard {
+            scheduler: self.clone(),
+            reason,
+        }
+    }
+
+    pub fn status(&self, id: JobId) -> Option<JobStatus> {
+        self.lock().jobs.get(&id).cloned()
+    }
+
+    /// Every tracked job, oldest first.
+    pub fn jobs(&self) -> Vec<JobStatus> {
+        self.lock().jobs.values().cloned().collect()
+    }
+
+    pub fn summary(&self) -> SchedulerSummary {
+        let state = self.lock();
+        SchedulerSummary {
+            classes: JobPriority::ALL
+                .into_iter()
+                .map(|priority| ClassSummary {
+                    priority,
+                    queued: state.queues.get(&priority).map_or(0, VecDeque::len),
+                    running: state.running.get(&priority).copied().unwrap_or(0),
+                    limit: state.limits.limit(priority),
+                    paused: state.paused(priority),
+                })
+                .collect(),
+            pauses: state.pauses.keys().cloned().collect(),
+            resources: ResourceSummary {
+                capacity: state.capacity,
+                reserved: state.reserved(),
+                reservations: state.reservations.values().cloned().collect(),
+                pending: state
+                    .pending
+                    .iter()
+                    .map(|(_, pending)| pending.clone())
+                    .collect(),
+            },
+        }
+    }
+
+    /// Set the host resources reservations are granted from.
+    pub fn set_capacity(&self, capacity: HostResources) {
+        self.lock().capacity = Some(capacity);
+        self.shared.1.notify_all();
+    }
+
+    /// Reserve resources immediately, ahead of any queued requests. Meant for
+    /// long-lived services, such as the inference backend, that must not be starved.
+    pub fn reserve(
+        &self,
+        holder: impl Into<String>,
+        resources: HostResources,
+    ) -> Result<ReservationGuard, ReservationError> {
+        let holder = holder.into();
+        let mut state = self.lock();
+        state.check_capacity(&holder, &resources)?;
+        if !state.fits(&resources) {
+            return Err(ReservationError::Unavailable {
+                holder,
+                requested: resources,
+                available: state.available().unwrap_or_default(),
+            });
+        }
+        state.next_reservation += 1;
+        let id = state.next_reservation;
+        state.grant(id, holder, resources);
+        Ok(self.reservation_guard(id))
+    }
+
+    /// Wait until `resources` fit beside the existing reservations. Requests are
+    /// granted in the order they were made, so a large request is not starved by
+    /// smaller ones behind it.
+    pub fn acquire(
+        &self,
+        holder: impl Into<String>,
+        resources: HostResources,
+        timeout: Duration,
+    ) -> Result<ReservationGuard, ReservationError> {
+        let holder = holder.into();
+        let deadline = Instant::now() + timeout;
+        let mut state = self.lock();
+        state.check_capacity(&holder, &resources)?;
+        state.next_reservation += 1;
+        let id = state.next_reservation;
+        state.pending.push_back((
+            id,
+            PendingReservation {
+                holder: holder.clone(),
+                resources,
+                requested_at: current_timestamp_millis(),
+            },
+        ));
+        loop {
+            let first = state.pending.front().map(|(pending, _)| *pending) == Some(id);
+            if first && state.fits(&resources) {
+                state.pending.pop_front();
+                state.grant(id, holder, resources);
+                drop(state);
+                self.shared.1.notify_all();
+                return Ok(self.reservation_guard(id));
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                state.pending.retain(|(pending, _)| *pending != id);
+                drop(state);
+                self.shared.1.notify_all();
+                return Err(ReservationError::TimedOut {
+                    holder,
+                    requested: resources,
+                });
+            }
+            state = self.shared.1.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+
+    /// Release a reservation, letting queued requests proceed.
+    pub fn release(&self, id: ReservationId) {
+        let removed = self.lock().reservations.remove(&id).is_some();
+        if removed {
+            self.shared.1.notify_all();
+        }
+    }
+
+    fn reservation_guard(&self, id: ReservationId) -> ReservationGuard {
+        ReservationGuard {
+            scheduler: self.clone(),
+            id,
+        }
+    }
+
+    /// Block until the job finishes or `timeout` elapses, returning its last status.
+    pub fn wait(&self, id: JobId, timeout: Duration) -> Option<JobStatus> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.lock();
+        loop {
+            let status = state.jobs.get(&id).cloned()?;
+            let now = Instant::now();
+            if status.state.is_finished() || now >= deadline {
+                return Some(status);
+            }
+            state = self.shared.1.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+}
+
+/// Resumes paused classes when dropped.
+pub struct PauseGuard {
+    scheduler: BackgroundScheduler,
+    reason: String,
+}
+
+impl Drop for PauseGuard {
+    fn drop(&mut self) {
+        self.scheduler.resume(&self.reason);
+    }
+}
+
+/// Releases a reservation when dropped.
+pub struct ReservationGuard {
+    scheduler: BackgroundScheduler,
+    id: ReservationId,
+}
+
+impl ReservationGuard {
+    pub fn id(&self) -> ReservationId {
+        self.id
+    }
+}
+
+impl fmt::Debug for ReservationGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReservationGuard")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl Drop for ReservationGuard {
+    fn drop(&mut self) {
+        self.scheduler.release(self.id);
+    }
+}
+
+/// Kernel-wide scheduler shared by capabilities.
+pub fn global() -> &'static BackgroundScheduler {
+    static SCHEDULER: OnceLock<BackgroundScheduler> = OnceLock::new();
+    SCHEDULER.get_or_init(BackgroundScheduler::default)
+}
+
+/// Initialize the background scheduler
+pub fn init() -> Result<(), &'static str> {
+    println!("[SCHEDULER] Initializing background job scheduler...");
+    global().set_capacity(HostResources::from_profile(
+        &crate::hardware::detect_hardware_profile(),
+    ));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn pauses_hold_lower_classes_and_caps_apply_per_class() {
+        let scheduler = BackgroundScheduler::new(SchedulerLimits {
+            high: 1,
+            normal: 1,
+            low: 1,
+        });
+        let guard = scheduler.pause_guard("pipeline:p-1", JobPriority::High);
+
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let blocker = scheduler.submit("index refresh", JobPriority::High, move || {
+            release_rx.recv().map_err(|err| err.to_string())
+        });
+        let queued_high = scheduler.submit("index refresh 2", JobPriority::High, || Ok(()));
+        let compaction = scheduler.submit("compaction", JobPriority::Low, || Ok(()));
+        let failing = scheduler.submit("embeddings", JobPriority::Normal, || {
+            Err("model offline".to_string())
+        });
+
+        let summary = scheduler.summary();
+        assert_eq!(summary.pauses, vec!["pipeline:p-1".to_string()]);
+        let high = &summary.classes[0];
+        assert_eq!((high.running, high.queued, high.paused), (1, 1, false));
+        assert!(summary.classes[1].paused && summary.classes[2].paused);
+        assert_eq!(
+            scheduler.status(compaction).unwrap().state,
+            JobState::Queued
+        );
+
+        release_tx.send(()).unwrap();
+        let wait = Duration::from_secs(5);
+        assert_eq!(
+            scheduler.wait(blocker, wait).unwrap().state,
+            JobState::Completed
+        );
+        assert_eq!(
+            scheduler.wait(queued_high, wait).unwrap().state,
+            JobState::Completed
+        );
+        assert_eq!(
+            scheduler.status(compaction).unwrap().state,
+            JobState::Queued
+        );
+
+        drop(guard);
+        assert_eq!(
+            scheduler.wait(compaction, wait).unwrap().state,
+            JobState::Completed
+        );
+        let failed = scheduler.wait(failing, wait).unwrap();
+        assert_eq!(failed.state, JobState::Failed);
+        assert_eq!(failed.error.as_deref(), Some("model offline"));
+        assert!(scheduler.summary().pauses.is_empty());
+    }
+
+    #[test]
+    fn reservations_queue_requests_that_would_exceed_capacity() {
+        let scheduler = BackgroundScheduler::default();
+        scheduler.set_capacity(HostResources::new(8, 16_384));
+        let inference = scheduler
+            .reserve("inference", HostResources::new(4, 12_288))
+            .unwrap();
+        assert!(matches!(
+            scheduler.reserve("greedy", HostResources::new(2, 8_192)),
+            Err(ReservationError::Unavailable { .. })
+        ));
+        assert!(matches!(
+            scheduler.acquire("huge", HostResources::new(16, 0), Duration::ZERO),
+            Err(ReservationError::ExceedsCapacity { .. })
+        ));
+
+        let first = scheduler
+            .acquire("build-1", HostResources::new(3, 0), Duration::ZERO)
+            .unwrap();
+        let (granted_tx, granted_rx) = mpsc::channel();
+        let waiter = scheduler.clone();
+        let handle = std::thread::spawn(move || {
+            let guard = waiter
+                .acquire("build-2", HostResources::new(2, 0), Duration::from_secs(5))
+                .unwrap();
+            granted_tx.send(()).unwrap();
+            guard
+        });
+        while scheduler.summary().resources.pending.is_empty() {
+            std::thread::yield_now();
+        }
+
+        let resources = scheduler.summary().resources;
+        assert_eq!(resources.reserved, HostResources::new(7, 12_288));
+        assert_eq!(resources.pending[0].holder, "build-2");
+        assert_eq!(
+            resources
+                .reservations
+                .iter()
+                .map(|reservation| reservation.holder.as_str())
+                .collect::<Vec<_>>(),
+            ["inference", "build-1"]
+        );
+        assert!(granted_rx.try_recv().is_err());
+        assert!(matches!(
+            scheduler.acquire(
+                "build-3",
+                HostResources::new(1, 0),
+                Duration::from_millis(10)
+            ),
+            Err(ReservationError::TimedOut { .. })
+        ));
+
+        drop(first);
+        granted_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        let second = handle.join().unwrap();
+        let resources = scheduler.summary().resources;
+        assert!(resources.pending.is_empty());
+        assert_eq!(resources.reserved, HostResources::new(6, 12_288));
+
+        drop((second, inference));
+        assert_eq!(
+            scheduler.summary().resources.reserved,
+            HostResources::default()
+        );
+    }
+}
@@ -32,6 +32,8 @@ pub enum Permission {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum OperationKind {
     FileMove,
+    FileWrite,
+    FileDelete,
     DocumentUpdate,
     StageReceipt,
     SecurityScan,
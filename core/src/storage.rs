@@ -0,0 +1,708 @@
+//! Disk budgets for the workspace's storage subsystems
+//!
+//! Each [`StorageBudget`] caps the bytes one directory (archives, indexes, the
+//! evidence ledger, artifacts) may hold. [`StorageManager::usage`] measures every
+//! budgeted directory, keeps a short history to estimate growth and when the budget
+//! will run out, and records `storage.used_bytes` samples when a time-series store is
+//! attached. [`StorageManager::enforce`] additionally applies each exceeded budget's
+//! [`BudgetAction`]: a warning only, a registered compactor, or evicting the oldest
+//! files that no [`EvictionGuard`] protects. Threshold crossings raise a
+//! [`StorageAlert`], also published on the `storage_alerts` telemetry stream.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::cost::{AlertLevel, DEFAULT_WARN_RATIO};
+use crate::metrics::timeseries::{Sample, SeriesQuery, TimeSeriesStore};
+use crate::telemetry::{FanoutEmitter, TelemetryEmitter, TelemetryRecord};
+
+/// Telemetry stream storage alerts are emitted on.
+pub const STORAGE_ALERT_STREAM: &str = "storage_alerts";
+/// Time series recording each subsystem's usage, labelled by `subsystem`.
+pub const STORAGE_USAGE_SERIES: &str = "storage.used_bytes";
+/// Budgets file read by the CLI when present, relative to the workspace.
+pub const STORAGE_BUDGETS_FILE: &str = "storage/telemetry/storage_budgets.json";
+/// Usage measurements kept per subsystem for growth estimates.
+const MAX_HISTORY: usize = 64;
+/// How far back stored samples seed the growth estimate of a fresh manager.
+const HISTORY_WINDOW_MS: u64 = 24 * 60 * 60 * 1000;
+
+const GIB: u64 = 1 << 30;
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("time series error: {0}")]
+    TimeSeries(#[from] crate::metrics::timeseries::TimeSeriesError),
+}
+
+/// What [`StorageManager::enforce`] does once a budget is exceeded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    /// Raise an alert and leave the data alone, e.g. for hash-chained ledgers.
+    #[default]
+    Warn,
+    /// Run the compactor registered for the subsystem.
+    Compact,
+    /// Delete the least recently modified files until usage is back under the
+    /// warning threshold.
+    EvictOldest,
+}
+
+/// Disk budget of one subsystem directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageBudget {
+    /// Subsystem name, e.g. `archives`.
+    pub name: String,
+    /// Directory relative to the workspace root (or absolute).
+    pub path: PathBuf,
+    pub limit_bytes: u64,
+    /// Fraction of the limit at which a warning is raised.
+    #[serde(default = "default_warn_ratio")]
+    pub warn_ratio: f64,
+    #[serde(default)]
+    pub action: BudgetAction,
+}
+
+fn default_warn_ratio() -> f64 {
+    DEFAULT_WARN_RATIO
+}
+
+impl StorageBudget {
+    pub fn new(name: impl Into<String>, path: impl Into<PathBuf>, limit_bytes: u64) -> Self {
+        Self {
+            name: name.into(),
+            path: path.into(),
+            limit_bytes,
+            warn_ratio: DEFAULT_WARN_RATIO,
+            action: BudgetAction::Warn,
+        }
+    }
+
+    pub fn with_action(mut self, action: BudgetAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    pub fn with_warn_ratio(mut self, warn_ratio: f64) -> Self {
+        self.warn_ratio = warn_ratio;
+        self
+    }
+
+    fn warn_bytes(&self) -> u64 {
+        (self.limit_bytes as f64 * self.warn_ratio) as u64
+    }
+
+    fn status(&self, bytes: u64) -> UsageStatus {
+        if bytes > self.limit_bytes {
+            UsageStatus::Exceeded
+        } else if bytes >= self.warn_bytes() {
+            UsageStatus::Warning
+        } else {
+            UsageStatus::Ok
+        }
+    }
+}
+
+/// Budgets for the archives, indexes, evidence ledger, and artifact store.
+pub fn default_budgets() -> Vec<StorageBudget> {
+    vec![
+        StorageBudget::new("archives", "crc/archive", 20 * GIB)
+            .with_action(BudgetAction::EvictOldest),
+        StorageBudget::new("indexes", ".workspace/indexes", 5 * GIB)
+            .with_action(BudgetAction::Compact),
+        StorageBudget::new("ledger", "storage/db/evidence", 2 * GIB),
+        StorageBudget::new("artifacts", "storage/db/artifacts", 20 * GIB),
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageStatus {
+    Ok,
+    Warning,
+    Exceeded,
+}
+
+/// Measured usage of one budgeted subsystem.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageUsage {
+    pub name: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub files: u64,
+    pub limit_bytes: u64,
+    pub status: UsageStatus,
+    pub measured_at: u64,
+    /// Growth fitted over recent measurements; `None` until there are two.
+    #[serde(default)]
+    pub growth_bytes_per_hour: Option<f64>,
+    /// Unix time in milliseconds at which the limit is reached at the current growth.
+    #[serde(default)]
+    pub projected_exhaustion_at: Option<u64>,
+}
+
+impl StorageUsage {
+    pub fn ratio(&self) -> f64 {
+        if self.limit_bytes == 0 {
+            return f64::INFINITY;
+        }
+        self.bytes as f64 / self.limit_bytes as f64
+    }
+}
+
+/// Raised when a subsystem's usage moves into a worse [`UsageStatus`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageAlert {
+    pub budget: String,
+    pub level: AlertLevel,
+    pub used_bytes: u64,
+    pub limit_bytes: u64,
+    pub action: BudgetAction,
+    #[serde(default)]
+    pub projected_exhaustion_at: Option<u64>,
+    pub raised_at: u64,
+}
+
+/// Outcome of applying a budget's action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnforcementAction {
+    pub budget: String,
+    pub action: BudgetAction,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Files deleted by [`BudgetAction::EvictOldest`], relative to the budget directory.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evicted: Vec<PathBuf>,
+    /// Files an [`EvictionGuard`] kept although they were old enough to be evicted.
+    #[serde(default)]
+    pub protected: usize,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageReport {
+    pub usage: Vec<StorageUsage>,
+    pub alerts: Vec<StorageAlert>,
+    pub actions: Vec<EnforcementAction>,
+}
+
+/// Shrinks a subsystem directory in place, e.g. by merging segments.
+pub type Compactor = Arc<dyn Fn(&Path) -> Result<(), String> + Send + Sync>;
+
+/// Returns whether a file found under a budget directory (joined onto the manager's root)
+/// must survive eviction, e.g. because it belongs to a pinned release.
+pub type EvictionGuard = Arc<dyn Fn(&Path) -> bool + Send + Sync>;
+
+#[derive(Default)]
+struct ManagerState {
+    budgets: Vec<StorageBudget>,
+    compactors: HashMap<String, Compactor>,
+    guards: Vec<EvictionGuard>,
+    history: HashMap<String, VecDeque<(u64, u64)>>,
+    status: HashMap<String, UsageStatus>,
+    alerts: Vec<StorageAlert>,
+}
+
+/// Watches the budgeted directories under a workspace root.
+pub struct StorageManager {
+    root: PathBuf,
+    state: Mutex<ManagerState>,
+    emitter: FanoutEmitter,
+    timeseries: Option<Arc<TimeSeriesStore>>,
+}
+
+impl fmt::Debug for StorageManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("StorageManager")
+            .field("root", &self.root)
+            .field("budgets", &state.budgets.len())
+            .field("compactors", &state.compactors.len())
+            .field("guards", &state.guards.len())
+            .finish()
+    }
+}
+
+impl StorageManager {
+    /// Manager without budgets for the workspace at `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            state: Mutex::new(ManagerState::default()),
+            emitter: FanoutEmitter::default(),
+            timeseries: None,
+        }
+    }
+
+    /// Manager with [`default_budgets`].
+    pub fn with_defaults(root: impl Into<PathBuf>) -> Self {
+        default_budgets()
+            .into_iter()
+            .fold(Self::new(root), Self::with_budget)
+    }
+
+    pub fn with_budget(self, budget: StorageBudget) -> Self {
+        self.set_budget(budget);
+        self
+    }
+
+    /// Also publish alerts to `emitter` on the `storage_alerts` stream.
+    pub fn with_emitter(mut self, emitter: Arc<dyn TelemetryEmitter>) -> Self {
+        self.emitter = self.emitter.with(emitter);
+        self
+    }
+
+    /// Record every measurement as a `storage.used_bytes` sample, and seed growth
+    /// estimates from the last day of samples so one-off runs can project exhaustion.
+    pub fn with_timeseries(mut self, store: Arc<TimeSeriesStore>) -> Self {
+        self.timeseries = Some(store);
+        self
+    }
+
+    /// Add a budget, replacing any with the same name.
+    pub fn set_budget(&self, budget: StorageBudget) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .budgets
+            .retain(|existing| existing.name != budget.name);
+        state.budgets.push(budget);
+    }
+
+    /// Load budgets from a JSON array, e.g. [`STORAGE_BUDGETS_FILE`].
+    pub fn load_budgets(&self, path: &Path) -> Result<usize, StorageError> {
+        let budgets: Vec<StorageBudget> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let count = budgets.len();
+        for budget in budgets {
+            self.set_budget(budget);
+        }
+        Ok(count)
+    }
+
+    pub fn budgets(&self) -> Vec<StorageBudget> {
+        self.state.lock().unwrap().budgets.clone()
+    }
+
+    /// Register the compactor run for `budget` when its action is [`BudgetAction::Compact`].
+    pub fn set_compactor(&self, budget: impl Into<String>, compactor: Compactor) {
+        self.state
+            .lock()
+            .unwrap()
+            .compactors
+            .insert(budget.into(), compactor);
+    }
+
+    /// Keep every file `guard` protects out of [`BudgetAction::EvictOldest`] evictions.
+    pub fn add_eviction_guard(&self, guard: EvictionGuard) {
+        self.state.lock().unwrap().guards.push(guard);
+    }
+
+    /// Alerts raised so far, oldest first.
+    pub fn alerts(&self) -> Vec<StorageAlert> {
+        self.state.lock().unwrap().alerts.clone()
+    }
+
+    /// Measure every budgeted directory at `now_ms`.
+    pub fn usage(&self, now_ms: u64) -> Result<Vec<StorageUsage>, StorageError> {
+        Ok(self.measure_all(now_ms)?.0)
+    }
+
+    /// Measure every budgeted directory and apply the action of each exceeded budget.
+    pub fn enforce(&self, now_ms: u64) -> Result<StorageReport, StorageError> {
+        let (measured, mut alerts) = self.measure_all(now_ms)?;
+        let budgets = self.budgets();
+        let mut usage = Vec::with_capacity(measured.len());
+        let mut actions = Vec::new();
+        for (budget, measured) in budgets.iter().zip(measured) {
+            if measured.status != UsageStatus::Exceeded || budget.action == BudgetAction::Warn {
+                usage.push(measured);
+                continue;
+            }
+            let dir = self.dir(budget);
+            let (evicted, protected, error) = match budget.action {
+                BudgetAction::Compact => {
+                    let compactor = self
+                        .state
+                        .lock()
+                        .unwrap()
+                        .compactors
+                        .get(&budget.name)
+                        .cloned();
+                    let error = match compactor {
+                        Some(compactor) => compactor(&dir).err(),
+                        None => Some(format!("no compactor registered for '{}'", budget.name)),
+                    };
+                    (Vec::new(), 0, error)
+                }
+                BudgetAction::EvictOldest => {
+                    let guards = self.state.lock().unwrap().guards.clone();
+                    match evict_oldest(&dir, measured.bytes, budget.warn_bytes(), &guards) {
+                        Ok((evicted, protected)) => (evicted, protected, None),
+                        Err(err) => (Vec::new(), 0, Some(err.to_string())),
+                    }
+                }
+                BudgetAction::Warn => unreachable!("warn budgets are skipped above"),
+            };
+            let (after, more) = self.measure(budget, now_ms)?;
+            alerts.extend(more);
+            actions.push(EnforcementAction {
+                budget: budget.name.clone(),
+                action: budget.action,
+                bytes_before: measured.bytes,
+                bytes_after: after.bytes,
+                evicted,
+                protected,
+                error,
+            });
+            usage.push(after);
+        }
+        Ok(StorageReport {
+            usage,
+            alerts,
+            actions,
+        })
+    }
+
+    fn measure_all(
+        &self,
+        now_ms: u64,
+    ) -> Result<(Vec<StorageUsage>, Vec<StorageAlert>), StorageError> {
+        let mut usage = Vec::new();
+        let mut alerts = Vec::new();
+        for budget in self.budgets() {
+            let (measured, raised) = self.measure(&budget, now_ms)?;
+            usage.push(measured);
+            alerts.extend(raised);
+        }
+        Ok((usage, alerts))
+    }
+
+    /// Measure one budget, record it in the history and time series, and raise an
+    /// alert if its status got worse since the last measurement.
+    fn measure(
+        &self,
+        budget: &StorageBudget,
+        now_ms: u64,
+    ) -> Result<(StorageUsage, Option<StorageAlert>), StorageError> {
+        let (bytes, files) = directory_size(&self.dir(budget))?;
+        let status = budget.status(bytes);
+        let seed = self.stored_history(budget, now_ms)?;
+        let (growth, alert) = {
+            let mut state = self.state.lock().unwrap();
+            let history = state
+                .history
+                .entry(budget.name.clone())
+                .or_insert_with(|| seed.unwrap_or_default());
+            if history.back().is_some_and(|(at, _)| *at == now_ms) {
+                history.pop_back();
+            }
+            history.push_back((now_ms, bytes));
+            while history.len() > MAX_HISTORY {
+                history.pop_front();
+            }
+            let growth = growth_per_ms(history);
+            let previous = state
+                .status
+                .insert(budget.name.clone(), status)
+                .unwrap_or(UsageStatus::Ok);
+            let level = match status {
+                UsageStatus::Exceeded if previous != UsageStatus::Exceeded => {
+                    Some(AlertLevel::Exceeded)
+                }
+                UsageStatus::Warning if previous == UsageStatus::Ok => Some(AlertLevel::Warning),
+                _ => None,
+            };
+            let alert = level.map(|level| StorageAlert {
+                budget: budget.name.clone(),
+                level,
+                used_bytes: bytes,
+                limit_bytes: budget.limit_bytes,
+                action: budget.action,
+                projected_exhaustion_at: exhaustion_at(budget, bytes, growth, now_ms),
+                raised_at: now_ms,
+            });
+            if let Some(alert) = &alert {
+                state.alerts.push(alert.clone());
+            }
+            (growth, alert)
+        };
+        if let Some(alert) = &alert {
+            // Alerts stay on the manager even when an emitter is unreachable.
+            let _ = TelemetryRecord::of(STORAGE_ALERT_STREAM, alert)
+                .and_then(|record| self.emitter.emit(&record));
+        }
+        if let Some(store) = &self.timeseries {
+            store.append(
+                Sample::new(STORAGE_USAGE_SERIES, now_ms, bytes as f64)
+                    .with_label("subsystem", budget.name.clone()),
+            )?;
+        }
+        Ok((
+            StorageUsage {
+                name: budget.name.clone(),
+                path: budget.path.clone(),
+                bytes,
+                files,
+                limit_bytes: budget.limit_bytes,
+                status,
+                measured_at: now_ms,
+                growth_bytes_per_hour: growth.map(|per_ms| per_ms * 3_600_000.0),
+                projected_exhaustion_at: exhaustion_at(budget, bytes, growth, now_ms),
+            },
+            alert,
+        ))
+    }
+
+    /// Earlier measurements of `budget` from the time-series store, when the manager
+    /// has none of its own yet.
+    fn stored_history(
+        &self,
+        budget: &StorageBudget,
+        now_ms: u64,
+    ) -> Result<Option<VecDeque<(u64, u64)>>, StorageError> {
+        let Some(store) = &self.timeseries else {
+            return Ok(None);
+        };
+        if self
+            .state
+            .lock()
+            .unwrap()
+            .history
+            .contains_key(&budget.name)
+        {
+            return Ok(None);
+        }
+        let query = SeriesQuery::new(
+            STORAGE_USAGE_SERIES,
+            now_ms.saturating_sub(HISTORY_WINDOW_MS),
+            now_ms,
+        )
+        .with_label("subsystem", budget.name.clone());
+        let mut points: Vec<(u64, u64)> = store
+            .query(&query, now_ms)?
+            .into_iter()
+            .flat_map(|series| series.points)
+            .map(|point| (point.timestamp_ms, point.mean() as u64))
+            .collect();
+        points.sort_unstable();
+        let skip = points.len().saturating_sub(MAX_HISTORY - 1);
+        Ok(Some(points.into_iter().skip(skip).collect()))
+    }
+
+    fn dir(&self, budget: &StorageBudget) -> PathBuf {
+        self.root.join(&budget.path)
+    }
+}
+
+/// Least-squares slope of bytes over time, in bytes per millisecond.
+fn growth_per_ms(history: &VecDeque<(u64, u64)>) -> Option<f64> {
+    if history.len() < 2 {
+        return None;
+    }
+    let n = history.len() as f64;
+    let t0 = history.front()?.0;
+    let points = history
+        .iter()
+        .map(|(at, bytes)| ((at - t0) as f64, *bytes as f64));
+    let (sum_t, sum_b) = points
+        .clone()
+        .fold((0.0, 0.0), |(st, sb), (t, b)| (st + t, sb + b));
+    let (mean_t, mean_b) = (sum_t / n, sum_b / n);
+    let (cov, var) = points.fold((0.0, 0.0), |(cov, var), (t, b)| {
+        (
+            cov + (t - mean_t) * (b - mean_b),
+            var + (t - mean_t).powi(2),
+        )
+    });
+    (var > 0.0).then(|| cov / var)
+}
+
+fn exhaustion_at(
+    budget: &StorageBudget,
+    bytes: u64,
+    growth: Option<f64>,
+    now_ms: u64,
+) -> Option<u64> {
+    if bytes >= budget.limit_bytes {
+        return Some(now_ms);
+    }
+    let growth = growth.filter(|per_ms| *per_ms > 0.0)?;
+    let remaining = (budget.limit_bytes - bytes) as f64;
+    Some(now_ms.saturating_add((remaining / growth).ceil() as u64))
+}
+
+/// Total size and number of regular files under `dir`; a missing directory is empty.
+fn directory_size(dir: &Path) -> std::io::Result<(u64, u64)> {
+    let mut total = (0, 0);
+    for (_, metadata) in files_under(dir)? {
+        total.0 += metadata.len();
+        total.1 += 1;
+    }
+    Ok(total)
+}
+
+fn files_under(dir: &Path) -> std::io::Result<Vec<(PathBuf, fs::Metadata)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = match fs::read_dir(&current) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.path().symlink_metadata()?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if metadata.is_file() {
+                files.push((entry.path(), metadata));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Remove the least recently modified unguarded files under `dir` until at most `target`
+/// bytes remain. Also returns how many files the guards kept.
+fn evict_oldest(
+    dir: &Path,
+    mut bytes: u64,
+    target: u64,
+    guards: &[EvictionGuard],
+) -> std::io::Result<(Vec<PathBuf>, usize)> {
+    let mut files = files_under(dir)?;
+    files.sort_by_key(|(path, metadata)| (metadata.modified().unwrap_or(UNIX_EPOCH), path.clone()));
+    let mut evicted = Vec::new();
+    let mut protected = 0;
+    for (path, metadata) in files {
+        if bytes <= target {
+            break;
+        }
+        if guards.iter().any(|guard| guard(&path)) {
+            protected += 1;
+            continue;
+        }
+        fs::remove_file(&path)?;
+        bytes = bytes.saturating_sub(metadata.len());
+        evicted.push(path.strip_prefix(dir).unwrap_or(&path).to_path_buf());
+    }
+    Ok((evicted, protected))
+}
+
+/// Current Unix time in milliseconds, for callers of [`StorageManager::enforce`].
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::MemoryEmitter;
+    use std::time::Duration;
+
+    fn write(path: &Path, bytes: usize, age_secs: u64) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; bytes]).unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(age_secs);
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn budgets_project_exhaustion_and_apply_their_actions() {
+        let root = tempfile::tempdir().unwrap();
+        let memory = Arc::new(MemoryEmitter::default());
+        let store = Arc::new(TimeSeriesStore::open(root.path().join("metrics")).unwrap());
+        let manager = StorageManager::new(root.path())
+            .with_emitter(memory.clone())
+            .with_timeseries(store.clone())
+            .with_budget(
+                StorageBudget::new("archives", "archive", 1_000)
+                    .with_action(BudgetAction::EvictOldest),
+            )
+            .with_budget(
+                StorageBudget::new("indexes", "indexes", 1_000).with_action(BudgetAction::Compact),
+            )
+            .with_budget(StorageBudget::new("ledger", "ledger", 1_000));
+
+        write(&root.path().join("archive/a.tar"), 300, 300);
+        write(&root.path().join("ledger/ledger.jsonl"), 100, 0);
+        let first = manager.usage(0).unwrap();
+        assert_eq!(first[0].bytes, 300);
+        assert_eq!(first[0].growth_bytes_per_hour, None);
+        assert_eq!(first[1].files, 0);
+
+        write(&root.path().join("archive/b.tar"), 300, 200);
+        let second = manager.usage(3_600_000).unwrap();
+        assert_eq!(second[0].growth_bytes_per_hour, Some(300.0));
+        // 400 bytes left at 300 bytes an hour
+        assert_eq!(
+            second[0].projected_exhaustion_at,
+            Some(3_600_000 + 4_800_000)
+        );
+        assert_eq!(second[2].projected_exhaustion_at, None);
+
+        write(&root.path().join("archive/c.tar"), 300, 100);
+        write(&root.path().join("archive/d.tar"), 300, 0);
+        write(&root.path().join("indexes/segment-1"), 1_200, 0);
+        write(&root.path().join("ledger/ledger.jsonl"), 1_100, 0);
+        manager.set_compactor(
+            "indexes",
+            Arc::new(|dir: &Path| {
+                fs::remove_file(dir.join("segment-1")).map_err(|err| err.to_string())
+            }),
+        );
+        let report = manager.enforce(7_200_000).unwrap();
+
+        let archives = &report.actions[0];
+        assert_eq!(archives.bytes_before, 1_200);
+        assert_eq!(archives.bytes_after, 600);
+        assert_eq!(
+            archives.evicted,
+            vec![PathBuf::from("a.tar"), PathBuf::from("b.tar")]
+        );
+        assert_eq!(report.actions[1].bytes_after, 0);
+        assert_eq!(report.actions.len(), 2, "warn-only ledger is left alone");
+        assert_eq!(report.usage[0].status, UsageStatus::Ok);
+        assert_eq!(report.usage[2].status, UsageStatus::Exceeded);
+        assert!(root.path().join("ledger/ledger.jsonl").exists());
+
+        let exceeded: Vec<&str> = report
+            .alerts
+            .iter()
+            .filter(|alert| alert.level == AlertLevel::Exceeded)
+            .map(|alert| alert.budget.as_str())
+            .collect();
+        assert_eq!(exceeded, vec!["archives", "indexes", "ledger"]);
+        assert_eq!(memory.records().len(), manager.alerts().len());
+        assert_eq!(memory.records()[0].stream, STORAGE_ALERT_STREAM);
+        assert!(manager.enforce(7_300_000).unwrap().alerts.is_empty());
+
+        // A fresh manager picks up the recorded history.
+        let restarted = StorageManager::new(root.path())
+            .with_timeseries(store)
+            .with_budget(StorageBudget::new("archives", "archive", 1_000));
+        let usage = restarted.usage(7_400_000).unwrap();
+        assert!(usage[0].growth_bytes_per_hour.is_some());
+    }
+}
@@ -0,0 +1,279 @@
+//! Pluggable telemetry emission
+//!
+//! Components describe what they record as a [`TelemetryRecord`] (a stream name and
+//! a JSON payload) and hand it to a [`TelemetryEmitter`] instead of writing files
+//! themselves. [`FileEmitter`] appends JSON lines to `<dir>/<stream>.log`,
+//! [`IpcEmitter`] publishes `{"topic", "payload"}` envelopes on an IPC channel so
+//! workflow triggers can react to them, and [`FanoutEmitter`] sends each record to
+//! several emitters. The OTLP exporter lives in `noa_observability`.
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::ipc::{self, ChannelId, Message};
+
+#[derive(Debug, Error)]
+pub enum TelemetryError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("ipc channel {channel}: {message}")]
+    Channel { channel: ChannelId, message: String },
+    #[error("export failed: {0}")]
+    Export(String),
+}
+
+/// One telemetry item: the stream it belongs to and its JSON payload.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TelemetryRecord {
+    /// Stream name, e.g. `pipeline_events` or `gateway_trust`. File emitters use it
+    /// as the log file name and IPC emitters as the topic.
+    pub stream: String,
+    pub payload: Value,
+}
+
+impl TelemetryRecord {
+    pub fn new(stream: impl Into<String>, payload: Value) -> Self {
+        Self {
+            stream: stream.into(),
+            payload,
+        }
+    }
+
+    /// Record for any serializable value.
+    pub fn of<T: Serialize>(stream: impl Into<String>, value: &T) -> Result<Self, TelemetryError> {
+        Ok(Self::new(stream, serde_json::to_value(value)?))
+    }
+}
+
+/// Destination for telemetry records.
+pub trait TelemetryEmitter: Send + Sync {
+    fn emit(&self, record: &TelemetryRecord) -> Result<(), TelemetryError>;
+
+    /// Push out anything buffered.
+    fn flush(&self) -> Result<(), TelemetryError> {
+        Ok(())
+    }
+}
+
+/// Appends each record as one JSON line to `<dir>/<stream>.log` in every directory.
+#[derive(Debug, Clone)]
+pub struct FileEmitter {
+    dirs: Vec<PathBuf>,
+    sync: bool,
+}
+
+impl FileEmitter {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dirs: vec![dir.into()],
+            sync: false,
+        }
+    }
+
+    /// Also write every record under `dir`, e.g. a storage mirror.
+    pub fn mirrored_to(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dirs.push(dir.into());
+        self
+    }
+
+    /// fsync after every record, for ledgers that must survive a crash.
+    pub fn synced(mut self) -> Self {
+        self.sync = true;
+        self
+    }
+
+    /// Log file for `stream` in the primary directory.
+    pub fn path_for(&self, stream: &str) -> PathBuf {
+        stream_path(&self.dirs[0], stream)
+    }
+}
+
+fn stream_path(dir: &Path, stream: &str) -> PathBuf {
+    dir.join(format!("{stream}.log"))
+}
+
+impl TelemetryEmitter for FileEmitter {
+    fn emit(&self, record: &TelemetryRecord) -> Result<(), TelemetryError> {
+        let line = format!("{}\n", serde_json::to_string(&record.payload)?);
+        for dir in &self.dirs {
+            fs::create_dir_all(dir)?;
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(stream_path(dir, &record.stream))?;
+            file.write_all(line.as_bytes())?;
+            if self.sync {
+                file.flush()?;
+                file.sync_all()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Publishes records on an IPC channel as `{"topic": stream, "payload": ...}`, the
+/// envelope workflow event triggers decode.
+#[derive(Debug, Clone)]
+pub struct IpcEmitter {
+    channel: ChannelId,
+    from: u64,
+}
+
+impl IpcEmitter {
+    /// Emitter for an existing `channel`; `from` identifies the sender.
+    pub fn new(channel: ChannelId, from: u64) -> Self {
+        Self { channel, from }
+    }
+}
+
+impl TelemetryEmitter for IpcEmitter {
+    fn emit(&self, record: &TelemetryRecord) -> Result<(), TelemetryError> {
+        let data = serde_json::to_vec(&json!({
+            "topic": record.stream,
+            "payload": record.payload,
+        }))?;
+        ipc::send_message(
+            self.channel,
+            Message {
+                from: self.from,
+                to: self.channel,
+                data,
+            },
+        )
+        .map_err(|err| TelemetryError::Channel {
+            channel: self.channel,
+            message: err.to_string(),
+        })
+    }
+}
+
+/// Sends every record to each emitter in turn. All emitters are tried even when
+/// one fails; the first error is returned.
+#[derive(Clone, Default)]
+pub struct FanoutEmitter {
+    emitters: Vec<Arc<dyn TelemetryEmitter>>,
+}
+
+impl FanoutEmitter {
+    pub fn new(emitters: Vec<Arc<dyn TelemetryEmitter>>) -> Self {
+        Self { emitters }
+    }
+
+    pub fn with(mut self, emitter: Arc<dyn TelemetryEmitter>) -> Self {
+        self.emitters.push(emitter);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.emitters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.emitters.is_empty()
+    }
+}
+
+impl fmt::Debug for FanoutEmitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FanoutEmitter")
+            .field("emitters", &self.emitters.len())
+            .finish()
+    }
+}
+
+impl TelemetryEmitter for FanoutEmitter {
+    fn emit(&self, record: &TelemetryRecord) -> Result<(), TelemetryError> {
+        first_error(self.emitters.iter().map(|emitter| emitter.emit(record)))
+    }
+
+    fn flush(&self) -> Result<(), TelemetryError> {
+        first_error(self.emitters.iter().map(|emitter| emitter.flush()))
+    }
+}
+
+/// Drains `results` and returns the first error, if any.
+fn first_error(
+    results: impl Iterator<Item = Result<(), TelemetryError>>,
+) -> Result<(), TelemetryError> {
+    let mut outcome = Ok(());
+    for result in results {
+        if outcome.is_ok() {
+            outcome = result;
+        }
+    }
+    outcome
+}
+
+/// Keeps records in memory; useful for tests and in-process inspection.
+#[derive(Debug, Default)]
+pub struct MemoryEmitter {
+    records: Mutex<Vec<TelemetryRecord>>,
+}
+
+impl MemoryEmitter {
+    pub fn records(&self) -> Vec<TelemetryRecord> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+impl TelemetryEmitter for MemoryEmitter {
+    fn emit(&self, record: &TelemetryRecord) -> Result<(), TelemetryError> {
+        self.records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FailingEmitter;
+
+    impl TelemetryEmitter for FailingEmitter {
+        fn emit(&self, _record: &TelemetryRecord) -> Result<(), TelemetryError> {
+            Err(TelemetryError::Export("collector unreachable".into()))
+        }
+    }
+
+    #[test]
+    fn fanout_reaches_files_ipc_and_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let channel: ChannelId = 0x7e1e_0001;
+        ipc::create_channel(channel).unwrap();
+        let memory = Arc::new(MemoryEmitter::default());
+        let fanout = FanoutEmitter::new(vec![
+            Arc::new(FailingEmitter),
+            Arc::new(
+                FileEmitter::new(dir.path().join("index")).mirrored_to(dir.path().join("mirror")),
+            ),
+            Arc::new(IpcEmitter::new(channel, 7)),
+            memory.clone(),
+        ]);
+
+        let record = TelemetryRecord::new("gateway_events", json!({ "request_id": "r-1" }));
+        assert!(matches!(
+            fanout.emit(&record),
+            Err(TelemetryError::Export(_))
+        ));
+
+        let index = fs::read_to_string(dir.path().join("index/gateway_events.log")).unwrap();
+        let mirror = fs::read_to_string(dir.path().join("mirror/gateway_events.log")).unwrap();
+        assert_eq!(index, "{\"request_id\":\"r-1\"}\n");
+        assert_eq!(index, mirror);
+
+        let message = ipc::receive_message(channel).expect("published on the bus");
+        let envelope: Value = serde_json::from_slice(&message.data).unwrap();
+        assert_eq!(envelope["topic"], "gateway_events");
+        assert_eq!(envelope["payload"]["request_id"], "r-1");
+        assert_eq!(memory.records(), vec![record]);
+    }
+}
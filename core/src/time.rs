@@ -1,7 +1,30 @@
 //! Time utilities for NOA ARK OS
+//!
+//! Besides the wall clock, this module tracks how far peer hosts' clocks drift from
+//! the local one. Security decisions that compare timestamps (token expiry,
+//! approval deadlines) go through [`ClockSkewMonitor::deadline_passed`], which
+//! widens the deadline by the configured skew tolerance plus the estimated drift
+//! and records every decision that only passed because of that widening.
 
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use serde::{Deserialize, Serialize};
+
+/// Environment variable overriding [`DEFAULT_SKEW_TOLERANCE_MS`].
+pub const SKEW_TOLERANCE_ENV: &str = "NOA_CLOCK_SKEW_TOLERANCE_MS";
+/// Skew tolerated on every deadline, regardless of observed drift.
+pub const DEFAULT_SKEW_TOLERANCE_MS: u64 = 30_000;
+/// Largest drift estimate added to a validation window, so peer timestamps
+/// cannot stretch deadlines indefinitely.
+pub const MAX_DRIFT_CORRECTION_MS: u64 = 300_000;
+
+/// Peer clock offsets kept for the drift estimate.
+const DRIFT_SAMPLES: usize = 32;
+/// Skew corrections kept for inspection before the oldest are dropped.
+const MAX_RECORDED_CORRECTIONS: usize = 128;
+
 /// Get the current timestamp in milliseconds since the Unix epoch.
 ///
 /// Returns 0 if the system time is before the Unix epoch (which should never happen
@@ -13,6 +36,147 @@ pub fn current_timestamp_millis() -> u128 {
         .unwrap_or(0)
 }
 
+/// A security decision that was accepted only because of skew tolerance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkewCorrection {
+    /// Kind of decision, e.g. `token.validate`.
+    pub decision: String,
+    /// What the decision was about, e.g. the token holder.
+    pub subject: String,
+    pub deadline_ms: u128,
+    pub observed_ms: u128,
+    /// How far past the raw deadline the local clock was.
+    pub corrected_by_ms: u128,
+}
+
+#[derive(Debug)]
+struct SkewState {
+    tolerance_ms: u64,
+    /// Peer minus local clock, in milliseconds.
+    offsets: VecDeque<i128>,
+    corrections: VecDeque<SkewCorrection>,
+}
+
+/// Skew tolerance and peer clock drift detection for timestamp comparisons.
+#[derive(Debug)]
+pub struct ClockSkewMonitor {
+    state: Mutex<SkewState>,
+}
+
+impl ClockSkewMonitor {
+    pub fn new(tolerance_ms: u64) -> Self {
+        Self {
+            state: Mutex::new(SkewState {
+                tolerance_ms,
+                offsets: VecDeque::new(),
+                corrections: VecDeque::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SkewState> {
+        self.state.lock().expect("clock skew state poisoned")
+    }
+
+    pub fn tolerance_ms(&self) -> u64 {
+        self.lock().tolerance_ms
+    }
+
+    pub fn set_tolerance_ms(&self, tolerance_ms: u64) {
+        self.lock().tolerance_ms = tolerance_ms;
+    }
+
+    /// Record a timestamp a peer host took at about local time `local_ms`.
+    ///
+    /// Every reading widens deadlines across the process, so only feed it clocks of
+    /// authenticated peer hosts, never timestamps from request payloads.
+    pub fn observe_peer_timestamp(&self, peer_ms: u128, local_ms: u128) {
+        let mut state = self.lock();
+        if state.offsets.len() == DRIFT_SAMPLES {
+            state.offsets.pop_front();
+        }
+        state.offsets.push_back(peer_ms as i128 - local_ms as i128);
+    }
+
+    /// Median peer-minus-local offset over recent observations.
+    pub fn estimated_drift_ms(&self) -> i128 {
+        let mut offsets: Vec<i128> = self.lock().offsets.iter().copied().collect();
+        if offsets.is_empty() {
+            return 0;
+        }
+        offsets.sort_unstable();
+        offsets[offsets.len() / 2]
+    }
+
+    /// Slack allowed past a deadline: the tolerance plus the capped drift estimate.
+    pub fn validation_window_ms(&self) -> u128 {
+        let drift = self
+            .estimated_drift_ms()
+            .unsigned_abs()
+            .min(u128::from(MAX_DRIFT_CORRECTION_MS));
+        u128::from(self.tolerance_ms()) + drift
+    }
+
+    /// Whether `deadline_ms` has passed at `now_ms` once the validation window is
+    /// applied. A deadline that passed on the raw clock but falls inside the window
+    /// is accepted, logged, and recorded as a [`SkewCorrection`].
+    pub fn deadline_passed(
+        &self,
+        decision: &str,
+        subject: &str,
+        deadline_ms: u128,
+        now_ms: u128,
+    ) -> bool {
+        if now_ms < deadline_ms {
+            return false;
+        }
+        let corrected_by_ms = now_ms - deadline_ms;
+        if corrected_by_ms >= self.validation_window_ms() {
+            return true;
+        }
+
+        println!(
+            "[TIME] Clock skew correction of {corrected_by_ms}ms applied to {decision} for {subject}"
+        );
+        let mut state = self.lock();
+        if state.corrections.len() == MAX_RECORDED_CORRECTIONS {
+            state.corrections.pop_front();
+        }
+        state.corrections.push_back(SkewCorrection {
+            decision: decision.to_string(),
+            subject: subject.to_string(),
+            deadline_ms,
+            observed_ms: now_ms,
+            corrected_by_ms,
+        });
+        false
+    }
+
+    /// Decisions accepted because of skew tolerance, oldest first.
+    pub fn recent_corrections(&self) -> Vec<SkewCorrection> {
+        self.lock().corrections.iter().cloned().collect()
+    }
+
+    /// Forget drift observations and recorded corrections.
+    pub fn reset(&self) {
+        let mut state = self.lock();
+        state.offsets.clear();
+        state.corrections.clear();
+    }
+}
+
+/// Access the global skew monitor, configured from [`SKEW_TOLERANCE_ENV`].
+pub fn skew_monitor() -> &'static ClockSkewMonitor {
+    static MONITOR: OnceLock<ClockSkewMonitor> = OnceLock::new();
+    MONITOR.get_or_init(|| {
+        let tolerance_ms = std::env::var(SKEW_TOLERANCE_ENV)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_SKEW_TOLERANCE_MS);
+        ClockSkewMonitor::new(tolerance_ms)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -23,4 +187,37 @@ mod tests {
         // Check that we get a reasonable timestamp (after 2020-01-01)
         assert!(timestamp > 1577836800000);
     }
+
+    #[test]
+    fn deadlines_tolerate_configured_skew_and_observed_drift() {
+        let monitor = ClockSkewMonitor::new(1_000);
+        assert!(!monitor.deadline_passed("token.validate", "alice", 10_000, 9_000));
+        assert!(monitor.recent_corrections().is_empty());
+
+        assert!(!monitor.deadline_passed("token.validate", "alice", 10_000, 10_500));
+        assert!(monitor.deadline_passed("token.validate", "alice", 10_000, 11_000));
+
+        // Peers run four to six seconds behind this host.
+        for peer_ms in [86_000, 85_000, 84_000] {
+            monitor.observe_peer_timestamp(peer_ms, 90_000);
+        }
+        assert_eq!(monitor.estimated_drift_ms(), -5_000);
+        assert_eq!(monitor.validation_window_ms(), 6_000);
+        assert!(!monitor.deadline_passed("approval.validate", "release::sign-off", 10_000, 15_000));
+
+        let far_ahead = u128::from(MAX_DRIFT_CORRECTION_MS) * 10;
+        for _ in 0..4 {
+            monitor.observe_peer_timestamp(far_ahead, 0);
+        }
+        assert_eq!(
+            monitor.validation_window_ms(),
+            1_000 + u128::from(MAX_DRIFT_CORRECTION_MS)
+        );
+
+        let corrections = monitor.recent_corrections();
+        assert_eq!(corrections.len(), 2);
+        assert_eq!(corrections[0].corrected_by_ms, 500);
+        assert_eq!(corrections[1].decision, "approval.validate");
+        assert_eq!(corrections[1].corrected_by_ms, 5_000);
+    }
 }
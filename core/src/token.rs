@@ -5,7 +5,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Mutex, OnceLock};
 
 use crate::config::manifest::TokenPolicyManifestEntry;
-use crate::time::current_timestamp_millis;
+use crate::time::{current_timestamp_millis, skew_monitor};
 use crate::utils::simple_hash;
 
 /// Internal counter used to produce deterministic token identifiers.
@@ -245,7 +245,7 @@ impl CapabilityTokenService {
         Ok(token)
     }
 
-    /// Validate a token against the provided scope.
+    /// Validate a token against the provided scope, tolerating clock skew on expiry.
     pub fn validate(&self, token: &str, scope: &str) -> Result<ScopeToken, TokenError> {
         let now = current_timestamp_millis();
         let store = self.store.lock().expect("token store mutex poisoned");
@@ -261,7 +261,13 @@ impl CapabilityTokenService {
         if store.revoked.contains(token) {
             return Err(TokenError::Revoked(token.to_string()));
         }
-        if issued.is_expired(now) {
+        let expired = skew_monitor().deadline_passed(
+            "token.validate",
+            &issued.issued_to,
+            issued.expires_at,
+            now,
+        );
+        if expired {
             return Err(TokenError::Expired(issued.expires_at));
         }
         if !issued.grants_scope(scope) {
@@ -1,7 +1,10 @@
 //! Utility functions shared across the NOA ARK OS core.
 
+use std::collections::{HashMap, VecDeque};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use thiserror::Error;
+
 /// Get current timestamp in milliseconds since UNIX epoch.
 pub fn current_timestamp_millis() -> u128 {
     SystemTime::now()
@@ -31,6 +34,73 @@ pub fn simple_hash(value: &str) -> String {
     format!("{:016x}", hash)
 }
 
+/// Why [`dependency_order`] could not order a graph.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DependencyError {
+    #[error("{node} depends on unknown {dependency}")]
+    Missing { node: String, dependency: String },
+    /// Nodes that could not be ordered: the members of a cycle and everything that
+    /// depends on one, in input order.
+    #[error("dependency cycle among {}", .0.join(", "))]
+    Cycle(Vec<String>),
+}
+
+/// Order `nodes`, given as `(name, dependencies)`, so every node comes after the
+/// nodes it depends on. Nodes that become ready together keep their input order, so
+/// the result is the same on every call.
+pub fn dependency_order<N, D>(nodes: &[(N, Vec<D>)]) -> Result<Vec<String>, DependencyError>
+where
+    N: AsRef<str>,
+    D: AsRef<str>,
+{
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(position, (name, _))| (name.as_ref(), position))
+        .collect();
+    let mut in_degree = vec![0usize; nodes.len()];
+    let mut dependents = vec![Vec::new(); nodes.len()];
+    for (position, (name, dependencies)) in nodes.iter().enumerate() {
+        for dependency in dependencies {
+            let Some(&parent) = index.get(dependency.as_ref()) else {
+                return Err(DependencyError::Missing {
+                    node: name.as_ref().to_string(),
+                    dependency: dependency.as_ref().to_string(),
+                });
+            };
+            in_degree[position] += 1;
+            dependents[parent].push(position);
+        }
+    }
+
+    let mut ready: VecDeque<usize> = (0..nodes.len())
+        .filter(|&position| in_degree[position] == 0)
+        .collect();
+    let mut ordered = Vec::with_capacity(nodes.len());
+    while let Some(position) = ready.pop_front() {
+        ordered.push(position);
+        for &child in &dependents[position] {
+            in_degree[child] -= 1;
+            if in_degree[child] == 0 {
+                ready.push_back(child);
+            }
+        }
+    }
+
+    if ordered.len() != nodes.len() {
+        return Err(DependencyError::Cycle(
+            (0..nodes.len())
+                .filter(|&position| in_degree[position] > 0)
+                .map(|position| nodes[position].0.as_ref().to_string())
+                .collect(),
+        ));
+    }
+    Ok(ordered
+        .into_iter()
+        .map(|position| nodes[position].0.as_ref().to_string())
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -49,6 +119,39 @@ mod tests {
         assert_ne!(hash1, hash2);
     }
 
+    #[test]
+    fn dependency_order_places_dependencies_first_and_reports_cycles() {
+        let graph = [
+            ("deploy", vec!["build", "test"]),
+            ("build", vec![]),
+            ("test", vec!["build"]),
+            ("lint", vec![]),
+        ];
+        assert_eq!(
+            dependency_order(&graph).unwrap(),
+            ["build", "lint", "test", "deploy"]
+        );
+
+        let cyclic = [
+            ("a", vec!["c"]),
+            ("b", vec![]),
+            ("c", vec!["a"]),
+            ("d", vec!["c"]),
+        ];
+        assert_eq!(
+            dependency_order(&cyclic),
+            Err(DependencyError::Cycle(vec![
+                "a".to_string(),
+                "c".to_string(),
+                "d".to_string()
+            ]))
+        );
+        assert!(matches!(
+            dependency_order(&[("a", vec!["missing"])]),
+            Err(DependencyError::Missing { .. })
+        ));
+    }
+
     #[test]
     fn test_timestamp_not_zero() {
         let ts = current_timestamp_millis();
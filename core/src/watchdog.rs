@@ -0,0 +1,125 @@
+//! Kernel watchdog
+//!
+//! [`KernelWatchdog`] subscribes to the kernel event bus and keeps track of the
+//! capabilities that are currently failing and of recent unhandled errors. The
+//! kernel installs one on the global bus (see [`crate::kernel::watchdog`]) and
+//! folds its report into agent health snapshots, so remediation directives
+//! account for failed capabilities instead of missing them.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::{KernelEvent, KernelEventKind, KernelEventSubscriber};
+
+/// Unhandled errors kept in a [`WatchdogReport`].
+pub const RECENT_ERROR_LIMIT: usize = 20;
+
+/// What the watchdog has observed so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchdogReport {
+    /// Failing capabilities and their latest error, until they initialize or are replaced.
+    pub failed_capabilities: BTreeMap<String, String>,
+    /// Latest unhandled errors, oldest first.
+    pub recent_errors: Vec<KernelEvent>,
+    pub unhandled_errors: u64,
+}
+
+impl WatchdogReport {
+    pub fn healthy(&self) -> bool {
+        self.failed_capabilities.is_empty() && self.recent_errors.is_empty()
+    }
+}
+
+#[derive(Default)]
+struct WatchdogState {
+    failed: BTreeMap<String, String>,
+    errors: VecDeque<KernelEvent>,
+    unhandled: u64,
+}
+
+/// Event bus subscriber tracking capability failures and unhandled errors.
+#[derive(Default)]
+pub struct KernelWatchdog {
+    state: Mutex<WatchdogState>,
+}
+
+impl KernelWatchdog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn report(&self) -> WatchdogReport {
+        let state = self.state.lock().expect("watchdog state poisoned");
+        WatchdogReport {
+            failed_capabilities: state.failed.clone(),
+            recent_errors: state.errors.iter().cloned().collect(),
+            unhandled_errors: state.unhandled,
+        }
+    }
+
+    /// Forget recorded unhandled errors, e.g. once an operator has reviewed them.
+    pub fn acknowledge_errors(&self) {
+        self.state
+            .lock()
+            .expect("watchdog state poisoned")
+            .errors
+            .clear();
+    }
+}
+
+impl KernelEventSubscriber for KernelWatchdog {
+    fn on_event(&self, event: &KernelEvent) {
+        let mut state = self.state.lock().expect("watchdog state poisoned");
+        match &event.kind {
+            KernelEventKind::CapabilityFailed { capability, error } => {
+                state.failed.insert(capability.clone(), error.clone());
+            }
+            KernelEventKind::CapabilityInitialized { capability, .. }
+            | KernelEventKind::CapabilityReplaced { capability } => {
+                state.failed.remove(capability);
+            }
+            KernelEventKind::UnhandledError { .. } => {
+                state.unhandled += 1;
+                if state.errors.len() == RECENT_ERROR_LIMIT {
+                    state.errors.pop_front();
+                }
+                state.errors.push_back(event.clone());
+            }
+            KernelEventKind::CapabilityRegistered { .. } => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::KernelEventBus;
+    use std::sync::Arc;
+
+    #[test]
+    fn failures_clear_once_the_capability_recovers() {
+        let bus = KernelEventBus::new();
+        let watchdog = Arc::new(KernelWatchdog::new());
+        bus.subscribe(watchdog.clone());
+
+        bus.publish(KernelEventKind::CapabilityFailed {
+            capability: "gpu".to_string(),
+            error: "driver missing".to_string(),
+        });
+        bus.report_error("kernel.shutdown", "hook timed out");
+        let report = watchdog.report();
+        assert!(!report.healthy());
+        assert_eq!(report.failed_capabilities["gpu"], "driver missing");
+        assert_eq!(report.unhandled_errors, 1);
+
+        bus.publish(KernelEventKind::CapabilityReplaced {
+            capability: "gpu".to_string(),
+        });
+        watchdog.acknowledge_errors();
+        let report = watchdog.report();
+        assert!(report.healthy());
+        assert_eq!(report.unhandled_errors, 1);
+    }
+}
@@ -0,0 +1,184 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::graph::{NodeKind, WorldGraph};
+use crate::hardware::HostClassification;
+
+/// Key under which capabilities travel in gateway payloads such as `connection_ack`.
+pub const HOST_CAPABILITIES_FIELD: &str = "hostCapabilities";
+
+/// Event type published whenever the host's capabilities change.
+pub const HOST_CAPABILITIES_EVENT: &str = "host_capabilities";
+
+/// World graph tag marking a dataset node as a model.
+pub const MODEL_TAG: &str = "model";
+
+/// World graph tag marking a model as serving speech recognition or synthesis.
+pub const VOICE_TAG: &str = "voice";
+
+/// Responsiveness clients can expect from the host.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyClass {
+    /// Suitable for continuous voice and spatial interaction.
+    Realtime,
+    #[default]
+    Interactive,
+    /// Requests should be queued; no conversational round trips.
+    Batch,
+}
+
+impl LatencyClass {
+    pub fn for_host(classification: &HostClassification) -> Self {
+        match classification {
+            HostClassification::Accelerated => LatencyClass::Realtime,
+            HostClassification::Standard => LatencyClass::Interactive,
+            HostClassification::Minimal => LatencyClass::Batch,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LatencyClass::Realtime => "realtime",
+            LatencyClass::Interactive => "interactive",
+            LatencyClass::Batch => "batch",
+        }
+    }
+}
+
+/// GPU the runtime plan schedules inference on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuCapability {
+    pub vendor: Option<String>,
+    pub memory_gb: Option<f64>,
+}
+
+/// A model declared in the world graph.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCapability {
+    pub id: String,
+    pub summary: String,
+    /// Whether the model's artifacts are present on the host.
+    pub loaded: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// What a host can do, as advertised to thin clients such as AR glasses and XR headsets.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HostCapabilities {
+    pub host: String,
+    pub classification: HostClassification,
+    pub latency: LatencyClass,
+    pub gpu: Option<GpuCapability>,
+    /// Execution backends selected by the runtime plan, e.g. `llama_cpp_gpu`.
+    #[serde(default)]
+    pub backends: Vec<String>,
+    #[serde(default)]
+    pub voice_models: Vec<ModelCapability>,
+    #[serde(default)]
+    pub models: Vec<ModelCapability>,
+    /// Workloads the host cannot run at its classification.
+    #[serde(default)]
+    pub degraded: Vec<String>,
+}
+
+impl HostCapabilities {
+    /// Models declared in `world`, split into voice and other models.
+    ///
+    /// A model is a dataset node tagged [`MODEL_TAG`]; it counts as loaded when its
+    /// path exists under `repo_root`.
+    pub fn models_from_world(
+        world: &WorldGraph,
+        repo_root: &Path,
+    ) -> (Vec<ModelCapability>, Vec<ModelCapability>) {
+        world
+            .nodes
+            .iter()
+            .filter(|node| {
+                node.kind == NodeKind::Dataset && node.tags.iter().any(|t| t == MODEL_TAG)
+            })
+            .map(|node| ModelCapability {
+                id: node.id.clone(),
+                summary: node.summary.clone(),
+                loaded: node.as_path(repo_root).exists(),
+                tags: node.tags.clone(),
+            })
+            .partition(|model| model.tags.iter().any(|t| t == VOICE_TAG))
+    }
+
+    pub fn gpu_available(&self) -> bool {
+        self.gpu.is_some()
+    }
+
+    /// Whether a voice model is loaded and ready to serve requests.
+    pub fn voice_ready(&self) -> bool {
+        self.voice_models.iter().any(|model| model.loaded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::graph::{Metadata, Node};
+    use tempfile::tempdir;
+
+    fn model(id: &str, path: &str, tags: &[&str]) -> Node {
+        Node {
+            id: id.to_string(),
+            kind: NodeKind::Dataset,
+            path: path.to_string(),
+            summary: format!("{id} weights"),
+            layer: Some("models".to_string()),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            owner: None,
+        }
+    }
+
+    #[test]
+    fn world_models_are_split_by_voice_tag_and_presence() {
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("whisper.bin"), b"").unwrap();
+        let world = WorldGraph {
+            version: "0.1.0".to_string(),
+            metadata: Metadata {
+                generated: "2026-01-01T00:00:00Z".to_string(),
+                description: "fixture".to_string(),
+                source: None,
+            },
+            nodes: vec![
+                model("whisper", "whisper.bin", &[MODEL_TAG, VOICE_TAG]),
+                model("piper", "piper.onnx", &[MODEL_TAG, VOICE_TAG]),
+                model("llama", "llama.gguf", &[MODEL_TAG]),
+                model("corpus", "corpus", &["training"]),
+            ],
+            edges: Vec::new(),
+        };
+
+        let (voice, other) = HostCapabilities::models_from_world(&world, root.path());
+        assert_eq!(
+            voice
+                .iter()
+                .map(|m| (m.id.as_str(), m.loaded))
+                .collect::<Vec<_>>(),
+            vec![("whisper", true), ("piper", false)]
+        );
+        assert_eq!(other.len(), 1);
+
+        let capabilities = HostCapabilities {
+            voice_models: voice,
+            models: other,
+            ..HostCapabilities::default()
+        };
+        assert!(capabilities.voice_ready());
+        assert!(!capabilities.gpu_available());
+        assert_eq!(
+            LatencyClass::for_host(&HostClassification::Minimal),
+            LatencyClass::Batch
+        );
+    }
+}
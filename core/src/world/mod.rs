@@ -1,5 +1,10 @@
 pub mod graph;
+pub mod host;
 pub mod reconciler;
 
 pub use graph::{Metadata as WorldMetadata, Node, NodeKind, WorldGraph, WorldGraphError};
+pub use host::{
+    GpuCapability, HostCapabilities, LatencyClass, ModelCapability, HOST_CAPABILITIES_EVENT,
+    HOST_CAPABILITIES_FIELD,
+};
 pub use reconciler::{Drift, DriftIssue, Reconciler, ReconciliationReport, RemediationStep};
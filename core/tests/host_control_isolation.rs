@@ -2,8 +2,14 @@ use std::sync::{Mutex, OnceLock};
 
 use noa_core::config::manifest::{
     KernelManifest, SCOPE_HOST_ENVIRONMENT_TAKEOVER, SCOPE_HOST_RESOURCE_ARBITRATE,
+    SCOPE_HOST_SERVICE_INSTALL, SCOPE_HOST_UPDATE,
+};
+use noa_core::host_control::{
+    self, DirectorySource, HostControlError, InstalledRelease, ReleaseBinary, ReleaseManifest,
+    ResourceArbitrationRequest, RuntimeGraph, RuntimeGraphService, SelfUpdater,
+    ServiceCommandRunner, ServiceInstallConfig, ServiceManagerKind, ServicePlan, UpdateOutcome,
+    RELEASE_MANIFEST_FILE, RELEASE_SIGNATURE_FILE,
 };
-use noa_core::host_control::{self, HostControlError, ResourceArbitrationRequest};
 use noa_core::token::{self, service as token_service, TokenIssuanceRequest};
 
 fn test_guard() -> &'static Mutex<()> {
@@ -79,3 +85,106 @@ fn arbitration_enforces_isolation() {
     assert!(granted.granted_cpu_share <= 0.75);
     assert!(granted.isolation_enforced);
 }
+
+#[test]
+fn service_install_requires_scope() {
+    struct NoopRunner;
+    impl ServiceCommandRunner for NoopRunner {
+        fn run(&self, _program: &str, _args: &[String]) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    let _guard = test_guard().lock().expect("test guard poisoned");
+    setup_services();
+    let units = tempfile::tempdir().expect("temp unit dir");
+    let graph = RuntimeGraph {
+        boot_order: vec!["kernel".to_string()],
+        services: vec![RuntimeGraphService {
+            id: "kernel".to_string(),
+            requires: vec![],
+            optional: vec![],
+            supported_classes: vec![],
+        }],
+    };
+    let plan = ServicePlan::from_graph(
+        &graph,
+        ServiceManagerKind::Systemd,
+        ServiceInstallConfig::new("/opt/noa/bin/noa_kernel").with_unit_dir(units.path()),
+    )
+    .expect("plan builds");
+
+    let operator = token_service()
+        .issue_token(TokenIssuanceRequest::new(
+            "operator",
+            [SCOPE_HOST_ENVIRONMENT_TAKEOVER],
+        ))
+        .expect("token issuance succeeds");
+    let denied = host_control::service().install_services(&operator.token, &plan, &NoopRunner);
+    assert!(matches!(denied, Err(HostControlError::Token(_))));
+    assert!(!units.path().join("noa-kernel.service").exists());
+
+    let installer = token_service()
+        .issue_token(TokenIssuanceRequest::new(
+            "installer",
+            [SCOPE_HOST_SERVICE_INSTALL],
+        ))
+        .expect("token issuance succeeds");
+    host_control::service()
+        .install_services(&installer.token, &plan, &NoopRunner)
+        .expect("installer may install services");
+    assert!(units.path().join("noa-kernel.service").exists());
+    host_control::service()
+        .uninstall_services(&installer.token, &plan, &NoopRunner)
+        .expect("installer may remove services");
+    assert!(!units.path().join("noa-kernel.service").exists());
+}
+
+#[test]
+fn self_update_requires_scope() {
+    let _guard = test_guard().lock().expect("test guard poisoned");
+    setup_services();
+    let release = tempfile::tempdir().expect("temp release dir");
+    let host = tempfile::tempdir().expect("temp host dir");
+    std::fs::write(release.path().join("noa_kernel"), b"kernel").expect("binary written");
+    let manifest = ReleaseManifest {
+        version: "1.0.0".to_string(),
+        commit_sha: "abc123".to_string(),
+        pipeline_id: Some("pipeline-1".to_string()),
+        rollout_percent: 100,
+        binaries: vec![ReleaseBinary {
+            name: "noa_kernel".to_string(),
+            path: "noa_kernel".to_string(),
+            // sha256("kernel")
+            sha256: "6923dd1bc0460082c5d55a831908c24a282860b7f1cd6c2b79cf1bc8857c639c".to_string(),
+        }],
+    };
+    let (bytes, signature) = manifest.sign().expect("manifest signs");
+    std::fs::write(release.path().join(RELEASE_MANIFEST_FILE), bytes).expect("manifest written");
+    std::fs::write(
+        release.path().join(RELEASE_SIGNATURE_FILE),
+        serde_json::to_vec(&signature).expect("signature serialises"),
+    )
+    .expect("signature written");
+    let source = DirectorySource::new(release.path());
+    let updater = SelfUpdater::new(host.path());
+    let healthy = |_: &InstalledRelease| Ok(());
+
+    let installer = token_service()
+        .issue_token(TokenIssuanceRequest::new(
+            "installer",
+            [SCOPE_HOST_SERVICE_INSTALL],
+        ))
+        .expect("token issuance succeeds");
+    let denied =
+        host_control::service().apply_update(&installer.token, &updater, &source, &healthy);
+    assert!(matches!(denied, Err(HostControlError::Token(_))));
+    assert!(updater.active().expect("slots readable").is_none());
+
+    let updater_token = token_service()
+        .issue_token(TokenIssuanceRequest::new("updater", [SCOPE_HOST_UPDATE]))
+        .expect("token issuance succeeds");
+    let outcome =
+        host_control::service().apply_update(&updater_token.token, &updater, &source, &healthy);
+    assert!(matches!(outcome, Ok(UpdateOutcome::Applied { .. })));
+}
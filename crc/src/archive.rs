@@ -8,7 +8,9 @@ use tokio::fs;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tracing::{error, info, instrument, warn};
 
-use crate::{ArchiveIndex, ArchiveInfo, FileEntry, Result, SourceType};
+use crate::chunks::{ChunkStore, ChunkedFile, DedupStats, CHUNKS_DIR};
+use crate::vendor::MirrorState;
+use crate::{ArchiveIndex, ArchiveInfo, Error, FileEntry, Result, SourceType};
 
 /// Archive configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,18 +55,31 @@ pub struct ArchiveManager {
     archive_path: PathBuf,
     config: ArchiveConfig,
     archives: HashMap<String, ArchiveInfo>,
+    chunks: ChunkStore,
 }
 
 impl ArchiveManager {
     /// Create new archive manager
     pub fn new(archive_path: PathBuf, config: ArchiveConfig) -> Self {
+        let chunks = ChunkStore::new(archive_path.join(CHUNKS_DIR));
         Self {
             archive_path,
             config,
             archives: HashMap::new(),
+            chunks,
         }
     }
 
+    /// Use a custom chunk store, e.g. one shared between archive roots.
+    pub fn with_chunk_store(mut self, chunks: ChunkStore) -> Self {
+        self.chunks = chunks;
+        self
+    }
+
+    pub fn chunk_store(&self) -> &ChunkStore {
+        &self.chunks
+    }
+
     /// Archive a processed drop
     #[instrument(skip(self))]
     pub async fn archive_drop(
@@ -126,6 +141,16 @@ impl ArchiveManager {
         let hash = self.calculate_hash(&archive_file_path).await?;
         info!("  Hash: {}", hash);
 
+        // Chunk files into the shared store
+        let (chunked_files, dedup) = self.chunk_drop(source_path).await?;
+        info!(
+            "  Chunked {} files: {} chunks ({} new), dedup ratio {:.2}",
+            chunked_files.len(),
+            dedup.total_chunks,
+            dedup.new_chunks,
+            dedup.ratio()
+        );
+
         // Create archive info
         let archive_info = ArchiveInfo {
             hash: hash.clone(),
@@ -133,6 +158,8 @@ impl ArchiveManager {
             created: timestamp,
             size: archive_size,
             index,
+            chunked_files,
+            dedup,
         };
 
         // Store archive info
@@ -147,6 +174,82 @@ impl ArchiveManager {
         Ok(archive_info)
     }
 
+    /// Rebuild a single file of an archived drop from the chunk store.
+    pub async fn reconstruct_file(&self, drop_id: &str, path: &str) -> Result<Vec<u8>> {
+        let info = self.archive_info(drop_id).await?;
+        let file = info
+            .chunked_files
+            .iter()
+            .find(|file| file.path == path)
+            .ok_or_else(|| Error::FileNotFound(format!("{drop_id}:{path}")))?
+            .clone();
+        let chunks = self.chunks.clone();
+        tokio::task::spawn_blocking(move || chunks.reconstruct(&file))
+            .await
+            .map_err(|err| Error::SystemError(err.to_string()))?
+    }
+
+    /// Rebuild every file of an archived drop under `destination`.
+    /// Returns the number of files written.
+    #[instrument(skip(self))]
+    pub async fn restore_drop(&self, drop_id: &str, destination: &Path) -> Result<usize> {
+        let info = self.archive_info(drop_id).await?;
+        for file in &info.chunked_files {
+            let relative = Path::new(&file.path);
+            if relative.is_absolute()
+                || relative
+                    .components()
+                    .any(|part| matches!(part, std::path::Component::ParentDir))
+            {
+                return Err(Error::ArchiveError(format!(
+                    "refusing to restore {} outside the destination",
+                    file.path
+                )));
+            }
+            let target = destination.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            let data = self.reconstruct_file(drop_id, &file.path).await?;
+            fs::write(&target, data).await?;
+        }
+        info!(
+            "✓ Restored {} files of {} to {}",
+            info.chunked_files.len(),
+            drop_id,
+            destination.display()
+        );
+        Ok(info.chunked_files.len())
+    }
+
+    /// Archive info for a drop, falling back to its saved metadata.
+    pub async fn archive_info(&self, drop_id: &str) -> Result<ArchiveInfo> {
+        if let Some(info) = self.archives.get(drop_id) {
+            return Ok(info.clone());
+        }
+        let metadata_path = self.archive_path.join(format!("{}.metadata.json", drop_id));
+        if !metadata_path.exists() {
+            return Err(Error::DropNotFound(drop_id.to_string()));
+        }
+        let json = fs::read_to_string(metadata_path).await?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Record the vendored mirror a drop was built against in its archive index,
+    /// replacing the index's dependency list with the mirrored dependencies.
+    pub async fn record_mirror_state(
+        &mut self,
+        drop_id: &str,
+        mirror: MirrorState,
+    ) -> Result<ArchiveInfo> {
+        let mut info = self.archive_info(drop_id).await?;
+        info.index.dependencies = mirror.dependencies();
+        info.index.mirror = Some(mirror);
+        self.save_archive_metadata(drop_id, &info).await?;
+        self.archives.insert(drop_id.to_string(), info.clone());
+        Ok(info)
+    }
+
     /// Clean up source after successful archiving
     #[instrument(skip(self))]
     pub async fn cleanup_source(&self, source_path: &Path) -> Result<()> {
@@ -259,8 +362,13 @@ impl ArchiveManager {
             total_size_bytes: 0,
             archives_by_type: HashMap::new(),
             oldest_archive_days: 0,
+            dedup: DedupStats::default(),
         };
 
+        for info in self.archives.values() {
+            stats.dedup.merge(&info.dedup);
+        }
+
         // Scan archive directories
         for source_type in [
             SourceType::StaleCodebase,
@@ -319,9 +427,41 @@ impl ArchiveManager {
             files,
             symbols: Vec::new(),
             dependencies: Vec::new(),
+            mirror: None,
         })
     }
 
+    async fn chunk_drop(&self, source_path: &Path) -> Result<(Vec<ChunkedFile>, DedupStats)> {
+        let chunks = self.chunks.clone();
+        let source_path = source_path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let mut files = Vec::new();
+            let mut dedup = DedupStats::default();
+            if !source_path.is_dir() {
+                return Ok((files, dedup));
+            }
+            for entry in walkdir::WalkDir::new(&source_path).sort_by_file_name() {
+                let entry = entry.map_err(|err| Error::ArchiveError(err.to_string()))?;
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let relative = entry
+                    .path()
+                    .strip_prefix(&source_path)
+                    .unwrap_or(entry.path())
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                let data = std::fs::read(entry.path())?;
+                let (file, stats) = chunks.put(&relative, &data)?;
+                dedup.merge(&stats);
+                files.push(file);
+            }
+            Ok((files, dedup))
+        })
+        .await
+        .map_err(|err| Error::SystemError(err.to_string()))?
+    }
+
     async fn compress_drop(&self, source_path: &Path, archive_path: &Path) -> Result<u64> {
         info!(
             "  Compressing {} -> {} with {:?} (level {})",
@@ -388,6 +528,9 @@ pub struct ArchiveStatistics {
     pub total_size_bytes: u64,
     pub archives_by_type: HashMap<SourceType, (usize, u64)>, // (count, total_size)
     pub oldest_archive_days: u64,
+    /// Chunk dedup totals across archives created by this manager.
+    #[serde(default)]
+    pub dedup: DedupStats,
 }
 
 #[cfg(test)]
@@ -402,6 +545,43 @@ mod tests {
         assert_eq!(config.auto_cleanup, true);
     }
 
+    #[tokio::test]
+    async fn test_archives_share_chunks_and_restore() {
+        let root = tempfile::tempdir().unwrap();
+        let mut manager =
+            ArchiveManager::new(root.path().join("archive"), ArchiveConfig::default());
+        let vendored: Vec<u8> = (0..200_000u32).map(|i| (i * 31 % 251) as u8).collect();
+
+        for (drop_id, own) in [("first", "fn a() {}"), ("second", "fn b() {}")] {
+            let source = root.path().join(drop_id);
+            std::fs::create_dir_all(source.join("vendor")).unwrap();
+            std::fs::write(source.join("vendor/dep.rs"), &vendored).unwrap();
+            std::fs::write(source.join("main.rs"), own).unwrap();
+            manager
+                .archive_drop(drop_id, &source, SourceType::ExternalRepo)
+                .await
+                .unwrap();
+        }
+
+        let first = manager.archive_info("first").await.unwrap();
+        let second = manager.archive_info("second").await.unwrap();
+        assert_eq!(first.chunked_files.len(), 2);
+        assert!(second.dedup.new_chunks < second.dedup.total_chunks);
+        assert!(second.dedup.ratio() > first.dedup.ratio());
+        assert!(root.path().join("archive/chunks").is_dir());
+
+        let restored = root.path().join("restored");
+        assert_eq!(manager.restore_drop("second", &restored).await.unwrap(), 2);
+        assert_eq!(
+            std::fs::read(restored.join("vendor/dep.rs")).unwrap(),
+            vendored
+        );
+        assert_eq!(
+            manager.reconstruct_file("second", "main.rs").await.unwrap(),
+            b"fn b() {}"
+        );
+    }
+
     #[test]
     fn test_source_type_short() {
         assert_eq!(source_type_short(&SourceType::StaleCodebase), "stale");
@@ -0,0 +1,242 @@
+// CRC Chunk Store - content-defined chunking and dedup for archives
+// Splits archived files with FastCDC and stores each chunk once, keyed by blake3 hash,
+// so vendored dependencies repeated across drops only cost their first copy.
+
+use fastcdc::v2020::FastCDC;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+use crate::{Error, Result};
+
+/// Directory under the archive root holding chunk objects.
+pub const CHUNKS_DIR: &str = "chunks";
+
+/// FastCDC chunk size bounds, in bytes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChunkingConfig {
+    pub min_size: u32,
+    pub avg_size: u32,
+    pub max_size: u32,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            min_size: 16 * 1024,
+            avg_size: 64 * 1024,
+            max_size: 256 * 1024,
+        }
+    }
+}
+
+/// One chunk of a file, in file order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChunkRef {
+    pub hash: String,
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// Chunk list needed to rebuild one archived file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChunkedFile {
+    pub path: String,
+    pub size: u64,
+    /// blake3 of the whole file, checked on reconstruction.
+    pub hash: String,
+    pub chunks: Vec<ChunkRef>,
+}
+
+/// Deduplication totals for an archive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DedupStats {
+    /// Bytes across all chunked files.
+    pub logical_bytes: u64,
+    /// Bytes of chunks this archive added to the store.
+    pub stored_bytes: u64,
+    pub total_chunks: usize,
+    /// Chunks that were not already in the store.
+    pub new_chunks: usize,
+}
+
+impl DedupStats {
+    /// Logical bytes per stored byte; 1.0 means nothing was deduplicated.
+    pub fn ratio(&self) -> f64 {
+        if self.stored_bytes == 0 {
+            if self.logical_bytes == 0 {
+                1.0
+            } else {
+                f64::INFINITY
+            }
+        } else {
+            self.logical_bytes as f64 / self.stored_bytes as f64
+        }
+    }
+
+    pub fn merge(&mut self, other: &DedupStats) {
+        self.logical_bytes += other.logical_bytes;
+        self.stored_bytes += other.stored_bytes;
+        self.total_chunks += other.total_chunks;
+        self.new_chunks += other.new_chunks;
+    }
+}
+
+/// Chunk objects stored as `<root>/<first two hex chars>/<blake3 hash>`.
+#[derive(Debug, Clone)]
+pub struct ChunkStore {
+    root: PathBuf,
+    config: ChunkingConfig,
+}
+
+impl ChunkStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            config: ChunkingConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: ChunkingConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn contains(&self, hash: &str) -> bool {
+        self.path_for(hash).exists()
+    }
+
+    /// Chunk `data` and store any chunks not already present.
+    pub fn put(&self, path: &str, data: &[u8]) -> Result<(ChunkedFile, DedupStats)> {
+        let mut stats = DedupStats {
+            logical_bytes: data.len() as u64,
+            ..DedupStats::default()
+        };
+        let mut chunks = Vec::new();
+        let chunker = FastCDC::new(
+            data,
+            self.config.min_size,
+            self.config.avg_size,
+            self.config.max_size,
+        );
+        for chunk in chunker {
+            let bytes = &data[chunk.offset..chunk.offset + chunk.length];
+            let hash = blake3::hash(bytes).to_hex().to_string();
+            if self.write_chunk(&hash, bytes)? {
+                stats.new_chunks += 1;
+                stats.stored_bytes += bytes.len() as u64;
+            }
+            stats.total_chunks += 1;
+            chunks.push(ChunkRef {
+                hash,
+                offset: chunk.offset as u64,
+                length: chunk.length as u64,
+            });
+        }
+
+        let file = ChunkedFile {
+            path: path.to_string(),
+            size: data.len() as u64,
+            hash: blake3::hash(data).to_hex().to_string(),
+            chunks,
+        };
+        Ok((file, stats))
+    }
+
+    /// Read one chunk, verifying its hash.
+    pub fn get(&self, hash: &str) -> Result<Vec<u8>> {
+        let bytes = fs::read(self.path_for(hash))
+            .map_err(|err| Error::ArchiveError(format!("missing chunk {hash}: {err}")))?;
+        if blake3::hash(&bytes).to_hex().as_str() != hash {
+            return Err(Error::ArchiveError(format!("chunk {hash} is corrupt")));
+        }
+        Ok(bytes)
+    }
+
+    /// Rebuild a file from its chunks.
+    pub fn reconstruct(&self, file: &ChunkedFile) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(file.size as usize);
+        for chunk in &file.chunks {
+            data.extend_from_slice(&self.get(&chunk.hash)?);
+        }
+        if data.len() as u64 != file.size || blake3::hash(&data).to_hex().as_str() != file.hash {
+            return Err(Error::ArchiveError(format!(
+                "reconstructed {} does not match its recorded hash",
+                file.path
+            )));
+        }
+        Ok(data)
+    }
+
+    /// Returns true when the chunk was newly written.
+    fn write_chunk(&self, hash: &str, bytes: &[u8]) -> Result<bool> {
+        let path = self.path_for(hash);
+        if path.exists() {
+            return Ok(false);
+        }
+        let bucket = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(bucket)?;
+        let mut tmp = NamedTempFile::new_in(bucket)?;
+        tmp.write_all(bytes)?;
+        tmp.flush()?;
+        tmp.persist(&path).map_err(|err| Error::Io(err.error))?;
+        Ok(true)
+    }
+
+    fn path_for(&self, hash: &str) -> PathBuf {
+        let bucket = hash.get(..2).unwrap_or("00");
+        self.root.join(bucket).join(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vendored(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn repeated_content_is_stored_once_and_reconstructs() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ChunkStore::new(dir.path()).with_config(ChunkingConfig {
+            min_size: 256,
+            avg_size: 1024,
+            max_size: 4096,
+        });
+        let shared = vendored(7, 64 * 1024);
+
+        let (first, first_stats) = store.put("a/vendor.rs", &shared).unwrap();
+        assert!(first_stats.total_chunks > 1);
+        assert_eq!(first_stats.new_chunks, first_stats.total_chunks);
+        assert!((first_stats.ratio() - 1.0).abs() < f64::EPSILON);
+
+        let mut edited = vendored(9, 512);
+        edited.extend_from_slice(&shared);
+        let (second, second_stats) = store.put("b/vendor.rs", &edited).unwrap();
+        assert!(second_stats.new_chunks < second_stats.total_chunks);
+        assert!(second_stats.ratio() > 4.0);
+
+        assert_eq!(store.reconstruct(&first).unwrap(), shared);
+        assert_eq!(store.reconstruct(&second).unwrap(), edited);
+
+        let victim = &second.chunks[0].hash;
+        fs::write(store.path_for(victim), b"tampered").unwrap();
+        assert!(store.reconstruct(&second).is_err());
+    }
+}
@@ -31,6 +31,12 @@ pub enum Error {
     #[error("Archive error: {0}")]
     ArchiveError(String),
 
+    #[error("Drop quarantined pending security review: {0}")]
+    Quarantined(String),
+
+    #[error("Quarantine error: {0}")]
+    QuarantineError(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -39,6 +45,9 @@ pub enum Error {
 
     #[error("YAML error: {0}")]
     Yaml(#[from] serde_yaml::Error),
+
+    #[error("Symbol graph error: {0}")]
+    SymbolGraph(#[from] noa_symbol_graph::GraphError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -12,6 +12,7 @@
 pub mod archive;
 pub mod build;
 pub mod cas;
+pub mod chunks;
 pub mod commands;
 pub mod digestors;
 pub mod engine;
@@ -22,16 +23,27 @@ pub mod ir;
 pub mod orchestrator;
 pub mod parallel;
 pub mod processor;
+pub mod provenance;
+pub mod quarantine;
+pub mod readapt;
 pub mod telemetry;
 pub mod transform;
 pub mod types;
+pub mod vendor;
 pub mod watcher;
 
 // Re-export common types
 pub use build::{BuildArtifact, BuildManifest, TargetProfile};
+pub use chunks::{ChunkStore, ChunkedFile, DedupStats};
 pub use error::{Error, Result};
+pub use noa_symbol_graph::DuplicateFinding;
+pub use provenance::{DropProvenance, ProvenanceVerification};
+pub use quarantine::{QuarantineGate, QuarantineRecord, QuarantineStatus};
+pub use readapt::{AdaptationDecision, ReadaptationReport};
 pub use types::*;
+pub use vendor::{MirrorState, VendorReport, Vendorer};
 
+use noa_workflow::AgentApproval;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::collections::HashMap;
@@ -76,6 +88,8 @@ impl SandboxModel {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum CRCState {
     Incoming,
+    /// Flagged by screening and held until a security agent approves it.
+    Quarantined,
     Queued,
     Analyzing,
     Adapting,
@@ -128,6 +142,9 @@ pub struct CodeDrop {
     pub analysis: Option<AnalysisResult>,
     pub adaptation: Option<AdaptationResult>,
     pub original_artifact: Option<OriginalArtifact>,
+    /// Per-file adaptation decisions, replayed by `CRCSystem::readapt`.
+    #[serde(default)]
+    pub decisions: Vec<AdaptationDecision>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -138,6 +155,9 @@ pub struct DropManifest {
     pub timestamp: u64,
     pub priority: Priority,
     pub metadata: HashMap<String, String>,
+    /// Structured upstream origin; `source` stays as the free-form label.
+    #[serde(default)]
+    pub provenance: Option<DropProvenance>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -157,6 +177,9 @@ pub struct AnalysisResult {
     pub patterns_found: Vec<String>,
     pub issues: Vec<String>,
     pub ai_confidence: f32,
+    /// Drop functions that closely match existing workspace code.
+    #[serde(default)]
+    pub duplicates: Vec<DuplicateFinding>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -185,6 +208,11 @@ pub struct ArchiveInfo {
     pub created: u64,
     pub size: u64,
     pub index: ArchiveIndex,
+    /// Chunk lists for rebuilding each file from the shared chunk store.
+    #[serde(default)]
+    pub chunked_files: Vec<ChunkedFile>,
+    #[serde(default)]
+    pub dedup: DedupStats,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -192,6 +220,9 @@ pub struct ArchiveIndex {
     pub files: Vec<FileEntry>,
     pub symbols: Vec<SymbolEntry>,
     pub dependencies: Vec<Dependency>,
+    /// Vendored dependency mirror the drop was built against, for offline rebuilds.
+    #[serde(default)]
+    pub mirror: Option<MirrorState>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -222,6 +253,7 @@ pub struct CRCSystem {
     archives: Arc<Mutex<HashMap<String, ArchiveInfo>>>,
     sandboxes: Arc<Mutex<HashMap<SandboxModel, SandboxState>>>,
     config: Arc<Mutex<CRCConfig>>,
+    quarantine: Arc<QuarantineGate>,
 }
 
 impl Clone for CRCSystem {
@@ -231,6 +263,7 @@ impl Clone for CRCSystem {
             archives: Arc::clone(&self.archives),
             sandboxes: Arc::clone(&self.sandboxes),
             config: Arc::clone(&self.config),
+            quarantine: Arc::clone(&self.quarantine),
         }
     }
 }
@@ -302,9 +335,20 @@ impl CRCSystem {
             archives: Arc::new(Mutex::new(HashMap::new())),
             sandboxes: Arc::new(Mutex::new(sandboxes)),
             config: Arc::new(Mutex::new(config)),
+            quarantine: Arc::new(QuarantineGate::default()),
         }
     }
 
+    /// Use a custom quarantine gate for screening incoming drops
+    pub fn with_quarantine(mut self, quarantine: QuarantineGate) -> Self {
+        self.quarantine = Arc::new(quarantine);
+        self
+    }
+
+    pub fn quarantine(&self) -> Arc<QuarantineGate> {
+        Arc::clone(&self.quarantine)
+    }
+
     /// Create test instance
     #[cfg(test)]
     pub fn new_test() -> Self {
@@ -346,6 +390,7 @@ impl CRCSystem {
             analysis: None,
             adaptation: None,
             original_artifact,
+            decisions: Vec::new(),
         };
 
         let mut drops = self.drops.lock().unwrap();
@@ -373,6 +418,21 @@ impl CRCSystem {
             Some(json!({ "drop_id": drop_id })),
         );
 
+        // New drops are screened before anything else looks at them
+        let state = self
+            .get_drop(drop_id)
+            .map(|drop| drop.state)
+            .ok_or_else(|| format!("Drop not found: {}", drop_id))?;
+        if state == CRCState::Incoming {
+            self.screen_drop(drop_id)?;
+        }
+        if self.get_drop(drop_id).map(|drop| drop.state) == Some(CRCState::Quarantined) {
+            return Err(format!(
+                "Drop {} is quarantined pending security review",
+                drop_id
+            ));
+        }
+
         // Update state
         self.update_state(drop_id, CRCState::Analyzing)?;
 
@@ -385,6 +445,7 @@ impl CRCSystem {
             patterns_found: vec![],
             issues: vec![],
             ai_confidence: 0.90,
+            duplicates: vec![],
         };
 
         // Store analysis
@@ -397,6 +458,291 @@ impl CRCSystem {
         Ok(analysis)
     }
 
+    /// Record an adaptation decision against the current content of `path`
+    pub fn record_decision(
+        &self,
+        drop_id: &str,
+        path: &str,
+        action: &str,
+    ) -> std::result::Result<AdaptationDecision, String> {
+        let mut drops = self.drops.lock().unwrap();
+        let drop = drops
+            .get_mut(drop_id)
+            .ok_or_else(|| format!("Drop not found: {}", drop_id))?;
+        let contents = std::fs::read(drop.source_path.join(path))
+            .map_err(|err| format!("Cannot read {} in drop {}: {}", path, drop_id, err))?;
+        let decision = AdaptationDecision {
+            path: path.to_string(),
+            action: action.to_string(),
+            content_hash: blake3::hash(&contents).to_hex().to_string(),
+        };
+        drop.decisions.retain(|existing| existing.path != path);
+        drop.decisions.push(decision.clone());
+        Ok(decision)
+    }
+
+    /// Re-adapt a drop against a new upstream version. Only added and modified paths
+    /// are re-analyzed; decisions on unchanged files are replayed and the rest are
+    /// reported as needing review or retired.
+    pub async fn readapt(
+        &self,
+        drop_id: &str,
+        new_source: &std::path::Path,
+    ) -> std::result::Result<ReadaptationReport, String> {
+        use crate::readapt::{delta_adaptation, stage_paths, DecisionReplay, TreeDiff};
+
+        let drop = self
+            .get_drop(drop_id)
+            .ok_or_else(|| format!("Drop not found: {}", drop_id))?;
+        let previous_files = match drop.manifest.provenance.as_ref() {
+            Some(provenance) if !provenance.files.is_empty() => provenance.files.clone(),
+            _ => provenance::file_hashes(&drop.source_path).map_err(|err| {
+                format!("Drop {} has no recorded tree to diff against: {}", drop_id, err)
+            })?,
+        };
+
+        let mut new_provenance =
+            DropProvenance::capture(new_source).map_err(|err| err.to_string())?;
+        if let Some(previous) = drop.manifest.provenance.as_ref() {
+            if new_provenance.upstream_url.is_none() {
+                new_provenance.upstream_url = previous.upstream_url.clone();
+            }
+        }
+        let diff = TreeDiff::between(&previous_files, &new_provenance.files);
+        crate::telemetry::info(
+            "crc.system",
+            "readapt_drop",
+            "Re-adapting drop against new upstream",
+            "started",
+            None,
+            Some(json!({
+                "drop_id": drop_id,
+                "added": diff.added.len(),
+                "modified": diff.modified.len(),
+                "removed": diff.removed.len(),
+            })),
+        );
+
+        let staging = tempfile::tempdir().map_err(|err| err.to_string())?;
+        stage_paths(new_source, diff.changed(), staging.path()).map_err(|err| err.to_string())?;
+        let temp_path = self.config.lock().unwrap().temp_path.clone();
+        let analysis = processor::DropProcessor::new(temp_path)
+            .analyze(staging.path(), &drop.source_type)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let decisions = DecisionReplay::sort(&drop.decisions, &new_provenance.files);
+        let adaptation = delta_adaptation(
+            &diff,
+            &analysis,
+            &decisions,
+            Some(new_provenance.summary()),
+        );
+        let report = ReadaptationReport {
+            drop_id: drop_id.to_string(),
+            previous_checksum: provenance::checksum_of(&previous_files),
+            new_checksum: new_provenance.tree_checksum.clone(),
+            diff,
+            analysis: analysis.clone(),
+            decisions: decisions.clone(),
+            adaptation: adaptation.clone(),
+        };
+
+        let mut drops = self.drops.lock().unwrap();
+        let drop = drops
+            .get_mut(drop_id)
+            .ok_or_else(|| format!("Drop not found: {}", drop_id))?;
+        drop.source_path = new_source.to_path_buf();
+        drop.manifest.provenance = Some(new_provenance);
+        drop.analysis = Some(analysis);
+        drop.adaptation = Some(adaptation);
+        drop.decisions = decisions.replayed;
+        drop.state = CRCState::Validating;
+        Ok(report)
+    }
+
+    /// Verify a re-fetch of a drop's upstream against its recorded provenance.
+    /// The verification is appended to the drop manifest either way.
+    pub fn verify_provenance(
+        &self,
+        drop_id: &str,
+        refetched: &std::path::Path,
+    ) -> std::result::Result<ProvenanceVerification, String> {
+        let mut drops = self.drops.lock().unwrap();
+        let drop = drops
+            .get_mut(drop_id)
+            .ok_or_else(|| format!("Drop not found: {}", drop_id))?;
+        let provenance = drop
+            .manifest
+            .provenance
+            .as_mut()
+            .ok_or_else(|| format!("Drop {} has no recorded provenance", drop_id))?;
+        let verification = provenance.verify(refetched).map_err(|err| err.to_string())?;
+
+        let details = json!({
+            "drop_id": drop_id,
+            "expected": provenance.tree_checksum,
+            "actual": verification.tree_checksum,
+        });
+        if verification.matches {
+            crate::telemetry::info(
+                "crc.system",
+                "provenance_verified",
+                "Re-fetched upstream matches recorded provenance",
+                "verified",
+                None,
+                Some(details),
+            );
+        } else {
+            crate::telemetry::warn(
+                "crc.system",
+                "provenance_mismatch",
+                "Re-fetched upstream differs from recorded provenance",
+                "mismatch",
+                None,
+                Some(details),
+            );
+        }
+        Ok(verification)
+    }
+
+    /// Vendor a drop's dependencies into `vendorer`'s mirror for offline builds and
+    /// rewrite its manifests to use it. The mirror state is recorded on the drop's
+    /// analysis and, once archived, in its archive index.
+    pub fn vendor_drop(
+        &self,
+        drop_id: &str,
+        vendorer: &Vendorer,
+    ) -> std::result::Result<VendorReport, String> {
+        let drop = self
+            .get_drop(drop_id)
+            .ok_or_else(|| format!("Drop not found: {}", drop_id))?;
+        let report = vendorer
+            .vendor(&drop.source_path)
+            .map_err(|err| err.to_string())?;
+
+        if let Some(analysis) = self
+            .drops
+            .lock()
+            .unwrap()
+            .get_mut(drop_id)
+            .and_then(|drop| drop.analysis.as_mut())
+        {
+            analysis.dependencies = report.mirror.dependencies();
+        }
+        if let Some(info) = self.archives.lock().unwrap().get_mut(drop_id) {
+            info.index.dependencies = report.mirror.dependencies();
+            info.index.mirror = Some(report.mirror.clone());
+        }
+
+        let details = json!({
+            "drop_id": drop_id,
+            "mirror": report.mirror.root,
+            "checksum": report.mirror.checksum,
+            "vendored": report.mirror.entries.len(),
+            "fetched": report.fetched,
+            "missing": report
+                .missing
+                .iter()
+                .map(|(dependency, _)| &dependency.name)
+                .collect::<Vec<_>>(),
+        });
+        if report.missing.is_empty() {
+            crate::telemetry::info(
+                "crc.system",
+                "drop_vendored",
+                "Drop dependencies vendored into the local mirror",
+                "success",
+                None,
+                Some(details),
+            );
+        } else {
+            crate::telemetry::warn(
+                "crc.system",
+                "drop_vendored",
+                "Some drop dependencies could not be vendored",
+                "partial",
+                None,
+                Some(details),
+            );
+        }
+        Ok(report)
+    }
+
+    /// Run quarantine screening for a drop. Flagged drops move to
+    /// `CRCState::Quarantined`; clean ones are queued for analysis.
+    pub fn screen_drop(&self, drop_id: &str) -> std::result::Result<QuarantineRecord, String> {
+        let drop = self
+            .get_drop(drop_id)
+            .ok_or_else(|| format!("Drop not found: {}", drop_id))?;
+        let record = self
+            .quarantine
+            .screen(drop_id, &drop.source_path)
+            .map_err(|err| err.to_string())?;
+
+        if record.status.may_proceed() {
+            self.update_state(drop_id, CRCState::Queued)?;
+        } else {
+            self.set_location(drop_id, CRCState::Quarantined, record.current_path())?;
+            crate::telemetry::warn(
+                "crc.system",
+                "drop_quarantined",
+                "Drop held in quarantine pending security review",
+                "held",
+                None,
+                Some(json!({
+                    "drop_id": drop_id,
+                    "findings": record.findings().count(),
+                })),
+            );
+        }
+        Ok(record)
+    }
+
+    /// Release a quarantined drop with a security agent's approval
+    pub fn release_drop(
+        &self,
+        drop_id: &str,
+        approval: AgentApproval,
+    ) -> std::result::Result<QuarantineRecord, String> {
+        let record = self
+            .quarantine
+            .release(drop_id, approval)
+            .map_err(|err| err.to_string())?;
+        self.set_location(drop_id, CRCState::Queued, record.current_path())?;
+        Ok(record)
+    }
+
+    /// Reject a quarantined drop; it stays in the quarantine area
+    pub fn reject_drop(
+        &self,
+        drop_id: &str,
+        agent_id: &str,
+        reason: &str,
+    ) -> std::result::Result<QuarantineRecord, String> {
+        let record = self
+            .quarantine
+            .reject(drop_id, agent_id, reason)
+            .map_err(|err| err.to_string())?;
+        self.update_state(drop_id, CRCState::Failed)?;
+        Ok(record)
+    }
+
+    fn set_location(
+        &self,
+        drop_id: &str,
+        state: CRCState,
+        path: &std::path::Path,
+    ) -> std::result::Result<(), String> {
+        let mut drops = self.drops.lock().unwrap();
+        let drop = drops
+            .get_mut(drop_id)
+            .ok_or_else(|| format!("Drop not found: {}", drop_id))?;
+        drop.state = state;
+        drop.source_path = path.to_path_buf();
+        Ok(())
+    }
+
     /// Update drop state
     fn update_state(&self, drop_id: &str, state: CRCState) -> std::result::Result<(), String> {
         let mut drops = self.drops.lock().unwrap();
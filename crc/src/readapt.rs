@@ -0,0 +1,236 @@
+// CRC Re-adaptation - incremental updates for drops whose upstream moved on
+// Diffs the recorded file hashes of the integrated version against the new upstream tree,
+// re-analyzes only what changed, and replays earlier adaptation decisions whose files are
+// untouched. Decisions on modified files are flagged for review instead of replayed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::{AdaptationResult, AnalysisResult, Result};
+
+/// A single adaptation applied to a drop file, pinned to the content it was made against.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AdaptationDecision {
+    pub path: String,
+    pub action: String,
+    /// blake3 of the upstream file the decision was made against.
+    pub content_hash: String,
+}
+
+/// Paths that differ between two versions of a drop.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TreeDiff {
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: usize,
+}
+
+impl TreeDiff {
+    pub fn between(
+        previous: &BTreeMap<String, String>,
+        current: &BTreeMap<String, String>,
+    ) -> Self {
+        let mut diff = TreeDiff::default();
+        for (path, hash) in current {
+            match previous.get(path) {
+                None => diff.added.push(path.clone()),
+                Some(old) if old != hash => diff.modified.push(path.clone()),
+                Some(_) => diff.unchanged += 1,
+            }
+        }
+        diff.removed = previous
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// Added and modified paths, the only ones that need re-analysis.
+    pub fn changed(&self) -> impl Iterator<Item = &String> {
+        self.added.iter().chain(self.modified.iter())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Prior decisions sorted by whether they still apply to the new version.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DecisionReplay {
+    /// File unchanged upstream; the decision is carried forward as-is.
+    pub replayed: Vec<AdaptationDecision>,
+    /// File changed upstream; the decision must be reviewed before it is reapplied.
+    pub stale: Vec<AdaptationDecision>,
+    /// File removed upstream; the decision no longer applies.
+    pub retired: Vec<AdaptationDecision>,
+}
+
+impl DecisionReplay {
+    pub fn sort(decisions: &[AdaptationDecision], current: &BTreeMap<String, String>) -> Self {
+        let mut replay = DecisionReplay::default();
+        for decision in decisions {
+            match current.get(&decision.path) {
+                Some(hash) if *hash == decision.content_hash => {
+                    replay.replayed.push(decision.clone())
+                }
+                Some(_) => replay.stale.push(decision.clone()),
+                None => replay.retired.push(decision.clone()),
+            }
+        }
+        replay
+    }
+}
+
+/// Delta report produced by `CRCSystem::readapt`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadaptationReport {
+    pub drop_id: String,
+    pub previous_checksum: String,
+    pub new_checksum: String,
+    pub diff: TreeDiff,
+    /// Analysis of the added and modified paths only.
+    pub analysis: AnalysisResult,
+    pub decisions: DecisionReplay,
+    pub adaptation: AdaptationResult,
+}
+
+impl ReadaptationReport {
+    /// The drop can go straight to validation: nothing stale and no new issues.
+    pub fn is_clean(&self) -> bool {
+        self.decisions.stale.is_empty() && self.analysis.issues.is_empty()
+    }
+}
+
+/// Copy `paths` from `root` into `staging`, preserving their relative layout.
+pub(crate) fn stage_paths<'a>(
+    root: &Path,
+    paths: impl IntoIterator<Item = &'a String>,
+    staging: &Path,
+) -> Result<usize> {
+    let mut staged = 0;
+    for path in paths {
+        let target = staging.join(path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(root.join(path), target)?;
+        staged += 1;
+    }
+    Ok(staged)
+}
+
+/// Delta adaptation summarising what changed and which decisions carried over.
+pub(crate) fn delta_adaptation(
+    diff: &TreeDiff,
+    analysis: &AnalysisResult,
+    decisions: &DecisionReplay,
+    provenance_summary: Option<String>,
+) -> AdaptationResult {
+    let mut diff_summary = format!(
+        "upstream update: {} added, {} modified, {} removed ({} unchanged); \
+         {} decisions replayed, {} need review, {} retired",
+        diff.added.len(),
+        diff.modified.len(),
+        diff.removed.len(),
+        diff.unchanged,
+        decisions.replayed.len(),
+        decisions.stale.len(),
+        decisions.retired.len()
+    );
+    if let Some(summary) = provenance_summary {
+        diff_summary.push_str("; ");
+        diff_summary.push_str(&summary);
+    }
+    let clean = decisions.stale.is_empty() && analysis.issues.is_empty();
+    AdaptationResult {
+        changes_made: decisions.replayed.len(),
+        files_modified: diff.added.len() + diff.modified.len() + diff.removed.len(),
+        tests_generated: 0,
+        ai_confidence: analysis.ai_confidence,
+        auto_approved: clean,
+        diff_summary,
+        sandbox_ready: clean,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CRCSystem, DropManifest, DropProvenance, Priority, SourceType};
+    use std::collections::HashMap;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let target = root.join(path);
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        fs::write(target, contents).unwrap();
+    }
+
+    #[tokio::test]
+    async fn readapt_reanalyzes_changed_paths_and_replays_untouched_decisions() {
+        let dir = tempfile::tempdir().unwrap();
+        let v1 = dir.path().join("v1");
+        write(&v1, "src/lib.rs", "pub mod client;\npub mod codec;\n");
+        write(&v1, "src/client.rs", "pub fn get() {}\n");
+        write(&v1, "src/codec.rs", "pub fn encode() {}\n");
+        write(&v1, "build.rs", "fn main() {}\n");
+
+        let crc = CRCSystem::new_test();
+        let manifest = DropManifest {
+            name: "http-lib".to_string(),
+            source: "github.com/acme/http-lib".to_string(),
+            source_type: SourceType::ExternalRepo,
+            timestamp: 0,
+            priority: Priority::Normal,
+            metadata: HashMap::new(),
+            provenance: Some(
+                DropProvenance::capture(&v1)
+                    .unwrap()
+                    .with_upstream_url("https://example.com/acme/http-lib.git")
+                    .with_tag("v1.0.0"),
+            ),
+        };
+        let drop_id = crc.register_drop(v1.clone(), manifest, None).unwrap();
+        crc.record_decision(&drop_id, "src/client.rs", "rename get -> fetch")
+            .unwrap();
+        crc.record_decision(&drop_id, "src/codec.rs", "use workspace codec")
+            .unwrap();
+        crc.record_decision(&drop_id, "build.rs", "strip build script")
+            .unwrap();
+
+        let v2 = dir.path().join("v2");
+        write(&v2, "src/lib.rs", "pub mod client;\npub mod codec;\n");
+        write(&v2, "src/client.rs", "pub fn get() {}\npub fn post() {}\n");
+        write(&v2, "src/codec.rs", "pub fn encode() {}\n");
+        write(&v2, "src/retry.rs", "pub fn backoff() {}\n");
+
+        let report = crc.readapt(&drop_id, &v2).await.unwrap();
+        assert_eq!(report.diff.added, ["src/retry.rs"]);
+        assert_eq!(report.diff.modified, ["src/client.rs"]);
+        assert_eq!(report.diff.removed, ["build.rs"]);
+        assert_eq!(report.diff.unchanged, 2);
+        assert_eq!(report.analysis.files_count, 2);
+        assert_eq!(report.decisions.replayed[0].path, "src/codec.rs");
+        assert_eq!(report.decisions.stale[0].path, "src/client.rs");
+        assert_eq!(report.decisions.retired[0].path, "build.rs");
+        assert!(!report.is_clean());
+        assert!(report
+            .adaptation
+            .diff_summary
+            .contains("1 decisions replayed, 1 need review, 1 retired"));
+
+        let drop = crc.get_drop(&drop_id).unwrap();
+        assert_eq!(drop.source_path, v2);
+        assert_eq!(drop.decisions.len(), 1);
+        let provenance = drop.manifest.provenance.unwrap();
+        assert_eq!(provenance.tree_checksum, report.new_checksum);
+        assert_eq!(
+            provenance.upstream_url.as_deref(),
+            Some("https://example.com/acme/http-lib.git")
+        );
+    }
+}
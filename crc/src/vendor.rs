@@ -0,0 +1,715 @@
+// CRC Vendor Mirror - dependency snapshots for offline and airgapped installs
+// Copies a drop's Cargo and npm dependencies into a local mirror laid out as
+// <mirror>/<ecosystem>/<name>/<version>/, rewrites the drop's manifests to use the mirror
+// copies, and returns a MirrorState for the archive index so the build can be reproduced
+// without network access. Contents come from an ordered list of fetchers: local snapshots
+// (cargo registry sources, node_modules, vendor/) and, on connected hosts, the registries.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use walkdir::WalkDir;
+
+use crate::provenance::{checksum_of, file_hashes};
+use crate::{Dependency, Error, Result};
+
+/// Default mirror root, next to the CRC archive.
+pub const MIRROR_DIR: &str = "crc/mirror";
+/// `Dependency::source` for crates.io dependencies.
+pub const CRATES_IO_SOURCE: &str = "crates.io";
+/// `Dependency::source` for npm registry dependencies.
+pub const NPM_SOURCE: &str = "npm";
+
+const CARGO_DEPENDENCY_TABLES: [&str; 3] =
+    ["dependencies", "dev-dependencies", "build-dependencies"];
+const NPM_DEPENDENCY_TABLES: [&str; 2] = ["dependencies", "devDependencies"];
+/// Directories never searched for manifests or copied into the mirror.
+const SKIPPED_DIRS: [&str; 4] = [".git", "target", "node_modules", "vendor"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+}
+
+impl Ecosystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "cargo",
+            Ecosystem::Npm => "npm",
+        }
+    }
+
+    fn source(&self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => CRATES_IO_SOURCE,
+            Ecosystem::Npm => NPM_SOURCE,
+        }
+    }
+
+    fn from_source(source: &str) -> Option<Self> {
+        match source {
+            CRATES_IO_SOURCE => Some(Ecosystem::Cargo),
+            NPM_SOURCE => Some(Ecosystem::Npm),
+            _ => None,
+        }
+    }
+}
+
+/// One dependency snapshot in the mirror.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MirrorEntry {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    /// Version as requested by the manifest.
+    pub version: String,
+    /// Snapshot directory relative to the mirror root.
+    pub path: String,
+    /// Tree checksum of the snapshot, as in `DropProvenance::tree_checksum`.
+    pub checksum: String,
+    pub files: usize,
+}
+
+/// Mirror contents a drop was built against, stored in its archive index.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MirrorState {
+    pub root: PathBuf,
+    pub entries: Vec<MirrorEntry>,
+    /// Checksum over every entry's id and tree checksum.
+    pub checksum: String,
+}
+
+impl MirrorState {
+    fn new(root: PathBuf, mut entries: Vec<MirrorEntry>) -> Self {
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        let checksums = entries
+            .iter()
+            .map(|entry| (entry.path.clone(), entry.checksum.clone()))
+            .collect();
+        Self {
+            root,
+            entries,
+            checksum: checksum_of(&checksums),
+        }
+    }
+
+    /// Entries whose snapshot is missing or no longer matches its checksum.
+    pub fn verify(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|entry| {
+                file_hashes(&self.root.join(&entry.path))
+                    .map(|files| checksum_of(&files) != entry.checksum)
+                    .unwrap_or(true)
+            })
+            .map(|entry| entry.path.clone())
+            .collect()
+    }
+
+    /// Dependencies the mirror covers, for `ArchiveIndex::dependencies`.
+    pub fn dependencies(&self) -> Vec<Dependency> {
+        self.entries
+            .iter()
+            .map(|entry| Dependency {
+                name: entry.name.clone(),
+                version: Some(entry.version.clone()),
+                source: entry.ecosystem.source().to_string(),
+                embedded_alternative: Some(self.root.join(&entry.path).display().to_string()),
+            })
+            .collect()
+    }
+}
+
+/// Outcome of vendoring one drop.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VendorReport {
+    pub mirror: MirrorState,
+    /// Snapshots added to the mirror by this run; the rest were already present.
+    pub fetched: usize,
+    /// Manifests rewritten to point at the mirror.
+    pub rewritten: Vec<PathBuf>,
+    /// Dependencies no fetcher could provide, with the last error.
+    pub missing: Vec<(Dependency, String)>,
+}
+
+/// Source of dependency contents for the mirror.
+pub trait DependencyFetcher: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Write `name` at `version` into the empty directory `dest`.
+    fn fetch(&self, ecosystem: Ecosystem, name: &str, version: &str, dest: &Path) -> Result<()>;
+}
+
+/// Copies dependencies from already-unpacked sources: cargo's `registry/src/<index>`
+/// directories (`<name>-<version>`), `node_modules` (`<name>`), or a `vendor/` tree.
+pub struct SnapshotFetcher {
+    roots: Vec<PathBuf>,
+}
+
+impl SnapshotFetcher {
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        Self { roots }
+    }
+
+    fn locate(&self, name: &str, version: &str) -> Option<PathBuf> {
+        let wanted = version_key(pinned(version));
+        for root in &self.roots {
+            let prefix = format!("{name}-");
+            let best = fs::read_dir(root)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| {
+                    let dir = entry.file_name().into_string().ok()?;
+                    let key = version_key(dir.strip_prefix(&prefix)?);
+                    (!key.is_empty() && key.starts_with(&wanted)).then(|| (key, entry.path()))
+                })
+                .max_by(|(a, _), (b, _)| a.cmp(b));
+            if let Some((_, path)) = best {
+                return Some(path);
+            }
+            let plain = root.join(name);
+            if plain.is_dir() {
+                return Some(plain);
+            }
+        }
+        None
+    }
+}
+
+impl DependencyFetcher for SnapshotFetcher {
+    fn name(&self) -> &str {
+        "snapshot"
+    }
+
+    fn fetch(&self, _ecosystem: Ecosystem, name: &str, version: &str, dest: &Path) -> Result<()> {
+        let source = self
+            .locate(name, version)
+            .ok_or_else(|| Error::FileNotFound(format!("no local snapshot of {name} {version}")))?;
+        copy_tree(&source, dest)
+    }
+}
+
+/// Downloads release tarballs from crates.io and the npm registry with `curl`.
+/// Only exact versions can be fetched this way.
+#[derive(Default)]
+pub struct RegistryFetcher;
+
+impl RegistryFetcher {
+    fn url(ecosystem: Ecosystem, name: &str, version: &str) -> String {
+        match ecosystem {
+            Ecosystem::Cargo => {
+                format!("https://static.crates.io/crates/{name}/{name}-{version}.crate")
+            }
+            Ecosystem::Npm => {
+                let file = name.rsplit('/').next().unwrap_or(name);
+                format!("https://registry.npmjs.org/{name}/-/{file}-{version}.tgz")
+            }
+        }
+    }
+}
+
+impl DependencyFetcher for RegistryFetcher {
+    fn name(&self) -> &str {
+        "registry"
+    }
+
+    fn fetch(&self, ecosystem: Ecosystem, name: &str, version: &str, dest: &Path) -> Result<()> {
+        let version = pinned(version);
+        if version_key(version).len() != 3 {
+            return Err(Error::ConfigError(format!(
+                "{name} {version} is not an exact version and cannot be downloaded"
+            )));
+        }
+        let url = Self::url(ecosystem, name, version);
+        let output = Command::new("curl")
+            .args(["-sSfL", &url])
+            .output()
+            .map_err(|err| Error::SystemError(format!("failed to run curl: {err}")))?;
+        if !output.status.success() {
+            return Err(Error::SystemError(format!(
+                "download of {url} failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        unpack_tarball(&output.stdout, dest)
+    }
+}
+
+/// Snapshots a drop's dependencies into the mirror and points its manifests there.
+pub struct Vendorer {
+    root: PathBuf,
+    fetchers: Vec<Arc<dyn DependencyFetcher>>,
+}
+
+impl Default for Vendorer {
+    fn default() -> Self {
+        Self::new(MIRROR_DIR)
+    }
+}
+
+impl Vendorer {
+    /// A vendorer that only reuses snapshots already in the mirror at `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            fetchers: Vec::new(),
+        }
+    }
+
+    /// Try `fetcher` for dependencies missing from the mirror, after those added earlier.
+    pub fn with_fetcher(mut self, fetcher: Arc<dyn DependencyFetcher>) -> Self {
+        self.fetchers.push(fetcher);
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Snapshot every registry dependency of the drop at `drop_path`, then rewrite its
+    /// manifests to use the mirror copies. Dependencies no fetcher provides are reported
+    /// and left pointing at their registry.
+    pub fn vendor(&self, drop_path: &Path) -> Result<VendorReport> {
+        let mut report = VendorReport::default();
+        let mut entries = BTreeMap::new();
+        for dependency in manifest_dependencies(drop_path)? {
+            let Some(ecosystem) = Ecosystem::from_source(&dependency.source) else {
+                continue;
+            };
+            let version = dependency
+                .version
+                .clone()
+                .unwrap_or_else(|| "*".to_string());
+            let relative = entry_path(ecosystem, &dependency.name, &version);
+            if entries.contains_key(&relative) {
+                continue;
+            }
+            let dest = self.root.join(&relative);
+            if !dest.is_dir() {
+                match self.fetch(ecosystem, &dependency.name, &version, &dest) {
+                    Ok(()) => report.fetched += 1,
+                    Err(err) => {
+                        report.missing.push((dependency, err.to_string()));
+                        continue;
+                    }
+                }
+            }
+            let files = file_hashes(&dest)?;
+            entries.insert(
+                relative.clone(),
+                MirrorEntry {
+                    ecosystem,
+                    name: dependency.name.clone(),
+                    version,
+                    path: relative,
+                    checksum: checksum_of(&files),
+                    files: files.len(),
+                },
+            );
+        }
+
+        let root = fs::canonicalize(&self.root).unwrap_or_else(|_| self.root.clone());
+        report.mirror = MirrorState::new(root, entries.into_values().collect());
+        for (manifest, ecosystem) in manifests(drop_path) {
+            let rewritten = match ecosystem {
+                Ecosystem::Cargo => rewrite_cargo_manifest(&manifest, &report.mirror)?,
+                Ecosystem::Npm => rewrite_npm_manifest(&manifest, &report.mirror)?,
+            };
+            if rewritten {
+                report.rewritten.push(manifest);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Fetch into a staging directory so a failed fetch never leaves a partial snapshot.
+    fn fetch(&self, ecosystem: Ecosystem, name: &str, version: &str, dest: &Path) -> Result<()> {
+        let parent = dest.parent().unwrap_or(&self.root);
+        fs::create_dir_all(parent)?;
+        let mut last_error = Error::FileNotFound(format!("{name} {version} is not in the mirror"));
+        for fetcher in &self.fetchers {
+            let staging = tempfile::tempdir_in(parent)?;
+            match fetcher.fetch(ecosystem, name, version, staging.path()) {
+                Ok(()) => {
+                    fs::rename(staging.path(), dest)?;
+                    return Ok(());
+                }
+                Err(err) => {
+                    last_error = Error::SystemError(format!("{}: {err}", fetcher.name()));
+                }
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// Registry dependencies declared by every Cargo.toml and package.json in the drop.
+/// Path, git, and workspace dependencies are skipped.
+pub fn manifest_dependencies(drop_path: &Path) -> Result<Vec<Dependency>> {
+    let mut dependencies = BTreeMap::new();
+    for (manifest, ecosystem) in manifests(drop_path) {
+        let raw = fs::read_to_string(&manifest)?;
+        let found = match ecosystem {
+            Ecosystem::Cargo => cargo_dependencies(&raw)
+                .map_err(|err| Error::ConfigError(format!("{}: {err}", manifest.display())))?,
+            Ecosystem::Npm => npm_dependencies(&serde_json::from_str(&raw)?),
+        };
+        for (name, version) in found {
+            dependencies
+                .entry((ecosystem, name.clone(), version.clone()))
+                .or_insert_with(|| Dependency {
+                    name,
+                    version,
+                    source: ecosystem.source().to_string(),
+                    embedded_alternative: None,
+                });
+        }
+    }
+    Ok(dependencies.into_values().collect())
+}
+
+fn manifests(drop_path: &Path) -> Vec<(PathBuf, Ecosystem)> {
+    WalkDir::new(drop_path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_dir()
+                || !SKIPPED_DIRS.iter().any(|dir| entry.file_name() == *dir)
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let ecosystem = match entry.file_name().to_str()? {
+                "Cargo.toml" => Ecosystem::Cargo,
+                "package.json" => Ecosystem::Npm,
+                _ => return None,
+            };
+            Some((entry.into_path(), ecosystem))
+        })
+        .collect()
+}
+
+/// Registry name and requested version for a Cargo dependency entry.
+fn cargo_registry_dependency(key: &str, spec: &toml::Value) -> Option<(String, Option<String>)> {
+    match spec {
+        toml::Value::String(version) => Some((key.to_string(), Some(version.clone()))),
+        toml::Value::Table(table) => {
+            if ["path", "git", "workspace", "registry"]
+                .iter()
+                .any(|local| table.contains_key(*local))
+            {
+                return None;
+            }
+            let name = table
+                .get("package")
+                .and_then(|value| value.as_str())
+                .unwrap_or(key);
+            let version = table
+                .get("version")
+                .and_then(|value| value.as_str())
+                .map(str::to_string);
+            Some((name.to_string(), version))
+        }
+        _ => None,
+    }
+}
+
+fn cargo_dependencies(raw: &str) -> std::result::Result<Vec<(String, Option<String>)>, String> {
+    let manifest: toml::Table = raw.parse().map_err(|err| format!("{err}"))?;
+    Ok(CARGO_DEPENDENCY_TABLES
+        .iter()
+        .filter_map(|table| manifest.get(*table)?.as_table())
+        .flat_map(|table| table.iter())
+        .filter_map(|(key, spec)| cargo_registry_dependency(key, spec))
+        .collect())
+}
+
+fn npm_registry_version(version: &str) -> bool {
+    !["file:", "link:", "git", "http", "workspace:", "npm:"]
+        .iter()
+        .any(|prefix| version.starts_with(prefix))
+        && !version.contains('/')
+}
+
+fn npm_dependencies(manifest: &serde_json::Value) -> Vec<(String, Option<String>)> {
+    NPM_DEPENDENCY_TABLES
+        .iter()
+        .filter_map(|table| manifest.get(*table)?.as_object())
+        .flat_map(|table| table.iter())
+        .filter_map(|(name, version)| {
+            let version = version.as_str()?;
+            npm_registry_version(version).then(|| (name.clone(), Some(version.to_string())))
+        })
+        .collect()
+}
+
+fn mirror_path(
+    mirror: &MirrorState,
+    ecosystem: Ecosystem,
+    name: &str,
+    version: &str,
+) -> Option<String> {
+    let relative = entry_path(ecosystem, name, version);
+    mirror
+        .entries
+        .iter()
+        .any(|entry| entry.path == relative)
+        .then(|| mirror.root.join(relative).display().to_string())
+}
+
+/// Point mirrored registry dependencies at their snapshots with `path = ...`, keeping
+/// the version requirement and other keys. Comments in the manifest are not preserved.
+fn rewrite_cargo_manifest(manifest: &Path, mirror: &MirrorState) -> Result<bool> {
+    let raw = fs::read_to_string(manifest)?;
+    let mut parsed: toml::Table = raw
+        .parse()
+        .map_err(|err| Error::ConfigError(format!("{}: {err}", manifest.display())))?;
+    let mut changed = false;
+    for table in CARGO_DEPENDENCY_TABLES {
+        let Some(dependencies) = parsed.get_mut(table).and_then(|value| value.as_table_mut())
+        else {
+            continue;
+        };
+        for (key, spec) in dependencies.iter_mut() {
+            let Some((name, version)) = cargo_registry_dependency(key, spec) else {
+                continue;
+            };
+            let version = version.unwrap_or_else(|| "*".to_string());
+            let Some(path) = mirror_path(mirror, Ecosystem::Cargo, &name, &version) else {
+                continue;
+            };
+            let mut table = match spec.clone() {
+                toml::Value::Table(table) => table,
+                _ => toml::Table::new(),
+            };
+            table.insert("version".to_string(), toml::Value::String(version));
+            table.insert("path".to_string(), toml::Value::String(path));
+            *spec = toml::Value::Table(table);
+            changed = true;
+        }
+    }
+    if changed {
+        let rendered = toml::to_string(&parsed)
+            .map_err(|err| Error::ConfigError(format!("{}: {err}", manifest.display())))?;
+        fs::write(manifest, rendered)?;
+    }
+    Ok(changed)
+}
+
+/// Replace mirrored npm version ranges with `file:` references to their snapshots.
+fn rewrite_npm_manifest(manifest: &Path, mirror: &MirrorState) -> Result<bool> {
+    let mut parsed: serde_json::Value = serde_json::from_str(&fs::read_to_string(manifest)?)?;
+    let mut changed = false;
+    for table in NPM_DEPENDENCY_TABLES {
+        let Some(dependencies) = parsed
+            .get_mut(table)
+            .and_then(|value| value.as_object_mut())
+        else {
+            continue;
+        };
+        for (name, version) in dependencies.iter_mut() {
+            let Some(requested) = version.as_str().filter(|v| npm_registry_version(v)) else {
+                continue;
+            };
+            if let Some(path) = mirror_path(mirror, Ecosystem::Npm, name, requested) {
+                *version = serde_json::Value::String(format!("file:{path}"));
+                changed = true;
+            }
+        }
+    }
+    if changed {
+        fs::write(manifest, serde_json::to_string_pretty(&parsed)? + "\n")?;
+    }
+    Ok(changed)
+}
+
+/// `<ecosystem>/<name>/<version>` with the version made safe for a directory name.
+fn entry_path(ecosystem: Ecosystem, name: &str, version: &str) -> String {
+    let version: String = version
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}/{}/{}", ecosystem.as_str(), name, version)
+}
+
+/// A version requirement without its operator, e.g. `^1.2` -> `1.2`.
+fn pinned(version: &str) -> &str {
+    version.trim().trim_start_matches(['^', '~', '=', 'v', ' '])
+}
+
+/// Leading numeric components of a version, e.g. `1.0.197` -> `[1, 0, 197]`.
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+fn copy_tree(source: &Path, dest: &Path) -> Result<()> {
+    let walker = WalkDir::new(source).into_iter().filter_entry(|entry| {
+        entry.depth() == 0 || !SKIPPED_DIRS.iter().any(|dir| entry.file_name() == *dir)
+    });
+    for entry in walker {
+        let entry = entry.map_err(|err| Error::SystemError(err.to_string()))?;
+        let relative = entry.path().strip_prefix(source).unwrap_or(entry.path());
+        let target = dest.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Unpack a gzipped release tarball into `dest`, dropping its single top-level directory.
+fn unpack_tarball(data: &[u8], dest: &Path) -> Result<()> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(data));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let relative: PathBuf = path.components().skip(1).collect();
+        if relative.as_os_str().is_empty()
+            || relative
+                .components()
+                .any(|part| !matches!(part, std::path::Component::Normal(_)))
+        {
+            continue;
+        }
+        let target = dest.join(&relative);
+        if entry.header().entry_type().is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        fs::write(target, contents)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn vendor_snapshots_dependencies_and_rewrites_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        let drop = dir.path().join("drop");
+        write(
+            &drop,
+            "Cargo.toml",
+            r#"[package]
+name = "demo"
+version = "0.1.0"
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] }
+local = { path = "../local" }
+missing = "2"
+"#,
+        );
+        write(
+            &drop,
+            "web/package.json",
+            r#"{ "name": "web", "dependencies": { "left-pad": "^1.3.0", "mine": "file:../mine" } }"#,
+        );
+        let registry = dir.path().join("registry");
+        write(&registry, "serde-1.0.100/src/lib.rs", "// old");
+        write(&registry, "serde-1.0.197/src/lib.rs", "// serde");
+        write(&registry, "serde-1.0.197/target/debug/junk", "");
+        write(&registry, "left-pad/index.js", "module.exports = 1;");
+
+        let vendorer = Vendorer::new(dir.path().join("mirror"))
+            .with_fetcher(Arc::new(SnapshotFetcher::new(vec![registry])));
+        let report = vendorer.vendor(&drop).unwrap();
+
+        assert_eq!(report.fetched, 2);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].0.name, "missing");
+        let paths: Vec<_> = report
+            .mirror
+            .entries
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect();
+        assert_eq!(paths, ["cargo/serde/1.0", "npm/left-pad/_1.3.0"]);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("mirror/cargo/serde/1.0/src/lib.rs")).unwrap(),
+            "// serde"
+        );
+        assert!(!dir.path().join("mirror/cargo/serde/1.0/target").exists());
+        assert_eq!(report.rewritten.len(), 2);
+
+        let cargo: toml::Table = fs::read_to_string(drop.join("Cargo.toml"))
+            .unwrap()
+            .parse()
+            .unwrap();
+        let serde = cargo["dependencies"]["serde"].as_table().unwrap();
+        assert!(serde["path"].as_str().unwrap().ends_with("cargo/serde/1.0"));
+        assert_eq!(serde["features"].as_array().unwrap().len(), 1);
+        assert_eq!(cargo["dependencies"]["missing"].as_str(), Some("2"));
+        let npm: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(drop.join("web/package.json")).unwrap())
+                .unwrap();
+        assert!(npm["dependencies"]["left-pad"]
+            .as_str()
+            .unwrap()
+            .starts_with("file:"));
+        assert_eq!(npm["dependencies"]["mine"], "file:../mine");
+
+        // Another drop with the same requirement reuses the snapshot without fetching.
+        write(
+            &dir.path().join("other"),
+            "Cargo.toml",
+            "[dependencies]\nserde = \"1.0\"\n",
+        );
+        let reused = Vendorer::new(dir.path().join("mirror"))
+            .vendor(&dir.path().join("other"))
+            .unwrap();
+        assert_eq!(reused.fetched, 0);
+        assert_eq!(reused.mirror.entries, report.mirror.entries[..1]);
+        assert!(report.mirror.verify().is_empty());
+        fs::write(
+            dir.path().join("mirror/cargo/serde/1.0/src/lib.rs"),
+            "// tampered",
+        )
+        .unwrap();
+        assert_eq!(report.mirror.verify(), ["cargo/serde/1.0"]);
+    }
+
+    #[test]
+    fn versions_are_matched_by_leading_components() {
+        assert_eq!(pinned("^1.2"), "1.2");
+        assert_eq!(version_key("1.0.197"), [1, 0, 197]);
+        assert_eq!(version_key("2.0.0-beta.1"), [2, 0, 0]);
+        assert_eq!(entry_path(Ecosystem::Npm, "a", ">=1 <2"), "npm/a/__1__2");
+    }
+}
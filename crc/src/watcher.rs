@@ -10,8 +10,8 @@ use std::time::Duration;
 use tracing::{debug, error, info};
 
 use crate::{
-    extraction::prepare_artifact_for_processing, CRCSystem, DropManifest, Error, Priority,
-    SourceType,
+    extraction::prepare_artifact_for_processing, CRCSystem, DropManifest, DropProvenance, Error,
+    Priority, SourceType,
 };
 
 /// Source type detection and configuration
@@ -328,6 +328,11 @@ impl CRCWatcher {
                 .as_secs(),
             priority: config.priority.clone(),
             metadata,
+            provenance: if path.is_dir() {
+                DropProvenance::capture(path).ok()
+            } else {
+                None
+            },
         };
 
         Ok(manifest)
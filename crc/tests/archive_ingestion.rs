@@ -68,6 +68,7 @@ async fn processes_zip_archive_via_extraction() -> Result<()> {
             .as_secs(),
         priority: Priority::High,
         metadata,
+        provenance: None,
     };
 
     let crc_system = CRCSystem::new(CRCConfig::default());
@@ -110,6 +111,7 @@ async fn processes_zip_archive_via_extraction() -> Result<()> {
             drop.source_type.clone(),
             drop.source_path.clone(),
             drop.original_artifact.clone(),
+            drop.manifest.provenance.clone(),
         )
         .await?;
 
@@ -190,6 +192,7 @@ async fn processes_tar_gz_archive_via_extraction() -> Result<()> {
             .as_secs(),
         priority: Priority::High,
         metadata,
+        provenance: None,
     };
 
     let crc_system = CRCSystem::new(CRCConfig::default());
@@ -225,6 +228,7 @@ async fn processes_tar_gz_archive_via_extraction() -> Result<()> {
             drop.source_type.clone(),
             drop.source_path.clone(),
             drop.original_artifact.clone(),
+            drop.manifest.provenance.clone(),
         )
         .await?;
 
@@ -80,6 +80,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             m.insert("purpose".to_string(), "http client library".to_string());
             m
         },
+        provenance: None,
     };
 
     let drop_id1 = crc.register_drop(
@@ -110,6 +111,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             m.insert("original_date".to_string(), "2020-05-12".to_string());
             m
         },
+        provenance: None,
     };
 
     let drop_id2 = crc.register_drop(
@@ -5,8 +5,8 @@ pub mod models;
 mod store;
 
 pub use coordinator::MemoryCoordinator;
-pub use models::{MemoryCursor, MemoryRecord, MemoryRetrieval, MemoryRole};
-pub use store::{LongTermMemory, MemoryError, SessionMemory};
+pub use models::{MemoryCursor, MemoryRecord, MemoryRetrieval, MemoryRole, RetentionPolicy};
+pub use store::{LongTermMemory, MemoryError, MemoryStore, SessionMemory};
 
 #[cfg(test)]
 mod tests {
@@ -59,4 +59,42 @@ mod tests {
             assert!(durations[index] < 100, "p95 exceeded: {:?}", durations);
         }
     }
+
+    #[test]
+    fn retention_drops_expired_then_oldest_and_compaction_summarizes() {
+        let dir = tempdir().unwrap();
+        let memory = LongTermMemory::open(dir.path()).unwrap();
+        for i in 0..5 {
+            memory
+                .append(
+                    "planner",
+                    MemoryRole::Observation,
+                    &format!("note-{i}"),
+                    HashMap::new(),
+                    Vec::new(),
+                )
+                .unwrap();
+        }
+
+        let now = chrono::Utc::now();
+        let unbounded = RetentionPolicy::default().with_max_age_secs(3600);
+        assert_eq!(memory.apply_retention(&unbounded, now).unwrap(), 0);
+        let expired = now + chrono::Duration::hours(2);
+        let capped = RetentionPolicy::default().with_max_records(3);
+        assert_eq!(memory.apply_retention(&capped, now).unwrap(), 2);
+        let contents: Vec<_> = memory.records().into_iter().map(|r| r.content).collect();
+        assert_eq!(contents, ["note-2", "note-3", "note-4"]);
+
+        let ids: Vec<u64> = memory.records()[..2].iter().map(|r| r.id).collect();
+        let summary = memory
+            .compact(&ids, "planner", "notes 2 and 3", Vec::new())
+            .unwrap();
+        assert_eq!(summary.metadata["summarizes"], "3,4");
+        let reopened = LongTermMemory::open(dir.path()).unwrap();
+        let roles: Vec<_> = reopened.records().into_iter().map(|r| r.role).collect();
+        assert_eq!(roles, [MemoryRole::Observation, MemoryRole::Summary]);
+
+        assert_eq!(reopened.apply_retention(&unbounded, expired).unwrap(), 2);
+        assert!(reopened.records().is_empty());
+    }
 }
@@ -74,3 +74,31 @@ impl MemoryRetrieval {
         }
     }
 }
+
+/// Limits applied when pruning a memory store. `None` leaves that dimension unbounded.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    #[serde(default)]
+    pub max_records: Option<usize>,
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+}
+
+impl RetentionPolicy {
+    pub fn with_max_records(mut self, max_records: usize) -> Self {
+        self.max_records = Some(max_records);
+        self
+    }
+
+    pub fn with_max_age_secs(mut self, max_age_secs: u64) -> Self {
+        self.max_age_secs = Some(max_age_secs);
+        self
+    }
+
+    /// Whether `record` is too old to keep at `now`.
+    pub fn is_expired(&self, record: &MemoryRecord, now: DateTime<Utc>) -> bool {
+        self.max_age_secs.is_some_and(|max_age| {
+            now.signed_duration_since(record.timestamp).num_seconds() > max_age as i64
+        })
+    }
+}
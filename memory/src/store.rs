@@ -1,4 +1,5 @@
-use crate::models::{MemoryCursor, MemoryRecord, MemoryRetrieval, MemoryRole};
+use crate::models::{MemoryCursor, MemoryRecord, MemoryRetrieval, MemoryRole, RetentionPolicy};
+use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -94,6 +95,75 @@ impl LongTermMemory {
         let snapshot = self.entries.read().clone();
         PersistedStore::save(&self.path, &snapshot)
     }
+
+    /// Snapshot of every stored record, oldest first.
+    pub fn records(&self) -> Vec<MemoryRecord> {
+        self.entries.read().clone()
+    }
+
+    /// Drop expired records, then the oldest ones beyond `max_records`.
+    /// Returns how many records were removed.
+    pub fn apply_retention(
+        &self,
+        policy: &RetentionPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<usize, MemoryError> {
+        let removed = {
+            let mut entries = self.entries.write();
+            let before = entries.len();
+            entries.retain(|record| !policy.is_expired(record, now));
+            if let Some(max_records) = policy.max_records {
+                let overflow = entries.len().saturating_sub(max_records);
+                entries.drain(..overflow);
+            }
+            before - entries.len()
+        };
+        if removed > 0 {
+            self.persist()?;
+        }
+        Ok(removed)
+    }
+
+    /// Drop every record matching `predicate`, returning how many were removed.
+    pub fn remove_where(
+        &self,
+        predicate: impl Fn(&MemoryRecord) -> bool,
+    ) -> Result<usize, MemoryError> {
+        let removed = {
+            let mut entries = self.entries.write();
+            let before = entries.len();
+            entries.retain(|record| !predicate(record));
+            before - entries.len()
+        };
+        if removed > 0 {
+            self.persist()?;
+        }
+        Ok(removed)
+    }
+
+    /// Replace the records in `ids` with a single [`MemoryRole::Summary`] record.
+    pub fn compact(
+        &self,
+        ids: &[u64],
+        agent: &str,
+        summary: &str,
+        tags: Vec<String>,
+    ) -> Result<MemoryRecord, MemoryError> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "summarizes".to_string(),
+            ids.iter().map(u64::to_string).collect::<Vec<_>>().join(","),
+        );
+        let record = MemoryRecord::new(id, agent, MemoryRole::Summary, summary, metadata, tags);
+        {
+            let mut entries = self.entries.write();
+            entries.retain(|record| !ids.contains(&record.id));
+            entries.push(record.clone());
+        }
+        self.persist()?;
+        Ok(record)
+    }
 }
 
 impl MemoryStore for LongTermMemory {
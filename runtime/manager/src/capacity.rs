@@ -0,0 +1,170 @@
+//! Capacity reports from synthetic load runs and the host classification
+//! thresholds derived from them.
+//!
+//! `noa-loadgen` writes one [`CapacityReport`] per run, recording the host it ran
+//! on and whether the gateway/API met its latency and error-rate targets.
+//! [`CapacityThresholds::from_reports`] turns a set of reports into memory
+//! thresholds for [`RuntimePolicy::with_capacity_thresholds`](crate::RuntimePolicy::with_capacity_thresholds):
+//! the smallest host that met its targets, above every host that did not.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Host a load run was measured on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CapacityHost {
+    pub cpu_cores: usize,
+    pub memory_gb: f64,
+    pub gpu_count: usize,
+    /// Memory of the largest GPU, when one was detected.
+    #[serde(default)]
+    pub gpu_memory_gb: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct LatencyPercentiles {
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Outcome of one synthetic load run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CapacityReport {
+    pub target: String,
+    pub profile: String,
+    pub host: CapacityHost,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub throughput_rps: f64,
+    pub latency: LatencyPercentiles,
+    /// Whether every latency and error-rate target was met.
+    pub targets_met: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum CapacityReportError {
+    #[error("failed to read capacity report at {path}: {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to parse capacity report at {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+impl CapacityReport {
+    pub fn load_from_path(path: impl AsRef<Path>) -> Result<Self, CapacityReportError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|source| CapacityReportError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        serde_json::from_str(&data).map_err(|source| CapacityReportError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// Classification thresholds supported by measured capacity. `None` leaves the
+/// policy default in place.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CapacityThresholds {
+    pub lightweight_memory_threshold_gb: Option<f64>,
+    pub min_gpu_memory_gb: Option<f64>,
+    /// How each threshold was derived, for plan notes.
+    pub evidence: Vec<String>,
+}
+
+impl CapacityThresholds {
+    /// Derive thresholds from load runs. CPU-only hosts set the lightweight
+    /// memory threshold from system memory; GPU hosts set the minimum GPU memory.
+    pub fn from_reports(reports: &[CapacityReport]) -> Self {
+        let cpu = reports
+            .iter()
+            .filter(|report| report.host.gpu_count == 0)
+            .map(|report| (report.host.memory_gb, report.targets_met));
+        let gpu = reports
+            .iter()
+            .filter(|report| report.host.gpu_count > 0)
+            .filter_map(|report| Some((report.host.gpu_memory_gb?, report.targets_met)));
+
+        let mut thresholds = Self::default();
+        if let Some(threshold) = smallest_passing(cpu) {
+            thresholds.lightweight_memory_threshold_gb = Some(threshold);
+            thresholds.evidence.push(format!(
+                "lightweight memory threshold {:.1} GiB: smallest CPU host meeting load targets",
+                threshold
+            ));
+        }
+        if let Some(threshold) = smallest_passing(gpu) {
+            thresholds.min_gpu_memory_gb = Some(threshold);
+            thresholds.evidence.push(format!(
+                "minimum GPU memory {:.1} GiB: smallest GPU meeting load targets",
+                threshold
+            ));
+        }
+        thresholds
+    }
+}
+
+/// Smallest passing size strictly above every failing size.
+fn smallest_passing(samples: impl Iterator<Item = (f64, bool)>) -> Option<f64> {
+    let (passing, failing): (Vec<_>, Vec<_>) = samples.partition(|(_, met)| *met);
+    let ceiling = failing
+        .iter()
+        .map(|(size, _)| *size)
+        .fold(f64::NEG_INFINITY, f64::max);
+    passing
+        .into_iter()
+        .map(|(size, _)| size)
+        .filter(|size| *size > ceiling)
+        .min_by(f64::total_cmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(memory_gb: f64, gpu_memory_gb: Option<f64>, targets_met: bool) -> CapacityReport {
+        CapacityReport {
+            host: CapacityHost {
+                cpu_cores: 8,
+                memory_gb,
+                gpu_count: usize::from(gpu_memory_gb.is_some()),
+                gpu_memory_gb,
+            },
+            targets_met,
+            ..CapacityReport::default()
+        }
+    }
+
+    #[test]
+    fn thresholds_sit_above_every_failing_host() {
+        let thresholds = CapacityThresholds::from_reports(&[
+            report(4.0, None, false),
+            report(8.0, None, true),
+            report(12.0, None, false),
+            report(16.0, None, true),
+            report(32.0, Some(6.0), false),
+            report(32.0, Some(12.0), true),
+        ]);
+        assert_eq!(thresholds.lightweight_memory_threshold_gb, Some(16.0));
+        assert_eq!(thresholds.min_gpu_memory_gb, Some(12.0));
+        assert_eq!(thresholds.evidence.len(), 2);
+
+        let none = CapacityThresholds::from_reports(&[report(4.0, None, false)]);
+        assert_eq!(none, CapacityThresholds::default());
+    }
+}
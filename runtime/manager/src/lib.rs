@@ -2,13 +2,19 @@ use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+pub use noa_core::hardware::HostClassification;
 use noa_core::hardware::{AcceleratorKind, HardwareProfile};
 #[cfg(test)]
 use noa_core::hardware::{CpuProfile, GpuBackend, GpuProfile, MemoryProfile};
+use noa_core::world::{GpuCapability, HostCapabilities, LatencyClass, WorldGraph};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+mod capacity;
 mod wasm;
+pub use capacity::{
+    CapacityHost, CapacityReport, CapacityReportError, CapacityThresholds, LatencyPercentiles,
+};
 pub use wasm::{WasmProbeConfig, WasmProbeError, WasmProbeReport, WasmProbeRunner};
 
 /// Policy describing how runtime backends should be prioritized.
@@ -39,6 +45,19 @@ impl Default for RuntimePolicy {
     }
 }
 
+impl RuntimePolicy {
+    /// Apply thresholds measured by synthetic load runs.
+    pub fn with_capacity_thresholds(mut self, thresholds: &CapacityThresholds) -> Self {
+        if let Some(threshold) = thresholds.lightweight_memory_threshold_gb {
+            self.lightweight_memory_threshold_gb = threshold;
+        }
+        if let Some(threshold) = thresholds.min_gpu_memory_gb {
+            self.min_gpu_memory_gb = threshold;
+        }
+        self
+    }
+}
+
 /// Component type managed by the runtime manager.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum RuntimeComponent {
@@ -75,6 +94,19 @@ pub enum ExecutionBackend {
     },
 }
 
+impl ExecutionBackend {
+    /// Stable identifier advertised to clients.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExecutionBackend::LlamaCppCpu => "llama_cpp_cpu",
+            ExecutionBackend::LlamaCppGpu { .. } => "llama_cpp_gpu",
+            ExecutionBackend::PythonLightweight => "python_lightweight",
+            ExecutionBackend::PythonCPython => "python_cpython",
+            ExecutionBackend::AcceleratorOffload { .. } => "accelerator_offload",
+        }
+    }
+}
+
 /// Assignment of a backend to a component with explanatory context.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackendSelection {
@@ -97,15 +129,6 @@ impl RuntimePlan {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
-#[serde(rename_all = "lowercase")]
-pub enum HostClassification {
-    Minimal,
-    #[default]
-    Standard,
-    Accelerated,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CapabilitySignal {
     pub os: String,
@@ -180,6 +203,45 @@ pub struct CapabilityAssessment {
     pub fallback_notes: Vec<String>,
 }
 
+impl CapabilityAssessment {
+    /// Capabilities `host` advertises to clients, combining this plan with the models
+    /// declared in `world`.
+    pub fn host_capabilities(
+        &self,
+        host: impl Into<String>,
+        world: &WorldGraph,
+        repo_root: &Path,
+    ) -> HostCapabilities {
+        let (voice_models, models) = HostCapabilities::models_from_world(world, repo_root);
+        let gpu = self
+            .plan
+            .selections
+            .iter()
+            .find_map(|selection| match &selection.backend {
+                ExecutionBackend::LlamaCppGpu { vendor, memory_gb } => Some(GpuCapability {
+                    vendor: vendor.clone(),
+                    memory_gb: *memory_gb,
+                }),
+                _ => None,
+            });
+        HostCapabilities {
+            host: host.into(),
+            classification: self.classification.clone(),
+            latency: LatencyClass::for_host(&self.classification),
+            gpu,
+            backends: self
+                .plan
+                .selections
+                .iter()
+                .map(|selection| selection.backend.name().to_string())
+                .collect(),
+            voice_models,
+            models,
+            degraded: self.unsupported_dependencies.clone(),
+        }
+    }
+}
+
 pub struct AdaptiveRuntimeController {
     policy: RuntimePolicy,
     graph: KernelRuntimeGraph,
@@ -603,6 +665,17 @@ mod tests {
             .selections
             .iter()
             .any(|selection| matches!(selection.backend, ExecutionBackend::LlamaCppGpu { .. })));
+
+        let world = WorldGraph::load_default().expect("world graph should load");
+        let capabilities =
+            assessment.host_capabilities("edge-01", &world, &WorldGraph::repo_root());
+        assert_eq!(capabilities.latency, LatencyClass::Realtime);
+        assert_eq!(
+            capabilities.gpu.and_then(|gpu| gpu.vendor).as_deref(),
+            Some(GpuBackend::Nvidia.vendor_name())
+        );
+        assert_eq!(capabilities.backends[0], "llama_cpp_gpu");
+        assert!(capabilities.degraded.is_empty());
     }
 
     #[test]
@@ -629,6 +702,31 @@ mod tests {
         assert!(!assessment.fallback_notes.is_empty());
     }
 
+    #[test]
+    fn capacity_thresholds_reclassify_undersized_hosts() {
+        let profile = HardwareProfile {
+            cpu: cpu(),
+            memory: mem(16, 12),
+            gpus: vec![],
+            accelerators: vec![],
+        };
+        let thresholds = CapacityThresholds {
+            lightweight_memory_threshold_gb: Some(24.0),
+            ..CapacityThresholds::default()
+        };
+        let policy = RuntimePolicy::default().with_capacity_thresholds(&thresholds);
+        assert_eq!(policy.min_gpu_memory_gb, 8.0);
+
+        let controller = AdaptiveRuntimeController::new(policy, runtime_graph());
+        let workloads = vec!["gateway".to_string()];
+        let assessment = controller.plan(&profile, &workloads).unwrap();
+        assert_eq!(assessment.classification, HostClassification::Minimal);
+        assert_eq!(
+            assessment.unsupported_dependencies,
+            vec!["gateway".to_string()]
+        );
+    }
+
     #[test]
     fn wasm_probe_runner_executes_minimal_module() {
         let dir = tempdir().unwrap();
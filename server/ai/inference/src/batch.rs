@@ -0,0 +1,456 @@
+//! Request coalescing for many small concurrent completions.
+//!
+//! [`InferenceBatcher`] queues requests for a short window and sends compatible ones,
+//! meaning those with the same temperature, token limit, and stop sequences, to the
+//! provider as one batch. Each batch takes at most `max_per_client` requests from any
+//! one caller, interleaving callers in arrival order, so a chatty agent cannot crowd out
+//! the rest. Every caller gets back its queue wait, backend latency, and batch size.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, warn};
+
+use crate::client::{CompletionRequest, CompletionResponse};
+use crate::providers::{Provider, ProviderMetadata};
+use crate::telemetry::{TelemetryEvent, TelemetryHandle, TelemetryStatus};
+
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    /// How long the first queued request waits for others to join it.
+    pub window: Duration,
+    pub max_batch_size: usize,
+    /// Cap on one client's requests within a single batch.
+    pub max_per_client: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(10),
+            max_batch_size: 16,
+            max_per_client: 4,
+        }
+    }
+}
+
+impl BatchConfig {
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size.max(1);
+        self
+    }
+
+    pub fn with_max_per_client(mut self, max_per_client: usize) -> Self {
+        self.max_per_client = max_per_client.max(1);
+        self
+    }
+}
+
+/// A completion returned through the batcher, with its latency breakdown.
+#[derive(Debug, Clone)]
+pub struct BatchedCompletion {
+    pub response: CompletionResponse,
+    /// Time spent queued before the batch was sent.
+    pub queue_ms: u128,
+    /// Backend time for the whole batch.
+    pub backend_ms: u128,
+    pub batch_size: usize,
+}
+
+impl BatchedCompletion {
+    pub fn total_ms(&self) -> u128 {
+        self.queue_ms + self.backend_ms
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchStats {
+    pub requests: u64,
+    pub batches: u64,
+    /// Requests that shared a backend call with at least one other request.
+    pub coalesced: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    requests: AtomicU64,
+    batches: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+/// Requests may share a batch only when their sampling parameters match.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BatchKey {
+    temperature: Option<u32>,
+    max_tokens: Option<usize>,
+    stop: Option<Vec<String>>,
+}
+
+impl BatchKey {
+    fn of(request: &CompletionRequest) -> Self {
+        Self {
+            temperature: request.temperature.map(f32::to_bits),
+            max_tokens: request.max_tokens,
+            stop: request.stop.clone(),
+        }
+    }
+}
+
+struct Pending {
+    client: String,
+    request: CompletionRequest,
+    enqueued: Instant,
+    reply: oneshot::Sender<anyhow::Result<BatchedCompletion>>,
+}
+
+#[derive(Clone)]
+pub struct InferenceBatcher {
+    sender: mpsc::UnboundedSender<Pending>,
+    counters: Arc<Counters>,
+}
+
+impl InferenceBatcher {
+    /// Start the batching task on the current tokio runtime.
+    pub fn spawn(provider: Arc<dyn Provider>, config: BatchConfig) -> Self {
+        Self::spawn_with_telemetry(provider, config, None)
+    }
+
+    /// Like [`InferenceBatcher::spawn`], recording one telemetry event per request.
+    pub fn spawn_with_telemetry(
+        provider: Arc<dyn Provider>,
+        config: BatchConfig,
+        telemetry: Option<TelemetryHandle>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let counters = Arc::new(Counters::default());
+        let worker = Worker {
+            provider,
+            config,
+            telemetry,
+            counters: Arc::clone(&counters),
+        };
+        tokio::spawn(worker.run(receiver));
+        Self { sender, counters }
+    }
+
+    /// Queue `request` on behalf of `client` and wait for its completion.
+    pub async fn complete(
+        &self,
+        client: impl Into<String>,
+        request: CompletionRequest,
+    ) -> anyhow::Result<BatchedCompletion> {
+        let (reply, response) = oneshot::channel();
+        self.counters.requests.fetch_add(1, Ordering::Relaxed);
+        self.sender
+            .send(Pending {
+                client: client.into(),
+                request,
+                enqueued: Instant::now(),
+                reply,
+            })
+            .map_err(|_| anyhow!("inference batcher has shut down"))?;
+        response
+            .await
+            .map_err(|_| anyhow!("inference batcher dropped the request"))?
+    }
+
+    pub fn stats(&self) -> BatchStats {
+        BatchStats {
+            requests: self.counters.requests.load(Ordering::Relaxed),
+            batches: self.counters.batches.load(Ordering::Relaxed),
+            coalesced: self.counters.coalesced.load(Ordering::Relaxed),
+        }
+    }
+}
+
+struct Worker {
+    provider: Arc<dyn Provider>,
+    config: BatchConfig,
+    telemetry: Option<TelemetryHandle>,
+    counters: Arc<Counters>,
+}
+
+impl Worker {
+    async fn run(self, mut receiver: mpsc::UnboundedReceiver<Pending>) {
+        let worker = Arc::new(self);
+        let mut queues: Vec<(BatchKey, Vec<Pending>)> = Vec::new();
+        let mut open = true;
+        while open || !queues.is_empty() {
+            if queues.is_empty() {
+                match receiver.recv().await {
+                    Some(pending) => enqueue(&mut queues, pending),
+                    None => break,
+                }
+            }
+            let deadline = tokio::time::sleep(worker.config.window);
+            tokio::pin!(deadline);
+            while open && !worker.any_full(&queues) {
+                tokio::select! {
+                    _ = &mut deadline => break,
+                    next = receiver.recv() => match next {
+                        Some(pending) => enqueue(&mut queues, pending),
+                        None => open = false,
+                    },
+                }
+            }
+
+            // One batch per compatibility group per window; leftovers stay queued
+            // ahead of newer arrivals.
+            for (_, queue) in queues.iter_mut() {
+                let batch = take_fair_batch(queue, &worker.config);
+                let worker = Arc::clone(&worker);
+                tokio::spawn(async move { worker.send(batch).await });
+            }
+            queues.retain(|(_, queue)| !queue.is_empty());
+        }
+    }
+
+    fn any_full(&self, queues: &[(BatchKey, Vec<Pending>)]) -> bool {
+        queues
+            .iter()
+            .any(|(_, queue)| queue.len() >= self.config.max_batch_size)
+    }
+
+    async fn send(&self, batch: Vec<Pending>) {
+        let batch_size = batch.len();
+        let started = Instant::now();
+        let requests: Vec<CompletionRequest> = batch
+            .iter()
+            .map(|pending| pending.request.clone())
+            .collect();
+        let result = if batch_size > 1 && self.provider.supports_batching() {
+            self.provider.complete_batch(requests).await
+        } else {
+            futures::future::try_join_all(
+                requests
+                    .into_iter()
+                    .map(|request| self.provider.complete(request)),
+            )
+            .await
+        };
+        let backend_ms = started.elapsed().as_millis();
+        self.counters.batches.fetch_add(1, Ordering::Relaxed);
+        if batch_size > 1 {
+            self.counters
+                .coalesced
+                .fetch_add(batch_size as u64, Ordering::Relaxed);
+        }
+        debug!(batch_size, backend_ms, "inference batch completed");
+
+        let metadata = self.provider.metadata();
+        match result {
+            Ok(responses) => {
+                for (pending, response) in batch.into_iter().zip(responses) {
+                    let completion = BatchedCompletion {
+                        queue_ms: started.duration_since(pending.enqueued).as_millis(),
+                        backend_ms,
+                        batch_size,
+                        response,
+                    };
+                    self.record(&metadata, &completion);
+                    let _ = pending.reply.send(Ok(completion));
+                }
+            }
+            Err(err) => {
+                let message = err.to_string();
+                warn!(batch_size, error = %message, "inference batch failed");
+                for pending in batch {
+                    if let Some(telemetry) = &self.telemetry {
+                        telemetry.record(TelemetryEvent {
+                            provider: metadata.id.to_string(),
+                            model: metadata.model.clone(),
+                            latency_ms: pending.enqueued.elapsed().as_millis(),
+                            tokens_prompt: 0,
+                            tokens_completion: 0,
+                            status: TelemetryStatus::Failure,
+                            error: Some(message.clone()),
+                        });
+                    }
+                    let _ = pending.reply.send(Err(anyhow!("{message}")));
+                }
+            }
+        }
+    }
+
+    fn record(&self, metadata: &ProviderMetadata, completion: &BatchedCompletion) {
+        if let Some(telemetry) = &self.telemetry {
+            telemetry.record(TelemetryEvent {
+                provider: metadata.id.to_string(),
+                model: metadata.model.clone(),
+                latency_ms: completion.total_ms(),
+                tokens_prompt: completion.response.tokens_evaluated,
+                tokens_completion: completion.response.tokens_predicted,
+                status: TelemetryStatus::Success,
+                error: None,
+            });
+        }
+    }
+}
+
+fn enqueue(queues: &mut Vec<(BatchKey, Vec<Pending>)>, pending: Pending) {
+    let key = BatchKey::of(&pending.request);
+    match queues.iter_mut().find(|(existing, _)| *existing == key) {
+        Some((_, queue)) => queue.push(pending),
+        None => queues.push((key, vec![pending])),
+    }
+}
+
+/// Take up to `max_batch_size` requests, one per client per round in order of each
+/// client's first request, and at most `max_per_client` from any client.
+fn take_fair_batch(queue: &mut Vec<Pending>, config: &BatchConfig) -> Vec<Pending> {
+    let mut clients: Vec<(&str, Vec<usize>)> = Vec::new();
+    for (index, pending) in queue.iter().enumerate() {
+        match clients
+            .iter_mut()
+            .find(|(client, _)| *client == pending.client)
+        {
+            Some((_, indices)) => indices.push(index),
+            None => clients.push((&pending.client, vec![index])),
+        }
+    }
+
+    let mut selected = Vec::new();
+    'rounds: for round in 0..config.max_per_client {
+        let mut progressed = false;
+        for (_, indices) in &clients {
+            if let Some(index) = indices.get(round) {
+                selected.push(*index);
+                progressed = true;
+                if selected.len() == config.max_batch_size {
+                    break 'rounds;
+                }
+            }
+        }
+        if !progressed {
+            break;
+        }
+    }
+
+    selected.sort_unstable();
+    let mut batch = Vec::with_capacity(selected.len());
+    for index in selected.into_iter().rev() {
+        batch.push(queue.remove(index));
+    }
+    batch.reverse();
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::CompletionStream;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct BatchingProvider {
+        batches: Mutex<Vec<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Provider for BatchingProvider {
+        fn metadata(&self) -> ProviderMetadata {
+            ProviderMetadata {
+                id: "batching",
+                model: "test".to_string(),
+            }
+        }
+
+        async fn complete(&self, request: CompletionRequest) -> anyhow::Result<CompletionResponse> {
+            Ok(self.complete_batch(vec![request]).await?.remove(0))
+        }
+
+        async fn stream(&self, _request: CompletionRequest) -> anyhow::Result<CompletionStream> {
+            anyhow::bail!("not used")
+        }
+
+        async fn health_check(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn supports_batching(&self) -> bool {
+            true
+        }
+
+        async fn complete_batch(
+            &self,
+            requests: Vec<CompletionRequest>,
+        ) -> anyhow::Result<Vec<CompletionResponse>> {
+            let prompts: Vec<String> = requests.iter().map(|r| r.prompt.clone()).collect();
+            self.batches.lock().unwrap().push(prompts.clone());
+            Ok(prompts
+                .into_iter()
+                .map(|prompt| CompletionResponse {
+                    content: format!("done:{prompt}"),
+                    model: "test".to_string(),
+                    tokens_evaluated: 1,
+                    tokens_predicted: 1,
+                    latency_ms: 0,
+                })
+                .collect())
+        }
+    }
+
+    fn request(prompt: &str, max_tokens: usize) -> CompletionRequest {
+        CompletionRequest {
+            prompt: prompt.to_string(),
+            temperature: Some(0.2),
+            max_tokens: Some(max_tokens),
+            stop: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn coalesces_compatible_requests_and_caps_each_client() {
+        let provider = Arc::new(BatchingProvider::default());
+        let batcher = InferenceBatcher::spawn(
+            provider.clone(),
+            BatchConfig::default()
+                .with_window(Duration::from_millis(50))
+                .with_max_batch_size(8)
+                .with_max_per_client(2),
+        );
+
+        let mut calls = Vec::new();
+        for (client, prompt, max_tokens) in [
+            ("greedy", "g1", 16),
+            ("greedy", "g2", 16),
+            ("greedy", "g3", 16),
+            ("polite", "p1", 16),
+            ("long", "l1", 512),
+        ] {
+            let batcher = batcher.clone();
+            calls.push(tokio::spawn(async move {
+                batcher.complete(client, request(prompt, max_tokens)).await
+            }));
+            tokio::task::yield_now().await;
+        }
+        let mut completions = Vec::new();
+        for call in calls {
+            completions.push(call.await.unwrap().unwrap());
+        }
+
+        assert_eq!(completions[3].response.content, "done:p1");
+        assert_eq!(completions[0].batch_size, 3);
+        assert_eq!(completions[2].batch_size, 1);
+        assert_eq!(completions[4].batch_size, 1);
+        assert!(completions[2].queue_ms >= completions[0].queue_ms);
+
+        let batches = provider.batches.lock().unwrap().clone();
+        assert!(batches.contains(&vec!["g1".into(), "g2".into(), "p1".into()]));
+        assert!(batches.contains(&vec!["l1".to_string()]));
+        assert!(batches.contains(&vec!["g3".to_string()]));
+        let stats = batcher.stats();
+        assert_eq!(stats.requests, 5);
+        assert_eq!(stats.batches, 3);
+        assert_eq!(stats.coalesced, 3);
+    }
+}
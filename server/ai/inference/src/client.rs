@@ -2,6 +2,7 @@ use std::time::Instant;
 
 use crate::stream::{parse_json_lines_stream, parse_sse_stream, CompletionChunk, CompletionStream};
 use anyhow::{anyhow, Context, Result};
+use noa_core::cost::CostUsage;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -30,6 +31,13 @@ pub struct CompletionResponse {
     pub latency_ms: u128,
 }
 
+impl CompletionResponse {
+    /// Tokens consumed, for charging to the request in a `CostLedger`.
+    pub fn cost(&self) -> CostUsage {
+        CostUsage::tokens((self.tokens_evaluated + self.tokens_predicted) as u64)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LlamaClient {
     client: Client,
@@ -67,6 +75,48 @@ impl LlamaClient {
         Ok(result)
     }
 
+    /// Complete several prompts sharing `template`'s sampling parameters in one call;
+    /// llama.cpp answers an array `prompt` with one result per entry.
+    pub async fn completion_batch(
+        &self,
+        template: &CompletionRequest,
+        prompts: Vec<String>,
+    ) -> Result<Vec<CompletionResponse>> {
+        let url = format!("{}/completion", self.base_url);
+        let started = Instant::now();
+        let expected = prompts.len();
+        let mut payload = serde_json::to_value(template)
+            .context("Failed to serialise batched completion request")?;
+        payload["prompt"] = json!(prompts);
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to send batched completion request")?
+            .error_for_status()
+            .map_err(|err| anyhow!("llama.cpp batched completion failed: {}", err))?;
+
+        let mut results: Vec<CompletionResponse> = response
+            .json()
+            .await
+            .context("Failed to parse batched completion response")?;
+        if results.len() != expected {
+            return Err(anyhow!(
+                "llama.cpp returned {} results for {} prompts",
+                results.len(),
+                expected
+            ));
+        }
+        let latency_ms = started.elapsed().as_millis();
+        for result in &mut results {
+            result.latency_ms = latency_ms;
+        }
+        Ok(results)
+    }
+
     pub async fn stream_completion(&self, request: CompletionRequest) -> Result<CompletionStream> {
         let url = format!("{}/completion", self.base_url);
         let mut payload = serde_json::to_value(&request)
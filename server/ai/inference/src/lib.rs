@@ -1,9 +1,11 @@
+pub mod batch;
 pub mod client;
 pub mod providers;
 pub mod router;
 pub mod stream;
 pub mod telemetry;
 
+pub use batch::{BatchConfig, BatchStats, BatchedCompletion, InferenceBatcher};
 pub use client::{CompletionRequest, CompletionResponse, LlamaClient};
 pub use providers::{Provider, ProviderMetadata};
 pub use router::ProviderRouter;
@@ -1,5 +1,8 @@
+use std::sync::Arc;
+
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
+use noa_core::scheduler::{self, HostResources, ReservationError, ReservationGuard};
 use serde::{Deserialize, Serialize};
 
 use super::{Provider, ProviderMetadata};
@@ -11,18 +14,38 @@ pub struct LlamaCppConfig {
     pub endpoint: String,
     #[serde(default = "default_model")]
     pub model: String,
+    /// CPU cores and VRAM held on the kernel scheduler while the provider is alive,
+    /// so pipeline stages cannot claim them.
+    #[serde(default)]
+    pub reservation: Option<HostResources>,
 }
 
 #[derive(Debug, Clone)]
 pub struct LlamaCppProvider {
     config: LlamaCppConfig,
     client: LlamaClient,
+    reservation: Option<Arc<ReservationGuard>>,
 }
 
 impl LlamaCppProvider {
     pub fn new(config: LlamaCppConfig) -> Self {
         let client = LlamaClient::new(config.endpoint.clone());
-        Self { config, client }
+        Self {
+            config,
+            client,
+            reservation: None,
+        }
+    }
+
+    /// Claim the configured reservation. Fails when the resources are already
+    /// reserved by other holders.
+    pub fn with_host_reservation(mut self) -> Result<Self, ReservationError> {
+        if let Some(resources) = self.config.reservation {
+            let guard = scheduler::global()
+                .reserve(format!("inference:{}", self.config.model), resources)?;
+            self.reservation = Some(Arc::new(guard));
+        }
+        Ok(self)
     }
 }
 
@@ -70,6 +93,38 @@ impl Provider for LlamaCppProvider {
         Ok(stream)
     }
 
+    fn supports_batching(&self) -> bool {
+        true
+    }
+
+    async fn complete_batch(
+        &self,
+        requests: Vec<CompletionRequest>,
+    ) -> anyhow::Result<Vec<CompletionResponse>> {
+        let Some(template) = requests.first().cloned() else {
+            return Ok(Vec::new());
+        };
+        let span = tracing::info_span!(
+            "llama_cpp.complete_batch",
+            endpoint = %self.config.endpoint,
+            model = %self.config.model,
+            batch_size = requests.len(),
+        );
+        let _guard = span.enter();
+        let prompts = requests.into_iter().map(|request| request.prompt).collect();
+        let mut responses = self
+            .client
+            .completion_batch(&template, prompts)
+            .await
+            .context("failed to complete batch via llama.cpp")?;
+        for response in &mut responses {
+            if response.model.is_empty() {
+                response.model = self.config.model.clone();
+            }
+        }
+        Ok(responses)
+    }
+
     async fn health_check(&self) -> anyhow::Result<()> {
         if self.client.health_check().await? {
             Ok(())
@@ -21,4 +21,19 @@ pub trait Provider: Send + Sync {
     async fn complete(&self, request: CompletionRequest) -> anyhow::Result<CompletionResponse>;
     async fn stream(&self, request: CompletionRequest) -> anyhow::Result<CompletionStream>;
     async fn health_check(&self) -> anyhow::Result<()>;
+
+    /// Whether [`Provider::complete_batch`] sends the whole batch in one backend call.
+    fn supports_batching(&self) -> bool {
+        false
+    }
+
+    /// Complete several requests sharing sampling parameters, returning one response
+    /// per request in order. The default issues the requests concurrently.
+    async fn complete_batch(
+        &self,
+        requests: Vec<CompletionRequest>,
+    ) -> anyhow::Result<Vec<CompletionResponse>> {
+        futures::future::try_join_all(requests.into_iter().map(|request| self.complete(request)))
+            .await
+    }
 }
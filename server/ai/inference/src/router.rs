@@ -6,6 +6,7 @@ use std::time::Instant;
 
 use anyhow::{anyhow, Context as AnyhowContext};
 use futures::Stream;
+use noa_core::scheduler::HostResources;
 use tracing::{info, warn};
 
 use crate::client::{CompletionRequest, CompletionResponse};
@@ -391,8 +392,24 @@ fn build_provider_from_env(id: &str) -> anyhow::Result<Arc<dyn Provider>> {
             let endpoint = env::var("LLAMA_CPP_ENDPOINT")
                 .unwrap_or_else(|_| "http://127.0.0.1:8080/v1".into());
             let model = env::var("LLAMA_CPP_MODEL").unwrap_or_else(|_| "llama.cpp".into());
-            let config = LlamaCppConfig { endpoint, model };
-            Ok(Arc::new(LlamaCppProvider::new(config)))
+            let cores = env::var("LLAMA_CPP_RESERVE_CORES")
+                .ok()
+                .and_then(|value| value.parse::<u32>().ok());
+            let vram_mb = env::var("LLAMA_CPP_RESERVE_VRAM_MB")
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok());
+            let reservation = (cores.is_some() || vram_mb.is_some()).then(|| {
+                HostResources::new(cores.unwrap_or_default(), vram_mb.unwrap_or_default())
+            });
+            let config = LlamaCppConfig {
+                endpoint,
+                model,
+                reservation,
+            };
+            let provider = LlamaCppProvider::new(config)
+                .with_host_reservation()
+                .context("failed to reserve host resources for llama.cpp")?;
+            Ok(Arc::new(provider))
         }
         other => Err(anyhow!("unsupported provider: {}", other)),
     }
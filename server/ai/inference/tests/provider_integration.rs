@@ -140,6 +140,7 @@ async fn router_falls_back_and_records_telemetry() {
         noa_inference::providers::llama_cpp::LlamaCppConfig {
             endpoint: fallback.uri(),
             model: "llama-local".into(),
+            reservation: None,
         },
     );
 
@@ -203,6 +204,7 @@ async fn telemetry_records_stream_completion() {
         noa_inference::providers::llama_cpp::LlamaCppConfig {
             endpoint: fallback.uri(),
             model: "llama-test".into(),
+            reservation: None,
         },
     );
 
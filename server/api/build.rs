@@ -1,6 +1,10 @@
+use std::path::PathBuf;
+
 fn main() {
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR set by cargo"));
     tonic_build::configure()
         .build_client(false)
+        .file_descriptor_set_path(out_dir.join("noa_api_descriptor.bin"))
         .compile(&["proto/noa_api.proto"], &["proto"])
         .expect("failed to compile noa api protos");
 }
@@ -0,0 +1,377 @@
+//! Request authentication for the HTTP and gRPC surfaces.
+//!
+//! Callers authenticate with an API key (`x-noa-api-key` or `x-api-key`) or a capability
+//! token issued by [`noa_core::token`] (`x-noa-capability` or `Authorization: Bearer`).
+//! The resolved [`RequestIdentity`] is stored in the request extensions for handlers to
+//! authorise against. Read-only requests may stay anonymous; mutating requests must
+//! authenticate unless their payload is signed (see [`SIGNED_PAYLOAD_PATHS`]), and
+//! presenting invalid credentials is always rejected.
+
+use crate::problem::{ErrorCode, Problem};
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, HeaderValue, Method, Request, Response};
+use axum::response::IntoResponse;
+use futures::future::BoxFuture;
+use metrics::counter;
+use noa_core::token;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+/// Scope a capability token must grant to be accepted by the API.
+pub const API_TOKEN_SCOPE: &str = "api.access";
+/// Scope that lets a caller act on behalf of any agent.
+pub const ADMIN_SCOPE: &str = "api.admin";
+/// gRPC services that are always open to anonymous callers, despite using POST.
+pub const PUBLIC_GRPC_SERVICES: [&str; 3] = [
+    "/grpc.health.v1.Health/",
+    "/grpc.reflection.v1.ServerReflection/",
+    "/grpc.reflection.v1alpha.ServerReflection/",
+];
+/// HTTP routes whose payloads carry their own signature, such as forge webhooks.
+pub const SIGNED_PAYLOAD_PATHS: [&str; 1] = ["/v1/pipelines/webhook"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMethod {
+    ApiKey,
+    CapabilityToken,
+    Anonymous,
+}
+
+/// Who made a request, as established by [`AuthLayer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestIdentity {
+    pub subject: String,
+    pub method: AuthMethod,
+    pub scopes: Vec<String>,
+}
+
+impl RequestIdentity {
+    pub fn anonymous() -> Self {
+        Self {
+            subject: "anonymous".into(),
+            method: AuthMethod::Anonymous,
+            scopes: Vec::new(),
+        }
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.method != AuthMethod::Anonymous
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|candidate| candidate == scope)
+    }
+
+    /// Whether the caller may act as `agent_id`: itself, or anyone with [`ADMIN_SCOPE`].
+    pub fn may_act_as(&self, agent_id: &str) -> bool {
+        self.subject == agent_id || self.has_scope(ADMIN_SCOPE)
+    }
+}
+
+/// Handlers without the layer in front of them see an anonymous caller.
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RequestIdentity {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<RequestIdentity>()
+            .cloned()
+            .unwrap_or_else(RequestIdentity::anonymous))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    api_keys: HashMap<String, RequestIdentity>,
+    /// Scope capability tokens are validated against.
+    pub token_scope: String,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            api_keys: HashMap::new(),
+            token_scope: API_TOKEN_SCOPE.into(),
+        }
+    }
+}
+
+impl AuthConfig {
+    pub fn with_api_key(
+        mut self,
+        key: impl Into<String>,
+        subject: impl Into<String>,
+        scopes: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.api_keys.insert(
+            key.into(),
+            RequestIdentity {
+                subject: subject.into(),
+                method: AuthMethod::ApiKey,
+                scopes: scopes.into_iter().map(Into::into).collect(),
+            },
+        );
+        self
+    }
+
+    pub fn with_token_scope(mut self, scope: impl Into<String>) -> Self {
+        self.token_scope = scope.into();
+        self
+    }
+
+    /// Resolve the caller from request headers. `Ok(None)` means no credentials were sent.
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Option<RequestIdentity>, String> {
+        let bearer = header_value(headers, header::AUTHORIZATION.as_str())
+            .and_then(|value| {
+                value
+                    .strip_prefix("Bearer ")
+                    .or_else(|| value.strip_prefix("bearer "))
+                    .map(str::to_string)
+            })
+            .filter(|value| !value.is_empty());
+
+        let api_key =
+            header_value(headers, "x-noa-api-key").or_else(|| header_value(headers, "x-api-key"));
+        if let Some(key) = api_key {
+            return self
+                .api_keys
+                .get(&key)
+                .cloned()
+                .map(Some)
+                .ok_or_else(|| "unknown api key".to_string());
+        }
+        if let Some(identity) = bearer.as_ref().and_then(|key| self.api_keys.get(key)) {
+            return Ok(Some(identity.clone()));
+        }
+
+        match header_value(headers, "x-noa-capability").or(bearer) {
+            Some(secret) => token::service()
+                .validate(&secret, &self.token_scope)
+                .map(|issued| {
+                    Some(RequestIdentity {
+                        subject: issued.issued_to,
+                        method: AuthMethod::CapabilityToken,
+                        scopes: issued.scopes,
+                    })
+                })
+                .map_err(|err| format!("invalid capability token: {err}")),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Tower layer that authenticates each request and attaches its [`RequestIdentity`].
+#[derive(Debug, Clone)]
+pub struct AuthLayer {
+    config: Arc<AuthConfig>,
+}
+
+impl AuthLayer {
+    pub fn new(config: AuthConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = AuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthService {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthService<S> {
+    inner: S,
+    config: Arc<AuthConfig>,
+}
+
+impl<S, B> Service<Request<B>> for AuthService<S>
+where
+    S: Service<Request<B>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let identity = match self.config.authenticate(request.headers()) {
+            Ok(Some(identity)) => identity,
+            Ok(None) if !is_mutating(&request) => RequestIdentity::anonymous(),
+            Ok(None) => {
+                let response = unauthorized("authentication required for mutating requests");
+                return Box::pin(async move { Ok(response) });
+            }
+            Err(reason) => {
+                let response = unauthorized(&reason);
+                return Box::pin(async move { Ok(response) });
+            }
+        };
+        counter!(
+            "api_auth_accepted_total",
+            1,
+            "method" => method_label(identity.method)
+        );
+        request.extensions_mut().insert(identity);
+        Box::pin(inner.call(request))
+    }
+}
+
+fn is_mutating<B>(request: &Request<B>) -> bool {
+    let path = request.uri().path();
+    !matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) && !PUBLIC_GRPC_SERVICES
+        .iter()
+        .any(|service| path.starts_with(service))
+        && !SIGNED_PAYLOAD_PATHS.contains(&path)
+}
+
+fn method_label(method: AuthMethod) -> &'static str {
+    match method {
+        AuthMethod::ApiKey => "api_key",
+        AuthMethod::CapabilityToken => "capability_token",
+        AuthMethod::Anonymous => "anonymous",
+    }
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn unauthorized(reason: &str) -> Response<Body> {
+    counter!("api_auth_rejected_total", 1);
+    let mut response = Problem::new(ErrorCode::Unauthenticated, reason).into_response();
+    response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Router;
+    use noa_core::config::manifest::TokenPolicyManifestEntry;
+    use noa_core::token::TokenIssuanceRequest;
+    use tower::ServiceExt;
+
+    async fn whoami(identity: RequestIdentity) -> String {
+        identity.subject
+    }
+
+    fn router() -> Router {
+        let config = AuthConfig::default().with_api_key("ops-key", "ops", [ADMIN_SCOPE]);
+        Router::new()
+            .route("/v1/whoami", get(whoami).post(whoami))
+            .layer(AuthLayer::new(config))
+    }
+
+    async fn call(
+        router: &Router,
+        method: Method,
+        headers: &[(&str, &str)],
+    ) -> (StatusCode, String) {
+        let mut builder = Request::builder().method(method).uri("/v1/whoami");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let response = router
+            .clone()
+            .oneshot(builder.body(Body::empty()).expect("request"))
+            .await
+            .expect("response");
+        let status = response.status();
+        let bytes = http_body_util::BodyExt::collect(response.into_body())
+            .await
+            .expect("body")
+            .to_bytes();
+        (status, String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    #[tokio::test]
+    async fn mutating_requests_require_a_valid_key_or_capability_token() {
+        let router = router();
+
+        assert_eq!(
+            call(&router, Method::GET, &[]).await,
+            (StatusCode::OK, "anonymous".into())
+        );
+        assert_eq!(
+            call(&router, Method::POST, &[]).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            call(&router, Method::GET, &[("x-noa-api-key", "wrong")])
+                .await
+                .0,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            call(&router, Method::POST, &[("x-noa-api-key", "ops-key")]).await,
+            (StatusCode::OK, "ops".into())
+        );
+
+        token::service().configure(vec![TokenPolicyManifestEntry {
+            scope: API_TOKEN_SCOPE.into(),
+            description: None,
+            ttl_seconds: 60,
+            capabilities: vec![],
+        }]);
+        let issued = token::service()
+            .issue_token(TokenIssuanceRequest::new("agent-7", [API_TOKEN_SCOPE]))
+            .expect("token issued");
+        let bearer = format!("Bearer {}", issued.token);
+        assert_eq!(
+            call(&router, Method::POST, &[("authorization", bearer.as_str())]).await,
+            (StatusCode::OK, "agent-7".into())
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert("x-noa-capability", issued.token.parse().unwrap());
+        let agent = AuthConfig::default()
+            .authenticate(&headers)
+            .unwrap()
+            .expect("identity");
+        assert!(agent.may_act_as("agent-7") && !agent.may_act_as("agent-8"));
+        token::service().revoke(&issued.token).expect("revoked");
+        assert_eq!(
+            call(
+                &router,
+                Method::POST,
+                &[("x-noa-capability", issued.token.as_str())]
+            )
+            .await
+            .0,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
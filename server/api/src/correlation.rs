@@ -0,0 +1,201 @@
+//! Correlation ids shared by responses, problem bodies, and tracing spans.
+//!
+//! When the caller sends a W3C `traceparent`, its trace id is reused so API errors line
+//! up with the distributed trace. Otherwise an `x-correlation-id` or `x-request-id`
+//! supplied by the caller is kept, and failing that a new id in the same 32-hex-digit
+//! format is generated. The id is recorded as `trace_id` on the request span and echoed
+//! in the `x-correlation-id` response header.
+
+use crate::problem::Problem;
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, HeaderValue, Request, Response};
+use futures::future::BoxFuture;
+use std::convert::Infallible;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tracing::Instrument;
+use uuid::Uuid;
+
+pub const CORRELATION_HEADER: &str = "x-correlation-id";
+const MAX_CALLER_ID_LEN: usize = 128;
+
+/// The correlation id of the current request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorrelationId(pub String);
+
+impl CorrelationId {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let value = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        if let Some(trace_id) = value("traceparent").and_then(trace_id) {
+            return Self(trace_id);
+        }
+        value(CORRELATION_HEADER)
+            .or_else(|| value("x-request-id"))
+            .map(str::trim)
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_CALLER_ID_LEN
+                    && id.bytes().all(|byte| byte.is_ascii_graphic())
+            })
+            .map(|id| Self(id.to_string()))
+            .unwrap_or_else(|| Self(Uuid::new_v4().simple().to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The trace id of a `traceparent` header (`version-traceid-parentid-flags`).
+fn trace_id(traceparent: &str) -> Option<String> {
+    let mut parts = traceparent.trim().split('-');
+    let (_version, trace_id) = (parts.next()?, parts.next()?);
+    let valid = trace_id.len() == 32
+        && trace_id.bytes().all(|byte| byte.is_ascii_hexdigit())
+        && trace_id.bytes().any(|byte| byte != b'0');
+    valid.then(|| trace_id.to_ascii_lowercase())
+}
+
+/// Handlers without the layer in front of them get a fresh id.
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for CorrelationId {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<CorrelationId>()
+            .cloned()
+            .unwrap_or_else(|| CorrelationId::from_headers(&parts.headers)))
+    }
+}
+
+/// Tower layer assigning each request a [`CorrelationId`] and completing problem bodies.
+#[derive(Debug, Clone, Default)]
+pub struct CorrelationLayer;
+
+impl<S> Layer<S> for CorrelationLayer {
+    type Service = CorrelationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CorrelationService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CorrelationService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<Request<B>> for CorrelationService<S>
+where
+    S: Service<Request<B>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let id = CorrelationId::from_headers(request.headers());
+        let path = request.uri().path().to_string();
+        let span = tracing::info_span!(
+            "api_request",
+            trace_id = %id.as_str(),
+            method = %request.method(),
+            path = %path,
+        );
+        request.extensions_mut().insert(id.clone());
+        let response = inner.call(request).instrument(span);
+        Box::pin(async move {
+            let response = response.await?;
+            Ok(complete(response, &id, path))
+        })
+    }
+}
+
+fn complete(response: Response<Body>, id: &CorrelationId, path: String) -> Response<Body> {
+    let (mut parts, body) = response.into_parts();
+    let body = match parts.extensions.remove::<Problem>() {
+        Some(mut problem) => {
+            problem.correlation_id = Some(id.0.clone());
+            problem.instance.get_or_insert(path);
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Body::from(serde_json::to_vec(&problem).unwrap_or_default())
+        }
+        None => body,
+    };
+    if let Ok(value) = HeaderValue::from_str(id.as_str()) {
+        parts.headers.insert(CORRELATION_HEADER, value);
+    }
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::{ErrorCode, PROBLEM_CONTENT_TYPE};
+    use axum::routing::get;
+    use axum::Router;
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    async fn missing(id: CorrelationId) -> Problem {
+        Problem::new(ErrorCode::NotFound, format!("nothing here for {}", id.0))
+    }
+
+    #[tokio::test]
+    async fn problems_carry_the_trace_id_as_correlation_id() {
+        let router = Router::new()
+            .route("/v1/missing", get(missing))
+            .route("/v1/ok", get(|| async { "ok" }))
+            .layer(CorrelationLayer);
+        let trace = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+        let response = router
+            .clone()
+            .oneshot(
+                Request::get("/v1/missing")
+                    .header("traceparent", format!("00-{trace}-00f067aa0ba902b7-01"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[CORRELATION_HEADER], trace);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            PROBLEM_CONTENT_TYPE
+        );
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["correlation_id"], trace);
+        assert_eq!(body["instance"], "/v1/missing");
+        assert_eq!(body["detail"], format!("nothing here for {trace}"));
+
+        let response = router
+            .oneshot(
+                Request::get("/v1/ok")
+                    .header("traceparent", "00-00000000000000000000000000000000-0-01")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let generated = response.headers()[CORRELATION_HEADER].to_str().unwrap();
+        assert_eq!(generated.len(), 32);
+        assert_ne!(generated, "00000000000000000000000000000000");
+    }
+}
@@ -0,0 +1,457 @@
+//! Offline documentation served from the workspace.
+//!
+//! A [`DocsLibrary`] groups directories into named collections — by default
+//! `docs/`, the pipeline reports, and the evidence bundles — each with a
+//! [`DocsAccess`] rule. Text files are indexed with tantivy in memory so
+//! `/v1/docs/search` can answer full-text queries without external doc hosting;
+//! bundles and other binary files are listed and served but not indexed. Callers
+//! only see collections their [`RequestIdentity`] may read.
+
+use crate::problem::{ErrorCode, Problem};
+use crate::RequestIdentity;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+use tantivy::collector::TopDocs;
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, Schema, Value, STORED, STRING, TEXT};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{doc, Index, IndexReader, ReloadPolicy, TantivyDocument, Term};
+
+/// Reports written by pipeline stages, relative to the workspace root.
+pub const PIPELINE_REPORTS_DIR: &str = "storage/db/pipelines/reports";
+/// Files larger than this are listed but not indexed.
+const MAX_INDEXED_BYTES: u64 = 2 * 1024 * 1024;
+const INDEXED_EXTENSIONS: [&str; 9] = [
+    "md", "txt", "json", "jsonl", "yaml", "yml", "toml", "html", "csv",
+];
+const WRITER_MEMORY_BYTES: usize = 15_000_000;
+
+/// Who may read a collection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "scope")]
+pub enum DocsAccess {
+    Public,
+    Authenticated,
+    /// Authenticated callers holding the scope.
+    Scope(String),
+}
+
+impl DocsAccess {
+    fn check(&self, identity: &RequestIdentity) -> Result<(), Problem> {
+        match self {
+            DocsAccess::Public => Ok(()),
+            _ if !identity.is_authenticated() => Err(Problem::new(
+                ErrorCode::Unauthenticated,
+                "authenticate to read this collection",
+            )),
+            DocsAccess::Authenticated => Ok(()),
+            DocsAccess::Scope(scope) if identity.has_scope(scope) => Ok(()),
+            DocsAccess::Scope(scope) => Err(Problem::new(
+                ErrorCode::Forbidden,
+                format!("reading this collection requires the {scope} scope"),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DocsCollection {
+    pub name: String,
+    #[serde(skip)]
+    pub root: PathBuf,
+    pub access: DocsAccess,
+}
+
+/// A file in a collection, as listed by the index route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DocEntry {
+    pub collection: String,
+    /// `/`-separated path inside the collection.
+    pub path: String,
+    pub title: String,
+    pub bytes: u64,
+    pub indexed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub collection: String,
+    pub path: String,
+    pub title: String,
+    pub score: f32,
+    /// Matching excerpt with terms wrapped in `<b>`.
+    pub snippet: String,
+}
+
+struct Fields {
+    collection: Field,
+    path: Field,
+    title: Field,
+    body: Field,
+}
+
+struct Snapshot {
+    index: Index,
+    reader: IndexReader,
+    entries: Vec<DocEntry>,
+}
+
+/// Collections served under `/v1/docs` and their search index.
+pub struct DocsLibrary {
+    collections: Vec<DocsCollection>,
+    fields: Fields,
+    schema: Schema,
+    snapshot: RwLock<Snapshot>,
+}
+
+impl DocsLibrary {
+    /// Index `collections`; directories that do not exist yet stay empty until
+    /// [`DocsLibrary::reindex`].
+    pub fn open(collections: Vec<DocsCollection>) -> Result<Self> {
+        let mut builder = Schema::builder();
+        let fields = Fields {
+            collection: builder.add_text_field("collection", STRING | STORED),
+            path: builder.add_text_field("path", STRING | STORED),
+            title: builder.add_text_field("title", TEXT | STORED),
+            body: builder.add_text_field("body", TEXT | STORED),
+        };
+        let schema = builder.build();
+        let snapshot = build_snapshot(&schema, &fields, &collections)?;
+        Ok(Self {
+            collections,
+            fields,
+            schema,
+            snapshot: RwLock::new(snapshot),
+        })
+    }
+
+    /// `docs/` for everyone, and pipeline reports and evidence bundles for
+    /// authenticated callers.
+    pub fn for_workspace(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref();
+        Self::open(vec![
+            DocsCollection {
+                name: "docs".into(),
+                root: root.join("docs"),
+                access: DocsAccess::Public,
+            },
+            DocsCollection {
+                name: "reports".into(),
+                root: root.join(PIPELINE_REPORTS_DIR),
+                access: DocsAccess::Authenticated,
+            },
+            DocsCollection {
+                name: "evidence".into(),
+                root: root.join(noa_cicd::evidence::EVIDENCE_BUNDLE_DIR),
+                access: DocsAccess::Authenticated,
+            },
+        ])
+    }
+
+    /// Rescan every collection and replace the index.
+    pub fn reindex(&self) -> Result<usize> {
+        let snapshot = build_snapshot(&self.schema, &self.fields, &self.collections)?;
+        let count = snapshot.entries.len();
+        *self.snapshot.write().expect("docs index lock poisoned") = snapshot;
+        Ok(count)
+    }
+
+    /// Collections `identity` may read.
+    pub fn collections(&self, identity: &RequestIdentity) -> Vec<&DocsCollection> {
+        self.collections
+            .iter()
+            .filter(|collection| collection.access.check(identity).is_ok())
+            .collect()
+    }
+
+    /// Files of the collections `identity` may read.
+    pub fn entries(&self, identity: &RequestIdentity) -> Vec<DocEntry> {
+        let readable = self.collections(identity);
+        self.snapshot
+            .read()
+            .expect("docs index lock poisoned")
+            .entries
+            .iter()
+            .filter(|entry| readable.iter().any(|c| c.name == entry.collection))
+            .cloned()
+            .collect()
+    }
+
+    /// Full-text search over the collections `identity` may read.
+    pub fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        identity: &RequestIdentity,
+    ) -> Result<Vec<SearchHit>, Problem> {
+        let readable = self.collections(identity);
+        if readable.is_empty() || query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let snapshot = self.snapshot.read().expect("docs index lock poisoned");
+        let parser =
+            QueryParser::for_index(&snapshot.index, vec![self.fields.title, self.fields.body]);
+        let parsed = parser
+            .parse_query(query)
+            .map_err(|err| Problem::new(ErrorCode::InvalidRequest, err.to_string()))?;
+        let allowed: Vec<(Occur, Box<dyn Query>)> = readable
+            .iter()
+            .map(|collection| {
+                let term = Term::from_field_text(self.fields.collection, &collection.name);
+                (
+                    Occur::Should,
+                    Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>,
+                )
+            })
+            .collect();
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, parsed),
+            (Occur::Must, Box::new(BooleanQuery::new(allowed))),
+        ]);
+
+        let internal =
+            |err: tantivy::TantivyError| Problem::new(ErrorCode::Internal, err.to_string());
+        let searcher = snapshot.reader.searcher();
+        let snippets =
+            SnippetGenerator::create(&searcher, &query, self.fields.body).map_err(internal)?;
+        let top = searcher
+            .search(&query, &TopDocs::with_limit(limit))
+            .map_err(internal)?;
+        let text = |doc: &TantivyDocument, field: Field| {
+            doc.get_first(field)
+                .and_then(|value| value.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        top.into_iter()
+            .map(|(score, address)| {
+                let doc: TantivyDocument = searcher.doc(address).map_err(internal)?;
+                Ok(SearchHit {
+                    collection: text(&doc, self.fields.collection),
+                    path: text(&doc, self.fields.path),
+                    title: text(&doc, self.fields.title),
+                    score,
+                    snippet: snippets.snippet_from_doc(&doc).to_html(),
+                })
+            })
+            .collect()
+    }
+
+    /// Location of `path` in `collection`, after checking `identity` may read it.
+    pub fn resolve(
+        &self,
+        collection: &str,
+        path: &str,
+        identity: &RequestIdentity,
+    ) -> Result<PathBuf, Problem> {
+        let not_found = || {
+            Problem::new(
+                ErrorCode::NotFound,
+                format!("no document {path} in {collection}"),
+            )
+        };
+        let collection = self
+            .collections
+            .iter()
+            .find(|candidate| candidate.name == collection)
+            .ok_or_else(not_found)?;
+        collection.access.check(identity)?;
+        let relative = Path::new(path);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(Problem::new(
+                ErrorCode::InvalidRequest,
+                "document paths must stay inside their collection",
+            ));
+        }
+        let resolved = collection.root.join(relative);
+        if resolved.is_file() {
+            Ok(resolved)
+        } else {
+            Err(not_found())
+        }
+    }
+}
+
+/// `Content-Type` to serve a document with.
+pub fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("md") => "text/markdown; charset=utf-8",
+        Some("html") => "text/html; charset=utf-8",
+        Some("json") => "application/json",
+        Some("jsonl") => "application/x-ndjson",
+        Some("yaml" | "yml") => "application/yaml",
+        Some("toml" | "txt" | "csv") => "text/plain; charset=utf-8",
+        Some("gz" | "tgz") => "application/gzip",
+        _ => "application/octet-stream",
+    }
+}
+
+fn build_snapshot(
+    schema: &Schema,
+    fields: &Fields,
+    collections: &[DocsCollection],
+) -> Result<Snapshot> {
+    let index = Index::create_in_ram(schema.clone());
+    let mut writer = index
+        .writer_with_num_threads(1, WRITER_MEMORY_BYTES)
+        .context("failed to open docs index writer")?;
+    let mut entries = Vec::new();
+    for collection in collections {
+        let mut files = Vec::new();
+        walk(&collection.root, &mut files);
+        files.sort();
+        for file in files {
+            let Ok(relative) = file.strip_prefix(&collection.root) else {
+                continue;
+            };
+            let relative = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let bytes = fs::metadata(&file).map(|meta| meta.len()).unwrap_or(0);
+            let body = indexable(&file, bytes).then(|| fs::read_to_string(&file).ok());
+            let body = body.flatten();
+            let title = body
+                .as_deref()
+                .and_then(markdown_title)
+                .unwrap_or_else(|| relative.clone());
+            if let Some(body) = &body {
+                writer
+                    .add_document(doc!(
+                        fields.collection => collection.name.as_str(),
+                        fields.path => relative.as_str(),
+                        fields.title => title.as_str(),
+                        fields.body => body.as_str(),
+                    ))
+                    .context("failed to index document")?;
+            }
+            entries.push(DocEntry {
+                collection: collection.name.clone(),
+                path: relative,
+                title,
+                bytes,
+                indexed: body.is_some(),
+            });
+        }
+    }
+    writer.commit().context("failed to commit docs index")?;
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()
+        .context("failed to open docs index reader")?;
+    Ok(Snapshot {
+        index,
+        reader,
+        entries,
+    })
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(read) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => walk(&path, files),
+            Ok(kind) if kind.is_file() => files.push(path),
+            _ => {}
+        }
+    }
+}
+
+fn indexable(path: &Path, bytes: u64) -> bool {
+    bytes <= MAX_INDEXED_BYTES
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| INDEXED_EXTENSIONS.contains(&ext))
+}
+
+fn markdown_title(body: &str) -> Option<String> {
+    body.lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AuthMethod;
+
+    fn operator() -> RequestIdentity {
+        RequestIdentity {
+            subject: "operator".into(),
+            method: AuthMethod::ApiKey,
+            scopes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn search_and_resolution_respect_collection_access() {
+        let workspace = tempfile::tempdir().expect("tempdir");
+        let docs = workspace.path().join("docs/deployments");
+        let reports = workspace.path().join(PIPELINE_REPORTS_DIR).join("p-1");
+        fs::create_dir_all(&docs).unwrap();
+        fs::create_dir_all(&reports).unwrap();
+        fs::write(
+            docs.join("single_host.md"),
+            "# Single host\n\nRun the stack with the single-host profile.\n",
+        )
+        .unwrap();
+        fs::write(
+            reports.join("footprint.md"),
+            "# Footprint\n\nThe gateway binary grew past the single-host budget.\n",
+        )
+        .unwrap();
+        let library = DocsLibrary::for_workspace(workspace.path()).unwrap();
+
+        let anonymous = RequestIdentity::anonymous();
+        let hits = library.search("single-host", 10, &anonymous).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].path, "deployments/single_host.md");
+        assert_eq!(hits[0].title, "Single host");
+        assert!(hits[0].snippet.contains("<b>"));
+        assert_eq!(
+            library
+                .search("single-host", 10, &operator())
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(library.entries(&anonymous).len(), 1);
+
+        let denied = library
+            .resolve("reports", "p-1/footprint.md", &anonymous)
+            .unwrap_err();
+        assert_eq!(denied.status_code(), ErrorCode::Unauthenticated.status());
+        assert!(library
+            .resolve("reports", "p-1/footprint.md", &operator())
+            .is_ok());
+        let escaped = library
+            .resolve(
+                "docs",
+                "../storage/db/pipelines/reports/p-1/footprint.md",
+                &anonymous,
+            )
+            .unwrap_err();
+        assert_eq!(escaped.status_code(), ErrorCode::InvalidRequest.status());
+
+        fs::write(
+            docs.join("upgrades.md"),
+            "# Upgrades\n\nBlue green slots.\n",
+        )
+        .unwrap();
+        assert!(library.search("slots", 10, &anonymous).unwrap().is_empty());
+        assert_eq!(library.reindex().unwrap(), 3);
+        assert_eq!(library.search("slots", 10, &anonymous).unwrap().len(), 1);
+    }
+}
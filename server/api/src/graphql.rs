@@ -0,0 +1,658 @@
+//! GraphQL view over pipelines, workflows, agents, and the evidence ledger.
+//!
+//! `POST /v1/graphql` resolves queries against the attached CI/CD system and
+//! workflow engine so the UI can fetch composed views in one round trip: a
+//! pipeline with its stages, scans, approvals, deployments, and their outcomes,
+//! or a workflow with its stage receipts. Ledger entries link back to the
+//! pipeline or workflow they describe. `GET /v1/graphql` returns the schema SDL.
+
+use crate::ApiState;
+use async_graphql::{
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Error, Json, Object, Result, Schema,
+    SimpleObject,
+};
+use noa_cicd::{CICDSystem, Deployment, Pipeline};
+use noa_core::recovery::RecoveryMode;
+use noa_workflow::{
+    AgentApproval, AgentApprovalRequirement, AgentStandingSummary, DeploymentOutcomeRecord,
+    EvidenceLedgerEntry, EvidenceLedgerKind, SecurityScanReport, StageType, Workflow,
+    WorkflowEngine,
+};
+use serde_json::Value;
+use std::sync::Arc;
+
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn build_schema(state: ApiState) -> ApiSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(state)
+        .finish()
+}
+
+fn attached_cicd(ctx: &Context<'_>) -> Result<Arc<CICDSystem>> {
+    ctx.data::<ApiState>()?
+        .cicd_system()
+        .ok_or_else(|| Error::new("cicd system not attached"))
+}
+
+fn attached_engine(ctx: &Context<'_>) -> Result<Arc<WorkflowEngine>> {
+    ctx.data::<ApiState>()?
+        .workflow_engine()
+        .ok_or_else(|| Error::new("workflow engine not attached"))
+}
+
+/// Ledger entries that parse; corrupt lines are skipped as in lenient recovery.
+fn ledger_entries(ctx: &Context<'_>) -> Result<Vec<EvidenceLedgerEntry>> {
+    let engine = attached_engine(ctx)?;
+    let recovered = engine
+        .instrumentation()
+        .evidence_ledger(RecoveryMode::Lenient)
+        .map_err(|err| Error::new(format!("failed to read evidence ledger: {err}")))?;
+    Ok(recovered.records)
+}
+
+/// The serde name of a unit enum variant, e.g. `stage_receipt`.
+fn kind_or_debug<T: serde::Serialize + std::fmt::Debug>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{value:?}"))
+}
+
+fn millis(timestamp: u128) -> u64 {
+    u64::try_from(timestamp).unwrap_or(u64::MAX)
+}
+
+fn workflow_node(engine: &WorkflowEngine, workflow_id: &str) -> Option<WorkflowNode> {
+    let workflow = engine.get_workflow(workflow_id)?;
+    let state = engine
+        .get_state(workflow_id)
+        .map(|state| format!("{state:?}"));
+    Some(WorkflowNode { workflow, state })
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Every pipeline, oldest trigger first.
+    async fn pipelines(&self, ctx: &Context<'_>) -> Result<Vec<PipelineNode>> {
+        let cicd = attached_cicd(ctx)?;
+        Ok(cicd
+            .list_pipelines()
+            .into_iter()
+            .map(PipelineNode)
+            .collect())
+    }
+
+    async fn pipeline(&self, ctx: &Context<'_>, id: String) -> Result<Option<PipelineNode>> {
+        Ok(attached_cicd(ctx)?.get_pipeline(&id).map(PipelineNode))
+    }
+
+    /// Every loaded workflow, sorted by name.
+    async fn workflows(&self, ctx: &Context<'_>) -> Result<Vec<WorkflowNode>> {
+        let engine = attached_engine(ctx)?;
+        Ok(engine
+            .workflow_ids()
+            .iter()
+            .filter_map(|workflow_id| workflow_node(&engine, workflow_id))
+            .collect())
+    }
+
+    async fn workflow(&self, ctx: &Context<'_>, id: String) -> Result<Option<WorkflowNode>> {
+        let engine = attached_engine(ctx)?;
+        Ok(workflow_node(&engine, &id))
+    }
+
+    /// Registered agents plus any agent with reward history, sorted by id.
+    async fn agents(&self, ctx: &Context<'_>) -> Result<Vec<AgentNode>> {
+        let engine = attached_engine(ctx)?;
+        let mut standings = engine.instrumentation().agent_standings();
+        let mut agents: Vec<AgentNode> = engine
+            .agent_registry()
+            .all()
+            .into_iter()
+            .map(|metadata| {
+                let standing = standings
+                    .iter()
+                    .position(|standing| standing.agent == metadata.agent_id)
+                    .map(|index| standings.remove(index));
+                AgentNode {
+                    agent_id: metadata.agent_id,
+                    name: Some(metadata.name),
+                    role: Some(metadata.role),
+                    capabilities: metadata.capabilities,
+                    standing: standing.map(StandingNode::from),
+                }
+            })
+            .collect();
+        agents.extend(standings.into_iter().map(|standing| AgentNode {
+            agent_id: standing.agent.clone(),
+            name: None,
+            role: None,
+            capabilities: Vec::new(),
+            standing: Some(StandingNode::from(standing)),
+        }));
+        agents.sort_by(|a, b| a.agent_id.cmp(&b.agent_id));
+        Ok(agents)
+    }
+
+    async fn agent(&self, ctx: &Context<'_>, id: String) -> Result<Option<AgentNode>> {
+        Ok(self
+            .agents(ctx)
+            .await?
+            .into_iter()
+            .find(|agent| agent.agent_id == id))
+    }
+
+    /// Evidence ledger entries in append order, optionally filtered by kind
+    /// (e.g. `stage_receipt`) and limited to the most recent `last`.
+    async fn ledger(
+        &self,
+        ctx: &Context<'_>,
+        kind: Option<String>,
+        last: Option<usize>,
+    ) -> Result<Vec<LedgerEntryNode>> {
+        let mut entries: Vec<LedgerEntryNode> = ledger_entries(ctx)?
+            .into_iter()
+            .filter(|entry| {
+                kind.as_deref()
+                    .is_none_or(|kind| kind_or_debug(&entry.kind) == kind)
+            })
+            .map(LedgerEntryNode)
+            .collect();
+        if let Some(last) = last {
+            entries.drain(..entries.len().saturating_sub(last));
+        }
+        Ok(entries)
+    }
+}
+
+pub struct PipelineNode(Pipeline);
+
+#[Object]
+impl PipelineNode {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn status(&self) -> String {
+        format!("{:?}", self.0.status)
+    }
+
+    async fn commit_sha(&self) -> &str {
+        &self.0.commit_sha
+    }
+
+    async fn triggered_at(&self) -> u64 {
+        self.0.triggered_at
+    }
+
+    async fn auto_approved(&self) -> bool {
+        self.0.auto_approved
+    }
+
+    async fn ai_confidence(&self) -> f32 {
+        self.0.ai_confidence
+    }
+
+    async fn crc_job_id(&self) -> Option<&str> {
+        self.0.crc_job_id.as_deref()
+    }
+
+    async fn diff_summary(&self) -> Option<&str> {
+        self.0.diff_summary.as_deref()
+    }
+
+    async fn stages(&self) -> Vec<PipelineStageNode> {
+        self.0
+            .stages
+            .iter()
+            .map(|stage| PipelineStageNode {
+                name: stage.name.clone(),
+                stage_type: format!("{:?}", stage.stage_type),
+                status: format!("{:?}", stage.status),
+                duration_ms: stage.duration_ms,
+            })
+            .collect()
+    }
+
+    async fn security_scans(&self) -> Vec<ScanNode> {
+        self.0
+            .security_scans
+            .iter()
+            .cloned()
+            .map(ScanNode)
+            .collect()
+    }
+
+    async fn approvals_required(&self) -> Vec<ApprovalRequirementNode> {
+        self.0
+            .approvals_required
+            .iter()
+            .map(ApprovalRequirementNode::from)
+            .collect()
+    }
+
+    async fn approvals_granted(&self) -> Vec<ApprovalNode> {
+        self.0
+            .approvals_granted
+            .iter()
+            .map(ApprovalNode::from)
+            .collect()
+    }
+
+    /// Deployments shipping this pipeline's build.
+    async fn deployments(&self, ctx: &Context<'_>) -> Result<Vec<DeploymentNode>> {
+        Ok(attached_cicd(ctx)?
+            .deployments_for_pipeline(&self.0.id)
+            .into_iter()
+            .map(DeploymentNode)
+            .collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct PipelineStageNode {
+    name: String,
+    stage_type: String,
+    status: String,
+    duration_ms: Option<u64>,
+}
+
+pub struct ScanNode(SecurityScanReport);
+
+#[Object]
+impl ScanNode {
+    async fn tool(&self) -> &str {
+        &self.0.tool
+    }
+
+    async fn subject(&self) -> &str {
+        &self.0.subject
+    }
+
+    async fn status(&self) -> String {
+        kind_or_debug(&self.0.status)
+    }
+
+    async fn issues(&self) -> &[String] {
+        &self.0.issues
+    }
+
+    async fn report_artifact(&self) -> Option<&str> {
+        self.0.report_artifact.as_deref()
+    }
+
+    async fn ledger_reference(&self) -> &str {
+        &self.0.ledger_reference
+    }
+
+    /// The evidence ledger entry recording this scan.
+    async fn ledger_entry(&self, ctx: &Context<'_>) -> Result<Option<LedgerEntryNode>> {
+        Ok(ledger_entries(ctx)?
+            .into_iter()
+            .find(|entry| {
+                entry.kind == EvidenceLedgerKind::SecurityScan
+                    && entry.reference == self.0.ledger_reference
+            })
+            .map(LedgerEntryNode))
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ApprovalRequirementNode {
+    role: String,
+    minimum_trust_score: f32,
+    required_evidence_tags: Vec<String>,
+}
+
+impl From<&AgentApprovalRequirement> for ApprovalRequirementNode {
+    fn from(requirement: &AgentApprovalRequirement) -> Self {
+        Self {
+            role: requirement.role.clone(),
+            minimum_trust_score: requirement.minimum_trust_score,
+            required_evidence_tags: requirement.required_evidence_tags.clone(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct ApprovalNode {
+    role: String,
+    agent_id: String,
+    trust_score: f32,
+    evidence_tags: Vec<String>,
+    evidence_references: Vec<String>,
+    recorded_at: u64,
+}
+
+impl From<&AgentApproval> for ApprovalNode {
+    fn from(approval: &AgentApproval) -> Self {
+        Self {
+            role: approval.role.clone(),
+            agent_id: approval.agent_id.clone(),
+            trust_score: approval.trust_score,
+            evidence_tags: approval.evidence_tags.clone(),
+            evidence_references: approval.evidence_references.clone(),
+            recorded_at: approval.recorded_at,
+        }
+    }
+}
+
+#[ComplexObject]
+impl ApprovalNode {
+    /// The approving agent, when it is registered or has reward history.
+    async fn agent(&self, ctx: &Context<'_>) -> Result<Option<AgentNode>> {
+        QueryRoot.agent(ctx, self.agent_id.clone()).await
+    }
+}
+
+pub struct DeploymentNode(Deployment);
+
+#[Object]
+impl DeploymentNode {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    async fn service(&self) -> &str {
+        &self.0.service
+    }
+
+    async fn environment(&self) -> String {
+        format!("{:?}", self.0.environment)
+    }
+
+    async fn strategy(&self) -> String {
+        format!("{:?}", self.0.strategy)
+    }
+
+    async fn version(&self) -> &str {
+        &self.0.version
+    }
+
+    async fn status(&self) -> String {
+        format!("{:?}", self.0.status)
+    }
+
+    async fn auto_approved(&self) -> bool {
+        self.0.auto_approved
+    }
+
+    async fn pipeline(&self, ctx: &Context<'_>) -> Result<Option<PipelineNode>> {
+        let Some(pipeline_id) = &self.0.pipeline_id else {
+            return Ok(None);
+        };
+        Ok(attached_cicd(ctx)?
+            .get_pipeline(pipeline_id)
+            .map(PipelineNode))
+    }
+
+    /// Promotion outcomes recorded for this deployment, oldest first.
+    async fn outcomes(&self, ctx: &Context<'_>) -> Result<Vec<OutcomeNode>> {
+        let outcomes = attached_cicd(ctx)?
+            .deployment_outcomes(&self.0.id)
+            .map_err(Error::new)?;
+        Ok(outcomes.into_iter().map(OutcomeNode::from).collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct OutcomeNode {
+    stage_id: String,
+    agent_role: String,
+    agent_id: String,
+    action: String,
+    status: String,
+    notes: Json<Value>,
+    recorded_at: String,
+}
+
+impl From<DeploymentOutcomeRecord> for OutcomeNode {
+    fn from(record: DeploymentOutcomeRecord) -> Self {
+        Self {
+            stage_id: record.stage_id,
+            agent_role: record.agent_role,
+            agent_id: record.agent_id,
+            action: record.action,
+            status: record.status,
+            notes: Json(record.notes),
+            recorded_at: record.recorded_at,
+        }
+    }
+}
+
+pub struct WorkflowNode {
+    workflow: Workflow,
+    state: Option<String>,
+}
+
+#[Object]
+impl WorkflowNode {
+    async fn id(&self) -> &str {
+        &self.workflow.name
+    }
+
+    async fn version(&self) -> &str {
+        &self.workflow.version
+    }
+
+    async fn state(&self) -> Option<&str> {
+        self.state.as_deref()
+    }
+
+    async fn stages(&self) -> Vec<WorkflowStageNode> {
+        self.workflow
+            .stages
+            .iter()
+            .map(|stage| WorkflowStageNode {
+                name: stage.name.clone(),
+                stage_type: match &stage.stage_type {
+                    StageType::Approval(_) => "approval".to_string(),
+                    other => kind_or_debug(other),
+                },
+                depends_on: stage.depends_on.clone(),
+                agents: stage.tasks.iter().map(|task| task.agent.clone()).collect(),
+            })
+            .collect()
+    }
+
+    /// Stage receipts the ledger holds for this workflow, in append order.
+    async fn receipts(&self, ctx: &Context<'_>) -> Result<Vec<LedgerEntryNode>> {
+        Ok(ledger_entries(ctx)?
+            .into_iter()
+            .filter(|entry| {
+                entry.kind == EvidenceLedgerKind::StageReceipt
+                    && entry.payload["workflow_id"] == self.workflow.name.as_str()
+            })
+            .map(LedgerEntryNode)
+            .collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct WorkflowStageNode {
+    name: String,
+    stage_type: String,
+    depends_on: Vec<String>,
+    /// Agents assigned to the stage's tasks, in task order.
+    agents: Vec<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct AgentNode {
+    agent_id: String,
+    name: Option<String>,
+    role: Option<String>,
+    capabilities: Vec<String>,
+    /// Reward standing; absent for agents without reward history.
+    standing: Option<StandingNode>,
+}
+
+#[derive(SimpleObject)]
+pub struct StandingNode {
+    total_reward: f64,
+    recent_average: f64,
+    penalties: u32,
+    requires_manual_approval: bool,
+}
+
+impl From<AgentStandingSummary> for StandingNode {
+    fn from(summary: AgentStandingSummary) -> Self {
+        Self {
+            total_reward: summary.total_reward,
+            recent_average: summary.recent_average,
+            penalties: summary.penalties,
+            requires_manual_approval: summary.requires_manual_approval,
+        }
+    }
+}
+
+pub struct LedgerEntryNode(EvidenceLedgerEntry);
+
+#[Object]
+impl LedgerEntryNode {
+    async fn kind(&self) -> String {
+        kind_or_debug(&self.0.kind)
+    }
+
+    async fn timestamp(&self) -> u64 {
+        millis(self.0.timestamp)
+    }
+
+    async fn reference(&self) -> &str {
+        &self.0.reference
+    }
+
+    async fn payload(&self) -> Json<Value> {
+        Json(self.0.payload.clone())
+    }
+
+    /// The workflow a stage receipt or task dispatch belongs to.
+    async fn workflow(&self, ctx: &Context<'_>) -> Result<Option<WorkflowNode>> {
+        let Some(workflow_id) = self.0.payload["workflow_id"].as_str() else {
+            return Ok(None);
+        };
+        let engine = attached_engine(ctx)?;
+        Ok(workflow_node(&engine, workflow_id))
+    }
+
+    /// The pipeline a security scan was run for.
+    async fn pipeline(&self, ctx: &Context<'_>) -> Result<Option<PipelineNode>> {
+        if self.0.kind != EvidenceLedgerKind::SecurityScan {
+            return Ok(None);
+        }
+        let Some(pipeline_id) = self.0.payload["subject"].as_str() else {
+            return Ok(None);
+        };
+        Ok(attached_cicd(ctx)?
+            .get_pipeline(pipeline_id)
+            .map(PipelineNode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noa_cicd::{DeploymentStrategy, Environment};
+    use noa_gateway::ProgrammableRouter;
+    use noa_workflow::{ConfigContext, Stage, StageCacheControl, StageContract};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn one_query_composes_pipelines_deployments_workflows_and_ledger() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let context = ConfigContext::isolated().with_workflow_root(dir.path());
+        let state = ApiState::for_tests(ProgrammableRouter::default());
+        let schema = build_schema(state.clone());
+
+        let detached = schema.execute("{ pipelines { id } }").await;
+        assert_eq!(detached.errors[0].message, "cicd system not attached");
+
+        let cicd = Arc::new(CICDSystem::with_context(context.clone()));
+        cicd.configure_workspace_root(dir.path());
+        let pipeline_id = cicd
+            .trigger_pipeline("release".into(), "abc123".into())
+            .expect("pipeline triggers");
+        let deployment_id = cicd
+            .deploy_pipeline_to_environment(
+                &pipeline_id,
+                "1.0.0".into(),
+                Environment::Staging,
+                DeploymentStrategy::Canary,
+            )
+            .expect("deployment starts");
+
+        let engine = Arc::new(WorkflowEngine::with_context(context));
+        let stage = Stage {
+            name: "build".into(),
+            stage_type: StageType::Sequential,
+            depends_on: vec![],
+            tasks: vec![],
+            compensation: vec![],
+            cache: StageCacheControl::default(),
+            contract: StageContract::default(),
+        };
+        engine
+            .load_workflow(Workflow {
+                name: "ship".into(),
+                version: "1.0".into(),
+                stages: vec![stage.clone()],
+            })
+            .expect("workflow loads");
+        let instrumentation = engine.instrumentation();
+        instrumentation
+            .log_stage_receipt("ship", &stage, &[json!({"status": "ok"})])
+            .expect("receipt logged");
+        instrumentation
+            .record_deployment_outcome(DeploymentOutcomeRecord {
+                workflow_id: deployment_id.clone(),
+                stage_id: "auto_promote".into(),
+                agent_role: "cicd".into(),
+                agent_id: "cicd".into(),
+                action: "promote:Production".into(),
+                status: "promoted".into(),
+                notes: json!({}),
+                recorded_at: "2025-01-01T00:00:00Z".into(),
+            })
+            .expect("outcome recorded");
+        state.set_cicd_system(cicd);
+        state.set_workflow_engine(engine);
+
+        let response = schema
+            .execute(format!(
+                r#"{{
+                    pipeline(id: "{pipeline_id}") {{
+                        name
+                        deployments {{ id environment outcomes {{ status }} pipeline {{ commitSha }} }}
+                    }}
+                    workflows {{ id stages {{ name stageType }} receipts {{ kind workflow {{ id }} }} }}
+                    ledger(kind: "stage_receipt", last: 1) {{ reference }}
+                }}"#
+            ))
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().expect("json data");
+
+        let pipeline = &data["pipeline"];
+        assert_eq!(pipeline["name"], "release");
+        let deployment = &pipeline["deployments"][0];
+        assert_eq!(deployment["id"], deployment_id.as_str());
+        assert_eq!(deployment["environment"], "Staging");
+        assert_eq!(deployment["outcomes"], json!([{"status": "promoted"}]));
+        assert_eq!(deployment["pipeline"]["commitSha"], "abc123");
+
+        let workflow = &data["workflows"][0];
+        assert_eq!(workflow["id"], "ship");
+        assert_eq!(workflow["stages"][0]["stageType"], "sequential");
+        assert_eq!(workflow["receipts"][0]["kind"], "stage_receipt");
+        assert_eq!(workflow["receipts"][0]["workflow"]["id"], "ship");
+        assert_eq!(data["ledger"].as_array().map(Vec::len), Some(1));
+    }
+}
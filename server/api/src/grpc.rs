@@ -5,7 +5,7 @@ use crate::proto::orchestration_service_server::{
 use crate::proto::retrieval_service_server::{RetrievalService, RetrievalServiceServer};
 use crate::proto::{
     InferenceRequest, InferenceResponse, OrchestrationRequest, OrchestrationResponse,
-    RetrievalRequest, RetrievalResponse, RoutedPlan,
+    RetrievalRequest, RetrievalResponse, RoutedPlan, FILE_DESCRIPTOR_SET,
 };
 use crate::ApiState;
 use axum::body::Body;
@@ -23,11 +23,36 @@ use tower::util::BoxCloneService;
 use tower::{Service, ServiceExt};
 use uuid::Uuid;
 
-pub fn build_grpc_service(
+/// Build the tonic service stack, including `grpc.health.v1` and server reflection.
+pub async fn build_grpc_service(
     state: ApiState,
-) -> BoxCloneService<Request<Body>, Response<Body>, Infallible> {
+) -> anyhow::Result<BoxCloneService<Request<Body>, Response<Body>, Infallible>> {
     let handler = GrpcHandler::new(state);
+
+    let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+    health_reporter
+        .set_serving::<InferenceServiceServer<GrpcHandler>>()
+        .await;
+    health_reporter
+        .set_serving::<RetrievalServiceServer<GrpcHandler>>()
+        .await;
+    health_reporter
+        .set_serving::<OrchestrationServiceServer<GrpcHandler>>()
+        .await;
+
+    // grpcurl and most load balancers still speak v1alpha, newer clients use v1.
+    let reflection = || {
+        tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+            .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+    };
+    let reflection_v1 = reflection().build_v1()?;
+    let reflection_v1alpha = reflection().build_v1alpha()?;
+
     let inner = tonic::transport::Server::builder()
+        .add_service(health_service)
+        .add_service(reflection_v1)
+        .add_service(reflection_v1alpha)
         .add_service(InferenceServiceServer::new(handler.clone()))
         .add_service(RetrievalServiceServer::new(handler.clone()))
         .add_service(OrchestrationServiceServer::new(handler))
@@ -48,7 +73,7 @@ pub fn build_grpc_service(
         }
     });
 
-    BoxCloneService::new(svc)
+    Ok(BoxCloneService::new(svc))
 }
 
 #[derive(Clone)]
@@ -233,4 +258,64 @@ mod tests {
         assert_eq!(plan.targets, vec![String::from("retrieval")]);
         assert!(plan.metadata_json.contains("federated"));
     }
+
+    #[tokio::test]
+    async fn health_and_reflection_services_answer_over_grpc() {
+        use tonic_health::pb::health_check_response::ServingStatus;
+        use tonic_health::pb::health_client::HealthClient;
+        use tonic_health::pb::HealthCheckRequest;
+        use tonic_reflection::pb::v1::server_reflection_client::ServerReflectionClient;
+        use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+        use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+        use tonic_reflection::pb::v1::ServerReflectionRequest;
+
+        let grpc = build_grpc_service(handler().state)
+            .await
+            .expect("grpc service builds");
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener");
+        let addr = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            axum::serve(listener, axum::Router::new().fallback_service(grpc)).await
+        });
+        let channel = tonic::transport::Endpoint::from_shared(format!("http://{addr}"))
+            .expect("endpoint")
+            .connect()
+            .await
+            .expect("channel connects");
+
+        let mut health = HealthClient::new(channel.clone());
+        let status = health
+            .check(HealthCheckRequest {
+                service: "noa.api.v1.InferenceService".into(),
+            })
+            .await
+            .expect("health check succeeds")
+            .into_inner()
+            .status;
+        assert_eq!(status, ServingStatus::Serving as i32);
+
+        let mut reflection = ServerReflectionClient::new(channel);
+        let request = ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(MessageRequest::ListServices(String::new())),
+        };
+        let mut responses = reflection
+            .server_reflection_info(futures::stream::iter(vec![request]))
+            .await
+            .expect("reflection stream opens")
+            .into_inner();
+        let response = responses
+            .message()
+            .await
+            .expect("reflection responds")
+            .expect("one response");
+        let Some(MessageResponse::ListServicesResponse(list)) = response.message_response else {
+            panic!("expected a service list");
+        };
+        let services: Vec<String> = list.service.into_iter().map(|svc| svc.name).collect();
+        assert!(services.contains(&"noa.api.v1.RetrievalService".to_string()));
+        assert!(services.contains(&"grpc.health.v1.Health".to_string()));
+    }
 }
@@ -0,0 +1,202 @@
+//! Liveness, readiness, and dependency health checks.
+//!
+//! `/healthz` only confirms the process is serving requests. `/readyz` additionally
+//! requires startup to have finished and every dependency to be usable, and
+//! `/health/deps` reports each dependency with its status and probe latency. Both
+//! return `503` when a dependency is down so load balancers and Caddy health probes can
+//! act on the status code alone.
+
+use noa_core::capabilities::CapabilityState;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+const PROBE_FILE: &str = ".api-health-probe";
+
+/// Where the dependency checks look.
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    /// Directory the server must be able to write to.
+    pub storage_root: PathBuf,
+    /// `host:port` of a standalone gateway; the in-process router is used when unset.
+    pub gateway_addr: Option<String>,
+    pub probe_timeout: Duration,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            storage_root: PathBuf::from("storage"),
+            gateway_addr: None,
+            probe_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyStatus {
+    Up,
+    /// Usable, but not in its preferred state.
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyCheck {
+    pub name: &'static str,
+    pub status: DependencyStatus,
+    pub latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl DependencyCheck {
+    fn timed(
+        name: &'static str,
+        started: Instant,
+        (status, detail): (DependencyStatus, Option<String>),
+    ) -> Self {
+        Self {
+            name,
+            status,
+            latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+            detail,
+        }
+    }
+}
+
+/// Worst status across `checks`; `Up` when there are none.
+pub fn overall_status(checks: &[DependencyCheck]) -> DependencyStatus {
+    checks
+        .iter()
+        .map(|check| check.status)
+        .max()
+        .unwrap_or(DependencyStatus::Up)
+}
+
+impl HealthConfig {
+    pub fn with_storage_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.storage_root = root.into();
+        self
+    }
+
+    pub fn with_gateway_addr(mut self, addr: impl Into<String>) -> Self {
+        self.gateway_addr = Some(addr.into());
+        self
+    }
+
+    pub async fn check_dependencies(&self) -> Vec<DependencyCheck> {
+        let started = Instant::now();
+        let kernel = DependencyCheck::timed("kernel", started, kernel_status());
+        let started = Instant::now();
+        let storage = DependencyCheck::timed("storage", started, self.storage_status());
+        let started = Instant::now();
+        let gateway = DependencyCheck::timed("gateway", started, self.gateway_status().await);
+        vec![kernel, storage, gateway]
+    }
+
+    fn storage_status(&self) -> (DependencyStatus, Option<String>) {
+        let probe = self.storage_root.join(PROBE_FILE);
+        let result = fs::create_dir_all(&self.storage_root)
+            .and_then(|_| fs::write(&probe, b"ok"))
+            .and_then(|_| fs::remove_file(&probe));
+        match result {
+            Ok(()) => (DependencyStatus::Up, None),
+            Err(err) => (
+                DependencyStatus::Down,
+                Some(format!(
+                    "{} is not writable: {err}",
+                    self.storage_root.display()
+                )),
+            ),
+        }
+    }
+
+    async fn gateway_status(&self) -> (DependencyStatus, Option<String>) {
+        let Some(addr) = &self.gateway_addr else {
+            return (DependencyStatus::Up, Some("in-process router".into()));
+        };
+        match tokio::time::timeout(self.probe_timeout, TcpStream::connect(addr)).await {
+            Ok(Ok(_)) => (DependencyStatus::Up, None),
+            Ok(Err(err)) => (
+                DependencyStatus::Down,
+                Some(format!("{addr} unreachable: {err}")),
+            ),
+            Err(_) => (
+                DependencyStatus::Down,
+                Some(format!(
+                    "{addr} did not answer within {:?}",
+                    self.probe_timeout
+                )),
+            ),
+        }
+    }
+}
+
+/// The API runs without a kernel in standalone mode, so a missing kernel only degrades;
+/// failed capabilities take it down.
+fn kernel_status() -> (DependencyStatus, Option<String>) {
+    let Some(kernel) = noa_core::kernel::handle() else {
+        return (
+            DependencyStatus::Degraded,
+            Some("kernel not running".into()),
+        );
+    };
+    let states = kernel.registry().states();
+    let failed: Vec<&str> = states
+        .iter()
+        .filter(|(_, state)| **state == CapabilityState::Failed)
+        .map(|(id, _)| id.as_str())
+        .collect();
+    if !failed.is_empty() {
+        return (
+            DependencyStatus::Down,
+            Some(format!("failed capabilities: {}", failed.join(", "))),
+        );
+    }
+    let ready = states
+        .values()
+        .filter(|state| **state == CapabilityState::Ready)
+        .count();
+    (
+        DependencyStatus::Up,
+        Some(format!("{ready}/{} capabilities ready", states.len())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn reports_storage_and_gateway_failures() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("listener");
+        let healthy = HealthConfig::default()
+            .with_storage_root(dir.path().join("storage"))
+            .with_gateway_addr(listener.local_addr().unwrap().to_string());
+        let checks = healthy.check_dependencies().await;
+        let names: Vec<_> = checks.iter().map(|check| check.name).collect();
+        assert_eq!(names, ["kernel", "storage", "gateway"]);
+        assert_eq!(checks[1].status, DependencyStatus::Up);
+        assert_eq!(checks[2].status, DependencyStatus::Up);
+        assert!(!dir.path().join("storage").join(PROBE_FILE).exists());
+
+        let blocked = dir.path().join("file");
+        fs::write(&blocked, "not a directory").unwrap();
+        let closed = TcpListener::bind("127.0.0.1:0").await.expect("listener");
+        let closed_addr = closed.local_addr().unwrap().to_string();
+        drop(closed);
+        let broken = HealthConfig::default()
+            .with_storage_root(&blocked)
+            .with_gateway_addr(closed_addr);
+        let checks = broken.check_dependencies().await;
+        assert_eq!(checks[1].status, DependencyStatus::Down);
+        assert_eq!(checks[2].status, DependencyStatus::Down);
+        assert_eq!(overall_status(&checks), DependencyStatus::Down);
+    }
+}
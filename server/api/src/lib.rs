@@ -1,35 +1,92 @@
+mod auth;
+mod correlation;
+mod docs;
+mod graphql;
 mod grpc;
+mod health;
+mod pagination;
+mod problem;
+mod rate_limit;
+mod reload;
 mod routes;
+mod timeout;
+mod tls;
 
 pub mod proto {
     tonic::include_proto!("noa.api.v1");
+
+    /// Encoded descriptors for the API services, served over gRPC reflection.
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("noa_api_descriptor");
 }
 
 use crate::grpc::build_grpc_service;
 use crate::routes::build_http_router;
+use crate::tls::ServerTls;
 use anyhow::{anyhow, Context, Result};
 use axum::body::Body;
 use hyper::{Request, Response};
+use metrics::counter;
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use noa_cicd::CICDSystem;
 use noa_gateway::{ProgrammableRouter, Protocol, RoutePlan};
+use noa_workflow::WorkflowEngine;
 use routes::ApiRoutes;
 use serde_json::Value;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::OnceLock;
-use std::time::Instant;
-use tokio::net::TcpListener;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
 use tokio::signal;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 use tower::util::BoxCloneService;
-use tracing::info;
+use tracing::{info, warn};
+
+pub use crate::auth::{
+    AuthConfig, AuthLayer, AuthMethod, AuthService, RequestIdentity, ADMIN_SCOPE, API_TOKEN_SCOPE,
+    PUBLIC_GRPC_SERVICES,
+};
+pub use crate::correlation::{
+    CorrelationId, CorrelationLayer, CorrelationService, CORRELATION_HEADER,
+};
+pub use crate::docs::{
+    content_type, DocEntry, DocsAccess, DocsCollection, DocsLibrary, SearchHit,
+    PIPELINE_REPORTS_DIR,
+};
+pub use crate::graphql::{build_schema, ApiSchema};
+pub use crate::health::{overall_status, DependencyCheck, DependencyStatus, HealthConfig};
+pub use crate::pagination::{ListQuery, Page, DEFAULT_LIMIT, MAX_LIMIT};
+pub use crate::problem::{
+    error_catalog, ErrorCatalogEntry, ErrorCode, Problem, ERROR_CATALOG_PATH, PROBLEM_CONTENT_TYPE,
+};
+pub use crate::rate_limit::{
+    ApiRateLimiter, ClassLimits, ClientClass, RateLimitConfig, RateLimitLayer, RateLimitService,
+    Rejection, DEFAULT_BUCKET_IDLE_TIMEOUT,
+};
+pub use crate::reload::{apply_config_file, ConfigWatcher};
+pub use crate::timeout::{RequestTimeoutLayer, RequestTimeoutService};
+pub use crate::tls::TlsConfig;
 
 /// Configuration controlling how the API server binds.
 #[derive(Debug, Clone)]
 pub struct ApiConfig {
     pub host: String,
     pub port: u16,
+    /// Deadline for each request to produce a response; zero disables it.
+    pub request_timeout: Duration,
+    /// Serve HTTPS and gRPC over TLS instead of plaintext.
+    pub tls: Option<TlsConfig>,
+    /// Per-key request limits enforced by the server itself.
+    pub rate_limits: RateLimitConfig,
+    /// API keys and token scope accepted when authenticating callers.
+    pub auth: AuthConfig,
+    /// Dependencies probed by the readiness and dependency health endpoints.
+    pub health: HealthConfig,
 }
 
 impl Default for ApiConfig {
@@ -37,6 +94,11 @@ impl Default for ApiConfig {
         Self {
             host: "127.0.0.1".into(),
             port: 8080,
+            request_timeout: Duration::from_secs(30),
+            tls: None,
+            rate_limits: RateLimitConfig::default(),
+            auth: AuthConfig::default(),
+            health: HealthConfig::default(),
         }
     }
 }
@@ -50,7 +112,12 @@ struct ApiStateInner {
     router: ProgrammableRouter,
     metrics: MetricsHandle,
     ready: AtomicBool,
+    draining: AtomicBool,
     started_at: Instant,
+    workflow_engine: RwLock<Option<Arc<WorkflowEngine>>>,
+    cicd: RwLock<Option<Arc<CICDSystem>>>,
+    docs: RwLock<Option<Arc<DocsLibrary>>>,
+    health: HealthConfig,
 }
 
 #[derive(Clone)]
@@ -97,13 +164,22 @@ impl MetricsHandle {
 }
 
 impl ApiState {
-    pub(crate) fn new(router: ProgrammableRouter, metrics: MetricsHandle) -> Self {
+    pub(crate) fn new(
+        router: ProgrammableRouter,
+        metrics: MetricsHandle,
+        health: HealthConfig,
+    ) -> Self {
         Self {
             inner: Arc::new(ApiStateInner {
                 router,
                 metrics,
                 ready: AtomicBool::new(false),
+                draining: AtomicBool::new(false),
                 started_at: Instant::now(),
+                workflow_engine: RwLock::new(None),
+                cicd: RwLock::new(None),
+                docs: RwLock::new(None),
+                health,
             }),
         }
     }
@@ -112,6 +188,10 @@ impl ApiState {
         &self.inner.metrics
     }
 
+    pub fn health(&self) -> &HealthConfig {
+        &self.inner.health
+    }
+
     pub fn mark_ready(&self) {
         self.inner.ready.store(true, Ordering::SeqCst);
     }
@@ -120,10 +200,61 @@ impl ApiState {
         self.inner.ready.load(Ordering::SeqCst)
     }
 
+    /// Report not-ready so load balancers stop sending traffic while listeners are
+    /// being swapped or shut down.
+    pub fn begin_drain(&self) {
+        self.inner.draining.store(true, Ordering::SeqCst);
+    }
+
+    pub fn end_drain(&self) {
+        self.inner.draining.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.inner.draining.load(Ordering::SeqCst)
+    }
+
     pub fn uptime_seconds(&self) -> u64 {
         self.inner.started_at.elapsed().as_secs()
     }
 
+    /// Attach the workflow engine backing the workflow inspection routes.
+    pub fn set_workflow_engine(&self, engine: Arc<WorkflowEngine>) {
+        if let Ok(mut slot) = self.inner.workflow_engine.write() {
+            slot.replace(engine);
+        }
+    }
+
+    pub fn workflow_engine(&self) -> Option<Arc<WorkflowEngine>> {
+        self.inner
+            .workflow_engine
+            .read()
+            .ok()
+            .and_then(|slot| slot.clone())
+    }
+
+    /// Attach the CI/CD system backing the pipeline evidence routes.
+    pub fn set_cicd_system(&self, cicd: Arc<CICDSystem>) {
+        if let Ok(mut slot) = self.inner.cicd.write() {
+            slot.replace(cicd);
+        }
+    }
+
+    pub fn cicd_system(&self) -> Option<Arc<CICDSystem>> {
+        self.inner.cicd.read().ok().and_then(|slot| slot.clone())
+    }
+
+    /// Attach the documentation library backing the `/v1/docs` routes.
+    pub fn set_docs_library(&self, docs: Arc<DocsLibrary>) {
+        if let Ok(mut slot) = self.inner.docs.write() {
+            slot.replace(docs);
+        }
+    }
+
+    pub fn docs_library(&self) -> Option<Arc<DocsLibrary>> {
+        self.inner.docs.read().ok().and_then(|slot| slot.clone())
+    }
+
     pub fn route(&self, protocol: Protocol, payload: Value) -> Result<RoutePlan> {
         self.inner
             .router
@@ -134,13 +265,18 @@ impl ApiState {
     #[cfg(test)]
     pub(crate) fn for_tests(router: ProgrammableRouter) -> Self {
         let metrics = MetricsHandle::install().expect("metrics recorder installed for tests");
-        Self::new(router, metrics)
+        let health =
+            HealthConfig::default().with_storage_root(std::env::temp_dir().join("noa-api-tests"));
+        Self::new(router, metrics, health)
     }
 }
 
+const CERTIFICATE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
 /// Axum + Tonic server wrapper.
 pub struct ApiServer {
     config: ApiConfig,
+    config_file: Option<PathBuf>,
     state: ApiState,
 }
 
@@ -148,37 +284,200 @@ impl ApiServer {
     pub fn new(config: ApiConfig) -> Result<Self> {
         let router = ProgrammableRouter::default();
         let metrics = MetricsHandle::install().context("failed to install metrics exporter")?;
+        let state = ApiState::new(router, metrics, config.health.clone());
         Ok(Self {
             config,
-            state: ApiState::new(router, metrics),
+            config_file: None,
+            state,
         })
     }
 
+    /// State shared with the HTTP and gRPC routes.
+    pub fn state(&self) -> &ApiState {
+        &self.state
+    }
+
+    /// Serve workflow inspection routes from the provided engine.
+    pub fn with_workflow_engine(self, engine: Arc<WorkflowEngine>) -> Self {
+        self.state.set_workflow_engine(engine);
+        self
+    }
+
+    /// Serve pipeline evidence bundles from the provided CI/CD system.
+    pub fn with_cicd_system(self, cicd: Arc<CICDSystem>) -> Self {
+        self.state.set_cicd_system(cicd);
+        self
+    }
+
+    /// Serve documentation, reports, and evidence bundles from the provided library.
+    pub fn with_docs_library(self, docs: Arc<DocsLibrary>) -> Self {
+        self.state.set_docs_library(docs);
+        self
+    }
+
+    /// Layer `path` over the configuration and apply its changes while running.
+    pub fn with_config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
     pub async fn run(self) -> Result<()> {
-        let addr: SocketAddr = format!("{}:{}", self.config.host, self.config.port)
-            .parse()
-            .context("invalid bind address")?;
-        let listener = TcpListener::bind(addr)
-            .await
-            .with_context(|| format!("failed to bind {addr}"))?;
+        let mut updates = match &self.config_file {
+            Some(path) => Some(ConfigWatcher::new(path, self.config.clone()).spawn()?),
+            None => None,
+        };
+        let mut current = match updates.as_mut() {
+            Some(updates) => updates.borrow_and_update().clone(),
+            None => self.config.clone(),
+        };
 
         let http_router = build_http_router(ApiRoutes::new(self.state.clone()));
         let grpc_service: BoxCloneService<Request<Body>, Response<Body>, std::convert::Infallible> =
-            build_grpc_service(self.state.clone());
+            build_grpc_service(self.state.clone()).await?;
+
+        let timeout = RequestTimeoutLayer::new(current.request_timeout);
+        let rate_limit = RateLimitLayer::new(current.rate_limits.clone());
+        let app = http_router
+            .fallback_service(grpc_service)
+            .layer(timeout.clone())
+            // The limiter classifies callers by the identity authentication resolved.
+            .layer(rate_limit.clone())
+            .layer(AuthLayer::new(current.auth.clone()))
+            .layer(CorrelationLayer);
+
+        let tls = match &current.tls {
+            Some(source) => Some(ServerTls::load(source.clone()).await?),
+            None => None,
+        };
+        let certificate_watch = tls.as_ref().map(|tls| tls.watch(CERTIFICATE_POLL_INTERVAL));
 
-        let app = http_router.fallback_service(grpc_service);
+        let mut server = Listener::serve(&current, tls.as_ref(), app.clone())?;
         self.state.mark_ready();
-        info!(?addr, "API server listening");
 
-        axum::serve(listener, app)
-            .with_graceful_shutdown(shutdown_signal())
-            .await
-            .context("api server terminated with error")
+        let shutdown = shutdown_signal();
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                result = &mut server.task => {
+                    return result
+                        .context("api server task failed")?
+                        .context("api server terminated with error");
+                }
+                mut next = next_config(&mut updates) => {
+                    rate_limit.limiter().reconfigure(next.rate_limits.clone());
+                    timeout.set_timeout(next.request_timeout);
+                    if next.tls != current.tls {
+                        let applied = match (&tls, &next.tls) {
+                            (Some(tls), Some(source)) => tls
+                                .replace(source.clone())
+                                .await
+                                .map_err(|err| warn!(error = %format!("{err:#}"), "keeping current TLS certificate"))
+                                .is_ok(),
+                            _ => {
+                                warn!("enabling or disabling TLS requires a restart");
+                                false
+                            }
+                        };
+                        if !applied {
+                            next.tls = current.tls.clone();
+                        }
+                    }
+                    if (&next.host, next.port) != (&current.host, current.port) {
+                        match Listener::serve(&next, tls.as_ref(), app.clone()) {
+                            Ok(replacement) => {
+                                let previous = std::mem::replace(&mut server, replacement);
+                                let state = self.state.clone();
+                                state.begin_drain();
+                                tokio::spawn(async move {
+                                    previous.drain().await;
+                                    state.end_drain();
+                                });
+                            }
+                            Err(err) => {
+                                warn!(error = %format!("{err:#}"), "keeping current listener");
+                                next.host = current.host.clone();
+                                next.port = current.port;
+                            }
+                        }
+                    }
+                    counter!("api_config_reloads_total", 1, "outcome" => "applied");
+                    current = next;
+                }
+            }
+        }
+
+        self.state.begin_drain();
+        server.drain().await;
+        if let Some(task) = certificate_watch {
+            task.abort();
+        }
+        Ok(())
+    }
+}
+
+/// One bound listener serving the app until told to stop.
+struct Listener {
+    addr: SocketAddr,
+    handle: axum_server::Handle,
+    task: JoinHandle<std::io::Result<()>>,
+}
+
+impl Listener {
+    /// Bind synchronously so a taken port is reported before the old listener drains.
+    fn serve(config: &ApiConfig, tls: Option<&ServerTls>, app: axum::Router) -> Result<Self> {
+        let addr: SocketAddr = format!("{}:{}", config.host, config.port)
+            .parse()
+            .context("invalid bind address")?;
+        let listener =
+            std::net::TcpListener::bind(addr).with_context(|| format!("failed to bind {addr}"))?;
+        listener
+            .set_nonblocking(true)
+            .context("failed to configure listener")?;
+        let addr = listener.local_addr().unwrap_or(addr);
+        let handle = axum_server::Handle::new();
+        // Anonymous callers are rate limited by their peer address.
+        let service = app.into_make_service_with_connect_info::<SocketAddr>();
+        let task = match tls {
+            Some(tls) => tokio::spawn(
+                axum_server::from_tcp_rustls(listener, tls.rustls())
+                    .handle(handle.clone())
+                    .serve(service),
+            ),
+            None => tokio::spawn(
+                axum_server::from_tcp(listener)
+                    .handle(handle.clone())
+                    .serve(service),
+            ),
+        };
+        info!(?addr, tls = tls.is_some(), "API server listening");
+        Ok(Self { addr, handle, task })
+    }
+
+    /// Stop accepting connections and wait for open ones to finish.
+    async fn drain(self) {
+        self.handle.graceful_shutdown(None);
+        match self.task.await {
+            Ok(Ok(())) => info!(addr = ?self.addr, "API listener drained"),
+            Ok(Err(err)) => warn!(addr = ?self.addr, ?err, "API listener failed while draining"),
+            Err(err) => warn!(addr = ?self.addr, ?err, "API listener task failed"),
+        }
+    }
+}
+
+/// The next published configuration; never resolves without a watcher.
+async fn next_config(updates: &mut Option<watch::Receiver<ApiConfig>>) -> ApiConfig {
+    if let Some(receiver) = updates {
+        if receiver.changed().await.is_ok() {
+            return receiver.borrow_and_update().clone();
+        }
     }
+    *updates = None;
+    std::future::pending().await
 }
 
 async fn shutdown_signal() {
     if let Err(err) = signal::ctrl_c().await {
-        tracing::warn!(?err, "ctrl-c listener failed");
+        warn!(?err, "ctrl-c listener failed");
     }
 }
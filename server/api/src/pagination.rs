@@ -0,0 +1,235 @@
+//! Shared query conventions for list endpoints.
+//!
+//! Every list route accepts the same query parameters:
+//!
+//! * `limit` – page size, defaulting to [`DEFAULT_LIMIT`] and capped at [`MAX_LIMIT`].
+//! * `cursor` – the opaque `next_cursor` returned by the previous page.
+//! * `sort` – a field name, prefixed with `-` for descending order. Ties, and lists
+//!   without an explicit sort, are ordered by the item's stable id.
+//! * any other parameter filters on the top-level field of the same name.
+//!
+//! Cursors encode the sort value and stable id of the last item returned, so pages stay
+//! consistent while items are added or removed between requests.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+pub const DEFAULT_LIMIT: usize = 50;
+pub const MAX_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(try_from = "BTreeMap<String, String>")]
+pub struct ListQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub filters: BTreeMap<String, String>,
+}
+
+impl TryFrom<BTreeMap<String, String>> for ListQuery {
+    type Error = String;
+
+    fn try_from(mut params: BTreeMap<String, String>) -> Result<Self, Self::Error> {
+        let limit = params
+            .remove("limit")
+            .map(|raw| {
+                raw.parse::<usize>()
+                    .map_err(|_| format!("invalid limit: {raw}"))
+            })
+            .transpose()?;
+        Ok(Self {
+            limit,
+            cursor: params.remove("cursor"),
+            sort: params.remove("sort"),
+            filters: params,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Pass back as `cursor` to fetch the next page; absent on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// Items matching the filters across all pages.
+    pub total: usize,
+}
+
+impl ListQuery {
+    /// Filter, sort, and slice `items`, using `stable_id` as the tiebreaker and cursor key.
+    pub fn apply<T: Serialize>(
+        &self,
+        items: Vec<T>,
+        stable_id: impl Fn(&T) -> String,
+    ) -> Result<Page<T>, String> {
+        let limit = self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let (field, descending) = match self.sort.as_deref() {
+            Some(sort) => match sort.strip_prefix('-') {
+                Some(field) => (Some(field), true),
+                None => (Some(sort), false),
+            },
+            None => (None, false),
+        };
+        let after = self.cursor.as_deref().map(decode_cursor).transpose()?;
+
+        let mut rows = Vec::with_capacity(items.len());
+        for item in items {
+            let value = serde_json::to_value(&item).map_err(|err| err.to_string())?;
+            if !self
+                .filters
+                .iter()
+                .all(|(name, expected)| field_text(&value, name).as_deref() == Some(expected))
+            {
+                continue;
+            }
+            let key = CursorKey {
+                sort: field
+                    .and_then(|field| value.get(field))
+                    .cloned()
+                    .unwrap_or(Value::Null),
+                id: stable_id(&item),
+            };
+            rows.push((key, item));
+        }
+        let total = rows.len();
+        let order = |a: &CursorKey, b: &CursorKey| {
+            let ordering = a.compare(b);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        };
+        rows.sort_by(|a, b| order(&a.0, &b.0));
+
+        let start = match &after {
+            Some(after) => rows.partition_point(|(key, _)| order(key, after) != Ordering::Greater),
+            None => 0,
+        };
+        let mut page: Vec<(CursorKey, T)> = rows.into_iter().skip(start).take(limit + 1).collect();
+        let next_cursor = if page.len() > limit {
+            page.truncate(limit);
+            page.last().map(|(key, _)| encode_cursor(key))
+        } else {
+            None
+        };
+        Ok(Page {
+            items: page.into_iter().map(|(_, item)| item).collect(),
+            next_cursor,
+            total,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CursorKey {
+    sort: Value,
+    id: String,
+}
+
+impl CursorKey {
+    fn compare(&self, other: &Self) -> Ordering {
+        compare_values(&self.sort, &other.sort).then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .unwrap_or_default()
+            .total_cmp(&b.as_f64().unwrap_or_default()),
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Less,
+        (_, Value::Null) => Ordering::Greater,
+        _ => value_text(a).cmp(&value_text(b)),
+    }
+}
+
+fn field_text(value: &Value, field: &str) -> Option<String> {
+    value.get(field).map(value_text)
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn encode_cursor(key: &CursorKey) -> String {
+    serde_json::to_vec(key)
+        .unwrap_or_default()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn decode_cursor(cursor: &str) -> Result<CursorKey, String> {
+    let invalid = || format!("invalid cursor: {cursor}");
+    if !cursor.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|index| {
+            cursor
+                .get(index..index + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(invalid)?;
+    serde_json::from_slice(&bytes).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn query(raw: &str) -> ListQuery {
+        let uri = format!("/v1/items?{raw}").parse().expect("uri");
+        axum::extract::Query::<ListQuery>::try_from_uri(&uri)
+            .expect("query parses")
+            .0
+    }
+
+    #[test]
+    fn filters_sorts_and_walks_pages_with_cursors() {
+        let items: Vec<Value> = (0..7)
+            .map(|index| {
+                json!({
+                    "id": format!("wf-{index}"),
+                    "state": if index % 2 == 0 { "Running" } else { "Completed" },
+                    "stages": 10 - index,
+                })
+            })
+            .collect();
+        let id = |item: &Value| item["id"].as_str().unwrap_or_default().to_string();
+        let ids = |page: &Page<Value>| page.items.iter().map(id).collect::<Vec<_>>();
+
+        let first = query("limit=2&sort=-stages&state=Running")
+            .apply(items.clone(), id)
+            .unwrap();
+        assert_eq!(first.total, 4);
+        assert_eq!(ids(&first), ["wf-0", "wf-2"]);
+
+        let cursor = first.next_cursor.clone().expect("more pages");
+        let second = query(&format!(
+            "limit=2&sort=-stages&state=Running&cursor={cursor}"
+        ))
+        .apply(items.clone(), id)
+        .unwrap();
+        assert_eq!(ids(&second), ["wf-4", "wf-6"]);
+        assert!(second.next_cursor.is_none());
+
+        let default = ListQuery::default().apply(items.clone(), id).unwrap();
+        assert_eq!(default.items.len(), 7);
+        assert!(query("cursor=zz").apply(items, id).is_err());
+        let uri = "/v1/items?limit=many".parse().unwrap();
+        assert!(axum::extract::Query::<ListQuery>::try_from_uri(&uri).is_err());
+    }
+}
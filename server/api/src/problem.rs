@@ -0,0 +1,286 @@
+//! RFC 7807 `application/problem+json` error responses.
+//!
+//! Every HTTP error carries a stable [`ErrorCode`] alongside the standard `type`,
+//! `title`, `status`, `detail`, and `instance` members. The `type` URI points into the
+//! catalog served at [`ERROR_CATALOG_PATH`], and [`CorrelationLayer`] fills in
+//! `correlation_id` so a failing request can be found in the traces.
+//!
+//! [`CorrelationLayer`]: crate::CorrelationLayer
+
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+/// Path of the endpoint listing every [`ErrorCode`].
+pub const ERROR_CATALOG_PATH: &str = "/v1/errors";
+
+/// Machine-readable error codes returned in the `code` member of a problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidRequest,
+    RoutingFailed,
+    Unauthenticated,
+    Forbidden,
+    NotFound,
+    Conflict,
+    RateLimited,
+    ConcurrencyLimited,
+    DependencyUnavailable,
+    Timeout,
+    Internal,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 11] = [
+        ErrorCode::InvalidRequest,
+        ErrorCode::RoutingFailed,
+        ErrorCode::Unauthenticated,
+        ErrorCode::Forbidden,
+        ErrorCode::NotFound,
+        ErrorCode::Conflict,
+        ErrorCode::RateLimited,
+        ErrorCode::ConcurrencyLimited,
+        ErrorCode::DependencyUnavailable,
+        ErrorCode::Timeout,
+        ErrorCode::Internal,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidRequest => "invalid_request",
+            ErrorCode::RoutingFailed => "routing_failed",
+            ErrorCode::Unauthenticated => "unauthenticated",
+            ErrorCode::Forbidden => "forbidden",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::Conflict => "conflict",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::ConcurrencyLimited => "concurrency_limited",
+            ErrorCode::DependencyUnavailable => "dependency_unavailable",
+            ErrorCode::Timeout => "timeout",
+            ErrorCode::Internal => "internal",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidRequest | ErrorCode::RoutingFailed => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthenticated => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::Conflict => StatusCode::CONFLICT,
+            ErrorCode::RateLimited | ErrorCode::ConcurrencyLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::DependencyUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidRequest => "Invalid request",
+            ErrorCode::RoutingFailed => "Request could not be routed",
+            ErrorCode::Unauthenticated => "Authentication required",
+            ErrorCode::Forbidden => "Not permitted",
+            ErrorCode::NotFound => "Resource not found",
+            ErrorCode::Conflict => "Conflicting state",
+            ErrorCode::RateLimited => "Rate limit exceeded",
+            ErrorCode::ConcurrencyLimited => "Too many concurrent requests",
+            ErrorCode::DependencyUnavailable => "Dependency unavailable",
+            ErrorCode::Timeout => "Request timed out",
+            ErrorCode::Internal => "Internal error",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidRequest => {
+                "The body, path, or query parameters could not be parsed or failed validation."
+            }
+            ErrorCode::RoutingFailed => {
+                "The gateway router rejected the payload, usually because a required field is missing."
+            }
+            ErrorCode::Unauthenticated => {
+                "Credentials were missing or invalid. Send an API key or capability token."
+            }
+            ErrorCode::Forbidden => {
+                "The caller is authenticated but may not perform this action."
+            }
+            ErrorCode::NotFound => "The workflow, approval token, or other resource does not exist.",
+            ErrorCode::Conflict => {
+                "The resource is not in a state that allows the request, such as an approval already registered."
+            }
+            ErrorCode::RateLimited => {
+                "The key exhausted its request budget. Retry after the `Retry-After` delay."
+            }
+            ErrorCode::ConcurrencyLimited => {
+                "Too many requests of the caller's class are in flight. Retry shortly."
+            }
+            ErrorCode::DependencyUnavailable => {
+                "A component the route needs, such as the workflow engine, is not available."
+            }
+            ErrorCode::Timeout => "The handler did not respond within the request timeout.",
+            ErrorCode::Internal => "An unexpected failure. Report the correlation id.",
+        }
+    }
+
+    /// The problem `type` URI, relative to the API root.
+    pub fn type_uri(&self) -> String {
+        format!("{ERROR_CATALOG_PATH}#{}", self.as_str())
+    }
+}
+
+/// One documented error code, as served by the catalog endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorCatalogEntry {
+    pub code: ErrorCode,
+    #[serde(rename = "type")]
+    pub type_uri: String,
+    pub status: u16,
+    pub title: &'static str,
+    pub description: &'static str,
+}
+
+pub fn error_catalog() -> Vec<ErrorCatalogEntry> {
+    ErrorCode::ALL
+        .iter()
+        .map(|code| ErrorCatalogEntry {
+            code: *code,
+            type_uri: code.type_uri(),
+            status: code.status().as_u16(),
+            title: code.title(),
+            description: code.description(),
+        })
+        .collect()
+}
+
+/// An RFC 7807 problem details object. `type`, `title`, and `status` follow from `code`.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub code: ErrorCode,
+    pub detail: String,
+    pub instance: Option<String>,
+    pub correlation_id: Option<String>,
+    /// Code-specific members, such as `retry_after_seconds`.
+    pub extensions: Map<String, Value>,
+}
+
+#[derive(Serialize)]
+struct ProblemBody<'a> {
+    #[serde(rename = "type")]
+    type_uri: String,
+    title: &'static str,
+    status: u16,
+    detail: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<&'a str>,
+    code: ErrorCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    correlation_id: Option<&'a str>,
+    #[serde(flatten)]
+    extensions: &'a Map<String, Value>,
+}
+
+impl Serialize for Problem {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ProblemBody {
+            type_uri: self.code.type_uri(),
+            title: self.code.title(),
+            status: self.code.status().as_u16(),
+            detail: &self.detail,
+            instance: self.instance.as_deref(),
+            code: self.code,
+            correlation_id: self.correlation_id.as_deref(),
+            extensions: &self.extensions,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Problem {
+    pub fn new(code: ErrorCode, detail: impl Into<String>) -> Self {
+        Self {
+            code,
+            detail: detail.into(),
+            instance: None,
+            correlation_id: None,
+            extensions: Map::new(),
+        }
+    }
+
+    pub fn with_extension(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.extensions.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        self.code.status()
+    }
+}
+
+/// The problem is also stored in the response extensions so [`CorrelationLayer`] can
+/// complete it once the request's correlation id and path are known.
+///
+/// [`CorrelationLayer`]: crate::CorrelationLayer
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let body = serde_json::to_vec(&self).unwrap_or_default();
+        let mut response = (self.status_code(), body).into_response();
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(PROBLEM_CONTENT_TYPE),
+        );
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+impl From<JsonRejection> for Problem {
+    fn from(rejection: JsonRejection) -> Self {
+        Problem::new(ErrorCode::InvalidRequest, rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for Problem {
+    fn from(rejection: QueryRejection) -> Self {
+        Problem::new(ErrorCode::InvalidRequest, rejection.body_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    #[tokio::test]
+    async fn problems_serialize_as_rfc7807_with_catalogued_codes() {
+        let response = Problem::new(ErrorCode::RateLimited, "slow down")
+            .with_extension("retry_after_seconds", 3)
+            .into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            PROBLEM_CONTENT_TYPE
+        );
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["type"], "/v1/errors#rate_limited");
+        assert_eq!(body["code"], "rate_limited");
+        assert_eq!(body["status"], 429);
+        assert_eq!(body["detail"], "slow down");
+        assert_eq!(body["retry_after_seconds"], 3);
+        assert!(body.get("correlation_id").is_none());
+
+        let catalog = error_catalog();
+        assert_eq!(catalog.len(), ErrorCode::ALL.len());
+        for entry in catalog {
+            assert_eq!(
+                serde_json::to_value(entry.code).unwrap(),
+                entry.code.as_str()
+            );
+        }
+    }
+}
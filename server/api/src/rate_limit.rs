@@ -0,0 +1,650 @@
+//! Per-key rate and concurrency limits for the standalone API server.
+//!
+//! Callers are classified from the [`RequestIdentity`] resolved by [`AuthLayer`], so the
+//! layer must run after authentication. Callers with [`ADMIN_SCOPE`] are admins, other
+//! authenticated callers are agents, and the rest are anonymous. Authenticated callers
+//! get a token bucket per subject; anonymous callers get one per client address, which is
+//! the peer socket address unless the peer is a configured proxy, in which case it is read
+//! from `X-Forwarded-For`. Each class has a cap on in-flight requests. Rejected requests
+//! receive `429 Too Many Requests` with a `Retry-After` header. Buckets left idle are
+//! dropped. These limits apply to the API process itself and are independent of the
+//! gateway's rate limiter.
+//!
+//! [`AuthLayer`]: crate::auth::AuthLayer
+
+use crate::auth::{RequestIdentity, ADMIN_SCOPE};
+use crate::problem::{ErrorCode, Problem};
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap, HeaderValue, Request, Response};
+use axum::response::IntoResponse;
+use futures::future::BoxFuture;
+use metrics::{counter, decrement_gauge, increment_gauge};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, RwLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tower::{Layer, Service};
+
+/// Paths probed by orchestrators and scrapers; never limited.
+const EXEMPT_PATHS: [&str; 6] = [
+    "/health",
+    "/ready",
+    "/metrics",
+    "/healthz",
+    "/readyz",
+    "/health/deps",
+];
+
+/// How long a bucket may go unused before it is dropped. Longer than any default class
+/// takes to refill, so a dropped bucket would have been full anyway.
+pub const DEFAULT_BUCKET_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientClass {
+    Admin,
+    Agent,
+    Anonymous,
+}
+
+impl ClientClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClientClass::Admin => "admin",
+            ClientClass::Agent => "agent",
+            ClientClass::Anonymous => "anonymous",
+        }
+    }
+}
+
+/// Limits applied to one client class.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ClassLimits {
+    /// Sustained requests per second for each caller.
+    pub requests_per_second: f64,
+    /// Requests a caller may make at once before the sustained rate applies.
+    pub burst: u32,
+    /// Requests of this class that may be in flight at the same time.
+    pub max_concurrent: usize,
+}
+
+impl ClassLimits {
+    pub fn new(requests_per_second: f64, burst: u32, max_concurrent: usize) -> Self {
+        Self {
+            requests_per_second,
+            burst,
+            max_concurrent,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub admin: ClassLimits,
+    pub agent: ClassLimits,
+    pub anonymous: ClassLimits,
+    /// Buckets unused for this long are dropped.
+    pub bucket_idle_timeout: Duration,
+    /// Proxies trusted to name the client in `X-Forwarded-For`.
+    trusted_proxies: HashSet<IpAddr>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            admin: ClassLimits::new(50.0, 100, 32),
+            agent: ClassLimits::new(20.0, 40, 16),
+            anonymous: ClassLimits::new(5.0, 10, 4),
+            bucket_idle_timeout: DEFAULT_BUCKET_IDLE_TIMEOUT,
+            trusted_proxies: HashSet::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn with_trusted_proxy(mut self, proxy: IpAddr) -> Self {
+        self.trusted_proxies.insert(proxy);
+        self
+    }
+
+    pub fn with_bucket_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.bucket_idle_timeout = timeout;
+        self
+    }
+
+    pub fn with_limits(mut self, class: ClientClass, limits: ClassLimits) -> Self {
+        match class {
+            ClientClass::Admin => self.admin = limits,
+            ClientClass::Agent => self.agent = limits,
+            ClientClass::Anonymous => self.anonymous = limits,
+        }
+        self
+    }
+
+    pub fn limits(&self, class: ClientClass) -> ClassLimits {
+        match class {
+            ClientClass::Admin => self.admin,
+            ClientClass::Agent => self.agent,
+            ClientClass::Anonymous => self.anonymous,
+        }
+    }
+
+    pub fn classify(&self, identity: &RequestIdentity) -> ClientClass {
+        if !identity.is_authenticated() {
+            ClientClass::Anonymous
+        } else if identity.has_scope(ADMIN_SCOPE) {
+            ClientClass::Admin
+        } else {
+            ClientClass::Agent
+        }
+    }
+
+    /// Address of the client behind `peer`. Forwarded hops are read from the nearest
+    /// one outwards while they are trusted proxies; the first untrusted hop is the client.
+    pub fn client_addr(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let mut client = peer;
+        if !self.trusted_proxies.contains(&peer) {
+            return client;
+        }
+        for hop in forwarded_for(headers).iter().rev() {
+            let Ok(hop) = hop.parse::<IpAddr>() else {
+                break;
+            };
+            client = hop;
+            if !self.trusted_proxies.contains(&hop) {
+                break;
+            }
+        }
+        client
+    }
+
+    /// Class and bucket of a caller. Requests served without connection info, such as
+    /// in-process calls, share a single anonymous bucket.
+    pub fn identify(
+        &self,
+        identity: &RequestIdentity,
+        peer: Option<IpAddr>,
+        headers: &HeaderMap,
+    ) -> (ClientClass, String) {
+        let class = self.classify(identity);
+        let key = match (class, peer) {
+            (ClientClass::Anonymous, Some(peer)) => {
+                format!("ip:{}", self.client_addr(peer, headers))
+            }
+            (ClientClass::Anonymous, None) => "anonymous".to_string(),
+            _ => format!("subject:{}", identity.subject),
+        };
+        (class, key)
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    entries: HashMap<(ClientClass, String), Bucket>,
+    swept: Instant,
+}
+
+impl Buckets {
+    /// Drop buckets idle for `timeout`, at most once per `timeout`.
+    fn sweep(&mut self, now: Instant, timeout: Duration) {
+        if now.saturating_duration_since(self.swept) < timeout {
+            return;
+        }
+        self.entries
+            .retain(|_, bucket| now.saturating_duration_since(bucket.updated) < timeout);
+        self.swept = now;
+    }
+}
+
+/// Why a request was turned away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// The caller exhausted its bucket; retry after the given delay.
+    RateLimited(Duration),
+    /// The class already has `max_concurrent` requests in flight.
+    Concurrency,
+}
+
+/// Shared limiter state behind [`RateLimitLayer`].
+#[derive(Debug)]
+pub struct ApiRateLimiter {
+    config: RwLock<RateLimitConfig>,
+    buckets: Mutex<Buckets>,
+    in_flight: RwLock<HashMap<ClientClass, Arc<Semaphore>>>,
+}
+
+impl ApiRateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            in_flight: RwLock::new(class_semaphores(&config)),
+            config: RwLock::new(config),
+            buckets: Mutex::new(Buckets {
+                entries: HashMap::new(),
+                swept: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn config(&self) -> RateLimitConfig {
+        self.config
+            .read()
+            .expect("rate limit config poisoned")
+            .clone()
+    }
+
+    pub fn identify(
+        &self,
+        identity: &RequestIdentity,
+        peer: Option<IpAddr>,
+        headers: &HeaderMap,
+    ) -> (ClientClass, String) {
+        self.config
+            .read()
+            .expect("rate limit config poisoned")
+            .identify(identity, peer, headers)
+    }
+
+    /// Number of buckets currently held.
+    pub fn bucket_count(&self) -> usize {
+        self.buckets
+            .lock()
+            .expect("rate limit buckets poisoned")
+            .entries
+            .len()
+    }
+
+    /// Swap in new limits and proxies. Buckets carry over, clamped to the new burst on
+    /// their next use; requests already in flight keep their slot under the old cap.
+    pub fn reconfigure(&self, config: RateLimitConfig) {
+        let semaphores = class_semaphores(&config);
+        *self
+            .in_flight
+            .write()
+            .expect("rate limit semaphores poisoned") = semaphores;
+        *self.config.write().expect("rate limit config poisoned") = config;
+    }
+
+    /// Take a token from the bucket `key` and an in-flight slot for its class.
+    pub fn acquire(
+        &self,
+        class: ClientClass,
+        key: &str,
+    ) -> Result<OwnedSemaphorePermit, Rejection> {
+        self.take_token(class, key, Instant::now())?;
+        let semaphore = self
+            .in_flight
+            .read()
+            .expect("rate limit semaphores poisoned")[&class]
+            .clone();
+        semaphore
+            .try_acquire_owned()
+            .map_err(|_| Rejection::Concurrency)
+    }
+
+    fn take_token(&self, class: ClientClass, key: &str, now: Instant) -> Result<(), Rejection> {
+        let config = self.config();
+        let limits = config.limits(class);
+        let mut buckets = self.buckets.lock().expect("rate limit buckets poisoned");
+        buckets.sweep(now, config.bucket_idle_timeout);
+        let bucket = buckets
+            .entries
+            .entry((class, key.to_string()))
+            .or_insert_with(|| Bucket {
+                tokens: f64::from(limits.burst),
+                updated: now,
+            });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens =
+            (bucket.tokens + elapsed * limits.requests_per_second).min(f64::from(limits.burst));
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let wait = if limits.requests_per_second > 0.0 {
+            (1.0 - bucket.tokens) / limits.requests_per_second
+        } else {
+            f64::from(u32::MAX)
+        };
+        Err(Rejection::RateLimited(Duration::from_secs_f64(wait)))
+    }
+}
+
+fn class_semaphores(config: &RateLimitConfig) -> HashMap<ClientClass, Arc<Semaphore>> {
+    [
+        ClientClass::Admin,
+        ClientClass::Agent,
+        ClientClass::Anonymous,
+    ]
+    .into_iter()
+    .map(|class| {
+        let permits = config.limits(class).max_concurrent;
+        (class, Arc::new(Semaphore::new(permits)))
+    })
+    .collect()
+}
+
+/// Tower layer enforcing [`RateLimitConfig`] on every non-probe request.
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<ApiRateLimiter>,
+}
+
+impl RateLimitLayer {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            limiter: Arc::new(ApiRateLimiter::new(config)),
+        }
+    }
+
+    /// The limiter shared by every service this layer produces.
+    pub fn limiter(&self) -> Arc<ApiRateLimiter> {
+        self.limiter.clone()
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    limiter: Arc<ApiRateLimiter>,
+}
+
+impl<S, B> Service<Request<B>> for RateLimitService<S>
+where
+    S: Service<Request<B>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        // Use the service that was polled ready and leave a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let path = request.uri().path();
+        if EXEMPT_PATHS.contains(&path) || path.starts_with("/grpc.health.v1.Health/") {
+            return Box::pin(inner.call(request));
+        }
+
+        let identity = request
+            .extensions()
+            .get::<RequestIdentity>()
+            .cloned()
+            .unwrap_or_else(RequestIdentity::anonymous);
+        let peer = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let (class, bucket_key) = self.limiter.identify(&identity, peer, request.headers());
+        let label = class.as_str();
+
+        match self.limiter.acquire(class, &bucket_key) {
+            Ok(permit) => {
+                counter!("api_rate_limit_allowed_total", 1, "class" => label);
+                increment_gauge!("api_requests_in_flight", 1.0, "class" => label);
+                Box::pin(async move {
+                    let response = inner.call(request).await;
+                    drop(permit);
+                    decrement_gauge!("api_requests_in_flight", 1.0, "class" => label);
+                    response
+                })
+            }
+            Err(rejection) => {
+                let reason = match rejection {
+                    Rejection::RateLimited(_) => "rate",
+                    Rejection::Concurrency => "concurrency",
+                };
+                counter!(
+                    "api_rate_limit_rejected_total",
+                    1,
+                    "class" => label,
+                    "reason" => reason
+                );
+                Box::pin(async move { Ok(too_many_requests(class, rejection)) })
+            }
+        }
+    }
+}
+
+/// Every `X-Forwarded-For` hop, client first.
+fn forwarded_for(headers: &HeaderMap) -> Vec<String> {
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|hop| hop.trim().to_string())
+        .filter(|hop| !hop.is_empty())
+        .collect()
+}
+
+fn too_many_requests(class: ClientClass, rejection: Rejection) -> Response<Body> {
+    let (retry_after, code, message) = match rejection {
+        Rejection::RateLimited(wait) => (
+            wait.as_secs_f64().ceil().max(1.0) as u64,
+            ErrorCode::RateLimited,
+            "rate limit exceeded",
+        ),
+        Rejection::Concurrency => (
+            1,
+            ErrorCode::ConcurrencyLimited,
+            "too many concurrent requests",
+        ),
+    };
+    let mut response = Problem::new(code, message)
+        .with_extension("class", class.as_str())
+        .with_extension("retry_after_seconds", retry_after)
+        .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{AuthConfig, AuthLayer};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    fn request(path: &str, key: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder().uri(path);
+        if let Some(key) = key {
+            builder = builder.header("x-api-key", key);
+        }
+        builder.body(Body::empty()).expect("request")
+    }
+
+    fn from_peer(peer: &str, forwarded: Option<&str>) -> Request<Body> {
+        let mut request = request("/v1/ping", None);
+        if let Some(forwarded) = forwarded {
+            request.headers_mut().insert(
+                "x-forwarded-for",
+                HeaderValue::from_str(forwarded).expect("header"),
+            );
+        }
+        let peer: SocketAddr = peer.parse().expect("peer address");
+        request.extensions_mut().insert(ConnectInfo(peer));
+        request
+    }
+
+    fn agent(subject: &str) -> RequestIdentity {
+        RequestIdentity {
+            subject: subject.into(),
+            method: crate::auth::AuthMethod::CapabilityToken,
+            scopes: vec![crate::auth::API_TOKEN_SCOPE.into()],
+        }
+    }
+
+    #[tokio::test]
+    async fn limits_each_caller_by_class_and_reports_retry_after() {
+        let config = RateLimitConfig::default()
+            .with_limits(ClientClass::Anonymous, ClassLimits::new(0.5, 1, 4))
+            .with_limits(ClientClass::Admin, ClassLimits::new(1.0, 3, 4));
+        let router = Router::new()
+            .route("/v1/ping", get(|| async { "pong" }))
+            .route("/health", get(|| async { "ok" }))
+            .layer(RateLimitLayer::new(config))
+            .layer(AuthLayer::new(AuthConfig::default().with_api_key(
+                "root",
+                "ops",
+                [ADMIN_SCOPE],
+            )));
+
+        let first = router
+            .clone()
+            .oneshot(request("/v1/ping", None))
+            .await
+            .unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        let limited = router
+            .clone()
+            .oneshot(request("/v1/ping", None))
+            .await
+            .unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            limited.headers().get(header::RETRY_AFTER),
+            Some(&HeaderValue::from(2u64))
+        );
+        let probe = router
+            .clone()
+            .oneshot(request("/health", None))
+            .await
+            .unwrap();
+        assert_eq!(probe.status(), StatusCode::OK);
+
+        // A made-up key is rejected by authentication rather than given a fresh bucket.
+        let rotated = router
+            .clone()
+            .oneshot(request("/v1/ping", Some("junk-1")))
+            .await
+            .unwrap();
+        assert_eq!(rotated.status(), StatusCode::UNAUTHORIZED);
+
+        for _ in 0..3 {
+            let response = router
+                .clone()
+                .oneshot(request("/v1/ping", Some("root")))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let admin_limited = router
+            .clone()
+            .oneshot(request("/v1/ping", Some("root")))
+            .await
+            .unwrap();
+        assert_eq!(admin_limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn anonymous_callers_are_bucketed_by_peer_unless_behind_a_trusted_proxy() {
+        let config = RateLimitConfig::default()
+            .with_limits(ClientClass::Anonymous, ClassLimits::new(0.1, 1, 4))
+            .with_trusted_proxy("10.0.0.1".parse().unwrap());
+        let router = Router::new()
+            .route("/v1/ping", get(|| async { "pong" }))
+            .layer(RateLimitLayer::new(config));
+        let status = |request: Request<Body>| {
+            let router = router.clone();
+            async move { router.oneshot(request).await.unwrap().status() }
+        };
+
+        assert_eq!(
+            status(from_peer("192.0.2.7:4000", Some("203.0.113.1"))).await,
+            StatusCode::OK
+        );
+        // An untrusted peer cannot pick a new bucket by rotating X-Forwarded-For.
+        assert_eq!(
+            status(from_peer("192.0.2.7:4001", Some("203.0.113.2"))).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            status(from_peer("192.0.2.8:4000", None)).await,
+            StatusCode::OK
+        );
+
+        // Behind the proxy, the client is the nearest untrusted hop.
+        assert_eq!(
+            status(from_peer("10.0.0.1:5000", Some("spoofed, 198.51.100.4"))).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(from_peer("10.0.0.1:5001", Some("198.51.100.4"))).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            status(from_peer("10.0.0.1:5002", Some("198.51.100.5"))).await,
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn idle_buckets_are_evicted() {
+        let limiter = ApiRateLimiter::new(
+            RateLimitConfig::default().with_bucket_idle_timeout(Duration::from_secs(60)),
+        );
+        let start = Instant::now();
+        for peer in 0..100 {
+            limiter
+                .take_token(ClientClass::Anonymous, &format!("ip:192.0.2.{peer}"), start)
+                .unwrap();
+        }
+        assert_eq!(limiter.bucket_count(), 100);
+
+        let later = start + Duration::from_secs(61);
+        limiter
+            .take_token(ClientClass::Agent, "subject:release-agent", later)
+            .unwrap();
+        assert_eq!(limiter.bucket_count(), 1);
+    }
+
+    #[test]
+    fn concurrency_is_capped_per_class() {
+        let limiter = ApiRateLimiter::new(
+            RateLimitConfig::default()
+                .with_limits(ClientClass::Agent, ClassLimits::new(10.0, 10, 1)),
+        );
+        let (class, key) = limiter.identify(&agent("agent-1"), None, &HeaderMap::new());
+        assert_eq!(class, ClientClass::Agent);
+        assert_eq!(key, "subject:agent-1");
+        let held = limiter.acquire(class, &key).unwrap();
+        assert_eq!(
+            limiter.acquire(class, &key).unwrap_err(),
+            Rejection::Concurrency
+        );
+        drop(held);
+        assert!(limiter.acquire(class, &key).is_ok());
+    }
+}
@@ -0,0 +1,285 @@
+//! Live reload of [`ApiConfig`] from a TOML file.
+//!
+//! The file layers over the configuration the server was built with and may set:
+//!
+//! ```toml
+//! [server]
+//! host = "0.0.0.0"
+//! port = 8080
+//! request_timeout_secs = 30
+//!
+//! [server.tls]
+//! cert_path = "server/vault/runtime/tls/dev-cert.pem"
+//! key_path = "server/vault/runtime/tls/dev-key.pem"
+//! # or: self_signed = true
+//!
+//! [rate_limits]
+//! trusted_proxies = ["10.0.0.1"]
+//! bucket_idle_secs = 600
+//!
+//! [rate_limits.agent]
+//! requests_per_second = 20.0
+//! burst = 40
+//! max_concurrent = 16
+//! ```
+//!
+//! Other sections are ignored, so the watcher can point at the shared server config.
+//! [`ConfigWatcher`] polls the file and publishes each valid revision; a file that
+//! fails to parse is logged and the previous configuration stays in effect.
+
+use crate::rate_limit::{ClassLimits, ClientClass};
+use crate::tls::TlsConfig;
+use crate::ApiConfig;
+use anyhow::{bail, Context, Result};
+use metrics::counter;
+use serde::Deserialize;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Default, Deserialize)]
+struct ApiConfigFile {
+    #[serde(default)]
+    server: ServerSection,
+    #[serde(default)]
+    rate_limits: RateLimitSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ServerSection {
+    host: Option<String>,
+    port: Option<u16>,
+    request_timeout_secs: Option<u64>,
+    tls: Option<TlsSection>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TlsSection {
+    cert_path: Option<PathBuf>,
+    key_path: Option<PathBuf>,
+    #[serde(default)]
+    self_signed: bool,
+}
+
+impl TlsSection {
+    fn into_config(self) -> Result<TlsConfig> {
+        match (self.cert_path, self.key_path, self.self_signed) {
+            (None, None, true) => Ok(TlsConfig::self_signed()),
+            (Some(cert), Some(key), false) => Ok(TlsConfig::pem(cert, key)),
+            _ => bail!("[server.tls] needs cert_path and key_path, or self_signed = true"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RateLimitSection {
+    admin: Option<ClassLimits>,
+    agent: Option<ClassLimits>,
+    anonymous: Option<ClassLimits>,
+    #[serde(default)]
+    trusted_proxies: Vec<IpAddr>,
+    bucket_idle_secs: Option<u64>,
+}
+
+/// Layer the TOML document `contents` over `base`.
+pub fn apply_config_file(base: &ApiConfig, contents: &str) -> Result<ApiConfig> {
+    let file: ApiConfigFile = toml::from_str(contents).context("invalid api config file")?;
+    let mut config = base.clone();
+    if let Some(host) = file.server.host {
+        config.host = host;
+    }
+    if let Some(port) = file.server.port {
+        config.port = port;
+    }
+    if let Some(seconds) = file.server.request_timeout_secs {
+        config.request_timeout = Duration::from_secs(seconds);
+    }
+    if let Some(tls) = file.server.tls {
+        config.tls = Some(tls.into_config()?);
+    }
+
+    let limits = file.rate_limits;
+    let mut rate_limits = config.rate_limits;
+    for (class, overrides) in [
+        (ClientClass::Admin, limits.admin),
+        (ClientClass::Agent, limits.agent),
+        (ClientClass::Anonymous, limits.anonymous),
+    ] {
+        if let Some(overrides) = overrides {
+            rate_limits = rate_limits.with_limits(class, overrides);
+        }
+    }
+    for proxy in limits.trusted_proxies {
+        rate_limits = rate_limits.with_trusted_proxy(proxy);
+    }
+    if let Some(seconds) = limits.bucket_idle_secs {
+        rate_limits = rate_limits.with_bucket_idle_timeout(Duration::from_secs(seconds));
+    }
+    config.rate_limits = rate_limits;
+    Ok(config)
+}
+
+/// Polls a config file and publishes the merged [`ApiConfig`] whenever it changes.
+#[derive(Debug, Clone)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    base: ApiConfig,
+    poll_interval: Duration,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: impl Into<PathBuf>, base: ApiConfig) -> Self {
+        Self {
+            path: path.into(),
+            base,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load(&self) -> Result<ApiConfig> {
+        let contents = fs::read_to_string(&self.path)
+            .with_context(|| format!("failed to read {}", self.path.display()))?;
+        apply_config_file(&self.base, &contents)
+    }
+
+    /// Load the file once, then keep polling it in the background. The receiver starts
+    /// with the initial configuration; polling stops when every receiver is dropped.
+    pub fn spawn(self) -> Result<watch::Receiver<ApiConfig>> {
+        let initial = self.load()?;
+        let (sender, receiver) = watch::channel(initial);
+        tokio::spawn(async move {
+            let mut last = read_snapshot(&self.path).await;
+            let mut ticker = tokio::time::interval(self.poll_interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if sender.is_closed() {
+                    break;
+                }
+                let snapshot = read_snapshot(&self.path).await;
+                if snapshot == last {
+                    continue;
+                }
+                last = snapshot.clone();
+                match snapshot.and_then(|contents| {
+                    apply_config_file(&self.base, &contents).map_err(|err| format!("{err:#}"))
+                }) {
+                    Ok(config) => {
+                        info!(path = %self.path.display(), "api config changed");
+                        sender.send_replace(config);
+                    }
+                    Err(err) => {
+                        counter!("api_config_reloads_total", 1, "outcome" => "rejected");
+                        warn!(path = %self.path.display(), %err, "ignoring api config update");
+                    }
+                }
+            }
+        });
+        Ok(receiver)
+    }
+}
+
+/// File contents, or the read error, so repeated failures are only reported once.
+async fn read_snapshot(path: &Path) -> Result<String, String> {
+    tokio::fs::read_to_string(path)
+        .await
+        .map_err(|err| format!("failed to read {}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_overrides_bind_timeout_and_limits() {
+        let base = ApiConfig::default();
+        let config = apply_config_file(
+            &base,
+            r#"
+            [server]
+            port = 9090
+            request_timeout_secs = 5
+            workers = 4
+
+            [rate_limits]
+            trusted_proxies = ["10.0.0.1"]
+            bucket_idle_secs = 120
+
+            [rate_limits.agent]
+            requests_per_second = 2.0
+            burst = 3
+            max_concurrent = 1
+
+            [database]
+            url = "postgresql://localhost:5432/noa"
+            "#,
+        )
+        .expect("config applies");
+        assert_eq!(config.host, base.host);
+        assert_eq!(config.port, 9090);
+        assert_eq!(config.request_timeout, Duration::from_secs(5));
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert("x-forwarded-for", "198.51.100.4".parse().unwrap());
+        assert_eq!(
+            config
+                .rate_limits
+                .client_addr("10.0.0.1".parse().unwrap(), &headers),
+            "198.51.100.4".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            config.rate_limits.bucket_idle_timeout,
+            Duration::from_secs(120)
+        );
+        assert_eq!(config.rate_limits.agent, ClassLimits::new(2.0, 3, 1));
+        assert_eq!(config.rate_limits.admin, base.rate_limits.admin);
+        assert!(config.tls.is_none());
+        assert!(apply_config_file(&base, "[server]\nport = \"eighty\"").is_err());
+
+        let tls = apply_config_file(
+            &base,
+            "[server.tls]\ncert_path = \"cert.pem\"\nkey_path = \"key.pem\"\n",
+        )
+        .expect("tls applies");
+        assert_eq!(tls.tls, Some(TlsConfig::pem("cert.pem", "key.pem")));
+        assert!(apply_config_file(&base, "[server.tls]\ncert_path = \"cert.pem\"\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn watcher_publishes_valid_changes_only() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("api.toml");
+        fs::write(&path, "[server]\nport = 9000\n").unwrap();
+        let mut updates = ConfigWatcher::new(&path, ApiConfig::default())
+            .with_poll_interval(Duration::from_millis(10))
+            .spawn()
+            .expect("watcher starts");
+        assert_eq!(updates.borrow_and_update().port, 9000);
+
+        fs::write(&path, "[server\n").unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!updates.has_changed().unwrap());
+
+        fs::write(&path, "[server]\nport = 9001\n").unwrap();
+        tokio::time::timeout(Duration::from_secs(2), updates.changed())
+            .await
+            .expect("update published")
+            .unwrap();
+        assert_eq!(updates.borrow_and_update().port, 9001);
+    }
+}
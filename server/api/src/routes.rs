@@ -1,38 +1,47 @@
-use crate::ApiState;
-use anyhow::Error;
+use crate::graphql::{build_schema, ApiSchema};
+use crate::health::{overall_status, DependencyCheck, DependencyStatus};
+use crate::pagination::{ListQuery, Page};
+use crate::problem::{error_catalog, ErrorCode, Problem, ERROR_CATALOG_PATH};
+use crate::{content_type, ApiState, CorrelationId, DocsLibrary, RequestIdentity, MAX_LIMIT};
+use axum::body::Bytes;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
 use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
-use axum::extract::{Path, State};
-use axum::http::{header, StatusCode};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use metrics::counter;
+use noa_cicd::webhook::{WebhookDelivery, WebhookError, WebhookOutcome};
+use noa_core::metrics::history_store;
+use noa_core::metrics::timeseries::{Resolution, SeriesQuery};
 use noa_gateway::{Protocol, RoutePlan};
+use noa_workflow::{GraphFormat, PendingApproval, Workflow, WorkflowEngine, WorkflowState};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::borrow::Cow;
-use uuid::Uuid;
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Clone)]
 pub struct ApiRoutes {
     state: ApiState,
+    graphql: ApiSchema,
 }
 
 impl ApiRoutes {
     pub fn new(state: ApiState) -> Self {
-        Self { state }
+        let graphql = build_schema(state.clone());
+        Self { state, graphql }
     }
 
     fn record_request(&self, endpoint: &str) {
         counter!("api_requests_total", 1, "endpoint" => endpoint.to_string());
     }
 
-    fn route(&self, protocol: Protocol, payload: Value) -> Result<RoutePlan, ApiError> {
-        self.state.route(protocol, payload).map_err(ApiError::from)
-    }
-
-    fn new_request_id(&self) -> String {
-        Uuid::new_v4().to_string()
+    fn route(&self, protocol: Protocol, payload: Value) -> Result<RoutePlan, Problem> {
+        self.state
+            .route(protocol, payload)
+            .map_err(|err| Problem::new(ErrorCode::RoutingFailed, format!("{err:#}")))
     }
 
     pub fn state(&self) -> &ApiState {
@@ -44,10 +53,31 @@ pub fn build_http_router(state: ApiRoutes) -> Router {
     Router::new()
         .route("/health", get(health))
         .route("/ready", get(ready))
+        .route("/healthz", get(liveness))
+        .route("/readyz", get(readiness))
+        .route("/health/deps", get(dependency_health))
         .route("/metrics", get(metrics))
         .route("/v1/inference", post(inference))
         .route("/v1/retrieval", post(retrieval))
         .route("/v1/orchestration", post(orchestration))
+        .route("/v1/workflows", get(list_workflows).post(submit_workflow))
+        .route("/v1/workflows/:workflow_id/graph", get(workflow_graph))
+        .route("/v1/workflows/approvals", get(pending_approvals))
+        .route("/v1/workflows/approvals/:token", post(register_approval))
+        .route("/v1/tools", get(list_tools))
+        .route("/v1/graphql", get(graphql_schema).post(graphql))
+        .route(
+            "/v1/pipelines/:pipeline_id/evidence",
+            get(pipeline_evidence),
+        )
+        .route("/v1/pipelines/:base/compare/:head", get(compare_pipelines))
+        .route("/v1/pipelines/webhook", post(pipeline_webhook))
+        .route("/v1/metrics/history", get(metrics_history))
+        .route("/v1/docs", get(docs_index))
+        .route("/v1/docs/search", get(docs_search))
+        .route("/v1/docs/reindex", post(docs_reindex))
+        .route("/v1/docs/files/:collection/*path", get(docs_file))
+        .route(ERROR_CATALOG_PATH, get(errors))
         .route("/ws/:channel", get(websocket))
         .with_state(state)
 }
@@ -76,6 +106,63 @@ async fn ready(State(routes): State<ApiRoutes>) -> impl IntoResponse {
     })
 }
 
+async fn liveness(State(routes): State<ApiRoutes>) -> impl IntoResponse {
+    Json(json!({
+        "status": "alive",
+        "uptime_seconds": routes.state().uptime_seconds(),
+    }))
+}
+
+#[derive(Serialize)]
+struct ReadinessResponse {
+    status: &'static str,
+    ready: bool,
+    uptime_seconds: u64,
+    checks: Vec<DependencyCheck>,
+}
+
+async fn readiness(State(routes): State<ApiRoutes>) -> impl IntoResponse {
+    let checks = routes.state().health().check_dependencies().await;
+    let started = routes.state().is_ready();
+    let draining = routes.state().is_draining();
+    let ready = started && !draining && overall_status(&checks) != DependencyStatus::Down;
+    let status = match (started, draining, ready) {
+        (false, _, _) => "starting",
+        (true, true, _) => "draining",
+        (true, false, false) => "unavailable",
+        (true, false, true) => "ready",
+    };
+    let code = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = ReadinessResponse {
+        status,
+        ready,
+        uptime_seconds: routes.state().uptime_seconds(),
+        checks,
+    };
+    (code, Json(body))
+}
+
+async fn dependency_health(State(routes): State<ApiRoutes>) -> impl IntoResponse {
+    let checks = routes.state().health().check_dependencies().await;
+    let status = overall_status(&checks);
+    let code = if status == DependencyStatus::Down {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (
+        code,
+        Json(json!({
+            "status": status,
+            "dependencies": checks,
+        })),
+    )
+}
+
 async fn metrics(State(routes): State<ApiRoutes>) -> impl IntoResponse {
     match Response::builder()
         .status(StatusCode::OK)
@@ -85,11 +172,16 @@ async fn metrics(State(routes): State<ApiRoutes>) -> impl IntoResponse {
         Ok(resp) => resp,
         Err(err) => {
             tracing::error!(?err, "failed to build metrics response");
-            (StatusCode::INTERNAL_SERVER_ERROR, "metrics exporter error").into_response()
+            Problem::new(ErrorCode::Internal, "metrics exporter error").into_response()
         }
     }
 }
 
+async fn errors(State(routes): State<ApiRoutes>) -> impl IntoResponse {
+    routes.record_request("errors");
+    Json(json!({ "errors": error_catalog() }))
+}
+
 #[derive(Debug, Deserialize)]
 struct InferenceRestRequest {
     prompt: String,
@@ -127,9 +219,11 @@ struct RoutedResponse {
 
 async fn inference(
     State(routes): State<ApiRoutes>,
-    Json(payload): Json<InferenceRestRequest>,
-) -> Result<Json<RoutedResponse>, ApiError> {
+    correlation: CorrelationId,
+    payload: Result<Json<InferenceRestRequest>, JsonRejection>,
+) -> Result<Json<RoutedResponse>, Problem> {
     routes.record_request("inference");
+    let Json(payload) = payload?;
     let protocol = payload.protocol.unwrap_or(Protocol::Grpc);
     let plan = routes.route(
         protocol,
@@ -141,7 +235,7 @@ async fn inference(
         }),
     )?;
     Ok(Json(RoutedResponse {
-        request_id: routes.new_request_id(),
+        request_id: correlation.0,
         plan,
         status: "accepted",
         note: "inference request routed".into(),
@@ -150,9 +244,11 @@ async fn inference(
 
 async fn retrieval(
     State(routes): State<ApiRoutes>,
-    Json(payload): Json<RetrievalRestRequest>,
-) -> Result<Json<RoutedResponse>, ApiError> {
+    correlation: CorrelationId,
+    payload: Result<Json<RetrievalRestRequest>, JsonRejection>,
+) -> Result<Json<RoutedResponse>, Problem> {
     routes.record_request("retrieval");
+    let Json(payload) = payload?;
     let protocol = payload.protocol.unwrap_or(Protocol::GraphQl);
     let plan = routes.route(
         protocol,
@@ -164,7 +260,7 @@ async fn retrieval(
         }),
     )?;
     Ok(Json(RoutedResponse {
-        request_id: routes.new_request_id(),
+        request_id: correlation.0,
         plan,
         status: "accepted",
         note: "retrieval request routed".into(),
@@ -173,9 +269,11 @@ async fn retrieval(
 
 async fn orchestration(
     State(routes): State<ApiRoutes>,
-    Json(payload): Json<OrchestrationRestRequest>,
-) -> Result<Json<RoutedResponse>, ApiError> {
+    correlation: CorrelationId,
+    payload: Result<Json<OrchestrationRestRequest>, JsonRejection>,
+) -> Result<Json<RoutedResponse>, Problem> {
     routes.record_request("orchestration");
+    let Json(payload) = payload?;
     let protocol = payload.protocol.unwrap_or(Protocol::GraphQl);
     let plan = routes.route(
         protocol,
@@ -187,18 +285,479 @@ async fn orchestration(
         }),
     )?;
     Ok(Json(RoutedResponse {
-        request_id: routes.new_request_id(),
+        request_id: correlation.0,
         plan,
         status: "accepted",
         note: "orchestration request routed".into(),
     }))
 }
 
+#[derive(Debug, Deserialize)]
+struct WorkflowGraphQuery {
+    #[serde(default)]
+    format: Option<String>,
+}
+
+async fn workflow_graph(
+    Path(workflow_id): Path<String>,
+    query: Result<Query<WorkflowGraphQuery>, QueryRejection>,
+    State(routes): State<ApiRoutes>,
+) -> Result<Response, Problem> {
+    routes.record_request("workflow_graph");
+    let Query(query) = query?;
+    let format = match query.format.as_deref() {
+        Some(raw) => raw
+            .parse::<GraphFormat>()
+            .map_err(|err| Problem::new(ErrorCode::InvalidRequest, err))?,
+        None => GraphFormat::default(),
+    };
+    let engine = attached_engine(&routes)?;
+    let rendered = engine
+        .render_workflow(&workflow_id, format)
+        .ok_or_else(|| {
+            Problem::new(
+                ErrorCode::NotFound,
+                format!("workflow not found: {workflow_id}"),
+            )
+        })?;
+    Ok(([(header::CONTENT_TYPE, format.content_type())], rendered).into_response())
+}
+
+/// The shared tool registry that workflow tool requirements are validated against.
+async fn list_tools(State(routes): State<ApiRoutes>) -> Result<Json<Value>, Problem> {
+    routes.record_request("tools");
+    let engine = attached_engine(&routes)?;
+    Ok(Json(json!({ "tools": engine.list_tools() })))
+}
+
+/// Export the pipeline's signed evidence bundle and return it as a gzipped tar.
+async fn pipeline_evidence(
+    Path(pipeline_id): Path<String>,
+    State(routes): State<ApiRoutes>,
+) -> Result<Response, Problem> {
+    routes.record_request("pipeline_evidence");
+    let cicd = routes.state().cicd_system().ok_or_else(|| {
+        Problem::new(ErrorCode::DependencyUnavailable, "cicd system not attached")
+    })?;
+    if cicd.get_pipeline_status(&pipeline_id).is_none() {
+        return Err(Problem::new(
+            ErrorCode::NotFound,
+            format!("pipeline not found: {pipeline_id}"),
+        ));
+    }
+    let export_id = pipeline_id.clone();
+    let bundle = tokio::task::spawn_blocking(move || {
+        let path = cicd.export_evidence_bundle(&export_id)?;
+        std::fs::read(&path).map_err(|err| format!("failed to read evidence bundle: {err}"))
+    })
+    .await
+    .map_err(|err| Problem::new(ErrorCode::Internal, err.to_string()))?
+    .map_err(|err| Problem::new(ErrorCode::Internal, err))?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{pipeline_id}-evidence.tar.gz\""),
+            ),
+        ],
+        bundle,
+    )
+        .into_response())
+}
+
+/// Regression report of the `head` pipeline against `base`.
+async fn compare_pipelines(
+    Path((base, head)): Path<(String, String)>,
+    State(routes): State<ApiRoutes>,
+) -> Result<Json<Value>, Problem> {
+    routes.record_request("compare_pipelines");
+    let cicd = routes.state().cicd_system().ok_or_else(|| {
+        Problem::new(ErrorCode::DependencyUnavailable, "cicd system not attached")
+    })?;
+    let comparison = cicd
+        .compare(&base, &head)
+        .map_err(|err| Problem::new(ErrorCode::NotFound, err))?;
+    serde_json::to_value(comparison)
+        .map(Json)
+        .map_err(|err| Problem::new(ErrorCode::Internal, err.to_string()))
+}
+
+#[derive(Deserialize)]
+struct WebhookQuery {
+    /// Pipeline to trigger; defaults to the repository name.
+    pipeline: Option<String>,
+}
+
+/// Trigger a pipeline from a GitHub or GitLab push or pull request webhook. Deliveries
+/// are authenticated by their signature against `NOA_WEBHOOK_SECRET` rather than by
+/// API credentials.
+async fn pipeline_webhook(
+    State(routes): State<ApiRoutes>,
+    query: Result<Query<WebhookQuery>, QueryRejection>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<Value>), Problem> {
+    routes.record_request("pipeline_webhook");
+    let Query(query) = query?;
+    let cicd = routes.state().cicd_system().ok_or_else(|| {
+        Problem::new(ErrorCode::DependencyUnavailable, "cicd system not attached")
+    })?;
+    let delivery = WebhookDelivery::from_headers(|name| {
+        headers.get(name).and_then(|value| value.to_str().ok())
+    })
+    .ok_or_else(|| {
+        Problem::new(
+            ErrorCode::InvalidRequest,
+            "missing X-GitHub-Event or X-Gitlab-Event header",
+        )
+    })?;
+    let outcome = tokio::task::spawn_blocking(move || {
+        cicd.handle_webhook(&delivery, &body, query.pipeline.as_deref())
+    })
+    .await
+    .map_err(|err| Problem::new(ErrorCode::Internal, err.to_string()))?
+    .map_err(|err| {
+        let code = match &err {
+            WebhookError::NotConfigured => ErrorCode::DependencyUnavailable,
+            WebhookError::Unauthorized(_) => ErrorCode::Unauthenticated,
+            WebhookError::InvalidPayload(_) => ErrorCode::InvalidRequest,
+            WebhookError::Trigger(_) => ErrorCode::Internal,
+        };
+        Problem::new(code, err.to_string())
+    })?;
+    let status = match outcome {
+        WebhookOutcome::Triggered { .. } => StatusCode::ACCEPTED,
+        WebhookOutcome::Ignored { .. } => StatusCode::OK,
+    };
+    serde_json::to_value(outcome)
+        .map(|body| (status, Json(body)))
+        .map_err(|err| Problem::new(ErrorCode::Internal, err.to_string()))
+}
+
+const DEFAULT_HISTORY_WINDOW_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Points of a metric series from the local time-series store. `series` is
+/// required; `start_ms`/`end_ms` default to the last day, `resolution` to the finest
+/// tier still retaining the start, and any other parameter filters on a label.
+async fn metrics_history(
+    State(routes): State<ApiRoutes>,
+    query: Result<Query<BTreeMap<String, String>>, QueryRejection>,
+) -> Result<Json<Value>, Problem> {
+    routes.record_request("metrics_history");
+    let Query(mut params) = query?;
+    let store = history_store().ok_or_else(|| {
+        Problem::new(
+            ErrorCode::DependencyUnavailable,
+            "metrics history store not attached",
+        )
+    })?;
+    let series = params
+        .remove("series")
+        .ok_or_else(|| Problem::new(ErrorCode::InvalidRequest, "missing `series` parameter"))?;
+    let mut millis = |name: &str| {
+        params
+            .remove(name)
+            .map(|raw| {
+                raw.parse::<u64>().map_err(|_| {
+                    Problem::new(
+                        ErrorCode::InvalidRequest,
+                        format!("`{name}` must be milliseconds since the epoch"),
+                    )
+                })
+            })
+            .transpose()
+    };
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default();
+    let end_ms = millis("end_ms")?.unwrap_or(now_ms + 1);
+    let start_ms =
+        millis("start_ms")?.unwrap_or_else(|| end_ms.saturating_sub(DEFAULT_HISTORY_WINDOW_MS));
+    let mut series_query = SeriesQuery::new(series, start_ms, end_ms);
+    if let Some(raw) = params.remove("resolution") {
+        let resolution = raw
+            .parse::<Resolution>()
+            .map_err(|err| Problem::new(ErrorCode::InvalidRequest, err.to_string()))?;
+        series_query = series_query.with_resolution(resolution);
+    }
+    series_query.labels.extend(params);
+    let data = tokio::task::spawn_blocking(move || store.query(&series_query, now_ms))
+        .await
+        .map_err(|err| Problem::new(ErrorCode::Internal, err.to_string()))?
+        .map_err(|err| Problem::new(ErrorCode::Internal, err.to_string()))?;
+    Ok(Json(json!({
+        "start_ms": start_ms,
+        "end_ms": end_ms,
+        "series": data,
+    })))
+}
+
+fn attached_docs(routes: &ApiRoutes) -> Result<std::sync::Arc<DocsLibrary>, Problem> {
+    routes.state().docs_library().ok_or_else(|| {
+        Problem::new(
+            ErrorCode::DependencyUnavailable,
+            "documentation library not attached",
+        )
+    })
+}
+
+/// Collections and files the caller may read.
+async fn docs_index(
+    State(routes): State<ApiRoutes>,
+    identity: RequestIdentity,
+) -> Result<Json<Value>, Problem> {
+    routes.record_request("docs_index");
+    let docs = attached_docs(&routes)?;
+    Ok(Json(json!({
+        "collections": docs.collections(&identity),
+        "documents": docs.entries(&identity),
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct DocsSearchQuery {
+    q: String,
+    #[serde(default = "default_docs_hits")]
+    limit: usize,
+}
+
+fn default_docs_hits() -> usize {
+    20
+}
+
+/// Full-text search over the collections the caller may read.
+async fn docs_search(
+    State(routes): State<ApiRoutes>,
+    identity: RequestIdentity,
+    query: Result<Query<DocsSearchQuery>, QueryRejection>,
+) -> Result<Json<Value>, Problem> {
+    routes.record_request("docs_search");
+    let Query(query) = query?;
+    let docs = attached_docs(&routes)?;
+    let hits = docs.search(&query.q, query.limit.clamp(1, MAX_LIMIT), &identity)?;
+    Ok(Json(json!({ "query": query.q, "hits": hits })))
+}
+
+/// Rescan the collections after new reports or bundles are written.
+async fn docs_reindex(State(routes): State<ApiRoutes>) -> Result<Json<Value>, Problem> {
+    routes.record_request("docs_reindex");
+    let docs = attached_docs(&routes)?;
+    let documents = tokio::task::spawn_blocking(move || docs.reindex())
+        .await
+        .map_err(|err| Problem::new(ErrorCode::Internal, err.to_string()))?
+        .map_err(|err| Problem::new(ErrorCode::Internal, format!("{err:#}")))?;
+    Ok(Json(json!({ "documents": documents })))
+}
+
+/// Serve one document from a collection the caller may read.
+async fn docs_file(
+    Path((collection, path)): Path<(String, String)>,
+    State(routes): State<ApiRoutes>,
+    identity: RequestIdentity,
+) -> Result<Response, Problem> {
+    routes.record_request("docs_file");
+    let docs = attached_docs(&routes)?;
+    let file = docs.resolve(&collection, &path, &identity)?;
+    let bytes = tokio::fs::read(&file)
+        .await
+        .map_err(|err| Problem::new(ErrorCode::Internal, err.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, content_type(&file))], bytes).into_response())
+}
+
+/// Resolve a GraphQL query over pipelines, workflows, agents, and the ledger.
+async fn graphql(
+    State(routes): State<ApiRoutes>,
+    request: Result<Json<async_graphql::Request>, JsonRejection>,
+) -> Result<Json<async_graphql::Response>, Problem> {
+    routes.record_request("graphql");
+    let Json(request) = request?;
+    Ok(Json(routes.graphql.execute(request).await))
+}
+
+/// The GraphQL schema in SDL, for client code generation.
+async fn graphql_schema(State(routes): State<ApiRoutes>) -> impl IntoResponse {
+    routes.record_request("graphql_schema");
+    routes.graphql.sdl()
+}
+
+fn attached_engine(routes: &ApiRoutes) -> Result<std::sync::Arc<WorkflowEngine>, Problem> {
+    routes.state().workflow_engine().ok_or_else(|| {
+        Problem::new(
+            ErrorCode::DependencyUnavailable,
+            "workflow engine not attached",
+        )
+    })
+}
+
+fn paginate<T: Serialize>(
+    query: &ListQuery,
+    items: Vec<T>,
+    stable_id: impl Fn(&T) -> String,
+) -> Result<Json<Page<T>>, Problem> {
+    query
+        .apply(items, stable_id)
+        .map(Json)
+        .map_err(|err| Problem::new(ErrorCode::InvalidRequest, err))
+}
+
+#[derive(Debug, Serialize)]
+struct WorkflowSummary {
+    workflow_id: String,
+    version: String,
+    state: Option<WorkflowState>,
+    stages: usize,
+}
+
+async fn list_workflows(
+    query: Result<Query<ListQuery>, QueryRejection>,
+    State(routes): State<ApiRoutes>,
+) -> Result<Json<Page<WorkflowSummary>>, Problem> {
+    routes.record_request("workflows");
+    let Query(query) = query?;
+    let engine = attached_engine(&routes)?;
+    let summaries = engine
+        .workflow_ids()
+        .into_iter()
+        .filter_map(|workflow_id| {
+            let workflow = engine.get_workflow(&workflow_id)?;
+            Some(WorkflowSummary {
+                state: engine.get_state(&workflow_id),
+                version: workflow.version,
+                stages: workflow.stages.len(),
+                workflow_id,
+            })
+        })
+        .collect();
+    paginate(&query, summaries, |summary| summary.workflow_id.clone())
+}
+
+/// Validate a submitted definition and load it, replacing any workflow of the same name.
+async fn submit_workflow(
+    State(routes): State<ApiRoutes>,
+    workflow: Result<Json<Workflow>, JsonRejection>,
+) -> Result<(StatusCode, Json<WorkflowSummary>), Problem> {
+    routes.record_request("workflow_submit");
+    let Json(workflow) = workflow?;
+    let engine = attached_engine(&routes)?;
+    engine
+        .validate(&workflow)
+        .map_err(|err| Problem::new(ErrorCode::InvalidRequest, err))?;
+    let summary = WorkflowSummary {
+        workflow_id: workflow.name.clone(),
+        state: None,
+        version: workflow.version.clone(),
+        stages: workflow.stages.len(),
+    };
+    engine
+        .load_workflow(workflow)
+        .map_err(|err| Problem::new(ErrorCode::Conflict, err))?;
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+async fn pending_approvals(
+    query: Result<Query<ListQuery>, QueryRejection>,
+    State(routes): State<ApiRoutes>,
+) -> Result<Json<Page<PendingApproval>>, Problem> {
+    routes.record_request("workflow_approvals");
+    let Query(query) = query?;
+    let engine = attached_engine(&routes)?;
+    paginate(&query, engine.pending_approvals(), |pending| {
+        pending.token.clone()
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct ApprovalResponse {
+    workflow_id: String,
+    stage_id: String,
+    state: WorkflowState,
+}
+
+/// Body of an approval: who approves and the evidence they cite. The approver's role
+/// and trust score are looked up server-side.
+#[derive(Debug, Deserialize)]
+struct ApprovalSubmission {
+    /// Defaults to the caller; admins may approve on behalf of another agent.
+    #[serde(default)]
+    agent_id: Option<String>,
+    #[serde(default)]
+    evidence_tags: Vec<String>,
+    #[serde(default)]
+    evidence_references: Vec<String>,
+}
+
+async fn register_approval(
+    Path(token): Path<String>,
+    State(routes): State<ApiRoutes>,
+    identity: RequestIdentity,
+    submission: Result<Json<ApprovalSubmission>, JsonRejection>,
+) -> Result<Json<ApprovalResponse>, Problem> {
+    routes.record_request("workflow_approval_register");
+    let Json(submission) = submission?;
+    if !identity.is_authenticated() {
+        return Err(Problem::new(
+            ErrorCode::Unauthenticated,
+            "approvals require an authenticated approver",
+        ));
+    }
+    let agent_id = submission
+        .agent_id
+        .unwrap_or_else(|| identity.subject.clone());
+    if !identity.may_act_as(&agent_id) {
+        return Err(Problem::new(
+            ErrorCode::Forbidden,
+            format!(
+                "{} may not approve on behalf of {}",
+                identity.subject, agent_id
+            ),
+        ));
+    }
+    let engine = attached_engine(&routes)?;
+    let pending = engine
+        .pending_approvals()
+        .into_iter()
+        .find(|pending| pending.token == token)
+        .ok_or_else(|| {
+            Problem::new(
+                ErrorCode::NotFound,
+                format!("approval token not found: {token}"),
+            )
+        })?;
+    // The standing lookup may retire expired overrides, which writes the ledger.
+    let lookup = engine.clone();
+    let approval = tokio::task::spawn_blocking(move || {
+        lookup.approval_from(
+            &agent_id,
+            submission.evidence_tags,
+            submission.evidence_references,
+        )
+    })
+    .await
+    .map_err(|err| Problem::new(ErrorCode::Internal, err.to_string()))?
+    .map_err(|err| Problem::new(ErrorCode::Forbidden, err))?;
+    pending
+        .validate(&approval)
+        .map_err(|err| Problem::new(ErrorCode::Forbidden, err))?;
+
+    // Registering resumes the workflow, which runs its remaining stages.
+    let state = tokio::task::spawn_blocking(move || engine.register_approval(&token, approval))
+        .await
+        .map_err(|err| Problem::new(ErrorCode::Internal, err.to_string()))?
+        .map_err(|err| Problem::new(ErrorCode::Conflict, err))?;
+    Ok(Json(ApprovalResponse {
+        workflow_id: pending.workflow_id,
+        stage_id: pending.stage_id,
+        state,
+    }))
+}
+
 async fn websocket(
     ws: WebSocketUpgrade,
     Path(channel): Path<String>,
     State(routes): State<ApiRoutes>,
-) -> Result<impl IntoResponse, ApiError> {
+) -> Result<impl IntoResponse, Problem> {
     routes.record_request("websocket");
     let plan = routes.route(
         Protocol::WebSocket,
@@ -225,36 +784,6 @@ fn websocket_bootstrap_frame(plan: &RoutePlan) -> String {
     .to_string()
 }
 
-#[derive(Debug)]
-struct ApiError {
-    status: StatusCode,
-    message: Cow<'static, str>,
-}
-
-impl ApiError {
-    fn new(status: StatusCode, message: impl Into<Cow<'static, str>>) -> Self {
-        Self {
-            status,
-            message: message.into(),
-        }
-    }
-}
-
-impl From<Error> for ApiError {
-    fn from(err: Error) -> Self {
-        ApiError::new(StatusCode::BAD_REQUEST, format!("routing error: {err}"))
-    }
-}
-
-impl IntoResponse for ApiError {
-    fn into_response(self) -> Response {
-        let body = Json(json!({
-            "error": self.message,
-        }));
-        (self.status, body).into_response()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -277,16 +806,26 @@ mod tests {
     }
 
     async fn post_json(router: &Router, path: &str, payload: Value) -> (StatusCode, Value) {
+        post_json_as(router, path, RequestIdentity::anonymous(), payload).await
+    }
+
+    /// Post as `identity`, as if [`crate::AuthLayer`] had authenticated the caller.
+    async fn post_json_as(
+        router: &Router,
+        path: &str,
+        identity: RequestIdentity,
+        payload: Value,
+    ) -> (StatusCode, Value) {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(payload.to_string()))
+            .expect("valid request");
+        request.extensions_mut().insert(identity);
         let response = router
             .clone()
-            .oneshot(
-                Request::builder()
-                    .method("POST")
-                    .uri(path)
-                    .header(header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(payload.to_string()))
-                    .expect("valid request"),
-            )
+            .oneshot(request)
             .await
             .expect("router responds");
         let status = response.status();
@@ -304,6 +843,7 @@ mod tests {
     async fn readiness_endpoint_reflects_state() {
         let router = build_test_router();
         let response = router
+            .clone()
             .oneshot(
                 Request::builder()
                     .uri("/ready")
@@ -321,6 +861,94 @@ mod tests {
             .to_bytes();
         let payload: Value = serde_json::from_slice(&bytes).expect("ready payload");
         assert_eq!(payload["ready"], Value::Bool(true));
+
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .uri("/readyz")
+                    .body(Body::empty())
+                    .expect("readyz request"),
+            )
+            .await
+            .expect("readyz response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        let payload: Value = serde_json::from_slice(&bytes).expect("readyz payload");
+        assert_eq!(payload["status"], json!("ready"));
+        assert_eq!(payload["checks"][1]["name"], json!("storage"));
+        assert_eq!(payload["checks"][1]["status"], json!("up"));
+
+        let state = ApiState::for_tests(ProgrammableRouter::default());
+        state.mark_ready();
+        state.begin_drain();
+        let response = build_http_router(ApiRoutes::new(state))
+            .oneshot(
+                Request::builder()
+                    .uri("/readyz")
+                    .body(Body::empty())
+                    .expect("readyz request"),
+            )
+            .await
+            .expect("readyz response");
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn errors_use_problem_json_from_the_catalog() {
+        let router = build_test_router();
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/inference")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from("{\"prompt\":"))
+                    .expect("inference request"),
+            )
+            .await
+            .expect("inference response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            crate::PROBLEM_CONTENT_TYPE
+        );
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        let problem: Value = serde_json::from_slice(&bytes).expect("problem body");
+        assert_eq!(problem["code"], json!("invalid_request"));
+
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .uri(ERROR_CATALOG_PATH)
+                    .body(Body::empty())
+                    .expect("catalog request"),
+            )
+            .await
+            .expect("catalog response");
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        let catalog: Value = serde_json::from_slice(&bytes).expect("catalog body");
+        let documented = catalog["errors"]
+            .as_array()
+            .expect("errors")
+            .iter()
+            .any(|entry| entry["type"] == problem["type"]);
+        assert!(documented);
     }
 
     #[tokio::test]
@@ -364,6 +992,224 @@ mod tests {
         assert_eq!(payload["plan"]["targets"], json!(["retrieval"]));
     }
 
+    #[tokio::test]
+    async fn workflow_graph_route_renders_loaded_workflow() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let engine = std::sync::Arc::new(noa_workflow::WorkflowEngine::with_context(
+            noa_workflow::ConfigContext::isolated().with_workflow_root(dir.path()),
+        ));
+        engine
+            .load_workflow(noa_workflow::Workflow {
+                name: "graph-demo".into(),
+                version: "1.0".into(),
+                stages: vec![
+                    noa_workflow::Stage {
+                        name: "build".into(),
+                        stage_type: noa_workflow::StageType::Sequential,
+                        depends_on: vec![],
+                        tasks: vec![],
+                        compensation: vec![],
+                        cache: noa_workflow::StageCacheControl::default(),
+                        contract: noa_workflow::StageContract::default(),
+                    },
+                    noa_workflow::Stage {
+                        name: "test".into(),
+                        stage_type: noa_workflow::StageType::Sequential,
+                        depends_on: vec!["build".into()],
+                        tasks: vec![],
+                        compensation: vec![],
+                        cache: noa_workflow::StageCacheControl::default(),
+                        contract: noa_workflow::StageContract::default(),
+                    },
+                ],
+            })
+            .expect("workflow loads");
+
+        let state = ApiState::for_tests(ProgrammableRouter::default());
+        state.set_workflow_engine(engine);
+        let router = build_http_router(ApiRoutes::new(state));
+
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/workflows/graph-demo/graph?format=dot")
+                    .body(Body::empty())
+                    .expect("graph request"),
+            )
+            .await
+            .expect("graph response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        let body = String::from_utf8(bytes.to_vec()).expect("utf8 body");
+        assert!(body.contains("\"build\" -> \"test\";"));
+
+        let missing = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/workflows/unknown/graph")
+                    .body(Body::empty())
+                    .expect("graph request"),
+            )
+            .await
+            .expect("graph response");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+
+        let tools = router
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/tools")
+                    .body(Body::empty())
+                    .expect("tools request"),
+            )
+            .await
+            .expect("tools response");
+        assert_eq!(tools.status(), StatusCode::OK);
+        let bytes = tools
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).expect("json body");
+        assert!(body["tools"]
+            .as_array()
+            .expect("tools array")
+            .iter()
+            .any(|tool| tool["capability"] == "workflow.taskDispatch"));
+    }
+
+    #[tokio::test]
+    async fn approval_routes_list_and_resume_paused_workflow() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let engine = std::sync::Arc::new(noa_workflow::WorkflowEngine::with_context(
+            noa_workflow::ConfigContext::isolated().with_workflow_root(dir.path()),
+        ));
+        engine
+            .load_workflow(noa_workflow::Workflow {
+                name: "approval-demo".into(),
+                version: "1.0".into(),
+                stages: vec![noa_workflow::Stage {
+                    name: "sign-off".into(),
+                    stage_type: noa_workflow::StageType::Approval(
+                        noa_workflow::AgentApprovalRequirement {
+                            role: "release-manager".into(),
+                            minimum_trust_score: 0.5,
+                            required_evidence_tags: vec![],
+                        },
+                    ),
+                    depends_on: vec![],
+                    tasks: vec![],
+                    compensation: vec![],
+                    cache: noa_workflow::StageCacheControl::default(),
+                    contract: noa_workflow::StageContract::default(),
+                }],
+            })
+            .expect("workflow loads");
+        engine.execute("approval-demo").expect("workflow pauses");
+
+        let state = ApiState::for_tests(ProgrammableRouter::default());
+        state.set_workflow_engine(engine.clone());
+        let router = build_http_router(ApiRoutes::new(state));
+
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/workflows/approvals")
+                    .body(Body::empty())
+                    .expect("approvals request"),
+            )
+            .await
+            .expect("approvals response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        let pending: Value = serde_json::from_slice(&bytes).expect("json body");
+        assert_eq!(pending["total"], json!(1));
+        assert_eq!(pending["items"][0]["stage_id"], json!("sign-off"));
+        let token = pending["items"][0]["token"]
+            .as_str()
+            .expect("token")
+            .to_string();
+
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/workflows?state=Paused&limit=1")
+                    .body(Body::empty())
+                    .expect("workflows request"),
+            )
+            .await
+            .expect("workflows response");
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        let workflows: Value = serde_json::from_slice(&bytes).expect("json body");
+        assert_eq!(workflows["items"][0]["workflow_id"], json!("approval-demo"));
+        assert!(workflows.get("next_cursor").is_none());
+
+        let registry = engine.agent_registry();
+        for (agent_id, role) in [
+            ("reviewer", "developer"),
+            ("release-bot", "release-manager"),
+        ] {
+            let mut metadata = noa_agents::AgentMetadata::minimal(
+                agent_id.into(),
+                agent_id.into(),
+                noa_agents::AgentCategory::Other,
+            );
+            metadata.role = role.into();
+            registry
+                .upsert_metadata(metadata)
+                .expect("register approver");
+        }
+        let caller = |subject: &str| RequestIdentity {
+            subject: subject.into(),
+            method: crate::AuthMethod::ApiKey,
+            scopes: vec![crate::API_TOKEN_SCOPE.into()],
+        };
+        let path = format!("/v1/workflows/approvals/{token}");
+
+        let (status, _) = post_json(&router, &path, json!({})).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // Role and trust claimed in the body are ignored in favour of the registry.
+        let claimed = json!({ "role": "release-manager", "trust_score": 1.0 });
+        let (status, _) = post_json_as(&router, &path, caller("reviewer"), claimed).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let on_behalf = json!({ "agent_id": "release-bot" });
+        let (status, _) = post_json_as(&router, &path, caller("reviewer"), on_behalf).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, payload) =
+            post_json_as(&router, &path, caller("release-bot"), json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(payload["state"], json!("Completed"));
+        assert_eq!(
+            engine.get_state("approval-demo"),
+            Some(noa_workflow::WorkflowState::Completed)
+        );
+
+        let (status, _) = post_json_as(&router, &path, caller("release-bot"), json!({})).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
     #[tokio::test]
     async fn websocket_frame_includes_mode_and_targets() {
         let mut plan = RoutePlan::new(Protocol::WebSocket);
@@ -375,4 +1221,269 @@ mod tests {
         assert_eq!(parsed["targets"], json!(["agent-activity"]));
         assert_eq!(parsed["mode"], Value::String("multiplex".into()));
     }
+
+    #[tokio::test]
+    async fn submitted_workflows_are_validated_before_loading() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let engine = std::sync::Arc::new(noa_workflow::WorkflowEngine::with_context(
+            noa_workflow::ConfigContext::isolated().with_workflow_root(dir.path()),
+        ));
+        let state = ApiState::for_tests(ProgrammableRouter::default());
+        state.set_workflow_engine(engine.clone());
+        let router = build_http_router(ApiRoutes::new(state));
+        let submit = |depends_on: &str| {
+            let body = json!({
+                "name": "composed",
+                "version": "1.0",
+                "stages": [
+                    { "name": "build", "stage_type": "sequential", "depends_on": [], "tasks": [] },
+                    { "name": "test", "stage_type": "parallel", "depends_on": [depends_on], "tasks": [] }
+                ]
+            });
+            Request::builder()
+                .method("POST")
+                .uri("/v1/workflows")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .expect("submit request")
+        };
+
+        let rejected = router
+            .clone()
+            .oneshot(submit("deploy"))
+            .await
+            .expect("submit response");
+        assert_eq!(rejected.status(), StatusCode::BAD_REQUEST);
+        assert!(engine.get_workflow("composed").is_none());
+
+        let created = router
+            .oneshot(submit("build"))
+            .await
+            .expect("submit response");
+        assert_eq!(created.status(), StatusCode::CREATED);
+        let bytes = created
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).expect("json body");
+        assert_eq!(body["workflow_id"], json!("composed"));
+        assert_eq!(body["stages"], json!(2));
+        assert!(engine.get_workflow("composed").is_some());
+    }
+
+    #[tokio::test]
+    async fn pipeline_evidence_route_serves_gzipped_bundle() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let state = ApiState::for_tests(ProgrammableRouter::default());
+        let router = build_http_router(ApiRoutes::new(state.clone()));
+        let evidence = |uri: String| {
+            Request::builder()
+                .uri(uri)
+                .body(Body::empty())
+                .expect("evidence request")
+        };
+
+        let detached = router
+            .clone()
+            .oneshot(evidence("/v1/pipelines/any/evidence".into()))
+            .await
+            .expect("evidence response");
+        assert_eq!(detached.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let cicd = std::sync::Arc::new(noa_cicd::CICDSystem::with_context(
+            noa_cicd::ConfigContext::isolated().with_workflow_root(dir.path()),
+        ));
+        cicd.configure_workspace_root(dir.path());
+        let pipeline_id = cicd
+            .trigger_pipeline("release".into(), "abc123".into())
+            .expect("pipeline triggers");
+        state.set_cicd_system(cicd);
+
+        let response = router
+            .clone()
+            .oneshot(evidence(format!("/v1/pipelines/{pipeline_id}/evidence")))
+            .await
+            .expect("evidence response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/gzip");
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        assert_eq!(&bytes[..2], &[0x1f, 0x8b]);
+
+        let missing = router
+            .oneshot(evidence("/v1/pipelines/unknown/evidence".into()))
+            .await
+            .expect("evidence response");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn pipeline_webhook_triggers_from_gitlab_push() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let state = ApiState::for_tests(ProgrammableRouter::default());
+        let router = build_http_router(ApiRoutes::new(state.clone()));
+        let cicd = std::sync::Arc::new(noa_cicd::CICDSystem::with_context(
+            noa_cicd::ConfigContext::isolated()
+                .with_workflow_root(dir.path())
+                .with_var(noa_cicd::webhook::WEBHOOK_SECRET_ENV, "hook-token"),
+        ));
+        cicd.configure_workspace_root(dir.path());
+        state.set_cicd_system(cicd.clone());
+        let push = |token: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/pipelines/webhook?pipeline=ark")
+                .header("x-gitlab-event", "Push Hook")
+                .header("x-gitlab-token", token)
+                .body(Body::from(
+                    json!({
+                        "ref": "refs/heads/main",
+                        "checkout_sha": "c0ffee",
+                        "project": { "path_with_namespace": "noa/ark" },
+                    })
+                    .to_string(),
+                ))
+                .expect("webhook request")
+        };
+
+        let rejected = router
+            .clone()
+            .oneshot(push("wrong"))
+            .await
+            .expect("webhook response");
+        assert_eq!(rejected.status(), StatusCode::UNAUTHORIZED);
+
+        let response = router
+            .oneshot(push("hook-token"))
+            .await
+            .expect("webhook response");
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).expect("json body");
+        assert_eq!(body["status"], "triggered");
+        let pipeline = cicd
+            .get_pipeline(body["pipeline_id"].as_str().unwrap())
+            .expect("pipeline recorded");
+        assert_eq!(pipeline.name, "ark");
+        assert_eq!(pipeline.source.unwrap().branch, "main");
+    }
+
+    #[tokio::test]
+    async fn docs_routes_search_and_serve_readable_collections() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(dir.path().join("docs")).expect("docs dir");
+        std::fs::write(
+            dir.path().join("docs/README.md"),
+            "# Operators\n\nRestart the gateway after upgrades.\n",
+        )
+        .expect("doc written");
+        let state = ApiState::for_tests(ProgrammableRouter::default());
+        let docs = DocsLibrary::for_workspace(dir.path()).expect("docs indexed");
+        state.set_docs_library(std::sync::Arc::new(docs));
+        let router = build_http_router(ApiRoutes::new(state));
+        let get = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .body(Body::empty())
+                .expect("docs request")
+        };
+
+        let search = router
+            .clone()
+            .oneshot(get("/v1/docs/search?q=gateway"))
+            .await
+            .expect("search response");
+        assert_eq!(search.status(), StatusCode::OK);
+        let bytes = search
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).expect("search json");
+        assert_eq!(body["hits"][0]["path"], "README.md");
+
+        let file = router
+            .clone()
+            .oneshot(get("/v1/docs/files/docs/README.md"))
+            .await
+            .expect("file response");
+        assert_eq!(file.status(), StatusCode::OK);
+        assert_eq!(
+            file.headers()[header::CONTENT_TYPE],
+            "text/markdown; charset=utf-8"
+        );
+
+        let report = router
+            .oneshot(get("/v1/docs/files/reports/p-1/footprint.md"))
+            .await
+            .expect("report response");
+        assert_eq!(report.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn metrics_history_route_queries_attached_store() {
+        use noa_core::metrics::timeseries::{Sample, TimeSeriesStore};
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let store = TimeSeriesStore::open(dir.path()).expect("store opened");
+        store
+            .append_all(&[
+                Sample::new("pipeline.succeeded", 1_000, 1.0).with_label("pipeline", "build"),
+                Sample::new("pipeline.succeeded", 2_000, 0.0).with_label("pipeline", "build"),
+                Sample::new("pipeline.succeeded", 2_000, 1.0).with_label("pipeline", "docs"),
+            ])
+            .expect("samples appended");
+        noa_core::metrics::attach_store(std::sync::Arc::new(store));
+        let router = build_http_router(ApiRoutes::new(ApiState::for_tests(
+            ProgrammableRouter::default(),
+        )));
+
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/metrics/history?series=pipeline.succeeded&start_ms=0&end_ms=5000&resolution=raw&pipeline=build")
+                    .body(Body::empty())
+                    .expect("history request"),
+            )
+            .await
+            .expect("history response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("read bytes")
+            .to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).expect("history json");
+        assert_eq!(body["series"].as_array().map(Vec::len), Some(1));
+        assert_eq!(body["series"][0]["labels"]["pipeline"], "build");
+        assert_eq!(
+            body["series"][0]["points"].as_array().map(Vec::len),
+            Some(2)
+        );
+
+        let invalid = router
+            .oneshot(
+                Request::builder()
+                    .uri("/v1/metrics/history?series=pipeline.succeeded&resolution=1d")
+                    .body(Body::empty())
+                    .expect("history request"),
+            )
+            .await
+            .expect("history response");
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+    }
 }
@@ -0,0 +1,134 @@
+//! Per-request deadline that can be changed while the server is running.
+//!
+//! The deadline covers the time until a handler produces its response head; streaming
+//! bodies, such as gRPC reflection streams, are not cut off once they have started.
+
+use crate::problem::{ErrorCode, Problem};
+use axum::body::Body;
+use axum::http::{Request, Response};
+use axum::response::IntoResponse;
+use futures::future::BoxFuture;
+use metrics::counter;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower::{Layer, Service};
+
+/// Tower layer answering `504 Gateway Timeout` when a handler overruns its deadline.
+/// A zero timeout disables the deadline.
+#[derive(Debug, Clone)]
+pub struct RequestTimeoutLayer {
+    timeout_ms: Arc<AtomicU64>,
+}
+
+impl RequestTimeoutLayer {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout_ms: Arc::new(AtomicU64::new(millis(timeout))),
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.load(Ordering::Relaxed))
+    }
+
+    /// Applies to requests that start after the call; in-flight requests keep theirs.
+    pub fn set_timeout(&self, timeout: Duration) {
+        self.timeout_ms.store(millis(timeout), Ordering::Relaxed);
+    }
+}
+
+fn millis(timeout: Duration) -> u64 {
+    u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX)
+}
+
+impl<S> Layer<S> for RequestTimeoutLayer {
+    type Service = RequestTimeoutService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestTimeoutService {
+            inner,
+            timeout_ms: self.timeout_ms.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestTimeoutService<S> {
+    inner: S,
+    timeout_ms: Arc<AtomicU64>,
+}
+
+impl<S, B> Service<Request<B>> for RequestTimeoutService<S>
+where
+    S: Service<Request<B>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let timeout = Duration::from_millis(self.timeout_ms.load(Ordering::Relaxed));
+        let path = request.uri().path().to_string();
+        let response = inner.call(request);
+        if timeout.is_zero() {
+            return Box::pin(response);
+        }
+        Box::pin(async move {
+            match tokio::time::timeout(timeout, response).await {
+                Ok(result) => result,
+                Err(_) => {
+                    counter!("api_request_timeouts_total", 1);
+                    tracing::warn!(%path, ?timeout, "request exceeded its deadline");
+                    Ok(Problem::new(
+                        ErrorCode::Timeout,
+                        format!("no response within {}ms", millis(timeout)),
+                    )
+                    .with_extension("timeout_ms", millis(timeout))
+                    .into_response())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        "done"
+    }
+
+    #[tokio::test]
+    async fn deadline_changes_apply_to_new_requests() {
+        let layer = RequestTimeoutLayer::new(Duration::from_millis(20));
+        let router = Router::new().route("/slow", get(slow)).layer(layer.clone());
+        let request = || Request::get("/slow").body(Body::empty()).unwrap();
+
+        let response = router.clone().oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        layer.set_timeout(Duration::from_secs(5));
+        let response = router.clone().oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        layer.set_timeout(Duration::ZERO);
+        let response = router.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
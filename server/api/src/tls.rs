@@ -0,0 +1,247 @@
+//! Optional TLS termination for the API listener.
+//!
+//! Certificates come from PEM files or, for local development, a self-signed
+//! certificate generated at startup. ALPN offers `h2` and `http/1.1`, so gRPC and REST
+//! clients share the encrypted port. Certificate files are polled and swapped into the
+//! running listener when they change; handshakes already in progress keep the old one.
+
+use anyhow::{Context, Result};
+use axum_server::tls_rustls::RustlsConfig;
+use metrics::counter;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+/// Where the listener's certificate comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlsConfig {
+    /// PEM certificate chain and private key, reloaded when either file changes.
+    Pem {
+        cert_path: PathBuf,
+        key_path: PathBuf,
+    },
+    /// Certificate generated at startup for the given names. Development only.
+    SelfSigned { subject_alt_names: Vec<String> },
+}
+
+impl TlsConfig {
+    pub fn pem(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        TlsConfig::Pem {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+        }
+    }
+
+    /// Self-signed certificate valid for `localhost` and `127.0.0.1`.
+    pub fn self_signed() -> Self {
+        TlsConfig::SelfSigned {
+            subject_alt_names: vec!["localhost".into(), "127.0.0.1".into()],
+        }
+    }
+
+    /// Certificate and key as PEM.
+    fn read_pem(&self) -> Result<(Vec<u8>, Vec<u8>)> {
+        match self {
+            TlsConfig::Pem {
+                cert_path,
+                key_path,
+            } => {
+                let cert = fs::read(cert_path)
+                    .with_context(|| format!("failed to read {}", cert_path.display()))?;
+                let key = fs::read(key_path)
+                    .with_context(|| format!("failed to read {}", key_path.display()))?;
+                Ok((cert, key))
+            }
+            TlsConfig::SelfSigned { subject_alt_names } => {
+                let generated = rcgen::generate_simple_self_signed(subject_alt_names.clone())
+                    .context("failed to generate self-signed certificate")?;
+                Ok((
+                    generated.cert.pem().into_bytes(),
+                    generated.key_pair.serialize_pem().into_bytes(),
+                ))
+            }
+        }
+    }
+
+    /// Modification times of the certificate files; `None` for generated certificates.
+    fn stamp(&self) -> Option<(SystemTime, SystemTime)> {
+        let TlsConfig::Pem {
+            cert_path,
+            key_path,
+        } = self
+        else {
+            return None;
+        };
+        let modified = |path: &PathBuf| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        Some((modified(cert_path)?, modified(key_path)?))
+    }
+}
+
+/// The rustls configuration served by the listener, kept in step with its source.
+#[derive(Debug, Clone)]
+pub(crate) struct ServerTls {
+    rustls: RustlsConfig,
+    source: Arc<Mutex<TlsConfig>>,
+}
+
+impl ServerTls {
+    pub(crate) async fn load(source: TlsConfig) -> Result<Self> {
+        // Several rustls providers are linked in, so rustls cannot pick one on its own.
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+        let (cert, key) = source.read_pem()?;
+        let rustls = RustlsConfig::from_pem(cert, key)
+            .await
+            .context("invalid TLS certificate or key")?;
+        if matches!(source, TlsConfig::SelfSigned { .. }) {
+            warn!("serving a self-signed certificate; use only for development");
+        }
+        Ok(Self {
+            rustls,
+            source: Arc::new(Mutex::new(source)),
+        })
+    }
+
+    pub(crate) fn rustls(&self) -> RustlsConfig {
+        self.rustls.clone()
+    }
+
+    /// Switch to `source`, keeping the current certificate if it cannot be loaded.
+    pub(crate) async fn replace(&self, source: TlsConfig) -> Result<()> {
+        self.reload(&source).await?;
+        *self.source.lock().expect("tls source poisoned") = source;
+        Ok(())
+    }
+
+    async fn reload(&self, source: &TlsConfig) -> Result<()> {
+        let (cert, key) = source.read_pem()?;
+        let result = self
+            .rustls
+            .reload_from_pem(cert, key)
+            .await
+            .context("invalid TLS certificate or key");
+        let outcome = if result.is_ok() {
+            "applied"
+        } else {
+            "rejected"
+        };
+        counter!("api_tls_reloads_total", 1, "outcome" => outcome);
+        result
+    }
+
+    /// Poll the certificate files and reload whenever their modification times change.
+    pub(crate) fn watch(&self, interval: Duration) -> JoinHandle<()> {
+        let tls = self.clone();
+        tokio::spawn(async move {
+            let current = || tls.source.lock().expect("tls source poisoned").clone();
+            let mut last = {
+                let source = current();
+                let stamp = source.stamp();
+                (source, stamp)
+            };
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let source = current();
+                let stamp = source.stamp();
+                if last.0 != source {
+                    // Replaced through the config file, which already loaded it.
+                    last = (source, stamp);
+                    continue;
+                }
+                if stamp.is_none() || stamp == last.1 {
+                    continue;
+                }
+                match tls.reload(&source).await {
+                    Ok(()) => info!(?source, "reloaded TLS certificate"),
+                    Err(err) => {
+                        warn!(error = %format!("{err:#}"), "keeping current TLS certificate")
+                    }
+                }
+                last = (source, stamp);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use axum::Router;
+    use reqwest::tls::TlsInfo;
+
+    fn write_certificate(dir: &std::path::Path, name: &str) -> TlsConfig {
+        let (cert, key) = TlsConfig::self_signed().read_pem().unwrap();
+        let source = TlsConfig::pem(
+            dir.join(format!("{name}.pem")),
+            dir.join(format!("{name}-key.pem")),
+        );
+        if let TlsConfig::Pem {
+            cert_path,
+            key_path,
+        } = &source
+        {
+            fs::write(cert_path, cert).unwrap();
+            fs::write(key_path, key).unwrap();
+        }
+        source
+    }
+
+    async fn peer_certificate(client: &reqwest::Client, url: &str) -> Vec<u8> {
+        let response = client.get(url).send().await.expect("https response");
+        assert_eq!(response.version(), reqwest::Version::HTTP_2);
+        response
+            .extensions()
+            .get::<TlsInfo>()
+            .and_then(TlsInfo::peer_certificate)
+            .expect("peer certificate")
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn negotiates_h2_and_picks_up_rotated_certificates() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let source = write_certificate(dir.path(), "server");
+        let tls = ServerTls::load(source.clone()).await.expect("tls loads");
+        let watcher = tls.watch(Duration::from_millis(20));
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/healthz", get(|| async { "ok" }));
+        tokio::spawn(
+            axum_server::from_tcp_rustls(listener, tls.rustls()).serve(app.into_make_service()),
+        );
+
+        let url = format!("https://localhost:{}/healthz", addr.port());
+        let client = || {
+            reqwest::Client::builder()
+                .danger_accept_invalid_certs(true)
+                .resolve("localhost", addr)
+                .tls_info(true)
+                .build()
+                .unwrap()
+        };
+        let before = peer_certificate(&client(), &url).await;
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let rotated = write_certificate(dir.path(), "server");
+        assert_eq!(rotated, source);
+        let mut after = before.clone();
+        for _ in 0..50 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            after = peer_certificate(&client(), &url).await;
+            if after != before {
+                break;
+            }
+        }
+        assert_ne!(after, before, "rotated certificate is served");
+        watcher.abort();
+    }
+}
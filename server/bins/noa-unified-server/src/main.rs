@@ -1,8 +1,12 @@
 use anyhow::Context;
 use clap::Parser;
-use noa_api::{ApiConfig, ApiServer};
+use noa_api::{ApiConfig, ApiServer, DocsLibrary};
+use noa_core::metrics::timeseries::TimeSeriesStore;
 use noa_gateway::bootstrap_gateway;
 use noa_orchestrator::UnifiedOrchestrator;
+use noa_workflow::{ConfigContext, WorkflowEngine};
+use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::runtime::Builder;
 use tracing::{info, warn};
 
@@ -17,6 +21,21 @@ struct Cli {
 
     #[arg(long, default_value_t = Cli::default_workers())]
     workers: usize,
+
+    /// Workspace whose docs/, pipeline reports, and evidence bundles are served
+    /// under /v1/docs.
+    #[arg(long)]
+    docs_root: Option<PathBuf>,
+
+    /// Directory of the local metrics time-series store served under
+    /// /v1/metrics/history.
+    #[arg(long)]
+    metrics_store: Option<PathBuf>,
+
+    /// Workflow root of the engine served under /v1/workflows. Defaults to
+    /// NOA_WORKFLOW_ROOT.
+    #[arg(long)]
+    workflow_root: Option<PathBuf>,
 }
 
 impl Cli {
@@ -35,10 +54,36 @@ fn init_tracing() {
     }
 }
 
+fn build_api_server(cli: &Cli) -> anyhow::Result<ApiServer> {
+    let mut context = ConfigContext::from_env();
+    if let Some(root) = &cli.workflow_root {
+        context = context.with_workflow_root(root);
+    }
+    let mut server = ApiServer::new(ApiConfig {
+        host: cli.host.clone(),
+        port: cli.port,
+        ..ApiConfig::default()
+    })
+    .context("failed to initialise API server")?
+    .with_workflow_engine(Arc::new(WorkflowEngine::with_context(context)));
+    if let Some(root) = &cli.docs_root {
+        let docs = DocsLibrary::for_workspace(root)
+            .with_context(|| format!("failed to index docs under {}", root.display()))?;
+        server = server.with_docs_library(Arc::new(docs));
+    }
+    Ok(server)
+}
+
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
     init_tracing();
 
+    if let Some(dir) = &cli.metrics_store {
+        let store = TimeSeriesStore::open(dir)
+            .with_context(|| format!("failed to open metrics store {}", dir.display()))?;
+        noa_core::metrics::attach_store(Arc::new(store));
+    }
+
     let orchestrator = UnifiedOrchestrator::default();
     let decision = orchestrator.evaluate_scaling();
     info!(?decision, "orchestrator ready");
@@ -53,11 +98,7 @@ fn main() -> anyhow::Result<()> {
         .context("failed to build tokio runtime")?;
 
     runtime.block_on(async move {
-        let server = ApiServer::new(ApiConfig {
-            host: cli.host,
-            port: cli.port,
-        })
-        .context("failed to initialise API server")?;
+        let server = build_api_server(&cli)?;
 
         info!("starting Axum + Tonic API server");
 
@@ -65,3 +106,28 @@ fn main() -> anyhow::Result<()> {
         server.run().await
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bootstrapped_servers_serve_workflows_from_the_workflow_root() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cli = Cli::parse_from([
+            "noa-unified-server",
+            "--workflow-root",
+            dir.path().to_str().expect("utf-8 path"),
+        ]);
+
+        let server = build_api_server(&cli).expect("server builds");
+        let engine = server
+            .state()
+            .workflow_engine()
+            .expect("workflow engine attached");
+        assert_eq!(
+            engine.instrumentation().context().workflow_root(),
+            dir.path()
+        );
+    }
+}
@@ -2,17 +2,22 @@
 //!
 //! The manager wraps a reqwest client with sensible timeouts and exposes
 //! helpers to push reverse proxy routes or reload the active configuration.
+//! Routes carrying an `id` are tagged with Caddy's `@id` so they can be read,
+//! replaced, or removed later through the `/id/<id>` endpoints.
 
 use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
-use reqwest::{Client, StatusCode, Url};
+use reqwest::{Client, Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 /// Configuration for a reverse proxy route that proxies a domain to one or
 /// more upstream services.
 #[derive(Debug, Clone)]
 pub struct ReverseProxyRoute {
+    /// Caddy `@id` of the route; required to update or remove it in place.
+    pub id: Option<String>,
     pub domain: String,
     pub upstreams: Vec<String>,
     pub health_probe: Option<HealthProbe>,
@@ -115,6 +120,9 @@ impl ReverseProxyRoute {
             "handle": handles,
             "terminal": true
         });
+        if let Some(id) = &self.id {
+            route["@id"] = json!(id);
+        }
 
         if let Some(limit) = &self.rate_limit {
             route["rate_limits"] = json!([{
@@ -132,6 +140,7 @@ impl ReverseProxyRoute {
 impl Default for ReverseProxyRoute {
     fn default() -> Self {
         Self {
+            id: None,
             domain: "noa-ark-os.com".to_string(),
             upstreams: vec!["localhost:8080".to_string()],
             health_probe: Some(HealthProbe::default()),
@@ -178,6 +187,13 @@ impl Default for RateLimitConfig {
     }
 }
 
+/// Status and body returned by the admin API for a route change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdminResponse {
+    pub status: u16,
+    pub body: String,
+}
+
 /// Client wrapper for the Caddy admin API.
 pub struct CaddyManager {
     admin_endpoint: Url,
@@ -224,6 +240,62 @@ impl CaddyManager {
         Ok(())
     }
 
+    /// Current configuration of the route tagged `id`, or `None` if Caddy has none.
+    pub async fn route(&self, id: &str) -> Result<Option<Value>> {
+        let response = self
+            .client
+            .get(self.id_url(id)?)
+            .send()
+            .await
+            .context("failed to read route from Caddy")?;
+        match response.status() {
+            StatusCode::OK => Ok(Some(
+                response
+                    .json()
+                    .await
+                    .context("invalid route returned by Caddy")?,
+            )),
+            StatusCode::NOT_FOUND => Ok(None),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(anyhow!(
+                    "caddy route lookup failed with status {status}: {body}"
+                ))
+            }
+        }
+    }
+
+    /// Replace the route with the same `id`, or append it when Caddy has none yet.
+    pub async fn upsert_route(&self, route: &ReverseProxyRoute) -> Result<AdminResponse> {
+        route.validate()?;
+        let id = route
+            .id
+            .as_deref()
+            .ok_or_else(|| anyhow!("route needs an id to be updated in place"))?;
+        let payload = route.as_caddy_json();
+        if self.route(id).await?.is_some() {
+            return self
+                .send(Method::PATCH, self.id_url(id)?, Some(&payload))
+                .await;
+        }
+        let target = self
+            .admin_endpoint
+            .join("/config/apps/http/servers/srv0/routes")
+            .context("invalid admin endpoint URL")?;
+        self.send(Method::POST, target, Some(&payload)).await
+    }
+
+    /// Put `config` back as the route tagged `id`, e.g. a value read with [`Self::route`].
+    pub async fn replace_route_json(&self, id: &str, config: &Value) -> Result<AdminResponse> {
+        self.send(Method::PATCH, self.id_url(id)?, Some(config))
+            .await
+    }
+
+    /// Remove the route tagged `id`.
+    pub async fn delete_route(&self, id: &str) -> Result<AdminResponse> {
+        self.send(Method::DELETE, self.id_url(id)?, None).await
+    }
+
     pub async fn reload(&self) -> Result<()> {
         let target = self
             .admin_endpoint
@@ -246,6 +318,40 @@ impl CaddyManager {
         }
         Ok(())
     }
+
+    fn id_url(&self, id: &str) -> Result<Url> {
+        if id.is_empty() || id.contains('/') {
+            return Err(anyhow!("invalid route id {id:?}"));
+        }
+        self.admin_endpoint
+            .join(&format!("/id/{id}"))
+            .context("invalid admin endpoint URL")
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        target: Url,
+        payload: Option<&Value>,
+    ) -> Result<AdminResponse> {
+        let mut request = self.client.request(method.clone(), target);
+        if let Some(payload) = payload {
+            request = request.json(payload);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("failed to send {method} to Caddy"))?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(anyhow!("caddy rejected {method} (status {status}): {body}"));
+        }
+        Ok(AdminResponse {
+            status: status.as_u16(),
+            body,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -270,6 +376,7 @@ mod tests {
         });
 
         let payload = route.as_caddy_json();
+        assert!(payload.get("@id").is_none());
         assert_eq!(
             payload
                 .get("match")
@@ -287,5 +394,8 @@ mod tests {
                 .and_then(|v| v.as_u64()),
             Some(50)
         );
+
+        route.id = Some("noa-default-staging".into());
+        assert_eq!(route.as_caddy_json()["@id"], "noa-default-staging");
     }
 }
@@ -0,0 +1,348 @@
+//! Priority lanes that keep interactive traffic responsive under batch load.
+//!
+//! Every request is assigned a [`Lane`] by the first [`LaneRule`] matching its caller
+//! identity (`agent:<id>` or `user:<id>`) and route targets. Without a match, user
+//! requests ride the interactive lane and agent requests the standard lane. Each lane has
+//! its own concurrency budget and bounded queue, so a burst of batch calls can only
+//! occupy the batch lane's slots.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Lane {
+    Interactive,
+    Standard,
+    Batch,
+}
+
+impl Lane {
+    pub const ALL: [Lane; 3] = [Lane::Interactive, Lane::Standard, Lane::Batch];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Lane::Interactive => "interactive",
+            Lane::Standard => "standard",
+            Lane::Batch => "batch",
+        }
+    }
+}
+
+/// Concurrency and queueing limits of one lane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaneBudget {
+    /// Requests the lane serves at once.
+    pub max_concurrent: usize,
+    /// Requests allowed to wait for a slot; further requests are rejected.
+    pub max_queued: usize,
+    /// How long a queued request waits before it is rejected.
+    pub queue_timeout_ms: u64,
+}
+
+impl LaneBudget {
+    pub fn default_for(lane: Lane) -> Self {
+        match lane {
+            Lane::Interactive => Self {
+                max_concurrent: 64,
+                max_queued: 128,
+                queue_timeout_ms: 2_000,
+            },
+            Lane::Standard => Self {
+                max_concurrent: 32,
+                max_queued: 256,
+                queue_timeout_ms: 10_000,
+            },
+            Lane::Batch => Self {
+                max_concurrent: 8,
+                max_queued: 1_024,
+                queue_timeout_ms: 60_000,
+            },
+        }
+    }
+}
+
+/// Assigns matching requests to `lane`. A trailing `*` in a pattern matches any suffix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaneRule {
+    pub lane: Lane,
+    /// Caller identity, `agent:<id>` or `user:<id>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    /// Route target such as `analytics` or `inference/Complete`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+}
+
+impl LaneRule {
+    pub fn matches(&self, identity: &str, targets: &[String]) -> bool {
+        self.identity
+            .as_deref()
+            .is_none_or(|pattern| pattern_matches(pattern, identity))
+            && self.route.as_deref().is_none_or(|pattern| {
+                targets
+                    .iter()
+                    .any(|target| pattern_matches(pattern, target))
+            })
+    }
+}
+
+fn pattern_matches(pattern: &str, value: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => pattern == value,
+    }
+}
+
+/// Lane budgets and selection rules, as stored in `gateway_lanes.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LaneConfig {
+    /// Budgets overriding [`LaneBudget::default_for`].
+    #[serde(default)]
+    pub budgets: HashMap<Lane, LaneBudget>,
+    /// Rules tried in order; the first match picks the lane.
+    #[serde(default)]
+    pub rules: Vec<LaneRule>,
+}
+
+impl LaneConfig {
+    pub fn load(path: &Path) -> Result<Self, LaneError> {
+        let raw = std::fs::read(path)
+            .map_err(|err| LaneError::Config(format!("{}: {err}", path.display())))?;
+        serde_json::from_slice(&raw)
+            .map_err(|err| LaneError::Config(format!("{}: {err}", path.display())))
+    }
+
+    pub fn with_rule(mut self, rule: LaneRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn with_budget(mut self, lane: Lane, budget: LaneBudget) -> Self {
+        self.budgets.insert(lane, budget);
+        self
+    }
+
+    pub fn budget(&self, lane: Lane) -> LaneBudget {
+        self.budgets
+            .get(&lane)
+            .copied()
+            .unwrap_or_else(|| LaneBudget::default_for(lane))
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LaneError {
+    #[error("{} lane queue is full", .0.as_str())]
+    QueueFull(Lane),
+    #[error("timed out waiting in the {} lane queue", .0.as_str())]
+    QueueTimeout(Lane),
+    #[error("invalid lane configuration: {0}")]
+    Config(String),
+}
+
+/// Point-in-time view of a lane.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LaneSnapshot {
+    pub lane: Lane,
+    pub max_concurrent: usize,
+    pub in_flight: usize,
+    pub queued: usize,
+    pub admitted: u64,
+    pub rejected: u64,
+}
+
+struct LaneState {
+    budget: LaneBudget,
+    slots: Arc<Semaphore>,
+    queued: AtomicUsize,
+    admitted: AtomicU64,
+    rejected: AtomicU64,
+}
+
+/// Holds a lane slot until dropped.
+#[derive(Debug)]
+pub struct LanePermit {
+    lane: Lane,
+    _slot: OwnedSemaphorePermit,
+}
+
+impl LanePermit {
+    pub fn lane(&self) -> Lane {
+        self.lane
+    }
+}
+
+/// Admission control across the interactive, standard, and batch lanes.
+pub struct PriorityLanes {
+    rules: Vec<LaneRule>,
+    lanes: HashMap<Lane, LaneState>,
+}
+
+impl PriorityLanes {
+    pub fn new(config: LaneConfig) -> Self {
+        let lanes = Lane::ALL
+            .into_iter()
+            .map(|lane| {
+                let budget = config.budget(lane);
+                let state = LaneState {
+                    budget,
+                    slots: Arc::new(Semaphore::new(budget.max_concurrent)),
+                    queued: AtomicUsize::new(0),
+                    admitted: AtomicU64::new(0),
+                    rejected: AtomicU64::new(0),
+                };
+                (lane, state)
+            })
+            .collect();
+        Self {
+            rules: config.rules,
+            lanes,
+        }
+    }
+
+    /// Lane for a caller `identity` whose request routes to `targets`.
+    pub fn select(&self, identity: &str, targets: &[String]) -> Lane {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(identity, targets))
+            .map(|rule| rule.lane)
+            .unwrap_or(if identity.starts_with("agent:") {
+                Lane::Standard
+            } else {
+                Lane::Interactive
+            })
+    }
+
+    /// Wait for a slot in `lane`, queueing within the lane's budget.
+    pub async fn acquire(&self, lane: Lane) -> Result<LanePermit, LaneError> {
+        let state = &self.lanes[&lane];
+        if let Ok(slot) = state.slots.clone().try_acquire_owned() {
+            state.admitted.fetch_add(1, Ordering::Relaxed);
+            return Ok(LanePermit { lane, _slot: slot });
+        }
+
+        let queued = state.queued.fetch_add(1, Ordering::AcqRel);
+        let _queued = QueueGuard(&state.queued);
+        if queued >= state.budget.max_queued {
+            state.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(LaneError::QueueFull(lane));
+        }
+        let timeout = Duration::from_millis(state.budget.queue_timeout_ms);
+        match tokio::time::timeout(timeout, state.slots.clone().acquire_owned()).await {
+            Ok(slot) => {
+                state.admitted.fetch_add(1, Ordering::Relaxed);
+                Ok(LanePermit {
+                    lane,
+                    _slot: slot.expect("lane semaphores are never closed"),
+                })
+            }
+            Err(_) => {
+                state.rejected.fetch_add(1, Ordering::Relaxed);
+                Err(LaneError::QueueTimeout(lane))
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<LaneSnapshot> {
+        Lane::ALL
+            .into_iter()
+            .map(|lane| {
+                let state = &self.lanes[&lane];
+                LaneSnapshot {
+                    lane,
+                    max_concurrent: state.budget.max_concurrent,
+                    in_flight: state
+                        .budget
+                        .max_concurrent
+                        .saturating_sub(state.slots.available_permits()),
+                    queued: state.queued.load(Ordering::Acquire),
+                    admitted: state.admitted.load(Ordering::Relaxed),
+                    rejected: state.rejected.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for PriorityLanes {
+    fn default() -> Self {
+        Self::new(LaneConfig::default())
+    }
+}
+
+/// Leaves the queue when the waiting request is admitted, rejected, or cancelled.
+struct QueueGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueueGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(max_concurrent: usize, max_queued: usize, queue_timeout_ms: u64) -> LaneBudget {
+        LaneBudget {
+            max_concurrent,
+            max_queued,
+            queue_timeout_ms,
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_saturation_leaves_interactive_lane_free() {
+        let lanes = PriorityLanes::new(
+            LaneConfig::default()
+                .with_rule(LaneRule {
+                    lane: Lane::Batch,
+                    identity: Some("agent:analysis-*".into()),
+                    route: None,
+                })
+                .with_rule(LaneRule {
+                    lane: Lane::Batch,
+                    identity: None,
+                    route: Some("analytics".into()),
+                })
+                .with_budget(Lane::Batch, budget(1, 1, 20)),
+        );
+        assert_eq!(lanes.select("agent:analysis-7", &[]), Lane::Batch);
+        assert_eq!(lanes.select("user:3", &["analytics".into()]), Lane::Batch);
+        assert_eq!(lanes.select("agent:planner", &[]), Lane::Standard);
+        assert_eq!(
+            lanes.select("user:3", &["inference/Complete".into()]),
+            Lane::Interactive
+        );
+
+        let running = lanes.acquire(Lane::Batch).await.expect("batch slot");
+        let (queued, overflow) = tokio::join!(lanes.acquire(Lane::Batch), async {
+            tokio::task::yield_now().await;
+            lanes.acquire(Lane::Batch).await
+        });
+        assert_eq!(queued.unwrap_err(), LaneError::QueueTimeout(Lane::Batch));
+        assert_eq!(overflow.unwrap_err(), LaneError::QueueFull(Lane::Batch));
+
+        let interactive = lanes
+            .acquire(Lane::Interactive)
+            .await
+            .expect("interactive lane unaffected by batch load");
+        assert_eq!(interactive.lane(), Lane::Interactive);
+
+        let snapshot = lanes.snapshot();
+        assert_eq!(snapshot[0].in_flight, 1);
+        assert_eq!(snapshot[2].in_flight, 1);
+        assert_eq!(snapshot[2].rejected, 2);
+        assert_eq!(snapshot[2].queued, 0);
+
+        drop(running);
+        lanes.acquire(Lane::Batch).await.expect("slot released");
+    }
+}
@@ -3,32 +3,68 @@
 //! This crate models the high-level orchestration of the application gateway used
 //! across NOA ARK OS. The design emphasises:
 //! - Programmable routing covering GraphQL federation, gRPC proxying, and WebSocket multiplexing.
+//! - GraphQL subscriptions over `graphql-transport-ws`, bridged to workflow, pipeline, and agent
+//!   events with per-subscription permission checks and server-driven keepalive.
 //! - Unified authentication & authorisation that leverages the core security subsystem.
 //! - Rate limiting tied to agent/service identities sourced from the hive mind registry.
 //! - Distributed tracing and telemetry export compatible with OpenTelemetry pipelines.
+//! - Per-request cost attribution to the originating identity and namespace.
+//! - Priority lanes (interactive, standard, batch) with separate concurrency budgets.
+//! - Host capability broadcasts for AR/XR clients, sent on connect and whenever they change.
+//! - Per-protocol request body limits, with large uploads streamed to the file upload API.
 //!
 //! The implementation intentionally focuses on deterministic, testable behaviour
 //! so it can run in CI without external infrastructure.
 
 mod auth;
+mod lanes;
+mod payload;
 mod policy;
 mod rate_limit;
 mod router;
+mod subscription;
 mod telemetry;
+mod trust;
 
 pub use auth::{AuthCredentials, UnifiedAuthenticator};
+pub use lanes::{
+    Lane, LaneBudget, LaneConfig, LaneError, LanePermit, LaneRule, LaneSnapshot, PriorityLanes,
+};
+pub use payload::{
+    BodyKind, BodyLimits, BodyMeter, BoxError, PayloadConfig, PayloadError,
+    DEFAULT_GRAPHQL_BODY_LIMIT, DEFAULT_GRPC_BODY_LIMIT, DEFAULT_UPLOAD_BODY_LIMIT,
+    DEFAULT_UPLOAD_URL, DEFAULT_WEBSOCKET_BODY_LIMIT,
+};
 pub use policy::{GatewayPolicy, PolicyEnforcer};
-pub use rate_limit::{RateLimiter, RateLimiterConfig};
+pub use rate_limit::{
+    BucketMode, BucketState, BucketStore, BucketStoreConfig, FileBucketStore, RateLimitError,
+    RateLimiter, RateLimiterConfig, DEFAULT_BUCKET_DIR,
+};
 pub use router::{ProgrammableRouter, Protocol, RoutePlan, RoutingError};
+pub use subscription::{
+    ActiveSubscription, ClientMessage, EventTopic, ServerMessage, SubscribePayload,
+    SubscriptionError, SubscriptionEvent, SubscriptionHub, SubscriptionRequest, DEFAULT_KEEPALIVE,
+};
 pub use telemetry::{GatewayMetrics, TelemetryEvent, TelemetrySink};
+pub use trust::{TrustAssessment, TrustDecision, TrustError, TrustGate, TrustPolicyConfig};
 
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use noa_agents::registry::AgentRegistry;
+use noa_core::cost::{CostAccount, CostKind, CostLedger, CostQuery, CostReport, CostUsage};
 use noa_core::security::{self, Permission};
+use noa_core::world::{HostCapabilities, HOST_CAPABILITIES_FIELD};
+use parking_lot::RwLock;
 use serde::Serialize;
+use serde_json::json;
 use std::sync::Arc;
-use tracing::instrument;
+use std::time::Instant;
+use tracing::{instrument, warn};
+
+/// Namespace requests are charged to when their payload names none.
+pub const DEFAULT_COST_NAMESPACE: &str = "default";
+/// Bucket store settings read from the gateway's storage directory by [`bootstrap_gateway`].
+pub const RATE_LIMIT_CONFIG_FILE: &str = "gateway_rate_limits.json";
 
 /// High-level request entering the gateway.
 #[derive(Debug, Clone)]
@@ -51,6 +87,17 @@ pub struct GatewayResponse {
     pub timestamp: DateTime<Utc>,
 }
 
+/// GraphQL subscription opened through the gateway.
+#[derive(Debug, Clone)]
+pub struct GatewaySubscriptionRequest {
+    pub subscription_id: String,
+    pub user_id: security::UserId,
+    pub agent_id: Option<String>,
+    pub credentials: AuthCredentials,
+    pub query: String,
+    pub variables: serde_json::Value,
+}
+
 /// Core orchestrator wiring all gateway subsystems together.
 pub struct Gateway {
     authenticator: UnifiedAuthenticator,
@@ -58,6 +105,12 @@ pub struct Gateway {
     router: ProgrammableRouter,
     rate_limiter: RateLimiter,
     telemetry: TelemetrySink,
+    trust_gate: Option<TrustGate>,
+    subscriptions: SubscriptionHub,
+    costs: Option<Arc<CostLedger>>,
+    lanes: Option<Arc<PriorityLanes>>,
+    payloads: PayloadConfig,
+    host_capabilities: RwLock<Option<HostCapabilities>>,
 }
 
 impl Gateway {
@@ -75,9 +128,139 @@ impl Gateway {
             router,
             rate_limiter,
             telemetry,
+            trust_gate: None,
+            subscriptions: SubscriptionHub::default(),
+            costs: None,
+            lanes: None,
+            payloads: PayloadConfig::default(),
+            host_capabilities: RwLock::new(None),
         })
     }
 
+    /// Replace the default in-memory rate limiter, e.g. with one backed by a bucket store.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Consult agent reward standings before authorising and rate limiting requests.
+    pub fn with_trust_gate(mut self, trust_gate: TrustGate) -> Self {
+        self.trust_gate = Some(trust_gate);
+        self
+    }
+
+    /// Serve subscriptions from `hub`, e.g. one bridged to a workflow engine's event stream.
+    pub fn with_subscription_hub(mut self, hub: SubscriptionHub) -> Self {
+        self.subscriptions = hub;
+        self
+    }
+
+    /// Hub that workflow, pipeline, and agent events are published to.
+    pub fn subscriptions(&self) -> &SubscriptionHub {
+        &self.subscriptions
+    }
+
+    /// Charge each handled request to its caller in `ledger`.
+    ///
+    /// The account is `agent:<id>` for agent requests and `user:<id>` otherwise, in the
+    /// namespace named by the payload's `namespace` field.
+    pub fn with_cost_ledger(mut self, ledger: Arc<CostLedger>) -> Self {
+        self.costs = Some(ledger);
+        self
+    }
+
+    pub fn cost_ledger(&self) -> Option<&Arc<CostLedger>> {
+        self.costs.as_ref()
+    }
+
+    /// Charge usage measured downstream, e.g. inference tokens, to a handled request.
+    pub fn charge_request(&self, request_id: &str, usage: CostUsage) -> Result<()> {
+        let Some(ledger) = &self.costs else {
+            return Ok(());
+        };
+        let alerts = ledger
+            .charge_subject(CostKind::Request, request_id, usage)
+            .context("cost attribution failed")?;
+        for alert in alerts {
+            warn!(?alert, "cost budget threshold crossed");
+        }
+        Ok(())
+    }
+
+    /// Showback for the charges matching `query`; `None` without a cost ledger.
+    pub fn cost_report(&self, query: &CostQuery) -> Option<CostReport> {
+        self.costs.as_ref().map(|ledger| ledger.report(query))
+    }
+
+    /// Admit requests through priority lanes so batch traffic cannot starve interactive callers.
+    pub fn with_priority_lanes(mut self, lanes: PriorityLanes) -> Self {
+        self.lanes = Some(Arc::new(lanes));
+        self
+    }
+
+    /// Lane `request` is admitted through; `None` without priority lanes.
+    pub fn select_lane(&self, request: &GatewayRequest) -> Option<Lane> {
+        let lanes = self.lanes.as_ref()?;
+        let targets = self
+            .router
+            .route(&request.protocol, &request.payload)
+            .map(|plan| plan.targets)
+            .unwrap_or_default();
+        Some(lanes.select(&caller_identity(request), &targets))
+    }
+
+    /// Wait for a slot in the request's lane. Hold the permit while the request is served;
+    /// without priority lanes every request is admitted immediately.
+    pub async fn admit(&self, request: &GatewayRequest) -> Result<Option<LanePermit>, LaneError> {
+        match (&self.lanes, self.select_lane(request)) {
+            (Some(lanes), Some(lane)) => lanes.acquire(lane).await.map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Occupancy of each lane; `None` without priority lanes.
+    pub fn lane_snapshot(&self) -> Option<Vec<LaneSnapshot>> {
+        self.lanes.as_ref().map(|lanes| lanes.snapshot())
+    }
+
+    /// Enforce `config`'s body limits and stream uploads to its upload API.
+    pub fn with_payload_config(mut self, config: PayloadConfig) -> Self {
+        self.payloads = config;
+        self
+    }
+
+    pub fn payload_config(&self) -> &PayloadConfig {
+        &self.payloads
+    }
+
+    /// Record the host's current capabilities, broadcasting them to `hostCapabilities`
+    /// subscribers when they differ from the last published set. Returns whether they changed.
+    pub fn publish_host_capabilities(&self, capabilities: HostCapabilities) -> bool {
+        let mut current = self.host_capabilities.write();
+        if current.as_ref() == Some(&capabilities) {
+            return false;
+        }
+        self.subscriptions
+            .publish(SubscriptionEvent::from(&capabilities));
+        *current = Some(capabilities);
+        true
+    }
+
+    pub fn host_capabilities(&self) -> Option<HostCapabilities> {
+        self.host_capabilities.read().clone()
+    }
+
+    /// `connection_ack` carrying the host's capabilities, so clients learn them on connect.
+    pub fn connection_ack(&self) -> ServerMessage {
+        ServerMessage::ConnectionAck {
+            payload: self
+                .host_capabilities
+                .read()
+                .as_ref()
+                .map(|capabilities| json!({ HOST_CAPABILITIES_FIELD: capabilities })),
+        }
+    }
+
     /// Helper constructor that loads the shared agent registry and builds supporting components.
     pub fn with_defaults(registry: Arc<AgentRegistry>, telemetry: TelemetrySink) -> Result<Self> {
         let authenticator = UnifiedAuthenticator::default();
@@ -90,6 +273,7 @@ impl Gateway {
     /// Handle an incoming request by applying authN/Z, rate limiting, routing and telemetry.
     #[instrument(skip(self))]
     pub fn handle_request(&self, request: GatewayRequest) -> Result<GatewayResponse> {
+        let started = Instant::now();
         // Step 1 - authenticate
         self.authenticator
             .verify(&request.credentials, &request.agent_id)
@@ -97,18 +281,37 @@ impl Gateway {
 
         // Step 2 - authorise via core security policies
         self.policy
-            .enforce(request.user_id, request.required_permission)
+            .enforce(request.user_id, request.required_permission.clone())
             .context("policy enforcement failure")?;
 
-        // Step 3 - enforce rate limits for the linked agent/service
+        // Step 3 - consult the agent's reward standing when a trust gate is configured
+        let limit_factor = match (&self.trust_gate, &request.agent_id) {
+            (Some(gate), Some(agent_id)) => {
+                let assessment = gate.assess(agent_id, &request.required_permission);
+                self.telemetry.record_trust(&assessment)?;
+                assessment
+                    .ensure_allowed()
+                    .context("trust gate denied request")?;
+                assessment.limit_factor()
+            }
+            _ => 1.0,
+        };
+
+        // Step 4 - enforce rate limits for the linked agent/service
         self.rate_limiter
-            .check(&request.agent_id)
+            .check_scaled(&request.agent_id, limit_factor)
             .context("rate limit exceeded")?;
 
-        // Step 4 - compute programmable route plan
-        let route_plan = self.router.route(&request.protocol, &request.payload)?;
+        // Step 5 - compute programmable route plan, tagged with the request's lane
+        let mut route_plan = self.router.route(&request.protocol, &request.payload)?;
+        if let Some(lanes) = &self.lanes {
+            let lane = lanes.select(&caller_identity(&request), &route_plan.targets);
+            route_plan
+                .metadata
+                .insert("lane".into(), json!(lane.as_str()));
+        }
 
-        // Step 5 - emit telemetry covering traces + metrics snapshot
+        // Step 6 - emit telemetry covering traces + metrics snapshot
         self.telemetry.record(TelemetryEvent::new(
             request.request_id.clone(),
             request.protocol.clone(),
@@ -116,6 +319,21 @@ impl Gateway {
             request.agent_id.clone(),
         ))?;
 
+        // Step 7 - attribute the gateway's own work to the caller
+        if let Some(ledger) = &self.costs {
+            let alerts = ledger
+                .charge(
+                    CostKind::Request,
+                    &request.request_id,
+                    cost_account(&request),
+                    CostUsage::cpu(started.elapsed().as_secs_f64()),
+                )
+                .context("cost attribution failed")?;
+            for alert in alerts {
+                warn!(?alert, "cost budget threshold crossed");
+            }
+        }
+
         Ok(GatewayResponse {
             request_id: request.request_id,
             route_plan,
@@ -123,10 +341,80 @@ impl Gateway {
             timestamp: Utc::now(),
         })
     }
+
+    /// Open a GraphQL subscription after the same authN/Z, trust, and rate limiting
+    /// checks as `handle_request`. The permission checked depends on the event topic.
+    #[instrument(skip(self))]
+    pub fn subscribe(&self, request: GatewaySubscriptionRequest) -> Result<ActiveSubscription> {
+        self.authenticator
+            .verify(&request.credentials, &request.agent_id)
+            .context("authentication failed")?;
+
+        let subscription = SubscriptionRequest::parse(
+            request.subscription_id.clone(),
+            &request.query,
+            &request.variables,
+        )?;
+        let permission = self.subscriptions.required_permission(subscription.topic);
+        self.policy
+            .enforce(request.user_id, permission.clone())
+            .with_context(|| format!("subscription to {} denied", subscription.topic.field()))?;
+
+        let limit_factor = match (&self.trust_gate, &request.agent_id) {
+            (Some(gate), Some(agent_id)) => {
+                let assessment = gate.assess(agent_id, &permission);
+                self.telemetry.record_trust(&assessment)?;
+                assessment
+                    .ensure_allowed()
+                    .context("trust gate denied request")?;
+                assessment.limit_factor()
+            }
+            _ => 1.0,
+        };
+        self.rate_limiter
+            .check_scaled(&request.agent_id, limit_factor)
+            .context("rate limit exceeded")?;
+
+        let route_plan = self.router.route_subscription(&subscription);
+        self.telemetry.record(TelemetryEvent::new(
+            request.subscription_id,
+            Protocol::GraphQl,
+            route_plan,
+            request.agent_id,
+        ))?;
+
+        let active = self.subscriptions.subscribe(subscription);
+        Ok(match self.host_capabilities.read().as_ref() {
+            Some(capabilities) => active.with_initial(SubscriptionEvent::from(capabilities)),
+            None => active,
+        })
+    }
+}
+
+fn caller_identity(request: &GatewayRequest) -> String {
+    match &request.agent_id {
+        Some(agent_id) => format!("agent:{agent_id}"),
+        None => format!("user:{}", request.user_id),
+    }
+}
+
+fn cost_account(request: &GatewayRequest) -> CostAccount {
+    let identity = caller_identity(request);
+    let namespace = request
+        .payload
+        .get("namespace")
+        .and_then(|value| value.as_str())
+        .unwrap_or(DEFAULT_COST_NAMESPACE);
+    CostAccount::new(identity, namespace)
 }
 
 /// Build a production-like gateway composed of workspace primitives.
 pub fn bootstrap_gateway() -> Result<Gateway> {
+    bootstrap_gateway_with_telemetry(TelemetrySink::default())
+}
+
+/// [`bootstrap_gateway`] exporting telemetry through `telemetry`.
+pub fn bootstrap_gateway_with_telemetry(telemetry: TelemetrySink) -> Result<Gateway> {
     // Ensure the security subsystem is initialised so policy checks work.
     security::init().map_err(|err| anyhow!("failed to init security: {}", err))?;
 
@@ -134,14 +422,31 @@ pub fn bootstrap_gateway() -> Result<Gateway> {
     let registry =
         Arc::new(AgentRegistry::with_default_data().context("failed to load agent registry")?);
 
-    let telemetry = TelemetrySink::default();
-    Gateway::with_defaults(registry, telemetry)
+    let rate_limiter = persistent_rate_limiter(telemetry.storage_dir(), registry.clone())?;
+    Ok(Gateway::with_defaults(registry, telemetry)?.with_rate_limiter(rate_limiter))
+}
+
+/// Rate limiter whose buckets survive restarts. `gateway_rate_limits.json` in `dir` sets
+/// the store's path and mode; without it, buckets are kept locally in `rate_limit_buckets/`.
+fn persistent_rate_limiter(
+    dir: &std::path::Path,
+    registry: Arc<AgentRegistry>,
+) -> Result<RateLimiter> {
+    let config_path = dir.join(RATE_LIMIT_CONFIG_FILE);
+    let store_config = if config_path.exists() {
+        BucketStoreConfig::load(&config_path)?
+    } else {
+        BucketStoreConfig::default()
+    };
+    let store = Arc::new(FileBucketStore::new(store_config.store_path(dir)));
+    RateLimiter::new(store_config.apply(RateLimiterConfig::default()), registry)
+        .with_store(store)
+        .context("failed to restore rate limiter buckets")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rate_limit::RateLimitError;
     use serde_json::json;
     use std::collections::HashMap;
     use std::sync::Arc;
@@ -223,6 +528,7 @@ mod tests {
             RateLimiterConfig {
                 refill_interval: Duration::from_secs(60),
                 layer_limits,
+                ..RateLimiterConfig::default()
             },
             registry,
         );
@@ -234,4 +540,544 @@ mod tests {
             .expect_err("second call should exceed configured limit");
         assert!(matches!(err, RateLimitError::LimitExceeded(_)));
     }
+
+    fn single_token_config(
+        layer: noa_agents::unified_types::AgentLayer,
+        mode: BucketMode,
+    ) -> RateLimiterConfig {
+        let mut layer_limits = HashMap::new();
+        layer_limits.insert(layer, 1);
+        RateLimiterConfig {
+            layer_limits,
+            mode,
+            ..RateLimiterConfig::default()
+        }
+    }
+
+    #[test]
+    fn rate_limit_buckets_survive_restart() {
+        let registry =
+            Arc::new(AgentRegistry::with_default_data().expect("agent registry should load"));
+        let agent = registry.all().into_iter().next().expect("agent available");
+        let tmp = tempdir().expect("tempdir");
+        let store = Arc::new(FileBucketStore::new(tmp.path().join("buckets")));
+        let agent_id = Some(agent.agent_id.clone());
+
+        let limiter = RateLimiter::new(
+            single_token_config(agent.layer.clone(), BucketMode::Local),
+            registry.clone(),
+        )
+        .with_store(store.clone())
+        .expect("store attaches");
+        assert!(limiter.check(&agent_id).is_ok());
+        drop(limiter);
+
+        let restarted = RateLimiter::new(
+            single_token_config(agent.layer.clone(), BucketMode::Local),
+            registry,
+        )
+        .with_store(store)
+        .expect("store reloads");
+        let err = restarted
+            .check(&agent_id)
+            .expect_err("restored bucket should already be drained");
+        assert!(matches!(err, RateLimitError::LimitExceeded(_)));
+    }
+
+    #[test]
+    fn shared_buckets_enforce_one_limit_across_instances() {
+        let registry =
+            Arc::new(AgentRegistry::with_default_data().expect("agent registry should load"));
+        let agent = registry.all().into_iter().next().expect("agent available");
+        let tmp = tempdir().expect("tempdir");
+        let store: Arc<dyn BucketStore> =
+            Arc::new(FileBucketStore::new(tmp.path().join("buckets")));
+        let agent_id = Some(agent.agent_id.clone());
+
+        let first = RateLimiter::new(
+            single_token_config(agent.layer.clone(), BucketMode::Shared),
+            registry.clone(),
+        )
+        .with_store(store.clone())
+        .expect("first instance");
+        let second = RateLimiter::new(
+            single_token_config(agent.layer.clone(), BucketMode::Shared),
+            registry,
+        )
+        .with_store(store)
+        .expect("second instance");
+
+        assert!(first.check(&agent_id).is_ok());
+        // Normally done by each instance's writer thread every persist interval.
+        first.persist().expect("first instance syncs");
+        second.persist().expect("second instance syncs");
+        let err = second
+            .check(&agent_id)
+            .expect_err("second instance shares the drained bucket");
+        assert!(matches!(err, RateLimitError::LimitExceeded(_)));
+    }
+
+    #[test]
+    fn bootstrapped_gateways_restore_rate_limit_buckets() {
+        let tmp = tempdir().expect("tempdir");
+        std::fs::write(
+            tmp.path().join(RATE_LIMIT_CONFIG_FILE),
+            r#"{ "mode": "local", "path": "buckets/agents" }"#,
+        )
+        .expect("rate limit config");
+        let agent = Some("fixed_agent_gateway".to_string());
+
+        let gateway = bootstrap_gateway_with_telemetry(
+            TelemetrySink::new(tmp.path()).expect("telemetry sink"),
+        )
+        .expect("gateway bootstrap");
+        gateway.rate_limiter.check(&agent).expect("first request");
+        let bucket = gateway
+            .rate_limiter
+            .bucket("fixed_agent_gateway")
+            .expect("bucket created");
+        drop(gateway);
+        assert!(tmp.path().join("buckets/agents").is_dir());
+
+        let restarted = bootstrap_gateway_with_telemetry(
+            TelemetrySink::new(tmp.path()).expect("telemetry sink"),
+        )
+        .expect("gateway restart");
+        assert_eq!(
+            restarted.rate_limiter.bucket("fixed_agent_gateway"),
+            Some(bucket)
+        );
+    }
+
+    #[test]
+    fn corrupt_bucket_files_are_moved_aside_at_bootstrap() {
+        let tmp = tempdir().expect("tempdir");
+        std::fs::write(
+            tmp.path().join(RATE_LIMIT_CONFIG_FILE),
+            r#"{ "mode": "local", "path": "buckets" }"#,
+        )
+        .expect("rate limit config");
+        let store = FileBucketStore::new(tmp.path().join("buckets"));
+        let drained = BucketState {
+            remaining: 0,
+            last_refill: Utc::now(),
+        };
+        store
+            .save(&HashMap::from([(
+                "fixed_agent_gateway".to_string(),
+                drained,
+            )]))
+            .expect("seed bucket");
+        std::fs::write(
+            tmp.path().join("buckets/truncated.json"),
+            b"{\"agent_id\": \"pl",
+        )
+        .expect("corrupt bucket");
+
+        let gateway = bootstrap_gateway_with_telemetry(
+            TelemetrySink::new(tmp.path()).expect("telemetry sink"),
+        )
+        .expect("gateway starts despite a corrupt bucket file");
+        assert!(!tmp.path().join("buckets/truncated.json").exists());
+        assert!(tmp.path().join("buckets/truncated.corrupt").exists());
+        let err = gateway
+            .rate_limiter
+            .check(&Some("fixed_agent_gateway".to_string()))
+            .expect_err("intact buckets are still restored");
+        assert!(matches!(err, RateLimitError::LimitExceeded(_)));
+    }
+
+    #[test]
+    fn crashed_lock_holders_do_not_block_the_bucket_store() {
+        let tmp = tempdir().expect("tempdir");
+        let store = FileBucketStore::new(tmp.path().join("buckets"));
+        // A lock file left behind by a process that died while holding the lock.
+        std::fs::write(tmp.path().join("buckets.lock"), b"").expect("stale lock file");
+
+        let started = std::time::Instant::now();
+        store.save(&HashMap::new()).expect("store not blocked");
+        assert!(store.load().expect("load").is_empty());
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[derive(Default)]
+    struct RecordingStore {
+        saves: parking_lot::Mutex<Vec<HashMap<String, BucketState>>>,
+        fail: bool,
+    }
+
+    impl BucketStore for RecordingStore {
+        fn load(&self) -> Result<HashMap<String, BucketState>, RateLimitError> {
+            Ok(HashMap::new())
+        }
+
+        fn save(&self, changed: &HashMap<String, BucketState>) -> Result<(), RateLimitError> {
+            if self.fail {
+                return Err(RateLimitError::Persistence("disk full".into()));
+            }
+            self.saves.lock().push(changed.clone());
+            Ok(())
+        }
+
+        fn transact(
+            &self,
+            _: &mut dyn FnMut(&mut HashMap<String, BucketState>),
+        ) -> Result<(), RateLimitError> {
+            Err(RateLimitError::Persistence("disk full".into()))
+        }
+    }
+
+    #[test]
+    fn checks_leave_store_writes_to_the_writer_and_flush_only_changed_buckets() {
+        let registry =
+            Arc::new(AgentRegistry::with_default_data().expect("agent registry should load"));
+        let agents: Vec<_> = registry.all().into_iter().take(2).collect();
+        let store = Arc::new(RecordingStore::default());
+        let limiter = RateLimiter::new(RateLimiterConfig::default(), registry)
+            .with_store(store.clone())
+            .expect("store attaches");
+
+        let first = Some(agents[0].agent_id.clone());
+        for _ in 0..5 {
+            limiter.check(&first).expect("within limit");
+        }
+        assert!(
+            store.saves.lock().is_empty(),
+            "checks never write the store"
+        );
+
+        limiter.persist().expect("flush");
+        limiter.persist().expect("nothing left to flush");
+        limiter
+            .check(&Some(agents[1].agent_id.clone()))
+            .expect("within limit");
+        limiter.persist().expect("flush");
+
+        let saves = store.saves.lock().clone();
+        assert_eq!(saves.len(), 2);
+        assert_eq!(
+            saves[0].keys().collect::<Vec<_>>(),
+            vec![&agents[0].agent_id]
+        );
+        assert_eq!(
+            saves[1].keys().collect::<Vec<_>>(),
+            vec![&agents[1].agent_id]
+        );
+
+        let tmp = tempdir().expect("tempdir");
+        let files = FileBucketStore::new(tmp.path().join("buckets"));
+        let bucket = |remaining| BucketState {
+            remaining,
+            last_refill: Utc::now(),
+        };
+        files
+            .save(&HashMap::from([("a/b".to_string(), bucket(1))]))
+            .expect("save a/b");
+        files
+            .save(&HashMap::from([("c".to_string(), bucket(2))]))
+            .expect("save c");
+        let loaded = files.load().expect("load");
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded["a/b"].remaining, 1);
+    }
+
+    #[test]
+    fn failed_flushes_do_not_reject_allowed_requests() {
+        let registry =
+            Arc::new(AgentRegistry::with_default_data().expect("agent registry should load"));
+        let agent = registry.all().into_iter().next().expect("agent available");
+        let limiter = RateLimiter::new(
+            RateLimiterConfig {
+                persist_interval: Duration::ZERO,
+                ..RateLimiterConfig::default()
+            },
+            registry,
+        )
+        .with_store(Arc::new(RecordingStore {
+            fail: true,
+            ..RecordingStore::default()
+        }))
+        .expect("store attaches");
+
+        let agent_id = Some(agent.agent_id.clone());
+        limiter
+            .check(&agent_id)
+            .expect("allowed despite failed flush");
+        let started = std::time::Instant::now();
+        while limiter.persist_failures() == 0 && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(limiter.persist_failures() >= 1);
+    }
+
+    #[test]
+    fn subscriptions_check_topic_permissions() {
+        let (gateway, _tmp) = gateway_with_tempdir();
+        let request = GatewaySubscriptionRequest {
+            subscription_id: "sub-agents".into(),
+            user_id: 0,
+            agent_id: Some("fixed_agent_gateway".into()),
+            credentials: AuthCredentials {
+                mtls: None,
+                oidc: None,
+                api_key: Some("key-123".into()),
+            },
+            query: "subscription { agentEvents(agentId: \"planner\") { eventType } }".into(),
+            variables: serde_json::Value::Null,
+        };
+
+        let subscription = gateway
+            .subscribe(request.clone())
+            .expect("root may follow agent events");
+        assert_eq!(subscription.request().topic, EventTopic::Agent);
+
+        let err = gateway
+            .subscribe(GatewaySubscriptionRequest {
+                user_id: 4242,
+                ..request
+            })
+            .expect_err("unknown users cannot subscribe");
+        assert!(err
+            .to_string()
+            .contains("subscription to agentEvents denied"));
+    }
+
+    #[test]
+    fn requests_are_charged_to_the_calling_agent() {
+        let (gateway, _tmp) = gateway_with_tempdir();
+        let ledger = Arc::new(CostLedger::new());
+        let gateway = gateway.with_cost_ledger(ledger.clone());
+
+        let request = GatewayRequest {
+            request_id: "req-costed".into(),
+            user_id: 0,
+            agent_id: Some("fixed_agent_gateway".into()),
+            credentials: AuthCredentials {
+                mtls: Some("agent-cert".into()),
+                oidc: None,
+                api_key: Some("key-123".into()),
+            },
+            protocol: Protocol::Grpc,
+            payload: json!({ "service": "inference", "method": "Complete", "namespace": "research" }),
+            required_permission: Permission::Read,
+        };
+        gateway.handle_request(request).expect("request handled");
+        gateway
+            .charge_request("req-costed", CostUsage::tokens(640))
+            .expect("inference tokens attributed");
+        assert!(gateway
+            .charge_request("req-unknown", CostUsage::tokens(1))
+            .is_err());
+
+        let report = gateway
+            .cost_report(&CostQuery {
+                namespace: Some("research".into()),
+                ..CostQuery::default()
+            })
+            .expect("ledger configured");
+        assert_eq!(report.accounts.len(), 1);
+        assert_eq!(
+            report.accounts[0].account,
+            CostAccount::new("agent:fixed_agent_gateway", "research")
+        );
+        assert_eq!(report.total.tokens, 640);
+        assert_eq!(report.subjects[0].charges, 2);
+    }
+
+    #[tokio::test]
+    async fn agent_batch_requests_are_admitted_through_their_own_lane() {
+        let (gateway, _tmp) = gateway_with_tempdir();
+        let gateway = gateway.with_priority_lanes(PriorityLanes::new(
+            LaneConfig::default()
+                .with_rule(LaneRule {
+                    lane: Lane::Batch,
+                    identity: None,
+                    route: Some("memory/*".into()),
+                })
+                .with_budget(
+                    Lane::Batch,
+                    LaneBudget {
+                        max_concurrent: 1,
+                        max_queued: 0,
+                        queue_timeout_ms: 0,
+                    },
+                ),
+        ));
+        let request = GatewayRequest {
+            request_id: "req-batch".into(),
+            user_id: 0,
+            agent_id: Some("fixed_agent_gateway".into()),
+            credentials: AuthCredentials {
+                mtls: Some("agent-cert".into()),
+                oidc: None,
+                api_key: Some("key-123".into()),
+            },
+            protocol: Protocol::Grpc,
+            payload: json!({ "service": "memory", "method": "Reindex" }),
+            required_permission: Permission::Read,
+        };
+
+        let permit = gateway.admit(&request).await.expect("batch slot free");
+        assert_eq!(permit.as_ref().map(LanePermit::lane), Some(Lane::Batch));
+        let err = gateway
+            .admit(&request)
+            .await
+            .expect_err("batch lane exhausted");
+        assert_eq!(err, LaneError::QueueFull(Lane::Batch));
+
+        let interactive = GatewayRequest {
+            request_id: "req-ui".into(),
+            agent_id: None,
+            payload: json!({ "service": "workflow", "method": "Status" }),
+            ..request.clone()
+        };
+        assert_eq!(gateway.select_lane(&interactive), Some(Lane::Interactive));
+        let ui_permit = gateway.admit(&interactive).await.expect("ui admitted");
+        assert!(ui_permit.is_some());
+
+        let response = gateway.handle_request(request).expect("request handled");
+        assert_eq!(response.route_plan.metadata["lane"], "batch");
+        let snapshot = gateway.lane_snapshot().expect("lanes configured");
+        assert_eq!(snapshot[2].rejected, 1);
+    }
+
+    #[tokio::test]
+    async fn host_capabilities_reach_clients_on_connect_and_on_change() {
+        let (gateway, _tmp) = gateway_with_tempdir();
+        assert_eq!(
+            gateway.connection_ack(),
+            ServerMessage::ConnectionAck { payload: None }
+        );
+
+        let capabilities = HostCapabilities {
+            host: "edge-01".into(),
+            ..HostCapabilities::default()
+        };
+        assert!(gateway.publish_host_capabilities(capabilities.clone()));
+        assert!(!gateway.publish_host_capabilities(capabilities.clone()));
+        let ServerMessage::ConnectionAck { payload: Some(ack) } = gateway.connection_ack() else {
+            panic!("ack should carry host capabilities");
+        };
+        assert_eq!(ack[HOST_CAPABILITIES_FIELD]["host"], "edge-01");
+
+        let mut subscription = gateway
+            .subscribe(GatewaySubscriptionRequest {
+                subscription_id: "sub-host".into(),
+                user_id: 0,
+                agent_id: Some("fixed_agent_gateway".into()),
+                credentials: AuthCredentials {
+                    mtls: None,
+                    oidc: None,
+                    api_key: Some("key-123".into()),
+                },
+                query: "subscription { hostCapabilities { data } }".into(),
+                variables: serde_json::Value::Null,
+            })
+            .expect("root may follow host capabilities");
+        let latency = |message: Option<ServerMessage>| match message {
+            Some(ServerMessage::Next { payload, .. }) => {
+                payload["data"]["hostCapabilities"]["data"]["latency"].clone()
+            }
+            other => panic!("expected a capability broadcast, got {other:?}"),
+        };
+        assert_eq!(latency(subscription.next_message().await), "interactive");
+
+        gateway.publish_host_capabilities(HostCapabilities {
+            latency: noa_core::world::LatencyClass::Realtime,
+            ..capabilities
+        });
+        assert_eq!(latency(subscription.next_message().await), "realtime");
+    }
+
+    fn penalised_scorekeeper(
+        dir: &std::path::Path,
+        agent_id: &str,
+    ) -> Arc<parking_lot::RwLock<noa_workflow::RewardScorekeeper>> {
+        let mut keeper = noa_workflow::RewardScorekeeper::new(dir.join("reward_history.json"))
+            .expect("scorekeeper");
+        let bad_inputs = noa_workflow::RewardInputs {
+            coverage: 0.2,
+            flake_rate: 0.6,
+            token_ratio: 1.9,
+            rollback_count: 3,
+        };
+        let agents = [noa_workflow::RewardAgentSnapshot {
+            agent: agent_id.to_string(),
+            success: false,
+        }];
+        for _ in 0..3 {
+            keeper.record("goal", "wf", bad_inputs.clone(), &agents);
+        }
+        Arc::new(parking_lot::RwLock::new(keeper))
+    }
+
+    #[test]
+    fn trust_gate_denies_writes_for_low_standing_agents() {
+        let (gateway, tmp) = gateway_with_tempdir();
+        let scorekeeper = penalised_scorekeeper(tmp.path(), "fixed_agent_gateway");
+        let gateway =
+            gateway.with_trust_gate(TrustGate::new(TrustPolicyConfig::default(), scorekeeper));
+
+        let request = GatewayRequest {
+            request_id: "req-untrusted".into(),
+            user_id: 0,
+            agent_id: Some("fixed_agent_gateway".into()),
+            credentials: AuthCredentials {
+                mtls: Some("agent-cert".into()),
+                oidc: None,
+                api_key: Some("key-123".into()),
+            },
+            protocol: Protocol::Grpc,
+            payload: json!({ "service": "workflow", "method": "Run" }),
+            required_permission: Permission::Write,
+        };
+
+        let err = gateway
+            .handle_request(request.clone())
+            .expect_err("write should be denied");
+        assert!(err.to_string().contains("trust gate denied request"));
+
+        let read = GatewayRequest {
+            required_permission: Permission::Read,
+            ..request
+        };
+        gateway
+            .handle_request(read)
+            .expect("reads are throttled, not denied");
+
+        let metrics = gateway.telemetry.snapshot();
+        assert_eq!(metrics.trust_denied, 1);
+        assert_eq!(metrics.trust_throttled, 1);
+        let log = std::fs::read_to_string(tmp.path().join("gateway_trust.log")).expect("trust log");
+        assert_eq!(log.lines().count(), 2);
+    }
+
+    #[test]
+    fn trust_gate_records_nothing_for_allowed_agents() {
+        let (gateway, tmp) = gateway_with_tempdir();
+        let scorekeeper = penalised_scorekeeper(tmp.path(), "fixed_agent_gateway");
+        let gateway =
+            gateway.with_trust_gate(TrustGate::new(TrustPolicyConfig::default(), scorekeeper));
+
+        gateway
+            .handle_request(GatewayRequest {
+                request_id: "req-trusted".into(),
+                user_id: 0,
+                agent_id: Some("fresh_agent_gateway".into()),
+                credentials: AuthCredentials {
+                    mtls: Some("agent-cert".into()),
+                    oidc: None,
+                    api_key: Some("key-123".into()),
+                },
+                protocol: Protocol::Grpc,
+                payload: json!({ "service": "workflow", "method": "Run" }),
+                required_permission: Permission::Write,
+            })
+            .expect("agents in good standing are allowed");
+
+        let metrics = gateway.telemetry.snapshot();
+        assert_eq!(metrics.trust_denied, 0);
+        assert_eq!(metrics.trust_throttled, 0);
+        assert!(!tmp.path().join("gateway_trust.log").exists());
+    }
 }
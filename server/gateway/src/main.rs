@@ -1,30 +1,56 @@
+use std::collections::HashMap;
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
-use axum::extract::State;
+use axum::body::Body;
+use axum::extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
 use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use axum_server::tls_rustls::RustlsConfig;
 use clap::Parser;
+use noa_core::cost::{CostLedger, CostQuery, CostReport};
+use noa_core::events;
+use noa_core::hardware::detect_hardware_profile;
 use noa_core::security::Permission;
+use noa_core::world::{HostCapabilities, WorldGraph};
 use noa_gateway::{
-    bootstrap_gateway, AuthCredentials, Gateway, GatewayRequest, GatewayResponse, Protocol,
+    bootstrap_gateway_with_telemetry, AuthCredentials, BodyKind, ClientMessage, Gateway,
+    GatewayRequest, GatewayResponse, GatewaySubscriptionRequest, LaneConfig, LaneError,
+    LaneSnapshot, PayloadConfig, PayloadError, PriorityLanes, Protocol, ServerMessage,
+    TelemetrySink,
+};
+use noa_observability::{
+    self as observability, KernelEventRecorder, LogFormat, MetricsExporter, OtlpEmitter,
+    TracingConfig,
 };
-use noa_observability::{self as observability, LogFormat, MetricsExporter, TracingConfig};
 use noa_server_core::config::{self, ConfigOverrides, ServerConfig};
 use redis::Client as RedisClient;
+use runtime_manager::{AdaptiveRuntimeController, KernelRuntimeGraph, RuntimePolicy};
 use serde::Deserialize;
 use serde_json::Value;
 use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use tokio::{net::TcpListener, signal};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use url::Url;
 use uuid::Uuid;
 
+const COST_LEDGER_FILE: &str = "cost_ledger.jsonl";
+const COST_BUDGETS_FILE: &str = "cost_budgets.json";
+const LANES_FILE: &str = "gateway_lanes.json";
+const PAYLOADS_FILE: &str = "gateway_payloads.json";
+const UPLOAD_PATH: &str = "/v1/uploads";
+const KERNEL_RUNTIME_GRAPH: &str = "runtime/kernel/graph.yaml";
+const HOST_CAPABILITY_REFRESH: Duration = Duration::from_secs(30);
+
 #[derive(Parser, Debug, Clone)]
 #[command(name = "noa-gateway", about = "Unified NOA gateway binary")]
 struct GatewayCli {
@@ -95,8 +121,26 @@ async fn main() -> Result<()> {
         resource_attributes: vec![("component".into(), "gateway".into())],
     };
     let (_tracing_guard, metrics_exporter) = observability::init(&tracing_config, None)?;
-
-    let gateway = Arc::new(bootstrap_gateway().context("failed to bootstrap gateway")?);
+    events::global().subscribe(Arc::new(KernelEventRecorder));
+    events::install_panic_hook();
+
+    let mut telemetry = TelemetrySink::default();
+    let mut cost_ledger = open_cost_ledger(telemetry.storage_dir())?;
+    let lanes = open_priority_lanes(telemetry.storage_dir())?;
+    let payloads = open_payload_config(telemetry.storage_dir())?;
+    if tracing_config.otlp_endpoint.is_some() {
+        let otlp = Arc::new(OtlpEmitter::new("noa-gateway"));
+        telemetry = telemetry.with_emitter(otlp.clone());
+        cost_ledger = cost_ledger.with_emitter(otlp);
+    }
+    let gateway = Arc::new(
+        bootstrap_gateway_with_telemetry(telemetry)
+            .context("failed to bootstrap gateway")?
+            .with_cost_ledger(Arc::new(cost_ledger))
+            .with_priority_lanes(lanes)
+            .with_payload_config(payloads),
+    );
+    tokio::spawn(broadcast_host_capabilities(gateway.clone()));
     let dependencies = Arc::new(DependencyClients::initialise(&server_config)?);
     let readiness = Arc::new(ReadinessState::default());
     readiness.mark_ready();
@@ -106,6 +150,7 @@ async fn main() -> Result<()> {
         metrics: metrics_exporter.clone(),
         readiness: readiness.clone(),
         dependencies,
+        uploads: reqwest::Client::new(),
     };
 
     let router = Router::new()
@@ -113,6 +158,10 @@ async fn main() -> Result<()> {
         .route("/ready", get(readiness_probe))
         .route("/metrics", get(metrics_handler))
         .route("/v1/route", post(gateway_entrypoint))
+        .route(UPLOAD_PATH, post(upload_entrypoint))
+        .route("/v1/costs", get(cost_report))
+        .route("/v1/lanes", get(lane_report))
+        .route("/v1/graphql/ws", get(graphql_subscriptions))
         .with_state(state.clone());
 
     let addr = server_config
@@ -187,8 +236,25 @@ async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
 async fn gateway_entrypoint(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(payload): Json<GatewayHttpRequest>,
+    body: Body,
 ) -> Result<Json<GatewayResponse>, GatewayHttpError> {
+    // Refuse oversized bodies by their declared length, or as soon as they pass the limit
+    // of the protocol named in `x-noa-protocol` (the largest limit without one).
+    let limits = state.gateway.payload_config().limits;
+    let hinted = header_value(&headers, "x-noa-protocol").and_then(|v| protocol_from_str(&v));
+    let meter = limits.request_meter(hinted.as_ref());
+    meter.check_declared(content_length(&headers))?;
+    let body = meter
+        .collect(body.into_data_stream())
+        .await
+        .map_err(|err| match meter.error() {
+            Some(err) => GatewayHttpError::from(err),
+            None => GatewayHttpError::bad_request(format!("failed to read request body: {err}")),
+        })?;
+    let payload: GatewayHttpRequest = serde_json::from_slice(&body)
+        .map_err(|err| GatewayHttpError::bad_request(format!("invalid request body: {err}")))?;
+    limits.check_protocol(&payload.protocol, body.len() as u64)?;
+
     let capability_scope = payload
         .capability_scope
         .clone()
@@ -219,6 +285,11 @@ async fn gateway_entrypoint(
         required_permission: permission,
     };
 
+    let _permit = state
+        .gateway
+        .admit(&request)
+        .await
+        .map_err(GatewayHttpError::from)?;
     let response = state
         .gateway
         .handle_request(request)
@@ -227,6 +298,360 @@ async fn gateway_entrypoint(
     Ok(Json(response))
 }
 
+/// Stream an upload to the file upload API without buffering it, cutting it off once it
+/// passes the upload limit. The upstream response is relayed as is.
+async fn upload_entrypoint(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, GatewayHttpError> {
+    let capability_scope = header_value(&headers, "x-noa-capability-scope");
+    let capability_token = header_value(&headers, "x-noa-capability");
+    enforce_capability_token(capability_token, capability_scope.as_deref())?;
+
+    let config = state.gateway.payload_config();
+    let meter = config.limits.upload_meter();
+    meter.check_declared(content_length(&headers))?;
+
+    let mut upstream = state
+        .uploads
+        .post(&config.upload_url)
+        .body(reqwest::Body::wrap_stream(
+            meter.meter(body.into_data_stream()),
+        ));
+    if let Some(content_type) = headers.get(header::CONTENT_TYPE) {
+        upstream = upstream.header(header::CONTENT_TYPE, content_type);
+    }
+    let response = match upstream.send().await {
+        Ok(response) => response,
+        Err(err) => {
+            return Err(match meter.error() {
+                Some(err) => GatewayHttpError::from(err),
+                None => GatewayHttpError::bad_gateway(format!("upload API unavailable: {err}")),
+            })
+        }
+    };
+
+    let status = response.status();
+    let content_type = response.headers().get(header::CONTENT_TYPE).cloned();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|err| GatewayHttpError::bad_gateway(format!("upload API response: {err}")))?;
+    let mut relayed = (status, body).into_response();
+    if let Some(content_type) = content_type {
+        relayed
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, content_type);
+    }
+    Ok(relayed)
+}
+
+/// Occupancy of the interactive, standard, and batch lanes.
+async fn lane_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<LaneSnapshot>>, GatewayHttpError> {
+    let capability_scope = header_value(&headers, "x-noa-capability-scope");
+    let capability_token = header_value(&headers, "x-noa-capability");
+    enforce_capability_token(capability_token, capability_scope.as_deref())?;
+
+    state
+        .gateway
+        .lane_snapshot()
+        .map(Json)
+        .ok_or_else(|| GatewayHttpError::internal("priority lanes are not enabled"))
+}
+
+/// Cost showback filtered by `namespace`, `identity`, `kind`, and `since` query parameters.
+async fn cost_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<CostQuery>,
+) -> Result<Json<CostReport>, GatewayHttpError> {
+    let capability_scope = header_value(&headers, "x-noa-capability-scope");
+    let capability_token = header_value(&headers, "x-noa-capability");
+    enforce_capability_token(capability_token, capability_scope.as_deref())?;
+
+    state
+        .gateway
+        .cost_report(&query)
+        .map(Json)
+        .ok_or_else(|| GatewayHttpError::internal("cost accounting is not enabled"))
+}
+
+/// Cost ledger kept next to the gateway telemetry, with budgets from `cost_budgets.json`.
+fn open_cost_ledger(dir: &Path) -> Result<CostLedger> {
+    let ledger =
+        CostLedger::open(dir.join(COST_LEDGER_FILE)).context("failed to open cost ledger")?;
+    let budgets = dir.join(COST_BUDGETS_FILE);
+    if budgets.exists() {
+        let count = ledger
+            .load_budgets(&budgets)
+            .with_context(|| format!("failed to load cost budgets from {}", budgets.display()))?;
+        info!(count, "loaded cost budgets");
+    }
+    Ok(ledger)
+}
+
+/// Priority lanes with budgets and rules from `gateway_lanes.json` when present.
+fn open_priority_lanes(dir: &Path) -> Result<PriorityLanes> {
+    let path = dir.join(LANES_FILE);
+    if !path.exists() {
+        return Ok(PriorityLanes::default());
+    }
+    let config = LaneConfig::load(&path)?;
+    info!(
+        rules = config.rules.len(),
+        "loaded priority lane configuration"
+    );
+    Ok(PriorityLanes::new(config))
+}
+
+/// Body limits and upload target from `gateway_payloads.json` when present.
+fn open_payload_config(dir: &Path) -> Result<PayloadConfig> {
+    let path = dir.join(PAYLOADS_FILE);
+    if !path.exists() {
+        return Ok(PayloadConfig::default());
+    }
+    let config = PayloadConfig::load(&path)?;
+    info!(limits = ?config.limits, "loaded payload configuration");
+    Ok(config)
+}
+
+/// Re-derive the host's capabilities from the world model and runtime plan, publishing
+/// them to the gateway whenever they change.
+async fn broadcast_host_capabilities(gateway: Arc<Gateway>) {
+    let repo_root = WorldGraph::repo_root();
+    let graph = match KernelRuntimeGraph::load_from_path(repo_root.join(KERNEL_RUNTIME_GRAPH)) {
+        Ok(graph) => graph,
+        Err(err) => {
+            warn!(error = %err, "host capability broadcast disabled");
+            return;
+        }
+    };
+    let workloads = graph.boot_order.clone();
+    let controller = Arc::new(AdaptiveRuntimeController::new(
+        RuntimePolicy::default(),
+        graph,
+    ));
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string());
+
+    let mut interval = tokio::time::interval(HOST_CAPABILITY_REFRESH);
+    loop {
+        interval.tick().await;
+        // Hardware detection shells out to vendor tools, so keep it off the async workers.
+        let (controller, workloads, host, repo_root) = (
+            controller.clone(),
+            workloads.clone(),
+            host.clone(),
+            repo_root.clone(),
+        );
+        let detected = tokio::task::spawn_blocking(move || {
+            detect_host_capabilities(&controller, &workloads, &host, &repo_root)
+        })
+        .await;
+        match detected {
+            Ok(Ok(capabilities)) => {
+                if gateway.publish_host_capabilities(capabilities) {
+                    info!("host capabilities changed");
+                }
+            }
+            Ok(Err(err)) => warn!(error = %err, "failed to derive host capabilities"),
+            Err(err) => warn!(error = %err, "host capability detection panicked"),
+        }
+    }
+}
+
+fn detect_host_capabilities(
+    controller: &AdaptiveRuntimeController,
+    workloads: &[String],
+    host: &str,
+    repo_root: &Path,
+) -> Result<HostCapabilities> {
+    let world = WorldGraph::load_default().context("failed to load world graph")?;
+    let assessment = controller
+        .plan(&detect_hardware_profile(), workloads)
+        .context("failed to plan runtime")?;
+    Ok(assessment.host_capabilities(host, &world, repo_root))
+}
+
+/// GraphQL subscriptions over the `graphql-transport-ws` protocol.
+async fn graphql_subscriptions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Result<Response, GatewayHttpError> {
+    let capability_scope = header_value(&headers, "x-noa-capability-scope");
+    let capability_token = header_value(&headers, "x-noa-capability");
+    enforce_capability_token(capability_token, capability_scope.as_deref())?;
+
+    let connection = SubscriptionConnection {
+        gateway: state.gateway.clone(),
+        user_id: header_value(&headers, "x-noa-user-id")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default(),
+        agent_id: header_value(&headers, "x-noa-agent-id"),
+        credentials: credentials_from_headers(&headers),
+    };
+    let message_limit = state.gateway.payload_config().limits.websocket as usize;
+    Ok(ws
+        .protocols(["graphql-transport-ws"])
+        .max_message_size(message_limit)
+        .max_frame_size(message_limit)
+        .on_upgrade(move |socket| connection.serve(socket)))
+}
+
+struct SubscriptionConnection {
+    gateway: Arc<Gateway>,
+    user_id: u64,
+    agent_id: Option<String>,
+    credentials: AuthCredentials,
+}
+
+impl SubscriptionConnection {
+    async fn serve(mut self, mut socket: WebSocket) {
+        let (outgoing, mut queued) = mpsc::channel::<ServerMessage>(64);
+        let mut active: HashMap<String, JoinHandle<()>> = HashMap::new();
+        let mut acknowledged = false;
+
+        loop {
+            let reply = tokio::select! {
+                frame = socket.recv() => match frame {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<ClientMessage>(&text) {
+                            Ok(message) => {
+                                match self.handle(message, &mut acknowledged, &mut active, &outgoing) {
+                                    Ok(reply) => reply,
+                                    Err((code, reason)) => {
+                                        let _ = socket
+                                            .send(Message::Close(Some(CloseFrame {
+                                                code,
+                                                reason: reason.into(),
+                                            })))
+                                            .await;
+                                        break;
+                                    }
+                                }
+                            }
+                            Err(err) => {
+                                warn!(error = %err, "malformed subscription message");
+                                let _ = socket
+                                    .send(Message::Close(Some(CloseFrame {
+                                        code: 4400,
+                                        reason: "Invalid message".into(),
+                                    })))
+                                    .await;
+                                break;
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                    Some(Ok(_)) => None,
+                },
+                Some(message) = queued.recv() => {
+                    if let ServerMessage::Complete { id } | ServerMessage::Error { id, .. } = &message {
+                        active.remove(id);
+                    }
+                    Some(message)
+                }
+            };
+
+            if let Some(message) = reply {
+                let Ok(text) = serde_json::to_string(&message) else {
+                    continue;
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+        }
+
+        for (_, task) in active {
+            task.abort();
+        }
+    }
+
+    /// Apply one client message, returning an immediate reply or a close code.
+    fn handle(
+        &mut self,
+        message: ClientMessage,
+        acknowledged: &mut bool,
+        active: &mut HashMap<String, JoinHandle<()>>,
+        outgoing: &mpsc::Sender<ServerMessage>,
+    ) -> Result<Option<ServerMessage>, (u16, &'static str)> {
+        match message {
+            ClientMessage::ConnectionInit { payload } => {
+                if *acknowledged {
+                    return Err((4429, "Too many initialisation requests"));
+                }
+                if let Some(payload) = payload {
+                    self.apply_init_payload(&payload);
+                }
+                *acknowledged = true;
+                Ok(Some(self.gateway.connection_ack()))
+            }
+            ClientMessage::Ping { .. } => Ok(Some(ServerMessage::Pong)),
+            ClientMessage::Pong { .. } => Ok(None),
+            ClientMessage::Subscribe { .. } if !*acknowledged => Err((4401, "Unauthorized")),
+            ClientMessage::Subscribe { id, payload } => {
+                if active.contains_key(&id) {
+                    return Err((4409, "Subscriber already exists"));
+                }
+                let request = GatewaySubscriptionRequest {
+                    subscription_id: id.clone(),
+                    user_id: self.user_id,
+                    agent_id: self.agent_id.clone(),
+                    credentials: self.credentials.clone(),
+                    query: payload.query,
+                    variables: payload.variables,
+                };
+                let mut subscription = match self.gateway.subscribe(request) {
+                    Ok(subscription) => subscription,
+                    Err(err) => return Ok(Some(ServerMessage::error(id, format!("{err:#}")))),
+                };
+                let outgoing = outgoing.clone();
+                let task = tokio::spawn(async move {
+                    while let Some(message) = subscription.next_message().await {
+                        if outgoing.send(message).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+                active.insert(id, task);
+                Ok(None)
+            }
+            ClientMessage::Complete { id } => {
+                if let Some(task) = active.remove(&id) {
+                    task.abort();
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Credentials may also arrive in the `connection_init` payload, since browsers
+    /// cannot set headers on WebSocket upgrades.
+    fn apply_init_payload(&mut self, payload: &Value) {
+        if let Some(user_id) = payload.get("userId").and_then(Value::as_u64) {
+            self.user_id = user_id;
+        }
+        if let Some(agent_id) = payload.get("agentId").and_then(Value::as_str) {
+            self.agent_id = Some(agent_id.to_string());
+        }
+        if let Some(api_key) = payload.get("apiKey").and_then(Value::as_str) {
+            self.credentials.api_key = Some(api_key.to_string());
+        }
+        if let Some(token) = payload
+            .get("authorization")
+            .and_then(Value::as_str)
+            .map(|value| value.trim_start_matches("Bearer ").to_string())
+        {
+            self.credentials.oidc = Some(token);
+        }
+    }
+}
+
 fn enforce_capability_token(
     token: Option<String>,
     scope: Option<&str>,
@@ -282,6 +707,7 @@ struct AppState {
     readiness: Arc<ReadinessState>,
     #[allow(dead_code)]
     dependencies: Arc<DependencyClients>,
+    uploads: reqwest::Client,
 }
 
 #[derive(Default)]
@@ -352,6 +778,8 @@ impl QdrantHandle {
 struct GatewayHttpError {
     status: StatusCode,
     message: String,
+    /// Extra fields merged into the error body.
+    details: Option<Value>,
 }
 
 impl GatewayHttpError {
@@ -359,6 +787,7 @@ impl GatewayHttpError {
         Self {
             status: StatusCode::BAD_REQUEST,
             message: message.into(),
+            details: None,
         }
     }
 
@@ -366,6 +795,7 @@ impl GatewayHttpError {
         Self {
             status: StatusCode::UNAUTHORIZED,
             message: message.into(),
+            details: None,
         }
     }
 
@@ -373,15 +803,71 @@ impl GatewayHttpError {
         Self {
             status: StatusCode::INTERNAL_SERVER_ERROR,
             message: message.into(),
+            details: None,
+        }
+    }
+
+    fn bad_gateway(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_GATEWAY,
+            message: message.into(),
+            details: None,
+        }
+    }
+}
+
+impl From<LaneError> for GatewayHttpError {
+    fn from(err: LaneError) -> Self {
+        let status = match err {
+            LaneError::QueueFull(_) => StatusCode::TOO_MANY_REQUESTS,
+            LaneError::QueueTimeout(_) => StatusCode::SERVICE_UNAVAILABLE,
+            LaneError::Config(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self {
+            status,
+            message: err.to_string(),
+            details: None,
+        }
+    }
+}
+
+impl From<PayloadError> for GatewayHttpError {
+    fn from(err: PayloadError) -> Self {
+        let (status, details) = match &err {
+            PayloadError::TooLarge { kind, limit, size } => {
+                let mut details = serde_json::json!({
+                    "code": "payload_too_large",
+                    "kind": match kind {
+                        BodyKind::Protocol(protocol) => protocol.as_str(),
+                        BodyKind::Request => "request",
+                        BodyKind::Upload => "upload",
+                    },
+                    "limit_bytes": limit,
+                    "received_bytes": size,
+                });
+                if *kind != BodyKind::Upload {
+                    details["upload_path"] = Value::from(UPLOAD_PATH);
+                }
+                (StatusCode::PAYLOAD_TOO_LARGE, Some(details))
+            }
+            PayloadError::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, None),
+        };
+        Self {
+            status,
+            message: err.to_string(),
+            details,
         }
     }
 }
 
 impl IntoResponse for GatewayHttpError {
     fn into_response(self) -> Response {
-        let body = serde_json::json!({
+        let mut body = serde_json::json!({
             "error": self.message,
         });
+        if let (Some(Value::Object(details)), Some(fields)) = (self.details, body.as_object_mut()) {
+            fields.extend(details);
+        }
         (self.status, Json(body)).into_response()
     }
 }
@@ -396,6 +882,19 @@ fn permission_from_str(value: &str) -> Option<Permission> {
     }
 }
 
+fn protocol_from_str(value: &str) -> Option<Protocol> {
+    match value.to_ascii_lowercase().as_str() {
+        "graphql" => Some(Protocol::GraphQl),
+        "grpc" => Some(Protocol::Grpc),
+        "websocket" => Some(Protocol::WebSocket),
+        _ => None,
+    }
+}
+
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+    header_value(headers, header::CONTENT_LENGTH.as_str()).and_then(|v| v.parse().ok())
+}
+
 fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
     headers
         .get(name)
@@ -452,6 +951,23 @@ mod tests {
         assert_eq!(permission_from_str("unknown"), None);
     }
 
+    #[test]
+    fn oversized_bodies_are_rejected_with_a_structured_error() {
+        assert_eq!(protocol_from_str("GraphQL"), Some(Protocol::GraphQl));
+        assert_eq!(protocol_from_str("soap"), None);
+
+        let meter = PayloadConfig::default()
+            .limits
+            .request_meter(Some(&Protocol::GraphQl));
+        let err = GatewayHttpError::from(meter.check_declared(Some(8 << 20)).unwrap_err());
+        assert_eq!(err.status, StatusCode::PAYLOAD_TOO_LARGE);
+        let details = err.details.expect("limit details");
+        assert_eq!(details["code"], "payload_too_large");
+        assert_eq!(details["kind"], "graphql");
+        assert_eq!(details["received_bytes"], 8 << 20);
+        assert_eq!(details["upload_path"], UPLOAD_PATH);
+    }
+
     #[test]
     fn parses_log_format_variants() {
         assert_eq!(LogFormat::from_str("json").unwrap(), LogFormat::Json);
@@ -0,0 +1,329 @@
+//! Request body limits per protocol and metering for streamed uploads.
+//!
+//! Routed requests are buffered before they are parsed, so each protocol has a ceiling
+//! on how many bytes the gateway will hold for it. A [`BodyMeter`] counts a body as it
+//! arrives and cuts it off once it passes its limit, which lets a request with a declared
+//! `Content-Length` be refused before any of it is read and a chunked one be refused
+//! without reading past the limit. Large files skip buffering entirely: they are streamed
+//! through to the file upload API under a separate, much larger limit.
+
+use axum::body::Bytes;
+use futures_util::{Stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::router::Protocol;
+
+pub const DEFAULT_GRAPHQL_BODY_LIMIT: u64 = 1024 * 1024;
+/// Matches the default maximum message size of gRPC servers.
+pub const DEFAULT_GRPC_BODY_LIMIT: u64 = 4 * 1024 * 1024;
+pub const DEFAULT_WEBSOCKET_BODY_LIMIT: u64 = 64 * 1024;
+pub const DEFAULT_UPLOAD_BODY_LIMIT: u64 = 1024 * 1024 * 1024;
+/// Upload endpoint of the UI API, where streamed uploads are sent by default.
+pub const DEFAULT_UPLOAD_URL: &str = "http://127.0.0.1:8787/api/uploads";
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Largest body, in bytes, accepted for each kind of request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BodyLimits {
+    pub graphql: u64,
+    pub grpc: u64,
+    /// Routed WebSocket requests and each message on a subscription socket.
+    pub websocket: u64,
+    /// Uploads streamed to the file upload API.
+    pub upload: u64,
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self {
+            graphql: DEFAULT_GRAPHQL_BODY_LIMIT,
+            grpc: DEFAULT_GRPC_BODY_LIMIT,
+            websocket: DEFAULT_WEBSOCKET_BODY_LIMIT,
+            upload: DEFAULT_UPLOAD_BODY_LIMIT,
+        }
+    }
+}
+
+impl BodyLimits {
+    pub fn limit_for(&self, protocol: &Protocol) -> u64 {
+        match protocol {
+            Protocol::GraphQl => self.graphql,
+            Protocol::Grpc => self.grpc,
+            Protocol::WebSocket => self.websocket,
+        }
+    }
+
+    /// Meter for a routed request. Before its protocol is known, a request may be as
+    /// large as the most generous protocol allows.
+    pub fn request_meter(&self, protocol: Option<&Protocol>) -> BodyMeter {
+        match protocol {
+            Some(protocol) => BodyMeter::new(
+                BodyKind::Protocol(protocol.clone()),
+                self.limit_for(protocol),
+            ),
+            None => BodyMeter::new(
+                BodyKind::Request,
+                self.graphql.max(self.grpc).max(self.websocket),
+            ),
+        }
+    }
+
+    pub fn upload_meter(&self) -> BodyMeter {
+        BodyMeter::new(BodyKind::Upload, self.upload)
+    }
+
+    /// Check a buffered request against the limit of the protocol it turned out to use.
+    pub fn check_protocol(&self, protocol: &Protocol, size: u64) -> Result<(), PayloadError> {
+        let limit = self.limit_for(protocol);
+        if size > limit {
+            return Err(PayloadError::TooLarge {
+                kind: BodyKind::Protocol(protocol.clone()),
+                limit,
+                size,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Body limits and where streamed uploads go, read from `gateway_payloads.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadConfig {
+    #[serde(default)]
+    pub limits: BodyLimits,
+    #[serde(default = "default_upload_url")]
+    pub upload_url: String,
+}
+
+fn default_upload_url() -> String {
+    DEFAULT_UPLOAD_URL.to_string()
+}
+
+impl Default for PayloadConfig {
+    fn default() -> Self {
+        Self {
+            limits: BodyLimits::default(),
+            upload_url: default_upload_url(),
+        }
+    }
+}
+
+impl PayloadConfig {
+    pub fn load(path: &Path) -> Result<Self, PayloadError> {
+        let raw = std::fs::read(path)
+            .map_err(|err| PayloadError::Config(format!("{}: {err}", path.display())))?;
+        serde_json::from_slice(&raw)
+            .map_err(|err| PayloadError::Config(format!("{}: {err}", path.display())))
+    }
+}
+
+/// What a body limit applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BodyKind {
+    Protocol(Protocol),
+    /// A routed request whose protocol is not known yet.
+    Request,
+    Upload,
+}
+
+impl fmt::Display for BodyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyKind::Protocol(protocol) => write!(f, "{protocol} request"),
+            BodyKind::Request => f.write_str("request"),
+            BodyKind::Upload => f.write_str("upload"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PayloadError {
+    /// `size` is the declared length, or the bytes read before the body was cut off.
+    #[error("{kind} body of {size} bytes exceeds the {limit} byte limit")]
+    TooLarge {
+        kind: BodyKind,
+        limit: u64,
+        size: u64,
+    },
+    #[error("invalid payload configuration: {0}")]
+    Config(String),
+}
+
+/// Counts the bytes of one body and cuts it off once they pass the limit.
+#[derive(Debug, Clone)]
+pub struct BodyMeter {
+    kind: BodyKind,
+    limit: u64,
+    received: Arc<AtomicU64>,
+}
+
+impl BodyMeter {
+    pub fn new(kind: BodyKind, limit: u64) -> Self {
+        Self {
+            kind,
+            limit,
+            received: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    pub fn received(&self) -> u64 {
+        self.received.load(Ordering::Acquire)
+    }
+
+    /// Refuse a body by its declared length, before reading any of it.
+    pub fn check_declared(&self, content_length: Option<u64>) -> Result<(), PayloadError> {
+        match content_length {
+            Some(size) if size > self.limit => Err(self.too_large(size)),
+            _ => Ok(()),
+        }
+    }
+
+    /// The limit error, once the metered body has passed its limit.
+    pub fn error(&self) -> Option<PayloadError> {
+        let received = self.received();
+        (received > self.limit).then(|| self.too_large(received))
+    }
+
+    /// Pass `stream` through, failing it with [`PayloadError::TooLarge`] on the chunk
+    /// that takes it past the limit.
+    pub fn meter<S, E>(
+        &self,
+        stream: S,
+    ) -> impl Stream<Item = Result<Bytes, BoxError>> + Send + 'static
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        let meter = self.clone();
+        stream.map(move |chunk| {
+            let chunk = chunk.map_err(Into::into)?;
+            let received = meter
+                .received
+                .fetch_add(chunk.len() as u64, Ordering::AcqRel)
+                + chunk.len() as u64;
+            if received > meter.limit {
+                return Err(meter.too_large(received).into());
+            }
+            Ok(chunk)
+        })
+    }
+
+    /// Buffer a whole body, stopping at the chunk that takes it past the limit.
+    pub async fn collect<S, E>(&self, stream: S) -> Result<Vec<u8>, BoxError>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        self.meter(stream)
+            .try_fold(Vec::new(), |mut body, chunk| async move {
+                body.extend_from_slice(&chunk);
+                Ok(body)
+            })
+            .await
+    }
+
+    fn too_large(&self, size: u64) -> PayloadError {
+        PayloadError::TooLarge {
+            kind: self.kind.clone(),
+            limit: self.limit,
+            size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+    use std::convert::Infallible;
+
+    fn chunks(sizes: &[usize]) -> impl Stream<Item = Result<Bytes, Infallible>> + Send + 'static {
+        stream::iter(
+            sizes
+                .iter()
+                .map(|size| Ok(Bytes::from(vec![b'x'; *size])))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn limits_follow_the_protocol() {
+        let limits = BodyLimits {
+            graphql: 10,
+            grpc: 40,
+            websocket: 5,
+            upload: 1_000,
+        };
+        assert!(limits.check_protocol(&Protocol::Grpc, 40).is_ok());
+        assert_eq!(
+            limits.check_protocol(&Protocol::GraphQl, 11),
+            Err(PayloadError::TooLarge {
+                kind: BodyKind::Protocol(Protocol::GraphQl),
+                limit: 10,
+                size: 11,
+            })
+        );
+        assert_eq!(limits.request_meter(None).limit(), 40);
+        assert_eq!(limits.request_meter(Some(&Protocol::WebSocket)).limit(), 5);
+
+        let meter = limits.upload_meter();
+        assert!(meter.check_declared(None).is_ok());
+        let err = meter.check_declared(Some(1_001)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "upload body of 1001 bytes exceeds the 1000 byte limit"
+        );
+    }
+
+    #[tokio::test]
+    async fn meters_cut_off_bodies_past_their_limit() {
+        let meter = BodyMeter::new(BodyKind::Request, 10);
+        let body = meter.collect(chunks(&[4, 6])).await.unwrap();
+        assert_eq!(body.len(), 10);
+        assert!(meter.error().is_none());
+
+        let meter = BodyMeter::new(BodyKind::Upload, 10);
+        let mut metered = Box::pin(meter.meter(chunks(&[4, 4, 4, 4])));
+        let mut passed = 0;
+        let err = loop {
+            match metered.next().await {
+                Some(Ok(chunk)) => passed += chunk.len(),
+                Some(Err(err)) => break err,
+                None => panic!("metered stream should fail"),
+            }
+        };
+        assert_eq!(passed, 8);
+        assert_eq!(
+            err.downcast_ref::<PayloadError>(),
+            meter.error().as_ref(),
+            "the stream and the meter report the same error"
+        );
+        assert_eq!(meter.received(), 12);
+    }
+
+    #[test]
+    fn config_files_may_set_only_some_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gateway_payloads.json");
+        std::fs::write(&path, r#"{ "limits": { "grpc": 1024 } }"#).unwrap();
+        let config = PayloadConfig::load(&path).unwrap();
+        assert_eq!(config.limits.grpc, 1024);
+        assert_eq!(config.limits.graphql, DEFAULT_GRAPHQL_BODY_LIMIT);
+        assert_eq!(config.upload_url, DEFAULT_UPLOAD_URL);
+        assert!(matches!(
+            PayloadConfig::load(&dir.path().join("missing.json")),
+            Err(PayloadError::Config(_))
+        ));
+    }
+}
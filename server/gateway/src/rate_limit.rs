@@ -1,15 +1,43 @@
+use chrono::{DateTime, Utc};
+use fs4::FileExt;
 use noa_agents::registry::AgentRegistry;
 use noa_agents::unified_types::AgentLayer;
 use parking_lot::Mutex;
-use std::collections::HashMap;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
 use std::time::{Duration, Instant};
 use thiserror::Error;
 
+/// Directory the gateway binary keeps buckets in, under its storage directory.
+pub const DEFAULT_BUCKET_DIR: &str = "rate_limit_buckets";
+
+/// Shortest pause between store flushes, however small the configured interval.
+const MIN_PERSIST_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How bucket state is shared between gateway instances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BucketMode {
+    /// Buckets live in memory; changed buckets are flushed to the store periodically.
+    #[default]
+    Local,
+    /// Instances merge the tokens they take into the store every persist interval and
+    /// adopt each other's usage, so all of them enforce one limit per agent. An instance
+    /// can overshoot by what it takes within one interval.
+    Shared,
+}
+
 #[derive(Debug, Clone)]
 pub struct RateLimiterConfig {
     pub refill_interval: Duration,
     pub layer_limits: HashMap<AgentLayer, u32>,
+    pub mode: BucketMode,
+    pub persist_interval: Duration,
 }
 
 impl Default for RateLimiterConfig {
@@ -24,6 +52,54 @@ impl Default for RateLimiterConfig {
         Self {
             refill_interval: Duration::from_secs(60),
             layer_limits,
+            mode: BucketMode::Local,
+            persist_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Where and how a bootstrapped gateway persists its buckets, read from
+/// `gateway_rate_limits.json`.
+///
+/// Buckets are kept as JSON files in a directory guarded by an OS advisory lock. The
+/// core has no key-value capability to hold them, and files on local disk are what
+/// instances on one host can share.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketStoreConfig {
+    #[serde(default)]
+    pub mode: BucketMode,
+    /// Bucket directory; relative paths are resolved against the gateway's storage directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// How often buckets are flushed or synced; defaults to the limiter's interval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persist_interval_ms: Option<u64>,
+}
+
+impl BucketStoreConfig {
+    pub fn load(path: &Path) -> Result<Self, RateLimitError> {
+        let raw = fs::read(path)
+            .map_err(|err| RateLimitError::Persistence(format!("{}: {err}", path.display())))?;
+        serde_json::from_slice(&raw)
+            .map_err(|err| RateLimitError::Persistence(format!("{}: {err}", path.display())))
+    }
+
+    pub fn store_path(&self, storage_dir: &Path) -> PathBuf {
+        match &self.path {
+            Some(path) => storage_dir.join(path),
+            None => storage_dir.join(DEFAULT_BUCKET_DIR),
+        }
+    }
+
+    /// `config` with this store's mode and flush interval.
+    pub fn apply(&self, config: RateLimiterConfig) -> RateLimiterConfig {
+        RateLimiterConfig {
+            mode: self.mode,
+            persist_interval: self
+                .persist_interval_ms
+                .map(Duration::from_millis)
+                .unwrap_or(config.persist_interval),
+            ..config
         }
     }
 }
@@ -34,19 +110,396 @@ pub enum RateLimitError {
     MissingAgentIdentity,
     #[error("rate limit exceeded for agent {0}")]
     LimitExceeded(String),
+    #[error("rate limit state unavailable: {0}")]
+    Persistence(String),
+}
+
+/// Persisted view of a single agent's token bucket.
+///
+/// Wall-clock timestamps are used so buckets survive restarts and can be shared
+/// between processes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketState {
+    pub remaining: u32,
+    pub last_refill: DateTime<Utc>,
+}
+
+impl BucketState {
+    fn full(limit: u32, now: DateTime<Utc>) -> Self {
+        Self {
+            remaining: limit,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, limit: u32, refill_interval: Duration, now: DateTime<Utc>) {
+        let elapsed = (now - self.last_refill).to_std().unwrap_or(Duration::ZERO);
+        if elapsed >= refill_interval {
+            self.remaining = limit;
+            self.last_refill = now;
+        }
+        self.remaining = self.remaining.min(limit);
+    }
+
+    /// Refill if the interval elapsed, then try to take one token.
+    fn take(&mut self, limit: u32, refill_interval: Duration, now: DateTime<Utc>) -> bool {
+        self.refill(limit, refill_interval, now);
+        if self.remaining == 0 {
+            return false;
+        }
+
+        self.remaining -= 1;
+        true
+    }
+
+    /// Refill if the interval elapsed, then charge `tokens` taken elsewhere.
+    fn charge(&mut self, tokens: u32, limit: u32, refill_interval: Duration, now: DateTime<Utc>) {
+        self.refill(limit, refill_interval, now);
+        self.remaining = self.remaining.saturating_sub(tokens);
+    }
+}
+
+/// Backing store for token buckets.
+pub trait BucketStore: Send + Sync {
+    /// Load every persisted bucket.
+    fn load(&self) -> Result<HashMap<String, BucketState>, RateLimitError>;
+
+    /// Persist `changed`, leaving every other bucket as it is.
+    fn save(&self, changed: &HashMap<String, BucketState>) -> Result<(), RateLimitError>;
+
+    /// Apply `update` to the persisted buckets while holding the store lock, persisting
+    /// the buckets it changed.
+    fn transact(
+        &self,
+        update: &mut dyn FnMut(&mut HashMap<String, BucketState>),
+    ) -> Result<(), RateLimitError>;
+}
+
+/// A bucket file: the agent it belongs to and its state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredBucket {
+    agent_id: String,
+    #[serde(flatten)]
+    state: BucketState,
 }
 
-#[derive(Debug)]
-struct RateState {
-    remaining: u32,
-    last_refill: Instant,
+/// Directory with one JSON file per bucket, guarded by an advisory lock on a sibling
+/// lock file so gateway processes on the same host can share it. Writing a bucket
+/// rewrites only its own file. The OS drops the lock when its holder exits, so a
+/// crashed process cannot leave the store locked.
+#[derive(Debug, Clone)]
+pub struct FileBucketStore {
+    dir: PathBuf,
+    lock_path: PathBuf,
+    lock_timeout: Duration,
+}
+
+impl FileBucketStore {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        let dir = dir.as_ref().to_path_buf();
+        let lock_path = dir.with_extension("lock");
+        Self {
+            dir,
+            lock_path,
+            lock_timeout: Duration::from_secs(2),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// File holding `agent_id`'s bucket. Ids are hex-encoded so any id is a safe file name.
+    fn bucket_path(&self, agent_id: &str) -> PathBuf {
+        let name: String = agent_id.bytes().map(|byte| format!("{byte:02x}")).collect();
+        self.dir.join(format!("{name}.json"))
+    }
+
+    fn lock(&self) -> Result<FileLockGuard, RateLimitError> {
+        if let Some(parent) = self.lock_path.parent() {
+            fs::create_dir_all(parent).map_err(persistence_error)?;
+        }
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.lock_path)
+            .map_err(persistence_error)?;
+        let contended = fs4::lock_contended_error().raw_os_error();
+        let started = Instant::now();
+        loop {
+            match file.try_lock_exclusive() {
+                Ok(()) => return Ok(FileLockGuard { file }),
+                Err(err) if err.raw_os_error() == contended => {
+                    if started.elapsed() >= self.lock_timeout {
+                        return Err(RateLimitError::Persistence(format!(
+                            "timed out waiting for {}",
+                            self.lock_path.display()
+                        )));
+                    }
+                    thread::sleep(Duration::from_millis(2));
+                }
+                Err(err) => return Err(persistence_error(err)),
+            }
+        }
+    }
+
+    fn read(&self) -> Result<HashMap<String, BucketState>, RateLimitError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(err) => return Err(persistence_error(err)),
+        };
+        let mut buckets = HashMap::new();
+        for entry in entries {
+            let path = entry.map_err(persistence_error)?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let bytes = fs::read(&path).map_err(persistence_error)?;
+            match serde_json::from_slice::<StoredBucket>(&bytes) {
+                Ok(stored) => {
+                    buckets.insert(stored.agent_id, stored.state);
+                }
+                Err(err) => Self::quarantine(&path, &err),
+            }
+        }
+        Ok(buckets)
+    }
+
+    /// Move an unreadable bucket file aside so its agent starts with a full bucket
+    /// instead of the store failing to load.
+    fn quarantine(path: &Path, err: &serde_json::Error) {
+        let aside = path.with_extension("corrupt");
+        match fs::rename(path, &aside) {
+            Ok(()) => tracing::warn!(
+                "corrupt rate limit bucket {} moved to {}: {}",
+                path.display(),
+                aside.display(),
+                err
+            ),
+            Err(rename_err) => tracing::warn!(
+                "corrupt rate limit bucket {} ignored ({}); could not move it aside: {}",
+                path.display(),
+                err,
+                rename_err
+            ),
+        }
+    }
+
+    fn write(&self, changed: &HashMap<String, BucketState>) -> Result<(), RateLimitError> {
+        if changed.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir).map_err(persistence_error)?;
+        for (agent_id, state) in changed {
+            let stored = StoredBucket {
+                agent_id: agent_id.clone(),
+                state: state.clone(),
+            };
+            let json = serde_json::to_vec_pretty(&stored).map_err(persistence_error)?;
+            let path = self.bucket_path(agent_id);
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, json).map_err(persistence_error)?;
+            fs::rename(&tmp, &path).map_err(persistence_error)?;
+        }
+        Ok(())
+    }
+}
+
+impl BucketStore for FileBucketStore {
+    fn load(&self) -> Result<HashMap<String, BucketState>, RateLimitError> {
+        let _guard = self.lock()?;
+        self.read()
+    }
+
+    fn save(&self, changed: &HashMap<String, BucketState>) -> Result<(), RateLimitError> {
+        let _guard = self.lock()?;
+        self.write(changed)
+    }
+
+    fn transact(
+        &self,
+        update: &mut dyn FnMut(&mut HashMap<String, BucketState>),
+    ) -> Result<(), RateLimitError> {
+        let _guard = self.lock()?;
+        let mut buckets = self.read()?;
+        let before = buckets.clone();
+        update(&mut buckets);
+        buckets.retain(|agent_id, state| before.get(agent_id) != Some(state));
+        self.write(&buckets)
+    }
+}
+
+struct FileLockGuard {
+    file: File,
+}
+
+impl Drop for FileLockGuard {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+fn persistence_error(err: impl ToString) -> RateLimitError {
+    RateLimitError::Persistence(err.to_string())
+}
+
+/// Tokens taken since the last shared sync, and the limit they were taken under.
+#[derive(Debug, Clone, Copy)]
+struct TakenTokens {
+    count: u32,
+    limit: u32,
+}
+
+/// In-memory buckets plus the changes the store has not seen yet.
+#[derive(Debug, Default)]
+struct Buckets {
+    states: HashMap<String, BucketState>,
+    /// Local mode: buckets changed since the last flush.
+    dirty: HashSet<String>,
+    /// Shared mode: tokens taken since the last sync.
+    taken: HashMap<String, TakenTokens>,
+}
+
+/// What the store writer thread needs to bring the store up to date.
+struct StoreSync {
+    store: Arc<dyn BucketStore>,
+    buckets: Arc<Mutex<Buckets>>,
+    mode: BucketMode,
+    refill_interval: Duration,
+    failures: AtomicU64,
+}
+
+impl StoreSync {
+    fn flush(&self) -> Result<(), RateLimitError> {
+        match self.mode {
+            BucketMode::Local => self.flush_changed(),
+            BucketMode::Shared => self.sync_shared(),
+        }
+    }
+
+    /// A failed flush is logged and retried on the next one.
+    fn flush_logged(&self) {
+        if let Err(err) = self.flush() {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!("failed to persist rate limiter state: {}", err);
+        }
+    }
+
+    /// Write the buckets changed since the last flush.
+    fn flush_changed(&self) -> Result<(), RateLimitError> {
+        let changed: HashMap<String, BucketState> = {
+            let mut buckets = self.buckets.lock();
+            let dirty = std::mem::take(&mut buckets.dirty);
+            dirty
+                .into_iter()
+                .filter_map(|agent_id| {
+                    let state = buckets.states.get(&agent_id).cloned()?;
+                    Some((agent_id, state))
+                })
+                .collect()
+        };
+        if changed.is_empty() {
+            return Ok(());
+        }
+        self.store.save(&changed).inspect_err(|_| {
+            self.buckets.lock().dirty.extend(changed.keys().cloned());
+        })
+    }
+
+    /// Charge the tokens taken here since the last sync to the store, then adopt the
+    /// stored buckets so tokens taken by other instances count here too.
+    fn sync_shared(&self) -> Result<(), RateLimitError> {
+        let taken = std::mem::take(&mut self.buckets.lock().taken);
+        let now = Utc::now();
+        let refill_interval = self.refill_interval;
+        let mut merged = HashMap::new();
+        let result = self.store.transact(&mut |stored| {
+            for (agent_id, tokens) in &taken {
+                stored
+                    .entry(agent_id.clone())
+                    .or_insert_with(|| BucketState::full(tokens.limit, now))
+                    .charge(tokens.count, tokens.limit, refill_interval, now);
+            }
+            merged = stored.clone();
+        });
+
+        let mut buckets = self.buckets.lock();
+        let Buckets {
+            states,
+            taken: pending,
+            ..
+        } = &mut *buckets;
+        if let Err(err) = result {
+            for (agent_id, tokens) in taken {
+                pending
+                    .entry(agent_id)
+                    .and_modify(|entry| entry.count += tokens.count)
+                    .or_insert(tokens);
+            }
+            return Err(err);
+        }
+        // Tokens taken while the store was being synced are charged on the next sync,
+        // but already count against the adopted buckets.
+        for (agent_id, mut state) in merged {
+            if let Some(tokens) = pending.get(&agent_id) {
+                state.remaining = state.remaining.saturating_sub(tokens.count);
+            }
+            states.insert(agent_id, state);
+        }
+        Ok(())
+    }
+}
+
+/// Thread that flushes a limiter's buckets every persist interval and once more when
+/// the limiter is dropped, so checks never wait on the store.
+struct StoreWriter {
+    shutdown: Option<mpsc::Sender<()>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl StoreWriter {
+    fn spawn(sync: Arc<StoreSync>, interval: Duration) -> Result<Self, RateLimitError> {
+        let interval = interval.max(MIN_PERSIST_INTERVAL);
+        let (shutdown, stop) = mpsc::channel::<()>();
+        let handle = thread::Builder::new()
+            .name("rate-limit-store".into())
+            .spawn(move || loop {
+                let stopping = !matches!(
+                    stop.recv_timeout(interval),
+                    Err(mpsc::RecvTimeoutError::Timeout)
+                );
+                sync.flush_logged();
+                if stopping {
+                    break;
+                }
+            })
+            .map_err(persistence_error)?;
+        Ok(Self {
+            shutdown: Some(shutdown),
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for StoreWriter {
+    fn drop(&mut self) {
+        drop(self.shutdown.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
 }
 
 /// Token bucket rate limiter informed by hive mind metadata.
 pub struct RateLimiter {
     config: RateLimiterConfig,
     registry: Arc<AgentRegistry>,
-    states: Mutex<HashMap<String, RateState>>,
+    buckets: Arc<Mutex<Buckets>>,
+    sync: Option<Arc<StoreSync>>,
+    writer: Option<StoreWriter>,
 }
 
 impl RateLimiter {
@@ -54,11 +507,43 @@ impl RateLimiter {
         Self {
             config,
             registry,
-            states: Mutex::new(HashMap::new()),
+            buckets: Arc::new(Mutex::new(Buckets::default())),
+            sync: None,
+            writer: None,
         }
     }
 
+    /// Attach a bucket store, restoring any buckets persisted by a previous run, and
+    /// start the thread that keeps the store up to date.
+    pub fn with_store(mut self, store: Arc<dyn BucketStore>) -> Result<Self, RateLimitError> {
+        self.buckets.lock().states = store.load()?;
+        let sync = Arc::new(StoreSync {
+            store,
+            buckets: self.buckets.clone(),
+            mode: self.config.mode,
+            refill_interval: self.config.refill_interval,
+            failures: AtomicU64::new(0),
+        });
+        self.writer = Some(StoreWriter::spawn(
+            sync.clone(),
+            self.config.persist_interval,
+        )?);
+        self.sync = Some(sync);
+        Ok(self)
+    }
+
     pub fn check(&self, agent_id: &Option<String>) -> Result<(), RateLimitError> {
+        self.check_scaled(agent_id, 1.0)
+    }
+
+    /// Check the bucket using a fraction of the configured layer limit.
+    ///
+    /// Used to apply stricter limits to agents with a degraded standing.
+    pub fn check_scaled(
+        &self,
+        agent_id: &Option<String>,
+        limit_factor: f64,
+    ) -> Result<(), RateLimitError> {
         let agent_id = agent_id
             .as_ref()
             .ok_or(RateLimitError::MissingAgentIdentity)?
@@ -70,25 +555,62 @@ impl RateLimiter {
             .map(|m| m.layer)
             .unwrap_or(AgentLayer::L5Infrastructure);
 
-        let limit = self.config.layer_limits.get(&layer).copied().unwrap_or(50);
+        let base_limit = self.config.layer_limits.get(&layer).copied().unwrap_or(50);
+        let limit = ((base_limit as f64 * limit_factor.clamp(0.0, 1.0)) as u32).max(1);
+        let now = Utc::now();
 
-        let mut states = self.states.lock();
-        let entry = states.entry(agent_id.clone()).or_insert_with(|| RateState {
-            remaining: limit,
-            last_refill: Instant::now(),
-        });
+        let allowed = {
+            let mut buckets = self.buckets.lock();
+            let allowed = buckets
+                .states
+                .entry(agent_id.clone())
+                .or_insert_with(|| BucketState::full(limit, now))
+                .take(limit, self.config.refill_interval, now);
+            if self.sync.is_some() {
+                match self.config.mode {
+                    BucketMode::Local => {
+                        buckets.dirty.insert(agent_id.clone());
+                    }
+                    BucketMode::Shared if allowed => {
+                        let tokens = buckets
+                            .taken
+                            .entry(agent_id.clone())
+                            .or_insert(TakenTokens { count: 0, limit });
+                        tokens.count += 1;
+                        tokens.limit = limit;
+                    }
+                    BucketMode::Shared => {}
+                }
+            }
+            allowed
+        };
 
-        let elapsed = entry.last_refill.elapsed();
-        if elapsed >= self.config.refill_interval {
-            entry.remaining = limit;
-            entry.last_refill = Instant::now();
+        if allowed {
+            Ok(())
+        } else {
+            Err(RateLimitError::LimitExceeded(agent_id))
         }
+    }
 
-        if entry.remaining == 0 {
-            return Err(RateLimitError::LimitExceeded(agent_id));
+    /// Bring the attached store up to date now rather than on the writer's next pass.
+    /// In shared mode this also adopts the usage other instances have synced.
+    pub fn persist(&self) -> Result<(), RateLimitError> {
+        match &self.sync {
+            Some(sync) => sync.flush(),
+            None => Ok(()),
         }
+    }
 
-        entry.remaining -= 1;
-        Ok(())
+    /// Bucket of `agent_id` as this limiter last saw it.
+    pub fn bucket(&self, agent_id: &str) -> Option<BucketState> {
+        self.buckets.lock().states.get(agent_id).cloned()
+    }
+
+    /// Background flushes that failed; the checks made meanwhile were still answered.
+    pub fn persist_failures(&self) -> u64 {
+        self.sync
+            .as_ref()
+            .map(|sync| sync.failures.load(Ordering::Relaxed))
+            .unwrap_or(0)
     }
 }
@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
 use thiserror::Error;
 
+use crate::subscription::{is_subscription, SubscriptionError, SubscriptionRequest};
+
 /// Supported protocols by the programmable router.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Protocol {
@@ -12,6 +15,22 @@ pub enum Protocol {
     WebSocket,
 }
 
+impl Protocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::GraphQl => "graphql",
+            Protocol::Grpc => "grpc",
+            Protocol::WebSocket => "websocket",
+        }
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Routing plan describing downstream targets and behaviour.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutePlan {
@@ -34,6 +53,8 @@ impl RoutePlan {
 pub enum RoutingError {
     #[error("missing field: {0}")]
     MissingField(&'static str),
+    #[error("invalid subscription: {0}")]
+    Subscription(#[from] SubscriptionError),
 }
 
 /// Programmable router that understands multiple transport protocols.
@@ -65,7 +86,33 @@ impl ProgrammableRouter {
         }
     }
 
+    /// Route a parsed subscription to the event topic it follows.
+    pub fn route_subscription(&self, request: &SubscriptionRequest) -> RoutePlan {
+        let mut plan = RoutePlan::new(Protocol::GraphQl);
+        plan.targets.push(request.topic.channel().to_string());
+        plan.metadata
+            .insert("mode".into(), Value::String("subscription".into()));
+        plan.metadata.insert(
+            "field".into(),
+            Value::String(request.topic.field().to_string()),
+        );
+        if let Some(subject) = &request.subject {
+            plan.metadata
+                .insert("subject".into(), Value::String(subject.clone()));
+        }
+        plan
+    }
+
     fn route_graphql(&self, payload: &Value) -> Result<RoutePlan, RoutingError> {
+        if let Some(query) = payload
+            .get("query")
+            .and_then(|v| v.as_str())
+            .filter(|query| is_subscription(query))
+        {
+            let variables = payload.get("variables").cloned().unwrap_or(Value::Null);
+            let request = SubscriptionRequest::parse("route", query, &variables)?;
+            return Ok(self.route_subscription(&request));
+        }
         let federation = payload
             .get("federation")
             .ok_or(RoutingError::MissingField("federation"))?;
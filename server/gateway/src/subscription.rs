@@ -0,0 +1,689 @@
+use noa_core::security::Permission;
+use noa_core::world::{HostCapabilities, HOST_CAPABILITIES_EVENT};
+use noa_workflow::{WorkflowEvent, WorkflowEventStream};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Interval after which an idle subscription emits a server ping.
+pub const DEFAULT_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// Event families that GraphQL subscriptions can follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTopic {
+    Workflow,
+    Pipeline,
+    Agent,
+    /// Capability broadcasts from the host, see [`HostCapabilities`].
+    Host,
+}
+
+impl EventTopic {
+    /// Root subscription field exposing this topic.
+    pub fn field(&self) -> &'static str {
+        match self {
+            EventTopic::Workflow => "workflowEvents",
+            EventTopic::Pipeline => "pipelineEvents",
+            EventTopic::Agent => "agentEvents",
+            EventTopic::Host => "hostCapabilities",
+        }
+    }
+
+    /// Argument filtering the topic down to one workflow, pipeline, or agent.
+    pub fn subject_argument(&self) -> &'static str {
+        match self {
+            EventTopic::Workflow => "workflowId",
+            EventTopic::Pipeline => "pipelineId",
+            EventTopic::Agent => "agentId",
+            EventTopic::Host => "hostId",
+        }
+    }
+
+    /// Route target used in plans and telemetry.
+    pub fn channel(&self) -> &'static str {
+        match self {
+            EventTopic::Workflow => "workflow-events",
+            EventTopic::Pipeline => "pipeline-events",
+            EventTopic::Agent => "agent-events",
+            EventTopic::Host => "host-capabilities",
+        }
+    }
+
+    pub fn from_field(field: &str) -> Option<Self> {
+        [
+            EventTopic::Workflow,
+            EventTopic::Pipeline,
+            EventTopic::Agent,
+            EventTopic::Host,
+        ]
+        .into_iter()
+        .find(|topic| topic.field() == field)
+    }
+}
+
+/// Fields selectable on every subscription payload.
+const EVENT_FIELDS: &[&str] = &["topic", "subject", "eventType", "data", "timestamp"];
+
+/// Typed event delivered to subscribers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionEvent {
+    pub topic: EventTopic,
+    /// Workflow, pipeline, or agent the event belongs to.
+    pub subject: String,
+    pub event_type: String,
+    pub data: Value,
+    pub timestamp: String,
+}
+
+impl SubscriptionEvent {
+    pub fn new(
+        topic: EventTopic,
+        subject: impl Into<String>,
+        event_type: impl Into<String>,
+        data: Value,
+    ) -> Self {
+        Self {
+            topic,
+            subject: subject.into(),
+            event_type: event_type.into(),
+            data,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+impl From<&WorkflowEvent> for SubscriptionEvent {
+    fn from(event: &WorkflowEvent) -> Self {
+        let mut data = serde_json::to_value(event).unwrap_or(Value::Null);
+        let (event_type, subject, timestamp) = match data.as_object_mut() {
+            Some(fields) => (
+                take_string(fields, "type"),
+                take_string(fields, "workflow_id"),
+                take_string(fields, "timestamp"),
+            ),
+            None => Default::default(),
+        };
+        Self {
+            topic: EventTopic::Workflow,
+            subject,
+            event_type,
+            data,
+            timestamp,
+        }
+    }
+}
+
+impl From<&HostCapabilities> for SubscriptionEvent {
+    fn from(capabilities: &HostCapabilities) -> Self {
+        Self::new(
+            EventTopic::Host,
+            capabilities.host.clone(),
+            HOST_CAPABILITIES_EVENT,
+            serde_json::to_value(capabilities).unwrap_or(Value::Null),
+        )
+    }
+}
+
+fn take_string(fields: &mut Map<String, Value>, key: &str) -> String {
+    match fields.remove(key) {
+        Some(Value::String(value)) => value,
+        _ => String::new(),
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SubscriptionError {
+    #[error("operation is not a subscription")]
+    NotASubscription,
+    #[error("malformed subscription: {0}")]
+    Malformed(String),
+    #[error("unknown subscription field '{0}'")]
+    UnknownField(String),
+    #[error("unknown argument '{argument}' on {field}")]
+    UnknownArgument { field: String, argument: String },
+    #[error("variable '${0}' is not defined")]
+    MissingVariable(String),
+    #[error("field '{0}' does not exist on subscription events")]
+    UnknownSelection(String),
+}
+
+/// A parsed GraphQL subscription operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriptionRequest {
+    pub id: String,
+    pub topic: EventTopic,
+    /// Response key, the alias when one was given.
+    pub response_key: String,
+    pub subject: Option<String>,
+    pub event_type: Option<String>,
+    /// Selected event fields; every field when the selection set is omitted.
+    pub selection: Vec<String>,
+}
+
+impl SubscriptionRequest {
+    /// Parse a single-root-field subscription such as
+    /// `subscription { workflowEvents(workflowId: $id) { eventType data } }`.
+    pub fn parse(
+        id: impl Into<String>,
+        query: &str,
+        variables: &Value,
+    ) -> Result<Self, SubscriptionError> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        match parser.next() {
+            Some(Token::Name(keyword)) if keyword == "subscription" => {}
+            _ => return Err(SubscriptionError::NotASubscription),
+        }
+        if let Some(Token::Name(_)) = parser.peek() {
+            parser.next();
+        }
+        if parser.peek() == Some(&Token::Punct('(')) {
+            parser.skip_group('(', ')')?;
+        }
+        parser.expect('{')?;
+
+        let mut field = parser.name()?;
+        let response_key = field.clone();
+        if parser.peek() == Some(&Token::Punct(':')) {
+            parser.next();
+            field = parser.name()?;
+        }
+        let topic = EventTopic::from_field(&field)
+            .ok_or_else(|| SubscriptionError::UnknownField(field.clone()))?;
+
+        let mut subject = None;
+        let mut event_type = None;
+        if parser.peek() == Some(&Token::Punct('(')) {
+            parser.next();
+            while parser.peek() != Some(&Token::Punct(')')) {
+                let argument = parser.name()?;
+                parser.expect(':')?;
+                let value = parser.value(variables)?;
+                if argument == topic.subject_argument() {
+                    subject = Some(value);
+                } else if argument == "eventType" {
+                    event_type = Some(value);
+                } else {
+                    return Err(SubscriptionError::UnknownArgument { field, argument });
+                }
+            }
+            parser.expect(')')?;
+        }
+
+        let mut selection = Vec::new();
+        if parser.peek() == Some(&Token::Punct('{')) {
+            parser.next();
+            while parser.peek() != Some(&Token::Punct('}')) {
+                let name = parser.name()?;
+                if !EVENT_FIELDS.contains(&name.as_str()) {
+                    return Err(SubscriptionError::UnknownSelection(name));
+                }
+                selection.push(name);
+            }
+            parser.expect('}')?;
+        }
+        parser.expect('}')?;
+        if parser.peek().is_some() {
+            return Err(SubscriptionError::Malformed(
+                "subscriptions must select exactly one root field".into(),
+            ));
+        }
+
+        Ok(Self {
+            id: id.into(),
+            topic,
+            response_key,
+            subject,
+            event_type,
+            selection,
+        })
+    }
+
+    pub fn matches(&self, event: &SubscriptionEvent) -> bool {
+        event.topic == self.topic
+            && self
+                .subject
+                .as_ref()
+                .is_none_or(|subject| *subject == event.subject)
+            && self
+                .event_type
+                .as_ref()
+                .is_none_or(|event_type| *event_type == event.event_type)
+    }
+
+    /// GraphQL execution result carrying the selected fields of `event`.
+    pub fn render(&self, event: &SubscriptionEvent) -> Value {
+        let mut fields = match serde_json::to_value(event) {
+            Ok(Value::Object(fields)) => fields,
+            _ => Map::new(),
+        };
+        if !self.selection.is_empty() {
+            fields.retain(|key, _| self.selection.contains(key));
+        }
+        json!({ "data": { self.response_key.clone(): fields } })
+    }
+}
+
+/// Messages sent by clients speaking the `graphql-transport-ws` protocol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    ConnectionInit {
+        #[serde(default)]
+        payload: Option<Value>,
+    },
+    Ping {
+        #[serde(default)]
+        payload: Option<Value>,
+    },
+    Pong {
+        #[serde(default)]
+        payload: Option<Value>,
+    },
+    Subscribe {
+        id: String,
+        payload: SubscribePayload,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribePayload {
+    pub query: String,
+    #[serde(default)]
+    pub variables: Value,
+    #[serde(default)]
+    pub operation_name: Option<String>,
+}
+
+/// Messages sent by the gateway over the `graphql-transport-ws` protocol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    ConnectionAck {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        payload: Option<Value>,
+    },
+    Ping,
+    Pong,
+    Next {
+        id: String,
+        payload: Value,
+    },
+    Error {
+        id: String,
+        payload: Vec<Value>,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+impl ServerMessage {
+    pub fn error(id: impl Into<String>, message: impl Into<String>) -> Self {
+        ServerMessage::Error {
+            id: id.into(),
+            payload: vec![json!({ "message": message.into() })],
+        }
+    }
+}
+
+/// Fan-out point for workflow, pipeline, and agent events.
+#[derive(Debug, Clone)]
+pub struct SubscriptionHub {
+    sender: broadcast::Sender<SubscriptionEvent>,
+    keepalive: Duration,
+    permissions: BTreeMap<EventTopic, Permission>,
+}
+
+impl SubscriptionHub {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _receiver) = broadcast::channel(capacity.max(1));
+        let mut permissions = BTreeMap::new();
+        permissions.insert(EventTopic::Workflow, Permission::Read);
+        permissions.insert(EventTopic::Pipeline, Permission::Read);
+        permissions.insert(EventTopic::Agent, Permission::Execute);
+        permissions.insert(EventTopic::Host, Permission::Read);
+        Self {
+            sender,
+            keepalive: DEFAULT_KEEPALIVE,
+            permissions,
+        }
+    }
+
+    pub fn with_keepalive(mut self, keepalive: Duration) -> Self {
+        self.keepalive = keepalive;
+        self
+    }
+
+    pub fn with_permission(mut self, topic: EventTopic, permission: Permission) -> Self {
+        self.permissions.insert(topic, permission);
+        self
+    }
+
+    /// Permission a user needs to subscribe to `topic`.
+    pub fn required_permission(&self, topic: EventTopic) -> Permission {
+        self.permissions
+            .get(&topic)
+            .cloned()
+            .unwrap_or(Permission::Admin)
+    }
+
+    /// Publish an event; returns how many subscriptions received it.
+    pub fn publish(&self, event: SubscriptionEvent) -> usize {
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    /// Forward every event from a workflow engine stream until it closes.
+    pub fn bridge_workflow_events(&self, stream: &WorkflowEventStream) -> JoinHandle<()> {
+        let mut receiver = stream.subscribe();
+        let hub = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        hub.publish(SubscriptionEvent::from(&event));
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(
+                            skipped,
+                            "subscription bridge lagged behind workflow events"
+                        );
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    pub fn subscribe(&self, request: SubscriptionRequest) -> ActiveSubscription {
+        ActiveSubscription {
+            request,
+            receiver: self.sender.subscribe(),
+            keepalive: self.keepalive,
+            completed: false,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl Default for SubscriptionHub {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+/// A subscription accepted by the gateway, yielding protocol messages.
+#[derive(Debug)]
+pub struct ActiveSubscription {
+    request: SubscriptionRequest,
+    receiver: broadcast::Receiver<SubscriptionEvent>,
+    keepalive: Duration,
+    completed: bool,
+    pending: VecDeque<SubscriptionEvent>,
+}
+
+impl ActiveSubscription {
+    pub fn request(&self) -> &SubscriptionRequest {
+        &self.request
+    }
+
+    /// Deliver `event` before anything published later, if the subscription matches it.
+    /// Used to hand new subscribers the current state of snapshot topics.
+    pub fn with_initial(mut self, event: SubscriptionEvent) -> Self {
+        if self.request.matches(&event) {
+            self.pending.push_back(event);
+        }
+        self
+    }
+
+    /// Next message for the client: a `next` for each matching event, a `ping`
+    /// whenever nothing was delivered for the keepalive interval, and a final
+    /// `complete` once the event source shuts down.
+    pub async fn next_message(&mut self) -> Option<ServerMessage> {
+        if self.completed {
+            return None;
+        }
+        if let Some(event) = self.pending.pop_front() {
+            return Some(ServerMessage::Next {
+                id: self.request.id.clone(),
+                payload: self.request.render(&event),
+            });
+        }
+        let deadline = Instant::now() + self.keepalive;
+        loop {
+            match tokio::time::timeout_at(deadline, self.receiver.recv()).await {
+                Err(_) => return Some(ServerMessage::Ping),
+                Ok(Ok(event)) if self.request.matches(&event) => {
+                    return Some(ServerMessage::Next {
+                        id: self.request.id.clone(),
+                        payload: self.request.render(&event),
+                    });
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    tracing::warn!(
+                        subscription = %self.request.id,
+                        skipped,
+                        "subscriber lagged behind event stream"
+                    );
+                }
+                Ok(Err(RecvError::Closed)) => {
+                    self.completed = true;
+                    return Some(ServerMessage::Complete {
+                        id: self.request.id.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Str(String),
+    Punct(char),
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, SubscriptionError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            c if c.is_whitespace() || c == ',' => {
+                chars.next();
+            }
+            '#' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '{' | '}' | '(' | ')' | ':' | '$' | '!' | '[' | ']' | '=' => {
+                tokens.push(Token::Punct(ch));
+                chars.next();
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => value.push(escaped),
+                            None => break,
+                        },
+                        Some(c) => value.push(c),
+                        None => {
+                            return Err(SubscriptionError::Malformed("unterminated string".into()))
+                        }
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            c if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' => {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' {
+                        name.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Name(name));
+            }
+            other => {
+                return Err(SubscriptionError::Malformed(format!(
+                    "unexpected character '{other}'"
+                )))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), SubscriptionError> {
+        match self.next() {
+            Some(Token::Punct(found)) if found == punct => Ok(()),
+            other => Err(SubscriptionError::Malformed(format!(
+                "expected '{punct}', found {other:?}"
+            ))),
+        }
+    }
+
+    fn name(&mut self) -> Result<String, SubscriptionError> {
+        match self.next() {
+            Some(Token::Name(name)) => Ok(name),
+            other => Err(SubscriptionError::Malformed(format!(
+                "expected a name, found {other:?}"
+            ))),
+        }
+    }
+
+    fn value(&mut self, variables: &Value) -> Result<String, SubscriptionError> {
+        match self.next() {
+            Some(Token::Str(value)) | Some(Token::Name(value)) => Ok(value),
+            Some(Token::Punct('$')) => {
+                let name = self.name()?;
+                match variables.get(&name) {
+                    Some(Value::String(value)) => Ok(value.clone()),
+                    Some(Value::Null) | None => Err(SubscriptionError::MissingVariable(name)),
+                    Some(other) => Ok(other.to_string()),
+                }
+            }
+            other => Err(SubscriptionError::Malformed(format!(
+                "expected an argument value, found {other:?}"
+            ))),
+        }
+    }
+
+    fn skip_group(&mut self, open: char, close: char) -> Result<(), SubscriptionError> {
+        let mut depth = 0usize;
+        while let Some(token) = self.next() {
+            if token == Token::Punct(open) {
+                depth += 1;
+            } else if token == Token::Punct(close) {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+        }
+        Err(SubscriptionError::Malformed(format!("unclosed '{open}'")))
+    }
+}
+
+/// Whether a GraphQL document is a subscription operation.
+pub fn is_subscription(query: &str) -> bool {
+    query.trim_start().starts_with("subscription")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noa_workflow::WorkflowState;
+
+    #[tokio::test]
+    async fn subscriptions_filter_project_and_keep_alive() {
+        let hub = SubscriptionHub::new(16).with_keepalive(Duration::from_millis(50));
+        let stream = WorkflowEventStream::new(16);
+        let bridge = hub.bridge_workflow_events(&stream);
+
+        let request = SubscriptionRequest::parse(
+            "sub-1",
+            "subscription Watch($id: ID!) { updates: workflowEvents(workflowId: $id) { eventType data } }",
+            &json!({ "id": "wf-1" }),
+        )
+        .unwrap();
+        assert_eq!(request.topic, EventTopic::Workflow);
+        assert_eq!(request.subject.as_deref(), Some("wf-1"));
+        let mut subscription = hub.subscribe(request);
+
+        for workflow_id in ["wf-2", "wf-1"] {
+            stream.send(WorkflowEvent::WorkflowState {
+                workflow_id: workflow_id.to_string(),
+                state: WorkflowState::Running,
+                timestamp: "2026-01-01T00:00:00Z".to_string(),
+            });
+        }
+        let message = subscription.next_message().await.unwrap();
+        assert_eq!(
+            message,
+            ServerMessage::Next {
+                id: "sub-1".to_string(),
+                payload: json!({
+                    "data": {
+                        "updates": {
+                            "eventType": "workflow_state",
+                            "data": { "state": "Running" },
+                        }
+                    }
+                }),
+            }
+        );
+        assert_eq!(subscription.next_message().await, Some(ServerMessage::Ping));
+
+        assert_eq!(
+            SubscriptionRequest::parse("sub-2", "{ workflowEvents { data } }", &Value::Null),
+            Err(SubscriptionError::NotASubscription)
+        );
+        assert!(matches!(
+            SubscriptionRequest::parse(
+                "sub-3",
+                "subscription { pipelineEvents { secrets } }",
+                &Value::Null
+            ),
+            Err(SubscriptionError::UnknownSelection(_))
+        ));
+        bridge.abort();
+    }
+}
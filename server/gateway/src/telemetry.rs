@@ -1,13 +1,20 @@
 use crate::router::{Protocol, RoutePlan};
+use crate::trust::{TrustAssessment, TrustDecision};
 use chrono::{DateTime, Utc};
+use noa_core::telemetry::{
+    FanoutEmitter, FileEmitter, TelemetryEmitter, TelemetryError as EmitError, TelemetryRecord,
+};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs::{create_dir_all, OpenOptions};
-use std::io::Write;
+use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use thiserror::Error;
 
+const EVENTS_STREAM: &str = "gateway_events";
+const TRUST_STREAM: &str = "gateway_trust";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TelemetryEvent {
     pub request_id: String,
@@ -57,6 +64,10 @@ pub struct GatewayMetrics {
     pub total_requests: u64,
     pub per_protocol: HashMap<String, u64>,
     pub last_event: Option<TelemetryEvent>,
+    #[serde(default)]
+    pub trust_throttled: u64,
+    #[serde(default)]
+    pub trust_denied: u64,
 }
 
 #[derive(Debug, Error)]
@@ -65,6 +76,8 @@ pub enum TelemetryError {
     Io(String),
     #[error("serialization error: {0}")]
     Serde(String),
+    #[error("telemetry emitter error: {0}")]
+    Emit(String),
 }
 
 impl From<std::io::Error> for TelemetryError {
@@ -79,26 +92,51 @@ impl From<serde_json::Error> for TelemetryError {
     }
 }
 
+impl From<EmitError> for TelemetryError {
+    fn from(value: EmitError) -> Self {
+        match value {
+            EmitError::Io(err) => TelemetryError::Io(err.to_string()),
+            EmitError::Serde(err) => TelemetryError::Serde(err.to_string()),
+            other => TelemetryError::Emit(other.to_string()),
+        }
+    }
+}
+
 /// Sink that exports gateway telemetry artefacts into storage/telemetry.
+///
+/// The metrics snapshot is kept in `gateway_metrics.json`; request events and trust
+/// decisions go to the `gateway_events` and `gateway_trust` streams of a
+/// [`TelemetryEmitter`], by default JSON lines in the same directory.
 #[derive(Debug)]
 pub struct TelemetrySink {
     metrics: Mutex<GatewayMetrics>,
     metrics_path: PathBuf,
-    events_path: PathBuf,
+    emitter: FanoutEmitter,
 }
 
 impl TelemetrySink {
     pub fn new<P: AsRef<Path>>(storage_dir: P) -> Result<Self, TelemetryError> {
         create_dir_all(&storage_dir)?;
         let metrics_path = storage_dir.as_ref().join("gateway_metrics.json");
-        let events_path = storage_dir.as_ref().join("gateway_events.log");
+        let files = FileEmitter::new(storage_dir.as_ref());
         Ok(Self {
             metrics: Mutex::new(GatewayMetrics::default()),
             metrics_path,
-            events_path,
+            emitter: FanoutEmitter::new(vec![Arc::new(files)]),
         })
     }
 
+    /// Directory the metrics snapshot and default event logs are written to.
+    pub fn storage_dir(&self) -> &Path {
+        self.metrics_path.parent().unwrap_or_else(|| Path::new("."))
+    }
+
+    /// Also send events and trust decisions to `emitter`, e.g. OTLP or the IPC bus.
+    pub fn with_emitter(mut self, emitter: Arc<dyn TelemetryEmitter>) -> Self {
+        self.emitter = self.emitter.with(emitter);
+        self
+    }
+
     pub fn record(&self, event: TelemetryEvent) -> Result<(), TelemetryError> {
         {
             let mut metrics = self.metrics.lock();
@@ -113,13 +151,30 @@ impl TelemetrySink {
             std::fs::write(&self.metrics_path, json)?;
         }
 
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.events_path)?;
-        file.write_all(serde_json::to_string(&event)?.as_bytes())?;
-        file.write_all(b"\n")?;
+        self.emitter
+            .emit(&TelemetryRecord::of(EVENTS_STREAM, &event)?)?;
+        Ok(())
+    }
+
+    /// Record a throttle or deny decision and the standing that produced it.
+    ///
+    /// Allowed requests are not recorded, so the trust gate adds no writes to the path
+    /// most requests take.
+    pub fn record_trust(&self, assessment: &TrustAssessment) -> Result<(), TelemetryError> {
+        {
+            let mut metrics = self.metrics.lock();
+            match assessment.decision {
+                TrustDecision::Allow => return Ok(()),
+                TrustDecision::Throttle { .. } => metrics.trust_throttled += 1,
+                TrustDecision::Deny => metrics.trust_denied += 1,
+            }
+
+            let json = serde_json::to_vec_pretty(&*metrics)?;
+            std::fs::write(&self.metrics_path, json)?;
+        }
 
+        self.emitter
+            .emit(&TelemetryRecord::of(TRUST_STREAM, assessment)?)?;
         Ok(())
     }
 
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use noa_core::security::Permission;
+use noa_workflow::RewardScorekeeper;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Thresholds applied to agent reward standings before routing.
+#[derive(Debug, Clone)]
+pub struct TrustPolicyConfig {
+    /// Agents whose total reward falls below this value are considered untrusted.
+    pub min_standing: f64,
+    /// Fraction of the layer rate limit granted to untrusted agents.
+    pub throttle_factor: f64,
+    /// Permissions refused outright to untrusted agents.
+    pub denied_permissions: Vec<Permission>,
+}
+
+impl Default for TrustPolicyConfig {
+    fn default() -> Self {
+        Self {
+            min_standing: -5.0,
+            throttle_factor: 0.25,
+            denied_permissions: vec![Permission::Write, Permission::Admin],
+        }
+    }
+}
+
+/// Outcome of a trust evaluation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum TrustDecision {
+    Allow,
+    Throttle { limit_factor: f64 },
+    Deny,
+}
+
+/// Decision together with the standing that produced it, as recorded in telemetry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustAssessment {
+    pub agent_id: String,
+    pub permission: String,
+    pub decision: TrustDecision,
+    pub total_reward: f64,
+    pub recent_average: f64,
+    pub min_standing: f64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl TrustAssessment {
+    pub fn limit_factor(&self) -> f64 {
+        match self.decision {
+            TrustDecision::Throttle { limit_factor } => limit_factor,
+            _ => 1.0,
+        }
+    }
+
+    /// Convert a deny decision into an error.
+    pub fn ensure_allowed(&self) -> Result<(), TrustError> {
+        match self.decision {
+            TrustDecision::Deny => Err(TrustError::StandingTooLow {
+                agent_id: self.agent_id.clone(),
+                permission: self.permission.clone(),
+                total_reward: self.total_reward,
+                min_standing: self.min_standing,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TrustError {
+    #[error(
+        "agent {agent_id} standing {total_reward:.2} is below {min_standing:.2} for {permission}"
+    )]
+    StandingTooLow {
+        agent_id: String,
+        permission: String,
+        total_reward: f64,
+        min_standing: f64,
+    },
+}
+
+/// Authorisation gate backed by the workflow reward scorekeeper.
+#[derive(Clone)]
+pub struct TrustGate {
+    config: TrustPolicyConfig,
+    scorekeeper: Arc<RwLock<RewardScorekeeper>>,
+}
+
+impl TrustGate {
+    pub fn new(config: TrustPolicyConfig, scorekeeper: Arc<RwLock<RewardScorekeeper>>) -> Self {
+        Self {
+            config,
+            scorekeeper,
+        }
+    }
+
+    pub fn config(&self) -> &TrustPolicyConfig {
+        &self.config
+    }
+
+    /// Evaluate the agent's current standing for the requested permission.
+    pub fn assess(&self, agent_id: &str, permission: &Permission) -> TrustAssessment {
+        let standing = self
+            .scorekeeper
+            .read()
+            .standings()
+            .get(agent_id)
+            .cloned()
+            .unwrap_or_default();
+
+        let decision = if standing.total_reward >= self.config.min_standing {
+            TrustDecision::Allow
+        } else if self.config.denied_permissions.contains(permission) {
+            TrustDecision::Deny
+        } else {
+            TrustDecision::Throttle {
+                limit_factor: self.config.throttle_factor,
+            }
+        };
+
+        TrustAssessment {
+            agent_id: agent_id.to_string(),
+            permission: format!("{:?}", permission),
+            decision,
+            total_reward: standing.total_reward,
+            recent_average: standing.recent_average(),
+            min_standing: self.config.min_standing,
+            recorded_at: Utc::now(),
+        }
+    }
+}
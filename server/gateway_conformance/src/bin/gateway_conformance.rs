@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use noa_gateway_conformance::{
+    builtin_corpus, load_corpus, run_suite, FixtureKind, GatewayTarget, InProcessTarget,
+    LiveTarget, DEFAULT_LIVE_BURST,
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "gateway-conformance",
+    about = "Run the gateway protocol conformance suite"
+)]
+struct Cli {
+    /// Base URL of a deployed gateway; runs in-process when omitted.
+    #[arg(long)]
+    live: Option<String>,
+    /// Capability token sent to a live gateway.
+    #[arg(long, env = "NOA_CAPABILITY_TOKEN")]
+    capability_token: Option<String>,
+    /// Capability scope the token was issued for.
+    #[arg(long, env = "NOA_CAPABILITY_SCOPE", default_value = "gateway.route")]
+    capability_scope: String,
+    /// Requests an agent may send before the live gateway rate limits it.
+    #[arg(long, default_value_t = DEFAULT_LIVE_BURST)]
+    burst: u32,
+    /// Directory of fixture files replacing the built-in corpus.
+    #[arg(long)]
+    fixtures: Option<PathBuf>,
+    /// Fixture kinds to skip (valid, malformed, unauthorized, rate_limited).
+    #[arg(long, value_delimiter = ',')]
+    exclude: Vec<String>,
+    /// Write the JSON report to this path.
+    #[arg(long)]
+    report: Option<PathBuf>,
+}
+
+fn main() {
+    match run() {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("{err:?}");
+            std::process::exit(2);
+        }
+    }
+}
+
+fn run() -> Result<bool> {
+    let cli = Cli::parse();
+    let corpus = match &cli.fixtures {
+        Some(dir) => load_corpus(dir)?,
+        None => builtin_corpus()?,
+    };
+    let exclude = cli
+        .exclude
+        .iter()
+        .map(|kind| FixtureKind::parse(kind).ok_or_else(|| anyhow!("unknown fixture kind {kind}")))
+        .collect::<Result<HashSet<_>>>()?;
+
+    let target: Box<dyn GatewayTarget> = match &cli.live {
+        Some(url) => {
+            let token = cli
+                .capability_token
+                .clone()
+                .context("--capability-token is required for live runs")?;
+            Box::new(
+                LiveTarget::new(url, token, cli.capability_scope.clone())?.with_burst(cli.burst),
+            )
+        }
+        None => Box::new(InProcessTarget::new()?),
+    };
+
+    let report = run_suite(target.as_ref(), &corpus, &exclude);
+    for result in &report.results {
+        let status = if result.passed() { "ok" } else { "FAIL" };
+        println!("{status:>4}  {:?}  {}", result.kind, result.name);
+        for failure in &result.failures {
+            println!("        {failure}");
+        }
+    }
+    println!("{}", report.summary());
+
+    if let Some(path) = &cli.report {
+        fs::write(path, serde_json::to_vec_pretty(&report)?)
+            .with_context(|| format!("failed to write report to {}", path.display()))?;
+    }
+    Ok(report.passed())
+}
@@ -0,0 +1,178 @@
+use anyhow::{Context, Result};
+use noa_core::security::{Permission, UserId};
+use noa_gateway::{AuthCredentials, Protocol};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+const BUILTIN_CORPUS: &[(&str, &str)] = &[
+    ("graphql.json", include_str!("../fixtures/graphql.json")),
+    ("grpc.json", include_str!("../fixtures/grpc.json")),
+    ("websocket.json", include_str!("../fixtures/websocket.json")),
+];
+
+/// What a fixture exercises; used to filter runs (e.g. skip bursts against staging).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixtureKind {
+    Valid,
+    Malformed,
+    Unauthorized,
+    /// Sent until the target's burst allowance is spent; the last request is checked.
+    RateLimited,
+}
+
+impl FixtureKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "valid" => Some(FixtureKind::Valid),
+            "malformed" => Some(FixtureKind::Malformed),
+            "unauthorized" => Some(FixtureKind::Unauthorized),
+            "rate_limited" | "rate-limited" => Some(FixtureKind::RateLimited),
+            _ => None,
+        }
+    }
+}
+
+/// Error families a gateway rejection falls into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    Authentication,
+    Authorization,
+    TrustDenied,
+    RateLimited,
+    Routing,
+    Internal,
+}
+
+impl ErrorClass {
+    /// Classify a gateway error from its message. The context strings attached by
+    /// `Gateway::handle_request` are the contract shared by in-process and HTTP callers.
+    pub fn classify(message: &str) -> Self {
+        if message.starts_with("authentication failed") {
+            ErrorClass::Authentication
+        } else if message.starts_with("policy enforcement failure")
+            || (message.starts_with("subscription to ") && message.contains(" denied"))
+        {
+            ErrorClass::Authorization
+        } else if message.starts_with("trust gate denied") {
+            ErrorClass::TrustDenied
+        } else if message.starts_with("rate limit exceeded") {
+            ErrorClass::RateLimited
+        } else if message.starts_with("missing field")
+            || message.starts_with("invalid subscription")
+        {
+            ErrorClass::Routing
+        } else {
+            ErrorClass::Internal
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixturePermission {
+    Read,
+    Write,
+    Execute,
+    Admin,
+}
+
+impl FixturePermission {
+    pub fn permission(&self) -> Permission {
+        match self {
+            FixturePermission::Read => Permission::Read,
+            FixturePermission::Write => Permission::Write,
+            FixturePermission::Execute => Permission::Execute,
+            FixturePermission::Admin => Permission::Admin,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FixturePermission::Read => "read",
+            FixturePermission::Write => "write",
+            FixturePermission::Execute => "execute",
+            FixturePermission::Admin => "admin",
+        }
+    }
+}
+
+/// Expected outcome of a fixture's (final) request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expectation {
+    Routed { targets: Vec<String>, mode: String },
+    Error(ErrorClass),
+}
+
+/// One request sent to the gateway and the behaviour it must produce.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub name: String,
+    pub kind: FixtureKind,
+    pub protocol: Protocol,
+    #[serde(default)]
+    pub user_id: UserId,
+    #[serde(default = "default_agent")]
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub credentials: AuthCredentials,
+    pub payload: Value,
+    #[serde(default = "default_permission")]
+    pub permission: FixturePermission,
+    pub expect: Expectation,
+    /// Telemetry events the final request must emit; defaults to one per routed request.
+    #[serde(default)]
+    pub telemetry: Option<usize>,
+}
+
+impl Fixture {
+    pub fn expected_telemetry(&self) -> usize {
+        self.telemetry.unwrap_or(match self.expect {
+            Expectation::Routed { .. } => 1,
+            Expectation::Error(_) => 0,
+        })
+    }
+}
+
+fn default_agent() -> Option<String> {
+    Some("fixed_agent_gateway".into())
+}
+
+fn default_permission() -> FixturePermission {
+    FixturePermission::Read
+}
+
+/// The corpus shipped with this crate.
+pub fn builtin_corpus() -> Result<Vec<Fixture>> {
+    let mut fixtures = Vec::new();
+    for (name, raw) in BUILTIN_CORPUS {
+        fixtures.extend(parse_fixtures(name, raw)?);
+    }
+    Ok(fixtures)
+}
+
+/// Load every `*.json` fixture file in `dir`, in file name order.
+pub fn load_corpus(dir: &Path) -> Result<Vec<Fixture>> {
+    let mut paths = fs::read_dir(dir)
+        .with_context(|| format!("failed to read fixture directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut fixtures = Vec::new();
+    for path in paths {
+        let raw = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        fixtures.extend(parse_fixtures(&path.display().to_string(), &raw)?);
+    }
+    Ok(fixtures)
+}
+
+fn parse_fixtures(source: &str, raw: &str) -> Result<Vec<Fixture>> {
+    serde_json::from_str(raw).with_context(|| format!("invalid fixture file {source}"))
+}
@@ -0,0 +1,205 @@
+//! NOA Gateway conformance suite
+//!
+//! Drives a gateway with a corpus of GraphQL, gRPC, and WebSocket request fixtures
+//! (valid, malformed, unauthorized, and rate limited) and checks the resulting
+//! route plans, error classes, and telemetry emissions:
+//! - [`InProcessTarget`] builds a gateway per fixture so the suite runs in CI without
+//!   external infrastructure.
+//! - [`LiveTarget`] sends the same corpus to a deployed gateway, e.g. staging.
+//!
+//! The `gateway-conformance` binary runs the suite against either target.
+
+mod fixture;
+mod target;
+
+pub use fixture::{
+    builtin_corpus, load_corpus, ErrorClass, Expectation, Fixture, FixtureKind, FixturePermission,
+};
+pub use target::{
+    GatewayTarget, InProcessTarget, LiveTarget, Observation, ObservedError, DEFAULT_LIVE_BURST,
+    IN_PROCESS_BURST,
+};
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Outcome of one fixture.
+#[derive(Debug, Clone, Serialize)]
+pub struct FixtureResult {
+    pub name: String,
+    pub kind: FixtureKind,
+    /// Mismatches between expected and observed behaviour; empty when the fixture passed.
+    pub failures: Vec<String>,
+    /// Whether telemetry was observed and checked.
+    pub telemetry_checked: bool,
+}
+
+impl FixtureResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConformanceReport {
+    pub target: String,
+    pub results: Vec<FixtureResult>,
+}
+
+impl ConformanceReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(FixtureResult::passed)
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &FixtureResult> {
+        self.results.iter().filter(|result| !result.passed())
+    }
+
+    pub fn summary(&self) -> String {
+        let failed = self.failed().count();
+        format!(
+            "{}: {} fixtures, {} passed, {} failed",
+            self.target,
+            self.results.len(),
+            self.results.len() - failed,
+            failed
+        )
+    }
+}
+
+/// Run every fixture whose kind is not in `exclude` against `target`.
+pub fn run_suite(
+    target: &dyn GatewayTarget,
+    corpus: &[Fixture],
+    exclude: &HashSet<FixtureKind>,
+) -> ConformanceReport {
+    let results = corpus
+        .iter()
+        .filter(|fixture| !exclude.contains(&fixture.kind))
+        .map(|fixture| match target.execute(fixture) {
+            Ok(observation) => check(fixture, &observation),
+            Err(err) => FixtureResult {
+                name: fixture.name.clone(),
+                kind: fixture.kind,
+                failures: vec![format!("target error: {err:#}")],
+                telemetry_checked: false,
+            },
+        })
+        .collect();
+    ConformanceReport {
+        target: target.name().to_string(),
+        results,
+    }
+}
+
+/// Compare an observation with the fixture's expectation.
+pub fn check(fixture: &Fixture, observation: &Observation) -> FixtureResult {
+    let mut failures = Vec::new();
+    match (&fixture.expect, &observation.outcome) {
+        (Expectation::Routed { targets, mode }, Ok(plan)) => {
+            if plan.protocol != fixture.protocol {
+                failures.push(format!(
+                    "routed as {:?}, expected {:?}",
+                    plan.protocol, fixture.protocol
+                ));
+            }
+            if &plan.targets != targets {
+                failures.push(format!(
+                    "targets {:?}, expected {:?}",
+                    plan.targets, targets
+                ));
+            }
+            let observed_mode = plan.metadata.get("mode").and_then(|mode| mode.as_str());
+            if observed_mode != Some(mode.as_str()) {
+                failures.push(format!("mode {observed_mode:?}, expected {mode:?}"));
+            }
+        }
+        (Expectation::Routed { .. }, Err(err)) => {
+            failures.push(format!("rejected ({:?}): {}", err.class, err.message));
+        }
+        (Expectation::Error(class), Ok(plan)) => {
+            failures.push(format!(
+                "routed to {:?}, expected a {class:?} error",
+                plan.targets
+            ));
+        }
+        (Expectation::Error(class), Err(err)) => {
+            if err.class != *class {
+                failures.push(format!(
+                    "{:?} error ({}), expected {class:?}",
+                    err.class, err.message
+                ));
+            }
+        }
+    }
+
+    if let Some(events) = &observation.telemetry {
+        let expected = fixture.expected_telemetry();
+        if events.len() != expected {
+            failures.push(format!(
+                "{} telemetry events, expected {expected}",
+                events.len()
+            ));
+        }
+        if let Ok(plan) = &observation.outcome {
+            for event in events {
+                if event.protocol != plan.protocol || event.route_targets != plan.targets {
+                    failures.push(format!(
+                        "telemetry for {} does not match the route plan",
+                        event.request_id
+                    ));
+                }
+            }
+        }
+    }
+
+    FixtureResult {
+        name: fixture.name.clone(),
+        kind: fixture.kind,
+        failures,
+        telemetry_checked: observation.telemetry.is_some(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noa_gateway::{Protocol, RoutePlan};
+
+    #[test]
+    fn check_reports_route_and_telemetry_mismatches() {
+        let fixture: Fixture = serde_json::from_value(serde_json::json!({
+            "name": "ws",
+            "kind": "valid",
+            "protocol": "WebSocket",
+            "payload": { "channel": "alerts" },
+            "expect": { "routed": { "targets": ["alerts"], "mode": "multiplex" } }
+        }))
+        .unwrap();
+        assert_eq!(fixture.agent_id.as_deref(), Some("fixed_agent_gateway"));
+
+        let mut plan = RoutePlan::new(Protocol::WebSocket);
+        plan.targets.push("alerts".into());
+        plan.metadata.insert("mode".into(), "multiplex".into());
+        let observation = Observation {
+            outcome: Ok(plan),
+            telemetry: Some(Vec::new()),
+        };
+        let result = check(&fixture, &observation);
+        assert_eq!(result.failures, ["0 telemetry events, expected 1"]);
+
+        let rejected = Observation {
+            outcome: Err(ObservedError {
+                class: ErrorClass::classify("rate limit exceeded"),
+                message: "rate limit exceeded".into(),
+            }),
+            telemetry: None,
+        };
+        let result = check(&fixture, &rejected);
+        assert!(!result.telemetry_checked);
+        assert_eq!(
+            result.failures,
+            ["rejected (RateLimited): rate limit exceeded"]
+        );
+    }
+}
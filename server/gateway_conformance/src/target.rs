@@ -0,0 +1,257 @@
+use anyhow::{anyhow, Context, Result};
+use noa_agents::registry::AgentRegistry;
+use noa_core::security;
+use noa_gateway::{
+    Gateway, GatewayRequest, PolicyEnforcer, ProgrammableRouter, RateLimiter, RateLimiterConfig,
+    RoutePlan, TelemetryEvent, TelemetrySink, UnifiedAuthenticator,
+};
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::fixture::{ErrorClass, Fixture, FixtureKind};
+
+/// Burst allowance the in-process gateway grants every agent.
+pub const IN_PROCESS_BURST: u32 = 5;
+
+/// Rate limit of the default `L5Infrastructure` layer, which unknown agents fall into.
+pub const DEFAULT_LIVE_BURST: u32 = 40;
+
+/// A gateway rejection as seen by the caller.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObservedError {
+    pub class: ErrorClass,
+    pub message: String,
+}
+
+/// What a target observed for the final request of a fixture.
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub outcome: std::result::Result<RoutePlan, ObservedError>,
+    /// Telemetry emitted by the final request, when the target can see it.
+    pub telemetry: Option<Vec<TelemetryEvent>>,
+}
+
+/// A gateway the conformance suite can drive.
+pub trait GatewayTarget {
+    fn name(&self) -> &str;
+
+    /// Requests a single agent may send before being rate limited.
+    fn burst(&self) -> u32;
+
+    /// Send `fixture` once, or `burst() + 1` times for rate limited fixtures, and
+    /// report what happened to the last request.
+    fn execute(&self, fixture: &Fixture) -> Result<Observation>;
+}
+
+fn attempts(target: &dyn GatewayTarget, fixture: &Fixture) -> u32 {
+    match fixture.kind {
+        FixtureKind::RateLimited => target.burst() + 1,
+        _ => 1,
+    }
+}
+
+/// Gateway built in-process for each fixture, with telemetry in a scratch directory.
+pub struct InProcessTarget {
+    registry: Arc<AgentRegistry>,
+    burst: u32,
+}
+
+impl InProcessTarget {
+    pub fn new() -> Result<Self> {
+        security::init().map_err(|err| anyhow!("failed to init security: {}", err))?;
+        let registry =
+            Arc::new(AgentRegistry::with_default_data().context("failed to load agent registry")?);
+        Ok(Self {
+            registry,
+            burst: IN_PROCESS_BURST,
+        })
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    fn gateway(&self, telemetry_dir: &Path) -> Result<Gateway> {
+        let mut config = RateLimiterConfig {
+            refill_interval: Duration::from_secs(3600),
+            ..RateLimiterConfig::default()
+        };
+        for limit in config.layer_limits.values_mut() {
+            *limit = self.burst;
+        }
+        Gateway::new(
+            UnifiedAuthenticator::default(),
+            PolicyEnforcer::new(),
+            ProgrammableRouter::default(),
+            RateLimiter::new(config, self.registry.clone()),
+            TelemetrySink::new(telemetry_dir)?,
+        )
+    }
+}
+
+impl GatewayTarget for InProcessTarget {
+    fn name(&self) -> &str {
+        "in-process"
+    }
+
+    fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    fn execute(&self, fixture: &Fixture) -> Result<Observation> {
+        let scratch = tempfile::tempdir().context("failed to create telemetry directory")?;
+        let gateway = self.gateway(scratch.path())?;
+        let events_path = scratch.path().join("gateway_events.log");
+
+        let mut last = None;
+        for attempt in 0..attempts(self, fixture) {
+            let emitted_before = read_events(&events_path)?.len();
+            let request = GatewayRequest {
+                request_id: format!("conformance-{}-{attempt}", fixture.name),
+                user_id: fixture.user_id,
+                agent_id: fixture.agent_id.clone(),
+                credentials: fixture.credentials.clone(),
+                protocol: fixture.protocol.clone(),
+                payload: fixture.payload.clone(),
+                required_permission: fixture.permission.permission(),
+            };
+            let outcome = gateway
+                .handle_request(request)
+                .map(|response| response.route_plan)
+                .map_err(|err| {
+                    let message = err.to_string();
+                    ObservedError {
+                        class: ErrorClass::classify(&message),
+                        message,
+                    }
+                });
+            let mut telemetry = read_events(&events_path)?;
+            telemetry.drain(..emitted_before);
+            last = Some(Observation {
+                outcome,
+                telemetry: Some(telemetry),
+            });
+        }
+        last.ok_or_else(|| anyhow!("fixture {} sent no requests", fixture.name))
+    }
+}
+
+fn read_events(path: &Path) -> Result<Vec<TelemetryEvent>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).context("invalid telemetry event"))
+        .collect()
+}
+
+/// A deployed gateway reached through its `/v1/route` endpoint. Telemetry is not
+/// observable remotely, so only route plans and error classes are checked.
+pub struct LiveTarget {
+    client: Client,
+    endpoint: String,
+    capability_token: String,
+    capability_scope: String,
+    burst: u32,
+}
+
+impl LiveTarget {
+    pub fn new(
+        base_url: &str,
+        capability_token: impl Into<String>,
+        capability_scope: impl Into<String>,
+    ) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("failed to build HTTP client")?;
+        Ok(Self {
+            client,
+            endpoint: format!("{}/v1/route", base_url.trim_end_matches('/')),
+            capability_token: capability_token.into(),
+            capability_scope: capability_scope.into(),
+            burst: DEFAULT_LIVE_BURST,
+        })
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    fn send(&self, fixture: &Fixture, attempt: u32) -> Result<Observation> {
+        let mut request = self
+            .client
+            .post(&self.endpoint)
+            .header("x-noa-capability", &self.capability_token)
+            .json(&json!({
+                "request_id": format!("conformance-{}-{attempt}", fixture.name),
+                "user_id": fixture.user_id,
+                "agent_id": fixture.agent_id,
+                "protocol": fixture.protocol,
+                "payload": fixture.payload,
+                "required_permission": fixture.permission.as_str(),
+                "capability_scope": self.capability_scope,
+            }));
+        if let Some(api_key) = &fixture.credentials.api_key {
+            request = request.header("x-noa-api-key", api_key);
+        }
+        if let Some(token) = &fixture.credentials.oidc {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .with_context(|| format!("failed to reach {}", self.endpoint))?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .with_context(|| format!("gateway returned a non-JSON body ({status})"))?;
+        let outcome = if status.is_success() {
+            let plan = body
+                .get("route_plan")
+                .cloned()
+                .ok_or_else(|| anyhow!("response is missing route_plan"))?;
+            Ok(serde_json::from_value(plan).context("invalid route_plan")?)
+        } else {
+            let message = body
+                .get("error")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            Err(ObservedError {
+                class: ErrorClass::classify(&message),
+                message,
+            })
+        };
+        Ok(Observation {
+            outcome,
+            telemetry: None,
+        })
+    }
+}
+
+impl GatewayTarget for LiveTarget {
+    fn name(&self) -> &str {
+        &self.endpoint
+    }
+
+    fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    fn execute(&self, fixture: &Fixture) -> Result<Observation> {
+        let mut last = None;
+        for attempt in 0..attempts(self, fixture) {
+            last = Some(self.send(fixture, attempt)?);
+        }
+        last.ok_or_else(|| anyhow!("fixture {} sent no requests", fixture.name))
+    }
+}
@@ -0,0 +1,25 @@
+use std::collections::HashSet;
+
+use noa_gateway_conformance::{builtin_corpus, run_suite, FixtureKind, InProcessTarget};
+
+#[test]
+fn builtin_corpus_passes_in_process() {
+    let corpus = builtin_corpus().expect("built-in corpus parses");
+    for kind in [
+        FixtureKind::Valid,
+        FixtureKind::Malformed,
+        FixtureKind::Unauthorized,
+        FixtureKind::RateLimited,
+    ] {
+        assert!(corpus.iter().any(|fixture| fixture.kind == kind));
+    }
+
+    let target = InProcessTarget::new().expect("in-process gateway");
+    let report = run_suite(&target, &corpus, &HashSet::new());
+    let failures = report
+        .failed()
+        .map(|result| format!("{}: {}", result.name, result.failures.join("; ")))
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+    assert!(report.results.iter().all(|result| result.telemetry_checked));
+}
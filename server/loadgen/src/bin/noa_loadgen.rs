@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use noa_core::hardware::detect_hardware_profile;
+use noa_loadgen::{
+    builtin_profile, load_profile, run_load, CapacityHost, HttpTarget, InProcessTarget, LoadTarget,
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "noa-loadgen",
+    about = "Generate synthetic gateway/API load and report capacity"
+)]
+struct Cli {
+    /// Base URL of a deployed gateway or API server; runs in-process when omitted.
+    #[arg(long)]
+    live: Option<String>,
+    /// Request path on the live server.
+    #[arg(long, default_value = noa_loadgen::DEFAULT_ROUTE_PATH)]
+    path: String,
+    /// Capability token sent to a live server.
+    #[arg(long, env = "NOA_CAPABILITY_TOKEN")]
+    capability_token: Option<String>,
+    /// Capability scope the token was issued for.
+    #[arg(long, env = "NOA_CAPABILITY_SCOPE", default_value = "gateway.route")]
+    capability_scope: String,
+    /// Load profile replacing the built-in mixed profile.
+    #[arg(long)]
+    profile: Option<PathBuf>,
+    /// Override the profile's request count.
+    #[arg(long)]
+    requests: Option<usize>,
+    /// Override the profile's worker count.
+    #[arg(long)]
+    concurrency: Option<usize>,
+    /// Write the full JSON load report to this path.
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// Write the capacity report for the runtime planner to this path.
+    #[arg(long)]
+    capacity_report: Option<PathBuf>,
+}
+
+fn main() {
+    match run() {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("{err:?}");
+            std::process::exit(2);
+        }
+    }
+}
+
+fn run() -> Result<bool> {
+    let cli = Cli::parse();
+    let mut profile = match &cli.profile {
+        Some(path) => load_profile(path)?,
+        None => builtin_profile()?,
+    };
+    if let Some(requests) = cli.requests {
+        profile.requests = requests;
+    }
+    if let Some(concurrency) = cli.concurrency {
+        profile.concurrency = concurrency;
+    }
+    profile.validate()?;
+
+    let target: Box<dyn LoadTarget> = match &cli.live {
+        Some(url) => {
+            let token = cli
+                .capability_token
+                .clone()
+                .context("--capability-token is required for live runs")?;
+            Box::new(
+                HttpTarget::new(url, token, cli.capability_scope.clone())?.with_path(&cli.path),
+            )
+        }
+        None => Box::new(InProcessTarget::new()?),
+    };
+
+    let report = run_load(target.as_ref(), &profile);
+    for protocol in &report.protocols {
+        println!(
+            "{:>10?}  {:>6} requests  {:>4} errors  p95 {:.2} ms",
+            protocol.protocol, protocol.requests, protocol.errors, protocol.latency.p95_ms
+        );
+    }
+    for sample in &report.error_samples {
+        println!("     error  {sample}");
+    }
+    for violation in &report.violations {
+        println!("      FAIL  {violation}");
+    }
+    println!("{}", report.summary());
+
+    if let Some(path) = &cli.report {
+        fs::write(path, serde_json::to_vec_pretty(&report)?)
+            .with_context(|| format!("failed to write report to {}", path.display()))?;
+    }
+    if let Some(path) = &cli.capacity_report {
+        let host = CapacityHost::from(&detect_hardware_profile());
+        fs::write(
+            path,
+            serde_json::to_vec_pretty(&report.capacity_report(host))?,
+        )
+        .with_context(|| format!("failed to write capacity report to {}", path.display()))?;
+    }
+    Ok(report.passed())
+}
@@ -0,0 +1,288 @@
+//! NOA synthetic load generator
+//!
+//! Sends a configurable mix of gateway traffic (protocol distribution, payload
+//! sizes, and caller identities from a [`LoadProfile`]) to a [`LoadTarget`] and
+//! measures latency percentiles and error rates against the profile's targets:
+//! - [`InProcessTarget`] drives a gateway built in-process, for CI and for
+//!   measuring what a host can sustain.
+//! - [`HttpTarget`] drives a deployed gateway or API server.
+//!
+//! [`LoadReport::capacity_report`] condenses a run into a [`CapacityReport`] for the
+//! host it ran on; the runtime manager derives its host classification thresholds
+//! from a set of those reports. The `noa-loadgen` binary runs a profile and writes
+//! both reports.
+
+mod profile;
+mod target;
+
+pub use profile::{
+    builtin_profile, load_profile, Identity, IdentityPermission, LoadProfile, LoadRequest,
+    LoadTargets, Weighted,
+};
+pub use target::{HttpTarget, InProcessTarget, LoadTarget, DEFAULT_ROUTE_PATH};
+
+use noa_core::hardware::HardwareProfile;
+use noa_gateway::Protocol;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+/// Error messages kept in a report as examples.
+const ERROR_SAMPLES: usize = 5;
+
+/// Nearest-rank latency percentiles in milliseconds.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct LatencyPercentiles {
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencyPercentiles {
+    pub fn from_samples(samples: &mut [f64]) -> Self {
+        samples.sort_by(f64::total_cmp);
+        let rank = |percentile: f64| {
+            if samples.is_empty() {
+                return 0.0;
+            }
+            let index = ((percentile / 100.0) * samples.len() as f64).ceil() as usize;
+            samples[index.clamp(1, samples.len()) - 1]
+        };
+        Self {
+            p50_ms: rank(50.0),
+            p95_ms: rank(95.0),
+            p99_ms: rank(99.0),
+            max_ms: samples.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolSummary {
+    pub protocol: Protocol,
+    pub requests: u64,
+    pub errors: u64,
+    pub latency: LatencyPercentiles,
+}
+
+/// Outcome of one load run.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadReport {
+    pub target: String,
+    pub profile: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub duration_ms: f64,
+    pub throughput_rps: f64,
+    pub latency: LatencyPercentiles,
+    pub protocols: Vec<ProtocolSummary>,
+    /// Targets the run missed; empty when every target was met.
+    pub violations: Vec<String>,
+    pub error_samples: Vec<String>,
+}
+
+impl LoadReport {
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} ({}): {} requests, {:.1} req/s, p50 {:.2} ms, p95 {:.2} ms, p99 {:.2} ms, {:.2}% errors",
+            self.target,
+            self.profile,
+            self.requests,
+            self.throughput_rps,
+            self.latency.p50_ms,
+            self.latency.p95_ms,
+            self.latency.p99_ms,
+            self.error_rate * 100.0
+        )
+    }
+
+    pub fn capacity_report(&self, host: CapacityHost) -> CapacityReport {
+        CapacityReport {
+            target: self.target.clone(),
+            profile: self.profile.clone(),
+            host,
+            requests: self.requests,
+            errors: self.errors,
+            error_rate: self.error_rate,
+            throughput_rps: self.throughput_rps,
+            latency: self.latency,
+            targets_met: self.passed(),
+        }
+    }
+}
+
+/// Host a run was measured on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CapacityHost {
+    pub cpu_cores: usize,
+    pub memory_gb: f64,
+    pub gpu_count: usize,
+    pub gpu_memory_gb: Option<f64>,
+}
+
+impl From<&HardwareProfile> for CapacityHost {
+    fn from(profile: &HardwareProfile) -> Self {
+        Self {
+            cpu_cores: profile.cpu.logical_cores,
+            memory_gb: profile.total_memory_gb(),
+            gpu_count: profile.gpus.len(),
+            gpu_memory_gb: profile
+                .gpus
+                .iter()
+                .filter_map(|gpu| gpu.memory_total_bytes)
+                .max()
+                .map(|bytes| bytes as f64 / (1024.0 * 1024.0 * 1024.0)),
+        }
+    }
+}
+
+/// Capacity evidence read by `runtime_manager::CapacityThresholds::from_reports`.
+/// The field layout is shared with `runtime_manager::CapacityReport`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CapacityReport {
+    pub target: String,
+    pub profile: String,
+    pub host: CapacityHost,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub throughput_rps: f64,
+    pub latency: LatencyPercentiles,
+    pub targets_met: bool,
+}
+
+struct Sample {
+    protocol: Protocol,
+    latency_ms: f64,
+    error: Option<String>,
+}
+
+/// Send every request of `profile` to `target` from `profile.concurrency` workers.
+pub fn run_load(target: &dyn LoadTarget, profile: &LoadProfile) -> LoadReport {
+    let requests = profile.generate();
+    let next = AtomicUsize::new(0);
+    let samples = Mutex::new(Vec::with_capacity(requests.len()));
+
+    let started = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..profile.concurrency.min(requests.len()) {
+            scope.spawn(|| {
+                let mut local = Vec::new();
+                while let Some(request) = requests.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let sent = Instant::now();
+                    let outcome = target.send(request);
+                    local.push(Sample {
+                        protocol: request.protocol.clone(),
+                        latency_ms: sent.elapsed().as_secs_f64() * 1000.0,
+                        error: outcome.err().map(|err| format!("{err:#}")),
+                    });
+                }
+                samples.lock().expect("samples poisoned").extend(local);
+            });
+        }
+    });
+    let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let samples = samples.into_inner().expect("samples poisoned");
+
+    let (latency, errors) = summarize(samples.iter());
+    let protocols = [Protocol::GraphQl, Protocol::Grpc, Protocol::WebSocket]
+        .into_iter()
+        .filter_map(|protocol| {
+            let matching = samples.iter().filter(|sample| sample.protocol == protocol);
+            let requests = matching.clone().count() as u64;
+            let (latency, errors) = summarize(matching);
+            (requests > 0).then_some(ProtocolSummary {
+                protocol,
+                requests,
+                errors,
+                latency,
+            })
+        })
+        .collect();
+    let total = samples.len() as u64;
+    let error_rate = if total == 0 {
+        0.0
+    } else {
+        errors as f64 / total as f64
+    };
+
+    let mut report = LoadReport {
+        target: target.name().to_string(),
+        profile: profile.name.clone(),
+        requests: total,
+        errors,
+        error_rate,
+        duration_ms,
+        throughput_rps: total as f64 / (duration_ms / 1000.0).max(f64::EPSILON),
+        latency,
+        protocols,
+        violations: Vec::new(),
+        error_samples: samples
+            .iter()
+            .filter_map(|sample| sample.error.clone())
+            .take(ERROR_SAMPLES)
+            .collect(),
+    };
+    report.violations = violations(&report, &profile.targets);
+    report
+}
+
+fn summarize<'a>(samples: impl Iterator<Item = &'a Sample>) -> (LatencyPercentiles, u64) {
+    let mut latencies = Vec::new();
+    let mut errors = 0;
+    for sample in samples {
+        latencies.push(sample.latency_ms);
+        errors += u64::from(sample.error.is_some());
+    }
+    (LatencyPercentiles::from_samples(&mut latencies), errors)
+}
+
+fn violations(report: &LoadReport, targets: &LoadTargets) -> Vec<String> {
+    let mut violations = Vec::new();
+    for (name, observed, target) in [
+        ("p50", report.latency.p50_ms, targets.p50_ms),
+        ("p95", report.latency.p95_ms, targets.p95_ms),
+        ("p99", report.latency.p99_ms, targets.p99_ms),
+    ] {
+        if observed > target {
+            violations.push(format!(
+                "{name} latency {observed:.2} ms exceeds {target:.2} ms"
+            ));
+        }
+    }
+    if report.error_rate > targets.max_error_rate {
+        violations.push(format!(
+            "error rate {:.2}% exceeds {:.2}%",
+            report.error_rate * 100.0,
+            targets.max_error_rate * 100.0
+        ));
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let mut samples: Vec<f64> = (1..=100).rev().map(f64::from).collect();
+        let latency = LatencyPercentiles::from_samples(&mut samples);
+        assert_eq!(latency.p50_ms, 50.0);
+        assert_eq!(latency.p95_ms, 95.0);
+        assert_eq!(latency.p99_ms, 99.0);
+        assert_eq!(latency.max_ms, 100.0);
+        assert_eq!(
+            LatencyPercentiles::from_samples(&mut []),
+            LatencyPercentiles::default()
+        );
+    }
+}
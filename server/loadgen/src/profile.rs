@@ -0,0 +1,200 @@
+use anyhow::{bail, Context, Result};
+use noa_core::security::{Permission, UserId};
+use noa_gateway::{AuthCredentials, Protocol};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+const BUILTIN_PROFILE: &str = include_str!("../profiles/mixed.json");
+
+/// A value drawn with probability proportional to `weight`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Weighted<T> {
+    pub value: T,
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentityPermission {
+    Read,
+    Write,
+    Execute,
+    Admin,
+}
+
+impl IdentityPermission {
+    pub fn permission(&self) -> Permission {
+        match self {
+            IdentityPermission::Read => Permission::Read,
+            IdentityPermission::Write => Permission::Write,
+            IdentityPermission::Execute => Permission::Execute,
+            IdentityPermission::Admin => Permission::Admin,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdentityPermission::Read => "read",
+            IdentityPermission::Write => "write",
+            IdentityPermission::Execute => "execute",
+            IdentityPermission::Admin => "admin",
+        }
+    }
+}
+
+/// Caller the generated traffic authenticates as.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Identity {
+    pub name: String,
+    #[serde(default)]
+    pub user_id: UserId,
+    #[serde(default = "default_agent")]
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub credentials: AuthCredentials,
+    #[serde(default = "default_permission")]
+    pub permission: IdentityPermission,
+}
+
+/// Service levels a run must meet for its host to count as having capacity.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LoadTargets {
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_error_rate: f64,
+}
+
+/// Traffic mix for one load run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadProfile {
+    pub name: String,
+    pub requests: usize,
+    pub concurrency: usize,
+    /// Seed for the request mix, so runs on different hosts send the same traffic.
+    #[serde(default)]
+    pub seed: u64,
+    pub protocols: Vec<Weighted<Protocol>>,
+    pub payload_bytes: Vec<Weighted<usize>>,
+    pub identities: Vec<Weighted<Identity>>,
+    pub targets: LoadTargets,
+}
+
+/// One generated request.
+#[derive(Debug, Clone)]
+pub struct LoadRequest {
+    pub request_id: String,
+    pub protocol: Protocol,
+    pub identity: Identity,
+    pub payload: Value,
+}
+
+impl LoadProfile {
+    pub fn validate(&self) -> Result<()> {
+        if self.requests == 0 || self.concurrency == 0 {
+            bail!(
+                "profile {} needs at least one request and worker",
+                self.name
+            );
+        }
+        if total_weight(&self.protocols) == 0
+            || total_weight(&self.payload_bytes) == 0
+            || total_weight(&self.identities) == 0
+        {
+            bail!(
+                "profile {} needs a positive weight for protocols, payload sizes, and identities",
+                self.name
+            );
+        }
+        Ok(())
+    }
+
+    /// Expand the profile into its request sequence.
+    pub fn generate(&self) -> Vec<LoadRequest> {
+        let mut rng = SplitMix64(self.seed);
+        (0..self.requests)
+            .map(|sequence| {
+                let protocol = pick(&self.protocols, &mut rng).clone();
+                let bytes = *pick(&self.payload_bytes, &mut rng);
+                let identity = pick(&self.identities, &mut rng).clone();
+                LoadRequest {
+                    request_id: format!("loadgen-{}-{sequence}", self.name),
+                    payload: payload(&protocol, bytes),
+                    protocol,
+                    identity,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The mixed GraphQL/gRPC/WebSocket profile shipped with this crate.
+pub fn builtin_profile() -> Result<LoadProfile> {
+    parse_profile("profiles/mixed.json", BUILTIN_PROFILE)
+}
+
+pub fn load_profile(path: &Path) -> Result<LoadProfile> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse_profile(&path.display().to_string(), &raw)
+}
+
+fn parse_profile(source: &str, raw: &str) -> Result<LoadProfile> {
+    let profile: LoadProfile =
+        serde_json::from_str(raw).with_context(|| format!("invalid load profile {source}"))?;
+    profile.validate()?;
+    Ok(profile)
+}
+
+/// A routable payload for `protocol`, padded to roughly `bytes` when serialized.
+fn payload(protocol: &Protocol, bytes: usize) -> Value {
+    let mut payload = match protocol {
+        Protocol::GraphQl => json!({
+            "query": "{ serviceA { id name } }",
+            "federation": { "services": ["serviceA", "serviceB"], "version": "1.0" },
+        }),
+        Protocol::Grpc => json!({ "service": "workflow", "method": "Run" }),
+        Protocol::WebSocket => json!({ "channel": "alerts" }),
+    };
+    let used = payload.to_string().len() + r#","padding":"""#.len();
+    payload["padding"] = Value::String("x".repeat(bytes.saturating_sub(used)));
+    payload
+}
+
+fn total_weight<T>(choices: &[Weighted<T>]) -> u64 {
+    choices.iter().map(|choice| u64::from(choice.weight)).sum()
+}
+
+fn pick<'a, T>(choices: &'a [Weighted<T>], rng: &mut SplitMix64) -> &'a T {
+    let mut roll = rng.next() % total_weight(choices);
+    for choice in choices {
+        let weight = u64::from(choice.weight);
+        if roll < weight {
+            return &choice.value;
+        }
+        roll -= weight;
+    }
+    unreachable!("roll is below the total weight")
+}
+
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+fn default_agent() -> Option<String> {
+    Some("fixed_agent_gateway".into())
+}
+
+fn default_permission() -> IdentityPermission {
+    IdentityPermission::Read
+}
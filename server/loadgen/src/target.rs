@@ -0,0 +1,158 @@
+use anyhow::{anyhow, bail, Context, Result};
+use noa_agents::registry::AgentRegistry;
+use noa_core::security;
+use noa_gateway::{
+    Gateway, GatewayRequest, PolicyEnforcer, ProgrammableRouter, RateLimiter, RateLimiterConfig,
+    TelemetrySink, UnifiedAuthenticator,
+};
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+use crate::profile::LoadRequest;
+
+/// Path of the gateway routing endpoint on deployed servers.
+pub const DEFAULT_ROUTE_PATH: &str = "/v1/route";
+
+/// Something the load generator can send requests to. Workers share the target
+/// across threads.
+pub trait LoadTarget: Sync {
+    fn name(&self) -> &str;
+
+    /// Send one request; an error counts against the run's error rate.
+    fn send(&self, request: &LoadRequest) -> Result<()>;
+}
+
+/// A single gateway built in-process with rate limits lifted, so a run measures
+/// authentication, policy, routing, and telemetry cost rather than bucket sizes.
+pub struct InProcessTarget {
+    gateway: Gateway,
+    _telemetry: TempDir,
+}
+
+impl InProcessTarget {
+    pub fn new() -> Result<Self> {
+        security::init().map_err(|err| anyhow!("failed to init security: {}", err))?;
+        let registry =
+            Arc::new(AgentRegistry::with_default_data().context("failed to load agent registry")?);
+        let mut config = RateLimiterConfig {
+            refill_interval: Duration::from_secs(1),
+            ..RateLimiterConfig::default()
+        };
+        for limit in config.layer_limits.values_mut() {
+            *limit = u32::MAX;
+        }
+        let telemetry = tempfile::tempdir().context("failed to create telemetry directory")?;
+        let gateway = Gateway::new(
+            UnifiedAuthenticator::default(),
+            PolicyEnforcer::new(),
+            ProgrammableRouter::default(),
+            RateLimiter::new(config, registry),
+            TelemetrySink::new(telemetry.path())?,
+        )?;
+        Ok(Self {
+            gateway,
+            _telemetry: telemetry,
+        })
+    }
+}
+
+impl LoadTarget for InProcessTarget {
+    fn name(&self) -> &str {
+        "in-process"
+    }
+
+    fn send(&self, request: &LoadRequest) -> Result<()> {
+        self.gateway.handle_request(GatewayRequest {
+            request_id: request.request_id.clone(),
+            user_id: request.identity.user_id,
+            agent_id: request.identity.agent_id.clone(),
+            credentials: request.identity.credentials.clone(),
+            protocol: request.protocol.clone(),
+            payload: request.payload.clone(),
+            required_permission: request.identity.permission.permission(),
+        })?;
+        Ok(())
+    }
+}
+
+/// A deployed gateway or API server reached over HTTP. Deployed gateways keep
+/// their rate limits, which show up as errors once an identity's burst is spent.
+pub struct HttpTarget {
+    client: Client,
+    endpoint: String,
+    capability_token: String,
+    capability_scope: String,
+}
+
+impl HttpTarget {
+    pub fn new(
+        base_url: &str,
+        capability_token: impl Into<String>,
+        capability_scope: impl Into<String>,
+    ) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("failed to build HTTP client")?;
+        Ok(Self {
+            client,
+            endpoint: format!("{}{DEFAULT_ROUTE_PATH}", base_url.trim_end_matches('/')),
+            capability_token: capability_token.into(),
+            capability_scope: capability_scope.into(),
+        })
+    }
+
+    /// Send to `path` on the same host instead of the gateway route endpoint.
+    pub fn with_path(mut self, path: &str) -> Self {
+        if let Some(base) = self.endpoint.strip_suffix(DEFAULT_ROUTE_PATH) {
+            self.endpoint = format!("{base}/{}", path.trim_start_matches('/'));
+        }
+        self
+    }
+}
+
+impl LoadTarget for HttpTarget {
+    fn name(&self) -> &str {
+        &self.endpoint
+    }
+
+    fn send(&self, request: &LoadRequest) -> Result<()> {
+        let identity = &request.identity;
+        let mut http = self
+            .client
+            .post(&self.endpoint)
+            .header("x-noa-capability", &self.capability_token)
+            .json(&json!({
+                "request_id": request.request_id,
+                "user_id": identity.user_id,
+                "agent_id": identity.agent_id,
+                "protocol": request.protocol,
+                "payload": request.payload,
+                "required_permission": identity.permission.as_str(),
+                "capability_scope": self.capability_scope,
+            }));
+        if let Some(api_key) = &identity.credentials.api_key {
+            http = http.header("x-noa-api-key", api_key);
+        }
+        if let Some(token) = &identity.credentials.oidc {
+            http = http.bearer_auth(token);
+        }
+
+        let response = http
+            .send()
+            .with_context(|| format!("failed to reach {}", self.endpoint))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let message = response
+            .json::<Value>()
+            .ok()
+            .and_then(|body| body.get("error").and_then(Value::as_str).map(String::from))
+            .unwrap_or_default();
+        bail!("{status}: {message}")
+    }
+}
@@ -0,0 +1,56 @@
+use noa_gateway::Protocol;
+use noa_loadgen::{builtin_profile, run_load, CapacityHost, InProcessTarget, LoadTargets};
+
+#[test]
+fn builtin_profile_runs_in_process() {
+    let mut profile = builtin_profile().expect("built-in profile parses");
+    profile.requests = 60;
+    profile.concurrency = 4;
+    profile.targets = LoadTargets {
+        p50_ms: 1_000.0,
+        p95_ms: 1_000.0,
+        p99_ms: 1_000.0,
+        max_error_rate: 0.0,
+    };
+
+    let requests = profile.generate();
+    let again = profile.generate();
+    assert!(requests
+        .iter()
+        .zip(&again)
+        .all(|(a, b)| a.protocol == b.protocol && a.payload == b.payload));
+    assert!(requests
+        .iter()
+        .any(|request| request.payload.to_string().len() >= 1024));
+
+    let target = InProcessTarget::new().expect("in-process gateway");
+    let report = run_load(&target, &profile);
+    assert_eq!(report.requests, 60);
+    assert!(
+        report.passed(),
+        "{:?} {:?}",
+        report.violations,
+        report.error_samples
+    );
+    for protocol in [Protocol::GraphQl, Protocol::Grpc, Protocol::WebSocket] {
+        assert!(report
+            .protocols
+            .iter()
+            .any(|summary| summary.protocol == protocol));
+    }
+
+    let capacity = report.capacity_report(CapacityHost {
+        cpu_cores: 8,
+        memory_gb: 16.0,
+        ..CapacityHost::default()
+    });
+    assert!(capacity.targets_met);
+    let json = serde_json::to_value(&capacity).unwrap();
+    assert_eq!(json["host"]["memory_gb"], 16.0);
+    assert!(json["latency"]["p99_ms"].is_number());
+
+    profile.targets.p50_ms = 0.0;
+    let strict = run_load(&target, &profile);
+    assert!(!strict.passed());
+    assert!(!strict.capacity_report(CapacityHost::default()).targets_met);
+}
@@ -1,5 +1,9 @@
 use anyhow::{anyhow, Context, Result};
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use noa_core::events::{KernelEvent, KernelEventKind, KernelEventSubscriber};
+use noa_core::telemetry::{TelemetryEmitter, TelemetryError, TelemetryRecord};
+use opentelemetry::global::BoxedTracer;
+use opentelemetry::trace::{Span as _, Tracer as _};
 use opentelemetry::{global, trace::TracerProvider as _, KeyValue};
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::{self, Resource};
@@ -152,6 +156,78 @@ impl MetricsExporter {
     }
 }
 
+/// Telemetry emitter exporting each record as a span through the global tracer
+/// provider, i.e. over OTLP once [`init_tracing`] was given an endpoint. The span is
+/// named after the record's stream; top-level payload fields become
+/// `telemetry.<field>` attributes.
+pub struct OtlpEmitter {
+    tracer: BoxedTracer,
+}
+
+impl OtlpEmitter {
+    pub fn new(instrumentation_scope: &'static str) -> Self {
+        Self {
+            tracer: global::tracer(instrumentation_scope),
+        }
+    }
+}
+
+impl TelemetryEmitter for OtlpEmitter {
+    fn emit(&self, record: &TelemetryRecord) -> std::result::Result<(), TelemetryError> {
+        let mut span = self.tracer.start(record.stream.clone());
+        match &record.payload {
+            serde_json::Value::Object(fields) => {
+                for (key, value) in fields {
+                    let value = match value {
+                        serde_json::Value::String(text) => text.clone(),
+                        other => other.to_string(),
+                    };
+                    span.set_attribute(KeyValue::new(format!("telemetry.{key}"), value));
+                }
+            }
+            other => span.set_attribute(KeyValue::new("telemetry.payload", other.to_string())),
+        }
+        span.end();
+        Ok(())
+    }
+}
+
+/// Kernel event bus subscriber logging each event through `tracing` and counting
+/// it in `noa_kernel_events_total{kind}`. Capability initialization times are
+/// recorded in the `noa_kernel_capability_init_ms{capability}` histogram.
+#[derive(Debug, Default)]
+pub struct KernelEventRecorder;
+
+impl KernelEventSubscriber for KernelEventRecorder {
+    fn on_event(&self, event: &KernelEvent) {
+        let kind = event.kind.name();
+        metrics::counter!("noa_kernel_events_total", 1, "kind" => kind);
+        match &event.kind {
+            KernelEventKind::CapabilityInitialized {
+                capability,
+                duration_ms,
+            } => {
+                metrics::histogram!(
+                    "noa_kernel_capability_init_ms",
+                    *duration_ms as f64,
+                    "capability" => capability.clone()
+                );
+                tracing::info!(target: "noa_kernel", kind, capability, duration_ms);
+            }
+            KernelEventKind::CapabilityRegistered { capability }
+            | KernelEventKind::CapabilityReplaced { capability } => {
+                tracing::info!(target: "noa_kernel", kind, capability);
+            }
+            KernelEventKind::CapabilityFailed { capability, error } => {
+                tracing::error!(target: "noa_kernel", kind, capability, error);
+            }
+            KernelEventKind::UnhandledError { source, message } => {
+                tracing::error!(target: "noa_kernel", kind, source, message);
+            }
+        }
+    }
+}
+
 /// Convenience helper initialising tracing + metrics with a single call.
 pub fn init(
     tracing: &TracingConfig,
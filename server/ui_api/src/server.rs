@@ -19,7 +19,9 @@ use noa_crc::engine::Engine;
 use noa_crc::graph::{CRCGraph, GraphNode, NodeKind};
 use noa_crc::ir::Lane;
 use noa_crc::{CRCState, CRCSystem, DropManifest, OriginalArtifact, Priority, SourceType};
-use noa_workflow::{StageState, Workflow, WorkflowEngine, WorkflowState};
+use noa_workflow::{
+    PendingApproval, StageState, Workflow, WorkflowEngine, WorkflowState,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value as JsonValue};
 use tokio::fs;
@@ -180,6 +182,8 @@ impl UiApiServer {
             .route("/ui/pages/:page_id", get(Self::get_page))
             .route("/ui/pages/:page_id/events", get(Self::stream_events))
             .route("/ui/workflows", post(Self::start_workflow))
+            // Approvals are registered through the authenticated noa_api endpoint.
+            .route("/ui/workflows/approvals", get(Self::pending_approvals))
             .route("/healthz", get(Self::health))
             .route("/readyz", get(Self::ready))
             // Upload → Digest
@@ -379,6 +383,10 @@ f.addEventListener('submit', async e=>{
         }))
     }
 
+    async fn pending_approvals(State(state): State<UiApiState>) -> Json<Vec<PendingApproval>> {
+        Json(state.workflow_engine().pending_approvals())
+    }
+
     async fn upload_drop(
         State(state): State<UiApiState>,
         mut multipart: Multipart,
@@ -444,6 +452,7 @@ f.addEventListener('submit', async e=>{
             timestamp: if timestamp >= 0 { timestamp as u64 } else { 0 },
             priority: Priority::Normal,
             metadata,
+            provenance: None,
         };
 
         let registry = state.drop_registry();
@@ -837,7 +846,7 @@ mod tests {
     use axum::body::Body;
     use axum::http::{Request, StatusCode as HttpStatus};
     use http_body_util::BodyExt;
-    use noa_workflow::{Stage, StageType, Task, Workflow};
+    use noa_workflow::{Stage, StageCacheControl, StageContract, StageType, Task, Workflow};
     use serde::{Deserialize, Serialize};
     use serde_json::Value;
     use std::sync::Mutex as StdMutex;
@@ -989,6 +998,9 @@ mod tests {
                 stage_type: StageType::Sequential,
                 depends_on: vec![],
                 tasks: Vec::<Task>::new(),
+                compensation: vec![],
+                cache: StageCacheControl::default(),
+                contract: StageContract::default(),
             }],
         };
 
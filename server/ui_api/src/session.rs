@@ -81,6 +81,32 @@ impl SessionBridge {
                 }),
                 timestamp,
             },
+            WorkflowEvent::ApprovalRequested {
+                workflow_id,
+                approval,
+                timestamp,
+            } => RealTimeEvent {
+                event_type: "workflow/approval".into(),
+                workflow_id,
+                payload: json!({
+                    "approval": approval,
+                }),
+                timestamp,
+            },
+            WorkflowEvent::RunQueued {
+                workflow_id,
+                namespace,
+                position,
+                timestamp,
+            } => RealTimeEvent {
+                event_type: "workflow/queue".into(),
+                workflow_id,
+                payload: json!({
+                    "namespace": namespace,
+                    "position": position,
+                }),
+                timestamp,
+            },
         }
     }
 }
@@ -0,0 +1,58 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use noa_e2e_harness::{Scenario, ScenarioHarness};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "e2e-harness",
+    about = "Drive a synthetic drop through CRC, CI/CD, and deployment and verify the result"
+)]
+struct Cli {
+    /// Empty directory to run in and keep afterwards; a temporary one is used otherwise.
+    #[arg(long)]
+    workspace: Option<PathBuf>,
+    /// Write the JSON report to this path.
+    #[arg(long)]
+    report: Option<PathBuf>,
+}
+
+fn main() {
+    match run() {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("{err:?}");
+            std::process::exit(2);
+        }
+    }
+}
+
+fn run() -> Result<bool> {
+    let cli = Cli::parse();
+    let harness = match &cli.workspace {
+        Some(workspace) => ScenarioHarness::in_workspace(workspace)?,
+        None => ScenarioHarness::new()?,
+    };
+
+    let report = harness.run(&Scenario::synthetic_drop())?;
+    for step in &report.steps {
+        let status = if step.error.is_none() { "ok" } else { "FAIL" };
+        println!("{status:>4}  {:<8}  {} ms", step.name, step.duration_ms);
+        if let Some(error) = &step.error {
+            println!("        {error}");
+        }
+    }
+    for violation in &report.violations {
+        println!("FAIL  invariant: {violation}");
+    }
+    println!("{}", report.summary());
+
+    if let Some(path) = &cli.report {
+        fs::write(path, serde_json::to_vec_pretty(&report)?)
+            .with_context(|| format!("failed to write report to {}", path.display()))?;
+    }
+    Ok(report.passed())
+}
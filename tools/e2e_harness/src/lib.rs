@@ -0,0 +1,629 @@
+//! NOA end-to-end scenario harness
+//!
+//! Slice tests cover CRC, CI/CD, and deployments on their own; this harness drives
+//! the whole loop against a temporary workspace and checks the hand-offs between them:
+//! 1. **intake** registers a synthetic drop with captured provenance.
+//! 2. **adapt** runs the CRC processor: quarantine screening, analysis, adaptation,
+//!    validation, profile builds, and archiving.
+//! 3. **pipeline** triggers a CI/CD pipeline from the CRC job and executes it, with the
+//!    build stage attaching the CRC build manifests as artifacts.
+//! 4. **deploy** ships the pipeline to a local environment and monitors its health.
+//! 5. **verify** checks the invariants tying the steps together: the archived drop,
+//!    the pipeline's link to its CRC job, signed evidence ledger entries, the hash chain
+//!    of the pipeline event log, artifact digests, and the exported evidence bundle.
+//!
+//! The `e2e-harness` binary runs the built-in scenario as one command.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{anyhow, bail, Context, Result};
+use noa_cicd::{CICDSystem, DeploymentStrategy, Environment, HealthMetrics, PipelineStatus};
+use noa_core::recovery::RecoveryMode;
+use noa_crc::processor::{DropProcessor, ProcessingResult};
+use noa_crc::{
+    BuildArtifact, CRCConfig, CRCSystem, DropManifest, DropProvenance, Priority, QuarantineGate,
+    SourceType,
+};
+use noa_workflow::{
+    read_evidence_ledger, ConfigContext, EvidenceLedgerKind, Namespace, PipelineInstrumentation,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Pipeline event log written by CI/CD, relative to the workspace.
+const PIPELINE_EVENT_LOG: &str = ".workspace/indexes/pipeline_events.log";
+/// Evidence ledger written by the pipeline instrumentation, relative to the workspace.
+const EVIDENCE_LEDGER: &str = "storage/db/evidence/ledger.jsonl";
+/// CRC drop-in, quarantine, and archive root, relative to the workspace.
+const CRC_ROOT: &str = "crc";
+/// Steps of the loop, in the order they run.
+pub const STEPS: [&str; 5] = ["intake", "adapt", "pipeline", "deploy", "verify"];
+
+/// A synthetic drop and where it is deployed.
+#[derive(Debug, Clone, Serialize)]
+pub struct Scenario {
+    pub name: String,
+    pub source_type: SourceType,
+    /// Files of the drop, relative path to contents.
+    pub files: BTreeMap<String, String>,
+    pub environment: Environment,
+    /// Confidence the pipeline is triggered with; below the CI/CD auto-approval
+    /// threshold the scenario stops at agent review and fails.
+    pub ai_confidence: f32,
+}
+
+impl Scenario {
+    /// A small, clean Rust crate from an external repository, deployed to staging.
+    pub fn synthetic_drop() -> Self {
+        let files = [
+            (
+                "Cargo.toml",
+                "[package]\nname = \"e2e_widget\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            ),
+            (
+                "src/lib.rs",
+                "/// Sum of the widget weights.\npub fn total_weight(weights: &[u32]) -> u32 {\n    weights.iter().sum()\n}\n",
+            ),
+            ("README.md", "# e2e_widget\n\nSynthetic drop for the end-to-end harness.\n"),
+            ("LICENSE", "MIT License\n"),
+        ];
+        Self {
+            name: "e2e_widget".to_string(),
+            source_type: SourceType::ExternalRepo,
+            files: files
+                .into_iter()
+                .map(|(path, contents)| (path.to_string(), contents.to_string()))
+                .collect(),
+            environment: Environment::Staging,
+            ai_confidence: 0.99,
+        }
+    }
+
+    pub fn with_file(mut self, path: impl Into<String>, contents: impl Into<String>) -> Self {
+        self.files.insert(path.into(), contents.into());
+        self
+    }
+}
+
+/// Outcome of one step of the loop.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepResult {
+    pub name: String,
+    pub duration_ms: u64,
+    /// Identifiers and counts produced by the step.
+    pub detail: Value,
+    /// Why the step failed; later steps are skipped.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioReport {
+    pub scenario: String,
+    pub workspace: PathBuf,
+    pub drop_id: Option<String>,
+    pub pipeline_id: Option<String>,
+    pub deployment_id: Option<String>,
+    pub steps: Vec<StepResult>,
+    /// Broken invariants found by the verify step.
+    pub violations: Vec<String>,
+}
+
+impl ScenarioReport {
+    pub fn passed(&self) -> bool {
+        self.steps.iter().all(|step| step.error.is_none())
+            && self.violations.is_empty()
+            && self.steps.iter().any(|step| step.name == "verify")
+    }
+
+    pub fn summary(&self) -> String {
+        let failed = self
+            .steps
+            .iter()
+            .filter(|step| step.error.is_some())
+            .count();
+        format!(
+            "{}: {} steps, {} failed, {} invariant violations",
+            self.scenario,
+            self.steps.len(),
+            failed,
+            self.violations.len()
+        )
+    }
+}
+
+/// Runs scenarios in a workspace of their own.
+pub struct ScenarioHarness {
+    workspace: PathBuf,
+    _temp: Option<tempfile::TempDir>,
+}
+
+impl ScenarioHarness {
+    /// Harness in a fresh temporary workspace, removed when the harness is dropped.
+    pub fn new() -> Result<Self> {
+        let temp = tempfile::tempdir().context("failed to create temporary workspace")?;
+        Ok(Self {
+            workspace: temp.path().to_path_buf(),
+            _temp: Some(temp),
+        })
+    }
+
+    /// Harness in `workspace`, kept afterwards for inspection. The directory must be
+    /// empty or missing so earlier runs cannot satisfy the invariants.
+    pub fn in_workspace(workspace: impl Into<PathBuf>) -> Result<Self> {
+        let workspace = workspace.into();
+        if workspace.exists() && fs::read_dir(&workspace)?.next().is_some() {
+            bail!("workspace {} is not empty", workspace.display());
+        }
+        fs::create_dir_all(&workspace)
+            .with_context(|| format!("failed to create {}", workspace.display()))?;
+        Ok(Self {
+            workspace,
+            _temp: None,
+        })
+    }
+
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    /// Drive `scenario` through the loop. Step failures and invariant violations are
+    /// recorded in the report; only failing to set up the systems is an error.
+    pub fn run(&self, scenario: &Scenario) -> Result<ScenarioReport> {
+        let mut run = Run {
+            workspace: &self.workspace,
+            scenario,
+            crc: crc_system(&self.workspace)?,
+            cicd: CICDSystem::with_context(
+                ConfigContext::isolated().with_workflow_root(&self.workspace),
+            ),
+            report: ScenarioReport {
+                scenario: scenario.name.clone(),
+                workspace: self.workspace.clone(),
+                drop_id: None,
+                pipeline_id: None,
+                deployment_id: None,
+                steps: Vec::new(),
+                violations: Vec::new(),
+            },
+            provenance: None,
+            processing: None,
+        };
+        run.cicd.configure_workspace_root(&self.workspace);
+        for name in STEPS {
+            let start = Instant::now();
+            let outcome = run.step(name);
+            let failed = outcome.is_err();
+            run.report.steps.push(StepResult {
+                name: name.to_string(),
+                duration_ms: start.elapsed().as_millis() as u64,
+                detail: outcome.as_ref().cloned().unwrap_or(Value::Null),
+                error: outcome.err().map(|err| format!("{err:#}")),
+            });
+            if failed {
+                break;
+            }
+        }
+        Ok(run.report)
+    }
+}
+
+struct Run<'a> {
+    workspace: &'a Path,
+    scenario: &'a Scenario,
+    crc: CRCSystem,
+    cicd: CICDSystem,
+    report: ScenarioReport,
+    provenance: Option<DropProvenance>,
+    processing: Option<ProcessingResult>,
+}
+
+impl Run<'_> {
+    fn step(&mut self, name: &str) -> Result<Value> {
+        match name {
+            "intake" => self.intake(),
+            "adapt" => self.adapt(),
+            "pipeline" => self.pipeline(),
+            "deploy" => self.deploy(),
+            "verify" => self.verify(),
+            _ => bail!("unknown step {name}"),
+        }
+    }
+
+    fn crc_root(&self) -> PathBuf {
+        self.workspace.join(CRC_ROOT)
+    }
+
+    fn drop_path(&self) -> PathBuf {
+        self.crc_root()
+            .join("drop-in/incoming/repos")
+            .join(&self.scenario.name)
+    }
+
+    fn drop_id(&self) -> Result<&str> {
+        self.report
+            .drop_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("no drop registered"))
+    }
+
+    fn pipeline_id(&self) -> Result<&str> {
+        self.report
+            .pipeline_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("no pipeline triggered"))
+    }
+
+    fn intake(&mut self) -> Result<Value> {
+        let source = self.drop_path();
+        for (path, contents) in &self.scenario.files {
+            let file = source.join(path);
+            fs::create_dir_all(file.parent().expect("drop file has a parent"))?;
+            fs::write(&file, contents)
+                .with_context(|| format!("failed to write {}", file.display()))?;
+        }
+        let provenance = DropProvenance::capture(&source)?;
+        let manifest = DropManifest {
+            name: self.scenario.name.clone(),
+            source: "e2e-harness".to_string(),
+            source_type: self.scenario.source_type.clone(),
+            timestamp: provenance.fetched_at,
+            priority: Priority::Normal,
+            metadata: Default::default(),
+            provenance: Some(provenance.clone()),
+        };
+        let drop_id = self
+            .crc
+            .register_drop(source, manifest, None)
+            .map_err(|err| anyhow!(err))?;
+        let detail = json!({
+            "drop_id": drop_id,
+            "files": provenance.file_count,
+            "tree_checksum": provenance.tree_checksum,
+        });
+        self.report.drop_id = Some(drop_id);
+        self.provenance = Some(provenance);
+        Ok(detail)
+    }
+
+    fn adapt(&mut self) -> Result<Value> {
+        let drop_id = self.drop_id()?.to_string();
+        let processor = DropProcessor::new(self.crc_root())
+            .with_workspace_root(self.workspace.to_path_buf())
+            .with_quarantine(self.crc.quarantine());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let result = runtime.block_on(processor.process_drop(
+            &drop_id,
+            self.scenario.source_type.clone(),
+            self.drop_path(),
+            None,
+            self.provenance.clone(),
+        ))?;
+        if !result.success {
+            bail!(
+                "CRC processing stopped at {}: {:?}",
+                result.stage,
+                result.errors
+            );
+        }
+        let detail = json!({
+            "stage": result.stage,
+            "confidence": result.confidence,
+            "archive_path": result.metadata.get("archive_path"),
+            "warnings": result.warnings.len(),
+        });
+        self.processing = Some(result);
+        Ok(detail)
+    }
+
+    fn pipeline(&mut self) -> Result<Value> {
+        let drop_id = self.drop_id()?.to_string();
+        let builds = self.build_artifacts()?;
+        let artifacts: BTreeMap<String, String> = builds
+            .iter()
+            .map(|build| {
+                let path = build.artifact_path.join("manifest.yaml");
+                let relative = path
+                    .strip_prefix(self.workspace)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .into_owned();
+                (
+                    format!("{:?}", build.manifest.profile).to_lowercase(),
+                    relative,
+                )
+            })
+            .collect();
+        let definition = json!({
+            "stages": [
+                { "name": "validate", "type": "validate" },
+                { "name": "build", "type": "build", "parameters": { "artifacts": artifacts } },
+                { "name": "test", "type": "test" },
+                { "name": "deploy", "type": "deploy" },
+            ],
+        });
+        fs::write(
+            self.workspace.join("pipeline.yaml"),
+            serde_json::to_string_pretty(&definition)?,
+        )?;
+
+        let checksum = self
+            .provenance
+            .as_ref()
+            .map(|provenance| provenance.tree_checksum.clone())
+            .unwrap_or_default();
+        let pipeline_id = self
+            .cicd
+            .trigger_from_crc(
+                self.scenario.name.clone(),
+                checksum,
+                drop_id,
+                self.scenario.ai_confidence,
+            )
+            .map_err(|err| anyhow!(err))?;
+        self.report.pipeline_id = Some(pipeline_id.clone());
+        self.cicd
+            .execute_pipeline(&pipeline_id)
+            .map_err(|err| anyhow!(err))?;
+        let status = self.cicd.get_pipeline_status(&pipeline_id);
+        if status != Some(PipelineStatus::Success) {
+            bail!("pipeline {pipeline_id} finished as {status:?}");
+        }
+        Ok(json!({
+            "pipeline_id": pipeline_id,
+            "artifacts": artifacts.len(),
+        }))
+    }
+
+    fn deploy(&mut self) -> Result<Value> {
+        let pipeline_id = self.pipeline_id()?.to_string();
+        let deployment_id = self
+            .cicd
+            .deploy_pipeline_to_environment(
+                &pipeline_id,
+                "0.1.0".to_string(),
+                self.scenario.environment.clone(),
+                DeploymentStrategy::BlueGreen,
+            )
+            .map_err(|err| anyhow!(err))?;
+        self.report.deployment_id = Some(deployment_id.clone());
+        self.cicd
+            .record_deployment_metrics(&deployment_id, HealthMetrics::default())
+            .map_err(|err| anyhow!(err))?;
+        if !self
+            .cicd
+            .monitor_deployment(&deployment_id)
+            .map_err(|err| anyhow!(err))?
+        {
+            bail!("deployment {deployment_id} reported unhealthy");
+        }
+        Ok(json!({
+            "deployment_id": deployment_id,
+            "environment": self.scenario.environment,
+        }))
+    }
+
+    fn verify(&mut self) -> Result<Value> {
+        let mut violations = Vec::new();
+        self.check_crc(&mut violations)?;
+        self.check_pipeline(&mut violations)?;
+        let ledger_entries = self.check_ledger(&mut violations)?;
+        let events = self.check_event_log(&mut violations)?;
+        let bundle = self.check_evidence_bundle(&mut violations)?;
+        let checked = json!({
+            "ledger_entries": ledger_entries,
+            "pipeline_events": events,
+            "evidence_files": bundle,
+        });
+        self.report.violations = violations;
+        Ok(checked)
+    }
+
+    /// The drop was archived and the archive describes the tree that was registered.
+    fn check_crc(&self, violations: &mut Vec<String>) -> Result<()> {
+        let metadata = &self
+            .processing
+            .as_ref()
+            .ok_or_else(|| anyhow!("drop was not processed"))?
+            .metadata;
+        match metadata.get("archive_path") {
+            Some(path) if Path::new(path).is_file() => {}
+            other => violations.push(format!("archive missing at {other:?}")),
+        }
+        let checksum = self.provenance.as_ref().map(|p| &p.tree_checksum);
+        if metadata.get("provenance_tree_checksum") != checksum {
+            violations.push("archived provenance checksum differs from intake".to_string());
+        }
+        Ok(())
+    }
+
+    /// The pipeline belongs to the CRC job, ran every stage, and ships its artifacts.
+    fn check_pipeline(&self, violations: &mut Vec<String>) -> Result<()> {
+        let pipeline_id = self.pipeline_id()?;
+        let pipeline = self
+            .cicd
+            .get_pipeline(pipeline_id)
+            .ok_or_else(|| anyhow!("pipeline {pipeline_id} disappeared"))?;
+        if pipeline.crc_job_id.as_deref() != self.report.drop_id.as_deref() {
+            violations.push(format!(
+                "pipeline CRC job {:?} is not drop {:?}",
+                pipeline.crc_job_id, self.report.drop_id
+            ));
+        }
+        for stage in &pipeline.stages {
+            if stage.status != PipelineStatus::Success {
+                violations.push(format!("stage {} ended as {:?}", stage.name, stage.status));
+            }
+        }
+
+        let artifacts = self
+            .cicd
+            .list_artifacts(pipeline_id)
+            .map_err(|err| anyhow!(err))?;
+        if artifacts.is_empty() {
+            violations.push("build stage attached no artifacts".to_string());
+        }
+        for artifact in &artifacts {
+            let check = self
+                .cicd
+                .verify_artifact(pipeline_id, &artifact.path)
+                .map_err(|err| anyhow!(err))?;
+            if !check.verified() {
+                violations.push(format!("artifact {} failed verification", artifact.path));
+            }
+        }
+        if let Some(deployment_id) = &self.report.deployment_id {
+            let shipped = self
+                .cicd
+                .deployment_artifacts(deployment_id)
+                .map_err(|err| anyhow!(err))?;
+            if shipped != artifacts {
+                violations.push(format!(
+                    "deployment {deployment_id} does not ship the pipeline's artifacts"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Every evidence ledger entry about the pipeline carries a valid signature.
+    fn check_ledger(&self, violations: &mut Vec<String>) -> Result<usize> {
+        let pipeline_id = self.pipeline_id()?;
+        let ledger =
+            read_evidence_ledger(&self.workspace.join(EVIDENCE_LEDGER), RecoveryMode::Strict)?;
+        let entries: Vec<_> = ledger
+            .records
+            .iter()
+            .filter(|entry| {
+                ["workflow_id", "subject"]
+                    .iter()
+                    .any(|key| entry.payload.get(key).and_then(Value::as_str) == Some(pipeline_id))
+            })
+            .collect();
+        if !entries
+            .iter()
+            .any(|entry| entry.kind == EvidenceLedgerKind::Artifact)
+        {
+            violations.push("evidence ledger records no artifacts for the pipeline".to_string());
+        }
+        for entry in &entries {
+            if !noa_core::security::verify_signed_operation(&entry.signed_operation) {
+                violations.push(format!(
+                    "ledger entry {:?} {} has an invalid signature",
+                    entry.kind, entry.reference
+                ));
+            }
+        }
+        Ok(entries.len())
+    }
+
+    /// The pipeline event log is an unbroken hash chain and records the pipeline's
+    /// stages in order, followed by its deployment.
+    fn check_event_log(&self, violations: &mut Vec<String>) -> Result<usize> {
+        let pipeline_id = self.pipeline_id()?;
+        self.cicd.flush_events().map_err(|err| anyhow!(err))?;
+        let path = self.workspace.join(PIPELINE_EVENT_LOG);
+        let log = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let entries = log
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str::<Value>)
+            .collect::<Result<Vec<_>, _>>()?;
+        for pair in entries.windows(2) {
+            if pair[1]["previous_hash"] != pair[0]["entry_hash"] {
+                violations.push(format!(
+                    "pipeline event log chain breaks after {}",
+                    pair[0]["entry_hash"]
+                ));
+            }
+        }
+
+        let completed: Vec<&str> = entries
+            .iter()
+            .filter(|entry| entry["event"]["scope"] == pipeline_id)
+            .filter(|entry| entry["event"]["event_type"] == "pipeline.stage_completed")
+            .filter_map(|entry| entry["event"]["metadata"]["stage"].as_str())
+            .collect();
+        let stages: Vec<String> = self
+            .cicd
+            .get_pipeline(pipeline_id)
+            .map(|pipeline| {
+                pipeline
+                    .stages
+                    .into_iter()
+                    .map(|stage| stage.name)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if completed != stages {
+            violations.push(format!(
+                "stages completed {completed:?}, expected {stages:?}"
+            ));
+        }
+        if let Some(deployment_id) = &self.report.deployment_id {
+            let scope = format!("deployment::{deployment_id}");
+            if !entries.iter().any(|entry| {
+                entry["event"]["scope"] == scope.as_str()
+                    && entry["event"]["event_type"] == "deployment.auto_start"
+            }) {
+                violations.push(format!("deployment {deployment_id} start was not logged"));
+            }
+        }
+        Ok(entries.len())
+    }
+
+    /// The exported evidence bundle verifies and its manifest is signed.
+    fn check_evidence_bundle(&self, violations: &mut Vec<String>) -> Result<usize> {
+        let pipeline_id = self.pipeline_id()?;
+        let bundle = self.workspace.join("out/e2e/evidence.tar.gz");
+        fs::create_dir_all(bundle.parent().expect("bundle path has a parent"))?;
+        self.cicd
+            .export_evidence_bundle_to(pipeline_id, &bundle)
+            .map_err(|err| anyhow!(err))?;
+        let verification =
+            noa_cicd::evidence::verify_evidence_bundle(&bundle).map_err(|err| anyhow!(err))?;
+        if verification.pipeline_id != pipeline_id {
+            violations.push(format!(
+                "evidence bundle is for {}, expected {pipeline_id}",
+                verification.pipeline_id
+            ));
+        }
+        if !verification.signature_valid {
+            violations.push("evidence bundle manifest signature is invalid".to_string());
+        }
+        Ok(verification.files_verified)
+    }
+
+    fn build_artifacts(&self) -> Result<Vec<BuildArtifact>> {
+        let serialized = self
+            .processing
+            .as_ref()
+            .and_then(|result| result.metadata.get("build_artifacts"))
+            .ok_or_else(|| anyhow!("CRC produced no build artifacts"))?;
+        Ok(serde_json::from_str(serialized)?)
+    }
+}
+
+/// CRC system keeping its drop-in, archive, quarantine, and audit trail in `workspace`.
+fn crc_system(workspace: &Path) -> Result<CRCSystem> {
+    let root = workspace.join(CRC_ROOT);
+    let config = CRCConfig {
+        drop_in_path: root.join("drop-in"),
+        archive_path: root.join("archive"),
+        temp_path: root.join("temp"),
+        ..CRCConfig::default()
+    };
+    let audit = PipelineInstrumentation::with_context(
+        &Namespace::default(),
+        ConfigContext::isolated().with_workflow_root(workspace),
+    )?;
+    let quarantine =
+        QuarantineGate::new(root.join("quarantine")).with_instrumentation(Arc::new(audit));
+    Ok(CRCSystem::new(config).with_quarantine(quarantine))
+}
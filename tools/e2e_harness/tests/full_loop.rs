@@ -0,0 +1,40 @@
+use noa_e2e_harness::{Scenario, ScenarioHarness, STEPS};
+
+#[test]
+fn synthetic_drop_flows_from_crc_to_a_verified_deployment() {
+    let harness = ScenarioHarness::new().expect("temporary workspace");
+    let report = harness
+        .run(&Scenario::synthetic_drop())
+        .expect("systems set up");
+    let errors = report
+        .steps
+        .iter()
+        .filter_map(|step| {
+            step.error
+                .as_ref()
+                .map(|err| format!("{}: {err}", step.name))
+        })
+        .chain(report.violations.iter().cloned())
+        .collect::<Vec<_>>();
+    assert!(report.passed(), "{}", errors.join("\n"));
+
+    let steps: Vec<&str> = report.steps.iter().map(|step| step.name.as_str()).collect();
+    assert_eq!(steps, STEPS);
+    assert!(report.deployment_id.is_some());
+    let verify = &report.steps[4].detail;
+    assert!(verify["ledger_entries"].as_u64().unwrap() > 0);
+    assert!(verify["evidence_files"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn low_confidence_drop_stops_before_deployment() {
+    let harness = ScenarioHarness::new().expect("temporary workspace");
+    let mut scenario = Scenario::synthetic_drop();
+    scenario.ai_confidence = 0.2;
+    let report = harness.run(&scenario).expect("systems set up");
+    assert!(!report.passed());
+    let failed = report.steps.last().unwrap();
+    assert_eq!(failed.name, "pipeline");
+    assert!(failed.error.is_some());
+    assert!(report.deployment_id.is_none());
+}
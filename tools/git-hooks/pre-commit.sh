@@ -0,0 +1,15 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+ROOT_DIR="$(git rev-parse --show-toplevel 2>/dev/null || pwd)"
+CARGO_BIN="${CARGO_BIN:-cargo}"
+
+if ! command -v "${CARGO_BIN}" >/dev/null 2>&1; then
+  echo "❌ cargo not found; cannot check the workspace policy." >&2
+  exit 1
+fi
+
+"${CARGO_BIN}" run --quiet --manifest-path "${ROOT_DIR}/Cargo.toml" \
+  -p noa_cicd --bin noa_workspace_policy -- \
+  --root "${ROOT_DIR}" \
+  --staged
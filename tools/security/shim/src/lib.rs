@@ -19,6 +19,10 @@ pub enum ShimError {
     Serde(#[from] serde_json::Error),
 }
 
+/// Report directory used when a [`ScanConfig`] has no `cache_dir`, relative to the
+/// current directory.
+pub const DEFAULT_REPORT_DIR: &str = ".workspace/indexes/security_scans";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanConfig {
     pub target: PathBuf,
@@ -32,6 +36,21 @@ fn default_offline() -> bool {
     true
 }
 
+impl ScanConfig {
+    pub fn for_target(target: impl Into<PathBuf>) -> Self {
+        Self {
+            target: target.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Write reports under `cache_dir` instead of [`DEFAULT_REPORT_DIR`].
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+}
+
 impl Default for ScanConfig {
     fn default() -> Self {
         Self {
@@ -151,7 +170,9 @@ fn vulnerability_hints(config: &ScanConfig) -> Result<Vec<ScanFinding>, ShimErro
             continue;
         }
         let path = entry.path();
-        let content = fs::read_to_string(path)?;
+        let Some(content) = read_text(path)? else {
+            continue;
+        };
         if content.contains("VULNERABLE") || content.contains("CVE-") {
             findings.push(ScanFinding {
                 file: relative(path, &config.target),
@@ -195,7 +216,9 @@ fn secret_patterns(config: &ScanConfig) -> Result<Vec<ScanFinding>, ShimError> {
             continue;
         }
         let path = entry.path();
-        let content = fs::read_to_string(path)?;
+        let Some(content) = read_text(path)? else {
+            continue;
+        };
         for needle in ["SECRET=", "PRIVATE_KEY", "AWS_ACCESS_KEY_ID"] {
             if content.contains(needle) {
                 findings.push(ScanFinding {
@@ -218,7 +241,7 @@ fn persist_report(
     let base = config
         .cache_dir
         .clone()
-        .unwrap_or_else(|| PathBuf::from(".workspace/indexes/security_scans"));
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_REPORT_DIR));
     fs::create_dir_all(&base)?;
     let timestamp = Utc::now().format("%Y%m%dT%H%M%S");
     let path = base.join(format!("{}_{}.json", tool, timestamp));
@@ -231,6 +254,15 @@ fn persist_report(
     Ok(Some(path.to_string_lossy().to_string()))
 }
 
+/// File contents as text, or `None` for binary files that are not valid UTF-8.
+fn read_text(path: &Path) -> Result<Option<String>, ShimError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(err) if err.kind() == std::io::ErrorKind::InvalidData => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
 fn relative(path: &Path, root: &Path) -> String {
     path.strip_prefix(root)
         .unwrap_or(path)
@@ -258,6 +290,24 @@ mod tests {
         assert!(!result.findings.is_empty());
     }
 
+    #[test]
+    fn text_scanners_skip_binary_files() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("logo.png"),
+            [0x89, b'P', b'N', b'G', 0xff, 0x00],
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.md"), "CVE-2024-0001").unwrap();
+        let config = ScanConfig {
+            target: dir.path().to_path_buf(),
+            cache_dir: Some(dir.path().join("reports")),
+            ..ScanConfig::default()
+        };
+        assert_eq!(run_gitleaks(&config).unwrap().status, ScanStatus::Passed);
+        assert_eq!(run_grype(&config).unwrap().findings.len(), 1);
+    }
+
     #[test]
     fn syft_reports_manifests() {
         let dir = tempdir().unwrap();
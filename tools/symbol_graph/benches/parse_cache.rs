@@ -0,0 +1,86 @@
+//! Symbol graph rebuilds over a generated fixture repository, with and without a
+//! warm parse cache. Compare `rebuild/cold` against `rebuild/warm` and
+//! `rebuild/small_edit` for the speedup.
+
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use noa_symbol_graph::{ParseCache, SymbolGraphBuilder};
+use tempfile::TempDir;
+
+const FILES: usize = 200;
+const FUNCTIONS_PER_FILE: usize = 60;
+
+fn fixture_repo() -> TempDir {
+    let dir = tempfile::tempdir().expect("fixture dir");
+    for file in 0..FILES {
+        let module = dir.path().join(format!("crate_{}/src", file % 10));
+        fs::create_dir_all(&module).expect("fixture module");
+        let source: String = (0..FUNCTIONS_PER_FILE)
+            .map(|index| {
+                format!(
+                    "pub struct Item{file}_{index} {{ value: i64 }}\n\
+                     pub fn compute_{file}_{index}(input: i64) -> i64 {{\n    \
+                     let doubled = input * 2;\n    helper_{index}(doubled) + {index}\n}}\n"
+                )
+            })
+            .collect();
+        fs::write(module.join(format!("file_{file}.rs")), source).expect("fixture file");
+    }
+    dir
+}
+
+fn rebuild(root: &Path, store: &Path, cache: Arc<Mutex<ParseCache>>) {
+    SymbolGraphBuilder::new(root)
+        .with_store_root(store)
+        .with_parse_cache(cache)
+        .index()
+        .expect("symbol graph rebuild");
+}
+
+fn bench_rebuilds(c: &mut Criterion) {
+    let repo = fixture_repo();
+    let root = repo.path();
+    let store = tempfile::tempdir().expect("store dir");
+    let store = store.path();
+    let mut group = c.benchmark_group("rebuild");
+    group.sample_size(10);
+
+    group.bench_function("cold", |b| {
+        b.iter(|| rebuild(root, store, Arc::new(Mutex::new(ParseCache::new()))));
+    });
+
+    let warm = Arc::new(Mutex::new(ParseCache::new()));
+    rebuild(root, store, Arc::clone(&warm));
+    group.bench_function("warm", |b| {
+        b.iter(|| rebuild(root, store, Arc::clone(&warm)));
+    });
+
+    let edited = root.join("crate_0/src/file_0.rs");
+    let original = fs::read_to_string(&edited).expect("fixture source");
+    let mut toggle = false;
+    group.bench_function("small_edit", |b| {
+        b.iter(|| {
+            toggle = !toggle;
+            let source = if toggle {
+                original.replacen("input * 2", "input * 3", 1)
+            } else {
+                original.clone()
+            };
+            fs::write(&edited, source).expect("edit fixture");
+            rebuild(root, store, Arc::clone(&warm));
+        });
+    });
+    group.finish();
+
+    let stats = warm.lock().expect("parse cache").stats();
+    println!(
+        "parse cache: {} hits, {} incremental, {} full parses",
+        stats.hits, stats.incremental, stats.full
+    );
+}
+
+criterion_group!(benches, bench_rebuilds);
+criterion_main!(benches);
@@ -0,0 +1,286 @@
+//! Dead-code and orphan detection.
+//!
+//! [`DeadCodeReport::analyze`] walks the call and import edges of a [`SymbolGraph`].
+//! Functions that nothing outside their own file calls are reported as candidate dead
+//! code, and source files that no `mod` declaration or relative import brings in are
+//! reported as orphans. Both lists are ranked so the largest likely-unused code comes
+//! first, and the report can be written as JSON and markdown evidence.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use noa_core::symbols::stable_symbol_id;
+use serde::{Deserialize, Serialize};
+
+use crate::{GraphError, SymbolGraph};
+
+/// File names that are loaded by the toolchain rather than imported.
+const ENTRY_FILES: [&str; 5] = ["lib.rs", "main.rs", "build.rs", "index.ts", "index.tsx"];
+/// Directories whose files are compiled as their own targets.
+const ENTRY_DIRS: [&str; 4] = ["bin", "tests", "benches", "examples"];
+/// Functions invoked by the runtime or test harness rather than by other code.
+const ENTRY_FUNCTIONS: [&str; 2] = ["main", "anonymous"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanSymbolKind {
+    /// Not called from anywhere in the graph.
+    Unreferenced,
+    /// Only called from within its own file.
+    FileLocal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OrphanSymbol {
+    pub stable_id: String,
+    pub name: String,
+    pub kind: String,
+    pub file: String,
+    pub span: (usize, usize),
+    pub orphan: OrphanSymbolKind,
+    /// Calls from the symbol's own file.
+    pub local_callers: usize,
+}
+
+impl OrphanSymbol {
+    pub fn lines(&self) -> usize {
+        self.span.1.saturating_sub(self.span.0) + 1
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OrphanFile {
+    pub file: String,
+    /// Symbols defined in the file.
+    pub symbols: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DeadCodeReport {
+    pub symbols_analyzed: usize,
+    pub files_analyzed: usize,
+    /// Ranked unreferenced symbols first, then by size.
+    pub orphan_symbols: Vec<OrphanSymbol>,
+    /// Ranked by the number of symbols the file defines.
+    pub orphan_files: Vec<OrphanFile>,
+}
+
+impl DeadCodeReport {
+    pub fn analyze(graph: &SymbolGraph) -> Self {
+        let mut callers: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut imported = BTreeSet::new();
+        for edge in &graph.edges {
+            match edge.kind.as_str() {
+                "call" => callers
+                    .entry(edge.to.as_str())
+                    .or_default()
+                    .push(edge.from.as_str()),
+                "import" => {
+                    imported.insert(edge.to.as_str());
+                }
+                _ => {}
+            }
+        }
+
+        let mut orphan_symbols: Vec<OrphanSymbol> = graph
+            .nodes
+            .values()
+            .filter(|node| node.kind == "function")
+            .filter(|node| !ENTRY_FUNCTIONS.contains(&node.name.as_str()))
+            .filter(|node| !is_entry_file(&node.file))
+            .filter_map(|node| {
+                let call_id = stable_symbol_id(&node.language, &node.name, "call", &node.name);
+                let mut local_callers = 0;
+                for caller in callers.get(call_id.as_str()).into_iter().flatten() {
+                    // Callers that are not nodes (closures, methods) count as external.
+                    match graph.find(caller) {
+                        Some(caller) if caller.file == node.file => local_callers += 1,
+                        _ => return None,
+                    }
+                }
+                Some(OrphanSymbol {
+                    stable_id: node.stable_id.clone(),
+                    name: node.name.clone(),
+                    kind: node.kind.clone(),
+                    file: node.file.clone(),
+                    span: node.span,
+                    orphan: if local_callers == 0 {
+                        OrphanSymbolKind::Unreferenced
+                    } else {
+                        OrphanSymbolKind::FileLocal
+                    },
+                    local_callers,
+                })
+            })
+            .collect();
+        orphan_symbols.sort_by(|a, b| {
+            a.orphan
+                .cmp(&b.orphan)
+                .then(b.lines().cmp(&a.lines()))
+                .then_with(|| a.file.cmp(&b.file))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        let mut symbols_per_file: BTreeMap<&str, usize> = BTreeMap::new();
+        for node in graph.nodes.values() {
+            *symbols_per_file.entry(node.file.as_str()).or_default() += 1;
+        }
+        let files_analyzed = symbols_per_file.len();
+        let mut orphan_files: Vec<OrphanFile> = symbols_per_file
+            .into_iter()
+            .filter(|(file, _)| !imported.contains(file) && !is_entry_file(file))
+            .map(|(file, symbols)| OrphanFile {
+                file: file.to_string(),
+                symbols,
+            })
+            .collect();
+        orphan_files.sort_by(|a, b| b.symbols.cmp(&a.symbols).then_with(|| a.file.cmp(&b.file)));
+
+        Self {
+            symbols_analyzed: graph.nodes.len(),
+            files_analyzed,
+            orphan_symbols,
+            orphan_files,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orphan_symbols.is_empty() && self.orphan_files.is_empty()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Dead Code Report\n\n");
+        let _ = writeln!(
+            out,
+            "Analyzed {} symbols across {} files: {} candidate dead symbols, {} orphan files.\n",
+            self.symbols_analyzed,
+            self.files_analyzed,
+            self.orphan_symbols.len(),
+            self.orphan_files.len()
+        );
+        out.push_str("## Candidate Dead Symbols\n\n");
+        if self.orphan_symbols.is_empty() {
+            out.push_str("None found.\n");
+        } else {
+            out.push_str("| Rank | Symbol | File | Lines | Callers |\n");
+            out.push_str("| --- | --- | --- | --- | --- |\n");
+            for (rank, symbol) in self.orphan_symbols.iter().enumerate() {
+                let callers = match symbol.orphan {
+                    OrphanSymbolKind::Unreferenced => "none".to_string(),
+                    OrphanSymbolKind::FileLocal => format!("{} (same file)", symbol.local_callers),
+                };
+                let _ = writeln!(
+                    out,
+                    "| {} | `{}` | {}:{} | {} | {} |",
+                    rank + 1,
+                    symbol.name,
+                    symbol.file,
+                    symbol.span.0,
+                    symbol.lines(),
+                    callers
+                );
+            }
+        }
+        out.push_str("\n## Orphan Files\n\n");
+        if self.orphan_files.is_empty() {
+            out.push_str("None found.\n");
+        } else {
+            for (rank, file) in self.orphan_files.iter().enumerate() {
+                let _ = writeln!(
+                    out,
+                    "{}. {} ({} symbols)",
+                    rank + 1,
+                    file.file,
+                    file.symbols
+                );
+            }
+        }
+        out
+    }
+
+    /// Write `dead_code.json` and `dead_code.md` into `dir`, returning their paths.
+    pub fn write(&self, dir: &Path) -> Result<(PathBuf, PathBuf), GraphError> {
+        fs::create_dir_all(dir)?;
+        let json_path = dir.join("dead_code.json");
+        let markdown_path = dir.join("dead_code.md");
+        fs::write(&json_path, serde_json::to_string_pretty(self)?)?;
+        fs::write(&markdown_path, self.to_markdown())?;
+        Ok((json_path, markdown_path))
+    }
+}
+
+fn is_entry_file(file: &str) -> bool {
+    let path = Path::new(file);
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    ENTRY_FILES.contains(&name)
+        || name.ends_with(".d.ts")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+        || path
+            .components()
+            .any(|component| ENTRY_DIRS.contains(&component.as_os_str().to_str().unwrap_or("")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SymbolGraphBuilder;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reports_uncalled_symbols_and_unimported_files() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("util")).unwrap();
+        fs::write(
+            src.join("lib.rs"),
+            "mod util;\npub fn run() -> i32 { util::helpers::shared(1) }\n",
+        )
+        .unwrap();
+        fs::write(src.join("util/mod.rs"), "pub mod helpers;\n").unwrap();
+        fs::write(
+            src.join("util/helpers.rs"),
+            "pub fn shared(v: i32) -> i32 { local(v) }\n\
+             fn local(v: i32) -> i32 { v }\n\
+             pub fn unused(v: i32) -> i32 {\n    v * 2\n}\n",
+        )
+        .unwrap();
+        fs::write(src.join("stale.rs"), "pub fn forgotten() {}\n").unwrap();
+
+        let store = tempdir().unwrap();
+        let graph = SymbolGraphBuilder::new(dir.path())
+            .with_store_root(store.path())
+            .index()
+            .unwrap();
+        let report = DeadCodeReport::analyze(&graph);
+
+        let names: Vec<(&str, OrphanSymbolKind)> = report
+            .orphan_symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.orphan))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("unused", OrphanSymbolKind::Unreferenced),
+                ("forgotten", OrphanSymbolKind::Unreferenced),
+                ("local", OrphanSymbolKind::FileLocal),
+            ]
+        );
+        assert_eq!(report.orphan_files.len(), 1);
+        assert!(report.orphan_files[0].file.ends_with("stale.rs"));
+
+        let (json_path, markdown_path) = report.write(store.path()).unwrap();
+        let reloaded: DeadCodeReport =
+            serde_json::from_str(&fs::read_to_string(json_path).unwrap()).unwrap();
+        assert_eq!(reloaded, report);
+        assert!(fs::read_to_string(markdown_path)
+            .unwrap()
+            .contains("| 1 | `unused` |"));
+    }
+}
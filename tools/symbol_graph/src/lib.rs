@@ -1,14 +1,36 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::{self, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
-use std::path::{Path, PathBuf};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
+use noa_core::fs::{ChangeJournal, JournalError};
+use noa_core::recovery::{self, RecordError, Recovered, RecoveryMode, SkippedRecord};
 use noa_core::symbols::stable_symbol_id;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tree_sitter::{Language, Node, Parser};
+use tree_sitter::{Language, Node};
 use walkdir::WalkDir;
 
+pub mod dead_code;
+pub mod ownership;
+pub mod parse_cache;
+pub mod similarity;
+
+pub use dead_code::{DeadCodeReport, OrphanFile, OrphanSymbol, OrphanSymbolKind};
+pub use ownership::{CodeOwners, Ownership, OwnershipRule};
+pub use parse_cache::{ParseCache, ParseCacheStats};
+pub use similarity::{
+    CanonicalTarget, DuplicateFinding, SimilarityIndex, DEFAULT_DUPLICATE_THRESHOLD,
+};
+
+/// Store location, relative to the indexed root, used unless overridden.
+pub const DEFAULT_STORE_DIR: &str = ".workspace/indexes/symbol_graph";
+
+/// File in the store holding the last journal sequence applied by [`SymbolGraphBuilder::apply_journal`].
+const JOURNAL_CURSOR: &str = "journal.cursor";
+
 #[derive(Debug, Error)]
 pub enum GraphError {
     #[error("unsupported language for path {0}")]
@@ -21,6 +43,10 @@ pub enum GraphError {
     Walkdir(#[from] walkdir::Error),
     #[error("serde error: {0}")]
     Serde(#[from] serde_json::Error),
+    #[error("corrupt store: {0}")]
+    Record(#[from] RecordError),
+    #[error("journal error: {0}")]
+    Journal(#[from] JournalError),
     #[error("internal error: {0}")]
     Internal(String),
 }
@@ -60,37 +86,72 @@ impl SymbolGraph {
     }
 
     pub fn load(store_root: impl AsRef<Path>) -> Result<Self, GraphError> {
+        Self::load_with_recovery(store_root, RecoveryMode::Strict).map(|(graph, _)| graph)
+    }
+
+    /// Load the store, skipping corrupt JSONL lines in lenient mode. Skipped lines
+    /// are reported with their file name, e.g. `nodes.jsonl line 4`.
+    pub fn load_with_recovery(
+        store_root: impl AsRef<Path>,
+        mode: RecoveryMode,
+    ) -> Result<(Self, Vec<SkippedRecord>), GraphError> {
         let root = store_root.as_ref();
-        let nodes_path = root.join("nodes.jsonl");
-        let edges_path = root.join("edges.jsonl");
         let mut graph = SymbolGraph::default();
+        let mut skipped = Vec::new();
 
-        if nodes_path.exists() {
-            let reader = BufReader::new(std::fs::File::open(&nodes_path)?);
-            for line in reader.lines() {
-                let line = line?;
-                if line.trim().is_empty() {
-                    continue;
-                }
-                let node: SymbolNode = serde_json::from_str(&line)?;
-                graph.nodes.insert(node.stable_id.clone(), node);
-            }
+        let nodes = read_jsonl::<SymbolNode>(&root.join("nodes.jsonl"), mode)?;
+        for node in nodes.records {
+            graph.nodes.insert(node.stable_id.clone(), node);
         }
+        skipped.extend(nodes.skipped);
 
-        if edges_path.exists() {
-            let reader = BufReader::new(std::fs::File::open(&edges_path)?);
-            for line in reader.lines() {
-                let line = line?;
-                if line.trim().is_empty() {
-                    continue;
-                }
-                let edge: SymbolEdge = serde_json::from_str(&line)?;
-                graph.edges.push(edge);
-            }
+        let edges = read_jsonl::<SymbolEdge>(&root.join("edges.jsonl"), mode)?;
+        graph.edges = edges.records;
+        skipped.extend(edges.skipped);
+
+        Ok((graph, skipped))
+    }
+
+    /// Rewrite the store without the lines that fail to parse and return them. When
+    /// any were dropped the journal cursor is removed too, so the next
+    /// [`SymbolGraphBuilder::apply_journal`] re-indexes the whole root.
+    pub fn repair(store_root: impl AsRef<Path>) -> Result<Vec<SkippedRecord>, GraphError> {
+        let root = store_root.as_ref();
+        let (graph, skipped) = Self::load_with_recovery(root, RecoveryMode::Lenient)?;
+        if skipped.is_empty() {
+            return Ok(skipped);
         }
+        write_jsonl(&root.join("nodes.jsonl"), graph.nodes.values())?;
+        write_jsonl(&root.join("edges.jsonl"), graph.edges.iter())?;
+        match fs::remove_file(root.join(JOURNAL_CURSOR)) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        Ok(skipped)
+    }
+}
 
-        Ok(graph)
+fn read_jsonl<T: DeserializeOwned>(
+    path: &Path,
+    mode: RecoveryMode,
+) -> Result<Recovered<T>, GraphError> {
+    if !path.exists() {
+        return Ok(Recovered::default());
+    }
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let label = |location: String| format!("{name} {location}");
+    let mut recovered = recovery::parse_jsonl(&fs::read(path)?, mode).map_err(|mut err| {
+        err.location = label(err.location);
+        err
+    })?;
+    for skip in &mut recovered.skipped {
+        skip.location = label(std::mem::take(&mut skip.location));
     }
+    Ok(recovered)
 }
 
 pub struct SymbolGraphBuilder {
@@ -98,20 +159,28 @@ pub struct SymbolGraphBuilder {
     store_root: PathBuf,
     nodes: HashMap<String, SymbolNode>,
     edges: Vec<SymbolEdge>,
+    parse_cache: Arc<Mutex<ParseCache>>,
 }
 
 impl SymbolGraphBuilder {
     pub fn new(root: impl Into<PathBuf>) -> Self {
         let root = root.into();
-        let store_root = root.join(".workspace").join("indexes").join("symbol_graph");
+        let store_root = root.join(DEFAULT_STORE_DIR);
         Self {
             root,
             store_root,
             nodes: HashMap::new(),
             edges: Vec::new(),
+            parse_cache: Arc::new(Mutex::new(ParseCache::new())),
         }
     }
 
+    /// Share parsed trees across rebuilds so unchanged files are not parsed again.
+    pub fn with_parse_cache(mut self, cache: Arc<Mutex<ParseCache>>) -> Self {
+        self.parse_cache = cache;
+        self
+    }
+
     pub fn with_store_root(mut self, store: impl Into<PathBuf>) -> Self {
         self.store_root = store.into();
         self
@@ -136,16 +205,73 @@ impl SymbolGraphBuilder {
         SymbolGraph::load(&self.store_root)
     }
 
+    /// Update the store with the files changed in `journal` since the last call:
+    /// symbols and edges of changed files are dropped, and files that still exist
+    /// are re-indexed. Indexes everything when the store has no cursor yet.
+    pub fn apply_journal(mut self, journal: &ChangeJournal) -> Result<SymbolGraph, GraphError> {
+        let cursor_path = self.store_root.join(JOURNAL_CURSOR);
+        let cursor = match fs::read_to_string(&cursor_path) {
+            Ok(cursor) => cursor.trim().parse::<u64>().unwrap_or(0),
+            Err(_) => {
+                let changes = journal.changes_since(0)?;
+                let store_root = self.store_root.clone();
+                let graph = self.index()?;
+                fs::write(
+                    store_root.join(JOURNAL_CURSOR),
+                    changes.last_sequence.to_string(),
+                )?;
+                return Ok(graph);
+            }
+        };
+
+        let changes = journal.changes_since(cursor)?;
+        let changed: Vec<PathBuf> = changes
+            .updated
+            .iter()
+            .chain(changes.removed.iter())
+            .map(|path| journal.root().join(path))
+            .filter(|path| path.starts_with(&self.root))
+            .collect();
+        let stale_files: BTreeSet<String> =
+            changed.iter().map(|path| relative_file(path)).collect();
+
+        let mut graph = SymbolGraph::load(&self.store_root)?;
+        let stale_ids: BTreeSet<String> = graph
+            .nodes
+            .values()
+            .filter(|node| stale_files.contains(&node.file))
+            .map(|node| node.stable_id.clone())
+            .collect();
+        graph.nodes.retain(|id, _| !stale_ids.contains(id));
+        graph
+            .edges
+            .retain(|edge| !stale_ids.contains(&edge.from) && !stale_files.contains(&edge.from));
+        write_jsonl(&self.store_root.join("nodes.jsonl"), graph.nodes.values())?;
+        write_jsonl(&self.store_root.join("edges.jsonl"), graph.edges.iter())?;
+
+        for path in changed.iter().filter(|path| path.is_file()) {
+            match self.index_file(path) {
+                Ok(()) | Err(GraphError::UnsupportedLanguage(_)) => {}
+                Err(err) => eprintln!(
+                    "[symbol-graph] skipping {} due to error: {}",
+                    path.display(),
+                    err
+                ),
+            }
+        }
+        self.persist()?;
+        fs::write(&cursor_path, changes.last_sequence.to_string())?;
+        SymbolGraph::load(&self.store_root)
+    }
+
     pub fn index_file(&mut self, path: &Path) -> Result<(), GraphError> {
         let (language_id, language) = language_for(path)?;
         let source = fs::read_to_string(path)?;
-        let mut parser = Parser::new();
-        parser
-            .set_language(language)
-            .map_err(|err| GraphError::Parser(err.to_string()))?;
-        let tree = parser
-            .parse(&source, None)
-            .ok_or_else(|| GraphError::Parser("failed to parse source".into()))?;
+        let tree = self
+            .parse_cache
+            .lock()
+            .map_err(|_| GraphError::Internal("parse cache lock poisoned".into()))?
+            .parse(path, language, &source)?;
         let cursor = tree.walk();
         let mut stack = vec![cursor.clone()];
 
@@ -174,7 +300,15 @@ impl SymbolGraphBuilder {
 
     fn persist(&self) -> Result<(), GraphError> {
         fs::create_dir_all(&self.store_root)?;
-        let mut graph = SymbolGraph::load(&self.store_root).unwrap_or_default();
+        let (mut graph, skipped) =
+            SymbolGraph::load_with_recovery(&self.store_root, RecoveryMode::Lenient)
+                .unwrap_or_default();
+        for skip in skipped {
+            eprintln!(
+                "[symbol-graph] dropping corrupt record {}: {}",
+                skip.location, skip.reason
+            );
+        }
         for (id, node) in &self.nodes {
             graph.nodes.insert(id.clone(), node.clone());
         }
@@ -250,7 +384,7 @@ fn collect_symbol(
     }?;
 
     if node.kind() == "call_expression" {
-        if let Some(name) = extract_identifier(language_id, node, source) {
+        if let Some(name) = callee_name(node, source) {
             // Find the enclosing function/method node and use its stable_id as the caller
             if let Some((enclosing_name, enclosing_kind, enclosing_signature)) =
                 find_enclosing_function(node, source, language_id)
@@ -271,9 +405,91 @@ fn collect_symbol(
         }
     }
 
+    if let Some(target) = imported_file(language_id, node, source, path) {
+        edges.push(SymbolEdge {
+            from: relative_file(path),
+            to: relative_file(&target),
+            kind: "import".to_string(),
+        });
+    }
+
     Ok(())
 }
 
+/// Name of the function a call expression invokes, ignoring any path or receiver.
+fn callee_name(node: Node, source: &str) -> Option<String> {
+    let mut callee = node.child_by_field_name("function")?;
+    loop {
+        callee = match callee.kind() {
+            "identifier" | "field_identifier" | "property_identifier" => break,
+            "scoped_identifier" => callee.child_by_field_name("name")?,
+            "field_expression" => callee.child_by_field_name("field")?,
+            "member_expression" => callee.child_by_field_name("property")?,
+            "generic_function" => callee.child_by_field_name("function")?,
+            _ => return None,
+        };
+    }
+    callee
+        .utf8_text(source.as_bytes())
+        .ok()
+        .map(|name| name.to_string())
+}
+
+/// File brought in by a `mod name;` declaration or a relative `import`, if it exists.
+fn imported_file(language: &str, node: Node, source: &str, path: &Path) -> Option<PathBuf> {
+    let dir = path.parent()?;
+    let text = |field| {
+        node.child_by_field_name(field)
+            .and_then(|child| child.utf8_text(source.as_bytes()).ok())
+    };
+    let candidates = match (language, node.kind()) {
+        ("rust", "mod_item") if node.child_by_field_name("body").is_none() => {
+            let name = text("name")?;
+            let stem = path.file_stem()?.to_str()?;
+            let base = if matches!(stem, "lib" | "main" | "mod") {
+                dir.to_path_buf()
+            } else {
+                dir.join(stem)
+            };
+            vec![
+                base.join(format!("{name}.rs")),
+                base.join(name).join("mod.rs"),
+            ]
+        }
+        ("typescript", "import_statement") => {
+            let specifier = text("source")?.trim_matches(|c| matches!(c, '"' | '\'' | '`'));
+            if !specifier.starts_with('.') {
+                return None;
+            }
+            let base = lexical_normalise(&dir.join(specifier));
+            let display = base.display();
+            vec![
+                base.clone(),
+                PathBuf::from(format!("{display}.ts")),
+                PathBuf::from(format!("{display}.tsx")),
+                base.join("index.ts"),
+                base.join("index.tsx"),
+            ]
+        }
+        _ => return None,
+    };
+    candidates.into_iter().find(|candidate| candidate.is_file())
+}
+
+fn lexical_normalise(path: &Path) -> PathBuf {
+    let mut normalised = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalised.pop();
+            }
+            other => normalised.push(other),
+        }
+    }
+    normalised
+}
+
 fn collect_rust_symbol(
     node: Node,
     source: &str,
@@ -345,13 +561,19 @@ fn find_enclosing_function(
     loop {
         if let Some(parent) = current.parent() {
             match parent.kind() {
-                "function_definition"
+                "function_item"
+                | "function_definition"
                 | "function_declaration"
                 | "method_definition"
                 | "arrow_function" => {
                     if let Some(name) = extract_identifier("rust", parent, source) {
                         let signature = normalise_signature("rust", parent, source);
-                        let kind = parent.kind().to_string();
+                        // Match the kind recorded for the function's own node.
+                        let kind = match parent.kind() {
+                            "function_item" | "function_declaration" => "function",
+                            other => other,
+                        }
+                        .to_string();
                         return Some((name, kind, signature));
                     }
                 }
@@ -424,9 +646,54 @@ fn normalise_signature(language: &str, node: Node, source: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     use std::fs;
     use tempfile::tempdir;
 
+    proptest! {
+        #[test]
+        fn store_loader_never_panics_on_corrupt_jsonl(
+            nodes in proptest::collection::vec(any::<u8>(), 0..256),
+            edges in ".*",
+        ) {
+            let dir = tempdir().unwrap();
+            fs::write(dir.path().join("nodes.jsonl"), &nodes).unwrap();
+            fs::write(dir.path().join("edges.jsonl"), &edges).unwrap();
+            let _ = SymbolGraph::load(dir.path());
+            let (_, skipped) =
+                SymbolGraph::load_with_recovery(dir.path(), RecoveryMode::Lenient).unwrap();
+            prop_assert!(skipped.iter().all(|skip| skip.location.contains(".jsonl line ")));
+        }
+    }
+
+    #[test]
+    fn lenient_load_keeps_valid_records() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "pub fn kept() {}").unwrap();
+        let builder = SymbolGraphBuilder::new(dir.path());
+        let graph = builder.index().unwrap();
+        let store = dir.path().join(DEFAULT_STORE_DIR);
+
+        let nodes = store.join("nodes.jsonl");
+        let mut content = fs::read_to_string(&nodes).unwrap();
+        content.push_str("{\"stable_id\": \"truncated\n");
+        fs::write(&nodes, content).unwrap();
+
+        assert!(matches!(
+            SymbolGraph::load(&store),
+            Err(GraphError::Record(_))
+        ));
+        let (recovered, skipped) =
+            SymbolGraph::load_with_recovery(&store, RecoveryMode::Lenient).unwrap();
+        assert_eq!(recovered.nodes, graph.nodes);
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].location.starts_with("nodes.jsonl line "));
+
+        assert_eq!(SymbolGraph::repair(&store).unwrap(), skipped);
+        assert_eq!(SymbolGraph::load(&store).unwrap().nodes, graph.nodes);
+        assert!(SymbolGraph::repair(&store).unwrap().is_empty());
+    }
+
     #[test]
     fn builds_symbol_graph_for_rust_file() {
         let dir = tempdir().unwrap();
@@ -448,6 +715,40 @@ mod tests {
         assert_eq!(node.kind, "function");
     }
 
+    #[test]
+    fn journal_updates_only_changed_files() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("kept.rs"), "pub fn kept() {}").unwrap();
+        fs::write(dir.path().join("old.rs"), "pub fn retired() {}").unwrap();
+        let journal = ChangeJournal::open(dir.path()).unwrap();
+        let graph = SymbolGraphBuilder::new(dir.path())
+            .apply_journal(&journal)
+            .unwrap();
+        assert!(graph.nodes.values().any(|node| node.name == "retired"));
+
+        journal
+            .write("agent-a", "old.rs", "pub fn replacement() {}")
+            .unwrap();
+        journal.rename("agent-a", "old.rs", "renamed.rs").unwrap();
+        let graph = SymbolGraphBuilder::new(dir.path())
+            .apply_journal(&journal)
+            .unwrap();
+
+        let mut names: Vec<_> = graph
+            .nodes
+            .values()
+            .map(|node| node.name.as_str())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["kept", "replacement"]);
+        let moved = graph
+            .nodes
+            .values()
+            .find(|node| node.name == "replacement")
+            .unwrap();
+        assert!(moved.file.ends_with("renamed.rs"));
+    }
+
     #[test]
     fn stable_ids_survive_file_moves() {
         let dir = tempdir().unwrap();
@@ -1,6 +1,7 @@
 use std::env;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
@@ -10,7 +11,7 @@ use notify::{
 use serde_json::to_string_pretty;
 
 use noa_symbol_graph::notebook::NotebookMetadataDiff;
-use noa_symbol_graph::{SymbolGraph, SymbolGraphBuilder};
+use noa_symbol_graph::{ParseCache, SymbolGraph, SymbolGraphBuilder};
 
 fn main() -> Result<()> {
     let (root, once) = parse_args(env::args().skip(1));
@@ -41,7 +42,7 @@ where
 
 fn run_once(root: &Path) -> Result<()> {
     let mut previous = load_existing_graph(root)?;
-    let new_graph = rebuild_graph(root)?;
+    let new_graph = rebuild_graph(root, &Arc::new(Mutex::new(ParseCache::new())))?;
     let diff = NotebookMetadataDiff::from_graphs(&previous, &new_graph);
     if diff.has_changes() {
         write_diff(root, &diff)?;
@@ -53,6 +54,9 @@ fn run_once(root: &Path) -> Result<()> {
 
 fn run_watch(root: PathBuf) -> Result<()> {
     let mut previous = load_existing_graph(&root)?;
+    // Shared across rebuilds so unchanged files reuse their trees and saves re-parse
+    // incrementally.
+    let cache = Arc::new(Mutex::new(ParseCache::new()));
     let (tx, rx) = channel();
     let mut watcher = RecommendedWatcher::new(tx, Config::default())
         .with_context(|| "failed to start filesystem watcher")?;
@@ -64,7 +68,7 @@ fn run_watch(root: PathBuf) -> Result<()> {
         match event {
             Ok(event) => {
                 if should_process(&event) {
-                    if let Err(err) = handle_event(&root, &mut previous) {
+                    if let Err(err) = handle_event(&root, &mut previous, &cache) {
                         eprintln!("[symbol-graph] watcher error: {err}");
                     }
                 }
@@ -75,8 +79,12 @@ fn run_watch(root: PathBuf) -> Result<()> {
     Ok(())
 }
 
-fn handle_event(root: &Path, previous: &mut SymbolGraph) -> Result<()> {
-    let new_graph = rebuild_graph(root)?;
+fn handle_event(
+    root: &Path,
+    previous: &mut SymbolGraph,
+    cache: &Arc<Mutex<ParseCache>>,
+) -> Result<()> {
+    let new_graph = rebuild_graph(root, cache)?;
     let diff = NotebookMetadataDiff::from_graphs(previous, &new_graph);
     if diff.has_changes() {
         write_diff(root, &diff)?;
@@ -115,8 +123,9 @@ fn is_workspace_path(path: &Path) -> bool {
     })
 }
 
-fn rebuild_graph(root: &Path) -> Result<SymbolGraph> {
+fn rebuild_graph(root: &Path, cache: &Arc<Mutex<ParseCache>>) -> Result<SymbolGraph> {
     SymbolGraphBuilder::new(root)
+        .with_parse_cache(Arc::clone(cache))
         .index()
         .with_context(|| format!("failed to rebuild symbol graph for {}", root.display()))
 }
@@ -202,7 +211,8 @@ mod tests {
         fs::write(root.join("src/lib.rs"), "pub fn example() {}").unwrap();
 
         let mut previous = SymbolGraph::default();
-        handle_event(root, &mut previous).unwrap();
+        let cache = Arc::new(Mutex::new(ParseCache::new()));
+        handle_event(root, &mut previous, &cache).unwrap();
         let diff_root = root.join(".workspace/notebook_sync/diffs");
         assert!(diff_root.exists());
         let entries: Vec<_> = fs::read_dir(diff_root).unwrap().collect();
@@ -0,0 +1,298 @@
+//! Symbol-level ownership.
+//!
+//! Owners are declared in a CODEOWNERS-style file: each line holds a pattern followed
+//! by one or more owning agents or teams, and the last matching line wins. Besides the
+//! usual path patterns, `symbol:<stable id or name>` lines assign a single symbol
+//! regardless of the file it lives in. [`Ownership`] combines those rules with a
+//! [`SymbolGraph`] to answer who owns a given symbol.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GraphError, SymbolGraph, SymbolNode};
+
+/// Locations searched for an ownership file, in order.
+pub const CODEOWNERS_LOCATIONS: [&str; 3] = ["CODEOWNERS", ".github/CODEOWNERS", "docs/CODEOWNERS"];
+
+const SYMBOL_PREFIX: &str = "symbol:";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OwnershipRule {
+    pub pattern: String,
+    /// Owning agents or teams; empty when the line explicitly leaves the pattern unowned.
+    pub owners: Vec<String>,
+}
+
+impl OwnershipRule {
+    fn symbol_target(&self) -> Option<&str> {
+        self.pattern.strip_prefix(SYMBOL_PREFIX)
+    }
+}
+
+/// Parsed CODEOWNERS-style rules.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CodeOwners {
+    rules: Vec<OwnershipRule>,
+}
+
+impl CodeOwners {
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let pattern = parts.next()?.to_string();
+                let owners = parts
+                    .take_while(|part| !part.starts_with('#'))
+                    .map(str::to_string)
+                    .collect();
+                Some(OwnershipRule { pattern, owners })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GraphError> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Load the first ownership file found under `root`, if any.
+    pub fn discover(root: impl AsRef<Path>) -> Result<Option<Self>, GraphError> {
+        CODEOWNERS_LOCATIONS
+            .iter()
+            .map(|location| root.as_ref().join(location))
+            .find(|path| path.is_file())
+            .map(Self::load)
+            .transpose()
+    }
+
+    pub fn rules(&self) -> &[OwnershipRule] {
+        &self.rules
+    }
+
+    /// Owners of a repository-relative path.
+    pub fn owners_for_path(&self, path: &str) -> &[String] {
+        let path = normalise(path);
+        self.rules
+            .iter()
+            .rev()
+            .filter(|rule| rule.symbol_target().is_none())
+            .find(|rule| path_matches(&rule.pattern, path))
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Owners of a symbol: a matching `symbol:` rule, otherwise the owners of its file.
+    pub fn owners_for_symbol(&self, node: &SymbolNode) -> &[String] {
+        self.symbol_rule(&node.stable_id, Some(&node.name))
+            .unwrap_or_else(|| self.owners_for_path(&node.file))
+    }
+
+    fn symbol_rule(&self, stable_id: &str, name: Option<&str>) -> Option<&[String]> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                rule.symbol_target()
+                    .is_some_and(|target| target == stable_id || Some(target) == name)
+            })
+            .map(|rule| rule.owners.as_slice())
+    }
+}
+
+/// Ownership rules resolved against a symbol graph.
+#[derive(Debug, Clone, Default)]
+pub struct Ownership {
+    codeowners: CodeOwners,
+    graph: SymbolGraph,
+    root: Option<PathBuf>,
+}
+
+impl Ownership {
+    pub fn new(codeowners: CodeOwners, graph: SymbolGraph) -> Self {
+        Self {
+            codeowners,
+            graph,
+            root: None,
+        }
+    }
+
+    /// Strip `root` from symbol file paths before matching them against path rules.
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    pub fn codeowners(&self) -> &CodeOwners {
+        &self.codeowners
+    }
+
+    /// Owners of the symbol with the given stable id; empty when it is unowned or unknown.
+    pub fn owner_of(&self, symbol_id: &str) -> &[String] {
+        match self.graph.find(symbol_id) {
+            Some(node) => self
+                .codeowners
+                .symbol_rule(&node.stable_id, Some(&node.name))
+                .unwrap_or_else(|| self.owners_for_path(&node.file)),
+            None => self.codeowners.symbol_rule(symbol_id, None).unwrap_or(&[]),
+        }
+    }
+
+    pub fn owners_for_path(&self, path: &str) -> &[String] {
+        let relative = self
+            .root
+            .as_ref()
+            .and_then(|root| Path::new(path).strip_prefix(root).ok())
+            .map(|relative| relative.to_string_lossy().to_string());
+        self.codeowners
+            .owners_for_path(relative.as_deref().unwrap_or(path))
+    }
+
+    /// Owners of everything touched, where each entry is a symbol id or a file path.
+    pub fn owners_for(&self, touched: &[String]) -> BTreeSet<String> {
+        touched
+            .iter()
+            .flat_map(|item| {
+                if self.graph.find(item).is_some() {
+                    self.owner_of(item)
+                } else {
+                    self.owners_for_path(item)
+                }
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+fn normalise(path: &str) -> &str {
+    let path = path.trim_start_matches("./");
+    path.trim_start_matches('/')
+}
+
+/// CODEOWNERS pattern semantics: `*` stays within a directory, `**` spans directories,
+/// patterns without an inner `/` match at any depth, and a pattern naming a directory
+/// covers everything beneath it.
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    let anchored = pattern.trim_end_matches('/').contains('/');
+    let directory_only = pattern.ends_with('/');
+    let pattern = pattern.trim_matches('/');
+    if pattern.is_empty() {
+        return false;
+    }
+    let covers_children = !pattern
+        .rsplit('/')
+        .next()
+        .is_some_and(|last| last.contains('*') || last.contains('?'));
+
+    let components: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+    let starts = if anchored { 0..1 } else { 0..components.len() };
+    starts.into_iter().any(|start| {
+        (start..components.len()).any(|end| {
+            let is_file = end + 1 == components.len();
+            if directory_only && is_file {
+                return false;
+            }
+            if !is_file && !covers_children {
+                return false;
+            }
+            glob_match(
+                pattern.as_bytes(),
+                components[start..=end].join("/").as_bytes(),
+            )
+        })
+    })
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => match rest.strip_prefix(b"*") {
+            Some(rest) => match rest.strip_prefix(b"/") {
+                Some(after) => (0..=text.len())
+                    .filter(|&index| index == 0 || text[index - 1] == b'/')
+                    .any(|index| glob_match(after, &text[index..])),
+                None => (0..=text.len()).any(|index| glob_match(rest, &text[index..])),
+            },
+            None => {
+                let limit = text
+                    .iter()
+                    .position(|byte| *byte == b'/')
+                    .unwrap_or(text.len());
+                (0..=limit).any(|index| glob_match(rest, &text[index..]))
+            }
+        },
+        Some((b'?', rest)) => text
+            .split_first()
+            .is_some_and(|(byte, remaining)| *byte != b'/' && glob_match(rest, remaining)),
+        Some((expected, rest)) => text
+            .split_first()
+            .is_some_and(|(byte, remaining)| byte == expected && glob_match(rest, remaining)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = "\
+# Default ownership
+*                     @noa-ark/maintainers
+server/ai/*           @noa-ark/ai-systems
+docs/                 @noa-ark/docs
+/crc/**/*.rs          @noa-ark/crc-core
+Makefile              @noa-ark/build-system # inline comment
+generated/
+symbol:compute        @noa-ark/numerics
+";
+
+    fn node(name: &str, file: &str) -> SymbolNode {
+        SymbolNode {
+            stable_id: format!("id-{name}"),
+            language: "rust".to_string(),
+            name: name.to_string(),
+            kind: "function".to_string(),
+            file: file.to_string(),
+            signature: String::new(),
+            span: (1, 1),
+        }
+    }
+
+    #[test]
+    fn path_and_symbol_rules_resolve_owners() {
+        let codeowners = CodeOwners::parse(RULES);
+        assert_eq!(codeowners.rules().len(), 7);
+        let owner = |path: &str| codeowners.owners_for_path(path).to_vec();
+
+        assert_eq!(owner("README.md"), vec!["@noa-ark/maintainers"]);
+        assert_eq!(owner("server/ai/router.rs"), vec!["@noa-ark/ai-systems"]);
+        assert_eq!(owner("server/ai/nested/x.rs"), vec!["@noa-ark/maintainers"]);
+        assert_eq!(owner("./docs/guides/intro.md"), vec!["@noa-ark/docs"]);
+        assert_eq!(owner("crc/src/deep/lib.rs"), vec!["@noa-ark/crc-core"]);
+        assert_eq!(owner("vendor/crc/src/lib.rs"), vec!["@noa-ark/maintainers"]);
+        assert_eq!(owner("tools/Makefile"), vec!["@noa-ark/build-system"]);
+        assert!(owner("generated/schema.rs").is_empty());
+
+        let mut graph = SymbolGraph::default();
+        for node in [
+            node("compute", "server/ai/math.rs"),
+            node("route", "/repo/server/ai/router.rs"),
+        ] {
+            graph.nodes.insert(node.stable_id.clone(), node);
+        }
+        let ownership = Ownership::new(codeowners, graph).with_root("/repo");
+        assert_eq!(ownership.owner_of("id-compute"), ["@noa-ark/numerics"]);
+        assert_eq!(ownership.owner_of("id-route"), ["@noa-ark/ai-systems"]);
+        assert!(ownership.owner_of("id-missing").is_empty());
+
+        let owners = ownership.owners_for(&["id-compute".to_string(), "docs/a.md".to_string()]);
+        assert_eq!(
+            owners.into_iter().collect::<Vec<_>>(),
+            vec!["@noa-ark/docs", "@noa-ark/numerics"]
+        );
+    }
+}
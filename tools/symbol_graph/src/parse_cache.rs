@@ -0,0 +1,193 @@
+//! Warm cache of parsed Tree-sitter trees.
+//!
+//! Trees are keyed by file path and the hash of the source they were parsed from.
+//! Unchanged files reuse their tree outright; files with a single small edit (the
+//! common case when the watcher fires after a save) are re-parsed incrementally
+//! from the previous tree, and anything else falls back to a full parse.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use noa_core::utils::simple_hash;
+use tree_sitter::{InputEdit, Language, Parser, Point, Tree};
+
+use crate::GraphError;
+
+/// Largest edit, as a fraction of the file, that is still re-parsed incrementally.
+pub const INCREMENTAL_EDIT_RATIO: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseCacheStats {
+    pub hits: u64,
+    pub incremental: u64,
+    pub full: u64,
+}
+
+struct CachedTree {
+    hash: String,
+    source: String,
+    tree: Tree,
+}
+
+pub struct ParseCache {
+    entries: HashMap<PathBuf, CachedTree>,
+    parser: Parser,
+    stats: ParseCacheStats,
+}
+
+impl Default for ParseCache {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            parser: Parser::new(),
+            stats: ParseCacheStats::default(),
+        }
+    }
+}
+
+impl ParseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> ParseCacheStats {
+        self.stats
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forget a file, e.g. after it was deleted.
+    pub fn invalidate(&mut self, path: &Path) {
+        self.entries.remove(path);
+    }
+
+    /// Parse `source`, reusing or incrementally updating the cached tree for `path`.
+    pub fn parse(
+        &mut self,
+        path: &Path,
+        language: Language,
+        source: &str,
+    ) -> Result<Tree, GraphError> {
+        let hash = simple_hash(source);
+        if let Some(cached) = self.entries.get(path).filter(|cached| cached.hash == hash) {
+            self.stats.hits += 1;
+            return Ok(cached.tree.clone());
+        }
+        let previous = self.entries.remove(path);
+
+        self.parser
+            .set_language(language)
+            .map_err(|err| GraphError::Parser(err.to_string()))?;
+        let old_tree = previous.and_then(|cached| {
+            let edit = single_edit(&cached.source, source)?;
+            let mut tree = cached.tree;
+            tree.edit(&edit);
+            Some(tree)
+        });
+        if old_tree.is_some() {
+            self.stats.incremental += 1;
+        } else {
+            self.stats.full += 1;
+        }
+        let tree = self
+            .parser
+            .parse(source, old_tree.as_ref())
+            .ok_or_else(|| GraphError::Parser("failed to parse source".into()))?;
+
+        self.entries.insert(
+            path.to_path_buf(),
+            CachedTree {
+                hash,
+                source: source.to_string(),
+                tree: tree.clone(),
+            },
+        );
+        Ok(tree)
+    }
+}
+
+/// Describe the change from `old` to `new` as one contiguous edit, if it is small enough.
+fn single_edit(old: &str, new: &str) -> Option<InputEdit> {
+    let (old_bytes, new_bytes) = (old.as_bytes(), new.as_bytes());
+    let prefix = old_bytes
+        .iter()
+        .zip(new_bytes)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = old_bytes.len().min(new_bytes.len()) - prefix;
+    let suffix = old_bytes
+        .iter()
+        .rev()
+        .zip(new_bytes.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_end = old_bytes.len() - suffix;
+    let new_end = new_bytes.len() - suffix;
+    let changed = (old_end - prefix).max(new_end - prefix);
+    let limit = (old_bytes.len().max(new_bytes.len()) as f64 * INCREMENTAL_EDIT_RATIO) as usize;
+    if changed > limit {
+        return None;
+    }
+
+    Some(InputEdit {
+        start_byte: prefix,
+        old_end_byte: old_end,
+        new_end_byte: new_end,
+        start_position: point_at(old_bytes, prefix),
+        old_end_position: point_at(old_bytes, old_end),
+        new_end_position: point_at(new_bytes, new_end),
+    })
+}
+
+fn point_at(source: &[u8], offset: usize) -> Point {
+    let before = &source[..offset];
+    let row = before.iter().filter(|byte| **byte == b'\n').count();
+    let column = match before.iter().rposition(|byte| *byte == b'\n') {
+        Some(newline) => offset - newline - 1,
+        None => offset,
+    };
+    Point { row, column }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_reuses_and_incrementally_reparses_trees() {
+        let path = Path::new("src/lib.rs");
+        let language = tree_sitter_rust::language();
+        let original: String = (0..20)
+            .map(|index| format!("pub fn item_{index}(value: i32) -> i32 {{ value + {index} }}\n"))
+            .collect();
+        let mut cache = ParseCache::new();
+
+        cache.parse(path, language, &original).unwrap();
+        cache.parse(path, language, &original).unwrap();
+        let edited = original.replace("value + 7", "value * 7");
+        let tree = cache.parse(path, language, &edited).unwrap();
+        assert_eq!(
+            cache.stats(),
+            ParseCacheStats {
+                hits: 1,
+                incremental: 1,
+                full: 1,
+            }
+        );
+        assert!(!tree.root_node().has_error());
+        assert_eq!(tree.root_node().named_child_count(), 20);
+
+        cache.parse(path, language, "pub struct Replaced;").unwrap();
+        assert_eq!(cache.stats().full, 2);
+        cache.invalidate(path);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,312 @@
+//! Near-duplicate function detection.
+//!
+//! Function bodies are tokenised and hashed into overlapping token shingles. Two
+//! functions are compared by the Jaccard similarity of their shingle sets, so renamed
+//! locals or reordered statements still score highly while unrelated code scores near
+//! zero. [`SimilarityIndex::find_duplicates`] pairs each candidate function (e.g. from
+//! an incoming drop) with its closest match in a reference index (e.g. the workspace).
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::fs;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{SymbolGraph, SymbolNode};
+
+/// Minimum similarity for a pair to be reported.
+pub const DEFAULT_DUPLICATE_THRESHOLD: f32 = 0.8;
+/// Tokens per shingle.
+const SHINGLE_SIZE: usize = 5;
+/// Functions with fewer shingles than this are too small to compare meaningfully.
+const MIN_SHINGLES: usize = 4;
+
+/// Shingle fingerprint of a single function.
+#[derive(Debug, Clone)]
+pub struct FunctionFingerprint {
+    pub stable_id: String,
+    pub name: String,
+    pub file: String,
+    pub span: (usize, usize),
+    shingles: BTreeSet<u64>,
+}
+
+impl FunctionFingerprint {
+    pub fn from_source(node: &SymbolNode, body: &str) -> Self {
+        Self {
+            stable_id: node.stable_id.clone(),
+            name: node.name.clone(),
+            file: node.file.clone(),
+            span: node.span,
+            shingles: shingles(body),
+        }
+    }
+
+    pub fn similarity(&self, other: &Self) -> f32 {
+        let shared = self.shingles.intersection(&other.shingles).count();
+        let total = self.shingles.len() + other.shingles.len() - shared;
+        if total == 0 {
+            return 0.0;
+        }
+        shared as f32 / total as f32
+    }
+}
+
+/// Existing code a duplicate should be replaced with.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CanonicalTarget {
+    pub stable_id: String,
+    pub name: String,
+    pub file: String,
+    pub span: (usize, usize),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DuplicateFinding {
+    pub name: String,
+    pub file: String,
+    pub span: (usize, usize),
+    pub similarity: f32,
+    /// The canonical function to reuse instead.
+    pub canonical: CanonicalTarget,
+}
+
+impl DuplicateFinding {
+    pub fn is_exact(&self) -> bool {
+        self.similarity >= 1.0 - f32::EPSILON
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimilarityIndex {
+    functions: Vec<FunctionFingerprint>,
+}
+
+impl SimilarityIndex {
+    /// Fingerprint every function in `graph`, reading bodies from the recorded files.
+    /// Functions whose files cannot be read or that are too small are skipped.
+    pub fn from_graph(graph: &SymbolGraph) -> Self {
+        let mut functions = Vec::new();
+        let mut current: Option<(String, Vec<String>)> = None;
+        let mut nodes: Vec<&SymbolNode> = graph
+            .nodes
+            .values()
+            .filter(|node| node.kind == "function")
+            .collect();
+        nodes.sort_by(|a, b| a.file.cmp(&b.file));
+        for node in nodes {
+            if current.as_ref().map(|(file, _)| file) != Some(&node.file) {
+                let lines = fs::read_to_string(&node.file)
+                    .map(|source| source.lines().map(str::to_string).collect())
+                    .unwrap_or_default();
+                current = Some((node.file.clone(), lines));
+            }
+            let Some((_, lines)) = &current else {
+                continue;
+            };
+            let start = node.span.0.saturating_sub(1);
+            let end = node.span.1.min(lines.len());
+            if start >= end {
+                continue;
+            }
+            let fingerprint = FunctionFingerprint::from_source(node, &lines[start..end].join("\n"));
+            if fingerprint.shingles.len() >= MIN_SHINGLES {
+                functions.push(fingerprint);
+            }
+        }
+        Self { functions }
+    }
+
+    pub fn len(&self) -> usize {
+        self.functions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    /// Best match in `self` for each function in `candidates` scoring at least
+    /// `threshold`, most similar first.
+    pub fn find_duplicates(
+        &self,
+        candidates: &SimilarityIndex,
+        threshold: f32,
+    ) -> Vec<DuplicateFinding> {
+        let mut findings: Vec<DuplicateFinding> = candidates
+            .functions
+            .iter()
+            .filter_map(|candidate| {
+                self.functions
+                    .iter()
+                    .map(|existing| (existing, candidate.similarity(existing)))
+                    .filter(|(_, similarity)| *similarity >= threshold)
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(existing, similarity)| DuplicateFinding {
+                        name: candidate.name.clone(),
+                        file: candidate.file.clone(),
+                        span: candidate.span,
+                        similarity,
+                        canonical: CanonicalTarget {
+                            stable_id: existing.stable_id.clone(),
+                            name: existing.name.clone(),
+                            file: existing.file.clone(),
+                            span: existing.span,
+                        },
+                    })
+            })
+            .collect();
+        findings.sort_by(|a, b| {
+            b.similarity
+                .total_cmp(&a.similarity)
+                .then_with(|| a.file.cmp(&b.file))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        findings
+    }
+}
+
+/// Hashes of every run of [`SHINGLE_SIZE`] consecutive tokens.
+fn shingles(source: &str) -> BTreeSet<u64> {
+    let tokens = tokenize(source);
+    tokens
+        .windows(SHINGLE_SIZE.min(tokens.len().max(1)))
+        .map(|window| {
+            let mut hasher = DefaultHasher::new();
+            window.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// Split source into tokens, dropping comments and visibility. Local and defined names
+/// are normalised so renaming does not hide a copy, while called functions, methods,
+/// and fields keep their names.
+fn tokenize(source: &str) -> Vec<String> {
+    let mut raw: Vec<(String, bool)> = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch.is_whitespace() {
+            continue;
+        }
+        if ch == '/' && chars.peek() == Some(&'/') {
+            for next in chars.by_ref() {
+                if next == '\n' {
+                    break;
+                }
+            }
+            continue;
+        }
+        if ch.is_alphanumeric() || ch == '_' {
+            let mut word = String::from(ch);
+            while let Some(&next) = chars.peek() {
+                if !(next.is_alphanumeric() || next == '_') {
+                    break;
+                }
+                word.push(next);
+                chars.next();
+            }
+            if word == "pub" || word == "export" {
+                continue;
+            }
+            if ch.is_ascii_digit() {
+                raw.push(("0".to_string(), false));
+            } else {
+                let is_name = !is_keyword(&word);
+                raw.push((word, is_name));
+            }
+        } else {
+            raw.push((ch.to_string(), false));
+        }
+    }
+
+    (0..raw.len())
+        .map(|index| {
+            let (token, is_name) = &raw[index];
+            let called =
+                matches!(raw.get(index + 1), Some((next, _)) if next == "(" || next == "!");
+            let previous = index
+                .checked_sub(1)
+                .map(|previous| raw[previous].0.as_str());
+            let member = previous == Some(".");
+            let defined = matches!(previous, Some("fn" | "function"));
+            if *is_name && (defined || (!called && !member)) {
+                "ident".to_string()
+            } else {
+                token.clone()
+            }
+        })
+        .collect()
+}
+
+fn is_keyword(word: &str) -> bool {
+    matches!(
+        word,
+        "as" | "async"
+            | "await"
+            | "break"
+            | "const"
+            | "continue"
+            | "else"
+            | "enum"
+            | "fn"
+            | "for"
+            | "function"
+            | "if"
+            | "impl"
+            | "in"
+            | "let"
+            | "loop"
+            | "match"
+            | "mut"
+            | "new"
+            | "return"
+            | "self"
+            | "Self"
+            | "struct"
+            | "while"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SymbolGraphBuilder;
+    use tempfile::tempdir;
+
+    fn index(files: &[(&str, &str)]) -> (tempfile::TempDir, SimilarityIndex) {
+        let dir = tempdir().unwrap();
+        for (name, source) in files {
+            fs::write(dir.path().join(name), source).unwrap();
+        }
+        let store = tempdir().unwrap();
+        let graph = SymbolGraphBuilder::new(dir.path())
+            .with_store_root(store.path())
+            .index()
+            .unwrap();
+        (dir, SimilarityIndex::from_graph(&graph))
+    }
+
+    #[test]
+    fn renamed_copies_are_flagged_with_their_canonical_target() {
+        let (_workspace, workspace) = index(&[(
+            "math.rs",
+            "pub fn checksum(values: &[u8]) -> u32 {\n    let mut total = 0u32;\n    \
+             for value in values {\n        total = total.wrapping_mul(31).wrapping_add(*value as u32);\n    \
+             }\n    total\n}\n\npub fn greet(name: &str) -> String {\n    format!(\"hi {name}\")\n}\n",
+        )]);
+        let (_drop, incoming) = index(&[(
+            "copied.rs",
+            "fn digest(bytes: &[u8]) -> u32 {\n    let mut acc = 0u32;\n    \
+             for byte in bytes {\n        acc = acc.wrapping_mul(31).wrapping_add(*byte as u32);\n    \
+             }\n    acc\n}\n\nfn unrelated(flag: bool) -> Option<i64> {\n    if flag { Some(1) } else { None }\n}\n",
+        )]);
+
+        let findings = workspace.find_duplicates(&incoming, DEFAULT_DUPLICATE_THRESHOLD);
+        assert_eq!(findings.len(), 1, "{findings:?}");
+        assert_eq!(findings[0].name, "digest");
+        assert_eq!(findings[0].canonical.name, "checksum");
+        assert!(findings[0].canonical.file.ends_with("math.rs"));
+        assert!(findings[0].is_exact());
+    }
+}
@@ -0,0 +1,203 @@
+//! Accessibility metadata and checks for shell components.
+//!
+//! Components describe themselves to assistive technology as a flat list of
+//! [`AccessibleNode`]s: a semantic role, a label read by screen readers, and a
+//! focus order for keyboard, switch, and gaze navigation. [`audit`] checks that
+//! list against the [`AccessibilityPolicy`] of the target [`Platform`]; platform
+//! adapters refuse to mount chrome that fails it. User preferences select the
+//! reduced-motion and high-contrast [`VisualVariant`] renderers apply.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Platform;
+
+/// Semantic role exposed to screen readers.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum AccessibilityRole {
+    Navigation,
+    Link,
+    TabList,
+    Tab,
+    Region,
+    Button,
+    Status,
+}
+
+impl AccessibilityRole {
+    /// Whether the role takes focus and responds to input.
+    pub fn is_interactive(self) -> bool {
+        matches!(self, Self::Link | Self::Tab | Self::Button)
+    }
+}
+
+/// Accessibility description of one rendered element.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AccessibleNode {
+    pub id: String,
+    pub role: AccessibilityRole,
+    pub label: String,
+    /// Position in the focus sequence; `None` for elements that are not focusable.
+    pub focus_order: Option<u32>,
+    pub selected: bool,
+}
+
+impl AccessibleNode {
+    pub fn new(id: impl Into<String>, role: AccessibilityRole, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            role,
+            label: label.into(),
+            focus_order: None,
+            selected: false,
+        }
+    }
+
+    pub fn with_focus_order(mut self, focus_order: u32) -> Self {
+        self.focus_order = Some(focus_order);
+        self
+    }
+
+    pub fn with_selected(mut self, selected: bool) -> Self {
+        self.selected = selected;
+        self
+    }
+}
+
+/// Implemented by components that render to assistive technology.
+pub trait Accessible {
+    /// Nodes in reading order. Focus orders start after `focus_offset`.
+    fn accessibility_nodes(&self, focus_offset: u32) -> Vec<AccessibleNode>;
+}
+
+/// Per-user accessibility preferences.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct AccessibilityPreferences {
+    pub reduced_motion: bool,
+    pub high_contrast: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum MotionVariant {
+    #[default]
+    Standard,
+    Reduced,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ContrastVariant {
+    #[default]
+    Standard,
+    High,
+}
+
+/// Visual variant a renderer applies to the chrome.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct VisualVariant {
+    pub motion: MotionVariant,
+    pub contrast: ContrastVariant,
+}
+
+impl From<AccessibilityPreferences> for VisualVariant {
+    fn from(preferences: AccessibilityPreferences) -> Self {
+        Self {
+            motion: if preferences.reduced_motion {
+                MotionVariant::Reduced
+            } else {
+                MotionVariant::Standard
+            },
+            contrast: if preferences.high_contrast {
+                ContrastVariant::High
+            } else {
+                ContrastVariant::Standard
+            },
+        }
+    }
+}
+
+/// Requirements a platform places on the accessibility tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessibilityPolicy {
+    /// Every node needs a label for screen readers.
+    pub require_labels: bool,
+    /// Interactive nodes need a focus order for keyboard, switch, or gaze input.
+    pub require_focus_order: bool,
+}
+
+impl AccessibilityPolicy {
+    pub fn for_platform(platform: &Platform) -> Self {
+        match platform {
+            // Server surfaces are consumed by API clients, not people.
+            Platform::Server => Self {
+                require_labels: false,
+                require_focus_order: false,
+            },
+            Platform::Mobile
+            | Platform::Desktop
+            | Platform::Web
+            | Platform::ARGlasses
+            | Platform::XRHeadset => Self {
+                require_labels: true,
+                require_focus_order: true,
+            },
+        }
+    }
+}
+
+/// A problem found by [`audit`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AccessibilityIssue {
+    pub node_id: String,
+    pub problem: String,
+}
+
+/// Check nodes against a policy. Duplicate ids and focus orders are always issues.
+pub fn audit(nodes: &[AccessibleNode], policy: AccessibilityPolicy) -> Vec<AccessibilityIssue> {
+    let mut issues = vec![];
+    let mut ids = HashMap::new();
+    let mut focus = HashMap::new();
+    for node in nodes {
+        let mut issue = |problem: String| {
+            issues.push(AccessibilityIssue {
+                node_id: node.id.clone(),
+                problem,
+            })
+        };
+        if let Some(first) = ids.insert(node.id.as_str(), node) {
+            issue(format!("duplicate id also used by a {:?}", first.role));
+        }
+        if policy.require_labels && node.label.trim().is_empty() {
+            issue(format!("{:?} has no accessible label", node.role));
+        }
+        match node.focus_order {
+            Some(order) => {
+                if let Some(other) = focus.insert(order, node.id.as_str()) {
+                    issue(format!("focus order {} is also used by {}", order, other));
+                }
+            }
+            None if policy.require_focus_order && node.role.is_interactive() => {
+                issue(format!("interactive {:?} is not focusable", node.role));
+            }
+            None => {}
+        }
+    }
+    issues
+}
+
+/// Audit nodes for a platform and describe every issue in one error.
+pub fn enforce(platform: &Platform, nodes: &[AccessibleNode]) -> Result<(), String> {
+    let issues = audit(nodes, AccessibilityPolicy::for_platform(platform));
+    if issues.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "accessibility check failed for {:?}: {}",
+        platform,
+        issues
+            .iter()
+            .map(|issue| format!("{}: {}", issue.node_id, issue.problem))
+            .collect::<Vec<_>>()
+            .join("; ")
+    ))
+}
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 
 use serde_json::Value;
 
+use crate::accessibility::{self, Accessible};
 use crate::components::ShellChrome;
 use crate::renderer::renderer::Renderer;
 use crate::UIState;
@@ -24,9 +25,10 @@ impl PlatformAdapter for ServerAdapter {
     fn mount(
         &self,
         renderer: &Renderer,
-        _chrome: &ShellChrome,
+        chrome: &ShellChrome,
         _state: &UIState,
     ) -> Result<(), String> {
+        ensure_accessible(renderer, chrome)?;
         renderer.render("server-shell")
     }
 }
@@ -41,6 +43,7 @@ impl PlatformAdapter for ReactAdapter {
         chrome: &ShellChrome,
         _state: &UIState,
     ) -> Result<(), String> {
+        ensure_accessible(renderer, chrome)?;
         let root = workspace_root()?.join("ui/noa-dashboard/index.html");
         let html = fs::read_to_string(&root)
             .map_err(|err| format!("failed to read {}: {}", root.display(), err))?;
@@ -87,6 +90,7 @@ impl PlatformAdapter for TauriAdapter {
         chrome: &ShellChrome,
         _state: &UIState,
     ) -> Result<(), String> {
+        ensure_accessible(renderer, chrome)?;
         let manifest = fs::read_to_string(&self.manifest)
             .map_err(|err| format!("failed to read {}: {}", self.manifest.display(), err))?;
         let json: Value = serde_json::from_str(&manifest).map_err(|err| {
@@ -147,6 +151,7 @@ impl PlatformAdapter for ReactNativeAdapter {
         chrome: &ShellChrome,
         state: &UIState,
     ) -> Result<(), String> {
+        ensure_accessible(renderer, chrome)?;
         let manifest = fs::read_to_string(&self.app_manifest)
             .map_err(|err| format!("failed to read {}: {}", self.app_manifest.display(), err))?;
         let json: Value = serde_json::from_str(&manifest).map_err(|err| {
@@ -201,8 +206,9 @@ impl PlatformAdapter for SpatialAdapter {
         &self,
         renderer: &Renderer,
         chrome: &ShellChrome,
-        _state: &UIState,
+        state: &UIState,
     ) -> Result<(), String> {
+        ensure_accessible(renderer, chrome)?;
         let manifest = fs::read_to_string(&self.scene_manifest)
             .map_err(|err| format!("failed to read {}: {}", self.scene_manifest.display(), err))?;
         let json: Value = serde_json::from_str(&manifest).map_err(|err| {
@@ -223,15 +229,31 @@ impl PlatformAdapter for SpatialAdapter {
             .map(|edges| edges.len())
             .unwrap_or(0);
 
+        let host = match state.context.host.capabilities() {
+            Some(host) => format!(
+                " host={} latency={} voice={}",
+                host.host,
+                host.latency.as_str(),
+                if host.voice_ready() { "ready" } else { "off" }
+            ),
+            None => String::new(),
+        };
+
         renderer.render(&format!(
-            "spatial-shell:nodes={} edges={} workspaces={}",
+            "spatial-shell:nodes={} edges={} workspaces={}{}",
             nodes,
             edges,
-            chrome.workspace_switcher.workspaces.len()
+            chrome.workspace_switcher.workspaces.len(),
+            host
         ))
     }
 }
 
+/// Refuse to mount chrome that fails the platform's accessibility policy.
+fn ensure_accessible(renderer: &Renderer, chrome: &ShellChrome) -> Result<(), String> {
+    accessibility::enforce(renderer.platform(), &chrome.accessibility_nodes(0))
+}
+
 fn workspace_root() -> Result<PathBuf, String> {
     let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     manifest_dir
@@ -275,24 +297,28 @@ mod tests {
     }
 
     fn renderer_fixture() -> Renderer {
-        let context = UIContext {
-            platform: Platform::Web,
-            screen_width: 1920,
-            screen_height: 1080,
-            dpi: 1.0,
-            capabilities: vec![Capability::Mouse],
-        };
+        let context = UIContext::new(Platform::Web, 1920, 1080, 1.0, vec![Capability::Mouse]);
         Renderer::new(context)
     }
 
     fn state_fixture() -> UIState {
-        UIState::new(UIContext {
-            platform: Platform::Web,
-            screen_width: 1920,
-            screen_height: 1080,
-            dpi: 1.0,
-            capabilities: vec![Capability::Mouse],
-        })
+        UIState::new(UIContext::new(
+            Platform::Web,
+            1920,
+            1080,
+            1.0,
+            vec![Capability::Mouse],
+        ))
+    }
+
+    #[test]
+    fn adapters_reject_inaccessible_chrome() {
+        let mut chrome = chrome_fixture();
+        chrome.workspace_switcher.workspaces[0].label.clear();
+        let err = ReactAdapter
+            .mount(&renderer_fixture(), &chrome, &state_fixture())
+            .unwrap_err();
+        assert!(err.contains("workspaces.ai-studio: Tab has no accessible label"));
     }
 
     #[test]
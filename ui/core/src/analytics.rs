@@ -1,9 +1,14 @@
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
+use noa_core::utils::current_timestamp_millis;
+use noa_workflow::{PurgeCounts, RetentionError, RetentionPolicy, RetentionStore};
 use serde::{Deserialize, Serialize};
 
 use crate::state::GlobalStore;
 
+const ANALYTICS_STORE: &str = "ui_analytics";
+
 /// Represents KPI metrics aggregated across modules.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Metric {
@@ -49,13 +54,50 @@ pub struct TelemetryInsights {
 pub struct AnalyticsEngine {
     pub metrics: HashMap<String, Metric>,
     pub insights: TelemetryInsights,
+    ingested_at: HashMap<String, u128>,
 }
 
 impl AnalyticsEngine {
     pub fn ingest(&mut self, metric: Metric) {
+        self.ingested_at
+            .insert(metric.id.clone(), current_timestamp_millis());
         self.metrics.insert(metric.id.clone(), metric);
     }
 
+    /// Drop metrics not re-ingested within the policy's age limit, then the
+    /// least recently ingested beyond its record limit. Returns how many.
+    pub fn prune(&mut self, policy: &RetentionPolicy, now_ms: u128) -> usize {
+        let mut ages: Vec<(u128, String)> = self
+            .metrics
+            .keys()
+            .map(|id| (self.ingested_at.get(id).copied().unwrap_or(0), id.clone()))
+            .collect();
+        ages.sort();
+        let mut expired = Vec::new();
+        if let Some(max_age_secs) = policy.max_age_secs {
+            let cutoff = now_ms.saturating_sub(u128::from(max_age_secs) * 1000);
+            let stale = ages.iter().take_while(|(at, _)| *at < cutoff).count();
+            expired.extend(ages.drain(..stale).map(|(_, id)| id));
+        }
+        if let Some(max_records) = policy.max_records {
+            let overflow = ages.len().saturating_sub(max_records);
+            expired.extend(ages.drain(..overflow).map(|(_, id)| id));
+        }
+        for id in &expired {
+            self.metrics.remove(id);
+            self.ingested_at.remove(id);
+        }
+        expired.len()
+    }
+
+    /// Drop efficiency measurements for `agent_id`, returning how many.
+    pub fn purge_agent(&mut self, agent_id: &str) -> usize {
+        let efficiency = &mut self.insights.agent_efficiency;
+        let before = efficiency.len();
+        efficiency.retain(|entry| entry.agent_id != agent_id);
+        before - efficiency.len()
+    }
+
     pub fn compute_roi(&self) -> Option<f64> {
         let productivity = self.metrics.get("developer_productivity")?.value;
         let infrastructure = self.metrics.get("infrastructure_cost")?.value;
@@ -85,6 +127,55 @@ impl AnalyticsEngine {
     }
 }
 
+/// [`RetentionStore`] over a shell's analytics that republishes to the global
+/// store after every change.
+#[derive(Clone)]
+pub struct AnalyticsRetention {
+    engine: Arc<Mutex<AnalyticsEngine>>,
+    store: GlobalStore,
+}
+
+impl AnalyticsRetention {
+    pub fn new(engine: Arc<Mutex<AnalyticsEngine>>, store: GlobalStore) -> Self {
+        Self { engine, store }
+    }
+}
+
+impl RetentionStore for AnalyticsRetention {
+    fn store_name(&self) -> &str {
+        ANALYTICS_STORE
+    }
+
+    fn apply_retention(
+        &self,
+        policy: &RetentionPolicy,
+        now_ms: u128,
+    ) -> Result<usize, RetentionError> {
+        let mut engine = self.engine.lock().unwrap();
+        let removed = engine.prune(policy, now_ms);
+        if removed > 0 {
+            engine.sync_to_state(&self.store);
+        }
+        Ok(removed)
+    }
+
+    fn purge_subject(
+        &self,
+        subject_id: &str,
+        _pseudonym: &str,
+    ) -> Result<PurgeCounts, RetentionError> {
+        let mut engine = self.engine.lock().unwrap();
+        let removed = engine.purge_agent(subject_id);
+        if removed > 0 {
+            engine.sync_to_state(&self.store);
+        }
+        Ok(PurgeCounts {
+            removed,
+            anonymized: 0,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,4 +231,49 @@ mod tests {
         let snapshot = store.read();
         assert!(snapshot.data.contains_key("analytics.insights"));
     }
+
+    #[test]
+    fn retention_prunes_stale_metrics_and_purges_agents() {
+        let mut engine = AnalyticsEngine::default();
+        for id in [
+            "developer_productivity",
+            "infrastructure_cost",
+            "deploy_rate",
+        ] {
+            engine.ingest(Metric {
+                id: id.into(),
+                label: id.into(),
+                value: 1.0,
+                unit: "count".into(),
+            });
+        }
+        engine.layer_insights(TelemetryInsights {
+            agent_efficiency: vec![AgentEfficiency {
+                agent_id: "deploy-coordinator".into(),
+                utilization: 0.91,
+                impact_score: 8.7,
+            }],
+            ..TelemetryInsights::default()
+        });
+        let engine = Arc::new(Mutex::new(engine));
+        let store = GlobalStore::new(GlobalState::default());
+        let retention = AnalyticsRetention::new(Arc::clone(&engine), store.clone());
+
+        let now = current_timestamp_millis();
+        let capped = RetentionPolicy::default().with_max_records(2);
+        assert_eq!(retention.apply_retention(&capped, now).unwrap(), 1);
+        let aged = RetentionPolicy::default().with_max_age_secs(60);
+        assert_eq!(retention.apply_retention(&aged, now).unwrap(), 0);
+        assert_eq!(retention.apply_retention(&aged, now + 120_000).unwrap(), 2);
+
+        let purged = retention
+            .purge_subject("deploy-coordinator", "anon:x")
+            .unwrap();
+        assert_eq!(purged.removed, 1);
+        assert!(engine.lock().unwrap().insights.agent_efficiency.is_empty());
+        assert_eq!(
+            store.read().data["analytics.insights"]["agent_efficiency"],
+            serde_json::json!([])
+        );
+    }
 }
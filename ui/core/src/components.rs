@@ -1,6 +1,8 @@
+use crate::accessibility::{AccessibilityRole, Accessible, AccessibleNode, VisualVariant};
 use crate::state::{
     KnowledgeArticle, NavigationItem, NavigationState, Workspace, WorkspacePersona,
 };
+use crate::timetravel::SessionTrace;
 
 /// Declarative navigation rail component representation.
 #[derive(Debug, Clone)]
@@ -22,6 +24,31 @@ impl NavigationRail {
     }
 }
 
+impl Accessible for NavigationRail {
+    fn accessibility_nodes(&self, focus_offset: u32) -> Vec<AccessibleNode> {
+        let mut nodes = vec![AccessibleNode::new(
+            "navigation",
+            AccessibilityRole::Navigation,
+            "Primary navigation",
+        )];
+        nodes.extend(
+            self.items
+                .iter()
+                .zip(focus_offset + 1..)
+                .map(|(item, order)| {
+                    AccessibleNode::new(
+                        format!("navigation.{}", item.id),
+                        AccessibilityRole::Link,
+                        item.label.clone(),
+                    )
+                    .with_focus_order(order)
+                    .with_selected(self.active_route.as_deref() == Some(item.route.as_str()))
+                }),
+        );
+        nodes
+    }
+}
+
 /// Workspace switcher component used across platforms.
 #[derive(Debug, Clone)]
 pub struct WorkspaceSwitcher {
@@ -35,6 +62,31 @@ impl WorkspaceSwitcher {
     }
 }
 
+impl Accessible for WorkspaceSwitcher {
+    fn accessibility_nodes(&self, focus_offset: u32) -> Vec<AccessibleNode> {
+        let mut nodes = vec![AccessibleNode::new(
+            "workspaces",
+            AccessibilityRole::TabList,
+            "Workspaces",
+        )];
+        nodes.extend(
+            self.workspaces
+                .iter()
+                .zip(focus_offset + 1..)
+                .map(|(workspace, order)| {
+                    AccessibleNode::new(
+                        format!("workspaces.{}", workspace.id),
+                        AccessibilityRole::Tab,
+                        workspace.label.clone(),
+                    )
+                    .with_focus_order(order)
+                    .with_selected(self.active.as_deref() == Some(workspace.id.as_str()))
+                }),
+        );
+        nodes
+    }
+}
+
 /// Knowledge base overlay tailored to the active persona.
 #[derive(Debug, Clone)]
 pub struct KnowledgeOverlay {
@@ -48,12 +100,116 @@ impl KnowledgeOverlay {
     }
 }
 
+impl Accessible for KnowledgeOverlay {
+    fn accessibility_nodes(&self, focus_offset: u32) -> Vec<AccessibleNode> {
+        let mut nodes = vec![AccessibleNode::new(
+            "knowledge",
+            AccessibilityRole::Region,
+            format!("{:?} knowledge base", self.persona),
+        )];
+        nodes.extend(
+            self.articles
+                .iter()
+                .zip(focus_offset + 1..)
+                .map(|(article, order)| {
+                    AccessibleNode::new(
+                        format!("knowledge.{}", article.id),
+                        AccessibilityRole::Link,
+                        article.title.clone(),
+                    )
+                    .with_focus_order(order)
+                }),
+        );
+        nodes
+    }
+}
+
+/// One row of the state timeline viewer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineRow {
+    pub sequence: u64,
+    pub source: String,
+    pub timestamp_ms: u64,
+    pub changed: Vec<String>,
+}
+
+/// Dev-mode viewer listing recorded state mutations, with step controls.
+#[derive(Debug, Clone)]
+pub struct StateTimelineViewer {
+    pub rows: Vec<TimelineRow>,
+    pub cursor: Option<u64>,
+}
+
+impl StateTimelineViewer {
+    pub fn from_trace(trace: &SessionTrace) -> Self {
+        Self {
+            rows: trace
+                .mutations
+                .iter()
+                .map(|mutation| TimelineRow {
+                    sequence: mutation.sequence,
+                    source: mutation.source.clone(),
+                    timestamp_ms: mutation.timestamp_ms,
+                    changed: mutation.changed.clone(),
+                })
+                .collect(),
+            cursor: trace.cursor,
+        }
+    }
+
+    /// Rows whose mutation came from `source`.
+    pub fn filter_source(&self, source: &str) -> Vec<&TimelineRow> {
+        self.rows
+            .iter()
+            .filter(|row| row.source == source)
+            .collect()
+    }
+}
+
+impl Accessible for StateTimelineViewer {
+    /// Step back, step forward, then one entry per mutation.
+    fn accessibility_nodes(&self, focus_offset: u32) -> Vec<AccessibleNode> {
+        let mut nodes = vec![
+            AccessibleNode::new("timeline", AccessibilityRole::Region, "State timeline"),
+            AccessibleNode::new("timeline.step-back", AccessibilityRole::Button, "Step back")
+                .with_focus_order(focus_offset + 1),
+            AccessibleNode::new(
+                "timeline.step-forward",
+                AccessibilityRole::Button,
+                "Step forward",
+            )
+            .with_focus_order(focus_offset + 2),
+        ];
+        nodes.extend(
+            self.rows
+                .iter()
+                .zip(focus_offset + 3..)
+                .map(|(row, order)| {
+                    let changed = if row.changed.is_empty() {
+                        "no changes".to_string()
+                    } else {
+                        row.changed.join(", ")
+                    };
+                    AccessibleNode::new(
+                        format!("timeline.{}", row.sequence),
+                        AccessibilityRole::Button,
+                        format!("Step {} by {}: {}", row.sequence, row.source, changed),
+                    )
+                    .with_focus_order(order)
+                    .with_selected(self.cursor == Some(row.sequence))
+                }),
+        );
+        nodes
+    }
+}
+
 /// Composite shell chrome returned to platform renderers.
 #[derive(Debug, Clone)]
 pub struct ShellChrome {
     pub navigation: NavigationRail,
     pub workspace_switcher: WorkspaceSwitcher,
     pub knowledge: KnowledgeOverlay,
+    pub variant: VisualVariant,
 }
 
 impl ShellChrome {
@@ -66,14 +222,34 @@ impl ShellChrome {
             navigation,
             workspace_switcher,
             knowledge,
+            variant: VisualVariant::default(),
         }
     }
+
+    pub fn with_variant(mut self, variant: VisualVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+}
+
+impl Accessible for ShellChrome {
+    /// Navigation, then workspaces, then knowledge articles, in one focus sequence.
+    fn accessibility_nodes(&self, focus_offset: u32) -> Vec<AccessibleNode> {
+        let mut nodes = self.navigation.accessibility_nodes(focus_offset);
+        let offset = focus_offset + self.navigation.items.len() as u32;
+        nodes.extend(self.workspace_switcher.accessibility_nodes(offset));
+        let offset = offset + self.workspace_switcher.workspaces.len() as u32;
+        nodes.extend(self.knowledge.accessibility_nodes(offset));
+        nodes
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::accessibility::{audit, enforce, AccessibilityPolicy};
     use crate::state::{Workspace, WorkspacePersona};
+    use crate::Platform;
 
     #[test]
     fn shell_chrome_wires_navigation_and_knowledge() {
@@ -92,4 +268,73 @@ mod tests {
         let chrome = ShellChrome::new(nav, workspace, knowledge);
         assert_eq!(chrome.knowledge.persona, WorkspacePersona::Developer);
     }
+
+    #[test]
+    fn shell_chrome_passes_accessibility_audit_on_every_platform() {
+        let nav = NavigationRail::new(
+            vec![
+                NavigationItem {
+                    id: "ai-studio".into(),
+                    label: "AI Ops Studio".into(),
+                    icon: "message-circle".into(),
+                    route: "/chat".into(),
+                    allowed_roles: vec![],
+                },
+                NavigationItem {
+                    id: "ci".into(),
+                    label: "CI/CD Console".into(),
+                    icon: "activity".into(),
+                    route: "/ci".into(),
+                    allowed_roles: vec![],
+                },
+            ],
+            Some("/ci".into()),
+        );
+        let workspace = WorkspaceSwitcher::new(
+            vec![Workspace {
+                id: "ai-studio".into(),
+                label: "AI Ops Studio".into(),
+                persona: WorkspacePersona::Developer,
+                routes: vec!["/chat".into()],
+                allowed_roles: vec![],
+            }],
+            Some("ai-studio".into()),
+        );
+        let knowledge = KnowledgeOverlay::new(
+            WorkspacePersona::Developer,
+            vec![KnowledgeArticle {
+                id: "dev-workflow".into(),
+                title: "Ship workflows from chat".into(),
+                summary: String::new(),
+                link: "docs/workflows/ai-ops-studio.md".into(),
+            }],
+        );
+        let mut chrome = ShellChrome::new(nav, workspace, knowledge);
+
+        let nodes = chrome.accessibility_nodes(0);
+        let focus: Vec<_> = nodes.iter().filter_map(|node| node.focus_order).collect();
+        assert_eq!(focus, [1, 2, 3, 4]);
+        assert!(nodes
+            .iter()
+            .any(|node| node.id == "navigation.ci" && node.selected));
+        for platform in [
+            Platform::Server,
+            Platform::Mobile,
+            Platform::Desktop,
+            Platform::Web,
+            Platform::ARGlasses,
+            Platform::XRHeadset,
+        ] {
+            assert_eq!(
+                audit(&nodes, AccessibilityPolicy::for_platform(&platform)),
+                vec![]
+            );
+        }
+
+        chrome.navigation.items[1].label = " ".into();
+        let nodes = chrome.accessibility_nodes(0);
+        assert!(enforce(&Platform::Server, &nodes).is_ok());
+        let err = enforce(&Platform::Web, &nodes).unwrap_err();
+        assert!(err.contains("navigation.ci: Link has no accessible label"));
+    }
 }
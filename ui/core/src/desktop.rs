@@ -0,0 +1,441 @@
+//! Desktop notification and tray integration.
+//!
+//! [`DesktopModule`] watches the shell event bus for pipeline failures, pending
+//! approvals, and agent escalations. Each one raises an OS notification through
+//! the host's [`DesktopBridge`] and lands in the shell notification center, and
+//! the tray icon reports `Degraded` while any failure or escalation is
+//! unresolved. Clicking a notification deep-links into the dashboard module.
+//! Notifications and tray status are also mirrored into the store under
+//! [`TRAY_STATE_KEY`] and [`NOTIFICATIONS_STATE_KEY`] for renderers without a
+//! native bridge.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::ShellEvent;
+use crate::module::{ModuleCapability, ModuleContext, ModuleDescriptor, ModuleMount, ShellModule};
+use crate::state::{NotificationLevel, WorkspacePersona};
+
+pub const TRAY_STATE_KEY: &str = "desktop.tray";
+pub const NOTIFICATIONS_STATE_KEY: &str = "desktop.notifications";
+
+const DASHBOARD_MODULE_ID: &str = "noa-dashboard";
+const DASHBOARD_ROUTE: &str = "/dashboard";
+
+/// What a desktop notification is about.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DesktopAlertKind {
+    PipelineFailure,
+    PendingApproval,
+    AgentEscalation,
+}
+
+/// Notification handed to the OS, with the dashboard route it opens.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DesktopNotification {
+    pub id: String,
+    pub kind: DesktopAlertKind,
+    pub title: String,
+    pub body: String,
+    pub level: NotificationLevel,
+    pub deep_link: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum TrayHealth {
+    #[default]
+    Healthy,
+    Degraded,
+}
+
+/// Tray icon state.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct TrayStatus {
+    pub health: TrayHealth,
+    pub tooltip: String,
+    pub pipeline_failures: usize,
+    pub pending_approvals: usize,
+    pub escalations: usize,
+}
+
+impl TrayStatus {
+    pub fn icon(&self) -> &'static str {
+        match self.health {
+            TrayHealth::Healthy => "tray-healthy",
+            TrayHealth::Degraded => "tray-degraded",
+        }
+    }
+}
+
+/// Native side of the integration, implemented by the desktop host (e.g. Tauri).
+pub trait DesktopBridge: Send + Sync {
+    fn show_notification(&self, notification: &DesktopNotification);
+    fn set_tray_status(&self, status: &TrayStatus);
+}
+
+#[derive(Default)]
+struct DesktopAlerts {
+    /// Unresolved alerts keyed by the pipeline id, approval token, or agent id.
+    failures: BTreeMap<String, DesktopNotification>,
+    approvals: BTreeMap<String, DesktopNotification>,
+    escalations: BTreeMap<String, DesktopNotification>,
+}
+
+impl DesktopAlerts {
+    fn tray_status(&self) -> TrayStatus {
+        let degraded = !self.failures.is_empty() || !self.escalations.is_empty();
+        let mut parts = vec![];
+        for (count, noun) in [
+            (self.failures.len(), "pipeline failure"),
+            (self.escalations.len(), "agent escalation"),
+            (self.approvals.len(), "pending approval"),
+        ] {
+            if count > 0 {
+                parts.push(format!(
+                    "{} {}{}",
+                    count,
+                    noun,
+                    if count == 1 { "" } else { "s" }
+                ));
+            }
+        }
+        TrayStatus {
+            health: if degraded {
+                TrayHealth::Degraded
+            } else {
+                TrayHealth::Healthy
+            },
+            tooltip: if parts.is_empty() {
+                "All systems healthy".into()
+            } else {
+                parts.join(", ")
+            },
+            pipeline_failures: self.failures.len(),
+            pending_approvals: self.approvals.len(),
+            escalations: self.escalations.len(),
+        }
+    }
+
+    fn find(&self, notification_id: &str) -> Option<&DesktopNotification> {
+        self.failures
+            .values()
+            .chain(self.approvals.values())
+            .chain(self.escalations.values())
+            .find(|notification| notification.id == notification_id)
+    }
+
+    fn outstanding(&self) -> Vec<DesktopNotification> {
+        self.failures
+            .values()
+            .chain(self.escalations.values())
+            .chain(self.approvals.values())
+            .cloned()
+            .collect()
+    }
+}
+
+/// Shell module surfacing operational alerts as desktop notifications and tray state.
+pub struct DesktopModule {
+    descriptor: ModuleDescriptor,
+    bridge: Option<Arc<dyn DesktopBridge>>,
+    alerts: Mutex<DesktopAlerts>,
+}
+
+impl DesktopModule {
+    pub fn new() -> Self {
+        Self {
+            descriptor: ModuleDescriptor::new(
+                "desktop-integration",
+                "Desktop Notifications",
+                "bell",
+                WorkspacePersona::Operator,
+                vec![],
+                vec![
+                    ModuleCapability::ContinuousDelivery,
+                    ModuleCapability::Workflows,
+                    ModuleCapability::Agents,
+                ],
+                ModuleMount::InternalComponent {
+                    name: "DesktopTray".into(),
+                },
+                vec![],
+            ),
+            bridge: None,
+            alerts: Mutex::new(DesktopAlerts::default()),
+        }
+    }
+
+    pub fn with_bridge(mut self, bridge: Arc<dyn DesktopBridge>) -> Self {
+        self.bridge = Some(bridge);
+        self
+    }
+
+    pub fn tray_status(&self) -> TrayStatus {
+        self.alerts
+            .lock()
+            .expect("desktop alerts poisoned")
+            .tray_status()
+    }
+
+    fn raise(&self, context: &ModuleContext, notification: DesktopNotification, key: String) {
+        context.notify(
+            format!("{}: {}", notification.title, notification.body),
+            notification.level,
+        );
+        if let Some(bridge) = &self.bridge {
+            bridge.show_notification(&notification);
+        }
+        self.update(context, |alerts| {
+            let slot = match notification.kind {
+                DesktopAlertKind::PipelineFailure => &mut alerts.failures,
+                DesktopAlertKind::PendingApproval => &mut alerts.approvals,
+                DesktopAlertKind::AgentEscalation => &mut alerts.escalations,
+            };
+            slot.insert(key, notification);
+        });
+    }
+
+    fn update<F>(&self, context: &ModuleContext, f: F)
+    where
+        F: FnOnce(&mut DesktopAlerts),
+    {
+        let (status, outstanding) = {
+            let mut alerts = self.alerts.lock().expect("desktop alerts poisoned");
+            f(&mut alerts);
+            (alerts.tray_status(), alerts.outstanding())
+        };
+        if let Some(bridge) = &self.bridge {
+            bridge.set_tray_status(&status);
+        }
+        context.store.put_data(
+            TRAY_STATE_KEY,
+            serde_json::json!({
+                "health": status.health,
+                "icon": status.icon(),
+                "tooltip": status.tooltip,
+                "pipeline_failures": status.pipeline_failures,
+                "pending_approvals": status.pending_approvals,
+                "escalations": status.escalations,
+            }),
+        );
+        context.store.put_data(
+            NOTIFICATIONS_STATE_KEY,
+            serde_json::to_value(outstanding).unwrap_or_default(),
+        );
+    }
+
+    /// Open the dashboard at the notification's deep link.
+    fn activate(&self, context: &ModuleContext, notification_id: &str) {
+        let Some(route) = self
+            .alerts
+            .lock()
+            .expect("desktop alerts poisoned")
+            .find(notification_id)
+            .map(|notification| notification.deep_link.clone())
+        else {
+            return;
+        };
+        context.store.update(|state| {
+            state.navigation.active_route = Some(route.clone());
+        });
+        context.store.set_active_workspace(DASHBOARD_MODULE_ID);
+        context
+            .services
+            .publish(ShellEvent::RouteActivated { route });
+    }
+}
+
+impl Default for DesktopModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShellModule for DesktopModule {
+    fn descriptor(&self) -> &ModuleDescriptor {
+        &self.descriptor
+    }
+
+    fn hydrate(&self, context: &ModuleContext) {
+        self.update(context, |_| {});
+    }
+
+    fn handle_event(&self, event: &ShellEvent, context: &ModuleContext) {
+        match event {
+            ShellEvent::PipelineFailed {
+                pipeline_id,
+                reason,
+            } => self.raise(
+                context,
+                DesktopNotification {
+                    id: format!("pipeline-failure-{}", pipeline_id),
+                    kind: DesktopAlertKind::PipelineFailure,
+                    title: format!("Pipeline {} failed", pipeline_id),
+                    body: reason.clone(),
+                    level: NotificationLevel::Error,
+                    deep_link: format!("{}/pipelines/{}", DASHBOARD_ROUTE, pipeline_id),
+                },
+                pipeline_id.clone(),
+            ),
+            ShellEvent::PipelineRecovered { pipeline_id } => {
+                self.update(context, |alerts| {
+                    alerts.failures.remove(pipeline_id);
+                });
+            }
+            ShellEvent::ApprovalRequested {
+                workflow_id,
+                stage_id,
+                token,
+            } => self.raise(
+                context,
+                DesktopNotification {
+                    id: format!("approval-{}", token),
+                    kind: DesktopAlertKind::PendingApproval,
+                    title: format!("Approval needed for {}", workflow_id),
+                    body: format!("Stage '{}' is waiting for sign-off.", stage_id),
+                    level: NotificationLevel::Warning,
+                    deep_link: format!("{}/approvals/{}", DASHBOARD_ROUTE, token),
+                },
+                token.clone(),
+            ),
+            ShellEvent::ApprovalResolved { token } => {
+                self.update(context, |alerts| {
+                    alerts.approvals.remove(token);
+                });
+            }
+            ShellEvent::AgentEscalated {
+                agent_id,
+                escalation_to,
+                reason,
+            } => self.raise(
+                context,
+                DesktopNotification {
+                    id: format!("escalation-{}", agent_id),
+                    kind: DesktopAlertKind::AgentEscalation,
+                    title: match escalation_to {
+                        Some(target) => format!("{} escalated to {}", agent_id, target),
+                        None => format!("{} escalated", agent_id),
+                    },
+                    body: reason.clone(),
+                    level: NotificationLevel::Error,
+                    deep_link: format!("{}/agents/{}", DASHBOARD_ROUTE, agent_id),
+                },
+                agent_id.clone(),
+            ),
+            ShellEvent::EscalationResolved { agent_id } => {
+                self.update(context, |alerts| {
+                    alerts.escalations.remove(agent_id);
+                });
+            }
+            ShellEvent::DesktopNotificationClicked { notification_id } => {
+                self.activate(context, notification_id);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::DashboardModule;
+    use crate::services::use_shell_services;
+    use crate::state::{GlobalState, GlobalStore};
+    use crate::workflows::WorkflowCatalog;
+
+    #[derive(Default)]
+    struct RecordingBridge {
+        shown: Mutex<Vec<DesktopNotification>>,
+        tray: Mutex<Vec<TrayStatus>>,
+    }
+
+    impl DesktopBridge for RecordingBridge {
+        fn show_notification(&self, notification: &DesktopNotification) {
+            self.shown.lock().unwrap().push(notification.clone());
+        }
+
+        fn set_tray_status(&self, status: &TrayStatus) {
+            self.tray.lock().unwrap().push(status.clone());
+        }
+    }
+
+    #[test]
+    fn alerts_drive_tray_health_and_deep_links() {
+        let store = GlobalStore::new(GlobalState::default());
+        store.update(|state| state.session.roles = vec!["executive".into()]);
+        let published = Arc::new(Mutex::new(vec![]));
+        let sink: Arc<dyn Fn(ShellEvent) + Send + Sync> = Arc::new({
+            let published = published.clone();
+            move |event| published.lock().unwrap().push(event)
+        });
+        let context = ModuleContext {
+            store: store.clone(),
+            workflows: WorkflowCatalog::default(),
+            emit: sink.clone(),
+            services: use_shell_services(&store, sink),
+        };
+        DashboardModule::new().hydrate(&context);
+        let hydrated = store.read().notifications.len();
+        let bridge = Arc::new(RecordingBridge::default());
+        let module = DesktopModule::new().with_bridge(bridge.clone());
+        module.hydrate(&context);
+        assert_eq!(module.tray_status().health, TrayHealth::Healthy);
+
+        module.handle_event(
+            &ShellEvent::ApprovalRequested {
+                workflow_id: "release".into(),
+                stage_id: "sign-off".into(),
+                token: "tok-1".into(),
+            },
+            &context,
+        );
+        assert_eq!(module.tray_status().health, TrayHealth::Healthy);
+        module.handle_event(
+            &ShellEvent::PipelineFailed {
+                pipeline_id: "pipe-7".into(),
+                reason: "unit tests failed".into(),
+            },
+            &context,
+        );
+        let status = module.tray_status();
+        assert_eq!(status.health, TrayHealth::Degraded);
+        assert_eq!(status.tooltip, "1 pipeline failure, 1 pending approval");
+        assert_eq!(bridge.shown.lock().unwrap().len(), 2);
+        assert_eq!(store.read().data[TRAY_STATE_KEY]["icon"], "tray-degraded");
+        assert_eq!(store.read().notifications.len(), hydrated + 2);
+
+        module.handle_event(
+            &ShellEvent::DesktopNotificationClicked {
+                notification_id: "pipeline-failure-pipe-7".into(),
+            },
+            &context,
+        );
+        let state = store.read();
+        assert_eq!(
+            state.navigation.active_route.as_deref(),
+            Some("/dashboard/pipelines/pipe-7")
+        );
+        assert_eq!(
+            state.session.active_workspace.as_deref(),
+            Some(DASHBOARD_MODULE_ID)
+        );
+        assert!(matches!(
+            published.lock().unwrap().last(),
+            Some(ShellEvent::RouteActivated { route }) if route == "/dashboard/pipelines/pipe-7"
+        ));
+
+        module.handle_event(
+            &ShellEvent::PipelineRecovered {
+                pipeline_id: "pipe-7".into(),
+            },
+            &context,
+        );
+        assert_eq!(module.tray_status().health, TrayHealth::Healthy);
+        assert_eq!(
+            bridge.tray.lock().unwrap().last().unwrap().tooltip,
+            "1 pending approval"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use noa_core::events::KernelEvent;
 use serde::{Deserialize, Serialize};
 
 use crate::workflows::WorkflowRun;
@@ -39,6 +40,41 @@ pub enum ShellEvent {
     QuickActionTriggered {
         action: String,
     },
+    PipelineFailed {
+        pipeline_id: String,
+        reason: String,
+    },
+    PipelineRecovered {
+        pipeline_id: String,
+    },
+    ApprovalRequested {
+        workflow_id: String,
+        stage_id: String,
+        token: String,
+    },
+    ApprovalResolved {
+        token: String,
+    },
+    AgentEscalated {
+        agent_id: String,
+        escalation_to: Option<String>,
+        reason: String,
+    },
+    EscalationResolved {
+        agent_id: String,
+    },
+    DesktopNotificationClicked {
+        notification_id: String,
+    },
+    VoiceIntentRecognized {
+        module_id: String,
+        intent: String,
+        transcript: String,
+    },
+    /// Capability lifecycle change or unhandled error published on the kernel event bus.
+    KernelEventPublished {
+        event: KernelEvent,
+    },
 }
 
 /// Lightweight client that resolves WebSocket endpoints for shell channels.
@@ -0,0 +1,84 @@
+//! Host capabilities broadcast by the gateway.
+//!
+//! Thin clients such as AR glasses and XR headsets run speech and inference on
+//! the host. The gateway sends its [`HostCapabilities`] in the `connection_ack`
+//! of the subscription WebSocket and again on the `hostCapabilities`
+//! subscription whenever they change; applying them to a [`crate::UIContext`]
+//! lets adapters reflect the host's latency class and gates push-to-talk on a
+//! loaded voice model.
+
+use std::sync::{Arc, RwLock};
+
+use noa_core::world::{HostCapabilities, HOST_CAPABILITIES_FIELD};
+use serde_json::Value;
+
+/// Latest capabilities of the connected host, shared by every clone of a [`crate::UIContext`].
+#[derive(Debug, Clone, Default)]
+pub struct HostLink {
+    capabilities: Arc<RwLock<Option<HostCapabilities>>>,
+}
+
+impl HostLink {
+    /// `None` until the gateway has advertised the host.
+    pub fn capabilities(&self) -> Option<HostCapabilities> {
+        self.capabilities
+            .read()
+            .expect("host link poisoned")
+            .clone()
+    }
+
+    pub(crate) fn replace(&self, capabilities: HostCapabilities) {
+        *self.capabilities.write().expect("host link poisoned") = Some(capabilities);
+    }
+}
+
+/// Capabilities carried by a `connection_ack` payload, if the gateway sent any.
+pub fn from_connection_ack(payload: &Value) -> Option<HostCapabilities> {
+    serde_json::from_value(payload.get(HOST_CAPABILITIES_FIELD)?.clone()).ok()
+}
+
+/// Capabilities carried by a `next` message of a `hostCapabilities` subscription
+/// selecting the `data` field.
+pub fn from_subscription(payload: &Value) -> Option<HostCapabilities> {
+    let data = payload.pointer("/data")?.as_object()?.values().next()?;
+    serde_json::from_value(data.get("data")?.clone()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::PushToTalkState;
+    use crate::{init, Platform};
+    use noa_core::world::{LatencyClass, ModelCapability};
+    use serde_json::json;
+
+    #[test]
+    fn broadcasts_gate_push_to_talk_on_host_voice_models() {
+        let context = init(Platform::ARGlasses).unwrap();
+        let shared = context.clone();
+        let mut capabilities = HostCapabilities {
+            host: "edge-01".into(),
+            latency: LatencyClass::Realtime,
+            ..HostCapabilities::default()
+        };
+
+        let ack = json!({ HOST_CAPABILITIES_FIELD: capabilities });
+        context.apply_host_capabilities(from_connection_ack(&ack).unwrap());
+        assert_eq!(shared.push_to_talk.state(), PushToTalkState::Unavailable);
+
+        capabilities.voice_models.push(ModelCapability {
+            id: "whisper".into(),
+            summary: "speech recognition".into(),
+            loaded: true,
+            tags: vec!["model".into(), "voice".into()],
+        });
+        let next = json!({ "data": { "hostCapabilities": { "data": capabilities } } });
+        context.apply_host_capabilities(from_subscription(&next).unwrap());
+        assert_eq!(shared.push_to_talk.state(), PushToTalkState::Idle);
+        assert_eq!(shared.host.capabilities(), Some(capabilities.clone()));
+
+        let web = init(Platform::Web).unwrap();
+        web.apply_host_capabilities(capabilities);
+        assert_eq!(web.push_to_talk.state(), PushToTalkState::Unavailable);
+    }
+}
@@ -1,15 +1,20 @@
 //! Dynamic UI Core - Multi-platform UI framework
 
+pub mod accessibility;
 pub mod adapters;
 pub mod analytics;
 pub mod chat;
 pub mod components;
+pub mod desktop;
 pub mod events;
+pub mod host;
 pub mod module;
 pub mod renderer;
 pub mod services;
 pub mod shell;
 pub mod state;
+pub mod timetravel;
+pub mod voice;
 pub mod workflows;
 
 use std::collections::HashMap;
@@ -18,10 +23,13 @@ use std::sync::Arc;
 use noa_core::capabilities::{CapabilityError, KernelHandle};
 use noa_core::config::manifest::CAPABILITY_PROCESS;
 use noa_core::process::ProcessService;
+use noa_core::world::HostCapabilities;
 
+use host::HostLink;
 pub use module::{ModuleCapability, ModuleDescriptor, ShellModule};
 pub use shell::{ShellBuilder, UnifiedShell};
 use state::GlobalStore;
+use voice::PushToTalk;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Platform {
@@ -52,6 +60,37 @@ pub struct UIContext {
     pub screen_height: u32,
     pub dpi: f32,
     pub capabilities: Vec<Capability>,
+    pub push_to_talk: PushToTalk,
+    pub host: HostLink,
+}
+
+impl UIContext {
+    pub fn new(
+        platform: Platform,
+        screen_width: u32,
+        screen_height: u32,
+        dpi: f32,
+        capabilities: Vec<Capability>,
+    ) -> Self {
+        Self {
+            push_to_talk: PushToTalk::for_capabilities(&capabilities),
+            platform,
+            screen_width,
+            screen_height,
+            dpi,
+            capabilities,
+            host: HostLink::default(),
+        }
+    }
+
+    /// Record capabilities broadcast by the host. Voice input on this platform is only
+    /// available while the host has a voice model loaded.
+    pub fn apply_host_capabilities(&self, capabilities: HostCapabilities) {
+        if self.capabilities.contains(&Capability::Voice) {
+            self.push_to_talk.set_available(capabilities.voice_ready());
+        }
+        self.host.replace(capabilities);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -86,56 +125,50 @@ pub fn init(platform: Platform) -> Result<UIContext, &'static str> {
     println!("[UI] Initializing UI system for platform: {:?}", platform);
 
     let context = match platform {
-        Platform::Server => UIContext {
-            platform,
-            screen_width: 0,
-            screen_height: 0,
-            dpi: 1.0,
-            capabilities: vec![],
-        },
-        Platform::Mobile => UIContext {
+        Platform::Server => UIContext::new(platform, 0, 0, 1.0, vec![]),
+        Platform::Mobile => UIContext::new(
             platform,
-            screen_width: 1080,
-            screen_height: 2400,
-            dpi: 3.0,
-            capabilities: vec![Capability::Touch, Capability::Voice],
-        },
-        Platform::Desktop => UIContext {
+            1080,
+            2400,
+            3.0,
+            vec![Capability::Touch, Capability::Voice],
+        ),
+        Platform::Desktop => UIContext::new(
             platform,
-            screen_width: 1920,
-            screen_height: 1080,
-            dpi: 1.0,
-            capabilities: vec![Capability::Mouse, Capability::Keyboard],
-        },
-        Platform::Web => UIContext {
+            1920,
+            1080,
+            1.0,
+            vec![Capability::Mouse, Capability::Keyboard],
+        ),
+        Platform::Web => UIContext::new(
             platform,
-            screen_width: 1920,
-            screen_height: 1080,
-            dpi: 1.0,
-            capabilities: vec![Capability::Mouse, Capability::Keyboard, Capability::Touch],
-        },
-        Platform::ARGlasses => UIContext {
+            1920,
+            1080,
+            1.0,
+            vec![Capability::Mouse, Capability::Keyboard, Capability::Touch],
+        ),
+        Platform::ARGlasses => UIContext::new(
             platform,
-            screen_width: 1280,
-            screen_height: 720,
-            dpi: 2.0,
-            capabilities: vec![
+            1280,
+            720,
+            2.0,
+            vec![
                 Capability::Gesture,
                 Capability::Voice,
                 Capability::EyeTracking,
             ],
-        },
-        Platform::XRHeadset => UIContext {
+        ),
+        Platform::XRHeadset => UIContext::new(
             platform,
-            screen_width: 2560,
-            screen_height: 1440,
-            dpi: 2.0,
-            capabilities: vec![
+            2560,
+            1440,
+            2.0,
+            vec![
                 Capability::SpatialTracking,
                 Capability::HandTracking,
                 Capability::Voice,
             ],
-        },
+        ),
     };
 
     Ok(context)
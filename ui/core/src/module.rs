@@ -1,13 +1,19 @@
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
 
+use noa_core::events::{KernelEventKind, KernelEventSubscriber};
+use noa_core::watchdog::KernelWatchdog;
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 
 use crate::chat::{ChatAction, ChatCommandDescriptor};
+use crate::desktop::DesktopModule;
 use crate::events::ShellEvent;
 use crate::services::ShellServices;
 use crate::state::{GlobalStore, NavigationItem, NotificationLevel, Workspace, WorkspacePersona};
+use crate::voice::VoiceGrammar;
 use crate::workflows::{Workflow, WorkflowCatalog};
+use crate::Platform;
 
 /// Describes the capabilities surfaced by a module.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -171,6 +177,10 @@ impl ShellModule for LazyModule {
     fn chat_commands(&self) -> Vec<ChatCommandDescriptor> {
         self.ensure_loaded().chat_commands()
     }
+
+    fn voice_grammar(&self) -> Vec<VoiceGrammar> {
+        self.ensure_loaded().voice_grammar()
+    }
 }
 
 /// Trait implemented by each module integrated into the unified shell.
@@ -181,6 +191,13 @@ pub trait ShellModule: Send + Sync {
     fn chat_commands(&self) -> Vec<ChatCommandDescriptor> {
         vec![]
     }
+    /// Voice grammars for the module; defaults to one per chat command.
+    fn voice_grammar(&self) -> Vec<VoiceGrammar> {
+        self.chat_commands()
+            .iter()
+            .map(|command| VoiceGrammar::from_chat_command(&self.descriptor().id, command))
+            .collect()
+    }
 }
 
 /// Module dedicated to orchestrating workflows.
@@ -197,7 +214,11 @@ impl WorkflowModule {
                 "Workflow Command Center",
                 "workflow",
                 WorkspacePersona::Operator,
-                vec!["/workflows".into(), "/workflows/history".into()],
+                vec![
+                    "/workflows".into(),
+                    "/workflows/history".into(),
+                    "/workflows/builder".into(),
+                ],
                 vec![ModuleCapability::Workflows, ModuleCapability::Sandbox],
                 ModuleMount::InternalComponent {
                     name: "WorkflowCommandCenter".into(),
@@ -292,9 +313,17 @@ impl ShellModule for AgentModule {
     }
 }
 
+/// Store key holding the kernel health panel rendered by the dashboard.
+pub const KERNEL_HEALTH_STATE_KEY: &str = "dashboard.kernel";
+
 /// Module bridging the legacy dashboard into the unified shell.
+///
+/// Kernel events forwarded into the shell feed its kernel health panel: the latest
+/// lifecycle state of every capability plus the watchdog's failures and errors.
 pub struct DashboardModule {
     descriptor: ModuleDescriptor,
+    capabilities: Mutex<BTreeMap<String, &'static str>>,
+    watchdog: KernelWatchdog,
 }
 
 impl DashboardModule {
@@ -313,7 +342,49 @@ impl DashboardModule {
                 },
                 vec!["executive".into(), "admin".into()],
             ),
+            capabilities: Mutex::new(BTreeMap::new()),
+            watchdog: KernelWatchdog::new(),
+        }
+    }
+
+    fn record_kernel_event(&self, event: &noa_core::events::KernelEvent, context: &ModuleContext) {
+        self.watchdog.on_event(event);
+        let capabilities = {
+            let mut capabilities = self
+                .capabilities
+                .lock()
+                .expect("dashboard capabilities poisoned");
+            if let Some(capability) = event.kind.capability() {
+                let state = match event.kind {
+                    KernelEventKind::CapabilityInitialized { .. } => "ready",
+                    KernelEventKind::CapabilityFailed { .. } => "failed",
+                    _ => "registered",
+                };
+                capabilities.insert(capability.to_string(), state);
+            }
+            capabilities.clone()
+        };
+        match &event.kind {
+            KernelEventKind::CapabilityFailed { capability, error } => context.notify(
+                format!("Capability {} failed: {}", capability, error),
+                NotificationLevel::Error,
+            ),
+            KernelEventKind::UnhandledError { source, message } => context.notify(
+                format!("Kernel error in {}: {}", source, message),
+                NotificationLevel::Error,
+            ),
+            _ => {}
         }
+        let report = self.watchdog.report();
+        context.store.put_data(
+            KERNEL_HEALTH_STATE_KEY,
+            serde_json::json!({
+                "healthy": report.healthy(),
+                "capabilities": capabilities,
+                "failed": report.failed_capabilities,
+                "recent_errors": report.recent_errors,
+            }),
+        );
     }
 }
 
@@ -336,6 +407,12 @@ impl ShellModule for DashboardModule {
             NotificationLevel::Success,
         );
     }
+
+    fn handle_event(&self, event: &ShellEvent, context: &ModuleContext) {
+        if let ShellEvent::KernelEventPublished { event } = event {
+            self.record_kernel_event(event, context);
+        }
+    }
 }
 
 /// Module wrapping the Vibe Kanban Next.js experience.
@@ -636,6 +713,15 @@ pub fn default_modules() -> Vec<Arc<dyn ShellModule>> {
     modules.into_iter().map(wrap).collect()
 }
 
+/// Stock modules plus the ones specific to `platform`.
+pub fn default_modules_for(platform: &Platform) -> Vec<Arc<dyn ShellModule>> {
+    let mut modules = default_modules();
+    if *platform == Platform::Desktop {
+        modules.push(Arc::new(DesktopModule::new()));
+    }
+    modules
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -21,6 +21,10 @@ pub mod renderer {
             Self { context }
         }
 
+        pub fn platform(&self) -> &Platform {
+            &self.context.platform
+        }
+
         pub fn render(&self, component: &str) -> Result<(), String> {
             match self.context.platform {
                 Platform::Server => self.render_api(component),
@@ -34,7 +38,7 @@ pub mod renderer {
 
         pub fn render_frame(&self, frame: &RenderFrame<'_>) -> Result<(), String> {
             let component = format!(
-                "shell-navigation:{} workspaces:{} active:{} knowledge:{}",
+                "shell-navigation:{} workspaces:{} active:{} knowledge:{} motion:{:?} contrast:{:?}",
                 frame.chrome.navigation.items.len(),
                 frame.chrome.workspace_switcher.workspaces.len(),
                 frame
@@ -43,7 +47,9 @@ pub mod renderer {
                     .active
                     .as_deref()
                     .unwrap_or("none"),
-                frame.chrome.knowledge.articles.len()
+                frame.chrome.knowledge.articles.len(),
+                frame.chrome.variant.motion,
+                frame.chrome.variant.contrast
             );
             self.render(&component)
         }
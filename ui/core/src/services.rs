@@ -31,6 +31,10 @@ impl ShellServices {
             .push_notification(Notification::new(level, message.into()));
     }
 
+    pub fn event_sink(&self) -> Arc<dyn Fn(ShellEvent) + Send + Sync> {
+        self.event_sink.clone()
+    }
+
     pub fn publish(&self, event: ShellEvent) {
         (self.event_sink)(event);
     }
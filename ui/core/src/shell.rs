@@ -1,20 +1,28 @@
 use std::collections::HashMap;
+use std::sync::mpsc::Receiver;
 use std::sync::{Arc, Mutex};
 
+use noa_core::events::KernelEvent;
+
 use crate::adapters::{
     PlatformAdapter, ReactAdapter, ReactNativeAdapter, ServerAdapter, SpatialAdapter, TauriAdapter,
 };
 use crate::analytics::{
-    AgentEfficiency, AnalyticsEngine, HeatmapPoint, Metric, ModelRoi, TelemetryInsights,
+    AgentEfficiency, AnalyticsEngine, AnalyticsRetention, HeatmapPoint, Metric, ModelRoi,
+    TelemetryInsights,
 };
 use crate::chat::ChatWorkspace;
-use crate::components::{KnowledgeOverlay, NavigationRail, ShellChrome, WorkspaceSwitcher};
+use crate::components::{
+    KnowledgeOverlay, NavigationRail, ShellChrome, StateTimelineViewer, WorkspaceSwitcher,
+};
 use crate::events::ShellEvent;
-use crate::module::{default_modules, ModuleContext, ShellModule};
+use crate::module::{default_modules_for, ModuleContext, ShellModule};
 use crate::renderer::renderer::Renderer;
 use crate::renderer::RenderFrame;
 use crate::services::ShellServices;
 use crate::state::{GlobalState, GlobalStore, KnowledgeArticle, UserSession, WorkspacePersona};
+use crate::timetravel::DEFAULT_TIME_TRAVEL_CAPACITY;
+use crate::voice::VoiceRouter;
 use crate::workflows::WorkflowCatalog;
 use crate::{init, Platform, UIContext, UIState};
 
@@ -23,6 +31,7 @@ pub struct ShellBuilder {
     platform: Platform,
     modules: Option<Vec<Arc<dyn ShellModule>>>,
     session: Option<UserSession>,
+    time_travel: bool,
 }
 
 impl ShellBuilder {
@@ -31,6 +40,7 @@ impl ShellBuilder {
             platform,
             modules: None,
             session: None,
+            time_travel: false,
         }
     }
 
@@ -44,12 +54,17 @@ impl ShellBuilder {
         self
     }
 
+    /// Record every store mutation for time-travel debugging (dev mode).
+    pub fn with_time_travel(mut self, enabled: bool) -> Self {
+        self.time_travel = enabled;
+        self
+    }
+
     pub fn build(self) -> Result<UnifiedShell, &'static str> {
-        UnifiedShell::new(
-            self.platform,
-            self.modules.unwrap_or_else(default_modules),
-            self.session,
-        )
+        let modules = self
+            .modules
+            .unwrap_or_else(|| default_modules_for(&self.platform));
+        UnifiedShell::new(self.platform, modules, self.session, self.time_travel)
     }
 }
 
@@ -62,9 +77,10 @@ pub struct UnifiedShell {
     modules: Vec<Arc<dyn ShellModule>>,
     workflow_catalog: WorkflowCatalog,
     chat_workspace: Arc<Mutex<ChatWorkspace>>,
-    analytics: Mutex<AnalyticsEngine>,
+    analytics: Arc<Mutex<AnalyticsEngine>>,
     event_log: Arc<Mutex<Vec<ShellEvent>>>,
     services: ShellServices,
+    voice: VoiceRouter,
 }
 
 impl UnifiedShell {
@@ -72,12 +88,16 @@ impl UnifiedShell {
         platform: Platform,
         modules: Vec<Arc<dyn ShellModule>>,
         session: Option<UserSession>,
+        time_travel: bool,
     ) -> Result<Self, &'static str> {
         let context = init(platform.clone())?;
         let renderer = Renderer::new(context.clone());
         let state = UIState::new(context.clone());
         // Each shell instance holds its own store to avoid test interference when run in parallel.
         let store = GlobalStore::new(GlobalState::default());
+        if time_travel {
+            store.enable_time_travel(DEFAULT_TIME_TRAVEL_CAPACITY);
+        }
 
         if let Some(session) = session {
             store.update(|state| state.session = session);
@@ -114,6 +134,11 @@ impl UnifiedShell {
             event_sink.clone(),
         )));
 
+        let mut voice = VoiceRouter::new();
+        for module in &modules {
+            voice.register_module(module.as_ref());
+        }
+
         let shell = Self {
             context,
             renderer,
@@ -122,9 +147,10 @@ impl UnifiedShell {
             modules,
             workflow_catalog,
             chat_workspace,
-            analytics: Mutex::new(AnalyticsEngine::default()),
+            analytics: Arc::new(Mutex::new(AnalyticsEngine::default())),
             event_log,
             services,
+            voice,
         };
 
         shell.bootstrap_modules(event_sink);
@@ -134,18 +160,26 @@ impl UnifiedShell {
         Ok(shell)
     }
 
-    fn bootstrap_modules(&self, event_sink: Arc<dyn Fn(ShellEvent) + Send + Sync>) {
-        let context = ModuleContext {
-            store: self.store.clone(),
+    /// Context whose store and services attribute mutations to `module`.
+    fn module_context(
+        &self,
+        module: &dyn ShellModule,
+        emit: Arc<dyn Fn(ShellEvent) + Send + Sync>,
+    ) -> ModuleContext {
+        let store = self.store.for_module(&module.descriptor().id);
+        ModuleContext {
+            services: ShellServices::new(store.clone(), self.services.event_sink()),
+            store,
             workflows: self.workflow_catalog.clone(),
-            emit: event_sink.clone(),
-            services: self.services.clone(),
-        };
+            emit,
+        }
+    }
 
+    fn bootstrap_modules(&self, event_sink: Arc<dyn Fn(ShellEvent) + Send + Sync>) {
         let mut commands = vec![];
 
         for module in &self.modules {
-            module.hydrate(&context);
+            module.hydrate(&self.module_context(module.as_ref(), event_sink.clone()));
             commands.extend(module.chat_commands());
         }
 
@@ -278,16 +312,20 @@ impl UnifiedShell {
         for module in &self.modules {
             module.handle_event(
                 &event,
-                &ModuleContext {
-                    store: self.store.clone(),
-                    workflows: self.workflow_catalog.clone(),
-                    emit: Arc::new(|_| {}),
-                    services: self.services.clone(),
-                },
+                &self.module_context(module.as_ref(), Arc::new(|_| {})),
             );
         }
     }
 
+    /// Emit every kernel event waiting on `events`, e.g. a receiver from
+    /// `noa_core::events::global().channel()`, returning how many were forwarded.
+    pub fn forward_kernel_events(&self, events: &Receiver<KernelEvent>) -> usize {
+        events
+            .try_iter()
+            .map(|event| self.emit(ShellEvent::KernelEventPublished { event }))
+            .count()
+    }
+
     fn drain_events(&self) -> Vec<ShellEvent> {
         let mut log = self.event_log.lock().unwrap();
         let events = log.clone();
@@ -309,6 +347,32 @@ impl UnifiedShell {
         response
     }
 
+    /// Route a speech transcript heard while push-to-talk is held to its module
+    /// intent and run it like the matching chat command.
+    pub fn handle_voice_command(
+        &self,
+        transcript: &str,
+        confidence: f32,
+    ) -> Result<Option<String>, String> {
+        let intent = self.voice.route(
+            &self.context.push_to_talk,
+            transcript,
+            confidence,
+            &|event| self.event_log.lock().unwrap().push(event),
+        )?;
+        let response = self
+            .chat_workspace
+            .lock()
+            .ok()
+            .and_then(|chat| chat.handle_message(&intent.intent));
+
+        for event in self.drain_events() {
+            self.emit(event);
+        }
+
+        Ok(response)
+    }
+
     pub fn render(&self, adapter: &dyn PlatformAdapter) -> Result<(), String> {
         let state_snapshot = self.store.read();
         let session_roles = state_snapshot.session.roles.clone();
@@ -377,7 +441,8 @@ impl UnifiedShell {
         let navigation = NavigationRail::new(nav_items, active_route.clone());
         let workspace_switcher = WorkspaceSwitcher::new(workspaces, active_workspace.clone());
         let knowledge = KnowledgeOverlay::new(persona, knowledge_articles);
-        let chrome = ShellChrome::new(navigation, workspace_switcher, knowledge);
+        let chrome = ShellChrome::new(navigation, workspace_switcher, knowledge)
+            .with_variant(state_snapshot.accessibility.into());
         adapter.mount(&self.renderer, &chrome, &self.state)?;
         let frame = RenderFrame { chrome: &chrome };
         self.renderer.render_frame(&frame)
@@ -417,6 +482,18 @@ impl UnifiedShell {
     pub fn services(&self) -> ShellServices {
         self.services.clone()
     }
+
+    /// This shell's analytics as a store for a `noa_workflow::RetentionManager`.
+    pub fn analytics_retention(&self) -> AnalyticsRetention {
+        AnalyticsRetention::new(Arc::clone(&self.analytics), self.store.clone())
+    }
+
+    /// Timeline of recorded store mutations; `None` unless built with time travel.
+    pub fn timeline_viewer(&self) -> Option<StateTimelineViewer> {
+        self.store
+            .trace()
+            .map(|trace| StateTimelineViewer::from_trace(&trace))
+    }
 }
 
 impl Default for UnifiedShell {
@@ -449,6 +526,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn time_travel_records_module_mutations_for_the_viewer() {
+        use crate::accessibility::{enforce, Accessible};
+
+        assert!(UnifiedShell::default().timeline_viewer().is_none());
+        let shell = UnifiedShell::builder(Platform::Web)
+            .with_time_travel(true)
+            .build()
+            .unwrap();
+        let viewer = shell.timeline_viewer().unwrap();
+        assert!(!viewer.filter_source("ci-console").is_empty());
+        assert!(!viewer.filter_source("shell").is_empty());
+        assert!(enforce(&Platform::Web, &viewer.accessibility_nodes(0)).is_ok());
+
+        let store = shell.store_handle();
+        store.update(|state| state.navigation.active_route = Some("/ci".into()));
+        store.step_back().unwrap();
+        assert_ne!(store.read().navigation.active_route.as_deref(), Some("/ci"));
+        store.step_forward().unwrap();
+        assert_eq!(store.read().navigation.active_route.as_deref(), Some("/ci"));
+    }
+
+    #[test]
+    fn voice_commands_route_on_headsets_while_push_to_talk_is_held() {
+        let shell = UnifiedShell::builder(Platform::XRHeadset).build().unwrap();
+        assert!(shell.handle_voice_command("open ci", 0.9).is_err());
+
+        shell.context().push_to_talk.press().unwrap();
+        let response = shell.handle_voice_command("Hey NOA, open CI", 0.9).unwrap();
+        assert_eq!(response, Some("Navigated to /ci".into()));
+        assert_eq!(
+            shell.store.read().navigation.active_route.as_deref(),
+            Some("/ci")
+        );
+
+        let web = UnifiedShell::builder(Platform::Web).build().unwrap();
+        assert!(web.context().push_to_talk.press().is_err());
+    }
+
     #[test]
     fn persona_specific_navigation_filters_modules() {
         let session = UserSession {
@@ -476,9 +592,43 @@ mod tests {
                 || workspace.allowed_roles.contains(&"developer".into())));
     }
 
+    #[test]
+    fn kernel_events_feed_the_dashboard_health_panel() {
+        use crate::module::KERNEL_HEALTH_STATE_KEY;
+        use noa_core::events::{KernelEventBus, KernelEventKind};
+
+        let shell = UnifiedShell::builder(Platform::Web).build().unwrap();
+        let bus = KernelEventBus::new();
+        let events = bus.channel();
+        bus.publish(KernelEventKind::CapabilityInitialized {
+            capability: "core.memory".into(),
+            duration_ms: 2,
+        });
+        bus.publish(KernelEventKind::CapabilityFailed {
+            capability: "core.gateway".into(),
+            error: "port in use".into(),
+        });
+        assert_eq!(shell.forward_kernel_events(&events), 2);
+
+        let state = shell.store.read();
+        let health = &state.data[KERNEL_HEALTH_STATE_KEY];
+        assert_eq!(health["healthy"], false);
+        assert_eq!(health["capabilities"]["core.memory"], "ready");
+        assert_eq!(health["failed"]["core.gateway"], "port in use");
+        assert!(state
+            .notifications
+            .iter()
+            .any(|notification| notification.message.contains("core.gateway failed")));
+    }
+
     #[test]
     fn desktop_adapter_mounts_tauri_manifest() {
         let shell = UnifiedShell::builder(Platform::Desktop).build().unwrap();
+        assert!(shell
+            .modules
+            .iter()
+            .any(|module| module.descriptor().id == "desktop-integration"));
+        assert!(shell.store.read().data.contains_key("desktop.tray"));
         let adapter = shell.recommended_adapter();
         shell
             .render(adapter.as_ref())
@@ -1,12 +1,18 @@
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
+use crate::accessibility::AccessibilityPreferences;
+use crate::timetravel::{SessionTrace, StateMutation, StateRecorder};
+
+/// Source recorded for mutations made through an untagged store handle.
+pub const SHELL_SOURCE: &str = "shell";
+
 /// Personas describe the lens through which a workspace is configured.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
 pub enum WorkspacePersona {
@@ -119,21 +125,43 @@ pub struct GlobalState {
     pub notifications: Vec<Notification>,
     pub data: HashMap<String, serde_json::Value>,
     pub knowledge_base: HashMap<WorkspacePersona, Vec<KnowledgeArticle>>,
+    #[serde(default)]
+    pub accessibility: AccessibilityPreferences,
 }
 
 /// Thread-safe wrapper around [`GlobalState`].
+///
+/// Clones share the same state. A handle from [`GlobalStore::for_module`] tags
+/// its mutations with the module id for the time-travel recorder.
 #[derive(Clone)]
 pub struct GlobalStore {
     inner: Arc<RwLock<GlobalState>>,
+    recorder: Arc<Mutex<Option<StateRecorder>>>,
+    source: Arc<str>,
 }
 
 impl GlobalStore {
     pub fn new(state: GlobalState) -> Self {
         Self {
             inner: Arc::new(RwLock::new(state)),
+            recorder: Arc::new(Mutex::new(None)),
+            source: Arc::from(SHELL_SOURCE),
         }
     }
 
+    /// Handle to the same state whose mutations are attributed to `module_id`.
+    pub fn for_module(&self, module_id: &str) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            recorder: self.recorder.clone(),
+            source: Arc::from(module_id),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
     pub fn global() -> &'static GlobalStore {
         static STORE: Lazy<GlobalStore> = Lazy::new(|| GlobalStore::new(GlobalState::default()));
         &STORE
@@ -149,6 +177,84 @@ impl GlobalStore {
     {
         let mut guard = self.inner.write().expect("global state poisoned");
         f(&mut guard);
+        if let Some(recorder) = self.recorder().as_mut() {
+            recorder.record(&self.source, unix_time_millis(), &guard);
+        }
+    }
+
+    fn recorder(&self) -> std::sync::MutexGuard<'_, Option<StateRecorder>> {
+        self.recorder.lock().expect("state recorder poisoned")
+    }
+
+    /// Start recording mutations for time-travel debugging. Recording is meant for
+    /// development builds: every mutation stores a full state snapshot.
+    pub fn enable_time_travel(&self, capacity: usize) {
+        let guard = self.inner.read().expect("global state poisoned");
+        let mut recorder = self.recorder();
+        if recorder.is_none() {
+            *recorder = Some(StateRecorder::new(
+                guard.clone(),
+                capacity,
+                unix_time_millis(),
+            ));
+        }
+    }
+
+    pub fn disable_time_travel(&self) {
+        *self.recorder() = None;
+    }
+
+    pub fn time_travel_enabled(&self) -> bool {
+        self.recorder().is_some()
+    }
+
+    /// Restore the state before the current step. `None` at the start of the
+    /// recording or when time travel is disabled.
+    pub fn step_back(&self) -> Option<StateMutation> {
+        self.travel(|recorder| recorder.step_back().cloned())
+    }
+
+    /// Restore the state after the current step.
+    pub fn step_forward(&self) -> Option<StateMutation> {
+        self.travel(|recorder| recorder.step_forward().cloned())
+    }
+
+    /// Restore the state recorded at `sequence`.
+    pub fn jump_to(&self, sequence: u64) -> Option<StateMutation> {
+        self.travel(|recorder| recorder.jump_to(sequence).cloned())
+    }
+
+    fn travel<F>(&self, f: F) -> Option<StateMutation>
+    where
+        F: FnOnce(&mut StateRecorder) -> Option<StateMutation>,
+    {
+        // Lock order matches `update`: state first, then the recorder.
+        let mut guard = self.inner.write().expect("global state poisoned");
+        let mutation = f(self.recorder().as_mut()?)?;
+        *guard = mutation.state.clone();
+        Some(mutation)
+    }
+
+    /// The recorded session, if time travel is enabled.
+    pub fn trace(&self) -> Option<SessionTrace> {
+        self.recorder()
+            .as_ref()
+            .map(|recorder| recorder.trace(unix_time_millis()))
+    }
+
+    /// Write the recorded session as JSON.
+    pub fn export_trace<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        let trace = self
+            .trace()
+            .ok_or_else(|| std::io::Error::other("time travel is not enabled"))?;
+        let json = serde_json::to_string_pretty(&trace)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        if let Some(parent) = path.as_ref().parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        fs::write(path, json)
     }
 
     pub fn upsert_workspace(&self, workspace: Workspace) {
@@ -211,6 +317,12 @@ impl GlobalStore {
         });
     }
 
+    pub fn set_accessibility(&self, preferences: AccessibilityPreferences) {
+        self.update(|state| {
+            state.accessibility = preferences;
+        });
+    }
+
     pub fn knowledge_for(&self, persona: WorkspacePersona) -> Vec<KnowledgeArticle> {
         self.read()
             .knowledge_base
@@ -227,6 +339,13 @@ fn unix_time() -> u64 {
         .as_secs()
 }
 
+fn unix_time_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
 fn uuid() -> String {
     use std::sync::atomic::{AtomicU64, Ordering};
 
@@ -288,6 +407,40 @@ mod tests {
         let _ = std::fs::remove_file(path);
     }
 
+    #[test]
+    fn time_travel_attributes_mutations_and_restores_snapshots() {
+        let store = GlobalStore::new(GlobalState::default());
+        store.put_data("untracked", serde_json::json!(true));
+        assert!(store.step_back().is_none());
+
+        store.enable_time_travel(10);
+        store
+            .for_module("ci-console")
+            .put_data("ci.last_run", serde_json::json!("green"));
+        store.update(|state| state.navigation.active_route = Some("/ci".into()));
+
+        let trace = store.trace().unwrap();
+        let sources: Vec<_> = trace.mutations.iter().map(|m| m.source.as_str()).collect();
+        assert_eq!(sources, ["initial", "ci-console", "shell"]);
+        assert_eq!(trace.mutations[1].changed, ["data"]);
+
+        let restored = store.step_back().unwrap();
+        assert_eq!(restored.source, "ci-console");
+        assert_eq!(store.read().navigation.active_route, None);
+        store.step_back().unwrap();
+        assert!(!store.read().data.contains_key("ci.last_run"));
+        store.step_forward().unwrap();
+        assert_eq!(store.read().data["ci.last_run"], "green");
+
+        let path = std::env::temp_dir().join("noa_state_trace_test.json");
+        store.export_trace(&path).expect("export trace");
+        let exported: SessionTrace =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(exported.cursor, Some(1));
+        assert_eq!(exported.mutations.len(), 3);
+        let _ = std::fs::remove_file(path);
+    }
+
     #[test]
     fn knowledge_base_lookup_returns_articles() {
         let store = GlobalStore::new(GlobalState::default());
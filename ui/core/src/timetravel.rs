@@ -0,0 +1,188 @@
+//! Dev-mode time-travel debugging for the [`GlobalStore`].
+//!
+//! Once [`GlobalStore::enable_time_travel`] is called, every mutation is recorded
+//! with the module that made it, a millisecond timestamp, the top-level
+//! [`GlobalState`] fields it changed, and a snapshot of the resulting state.
+//! [`GlobalStore::step_back`] and [`GlobalStore::step_forward`] move through the
+//! recording by restoring snapshots; a mutation made while rewound discards the
+//! steps after the cursor. [`GlobalStore::export_trace`] writes the session as a
+//! [`SessionTrace`] for sharing, and the
+//! [`StateTimelineViewer`](crate::components::StateTimelineViewer) component
+//! renders it.
+//!
+//! [`GlobalStore`]: crate::state::GlobalStore
+//! [`GlobalStore::enable_time_travel`]: crate::state::GlobalStore::enable_time_travel
+//! [`GlobalStore::step_back`]: crate::state::GlobalStore::step_back
+//! [`GlobalStore::step_forward`]: crate::state::GlobalStore::step_forward
+//! [`GlobalStore::export_trace`]: crate::state::GlobalStore::export_trace
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::GlobalState;
+
+/// Mutations kept before the oldest are dropped.
+pub const DEFAULT_TIME_TRAVEL_CAPACITY: usize = 500;
+/// Source recorded for the state present when recording starts.
+pub const INITIAL_SOURCE: &str = "initial";
+
+/// One recorded mutation and the state it produced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateMutation {
+    pub sequence: u64,
+    /// Module id of the store handle that made the change.
+    pub source: String,
+    pub timestamp_ms: u64,
+    /// Top-level `GlobalState` fields that differ from the previous step.
+    pub changed: Vec<String>,
+    pub state: GlobalState,
+}
+
+/// Exported time-travel session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTrace {
+    pub exported_at_ms: u64,
+    /// Sequence number of the step the store was showing at export.
+    pub cursor: Option<u64>,
+    pub mutations: Vec<StateMutation>,
+}
+
+/// Bounded history of state mutations with a cursor for stepping.
+#[derive(Debug, Clone)]
+pub struct StateRecorder {
+    mutations: VecDeque<StateMutation>,
+    capacity: usize,
+    cursor: usize,
+    next_sequence: u64,
+}
+
+impl StateRecorder {
+    pub fn new(initial: GlobalState, capacity: usize, timestamp_ms: u64) -> Self {
+        let mut recorder = Self {
+            mutations: VecDeque::new(),
+            capacity: capacity.max(1),
+            cursor: 0,
+            next_sequence: 0,
+        };
+        recorder.push(INITIAL_SOURCE.into(), timestamp_ms, vec![], initial);
+        recorder
+    }
+
+    /// Record a mutation, discarding any steps after the cursor.
+    pub fn record(&mut self, source: &str, timestamp_ms: u64, state: &GlobalState) {
+        self.mutations.truncate(self.cursor + 1);
+        let changed = self
+            .mutations
+            .back()
+            .map(|previous| changed_fields(&previous.state, state))
+            .unwrap_or_default();
+        self.push(source.to_string(), timestamp_ms, changed, state.clone());
+    }
+
+    fn push(
+        &mut self,
+        source: String,
+        timestamp_ms: u64,
+        changed: Vec<String>,
+        state: GlobalState,
+    ) {
+        self.mutations.push_back(StateMutation {
+            sequence: self.next_sequence,
+            source,
+            timestamp_ms,
+            changed,
+            state,
+        });
+        self.next_sequence += 1;
+        if self.mutations.len() > self.capacity {
+            self.mutations.pop_front();
+        }
+        self.cursor = self.mutations.len() - 1;
+    }
+
+    pub fn current(&self) -> Option<&StateMutation> {
+        self.mutations.get(self.cursor)
+    }
+
+    pub fn step_back(&mut self) -> Option<&StateMutation> {
+        self.cursor = self.cursor.checked_sub(1)?;
+        self.current()
+    }
+
+    pub fn step_forward(&mut self) -> Option<&StateMutation> {
+        if self.cursor + 1 >= self.mutations.len() {
+            return None;
+        }
+        self.cursor += 1;
+        self.current()
+    }
+
+    /// Move the cursor to a recorded sequence number.
+    pub fn jump_to(&mut self, sequence: u64) -> Option<&StateMutation> {
+        self.cursor = self
+            .mutations
+            .iter()
+            .position(|mutation| mutation.sequence == sequence)?;
+        self.current()
+    }
+
+    pub fn mutations(&self) -> impl Iterator<Item = &StateMutation> {
+        self.mutations.iter()
+    }
+
+    pub fn trace(&self, exported_at_ms: u64) -> SessionTrace {
+        SessionTrace {
+            exported_at_ms,
+            cursor: self.current().map(|mutation| mutation.sequence),
+            mutations: self.mutations.iter().cloned().collect(),
+        }
+    }
+}
+
+fn changed_fields(previous: &GlobalState, next: &GlobalState) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(previous)), Ok(serde_json::Value::Object(next))) =
+        (serde_json::to_value(previous), serde_json::to_value(next))
+    else {
+        return vec![];
+    };
+    next.iter()
+        .filter(|(field, value)| previous.get(*field) != Some(value))
+        .map(|(field, _)| field.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorder_steps_and_truncates_on_new_mutations() {
+        let mut state = GlobalState::default();
+        let mut recorder = StateRecorder::new(state.clone(), 3, 1);
+
+        state.session.user_id = "first".into();
+        recorder.record("ci-console", 2, &state);
+        state.navigation.active_route = Some("/ci".into());
+        recorder.record("workflow-command-center", 3, &state);
+        assert_eq!(recorder.current().unwrap().changed, ["navigation"]);
+
+        let back = recorder.step_back().unwrap();
+        assert_eq!(back.source, "ci-console");
+        assert_eq!(back.state.navigation.active_route, None);
+        assert!(recorder.step_back().is_some());
+        assert!(recorder.step_back().is_none());
+        assert_eq!(recorder.step_forward().unwrap().sequence, 1);
+
+        state.session.user_id = "branch".into();
+        recorder.record("shell", 4, &state);
+        let sequences: Vec<_> = recorder.mutations().map(|m| m.sequence).collect();
+        assert_eq!(sequences, [0, 1, 3]);
+        assert!(recorder.step_forward().is_none());
+
+        recorder.record("shell", 5, &state);
+        assert_eq!(recorder.mutations().count(), 3);
+        assert_eq!(recorder.trace(6).cursor, Some(4));
+        assert!(recorder.jump_to(0).is_none());
+    }
+}
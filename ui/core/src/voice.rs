@@ -0,0 +1,282 @@
+//! Voice command routing for platforms with [`Capability::Voice`].
+//!
+//! Every [`ShellModule`] contributes [`VoiceGrammar`]s (by default its chat
+//! commands and their examples). The [`VoiceRouter`] matches a recognized
+//! transcript against those grammars and publishes a
+//! [`ShellEvent::VoiceIntentRecognized`] for the owning module; the shell then
+//! runs the intent's action like the equivalent chat command. Transcripts are
+//! only accepted while the context's [`PushToTalk`] is held.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat::{ChatAction, ChatCommandDescriptor};
+use crate::events::ShellEvent;
+use crate::module::ShellModule;
+use crate::Capability;
+
+/// Transcripts recognized with less confidence than this are ignored.
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.6;
+
+/// Wake phrases stripped from the start of a transcript.
+const WAKE_PHRASES: &[&str] = &["hey noa", "ok noa", "noa"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PushToTalkState {
+    /// The platform has no voice input.
+    Unavailable,
+    Idle,
+    Listening,
+}
+
+/// Push-to-talk switch shared by every clone of a [`crate::UIContext`].
+#[derive(Debug, Clone)]
+pub struct PushToTalk {
+    state: Arc<RwLock<PushToTalkState>>,
+}
+
+impl PushToTalk {
+    pub fn for_capabilities(capabilities: &[Capability]) -> Self {
+        let state = if capabilities.contains(&Capability::Voice) {
+            PushToTalkState::Idle
+        } else {
+            PushToTalkState::Unavailable
+        };
+        Self {
+            state: Arc::new(RwLock::new(state)),
+        }
+    }
+
+    pub fn state(&self) -> PushToTalkState {
+        *self.state.read().expect("push-to-talk poisoned")
+    }
+
+    /// Start listening. Fails on platforms without voice input.
+    pub fn press(&self) -> Result<(), String> {
+        let mut state = self.state.write().expect("push-to-talk poisoned");
+        if *state == PushToTalkState::Unavailable {
+            return Err("voice input is not available on this platform".into());
+        }
+        *state = PushToTalkState::Listening;
+        Ok(())
+    }
+
+    /// Enable or disable voice input on a platform that has it, e.g. when the
+    /// host loads or drops its voice models. Releases a held switch when disabled.
+    pub fn set_available(&self, available: bool) {
+        let mut state = self.state.write().expect("push-to-talk poisoned");
+        *state = match (*state, available) {
+            (_, false) => PushToTalkState::Unavailable,
+            (PushToTalkState::Unavailable, true) => PushToTalkState::Idle,
+            (current, true) => current,
+        };
+    }
+
+    pub fn release(&self) {
+        let mut state = self.state.write().expect("push-to-talk poisoned");
+        if *state == PushToTalkState::Listening {
+            *state = PushToTalkState::Idle;
+        }
+    }
+}
+
+/// Phrases that trigger one module intent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VoiceGrammar {
+    pub module_id: String,
+    /// Chat command the intent runs.
+    pub intent: String,
+    pub phrases: Vec<String>,
+    pub action: ChatAction,
+}
+
+impl VoiceGrammar {
+    /// Grammar accepting a chat command and its examples.
+    pub fn from_chat_command(
+        module_id: impl Into<String>,
+        descriptor: &ChatCommandDescriptor,
+    ) -> Self {
+        let mut phrases = vec![descriptor.command.clone()];
+        phrases.extend(descriptor.examples.iter().cloned());
+        Self {
+            module_id: module_id.into(),
+            intent: descriptor.command.clone(),
+            phrases,
+            action: descriptor.action.clone(),
+        }
+    }
+}
+
+/// A transcript matched to a module intent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VoiceIntent {
+    pub module_id: String,
+    pub intent: String,
+    pub transcript: String,
+    pub confidence: f32,
+    pub action: ChatAction,
+}
+
+/// Matches transcripts against the grammars registered by shell modules.
+#[derive(Debug, Clone)]
+pub struct VoiceRouter {
+    phrases: BTreeMap<String, VoiceGrammar>,
+    min_confidence: f32,
+}
+
+impl Default for VoiceRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VoiceRouter {
+    pub fn new() -> Self {
+        Self {
+            phrases: BTreeMap::new(),
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+        }
+    }
+
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Register a grammar. A phrase already claimed by another grammar keeps its
+    /// first owner.
+    pub fn register(&mut self, grammar: VoiceGrammar) {
+        for phrase in &grammar.phrases {
+            let phrase = normalize(phrase);
+            if !phrase.is_empty() {
+                self.phrases
+                    .entry(phrase)
+                    .or_insert_with(|| grammar.clone());
+            }
+        }
+    }
+
+    pub fn register_module(&mut self, module: &dyn ShellModule) {
+        for grammar in module.voice_grammar() {
+            self.register(grammar);
+        }
+    }
+
+    /// Every phrase the router understands, normalized and sorted.
+    pub fn phrases(&self) -> Vec<String> {
+        self.phrases.keys().cloned().collect()
+    }
+
+    pub fn recognize(&self, transcript: &str, confidence: f32) -> Option<VoiceIntent> {
+        if confidence < self.min_confidence {
+            return None;
+        }
+        let grammar = self.phrases.get(&normalize(transcript))?;
+        Some(VoiceIntent {
+            module_id: grammar.module_id.clone(),
+            intent: grammar.intent.clone(),
+            transcript: transcript.to_string(),
+            confidence,
+            action: grammar.action.clone(),
+        })
+    }
+
+    /// Recognize a transcript heard while push-to-talk is held and publish the intent.
+    pub fn route(
+        &self,
+        push_to_talk: &PushToTalk,
+        transcript: &str,
+        confidence: f32,
+        emit: &dyn Fn(ShellEvent),
+    ) -> Result<VoiceIntent, String> {
+        match push_to_talk.state() {
+            PushToTalkState::Listening => {}
+            PushToTalkState::Idle => return Err("push-to-talk is not engaged".into()),
+            PushToTalkState::Unavailable => {
+                return Err("voice input is not available on this platform".into())
+            }
+        }
+        let intent = self
+            .recognize(transcript, confidence)
+            .ok_or_else(|| format!("no voice command matches '{}'", transcript))?;
+        emit(ShellEvent::VoiceIntentRecognized {
+            module_id: intent.module_id.clone(),
+            intent: intent.intent.clone(),
+            transcript: intent.transcript.clone(),
+        });
+        Ok(intent)
+    }
+}
+
+/// Lowercase, drop punctuation, collapse whitespace, and strip a wake phrase.
+fn normalize(phrase: &str) -> String {
+    let cleaned: String = phrase
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '/' {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    let words = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    for wake in WAKE_PHRASES {
+        if let Some(rest) = words.strip_prefix(wake) {
+            if let Some(rest) = rest.strip_prefix(' ') {
+                return rest.to_string();
+            }
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::module::CiModule;
+
+    #[test]
+    fn router_matches_module_grammars_while_listening() {
+        let mut router = VoiceRouter::new();
+        router.register_module(&CiModule::new());
+        assert!(router.phrases().contains(&"open ci".to_string()));
+
+        let published = Mutex::new(vec![]);
+        let emit = |event: ShellEvent| published.lock().unwrap().push(event);
+        let push_to_talk = PushToTalk::for_capabilities(&[Capability::Voice]);
+        assert!(router
+            .route(&push_to_talk, "open ci", 0.9, &emit)
+            .unwrap_err()
+            .contains("not engaged"));
+
+        push_to_talk.press().unwrap();
+        let intent = router
+            .route(&push_to_talk, "Hey NOA, open CI!", 0.9, &emit)
+            .unwrap();
+        assert_eq!(intent.module_id, "ci-console");
+        assert_eq!(
+            intent.action,
+            ChatAction::Navigate {
+                route: "/ci".into()
+            }
+        );
+        assert!(matches!(
+            published.lock().unwrap().as_slice(),
+            [ShellEvent::VoiceIntentRecognized { module_id, .. }] if module_id == "ci-console"
+        ));
+        assert!(router.route(&push_to_talk, "open ci", 0.2, &emit).is_err());
+        assert!(router
+            .route(&push_to_talk, "open the pod bay doors", 0.9, &emit)
+            .is_err());
+
+        let keyboard_only = PushToTalk::for_capabilities(&[Capability::Keyboard]);
+        assert!(keyboard_only.press().is_err());
+        assert_eq!(keyboard_only.state(), PushToTalkState::Unavailable);
+    }
+}
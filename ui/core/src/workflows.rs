@@ -1,3 +1,5 @@
+pub mod composer;
+
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -0,0 +1,446 @@
+//! Drag-and-drop composition of engine workflow definitions.
+//!
+//! The workflow builder canvas edits a [`WorkflowComposer`]: stages are nodes
+//! placed on the canvas, dependencies are edges drawn between them, and each stage
+//! holds tasks assigned to agents, roles, and tools picked from a
+//! [`ComposerPalette`] built from the engine's registries.
+//! [`WorkflowComposer::compose`] emits a [`noa_workflow::Workflow`] with stages in
+//! dependency order, [`WorkflowComposer::validate`] checks it with
+//! [`WorkflowEngine::validate`], and [`WorkflowComposer::submission`] prepares the
+//! `POST /v1/workflows` request served by `server/api`.
+
+use std::collections::HashMap;
+
+use noa_agents::ToolSpec;
+use noa_workflow::{
+    ResourceRequirements, SandboxSpec, Stage, StageCacheControl, StageContract, StageType, Task,
+    ToolRequirement, Workflow as EngineWorkflow, WorkflowEngine,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// An agent the builder offers for task assignment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgentOption {
+    pub agent_id: String,
+    pub name: String,
+    pub role: String,
+}
+
+/// Agents and tools available to the builder.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposerPalette {
+    pub agents: Vec<AgentOption>,
+    pub tools: Vec<ToolSpec>,
+}
+
+impl ComposerPalette {
+    /// Snapshot the engine's agent and tool registries.
+    pub fn from_engine(engine: &WorkflowEngine) -> Self {
+        let mut agents: Vec<AgentOption> = engine
+            .agent_registry()
+            .all()
+            .into_iter()
+            .map(|agent| AgentOption {
+                agent_id: agent.agent_id,
+                name: agent.name,
+                role: agent.role,
+            })
+            .collect();
+        agents.sort_by(|a, b| a.agent_id.cmp(&b.agent_id));
+        Self {
+            agents,
+            tools: engine.list_tools(),
+        }
+    }
+
+    /// Distinct non-empty agent roles, sorted.
+    pub fn roles(&self) -> Vec<String> {
+        let mut roles: Vec<String> = self
+            .agents
+            .iter()
+            .filter(|agent| !agent.role.is_empty())
+            .map(|agent| agent.role.clone())
+            .collect();
+        roles.sort();
+        roles.dedup();
+        roles
+    }
+
+    pub fn agent(&self, agent_id: &str) -> Option<&AgentOption> {
+        self.agents.iter().find(|agent| agent.agent_id == agent_id)
+    }
+
+    pub fn tool(&self, capability: &str) -> Option<&ToolSpec> {
+        self.tools.iter().find(|tool| tool.capability == capability)
+    }
+}
+
+/// Where a stage node sits on the builder canvas.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct CanvasPosition {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl CanvasPosition {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+/// A stage on the canvas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageNode {
+    pub name: String,
+    pub stage_type: StageType,
+    pub position: CanvasPosition,
+    pub depends_on: Vec<String>,
+    pub tasks: Vec<Task>,
+}
+
+/// Request that submits a composed workflow to the API server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowSubmission {
+    pub method: String,
+    pub endpoint: String,
+    pub body: Value,
+}
+
+/// Editable workflow definition backing the builder canvas.
+#[derive(Debug, Clone)]
+pub struct WorkflowComposer {
+    name: String,
+    version: String,
+    palette: ComposerPalette,
+    nodes: Vec<StageNode>,
+}
+
+impl WorkflowComposer {
+    pub fn new(name: impl Into<String>, palette: ComposerPalette) -> Self {
+        Self {
+            name: name.into(),
+            version: "1.0".into(),
+            palette,
+            nodes: vec![],
+        }
+    }
+
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    pub fn palette(&self) -> &ComposerPalette {
+        &self.palette
+    }
+
+    pub fn stages(&self) -> &[StageNode] {
+        &self.nodes
+    }
+
+    /// Drop a new stage onto the canvas.
+    pub fn add_stage(
+        &mut self,
+        name: impl Into<String>,
+        stage_type: StageType,
+        position: CanvasPosition,
+    ) -> Result<(), String> {
+        let name = name.into();
+        if name.trim().is_empty() {
+            return Err("stage name is empty".into());
+        }
+        if self.node(&name).is_some() {
+            return Err(format!("stage '{}' already exists", name));
+        }
+        self.nodes.push(StageNode {
+            name,
+            stage_type,
+            position,
+            depends_on: vec![],
+            tasks: vec![],
+        });
+        Ok(())
+    }
+
+    /// Drag a stage to a new canvas position.
+    pub fn move_stage(&mut self, name: &str, position: CanvasPosition) -> Result<(), String> {
+        self.node_mut(name)?.position = position;
+        Ok(())
+    }
+
+    /// Remove a stage and every dependency edge touching it.
+    pub fn remove_stage(&mut self, name: &str) -> Result<(), String> {
+        let index = self
+            .nodes
+            .iter()
+            .position(|node| node.name == name)
+            .ok_or_else(|| format!("unknown stage '{}'", name))?;
+        self.nodes.remove(index);
+        for node in &mut self.nodes {
+            node.depends_on.retain(|dependency| dependency != name);
+        }
+        Ok(())
+    }
+
+    /// Draw an edge making `stage` depend on `dependency`.
+    pub fn connect(&mut self, dependency: &str, stage: &str) -> Result<(), String> {
+        if self.node(dependency).is_none() {
+            return Err(format!("unknown stage '{}'", dependency));
+        }
+        if dependency == stage || self.depends_on(dependency, stage) {
+            return Err(format!(
+                "connecting '{}' to '{}' would create a cycle",
+                dependency, stage
+            ));
+        }
+        let node = self.node_mut(stage)?;
+        if !node
+            .depends_on
+            .iter()
+            .any(|existing| existing == dependency)
+        {
+            node.depends_on.push(dependency.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn disconnect(&mut self, dependency: &str, stage: &str) -> Result<(), String> {
+        self.node_mut(stage)?
+            .depends_on
+            .retain(|existing| existing != dependency);
+        Ok(())
+    }
+
+    /// Add a task run by an agent from the palette, returning its index in the stage.
+    pub fn assign_agent(
+        &mut self,
+        stage: &str,
+        agent_id: &str,
+        action: impl Into<String>,
+    ) -> Result<usize, String> {
+        let agent = self
+            .palette
+            .agent(agent_id)
+            .ok_or_else(|| format!("agent '{}' is not in the registry", agent_id))?
+            .clone();
+        let node = self.node_mut(stage)?;
+        node.tasks.push(Task {
+            agent: agent.agent_id,
+            action: action.into(),
+            parameters: HashMap::new(),
+            agent_role: (!agent.role.is_empty()).then_some(agent.role),
+            tool_requirements: vec![],
+            resources: ResourceRequirements::default(),
+            sandbox: SandboxSpec::default(),
+        });
+        Ok(node.tasks.len() - 1)
+    }
+
+    /// Override the role a task runs under.
+    pub fn assign_role(&mut self, stage: &str, task: usize, role: &str) -> Result<(), String> {
+        if !self.palette.roles().iter().any(|known| known == role) {
+            return Err(format!(
+                "role '{}' is not held by any registered agent",
+                role
+            ));
+        }
+        self.task_mut(stage, task)?.agent_role = Some(role.to_string());
+        Ok(())
+    }
+
+    /// Require a tool from the palette for a task.
+    pub fn require_tool(
+        &mut self,
+        stage: &str,
+        task: usize,
+        capability: &str,
+    ) -> Result<(), String> {
+        let tool = self
+            .palette
+            .tool(capability)
+            .ok_or_else(|| format!("tool '{}' is not in the registry", capability))?;
+        let requirement = ToolRequirement {
+            name: tool.name.clone(),
+            capability: tool.capability.clone(),
+            optional: false,
+            parameters: Value::Null,
+        };
+        let task = self.task_mut(stage, task)?;
+        if !task
+            .tool_requirements
+            .iter()
+            .any(|existing| existing.capability == requirement.capability)
+        {
+            task.tool_requirements.push(requirement);
+        }
+        Ok(())
+    }
+
+    /// Build the engine definition. Stages are ordered so every dependency comes
+    /// first; independent stages keep their left-to-right canvas order.
+    pub fn compose(&self) -> Result<EngineWorkflow, String> {
+        let mut pending: Vec<&StageNode> = self.nodes.iter().collect();
+        pending.sort_by(|a, b| a.position.x.total_cmp(&b.position.x));
+        let mut stages: Vec<Stage> = Vec::with_capacity(pending.len());
+        while !pending.is_empty() {
+            let ready = pending
+                .iter()
+                .position(|node| {
+                    node.depends_on
+                        .iter()
+                        .all(|dependency| stages.iter().any(|stage| &stage.name == dependency))
+                })
+                .ok_or_else(|| {
+                    format!(
+                        "stages {} have unresolved dependencies",
+                        pending
+                            .iter()
+                            .map(|node| format!("'{}'", node.name))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })?;
+            let node = pending.remove(ready);
+            stages.push(Stage {
+                name: node.name.clone(),
+                stage_type: node.stage_type.clone(),
+                depends_on: node.depends_on.clone(),
+                tasks: node.tasks.clone(),
+                compensation: vec![],
+                cache: StageCacheControl::default(),
+                contract: StageContract::default(),
+            });
+        }
+        Ok(EngineWorkflow {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            stages,
+        })
+    }
+
+    /// Compose the definition and check it with the engine without loading it.
+    pub fn validate(&self, engine: &WorkflowEngine) -> Result<EngineWorkflow, String> {
+        let workflow = self.compose()?;
+        engine.validate(&workflow)?;
+        Ok(workflow)
+    }
+
+    /// Validate and build the request that submits the definition to the API server.
+    pub fn submission(
+        &self,
+        engine: &WorkflowEngine,
+        base_url: &str,
+    ) -> Result<WorkflowSubmission, String> {
+        let workflow = self.validate(engine)?;
+        let body = serde_json::to_value(&workflow)
+            .map_err(|err| format!("failed to serialise workflow: {}", err))?;
+        Ok(WorkflowSubmission {
+            method: "POST".into(),
+            endpoint: format!("{}/v1/workflows", base_url.trim_end_matches('/')),
+            body,
+        })
+    }
+
+    fn node(&self, name: &str) -> Option<&StageNode> {
+        self.nodes.iter().find(|node| node.name == name)
+    }
+
+    fn node_mut(&mut self, name: &str) -> Result<&mut StageNode, String> {
+        self.nodes
+            .iter_mut()
+            .find(|node| node.name == name)
+            .ok_or_else(|| format!("unknown stage '{}'", name))
+    }
+
+    fn task_mut(&mut self, stage: &str, task: usize) -> Result<&mut Task, String> {
+        self.node_mut(stage)?
+            .tasks
+            .get_mut(task)
+            .ok_or_else(|| format!("stage '{}' has no task {}", stage, task))
+    }
+
+    /// Whether `stage` depends on `dependency`, directly or transitively.
+    fn depends_on(&self, stage: &str, dependency: &str) -> bool {
+        let mut stack = vec![stage];
+        let mut visited = Vec::new();
+        while let Some(current) = stack.pop() {
+            if visited.contains(&current) {
+                continue;
+            }
+            visited.push(current);
+            if let Some(node) = self.node(current) {
+                for next in &node.depends_on {
+                    if next == dependency {
+                        return true;
+                    }
+                    stack.push(next);
+                }
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette() -> ComposerPalette {
+        ComposerPalette {
+            agents: vec![AgentOption {
+                agent_id: "builder".into(),
+                name: "Builder".into(),
+                role: "worker".into(),
+            }],
+            tools: vec![ToolSpec::new("dispatch", "workflow.taskDispatch")],
+        }
+    }
+
+    #[test]
+    fn composer_orders_stages_by_dependency_and_rejects_cycles() {
+        let mut composer = WorkflowComposer::new("release", palette());
+        composer
+            .add_stage("test", StageType::Parallel, CanvasPosition::new(0.0, 0.0))
+            .unwrap();
+        composer
+            .add_stage(
+                "build",
+                StageType::Sequential,
+                CanvasPosition::new(200.0, 0.0),
+            )
+            .unwrap();
+        composer.connect("build", "test").unwrap();
+        assert!(composer
+            .connect("test", "build")
+            .unwrap_err()
+            .contains("cycle"));
+
+        let task = composer
+            .assign_agent("build", "builder", "compile")
+            .unwrap();
+        composer
+            .require_tool("build", task, "workflow.taskDispatch")
+            .unwrap();
+        assert!(composer
+            .require_tool("build", task, "workflow.unknown")
+            .is_err());
+        assert!(composer.assign_role("build", task, "auditor").is_err());
+        assert!(composer.assign_agent("build", "ghost", "compile").is_err());
+
+        let workflow = composer.compose().unwrap();
+        let names: Vec<_> = workflow
+            .stages
+            .iter()
+            .map(|stage| stage.name.as_str())
+            .collect();
+        assert_eq!(names, ["build", "test"]);
+        assert_eq!(
+            workflow.stages[0].tasks[0].agent_role.as_deref(),
+            Some("worker")
+        );
+
+        composer.remove_stage("build").unwrap();
+        assert!(composer.stages()[0].depends_on.is_empty());
+    }
+}
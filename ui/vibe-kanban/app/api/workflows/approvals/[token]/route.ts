@@ -0,0 +1,25 @@
+import { NextResponse } from "next/server";
+
+import { submitApproval, type ApprovalSubmission } from "@/server/workflow-approvals";
+
+export const dynamic = "force-dynamic";
+
+export async function POST(
+  request: Request,
+  { params }: { params: { token: string } },
+): Promise<NextResponse> {
+  const authorization = request.headers.get("authorization");
+  if (!authorization) {
+    return NextResponse.json({ message: "approvals require an authenticated approver" }, { status: 401 });
+  }
+
+  let approval: ApprovalSubmission;
+  try {
+    approval = (await request.json()) as ApprovalSubmission;
+  } catch {
+    return NextResponse.json({ message: "approval payload must be JSON" }, { status: 400 });
+  }
+
+  const result = await submitApproval(params.token, approval, authorization);
+  return NextResponse.json(result.body, { status: result.status });
+}
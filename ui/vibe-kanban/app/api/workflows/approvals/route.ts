@@ -0,0 +1,15 @@
+import { NextResponse } from "next/server";
+
+import { listPendingApprovals } from "@/server/workflow-approvals";
+
+export const dynamic = "force-dynamic";
+
+export async function GET(): Promise<NextResponse> {
+  try {
+    const approvals = await listPendingApprovals();
+    return NextResponse.json({ approvals });
+  } catch (error) {
+    const message = error instanceof Error ? error.message : "Failed to load pending approvals";
+    return NextResponse.json({ message }, { status: 502 });
+  }
+}
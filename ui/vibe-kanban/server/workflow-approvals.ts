@@ -0,0 +1,73 @@
+export type ApprovalRequirement = {
+  role: string;
+  minimum_trust_score: number;
+  required_evidence_tags: string[];
+};
+
+export type PendingApproval = {
+  token: string;
+  workflow_id: string;
+  stage_id: string;
+  requirement: ApprovalRequirement;
+  requested_at: string;
+  expires_at: string;
+};
+
+/** Role and trust score are looked up server-side; the approver is the authenticated caller. */
+export type ApprovalSubmission = {
+  agent_id?: string;
+  evidence_tags?: string[];
+  evidence_references?: string[];
+};
+
+export type ApprovalResult = {
+  workflow_id: string;
+  stage_id: string;
+  state: string;
+};
+
+export type ApprovalClientOptions = {
+  uiApiBaseUrl?: string;
+  apiBaseUrl?: string;
+  fetchImpl?: typeof fetch;
+};
+
+const DEFAULT_UI_API = process.env.UI_API_URL ?? process.env.NEXT_PUBLIC_UI_API ?? "http://localhost:8787";
+const DEFAULT_API = process.env.NOA_API_URL ?? "http://localhost:8080";
+
+function resolveClient(options: ApprovalClientOptions) {
+  return {
+    fetchImpl: options.fetchImpl ?? fetch,
+    baseUrl: (options.uiApiBaseUrl ?? DEFAULT_UI_API).replace(/\/$/, ""),
+    apiBaseUrl: (options.apiBaseUrl ?? DEFAULT_API).replace(/\/$/, ""),
+  };
+}
+
+export async function listPendingApprovals(options: ApprovalClientOptions = {}): Promise<PendingApproval[]> {
+  const { fetchImpl, baseUrl } = resolveClient(options);
+  const response = await fetchImpl(`${baseUrl}/ui/workflows/approvals`);
+  if (!response.ok) {
+    const errorBody = await response.text().catch(() => "unknown");
+    throw new Error(`Failed to load pending approvals (${response.status}): ${errorBody}`);
+  }
+  return (await response.json()) as PendingApproval[];
+}
+
+export async function submitApproval(
+  token: string,
+  approval: ApprovalSubmission,
+  authorization: string,
+  options: ApprovalClientOptions = {},
+): Promise<{ status: number; body: ApprovalResult | { message: string } }> {
+  const { fetchImpl, apiBaseUrl } = resolveClient(options);
+  const response = await fetchImpl(`${apiBaseUrl}/v1/workflows/approvals/${encodeURIComponent(token)}`, {
+    method: "POST",
+    headers: {
+      "Content-Type": "application/json",
+      Authorization: authorization,
+    },
+    body: JSON.stringify(approval),
+  });
+  const body = await response.json().catch(() => ({ message: "invalid response from ui api" }));
+  return { status: response.status, body };
+}
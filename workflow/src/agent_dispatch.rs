@@ -1,13 +1,17 @@
 use std::collections::HashMap;
-use std::sync::{Arc, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 
+use noa_agents::memory::{AgentMemoryError, AgentMemoryStore};
 use noa_agents::registry::AgentRegistry;
+use noa_agents::trace::{ExecutionTrace, SpanKind, TraceError, TraceRecorder, TraceStore};
 use noa_agents::unified_types::AgentMetadata;
 use noa_agents::AgentFactory;
+use noa_core::hardware::{detect_hardware_profile, HardwareProfile};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
 
+use crate::placement::{PlacementDecision, QueuedTask};
 use crate::Task;
 use noa_core::scorekeeper::{MetricStatus, ScopeDirective, Scorekeeper};
 
@@ -17,8 +21,20 @@ pub enum AgentDispatchError {
     AgentNotFound(String),
     #[error("failed to instantiate agent: {0}")]
     AgentFactory(String),
+    #[error("task for agent '{agent}' queued until resources are available: {reason}")]
+    Queued { agent: String, reason: String },
+    #[error("external agent '{0}' has not passed its security scan")]
+    NotDispatchable(String),
+    #[error("failed to load agent memory: {0}")]
+    Memory(#[from] AgentMemoryError),
+    #[error("failed to read execution trace: {0}")]
+    Trace(#[from] TraceError),
 }
 
+/// Task parameter holding the agent memory injected at dispatch.
+pub const MEMORY_CONTEXT_KEY: &str = "memory";
+const MEMORY_CONTEXT_LIMIT: usize = 8;
+
 #[derive(Debug, Clone, Deserialize)]
 struct RoleMapping {
     agent_id: String,
@@ -79,11 +95,30 @@ pub struct TaskDispatchReceipt {
     pub task: Task,
     pub output: Value,
     pub tool_receipts: Vec<ToolExecutionReceipt>,
+    #[serde(default)]
+    pub placement: Option<PlacementDecision>,
+    /// Execution trace persisted for this dispatch, when the dispatcher keeps traces.
+    #[serde(default)]
+    pub trace_id: Option<String>,
+}
+
+/// What dispatching a task would do, without creating an agent instance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskDispatchPlan {
+    pub agent_metadata: AgentMetadata,
+    pub task: Task,
+    pub tool_receipts: Vec<ToolExecutionReceipt>,
+    pub placement: PlacementDecision,
 }
 
 pub struct AgentDispatcher {
     registry: Arc<AgentRegistry>,
     factory: Arc<AgentFactory>,
+    hardware: RwLock<Option<HardwareProfile>>,
+    queue: Mutex<Vec<QueuedTask>>,
+    memory: Option<Arc<AgentMemoryStore>>,
+    traces: Option<Arc<TraceStore>>,
+    tool_attempts: u32,
 }
 
 impl AgentDispatcher {
@@ -92,7 +127,86 @@ impl AgentDispatcher {
     }
 
     pub fn with_handles(registry: Arc<AgentRegistry>, factory: Arc<AgentFactory>) -> Self {
-        Self { registry, factory }
+        Self {
+            registry,
+            factory,
+            hardware: RwLock::new(None),
+            queue: Mutex::new(Vec::new()),
+            memory: None,
+            traces: None,
+            tool_attempts: 1,
+        }
+    }
+
+    /// Inject each agent's relevant memory into task parameters under [`MEMORY_CONTEXT_KEY`].
+    pub fn with_memory(mut self, memory: Arc<AgentMemoryStore>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    pub fn memory(&self) -> Option<Arc<AgentMemoryStore>> {
+        self.memory.clone()
+    }
+
+    /// Persist a step-level trace of every dispatch, successful or not.
+    pub fn with_traces(mut self, traces: Arc<TraceStore>) -> Self {
+        self.traces = Some(traces);
+        self
+    }
+
+    /// Try failing tool handlers up to `attempts` times per dispatch.
+    pub fn with_tool_attempts(mut self, attempts: u32) -> Self {
+        self.tool_attempts = attempts.max(1);
+        self
+    }
+
+    /// Load a persisted dispatch trace, e.g. the one named by a receipt's `trace_id`.
+    pub fn trace(&self, trace_id: &str) -> Result<Option<ExecutionTrace>, AgentDispatchError> {
+        let Some(traces) = &self.traces else {
+            return Ok(None);
+        };
+        match traces.load(trace_id) {
+            Ok(trace) => Ok(Some(trace)),
+            Err(TraceError::NotFound(_)) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Place tasks against a fixed hardware profile instead of probing the host.
+    pub fn with_hardware_profile(self, profile: HardwareProfile) -> Self {
+        self.set_hardware_profile(profile);
+        self
+    }
+
+    /// Replace the hardware profile used for placement, e.g. after resources were freed.
+    pub fn set_hardware_profile(&self, profile: HardwareProfile) {
+        *self.hardware.write().unwrap() = Some(profile);
+    }
+
+    /// Hardware profile used for placement, detected on first use.
+    pub fn hardware_profile(&self) -> HardwareProfile {
+        if let Some(profile) = self.hardware.read().unwrap().as_ref() {
+            return profile.clone();
+        }
+        self.hardware
+            .write()
+            .unwrap()
+            .get_or_insert_with(detect_hardware_profile)
+            .clone()
+    }
+
+    /// Tasks waiting for host resources.
+    pub fn queued_tasks(&self) -> Vec<QueuedTask> {
+        self.queue.lock().unwrap().clone()
+    }
+
+    /// Retry queued tasks; those that still do not fit return to the queue.
+    pub fn dispatch_queued(&self) -> Vec<Result<TaskDispatchReceipt, AgentDispatchError>> {
+        let queued = std::mem::take(&mut *self.queue.lock().unwrap());
+        queued
+            .into_iter()
+            .map(|entry| self.dispatch(&entry.task))
+            .collect()
     }
 
     pub fn registry(&self) -> Arc<AgentRegistry> {
@@ -104,88 +218,277 @@ impl AgentDispatcher {
     }
 
     pub fn dispatch(&self, task: &Task) -> Result<TaskDispatchReceipt, AgentDispatchError> {
-        let (allowed_optional, directive) = compute_trust_guardrails(&task.tool_requirements);
-        let mut optional_budget = allowed_optional;
+        let recorder = TraceRecorder::new(&task.agent, &task.action);
+        let mut result = self.dispatch_traced(task, &recorder);
+        let trace = recorder.finish(result.as_ref().err().map(ToString::to_string));
+        if let Some(traces) = &self.traces {
+            match traces.save(&trace) {
+                Ok(_) => {
+                    if let Ok(receipt) = result.as_mut() {
+                        receipt.trace_id = Some(trace.trace_id);
+                    }
+                }
+                Err(err) => println!(
+                    "[WORKFLOW] Failed to persist trace for {}::{}: {}",
+                    task.agent, task.action, err
+                ),
+            }
+        }
+        result
+    }
+
+    fn dispatch_traced(
+        &self,
+        task: &Task,
+        trace: &TraceRecorder,
+    ) -> Result<TaskDispatchReceipt, AgentDispatchError> {
+        let metadata = trace.in_span(None, SpanKind::ResolveAgent, "resolve_agent", |span| {
+            let metadata = self.resolve_agent_metadata(task)?;
+            trace.set_attribute(span, "agent_id", metadata.agent_id.as_str());
+            Ok::<_, AgentDispatchError>(metadata)
+        })?;
+        trace.set_agent(metadata.agent_id.as_str());
+        let placement = trace.in_span(None, SpanKind::Placement, "placement", |span| {
+            let placement = self.place(task);
+            trace.attach(
+                span,
+                "decision",
+                serde_json::to_value(&placement).unwrap_or_default(),
+            );
+            if placement.is_placed() {
+                return Ok(placement);
+            }
+            let reason = placement.reasons.join("; ");
+            self.queue
+                .lock()
+                .unwrap()
+                .push(QueuedTask::new(task.clone(), placement));
+            Err(AgentDispatchError::Queued {
+                agent: task.agent.clone(),
+                reason,
+            })
+        })?;
+        let task = &trace.in_span(None, SpanKind::Memory, "memory_context", |span| {
+            let task = self.with_memory_context(task, &metadata)?;
+            if let Some(records) = task.parameters.get(MEMORY_CONTEXT_KEY) {
+                trace.attach(span, MEMORY_CONTEXT_KEY, records.clone());
+            }
+            Ok::<_, AgentDispatchError>(task)
+        })?;
+
+        let instance_id = trace.in_span(None, SpanKind::Instantiate, "create_agent", |span| {
+            let instance_id = self
+                .factory
+                .create_agent(
+                    metadata.name.clone(),
+                    metadata.agent_type.clone(),
+                    metadata.language.clone(),
+                    true,
+                )
+                .map_err(|err| AgentDispatchError::AgentFactory(err.to_string()))?;
+            trace.set_attribute(span, "instance_id", instance_id.as_str());
+            Ok::<_, AgentDispatchError>(instance_id)
+        })?;
+
+        let tool_receipts = self.check_tool_requirements(task, &metadata, Some(trace));
+
+        let mut overall_output = Value::Null;
+        if tool_receipts
+            .iter()
+            .all(|receipt| !matches!(receipt.status, ToolExecutionStatus::Failed))
+        {
+            overall_output = serde_json::json!({
+                "agent": metadata.agent_id,
+                "status": "completed",
+            });
+        }
+
+        Ok(TaskDispatchReceipt {
+            agent_metadata: metadata,
+            agent_instance_id: instance_id,
+            task: task.clone(),
+            output: overall_output,
+            tool_receipts,
+            placement: Some(placement),
+            trace_id: None,
+        })
+    }
+
+    /// Resolve the agent and check tool requirements without instantiating anything.
+    pub fn plan(&self, task: &Task) -> Result<TaskDispatchPlan, AgentDispatchError> {
         let metadata = self.resolve_agent_metadata(task)?;
+        let task = &self.with_memory_context(task, &metadata)?;
+        let tool_receipts = self.check_tool_requirements(task, &metadata, None);
+        Ok(TaskDispatchPlan {
+            agent_metadata: metadata,
+            task: task.clone(),
+            tool_receipts,
+            placement: self.place(task),
+        })
+    }
 
-        let instance_id = self
-            .factory
-            .create_agent(
-                metadata.name.clone(),
-                metadata.agent_type.clone(),
-                metadata.language.clone(),
-                true,
-            )
-            .map_err(|err| AgentDispatchError::AgentFactory(err.to_string()))?;
+    /// Copy of `task` carrying the agent's memory most relevant to its action and
+    /// string parameters. Agents whose ids cannot name a memory directory get none.
+    fn with_memory_context(
+        &self,
+        task: &Task,
+        metadata: &AgentMetadata,
+    ) -> Result<Task, AgentDispatchError> {
+        let mut task = task.clone();
+        let Some(memory) = &self.memory else {
+            return Ok(task);
+        };
+        let query = std::iter::once(task.action.as_str())
+            .chain(task.parameters.values().filter_map(Value::as_str))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let records = match memory.relevant(&metadata.agent_id, &query, MEMORY_CONTEXT_LIMIT) {
+            Ok(records) => records,
+            Err(AgentMemoryError::InvalidAgentId(_)) => return Ok(task),
+            Err(err) => return Err(err.into()),
+        };
+        if !records.is_empty() {
+            task.parameters.insert(
+                MEMORY_CONTEXT_KEY.to_string(),
+                serde_json::to_value(records).unwrap_or_default(),
+            );
+        }
+        Ok(task)
+    }
+
+    fn place(&self, task: &Task) -> PlacementDecision {
+        if task.resources.is_empty() {
+            PlacementDecision::unconstrained()
+        } else {
+            PlacementDecision::evaluate(&task.resources, &self.hardware_profile())
+        }
+    }
 
+    /// Check each requirement against the agent's capabilities. With a trace, each
+    /// requirement is recorded as a tool-call span and tools with a registered handler
+    /// are invoked with the requirement's parameters.
+    fn check_tool_requirements(
+        &self,
+        task: &Task,
+        metadata: &AgentMetadata,
+        trace: Option<&TraceRecorder>,
+    ) -> Vec<ToolExecutionReceipt> {
+        let (allowed_optional, directive) = compute_trust_guardrails(&task.tool_requirements);
+        let mut optional_budget = allowed_optional;
         let mut tool_receipts = Vec::new();
         for requirement in &task.tool_requirements {
             if requirement.optional {
                 if optional_budget == 0 {
+                    let error = format!(
+                        "Optional capability '{}' gated by trust status {:?} (multiplier {:.2})",
+                        requirement.capability, directive.status, directive.optional_multiplier
+                    );
+                    if let Some(trace) = trace {
+                        let span =
+                            trace.start_span(None, SpanKind::ToolCall, &requirement.capability);
+                        trace.set_attribute(&span, "skipped", error.as_str());
+                        trace.end_span(&span, None);
+                    }
                     tool_receipts.push(ToolExecutionReceipt {
                         requirement: requirement.clone(),
                         status: ToolExecutionStatus::Skipped,
                         output: Value::Null,
-                        error: Some(format!(
-                            "Optional capability '{}' gated by trust status {:?} (multiplier {:.2})",
-                            requirement.capability, directive.status, directive.optional_multiplier
-                        )),
+                        error: Some(error),
                     });
                     continue;
                 }
                 optional_budget = optional_budget.saturating_sub(1);
             }
 
-            let status = if metadata
+            let held = metadata
                 .capabilities
                 .iter()
-                .any(|cap| requirement.matches(cap))
-            {
-                ToolExecutionStatus::Succeeded
-            } else if requirement.optional {
-                ToolExecutionStatus::Skipped
-            } else {
-                ToolExecutionStatus::Failed
-            };
-
-            let error = if matches!(status, ToolExecutionStatus::Failed) {
-                Some(format!(
-                    "Agent '{}' is missing required capability '{}'.",
-                    metadata.agent_id, requirement.capability
-                ))
-            } else {
-                None
+                .any(|cap| requirement.matches(cap));
+            let receipt = match trace {
+                Some(trace) if held => self.invoke_tool(trace, requirement),
+                _ => {
+                    let status = if held {
+                        ToolExecutionStatus::Succeeded
+                    } else if requirement.optional {
+                        ToolExecutionStatus::Skipped
+                    } else {
+                        ToolExecutionStatus::Failed
+                    };
+                    let error = matches!(status, ToolExecutionStatus::Failed).then(|| {
+                        format!(
+                            "Agent '{}' is missing required capability '{}'.",
+                            metadata.agent_id, requirement.capability
+                        )
+                    });
+                    if let Some(trace) = trace {
+                        let span =
+                            trace.start_span(None, SpanKind::ToolCall, &requirement.capability);
+                        trace.set_attribute(&span, "held", false);
+                        trace.end_span(&span, error.clone());
+                    }
+                    ToolExecutionReceipt {
+                        requirement: requirement.clone(),
+                        status,
+                        output: Value::Null,
+                        error,
+                    }
+                }
             };
-
-            tool_receipts.push(ToolExecutionReceipt {
-                requirement: requirement.clone(),
-                status,
-                output: Value::Null,
-                error,
-            });
+            tool_receipts.push(receipt);
         }
+        tool_receipts
+    }
 
-        let mut overall_output = Value::Null;
-        if tool_receipts
-            .iter()
-            .all(|receipt| !matches!(receipt.status, ToolExecutionStatus::Failed))
-        {
-            overall_output = serde_json::json!({
-                "agent": metadata.agent_id,
-                "status": "completed",
-            });
+    /// Run a held tool's handler through the trace; tools without one only need the
+    /// capability. Failed optional tools are reported as skipped.
+    fn invoke_tool(
+        &self,
+        trace: &TraceRecorder,
+        requirement: &ToolRequirement,
+    ) -> ToolExecutionReceipt {
+        let tools = self.registry.tools();
+        let invocable = tools
+            .get(&requirement.capability)
+            .is_some_and(|spec| spec.invocable);
+        let result = if invocable {
+            trace.invoke_tool(
+                None,
+                &tools,
+                &requirement.capability,
+                &requirement.parameters,
+                self.tool_attempts,
+            )
+        } else {
+            let span = trace.start_span(None, SpanKind::ToolCall, &requirement.capability);
+            trace.set_attribute(&span, "invoked", false);
+            trace.end_span(&span, None);
+            Ok(Value::Null)
+        };
+        let (status, output, error) = match result {
+            Ok(output) => (ToolExecutionStatus::Succeeded, output, None),
+            Err(err) if requirement.optional => (
+                ToolExecutionStatus::Skipped,
+                Value::Null,
+                Some(err.to_string()),
+            ),
+            Err(err) => (
+                ToolExecutionStatus::Failed,
+                Value::Null,
+                Some(err.to_string()),
+            ),
+        };
+        ToolExecutionReceipt {
+            requirement: requirement.clone(),
+            status,
+            output,
+            error,
         }
-
-        Ok(TaskDispatchReceipt {
-            agent_metadata: metadata,
-            agent_instance_id: instance_id,
-            task: task.clone(),
-            output: overall_output,
-            tool_receipts,
-        })
     }
 
-    fn resolve_agent_metadata(&self, task: &Task) -> Result<AgentMetadata, AgentDispatchError> {
+    pub(crate) fn resolve_agent_metadata(
+        &self,
+        task: &Task,
+    ) -> Result<AgentMetadata, AgentDispatchError> {
         if let Some(role) = task
             .agent_role
             .as_ref()
@@ -204,6 +507,7 @@ impl AgentDispatcher {
 
         self.find_agent(&task.agent)
             .ok_or_else(|| AgentDispatchError::AgentNotFound(task.agent.clone()))
+            .and_then(|metadata| self.ensure_dispatchable(metadata))
     }
 
     fn resolve_agent_by_role(
@@ -218,7 +522,8 @@ impl AgentDispatcher {
 
         let mut metadata = self
             .find_agent(&mapping.agent_id)
-            .ok_or_else(|| AgentDispatchError::AgentNotFound(mapping.agent_id.clone()))?;
+            .ok_or_else(|| AgentDispatchError::AgentNotFound(mapping.agent_id.clone()))
+            .and_then(|metadata| self.ensure_dispatchable(metadata))?;
         if let Some(description) = &mapping.description {
             metadata.description = description.clone();
         }
@@ -226,6 +531,18 @@ impl AgentDispatcher {
         Ok(metadata)
     }
 
+    /// Agents ingested from marketplace manifests stay listed but unusable until scanned.
+    fn ensure_dispatchable(
+        &self,
+        metadata: AgentMetadata,
+    ) -> Result<AgentMetadata, AgentDispatchError> {
+        if self.registry.is_dispatchable(&metadata.agent_id) {
+            Ok(metadata)
+        } else {
+            Err(AgentDispatchError::NotDispatchable(metadata.agent_id))
+        }
+    }
+
     fn find_agent(&self, identifier: &str) -> Option<AgentMetadata> {
         self.registry.get(identifier).or_else(|| {
             let name = identifier.to_lowercase();
@@ -249,8 +566,10 @@ fn compute_trust_guardrails(requirements: &[ToolRequirement]) -> (usize, ScopeDi
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{ResourceRequirements, SandboxSpec};
     use noa_agents::AgentFactory;
     use noa_core::scorekeeper::ScoreInputs;
+    use noa_memory::MemoryRole;
     use serde_json::Value;
     use std::collections::HashMap;
     use std::path::Path;
@@ -344,6 +663,8 @@ mod tests {
             parameters: HashMap::new(),
             agent_role: None,
             tool_requirements: requirements.clone(),
+            resources: ResourceRequirements::default(),
+            sandbox: SandboxSpec::default(),
         };
 
         let receipt = dispatcher
@@ -384,6 +705,8 @@ mod tests {
             parameters: HashMap::new(),
             agent_role: Some("planner".to_string()),
             tool_requirements: Vec::new(),
+            resources: ResourceRequirements::default(),
+            sandbox: SandboxSpec::default(),
         };
 
         let receipt = dispatcher
@@ -398,4 +721,184 @@ mod tests {
             "role mapping description should propagate to metadata"
         );
     }
+
+    #[test]
+    fn dispatch_injects_relevant_agent_memory() {
+        let registry = AgentRegistry::new();
+        registry
+            .upsert_metadata(AgentMetadata::from_registry(
+                "Deployer".to_string(),
+                "deployer".to_string(),
+            ))
+            .expect("register deployer");
+        let dir = tempdir().unwrap();
+        let memory = Arc::new(AgentMemoryStore::new(dir.path()));
+        for note in ["canary rollout needs approval", "lunch order placed"] {
+            memory
+                .record("deployer", MemoryRole::Observation, note, Vec::new())
+                .unwrap();
+        }
+        let dispatcher =
+            AgentDispatcher::with_handles(Arc::new(registry), Arc::new(AgentFactory::new()))
+                .with_memory(memory);
+
+        let mut parameters = HashMap::new();
+        parameters.insert("strategy".to_string(), Value::from("canary"));
+        let task = Task {
+            agent: "deployer".to_string(),
+            action: "rollout".to_string(),
+            parameters,
+            agent_role: None,
+            tool_requirements: Vec::new(),
+            resources: ResourceRequirements::default(),
+            sandbox: SandboxSpec::default(),
+        };
+
+        let receipt = dispatcher.dispatch(&task).expect("dispatch succeeds");
+        let injected = receipt.task.parameters[MEMORY_CONTEXT_KEY]
+            .as_array()
+            .expect("memory injected");
+        assert_eq!(injected.len(), 1);
+        assert_eq!(injected[0]["content"], "canary rollout needs approval");
+        assert!(!task.parameters.contains_key(MEMORY_CONTEXT_KEY));
+    }
+
+    #[test]
+    fn dispatch_persists_step_trace_with_tool_retries() {
+        use noa_agents::trace::{SpanStatus, TraceStore};
+        use noa_agents::ToolSpec;
+
+        let registry = AgentRegistry::new();
+        let mut metadata = AgentMetadata::from_registry("Linter".to_string(), "linter".to_string());
+        metadata.capabilities.push("trace.probe".to_string());
+        registry.upsert_metadata(metadata).expect("register linter");
+        let tools = registry.tools();
+        tools
+            .register(ToolSpec::new("probe", "trace.probe"))
+            .expect("register probe tool");
+        let calls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let counter = Arc::clone(&calls);
+        tools
+            .set_handler(
+                "trace.probe",
+                Arc::new(move |_input: &Value| {
+                    match counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                        0 => Err("probe timed out".to_string()),
+                        _ => Ok(serde_json::json!({ "ok": true })),
+                    }
+                }),
+            )
+            .unwrap();
+        let dir = tempdir().unwrap();
+        let store = Arc::new(TraceStore::new(dir.path()));
+        let dispatcher =
+            AgentDispatcher::with_handles(Arc::new(registry), Arc::new(AgentFactory::new()))
+                .with_traces(Arc::clone(&store))
+                .with_tool_attempts(2);
+
+        let mut task = Task {
+            agent: "linter".to_string(),
+            action: "lint".to_string(),
+            parameters: HashMap::new(),
+            agent_role: None,
+            tool_requirements: vec![ToolRequirement {
+                name: "probe".to_string(),
+                capability: "trace.probe".to_string(),
+                optional: false,
+                parameters: serde_json::json!({ "path": "src" }),
+            }],
+            resources: ResourceRequirements::default(),
+            sandbox: SandboxSpec::default(),
+        };
+        let receipt = dispatcher.dispatch(&task).expect("dispatch succeeds");
+        assert_eq!(receipt.tool_receipts[0].output["ok"], true);
+        let trace_id = receipt.trace_id.expect("trace persisted");
+        let trace = dispatcher.trace(&trace_id).unwrap().expect("trace loads");
+        assert_eq!(trace.status, SpanStatus::Succeeded);
+        let steps: Vec<_> = trace.children(None).iter().map(|span| span.kind).collect();
+        assert_eq!(
+            steps,
+            [
+                SpanKind::ResolveAgent,
+                SpanKind::Placement,
+                SpanKind::Memory,
+                SpanKind::Instantiate,
+                SpanKind::ToolCall,
+            ]
+        );
+        let call = trace.children(None)[4];
+        assert_eq!(call.artifact("input").unwrap().value["path"], "src");
+        let attempts = trace.children(Some(&call.id));
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].status, SpanStatus::Failed);
+        assert!(attempts[1].artifact("output").is_some());
+
+        task.agent = "missing".to_string();
+        let err = dispatcher.dispatch(&task).expect_err("unknown agent");
+        let failed = store.list(Some("missing")).unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].error.as_deref(), Some(err.to_string().as_str()));
+        assert_eq!(failed[0].failures()[0].kind, SpanKind::ResolveAgent);
+    }
+
+    #[test]
+    fn dispatch_queues_tasks_until_resources_fit() {
+        use noa_core::hardware::{CpuProfile, GpuBackend, GpuProfile, MemoryProfile};
+
+        let host = |gpus: Vec<GpuProfile>| HardwareProfile {
+            cpu: CpuProfile {
+                brand: "test".to_string(),
+                vendor: "test".to_string(),
+                physical_cores: 8,
+                logical_cores: 8,
+                frequency_mhz: None,
+            },
+            memory: MemoryProfile {
+                total_bytes: 32 << 30,
+                available_bytes: 16 << 30,
+            },
+            gpus,
+            accelerators: Vec::new(),
+        };
+        let registry = AgentRegistry::new();
+        let metadata = AgentMetadata::from_registry("Trainer".to_string(), "Trainer".to_string());
+        registry
+            .upsert_metadata(metadata.clone())
+            .expect("register trainer agent");
+        let dispatcher =
+            AgentDispatcher::with_handles(Arc::new(registry), Arc::new(AgentFactory::new()))
+                .with_hardware_profile(host(Vec::new()));
+
+        let task = Task {
+            agent: metadata.agent_id.clone(),
+            action: "fine-tune".to_string(),
+            parameters: HashMap::new(),
+            agent_role: None,
+            tool_requirements: Vec::new(),
+            resources: ResourceRequirements {
+                gpu: true,
+                min_memory_gb: Some(8.0),
+                min_cpu_cores: None,
+            },
+            sandbox: SandboxSpec::default(),
+        };
+
+        let err = dispatcher.dispatch(&task).expect_err("no GPU on host");
+        assert!(matches!(err, AgentDispatchError::Queued { .. }), "{err}");
+        assert_eq!(dispatcher.queued_tasks().len(), 1);
+
+        dispatcher.set_hardware_profile(host(vec![GpuProfile {
+            name: "gpu-0".to_string(),
+            backend: GpuBackend::Nvidia,
+            memory_total_bytes: Some(24 << 30),
+            driver: None,
+        }]));
+        let results = dispatcher.dispatch_queued();
+        assert_eq!(results.len(), 1);
+        let receipt = results[0].as_ref().expect("queued task placed");
+        let placement = receipt.placement.as_ref().expect("placement recorded");
+        assert!(placement.is_placed());
+        assert_eq!(placement.host.as_ref().map(|host| host.gpu_count), Some(1));
+        assert!(dispatcher.queued_tasks().is_empty());
+    }
 }
@@ -0,0 +1,202 @@
+//! Human-in-the-loop approval gates.
+//!
+//! An `approval` stage pauses its workflow and issues a resume token bound to an
+//! [`AgentApprovalRequirement`]. The workflow resumes once an [`AgentApproval`]
+//! satisfying the requirement is registered against that token.
+
+use chrono::{DateTime, Duration, Utc};
+use noa_core::time::skew_monitor;
+use noa_core::utils::current_timestamp_millis;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How long a pending approval token stays valid.
+pub const APPROVAL_TOKEN_TTL_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentApprovalRequirement {
+    pub role: String,
+    pub minimum_trust_score: f32,
+    #[serde(default)]
+    pub required_evidence_tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentApproval {
+    pub role: String,
+    pub agent_id: String,
+    pub trust_score: f32,
+    #[serde(default)]
+    pub evidence_tags: Vec<String>,
+    #[serde(default)]
+    pub evidence_references: Vec<String>,
+    pub recorded_at: u64,
+}
+
+impl AgentApprovalRequirement {
+    pub fn is_satisfied_by(&self, approval: &AgentApproval) -> bool {
+        if approval.role != self.role {
+            return false;
+        }
+        if approval.trust_score + f32::EPSILON < self.minimum_trust_score {
+            return false;
+        }
+        self.required_evidence_tags.iter().all(|required| {
+            approval
+                .evidence_tags
+                .iter()
+                .any(|provided| provided == required)
+        })
+    }
+}
+
+/// An approval stage waiting for a qualifying approver.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PendingApproval {
+    pub token: String,
+    pub workflow_id: String,
+    pub stage_id: String,
+    pub requirement: AgentApprovalRequirement,
+    pub requested_at: String,
+    pub expires_at: String,
+}
+
+impl PendingApproval {
+    /// Issue a pending approval under a random resume token. The token is the only
+    /// credential a resume needs besides the approver's own, so it must not be guessable.
+    pub fn issue(workflow_id: &str, stage_id: &str, requirement: AgentApprovalRequirement) -> Self {
+        let now = Utc::now();
+        Self {
+            token: Uuid::new_v4().simple().to_string(),
+            workflow_id: workflow_id.to_string(),
+            stage_id: stage_id.to_string(),
+            requirement,
+            requested_at: now.to_rfc3339(),
+            expires_at: (now + Duration::hours(APPROVAL_TOKEN_TTL_HOURS)).to_rfc3339(),
+        }
+    }
+
+    /// Whether the token has expired, allowing for clock skew between the host that
+    /// issued it and this one.
+    pub fn is_expired(&self) -> bool {
+        let Ok(expires_at) = DateTime::parse_from_rfc3339(&self.expires_at) else {
+            return true;
+        };
+        let Ok(deadline_ms) = u128::try_from(expires_at.timestamp_millis()) else {
+            return true;
+        };
+        skew_monitor().deadline_passed(
+            "approval.validate",
+            &format!("{}::{}", self.workflow_id, self.stage_id),
+            deadline_ms,
+            current_timestamp_millis(),
+        )
+    }
+
+    /// Check an approval against the requirement, explaining any mismatch.
+    ///
+    /// `recorded_at` comes from the caller and is not fed to the drift detector: an
+    /// unauthenticated clock reading would widen every deadline in the process.
+    pub fn validate(&self, approval: &AgentApproval) -> Result<(), String> {
+        if self.is_expired() {
+            return Err(format!(
+                "approval token for {}::{} expired at {}",
+                self.workflow_id, self.stage_id, self.expires_at
+            ));
+        }
+        if self.requirement.is_satisfied_by(approval) {
+            Ok(())
+        } else {
+            Err(format!(
+                "approval from {} ({}, trust {:.2}) does not satisfy requirement for role {} with trust >= {:.2}",
+                approval.agent_id,
+                approval.role,
+                approval.trust_score,
+                self.requirement.role,
+                self.requirement.minimum_trust_score
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approval(role: &str, trust_score: f32, tags: &[&str]) -> AgentApproval {
+        AgentApproval {
+            role: role.to_string(),
+            agent_id: "reviewer-1".to_string(),
+            trust_score,
+            evidence_tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            evidence_references: Vec::new(),
+            recorded_at: 0,
+        }
+    }
+
+    #[test]
+    fn pending_approval_checks_role_trust_and_evidence() {
+        let pending = PendingApproval::issue(
+            "release",
+            "sign-off",
+            AgentApprovalRequirement {
+                role: "release-manager".to_string(),
+                minimum_trust_score: 0.8,
+                required_evidence_tags: vec!["changelog".to_string()],
+            },
+        );
+        assert!(!pending.is_expired());
+        let reissued = PendingApproval::issue("release", "sign-off", pending.requirement.clone());
+        assert_eq!(pending.token.len(), 32);
+        assert_ne!(pending.token, reissued.token);
+        assert!(pending
+            .validate(&approval("release-manager", 0.9, &["changelog"]))
+            .is_ok());
+        assert!(pending
+            .validate(&approval("release-manager", 0.5, &["changelog"]))
+            .is_err());
+        assert!(pending
+            .validate(&approval("developer", 0.9, &["changelog"]))
+            .is_err());
+        assert!(pending
+            .validate(&approval("release-manager", 0.9, &[]))
+            .is_err());
+
+        let mut skewed = pending.clone();
+        skewed.expires_at = (Utc::now() - Duration::seconds(5)).to_rfc3339();
+        assert!(skewed
+            .validate(&approval("release-manager", 0.9, &["changelog"]))
+            .is_ok());
+        assert!(skew_monitor()
+            .recent_corrections()
+            .iter()
+            .any(|correction| correction.subject == "release::sign-off"));
+
+        let mut stale = pending.clone();
+        stale.expires_at = (Utc::now() - Duration::minutes(10)).to_rfc3339();
+        assert!(stale
+            .validate(&approval("release-manager", 0.9, &["changelog"]))
+            .unwrap_err()
+            .contains("expired"));
+    }
+
+    #[test]
+    fn approval_timestamps_do_not_widen_deadlines() {
+        let pending = PendingApproval::issue(
+            "release",
+            "skewed-sign-off",
+            AgentApprovalRequirement {
+                role: "release-manager".to_string(),
+                minimum_trust_score: 0.8,
+                required_evidence_tags: Vec::new(),
+            },
+        );
+        let window = skew_monitor().validation_window_ms();
+        let mut skewed = approval("release-manager", 0.9, &[]);
+        skewed.recorded_at = 1;
+        for _ in 0..8 {
+            let _ = pending.validate(&skewed);
+        }
+        assert_eq!(skew_monitor().validation_window_ms(), window);
+    }
+}
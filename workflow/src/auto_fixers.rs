@@ -144,20 +144,19 @@ mod tests {
     use std::sync::Arc;
     use tempfile::TempDir;
 
+    use crate::context::ConfigContext;
     use crate::instrumentation::PipelineInstrumentation;
+    use crate::namespace::Namespace;
 
     fn coordinator() -> (AutoFixCoordinator, TempDir) {
         let temp_root = TempDir::new().expect("temp workflow root");
-        let previous = std::env::var_os("NOA_WORKFLOW_ROOT");
-        std::env::set_var("NOA_WORKFLOW_ROOT", temp_root.path());
         let instrumentation = Arc::new(
-            PipelineInstrumentation::new().expect("instrumentation bootstrap"),
+            PipelineInstrumentation::with_context(
+                &Namespace::default(),
+                ConfigContext::isolated().with_workflow_root(temp_root.path()),
+            )
+            .expect("instrumentation bootstrap"),
         );
-        if let Some(value) = previous {
-            std::env::set_var("NOA_WORKFLOW_ROOT", value);
-        } else {
-            std::env::remove_var("NOA_WORKFLOW_ROOT");
-        }
 
         (AutoFixCoordinator::new(instrumentation), temp_root)
     }
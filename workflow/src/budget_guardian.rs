@@ -265,27 +265,28 @@ fn numeric(value: &Value) -> Option<f64> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{StageType, Task};
+    use crate::{
+        ResourceRequirements, SandboxSpec, StageCacheControl, StageContract, StageType, Task,
+    };
     use serde_json::{json, Value};
     use std::collections::HashMap;
     use std::io::Write;
     use std::sync::Arc;
     use tempfile::{NamedTempFile, TempDir};
 
+    use crate::context::ConfigContext;
     use crate::instrumentation::PipelineInstrumentation;
+    use crate::namespace::Namespace;
 
     fn instrumentation_with_temp_root() -> (Arc<PipelineInstrumentation>, TempDir) {
         let temp_root = TempDir::new().expect("temp workflow root");
-        let previous = std::env::var_os("NOA_WORKFLOW_ROOT");
-        std::env::set_var("NOA_WORKFLOW_ROOT", temp_root.path());
         let instrumentation = Arc::new(
-            PipelineInstrumentation::new().expect("instrumentation bootstrap"),
+            PipelineInstrumentation::with_context(
+                &Namespace::default(),
+                ConfigContext::isolated().with_workflow_root(temp_root.path()),
+            )
+            .expect("instrumentation bootstrap"),
         );
-        if let Some(value) = previous {
-            std::env::set_var("NOA_WORKFLOW_ROOT", value);
-        } else {
-            std::env::remove_var("NOA_WORKFLOW_ROOT");
-        }
 
         (instrumentation, temp_root)
     }
@@ -320,6 +321,8 @@ mod tests {
                     parameters: sensitive_params,
                     agent_role: None,
                     tool_requirements: vec![],
+                    resources: ResourceRequirements::default(),
+                    sandbox: SandboxSpec::default(),
                 },
                 Task {
                     agent: "type".to_string(),
@@ -327,8 +330,13 @@ mod tests {
                     parameters: normal_params,
                     agent_role: None,
                     tool_requirements: vec![],
+                    resources: ResourceRequirements::default(),
+                    sandbox: SandboxSpec::default(),
                 },
             ],
+            compensation: vec![],
+            cache: StageCacheControl::default(),
+            contract: StageContract::default(),
         }
     }
 
@@ -0,0 +1,664 @@
+//! Concurrency limits for workflow and pipeline runs.
+//!
+//! A [`ConcurrencyGovernor`] caps how many runs execute at once, node-wide and per
+//! namespace, starting from limits sized for the host's [`HostClassification`].
+//! Runs over a limit wait in a queue and can report their position. When a
+//! hardware refresh shows memory pressure the limits are reduced until it subsides;
+//! runs already executing are never interrupted.
+//!
+//! The queue is weighted-fair across namespaces: each waiting run is tagged with a
+//! virtual finish time that advances by `1 / weight` per run its namespace has
+//! queued, and the smallest tag is admitted first, so a burst from one namespace
+//! interleaves with other namespaces' runs in proportion to their weights instead
+//! of starving them. [`RunPriority::Critical`] runs go ahead of every normal run.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Instant;
+
+use noa_core::hardware::{detect_hardware_profile, HardwareProfile, HostClassification};
+use serde::{Deserialize, Serialize};
+
+use crate::Namespace;
+
+/// Maximum number of runs allowed to execute at once.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConcurrencyLimits {
+    pub global: usize,
+    pub per_namespace: usize,
+}
+
+impl ConcurrencyLimits {
+    /// Defaults sized for the host tier.
+    pub fn for_host(classification: &HostClassification) -> Self {
+        match classification {
+            HostClassification::Minimal => Self {
+                global: 2,
+                per_namespace: 1,
+            },
+            HostClassification::Standard => Self {
+                global: 8,
+                per_namespace: 4,
+            },
+            HostClassification::Accelerated => Self {
+                global: 16,
+                per_namespace: 8,
+            },
+        }
+    }
+
+    fn scaled(&self, factor: f64) -> Self {
+        Self {
+            global: scale(self.global, factor),
+            per_namespace: scale(self.per_namespace, factor),
+        }
+    }
+}
+
+fn scale(limit: usize, factor: f64) -> usize {
+    ((limit as f64 * factor).floor() as usize).max(1)
+}
+
+/// When memory pressure reduces the limits, and by how much.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PressurePolicy {
+    /// Reduce limits once memory use reaches this fraction.
+    pub reduce_at: f64,
+    /// Restore limits once memory use falls below this fraction.
+    pub restore_below: f64,
+    /// Multiplier applied to every limit while reduced; limits never drop below one.
+    pub factor: f64,
+}
+
+impl Default for PressurePolicy {
+    fn default() -> Self {
+        Self {
+            reduce_at: 0.85,
+            restore_below: 0.7,
+            factor: 0.5,
+        }
+    }
+}
+
+/// Limit change triggered by a memory pressure reading.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LimitAdjustment {
+    pub memory_pressure: f64,
+    pub reduced: bool,
+    pub limits: ConcurrencyLimits,
+}
+
+/// Queue share of namespaces without a configured weight.
+pub const DEFAULT_NAMESPACE_WEIGHT: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum RunPriority {
+    #[default]
+    Normal,
+    /// Queued ahead of every normal run, e.g. a hotfix or rollback pipeline.
+    Critical,
+}
+
+/// A run waiting for a slot.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QueuedRun {
+    pub run_id: String,
+    pub namespace: Namespace,
+    #[serde(default)]
+    pub priority: RunPriority,
+    /// One-based position in admission order.
+    pub position: usize,
+}
+
+/// Queue metrics of one namespace.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct NamespaceQueueMetrics {
+    pub weight: u32,
+    pub running: usize,
+    pub queued: usize,
+    /// Runs admitted since the governor started.
+    pub admitted: u64,
+    pub mean_wait_ms: f64,
+    pub max_wait_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConcurrencySnapshot {
+    /// Limits currently enforced, after any pressure reduction.
+    pub limits: ConcurrencyLimits,
+    pub reduced: bool,
+    pub running: BTreeMap<Namespace, usize>,
+    pub queued: Vec<QueuedRun>,
+    #[serde(default)]
+    pub namespaces: BTreeMap<Namespace, NamespaceQueueMetrics>,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    ticket: u64,
+    run_id: String,
+    namespace: Namespace,
+    priority: RunPriority,
+    start: f64,
+    finish: f64,
+    queued_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct WaitStats {
+    admitted: u64,
+    total_wait_ms: u64,
+    max_wait_ms: u64,
+}
+
+#[derive(Debug)]
+struct GovernorState {
+    base: ConcurrencyLimits,
+    policy: PressurePolicy,
+    reduced: bool,
+    namespace_limits: BTreeMap<Namespace, usize>,
+    weights: BTreeMap<Namespace, u32>,
+    running: BTreeMap<Namespace, usize>,
+    queue: VecDeque<Waiter>,
+    next_ticket: u64,
+    /// Start tag of the most recently admitted run.
+    virtual_time: f64,
+    last_finish: BTreeMap<Namespace, f64>,
+    waits: BTreeMap<Namespace, WaitStats>,
+}
+
+impl GovernorState {
+    fn factor(&self) -> f64 {
+        if self.reduced {
+            self.policy.factor
+        } else {
+            1.0
+        }
+    }
+
+    fn limits(&self) -> ConcurrencyLimits {
+        self.base.scaled(self.factor())
+    }
+
+    fn namespace_limit(&self, namespace: &Namespace) -> usize {
+        match self.namespace_limits.get(namespace) {
+            Some(limit) => scale(*limit, self.factor()),
+            None => self.limits().per_namespace,
+        }
+    }
+
+    fn has_room(&self, namespace: &Namespace) -> bool {
+        let total: usize = self.running.values().sum();
+        let in_namespace = self.running.get(namespace).copied().unwrap_or(0);
+        total < self.limits().global && in_namespace < self.namespace_limit(namespace)
+    }
+
+    fn weight(&self, namespace: &Namespace) -> u32 {
+        self.weights
+            .get(namespace)
+            .copied()
+            .unwrap_or(DEFAULT_NAMESPACE_WEIGHT)
+    }
+
+    fn enqueue(&mut self, namespace: &Namespace, run_id: &str, priority: RunPriority) -> u64 {
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        let start = self
+            .last_finish
+            .get(namespace)
+            .copied()
+            .unwrap_or(0.0)
+            .max(self.virtual_time);
+        let finish = start + 1.0 / f64::from(self.weight(namespace));
+        self.last_finish.insert(namespace.clone(), finish);
+        self.queue.push_back(Waiter {
+            ticket,
+            run_id: run_id.to_string(),
+            namespace: namespace.clone(),
+            priority,
+            start,
+            finish,
+            queued_at: Instant::now(),
+        });
+        ticket
+    }
+
+    /// Waiters in admission order: critical runs first, then by virtual finish time.
+    fn ordered(&self) -> Vec<&Waiter> {
+        let mut ordered: Vec<&Waiter> = self.queue.iter().collect();
+        ordered.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then(a.finish.total_cmp(&b.finish))
+                .then(a.ticket.cmp(&b.ticket))
+        });
+        ordered
+    }
+
+    /// First waiter in admission order whose namespace has room; waiters from
+    /// other namespaces may pass one held back only by its own namespace limit.
+    fn next_admissible(&self) -> Option<u64> {
+        self.ordered()
+            .into_iter()
+            .find(|waiter| self.has_room(&waiter.namespace))
+            .map(|waiter| waiter.ticket)
+    }
+
+    fn position(&self, ticket: u64) -> Option<usize> {
+        self.ordered()
+            .iter()
+            .position(|waiter| waiter.ticket == ticket)
+            .map(|index| index + 1)
+    }
+
+    fn admit(&mut self, namespace: &Namespace) {
+        *self.running.entry(namespace.clone()).or_default() += 1;
+        self.waits.entry(namespace.clone()).or_default().admitted += 1;
+    }
+
+    fn admit_queued(&mut self, ticket: u64) {
+        let Some(index) = self.queue.iter().position(|waiter| waiter.ticket == ticket) else {
+            return;
+        };
+        let waiter = self.queue.remove(index).expect("queued waiter");
+        self.virtual_time = self.virtual_time.max(waiter.start);
+        self.admit(&waiter.namespace);
+        let waited = waiter.queued_at.elapsed().as_millis() as u64;
+        let stats = self.waits.entry(waiter.namespace).or_default();
+        stats.total_wait_ms += waited;
+        stats.max_wait_ms = stats.max_wait_ms.max(waited);
+    }
+}
+
+/// Node-wide gate shared by every workflow engine and CI/CD system on the host.
+#[derive(Debug)]
+pub struct ConcurrencyGovernor {
+    state: Mutex<GovernorState>,
+    available: Condvar,
+}
+
+impl ConcurrencyGovernor {
+    pub fn new(limits: ConcurrencyLimits) -> Self {
+        Self {
+            state: Mutex::new(GovernorState {
+                base: limits,
+                policy: PressurePolicy::default(),
+                reduced: false,
+                namespace_limits: BTreeMap::new(),
+                weights: BTreeMap::new(),
+                running: BTreeMap::new(),
+                queue: VecDeque::new(),
+                next_ticket: 0,
+                virtual_time: 0.0,
+                last_finish: BTreeMap::new(),
+                waits: BTreeMap::new(),
+            }),
+            available: Condvar::new(),
+        }
+    }
+
+    pub fn for_host(classification: &HostClassification) -> Self {
+        Self::new(ConcurrencyLimits::for_host(classification))
+    }
+
+    pub fn with_pressure_policy(self, policy: PressurePolicy) -> Self {
+        self.lock().policy = policy;
+        self
+    }
+
+    /// Override the per-namespace limit for `namespace`; `None` restores the default.
+    pub fn set_namespace_limit(&self, namespace: &Namespace, limit: Option<usize>) {
+        let mut state = self.lock();
+        match limit {
+            Some(limit) => {
+                state.namespace_limits.insert(namespace.clone(), limit);
+            }
+            None => {
+                state.namespace_limits.remove(namespace);
+            }
+        }
+        drop(state);
+        self.available.notify_all();
+    }
+
+    /// Set the queue share of `namespace` relative to other namespaces; `None`
+    /// restores [`DEFAULT_NAMESPACE_WEIGHT`]. Weights below one are raised to one.
+    pub fn set_namespace_weight(&self, namespace: &Namespace, weight: Option<u32>) {
+        let mut state = self.lock();
+        match weight {
+            Some(weight) => {
+                state.weights.insert(namespace.clone(), weight.max(1));
+            }
+            None => {
+                state.weights.remove(namespace);
+            }
+        }
+    }
+
+    /// Wait for a slot for `run_id`. `on_queued` is called with the run's queue
+    /// position whenever it has to wait and that position changes.
+    pub fn acquire(
+        self: &Arc<Self>,
+        namespace: &Namespace,
+        run_id: &str,
+        on_queued: impl FnMut(usize),
+    ) -> ConcurrencyPermit {
+        self.acquire_with_priority(namespace, run_id, RunPriority::Normal, on_queued)
+    }
+
+    /// [`ConcurrencyGovernor::acquire`] for a run of the given priority.
+    pub fn acquire_with_priority(
+        self: &Arc<Self>,
+        namespace: &Namespace,
+        run_id: &str,
+        priority: RunPriority,
+        mut on_queued: impl FnMut(usize),
+    ) -> ConcurrencyPermit {
+        let mut state = self.lock();
+        let ticket = state.enqueue(namespace, run_id, priority);
+        if priority == RunPriority::Critical {
+            // Let the runs it passed report their new positions.
+            self.available.notify_all();
+        }
+
+        let mut reported = None;
+        loop {
+            if state.next_admissible() == Some(ticket) {
+                state.admit_queued(ticket);
+                return self.permit(namespace, run_id);
+            }
+            let position = state.position(ticket);
+            if position != reported {
+                reported = position;
+                drop(state);
+                if let Some(position) = position {
+                    on_queued(position);
+                }
+                state = self.lock();
+                continue;
+            }
+            state = self
+                .available
+                .wait(state)
+                .expect("concurrency governor lock poisoned");
+        }
+    }
+
+    /// Take a slot only if one is free and no queued run could use it.
+    pub fn try_acquire(
+        self: &Arc<Self>,
+        namespace: &Namespace,
+        run_id: &str,
+    ) -> Option<ConcurrencyPermit> {
+        let mut state = self.lock();
+        if !state.has_room(namespace) || state.next_admissible().is_some() {
+            return None;
+        }
+        state.admit(namespace);
+        Some(self.permit(namespace, run_id))
+    }
+
+    /// One-based position of a waiting run in admission order.
+    pub fn queue_position(&self, run_id: &str) -> Option<usize> {
+        self.lock()
+            .ordered()
+            .iter()
+            .position(|waiter| waiter.run_id == run_id)
+            .map(|index| index + 1)
+    }
+
+    pub fn snapshot(&self) -> ConcurrencySnapshot {
+        let state = self.lock();
+        let queued: Vec<QueuedRun> = state
+            .ordered()
+            .into_iter()
+            .enumerate()
+            .map(|(index, waiter)| QueuedRun {
+                run_id: waiter.run_id.clone(),
+                namespace: waiter.namespace.clone(),
+                priority: waiter.priority,
+                position: index + 1,
+            })
+            .collect();
+        let mut namespaces: BTreeMap<Namespace, NamespaceQueueMetrics> = BTreeMap::new();
+        let seen = state
+            .running
+            .keys()
+            .chain(state.weights.keys())
+            .chain(state.waits.keys())
+            .chain(state.queue.iter().map(|waiter| &waiter.namespace));
+        for namespace in seen {
+            let waits = state.waits.get(namespace);
+            let admitted = waits.map(|stats| stats.admitted).unwrap_or(0);
+            let total_wait_ms = waits.map(|stats| stats.total_wait_ms).unwrap_or(0);
+            namespaces.insert(
+                namespace.clone(),
+                NamespaceQueueMetrics {
+                    weight: state.weight(namespace),
+                    running: state.running.get(namespace).copied().unwrap_or(0),
+                    queued: queued
+                        .iter()
+                        .filter(|run| &run.namespace == namespace)
+                        .count(),
+                    admitted,
+                    mean_wait_ms: if admitted == 0 {
+                        0.0
+                    } else {
+                        total_wait_ms as f64 / admitted as f64
+                    },
+                    max_wait_ms: waits.map(|stats| stats.max_wait_ms).unwrap_or(0),
+                },
+            );
+        }
+        ConcurrencySnapshot {
+            limits: state.limits(),
+            reduced: state.reduced,
+            running: state.running.clone(),
+            queued,
+            namespaces,
+        }
+    }
+
+    /// Re-detect host hardware and adjust limits for its memory pressure.
+    pub fn refresh_host(&self) -> Option<LimitAdjustment> {
+        self.refresh(&detect_hardware_profile())
+    }
+
+    pub fn refresh(&self, profile: &HardwareProfile) -> Option<LimitAdjustment> {
+        self.observe_memory_pressure(profile.memory.pressure())
+    }
+
+    /// Reduce or restore limits for a memory pressure reading. Returns the
+    /// adjustment when the limits changed.
+    pub fn observe_memory_pressure(&self, pressure: f64) -> Option<LimitAdjustment> {
+        let mut state = self.lock();
+        let reduced = if state.reduced {
+            pressure >= state.policy.restore_below
+        } else {
+            pressure >= state.policy.reduce_at
+        };
+        if reduced == state.reduced {
+            return None;
+        }
+        state.reduced = reduced;
+        let adjustment = LimitAdjustment {
+            memory_pressure: pressure,
+            reduced,
+            limits: state.limits(),
+        };
+        drop(state);
+        self.available.notify_all();
+        Some(adjustment)
+    }
+
+    fn permit(self: &Arc<Self>, namespace: &Namespace, run_id: &str) -> ConcurrencyPermit {
+        ConcurrencyPermit {
+            governor: Arc::clone(self),
+            namespace: namespace.clone(),
+            run_id: run_id.to_string(),
+        }
+    }
+
+    fn release(&self, namespace: &Namespace) {
+        let mut state = self.lock();
+        if let Some(running) = state.running.get_mut(namespace) {
+            *running = running.saturating_sub(1);
+            if *running == 0 {
+                state.running.remove(namespace);
+            }
+        }
+        drop(state);
+        self.available.notify_all();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, GovernorState> {
+        self.state
+            .lock()
+            .expect("concurrency governor lock poisoned")
+    }
+}
+
+/// Slot held by a running workflow or pipeline; released on drop.
+#[derive(Debug)]
+pub struct ConcurrencyPermit {
+    governor: Arc<ConcurrencyGovernor>,
+    namespace: Namespace,
+    run_id: String,
+}
+
+impl ConcurrencyPermit {
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    pub fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
+}
+
+impl Drop for ConcurrencyPermit {
+    fn drop(&mut self) {
+        self.governor.release(&self.namespace);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn limits_queue_runs_and_shrink_under_memory_pressure() {
+        let governor = Arc::new(ConcurrencyGovernor::new(ConcurrencyLimits {
+            global: 2,
+            per_namespace: 2,
+        }));
+        let team_a = Namespace::new("team-a").unwrap();
+        let team_b = Namespace::new("team-b").unwrap();
+        governor.set_namespace_limit(&team_a, Some(1));
+
+        let first = governor.try_acquire(&team_a, "a-1").unwrap();
+        assert!(governor.try_acquire(&team_a, "a-2").is_none());
+        let second = governor.try_acquire(&team_b, "b-1").unwrap();
+        assert!(governor.try_acquire(&team_b, "b-2").is_none());
+
+        let (positions, reported) = mpsc::channel();
+        let waiter = {
+            let governor = Arc::clone(&governor);
+            let team_b = team_b.clone();
+            thread::spawn(move || {
+                governor
+                    .acquire(&team_b, "b-2", |position| {
+                        positions.send(position).unwrap();
+                    })
+                    .run_id()
+                    .to_string()
+            })
+        };
+        assert_eq!(reported.recv_timeout(Duration::from_secs(5)), Ok(1));
+        assert_eq!(governor.queue_position("b-2"), Some(1));
+        assert_eq!(governor.snapshot().queued[0].run_id, "b-2");
+
+        drop(first);
+        assert_eq!(waiter.join().unwrap(), "b-2");
+        assert!(governor.snapshot().queued.is_empty());
+
+        let adjustment = governor.observe_memory_pressure(0.93).unwrap();
+        assert!(adjustment.reduced);
+        assert_eq!(adjustment.limits.global, 1);
+        assert!(governor.observe_memory_pressure(0.8).is_none());
+        assert!(governor.try_acquire(&team_a, "a-3").is_none());
+        drop(second);
+
+        let restored = governor.observe_memory_pressure(0.4).unwrap();
+        assert!(!restored.reduced);
+        assert_eq!(restored.limits.global, 2);
+        assert!(governor.try_acquire(&team_a, "a-3").is_some());
+    }
+
+    #[test]
+    fn weighted_queue_interleaves_namespaces_and_admits_critical_runs_first() {
+        let governor = Arc::new(ConcurrencyGovernor::new(ConcurrencyLimits {
+            global: 1,
+            per_namespace: 1,
+        }));
+        let team_a = Namespace::new("team-a").unwrap();
+        let team_b = Namespace::new("team-b").unwrap();
+        governor.set_namespace_weight(&team_b, Some(2));
+        let holder = governor.try_acquire(&team_b, "b-0").unwrap();
+
+        let admitted = Arc::new(Mutex::new(Vec::new()));
+        let enqueue = |namespace: &Namespace, run_id: &str, priority: RunPriority| {
+            let handle = {
+                let governor = Arc::clone(&governor);
+                let admitted = Arc::clone(&admitted);
+                let namespace = namespace.clone();
+                let run_id = run_id.to_string();
+                thread::spawn(move || {
+                    let permit =
+                        governor.acquire_with_priority(&namespace, &run_id, priority, |_| {});
+                    admitted.lock().unwrap().push(run_id);
+                    drop(permit);
+                })
+            };
+            while governor.queue_position(run_id).is_none() {
+                thread::yield_now();
+            }
+            handle
+        };
+        let mut waiters = vec![
+            enqueue(&team_a, "a-1", RunPriority::Normal),
+            enqueue(&team_a, "a-2", RunPriority::Normal),
+            enqueue(&team_a, "a-3", RunPriority::Normal),
+            enqueue(&team_b, "b-1", RunPriority::Normal),
+            enqueue(&team_b, "b-2", RunPriority::Normal),
+        ];
+        waiters.push(enqueue(&team_a, "a-hotfix", RunPriority::Critical));
+
+        let order = ["a-hotfix", "b-1", "a-1", "b-2", "a-2", "a-3"];
+        let snapshot = governor.snapshot();
+        let queued: Vec<&str> = snapshot
+            .queued
+            .iter()
+            .map(|run| run.run_id.as_str())
+            .collect();
+        assert_eq!(queued, order);
+        assert_eq!(governor.queue_position("a-1"), Some(3));
+        assert_eq!(snapshot.namespaces[&team_a].queued, 4);
+        assert_eq!(snapshot.namespaces[&team_b].weight, 2);
+        assert_eq!(snapshot.namespaces[&team_b].running, 1);
+
+        drop(holder);
+        for waiter in waiters {
+            waiter.join().unwrap();
+        }
+        assert_eq!(*admitted.lock().unwrap(), order);
+        let metrics = governor.snapshot().namespaces;
+        assert_eq!(metrics[&team_a].admitted, 4);
+        assert_eq!(metrics[&team_b].admitted, 3);
+        assert_eq!(metrics[&team_a].queued, 0);
+    }
+}
@@ -0,0 +1,105 @@
+//! Explicit configuration for engines, instrumentation, and CI/CD.
+//!
+//! A [`ConfigContext`] carries the workflow root and named settings keyed like the
+//! environment variables they replace (e.g. `NOA_CICD_ENABLE_GITLEAKS`). Values set
+//! on the context win; anything unset falls back to the process environment unless
+//! the context is [`isolated`](ConfigContext::isolated). Instances built from
+//! different contexts can share a process without touching global state.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the workflow root when a context does not.
+pub const WORKFLOW_ROOT_ENV: &str = "NOA_WORKFLOW_ROOT";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigContext {
+    workflow_root: Option<PathBuf>,
+    vars: BTreeMap<String, String>,
+    isolated: bool,
+}
+
+impl ConfigContext {
+    /// Context that reads every setting from the process environment.
+    pub fn from_env() -> Self {
+        Self::default()
+    }
+
+    /// Context that never consults the process environment.
+    pub fn isolated() -> Self {
+        Self {
+            isolated: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_workflow_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.workflow_root = Some(root.into());
+        self
+    }
+
+    pub fn with_var(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.insert(key.into(), value.into());
+        self
+    }
+
+    /// Setting `key`, falling back to the environment unless isolated.
+    pub fn var(&self, key: &str) -> Option<String> {
+        if let Some(value) = self.vars.get(key) {
+            return Some(value.clone());
+        }
+        if self.isolated {
+            return None;
+        }
+        std::env::var(key).ok()
+    }
+
+    /// Whether `key` is set to `1` or `true`.
+    pub fn flag(&self, key: &str) -> bool {
+        self.var(key)
+            .map(|value| matches!(value.as_str(), "1" | "true" | "TRUE"))
+            .unwrap_or(false)
+    }
+
+    /// Root that instrumentation, namespaces, and artifacts are stored under: the
+    /// context's root, else `NOA_WORKFLOW_ROOT`, else the repository checkout.
+    pub fn workflow_root(&self) -> PathBuf {
+        if let Some(root) = &self.workflow_root {
+            return root.clone();
+        }
+        if let Some(root) = self.var(WORKFLOW_ROOT_ENV) {
+            return PathBuf::from(root);
+        }
+        let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        manifest
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    pub fn resolve_path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.workflow_root().join(relative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn isolated_contexts_ignore_the_environment() {
+        let context = ConfigContext::isolated()
+            .with_workflow_root("/srv/noa")
+            .with_var("NOA_CICD_ENABLE_GITLEAKS", "true");
+        assert!(context.flag("NOA_CICD_ENABLE_GITLEAKS"));
+        assert!(!context.flag("NOA_CICD_ENABLE_SYFT"));
+        assert_eq!(context.var("PATH"), None);
+        assert_eq!(
+            context.resolve_path("storage/db"),
+            PathBuf::from("/srv/noa/storage/db")
+        );
+
+        let fallback = ConfigContext::from_env();
+        assert_eq!(fallback.var("PATH"), std::env::var("PATH").ok());
+    }
+}
@@ -0,0 +1,249 @@
+//! Typed contracts between stages.
+//!
+//! Task outputs become the artifacts of their stage, and nothing checked what shape
+//! those artifacts had before a later stage acted on them. A stage may now declare a
+//! [`StageContract`]: a JSON Schema every artifact it produces must satisfy, and one
+//! every artifact reaching it from the stages it depends on must satisfy. The engine
+//! compiles both schemas when a workflow is loaded and fails the stage on the first
+//! artifact that breaks its contract, naming the field at fault.
+
+use std::fmt;
+
+use jsonschema::error::ValidationErrorKind;
+use jsonschema::ValidationError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{Stage, Workflow};
+
+/// JSON Schemas for the artifacts a stage accepts and produces; either may be omitted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StageContract {
+    /// Schema for each artifact produced by the stages this one depends on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<Value>,
+    /// Schema for each artifact this stage produces.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+}
+
+impl StageContract {
+    pub fn is_default(&self) -> bool {
+        self.input.is_none() && self.output.is_none()
+    }
+}
+
+/// Which side of a stage a contract covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractSide {
+    Input,
+    Output,
+}
+
+impl fmt::Display for ContractSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ContractSide::Input => "input",
+            ContractSide::Output => "output",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ContractError {
+    #[error("stage '{stage}' has an invalid {side} schema: {reason}")]
+    InvalidSchema {
+        stage: String,
+        side: ContractSide,
+        reason: String,
+    },
+    /// `field` is a JSON pointer into the artifact, `/` for the artifact itself.
+    #[error(
+        "contract violation: {side} of stage '{stage}' (artifact {artifact} from '{producer}') \
+         has invalid field '{field}': {reason}"
+    )]
+    Violation {
+        stage: String,
+        side: ContractSide,
+        producer: String,
+        artifact: usize,
+        field: String,
+        reason: String,
+    },
+}
+
+/// Compile every contract schema in `workflow`, so a broken schema fails the load
+/// instead of the run.
+pub(crate) fn check_schemas(workflow: &Workflow) -> Result<(), ContractError> {
+    for stage in &workflow.stages {
+        for (side, schema) in schemas(stage) {
+            compile(stage, side, schema)?;
+        }
+    }
+    Ok(())
+}
+
+/// Check the artifacts `producer` handed to `stage` against its input schema.
+pub(crate) fn check_input(
+    stage: &Stage,
+    producer: &str,
+    artifacts: &[Value],
+) -> Result<(), ContractError> {
+    check(stage, ContractSide::Input, producer, artifacts)
+}
+
+/// Check the artifacts `stage` produced against its output schema.
+pub(crate) fn check_output(stage: &Stage, artifacts: &[Value]) -> Result<(), ContractError> {
+    check(stage, ContractSide::Output, &stage.name, artifacts)
+}
+
+fn schemas(stage: &Stage) -> impl Iterator<Item = (ContractSide, &Value)> {
+    [
+        (ContractSide::Input, stage.contract.input.as_ref()),
+        (ContractSide::Output, stage.contract.output.as_ref()),
+    ]
+    .into_iter()
+    .filter_map(|(side, schema)| schema.map(|schema| (side, schema)))
+}
+
+fn compile(
+    stage: &Stage,
+    side: ContractSide,
+    schema: &Value,
+) -> Result<jsonschema::Validator, ContractError> {
+    jsonschema::validator_for(schema).map_err(|err| ContractError::InvalidSchema {
+        stage: stage.name.clone(),
+        side,
+        reason: err.to_string(),
+    })
+}
+
+fn check(
+    stage: &Stage,
+    side: ContractSide,
+    producer: &str,
+    artifacts: &[Value],
+) -> Result<(), ContractError> {
+    let Some((_, schema)) = schemas(stage).find(|(declared, _)| *declared == side) else {
+        return Ok(());
+    };
+    let validator = compile(stage, side, schema)?;
+    for (artifact, value) in artifacts.iter().enumerate() {
+        if let Some(error) = validator.iter_errors(value).next() {
+            return Err(ContractError::Violation {
+                stage: stage.name.clone(),
+                side,
+                producer: producer.to_string(),
+                artifact,
+                field: field(&error),
+                reason: error.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// JSON pointer of the field `error` is about. A missing required property is
+/// reported at the property rather than at the object lacking it.
+fn field(error: &ValidationError<'_>) -> String {
+    let mut pointer = error.instance_path.to_string();
+    if let ValidationErrorKind::Required { property } = &error.kind {
+        pointer.push('/');
+        pointer.push_str(property.as_str().unwrap_or_default());
+    }
+    if pointer.is_empty() {
+        pointer.push('/');
+    }
+    pointer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StageCacheControl, StageType};
+    use serde_json::json;
+
+    fn stage(contract: StageContract) -> Stage {
+        Stage {
+            name: "report".to_string(),
+            stage_type: StageType::Sequential,
+            depends_on: vec!["scan".to_string()],
+            tasks: Vec::new(),
+            compensation: Vec::new(),
+            cache: StageCacheControl::default(),
+            contract,
+        }
+    }
+
+    #[test]
+    fn violations_name_the_offending_field() {
+        let stage = stage(StageContract {
+            input: Some(json!({
+                "type": "object",
+                "required": ["findings"],
+                "properties": {
+                    "findings": {
+                        "type": "array",
+                        "items": {"type": "object", "properties": {"severity": {"type": "integer"}}}
+                    }
+                }
+            })),
+            output: None,
+        });
+
+        check_input(&stage, "scan", &[json!({"findings": [{"severity": 2}]})]).unwrap();
+        check_output(&stage, &[json!("anything")]).unwrap();
+
+        let err = check_input(
+            &stage,
+            "scan",
+            &[
+                json!({"findings": []}),
+                json!({"findings": [{"severity": "high"}]}),
+            ],
+        )
+        .unwrap_err();
+        match &err {
+            ContractError::Violation {
+                side,
+                producer,
+                artifact,
+                field,
+                ..
+            } => {
+                assert_eq!(*side, ContractSide::Input);
+                assert_eq!(producer, "scan");
+                assert_eq!(*artifact, 1);
+                assert_eq!(field, "/findings/0/severity");
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+        assert!(err.to_string().contains("contract violation"), "{err}");
+
+        let missing = check_input(&stage, "scan", &[json!({})]).unwrap_err();
+        assert!(matches!(
+            missing,
+            ContractError::Violation { ref field, .. } if field == "/findings"
+        ));
+    }
+
+    #[test]
+    fn broken_schemas_are_rejected_up_front() {
+        let workflow = Workflow {
+            name: "contracts".to_string(),
+            version: "1".to_string(),
+            stages: vec![stage(StageContract {
+                input: None,
+                output: Some(json!({"type": "no-such-type"})),
+            })],
+        };
+        assert!(matches!(
+            check_schemas(&workflow),
+            Err(ContractError::InvalidSchema {
+                side: ContractSide::Output,
+                ..
+            })
+        ));
+    }
+}
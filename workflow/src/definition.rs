@@ -0,0 +1,187 @@
+//! Parsing of workflow definition files.
+//!
+//! Definitions are JSON or YAML documents describing a [`Workflow`]. Parsing also
+//! checks the structure the engine relies on — a name, unique stage names, and
+//! dependencies on stages that exist — so a malformed file is rejected at load
+//! rather than mid-run. [`load_workflow_definitions`] reads a directory of
+//! definitions; in lenient mode files that fail to parse are skipped and reported.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use noa_core::recovery::{Recovered, RecoveryMode, SkippedRecord};
+use thiserror::Error;
+
+use crate::Workflow;
+
+const DEFINITION_EXTENSIONS: &[&str] = &["json", "yaml", "yml"];
+
+#[derive(Debug, Error)]
+pub enum DefinitionError {
+    #[error("failed to parse workflow definition: {0}")]
+    Parse(String),
+    #[error("workflow definition {workflow:?} is invalid: {reason}")]
+    Invalid { workflow: String, reason: String },
+    #[error("{path}: {reason}")]
+    File { path: String, reason: String },
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Parse and validate one workflow definition. Documents starting with `{` are
+/// read as JSON, anything else as YAML.
+pub fn parse_workflow_definition(raw: &str) -> Result<Workflow, DefinitionError> {
+    let workflow: Workflow = if raw.trim_start().starts_with('{') {
+        serde_json::from_str(raw).map_err(|err| DefinitionError::Parse(err.to_string()))?
+    } else {
+        serde_yaml::from_str(raw).map_err(|err| DefinitionError::Parse(err.to_string()))?
+    };
+    validate_structure(&workflow).map_err(|reason| DefinitionError::Invalid {
+        workflow: workflow.name.clone(),
+        reason,
+    })?;
+    Ok(workflow)
+}
+
+/// Check the name, stage names, and that each dependency names an earlier stage.
+pub(crate) fn validate_structure(workflow: &Workflow) -> Result<(), String> {
+    if workflow.name.trim().is_empty() {
+        return Err("workflow name is empty".to_string());
+    }
+    let mut seen = HashSet::new();
+    for stage in &workflow.stages {
+        if stage.name.trim().is_empty() {
+            return Err("stage name is empty".to_string());
+        }
+        for dependency in &stage.depends_on {
+            if !seen.contains(dependency.as_str()) {
+                return Err(format!(
+                    "stage {} depends on {}, which is not an earlier stage",
+                    stage.name, dependency
+                ));
+            }
+        }
+        if !seen.insert(stage.name.as_str()) {
+            return Err(format!("stage {} is defined twice", stage.name));
+        }
+    }
+    Ok(())
+}
+
+/// Load every `.json`, `.yaml` and `.yml` definition in `dir`, in file name order.
+pub fn load_workflow_definitions(
+    dir: &Path,
+    mode: RecoveryMode,
+) -> Result<Recovered<Workflow>, DefinitionError> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_definition = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| DEFINITION_EXTENSIONS.contains(&ext));
+        if is_definition && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut recovered = Recovered::default();
+    for path in paths {
+        let parsed = fs::read(&path)
+            .map_err(DefinitionError::from)
+            .and_then(|bytes| {
+                String::from_utf8(bytes).map_err(|err| DefinitionError::Parse(err.to_string()))
+            })
+            .and_then(|raw| parse_workflow_definition(&raw));
+        match parsed {
+            Ok(workflow) => recovered.records.push(workflow),
+            Err(err) if mode == RecoveryMode::Lenient => recovered.skipped.push(SkippedRecord {
+                location: path.display().to_string(),
+                reason: err.to_string(),
+            }),
+            Err(err) => {
+                return Err(DefinitionError::File {
+                    path: path.display().to_string(),
+                    reason: err.to_string(),
+                })
+            }
+        }
+    }
+    Ok(recovered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use tempfile::tempdir;
+
+    const DEFINITION: &str = r#"
+name: nightly
+version: "1.0"
+stages:
+  - name: build
+    stage_type: sequential
+    depends_on: []
+    tasks:
+      - agent: builder
+        action: compile
+        parameters: {}
+  - name: verify
+    stage_type: parallel
+    depends_on: [build]
+    tasks: []
+"#;
+
+    proptest! {
+        #[test]
+        fn definition_parser_never_panics(raw in any::<String>()) {
+            let _ = parse_workflow_definition(&raw);
+            let _ = parse_workflow_definition(&format!("{{{raw}"));
+        }
+
+        #[test]
+        fn truncated_definitions_are_rejected_without_panicking(cut in 0..DEFINITION.len()) {
+            if let Ok(workflow) = parse_workflow_definition(&DEFINITION[..cut]) {
+                prop_assert!(validate_structure(&workflow).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn lenient_directory_load_skips_bad_definitions() {
+        let dir = tempdir().unwrap();
+        let workflow = parse_workflow_definition(DEFINITION).unwrap();
+        assert_eq!(workflow.stages[1].depends_on, vec!["build".to_string()]);
+        fs::write(dir.path().join("a.yaml"), DEFINITION).unwrap();
+        fs::write(
+            dir.path().join("b.json"),
+            serde_json::to_string(&workflow).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("c.yaml"),
+            DEFINITION.replace("[build]", "[deploy]"),
+        )
+        .unwrap();
+        fs::write(dir.path().join("d.yml"), [0xff, 0xfe, b'{']).unwrap();
+        fs::write(dir.path().join("notes.md"), "not a workflow").unwrap();
+
+        let recovered = load_workflow_definitions(dir.path(), RecoveryMode::Lenient).unwrap();
+        assert_eq!(recovered.records.len(), 2);
+        let skipped: Vec<_> = recovered
+            .skipped
+            .iter()
+            .map(|skip| Path::new(&skip.location).file_name().unwrap().to_owned())
+            .collect();
+        assert_eq!(skipped, ["c.yaml", "d.yml"]);
+        assert!(recovered.skipped[0].reason.contains("deploy"));
+
+        let strict = load_workflow_definitions(dir.path(), RecoveryMode::Strict).unwrap_err();
+        assert!(
+            matches!(strict, DefinitionError::File { ref path, .. } if path.ends_with("c.yaml"))
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Dry-run execution plans.
+//!
+//! [`crate::WorkflowEngine::dry_run`] walks a workflow exactly as `execute` would —
+//! resolving agents, checking tool requirements, and evaluating reward policy and
+//! approval gates — but dispatches nothing. The resulting [`ExecutionPlan`] lists
+//! what would run, what would block, and how long it is expected to take.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AgentApprovalRequirement, AgentApprovalStatus, GoalMetricSnapshot, PlacementDecision,
+    StageType, ToolExecutionReceipt,
+};
+
+/// Planned dispatch of a single task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskPlan {
+    pub agent: String,
+    pub action: String,
+    /// Agent the dispatcher would resolve the task to.
+    pub resolved_agent: Option<String>,
+    pub resolved_role: Option<String>,
+    pub policy: AgentApprovalStatus,
+    pub tool_checks: Vec<ToolExecutionReceipt>,
+    pub placement: Option<PlacementDecision>,
+    /// Problems found while planning, such as an unresolvable agent or missing capability.
+    pub issues: Vec<String>,
+}
+
+impl TaskPlan {
+    pub fn runnable(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Planned execution of a single stage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagePlan {
+    pub name: String,
+    pub stage_type: StageType,
+    pub depends_on: Vec<String>,
+    /// False when a dependency is missing from the workflow, so `execute` would skip it.
+    pub dependencies_met: bool,
+    pub approval: Option<AgentApprovalRequirement>,
+    /// Set when the stage would pause the run waiting for an approver.
+    pub awaits_approval: bool,
+    pub tasks: Vec<TaskPlan>,
+    pub compensation_tasks: usize,
+    pub estimated_duration_ms: Option<u64>,
+}
+
+/// Everything `execute` would do for a workflow, without side effects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionPlan {
+    pub workflow_id: String,
+    pub workflow_name: String,
+    pub stages: Vec<StagePlan>,
+    /// Number of recorded runs the duration estimates are based on.
+    pub history_runs: u64,
+    pub estimated_duration_ms: Option<u64>,
+}
+
+impl ExecutionPlan {
+    /// Issues that would stop the run, prefixed with the stage and agent involved.
+    pub fn blockers(&self) -> Vec<String> {
+        self.stages
+            .iter()
+            .flat_map(|stage| {
+                stage.tasks.iter().flat_map(move |task| {
+                    task.issues
+                        .iter()
+                        .map(move |issue| format!("{}::{}: {}", stage.name, task.agent, issue))
+                })
+            })
+            .collect()
+    }
+
+    pub fn runnable(&self) -> bool {
+        self.stages
+            .iter()
+            .all(|stage| stage.tasks.iter().all(TaskPlan::runnable))
+    }
+}
+
+/// Average lead time recorded for a workflow, matched by goal id first and then by name.
+pub(crate) fn historical_lead_time(
+    snapshots: &[GoalMetricSnapshot],
+    workflow_id: &str,
+    workflow_name: &str,
+) -> Option<(u64, f64)> {
+    snapshots
+        .iter()
+        .find(|snapshot| snapshot.goal_id == workflow_id)
+        .or_else(|| {
+            snapshots
+                .iter()
+                .find(|snapshot| snapshot.workflow_id == workflow_name)
+        })
+        .filter(|snapshot| snapshot.total_runs > 0)
+        .map(|snapshot| (snapshot.total_runs, snapshot.average_lead_time_ms))
+}
+
+/// Split a workflow-level estimate across stages in proportion to their task counts.
+pub(crate) fn apportion_estimate(total_ms: f64, task_counts: &[usize]) -> Vec<u64> {
+    let weights: Vec<usize> = task_counts.iter().map(|count| (*count).max(1)).collect();
+    let total_weight: usize = weights.iter().sum();
+    if total_weight == 0 {
+        return Vec::new();
+    }
+    weights
+        .iter()
+        .map(|weight| (total_ms * *weight as f64 / total_weight as f64).round() as u64)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_are_apportioned_by_task_count() {
+        assert_eq!(apportion_estimate(900.0, &[1, 2, 0]), vec![225, 450, 225]);
+        assert!(apportion_estimate(100.0, &[]).is_empty());
+    }
+}
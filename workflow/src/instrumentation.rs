@@ -1,18 +1,28 @@
+use crate::context::ConfigContext;
+use crate::namespace::Namespace;
 use crate::reward::RewardError;
 use crate::reward::{
-    AgentApprovalStatus, AgentStandingSummary, RewardAgentSnapshot, RewardInputs, RewardScorekeeper,
+    AgentApprovalStatus, AgentRewardExplanation, AgentStandingSummary, RewardAgentSnapshot,
+    RewardInputs, RewardPeriod, RewardScorekeeper, StandingOverride, StandingOverrideRequest,
 };
 use crate::{Stage, StageType, Task, TaskDispatchReceipt};
 use chrono::Utc;
+use noa_core::recovery::{self, RecordError, Recovered, RecoveryMode};
 use noa_core::security::{self, OperationKind, OperationRecord, SignedOperation};
+use noa_core::telemetry::{FanoutEmitter, FileEmitter, TelemetryEmitter, TelemetryError};
 use noa_core::utils::{current_timestamp_millis, simple_hash};
+use noa_memory::RetentionPolicy;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
 use std::io::{ErrorKind, Write};
-use std::path::PathBuf;
-use std::sync::{Mutex, OnceLock};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::broadcast;
+use writer::{Append, LogWriter};
+
+mod writer;
 
 const INDEX_DIR: &str = ".workspace/indexes";
 const STORAGE_MIRROR_DIR: &str = "storage/db";
@@ -23,6 +33,8 @@ const SECURITY_SCAN_LOG: &str = "security_scans";
 const TASK_DISPATCH_LOG: &str = "task_dispatches";
 const AUTO_FIX_LOG: &str = "auto_fix_actions";
 const BUDGET_DECISION_LOG: &str = "budget_guardian";
+const STANDING_OVERRIDE_LOG: &str = "standing_overrides";
+const ARTIFACT_LOG: &str = "artifacts";
 const AUTO_FIX_DIR: &str = "auto_fix";
 const BUDGET_GUARDIAN_DIR: &str = "budget_guardian";
 const INFERENCE_LOG: &str = "inference_metrics";
@@ -33,7 +45,22 @@ const GOAL_ANALYTICS_DIR: &str = "storage/db/analytics";
 const GOAL_ANALYTICS_FILE: &str = "goal_kpis.json";
 const METRICS_DIR: &str = "metrics";
 const REWARD_HISTORY_FILE: &str = "reward_history.json";
-const DEPLOYMENT_REPORT_PATH: &str = "docs/reports/AGENT_DEPLOYMENT_OUTCOMES.md";
+const DEPLOYMENT_REPORT_DIR: &str = "docs/reports";
+const DEPLOYMENT_REPORT_FILE: &str = "AGENT_DEPLOYMENT_OUTCOMES.md";
+const LEDGER_EVENT_BUFFER: usize = 256;
+/// Pipeline logs covered by telemetry retention and subject purges.
+const TELEMETRY_LOGS: [&str; 10] = [
+    RELOCATION_LOG,
+    DOCUMENT_LOG,
+    STAGE_RECEIPT_LOG,
+    SECURITY_SCAN_LOG,
+    TASK_DISPATCH_LOG,
+    AUTO_FIX_LOG,
+    BUDGET_DECISION_LOG,
+    STANDING_OVERRIDE_LOG,
+    INFERENCE_LOG,
+    PIPELINE_EVENT_LOG,
+];
 
 #[derive(Debug)]
 pub enum InstrumentationError {
@@ -41,6 +68,8 @@ pub enum InstrumentationError {
     Serialization(serde_json::Error),
     Security(security::PolicyError),
     Reward(RewardError),
+    Record(RecordError),
+    Telemetry(TelemetryError),
 }
 
 impl std::fmt::Display for InstrumentationError {
@@ -50,6 +79,8 @@ impl std::fmt::Display for InstrumentationError {
             InstrumentationError::Serialization(err) => write!(f, "serialization error: {}", err),
             InstrumentationError::Security(err) => write!(f, "policy error: {}", err),
             InstrumentationError::Reward(err) => write!(f, "reward error: {}", err),
+            InstrumentationError::Record(err) => write!(f, "ledger error: {}", err),
+            InstrumentationError::Telemetry(err) => write!(f, "telemetry error: {}", err),
         }
     }
 }
@@ -80,6 +111,22 @@ impl From<RewardError> for InstrumentationError {
     }
 }
 
+impl From<RecordError> for InstrumentationError {
+    fn from(err: RecordError) -> Self {
+        Self::Record(err)
+    }
+}
+
+impl From<TelemetryError> for InstrumentationError {
+    fn from(err: TelemetryError) -> Self {
+        match err {
+            TelemetryError::Io(err) => Self::Io(err),
+            TelemetryError::Serde(err) => Self::Serialization(err),
+            other => Self::Telemetry(other),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct PipelineLogEvent {
     event_type: String,
@@ -91,6 +138,28 @@ struct PipelineLogEvent {
     timestamp: u128,
 }
 
+/// A pipeline event as published to in-process subscribers once it is signed and
+/// queued for the ledger.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LedgerEvent {
+    pub namespace: Namespace,
+    pub event_type: String,
+    pub actor: String,
+    pub subject: String,
+    pub metadata: Value,
+    pub timestamp: u128,
+}
+
+/// Subscribe to pipeline events logged by any instrumentation in this process.
+pub fn subscribe_ledger_events() -> broadcast::Receiver<LedgerEvent> {
+    ledger_event_bus().subscribe()
+}
+
+fn ledger_event_bus() -> &'static broadcast::Sender<LedgerEvent> {
+    static BUS: OnceLock<broadcast::Sender<LedgerEvent>> = OnceLock::new();
+    BUS.get_or_init(|| broadcast::channel(LEDGER_EVENT_BUFFER).0)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct ImmutableLogEntry {
     event: PipelineLogEvent,
@@ -187,6 +256,12 @@ pub struct StageReceipt {
     pub levels: Vec<MerkleLevel>,
     pub leaves: Vec<MerkleLeaf>,
     pub tasks: Vec<TaskReceipt>,
+    /// Set when the receipt covers the stage's compensation tasks after a failure.
+    #[serde(default)]
+    pub compensation: bool,
+    /// Set when the stage reused a previous run's results instead of dispatching agents.
+    #[serde(default)]
+    pub cache_hit: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -550,11 +625,13 @@ impl StageReceipt {
         let mut leaves = Vec::new();
         let mut tasks = Vec::new();
 
-        for (index, task) in stage.tasks.iter().enumerate() {
+        // Stages without tasks (e.g. approvals) still commit their artifacts as leaves.
+        for index in 0..stage.tasks.len().max(artifacts.len()) {
+            let task = stage.tasks.get(index);
             let artifact = artifacts.get(index).cloned().unwrap_or(Value::Null);
             let artifact_repr = serde_json::to_string(&artifact)?;
             let artifact_hash = simple_hash(&artifact_repr);
-            let task_repr = serde_json::to_string(task)?;
+            let task_repr = serde_json::to_string(&task)?;
             let task_hash = simple_hash(&task_repr);
             let leaf_hash = simple_hash(&format!("{}::{}", task_hash, artifact_hash));
             leaves.push(MerkleLeaf {
@@ -563,12 +640,14 @@ impl StageReceipt {
                 task_hash: task_hash.clone(),
                 artifact_hash: artifact_hash.clone(),
             });
-            tasks.push(TaskReceipt {
-                task_index: index,
-                task: task.clone(),
-                task_hash,
-                artifact_hash,
-            });
+            if let Some(task) = task {
+                tasks.push(TaskReceipt {
+                    task_index: index,
+                    task: task.clone(),
+                    task_hash,
+                    artifact_hash,
+                });
+            }
         }
 
         let (levels, merkle_root) = build_merkle_tree(workflow_id, &stage.name, &leaves);
@@ -582,6 +661,8 @@ impl StageReceipt {
             levels,
             leaves,
             tasks,
+            compensation: false,
+            cache_hit: false,
         })
     }
 }
@@ -615,6 +696,8 @@ pub enum EvidenceLedgerKind {
     TaskDispatch,
     AutoFixAction,
     BudgetDecision,
+    StandingOverride,
+    Artifact,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -638,6 +721,7 @@ impl EvidenceLedgerEntry {
                 "stage_type": receipt.stage_type,
                 "levels": receipt.levels,
                 "leaves": receipt.leaves,
+                "compensation": receipt.compensation,
             }),
             signed_operation: signed,
         }
@@ -677,6 +761,47 @@ impl EvidenceLedgerEntry {
                 "agent_name": receipt.agent_metadata.name,
                 "tool_receipts": receipt.tool_receipts,
                 "output": receipt.output,
+                "placement": receipt.placement,
+            }),
+            signed_operation: signed,
+        }
+    }
+
+    fn artifact(
+        subject: &str,
+        stage: &str,
+        path: &str,
+        sha256: &str,
+        size: u64,
+        signed: SignedOperation,
+    ) -> Self {
+        Self {
+            kind: EvidenceLedgerKind::Artifact,
+            timestamp: current_timestamp_millis(),
+            reference: sha256.to_string(),
+            payload: json!({
+                "subject": subject,
+                "stage": stage,
+                "path": path,
+                "sha256": sha256,
+                "size": size,
+            }),
+            signed_operation: signed,
+        }
+    }
+
+    fn standing_override(
+        action: &str,
+        standing_override: &StandingOverride,
+        signed: SignedOperation,
+    ) -> Self {
+        Self {
+            kind: EvidenceLedgerKind::StandingOverride,
+            timestamp: current_timestamp_millis(),
+            reference: signed.signature.clone(),
+            payload: json!({
+                "action": action,
+                "override": standing_override,
             }),
             signed_operation: signed,
         }
@@ -752,8 +877,50 @@ impl EvidenceLedgerEntry {
     }
 }
 
+/// Parse one row of the deployment outcome table; header and separator rows
+/// yield `None`.
+fn parse_deployment_outcome_row(line: &str) -> Option<DeploymentOutcomeRecord> {
+    let row = line.strip_prefix("| ")?.strip_suffix(" |")?;
+    let fields: Vec<&str> = row.splitn(8, " | ").collect();
+    let [recorded_at, workflow_id, stage_id, agent_role, agent_id, action, status, notes] =
+        fields.as_slice()
+    else {
+        return None;
+    };
+    if *recorded_at == "Timestamp" || recorded_at.starts_with("---") {
+        return None;
+    }
+    let notes = notes.replace("\\|", "|");
+    Some(DeploymentOutcomeRecord {
+        workflow_id: workflow_id.to_string(),
+        stage_id: stage_id.to_string(),
+        agent_role: agent_role.to_string(),
+        agent_id: agent_id.to_string(),
+        action: action.to_string(),
+        status: status.to_string(),
+        notes: serde_json::from_str(&notes).unwrap_or(Value::String(notes)),
+        recorded_at: recorded_at.to_string(),
+    })
+}
+
+/// Read an evidence ledger. In lenient mode corrupt or truncated lines are
+/// skipped and listed in the result instead of failing the whole read.
+pub fn read_evidence_ledger(
+    path: &Path,
+    mode: RecoveryMode,
+) -> Result<Recovered<EvidenceLedgerEntry>, InstrumentationError> {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Recovered::default()),
+        Err(err) => return Err(err.into()),
+    };
+    Ok(recovery::parse_jsonl(&content, mode)?)
+}
+
 #[derive(Debug)]
 pub struct PipelineInstrumentation {
+    namespace: Namespace,
+    context: ConfigContext,
     index_dir: PathBuf,
     mirror_dir: PathBuf,
     evidence_dir: PathBuf,
@@ -766,15 +933,31 @@ pub struct PipelineInstrumentation {
     metrics_dir: PathBuf,
     reward_history_path: PathBuf,
     reward_scorekeeper: Mutex<RewardScorekeeper>,
+    /// Destination of pipeline log entries: the index logs and their storage mirror,
+    /// plus any emitter added with [`PipelineInstrumentation::with_emitter`].
+    telemetry: FanoutEmitter,
 }
 
 impl PipelineInstrumentation {
     pub fn new() -> Result<Self, InstrumentationError> {
-        let index_dir = resolve_path(INDEX_DIR);
-        let mirror_dir = resolve_path(STORAGE_MIRROR_DIR);
-        let evidence_dir = resolve_path(EVIDENCE_LEDGER_DIR);
-        let analytics_dir = resolve_path(GOAL_ANALYTICS_DIR);
-        let metrics_dir = resolve_path(METRICS_DIR);
+        Self::for_namespace(&Namespace::default())
+    }
+
+    /// Instrumentation whose ledgers, indexes, and reports live under `namespace`.
+    pub fn for_namespace(namespace: &Namespace) -> Result<Self, InstrumentationError> {
+        Self::with_context(namespace, ConfigContext::from_env())
+    }
+
+    /// Instrumentation for `namespace` under the workflow root of `context`.
+    pub fn with_context(
+        namespace: &Namespace,
+        context: ConfigContext,
+    ) -> Result<Self, InstrumentationError> {
+        let index_dir = context.resolve_path(namespace.scope_path(INDEX_DIR));
+        let mirror_dir = context.resolve_path(namespace.scope_path(STORAGE_MIRROR_DIR));
+        let evidence_dir = context.resolve_path(namespace.scope_path(EVIDENCE_LEDGER_DIR));
+        let analytics_dir = context.resolve_path(namespace.scope_path(GOAL_ANALYTICS_DIR));
+        let metrics_dir = context.resolve_path(namespace.scope_path(METRICS_DIR));
         fs::create_dir_all(&index_dir)?;
         fs::create_dir_all(&mirror_dir)?;
         fs::create_dir_all(&evidence_dir)?;
@@ -787,15 +970,22 @@ impl PipelineInstrumentation {
 
         let evidence_ledger_path = evidence_dir.join(EVIDENCE_LEDGER_FILE);
         let goal_metrics_path = analytics_dir.join(GOAL_ANALYTICS_FILE);
-        let deployment_report_path = resolve_path(DEPLOYMENT_REPORT_PATH);
+        let deployment_report_path = context
+            .resolve_path(namespace.scope_path(DEPLOYMENT_REPORT_DIR))
+            .join(DEPLOYMENT_REPORT_FILE);
         if let Some(parent) = deployment_report_path.parent() {
             fs::create_dir_all(parent)?;
         }
         let goal_metrics = Mutex::new(load_goal_metrics(&goal_metrics_path)?);
         let reward_history_path = metrics_dir.join(REWARD_HISTORY_FILE);
         let reward_scorekeeper = Mutex::new(RewardScorekeeper::new(reward_history_path.clone())?);
+        let log_files = FileEmitter::new(&index_dir)
+            .mirrored_to(&mirror_dir)
+            .synced();
 
         let instrumentation = Self {
+            namespace: namespace.clone(),
+            context,
             index_dir,
             mirror_dir,
             evidence_dir,
@@ -808,6 +998,7 @@ impl PipelineInstrumentation {
             metrics_dir,
             reward_history_path,
             reward_scorekeeper,
+            telemetry: FanoutEmitter::new(vec![Arc::new(log_files)]),
         };
 
         instrumentation.ensure_genesis(RELOCATION_LOG, OperationKind::FileMove)?;
@@ -827,6 +1018,30 @@ impl PipelineInstrumentation {
         Ok(instrumentation)
     }
 
+    pub fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
+
+    pub fn context(&self) -> &ConfigContext {
+        &self.context
+    }
+
+    /// Also send every pipeline log entry to `emitter` (OTLP, the IPC bus, ...) as a
+    /// record on the log's stream, e.g. `pipeline_events`. The hash-chained log files
+    /// stay authoritative; an emitter error is reported by the next
+    /// [`PipelineInstrumentation::flush`] after they were appended.
+    pub fn with_emitter(mut self, emitter: Arc<dyn TelemetryEmitter>) -> Self {
+        self.telemetry = std::mem::take(&mut self.telemetry).with(emitter);
+        self
+    }
+
+    /// Wait until every pipeline log entry queued in this process is on disk. Log
+    /// entries are written by a background thread, so read the log files only after
+    /// a flush. Returns the first write failure since the previous flush.
+    pub fn flush(&self) -> Result<(), InstrumentationError> {
+        LogWriter::global().flush()
+    }
+
     fn ensure_genesis(
         &self,
         log_name: &str,
@@ -970,6 +1185,7 @@ impl PipelineInstrumentation {
                 "agent": receipt.agent_metadata.agent_id,
                 "tool_receipts": receipt.tool_receipts,
                 "output": receipt.output,
+                "placement": receipt.placement,
             }),
             timestamp: current_timestamp_millis(),
         };
@@ -992,6 +1208,44 @@ impl PipelineInstrumentation {
         ))
     }
 
+    /// Ledger a build output by content hash so deployments of `subject` can be traced
+    /// back to the exact bytes it produced.
+    pub fn log_artifact(
+        &self,
+        subject: &str,
+        stage: &str,
+        path: &str,
+        sha256: &str,
+        size: u64,
+    ) -> Result<SignedOperation, InstrumentationError> {
+        let event = PipelineLogEvent {
+            event_type: "artifact.recorded".to_string(),
+            actor: stage.to_string(),
+            scope: subject.to_string(),
+            source: None,
+            target: Some(path.to_string()),
+            metadata: json!({
+                "stage": stage,
+                "sha256": sha256,
+                "size": size,
+            }),
+            timestamp: current_timestamp_millis(),
+        };
+        let record = OperationRecord::new(OperationKind::FileWrite, stage.to_string(), subject)
+            .with_context(None, Some(path.to_string()))
+            .with_metadata(json!({ "sha256": sha256, "size": size }));
+        let signed = self.append_entry(ARTIFACT_LOG, event, record)?;
+        self.append_evidence_ledger(EvidenceLedgerEntry::artifact(
+            subject,
+            stage,
+            path,
+            sha256,
+            size,
+            signed.clone(),
+        ))?;
+        Ok(signed)
+    }
+
     pub fn log_stage_receipt(
         &self,
         workflow_id: &str,
@@ -999,8 +1253,44 @@ impl PipelineInstrumentation {
         artifacts: &[Value],
     ) -> Result<StageReceipt, InstrumentationError> {
         let receipt = StageReceipt::new(workflow_id, stage, artifacts)?;
-        let stage_name = stage.name.clone();
-        let stage_type = stage.stage_type.clone();
+        self.append_stage_receipt(workflow_id, receipt, "stage_receipt")
+    }
+
+    /// Record the outcome of a stage's compensation tasks as a compensation receipt.
+    pub fn log_compensation_receipt(
+        &self,
+        workflow_id: &str,
+        stage: &Stage,
+        results: &[Value],
+    ) -> Result<StageReceipt, InstrumentationError> {
+        let compensation_stage = Stage {
+            tasks: stage.compensation.clone(),
+            compensation: Vec::new(),
+            ..stage.clone()
+        };
+        let mut receipt = StageReceipt::new(workflow_id, &compensation_stage, results)?;
+        receipt.compensation = true;
+        self.append_stage_receipt(workflow_id, receipt, "stage_compensation_receipt")
+    }
+
+    /// Record a receipt reused from the stage cache, marked as a cache hit.
+    pub fn log_cached_stage_receipt(
+        &self,
+        workflow_id: &str,
+        mut receipt: StageReceipt,
+    ) -> Result<StageReceipt, InstrumentationError> {
+        receipt.cache_hit = true;
+        self.append_stage_receipt(workflow_id, receipt, "stage_receipt")
+    }
+
+    fn append_stage_receipt(
+        &self,
+        workflow_id: &str,
+        receipt: StageReceipt,
+        event_type: &str,
+    ) -> Result<StageReceipt, InstrumentationError> {
+        let stage_name = receipt.stage_id.clone();
+        let stage_type = receipt.stage_type.clone();
         let stage_name_for_metadata = stage_name.clone();
         let stage_name_for_record = stage_name.clone();
         let event_scope = format!("{}::{}", workflow_id, stage_name);
@@ -1010,9 +1300,11 @@ impl PipelineInstrumentation {
             "stage_type": stage_type,
             "merkle_root": receipt.merkle_root,
             "leaf_count": receipt.leaves.len(),
+            "compensation": receipt.compensation,
+            "cache_hit": receipt.cache_hit,
         });
         let event = PipelineLogEvent {
-            event_type: "stage_receipt".to_string(),
+            event_type: event_type.to_string(),
             actor: "workflow_engine".to_string(),
             scope: event_scope,
             source: None,
@@ -1250,11 +1542,22 @@ impl PipelineInstrumentation {
             metadata: metadata_for_event,
             timestamp: current_timestamp_millis(),
         };
+        let published = LedgerEvent {
+            namespace: self.namespace.clone(),
+            event_type: event.event_type.clone(),
+            actor: event.actor.clone(),
+            subject: event.scope.clone(),
+            metadata: metadata.clone(),
+            timestamp: event.timestamp,
+        };
         let record =
             OperationRecord::new(OperationKind::Other, actor.to_string(), subject.to_string())
                 .with_context(Some(actor.to_string()), Some(subject.to_string()))
                 .with_metadata(metadata);
-        self.append_entry(PIPELINE_EVENT_LOG, event, record)
+        let signed = self.append_entry(PIPELINE_EVENT_LOG, event, record)?;
+        // Nobody listening is fine; the ledger entry is the durable record.
+        let _ = ledger_event_bus().send(published);
+        Ok(signed)
     }
     pub fn record_deployment_outcome(
         &self,
@@ -1289,6 +1592,21 @@ impl PipelineInstrumentation {
         })
     }
 
+    /// Outcomes recorded by [`Self::record_deployment_outcome`], oldest first.
+    pub fn deployment_outcomes(
+        &self,
+    ) -> Result<Vec<DeploymentOutcomeRecord>, InstrumentationError> {
+        let content = with_log_lock(|| match fs::read_to_string(&self.deployment_report_path) {
+            Ok(content) => Ok(content),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(String::new()),
+            Err(err) => Err(err.into()),
+        })?;
+        Ok(content
+            .lines()
+            .filter_map(parse_deployment_outcome_row)
+            .collect())
+    }
+
     pub fn record_goal_outcome(
         &self,
         outcome: GoalOutcomeRecord,
@@ -1344,21 +1662,231 @@ impl PipelineInstrumentation {
         self.persist_goal_metrics()
     }
 
+    pub fn evidence_ledger(
+        &self,
+        mode: RecoveryMode,
+    ) -> Result<Recovered<EvidenceLedgerEntry>, InstrumentationError> {
+        with_log_lock(|| read_evidence_ledger(&self.evidence_ledger_path, mode))
+    }
+
+    /// Drop evidence ledger entries outside `policy`, keeping the genesis entry.
+    /// Returns how many entries were dropped.
+    pub fn prune_evidence_ledger(
+        &self,
+        policy: &RetentionPolicy,
+        now_ms: u128,
+    ) -> Result<usize, InstrumentationError> {
+        with_log_lock(|| {
+            let mut entries =
+                read_evidence_ledger(&self.evidence_ledger_path, RecoveryMode::Strict)?.records;
+            let anchored = usize::from(
+                entries
+                    .first()
+                    .is_some_and(|entry| entry.kind == EvidenceLedgerKind::Genesis),
+            );
+            let removed = apply_window(
+                &mut entries,
+                anchored,
+                |entry| entry.timestamp,
+                policy,
+                now_ms,
+            );
+            if removed > 0 {
+                rewrite_jsonl(&self.evidence_ledger_path, &entries)?;
+            }
+            Ok(removed)
+        })
+    }
+
+    /// Replace `subject_id` with `replacement` wherever it appears as a whole
+    /// string in the evidence ledger. Returns how many entries were rewritten.
+    pub fn anonymize_evidence_ledger(
+        &self,
+        subject_id: &str,
+        replacement: &str,
+    ) -> Result<usize, InstrumentationError> {
+        with_log_lock(|| {
+            let entries =
+                read_evidence_ledger(&self.evidence_ledger_path, RecoveryMode::Strict)?.records;
+            let (entries, rewritten) = redact_entries(entries, subject_id, replacement)?;
+            if rewritten > 0 {
+                rewrite_jsonl(&self.evidence_ledger_path, &entries)?;
+            }
+            Ok(rewritten)
+        })
+    }
+
+    /// Put a genesis entry at the head of the evidence ledger when it has none,
+    /// keeping the existing lines byte for byte. Returns whether one was added.
+    pub fn restore_evidence_genesis(&self) -> Result<bool, InstrumentationError> {
+        with_log_lock(|| {
+            let existing = match fs::read_to_string(&self.evidence_ledger_path) {
+                Ok(content) => content,
+                Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+                Err(err) => return Err(err.into()),
+            };
+            let has_genesis = existing
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .and_then(|line| serde_json::from_str::<EvidenceLedgerEntry>(line).ok())
+                .is_some_and(|entry| entry.kind == EvidenceLedgerKind::Genesis);
+            if has_genesis {
+                return Ok(false);
+            }
+            let mut payload = serde_json::to_string(&EvidenceLedgerEntry::genesis())?;
+            payload.push('\n');
+            payload.push_str(&existing);
+            if !payload.ends_with('\n') {
+                payload.push('\n');
+            }
+            replace_file(&self.evidence_ledger_path, payload.as_bytes())?;
+            Ok(true)
+        })
+    }
+
+    /// Drop pipeline log entries outside `policy` from every telemetry log and its
+    /// storage mirror, re-chaining the hashes of what remains. Genesis entries are
+    /// kept. Returns how many entries were dropped from the index copies.
+    pub fn prune_telemetry_logs(
+        &self,
+        policy: &RetentionPolicy,
+        now_ms: u128,
+    ) -> Result<usize, InstrumentationError> {
+        self.rewrite_telemetry_logs(|entries| {
+            let anchored = usize::from(
+                entries
+                    .first()
+                    .is_some_and(|entry| entry.event.event_type.ends_with("::genesis")),
+            );
+            Ok(apply_window(
+                entries,
+                anchored,
+                |entry| entry.event.timestamp,
+                policy,
+                now_ms,
+            ))
+        })
+    }
+
+    /// Replace `subject_id` with `replacement` wherever it appears as a whole
+    /// string in the telemetry logs, re-chaining their hashes. Returns how many
+    /// entries were rewritten in the index copies.
+    pub fn anonymize_telemetry_logs(
+        &self,
+        subject_id: &str,
+        replacement: &str,
+    ) -> Result<usize, InstrumentationError> {
+        self.rewrite_telemetry_logs(|entries| {
+            let (redacted, rewritten) =
+                redact_entries(std::mem::take(entries), subject_id, replacement)?;
+            *entries = redacted;
+            Ok(rewritten)
+        })
+    }
+
     pub fn goal_metrics_snapshot(&self) -> Result<Vec<GoalMetricSnapshot>, InstrumentationError> {
         let store = self.goal_metrics.lock().unwrap();
         Ok(store.snapshots())
     }
 
+    /// Evaluate an agent against the reward gate, first retiring any expired
+    /// standing overrides so normal evaluation resumes.
     pub fn evaluate_agent_for_execution(&self, agent: &str) -> AgentApprovalStatus {
+        if let Err(err) = self.expire_standing_overrides() {
+            println!("[REWARD] Failed to expire standing overrides: {}", err);
+        }
         let keeper = self.reward_scorekeeper.lock().unwrap();
         keeper.approval_status(agent)
     }
 
+    /// Grant a flagged agent a time-boxed standing override and record it in the
+    /// evidence ledger.
+    pub fn grant_standing_override(
+        &self,
+        request: StandingOverrideRequest,
+    ) -> Result<StandingOverride, InstrumentationError> {
+        let granted = self
+            .reward_scorekeeper
+            .lock()
+            .unwrap()
+            .grant_override(request, Utc::now())?;
+        self.ledger_standing_override("granted", &granted)?;
+        Ok(granted)
+    }
+
+    /// Retire overrides past their expiry, recording each in the evidence ledger.
+    pub fn expire_standing_overrides(&self) -> Result<Vec<StandingOverride>, InstrumentationError> {
+        let expired = self
+            .reward_scorekeeper
+            .lock()
+            .unwrap()
+            .expire_overrides(Utc::now())?;
+        for entry in &expired {
+            self.ledger_standing_override("expired", entry)?;
+        }
+        Ok(expired)
+    }
+
+    fn ledger_standing_override(
+        &self,
+        action: &str,
+        standing_override: &StandingOverride,
+    ) -> Result<(), InstrumentationError> {
+        let event = PipelineLogEvent {
+            event_type: format!("standing_override.{}", action),
+            actor: standing_override.granted_by.clone(),
+            scope: standing_override.agent.clone(),
+            source: None,
+            target: None,
+            metadata: json!({
+                "role": standing_override.role,
+                "justification": standing_override.justification,
+                "expires_at": standing_override.expires_at,
+            }),
+            timestamp: current_timestamp_millis(),
+        };
+        let record = OperationRecord::new(
+            OperationKind::Other,
+            standing_override.granted_by.clone(),
+            standing_override.agent.clone(),
+        )
+        .with_metadata(json!({
+            "action": action,
+            "role": standing_override.role,
+            "expires_at": standing_override.expires_at,
+        }));
+        let signed = self.append_entry(STANDING_OVERRIDE_LOG, event, record)?;
+        self.append_evidence_ledger(EvidenceLedgerEntry::standing_override(
+            action,
+            standing_override,
+            signed,
+        ))
+    }
+
     pub fn flagged_agents(&self) -> Vec<AgentStandingSummary> {
         let keeper = self.reward_scorekeeper.lock().unwrap();
         keeper.flagged_agents()
     }
 
+    /// Standing of every agent with reward history, sorted by agent.
+    pub fn agent_standings(&self) -> Vec<AgentStandingSummary> {
+        let keeper = self.reward_scorekeeper.lock().unwrap();
+        let mut summaries = keeper.standing_summaries();
+        summaries.sort_by(|a, b| a.agent.cmp(&b.agent));
+        summaries
+    }
+
+    /// Explain an agent's rewards, linking each delta to its goal KPI record.
+    pub fn explain_agent_reward(
+        &self,
+        agent: &str,
+        period: &RewardPeriod,
+    ) -> AgentRewardExplanation {
+        let outcomes = self.goal_metrics_path.display().to_string();
+        let keeper = self.reward_scorekeeper.lock().unwrap();
+        keeper.explain(agent, period, |goal_id| format!("{}#{}", outcomes, goal_id))
+    }
+
     pub fn log_inference_metric(
         &self,
         metric: InferenceMetric,
@@ -1401,13 +1929,15 @@ impl PipelineInstrumentation {
         event: PipelineLogEvent,
         record: OperationRecord,
     ) -> Result<SignedOperation, InstrumentationError> {
-        with_log_lock(move || {
-            let previous_hash = self.tail_hash_locked(log_name)?;
-            let signed = security::enforce_operation(record)?;
-            let entry = ImmutableLogEntry::new(event, signed.clone(), previous_hash)?;
-            self.write_entry(log_name, &entry)?;
-            Ok(signed)
-        })
+        let signed = security::enforce_operation(record)?;
+        LogWriter::global().submit(Append {
+            path: self.log_path(log_name),
+            log_name: log_name.to_string(),
+            event,
+            policy: signed.clone(),
+            telemetry: self.telemetry.clone(),
+        })?;
+        Ok(signed)
     }
 
     fn ensure_evidence_ledger(&self) -> Result<(), InstrumentationError> {
@@ -1541,43 +2071,44 @@ impl PipelineInstrumentation {
         })
     }
 
-    fn tail_hash_locked(&self, log_name: &str) -> Result<String, InstrumentationError> {
-        let path = self.log_path(log_name);
-        if !path.exists() {
-            return Ok("GENESIS".to_string());
-        }
-        let content = fs::read_to_string(path)?;
-        for line in content.lines().rev() {
-            if line.trim().is_empty() {
-                continue;
+    fn rewrite_telemetry_logs(
+        &self,
+        mut edit: impl FnMut(&mut Vec<ImmutableLogEntry>) -> Result<usize, InstrumentationError>,
+    ) -> Result<usize, InstrumentationError> {
+        self.flush()?;
+        with_log_lock(|| {
+            LogWriter::global().forget_tails();
+            let mut changed = 0;
+            for log_name in TELEMETRY_LOGS {
+                for base in [&self.index_dir, &self.mirror_dir] {
+                    let path = base.join(format!("{}.log", log_name));
+                    let content = match fs::read_to_string(&path) {
+                        Ok(content) => content,
+                        Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                        Err(err) => return Err(err.into()),
+                    };
+                    let mut entries = content
+                        .lines()
+                        .filter(|line| !line.trim().is_empty())
+                        .map(serde_json::from_str)
+                        .collect::<Result<Vec<ImmutableLogEntry>, _>>()?;
+                    let edited = edit(&mut entries)?;
+                    if edited == 0 {
+                        continue;
+                    }
+                    rewrite_jsonl(&path, &rechain(entries)?)?;
+                    if base == &self.index_dir {
+                        changed += edited;
+                    }
+                }
             }
-            let entry: ImmutableLogEntry = serde_json::from_str(line)?;
-            return Ok(entry.entry_hash);
-        }
-        Ok("GENESIS".to_string())
+            Ok(changed)
+        })
     }
 
     fn log_path(&self, log_name: &str) -> PathBuf {
         self.index_dir.join(format!("{}.log", log_name))
     }
-
-    fn write_entry(
-        &self,
-        log_name: &str,
-        entry: &ImmutableLogEntry,
-    ) -> Result<(), InstrumentationError> {
-        let serialised = serde_json::to_string(entry)?;
-        let payload = format!("{}\n", serialised);
-
-        for base in [&self.index_dir, &self.mirror_dir] {
-            let path = base.join(format!("{}.log", log_name));
-            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
-            file.write_all(payload.as_bytes())?;
-            file.flush()?;
-            file.sync_all()?;
-        }
-        Ok(())
-    }
 }
 
 fn log_write_lock() -> &'static Mutex<()> {
@@ -1594,6 +2125,113 @@ fn with_log_lock<T>(
     f()
 }
 
+/// Drop entries older than the policy's age limit, then the oldest beyond its
+/// record limit. The first `anchored` entries are always kept and not counted.
+fn apply_window<T>(
+    entries: &mut Vec<T>,
+    anchored: usize,
+    timestamp: impl Fn(&T) -> u128,
+    policy: &RetentionPolicy,
+    now_ms: u128,
+) -> usize {
+    let mut body = entries.split_off(anchored.min(entries.len()));
+    let before = body.len();
+    if let Some(max_age_secs) = policy.max_age_secs {
+        let cutoff = now_ms.saturating_sub(u128::from(max_age_secs) * 1000);
+        body.retain(|entry| timestamp(entry) >= cutoff);
+    }
+    if let Some(max_records) = policy.max_records {
+        let overflow = body.len().saturating_sub(max_records);
+        body.drain(..overflow);
+    }
+    let removed = before - body.len();
+    entries.extend(body);
+    removed
+}
+
+/// Replace every string equal to `subject_id` inside `value`.
+fn redact_value(value: &mut Value, subject_id: &str, replacement: &str) -> bool {
+    match value {
+        Value::String(text) if text == subject_id => {
+            *text = replacement.to_string();
+            true
+        }
+        Value::Array(items) => items.iter_mut().fold(false, |changed, item| {
+            redact_value(item, subject_id, replacement) | changed
+        }),
+        Value::Object(fields) => fields.values_mut().fold(false, |changed, field| {
+            redact_value(field, subject_id, replacement) | changed
+        }),
+        _ => false,
+    }
+}
+
+fn redact_entries<T: Serialize + serde::de::DeserializeOwned>(
+    entries: Vec<T>,
+    subject_id: &str,
+    replacement: &str,
+) -> Result<(Vec<T>, usize), InstrumentationError> {
+    let mut rewritten = 0;
+    let mut redacted = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let mut value = serde_json::to_value(&entry)?;
+        if redact_value(&mut value, subject_id, replacement) {
+            rewritten += 1;
+            redacted.push(serde_json::from_value(value)?);
+        } else {
+            redacted.push(entry);
+        }
+    }
+    Ok((redacted, rewritten))
+}
+
+/// Recompute the hash chain after entries were dropped or rewritten, keeping the
+/// first entry's link so the log still anchors where it did.
+fn rechain(
+    entries: Vec<ImmutableLogEntry>,
+) -> Result<Vec<ImmutableLogEntry>, InstrumentationError> {
+    let mut chained: Vec<ImmutableLogEntry> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let previous_hash = match chained.last() {
+            Some(previous) => previous.entry_hash.clone(),
+            None => entry.previous_hash,
+        };
+        chained.push(ImmutableLogEntry::new(
+            entry.event,
+            entry.policy,
+            previous_hash,
+        )?);
+    }
+    Ok(chained)
+}
+
+/// Replace a JSONL file through a temporary sibling so readers never see a
+/// partial rewrite.
+fn rewrite_jsonl<T: Serialize>(path: &Path, entries: &[T]) -> Result<(), InstrumentationError> {
+    let mut payload = String::new();
+    for entry in entries {
+        payload.push_str(&serde_json::to_string(entry)?);
+        payload.push('\n');
+    }
+    replace_file(path, payload.as_bytes())
+}
+
+/// Write `payload` to a sibling of `path` and rename it into place.
+fn replace_file(path: &Path, payload: &[u8]) -> Result<(), InstrumentationError> {
+    let staging = path.with_extension("rewrite");
+    {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&staging)?;
+        file.write_all(payload)?;
+        file.sync_all()?;
+    }
+    fs::rename(&staging, path)?;
+    Ok(())
+}
+
 fn build_merkle_tree(
     workflow_id: &str,
     stage_id: &str,
@@ -1648,6 +2286,8 @@ impl Clone for PipelineInstrumentation {
         let metrics = self.goal_metrics.lock().unwrap().clone();
         let reward = self.reward_scorekeeper.lock().unwrap().clone();
         Self {
+            namespace: self.namespace.clone(),
+            context: self.context.clone(),
             index_dir: self.index_dir.clone(),
             mirror_dir: self.mirror_dir.clone(),
             evidence_dir: self.evidence_dir.clone(),
@@ -1660,19 +2300,19 @@ impl Clone for PipelineInstrumentation {
             metrics_dir: self.metrics_dir.clone(),
             reward_history_path: self.reward_history_path.clone(),
             reward_scorekeeper: Mutex::new(reward),
+            telemetry: self.telemetry.clone(),
         }
     }
 }
 
-fn resolve_path(relative: &str) -> PathBuf {
-    if let Ok(root) = std::env::var("NOA_WORKFLOW_ROOT") {
-        return PathBuf::from(root).join(relative);
+impl Drop for PipelineInstrumentation {
+    fn drop(&mut self) {
+        // Nothing is left to report a failure to; the entries that could be written
+        // are on disk once this returns.
+        if let Some(writer) = LogWriter::started() {
+            let _ = writer.flush();
+        }
     }
-    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    manifest
-        .parent()
-        .map(|root| root.join(relative))
-        .unwrap_or_else(|| PathBuf::from(relative))
 }
 
 fn load_goal_metrics(path: &PathBuf) -> Result<GoalMetricStore, InstrumentationError> {
@@ -1726,44 +2366,17 @@ fn load_goal_metrics(path: &PathBuf) -> Result<GoalMetricStore, InstrumentationE
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{ResourceRequirements, SandboxSpec, StageCacheControl, StageContract};
     use serde_json::json;
     use std::collections::HashMap;
-    use std::sync::{Mutex, OnceLock};
     use tempfile::tempdir;
 
-    fn env_lock() -> &'static Mutex<()> {
-        static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
-        LOCK.get_or_init(|| Mutex::new(()))
-    }
-
-    struct EnvGuard {
-        key: &'static str,
-        prev: Option<std::ffi::OsString>,
-        lock: Option<std::sync::MutexGuard<'static, ()>>,
-    }
-
-    impl EnvGuard {
-        fn set(key: &'static str, value: &PathBuf) -> Self {
-            let guard = env_lock().lock().expect("workflow env lock poisoned");
-            let prev = std::env::var_os(key);
-            std::env::set_var(key, value);
-            Self {
-                key,
-                prev,
-                lock: Some(guard),
-            }
-        }
-    }
-
-    impl Drop for EnvGuard {
-        fn drop(&mut self) {
-            if let Some(ref val) = self.prev {
-                std::env::set_var(self.key, val);
-            } else {
-                std::env::remove_var(self.key);
-            }
-            self.lock.take();
-        }
+    fn instrumentation_in(root: &Path) -> PipelineInstrumentation {
+        PipelineInstrumentation::with_context(
+            &Namespace::default(),
+            ConfigContext::isolated().with_workflow_root(root),
+        )
+        .unwrap()
     }
 
     fn sample_stage() -> Stage {
@@ -1777,7 +2390,12 @@ mod tests {
                 parameters: HashMap::from([("target".to_string(), json!({"path": "src/main.rs"}))]),
                 tool_requirements: Vec::new(),
                 agent_role: None,
+                resources: ResourceRequirements::default(),
+                sandbox: SandboxSpec::default(),
             }],
+            compensation: vec![],
+            cache: StageCacheControl::default(),
+            contract: StageContract::default(),
         }
     }
 
@@ -1785,8 +2403,7 @@ mod tests {
     fn merkle_roots_are_deterministic() {
         let dir = tempdir().unwrap();
         let root = dir.path().to_path_buf();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", &root);
-        let instrumentation = PipelineInstrumentation::new().unwrap();
+        let instrumentation = instrumentation_in(&root);
         let stage = sample_stage();
         let artifacts = vec![json!({"status": "ok"})];
 
@@ -1806,8 +2423,7 @@ mod tests {
     fn evidence_ledger_appends_stage_receipts() {
         let dir = tempdir().unwrap();
         let root = dir.path().to_path_buf();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", &root);
-        let instrumentation = PipelineInstrumentation::new().unwrap();
+        let instrumentation = instrumentation_in(&root);
         let stage = sample_stage();
         let artifacts = vec![json!({"status": "ok"})];
 
@@ -1816,7 +2432,110 @@ mod tests {
             .unwrap();
 
         let ledger_path = root.join(EVIDENCE_LEDGER_DIR).join(EVIDENCE_LEDGER_FILE);
-        let content = fs::read_to_string(ledger_path).unwrap();
+        let content = fs::read_to_string(&ledger_path).unwrap();
         assert!(content.lines().count() >= 2); // genesis + receipt
+
+        let mut file = OpenOptions::new().append(true).open(&ledger_path).unwrap();
+        writeln!(file, "{{\"kind\":\"stage_receipt\",\"timest").unwrap();
+        let recovered = instrumentation
+            .evidence_ledger(RecoveryMode::Lenient)
+            .unwrap();
+        assert_eq!(recovered.records.len(), content.lines().count());
+        assert_eq!(recovered.skipped.len(), 1);
+        assert!(instrumentation
+            .evidence_ledger(RecoveryMode::Strict)
+            .is_err());
+    }
+
+    #[test]
+    fn pipeline_entries_reach_added_emitters() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let memory = Arc::new(noa_core::telemetry::MemoryEmitter::default());
+        let instrumentation = instrumentation_in(&root).with_emitter(memory.clone());
+
+        instrumentation
+            .log_pipeline_event("ci", "wf", "pipeline.started", json!({"run": 1}))
+            .unwrap();
+        instrumentation.flush().unwrap();
+
+        let records = memory.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].stream, PIPELINE_EVENT_LOG);
+        assert_eq!(
+            records[0].payload["event"]["event_type"],
+            "pipeline.started"
+        );
+        let logged =
+            fs::read_to_string(instrumentation.index_dir.join("pipeline_events.log")).unwrap();
+        let last: Value = serde_json::from_str(logged.lines().last().unwrap()).unwrap();
+        assert_eq!(last, records[0].payload);
+    }
+
+    #[test]
+    fn concurrent_appends_from_separate_instances_keep_one_chain() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::thread::scope(|scope| {
+            for worker in 0..4 {
+                let instrumentation = instrumentation_in(&root);
+                scope.spawn(move || {
+                    for run in 0..25 {
+                        instrumentation
+                            .log_pipeline_event(
+                                "ci",
+                                &format!("pipeline-{worker}"),
+                                "pipeline.triggered",
+                                json!({"run": run}),
+                            )
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        let instrumentation = instrumentation_in(&root);
+        instrumentation.flush().unwrap();
+
+        for base in [&instrumentation.index_dir, &instrumentation.mirror_dir] {
+            let log = fs::read_to_string(base.join("pipeline_events.log")).unwrap();
+            let entries: Vec<ImmutableLogEntry> = log
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect();
+            let runs = entries
+                .iter()
+                .filter(|entry| entry.event.event_type == "pipeline.triggered")
+                .count();
+            assert_eq!(runs, 100);
+            for pair in entries.windows(2) {
+                assert_eq!(pair[1].previous_hash, pair[0].entry_hash);
+            }
+        }
+    }
+
+    #[test]
+    fn deployment_outcomes_read_back_from_the_report() {
+        let dir = tempdir().unwrap();
+        let instrumentation = instrumentation_in(dir.path());
+        assert!(instrumentation.deployment_outcomes().unwrap().is_empty());
+
+        instrumentation
+            .record_deployment_outcome(DeploymentOutcomeRecord {
+                workflow_id: "deploy-1".to_string(),
+                stage_id: "auto_promote".to_string(),
+                agent_role: "cicd".to_string(),
+                agent_id: "cicd".to_string(),
+                action: "promote:Production".to_string(),
+                status: "blocked".to_string(),
+                notes: json!({"reason": "error rate | latency"}),
+                recorded_at: "2025-01-01T00:00:00Z".to_string(),
+            })
+            .unwrap();
+
+        let outcomes = instrumentation.deployment_outcomes().unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].workflow_id, "deploy-1");
+        assert_eq!(outcomes[0].status, "blocked");
+        assert_eq!(outcomes[0].notes["reason"], "error rate | latency");
     }
 }
@@ -0,0 +1,201 @@
+//! Background writer for the hash-chained pipeline logs.
+//!
+//! An append used to take the process-wide log lock, re-read the whole log for its
+//! tail hash and fsync both copies before returning, so every pipeline in the
+//! process waited on one file append. [`LogWriter`] moves that work to a dedicated
+//! thread: callers sign their entry and queue it, blocking only while
+//! [`QUEUE_CAPACITY`] entries are already waiting. The thread chains and emits
+//! entries in queue order and remembers each log's tail hash, re-reading a log only
+//! when its size changed behind the writer's back (a restore, a hand edit).
+//! Write failures are kept and returned by the next [`LogWriter::flush`].
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock, PoisonError};
+
+use noa_core::security::SignedOperation;
+use noa_core::telemetry::{FanoutEmitter, TelemetryEmitter, TelemetryRecord};
+
+use super::{with_log_lock, ImmutableLogEntry, InstrumentationError, PipelineLogEvent};
+
+/// Entries that may wait for the writer before [`LogWriter::submit`] blocks.
+pub(super) const QUEUE_CAPACITY: usize = 1024;
+
+const GENESIS_HASH: &str = "GENESIS";
+
+/// A signed entry waiting to be chained onto the log at `path`.
+pub(super) struct Append {
+    /// Index copy of the log; its last entry holds the hash to chain onto.
+    pub(super) path: PathBuf,
+    pub(super) log_name: String,
+    pub(super) event: PipelineLogEvent,
+    pub(super) policy: SignedOperation,
+    pub(super) telemetry: FanoutEmitter,
+}
+
+#[derive(Default)]
+struct Progress {
+    queued: u64,
+    written: u64,
+    failure: Option<String>,
+}
+
+struct Tail {
+    len: u64,
+    hash: String,
+}
+
+pub(super) struct LogWriter {
+    queue: SyncSender<Append>,
+    progress: Mutex<Progress>,
+    written: Condvar,
+    tails: Mutex<HashMap<PathBuf, Tail>>,
+}
+
+static WRITER: OnceLock<LogWriter> = OnceLock::new();
+
+impl LogWriter {
+    /// The process-wide writer, started on first use.
+    pub(super) fn global() -> &'static LogWriter {
+        WRITER.get_or_init(|| {
+            let (queue, pending) = mpsc::sync_channel(QUEUE_CAPACITY);
+            std::thread::Builder::new()
+                .name("pipeline-log-writer".to_string())
+                .spawn(move || run(pending))
+                .expect("failed to start the pipeline log writer");
+            LogWriter {
+                queue,
+                progress: Mutex::default(),
+                written: Condvar::new(),
+                tails: Mutex::default(),
+            }
+        })
+    }
+
+    /// The writer if anything has been queued in this process.
+    pub(super) fn started() -> Option<&'static LogWriter> {
+        WRITER.get()
+    }
+
+    /// Queue `append`, blocking while the queue is full. Must not be called with the
+    /// log lock held: the writer needs it to drain the queue.
+    pub(super) fn submit(&self, append: Append) -> Result<(), InstrumentationError> {
+        self.progress().queued += 1;
+        if self.queue.send(append).is_err() {
+            self.progress().queued -= 1;
+            return Err(std::io::Error::other("pipeline log writer stopped").into());
+        }
+        Ok(())
+    }
+
+    /// Wait until everything queued so far is written, then return the first write
+    /// failure since the previous flush.
+    pub(super) fn flush(&self) -> Result<(), InstrumentationError> {
+        let progress = self.progress();
+        let target = progress.queued;
+        let mut progress = self
+            .written
+            .wait_while(progress, |progress| progress.written < target)
+            .unwrap_or_else(PoisonError::into_inner);
+        match progress.failure.take() {
+            Some(failure) => Err(std::io::Error::other(failure).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Drop remembered tail hashes. Called with the log lock held after logs were
+    /// rewritten in place, which can change hashes without changing sizes.
+    pub(super) fn forget_tails(&self) {
+        self.tails().clear();
+    }
+
+    fn progress(&self) -> MutexGuard<'_, Progress> {
+        self.progress.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn tails(&self) -> MutexGuard<'_, HashMap<PathBuf, Tail>> {
+        self.tails.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_batch(&self, batch: Vec<Append>) {
+        let count = batch.len() as u64;
+        let failure = with_log_lock(|| {
+            let mut tails = self.tails();
+            let mut failure = None;
+            for append in batch {
+                if let Err(err) = write(&mut tails, append) {
+                    failure.get_or_insert_with(|| err.to_string());
+                }
+            }
+            Ok(failure)
+        })
+        .unwrap_or_else(|err| Some(err.to_string()));
+
+        let mut progress = self.progress();
+        progress.written += count;
+        if progress.failure.is_none() {
+            progress.failure = failure;
+        }
+        drop(progress);
+        self.written.notify_all();
+    }
+}
+
+fn run(pending: Receiver<Append>) {
+    while let Ok(first) = pending.recv() {
+        let mut batch = vec![first];
+        batch.extend(pending.try_iter().take(QUEUE_CAPACITY));
+        LogWriter::global().write_batch(batch);
+    }
+}
+
+fn write(tails: &mut HashMap<PathBuf, Tail>, append: Append) -> Result<(), InstrumentationError> {
+    let previous_hash = tail_hash(tails, &append.path)?;
+    let entry = ImmutableLogEntry::new(append.event, append.policy, previous_hash)?;
+    append
+        .telemetry
+        .emit(&TelemetryRecord::of(&append.log_name, &entry)?)?;
+    let len = fs::metadata(&append.path)?.len();
+    tails.insert(
+        append.path,
+        Tail {
+            len,
+            hash: entry.entry_hash,
+        },
+    );
+    Ok(())
+}
+
+fn tail_hash(
+    tails: &mut HashMap<PathBuf, Tail>,
+    path: &Path,
+) -> Result<String, InstrumentationError> {
+    let len = match fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(GENESIS_HASH.to_string()),
+        Err(err) => return Err(err.into()),
+    };
+    if let Some(tail) = tails.get(path).filter(|tail| tail.len == len) {
+        return Ok(tail.hash.clone());
+    }
+    let hash = read_tail_hash(path)?;
+    tails.insert(
+        path.to_path_buf(),
+        Tail {
+            len,
+            hash: hash.clone(),
+        },
+    );
+    Ok(hash)
+}
+
+fn read_tail_hash(path: &Path) -> Result<String, InstrumentationError> {
+    let content = fs::read_to_string(path)?;
+    match content.lines().rev().find(|line| !line.trim().is_empty()) {
+        Some(line) => Ok(serde_json::from_str::<ImmutableLogEntry>(line)?.entry_hash),
+        None => Ok(GENESIS_HASH.to_string()),
+    }
+}
@@ -1,38 +1,85 @@
 //! Unified Workflow Engine - Orchestrates all operations
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use chrono::{Duration, Utc};
 use noa_agents::{
     unified_types::{AgentCategory, AgentMetadata},
-    AgentFactory, AgentRegistry, AGENT_FACTORY_CAPABILITY,
+    AgentFactory, AgentRegistry, ToolRegistry, ToolSpec, AGENT_FACTORY_CAPABILITY,
 };
 use noa_core::capabilities::KernelHandle;
 use noa_core::config::manifest::CAPABILITY_PROCESS;
+use noa_core::cost::{CostAccount, CostKind, CostLedger, CostUsage};
+use noa_core::ipc::{self, ChannelId};
 use noa_core::process::ProcessService;
 use noa_core::utils::current_timestamp_millis;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 mod agent_dispatch;
+mod approval;
+mod concurrency;
+mod context;
+mod contract;
+mod definition;
+mod dry_run;
 mod instrumentation;
+pub mod namespace;
+mod placement;
+mod replay;
+mod retention;
 mod reward;
+mod sandbox;
+mod stage_cache;
+mod triggers;
+mod visualization;
 pub use agent_dispatch::{
-    AgentDispatchError, AgentDispatcher, TaskDispatchReceipt, ToolExecutionReceipt,
-    ToolExecutionStatus, ToolRequirement,
+    AgentDispatchError, AgentDispatcher, TaskDispatchPlan, TaskDispatchReceipt,
+    ToolExecutionReceipt, ToolExecutionStatus, ToolRequirement,
 };
+pub use approval::{AgentApproval, AgentApprovalRequirement, PendingApproval};
+pub use concurrency::{
+    ConcurrencyGovernor, ConcurrencyLimits, ConcurrencyPermit, ConcurrencySnapshot,
+    LimitAdjustment, NamespaceQueueMetrics, PressurePolicy, QueuedRun, RunPriority,
+    DEFAULT_NAMESPACE_WEIGHT,
+};
+pub use context::{ConfigContext, WORKFLOW_ROOT_ENV};
+pub use contract::{ContractError, ContractSide, StageContract};
+pub use definition::{load_workflow_definitions, parse_workflow_definition, DefinitionError};
+pub use dry_run::{ExecutionPlan, StagePlan, TaskPlan};
 pub use instrumentation::{
-    AgentExecutionResult, DeploymentOutcomeRecord, EvidenceLedgerEntry, EvidenceLedgerKind,
-    GoalAgentMetric, GoalMetricSnapshot, GoalOutcomeRecord, InferenceMetric, MerkleLeaf,
-    MerkleLevel, PipelineInstrumentation, SecurityScanReport, SecurityScanStatus, StageReceipt,
-    TaskReceipt,
+    read_evidence_ledger, AgentExecutionResult, DeploymentOutcomeRecord, EvidenceLedgerEntry, EvidenceLedgerKind,
+    GoalAgentMetric, GoalMetricSnapshot, GoalOutcomeRecord, InferenceMetric, LedgerEvent,
+    MerkleLeaf, MerkleLevel, PipelineInstrumentation, SecurityScanReport, SecurityScanStatus,
+    StageReceipt, TaskReceipt,
+};
+pub use namespace::{Namespace, NamespaceError, NamespaceQuota, NamespaceRegistry};
+pub use placement::{
+    HostSnapshot, PlacementDecision, PlacementStatus, QueuedTask, ResourceRequirements,
 };
 pub use reward::{
-    AgentApprovalStatus, AgentStanding, AgentStandingSummary, RewardAgentSnapshot, RewardDelta,
-    RewardInputs, RewardReport, RewardScorekeeper,
+    AgentApprovalStatus, AgentRewardExplanation, AgentStanding, AgentStandingSummary,
+    RewardAgentSnapshot, RewardContribution, RewardDelta, RewardDeltaExplanation, RewardFactor,
+    RewardInputs, RewardPeriod, RewardReport, RewardScorekeeper, StandingOverride,
+    StandingOverrideRequest, STANDING_OVERRIDES_FILE,
+};
+pub use replay::{
+    ClockRead, RecordedDispatch, RecordedReceipt, ReplayBundle, ReplayError, ReplayReport,
+    REPLAY_BUNDLE_VERSION,
+};
+use replay::ReplaySession;
+pub use retention::{
+    subject_pseudonym, PurgeCounts, PurgeReceipt, RetentionError, RetentionManager,
+    RetentionPolicy, RetentionReport, RetentionStore, StorePurge, StoreRetention, PURGE_EVENT,
 };
+pub use sandbox::{SandboxArtifact, SandboxError, SandboxSpec, TaskSandbox};
+pub use stage_cache::{CachedStage, StageCache, StageCacheControl, StageCacheError};
 use tokio::sync::broadcast;
+use triggers::{CompiledTrigger, TriggerRegistry};
+pub use triggers::{TriggerBinding, TriggerError, TriggerEvent, TriggerRun, TriggerSource};
+pub use visualization::GraphFormat;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Workflow {
@@ -47,6 +94,15 @@ pub struct Stage {
     pub stage_type: StageType,
     pub depends_on: Vec<String>,
     pub tasks: Vec<Task>,
+    /// Tasks that undo this stage's side effects when a later stage fails.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compensation: Vec<Task>,
+    /// Reuse a previous run's artifacts and receipt when the stage's inputs are unchanged.
+    #[serde(default, skip_serializing_if = "StageCacheControl::is_default")]
+    pub cache: StageCacheControl,
+    /// JSON Schemas checked against the artifacts entering and leaving the stage.
+    #[serde(default, skip_serializing_if = "StageContract::is_default")]
+    pub contract: StageContract,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -56,6 +112,8 @@ pub enum StageType {
     Parallel,
     Conditional,
     Loop,
+    /// Pause until an approver satisfying the requirement signs off.
+    Approval(AgentApprovalRequirement),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,11 +125,18 @@ pub struct Task {
     pub agent_role: Option<String>,
     #[serde(default)]
     pub tool_requirements: Vec<ToolRequirement>,
+    /// Host resources the task needs; unmet requirements queue the task.
+    #[serde(default, skip_serializing_if = "ResourceRequirements::is_empty")]
+    pub resources: ResourceRequirements,
+    /// Workspace paths copied into the task's sandbox and outputs collected from it.
+    #[serde(default, skip_serializing_if = "SandboxSpec::is_empty")]
+    pub sandbox: SandboxSpec,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum WorkflowState {
     Pending,
+    Queued,
     Running,
     Paused,
     Completed,
@@ -85,6 +150,7 @@ pub enum StageState {
     Completed,
     Failed,
     Skipped,
+    Compensated,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,6 +187,17 @@ pub enum WorkflowEvent {
         token: WorkflowResumeToken,
         timestamp: String,
     },
+    ApprovalRequested {
+        workflow_id: String,
+        approval: PendingApproval,
+        timestamp: String,
+    },
+    RunQueued {
+        workflow_id: String,
+        namespace: Namespace,
+        position: usize,
+        timestamp: String,
+    },
 }
 
 #[derive(Clone)]
@@ -214,6 +291,9 @@ impl GoalRunTracker {
     }
 }
 
+/// Stage artifacts keyed by workflow id and stage name.
+type StageArtifacts = HashMap<(String, String), Vec<Value>>;
+
 pub struct WorkflowEngine {
     workflows: Arc<Mutex<HashMap<String, Workflow>>>,
     states: Arc<Mutex<HashMap<String, WorkflowState>>>,
@@ -222,12 +302,30 @@ pub struct WorkflowEngine {
     dispatcher: Arc<AgentDispatcher>,
     kernel: Option<KernelHandle>,
     event_stream: Arc<Mutex<Option<WorkflowEventStream>>>,
+    pending_approvals: Arc<Mutex<HashMap<String, PendingApproval>>>,
+    granted_approvals: Arc<Mutex<HashMap<(String, String), AgentApproval>>>,
+    triggers: Arc<Mutex<TriggerRegistry>>,
+    concurrency: Arc<Mutex<Option<Arc<ConcurrencyGovernor>>>>,
+    replay: Arc<Mutex<Option<ReplaySession>>>,
+    costs: Arc<Mutex<Option<Arc<CostLedger>>>>,
+    stage_cache: Arc<Mutex<Option<Arc<StageCache>>>>,
+    /// Artifacts of each stage in the current run of a workflow, checked against the
+    /// input contracts of the stages depending on it.
+    stage_artifacts: Arc<Mutex<StageArtifacts>>,
+    namespace: Namespace,
+    quota: NamespaceQuota,
 }
 
 impl WorkflowEngine {
     pub fn new() -> Self {
-        let instrumentation =
-            PipelineInstrumentation::new().expect("failed to initialise pipeline instrumentation");
+        Self::with_context(ConfigContext::from_env())
+    }
+
+    /// Create a workflow engine whose instrumentation, sandboxes, and artifacts live
+    /// under the workflow root of `context`.
+    pub fn with_context(context: ConfigContext) -> Self {
+        let instrumentation = PipelineInstrumentation::with_context(&Namespace::default(), context)
+            .expect("failed to initialise pipeline instrumentation");
         let registry = AgentRegistry::with_default_data().unwrap_or_else(|_| AgentRegistry::new());
         let factory = AgentFactory::new();
         let dispatcher = AgentDispatcher::new(registry, factory);
@@ -239,13 +337,70 @@ impl WorkflowEngine {
             dispatcher: Arc::new(dispatcher),
             kernel: None,
             event_stream: Arc::new(Mutex::new(None)),
+            pending_approvals: Arc::new(Mutex::new(HashMap::new())),
+            granted_approvals: Arc::new(Mutex::new(HashMap::new())),
+            triggers: Arc::new(Mutex::new(TriggerRegistry::default())),
+            concurrency: Arc::new(Mutex::new(None)),
+            replay: Arc::new(Mutex::new(None)),
+            costs: Arc::new(Mutex::new(None)),
+            stage_cache: Arc::new(Mutex::new(None)),
+            stage_artifacts: Arc::new(Mutex::new(HashMap::new())),
+            namespace: Namespace::default(),
+            quota: NamespaceQuota::default(),
         }
     }
 
+    /// Create a workflow engine confined to a registered namespace.
+    ///
+    /// Instrumentation is written under the namespace's storage and the
+    /// namespace quota limits how many workflows can be loaded.
+    pub fn for_namespace(
+        registry: &NamespaceRegistry,
+        namespace: Namespace,
+    ) -> Result<Self, NamespaceError> {
+        Self::for_namespace_with_context(registry, namespace, ConfigContext::from_env())
+    }
+
+    /// Create a namespaced workflow engine under the workflow root of `context`.
+    pub fn for_namespace_with_context(
+        registry: &NamespaceRegistry,
+        namespace: Namespace,
+        context: ConfigContext,
+    ) -> Result<Self, NamespaceError> {
+        let quota = registry.quota(&namespace)?;
+        let instrumentation = PipelineInstrumentation::with_context(&namespace, context)
+            .expect("failed to initialise pipeline instrumentation");
+        let registry = AgentRegistry::with_default_data().unwrap_or_else(|_| AgentRegistry::new());
+        let dispatcher = AgentDispatcher::new(registry, AgentFactory::new());
+        Ok(Self {
+            workflows: Arc::new(Mutex::new(HashMap::new())),
+            states: Arc::new(Mutex::new(HashMap::new())),
+            stage_states: Arc::new(Mutex::new(HashMap::new())),
+            instrumentation: Arc::new(instrumentation),
+            dispatcher: Arc::new(dispatcher),
+            kernel: None,
+            event_stream: Arc::new(Mutex::new(None)),
+            pending_approvals: Arc::new(Mutex::new(HashMap::new())),
+            granted_approvals: Arc::new(Mutex::new(HashMap::new())),
+            triggers: Arc::new(Mutex::new(TriggerRegistry::default())),
+            concurrency: Arc::new(Mutex::new(None)),
+            replay: Arc::new(Mutex::new(None)),
+            costs: Arc::new(Mutex::new(None)),
+            stage_cache: Arc::new(Mutex::new(None)),
+            stage_artifacts: Arc::new(Mutex::new(HashMap::new())),
+            namespace,
+            quota,
+        })
+    }
+
     pub fn instrumentation(&self) -> Arc<PipelineInstrumentation> {
         Arc::clone(&self.instrumentation)
     }
 
+    pub fn namespace(&self) -> &Namespace {
+        &self.namespace
+    }
+
     /// Create a workflow engine that interacts with kernel capabilities.
     pub fn with_kernel(kernel: KernelHandle) -> Self {
         let instrumentation =
@@ -264,6 +419,16 @@ impl WorkflowEngine {
             dispatcher: Arc::new(dispatcher),
             kernel: Some(kernel),
             event_stream: Arc::new(Mutex::new(None)),
+            pending_approvals: Arc::new(Mutex::new(HashMap::new())),
+            granted_approvals: Arc::new(Mutex::new(HashMap::new())),
+            triggers: Arc::new(Mutex::new(TriggerRegistry::default())),
+            concurrency: Arc::new(Mutex::new(None)),
+            replay: Arc::new(Mutex::new(None)),
+            costs: Arc::new(Mutex::new(None)),
+            stage_cache: Arc::new(Mutex::new(None)),
+            stage_artifacts: Arc::new(Mutex::new(HashMap::new())),
+            namespace: Namespace::default(),
+            quota: NamespaceQuota::default(),
         }
     }
 
@@ -277,11 +442,58 @@ impl WorkflowEngine {
         self.event_stream.lock().unwrap().clone()
     }
 
+    /// Gate `execute` on a concurrency governor shared with other engines on the host.
+    ///
+    /// The namespace's `max_concurrent_runs` quota, when set, becomes its limit, and
+    /// its `scheduling_weight` its share of the queue.
+    pub fn enable_concurrency_limits(&self, governor: Arc<ConcurrencyGovernor>) {
+        if self.quota.max_concurrent_runs.is_some() {
+            governor.set_namespace_limit(&self.namespace, self.quota.max_concurrent_runs);
+        }
+        if self.quota.scheduling_weight.is_some() {
+            governor.set_namespace_weight(&self.namespace, self.quota.scheduling_weight);
+        }
+        self.concurrency.lock().unwrap().replace(governor);
+    }
+
+    /// Charge each executed task to its agent in `ledger`, under this engine's namespace.
+    ///
+    /// A task is charged its run time as CPU-seconds (and GPU-seconds when it requires a
+    /// GPU), the `token_usage` its output or parameters report, and the bytes of the
+    /// sandbox outputs it stored. Replays are not charged.
+    pub fn enable_cost_accounting(&self, ledger: Arc<CostLedger>) {
+        self.costs.lock().unwrap().replace(ledger);
+    }
+
+    /// Reuse results from `cache` for stages whose [`StageCacheControl`] enables it.
+    ///
+    /// Recording and replaying runs bypass the cache.
+    pub fn enable_stage_cache(&self, cache: Arc<StageCache>) {
+        self.stage_cache.lock().unwrap().replace(cache);
+    }
+
+    /// Queue position of a workflow waiting for a concurrency slot.
+    pub fn queue_position(&self, workflow_id: &str) -> Option<usize> {
+        let governor = self.concurrency.lock().unwrap().clone()?;
+        governor.queue_position(&self.run_key(workflow_id))
+    }
+
     /// Load workflow from definition
     pub fn load_workflow(&self, workflow: Workflow) -> Result<String, String> {
+        self.validate_tool_requirements(&workflow)?;
+        contract::check_schemas(&workflow).map_err(|err| err.to_string())?;
         let id = workflow.name.clone();
 
         let mut workflows = self.workflows.lock().unwrap();
+        if !workflows.contains_key(&id) {
+            NamespaceQuota::check(
+                &self.namespace,
+                "workflows",
+                self.quota.max_workflows,
+                workflows.len(),
+            )
+            .map_err(|err| err.to_string())?;
+        }
         workflows.insert(id.clone(), workflow);
 
         let mut states = self.states.lock().unwrap();
@@ -290,7 +502,7 @@ impl WorkflowEngine {
         self.emit_event(WorkflowEvent::WorkflowState {
             workflow_id: id.clone(),
             state: WorkflowState::Pending,
-            timestamp: now_iso(),
+            timestamp: self.now_iso(),
         });
 
         println!("[WORKFLOW] Loaded workflow: {}", id);
@@ -307,25 +519,52 @@ impl WorkflowEngine {
                 .ok_or_else(|| format!("Workflow not found: {}", workflow_id))?
         };
 
-        // Update state to running
+        let resuming = self.get_state(workflow_id) == Some(WorkflowState::Paused);
+        let _permit = self.acquire_run_slot(workflow_id);
+
+        // Update state to running, remembering whether this resumes a paused run
         {
             let mut states = self.states.lock().unwrap();
             states.insert(workflow_id.to_string(), WorkflowState::Running);
         }
+        if !resuming {
+            // A fresh run must collect its approvals again
+            self.granted_approvals
+                .lock()
+                .unwrap()
+                .retain(|(id, _), _| id != workflow_id);
+            if let Some(cache) = self.stage_cache.lock().unwrap().as_ref() {
+                cache.reset_roots(workflow_id);
+            }
+            self.stage_artifacts
+                .lock()
+                .unwrap()
+                .retain(|(id, _), _| id != workflow_id);
+        }
 
         self.emit_event(WorkflowEvent::WorkflowState {
             workflow_id: workflow_id.to_string(),
             state: WorkflowState::Running,
-            timestamp: now_iso(),
+            timestamp: self.now_iso(),
         });
 
         println!("[WORKFLOW] Executing workflow: {}", workflow.name);
 
-        let run_started_at = current_timestamp_millis();
+        let run_started_at = self.now_millis();
         let mut tracker = GoalRunTracker::default();
 
+        // Stages whose side effects must be compensated if a later stage fails
+        let mut completed: Vec<&Stage> = Vec::new();
+
         // Execute stages
         for stage in &workflow.stages {
+            if resuming
+                && self.stage_states(workflow_id).get(&stage.name) == Some(&StageState::Completed)
+            {
+                completed.push(stage);
+                continue;
+            }
+
             // Check dependencies
             if !self.check_dependencies(workflow_id, &stage.depends_on)? {
                 println!(
@@ -335,12 +574,20 @@ impl WorkflowEngine {
                 continue;
             }
 
+            if let StageType::Approval(requirement) = &stage.stage_type {
+                if !self.approval_granted(workflow_id, &stage.name) {
+                    self.request_approval(workflow_id, &stage.name, requirement);
+                    return Ok(());
+                }
+            }
+
             if let Err(err) = self.execute_stage(workflow_id, stage, &mut tracker) {
                 println!(
                     "[WORKFLOW] Stage {} failed for workflow {}: {}",
                     stage.name, workflow.name, err
                 );
                 self.set_stage_state(workflow_id, &stage.name, StageState::Failed);
+                self.compensate(workflow_id, &completed, &mut tracker);
                 {
                     let mut states = self.states.lock().unwrap();
                     states.insert(workflow_id.to_string(), WorkflowState::Failed);
@@ -348,9 +595,9 @@ impl WorkflowEngine {
                 self.emit_event(WorkflowEvent::WorkflowState {
                     workflow_id: workflow_id.to_string(),
                     state: WorkflowState::Failed,
-                    timestamp: now_iso(),
+                    timestamp: self.now_iso(),
                 });
-                let completed_at = current_timestamp_millis();
+                let completed_at = self.now_millis();
                 let outcome = GoalOutcomeRecord {
                     goal_id: workflow_id.to_string(),
                     workflow_id: workflow.name.clone(),
@@ -366,9 +613,10 @@ impl WorkflowEngine {
                 }
                 return Err(err);
             }
+            completed.push(stage);
         }
 
-        let completed_at = current_timestamp_millis();
+        let completed_at = self.now_millis();
         let outcome = GoalOutcomeRecord {
             goal_id: workflow_id.to_string(),
             workflow_id: workflow.name.clone(),
@@ -392,7 +640,7 @@ impl WorkflowEngine {
         self.emit_event(WorkflowEvent::WorkflowState {
             workflow_id: workflow_id.to_string(),
             state: WorkflowState::Completed,
-            timestamp: now_iso(),
+            timestamp: self.now_iso(),
         });
 
         println!(
@@ -402,6 +650,267 @@ impl WorkflowEngine {
         Ok(())
     }
 
+    /// Execute a workflow while recording every dispatch result, clock read, and the
+    /// run seed into a [`ReplayBundle`].
+    pub fn record(&self, workflow_id: &str) -> Result<ReplayBundle, String> {
+        let workflow = self
+            .get_workflow(workflow_id)
+            .ok_or_else(|| format!("Workflow not found: {}", workflow_id))?;
+        let seed = current_timestamp_millis() as u64;
+        self.replay
+            .lock()
+            .unwrap()
+            .replace(ReplaySession::recording(seed));
+        let result = self.execute(workflow_id);
+        let session = self
+            .replay
+            .lock()
+            .unwrap()
+            .take()
+            .expect("recording session is active");
+
+        Ok(ReplayBundle {
+            version: REPLAY_BUNDLE_VERSION,
+            workflow_id: workflow_id.to_string(),
+            workflow,
+            seed,
+            recorded_at: now_iso(),
+            clock: session.recorded_clock,
+            dispatches: session.recorded_dispatches,
+            receipts: session.receipts,
+            error: result.err(),
+            final_state: self.get_state(workflow_id),
+        })
+    }
+
+    /// Re-execute a recorded run without calling agents.
+    ///
+    /// Dispatch results and clock reads come from the bundle; the replay fails if
+    /// the run asks for different dispatches, its stage receipts differ from the
+    /// recorded ones, or it ends with a different outcome.
+    pub fn replay(&self, bundle: &ReplayBundle) -> Result<ReplayReport, String> {
+        if bundle.version != REPLAY_BUNDLE_VERSION {
+            return Err(ReplayError::UnsupportedVersion(bundle.version).to_string());
+        }
+        let workflow_id = self.load_workflow(bundle.workflow.clone())?;
+        self.replay
+            .lock()
+            .unwrap()
+            .replace(ReplaySession::replaying(bundle));
+        let result = self.execute(&workflow_id);
+        let session = self
+            .replay
+            .lock()
+            .unwrap()
+            .take()
+            .expect("replay session is active");
+
+        let mut report = session.verify(bundle).map_err(|err| err.to_string())?;
+        let error = result.err();
+        if error != bundle.error {
+            return Err(ReplayError::OutcomeMismatch {
+                expected: bundle.error.clone(),
+                found: error,
+            }
+            .to_string());
+        }
+        report.final_state = self.get_state(&workflow_id);
+        println!(
+            "[WORKFLOW] Replay of {} matched {} stage receipts",
+            workflow_id, report.receipts_matched
+        );
+        Ok(report)
+    }
+
+    /// Seed for randomized choices in the current run, restored when it is replayed.
+    pub fn run_seed(&self) -> Option<u64> {
+        self.replay
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|session| session.seed)
+    }
+
+    /// Dispatch a task to its agent, or return the recorded result during a replay.
+    fn dispatch_task(&self, stage_id: &str, task: &Task) -> Result<TaskDispatchReceipt, String> {
+        let mut replay = self.replay.lock().unwrap();
+        if let Some(recorded) = replay
+            .as_mut()
+            .and_then(|session| session.replay_dispatch(stage_id, task))
+        {
+            return recorded;
+        }
+        let result = self.dispatcher.dispatch(task).map_err(|err| err.to_string());
+        if let Some(session) = replay.as_mut() {
+            session.record_dispatch(stage_id, task, &result);
+        }
+        result
+    }
+
+    fn now_iso(&self) -> String {
+        match self.replay.lock().unwrap().as_mut() {
+            Some(session) => session.clock_iso(now_iso),
+            None => now_iso(),
+        }
+    }
+
+    fn now_millis(&self) -> u128 {
+        match self.replay.lock().unwrap().as_mut() {
+            Some(session) => session.clock_millis(current_timestamp_millis),
+            None => current_timestamp_millis(),
+        }
+    }
+
+    /// Wait for a concurrency slot when limits are enabled, reporting the queue position.
+    fn acquire_run_slot(&self, workflow_id: &str) -> Option<ConcurrencyPermit> {
+        let governor = self.concurrency.lock().unwrap().clone()?;
+        Some(
+            governor.acquire(&self.namespace, &self.run_key(workflow_id), |position| {
+                self.states
+                    .lock()
+                    .unwrap()
+                    .insert(workflow_id.to_string(), WorkflowState::Queued);
+                self.emit_event(WorkflowEvent::RunQueued {
+                    workflow_id: workflow_id.to_string(),
+                    namespace: self.namespace.clone(),
+                    position,
+                    timestamp: self.now_iso(),
+                });
+            }),
+        )
+    }
+
+    fn run_key(&self, workflow_id: &str) -> String {
+        format!("workflow:{}/{}", self.namespace, workflow_id)
+    }
+
+    /// Plan a workflow run without dispatching anything.
+    ///
+    /// Agents are resolved and their tool requirements checked, reward policy and
+    /// approval gates are evaluated, and durations are estimated from recorded goal
+    /// outcomes. No state, events, or ledger entries are touched.
+    pub fn dry_run(&self, workflow_id: &str) -> Result<ExecutionPlan, String> {
+        let workflow = self
+            .get_workflow(workflow_id)
+            .ok_or_else(|| format!("Workflow not found: {}", workflow_id))?;
+
+        let snapshots = self
+            .instrumentation
+            .goal_metrics_snapshot()
+            .map_err(|err| format!("failed to load goal metrics: {}", err))?;
+        let history = dry_run::historical_lead_time(&snapshots, workflow_id, &workflow.name);
+        let task_counts: Vec<usize> = workflow
+            .stages
+            .iter()
+            .map(|stage| stage.tasks.len())
+            .collect();
+        let stage_estimates = history
+            .map(|(_, average_ms)| dry_run::apportion_estimate(average_ms, &task_counts))
+            .unwrap_or_default();
+
+        let mut planned: Vec<&str> = Vec::new();
+        let mut stages = Vec::with_capacity(workflow.stages.len());
+        for (index, stage) in workflow.stages.iter().enumerate() {
+            let dependencies_met = stage
+                .depends_on
+                .iter()
+                .all(|dependency| planned.contains(&dependency.as_str()));
+            if dependencies_met {
+                planned.push(stage.name.as_str());
+            }
+
+            let approval = match &stage.stage_type {
+                StageType::Approval(requirement) => Some(requirement.clone()),
+                _ => None,
+            };
+            let awaits_approval =
+                approval.is_some() && !self.approval_granted(workflow_id, &stage.name);
+
+            stages.push(StagePlan {
+                name: stage.name.clone(),
+                stage_type: stage.stage_type.clone(),
+                depends_on: stage.depends_on.clone(),
+                dependencies_met,
+                approval,
+                awaits_approval,
+                tasks: stage
+                    .tasks
+                    .iter()
+                    .map(|task| self.plan_task(task))
+                    .collect(),
+                compensation_tasks: stage.compensation.len(),
+                estimated_duration_ms: stage_estimates.get(index).copied(),
+            });
+        }
+
+        Ok(ExecutionPlan {
+            workflow_id: workflow_id.to_string(),
+            workflow_name: workflow.name,
+            stages,
+            history_runs: history.map(|(runs, _)| runs).unwrap_or(0),
+            estimated_duration_ms: history.map(|(_, average_ms)| average_ms.round() as u64),
+        })
+    }
+
+    fn plan_task(&self, task: &Task) -> TaskPlan {
+        let policy = self
+            .instrumentation
+            .evaluate_agent_for_execution(&task.agent);
+        let mut issues = Vec::new();
+        if policy.requires_manual_approval {
+            issues.push(format!(
+                "agent requires manual approval before execution: {}",
+                policy
+                    .reason
+                    .clone()
+                    .unwrap_or_else(|| "reward score below threshold".to_string())
+            ));
+        }
+
+        let (resolved_agent, resolved_role, tool_checks, placement) =
+            match self.dispatcher.plan(task) {
+                Ok(plan) => {
+                    if !plan.placement.is_placed() {
+                        issues.push(format!(
+                            "task would be queued until resources are available: {}",
+                            plan.placement.reasons.join("; ")
+                        ));
+                    }
+                    issues.extend(
+                        plan.tool_receipts
+                            .iter()
+                            .filter(|receipt| matches!(receipt.status, ToolExecutionStatus::Failed))
+                            .filter_map(|receipt| receipt.error.clone()),
+                    );
+                    let role = task
+                        .agent_role
+                        .clone()
+                        .unwrap_or_else(|| plan.agent_metadata.role.clone());
+                    (
+                        Some(plan.agent_metadata.agent_id),
+                        Some(role),
+                        plan.tool_receipts,
+                        Some(plan.placement),
+                    )
+                }
+                Err(err) => {
+                    issues.push(format!("agent dispatch would fail: {}", err));
+                    (None, task.agent_role.clone(), Vec::new(), None)
+                }
+            };
+
+        TaskPlan {
+            agent: task.agent.clone(),
+            action: task.action.clone(),
+            resolved_agent,
+            resolved_role,
+            policy,
+            tool_checks,
+            placement,
+            issues,
+        }
+    }
+
     /// Execute a single stage
     fn execute_stage(
         &self,
@@ -416,18 +925,69 @@ impl WorkflowEngine {
 
         // Update stage state
         self.set_stage_state(workflow_id, &stage.name, StageState::Running);
-
-        let artifacts = match stage.stage_type {
-            StageType::Sequential => self.execute_sequential(workflow_id, stage, tracker)?,
-            StageType::Parallel => self.execute_parallel(workflow_id, stage, tracker)?,
-            StageType::Conditional => self.execute_conditional(workflow_id, stage, tracker)?,
-            StageType::Loop => self.execute_loop(workflow_id, stage, tracker)?,
+        self.check_stage_inputs(workflow_id, stage)?;
+
+        let cache = self.stage_cache.lock().unwrap().clone();
+        let cache_key = cache
+            .as_ref()
+            .and_then(|cache| self.stage_cache_key(cache, workflow_id, stage));
+        let cached = cache
+            .as_ref()
+            .zip(cache_key.as_ref())
+            .and_then(|(cache, (key, _))| cache.lookup(key, &stage.cache));
+
+        let receipt = match cached {
+            Some(hit) => {
+                println!(
+                    "[WORKFLOW] Reusing cached results for {}::{} (key={})",
+                    workflow_id, stage.name, hit.key
+                );
+                self.record_stage_artifacts(workflow_id, stage, hit.artifacts)?;
+                self.instrumentation
+                    .log_cached_stage_receipt(workflow_id, hit.receipt)
+                    .map_err(|err| format!("stage receipt failed: {}", err))?
+            }
+            None => {
+                let artifacts = match &stage.stage_type {
+                    StageType::Sequential => {
+                        self.execute_sequential(workflow_id, stage, tracker)?
+                    }
+                    StageType::Parallel => self.execute_parallel(workflow_id, stage, tracker)?,
+                    StageType::Conditional => {
+                        self.execute_conditional(workflow_id, stage, tracker)?
+                    }
+                    StageType::Loop => self.execute_loop(workflow_id, stage, tracker)?,
+                    StageType::Approval(_) => self.approval_artifacts(workflow_id, &stage.name)?,
+                };
+                self.record_stage_artifacts(workflow_id, stage, artifacts.clone())?;
+
+                let receipt = self
+                    .instrumentation
+                    .log_stage_receipt(workflow_id, stage, &artifacts)
+                    .map_err(|err| format!("stage receipt failed: {}", err))?;
+                if let (Some(cache), Some((key, agent_versions))) = (&cache, cache_key) {
+                    let entry = CachedStage {
+                        key,
+                        workflow_id: workflow_id.to_string(),
+                        stage_id: stage.name.clone(),
+                        artifacts,
+                        receipt: receipt.clone(),
+                        agent_versions,
+                        stored_at: current_timestamp_millis(),
+                    };
+                    if let Err(err) = cache.store(entry) {
+                        println!(
+                            "[WORKFLOW] Failed to cache results for {}::{}: {}",
+                            workflow_id, stage.name, err
+                        );
+                    }
+                }
+                receipt
+            }
         };
-
-        let receipt = self
-            .instrumentation
-            .log_stage_receipt(workflow_id, stage, &artifacts)
-            .map_err(|err| format!("stage receipt failed: {}", err))?;
+        if let Some(cache) = &cache {
+            cache.record_root(workflow_id, &stage.name, &receipt.merkle_root);
+        }
 
         println!(
             "[WORKFLOW] Stage receipt generated for {}::{} (root={})",
@@ -438,13 +998,257 @@ impl WorkflowEngine {
             workflow_id: workflow_id.to_string(),
             stage_id: stage.name.clone(),
             receipt,
-            timestamp: now_iso(),
+            timestamp: self.now_iso(),
         });
         // Mark stage as completed
         self.set_stage_state(workflow_id, &stage.name, StageState::Completed);
         Ok(())
     }
 
+    /// Check the artifacts of the stages `stage` depends on against its input contract.
+    fn check_stage_inputs(&self, workflow_id: &str, stage: &Stage) -> Result<(), String> {
+        if stage.contract.input.is_none() {
+            return Ok(());
+        }
+        let recorded = self.stage_artifacts.lock().unwrap();
+        for dependency in &stage.depends_on {
+            let key = (workflow_id.to_string(), dependency.clone());
+            if let Some(artifacts) = recorded.get(&key) {
+                contract::check_input(stage, dependency, artifacts)
+                    .map_err(|err| err.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a stage's artifacts against its output contract and keep them for the
+    /// stages depending on it.
+    fn record_stage_artifacts(
+        &self,
+        workflow_id: &str,
+        stage: &Stage,
+        artifacts: Vec<Value>,
+    ) -> Result<(), String> {
+        contract::check_output(stage, &artifacts).map_err(|err| err.to_string())?;
+        self.stage_artifacts
+            .lock()
+            .unwrap()
+            .insert((workflow_id.to_string(), stage.name.clone()), artifacts);
+        Ok(())
+    }
+
+    /// Cache key and agent versions for a stage that may reuse cached results.
+    ///
+    /// `None` when the stage has not enabled caching, awaits approvals, runs under a
+    /// replay session, or names an agent that cannot be resolved.
+    fn stage_cache_key(
+        &self,
+        cache: &StageCache,
+        workflow_id: &str,
+        stage: &Stage,
+    ) -> Option<(String, BTreeMap<String, String>)> {
+        if !stage.cache.enabled
+            || matches!(stage.stage_type, StageType::Approval(_))
+            || self.replay.lock().unwrap().is_some()
+        {
+            return None;
+        }
+        let mut agent_versions = BTreeMap::new();
+        for task in &stage.tasks {
+            let metadata = self.dispatcher.resolve_agent_metadata(task).ok()?;
+            agent_versions.insert(
+                metadata.agent_id,
+                metadata
+                    .version
+                    .unwrap_or_else(|| "unversioned".to_string()),
+            );
+        }
+        let key = cache.key(workflow_id, stage, &agent_versions)?;
+        Some((key, agent_versions))
+    }
+
+    /// Run compensation tasks for completed stages in reverse order.
+    ///
+    /// Compensation is best effort: a failing compensation task is recorded and the
+    /// remaining stages are still compensated.
+    fn compensate(&self, workflow_id: &str, completed: &[&Stage], tracker: &mut GoalRunTracker) {
+        for stage in completed
+            .iter()
+            .rev()
+            .filter(|stage| !stage.compensation.is_empty())
+        {
+            println!(
+                "[WORKFLOW] Compensating stage {} for workflow {}",
+                stage.name, workflow_id
+            );
+            let results: Vec<Value> = stage
+                .compensation
+                .iter()
+                .map(
+                    |task| match self.execute_task(workflow_id, &stage.name, task, tracker) {
+                        Ok(output) => json!({
+                            "action": task.action,
+                            "status": "compensated",
+                            "output": output,
+                        }),
+                        Err(err) => json!({
+                            "action": task.action,
+                            "status": "failed",
+                            "error": err,
+                        }),
+                    },
+                )
+                .collect();
+
+            match self
+                .instrumentation
+                .log_compensation_receipt(workflow_id, stage, &results)
+            {
+                Ok(receipt) => self.emit_event(WorkflowEvent::StageReceiptGenerated {
+                    workflow_id: workflow_id.to_string(),
+                    stage_id: stage.name.clone(),
+                    receipt,
+                    timestamp: self.now_iso(),
+                }),
+                Err(err) => println!(
+                    "[WORKFLOW] Compensation receipt failed for {}::{}: {}",
+                    workflow_id, stage.name, err
+                ),
+            }
+            self.set_stage_state(workflow_id, &stage.name, StageState::Compensated);
+        }
+    }
+
+    /// Pause the workflow and issue a resume token for an approval stage
+    fn request_approval(
+        &self,
+        workflow_id: &str,
+        stage_name: &str,
+        requirement: &AgentApprovalRequirement,
+    ) {
+        let approval = PendingApproval::issue(workflow_id, stage_name, requirement.clone());
+        println!(
+            "[WORKFLOW] Stage {} awaiting approval from role {} (token={})",
+            stage_name, requirement.role, approval.token
+        );
+        {
+            let mut pending = self.pending_approvals.lock().unwrap();
+            pending.retain(|_, existing| {
+                existing.workflow_id != workflow_id || existing.stage_id != stage_name
+            });
+            pending.insert(approval.token.clone(), approval.clone());
+        }
+        self.set_stage_state(workflow_id, stage_name, StageState::Pending);
+        {
+            let mut states = self.states.lock().unwrap();
+            states.insert(workflow_id.to_string(), WorkflowState::Paused);
+        }
+        self.emit_event(WorkflowEvent::WorkflowState {
+            workflow_id: workflow_id.to_string(),
+            state: WorkflowState::Paused,
+            timestamp: self.now_iso(),
+        });
+        self.emit_event(WorkflowEvent::ApprovalRequested {
+            workflow_id: workflow_id.to_string(),
+            approval,
+            timestamp: self.now_iso(),
+        });
+    }
+
+    fn approval_granted(&self, workflow_id: &str, stage_name: &str) -> bool {
+        self.granted_approvals
+            .lock()
+            .unwrap()
+            .contains_key(&(workflow_id.to_string(), stage_name.to_string()))
+    }
+
+    /// The registered approval becomes the approval stage's receipt artifact
+    fn approval_artifacts(
+        &self,
+        workflow_id: &str,
+        stage_name: &str,
+    ) -> Result<Vec<Value>, String> {
+        let granted = self.granted_approvals.lock().unwrap();
+        let approval = granted
+            .get(&(workflow_id.to_string(), stage_name.to_string()))
+            .ok_or_else(|| format!("stage {stage_name} has no registered approval"))?;
+        let artifact = serde_json::to_value(approval)
+            .map_err(|err| format!("failed to serialise approval: {err}"))?;
+        Ok(vec![artifact])
+    }
+
+    /// Tasks the dispatcher queued because the host lacks their required resources
+    pub fn queued_tasks(&self) -> Vec<QueuedTask> {
+        self.dispatcher.queued_tasks()
+    }
+
+    /// Approvals that are waiting for a qualifying approver
+    pub fn pending_approvals(&self) -> Vec<PendingApproval> {
+        let pending = self.pending_approvals.lock().unwrap();
+        let mut approvals: Vec<PendingApproval> = pending.values().cloned().collect();
+        approvals.sort_by(|a, b| a.requested_at.cmp(&b.requested_at));
+        approvals
+    }
+
+    /// Build the approval `agent_id` gives, with its role from the agent registry and its
+    /// trust score from its reward standing. Only the evidence comes from the caller.
+    pub fn approval_from(
+        &self,
+        agent_id: &str,
+        evidence_tags: Vec<String>,
+        evidence_references: Vec<String>,
+    ) -> Result<AgentApproval, String> {
+        let metadata = self
+            .agent_registry()
+            .get(agent_id)
+            .ok_or_else(|| format!("unknown approver: {agent_id}"))?;
+        let status = self.instrumentation.evaluate_agent_for_execution(agent_id);
+        Ok(AgentApproval {
+            role: metadata.role,
+            agent_id: agent_id.to_string(),
+            trust_score: status.trust_score(),
+            evidence_tags,
+            evidence_references,
+            recorded_at: u64::try_from(current_timestamp_millis()).unwrap_or(u64::MAX),
+        })
+    }
+
+    /// Register an approval against a resume token and resume the paused workflow.
+    ///
+    /// Returns the workflow state after resuming, which may be `Paused` again when a
+    /// later approval stage is reached.
+    pub fn register_approval(
+        &self,
+        token: &str,
+        approval: AgentApproval,
+    ) -> Result<WorkflowState, String> {
+        let pending = {
+            let mut pending_approvals = self.pending_approvals.lock().unwrap();
+            let pending = pending_approvals
+                .get(token)
+                .cloned()
+                .ok_or_else(|| format!("unknown approval token: {token}"))?;
+            pending.validate(&approval)?;
+            pending_approvals.remove(token);
+            pending
+        };
+
+        println!(
+            "[WORKFLOW] Approval for {}::{} registered by {}",
+            pending.workflow_id, pending.stage_id, approval.agent_id
+        );
+        self.granted_approvals.lock().unwrap().insert(
+            (pending.workflow_id.clone(), pending.stage_id.clone()),
+            approval,
+        );
+
+        let result = self.execute(&pending.workflow_id);
+        let state = self
+            .get_state(&pending.workflow_id)
+            .unwrap_or(WorkflowState::Failed);
+        result.map(|_| state)
+    }
+
     /// Execute tasks sequentially
     fn execute_sequential(
         &self,
@@ -510,6 +1314,7 @@ impl WorkflowEngine {
         task: &Task,
         tracker: &mut GoalRunTracker,
     ) -> Result<Value, String> {
+        let started = Instant::now();
         let approval = self
             .instrumentation
             .evaluate_agent_for_execution(&task.agent);
@@ -526,7 +1331,7 @@ impl WorkflowEngine {
 
         let token_ratio = extract_token_ratio(&task.parameters);
         let rollback_flag = task_requests_rollback(task);
-        let dispatch_receipt = self.dispatcher.dispatch(task).map_err(|err| {
+        let dispatch_receipt = self.dispatch_task(stage_id, task).map_err(|err| {
             println!(
                 "[WORKFLOW] Dispatcher failed for agent {}: {}",
                 task.agent, err
@@ -542,11 +1347,18 @@ impl WorkflowEngine {
             .agent_role
             .clone()
             .unwrap_or_else(|| dispatch_receipt.agent_metadata.role.clone());
+        let sandbox =
+            TaskSandbox::create(&self.instrumentation.context().workflow_root(), &task.sandbox)
+            .map_err(|err| format!("failed to prepare task sandbox: {}", err))?;
         let mut observed_task = task.clone();
         observed_task.agent = resolved_agent.clone();
         if observed_task.agent_role.is_none() {
             observed_task.agent_role = Some(resolved_role.clone());
         }
+        observed_task.parameters.insert(
+            "workspace".to_string(),
+            json!(sandbox.path().display().to_string()),
+        );
 
         let result = (|| {
             println!(
@@ -578,7 +1390,7 @@ impl WorkflowEngine {
                 "action": task.action,
                 "parameters": parameters_to_value(&task.parameters),
                 "status": "completed",
-                "timestamp": now_iso(),
+                "timestamp": self.now_iso(),
             }))
         })();
 
@@ -589,6 +1401,30 @@ impl WorkflowEngine {
         if final_result.is_ok() && dispatch_receipt.output != Value::Null {
             final_result = Ok(dispatch_receipt.output.clone());
         }
+        final_result = final_result.and_then(|mut output| {
+            if task.sandbox.outputs.is_empty() {
+                return Ok(output);
+            }
+            let destination = self
+                .instrumentation
+                .context()
+                .resolve_path(self.namespace.scope_path("storage/db/artifacts"))
+                .join(workflow_id)
+                .join(stage_id);
+            let collected = sandbox
+                .collect(&task.sandbox, &destination)
+                .map_err(|err| format!("failed to collect task outputs: {}", err))?;
+            if let Some(fields) = output.as_object_mut() {
+                fields.insert("sandbox_outputs".to_string(), json!(collected));
+            }
+            Ok(output)
+        });
+        if let Err(err) = sandbox.close() {
+            println!(
+                "[WORKFLOW] Failed to clean up sandbox for {}::{}: {}",
+                workflow_id, stage_id, err
+            );
+        }
 
         let success = final_result.is_ok();
         tracker.record(&resolved_agent, success, token_ratio, rollback_flag);
@@ -615,7 +1451,7 @@ impl WorkflowEngine {
                     "failed".to_string()
                 },
                 notes,
-                recorded_at: now_iso(),
+                recorded_at: self.now_iso(),
             };
             if let Err(err) = self.instrumentation.record_deployment_outcome(record) {
                 println!(
@@ -625,9 +1461,80 @@ impl WorkflowEngine {
             }
         }
 
+        self.charge_task(
+            workflow_id,
+            &resolved_agent,
+            task,
+            started.elapsed(),
+            &final_result,
+        );
         final_result
     }
 
+    /// Attribute a task's resource usage to its agent when cost accounting is enabled.
+    fn charge_task(
+        &self,
+        workflow_id: &str,
+        agent: &str,
+        task: &Task,
+        elapsed: std::time::Duration,
+        result: &Result<Value, String>,
+    ) {
+        let Some(ledger) = self.costs.lock().unwrap().clone() else {
+            return;
+        };
+        if self
+            .replay
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(ReplaySession::is_replaying)
+        {
+            return;
+        }
+
+        let output = result.as_ref().ok();
+        let seconds = elapsed.as_secs_f64();
+        let mut usage = CostUsage::cpu(seconds);
+        if task.resources.gpu {
+            usage.gpu_seconds = seconds;
+        }
+        usage.tokens = output
+            .and_then(|output| output.get("token_usage"))
+            .or_else(|| task.parameters.get("token_usage"))
+            .and_then(Value::as_f64)
+            .map(|tokens| tokens.max(0.0) as u64)
+            .unwrap_or_default();
+        usage.storage_bytes = output
+            .and_then(|output| output.get("sandbox_outputs"))
+            .and_then(Value::as_array)
+            .map(|artifacts| {
+                artifacts
+                    .iter()
+                    .filter_map(|artifact| artifact.get("size_bytes").and_then(Value::as_u64))
+                    .sum()
+            })
+            .unwrap_or_default();
+
+        let account = CostAccount::new(format!("agent:{agent}"), self.namespace.to_string());
+        match ledger.charge(CostKind::Workflow, workflow_id, account, usage) {
+            Ok(alerts) => {
+                for alert in alerts {
+                    let _ = self.instrumentation.log_pipeline_event(
+                        "cost",
+                        workflow_id,
+                        "cost.budget_alert",
+                        json!(alert),
+                    );
+                }
+            }
+            Err(err) => println!(
+                "[WORKFLOW] Failed to record task cost for {}: {}",
+                workflow_id, err
+            ),
+        }
+    }
+
     fn log_task_dispatch(
         &self,
         workflow_id: &str,
@@ -666,6 +1573,8 @@ impl WorkflowEngine {
             task: task.clone(),
             output: dispatch_output,
             tool_receipts,
+            placement: None,
+            trace_id: None,
         };
         if let Err(err) = self
             .instrumentation
@@ -742,6 +1651,11 @@ impl WorkflowEngine {
     }
 
     fn emit_event(&self, event: WorkflowEvent) {
+        if let WorkflowEvent::StageReceiptGenerated { receipt, .. } = &event {
+            if let Some(session) = self.replay.lock().unwrap().as_mut() {
+                session.receipts.push(RecordedReceipt::from(receipt));
+            }
+        }
         if let Some(stream) = self.event_stream.lock().unwrap().clone() {
             stream.send(event);
         }
@@ -760,7 +1674,7 @@ impl WorkflowEngine {
             .or_insert_with(HashMap::new)
             .insert(stage_name.to_string(), state);
 
-        let timestamp = now_iso();
+        let timestamp = self.now_iso();
         self.emit_event(WorkflowEvent::StageState {
             workflow_id: workflow_id.to_string(),
             stage_id: stage_name.to_string(),
@@ -789,23 +1703,282 @@ impl WorkflowEngine {
         }
     }
 
-    /// Get workflow state
-    pub fn get_state(&self, workflow_id: &str) -> Option<WorkflowState> {
-        let states = self.states.lock().unwrap();
-        states.get(workflow_id).cloned()
+    /// Subscribe a loaded workflow to a bus topic or ledger event kind
+    pub fn register_trigger(&self, binding: TriggerBinding) -> Result<(), TriggerError> {
+        if !self
+            .workflows
+            .lock()
+            .unwrap()
+            .contains_key(&binding.workflow_id)
+        {
+            return Err(TriggerError::UnknownWorkflow(binding.workflow_id));
+        }
+        let compiled = CompiledTrigger::compile(binding)?;
+        let mut triggers = self.triggers.lock().unwrap();
+        if compiled.binding.source != Some(TriggerSource::Bus) && triggers.ledger.is_none() {
+            triggers.ledger = Some(instrumentation::subscribe_ledger_events());
+        }
+        println!(
+            "[WORKFLOW] Workflow {} triggers on {}",
+            compiled.binding.workflow_id, compiled.binding.topic
+        );
+        triggers.bindings.push(compiled);
+        Ok(())
     }
-}
 
-fn parameters_to_value(parameters: &HashMap<String, Value>) -> Value {
-    serde_json::to_value(parameters).unwrap_or(Value::Null)
-}
+    pub fn trigger_bindings(&self) -> Vec<TriggerBinding> {
+        let triggers = self.triggers.lock().unwrap();
+        triggers
+            .bindings
+            .iter()
+            .map(|trigger| trigger.binding.clone())
+            .collect()
+    }
 
-fn extract_token_ratio(parameters: &HashMap<String, Value>) -> Option<f64> {
-    parameters
-        .get("token_ratio")
-        .and_then(Value::as_f64)
-        .or_else(|| {
-            match (
+    /// Consume `{"topic", "payload"}` bus messages from an IPC channel on each poll
+    pub fn listen_on_ipc_channel(&self, channel_id: ChannelId) {
+        self.triggers.lock().unwrap().ipc_channel = Some(channel_id);
+    }
+
+    /// Drain pending bus messages and ledger events, running every triggered workflow
+    pub fn poll_triggers(&self) -> Vec<TriggerRun> {
+        self.drain_trigger_events()
+            .iter()
+            .flat_map(|event| self.dispatch_event(event))
+            .collect()
+    }
+
+    fn drain_trigger_events(&self) -> Vec<TriggerEvent> {
+        let mut triggers = self.triggers.lock().unwrap();
+        let mut events = Vec::new();
+        if let Some(channel_id) = triggers.ipc_channel {
+            while let Some(message) = ipc::receive_message(channel_id) {
+                match TriggerEvent::from_ipc_message(&message) {
+                    Ok(event) => events.push(event),
+                    Err(err) => println!("[WORKFLOW] Ignoring bus message: {}", err),
+                }
+            }
+        }
+        if let Some(receiver) = triggers.ledger.as_mut() {
+            loop {
+                match receiver.try_recv() {
+                    Ok(event) if event.namespace == self.namespace => {
+                        events.push(TriggerEvent::from_ledger_event(&event))
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                        println!(
+                            "[WORKFLOW] Trigger listener skipped {} ledger events",
+                            skipped
+                        )
+                    }
+                    Err(_) => break,
+                }
+            }
+        }
+        events
+    }
+
+    /// Run a fresh instance of every workflow whose trigger matches `event`
+    pub fn dispatch_event(&self, event: &TriggerEvent) -> Vec<TriggerRun> {
+        let matched: Vec<(String, u64)> = {
+            let mut triggers = self.triggers.lock().unwrap();
+            let workflow_ids: Vec<String> = triggers
+                .bindings
+                .iter()
+                .filter(|trigger| trigger.matches(event))
+                .map(|trigger| trigger.binding.workflow_id.clone())
+                .collect();
+            workflow_ids
+                .into_iter()
+                .map(|workflow_id| {
+                    triggers.sequence += 1;
+                    (workflow_id, triggers.sequence)
+                })
+                .collect()
+        };
+        matched
+            .into_iter()
+            .map(|(workflow_id, sequence)| self.run_triggered(&workflow_id, sequence, event))
+            .collect()
+    }
+
+    /// Instantiate a workflow with the event as the `trigger` input of every task
+    fn run_triggered(&self, workflow_id: &str, sequence: u64, event: &TriggerEvent) -> TriggerRun {
+        let instance_id = format!("{workflow_id}-trigger-{sequence}");
+        println!(
+            "[WORKFLOW] {} event {} triggered {}",
+            event.source, event.topic, instance_id
+        );
+        let result = self
+            .get_workflow(workflow_id)
+            .ok_or_else(|| format!("Workflow not found: {}", workflow_id))
+            .and_then(|mut instance| {
+                instance.name = instance_id.clone();
+                let input = json!({
+                    "source": event.source,
+                    "topic": event.topic,
+                    "payload": event.payload,
+                });
+                for task in instance
+                    .stages
+                    .iter_mut()
+                    .flat_map(|stage| stage.tasks.iter_mut())
+                {
+                    task.parameters
+                        .entry("trigger".to_string())
+                        .or_insert_with(|| input.clone());
+                }
+                self.load_workflow(instance)
+            })
+            .and_then(|id| self.execute(&id));
+        TriggerRun {
+            workflow_id: workflow_id.to_string(),
+            state: self
+                .get_state(&instance_id)
+                .unwrap_or(WorkflowState::Failed),
+            instance_id,
+            topic: event.topic.clone(),
+            error: result.err(),
+        }
+    }
+
+    /// Get workflow state
+    pub fn get_state(&self, workflow_id: &str) -> Option<WorkflowState> {
+        let states = self.states.lock().unwrap();
+        states.get(workflow_id).cloned()
+    }
+
+    /// Get workflow state on behalf of another namespace, denied unless granted
+    pub fn get_state_for(
+        &self,
+        requester: &Namespace,
+        registry: &NamespaceRegistry,
+        workflow_id: &str,
+    ) -> Result<Option<WorkflowState>, NamespaceError> {
+        registry.authorize(requester, &self.namespace)?;
+        Ok(self.get_state(workflow_id))
+    }
+
+    /// Get a loaded workflow definition
+    pub fn get_workflow(&self, workflow_id: &str) -> Option<Workflow> {
+        let workflows = self.workflows.lock().unwrap();
+        workflows.get(workflow_id).cloned()
+    }
+
+    /// Tools that workflow tasks may require, shared with the agent registry
+    pub fn tools(&self) -> Arc<ToolRegistry> {
+        self.dispatcher.registry().tools()
+    }
+
+    /// Every tool in the shared registry, sorted by name
+    pub fn list_tools(&self) -> Vec<ToolSpec> {
+        self.tools().list_tools()
+    }
+
+    /// Agents available to workflow tasks
+    pub fn agent_registry(&self) -> Arc<AgentRegistry> {
+        self.dispatcher.registry()
+    }
+
+    /// Check a workflow definition without loading it: its stage structure and
+    /// contract schemas, the agent roles its tasks request, and the tools they require.
+    pub fn validate(&self, workflow: &Workflow) -> Result<(), String> {
+        definition::validate_structure(workflow)
+            .map_err(|reason| format!("Workflow {} is invalid: {}", workflow.name, reason))?;
+        contract::check_schemas(workflow)
+            .map_err(|reason| format!("Workflow {} is invalid: {}", workflow.name, reason))?;
+        self.validate_agent_roles(workflow)?;
+        self.validate_tool_requirements(workflow)
+    }
+
+    /// Reject tasks requesting a role no registered agent holds. An empty
+    /// registry accepts any role, since agents may be spawned on demand.
+    fn validate_agent_roles(&self, workflow: &Workflow) -> Result<(), String> {
+        let agents = self.agent_registry().all();
+        if agents.is_empty() {
+            return Ok(());
+        }
+        let mut unknown = Vec::new();
+        for stage in &workflow.stages {
+            for task in stage.tasks.iter().chain(&stage.compensation) {
+                if let Some(role) = &task.agent_role {
+                    if !agents.iter().any(|agent| agent.role.eq_ignore_ascii_case(role)) {
+                        unknown.push(format!("'{}' (stage '{}')", role, stage.name));
+                    }
+                }
+            }
+        }
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Workflow {} requests unknown agent roles: {}",
+                workflow.name,
+                unknown.join(", ")
+            ))
+        }
+    }
+
+    /// Reject workflows whose tasks require a tool the registry does not know
+    fn validate_tool_requirements(&self, workflow: &Workflow) -> Result<(), String> {
+        let tools = self.tools();
+        let mut unknown = Vec::new();
+        for stage in &workflow.stages {
+            for task in stage.tasks.iter().chain(&stage.compensation) {
+                for requirement in &task.tool_requirements {
+                    if tools.get(&requirement.capability).is_none() {
+                        unknown.push(format!(
+                            "'{}' (stage '{}', agent '{}')",
+                            requirement.capability, stage.name, task.agent
+                        ));
+                    }
+                }
+            }
+        }
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Workflow {} requires unknown tools: {}",
+                workflow.name,
+                unknown.join(", ")
+            ))
+        }
+    }
+
+    /// List the ids of every loaded workflow
+    pub fn workflow_ids(&self) -> Vec<String> {
+        let workflows = self.workflows.lock().unwrap();
+        let mut ids: Vec<String> = workflows.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Get the current state of every stage that has started for a workflow
+    pub fn stage_states(&self, workflow_id: &str) -> HashMap<String, StageState> {
+        let stage_states = self.stage_states.lock().unwrap();
+        stage_states.get(workflow_id).cloned().unwrap_or_default()
+    }
+
+    /// Render the workflow stage graph coloured by current stage states
+    pub fn render_workflow(&self, workflow_id: &str, format: GraphFormat) -> Option<String> {
+        let workflow = self.get_workflow(workflow_id)?;
+        let states = self.stage_states(workflow_id);
+        Some(workflow.render_graph(format, Some(&states)))
+    }
+}
+
+fn parameters_to_value(parameters: &HashMap<String, Value>) -> Value {
+    serde_json::to_value(parameters).unwrap_or(Value::Null)
+}
+
+fn extract_token_ratio(parameters: &HashMap<String, Value>) -> Option<f64> {
+    parameters
+        .get("token_ratio")
+        .and_then(Value::as_f64)
+        .or_else(|| {
+            match (
                 parameters.get("token_usage"),
                 parameters.get("token_budget"),
             ) {
@@ -859,27 +2032,12 @@ mod tests {
     use crate::instrumentation::{EvidenceLedgerEntry, EvidenceLedgerKind};
     use tempfile::tempdir;
 
-    struct EnvGuard {
-        key: &'static str,
-        previous: Option<String>,
-    }
-
-    impl EnvGuard {
-        fn set(key: &'static str, value: &Path) -> Self {
-            let previous = std::env::var(key).ok();
-            std::env::set_var(key, value);
-            Self { key, previous }
-        }
+    fn context_in(root: &Path) -> ConfigContext {
+        ConfigContext::isolated().with_workflow_root(root)
     }
 
-    impl Drop for EnvGuard {
-        fn drop(&mut self) {
-            if let Some(ref previous) = self.previous {
-                std::env::set_var(self.key, previous);
-            } else {
-                std::env::remove_var(self.key);
-            }
-        }
+    fn engine_in(root: &Path) -> WorkflowEngine {
+        WorkflowEngine::with_context(context_in(root))
     }
 
     fn register_workflow_verifier(engine: &WorkflowEngine) {
@@ -902,14 +2060,13 @@ mod tests {
     #[test]
     fn test_workflow_creation() {
         let dir = tempdir().unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", dir.path());
         let workflow = Workflow {
             name: "test".to_string(),
             version: "1.0".to_string(),
             stages: vec![],
         };
 
-        let engine = WorkflowEngine::new();
+        let engine = engine_in(dir.path());
         register_workflow_verifier(&engine);
         let id = engine.load_workflow(workflow).unwrap();
         assert_eq!(engine.get_state(&id), Some(WorkflowState::Pending));
@@ -918,8 +2075,7 @@ mod tests {
     #[test]
     fn test_instrumentation_generates_signed_operations() {
         let dir = tempdir().unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", dir.path());
-        let engine = WorkflowEngine::new();
+        let engine = engine_in(dir.path());
         let instrumentation = engine.instrumentation();
 
         let relocation = instrumentation
@@ -945,8 +2101,7 @@ mod tests {
     #[test]
     fn task_dispatch_events_logged_with_tool_requirements() {
         let dir = tempdir().unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", dir.path());
-        let mut engine = WorkflowEngine::new();
+        let mut engine = engine_in(dir.path());
         let registry = AgentRegistry::new();
         let mut workflow_verifier = AgentMetadata::from_registry(
             "WorkflowVerifier".to_string(),
@@ -999,12 +2154,18 @@ mod tests {
                         optional: false,
                         parameters: json!({"depth": 1}),
                     }],
+                    resources: ResourceRequirements::default(),
+                    sandbox: SandboxSpec::default(),
                 }],
+                compensation: vec![],
+                cache: StageCacheControl::default(),
+                contract: StageContract::default(),
             }],
         };
 
         let id = engine.load_workflow(workflow).unwrap();
         engine.execute(&id).unwrap();
+        engine.instrumentation().flush().unwrap();
 
         let log_path = dir
             .path()
@@ -1054,8 +2215,7 @@ mod tests {
     #[test]
     fn stage_merkle_receipt_is_recorded() {
         let dir = tempdir().unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", dir.path());
-        let engine = WorkflowEngine::new();
+        let engine = engine_in(dir.path());
         register_workflow_verifier(&engine);
         let now = Utc::now();
         let fallback_nanos = now.timestamp_micros() * 1_000;
@@ -1076,7 +2236,12 @@ mod tests {
                     parameters: HashMap::from([(String::from("path"), json!("docs/test.md"))]),
                     agent_role: None,
                     tool_requirements: Vec::new(),
+                    resources: ResourceRequirements::default(),
+                    sandbox: SandboxSpec::default(),
                 }],
+                compensation: vec![],
+                cache: StageCacheControl::default(),
+                contract: StageContract::default(),
             }],
         };
 
@@ -1122,8 +2287,7 @@ mod tests {
     #[test]
     fn multi_stage_workflow_emits_receipts_for_each_stage() {
         let dir = tempdir().unwrap();
-        let _guard = EnvGuard::set("NOA_WORKFLOW_ROOT", dir.path());
-        let engine = WorkflowEngine::new();
+        let engine = engine_in(dir.path());
         register_workflow_verifier(&engine);
 
         let workflow = Workflow {
@@ -1140,7 +2304,12 @@ mod tests {
                         parameters: HashMap::new(),
                         agent_role: None,
                         tool_requirements: Vec::new(),
+                        resources: ResourceRequirements::default(),
+                        sandbox: SandboxSpec::default(),
                     }],
+                    compensation: vec![],
+                    cache: StageCacheControl::default(),
+                    contract: StageContract::default(),
                 },
                 Stage {
                     name: "stage-beta".to_string(),
@@ -1152,7 +2321,12 @@ mod tests {
                         parameters: HashMap::new(),
                         agent_role: None,
                         tool_requirements: Vec::new(),
+                        resources: ResourceRequirements::default(),
+                        sandbox: SandboxSpec::default(),
                     }],
+                    compensation: vec![],
+                    cache: StageCacheControl::default(),
+                    contract: StageContract::default(),
                 },
             ],
         };
@@ -1186,4 +2360,781 @@ mod tests {
             stages
         );
     }
+
+    #[test]
+    fn stage_contracts_stop_artifacts_that_break_them() {
+        let dir = tempdir().unwrap();
+        let engine = engine_in(dir.path());
+        register_workflow_verifier(&engine);
+        let stage = |name: &str, depends_on: &[&str], contract: StageContract| Stage {
+            name: name.to_string(),
+            stage_type: StageType::Sequential,
+            depends_on: depends_on.iter().map(|name| name.to_string()).collect(),
+            tasks: vec![Task {
+                agent: "WorkflowVerifier".to_string(),
+                action: "document".to_string(),
+                parameters: HashMap::new(),
+                agent_role: None,
+                tool_requirements: Vec::new(),
+                resources: ResourceRequirements::default(),
+                sandbox: SandboxSpec::default(),
+            }],
+            compensation: vec![],
+            cache: StageCacheControl::default(),
+            contract,
+        };
+        let workflow = Workflow {
+            name: "contracts".to_string(),
+            version: "1.0".to_string(),
+            stages: vec![
+                stage(
+                    "produce",
+                    &[],
+                    StageContract {
+                        input: None,
+                        output: Some(json!({"type": "object"})),
+                    },
+                ),
+                stage(
+                    "consume",
+                    &["produce"],
+                    StageContract {
+                        input: Some(json!({"type": "object", "required": ["ticket"]})),
+                        output: None,
+                    },
+                ),
+            ],
+        };
+
+        let mut broken = workflow.clone();
+        broken.stages[0].contract.output = Some(json!({"minLength": "three"}));
+        let err = engine.load_workflow(broken).unwrap_err();
+        assert!(err.contains("invalid output schema"), "{err}");
+
+        let id = engine.load_workflow(workflow).unwrap();
+        let err = engine.execute(&id).unwrap_err();
+        assert!(err.contains("contract violation"), "{err}");
+        assert!(err.contains("stage 'consume'"), "{err}");
+        assert!(err.contains("from 'produce'"), "{err}");
+        assert!(err.contains("'/ticket'"), "{err}");
+        let states = engine.stage_states(&id);
+        assert_eq!(states.get("produce"), Some(&StageState::Completed));
+        assert_eq!(states.get("consume"), Some(&StageState::Failed));
+    }
+
+    #[test]
+    fn approval_stage_pauses_until_qualified_approval_registered() {
+        let dir = tempdir().unwrap();
+        let engine = engine_in(dir.path());
+        register_workflow_verifier(&engine);
+        let stream = engine.enable_streaming(32);
+        let mut events = stream.subscribe();
+
+        let workflow = Workflow {
+            name: "gated".to_string(),
+            version: "1.0".to_string(),
+            stages: vec![
+                Stage {
+                    name: "sign-off".to_string(),
+                    stage_type: StageType::Approval(AgentApprovalRequirement {
+                        role: "release-manager".to_string(),
+                        minimum_trust_score: 0.8,
+                        required_evidence_tags: vec![],
+                    }),
+                    depends_on: vec![],
+                    tasks: vec![],
+                    compensation: vec![],
+                    cache: StageCacheControl::default(),
+                    contract: StageContract::default(),
+                },
+                Stage {
+                    name: "publish".to_string(),
+                    stage_type: StageType::Sequential,
+                    depends_on: vec!["sign-off".to_string()],
+                    tasks: vec![Task {
+                        agent: "WorkflowVerifier".to_string(),
+                        action: "document".to_string(),
+                        parameters: HashMap::new(),
+                        agent_role: None,
+                        tool_requirements: Vec::new(),
+                        resources: ResourceRequirements::default(),
+                        sandbox: SandboxSpec::default(),
+                    }],
+                    compensation: vec![],
+                    cache: StageCacheControl::default(),
+                    contract: StageContract::default(),
+                },
+            ],
+        };
+
+        let id = engine.load_workflow(workflow).unwrap();
+        engine.execute(&id).unwrap();
+        assert_eq!(engine.get_state(&id), Some(WorkflowState::Paused));
+        assert!(!engine.stage_states(&id).contains_key("publish"));
+
+        let pending = engine.pending_approvals();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].stage_id, "sign-off");
+        let requested = std::iter::from_fn(|| events.try_recv().ok()).any(|event| {
+            matches!(event, WorkflowEvent::ApprovalRequested { ref approval, .. }
+                if approval.token == pending[0].token)
+        });
+        assert!(requested, "approval request should be streamed");
+
+        assert!(engine.approval_from("alice", vec![], vec![]).is_err());
+        let registry = engine.agent_registry();
+        let mut verifier = registry.get("WorkflowVerifier").unwrap();
+        verifier.role = "release-manager".to_string();
+        registry.upsert_metadata(verifier).unwrap();
+        let attested = engine
+            .approval_from("WorkflowVerifier", vec!["changelog".to_string()], vec![])
+            .unwrap();
+        assert_eq!(attested.role, "release-manager");
+        assert!((attested.trust_score - 0.5).abs() < 1e-6);
+        assert_eq!(attested.evidence_tags, vec!["changelog".to_string()]);
+
+        let approval = |trust_score| AgentApproval {
+            role: "release-manager".to_string(),
+            agent_id: "alice".to_string(),
+            trust_score,
+            evidence_tags: vec![],
+            evidence_references: vec![],
+            recorded_at: 0,
+        };
+        assert!(engine
+            .register_approval(&pending[0].token, approval(0.4))
+            .is_err());
+        assert_eq!(engine.get_state(&id), Some(WorkflowState::Paused));
+
+        let state = engine
+            .register_approval(&pending[0].token, approval(0.9))
+            .unwrap();
+        assert_eq!(state, WorkflowState::Completed);
+        assert!(engine.pending_approvals().is_empty());
+        assert_eq!(
+            engine.stage_states(&id).get("publish"),
+            Some(&StageState::Completed)
+        );
+
+        let ledger = fs::read_to_string(dir.path().join("storage/db/evidence/ledger.jsonl"))
+            .expect("ledger should exist");
+        let approval_receipt = ledger
+            .lines()
+            .filter_map(|line| serde_json::from_str::<EvidenceLedgerEntry>(line).ok())
+            .filter(|entry| entry.kind == EvidenceLedgerKind::StageReceipt)
+            .any(|entry| {
+                entry.payload.get("stage_id") == Some(&json!("sign-off"))
+                    && entry.payload["leaves"].as_array().map(Vec::len) == Some(1)
+            });
+        assert!(
+            approval_receipt,
+            "approval should be recorded as a stage receipt"
+        );
+    }
+
+    #[test]
+    fn bus_and_ledger_events_start_filtered_workflow_instances() {
+        let dir = tempdir().unwrap();
+        let engine = engine_in(dir.path());
+        register_workflow_verifier(&engine);
+        engine
+            .load_workflow(Workflow {
+                name: "remediate".to_string(),
+                version: "1.0".to_string(),
+                stages: vec![Stage {
+                    name: "triage".to_string(),
+                    stage_type: StageType::Sequential,
+                    depends_on: vec![],
+                    tasks: vec![Task {
+                        agent: "WorkflowVerifier".to_string(),
+                        action: "document".to_string(),
+                        parameters: HashMap::new(),
+                        agent_role: None,
+                        tool_requirements: Vec::new(),
+                        resources: ResourceRequirements::default(),
+                        sandbox: SandboxSpec::default(),
+                    }],
+                    compensation: vec![],
+                    cache: StageCacheControl::default(),
+                    contract: StageContract::default(),
+                }],
+            })
+            .unwrap();
+
+        assert!(matches!(
+            engine.register_trigger(TriggerBinding::new("missing", "crc.drop.ready")),
+            Err(TriggerError::UnknownWorkflow(_))
+        ));
+        engine
+            .register_trigger(
+                TriggerBinding::new("remediate", "test.deployment.health_failed")
+                    .with_source(TriggerSource::Ledger)
+                    .with_filter(r#"payload.metadata.environment == "production""#),
+            )
+            .unwrap();
+        engine
+            .register_trigger(
+                TriggerBinding::new("remediate", "test.crc.drop.*")
+                    .with_source(TriggerSource::Bus)
+                    .with_filter("payload.priority >= 2"),
+            )
+            .unwrap();
+
+        let instrumentation = engine.instrumentation();
+        for environment in ["staging", "production"] {
+            instrumentation
+                .log_pipeline_event(
+                    "cicd",
+                    "deployment::d-1",
+                    "test.deployment.health_failed",
+                    json!({ "environment": environment }),
+                )
+                .unwrap();
+        }
+
+        let channel_id: ChannelId = 0x2929;
+        ipc::create_channel(channel_id).unwrap();
+        engine.listen_on_ipc_channel(channel_id);
+        for priority in [1, 3] {
+            ipc::send_message(
+                channel_id,
+                ipc::Message {
+                    from: 1,
+                    to: 2,
+                    data: serde_json::to_vec(&json!({
+                        "topic": "test.crc.drop.ready",
+                        "payload": { "drop_id": "drop-7", "priority": priority },
+                    }))
+                    .unwrap(),
+                },
+            )
+            .unwrap();
+        }
+
+        let runs = engine.poll_triggers();
+        assert_eq!(runs.len(), 2, "{runs:?}");
+        assert!(runs
+            .iter()
+            .all(|run| run.state == WorkflowState::Completed && run.error.is_none()));
+        let topics: Vec<&str> = runs.iter().map(|run| run.topic.as_str()).collect();
+        assert!(topics.contains(&"test.crc.drop.ready"));
+        assert!(topics.contains(&"test.deployment.health_failed"));
+
+        let bus_run = runs
+            .iter()
+            .find(|run| run.topic == "test.crc.drop.ready")
+            .unwrap();
+        let instance = engine.get_workflow(&bus_run.instance_id).unwrap();
+        assert_eq!(
+            instance.stages[0].tasks[0].parameters["trigger"]["payload"]["drop_id"],
+            json!("drop-7")
+        );
+        assert_eq!(engine.get_state("remediate"), Some(WorkflowState::Pending));
+        assert!(engine.poll_triggers().is_empty());
+    }
+
+    #[test]
+    fn failed_stage_compensates_completed_stages_in_reverse_order() {
+        let dir = tempdir().unwrap();
+        let engine = engine_in(dir.path());
+        register_workflow_verifier(&engine);
+
+        let task = |agent: &str, action: &str| Task {
+            agent: agent.to_string(),
+            action: action.to_string(),
+            parameters: HashMap::new(),
+            agent_role: None,
+            tool_requirements: Vec::new(),
+            resources: ResourceRequirements::default(),
+            sandbox: SandboxSpec::default(),
+        };
+        let stage = |name: &str, depends_on: &[&str], tasks: Vec<Task>, compensation| Stage {
+            name: name.to_string(),
+            stage_type: StageType::Sequential,
+            depends_on: depends_on.iter().map(|dep| dep.to_string()).collect(),
+            tasks,
+            compensation,
+            cache: StageCacheControl::default(),
+            contract: StageContract::default(),
+        };
+        let workflow = Workflow {
+            name: "saga".to_string(),
+            version: "1.0".to_string(),
+            stages: vec![
+                stage(
+                    "deploy",
+                    &[],
+                    vec![task("WorkflowVerifier", "deploy")],
+                    vec![task("WorkflowVerifier", "undeploy")],
+                ),
+                stage(
+                    "move-files",
+                    &["deploy"],
+                    vec![task("WorkflowVerifier", "move")],
+                    vec![task("WorkflowVerifier", "restore")],
+                ),
+                stage(
+                    "verify",
+                    &["move-files"],
+                    vec![task("UnregisteredAgent", "verify")],
+                    vec![task("WorkflowVerifier", "never-run")],
+                ),
+            ],
+        };
+
+        let id = engine.load_workflow(workflow).unwrap();
+        assert!(engine.execute(&id).is_err());
+        assert_eq!(engine.get_state(&id), Some(WorkflowState::Failed));
+        let states = engine.stage_states(&id);
+        assert_eq!(states.get("deploy"), Some(&StageState::Compensated));
+        assert_eq!(states.get("move-files"), Some(&StageState::Compensated));
+        assert_eq!(states.get("verify"), Some(&StageState::Failed));
+
+        let ledger = fs::read_to_string(dir.path().join("storage/db/evidence/ledger.jsonl"))
+            .expect("ledger should exist");
+        let compensated: Vec<String> = ledger
+            .lines()
+            .filter_map(|line| serde_json::from_str::<EvidenceLedgerEntry>(line).ok())
+            .filter(|entry| entry.payload["compensation"] == json!(true))
+            .filter_map(|entry| entry.payload["stage_id"].as_str().map(str::to_string))
+            .collect();
+        assert_eq!(compensated, vec!["move-files", "deploy"]);
+    }
+
+    #[test]
+    fn validate_checks_structure_roles_and_tools() {
+        let dir = tempdir().unwrap();
+        let engine = engine_in(dir.path());
+        register_workflow_verifier(&engine);
+        let registry = engine.agent_registry();
+        let mut verifier = registry.get("WorkflowVerifier").unwrap();
+        verifier.role = "verifier".to_string();
+        registry.upsert_metadata(verifier).unwrap();
+
+        let task = |role: &str, capability: &str| Task {
+            agent: "WorkflowVerifier".to_string(),
+            action: "run".to_string(),
+            parameters: HashMap::new(),
+            agent_role: Some(role.to_string()),
+            tool_requirements: vec![ToolRequirement {
+                name: "tool".to_string(),
+                capability: capability.to_string(),
+                optional: false,
+                parameters: Value::Null,
+            }],
+            resources: ResourceRequirements::default(),
+            sandbox: SandboxSpec::default(),
+        };
+        let workflow = |depends_on: &str, task: Task| Workflow {
+            name: "composed".to_string(),
+            version: "1.0".to_string(),
+            stages: vec![
+                Stage {
+                    name: "build".to_string(),
+                    stage_type: StageType::Sequential,
+                    depends_on: vec![],
+                    tasks: vec![],
+                    compensation: vec![],
+                    cache: StageCacheControl::default(),
+                    contract: StageContract::default(),
+                },
+                Stage {
+                    name: "verify".to_string(),
+                    stage_type: StageType::Sequential,
+                    depends_on: vec![depends_on.to_string()],
+                    tasks: vec![task],
+                    compensation: vec![],
+                    cache: StageCacheControl::default(),
+                    contract: StageContract::default(),
+                },
+            ],
+        };
+
+        engine
+            .validate(&workflow("build", task("Verifier", "workflow.taskDispatch")))
+            .unwrap();
+        let err = engine
+            .validate(&workflow("deploy", task("verifier", "workflow.taskDispatch")))
+            .unwrap_err();
+        assert!(err.contains("not an earlier stage"), "{err}");
+        let err = engine
+            .validate(&workflow("build", task("unassigned-role", "workflow.taskDispatch")))
+            .unwrap_err();
+        assert!(err.contains("unknown agent roles: 'unassigned-role'"), "{err}");
+        let err = engine
+            .validate(&workflow("build", task("verifier", "workflow.unregistered")))
+            .unwrap_err();
+        assert!(err.contains("unknown tools"), "{err}");
+        assert!(engine.get_workflow("composed").is_none());
+    }
+
+    #[test]
+    fn dry_run_plans_without_dispatching() {
+        let dir = tempdir().unwrap();
+        let engine = engine_in(dir.path());
+        register_workflow_verifier(&engine);
+
+        let task = |agent: &str, capability: Option<&str>| Task {
+            agent: agent.to_string(),
+            action: "run".to_string(),
+            parameters: HashMap::new(),
+            agent_role: None,
+            tool_requirements: capability
+                .map(|capability| ToolRequirement {
+                    name: "tool".to_string(),
+                    capability: capability.to_string(),
+                    optional: false,
+                    parameters: Value::Null,
+                })
+                .into_iter()
+                .collect(),
+            resources: ResourceRequirements::default(),
+            sandbox: SandboxSpec::default(),
+        };
+        let stage = |name: &str, stage_type, depends_on: &[&str], tasks| Stage {
+            name: name.to_string(),
+            stage_type,
+            depends_on: depends_on.iter().map(|dep| dep.to_string()).collect(),
+            tasks,
+            compensation: vec![],
+            cache: StageCacheControl::default(),
+            contract: StageContract::default(),
+        };
+        let workflow = Workflow {
+            name: "crc-generated".to_string(),
+            version: "1.0".to_string(),
+            stages: vec![
+                stage(
+                    "build",
+                    StageType::Sequential,
+                    &[],
+                    vec![task("WorkflowVerifier", Some("workflow.taskDispatch"))],
+                ),
+                stage(
+                    "verify",
+                    StageType::Parallel,
+                    &["build"],
+                    vec![
+                        task("UnregisteredAgent", None),
+                        task("WorkflowVerifier", Some("workflow.deploy")),
+                    ],
+                ),
+                stage(
+                    "sign-off",
+                    StageType::Approval(AgentApprovalRequirement {
+                        role: "release-manager".to_string(),
+                        minimum_trust_score: 0.8,
+                        required_evidence_tags: vec![],
+                    }),
+                    &["missing"],
+                    vec![],
+                ),
+            ],
+        };
+        let id = engine.load_workflow(workflow).unwrap();
+
+        let plan = engine.dry_run(&id).unwrap();
+        assert_eq!(engine.get_state(&id), Some(WorkflowState::Pending));
+        assert!(engine.stage_states(&id).is_empty());
+        assert!(engine.pending_approvals().is_empty());
+        assert_eq!(plan.history_runs, 0);
+        assert_eq!(plan.estimated_duration_ms, None);
+        assert!(!plan.runnable());
+        assert_eq!(plan.blockers().len(), 2, "{:?}", plan.blockers());
+        assert_eq!(
+            plan.stages[0].tasks[0].resolved_agent.as_deref(),
+            Some("WorkflowVerifier")
+        );
+        assert!(plan.stages[0].tasks[0].runnable());
+        assert!(plan.stages[1].dependencies_met);
+        assert!(plan.stages[1].tasks[1].issues[0].contains("workflow.deploy"));
+        assert!(!plan.stages[2].dependencies_met);
+        assert!(plan.stages[2].awaits_approval);
+
+        assert!(engine.execute(&id).is_err());
+        let plan = engine.dry_run(&id).unwrap();
+        assert_eq!(plan.history_runs, 1);
+        assert!(plan.estimated_duration_ms.is_some());
+        assert!(plan.stages[0].estimated_duration_ms.is_some());
+    }
+
+    #[test]
+    fn task_sandbox_outputs_become_stage_artifacts() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("config")).unwrap();
+        fs::write(dir.path().join("config/app.toml"), "mode = \"prod\"").unwrap();
+        let engine = engine_in(dir.path());
+        register_workflow_verifier(&engine);
+
+        let task = |outputs: &[&str]| Task {
+            agent: "WorkflowVerifier".to_string(),
+            action: "render".to_string(),
+            parameters: HashMap::new(),
+            agent_role: None,
+            tool_requirements: Vec::new(),
+            resources: ResourceRequirements::default(),
+            sandbox: SandboxSpec {
+                inputs: vec!["config".to_string()],
+                outputs: outputs.iter().map(|path| path.to_string()).collect(),
+            },
+        };
+        let workflow = |name: &str, outputs: &[&str]| Workflow {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            stages: vec![Stage {
+                name: "render".to_string(),
+                stage_type: StageType::Sequential,
+                depends_on: vec![],
+                tasks: vec![task(outputs)],
+                compensation: vec![],
+                cache: StageCacheControl::default(),
+                contract: StageContract::default(),
+            }],
+        };
+
+        let id = engine
+            .load_workflow(workflow("sandboxed", &["config/app.toml"]))
+            .unwrap();
+        let ledger = Arc::new(CostLedger::new());
+        engine.enable_cost_accounting(ledger.clone());
+        engine.execute(&id).unwrap();
+        let stored = dir
+            .path()
+            .join("storage/db/artifacts")
+            .join(&id)
+            .join("render/config/app.toml");
+        assert_eq!(fs::read_to_string(stored).unwrap(), "mode = \"prod\"");
+        let usage = ledger.subject_usage(CostKind::Workflow, &id);
+        assert_eq!(usage.storage_bytes, "mode = \"prod\"".len() as u64);
+        assert!(usage.cpu_seconds > 0.0);
+        assert_eq!(
+            ledger.owner(CostKind::Workflow, &id).unwrap().namespace,
+            "default"
+        );
+
+        let id = engine
+            .load_workflow(workflow("missing-output", &["report.txt"]))
+            .unwrap();
+        let err = engine.execute(&id).unwrap_err();
+        assert!(err.contains("report.txt"), "{err}");
+    }
+
+    #[test]
+    fn namespaced_engines_isolate_storage_quotas_and_state() {
+        let dir = tempdir().unwrap();
+        let team_a = Namespace::new("team-a").unwrap();
+        let team_b = Namespace::new("team-b").unwrap();
+        let mut registry = NamespaceRegistry::open_in(&context_in(dir.path())).unwrap();
+        registry
+            .register(
+                team_a.clone(),
+                NamespaceQuota {
+                    max_workflows: Some(1),
+                    ..NamespaceQuota::default()
+                },
+            )
+            .unwrap();
+        registry
+            .register(team_b.clone(), NamespaceQuota::default())
+            .unwrap();
+
+        let engine = WorkflowEngine::for_namespace_with_context(
+            &registry,
+            team_a.clone(),
+            context_in(dir.path()),
+        )
+        .unwrap();
+        assert!(dir
+            .path()
+            .join("storage/db/team-a/evidence/ledger.jsonl")
+            .exists());
+        assert!(!dir.path().join("storage/db/evidence").exists());
+
+        let workflow = |name: &str| Workflow {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            stages: vec![],
+        };
+        engine.load_workflow(workflow("first")).unwrap();
+        let err = engine.load_workflow(workflow("second")).unwrap_err();
+        assert!(err.contains("quota exceeded"), "{err}");
+
+        assert!(matches!(
+            engine.get_state_for(&team_b, &registry, "first"),
+            Err(NamespaceError::AccessDenied { .. })
+        ));
+        registry.grant_read(&team_a, &team_b).unwrap();
+        assert_eq!(
+            engine.get_state_for(&team_b, &registry, "first").unwrap(),
+            Some(WorkflowState::Pending)
+        );
+    }
+
+    #[test]
+    fn load_rejects_tasks_requiring_unknown_tools() {
+        let dir = tempdir().unwrap();
+        let engine = engine_in(dir.path());
+        let workflow = |capability: &str| Workflow {
+            name: "tooling".to_string(),
+            version: "1.0".to_string(),
+            stages: vec![Stage {
+                name: "lint".to_string(),
+                stage_type: StageType::Sequential,
+                depends_on: vec![],
+                tasks: vec![Task {
+                    agent: "WorkflowVerifier".to_string(),
+                    action: "run".to_string(),
+                    parameters: HashMap::new(),
+                    agent_role: None,
+                    tool_requirements: vec![ToolRequirement {
+                        name: "linter".to_string(),
+                        capability: capability.to_string(),
+                        optional: true,
+                        parameters: Value::Null,
+                    }],
+                    resources: ResourceRequirements::default(),
+                    sandbox: SandboxSpec::default(),
+                }],
+                compensation: vec![],
+                cache: StageCacheControl::default(),
+                contract: StageContract::default(),
+            }],
+        };
+
+        let err = engine.load_workflow(workflow("code.lnit")).unwrap_err();
+        assert!(err.contains("unknown tools: 'code.lnit'"), "{err}");
+        assert!(engine.get_workflow("tooling").is_none());
+        engine.load_workflow(workflow("CODE.LINT")).unwrap();
+        assert!(engine
+            .list_tools()
+            .iter()
+            .any(|tool| tool.capability == "code.lint"));
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_receipts_without_agents() {
+        let dir = tempdir().unwrap();
+        let engine = engine_in(dir.path());
+        register_workflow_verifier(&engine);
+        let task = |action: &str| Task {
+            agent: "WorkflowVerifier".to_string(),
+            action: action.to_string(),
+            parameters: HashMap::from([(String::from("path"), json!("docs/replay.md"))]),
+            agent_role: None,
+            tool_requirements: Vec::new(),
+            resources: ResourceRequirements::default(),
+            sandbox: SandboxSpec::default(),
+        };
+        let workflow = Workflow {
+            name: "replayable".to_string(),
+            version: "1.0".to_string(),
+            stages: vec![Stage {
+                name: "verify".to_string(),
+                stage_type: StageType::Sequential,
+                depends_on: vec![],
+                tasks: vec![task("inspect"), task("summarise")],
+                compensation: vec![],
+                cache: StageCacheControl::default(),
+                contract: StageContract::default(),
+            }],
+        };
+        let id = engine.load_workflow(workflow).unwrap();
+        let bundle = engine.record(&id).unwrap();
+        assert_eq!(bundle.dispatches.len(), 2);
+        assert_eq!(bundle.receipts.len(), 1);
+        assert!(bundle.error.is_none());
+        assert!(engine.run_seed().is_none());
+
+        let path = dir.path().join("replays").join("replayable.json");
+        bundle.save(&path).unwrap();
+        let loaded = ReplayBundle::load(&path).unwrap();
+
+        // A fresh engine has no agents registered, so any real dispatch would fail.
+        let replayer = engine_in(dir.path());
+        let report = replayer.replay(&loaded).unwrap();
+        assert_eq!(report.receipts_matched, 1);
+        assert_eq!(report.dispatches_replayed, 2);
+        assert_eq!(report.final_state, Some(WorkflowState::Completed));
+
+        let mut tampered = loaded.clone();
+        if let Ok(receipt) = tampered.dispatches[1].result.as_mut() {
+            receipt.output = json!({ "tampered": true });
+        }
+        let err = engine_in(dir.path()).replay(&tampered).unwrap_err();
+        assert!(err.starts_with("receipt mismatch for stage verify"), "{err}");
+
+        let mut truncated = loaded;
+        truncated.dispatches.pop();
+        let err = engine_in(dir.path()).replay(&truncated).unwrap_err();
+        assert!(err.contains("more dispatches than were recorded"), "{err}");
+    }
+
+    #[test]
+    fn cached_stages_reuse_receipts_until_agent_is_invalidated() {
+        let dir = tempdir().unwrap();
+        let engine = engine_in(dir.path());
+        register_workflow_verifier(&engine);
+        let cache = Arc::new(StageCache::new());
+        engine.enable_stage_cache(cache.clone());
+        let stream = engine.enable_streaming(64);
+        let mut events = stream.subscribe();
+
+        let stage = |name: &str, depends_on: Vec<String>| Stage {
+            name: name.to_string(),
+            stage_type: StageType::Sequential,
+            depends_on,
+            tasks: vec![Task {
+                agent: "WorkflowVerifier".to_string(),
+                action: format!("{name}-artifacts"),
+                parameters: HashMap::new(),
+                agent_role: None,
+                tool_requirements: Vec::new(),
+                resources: ResourceRequirements::default(),
+                sandbox: SandboxSpec::default(),
+            }],
+            compensation: vec![],
+            cache: StageCacheControl::enabled(),
+            contract: StageContract::default(),
+        };
+        let id = engine
+            .load_workflow(Workflow {
+                name: "cached".to_string(),
+                version: "1.0".to_string(),
+                stages: vec![
+                    stage("build", vec![]),
+                    stage("test", vec!["build".to_string()]),
+                ],
+            })
+            .unwrap();
+
+        let mut run = || {
+            engine.execute(&id).unwrap();
+            std::iter::from_fn(|| events.try_recv().ok())
+                .filter_map(|event| match event {
+                    WorkflowEvent::StageReceiptGenerated { receipt, .. } => Some(receipt),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let first = run();
+        assert_eq!(first.len(), 2);
+        assert!(first.iter().all(|receipt| !receipt.cache_hit));
+        assert_eq!(cache.len(), 2);
+
+        let second = run();
+        assert!(second.iter().all(|receipt| receipt.cache_hit));
+        let roots = |receipts: &[StageReceipt]| {
+            receipts
+                .iter()
+                .map(|receipt| receipt.merkle_root.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(roots(&first), roots(&second));
+
+        assert_eq!(cache.invalidate_agent("WorkflowVerifier").unwrap(), 2);
+        let third = run();
+        assert!(third.iter().all(|receipt| !receipt.cache_hit));
+        assert_eq!(engine.get_state(&id), Some(WorkflowState::Completed));
+    }
 }
@@ -0,0 +1,366 @@
+//! Tenant namespaces for pipelines, workflows, and their storage.
+//!
+//! The `default` namespace keeps the historical layout (`storage/db/...`) so
+//! existing data stays readable. Any other namespace is confined to
+//! `storage/db/<namespace>/...`, carries its own quotas, and may only be read by
+//! other namespaces that were explicitly granted access.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::context::ConfigContext;
+
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Registry location relative to the workflow root.
+pub const NAMESPACE_REGISTRY_FILE: &str = "storage/db/namespaces.json";
+
+const STORAGE_ROOT: &str = "storage/db";
+const MAX_NAMESPACE_LEN: usize = 63;
+
+#[derive(Debug, Error)]
+pub enum NamespaceError {
+    #[error("invalid namespace name '{0}': use 1-63 lowercase letters, digits, '-' or '_'")]
+    InvalidName(String),
+    #[error("namespace '{0}' is not registered")]
+    Unknown(String),
+    #[error("namespace '{0}' is already registered")]
+    AlreadyExists(String),
+    #[error("namespace '{requester}' is not allowed to access namespace '{target}'")]
+    AccessDenied { requester: String, target: String },
+    #[error("namespace '{namespace}' quota exceeded: {resource} limit is {limit}")]
+    QuotaExceeded {
+        namespace: String,
+        resource: String,
+        limit: usize,
+    },
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Validated namespace name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Namespace(String);
+
+impl Namespace {
+    pub fn new(name: impl Into<String>) -> Result<Self, NamespaceError> {
+        let name = name.into();
+        let valid = !name.is_empty()
+            && name.len() <= MAX_NAMESPACE_LEN
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+            && !name.starts_with(['-', '_']);
+        if valid {
+            Ok(Self(name))
+        } else {
+            Err(NamespaceError::InvalidName(name))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.0 == DEFAULT_NAMESPACE
+    }
+
+    /// Map a workspace-relative path into this namespace.
+    ///
+    /// Paths under `storage/db` gain a `<namespace>` segment right after that prefix;
+    /// any other path gets a trailing `<namespace>` directory. The default namespace
+    /// returns the path unchanged.
+    pub fn scope_path(&self, relative: &str) -> PathBuf {
+        if self.is_default() {
+            return PathBuf::from(relative);
+        }
+        match Path::new(relative).strip_prefix(STORAGE_ROOT) {
+            Ok(rest) => Path::new(STORAGE_ROOT).join(&self.0).join(rest),
+            Err(_) => Path::new(relative).join(&self.0),
+        }
+    }
+}
+
+impl Default for Namespace {
+    fn default() -> Self {
+        Self(DEFAULT_NAMESPACE.to_string())
+    }
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for Namespace {
+    type Error = NamespaceError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Namespace> for String {
+    fn from(namespace: Namespace) -> Self {
+        namespace.0
+    }
+}
+
+/// Resource limits applied to a namespace. `None` means unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NamespaceQuota {
+    #[serde(default)]
+    pub max_pipelines: Option<usize>,
+    #[serde(default)]
+    pub max_workflows: Option<usize>,
+    /// Workflow and pipeline runs allowed to execute at once in this namespace.
+    #[serde(default)]
+    pub max_concurrent_runs: Option<usize>,
+    /// Share of queued run slots relative to other namespaces; one when unset.
+    #[serde(default)]
+    pub scheduling_weight: Option<u32>,
+}
+
+impl NamespaceQuota {
+    /// Fail when adding one more `resource` would exceed `limit`.
+    pub fn check(
+        namespace: &Namespace,
+        resource: &str,
+        limit: Option<usize>,
+        current: usize,
+    ) -> Result<(), NamespaceError> {
+        match limit {
+            Some(limit) if current >= limit => Err(NamespaceError::QuotaExceeded {
+                namespace: namespace.to_string(),
+                resource: resource.to_string(),
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NamespaceEntry {
+    #[serde(default)]
+    pub quota: NamespaceQuota,
+    /// Namespaces allowed to read this namespace's pipelines and workflows.
+    #[serde(default)]
+    pub readers: BTreeSet<Namespace>,
+}
+
+/// Registry of namespaces hosted on this node, persisted as JSON.
+#[derive(Debug, Clone)]
+pub struct NamespaceRegistry {
+    path: Option<PathBuf>,
+    entries: BTreeMap<Namespace, NamespaceEntry>,
+}
+
+impl Default for NamespaceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NamespaceRegistry {
+    /// In-memory registry containing only the default namespace.
+    pub fn new() -> Self {
+        let mut entries = BTreeMap::new();
+        entries.insert(Namespace::default(), NamespaceEntry::default());
+        Self {
+            path: None,
+            entries,
+        }
+    }
+
+    /// Load the registry from `path`, creating an empty one when the file is absent.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, NamespaceError> {
+        let path = path.into();
+        let mut registry = Self::new();
+        if path.exists() {
+            let raw = fs::read_to_string(&path)?;
+            if !raw.trim().is_empty() {
+                let entries: BTreeMap<Namespace, NamespaceEntry> = serde_json::from_str(&raw)?;
+                registry.entries.extend(entries);
+            }
+        }
+        registry.path = Some(path);
+        Ok(registry)
+    }
+
+    /// Load the node-wide registry stored under the workflow root.
+    pub fn open_default() -> Result<Self, NamespaceError> {
+        Self::open_in(&ConfigContext::from_env())
+    }
+
+    /// Load the registry stored under the workflow root of `context`.
+    pub fn open_in(context: &ConfigContext) -> Result<Self, NamespaceError> {
+        Self::load(context.resolve_path(NAMESPACE_REGISTRY_FILE))
+    }
+
+    pub fn save(&self) -> Result<(), NamespaceError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&self.entries)?)?;
+        Ok(())
+    }
+
+    pub fn register(
+        &mut self,
+        namespace: Namespace,
+        quota: NamespaceQuota,
+    ) -> Result<(), NamespaceError> {
+        if self.entries.contains_key(&namespace) {
+            return Err(NamespaceError::AlreadyExists(namespace.to_string()));
+        }
+        self.entries.insert(
+            namespace,
+            NamespaceEntry {
+                quota,
+                readers: BTreeSet::new(),
+            },
+        );
+        self.save()
+    }
+
+    pub fn get(&self, namespace: &Namespace) -> Result<&NamespaceEntry, NamespaceError> {
+        self.entries
+            .get(namespace)
+            .ok_or_else(|| NamespaceError::Unknown(namespace.to_string()))
+    }
+
+    pub fn quota(&self, namespace: &Namespace) -> Result<NamespaceQuota, NamespaceError> {
+        self.get(namespace).map(|entry| entry.quota.clone())
+    }
+
+    pub fn set_quota(
+        &mut self,
+        namespace: &Namespace,
+        quota: NamespaceQuota,
+    ) -> Result<(), NamespaceError> {
+        self.entry_mut(namespace)?.quota = quota;
+        self.save()
+    }
+
+    /// Allow `reader` to access resources owned by `owner`.
+    pub fn grant_read(
+        &mut self,
+        owner: &Namespace,
+        reader: &Namespace,
+    ) -> Result<(), NamespaceError> {
+        self.get(reader)?;
+        self.entry_mut(owner)?.readers.insert(reader.clone());
+        self.save()
+    }
+
+    pub fn revoke_read(
+        &mut self,
+        owner: &Namespace,
+        reader: &Namespace,
+    ) -> Result<(), NamespaceError> {
+        self.entry_mut(owner)?.readers.remove(reader);
+        self.save()
+    }
+
+    /// Deny access across namespaces unless the owner granted it.
+    pub fn authorize(
+        &self,
+        requester: &Namespace,
+        owner: &Namespace,
+    ) -> Result<(), NamespaceError> {
+        self.get(requester)?;
+        let entry = self.get(owner)?;
+        if requester == owner || entry.readers.contains(requester) {
+            Ok(())
+        } else {
+            Err(NamespaceError::AccessDenied {
+                requester: requester.to_string(),
+                target: owner.to_string(),
+            })
+        }
+    }
+
+    pub fn namespaces(&self) -> impl Iterator<Item = &Namespace> {
+        self.entries.keys()
+    }
+
+    fn entry_mut(&mut self, namespace: &Namespace) -> Result<&mut NamespaceEntry, NamespaceError> {
+        self.entries
+            .get_mut(namespace)
+            .ok_or_else(|| NamespaceError::Unknown(namespace.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn scopes_storage_paths_per_namespace() {
+        let team = Namespace::new("team-a").unwrap();
+        assert_eq!(
+            team.scope_path("storage/db/pipelines/state.json"),
+            PathBuf::from("storage/db/team-a/pipelines/state.json")
+        );
+        assert_eq!(
+            team.scope_path(".workspace/indexes"),
+            PathBuf::from(".workspace/indexes/team-a")
+        );
+        assert_eq!(
+            Namespace::default().scope_path("storage/db/evidence"),
+            PathBuf::from("storage/db/evidence")
+        );
+        assert!(Namespace::new("../etc").is_err());
+        assert!(Namespace::new("Team").is_err());
+    }
+
+    #[test]
+    fn cross_namespace_access_requires_grant() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("namespaces.json");
+        let team_a = Namespace::new("team-a").unwrap();
+        let team_b = Namespace::new("team-b").unwrap();
+
+        let mut registry = NamespaceRegistry::load(&path).unwrap();
+        registry
+            .register(team_a.clone(), NamespaceQuota::default())
+            .unwrap();
+        registry
+            .register(
+                team_b.clone(),
+                NamespaceQuota {
+                    max_pipelines: Some(2),
+                    max_workflows: None,
+                    max_concurrent_runs: None,
+                    scheduling_weight: None,
+                },
+            )
+            .unwrap();
+
+        assert!(matches!(
+            registry.authorize(&team_a, &team_b),
+            Err(NamespaceError::AccessDenied { .. })
+        ));
+        registry.grant_read(&team_b, &team_a).unwrap();
+
+        let reloaded = NamespaceRegistry::load(&path).unwrap();
+        reloaded.authorize(&team_a, &team_b).unwrap();
+        assert!(reloaded.authorize(&team_b, &team_a).is_err());
+        assert_eq!(reloaded.quota(&team_b).unwrap().max_pipelines, Some(2));
+    }
+}
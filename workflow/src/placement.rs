@@ -0,0 +1,188 @@
+//! Resource-aware task placement.
+//!
+//! Tasks may declare [`ResourceRequirements`] such as a GPU or a minimum amount of
+//! memory. Before dispatching, [`crate::AgentDispatcher`] checks them against the
+//! host [`HardwareProfile`]; tasks that do not fit are queued instead of started,
+//! and every dispatch receipt carries the resulting [`PlacementDecision`].
+
+use chrono::Utc;
+use noa_core::hardware::HardwareProfile;
+use serde::{Deserialize, Serialize};
+
+use crate::Task;
+
+/// Host resources a task needs in order to run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ResourceRequirements {
+    #[serde(default)]
+    pub gpu: bool,
+    #[serde(default)]
+    pub min_memory_gb: Option<f64>,
+    #[serde(default)]
+    pub min_cpu_cores: Option<usize>,
+}
+
+impl ResourceRequirements {
+    pub fn is_empty(&self) -> bool {
+        !self.gpu && self.min_memory_gb.is_none() && self.min_cpu_cores.is_none()
+    }
+
+    /// Requirements the host cannot currently satisfy, described for operators.
+    pub fn unmet(&self, profile: &HardwareProfile) -> Vec<String> {
+        let mut unmet = Vec::new();
+        if self.gpu && !profile.has_gpu() {
+            unmet.push("requires a GPU but none was detected".to_string());
+        }
+        if let Some(required) = self.min_memory_gb {
+            let available = profile.available_memory_gb();
+            if available + f64::EPSILON < required {
+                unmet.push(format!(
+                    "requires {:.1} GB memory but {:.1} GB is available",
+                    required, available
+                ));
+            }
+        }
+        if let Some(required) = self.min_cpu_cores {
+            if profile.cpu.logical_cores < required {
+                unmet.push(format!(
+                    "requires {} CPU cores but the host has {}",
+                    required, profile.cpu.logical_cores
+                ));
+            }
+        }
+        unmet
+    }
+}
+
+/// Host capacity considered when placing a task.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HostSnapshot {
+    pub cpu_cores: usize,
+    pub available_memory_gb: f64,
+    pub gpu_count: usize,
+}
+
+impl From<&HardwareProfile> for HostSnapshot {
+    fn from(profile: &HardwareProfile) -> Self {
+        Self {
+            cpu_cores: profile.cpu.logical_cores,
+            available_memory_gb: profile.available_memory_gb(),
+            gpu_count: profile.gpus.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlacementStatus {
+    Placed,
+    Queued,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PlacementDecision {
+    pub status: PlacementStatus,
+    pub requirements: ResourceRequirements,
+    /// Host the decision was made against; absent when the task declared no requirements.
+    pub host: Option<HostSnapshot>,
+    pub reasons: Vec<String>,
+}
+
+impl PlacementDecision {
+    /// Placement for a task without resource requirements; no hardware probe is needed.
+    pub fn unconstrained() -> Self {
+        Self {
+            status: PlacementStatus::Placed,
+            requirements: ResourceRequirements::default(),
+            host: None,
+            reasons: Vec::new(),
+        }
+    }
+
+    pub fn evaluate(requirements: &ResourceRequirements, profile: &HardwareProfile) -> Self {
+        let reasons = requirements.unmet(profile);
+        Self {
+            status: if reasons.is_empty() {
+                PlacementStatus::Placed
+            } else {
+                PlacementStatus::Queued
+            },
+            requirements: requirements.clone(),
+            host: Some(HostSnapshot::from(profile)),
+            reasons,
+        }
+    }
+
+    pub fn is_placed(&self) -> bool {
+        self.status == PlacementStatus::Placed
+    }
+}
+
+/// A task waiting for host resources to become available.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTask {
+    pub task: Task,
+    pub placement: PlacementDecision,
+    pub queued_at: String,
+}
+
+impl QueuedTask {
+    pub(crate) fn new(task: Task, placement: PlacementDecision) -> Self {
+        Self {
+            task,
+            placement,
+            queued_at: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noa_core::hardware::{CpuProfile, GpuBackend, GpuProfile, MemoryProfile};
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn profile(cores: usize, available_gb: u64, gpus: usize) -> HardwareProfile {
+        HardwareProfile {
+            cpu: CpuProfile {
+                brand: "test".to_string(),
+                vendor: "test".to_string(),
+                physical_cores: cores,
+                logical_cores: cores,
+                frequency_mhz: None,
+            },
+            memory: MemoryProfile {
+                total_bytes: 64 * GB,
+                available_bytes: available_gb * GB,
+            },
+            gpus: (0..gpus)
+                .map(|index| GpuProfile {
+                    name: format!("gpu-{index}"),
+                    backend: GpuBackend::Nvidia,
+                    memory_total_bytes: Some(24 * GB),
+                    driver: None,
+                })
+                .collect(),
+            accelerators: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn placement_queues_when_host_lacks_resources() {
+        let requirements = ResourceRequirements {
+            gpu: true,
+            min_memory_gb: Some(8.0),
+            min_cpu_cores: Some(4),
+        };
+
+        let placed = PlacementDecision::evaluate(&requirements, &profile(8, 16, 1));
+        assert!(placed.is_placed());
+        assert_eq!(placed.host.as_ref().map(|host| host.gpu_count), Some(1));
+
+        let queued = PlacementDecision::evaluate(&requirements, &profile(2, 4, 0));
+        assert_eq!(queued.status, PlacementStatus::Queued);
+        assert_eq!(queued.reasons.len(), 3, "{:?}", queued.reasons);
+        assert!(queued.reasons[0].contains("GPU"));
+    }
+}
@@ -0,0 +1,297 @@
+//! Deterministic record/replay of workflow runs.
+//!
+//! [`crate::WorkflowEngine::record`] executes a workflow while capturing every
+//! agent dispatch result, every clock read, and the run's random seed into a
+//! [`ReplayBundle`]. [`crate::WorkflowEngine::replay`] re-executes the bundled
+//! workflow feeding those recorded values back in place of the dispatcher and the
+//! system clock, so no agent is contacted. The stage receipts produced by the
+//! replay must have the same Merkle roots as the recorded ones; any difference is
+//! reported as a divergence.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{StageReceipt, Task, TaskDispatchReceipt, Workflow, WorkflowState};
+
+/// Bundle format version written by this engine.
+pub const REPLAY_BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("unsupported replay bundle version {0}")]
+    UnsupportedVersion(u32),
+    #[error("replay diverged at dispatch {index}: expected {expected}, found {found}")]
+    DispatchDiverged {
+        index: usize,
+        expected: String,
+        found: String,
+    },
+    #[error("replay requested more dispatches than were recorded ({0})")]
+    DispatchExhausted(usize),
+    #[error("replay read the clock more often than the recording ({0} reads)")]
+    ClockExhausted(usize),
+    #[error("receipt mismatch for stage {stage_id}: expected {expected}, found {found}")]
+    ReceiptMismatch {
+        stage_id: String,
+        expected: String,
+        found: String,
+    },
+    #[error("replay produced {found} stage receipts, recording has {expected}")]
+    ReceiptCount { expected: usize, found: usize },
+    #[error("replay outcome {found:?} differs from recorded outcome {expected:?}")]
+    OutcomeMismatch {
+        expected: Option<String>,
+        found: Option<String>,
+    },
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// One clock read taken during the run, in the order it happened.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum ClockRead {
+    Iso(String),
+    Millis(u128),
+}
+
+/// Result of one call to the agent dispatcher.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedDispatch {
+    pub stage_id: String,
+    pub agent: String,
+    pub action: String,
+    pub result: Result<TaskDispatchReceipt, String>,
+}
+
+impl RecordedDispatch {
+    fn describe(stage_id: &str, task: &Task) -> String {
+        format!("{}::{}/{}", stage_id, task.agent, task.action)
+    }
+}
+
+/// Identity of a stage receipt produced during the run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedReceipt {
+    pub stage_id: String,
+    pub merkle_root: String,
+    #[serde(default)]
+    pub compensation: bool,
+}
+
+impl From<&StageReceipt> for RecordedReceipt {
+    fn from(receipt: &StageReceipt) -> Self {
+        Self {
+            stage_id: receipt.stage_id.clone(),
+            merkle_root: receipt.merkle_root.clone(),
+            compensation: receipt.compensation,
+        }
+    }
+}
+
+/// Everything needed to re-execute a workflow run deterministically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayBundle {
+    pub version: u32,
+    pub workflow_id: String,
+    pub workflow: Workflow,
+    pub seed: u64,
+    pub recorded_at: String,
+    pub clock: Vec<ClockRead>,
+    pub dispatches: Vec<RecordedDispatch>,
+    pub receipts: Vec<RecordedReceipt>,
+    /// Error the recorded run ended with, if it failed.
+    pub error: Option<String>,
+    pub final_state: Option<WorkflowState>,
+}
+
+impl ReplayBundle {
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        let bundle: Self = serde_json::from_slice(&fs::read(path)?)?;
+        if bundle.version != REPLAY_BUNDLE_VERSION {
+            return Err(ReplayError::UnsupportedVersion(bundle.version));
+        }
+        Ok(bundle)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Outcome of a replay whose receipts matched the recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub workflow_id: String,
+    pub dispatches_replayed: usize,
+    pub receipts_matched: usize,
+    pub final_state: Option<WorkflowState>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionMode {
+    Recording,
+    Replaying,
+}
+
+/// Recording or replay state attached to an engine for the duration of one run.
+pub(crate) struct ReplaySession {
+    mode: SessionMode,
+    pub(crate) seed: u64,
+    clock: VecDeque<ClockRead>,
+    clock_reads: usize,
+    dispatches: VecDeque<RecordedDispatch>,
+    dispatch_count: usize,
+    pub(crate) recorded_clock: Vec<ClockRead>,
+    pub(crate) recorded_dispatches: Vec<RecordedDispatch>,
+    pub(crate) receipts: Vec<RecordedReceipt>,
+    /// First divergence seen while replaying; execution continues on live values.
+    pub(crate) divergence: Option<ReplayError>,
+}
+
+impl ReplaySession {
+    pub(crate) fn recording(seed: u64) -> Self {
+        Self {
+            mode: SessionMode::Recording,
+            seed,
+            clock: VecDeque::new(),
+            clock_reads: 0,
+            dispatches: VecDeque::new(),
+            dispatch_count: 0,
+            recorded_clock: Vec::new(),
+            recorded_dispatches: Vec::new(),
+            receipts: Vec::new(),
+            divergence: None,
+        }
+    }
+
+    pub(crate) fn replaying(bundle: &ReplayBundle) -> Self {
+        Self {
+            mode: SessionMode::Replaying,
+            clock: bundle.clock.iter().cloned().collect(),
+            dispatches: bundle.dispatches.iter().cloned().collect(),
+            ..Self::recording(bundle.seed)
+        }
+    }
+
+    pub(crate) fn is_replaying(&self) -> bool {
+        self.mode == SessionMode::Replaying
+    }
+
+    fn diverge(&mut self, error: ReplayError) {
+        self.divergence.get_or_insert(error);
+    }
+
+    pub(crate) fn clock_iso(&mut self, live: impl FnOnce() -> String) -> String {
+        self.clock_reads += 1;
+        if self.mode == SessionMode::Replaying {
+            if let Some(ClockRead::Iso(value)) = self.clock.pop_front() {
+                return value;
+            }
+            self.diverge(ReplayError::ClockExhausted(self.clock_reads));
+            return live();
+        }
+        let value = live();
+        self.recorded_clock.push(ClockRead::Iso(value.clone()));
+        value
+    }
+
+    pub(crate) fn clock_millis(&mut self, live: impl FnOnce() -> u128) -> u128 {
+        self.clock_reads += 1;
+        if self.mode == SessionMode::Replaying {
+            if let Some(ClockRead::Millis(value)) = self.clock.pop_front() {
+                return value;
+            }
+            self.diverge(ReplayError::ClockExhausted(self.clock_reads));
+            return live();
+        }
+        let value = live();
+        self.recorded_clock.push(ClockRead::Millis(value));
+        value
+    }
+
+    /// The recorded dispatch result when replaying, `None` when recording.
+    pub(crate) fn replay_dispatch(
+        &mut self,
+        stage_id: &str,
+        task: &Task,
+    ) -> Option<Result<TaskDispatchReceipt, String>> {
+        if self.mode != SessionMode::Replaying {
+            return None;
+        }
+        let index = self.dispatch_count;
+        self.dispatch_count += 1;
+        let found = RecordedDispatch::describe(stage_id, task);
+        let Some(recorded) = self.dispatches.pop_front() else {
+            let error = ReplayError::DispatchExhausted(index);
+            let message = error.to_string();
+            self.diverge(error);
+            return Some(Err(message));
+        };
+        let expected = format!(
+            "{}::{}/{}",
+            recorded.stage_id, recorded.agent, recorded.action
+        );
+        if expected != found {
+            self.diverge(ReplayError::DispatchDiverged {
+                index,
+                expected,
+                found,
+            });
+        }
+        Some(recorded.result)
+    }
+
+    pub(crate) fn record_dispatch(
+        &mut self,
+        stage_id: &str,
+        task: &Task,
+        result: &Result<TaskDispatchReceipt, String>,
+    ) {
+        self.dispatch_count += 1;
+        self.recorded_dispatches.push(RecordedDispatch {
+            stage_id: stage_id.to_string(),
+            agent: task.agent.clone(),
+            action: task.action.clone(),
+            result: result.clone(),
+        });
+    }
+
+    /// Compare the receipts produced by a replay with the recording.
+    pub(crate) fn verify(self, bundle: &ReplayBundle) -> Result<ReplayReport, ReplayError> {
+        if let Some(divergence) = self.divergence {
+            return Err(divergence);
+        }
+        for (expected, found) in bundle.receipts.iter().zip(&self.receipts) {
+            if expected != found {
+                return Err(ReplayError::ReceiptMismatch {
+                    stage_id: expected.stage_id.clone(),
+                    expected: expected.merkle_root.clone(),
+                    found: format!("{} ({})", found.merkle_root, found.stage_id),
+                });
+            }
+        }
+        if bundle.receipts.len() != self.receipts.len() {
+            return Err(ReplayError::ReceiptCount {
+                expected: bundle.receipts.len(),
+                found: self.receipts.len(),
+            });
+        }
+        Ok(ReplayReport {
+            workflow_id: bundle.workflow_id.clone(),
+            dispatches_replayed: self.dispatch_count,
+            receipts_matched: self.receipts.len(),
+            final_state: None,
+        })
+    }
+}
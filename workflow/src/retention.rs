@@ -0,0 +1,469 @@
+//! Data retention and subject purges across NOA's stores.
+//!
+//! A [`RetentionManager`] holds one [`RetentionPolicy`] per registered
+//! [`RetentionStore`]: the evidence ledger, the pipeline telemetry logs, agent
+//! memories, and (from `noa_ui`) UI analytics. [`RetentionManager::apply`] prunes
+//! every store to its policy; [`RetentionManager::purge`] removes or anonymizes
+//! every record linked to a user or agent identity and returns a [`PurgeReceipt`]
+//! signed into the pipeline event log. Hash-chained stores are anonymized rather
+//! than thinned so their ordering survives: the identity is replaced by a stable
+//! pseudonym, which is also the only way the receipt names the subject.
+
+use crate::instrumentation::{InstrumentationError, PipelineInstrumentation};
+use chrono::{DateTime, Utc};
+use noa_agents::{AgentMemoryError, AgentMemoryStore};
+use noa_core::security::SignedOperation;
+use noa_core::utils::{current_timestamp_millis, simple_hash};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use thiserror::Error;
+
+pub use noa_memory::RetentionPolicy;
+
+/// Pipeline event type recorded for every purge.
+pub const PURGE_EVENT: &str = "retention.purge";
+const PURGE_ACTOR: &str = "system/retention";
+
+#[derive(Debug, Error)]
+pub enum RetentionError {
+    #[error("subject id must not be empty")]
+    EmptySubject,
+    #[error("store '{store}' failed: {message}")]
+    Store { store: String, message: String },
+    #[error(transparent)]
+    Instrumentation(#[from] InstrumentationError),
+}
+
+impl RetentionError {
+    pub fn store(store: &str, err: impl std::fmt::Display) -> Self {
+        Self::Store {
+            store: store.to_string(),
+            message: err.to_string(),
+        }
+    }
+}
+
+/// Records a store removed or rewrote for one purge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PurgeCounts {
+    pub removed: usize,
+    pub anonymized: usize,
+}
+
+/// A store whose records age out and can be purged per subject.
+pub trait RetentionStore: Send + Sync {
+    /// Name used in retention reports and purge receipts.
+    fn store_name(&self) -> &str;
+
+    /// Drop records `policy` no longer allows at `now_ms`, returning how many.
+    fn apply_retention(
+        &self,
+        policy: &RetentionPolicy,
+        now_ms: u128,
+    ) -> Result<usize, RetentionError>;
+
+    /// Remove records linked to `subject_id`, or replace the identity with
+    /// `pseudonym` where records cannot be dropped.
+    fn purge_subject(
+        &self,
+        subject_id: &str,
+        pseudonym: &str,
+    ) -> Result<PurgeCounts, RetentionError>;
+}
+
+/// The pseudonym a subject's identity is replaced with in anonymized records.
+pub fn subject_pseudonym(subject_id: &str) -> String {
+    format!("anon:{}", simple_hash(subject_id))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreRetention {
+    pub store: String,
+    pub removed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub applied_at: u128,
+    pub stores: Vec<StoreRetention>,
+}
+
+impl RetentionReport {
+    pub fn total_removed(&self) -> usize {
+        self.stores.iter().map(|store| store.removed).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorePurge {
+    pub store: String,
+    #[serde(flatten)]
+    pub counts: PurgeCounts,
+}
+
+/// Proof that a subject was purged. It names the subject only by its
+/// pseudonym and is signed into the pipeline event log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeReceipt {
+    pub pseudonym: String,
+    pub purged_at: u128,
+    pub stores: Vec<StorePurge>,
+    pub signed_operation: SignedOperation,
+}
+
+struct RegisteredStore {
+    store: Arc<dyn RetentionStore>,
+    policy: RetentionPolicy,
+}
+
+pub struct RetentionManager {
+    instrumentation: Arc<PipelineInstrumentation>,
+    stores: Vec<RegisteredStore>,
+}
+
+impl RetentionManager {
+    /// Manager that signs receipts through `instrumentation` and already covers
+    /// its evidence ledger and telemetry logs with unbounded retention.
+    pub fn new(instrumentation: Arc<PipelineInstrumentation>) -> Self {
+        let stores = vec![
+            RegisteredStore {
+                store: Arc::new(EvidenceLedgerRetention(Arc::clone(&instrumentation))),
+                policy: RetentionPolicy::default(),
+            },
+            RegisteredStore {
+                store: Arc::new(TelemetryLogRetention(Arc::clone(&instrumentation))),
+                policy: RetentionPolicy::default(),
+            },
+        ];
+        Self {
+            instrumentation,
+            stores,
+        }
+    }
+
+    /// Register `store` under `policy`, replacing any store with the same name.
+    pub fn with_store(mut self, store: Arc<dyn RetentionStore>, policy: RetentionPolicy) -> Self {
+        self.stores
+            .retain(|registered| registered.store.store_name() != store.store_name());
+        self.stores.push(RegisteredStore { store, policy });
+        self
+    }
+
+    pub fn with_ledger_retention(self, policy: RetentionPolicy) -> Self {
+        self.with_policy(LEDGER_STORE, policy)
+    }
+
+    pub fn with_telemetry_retention(self, policy: RetentionPolicy) -> Self {
+        self.with_policy(TELEMETRY_STORE, policy)
+    }
+
+    fn with_policy(mut self, store: &str, policy: RetentionPolicy) -> Self {
+        if let Some(registered) = self
+            .stores
+            .iter_mut()
+            .find(|registered| registered.store.store_name() == store)
+        {
+            registered.policy = policy;
+        }
+        self
+    }
+
+    pub fn store_names(&self) -> Vec<&str> {
+        self.stores
+            .iter()
+            .map(|registered| registered.store.store_name())
+            .collect()
+    }
+
+    /// Prune every store to its policy.
+    pub fn apply(&self) -> Result<RetentionReport, RetentionError> {
+        self.apply_at(current_timestamp_millis())
+    }
+
+    pub fn apply_at(&self, now_ms: u128) -> Result<RetentionReport, RetentionError> {
+        let stores = self
+            .stores
+            .iter()
+            .map(|registered| {
+                Ok(StoreRetention {
+                    store: registered.store.store_name().to_string(),
+                    removed: registered
+                        .store
+                        .apply_retention(&registered.policy, now_ms)?,
+                })
+            })
+            .collect::<Result<Vec<_>, RetentionError>>()?;
+        Ok(RetentionReport {
+            applied_at: now_ms,
+            stores,
+        })
+    }
+
+    /// Remove or anonymize everything linked to `subject_id` in every store and
+    /// sign a receipt. A store failing stops the purge before a receipt is issued;
+    /// purging again is safe and picks up what remains.
+    pub fn purge(&self, subject_id: &str) -> Result<PurgeReceipt, RetentionError> {
+        if subject_id.trim().is_empty() {
+            return Err(RetentionError::EmptySubject);
+        }
+        let pseudonym = subject_pseudonym(subject_id);
+        let stores = self
+            .stores
+            .iter()
+            .map(|registered| {
+                Ok(StorePurge {
+                    store: registered.store.store_name().to_string(),
+                    counts: registered.store.purge_subject(subject_id, &pseudonym)?,
+                })
+            })
+            .collect::<Result<Vec<_>, RetentionError>>()?;
+        let signed_operation = self.instrumentation.log_pipeline_event(
+            PURGE_ACTOR,
+            &pseudonym,
+            PURGE_EVENT,
+            json!({ "stores": &stores }),
+        )?;
+        Ok(PurgeReceipt {
+            pseudonym,
+            purged_at: signed_operation.record.timestamp,
+            stores,
+            signed_operation,
+        })
+    }
+}
+
+const LEDGER_STORE: &str = "evidence_ledger";
+const TELEMETRY_STORE: &str = "telemetry_logs";
+const AGENT_MEMORY_STORE: &str = "agent_memory";
+
+struct EvidenceLedgerRetention(Arc<PipelineInstrumentation>);
+
+impl RetentionStore for EvidenceLedgerRetention {
+    fn store_name(&self) -> &str {
+        LEDGER_STORE
+    }
+
+    fn apply_retention(
+        &self,
+        policy: &RetentionPolicy,
+        now_ms: u128,
+    ) -> Result<usize, RetentionError> {
+        Ok(self.0.prune_evidence_ledger(policy, now_ms)?)
+    }
+
+    fn purge_subject(
+        &self,
+        subject_id: &str,
+        pseudonym: &str,
+    ) -> Result<PurgeCounts, RetentionError> {
+        Ok(PurgeCounts {
+            removed: 0,
+            anonymized: self.0.anonymize_evidence_ledger(subject_id, pseudonym)?,
+        })
+    }
+}
+
+struct TelemetryLogRetention(Arc<PipelineInstrumentation>);
+
+impl RetentionStore for TelemetryLogRetention {
+    fn store_name(&self) -> &str {
+        TELEMETRY_STORE
+    }
+
+    fn apply_retention(
+        &self,
+        policy: &RetentionPolicy,
+        now_ms: u128,
+    ) -> Result<usize, RetentionError> {
+        Ok(self.0.prune_telemetry_logs(policy, now_ms)?)
+    }
+
+    fn purge_subject(
+        &self,
+        subject_id: &str,
+        pseudonym: &str,
+    ) -> Result<PurgeCounts, RetentionError> {
+        Ok(PurgeCounts {
+            removed: 0,
+            anonymized: self.0.anonymize_telemetry_logs(subject_id, pseudonym)?,
+        })
+    }
+}
+
+impl RetentionStore for AgentMemoryStore {
+    fn store_name(&self) -> &str {
+        AGENT_MEMORY_STORE
+    }
+
+    fn apply_retention(
+        &self,
+        policy: &RetentionPolicy,
+        now_ms: u128,
+    ) -> Result<usize, RetentionError> {
+        let now = i64::try_from(now_ms)
+            .ok()
+            .and_then(DateTime::<Utc>::from_timestamp_millis)
+            .unwrap_or_else(Utc::now);
+        self.apply_retention_all(policy, now)
+            .map_err(|err: AgentMemoryError| RetentionError::store(AGENT_MEMORY_STORE, err))
+    }
+
+    fn purge_subject(
+        &self,
+        subject_id: &str,
+        _pseudonym: &str,
+    ) -> Result<PurgeCounts, RetentionError> {
+        let removed = AgentMemoryStore::purge_subject(self, subject_id)
+            .map_err(|err| RetentionError::store(AGENT_MEMORY_STORE, err))?;
+        Ok(PurgeCounts {
+            removed,
+            anonymized: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ConfigContext, EvidenceLedgerKind, Namespace, Stage, StageCacheControl, StageContract,
+        StageType,
+    };
+    use noa_core::recovery::RecoveryMode;
+    use noa_core::security::verify_signed_operation;
+    use noa_memory::MemoryRole;
+
+    #[test]
+    fn purge_anonymizes_ledgers_drops_memories_and_signs_a_receipt() {
+        let dir = tempfile::tempdir().unwrap();
+        let instrumentation = Arc::new(
+            PipelineInstrumentation::with_context(
+                &Namespace::default(),
+                ConfigContext::isolated().with_workflow_root(dir.path()),
+            )
+            .unwrap(),
+        );
+        instrumentation
+            .log_pipeline_event("alice", "deploy-1", "deploy.requested", json!({}))
+            .unwrap();
+        instrumentation
+            .log_pipeline_event("bob", "deploy-2", "deploy.requested", json!({}))
+            .unwrap();
+
+        let memories = Arc::new(AgentMemoryStore::new(dir.path().join("agents")));
+        memories
+            .record(
+                "alice",
+                MemoryRole::Observation,
+                "private notes",
+                Vec::new(),
+            )
+            .unwrap();
+        memories
+            .record(
+                "planner",
+                MemoryRole::Observation,
+                "asked by alice",
+                vec!["alice".to_string()],
+            )
+            .unwrap();
+        memories
+            .record("planner", MemoryRole::Observation, "unrelated", Vec::new())
+            .unwrap();
+
+        let manager = RetentionManager::new(Arc::clone(&instrumentation))
+            .with_store(memories.clone(), RetentionPolicy::default());
+        assert_eq!(
+            manager.store_names(),
+            [LEDGER_STORE, TELEMETRY_STORE, AGENT_MEMORY_STORE]
+        );
+        assert!(matches!(
+            manager.purge(" "),
+            Err(RetentionError::EmptySubject)
+        ));
+
+        let receipt = manager.purge("alice").unwrap();
+        assert_eq!(receipt.pseudonym, subject_pseudonym("alice"));
+        assert!(verify_signed_operation(&receipt.signed_operation));
+        let counts = |store: &str| {
+            receipt
+                .stores
+                .iter()
+                .find(|purge| purge.store == store)
+                .map(|purge| purge.counts)
+                .unwrap()
+        };
+        assert_eq!(counts(TELEMETRY_STORE).anonymized, 1);
+        assert_eq!(counts(AGENT_MEMORY_STORE).removed, 2);
+
+        let log =
+            std::fs::read_to_string(dir.path().join(".workspace/indexes/pipeline_events.log"))
+                .unwrap();
+        assert!(!log.contains("\"alice\""));
+        assert!(log.contains("\"bob\""));
+        assert!(log.contains(&receipt.pseudonym));
+        assert!(memories.records("alice").unwrap().is_empty());
+        let remaining: Vec<_> = memories
+            .records("planner")
+            .unwrap()
+            .into_iter()
+            .map(|record| record.content)
+            .collect();
+        assert_eq!(remaining, ["unrelated"]);
+    }
+
+    #[test]
+    fn apply_prunes_each_store_to_its_policy_and_keeps_genesis() {
+        let dir = tempfile::tempdir().unwrap();
+        let instrumentation = Arc::new(
+            PipelineInstrumentation::with_context(
+                &Namespace::default(),
+                ConfigContext::isolated().with_workflow_root(dir.path()),
+            )
+            .unwrap(),
+        );
+        for deploy in ["deploy-1", "deploy-2", "deploy-3"] {
+            instrumentation
+                .log_pipeline_event("ops", deploy, "deploy.requested", json!({}))
+                .unwrap();
+        }
+
+        let manager = RetentionManager::new(Arc::clone(&instrumentation))
+            .with_telemetry_retention(RetentionPolicy::default().with_max_records(1));
+        let report = manager.apply().unwrap();
+        assert_eq!(report.total_removed(), 2);
+
+        let log =
+            std::fs::read_to_string(dir.path().join(".workspace/indexes/pipeline_events.log"))
+                .unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("::genesis"));
+        assert!(lines[1].contains("deploy-3"));
+
+        let stage = Stage {
+            name: "build".into(),
+            stage_type: StageType::Sequential,
+            depends_on: vec![],
+            tasks: vec![],
+            compensation: vec![],
+            cache: StageCacheControl::default(),
+            contract: StageContract::default(),
+        };
+        instrumentation
+            .log_stage_receipt("ship", &stage, &[json!({"status": "ok"})])
+            .unwrap();
+        let expiring = RetentionManager::new(Arc::clone(&instrumentation))
+            .with_ledger_retention(RetentionPolicy::default().with_max_age_secs(60));
+        assert_eq!(expiring.apply().unwrap().total_removed(), 0);
+        let later = current_timestamp_millis() + 3_600_000;
+        assert_eq!(expiring.apply_at(later).unwrap().total_removed(), 1);
+        let ledger = instrumentation
+            .evidence_ledger(RecoveryMode::Strict)
+            .unwrap()
+            .records;
+        assert_eq!(ledger.len(), 1);
+        assert_eq!(ledger[0].kind, EvidenceLedgerKind::Genesis);
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::path::PathBuf;
 
-use chrono::Utc;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -12,8 +12,13 @@ pub enum RewardError {
     Io(#[from] std::io::Error),
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    #[error("standing override rejected: {0}")]
+    OverrideRejected(String),
 }
 
+/// Overrides are stored next to the reward history under this file name.
+pub const STANDING_OVERRIDES_FILE: &str = "standing_overrides.json";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RewardInputs {
     pub coverage: f64,
@@ -141,6 +146,9 @@ pub struct RewardConfig {
     pub gating_threshold: f64,
     pub gating_recent_threshold: f64,
     pub trailing_window: usize,
+    /// Roles allowed to grant standing overrides.
+    pub override_roles: Vec<String>,
+    pub max_override_hours: i64,
 }
 
 impl Default for RewardConfig {
@@ -157,6 +165,8 @@ impl Default for RewardConfig {
             gating_threshold: -5.0,
             gating_recent_threshold: -0.5,
             trailing_window: 5,
+            override_roles: vec!["admin".to_string(), "release-manager".to_string()],
+            max_override_hours: 72,
         }
     }
 }
@@ -182,18 +192,290 @@ pub struct RewardReport {
     pub riskiest_agents: Vec<AgentStandingSummary>,
 }
 
+/// Time window for a reward explanation; open ends are unbounded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RewardPeriod {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl RewardPeriod {
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn between(since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        Self {
+            since: Some(since),
+            until: Some(until),
+        }
+    }
+
+    /// Whether an RFC 3339 timestamp falls inside the window. Unparseable
+    /// timestamps only match an unbounded window.
+    pub fn contains(&self, timestamp: &str) -> bool {
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        let Ok(at) = DateTime::parse_from_rfc3339(timestamp) else {
+            return false;
+        };
+        let at = at.with_timezone(&Utc);
+        self.since.is_none_or(|since| at >= since) && self.until.is_none_or(|until| at < until)
+    }
+
+    fn describe(&self) -> String {
+        match (self.since, self.until) {
+            (None, None) => "all recorded history".to_string(),
+            (Some(since), None) => format!("since {}", since.to_rfc3339()),
+            (None, Some(until)) => format!("until {}", until.to_rfc3339()),
+            (Some(since), Some(until)) => {
+                format!("{} to {}", since.to_rfc3339(), until.to_rfc3339())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardFactor {
+    Coverage,
+    FlakeRate,
+    TokenRatio,
+    Rollbacks,
+    /// Penalty charged to an agent whose own task failed.
+    AgentFailure,
+}
+
+impl RewardFactor {
+    pub const ALL: [RewardFactor; 5] = [
+        RewardFactor::Coverage,
+        RewardFactor::FlakeRate,
+        RewardFactor::TokenRatio,
+        RewardFactor::Rollbacks,
+        RewardFactor::AgentFailure,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RewardFactor::Coverage => "coverage",
+            RewardFactor::FlakeRate => "flake rate",
+            RewardFactor::TokenRatio => "token ratio",
+            RewardFactor::Rollbacks => "rollbacks",
+            RewardFactor::AgentFailure => "agent failure",
+        }
+    }
+}
+
+/// How one factor moved a reward.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RewardContribution {
+    pub factor: RewardFactor,
+    /// Observed input: coverage, flake rate, token ratio, rollback count, or 1.0
+    /// for a failed agent.
+    pub input: f64,
+    pub target: f64,
+    pub weight: f64,
+    /// The factor's part of the whole `RewardDelta`.
+    pub delta: f64,
+    /// The part credited to the explained agent.
+    pub agent_delta: f64,
+}
+
+/// One `RewardDelta` broken down for a single agent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RewardDeltaExplanation {
+    pub timestamp: String,
+    pub goal_id: String,
+    pub workflow_id: String,
+    /// Index of the delta in the reward history.
+    pub history_index: usize,
+    /// Where the goal outcome behind the delta is recorded.
+    pub outcome_link: String,
+    pub agent_success: bool,
+    /// Agents the run's reward was split between.
+    pub shared_with: usize,
+    pub total_reward: f64,
+    pub agent_reward: f64,
+    pub contributions: Vec<RewardContribution>,
+}
+
+/// Per-factor breakdown of an agent's rewards over a period.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentRewardExplanation {
+    pub agent: String,
+    pub period: RewardPeriod,
+    pub agent_reward: f64,
+    /// Summed agent share per factor, in [`RewardFactor::ALL`] order.
+    pub factor_totals: Vec<(RewardFactor, f64)>,
+    pub deltas: Vec<RewardDeltaExplanation>,
+    pub requires_manual_approval: bool,
+}
+
+impl AgentRewardExplanation {
+    /// Factor that cost the agent the most, if any factor was negative.
+    pub fn largest_drag(&self) -> Option<RewardFactor> {
+        self.factor_totals
+            .iter()
+            .filter(|(_, total)| *total < 0.0)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(factor, _)| *factor)
+    }
+
+    pub fn to_json(&self) -> Result<String, RewardError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Markdown for standing reviews.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Reward explanation: {}
+
+Period: {}  
+Runs: {}  
+Agent reward: {:+.2}  
+Manual approval required: {}
+
+",
+            self.agent,
+            self.period.describe(),
+            self.deltas.len(),
+            self.agent_reward,
+            if self.requires_manual_approval {
+                "yes"
+            } else {
+                "no"
+            }
+        );
+        out.push_str(
+            "## Contribution by factor
+
+| Factor | Agent share |
+| --- | --- |
+",
+        );
+        for (factor, total) in &self.factor_totals {
+            out.push_str(&format!(
+                "| {} | {:+.2} |
+",
+                factor.label(),
+                total
+            ));
+        }
+        if let Some(factor) = self.largest_drag() {
+            out.push_str(&format!(
+                "
+Largest drag: {}.
+",
+                factor.label()
+            ));
+        }
+        out.push_str(
+            "
+## Runs
+",
+        );
+        for delta in &self.deltas {
+            out.push_str(&format!(
+                "
+### {} ({})
+
+Goal outcome: [{}]({})  
+Recorded: {}  
+Agent {} · shared by {} agent(s) · run reward {:+.2} · agent reward {:+.2}
+
+| Factor | Input | Target | Weight | Run delta | Agent share |
+| --- | --- | --- | --- | --- | --- |
+",
+                delta.goal_id,
+                delta.workflow_id,
+                delta.goal_id,
+                delta.outcome_link,
+                delta.timestamp,
+                if delta.agent_success {
+                    "succeeded"
+                } else {
+                    "failed"
+                },
+                delta.shared_with,
+                delta.total_reward,
+                delta.agent_reward,
+            ));
+            for contribution in &delta.contributions {
+                out.push_str(&format!(
+                    "| {} | {:.2} | {:.2} | {:.2} | {:+.2} | {:+.2} |
+",
+                    contribution.factor.label(),
+                    contribution.input,
+                    contribution.target,
+                    contribution.weight,
+                    contribution.delta,
+                    contribution.agent_delta,
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Request to exempt a flagged agent from the reward gate for a while.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandingOverrideRequest {
+    pub agent: String,
+    pub granted_by: String,
+    pub role: String,
+    pub justification: String,
+    pub duration: Duration,
+}
+
+/// A time-boxed exemption from the reward gate. Normal evaluation resumes once
+/// it expires.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StandingOverride {
+    pub agent: String,
+    pub granted_by: String,
+    pub role: String,
+    pub justification: String,
+    pub granted_at: String,
+    pub expires_at: String,
+    /// Standing when the override was granted.
+    pub total_reward: f64,
+    pub recent_average: f64,
+}
+
+impl StandingOverride {
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        DateTime::parse_from_rfc3339(&self.expires_at)
+            .map(|expires_at| expires_at > now)
+            .unwrap_or(false)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AgentApprovalStatus {
     pub agent: String,
     pub requires_manual_approval: bool,
     pub reason: Option<String>,
     pub standing: AgentStanding,
+    /// Active override exempting the agent from the gate.
+    #[serde(default)]
+    pub standing_override: Option<StandingOverride>,
 }
 
 impl AgentApprovalStatus {
     pub fn approved(&self) -> bool {
         !self.requires_manual_approval
     }
+
+    /// Trust the agent's standing lends its approvals, in `[0, 1]`: zero while the
+    /// reward gate holds the agent, otherwise a logistic curve over its total reward
+    /// (0.5 with no history).
+    pub fn trust_score(&self) -> f32 {
+        if self.requires_manual_approval {
+            return 0.0;
+        }
+        (1.0 / (1.0 + (-self.standing.total_reward).exp())) as f32
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -202,6 +484,8 @@ pub struct RewardScorekeeper {
     history_path: PathBuf,
     history: Vec<RewardDelta>,
     standings: HashMap<String, AgentStanding>,
+    overrides_path: PathBuf,
+    overrides: HashMap<String, StandingOverride>,
 }
 
 impl RewardScorekeeper {
@@ -218,11 +502,27 @@ impl RewardScorekeeper {
         } else {
             serde_json::from_str::<Vec<RewardDelta>>(&raw)?
         };
+        let overrides_path = history_path.with_file_name(STANDING_OVERRIDES_FILE);
+        let overrides = if overrides_path.exists() {
+            let raw = fs::read_to_string(&overrides_path)?;
+            if raw.trim().is_empty() {
+                HashMap::new()
+            } else {
+                serde_json::from_str::<Vec<StandingOverride>>(&raw)?
+                    .into_iter()
+                    .map(|entry| (entry.agent.clone(), entry))
+                    .collect()
+            }
+        } else {
+            HashMap::new()
+        };
         let mut scorekeeper = Self {
             config: RewardConfig::default(),
             history_path,
             history,
             standings: HashMap::new(),
+            overrides_path,
+            overrides,
         };
         scorekeeper.rebuild_standings();
         Ok(scorekeeper)
@@ -257,12 +557,115 @@ impl RewardScorekeeper {
 
     pub fn requires_manual_approval(&self, agent: &str) -> bool {
         let standing = self.standings.get(agent).cloned().unwrap_or_default();
-        self.requires_manual_approval_for(&standing)
+        self.requires_manual_approval_for(&standing) && self.active_override(agent).is_none()
+    }
+
+    /// Override currently exempting `agent` from the gate, if any.
+    pub fn active_override(&self, agent: &str) -> Option<&StandingOverride> {
+        self.overrides
+            .get(agent)
+            .filter(|entry| entry.is_active_at(Utc::now()))
+    }
+
+    pub fn overrides(&self) -> Vec<&StandingOverride> {
+        let mut overrides: Vec<_> = self.overrides.values().collect();
+        overrides.sort_by(|a, b| a.agent.cmp(&b.agent));
+        overrides
+    }
+
+    /// Grant a time-boxed override to an agent the gate currently flags.
+    ///
+    /// The granting role must be listed in `override_roles`, a justification is
+    /// required, and the duration is capped at `max_override_hours`.
+    pub fn grant_override(
+        &mut self,
+        request: StandingOverrideRequest,
+        now: DateTime<Utc>,
+    ) -> Result<StandingOverride, RewardError> {
+        if !self
+            .config
+            .override_roles
+            .iter()
+            .any(|role| role.eq_ignore_ascii_case(&request.role))
+        {
+            return Err(RewardError::OverrideRejected(format!(
+                "role '{}' may not grant standing overrides",
+                request.role
+            )));
+        }
+        if request.justification.trim().is_empty() {
+            return Err(RewardError::OverrideRejected(
+                "a justification is required".to_string(),
+            ));
+        }
+        let max = Duration::hours(self.config.max_override_hours);
+        if request.duration <= Duration::zero() || request.duration > max {
+            return Err(RewardError::OverrideRejected(format!(
+                "duration must be between 0 and {} hours",
+                self.config.max_override_hours
+            )));
+        }
+        let standing = self
+            .standings
+            .get(&request.agent)
+            .cloned()
+            .unwrap_or_default();
+        if !self.requires_manual_approval_for(&standing) {
+            return Err(RewardError::OverrideRejected(format!(
+                "agent '{}' is not flagged for manual approval",
+                request.agent
+            )));
+        }
+        let granted = StandingOverride {
+            agent: request.agent,
+            granted_by: request.granted_by,
+            role: request.role,
+            justification: request.justification,
+            granted_at: now.to_rfc3339(),
+            expires_at: (now + request.duration).to_rfc3339(),
+            total_reward: standing.total_reward,
+            recent_average: standing.recent_average(),
+        };
+        self.overrides
+            .insert(granted.agent.clone(), granted.clone());
+        self.save_overrides()?;
+        Ok(granted)
+    }
+
+    /// Drop overrides that expired by `now`, returning them.
+    pub fn expire_overrides(
+        &mut self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<StandingOverride>, RewardError> {
+        let expired: Vec<String> = self
+            .overrides
+            .values()
+            .filter(|entry| !entry.is_active_at(now))
+            .map(|entry| entry.agent.clone())
+            .collect();
+        if expired.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut removed: Vec<StandingOverride> = expired
+            .iter()
+            .filter_map(|agent| self.overrides.remove(agent))
+            .collect();
+        removed.sort_by(|a, b| a.agent.cmp(&b.agent));
+        self.save_overrides()?;
+        Ok(removed)
+    }
+
+    fn save_overrides(&self) -> Result<(), RewardError> {
+        let payload = serde_json::to_string_pretty(&self.overrides())?;
+        fs::write(&self.overrides_path, payload)?;
+        Ok(())
     }
 
     pub fn approval_status(&self, agent: &str) -> AgentApprovalStatus {
         let standing = self.standings.get(agent).cloned().unwrap_or_default();
-        let requires_manual_approval = self.requires_manual_approval_for(&standing);
+        let standing_override = self.active_override(agent).cloned();
+        let requires_manual_approval =
+            self.requires_manual_approval_for(&standing) && standing_override.is_none();
         let reason = if requires_manual_approval {
             Some(format!(
                 "Reward total {:.2} or recent trend {:.2} below threshold",
@@ -277,6 +680,7 @@ impl RewardScorekeeper {
             requires_manual_approval,
             reason,
             standing,
+            standing_override,
         }
     }
 
@@ -298,18 +702,7 @@ impl RewardScorekeeper {
         let entries = self.history.len();
         let divisor = entries.max(1) as f64;
 
-        let mut summaries: Vec<AgentStandingSummary> = self
-            .standings
-            .iter()
-            .map(|(agent, standing)| AgentStandingSummary {
-                agent: agent.clone(),
-                total_reward: standing.total_reward,
-                recent_average: standing.recent_average(),
-                penalties: standing.penalties,
-                requires_manual_approval: self.requires_manual_approval_for(standing),
-            })
-            .collect();
-
+        let mut summaries = self.standing_summaries();
         summaries.sort_by(|a, b| {
             b.total_reward
                 .partial_cmp(&a.total_reward)
@@ -338,11 +731,130 @@ impl RewardScorekeeper {
         }
     }
 
+    /// Break down how each factor contributed to an agent's rewards in a period.
+    ///
+    /// `outcome_link` maps a goal id to the location of its recorded outcome.
+    /// Factor targets and weights come from the current configuration.
+    pub fn explain(
+        &self,
+        agent: &str,
+        period: &RewardPeriod,
+        outcome_link: impl Fn(&str) -> String,
+    ) -> AgentRewardExplanation {
+        let config = &self.config;
+        let mut totals: HashMap<RewardFactor, f64> = HashMap::new();
+        let mut deltas = Vec::new();
+        for (history_index, delta) in self.history.iter().enumerate() {
+            let Some(snapshot) = delta.agents.iter().find(|snapshot| snapshot.agent == agent)
+            else {
+                continue;
+            };
+            if !period.contains(&delta.timestamp) {
+                continue;
+            }
+            let shared_with = delta.agents.len();
+            let share = 1.0 / shared_with as f64;
+            let inputs = &delta.inputs;
+            let mut contributions = vec![
+                (
+                    RewardFactor::Coverage,
+                    inputs.coverage,
+                    config.coverage_target,
+                    config.coverage_weight,
+                    delta.coverage_delta,
+                ),
+                (
+                    RewardFactor::FlakeRate,
+                    inputs.flake_rate,
+                    config.flake_target,
+                    config.flake_weight,
+                    delta.flake_delta,
+                ),
+                (
+                    RewardFactor::TokenRatio,
+                    inputs.token_ratio,
+                    config.token_target,
+                    config.token_weight,
+                    delta.token_delta,
+                ),
+                (
+                    RewardFactor::Rollbacks,
+                    inputs.rollback_count as f64,
+                    0.0,
+                    config.rollback_weight,
+                    delta.rollback_delta,
+                ),
+            ]
+            .into_iter()
+            .map(
+                |(factor, input, target, weight, factor_delta)| RewardContribution {
+                    factor,
+                    input,
+                    target,
+                    weight,
+                    delta: factor_delta,
+                    agent_delta: factor_delta * share,
+                },
+            )
+            .collect::<Vec<_>>();
+            if !snapshot.success {
+                contributions.push(RewardContribution {
+                    factor: RewardFactor::AgentFailure,
+                    input: 1.0,
+                    target: 0.0,
+                    weight: config.failure_penalty,
+                    delta: -config.failure_penalty,
+                    agent_delta: -config.failure_penalty,
+                });
+            }
+            for contribution in &contributions {
+                *totals.entry(contribution.factor).or_default() += contribution.agent_delta;
+            }
+            deltas.push(RewardDeltaExplanation {
+                timestamp: delta.timestamp.clone(),
+                goal_id: delta.goal_id.clone(),
+                workflow_id: delta.workflow_id.clone(),
+                history_index,
+                outcome_link: outcome_link(&delta.goal_id),
+                agent_success: snapshot.success,
+                shared_with,
+                total_reward: delta.total_reward,
+                agent_reward: contributions.iter().map(|c| c.agent_delta).sum(),
+                contributions,
+            });
+        }
+        AgentRewardExplanation {
+            agent: agent.to_string(),
+            period: period.clone(),
+            agent_reward: deltas.iter().map(|delta| delta.agent_reward).sum(),
+            factor_totals: RewardFactor::ALL
+                .iter()
+                .map(|factor| (*factor, totals.get(factor).copied().unwrap_or_default()))
+                .collect(),
+            deltas,
+            requires_manual_approval: self.requires_manual_approval(agent),
+        }
+    }
+
+    /// Standing of every agent with reward history, in no particular order.
+    pub fn standing_summaries(&self) -> Vec<AgentStandingSummary> {
+        self.standings
+            .iter()
+            .map(|(agent, standing)| AgentStandingSummary {
+                agent: agent.clone(),
+                total_reward: standing.total_reward,
+                recent_average: standing.recent_average(),
+                penalties: standing.penalties,
+                requires_manual_approval: self.requires_manual_approval(agent),
+            })
+            .collect()
+    }
+
     pub fn flagged_agents(&self) -> Vec<AgentStandingSummary> {
         self.standings
             .iter()
             .filter_map(|(agent, standing)| {
-                if self.requires_manual_approval_for(standing) {
+                if self.requires_manual_approval(agent) {
                     Some(AgentStandingSummary {
                         agent: agent.clone(),
                         total_reward: standing.total_reward,
@@ -425,6 +937,146 @@ mod tests {
         assert!(keeper.requires_manual_approval("agent-a"));
     }
 
+    #[test]
+    fn trust_score_follows_reward_and_drops_to_zero_when_gated() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("reward_history.json");
+        let mut keeper = RewardScorekeeper::new(path).unwrap();
+        assert!((keeper.approval_status("agent-a").trust_score() - 0.5).abs() < 1e-6);
+
+        let good_inputs = RewardInputs {
+            coverage: 0.94,
+            flake_rate: 0.01,
+            token_ratio: 0.8,
+            rollback_count: 0,
+        };
+        keeper.record("goal", "wf", good_inputs, &sample_agents(true));
+        assert!(keeper.approval_status("agent-a").trust_score() > 0.5);
+
+        let bad_inputs = RewardInputs {
+            coverage: 0.42,
+            flake_rate: 0.55,
+            token_ratio: 1.7,
+            rollback_count: 2,
+        };
+        for _ in 0..3 {
+            keeper.record("goal", "wf", bad_inputs.clone(), &sample_agents(false));
+        }
+        assert!(keeper.requires_manual_approval("agent-a"));
+        assert_eq!(keeper.approval_status("agent-a").trust_score(), 0.0);
+    }
+
+    #[test]
+    fn explanation_attributes_agent_reward_to_factors() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("reward_history.json");
+        let mut keeper = RewardScorekeeper::new(path).unwrap();
+
+        let inputs = RewardInputs {
+            coverage: 0.45,
+            flake_rate: 0.3,
+            token_ratio: 1.2,
+            rollback_count: 1,
+        };
+        let agents = [
+            RewardAgentSnapshot {
+                agent: "agent-a".to_string(),
+                success: false,
+            },
+            RewardAgentSnapshot {
+                agent: "agent-b".to_string(),
+                success: true,
+            },
+        ];
+        keeper.record("goal-1", "wf", inputs, &agents);
+        keeper.record(
+            "goal-2",
+            "wf",
+            RewardInputs::default(),
+            &sample_agents(true),
+        );
+
+        let explanation = keeper.explain("agent-a", &RewardPeriod::all(), |goal| {
+            format!("goal_kpis.json#{goal}")
+        });
+        assert_eq!(explanation.deltas.len(), 2);
+        let first = &explanation.deltas[0];
+        assert_eq!(first.shared_with, 2);
+        assert_eq!(first.outcome_link, "goal_kpis.json#goal-1");
+        assert!(first
+            .contributions
+            .iter()
+            .any(|c| c.factor == RewardFactor::AgentFailure));
+        let standing = &keeper.standings()["agent-a"];
+        assert!((explanation.agent_reward - standing.total_reward).abs() < 1e-9);
+        assert_eq!(explanation.largest_drag(), Some(RewardFactor::AgentFailure));
+
+        let markdown = explanation.to_markdown();
+        assert!(markdown.contains("# Reward explanation: agent-a"));
+        assert!(markdown.contains("[goal-1](goal_kpis.json#goal-1)"));
+        assert!(explanation.to_json().unwrap().contains("\"agent_failure\""));
+
+        let future = Utc::now() + chrono::Duration::days(1);
+        let empty = keeper.explain(
+            "agent-a",
+            &RewardPeriod::between(future, future + chrono::Duration::days(1)),
+            |goal| goal.to_string(),
+        );
+        assert!(empty.deltas.is_empty());
+    }
+
+    #[test]
+    fn standing_override_is_authorized_time_boxed_and_persisted() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("reward_history.json");
+        let mut keeper = RewardScorekeeper::new(path.clone()).unwrap();
+        let request = StandingOverrideRequest {
+            agent: "agent-a".to_string(),
+            granted_by: "ops-lead".to_string(),
+            role: "release-manager".to_string(),
+            justification: "Flakes traced to a broken runner, not the agent".to_string(),
+            duration: Duration::hours(4),
+        };
+        let now = Utc::now();
+        assert!(keeper.grant_override(request.clone(), now).is_err());
+
+        let bad_inputs = RewardInputs {
+            coverage: 0.3,
+            flake_rate: 0.6,
+            token_ratio: 1.8,
+            rollback_count: 3,
+        };
+        keeper.record("goal", "wf", bad_inputs.clone(), &sample_agents(false));
+        keeper.record("goal", "wf", bad_inputs, &sample_agents(false));
+        assert!(keeper.requires_manual_approval("agent-a"));
+
+        let unauthorized = StandingOverrideRequest {
+            role: "developer".to_string(),
+            ..request.clone()
+        };
+        assert!(keeper.grant_override(unauthorized, now).is_err());
+        let too_long = StandingOverrideRequest {
+            duration: Duration::hours(500),
+            ..request.clone()
+        };
+        assert!(keeper.grant_override(too_long, now).is_err());
+
+        keeper.grant_override(request, now).unwrap();
+        let status = keeper.approval_status("agent-a");
+        assert!(status.approved());
+        assert_eq!(status.standing_override.unwrap().granted_by, "ops-lead");
+        assert!(keeper.flagged_agents().is_empty());
+
+        let reloaded = RewardScorekeeper::new(path).unwrap();
+        assert!(reloaded.active_override("agent-a").is_some());
+
+        assert!(keeper.expire_overrides(now).unwrap().is_empty());
+        let expired = keeper.expire_overrides(now + Duration::hours(5)).unwrap();
+        assert_eq!(expired.len(), 1);
+        assert!(keeper.overrides().is_empty());
+        assert!(keeper.requires_manual_approval("agent-a"));
+    }
+
     #[test]
     fn improvements_clear_penalties() {
         let dir = tempdir().unwrap();
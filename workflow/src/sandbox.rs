@@ -0,0 +1,192 @@
+//! Per-task execution sandboxes.
+//!
+//! Every dispatched task runs against its own temporary workspace. Paths the task
+//! declares as inputs are copied in from the workspace root, declared outputs are
+//! copied out into the stage's artifact directory, and the temporary directory is
+//! removed when the [`TaskSandbox`] is closed or dropped, whether the task succeeded
+//! or not.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use noa_core::utils::simple_hash;
+use serde::{Deserialize, Serialize};
+use tempfile::TempDir;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SandboxError {
+    #[error("sandbox path '{0}' must be relative and stay inside the workspace")]
+    InvalidPath(String),
+    #[error("declared input '{0}' does not exist in the workspace")]
+    MissingInput(String),
+    #[error("declared output '{0}' was not produced by the task")]
+    MissingOutput(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Workspace paths a task reads and the files it is expected to produce.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SandboxSpec {
+    /// Files or directories copied from the workspace root into the sandbox.
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// Files collected from the sandbox into the stage artifacts.
+    #[serde(default)]
+    pub outputs: Vec<String>,
+}
+
+impl SandboxSpec {
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty() && self.outputs.is_empty()
+    }
+}
+
+/// A file collected from a sandbox.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SandboxArtifact {
+    pub path: String,
+    pub stored_at: PathBuf,
+    pub size_bytes: u64,
+    pub hash: String,
+}
+
+/// Temporary workspace for a single task.
+pub struct TaskSandbox {
+    dir: TempDir,
+}
+
+impl TaskSandbox {
+    /// Create a sandbox seeded with copies of the spec's inputs from `workspace_root`.
+    pub fn create(workspace_root: &Path, spec: &SandboxSpec) -> Result<Self, SandboxError> {
+        let dir = tempfile::Builder::new().prefix("noa-task-").tempdir()?;
+        for input in &spec.inputs {
+            let relative = checked_relative(input)?;
+            let source = workspace_root.join(&relative);
+            if !source.exists() {
+                return Err(SandboxError::MissingInput(input.clone()));
+            }
+            copy_recursive(&source, &dir.path().join(&relative))?;
+        }
+        Ok(Self { dir })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Copy the spec's outputs into `destination`, preserving their relative paths.
+    pub fn collect(
+        &self,
+        spec: &SandboxSpec,
+        destination: &Path,
+    ) -> Result<Vec<SandboxArtifact>, SandboxError> {
+        let mut artifacts = Vec::with_capacity(spec.outputs.len());
+        for output in &spec.outputs {
+            let relative = checked_relative(output)?;
+            let produced = self.dir.path().join(&relative);
+            if !produced.is_file() {
+                return Err(SandboxError::MissingOutput(output.clone()));
+            }
+            let contents = fs::read(&produced)?;
+            let stored_at = destination.join(&relative);
+            if let Some(parent) = stored_at.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&stored_at, &contents)?;
+            artifacts.push(SandboxArtifact {
+                path: output.clone(),
+                stored_at,
+                size_bytes: contents.len() as u64,
+                hash: simple_hash(&String::from_utf8_lossy(&contents)),
+            });
+        }
+        Ok(artifacts)
+    }
+
+    /// Remove the sandbox, reporting any cleanup failure. Dropping also removes it.
+    pub fn close(self) -> Result<(), SandboxError> {
+        self.dir.close().map_err(SandboxError::from)
+    }
+}
+
+fn checked_relative(value: &str) -> Result<PathBuf, SandboxError> {
+    let path = Path::new(value);
+    let escapes = path
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+    if value.trim().is_empty() || escapes {
+        return Err(SandboxError::InvalidPath(value.to_string()));
+    }
+    Ok(path.to_path_buf())
+}
+
+fn copy_recursive(source: &Path, target: &Path) -> Result<(), SandboxError> {
+    if source.is_dir() {
+        fs::create_dir_all(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &target.join(entry.file_name()))?;
+        }
+    } else {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(source, target)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn sandbox_isolates_inputs_collects_outputs_and_cleans_up() {
+        let workspace = tempdir().unwrap();
+        fs::create_dir_all(workspace.path().join("docs/guides")).unwrap();
+        fs::write(workspace.path().join("docs/guides/intro.md"), "original").unwrap();
+        let artifacts_dir = tempdir().unwrap();
+        let spec = SandboxSpec {
+            inputs: vec!["docs".to_string()],
+            outputs: vec!["report/summary.txt".to_string()],
+        };
+
+        let sandbox = TaskSandbox::create(workspace.path(), &spec).unwrap();
+        let root = sandbox.path().to_path_buf();
+        fs::write(root.join("docs/guides/intro.md"), "edited in sandbox").unwrap();
+        assert!(matches!(
+            sandbox.collect(&spec, artifacts_dir.path()),
+            Err(SandboxError::MissingOutput(_))
+        ));
+        fs::create_dir_all(root.join("report")).unwrap();
+        fs::write(root.join("report/summary.txt"), "done").unwrap();
+
+        let collected = sandbox.collect(&spec, artifacts_dir.path()).unwrap();
+        assert_eq!(collected.len(), 1);
+        assert_eq!(collected[0].size_bytes, 4);
+        assert_eq!(
+            fs::read_to_string(artifacts_dir.path().join("report/summary.txt")).unwrap(),
+            "done"
+        );
+        assert_eq!(
+            fs::read_to_string(workspace.path().join("docs/guides/intro.md")).unwrap(),
+            "original"
+        );
+
+        sandbox.close().unwrap();
+        assert!(!root.exists());
+        assert!(matches!(
+            TaskSandbox::create(
+                workspace.path(),
+                &SandboxSpec {
+                    inputs: vec!["../etc".to_string()],
+                    outputs: Vec::new(),
+                }
+            ),
+            Err(SandboxError::InvalidPath(_))
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+//! Reuse of stage results across workflow runs.
+//!
+//! A stage opts in through its [`StageCacheControl`]. Its cache key hashes the
+//! stage's tasks (agents, actions, parameters), the receipt roots of the
+//! upstream stages it depends on in the current run, and the version of every
+//! agent its tasks resolve to. When a previous successful run stored an entry
+//! under the same key, the engine reuses that run's artifacts and receipt —
+//! marked as a cache hit — instead of dispatching agents. Upgrading an agent
+//! changes the key, and storing a stage evicts its entries for older keys.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use noa_core::utils::{current_timestamp_millis, simple_hash};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::instrumentation::StageReceipt;
+use crate::Stage;
+
+#[derive(Debug, Error)]
+pub enum StageCacheError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+}
+
+/// Per-stage cache settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageCacheControl {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Entries older than this are ignored; unbounded when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_secs: Option<u64>,
+}
+
+impl StageCacheControl {
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            max_age_secs: None,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Artifacts and receipt of a successful stage run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedStage {
+    pub key: String,
+    pub workflow_id: String,
+    pub stage_id: String,
+    pub artifacts: Vec<Value>,
+    pub receipt: StageReceipt,
+    /// Agent id to version for every task, as resolved when the entry was stored.
+    pub agent_versions: BTreeMap<String, String>,
+    pub stored_at: u128,
+}
+
+/// Stage results keyed on their inputs, persisted as JSON.
+#[derive(Debug)]
+pub struct StageCache {
+    path: Option<PathBuf>,
+    entries: Mutex<HashMap<String, CachedStage>>,
+    /// Receipt root of each stage's latest run, by `(workflow_id, stage_id)`.
+    roots: Mutex<HashMap<(String, String), String>>,
+}
+
+impl StageCache {
+    /// A cache kept in memory for the lifetime of the engine.
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            entries: Mutex::new(HashMap::new()),
+            roots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A cache persisted at `path`, loading any entries already stored there.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StageCacheError> {
+        let path = path.as_ref().to_path_buf();
+        let entries = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            path: Some(path),
+            entries: Mutex::new(entries),
+            roots: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Key for running `stage` with `agent_versions`, or `None` when an upstream
+    /// stage has not produced a receipt in this run.
+    pub fn key(
+        &self,
+        workflow_id: &str,
+        stage: &Stage,
+        agent_versions: &BTreeMap<String, String>,
+    ) -> Option<String> {
+        let roots = self.roots.lock().unwrap();
+        let mut upstream = BTreeMap::new();
+        for dependency in &stage.depends_on {
+            let root = roots.get(&(workflow_id.to_string(), dependency.clone()))?;
+            upstream.insert(dependency.clone(), root.clone());
+        }
+        let inputs = json!({
+            "workflow_id": workflow_id,
+            "stage": stage.name,
+            "stage_type": stage.stage_type,
+            "tasks": stage.tasks,
+            "upstream": upstream,
+            "agents": agent_versions,
+        });
+        Some(simple_hash(&inputs.to_string()))
+    }
+
+    /// Entry stored under `key`, unless it is older than `control` allows.
+    pub fn lookup(&self, key: &str, control: &StageCacheControl) -> Option<CachedStage> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(key)?;
+        if let Some(max_age) = control.max_age_secs {
+            let age_ms = current_timestamp_millis().saturating_sub(entry.stored_at);
+            if age_ms > u128::from(max_age) * 1000 {
+                return None;
+            }
+        }
+        Some(entry.clone())
+    }
+
+    /// Store a successful stage run, replacing entries the stage stored under older keys.
+    pub fn store(&self, entry: CachedStage) -> Result<(), StageCacheError> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, existing| {
+            existing.workflow_id != entry.workflow_id || existing.stage_id != entry.stage_id
+        });
+        entries.insert(entry.key.clone(), entry);
+        self.persist(&entries)
+    }
+
+    /// Drop every entry produced with `agent_id`, returning how many were removed.
+    pub fn invalidate_agent(&self, agent_id: &str) -> Result<usize, StageCacheError> {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|_, entry| !entry.agent_versions.contains_key(agent_id));
+        let removed = before - entries.len();
+        if removed > 0 {
+            self.persist(&entries)?;
+        }
+        Ok(removed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remember the receipt root a stage produced in the current run, so stages
+    /// depending on it can key on it.
+    pub(crate) fn record_root(&self, workflow_id: &str, stage_id: &str, merkle_root: &str) {
+        self.roots.lock().unwrap().insert(
+            (workflow_id.to_string(), stage_id.to_string()),
+            merkle_root.to_string(),
+        );
+    }
+
+    /// Forget the roots of a workflow's previous run.
+    pub(crate) fn reset_roots(&self, workflow_id: &str) {
+        self.roots
+            .lock()
+            .unwrap()
+            .retain(|(workflow, _), _| workflow != workflow_id);
+    }
+
+    fn persist(&self, entries: &HashMap<String, CachedStage>) -> Result<(), StageCacheError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(entries)?)?;
+        Ok(())
+    }
+}
+
+impl Default for StageCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,590 @@
+//! Event-driven workflow triggers.
+//!
+//! A [`TriggerBinding`] subscribes a loaded workflow to a bus topic or a ledger
+//! event kind (for example `crc.drop.ready` or `deployment.health_failed`). Events
+//! arrive either as JSON messages on an IPC channel or from pipeline events
+//! recorded by instrumentation. When an event's topic matches a binding and its
+//! optional filter expression holds, the engine instantiates the workflow with the
+//! event as input and runs it.
+//!
+//! Filter expressions compare dotted paths into the event (`topic`, `source`,
+//! `payload.<field>`) against JSON literals, e.g.
+//! `payload.environment == "production" && payload.error_rate > 5`.
+//! Supported operators are `==`, `!=`, `>`, `>=`, `<`, `<=`, `contains`, `!`,
+//! `&&`, `||`, and parentheses; a bare path is true when it is present and truthy.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use noa_core::ipc::{ChannelId, Message};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+use crate::instrumentation::LedgerEvent;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TriggerError {
+    #[error("trigger topic must not be empty")]
+    EmptyTopic,
+    #[error("workflow '{0}' is not loaded")]
+    UnknownWorkflow(String),
+    #[error("invalid filter expression '{expression}': {reason}")]
+    InvalidFilter { expression: String, reason: String },
+    #[error("invalid bus message: {0}")]
+    InvalidMessage(String),
+}
+
+/// Where a trigger event originated.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerSource {
+    Bus,
+    Ledger,
+}
+
+impl fmt::Display for TriggerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriggerSource::Bus => f.write_str("bus"),
+            TriggerSource::Ledger => f.write_str("ledger"),
+        }
+    }
+}
+
+/// An event that may start workflows.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TriggerEvent {
+    pub source: TriggerSource,
+    pub topic: String,
+    #[serde(default)]
+    pub payload: Value,
+}
+
+#[derive(Deserialize)]
+struct BusEnvelope {
+    topic: String,
+    #[serde(default)]
+    payload: Value,
+}
+
+impl TriggerEvent {
+    pub fn bus(topic: impl Into<String>, payload: Value) -> Self {
+        Self {
+            source: TriggerSource::Bus,
+            topic: topic.into(),
+            payload,
+        }
+    }
+
+    /// Decode an IPC message whose data is `{"topic": ..., "payload": ...}` JSON.
+    /// Typed messages from [`noa_core::ipc::publish`] use the same envelope.
+    pub fn from_ipc_message(message: &Message) -> Result<Self, TriggerError> {
+        let envelope: BusEnvelope = serde_json::from_slice(&message.data)
+            .map_err(|err| TriggerError::InvalidMessage(err.to_string()))?;
+        Ok(Self::bus(envelope.topic, envelope.payload))
+    }
+
+    /// Ledger events trigger on their event type; the payload carries the event details.
+    pub fn from_ledger_event(event: &LedgerEvent) -> Self {
+        Self {
+            source: TriggerSource::Ledger,
+            topic: event.event_type.clone(),
+            payload: json!({
+                "namespace": event.namespace,
+                "actor": event.actor,
+                "subject": event.subject,
+                "metadata": event.metadata,
+                "timestamp": event.timestamp,
+            }),
+        }
+    }
+
+    /// The JSON document filter paths are resolved against.
+    fn as_document(&self) -> Value {
+        json!({
+            "topic": self.topic,
+            "source": self.source,
+            "payload": self.payload,
+        })
+    }
+}
+
+/// Subscription of a loaded workflow to an event topic.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TriggerBinding {
+    pub workflow_id: String,
+    /// Exact topic, or a prefix pattern ending in `.*` (e.g. `crc.drop.*`).
+    pub topic: String,
+    /// Restrict the binding to one event source; `None` accepts both.
+    #[serde(default)]
+    pub source: Option<TriggerSource>,
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+impl TriggerBinding {
+    pub fn new(workflow_id: impl Into<String>, topic: impl Into<String>) -> Self {
+        Self {
+            workflow_id: workflow_id.into(),
+            topic: topic.into(),
+            source: None,
+            filter: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: TriggerSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    fn matches_topic(&self, topic: &str) -> bool {
+        match self.topic.strip_suffix(".*") {
+            Some(prefix) => topic
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with('.')),
+            None => self.topic == topic,
+        }
+    }
+}
+
+/// A binding whose filter has been parsed.
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledTrigger {
+    pub(crate) binding: TriggerBinding,
+    filter: Option<FilterExpr>,
+}
+
+impl CompiledTrigger {
+    pub(crate) fn compile(binding: TriggerBinding) -> Result<Self, TriggerError> {
+        if binding.topic.trim().is_empty() {
+            return Err(TriggerError::EmptyTopic);
+        }
+        let filter = binding
+            .filter
+            .as_deref()
+            .map(FilterExpr::parse)
+            .transpose()?;
+        Ok(Self { binding, filter })
+    }
+
+    pub(crate) fn matches(&self, event: &TriggerEvent) -> bool {
+        if self
+            .binding
+            .source
+            .is_some_and(|source| source != event.source)
+        {
+            return false;
+        }
+        if !self.binding.matches_topic(&event.topic) {
+            return false;
+        }
+        match &self.filter {
+            Some(filter) => filter.evaluate(&event.as_document()),
+            None => true,
+        }
+    }
+}
+
+/// Bindings and event sources owned by a workflow engine.
+#[derive(Default)]
+pub(crate) struct TriggerRegistry {
+    pub(crate) bindings: Vec<CompiledTrigger>,
+    pub(crate) ipc_channel: Option<ChannelId>,
+    pub(crate) ledger: Option<broadcast::Receiver<LedgerEvent>>,
+    pub(crate) sequence: u64,
+}
+
+/// Outcome of a workflow started by a trigger.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TriggerRun {
+    pub workflow_id: String,
+    pub instance_id: String,
+    pub topic: String,
+    pub state: crate::WorkflowState,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FilterExpr {
+    Truthy(String),
+    Not(Box<FilterExpr>),
+    Compare(String, CompareOp, Value),
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Path(String),
+    Literal(Value),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl FilterExpr {
+    fn parse(expression: &str) -> Result<Self, TriggerError> {
+        let invalid = |reason: String| TriggerError::InvalidFilter {
+            expression: expression.to_string(),
+            reason,
+        };
+        let tokens = tokenize(expression).map_err(invalid)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let expr = parser.parse_or().map_err(invalid)?;
+        if parser.position != parser.tokens.len() {
+            return Err(invalid(format!(
+                "unexpected token {:?}",
+                parser.tokens[parser.position]
+            )));
+        }
+        Ok(expr)
+    }
+
+    fn evaluate(&self, document: &Value) -> bool {
+        match self {
+            FilterExpr::Truthy(path) => resolve(document, path).is_some_and(truthy),
+            FilterExpr::Not(inner) => !inner.evaluate(document),
+            FilterExpr::And(left, right) => left.evaluate(document) && right.evaluate(document),
+            FilterExpr::Or(left, right) => left.evaluate(document) || right.evaluate(document),
+            FilterExpr::Compare(path, op, literal) => {
+                let actual = resolve(document, path).unwrap_or(&Value::Null);
+                compare(actual, *op, literal)
+            }
+        }
+    }
+}
+
+fn resolve<'a>(document: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(document, |value, segment| match value {
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => value.get(segment),
+        })
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(flag) => *flag,
+        Value::Number(number) => number.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+fn compare(actual: &Value, op: CompareOp, expected: &Value) -> bool {
+    match op {
+        CompareOp::Eq => {
+            actual == expected || numeric_order(actual, expected) == Some(Ordering::Equal)
+        }
+        CompareOp::Ne => !compare(actual, CompareOp::Eq, expected),
+        CompareOp::Gt => numeric_order(actual, expected) == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(
+            numeric_order(actual, expected),
+            Some(Ordering::Greater | Ordering::Equal)
+        ),
+        CompareOp::Lt => numeric_order(actual, expected) == Some(Ordering::Less),
+        CompareOp::Le => matches!(
+            numeric_order(actual, expected),
+            Some(Ordering::Less | Ordering::Equal)
+        ),
+        CompareOp::Contains => match (actual, expected) {
+            (Value::String(text), Value::String(needle)) => text.contains(needle.as_str()),
+            (Value::Array(items), needle) => items.contains(needle),
+            _ => false,
+        },
+    }
+}
+
+fn numeric_order(actual: &Value, expected: &Value) -> Option<Ordering> {
+    match (actual, expected) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::Open);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                i += 1;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Eq));
+                i += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Ne));
+                i += 2;
+            }
+            '!' => {
+                tokens.push(Token::Not);
+                i += 1;
+            }
+            '>' | '<' => {
+                let inclusive = next == Some('=');
+                let op = match (c, inclusive) {
+                    ('>', false) => CompareOp::Gt,
+                    ('>', true) => CompareOp::Ge,
+                    ('<', false) => CompareOp::Lt,
+                    _ => CompareOp::Le,
+                };
+                tokens.push(Token::Op(op));
+                i += if inclusive { 2 } else { 1 };
+            }
+            '"' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                if i >= chars.len() {
+                    return Err("unterminated string literal".to_string());
+                }
+                i += 1;
+                let raw: String = chars[start..i].iter().collect();
+                let literal = serde_json::from_str(&raw).map_err(|err| err.to_string())?;
+                tokens.push(Token::Literal(literal));
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let start = i;
+                i += 1;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || ".+-".contains(chars[i]))
+                {
+                    i += 1;
+                }
+                let raw: String = chars[start..i].iter().collect();
+                let literal: Value = serde_json::from_str(&raw)
+                    .map_err(|_| format!("invalid number literal '{raw}'"))?;
+                tokens.push(Token::Literal(literal));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || "_.-".contains(chars[i]))
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match word.as_str() {
+                    "true" => Token::Literal(Value::Bool(true)),
+                    "false" => Token::Literal(Value::Bool(false)),
+                    "null" => Token::Literal(Value::Null),
+                    "contains" => Token::Op(CompareOp::Contains),
+                    _ => Token::Path(word),
+                });
+            }
+            other => return Err(format!("unexpected character '{other}'")),
+        }
+    }
+    if tokens.is_empty() {
+        return Err("expression is empty".to_string());
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.advance();
+            expr = FilterExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.advance();
+            expr = FilterExpr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterExpr, String> {
+        match self.advance() {
+            Some(Token::Not) => Ok(FilterExpr::Not(Box::new(self.parse_unary()?))),
+            Some(Token::Open) => {
+                let expr = self.parse_or()?;
+                match self.advance() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err("missing closing parenthesis".to_string()),
+                }
+            }
+            Some(Token::Path(path)) => match self.peek() {
+                Some(Token::Op(op)) => {
+                    let op = *op;
+                    self.advance();
+                    match self.advance() {
+                        Some(Token::Literal(literal)) => Ok(FilterExpr::Compare(path, op, literal)),
+                        _ => Err(format!("expected a literal after '{path}'")),
+                    }
+                }
+                _ => Ok(FilterExpr::Truthy(path)),
+            },
+            Some(token) => Err(format!("unexpected token {token:?}")),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noa_core::ipc::topics::CrcDropReady;
+
+    fn compiled(topic: &str, filter: Option<&str>) -> CompiledTrigger {
+        let mut binding = TriggerBinding::new("wf", topic);
+        binding.filter = filter.map(str::to_string);
+        CompiledTrigger::compile(binding).unwrap()
+    }
+
+    #[test]
+    fn filters_match_event_payloads() {
+        let event = TriggerEvent::bus(
+            "deployment.health_failed",
+            json!({ "environment": "production", "error_rate": 7.5, "tags": ["canary"] }),
+        );
+
+        assert!(compiled("deployment.health_failed", None).matches(&event));
+        assert!(compiled("deployment.*", None).matches(&event));
+        assert!(!compiled("deploy.*", None).matches(&event));
+        assert!(compiled(
+            "deployment.*",
+            Some(r#"payload.environment == "production" && payload.error_rate > 5"#)
+        )
+        .matches(&event));
+        assert!(!compiled(
+            "deployment.*",
+            Some(r#"payload.environment != "production" || payload.error_rate <= 5"#)
+        )
+        .matches(&event));
+        assert!(compiled(
+            "deployment.*",
+            Some(r#"!(payload.missing) && payload.tags contains "canary""#)
+        )
+        .matches(&event));
+
+        let ledger_only = CompiledTrigger::compile(
+            TriggerBinding::new("wf", "deployment.health_failed")
+                .with_source(TriggerSource::Ledger),
+        )
+        .unwrap();
+        assert!(!ledger_only.matches(&event));
+    }
+
+    #[test]
+    fn rejects_malformed_filters_and_messages() {
+        for filter in [
+            "",
+            "payload.x ==",
+            "(payload.x",
+            "payload.x = 1",
+            "payload.x == 'y'",
+        ] {
+            let binding = TriggerBinding::new("wf", "topic").with_filter(filter);
+            assert!(
+                matches!(
+                    CompiledTrigger::compile(binding),
+                    Err(TriggerError::InvalidFilter { .. })
+                ),
+                "{filter} should be rejected"
+            );
+        }
+        assert_eq!(
+            CompiledTrigger::compile(TriggerBinding::new("wf", " ")).unwrap_err(),
+            TriggerError::EmptyTopic
+        );
+
+        let message = Message {
+            from: 1,
+            to: 2,
+            data: br#"{"topic":"crc.drop.ready","payload":{"drop_id":"d-1"}}"#.to_vec(),
+        };
+        let event = TriggerEvent::from_ipc_message(&message).unwrap();
+        assert_eq!(event.topic, "crc.drop.ready");
+        assert_eq!(event.payload["drop_id"], "d-1");
+
+        let typed = noa_core::ipc::schema::encode(
+            1,
+            2,
+            &CrcDropReady {
+                drop_id: "d-2".into(),
+                priority: Some(1),
+            },
+        )
+        .unwrap();
+        let event = TriggerEvent::from_ipc_message(&typed).unwrap();
+        assert_eq!(event.topic, "crc.drop.ready");
+        assert_eq!(event.payload["priority"], 1);
+        assert!(TriggerEvent::from_ipc_message(&Message {
+            from: 1,
+            to: 2,
+            data: b"not json".to_vec(),
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,245 @@
+//! Mermaid and Graphviz renderings of workflow stage graphs.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{StageState, Workflow};
+
+/// Output format for a rendered workflow graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    #[default]
+    Mermaid,
+    Dot,
+}
+
+impl std::str::FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "mermaid" | "mmd" => Ok(GraphFormat::Mermaid),
+            "dot" | "graphviz" => Ok(GraphFormat::Dot),
+            other => Err(format!("unsupported graph format: {other}")),
+        }
+    }
+}
+
+impl GraphFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            GraphFormat::Mermaid => "text/vnd.mermaid; charset=utf-8",
+            GraphFormat::Dot => "text/vnd.graphviz; charset=utf-8",
+        }
+    }
+}
+
+fn state_class(state: &StageState) -> &'static str {
+    match state {
+        StageState::Pending => "pending",
+        StageState::Running => "running",
+        StageState::Completed => "completed",
+        StageState::Failed => "failed",
+        StageState::Skipped => "skipped",
+        StageState::Compensated => "compensated",
+    }
+}
+
+fn state_color(state: &StageState) -> &'static str {
+    match state {
+        StageState::Pending => "#eeeeee",
+        StageState::Running => "#bbdefb",
+        StageState::Completed => "#c8e6c9",
+        StageState::Failed => "#ffcdd2",
+        StageState::Skipped => "#fff9c4",
+        StageState::Compensated => "#e1bee7",
+    }
+}
+
+const ALL_STATES: [StageState; 6] = [
+    StageState::Pending,
+    StageState::Running,
+    StageState::Completed,
+    StageState::Failed,
+    StageState::Skipped,
+    StageState::Compensated,
+];
+
+impl Workflow {
+    /// Render the stage dependency graph as a Mermaid flowchart.
+    ///
+    /// When `states` is provided each stage is coloured by its current [`StageState`].
+    pub fn to_mermaid(&self, states: Option<&HashMap<String, StageState>>) -> String {
+        let ids = self.node_ids();
+        let mut out = String::from("flowchart TD\n");
+
+        for (index, stage) in self.stages.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    stage_{}[\"{}\"]",
+                index,
+                stage.name.replace('"', "#quot;")
+            );
+        }
+        for (index, stage) in self.stages.iter().enumerate() {
+            for dependency in &stage.depends_on {
+                match ids.get(dependency.as_str()) {
+                    Some(dep_index) => {
+                        let _ = writeln!(out, "    stage_{} --> stage_{}", dep_index, index);
+                    }
+                    None => {
+                        let _ = writeln!(
+                            out,
+                            "    missing_{}[/\"{}\"/] -.-> stage_{}",
+                            index,
+                            dependency.replace('"', "#quot;"),
+                            index
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(states) = states {
+            for state in &ALL_STATES {
+                let _ = writeln!(
+                    out,
+                    "    classDef {} fill:{}",
+                    state_class(state),
+                    state_color(state)
+                );
+            }
+            for (index, stage) in self.stages.iter().enumerate() {
+                if let Some(state) = states.get(&stage.name) {
+                    let _ = writeln!(out, "    class stage_{} {}", index, state_class(state));
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Render the stage dependency graph in Graphviz DOT syntax.
+    ///
+    /// When `states` is provided each stage is filled with its current [`StageState`] colour.
+    pub fn to_dot(&self, states: Option<&HashMap<String, StageState>>) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph \"{}\" {{", escape_dot(&self.name));
+        out.push_str("    rankdir=TB;\n");
+        out.push_str("    node [shape=box, style=\"rounded,filled\", fillcolor=\"#ffffff\"];\n");
+
+        for stage in &self.stages {
+            let state = states.and_then(|states| states.get(&stage.name));
+            match state {
+                Some(state) => {
+                    let _ = writeln!(
+                        out,
+                        "    \"{}\" [fillcolor=\"{}\", tooltip=\"{}\"];",
+                        escape_dot(&stage.name),
+                        state_color(state),
+                        state_class(state)
+                    );
+                }
+                None => {
+                    let _ = writeln!(out, "    \"{}\";", escape_dot(&stage.name));
+                }
+            }
+        }
+        for stage in &self.stages {
+            for dependency in &stage.depends_on {
+                let _ = writeln!(
+                    out,
+                    "    \"{}\" -> \"{}\";",
+                    escape_dot(dependency),
+                    escape_dot(&stage.name)
+                );
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render the graph in the requested format.
+    pub fn render_graph(
+        &self,
+        format: GraphFormat,
+        states: Option<&HashMap<String, StageState>>,
+    ) -> String {
+        match format {
+            GraphFormat::Mermaid => self.to_mermaid(states),
+            GraphFormat::Dot => self.to_dot(states),
+        }
+    }
+
+    fn node_ids(&self) -> HashMap<&str, usize> {
+        self.stages
+            .iter()
+            .enumerate()
+            .map(|(index, stage)| (stage.name.as_str(), index))
+            .collect()
+    }
+}
+
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Stage, StageCacheControl, StageContract, StageType};
+
+    fn stage(name: &str, depends_on: &[&str]) -> Stage {
+        Stage {
+            name: name.to_string(),
+            stage_type: StageType::Sequential,
+            depends_on: depends_on.iter().map(|dep| dep.to_string()).collect(),
+            tasks: Vec::new(),
+            compensation: vec![],
+            cache: StageCacheControl::default(),
+            contract: StageContract::default(),
+        }
+    }
+
+    fn sample() -> Workflow {
+        Workflow {
+            name: "release".to_string(),
+            version: "1.0".to_string(),
+            stages: vec![
+                stage("build", &[]),
+                stage("test", &["build"]),
+                stage("deploy", &["test"]),
+            ],
+        }
+    }
+
+    #[test]
+    fn mermaid_renders_edges_and_state_classes() {
+        let states = HashMap::from([
+            ("build".to_string(), StageState::Completed),
+            ("test".to_string(), StageState::Failed),
+        ]);
+        let rendered = sample().to_mermaid(Some(&states));
+
+        assert!(rendered.starts_with("flowchart TD\n"));
+        assert!(rendered.contains("stage_0 --> stage_1"));
+        assert!(rendered.contains("stage_1 --> stage_2"));
+        assert!(rendered.contains("class stage_0 completed"));
+        assert!(rendered.contains("class stage_1 failed"));
+        assert!(!rendered.contains("class stage_2"));
+    }
+
+    #[test]
+    fn dot_renders_without_states() {
+        let rendered = sample().to_dot(None);
+
+        assert!(rendered.starts_with("digraph \"release\" {"));
+        assert!(rendered.contains("\"build\" -> \"test\";"));
+        assert!(!rendered.contains("tooltip"));
+        assert!(rendered.trim_end().ends_with('}'));
+    }
+}
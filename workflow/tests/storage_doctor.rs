@@ -1,41 +1,26 @@
-use noa_workflow::PipelineInstrumentation;
+use noa_workflow::{ConfigContext, Namespace, PipelineInstrumentation};
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::sync::Mutex;
-use tempfile::{tempdir, TempDir};
-
-static ENV_GUARD: Mutex<()> = Mutex::new(());
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
 
 fn with_temp_root<F>(f: F)
 where
-    F: FnOnce(&TempDir),
+    F: FnOnce(&Path),
 {
-    let guard = ENV_GUARD
-        .lock()
-        .unwrap_or_else(|poison| poison.into_inner());
     let temp = tempdir().expect("temporary workflow root");
-    std::env::set_var("NOA_WORKFLOW_ROOT", temp.path());
-    struct EnvReset<'a> {
-        _guard: std::sync::MutexGuard<'a, ()>,
-    }
-
-    impl<'a> Drop for EnvReset<'a> {
-        fn drop(&mut self) {
-            std::env::remove_var("NOA_WORKFLOW_ROOT");
-        }
-    }
-
-    let cleanup = EnvReset { _guard: guard };
-    f(&temp);
-    drop(temp);
-    drop(cleanup);
+    PipelineInstrumentation::with_context(
+        &Namespace::default(),
+        ConfigContext::isolated().with_workflow_root(temp.path()),
+    )
+    .expect("failed to bootstrap instrumentation");
+    f(temp.path());
 }
 
 #[test]
 fn storage_doctor_reports_healthy_after_bootstrap() {
-    with_temp_root(|_root| {
-        PipelineInstrumentation::new().expect("failed to bootstrap instrumentation");
-        let report = run_storage_doctor().expect("storage doctor execution");
+    with_temp_root(|root| {
+        let report = run_storage_doctor(root).expect("storage doctor execution");
         assert_eq!(report.status, StorageDoctorStatus::Healthy);
         assert!(report.is_healthy());
         assert!(report.drift.is_empty());
@@ -44,9 +29,8 @@ fn storage_doctor_reports_healthy_after_bootstrap() {
 
 #[test]
 fn storage_doctor_detects_log_drift() {
-    with_temp_root(|_root| {
-        PipelineInstrumentation::new().expect("failed to bootstrap instrumentation");
-        let (index_path, storage_path) = log_pair("relocation");
+    with_temp_root(|root| {
+        let (index_path, storage_path) = log_pair(root, "relocation");
         // Ensure both index and storage logs exist with identical genesis content
         std::fs::create_dir_all(index_path.parent().unwrap()).expect("create index dir");
         std::fs::create_dir_all(storage_path.parent().unwrap()).expect("create storage dir");
@@ -75,7 +59,7 @@ fn storage_doctor_detects_log_drift() {
             writeln!(idx, "{{\"drift\":true}}").expect("append drift entry");
         }
 
-        let report = run_storage_doctor().expect("storage doctor execution");
+        let report = run_storage_doctor(root).expect("storage doctor execution");
         let index_contents = std::fs::read_to_string(&index_path).expect("index contents");
         let storage_contents = std::fs::read_to_string(&storage_path).expect("storage contents");
         assert_ne!(index_contents, storage_contents);
@@ -124,28 +108,17 @@ impl StorageDoctorReport {
     }
 }
 
-fn workspace_root() -> std::path::PathBuf {
-    if let Ok(root) = std::env::var("NOA_WORKFLOW_ROOT") {
-        return std::path::PathBuf::from(root);
-    }
-    // Fallback to crate manifest dir (parent of workflow crate)
-    std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .parent()
-        .map(|p| p.to_path_buf())
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-}
-
-fn index_dir() -> std::path::PathBuf {
-    workspace_root().join(".workspace").join("indexes")
+fn index_dir(root: &Path) -> PathBuf {
+    root.join(".workspace").join("indexes")
 }
 
-fn mirror_dir() -> std::path::PathBuf {
-    workspace_root().join("storage").join("db")
+fn mirror_dir(root: &Path) -> PathBuf {
+    root.join("storage").join("db")
 }
 
-fn log_pair(name: &str) -> (std::path::PathBuf, std::path::PathBuf) {
-    let index = index_dir().join(format!("{}.log", name));
-    let mirror = mirror_dir().join(format!("{}.log", name));
+fn log_pair(root: &Path, name: &str) -> (PathBuf, PathBuf) {
+    let index = index_dir(root).join(format!("{}.log", name));
+    let mirror = mirror_dir(root).join(format!("{}.log", name));
     (index, mirror)
 }
 
@@ -163,9 +136,9 @@ fn compare_logs(index: &std::path::Path, mirror: &std::path::Path) -> bool {
     }
 }
 
-fn run_storage_doctor() -> Result<StorageDoctorReport, std::io::Error> {
-    let idx_dir = index_dir();
-    let mir_dir = mirror_dir();
+fn run_storage_doctor(root: &Path) -> Result<StorageDoctorReport, std::io::Error> {
+    let idx_dir = index_dir(root);
+    let mir_dir = mirror_dir(root);
     std::fs::create_dir_all(&idx_dir)?;
     std::fs::create_dir_all(&mir_dir)?;
 
@@ -184,7 +157,7 @@ fn run_storage_doctor() -> Result<StorageDoctorReport, std::io::Error> {
             .and_then(|s| s.to_str())
             .unwrap_or_default()
             .to_string();
-        let (index_path, storage_path) = log_pair(&name);
+        let (index_path, storage_path) = log_pair(root, &name);
         let index_exists = index_path.exists();
         let storage_exists = storage_path.exists();
         let drift = index_exists && storage_exists && compare_logs(&index_path, &storage_path);